@@ -2,11 +2,16 @@
 //
 // 用消息传递替代锁机制，消除Arc<Mutex<LLMManager>>的锁竞争
 
-use crate::llm::{CodexConfig, LLMConfig, LLMManager, QwenConfig, SessionBrief, SessionSummary};
+use crate::llm::{
+    CardBrief, CardCategory, CodexConfig, Flashcard, GeminiConfig, LLMConfig, LLMManager,
+    OllamaConfig, QwenConfig, SessionBrief, SessionSummary,
+};
 use anyhow::Result;
 use tokio::sync::{mpsc, oneshot};
 
 use crate::llm::{TimelineAnalysis, TimelineCard, VideoSegment};
+use crate::models::LlmCacheConfig;
+use crate::ocr::OcrContext;
 use crate::storage::Database;
 use chrono::{DateTime, Utc};
 use std::sync::Arc;
@@ -25,6 +30,25 @@ pub enum LLMCommand {
         reply: oneshot::Sender<Result<()>>,
     },
 
+    /// 配置 Ollama provider
+    ConfigureOllama {
+        config: OllamaConfig,
+        reply: oneshot::Sender<Result<()>>,
+    },
+
+    /// 配置 Gemini provider
+    ConfigureGemini {
+        config: GeminiConfig,
+        reply: oneshot::Sender<Result<()>>,
+    },
+
+    /// 配置响应缓存
+    ConfigureResponseCache {
+        config: LlmCacheConfig,
+        db: Option<Arc<Database>>,
+        reply: oneshot::Sender<()>,
+    },
+
     /// 分析帧
     AnalyzeFrames {
         frames: Vec<String>,
@@ -34,6 +58,9 @@ pub enum LLMCommand {
     /// 获取配置
     GetConfig { reply: oneshot::Sender<LLMConfig> },
 
+    /// 当前提供商是否已配置
+    IsConfigured { reply: oneshot::Sender<bool> },
+
     /// 设置视频路径
     SetVideoPath {
         video_path: Option<String>,
@@ -53,6 +80,12 @@ pub enum LLMCommand {
         reply: oneshot::Sender<()>,
     },
 
+    /// 设置下一次生成时间线使用的 OCR 上下文
+    SetOcrContext {
+        context: Option<OcrContext>,
+        reply: oneshot::Sender<()>,
+    },
+
     /// 设置provider的数据库连接
     SetProviderDatabase {
         db: Arc<Database>,
@@ -88,6 +121,19 @@ pub enum LLMCommand {
         reply: oneshot::Sender<Result<String>>,
     },
 
+    /// 从学习会话提取学习卡片
+    GenerateFlashcards {
+        session: SessionBrief,
+        max_cards: usize,
+        reply: oneshot::Sender<Result<Vec<Flashcard>>>,
+    },
+
+    /// 重新分类时间线卡片
+    CategorizeCards {
+        cards: Vec<CardBrief>,
+        reply: oneshot::Sender<Result<Vec<CardCategory>>>,
+    },
+
     /// 切换 LLM provider
     SwitchProvider {
         provider: String,
@@ -135,6 +181,21 @@ impl LLMManagerActor {
                     let _ = reply.send(result);
                 }
 
+                LLMCommand::ConfigureOllama { config, reply } => {
+                    let result = self.manager.configure_ollama(config).await;
+                    let _ = reply.send(result);
+                }
+
+                LLMCommand::ConfigureResponseCache { config, db, reply } => {
+                    self.manager.configure_response_cache(config, db).await;
+                    let _ = reply.send(());
+                }
+
+                LLMCommand::ConfigureGemini { config, reply } => {
+                    let result = self.manager.configure_gemini(config).await;
+                    let _ = reply.send(result);
+                }
+
                 LLMCommand::AnalyzeFrames { frames, reply } => {
                     let result = self.manager.analyze_frames(frames).await;
                     let _ = reply.send(result);
@@ -145,6 +206,10 @@ impl LLMManagerActor {
                     let _ = reply.send(config);
                 }
 
+                LLMCommand::IsConfigured { reply } => {
+                    let _ = reply.send(self.manager.is_configured());
+                }
+
                 LLMCommand::SetVideoPath { video_path, reply } => {
                     self.manager.set_video_path(video_path);
                     let _ = reply.send(()); // 发送确认
@@ -163,6 +228,11 @@ impl LLMManagerActor {
                     let _ = reply.send(()); // 发送确认
                 }
 
+                LLMCommand::SetOcrContext { context, reply } => {
+                    self.manager.set_ocr_context(context);
+                    let _ = reply.send(()); // 发送确认
+                }
+
                 LLMCommand::SetProviderDatabase {
                     db,
                     session_id,
@@ -211,6 +281,20 @@ impl LLMManagerActor {
                     let _ = reply.send(result);
                 }
 
+                LLMCommand::GenerateFlashcards {
+                    session,
+                    max_cards,
+                    reply,
+                } => {
+                    let result = self.manager.generate_flashcards(&session, max_cards).await;
+                    let _ = reply.send(result);
+                }
+
+                LLMCommand::CategorizeCards { cards, reply } => {
+                    let result = self.manager.categorize_cards(&cards).await;
+                    let _ = reply.send(result);
+                }
+
                 LLMCommand::SwitchProvider { provider, reply } => {
                     let result = self.manager.switch_provider(&provider).await;
                     let _ = reply.send(result);
@@ -258,6 +342,39 @@ impl LLMHandle {
         rx.await.map_err(|_| anyhow::anyhow!("Actor已停止"))?
     }
 
+    pub async fn configure_ollama(&self, config: OllamaConfig) -> Result<()> {
+        let (reply, rx) = oneshot::channel();
+        self.sender
+            .send(LLMCommand::ConfigureOllama { config, reply })
+            .await
+            .map_err(|_| anyhow::anyhow!("Actor通道已关闭"))?;
+        rx.await.map_err(|_| anyhow::anyhow!("Actor已停止"))?
+    }
+
+    pub async fn configure_gemini(&self, config: GeminiConfig) -> Result<()> {
+        let (reply, rx) = oneshot::channel();
+        self.sender
+            .send(LLMCommand::ConfigureGemini { config, reply })
+            .await
+            .map_err(|_| anyhow::anyhow!("Actor通道已关闭"))?;
+        rx.await.map_err(|_| anyhow::anyhow!("Actor已停止"))?
+    }
+
+    /// 配置响应缓存（db 为空时沿用已有的数据库连接）
+    pub async fn configure_response_cache(
+        &self,
+        config: LlmCacheConfig,
+        db: Option<Arc<Database>>,
+    ) -> Result<()> {
+        let (reply, rx) = oneshot::channel();
+        self.sender
+            .send(LLMCommand::ConfigureResponseCache { config, db, reply })
+            .await
+            .map_err(|_| anyhow::anyhow!("Actor通道已关闭"))?;
+        rx.await.map_err(|_| anyhow::anyhow!("Actor已停止"))?;
+        Ok(())
+    }
+
     /// 分析帧
     pub async fn analyze_frames(&self, frames: Vec<String>) -> Result<SessionSummary> {
         let (reply, rx) = oneshot::channel();
@@ -278,6 +395,16 @@ impl LLMHandle {
         Ok(rx.await.map_err(|_| anyhow::anyhow!("Actor已停止"))?)
     }
 
+    /// 当前提供商是否已配置
+    pub async fn is_configured(&self) -> Result<bool> {
+        let (reply, rx) = oneshot::channel();
+        self.sender
+            .send(LLMCommand::IsConfigured { reply })
+            .await
+            .map_err(|_| anyhow::anyhow!("Actor通道已关闭"))?;
+        rx.await.map_err(|_| anyhow::anyhow!("Actor已停止"))
+    }
+
     /// 设置视频路径
     pub async fn set_video_path(&self, video_path: Option<String>) -> Result<()> {
         let (reply, rx) = oneshot::channel();
@@ -318,6 +445,17 @@ impl LLMHandle {
         Ok(())
     }
 
+    /// 设置下一次生成时间线使用的 OCR 上下文（使用一次后清除）
+    pub async fn set_ocr_context(&self, context: Option<OcrContext>) -> Result<()> {
+        let (reply, rx) = oneshot::channel();
+        self.sender
+            .send(LLMCommand::SetOcrContext { context, reply })
+            .await
+            .map_err(|_| anyhow::anyhow!("Actor通道已关闭"))?;
+        rx.await.map_err(|_| anyhow::anyhow!("Actor已停止"))?;
+        Ok(())
+    }
+
     /// 设置provider的数据库连接
     pub async fn set_provider_database(
         &self,
@@ -408,6 +546,37 @@ impl LLMHandle {
         rx.await.map_err(|_| anyhow::anyhow!("Actor已停止"))?
     }
 
+    /// 从学习会话提取学习卡片
+    pub async fn generate_flashcards(
+        &self,
+        session: &SessionBrief,
+        max_cards: usize,
+    ) -> Result<Vec<Flashcard>> {
+        let (reply, rx) = oneshot::channel();
+        self.sender
+            .send(LLMCommand::GenerateFlashcards {
+                session: session.clone(),
+                max_cards,
+                reply,
+            })
+            .await
+            .map_err(|_| anyhow::anyhow!("Actor通道已关闭"))?;
+        rx.await.map_err(|_| anyhow::anyhow!("Actor已停止"))?
+    }
+
+    /// 重新分类时间线卡片
+    pub async fn categorize_cards(&self, cards: &[CardBrief]) -> Result<Vec<CardCategory>> {
+        let (reply, rx) = oneshot::channel();
+        self.sender
+            .send(LLMCommand::CategorizeCards {
+                cards: cards.to_vec(),
+                reply,
+            })
+            .await
+            .map_err(|_| anyhow::anyhow!("Actor通道已关闭"))?;
+        rx.await.map_err(|_| anyhow::anyhow!("Actor已停止"))?
+    }
+
     /// 切换 LLM provider
     pub async fn switch_provider(&self, provider: &str) -> Result<()> {
         let (reply, rx) = oneshot::channel();
@@ -5,10 +5,12 @@
 
 pub mod capture_settings;
 pub mod llm_manager;
+pub mod summary_queue;
 pub mod system_status;
 
 pub use capture_settings::{CaptureSettingsActor, CaptureSettingsCommand, CaptureSettingsHandle};
 pub use llm_manager::{LLMCommand, LLMHandle, LLMManagerActor};
+pub use summary_queue::{SummaryQueueActor, SummaryQueueHandle};
 pub use system_status::{SystemStatusActor, SystemStatusCommand, SystemStatusHandle};
 
 #[cfg(test)]
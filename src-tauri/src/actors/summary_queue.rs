@@ -0,0 +1,549 @@
+// Summary Queue Actor - 每日总结重新生成与批量导出的任务队列
+//
+// 按天拆分任务，按优先级（今天优先，其余日期从新到旧）调度，限制同时运行的任务数，
+// 通过广播通道发布进度事件，支持按批次取消
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// 默认并发数
+pub const DEFAULT_QUEUE_CONCURRENCY: usize = 2;
+
+/// 并发数上限
+pub const MAX_QUEUE_CONCURRENCY: usize = 8;
+
+/// 任务类型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SummaryJobKind {
+    /// 强制重新生成每日总结
+    Resummarize,
+    /// 导出到指定目标（obsidian / notion / logseq 等）
+    Export { target: String },
+}
+
+/// 任务优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobPriority {
+    /// 今天的任务
+    High,
+    Normal,
+}
+
+/// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Canceled,
+}
+
+/// 单个任务
+#[derive(Debug, Clone, Serialize)]
+pub struct SummaryJob {
+    pub id: u64,
+    pub batch_id: u64,
+    pub date: NaiveDate,
+    pub kind: SummaryJobKind,
+    pub priority: JobPriority,
+}
+
+impl SummaryJob {
+    /// 调度顺序：优先级高的先执行，同优先级日期新的先执行，其余按入队顺序
+    fn order_key(&self) -> (JobPriority, std::cmp::Reverse<NaiveDate>, u64) {
+        (self.priority, std::cmp::Reverse(self.date), self.id)
+    }
+}
+
+/// 批次进度
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct BatchProgress {
+    pub total: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub canceled: usize,
+}
+
+impl BatchProgress {
+    fn finished(&self) -> usize {
+        self.completed + self.failed + self.canceled
+    }
+}
+
+/// 进度事件
+#[derive(Debug, Clone, Serialize)]
+pub struct SummaryJobEvent {
+    pub job: SummaryJob,
+    pub status: JobStatus,
+    /// 完成时的结果说明或失败原因
+    pub message: Option<String>,
+    pub batch: BatchProgress,
+}
+
+/// 入队结果
+#[derive(Debug, Clone, Serialize)]
+pub struct EnqueueResult {
+    pub batch_id: u64,
+    pub queued: usize,
+    /// 已在队列中（相同日期与类型）而跳过的任务数
+    pub skipped: usize,
+}
+
+/// 队列状态
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueStatus {
+    pub concurrency: usize,
+    pub running: Vec<SummaryJob>,
+    pub pending: Vec<SummaryJob>,
+}
+
+/// 任务执行器（由调用方提供具体的总结与导出逻辑）
+#[async_trait]
+pub trait JobExecutor: Send + Sync {
+    async fn run(&self, job: &SummaryJob) -> Result<String>;
+}
+
+/// 队列命令
+pub enum SummaryQueueCommand {
+    /// 按天入队
+    Enqueue {
+        dates: Vec<NaiveDate>,
+        kind: SummaryJobKind,
+        today: NaiveDate,
+        reply: oneshot::Sender<EnqueueResult>,
+    },
+
+    /// 取消批次（为空时取消全部），返回取消的任务数
+    Cancel {
+        batch_id: Option<u64>,
+        reply: oneshot::Sender<usize>,
+    },
+
+    /// 调整并发数
+    SetConcurrency { concurrency: usize },
+
+    /// 获取队列状态
+    Status { reply: oneshot::Sender<QueueStatus> },
+}
+
+/// 任务完成通知（由执行任务发送给 Actor）
+struct JobFinished {
+    job_id: u64,
+    result: Result<String>,
+}
+
+/// 总结队列 Actor
+pub struct SummaryQueueActor {
+    receiver: mpsc::Receiver<SummaryQueueCommand>,
+    finished_tx: mpsc::UnboundedSender<JobFinished>,
+    finished_rx: mpsc::UnboundedReceiver<JobFinished>,
+    events: broadcast::Sender<SummaryJobEvent>,
+    executor: Arc<dyn JobExecutor>,
+    concurrency: usize,
+    pending: Vec<SummaryJob>,
+    running: HashMap<u64, (SummaryJob, JoinHandle<()>)>,
+    batches: HashMap<u64, BatchProgress>,
+    next_job_id: u64,
+    next_batch_id: u64,
+}
+
+impl SummaryQueueActor {
+    /// 创建新的Actor
+    pub fn new(executor: Arc<dyn JobExecutor>, concurrency: usize) -> (Self, SummaryQueueHandle) {
+        let (sender, receiver) = mpsc::channel(50);
+        let (finished_tx, finished_rx) = mpsc::unbounded_channel();
+        let (events, _) = broadcast::channel(256);
+        let actor = Self {
+            receiver,
+            finished_tx,
+            finished_rx,
+            events: events.clone(),
+            executor,
+            concurrency: clamp_concurrency(concurrency),
+            pending: Vec::new(),
+            running: HashMap::new(),
+            batches: HashMap::new(),
+            next_job_id: 1,
+            next_batch_id: 1,
+        };
+        let handle = SummaryQueueHandle { sender, events };
+        (actor, handle)
+    }
+
+    /// 运行Actor
+    pub async fn run(mut self) {
+        info!("Summary Queue Actor 已启动");
+
+        loop {
+            tokio::select! {
+                cmd = self.receiver.recv() => match cmd {
+                    Some(cmd) => self.handle_command(cmd),
+                    None => break,
+                },
+                Some(finished) = self.finished_rx.recv() => self.finish_job(finished),
+            }
+            self.schedule();
+        }
+
+        for (_, (_, task)) in self.running.drain() {
+            task.abort();
+        }
+        info!("Summary Queue Actor 已停止");
+    }
+
+    fn handle_command(&mut self, cmd: SummaryQueueCommand) {
+        match cmd {
+            SummaryQueueCommand::Enqueue {
+                dates,
+                kind,
+                today,
+                reply,
+            } => {
+                let result = self.enqueue(dates, kind, today);
+                let _ = reply.send(result);
+            }
+
+            SummaryQueueCommand::Cancel { batch_id, reply } => {
+                let canceled = self.cancel(batch_id);
+                let _ = reply.send(canceled);
+            }
+
+            SummaryQueueCommand::SetConcurrency { concurrency } => {
+                self.concurrency = clamp_concurrency(concurrency);
+                info!("总结队列并发数已设置为 {}", self.concurrency);
+            }
+
+            SummaryQueueCommand::Status { reply } => {
+                let mut running: Vec<SummaryJob> =
+                    self.running.values().map(|(job, _)| job.clone()).collect();
+                running.sort_by_key(SummaryJob::order_key);
+                let _ = reply.send(QueueStatus {
+                    concurrency: self.concurrency,
+                    running,
+                    pending: self.pending.clone(),
+                });
+            }
+        }
+    }
+
+    /// 入队（同一日期与类型已在排队或运行中时跳过）
+    fn enqueue(
+        &mut self,
+        mut dates: Vec<NaiveDate>,
+        kind: SummaryJobKind,
+        today: NaiveDate,
+    ) -> EnqueueResult {
+        dates.sort();
+        dates.dedup();
+
+        let batch_id = self.next_batch_id;
+        self.next_batch_id += 1;
+
+        let mut jobs = Vec::new();
+        let mut skipped = 0;
+        for date in dates {
+            let duplicate = self
+                .pending
+                .iter()
+                .chain(self.running.values().map(|(job, _)| job))
+                .any(|job| job.date == date && job.kind == kind);
+            if duplicate {
+                skipped += 1;
+                continue;
+            }
+
+            jobs.push(SummaryJob {
+                id: self.next_job_id,
+                batch_id,
+                date,
+                kind: kind.clone(),
+                priority: if date == today {
+                    JobPriority::High
+                } else {
+                    JobPriority::Normal
+                },
+            });
+            self.next_job_id += 1;
+        }
+
+        let queued = jobs.len();
+        if queued > 0 {
+            self.batches.insert(
+                batch_id,
+                BatchProgress {
+                    total: queued,
+                    ..BatchProgress::default()
+                },
+            );
+            for job in jobs {
+                self.emit(&job, JobStatus::Queued, None);
+                self.pending.push(job);
+            }
+            self.pending.sort_by_key(SummaryJob::order_key);
+            info!(
+                "总结队列新增批次 {}: {} 个任务（跳过 {} 个重复任务）",
+                batch_id, queued, skipped
+            );
+        }
+
+        EnqueueResult {
+            batch_id,
+            queued,
+            skipped,
+        }
+    }
+
+    /// 取消排队中的任务并中止运行中的任务
+    fn cancel(&mut self, batch_id: Option<u64>) -> usize {
+        let matches = |job: &SummaryJob| batch_id.is_none_or(|id| job.batch_id == id);
+
+        let (canceled, kept): (Vec<SummaryJob>, Vec<SummaryJob>) =
+            std::mem::take(&mut self.pending)
+                .into_iter()
+                .partition(matches);
+        self.pending = kept;
+
+        let running_ids: Vec<u64> = self
+            .running
+            .iter()
+            .filter(|(_, (job, _))| matches(job))
+            .map(|(id, _)| *id)
+            .collect();
+        let mut aborted = Vec::new();
+        for id in running_ids {
+            if let Some((job, task)) = self.running.remove(&id) {
+                task.abort();
+                aborted.push(job);
+            }
+        }
+
+        let count = canceled.len() + aborted.len();
+        for job in canceled.into_iter().chain(aborted) {
+            self.record(&job, JobStatus::Canceled, None);
+        }
+        if count > 0 {
+            info!("总结队列已取消 {} 个任务", count);
+        }
+        count
+    }
+
+    /// 在并发数允许的范围内启动排队中的任务
+    fn schedule(&mut self) {
+        while self.running.len() < self.concurrency && !self.pending.is_empty() {
+            let job = self.pending.remove(0);
+            self.emit(&job, JobStatus::Running, None);
+
+            let executor = self.executor.clone();
+            let finished_tx = self.finished_tx.clone();
+            let task_job = job.clone();
+            let task = tokio::spawn(async move {
+                let result = executor.run(&task_job).await;
+                let _ = finished_tx.send(JobFinished {
+                    job_id: task_job.id,
+                    result,
+                });
+            });
+            self.running.insert(job.id, (job, task));
+        }
+    }
+
+    fn finish_job(&mut self, finished: JobFinished) {
+        // 已取消的任务可能在中止前完成，忽略其结果
+        let Some((job, _)) = self.running.remove(&finished.job_id) else {
+            return;
+        };
+        match finished.result {
+            Ok(message) => self.record(&job, JobStatus::Completed, Some(message)),
+            Err(e) => {
+                warn!("总结队列任务 {} 失败: {}", job.date, e);
+                self.record(&job, JobStatus::Failed, Some(e.to_string()));
+            }
+        }
+    }
+
+    /// 更新批次进度并发布结束事件，批次全部结束后移除
+    fn record(&mut self, job: &SummaryJob, status: JobStatus, message: Option<String>) {
+        if let Some(batch) = self.batches.get_mut(&job.batch_id) {
+            match status {
+                JobStatus::Completed => batch.completed += 1,
+                JobStatus::Failed => batch.failed += 1,
+                JobStatus::Canceled => batch.canceled += 1,
+                JobStatus::Queued | JobStatus::Running => {}
+            }
+        }
+        self.emit(job, status, message);
+        if self
+            .batches
+            .get(&job.batch_id)
+            .is_some_and(|batch| batch.finished() >= batch.total)
+        {
+            self.batches.remove(&job.batch_id);
+        }
+    }
+
+    fn emit(&self, job: &SummaryJob, status: JobStatus, message: Option<String>) {
+        let _ = self.events.send(SummaryJobEvent {
+            job: job.clone(),
+            status,
+            message,
+            batch: self.batches.get(&job.batch_id).copied().unwrap_or_default(),
+        });
+    }
+}
+
+fn clamp_concurrency(concurrency: usize) -> usize {
+    concurrency.clamp(1, MAX_QUEUE_CONCURRENCY)
+}
+
+/// 总结队列Handle
+#[derive(Clone)]
+pub struct SummaryQueueHandle {
+    sender: mpsc::Sender<SummaryQueueCommand>,
+    events: broadcast::Sender<SummaryJobEvent>,
+}
+
+impl SummaryQueueHandle {
+    /// 按天入队
+    pub async fn enqueue(
+        &self,
+        dates: Vec<NaiveDate>,
+        kind: SummaryJobKind,
+    ) -> Result<EnqueueResult> {
+        let (reply, rx) = oneshot::channel();
+        self.sender
+            .send(SummaryQueueCommand::Enqueue {
+                dates,
+                kind,
+                today: chrono::Local::now().date_naive(),
+                reply,
+            })
+            .await
+            .map_err(|_| anyhow::anyhow!("Actor通道已关闭"))?;
+        rx.await.map_err(|_| anyhow::anyhow!("Actor已停止"))
+    }
+
+    /// 取消批次（为空时取消全部）
+    pub async fn cancel(&self, batch_id: Option<u64>) -> Result<usize> {
+        let (reply, rx) = oneshot::channel();
+        self.sender
+            .send(SummaryQueueCommand::Cancel { batch_id, reply })
+            .await
+            .map_err(|_| anyhow::anyhow!("Actor通道已关闭"))?;
+        rx.await.map_err(|_| anyhow::anyhow!("Actor已停止"))
+    }
+
+    /// 调整并发数
+    pub async fn set_concurrency(&self, concurrency: usize) {
+        let _ = self
+            .sender
+            .send(SummaryQueueCommand::SetConcurrency { concurrency })
+            .await;
+    }
+
+    /// 获取队列状态
+    pub async fn status(&self) -> Result<QueueStatus> {
+        let (reply, rx) = oneshot::channel();
+        self.sender
+            .send(SummaryQueueCommand::Status { reply })
+            .await
+            .map_err(|_| anyhow::anyhow!("Actor通道已关闭"))?;
+        rx.await.map_err(|_| anyhow::anyhow!("Actor已停止"))
+    }
+
+    /// 订阅进度事件
+    pub fn subscribe(&self) -> broadcast::Receiver<SummaryJobEvent> {
+        self.events.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// 记录执行顺序，指定日期的任务一直挂起直到被取消
+    struct RecordingExecutor {
+        order: Mutex<Vec<NaiveDate>>,
+        stuck: NaiveDate,
+    }
+
+    #[async_trait]
+    impl JobExecutor for RecordingExecutor {
+        async fn run(&self, job: &SummaryJob) -> Result<String> {
+            self.order.lock().unwrap().push(job.date);
+            if job.date == self.stuck {
+                std::future::pending::<()>().await;
+            }
+            Ok(format!("{} 完成", job.date))
+        }
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_summary_queue_priority_and_cancel() {
+        let executor = Arc::new(RecordingExecutor {
+            order: Mutex::new(Vec::new()),
+            stuck: day(1),
+        });
+        let (actor, handle) = SummaryQueueActor::new(executor.clone(), 1);
+        let mut events = handle.subscribe();
+
+        // 先入队再启动，保证调度时所有任务都已排队
+        let (reply, rx) = oneshot::channel();
+        handle
+            .sender
+            .send(SummaryQueueCommand::Enqueue {
+                dates: vec![day(1), day(3), day(5), day(4), day(3)],
+                kind: SummaryJobKind::Resummarize,
+                today: day(4),
+                reply,
+            })
+            .await
+            .unwrap();
+        tokio::spawn(actor.run());
+        let batch = rx.await.unwrap();
+        assert_eq!(batch.queued, 4);
+
+        let again = handle
+            .enqueue(vec![day(1)], SummaryJobKind::Resummarize)
+            .await
+            .unwrap();
+        assert_eq!((again.queued, again.skipped), (0, 1));
+
+        // 今天优先，其余从新到旧；day(1) 挂起后取消整个批次
+        let mut completed = 0;
+        while completed < 3 {
+            let event = events.recv().await.unwrap();
+            if event.status == JobStatus::Completed {
+                completed += 1;
+                assert_eq!(event.batch.completed, completed);
+            }
+        }
+        while events.recv().await.unwrap().status != JobStatus::Running {}
+        assert_eq!(handle.status().await.unwrap().running.len(), 1);
+        assert_eq!(handle.cancel(Some(batch.batch_id)).await.unwrap(), 1);
+
+        let canceled = events.recv().await.unwrap();
+        assert_eq!(canceled.status, JobStatus::Canceled);
+        assert_eq!(canceled.batch.finished(), 4);
+        assert_eq!(
+            *executor.order.lock().unwrap(),
+            vec![day(4), day(5), day(3), day(1)]
+        );
+        assert!(handle.status().await.unwrap().running.is_empty());
+    }
+}
@@ -254,6 +254,8 @@ pub async fn analyze_video_once(
                     .distractions
                     .as_ref()
                     .map(|d| serde_json::to_string(d).unwrap_or_else(|_| "[]".to_string())),
+                confidence: card.confidence.map(f64::from),
+                heuristic: card.heuristic,
                 video_preview_path: Some(video_path_str.clone()),
                 app_sites: serde_json::to_string(&card.app_sites)
                     .unwrap_or_else(|_| "{}".to_string()),
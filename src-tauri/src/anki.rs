@@ -0,0 +1,187 @@
+// Anki 导出 - 从学习类会话的总结中提取问答卡片，生成 Anki 可直接导入的 TSV 文本
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::actors::LLMHandle;
+use crate::exporter::{range_days, session_category};
+use crate::llm::{Flashcard, SessionBrief};
+use crate::storage::{Database, Session};
+
+/// 每个学习会话最多生成的卡片数量
+const MAX_CARDS_PER_SESSION: usize = 5;
+
+/// 导入后卡片所在的牌组
+const DECK_NAME: &str = "Screen Analyzer::学习";
+
+/// 一张待导出的卡片（附带来源会话，用于标签）
+#[derive(Debug, Clone)]
+pub struct AnkiNote {
+    pub card: Flashcard,
+    pub date: String,
+    pub session_id: i64,
+    pub session_title: String,
+}
+
+/// Anki 导出结果
+#[derive(Debug, Default)]
+pub struct AnkiExportOutcome {
+    pub path: PathBuf,
+    /// 学习类会话数量
+    pub session_count: usize,
+    pub card_count: usize,
+    pub warnings: Vec<String>,
+}
+
+impl AnkiExportOutcome {
+    /// 渲染提示信息
+    pub fn render_message(&self) -> String {
+        let mut message = format!(
+            "已从 {} 个学习会话生成 {} 张卡片\n{}",
+            self.session_count,
+            self.card_count,
+            self.path.to_string_lossy()
+        );
+        for warning in &self.warnings {
+            message.push_str("\n⚠ ");
+            message.push_str(warning);
+        }
+        message
+    }
+}
+
+/// 收集日期范围（含首尾）内学习类会话的卡片
+///
+/// 单个会话生成失败时记为警告并继续；返回（卡片, 学习会话数, 警告）。
+pub async fn collect_notes(
+    db: &Database,
+    llm_handle: &LLMHandle,
+    start_date: &str,
+    end_date: &str,
+) -> Result<(Vec<AnkiNote>, usize, Vec<String>)> {
+    let mut notes = Vec::new();
+    let mut session_count = 0;
+    let mut warnings = Vec::new();
+    for day in range_days(start_date, end_date)? {
+        let date = day.format("%Y-%m-%d").to_string();
+        for session in db.get_sessions_by_date(&date).await? {
+            if !is_learning(&session) || session.summary.trim().is_empty() {
+                continue;
+            }
+            let Some(session_id) = session.id else {
+                continue;
+            };
+            session_count += 1;
+
+            let brief = SessionBrief {
+                start_time: session.start_time,
+                end_time: session.end_time,
+                title: session.title.clone(),
+                summary: session.summary.clone(),
+            };
+            match llm_handle
+                .generate_flashcards(&brief, MAX_CARDS_PER_SESSION)
+                .await
+            {
+                Ok(cards) => notes.extend(cards.into_iter().map(|card| AnkiNote {
+                    card,
+                    date: date.clone(),
+                    session_id,
+                    session_title: session.title.clone(),
+                })),
+                Err(e) => {
+                    warn!("会话 {} 生成学习卡片失败: {}", session_id, e);
+                    warnings.push(format!("{} {}: {}", date, session.title, e));
+                }
+            }
+        }
+    }
+    Ok((notes, session_count, warnings))
+}
+
+fn is_learning(session: &Session) -> bool {
+    session_category(session) == Some("learning")
+}
+
+/// 渲染 Anki 纯文本导入格式（文件头声明分隔符、牌组与标签列）
+///
+/// 列：问题、答案（附来源会话）、标签。
+pub fn render_tsv(notes: &[AnkiNote]) -> String {
+    let mut content = format!(
+        "#separator:tab\n#html:true\n#notetype:Basic\n#deck:{}\n#tags column:3\n",
+        DECK_NAME
+    );
+    for note in notes {
+        let answer = format!(
+            "{}<br><br><small>{} · {}</small>",
+            escape_field(&note.card.answer),
+            note.date,
+            escape_field(&note.session_title)
+        );
+        let tags = format!(
+            "screen-analyzer learning {} session-{}",
+            note.date, note.session_id
+        );
+        content.push_str(&format!(
+            "{}\t{}\t{}\n",
+            escape_field(&note.card.question),
+            answer,
+            tags
+        ));
+    }
+    content
+}
+
+/// 写入 TSV 文件，返回文件路径
+pub fn write_tsv(notes: &[AnkiNote], output_dir: &Path, stem: &str) -> Result<PathBuf> {
+    std::fs::create_dir_all(output_dir)?;
+    let path = output_dir.join(format!("{}_anki.txt", stem));
+    std::fs::write(&path, render_tsv(notes))?;
+    Ok(path)
+}
+
+/// HTML 字段转义；制表符与换行会破坏行列结构，分别替换为空格与 <br>
+fn escape_field(text: &str) -> String {
+    text.trim()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\t', " ")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_tsv() {
+        let notes = vec![AnkiNote {
+            card: Flashcard {
+                question: "Vec<T> 扩容\t策略？".to_string(),
+                answer: "容量翻倍\n摊还 O(1)".to_string(),
+            },
+            date: "2024-05-12".to_string(),
+            session_id: 3,
+            session_title: "阅读 \"Rust 程序设计\"".to_string(),
+        }];
+
+        let content = render_tsv(&notes);
+        let lines = content.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "#separator:tab");
+        assert_eq!(lines[3], "#deck:Screen Analyzer::学习");
+        assert_eq!(lines.len(), 6);
+
+        let fields = lines[5].split('\t').collect::<Vec<_>>();
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields[0], "Vec&lt;T&gt; 扩容 策略？");
+        assert_eq!(
+            fields[1],
+            "容量翻倍<br>摊还 O(1)<br><br><small>2024-05-12 · 阅读 &quot;Rust 程序设计&quot;</small>"
+        );
+        assert_eq!(fields[2], "screen-analyzer learning 2024-05-12 session-3");
+    }
+}
@@ -0,0 +1,224 @@
+// 应用使用排行 - 按天汇总各前台应用的使用时长（会话分析时由窗口信息统计并保存），
+// 生成日期范围内的排行榜，并按配置的每日软上限标出达到上限的日期
+
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::models::AppLimit;
+use crate::storage::{DailyAppUsage, Database};
+
+/// 单次查询的最大天数
+pub const MAX_LEADERBOARD_DAYS: i64 = 366;
+
+/// 排行榜默认条数
+pub const DEFAULT_LEADERBOARD_SIZE: usize = 20;
+
+/// 应用某天的使用时长
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AppDayMinutes {
+    pub date: String,
+    pub minutes: i64,
+    pub over_limit: bool,
+}
+
+/// 排行榜中的一个应用
+#[derive(Debug, Clone, Serialize)]
+pub struct AppLeaderboardEntry {
+    pub rank: usize,
+    pub app_name: String,
+    pub total_minutes: i64,
+    /// 有使用记录的天数
+    pub active_days: usize,
+    /// 按有使用记录的天数计算的日均分钟数
+    pub avg_daily_minutes: i64,
+    /// 每天的使用时长（按日期升序）
+    pub daily: Vec<AppDayMinutes>,
+    /// 配置的每日上限（分钟）
+    pub limit_minutes: Option<u32>,
+    /// 达到上限的天数
+    pub days_over_limit: usize,
+}
+
+/// 某天达到上限的应用（用于每日总结）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppLimitBreach {
+    pub app_name: String,
+    /// 当天使用时长（分钟）
+    pub minutes: i64,
+    /// 每日上限（分钟）
+    pub limit_minutes: u32,
+}
+
+fn round_minutes(seconds: i64) -> i64 {
+    (seconds + 30) / 60
+}
+
+/// 应用对应的上限（应用名不区分大小写，上限为 0 视为未设置）
+pub fn limit_for<'a>(limits: &'a [AppLimit], app_name: &str) -> Option<&'a AppLimit> {
+    limits.iter().find(|limit| {
+        limit.daily_minutes > 0 && limit.app_name.trim().eq_ignore_ascii_case(app_name.trim())
+    })
+}
+
+/// 按每日使用时长生成排行榜（按总时长降序，取前 `size` 个）
+pub fn build_leaderboard(
+    usage: &[DailyAppUsage],
+    limits: &[AppLimit],
+    size: usize,
+) -> Vec<AppLeaderboardEntry> {
+    let mut entries: Vec<AppLeaderboardEntry> = Vec::new();
+    for day in usage {
+        let index = match entries
+            .iter()
+            .position(|entry| entry.app_name == day.app_name)
+        {
+            Some(index) => index,
+            None => {
+                entries.push(AppLeaderboardEntry {
+                    rank: 0,
+                    app_name: day.app_name.clone(),
+                    total_minutes: 0,
+                    active_days: 0,
+                    avg_daily_minutes: 0,
+                    daily: Vec::new(),
+                    limit_minutes: limit_for(limits, &day.app_name).map(|l| l.daily_minutes),
+                    days_over_limit: 0,
+                });
+                entries.len() - 1
+            }
+        };
+        let entry = &mut entries[index];
+        let minutes = round_minutes(day.seconds);
+        let over_limit = entry
+            .limit_minutes
+            .is_some_and(|limit| minutes >= i64::from(limit));
+        entry.total_minutes += minutes;
+        entry.days_over_limit += usize::from(over_limit);
+        entry.daily.push(AppDayMinutes {
+            date: day.date.clone(),
+            minutes,
+            over_limit,
+        });
+    }
+
+    for entry in &mut entries {
+        entry.daily.sort_by(|a, b| a.date.cmp(&b.date));
+        entry.active_days = entry.daily.iter().filter(|day| day.minutes > 0).count();
+        entry.avg_daily_minutes = entry.total_minutes / entry.active_days.max(1) as i64;
+    }
+    entries.retain(|entry| entry.total_minutes > 0);
+    entries.sort_by(|a, b| {
+        b.total_minutes
+            .cmp(&a.total_minutes)
+            .then_with(|| a.app_name.cmp(&b.app_name))
+    });
+    entries.truncate(size);
+    for (index, entry) in entries.iter_mut().enumerate() {
+        entry.rank = index + 1;
+    }
+    entries
+}
+
+/// 某天达到上限的应用（按超出比例降序）
+pub fn limit_breaches(usage: &[DailyAppUsage], limits: &[AppLimit]) -> Vec<AppLimitBreach> {
+    let mut breaches: Vec<AppLimitBreach> = build_leaderboard(usage, limits, usize::MAX)
+        .into_iter()
+        .filter_map(|entry| {
+            let limit = entry.limit_minutes?;
+            (entry.total_minutes >= i64::from(limit)).then_some(AppLimitBreach {
+                app_name: entry.app_name,
+                minutes: entry.total_minutes,
+                limit_minutes: limit,
+            })
+        })
+        .collect();
+    breaches.sort_by_key(|breach| {
+        std::cmp::Reverse(breach.minutes * 100 / i64::from(breach.limit_minutes))
+    });
+    breaches
+}
+
+/// 日期范围内（含首尾）的应用使用排行
+pub async fn app_leaderboard(
+    db: &Database,
+    start: NaiveDate,
+    end: NaiveDate,
+    limits: &[AppLimit],
+    size: usize,
+) -> Result<Vec<AppLeaderboardEntry>> {
+    if end < start {
+        return Err(anyhow!("结束日期早于开始日期"));
+    }
+    if (end - start).num_days() >= MAX_LEADERBOARD_DAYS {
+        return Err(anyhow!("排行查询最多 {} 天", MAX_LEADERBOARD_DAYS));
+    }
+    let usage = db
+        .get_daily_app_usage(
+            &start.format("%Y-%m-%d").to_string(),
+            &end.format("%Y-%m-%d").to_string(),
+        )
+        .await?;
+    Ok(build_leaderboard(&usage, limits, size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(date: &str, app: &str, minutes: i64) -> DailyAppUsage {
+        DailyAppUsage {
+            date: date.to_string(),
+            app_name: app.to_string(),
+            seconds: minutes * 60,
+        }
+    }
+
+    fn limits() -> Vec<AppLimit> {
+        vec![AppLimit {
+            app_name: "steam".to_string(),
+            daily_minutes: 60,
+        }]
+    }
+
+    #[test]
+    fn test_leaderboard_ranks_and_flags_limits() {
+        let usage = vec![
+            usage("2024-05-06", "Code", 240),
+            usage("2024-05-06", "Steam", 90),
+            usage("2024-05-07", "Code", 120),
+            usage("2024-05-07", "Steam", 30),
+            usage("2024-05-07", "Slack", 20),
+        ];
+        let board = build_leaderboard(&usage, &limits(), 2);
+        assert_eq!(board.len(), 2);
+        assert_eq!((board[0].rank, board[0].app_name.as_str()), (1, "Code"));
+        assert_eq!(board[0].total_minutes, 360);
+        assert_eq!(board[0].avg_daily_minutes, 180);
+        assert_eq!(board[0].limit_minutes, None);
+
+        let steam = &board[1];
+        assert_eq!((steam.total_minutes, steam.active_days), (120, 2));
+        assert_eq!(steam.limit_minutes, Some(60));
+        assert_eq!(steam.days_over_limit, 1);
+        assert!(steam.daily[0].over_limit && !steam.daily[1].over_limit);
+    }
+
+    #[test]
+    fn test_limit_breaches_for_day() {
+        let day = vec![
+            usage("2024-05-06", "Steam", 90),
+            usage("2024-05-06", "Code", 300),
+        ];
+        assert_eq!(
+            limit_breaches(&day, &limits()),
+            vec![AppLimitBreach {
+                app_name: "Steam".to_string(),
+                minutes: 90,
+                limit_minutes: 60,
+            }]
+        );
+        assert!(limit_breaches(&day[1..], &limits()).is_empty());
+    }
+}
@@ -0,0 +1,1282 @@
+// 加密云备份 - 定时把数据库快照（可选附带配置文件与截图）打包为 zip，用口令经 Argon2id 派生的密钥
+// 以 AES-256-GCM 加密后上传到 S3 兼容存储或 WebDAV；远端另存明文清单 `manifest.json`，记录各备份的名称与时间
+//
+// 密文格式：魔数 `SABACKUP`(8) | 格式版本(1) | 盐(16) | STREAM 随机数前缀(7) | 分块密文，头部作为每块的附加认证数据。
+// 明文按 1 MiB 分块以 STREAM 构造（aead::stream，BE32）逐块加密，每块带 16 字节认证标签，末块单独标记，
+// 截断、重排或篡改都会认证失败。压缩包与密文都先写入临时文件，备份体积不受内存限制；
+// 旧版（格式版本 1）为整体 AES-256-GCM 加密，仍可解密恢复
+// 恢复分两步：先下载、解密并解压到应用数据目录下的 `restore-pending`，下次启动时在打开数据库前替换数据库文件与配置文件、合并截图
+//
+// 本地数据库备份与恢复（`backup_database` / `restore_database`）复用同一恢复流程：SQLite 备份校验完整性并迁移表结构后
+// 放入 `restore-pending`；MariaDB / PostgreSQL 以 SQL 转储导出，恢复时直接执行
+
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::stream::{DecryptorBE32, EncryptorBE32};
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, Result};
+use argon2::Argon2;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{ConnectOptions, Connection};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::domains::StorageDomain;
+use crate::event_bus::{AppEvent, EventBus};
+use crate::models::{BackupConfig, BackupTarget};
+use crate::notion::s3::S3Uploader;
+use crate::storage::config::DatabaseConfig;
+use crate::storage::sqlcipher;
+use crate::storage::Database;
+
+/// 密文文件魔数
+const MAGIC: &[u8; 8] = b"SABACKUP";
+
+/// 密文格式版本（2 为分块 STREAM 加密）
+const FORMAT_VERSION: u8 = 2;
+
+/// 旧版整体加密的格式版本
+const LEGACY_FORMAT_VERSION: u8 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+/// STREAM 随机数前缀长度（AES-GCM 随机数去掉 BE32 的 4 字节计数器与 1 字节末块标记）
+const STREAM_NONCE_LEN: usize = NONCE_LEN - 5;
+const LEGACY_HEADER_LEN: usize = MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
+const HEADER_LEN: usize = MAGIC.len() + 1 + SALT_LEN + STREAM_NONCE_LEN;
+
+/// 明文分块大小
+const CHUNK_LEN: usize = 1024 * 1024;
+
+/// AES-GCM 认证标签长度
+const TAG_LEN: usize = 16;
+
+/// 口令最短长度
+const MIN_PASSPHRASE_CHARS: usize = 8;
+
+/// 远端备份清单文件名
+const MANIFEST_NAME: &str = "manifest.json";
+
+/// 暂存目录中的压缩包与密文文件名
+const ARCHIVE_FILE: &str = "backup.zip";
+const ENCRYPTED_FILE: &str = "backup.sab";
+
+/// 压缩包内的条目名
+const DB_ENTRY: &str = "data.db";
+const CONFIG_ENTRY: &str = "config.json";
+const FRAMES_PREFIX: &str = "frames/";
+
+/// 待应用的恢复内容目录（位于应用数据目录下）
+const PENDING_DIR: &str = "restore-pending";
+
+/// 被恢复内容替换的原文件后缀
+const REPLACED_SUFFIX: &str = ".before-restore";
+
+/// 上传与下载的超时（备份可能包含截图，体积较大）
+const TRANSFER_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30 * 60);
+
+/// 启动后首次检查前的等待（等待数据库初始化完成）
+const STARTUP_DELAY: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+/// 定时备份失败后的重试间隔
+const RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// 远端的一份备份
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupEntry {
+    /// 对象名（不含前缀）
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    /// 密文大小
+    pub size_bytes: u64,
+    pub device_name: String,
+    pub includes_config: bool,
+    pub includes_screenshots: bool,
+}
+
+/// 远端备份清单（按创建时间从旧到新）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub backups: Vec<BackupEntry>,
+}
+
+impl BackupManifest {
+    /// 追加一份备份，返回超出保留份数而需要删除的旧备份
+    fn push(&mut self, entry: BackupEntry, keep_count: u32) -> Vec<BackupEntry> {
+        self.backups.push(entry);
+        self.backups.sort_by_key(|entry| entry.created_at);
+        let excess = self
+            .backups
+            .len()
+            .saturating_sub(keep_count.max(1) as usize);
+        self.backups.drain(..excess).collect()
+    }
+}
+
+/// 校验备份配置（未启用时不校验，手动备份与恢复使用 `check_target`）
+pub fn validate(config: &BackupConfig) -> Result<()> {
+    if !config.enabled {
+        return Ok(());
+    }
+    if config.interval_hours == 0 {
+        return Err(anyhow!("备份间隔至少为 1 小时"));
+    }
+    if config.keep_count == 0 {
+        return Err(anyhow!("至少保留 1 份备份"));
+    }
+    check_target(config)
+}
+
+/// 校验备份目标与口令
+fn check_target(config: &BackupConfig) -> Result<()> {
+    check_passphrase(&config.passphrase)?;
+    match config.target {
+        BackupTarget::S3 => {
+            if config.s3.endpoint.trim().is_empty() || config.s3.bucket.trim().is_empty() {
+                return Err(anyhow!("请填写 S3 服务地址与 Bucket"));
+            }
+        }
+        BackupTarget::WebDav => {
+            let url = config.webdav_url.trim();
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(anyhow!("WebDAV 地址需以 http:// 或 https:// 开头"));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_passphrase(passphrase: &str) -> Result<()> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(anyhow!("备份口令至少需要 {} 个字符", MIN_PASSPHRASE_CHARS));
+    }
+    Ok(())
+}
+
+// ========== 加密 ==========
+
+/// 由口令与盐派生 256 位密钥（Argon2id 默认参数）
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("密钥派生失败: {}", e))?;
+    Ok(key)
+}
+
+fn new_cipher(passphrase: &str, salt: &[u8]) -> Result<Aes256Gcm> {
+    Aes256Gcm::new_from_slice(&derive_key(passphrase, salt)?)
+        .map_err(|e| anyhow!("初始化加密失败: {}", e))
+}
+
+/// 读满缓冲区，到达末尾时可能不满；返回读取的字节数
+fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(length) => filled += length,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// 分块加密备份内容（每次使用随机盐与随机数），返回写入的密文字节数
+pub fn encrypt_stream(
+    mut reader: impl Read,
+    mut writer: impl Write,
+    passphrase: &str,
+) -> Result<u64> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; STREAM_NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.push(FORMAT_VERSION);
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&nonce);
+    writer.write_all(&header)?;
+    let mut written = header.len() as u64;
+
+    let mut encryptor = EncryptorBE32::from_aead(
+        new_cipher(passphrase, &salt)?,
+        GenericArray::from_slice(&nonce),
+    );
+    let mut current = vec![0u8; CHUNK_LEN];
+    let mut next = vec![0u8; CHUNK_LEN];
+    let mut current_len = read_full(&mut reader, &mut current)?;
+    // 预读下一块以判断当前块是否为末块
+    loop {
+        let next_len = if current_len == CHUNK_LEN {
+            read_full(&mut reader, &mut next)?
+        } else {
+            0
+        };
+        let payload = Payload {
+            msg: &current[..current_len],
+            aad: &header,
+        };
+        if next_len == 0 {
+            let chunk = encryptor
+                .encrypt_last(payload)
+                .map_err(|_| anyhow!("加密备份失败"))?;
+            writer.write_all(&chunk)?;
+            written += chunk.len() as u64;
+            break;
+        }
+        let chunk = encryptor
+            .encrypt_next(payload)
+            .map_err(|_| anyhow!("加密备份失败"))?;
+        writer.write_all(&chunk)?;
+        written += chunk.len() as u64;
+        std::mem::swap(&mut current, &mut next);
+        current_len = next_len;
+    }
+    writer.flush()?;
+    Ok(written)
+}
+
+/// 分块解密备份内容（口令错误、内容被篡改或截断都会认证失败），兼容旧版整体加密的备份
+pub fn decrypt_stream(
+    mut reader: impl Read,
+    mut writer: impl Write,
+    passphrase: &str,
+) -> Result<()> {
+    let mut prefix = [0u8; MAGIC.len() + 1];
+    if read_full(&mut reader, &mut prefix)? < prefix.len() || prefix[..MAGIC.len()] != MAGIC[..] {
+        return Err(anyhow!("不是有效的备份文件"));
+    }
+    let version = prefix[MAGIC.len()];
+    if version == LEGACY_FORMAT_VERSION {
+        let mut data = prefix.to_vec();
+        reader.read_to_end(&mut data)?;
+        writer.write_all(&decrypt_legacy(&data, passphrase)?)?;
+        writer.flush()?;
+        return Ok(());
+    }
+    if version != FORMAT_VERSION {
+        return Err(anyhow!("不支持的备份格式版本: {}", version));
+    }
+
+    let mut header = [0u8; HEADER_LEN];
+    header[..prefix.len()].copy_from_slice(&prefix);
+    if read_full(&mut reader, &mut header[prefix.len()..])? < HEADER_LEN - prefix.len() {
+        return Err(anyhow!("不是有效的备份文件"));
+    }
+    let salt = &header[prefix.len()..prefix.len() + SALT_LEN];
+    let nonce = &header[prefix.len() + SALT_LEN..];
+
+    let mut decryptor = DecryptorBE32::from_aead(
+        new_cipher(passphrase, salt)?,
+        GenericArray::from_slice(nonce),
+    );
+    let mut current = vec![0u8; CHUNK_LEN + TAG_LEN];
+    let mut next = vec![0u8; CHUNK_LEN + TAG_LEN];
+    let mut current_len = read_full(&mut reader, &mut current)?;
+    loop {
+        let next_len = if current_len == current.len() {
+            read_full(&mut reader, &mut next)?
+        } else {
+            0
+        };
+        let payload = Payload {
+            msg: &current[..current_len],
+            aad: &header,
+        };
+        if next_len == 0 {
+            let chunk = decryptor
+                .decrypt_last(payload)
+                .map_err(|_| anyhow!("口令错误或备份已损坏"))?;
+            writer.write_all(&chunk)?;
+            break;
+        }
+        let chunk = decryptor
+            .decrypt_next(payload)
+            .map_err(|_| anyhow!("口令错误或备份已损坏"))?;
+        writer.write_all(&chunk)?;
+        std::mem::swap(&mut current, &mut next);
+        current_len = next_len;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// 解密旧版（格式版本 1）整体加密的备份
+fn decrypt_legacy(data: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    if data.len() < LEGACY_HEADER_LEN {
+        return Err(anyhow!("不是有效的备份文件"));
+    }
+    let (header, ciphertext) = data.split_at(LEGACY_HEADER_LEN);
+    let salt = &header[MAGIC.len() + 1..MAGIC.len() + 1 + SALT_LEN];
+    let nonce = &header[MAGIC.len() + 1 + SALT_LEN..];
+
+    new_cipher(passphrase, salt)?
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| anyhow!("口令错误或备份已损坏"))
+}
+
+// ========== 打包 ==========
+
+/// 把数据库快照与可选的配置文件、截图目录打包写入 `target`（逐个文件写入，不在内存中保留整个压缩包）
+fn write_archive(
+    db_snapshot: &Path,
+    config_path: Option<&Path>,
+    frames_dir: Option<&Path>,
+    target: &Path,
+) -> Result<()> {
+    let mut zip = ZipWriter::new(File::create(target)?);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    // 截图已是压缩格式，直接存储
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+    zip.start_file(DB_ENTRY, deflated)?;
+    io::copy(&mut File::open(db_snapshot)?, &mut zip)?;
+
+    if let Some(path) = config_path.filter(|path| path.is_file()) {
+        zip.start_file(CONFIG_ENTRY, deflated)?;
+        io::copy(&mut File::open(path)?, &mut zip)?;
+    }
+
+    if let Some(dir) = frames_dir.filter(|dir| dir.is_dir()) {
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(current) = pending.pop() {
+            for entry in std::fs::read_dir(&current)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+                let relative = path
+                    .strip_prefix(dir)
+                    .map_err(|e| anyhow!("截图路径异常: {}", e))?
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                zip.start_file(format!("{}{}", FRAMES_PREFIX, relative), stored)?;
+                // 加密的截图解密后写入（备份本身已用口令加密，恢复到其他设备时无需钥匙串密钥）
+                zip.write_all(&crate::frame_crypto::read_frame_blocking(&path)?)?;
+            }
+        }
+    }
+
+    zip.finish()?.sync_all()?;
+    Ok(())
+}
+
+/// 在暂存目录中生成备份明文（压缩包），返回压缩包路径
+async fn build_archive(
+    db: &Database,
+    config: &BackupConfig,
+    settings_path: &Path,
+    frames_dir: &Path,
+    staging: &Path,
+) -> Result<PathBuf> {
+    if !db.is_sqlite() {
+        return Err(anyhow!(
+            "云备份目前仅支持 SQLite 数据库，MariaDB / PostgreSQL 请使用数据库备份导出 SQL 转储"
+        ));
+    }
+    let snapshot = staging.join(DB_ENTRY);
+    db.snapshot_to(&snapshot.to_string_lossy()).await?;
+    // SQLCipher 数据库的快照导出为明文后打包（备份本身已用口令加密，恢复到其他设备时无需钥匙串密钥）
+    let snapshot = match sqlcipher::existing_key(&snapshot)? {
+        Some(key) => {
+            let plain = staging.join("plain.db");
+            sqlcipher::export(&snapshot, Some(&key), &plain, None).await?;
+            plain
+        }
+        None => snapshot,
+    };
+
+    let config_path = config.include_config.then(|| settings_path.to_path_buf());
+    let frames_dir = config.include_screenshots.then(|| frames_dir.to_path_buf());
+    let archive = staging.join(ARCHIVE_FILE);
+    let target = archive.clone();
+    tokio::task::spawn_blocking(move || {
+        let result = write_archive(
+            &snapshot,
+            config_path.as_deref(),
+            frames_dir.as_deref(),
+            &target,
+        );
+        // 快照已写入压缩包，尽早释放暂存空间
+        let _ = std::fs::remove_file(&snapshot);
+        result
+    })
+    .await
+    .map_err(|e| anyhow!("备份打包任务失败: {}", e))??;
+    Ok(archive)
+}
+
+/// 把压缩包文件解压到目录（拒绝越出目标目录的条目）
+fn extract_archive(archive: &Path, target: &Path) -> Result<()> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(archive)?))?;
+    if archive.index_for_name(DB_ENTRY).is_none() {
+        return Err(anyhow!("备份中缺少数据库文件"));
+    }
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        let Some(relative) = file.enclosed_name() else {
+            return Err(anyhow!("备份中包含非法路径: {}", file.name()));
+        };
+        let path = target.join(relative);
+        if file.is_dir() {
+            std::fs::create_dir_all(&path)?;
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut output = BufWriter::new(File::create(&path)?);
+        io::copy(&mut file, &mut output)?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+// ========== 远端存储 ==========
+
+/// 备份目标的读写
+enum Remote {
+    S3(S3Uploader),
+    WebDav {
+        client: Client,
+        base_url: String,
+        username: String,
+        password: String,
+    },
+}
+
+impl Remote {
+    fn from_config(config: &BackupConfig) -> Result<Self> {
+        check_target(config)?;
+        match config.target {
+            BackupTarget::S3 => Ok(Self::S3(
+                S3Uploader::new(config.s3.clone())?.with_timeout(TRANSFER_TIMEOUT)?,
+            )),
+            BackupTarget::WebDav => Ok(Self::WebDav {
+                client: Client::builder().timeout(TRANSFER_TIMEOUT).build()?,
+                base_url: config.webdav_url.trim().trim_end_matches('/').to_string(),
+                username: config.webdav_username.clone(),
+                password: config.webdav_password.clone(),
+            }),
+        }
+    }
+
+    fn webdav_request(&self, method: Method, name: &str) -> reqwest::RequestBuilder {
+        let Self::WebDav {
+            client,
+            base_url,
+            username,
+            password,
+        } = self
+        else {
+            unreachable!("仅用于 WebDAV 目标");
+        };
+        let url = if name.is_empty() {
+            format!("{}/", base_url)
+        } else {
+            format!("{}/{}", base_url, name)
+        };
+        let request = client.request(method, url);
+        if username.is_empty() {
+            request
+        } else {
+            request.basic_auth(username, Some(password))
+        }
+    }
+
+    async fn put(&self, name: &str, bytes: Vec<u8>, content_type: &str) -> Result<()> {
+        match self {
+            Self::S3(uploader) => {
+                uploader
+                    .upload(&uploader.object_key(name), bytes, content_type)
+                    .await?;
+            }
+            Self::WebDav { .. } => {
+                let send = |bytes: Vec<u8>| {
+                    self.webdav_request(Method::PUT, name)
+                        .header("Content-Type", content_type)
+                        .body(bytes)
+                        .send()
+                };
+                let mut response = send(bytes.clone()).await?;
+                // 409 表示目录不存在，创建后重试一次
+                if response.status() == StatusCode::CONFLICT {
+                    let mkcol = Method::from_bytes(b"MKCOL").expect("MKCOL 是合法的方法名");
+                    self.webdav_request(mkcol, "").send().await?;
+                    response = send(bytes).await?;
+                }
+                if !response.status().is_success() {
+                    return Err(anyhow!("上传到 WebDAV 失败: HTTP {}", response.status()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 从文件上传（请求体按块读取，不把整个文件读入内存）
+    async fn put_file(&self, name: &str, path: &Path, content_type: &str) -> Result<()> {
+        match self {
+            Self::S3(uploader) => {
+                uploader
+                    .upload_file(&uploader.object_key(name), path, content_type)
+                    .await?;
+            }
+            Self::WebDav { .. } => {
+                let length = tokio::fs::metadata(path).await?.len();
+                let send = || async {
+                    let file = tokio::fs::File::open(path).await?;
+                    let response = self
+                        .webdav_request(Method::PUT, name)
+                        .header("Content-Type", content_type)
+                        .header("Content-Length", length)
+                        .body(reqwest::Body::from(file))
+                        .send()
+                        .await?;
+                    Ok::<_, anyhow::Error>(response)
+                };
+                let mut response = send().await?;
+                // 409 表示目录不存在，创建后重试一次
+                if response.status() == StatusCode::CONFLICT {
+                    let mkcol = Method::from_bytes(b"MKCOL").expect("MKCOL 是合法的方法名");
+                    self.webdav_request(mkcol, "").send().await?;
+                    response = send().await?;
+                }
+                if !response.status().is_success() {
+                    return Err(anyhow!("上传到 WebDAV 失败: HTTP {}", response.status()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 下载到文件（按块写入），对象不存在时返回 false
+    async fn get_to_file(&self, name: &str, path: &Path) -> Result<bool> {
+        match self {
+            Self::S3(uploader) => {
+                uploader
+                    .download_to_file(&uploader.object_key(name), path)
+                    .await
+            }
+            Self::WebDav { .. } => {
+                let response = self.webdav_request(Method::GET, name).send().await?;
+                if response.status() == StatusCode::NOT_FOUND {
+                    return Ok(false);
+                }
+                if !response.status().is_success() {
+                    return Err(anyhow!("从 WebDAV 下载失败: HTTP {}", response.status()));
+                }
+                crate::notion::s3::write_response_to_file(response, path).await?;
+                Ok(true)
+            }
+        }
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        match self {
+            Self::S3(uploader) => uploader.download(&uploader.object_key(name)).await,
+            Self::WebDav { .. } => {
+                let response = self.webdav_request(Method::GET, name).send().await?;
+                if response.status() == StatusCode::NOT_FOUND {
+                    return Ok(None);
+                }
+                if !response.status().is_success() {
+                    return Err(anyhow!("从 WebDAV 下载失败: HTTP {}", response.status()));
+                }
+                Ok(Some(response.bytes().await?.to_vec()))
+            }
+        }
+    }
+
+    async fn delete(&self, name: &str) -> Result<()> {
+        match self {
+            Self::S3(uploader) => uploader.delete(&uploader.object_key(name)).await,
+            Self::WebDav { .. } => {
+                let response = self.webdav_request(Method::DELETE, name).send().await?;
+                if !response.status().is_success() && response.status() != StatusCode::NOT_FOUND {
+                    return Err(anyhow!("删除 WebDAV 文件失败: HTTP {}", response.status()));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    async fn manifest(&self) -> Result<BackupManifest> {
+        match self.get(MANIFEST_NAME).await? {
+            Some(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            None => Ok(BackupManifest::default()),
+        }
+    }
+}
+
+// ========== 备份与恢复 ==========
+
+/// 立即执行一次备份：打包、加密、上传，并按保留份数删除最旧的备份
+pub async fn run_backup(
+    db: &Database,
+    config: &BackupConfig,
+    settings_path: &Path,
+    frames_dir: &Path,
+) -> Result<BackupEntry> {
+    let remote = Remote::from_config(config)?;
+    let staging = tempfile::tempdir()?;
+    let archive = build_archive(db, config, settings_path, frames_dir, staging.path()).await?;
+    let encrypted = staging.path().join(ENCRYPTED_FILE);
+    let passphrase = config.passphrase.clone();
+    let target = encrypted.clone();
+    let size_bytes = tokio::task::spawn_blocking(move || -> Result<u64> {
+        let size = encrypt_stream(
+            BufReader::new(File::open(&archive)?),
+            BufWriter::new(File::create(&target)?),
+            &passphrase,
+        )?;
+        std::fs::remove_file(&archive)?;
+        Ok(size)
+    })
+    .await
+    .map_err(|e| anyhow!("备份加密任务失败: {}", e))??;
+
+    let created_at = Utc::now();
+    let entry = BackupEntry {
+        name: format!("backup-{}.sab", created_at.format("%Y%m%d-%H%M%S")),
+        created_at,
+        size_bytes,
+        device_name: whoami::devicename(),
+        includes_config: config.include_config,
+        includes_screenshots: config.include_screenshots,
+    };
+    remote
+        .put_file(&entry.name, &encrypted, "application/octet-stream")
+        .await?;
+
+    let mut manifest = remote.manifest().await?;
+    let expired = manifest.push(entry.clone(), config.keep_count);
+    remote
+        .put(
+            MANIFEST_NAME,
+            serde_json::to_vec_pretty(&manifest)?,
+            "application/json",
+        )
+        .await?;
+    for old in expired {
+        if let Err(e) = remote.delete(&old.name).await {
+            warn!("删除过期备份 {} 失败: {}", old.name, e);
+        }
+    }
+
+    info!("云备份完成: {} ({} 字节)", entry.name, entry.size_bytes);
+    Ok(entry)
+}
+
+/// 列出远端备份（从新到旧）
+pub async fn list_backups(config: &BackupConfig) -> Result<Vec<BackupEntry>> {
+    let mut backups = Remote::from_config(config)?.manifest().await?.backups;
+    backups.reverse();
+    Ok(backups)
+}
+
+/// 下载并解密指定备份，解压到待恢复目录；重启应用后生效
+pub async fn stage_restore(
+    config: &BackupConfig,
+    name: &str,
+    passphrase: &str,
+    app_dir: &Path,
+) -> Result<()> {
+    check_passphrase(passphrase)?;
+    let remote = Remote::from_config(config)?;
+    let manifest = remote.manifest().await?;
+    if !manifest.backups.iter().any(|entry| entry.name == name) {
+        return Err(anyhow!("备份不存在: {}", name));
+    }
+    let staging = tempfile::tempdir()?;
+    let encrypted = staging.path().join(ENCRYPTED_FILE);
+    if !remote.get_to_file(name, &encrypted).await? {
+        return Err(anyhow!("备份文件已被删除: {}", name));
+    }
+
+    let passphrase = passphrase.to_string();
+    let pending = app_dir.join(PENDING_DIR);
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let archive = staging.path().join(ARCHIVE_FILE);
+        decrypt_stream(
+            BufReader::new(File::open(&encrypted)?),
+            BufWriter::new(File::create(&archive)?),
+            &passphrase,
+        )?;
+        std::fs::remove_file(&encrypted)?;
+        if pending.exists() {
+            std::fs::remove_dir_all(&pending)?;
+        }
+        std::fs::create_dir_all(&pending)?;
+        if let Err(e) = extract_archive(&archive, &pending) {
+            let _ = std::fs::remove_dir_all(&pending);
+            return Err(e);
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| anyhow!("备份解压任务失败: {}", e))??;
+
+    info!("备份 {} 已解密，将在下次启动时恢复", name);
+    Ok(())
+}
+
+/// 当前配置使用的 SQLite 数据库文件（MariaDB / PostgreSQL 时返回 None）
+fn sqlite_path(app_dir: &Path, settings_path: &Path) -> Option<PathBuf> {
+    let database_config = std::fs::read(settings_path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok())
+        .and_then(|value| value.get("database_config").cloned())
+        .filter(|value| !value.is_null())
+        .and_then(|value| serde_json::from_value::<DatabaseConfig>(value).ok());
+    match database_config {
+        Some(DatabaseConfig::SQLite { db_path, .. }) => {
+            let path = PathBuf::from(db_path);
+            Some(if path.is_relative() {
+                app_dir.join(path)
+            } else {
+                path
+            })
+        }
+        Some(DatabaseConfig::MariaDB { .. } | DatabaseConfig::PostgreSQL { .. }) => None,
+        None => Some(app_dir.join("data.db")),
+    }
+}
+
+/// 用恢复内容替换文件，原文件保留为 `*.before-restore`
+fn replace_file(source: &Path, target: &Path) -> Result<()> {
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if target.exists() {
+        let mut replaced = target.as_os_str().to_owned();
+        replaced.push(REPLACED_SUFFIX);
+        std::fs::rename(target, PathBuf::from(replaced))?;
+    }
+    std::fs::copy(source, target)?;
+    Ok(())
+}
+
+/// 应用待恢复的备份（启动时、打开数据库与读取配置前调用），返回是否进行了恢复
+pub fn apply_pending_restore(app_dir: &Path, settings_path: &Path) -> Result<bool> {
+    let pending = app_dir.join(PENDING_DIR);
+    if !pending.join(DB_ENTRY).is_file() {
+        return Ok(false);
+    }
+
+    let config = pending.join(CONFIG_ENTRY);
+    if config.is_file() {
+        replace_file(&config, settings_path)?;
+    }
+
+    // 数据库路径以恢复后的配置为准
+    match sqlite_path(app_dir, settings_path) {
+        Some(db_path) => {
+            // 旧数据库的 WAL 文件不能与新数据库混用
+            for suffix in ["-wal", "-shm"] {
+                let mut sidecar = db_path.as_os_str().to_owned();
+                sidecar.push(suffix);
+                let sidecar = PathBuf::from(sidecar);
+                if sidecar.exists() {
+                    std::fs::remove_file(&sidecar)?;
+                }
+            }
+            replace_file(&pending.join(DB_ENTRY), &db_path)?;
+        }
+        None => warn!("当前配置使用远程数据库，跳过数据库文件恢复"),
+    }
+
+    // 截图按文件合并，不覆盖已有文件
+    let frames = pending.join(FRAMES_PREFIX.trim_end_matches('/'));
+    if frames.is_dir() {
+        let target_root = app_dir.join("frames");
+        let mut dirs = vec![frames.clone()];
+        while let Some(current) = dirs.pop() {
+            for entry in std::fs::read_dir(&current)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                let target = target_root.join(
+                    path.strip_prefix(&frames)
+                        .map_err(|e| anyhow!("截图路径异常: {}", e))?,
+                );
+                if !target.exists() {
+                    if let Some(parent) = target.parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    std::fs::copy(&path, &target)?;
+                }
+            }
+        }
+    }
+
+    std::fs::remove_dir_all(&pending)?;
+    Ok(true)
+}
+
+// ========== 本地数据库备份 ==========
+
+/// SQLite 数据库文件头
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// 本地数据库备份结果
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseBackupInfo {
+    pub path: String,
+    pub db_type: String,
+    pub size_bytes: u64,
+}
+
+/// 本地数据库恢复结果
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseRestoreOutcome {
+    /// SQLite 在下次启动时替换数据库文件，需重启应用
+    pub requires_restart: bool,
+}
+
+/// 数据库备份文件的扩展名（SQLite 为数据库文件，MariaDB / PostgreSQL 为 SQL 转储）
+pub fn database_backup_extension(db: &Database) -> &'static str {
+    if db.is_sqlite() {
+        "db"
+    } else {
+        "sql"
+    }
+}
+
+/// 把数据库的一致性快照写入指定文件（已存在时覆盖）
+pub async fn backup_database(db: &Database, path: &Path) -> Result<DatabaseBackupInfo> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    // VACUUM INTO 不能写入已存在的文件，先写临时文件再替换
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    if partial.exists() {
+        tokio::fs::remove_file(&partial).await?;
+    }
+    db.snapshot_to(&partial.to_string_lossy()).await?;
+    tokio::fs::rename(&partial, path).await?;
+
+    Ok(DatabaseBackupInfo {
+        path: path.to_string_lossy().to_string(),
+        db_type: db.db_type().to_string(),
+        size_bytes: tokio::fs::metadata(path).await?.len(),
+    })
+}
+
+/// 从备份文件恢复数据库：SQLite 校验后放入待恢复目录（重启生效），MariaDB / PostgreSQL 直接执行转储
+pub async fn restore_database(
+    db: &Database,
+    path: &Path,
+    app_dir: &Path,
+) -> Result<DatabaseRestoreOutcome> {
+    if !path.is_file() {
+        return Err(anyhow!("备份文件不存在: {}", path.display()));
+    }
+    if !db.is_sqlite() {
+        db.restore_from_dump(&path.to_string_lossy()).await?;
+        return Ok(DatabaseRestoreOutcome {
+            requires_restart: false,
+        });
+    }
+
+    let pending = app_dir.join(PENDING_DIR);
+    if pending.exists() {
+        tokio::fs::remove_dir_all(&pending).await?;
+    }
+    tokio::fs::create_dir_all(&pending).await?;
+    let staged = pending.join(DB_ENTRY);
+    if let Err(e) = stage_sqlite_backup(path, &staged).await {
+        let _ = tokio::fs::remove_dir_all(&pending).await;
+        return Err(e);
+    }
+
+    info!("数据库备份已校验，将在下次启动时恢复: {}", path.display());
+    Ok(DatabaseRestoreOutcome {
+        requires_restart: true,
+    })
+}
+
+/// 把 SQLite 备份复制到待恢复目录并校验（SQLCipher 加密的备份用本机密钥导出为明文，启动时按数据库配置重新加密）
+async fn stage_sqlite_backup(path: &Path, staged: &Path) -> Result<()> {
+    match sqlcipher::existing_key(path)? {
+        Some(key) => sqlcipher::export(path, Some(&key), staged, None).await?,
+        None => {
+            tokio::fs::copy(path, staged).await?;
+        }
+    }
+    verify_sqlite_backup(staged).await
+}
+
+/// 校验 SQLite 备份的文件头与完整性，并补齐旧版本缺少的表与字段
+async fn verify_sqlite_backup(path: &Path) -> Result<()> {
+    let mut header = [0u8; 16];
+    let is_sqlite = File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok()
+        && &header == SQLITE_HEADER;
+    if !is_sqlite {
+        return Err(anyhow!("备份文件不是 SQLite 数据库"));
+    }
+
+    let mut conn = SqliteConnectOptions::new().filename(path).connect().await?;
+    let report: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_all(&mut conn)
+        .await?;
+    if report != ["ok"] {
+        return Err(anyhow!("备份文件完整性校验失败: {}", report.join("; ")));
+    }
+    let has_sessions: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'sessions'",
+    )
+    .fetch_one(&mut conn)
+    .await?;
+    conn.close().await?;
+    if has_sessions == 0 {
+        return Err(anyhow!("备份文件不是本应用的数据库"));
+    }
+
+    // 打开时执行建表与字段迁移
+    Database::new_sqlite(&path.to_string_lossy()).await?;
+    Ok(())
+}
+
+/// 距下一次定时备份的等待时间
+fn next_backup_delay(
+    last_backup: Option<DateTime<Utc>>,
+    interval_hours: u32,
+    now: DateTime<Utc>,
+) -> std::time::Duration {
+    let interval = chrono::Duration::hours(i64::from(interval_hours.max(1)));
+    last_backup
+        .map(|last| (last + interval - now).to_std().unwrap_or_default())
+        .unwrap_or_default()
+}
+
+// ========== 定时备份服务 ==========
+
+/// 正在运行的定时备份任务（配置变更时重建）
+struct RunningTask {
+    config: BackupConfig,
+    task: JoinHandle<()>,
+}
+
+/// 云备份服务 - 启用时按间隔执行备份
+pub struct BackupService {
+    storage_domain: Arc<StorageDomain>,
+    frames_dir: PathBuf,
+    running: Mutex<Option<RunningTask>>,
+}
+
+impl BackupService {
+    pub fn new(storage_domain: Arc<StorageDomain>, frames_dir: PathBuf) -> Self {
+        Self {
+            storage_domain,
+            frames_dir,
+            running: Mutex::new(None),
+        }
+    }
+
+    /// 启动（配置更新时重建）
+    pub fn start(self: Arc<Self>, event_bus: Arc<EventBus>) {
+        let mut receiver = event_bus.subscribe();
+
+        tokio::spawn(async move {
+            self.refresh().await;
+
+            while let Ok(event) = receiver.recv().await {
+                if let AppEvent::ConfigUpdated { .. } = event {
+                    self.refresh().await;
+                }
+            }
+        });
+    }
+
+    /// 按最新配置启动、重启或停止
+    async fn refresh(&self) {
+        let config = self
+            .storage_domain
+            .get_settings()
+            .get()
+            .await
+            .backup_config
+            .unwrap_or_default();
+        let mut running = self.running.lock().await;
+        if config.enabled
+            && running
+                .as_ref()
+                .is_some_and(|current| current.config == config && !current.task.is_finished())
+        {
+            return;
+        }
+
+        if let Some(current) = running.take() {
+            current.task.abort();
+            info!("定时云备份已停止");
+        }
+        if !config.enabled {
+            return;
+        }
+        if let Err(e) = validate(&config) {
+            warn!("定时云备份未启动: {}", e);
+            return;
+        }
+
+        let task = self.spawn_scheduler(config.clone());
+        *running = Some(RunningTask { config, task });
+    }
+
+    fn spawn_scheduler(&self, config: BackupConfig) -> JoinHandle<()> {
+        info!("定时云备份已启动，间隔 {} 小时", config.interval_hours);
+        let storage_domain = self.storage_domain.clone();
+        let frames_dir = self.frames_dir.clone();
+        let interval = std::time::Duration::from_secs(u64::from(config.interval_hours) * 3600);
+        tokio::spawn(async move {
+            let last_backup = match list_backups(&config).await {
+                Ok(backups) => backups.first().map(|entry| entry.created_at),
+                Err(e) => {
+                    warn!("读取远端备份清单失败: {}", e);
+                    None
+                }
+            };
+            let mut delay = next_backup_delay(last_backup, config.interval_hours, Utc::now())
+                .max(STARTUP_DELAY);
+            loop {
+                tokio::time::sleep(delay).await;
+                let settings_path = storage_domain.get_settings().path().to_path_buf();
+                delay = match storage_domain.get_db().await {
+                    Ok(db) => match run_backup(&db, &config, &settings_path, &frames_dir).await {
+                        Ok(_) => interval,
+                        Err(e) => {
+                            warn!("定时云备份失败: {}", e);
+                            RETRY_DELAY.min(interval)
+                        }
+                    },
+                    Err(e) => {
+                        warn!("定时云备份跳过，数据库未就绪: {}", e);
+                        RETRY_DELAY.min(interval)
+                    }
+                };
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::tempdir;
+
+    fn encrypt(plain: &[u8], passphrase: &str) -> Vec<u8> {
+        let mut output = Vec::new();
+        let written = encrypt_stream(plain, &mut output, passphrase).unwrap();
+        assert_eq!(written, output.len() as u64);
+        output
+    }
+
+    fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+        decrypt_stream(data, &mut output, passphrase)?;
+        Ok(output)
+    }
+
+    #[test]
+    fn test_encrypt_round_trip_and_wrong_passphrase() {
+        let plain = b"screen analyzer backup".repeat(100);
+        let encrypted = encrypt(&plain, "correct horse");
+        assert_eq!(&encrypted[..8], MAGIC);
+        assert!(!encrypted
+            .windows(plain.len())
+            .any(|window| window == plain.as_slice()));
+        assert_eq!(decrypt(&encrypted, "correct horse").unwrap(), plain);
+
+        // 口令错误与篡改都无法解密
+        assert!(decrypt(&encrypted, "wrong horse").is_err());
+        let mut tampered = encrypted.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(decrypt(&tampered, "correct horse").is_err());
+
+        // 同一内容每次加密结果不同（随机盐与随机数）
+        assert_ne!(encrypt(&plain, "correct horse"), encrypted);
+    }
+
+    #[test]
+    fn test_encrypt_stream_chunk_boundaries() {
+        for length in [0, 1, CHUNK_LEN - 1, CHUNK_LEN, CHUNK_LEN + 1, 2 * CHUNK_LEN] {
+            let plain: Vec<u8> = (0..length).map(|i| (i % 251) as u8).collect();
+            let encrypted = encrypt(&plain, "passphrase");
+            let chunks = length.div_ceil(CHUNK_LEN).max(1);
+            assert_eq!(encrypted.len(), HEADER_LEN + length + chunks * TAG_LEN);
+            assert_eq!(decrypt(&encrypted, "passphrase").unwrap(), plain);
+        }
+
+        // 丢掉末块（截断在块边界）也无法通过认证
+        let plain = vec![7u8; 2 * CHUNK_LEN];
+        let encrypted = encrypt(&plain, "passphrase");
+        let truncated = &encrypted[..HEADER_LEN + CHUNK_LEN + TAG_LEN];
+        assert!(decrypt(truncated, "passphrase").is_err());
+    }
+
+    #[test]
+    fn test_decrypt_legacy_backup() {
+        let plain = b"legacy backup".to_vec();
+        let salt = [3u8; SALT_LEN];
+        let nonce = [5u8; NONCE_LEN];
+        let mut data = MAGIC.to_vec();
+        data.push(LEGACY_FORMAT_VERSION);
+        data.extend_from_slice(&salt);
+        data.extend_from_slice(&nonce);
+        let ciphertext = new_cipher("passphrase", &salt)
+            .unwrap()
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &plain,
+                    aad: &data,
+                },
+            )
+            .unwrap();
+        data.extend_from_slice(&ciphertext);
+
+        assert_eq!(decrypt(&data, "passphrase").unwrap(), plain);
+        assert!(decrypt(&data, "wrong passphrase").is_err());
+    }
+
+    #[test]
+    fn test_manifest_keeps_newest_backups() {
+        let entry = |day: u32| BackupEntry {
+            name: format!("backup-{}.sab", day),
+            created_at: Utc.with_ymd_and_hms(2024, 5, day, 3, 0, 0).unwrap(),
+            size_bytes: 1,
+            device_name: "mac".to_string(),
+            includes_config: true,
+            includes_screenshots: false,
+        };
+        let mut manifest = BackupManifest::default();
+        assert!(manifest.push(entry(2), 2).is_empty());
+        assert!(manifest.push(entry(1), 2).is_empty());
+        let expired = manifest.push(entry(3), 2);
+        assert_eq!(expired, vec![entry(1)]);
+        assert_eq!(manifest.backups, vec![entry(2), entry(3)]);
+
+        let now = Utc.with_ymd_and_hms(2024, 5, 3, 12, 0, 0).unwrap();
+        assert_eq!(
+            next_backup_delay(Some(entry(3).created_at), 24, now),
+            std::time::Duration::from_secs(15 * 3600)
+        );
+        assert_eq!(
+            next_backup_delay(Some(entry(1).created_at), 24, now),
+            std::time::Duration::ZERO
+        );
+        assert_eq!(next_backup_delay(None, 24, now), std::time::Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_restores_on_next_start() {
+        let source_dir = tempdir().unwrap();
+        let db = Database::new_sqlite(&source_dir.path().join("data.db").to_string_lossy())
+            .await
+            .unwrap();
+        let settings_path = source_dir.path().join("config.json");
+        std::fs::write(&settings_path, "{}").unwrap();
+        let frames_dir = source_dir.path().join("frames");
+        std::fs::create_dir_all(frames_dir.join("2024-05-08")).unwrap();
+        std::fs::write(frames_dir.join("2024-05-08/a.jpg"), b"jpg").unwrap();
+
+        let config = BackupConfig {
+            include_screenshots: true,
+            ..BackupConfig::default()
+        };
+        let staging = tempdir().unwrap();
+        let archive = build_archive(&db, &config, &settings_path, &frames_dir, staging.path())
+            .await
+            .unwrap();
+        let encrypted = encrypt(&std::fs::read(&archive).unwrap(), "passphrase");
+
+        // 模拟另一台设备：解密到待恢复目录，启动时替换数据库并合并截图
+        let target_dir = tempdir().unwrap();
+        let pending = target_dir.path().join(PENDING_DIR);
+        std::fs::create_dir_all(&pending).unwrap();
+        let decrypted = staging.path().join("restored.zip");
+        std::fs::write(&decrypted, decrypt(&encrypted, "passphrase").unwrap()).unwrap();
+        extract_archive(&decrypted, &pending).unwrap();
+        std::fs::write(target_dir.path().join("data.db"), b"old").unwrap();
+        let target_settings = target_dir.path().join("config.json");
+
+        assert!(apply_pending_restore(target_dir.path(), &target_settings).unwrap());
+        assert!(!pending.exists());
+        assert_eq!(
+            std::fs::read(target_dir.path().join("data.db.before-restore")).unwrap(),
+            b"old"
+        );
+        assert_eq!(
+            std::fs::read(target_dir.path().join("frames/2024-05-08/a.jpg")).unwrap(),
+            b"jpg"
+        );
+        let restored = Database::new_sqlite(&target_dir.path().join("data.db").to_string_lossy())
+            .await
+            .unwrap();
+        assert!(restored.get_all_sessions().await.unwrap().is_empty());
+        assert!(!apply_pending_restore(target_dir.path(), &target_settings).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_database_backup_verified_before_restore() {
+        let dir = tempdir().unwrap();
+        let db = Database::new_sqlite(&dir.path().join("data.db").to_string_lossy())
+            .await
+            .unwrap();
+        let backup_path = dir.path().join("backups/snapshot.db");
+        backup_database(&db, &backup_path).await.unwrap();
+        // 再次备份覆盖已有文件
+        let info = backup_database(&db, &backup_path).await.unwrap();
+        assert_eq!(info.db_type, "sqlite");
+        assert!(info.size_bytes > 0);
+
+        let app_dir = tempdir().unwrap();
+        let outcome = restore_database(&db, &backup_path, app_dir.path())
+            .await
+            .unwrap();
+        assert!(outcome.requires_restart);
+        assert!(app_dir.path().join(PENDING_DIR).join(DB_ENTRY).is_file());
+
+        // 非 SQLite 文件与损坏的数据库被拒绝，且不留下待恢复内容
+        let broken = dir.path().join("broken.db");
+        let mut bytes = std::fs::read(&backup_path).unwrap();
+        bytes.truncate(bytes.len() / 2);
+        for (path, contents) in [
+            (&broken, bytes),
+            (&dir.path().join("notes.db"), b"hello".to_vec()),
+        ] {
+            std::fs::write(path, contents).unwrap();
+            assert!(restore_database(&db, path, app_dir.path()).await.is_err());
+            assert!(!app_dir.path().join(PENDING_DIR).exists());
+        }
+    }
+}
@@ -0,0 +1,612 @@
+// 浏览器活动 - 本地 HTTP 端点接收浏览器扩展上报的当前标签页地址，
+// 仅保存域名，按天写入帧目录下的 `browser_activity_<日期>.jsonl`（随帧文件按保留期清理），
+// 会话分析时关联到帧，并汇总出各域名的访问时长
+//
+// 扩展接口（仅监听 127.0.0.1）：
+// - `POST /activity`，请求体 `{"url": "https://github.com/..."}`；标签页切换、地址变化时上报，
+//   浏览器窗口获得焦点期间每 30 秒重复上报一次；窗口失去焦点时上报 `{"url": null}`
+// - `GET /status`，用于扩展检测连接
+// 请求需携带 `X-Screen-Analyzer-Token` 请求头（启用时未配置令牌会自动生成并保存），
+// CORS 响应头只对浏览器扩展来源返回，普通网页无法跨域调用
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::capture::window::frame_durations;
+use crate::capture::ScreenFrame;
+use crate::event_bus::{AppEvent, EventBus};
+use crate::models::{AppConfig, BrowserActivityConfig};
+use crate::settings::SettingsManager;
+use crate::storage::{local_now, DomainUsageRecord};
+
+/// 域名记录文件名前缀
+const BROWSER_LOG_PREFIX: &str = "browser_activity_";
+
+/// 一条上报的有效期（秒），超过后视为浏览器不在前台
+const EVENT_TTL_SECONDS: i64 = 60;
+
+/// 访问令牌请求头
+const TOKEN_HEADER: &str = "x-screen-analyzer-token";
+
+/// 允许跨域访问的浏览器扩展来源协议
+const EXTENSION_ORIGIN_SCHEMES: [&str; 3] = [
+    "chrome-extension://",
+    "moz-extension://",
+    "safari-web-extension://",
+];
+
+/// 单个请求的最大字节数
+const MAX_REQUEST_BYTES: usize = 64 * 1024;
+
+/// 读取单个请求的超时
+const READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// 扩展上报的请求体
+#[derive(Debug, Deserialize)]
+struct ActivityPayload {
+    /// 当前标签页地址；为空表示浏览器失去焦点
+    url: Option<String>,
+}
+
+/// 域名记录文件中的一行（domain 为空表示浏览器失去焦点）
+#[derive(Debug, Serialize, Deserialize)]
+struct DomainRecord {
+    timestamp_ms: i64,
+    domain: String,
+}
+
+/// 解析后的 HTTP 请求（局域网同步端点共用）
+#[derive(Debug)]
+pub(crate) struct HttpRequest {
+    pub(crate) method: String,
+    pub(crate) path: String,
+    /// 请求头（名称已转为小写）
+    pub(crate) headers: HashMap<String, String>,
+    pub(crate) body: Vec<u8>,
+}
+
+/// 从地址中提取域名（仅 http/https，去掉 www. 前缀）
+pub fn extract_domain(url: &str) -> Option<String> {
+    let url = Url::parse(url.trim()).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    let host = url.host_str()?.trim_end_matches('.').to_lowercase();
+    let domain = host.strip_prefix("www.").unwrap_or(&host);
+    (!domain.is_empty()).then(|| domain.to_string())
+}
+
+fn browser_log_path(frames_dir: &Path, date: NaiveDate) -> PathBuf {
+    frames_dir.join(format!(
+        "{}{}.jsonl",
+        BROWSER_LOG_PREFIX,
+        date.format("%Y-%m-%d")
+    ))
+}
+
+/// 追加一条域名记录（domain 为 None 表示浏览器失去焦点）
+pub fn append_domain_record(
+    frames_dir: &Path,
+    timestamp: DateTime<Utc>,
+    domain: Option<&str>,
+) -> Result<()> {
+    let record = DomainRecord {
+        timestamp_ms: timestamp.timestamp_millis(),
+        domain: domain.unwrap_or_default().to_string(),
+    };
+    let mut line = serde_json::to_string(&record)?;
+    line.push('\n');
+
+    std::fs::create_dir_all(frames_dir)?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(browser_log_path(frames_dir, timestamp.date_naive()))?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// 读取域名记录并填入 `ScreenFrame::domain`：取帧之前最近一条且未过期的上报
+pub async fn attach_domains(frames_dir: &Path, frames: &mut [ScreenFrame]) {
+    let (Some(first), Some(last)) = (
+        frames.iter().map(|f| f.timestamp).min(),
+        frames.iter().map(|f| f.timestamp).max(),
+    ) else {
+        return;
+    };
+
+    let mut records: Vec<DomainRecord> = Vec::new();
+    let first_date = (first - Duration::seconds(EVENT_TTL_SECONDS)).date_naive();
+    for date in first_date
+        .iter_days()
+        .take_while(|date| *date <= last.date_naive())
+    {
+        let Ok(data) = tokio::fs::read_to_string(browser_log_path(frames_dir, date)).await else {
+            continue;
+        };
+        records.extend(
+            data.lines()
+                .filter_map(|line| serde_json::from_str::<DomainRecord>(line).ok()),
+        );
+    }
+    if records.is_empty() {
+        return;
+    }
+    records.sort_by_key(|record| record.timestamp_ms);
+
+    for frame in frames.iter_mut() {
+        if frame.domain.is_some() {
+            continue;
+        }
+        let frame_ms = frame.timestamp.timestamp_millis();
+        let index = records.partition_point(|record| record.timestamp_ms <= frame_ms);
+        frame.domain = index
+            .checked_sub(1)
+            .map(|index| &records[index])
+            .filter(|record| frame_ms - record.timestamp_ms <= EVENT_TTL_SECONDS * 1000)
+            .filter(|record| !record.domain.is_empty())
+            .map(|record| record.domain.clone());
+    }
+}
+
+/// 按域名汇总会话时长（见 `frame_durations`）；没有域名或已标记空闲的帧不计入
+pub fn domain_breakdown(
+    session_id: i64,
+    frames: &[ScreenFrame],
+    window_end: DateTime<Utc>,
+) -> Vec<DomainUsageRecord> {
+    let mut domains: HashMap<&str, i64> = HashMap::new();
+    for (frame, seconds) in frame_durations(frames, window_end) {
+        if frame.idle_seconds.is_some() {
+            continue;
+        }
+        if let Some(domain) = &frame.domain {
+            *domains.entry(domain.as_str()).or_default() += seconds;
+        }
+    }
+
+    let mut records: Vec<DomainUsageRecord> = domains
+        .into_iter()
+        .filter(|(_, seconds)| *seconds > 0)
+        .map(|(domain, seconds)| DomainUsageRecord {
+            session_id,
+            domain: domain.to_string(),
+            seconds,
+        })
+        .collect();
+
+    records.sort_by(|a, b| {
+        b.seconds
+            .cmp(&a.seconds)
+            .then_with(|| a.domain.cmp(&b.domain))
+    });
+    records
+}
+
+/// 处理一个请求，返回状态行与 JSON 响应体
+fn handle_request(
+    request: &HttpRequest,
+    token: &str,
+    frames_dir: &Path,
+    now: DateTime<Utc>,
+) -> (&'static str, String) {
+    if request.method == "OPTIONS" {
+        return ("204 No Content", String::new());
+    }
+
+    // 未配置令牌时拒绝所有请求，避免任意本地网页伪造上报
+    let token = token.trim();
+    if token.is_empty()
+        || !request
+            .headers
+            .get(TOKEN_HEADER)
+            .is_some_and(|provided| token_matches(provided.trim(), token))
+    {
+        return (
+            "401 Unauthorized",
+            json!({ "error": "访问令牌无效" }).to_string(),
+        );
+    }
+
+    let path = request.path.split('?').next().unwrap_or_default();
+    match (request.method.as_str(), path) {
+        ("GET", "/status") => ("200 OK", json!({ "ok": true }).to_string()),
+        ("POST", "/activity") => {
+            let payload = match serde_json::from_slice::<ActivityPayload>(&request.body) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    return (
+                        "400 Bad Request",
+                        json!({ "error": format!("请求体无效: {}", e) }).to_string(),
+                    )
+                }
+            };
+            // 非网页地址（如 chrome://、about:）按失去焦点处理
+            let domain = payload.url.as_deref().and_then(extract_domain);
+            match append_domain_record(frames_dir, now, domain.as_deref()) {
+                Ok(()) => {
+                    debug!("浏览器活动: {:?}", domain);
+                    ("200 OK", json!({ "domain": domain }).to_string())
+                }
+                Err(e) => {
+                    warn!("保存浏览器活动失败: {}", e);
+                    (
+                        "500 Internal Server Error",
+                        json!({ "error": e.to_string() }).to_string(),
+                    )
+                }
+            }
+        }
+        _ => ("404 Not Found", json!({ "error": "not found" }).to_string()),
+    }
+}
+
+/// 请求来源为浏览器扩展时返回该来源，用于 CORS 响应头
+fn extension_origin(request: &HttpRequest) -> Option<&str> {
+    request
+        .headers
+        .get("origin")
+        .map(|origin| origin.as_str())
+        .filter(|origin| {
+            EXTENSION_ORIGIN_SCHEMES
+                .iter()
+                .any(|scheme| origin.starts_with(scheme))
+        })
+}
+
+/// 生成随机访问令牌
+fn generate_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// 比较令牌（耗时与内容无关）
+pub(crate) fn token_matches(provided: &str, token: &str) -> bool {
+    let (provided, token) = (provided.as_bytes(), token.as_bytes());
+    provided.len() == token.len()
+        && provided
+            .iter()
+            .zip(token)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// 读取并解析一个 HTTP 请求（按 Content-Length 读取请求体，请求体不超过 `max_bytes`）
+pub(crate) async fn read_request(stream: &mut TcpStream, max_bytes: usize) -> Result<HttpRequest> {
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let header_end = loop {
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buffer.len() > MAX_REQUEST_BYTES {
+            return Err(anyhow!("请求头过大"));
+        }
+        let length = stream.read(&mut chunk).await?;
+        if length == 0 {
+            return Err(anyhow!("连接已关闭"));
+        }
+        buffer.extend_from_slice(&chunk[..length]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_uppercase();
+    let path = request_line.next().unwrap_or("/").to_string();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let content_length = headers
+        .get("content-length")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > max_bytes {
+        return Err(anyhow!("请求体过大"));
+    }
+    while buffer.len() < header_end + content_length {
+        let length = stream.read(&mut chunk).await?;
+        if length == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..length]);
+    }
+    let body_end = buffer.len().min(header_end + content_length);
+
+    Ok(HttpRequest {
+        method,
+        path,
+        headers,
+        body: buffer[header_end..body_end].to_vec(),
+    })
+}
+
+async fn handle_connection(mut stream: TcpStream, token: &str, frames_dir: &Path) -> Result<()> {
+    let request = tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream, MAX_REQUEST_BYTES))
+        .await
+        .map_err(|_| anyhow!("读取请求超时"))??;
+    let (status, body) = handle_request(&request, token, frames_dir, local_now());
+
+    // 扩展后台页跨域请求需要 CORS 响应头；其他来源不返回，浏览器会拦截网页的跨域请求
+    let cors = extension_origin(&request)
+        .map(|origin| {
+            format!(
+                "Access-Control-Allow-Origin: {}\r\n\
+                 Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+                 Access-Control-Allow-Headers: Content-Type, X-Screen-Analyzer-Token\r\n\
+                 Vary: Origin\r\n",
+                origin
+            )
+        })
+        .unwrap_or_default();
+    let response = format!(
+        "HTTP/1.1 {}\r\n\
+         Content-Type: application/json; charset=utf-8\r\n\
+         Content-Length: {}\r\n\
+         {}\
+         Connection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        cors,
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    Ok(())
+}
+
+/// 正在运行的监听器（配置变更时重建）
+struct RunningListener {
+    config: BrowserActivityConfig,
+    task: JoinHandle<()>,
+}
+
+/// 浏览器活动接收端 - 按当前配置在本地端口监听扩展上报
+pub struct BrowserActivityServer {
+    settings: Arc<SettingsManager>,
+    frames_dir: PathBuf,
+    listener: Mutex<Option<RunningListener>>,
+}
+
+impl BrowserActivityServer {
+    pub fn new(settings: Arc<SettingsManager>, frames_dir: PathBuf) -> Self {
+        Self {
+            settings,
+            frames_dir,
+            listener: Mutex::new(None),
+        }
+    }
+
+    /// 启动（配置更新时重建监听）
+    pub fn start(self: Arc<Self>, event_bus: Arc<EventBus>) {
+        let mut receiver = event_bus.subscribe();
+
+        tokio::spawn(async move {
+            if let Err(e) = self.refresh().await {
+                warn!("浏览器活动端点启动失败: {}", e);
+            }
+
+            while let Ok(event) = receiver.recv().await {
+                if let AppEvent::ConfigUpdated { .. } = event {
+                    if let Err(e) = self.refresh().await {
+                        warn!("浏览器活动端点启动失败: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// 按最新配置启动、重启或停止监听
+    async fn refresh(&self) -> Result<()> {
+        let mut config = self.settings.get().await.browser_config.unwrap_or_default();
+        if config.enabled && config.token.trim().is_empty() {
+            config.token = generate_token();
+            self.settings
+                .update(AppConfig {
+                    browser_config: Some(config.clone()),
+                    ..Default::default()
+                })
+                .await?;
+            info!("已为浏览器活动端点生成访问令牌");
+        }
+        let mut listener = self.listener.lock().await;
+        if config.enabled
+            && listener
+                .as_ref()
+                .is_some_and(|running| running.config == config && !running.task.is_finished())
+        {
+            return Ok(());
+        }
+
+        if let Some(running) = listener.take() {
+            running.task.abort();
+            info!("浏览器活动端点已停止");
+        }
+        if !config.enabled {
+            return Ok(());
+        }
+
+        let tcp = TcpListener::bind(("127.0.0.1", config.port))
+            .await
+            .map_err(|e| anyhow!("无法监听 127.0.0.1:{}: {}", config.port, e))?;
+        info!("浏览器活动端点已启动: http://127.0.0.1:{}", config.port);
+
+        let token = config.token.clone();
+        let frames_dir = self.frames_dir.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                match tcp.accept().await {
+                    Ok((stream, _)) => {
+                        let token = token.clone();
+                        let frames_dir = frames_dir.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = handle_connection(stream, &token, &frames_dir).await {
+                                debug!("浏览器活动请求处理失败: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        warn!("浏览器活动端点接受连接失败: {}", e);
+                        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                    }
+                }
+            }
+        });
+
+        *listener = Some(RunningListener { config, task });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        chrono::NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_utc()
+            + Duration::seconds(seconds)
+    }
+
+    fn frame(timestamp: DateTime<Utc>) -> ScreenFrame {
+        ScreenFrame {
+            timestamp,
+            file_path: format!("{}.jpg", timestamp.timestamp_millis()),
+            screen_id: 0,
+            window: None,
+            domain: None,
+            idle_seconds: None,
+            repeat: None,
+            meeting: None,
+            focus_block: None,
+        }
+    }
+
+    fn post(body: &str, token: Option<&str>) -> HttpRequest {
+        HttpRequest {
+            method: "POST".to_string(),
+            path: "/activity".to_string(),
+            headers: token
+                .map(|token| (TOKEN_HEADER.to_string(), token.to_string()))
+                .into_iter()
+                .collect(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_extract_domain() {
+        assert_eq!(
+            extract_domain("https://www.GitHub.com/Nita121388/screen-analyzer?tab=1"),
+            Some("github.com".to_string())
+        );
+        assert_eq!(
+            extract_domain("http://localhost:5173/"),
+            Some("localhost".to_string())
+        );
+        assert_eq!(extract_domain("chrome://extensions"), None);
+        assert_eq!(extract_domain("not a url"), None);
+    }
+
+    #[test]
+    fn test_cors_only_for_extension_origin() {
+        let mut request = post("{}", None);
+        assert_eq!(extension_origin(&request), None);
+        request
+            .headers
+            .insert("origin".to_string(), "https://example.com".to_string());
+        assert_eq!(extension_origin(&request), None);
+        request.headers.insert(
+            "origin".to_string(),
+            "chrome-extension://abcdefghijklmnop".to_string(),
+        );
+        assert_eq!(
+            extension_origin(&request),
+            Some("chrome-extension://abcdefghijklmnop")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_activity_requests_and_breakdown() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let (status, _) = handle_request(
+            &post(r#"{"url":"https://docs.rs/tokio"}"#, None),
+            "secret",
+            dir.path(),
+            at(0),
+        );
+        assert_eq!(status, "401 Unauthorized");
+        let (status, _) = handle_request(
+            &post(r#"{"url":"https://docs.rs/tokio"}"#, None),
+            "",
+            dir.path(),
+            at(0),
+        );
+        assert_eq!(status, "401 Unauthorized");
+        let (status, _) = handle_request(
+            &post(r#"{"url":"https://docs.rs/tokio"}"#, Some("secreT")),
+            "secret",
+            dir.path(),
+            at(0),
+        );
+        assert_eq!(status, "401 Unauthorized");
+
+        let requests = [
+            (0, r#"{"url":"https://docs.rs/tokio"}"#),
+            (25, r#"{"url":"https://www.github.com/pulls"}"#),
+            (40, r#"{"url":null}"#),
+            (100, r#"{"url":"https://docs.rs/serde"}"#),
+        ];
+        for (seconds, body) in requests {
+            let (status, _) = handle_request(
+                &post(body, Some("secret")),
+                "secret",
+                dir.path(),
+                at(seconds),
+            );
+            assert_eq!(status, "200 OK");
+        }
+        let (status, _) = handle_request(&post("{", Some("secret")), "secret", dir.path(), at(0));
+        assert_eq!(status, "400 Bad Request");
+
+        let mut frames: Vec<ScreenFrame> = [5, 20, 30, 50, 110, 200].map(at).map(frame).to_vec();
+        attach_domains(dir.path(), &mut frames).await;
+        let domains: Vec<Option<&str>> = frames.iter().map(|f| f.domain.as_deref()).collect();
+        // 40 秒失去焦点；200 秒时距上次上报已超过有效期
+        assert_eq!(
+            domains,
+            vec![
+                Some("docs.rs"),
+                Some("docs.rs"),
+                Some("github.com"),
+                None,
+                Some("docs.rs"),
+                None
+            ]
+        );
+
+        let records = domain_breakdown(3, &frames, at(210));
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].domain, "docs.rs");
+        assert_eq!(records[0].seconds, 15 + 10 + 60);
+        assert_eq!(records[1].domain, "github.com");
+        assert_eq!(records[1].seconds, 20);
+    }
+}
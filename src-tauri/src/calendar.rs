@@ -0,0 +1,591 @@
+// Google 日历 - 只读 OAuth 授权，同步当天会议：用会议标题标注时间线卡片，并为每日总结提供上下文
+
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, Utc};
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+use crate::models::GoogleCalendarConfig;
+use crate::settings::SettingsManager;
+use crate::storage::{local_now, CalendarEventRecord, Database, Session, TimelineCardRecord};
+
+const AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const REVOKE_URL: &str = "https://oauth2.googleapis.com/revoke";
+const CALENDARS_URL: &str = "https://www.googleapis.com/calendar/v3/calendars";
+/// 只申请日历只读权限
+const SCOPE: &str = "https://www.googleapis.com/auth/calendar.readonly";
+
+/// 等待用户在浏览器中完成授权的时间
+const AUTHORIZE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+/// 定时同步当天会议的间隔
+const SYNC_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30 * 60);
+
+/// 标注后的时间线卡片标题前缀
+const MEETING_TITLE_PREFIX: &str = "会议：";
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventList {
+    #[serde(default)]
+    items: Vec<GoogleEvent>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct GoogleEvent {
+    id: String,
+    status: String,
+    summary: String,
+    location: String,
+    start: EventTime,
+    end: EventTime,
+    attendees: Vec<Attendee>,
+}
+
+/// 全天事件只有 date 字段，没有 dateTime
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct EventTime {
+    date_time: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Attendee {
+    #[serde(rename = "self")]
+    is_self: bool,
+    response_status: String,
+}
+
+fn credentials(config: &GoogleCalendarConfig) -> Result<(&str, &str)> {
+    let client_id = config.client_id.trim();
+    if client_id.is_empty() {
+        return Err(anyhow!("请先填写 Google OAuth 客户端 ID"));
+    }
+    Ok((client_id, config.client_secret.trim()))
+}
+
+/// 请求令牌端点
+async fn request_token(client: &Client, form: &[(&str, &str)]) -> Result<TokenResponse> {
+    let response = client.post(TOKEN_URL).form(form).send().await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(anyhow!("Google 授权失败 {}: {}", status.as_u16(), body));
+    }
+    Ok(response.json().await?)
+}
+
+/// 通过本地回环地址完成 OAuth 授权（PKCE），返回刷新令牌
+///
+/// `open_browser` 负责在系统浏览器中打开授权页面。
+pub async fn authorize(
+    config: &GoogleCalendarConfig,
+    open_browser: impl FnOnce(&str) -> Result<()>,
+) -> Result<String> {
+    let (client_id, client_secret) = credentials(config)?;
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let redirect_uri = format!("http://127.0.0.1:{}", listener.local_addr()?.port());
+    let state = uuid::Uuid::new_v4().simple().to_string();
+    let verifier = format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    let challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .encode(Sha256::digest(verifier.as_bytes()));
+
+    let url = Url::parse_with_params(
+        AUTH_URL,
+        &[
+            ("client_id", client_id),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("response_type", "code"),
+            ("scope", SCOPE),
+            // 离线访问并强制同意页，确保返回刷新令牌
+            ("access_type", "offline"),
+            ("prompt", "consent"),
+            ("state", state.as_str()),
+            ("code_challenge", challenge.as_str()),
+            ("code_challenge_method", "S256"),
+        ],
+    )?;
+    open_browser(url.as_str())?;
+
+    let code = tokio::time::timeout(AUTHORIZE_TIMEOUT, wait_for_code(&listener, &state))
+        .await
+        .map_err(|_| anyhow!("等待浏览器授权超时"))??;
+    let token = request_token(
+        &Client::new(),
+        &[
+            ("code", code.as_str()),
+            ("client_id", client_id),
+            ("client_secret", client_secret),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("grant_type", "authorization_code"),
+            ("code_verifier", verifier.as_str()),
+        ],
+    )
+    .await?;
+    token
+        .refresh_token
+        .ok_or_else(|| anyhow!("Google 未返回刷新令牌，请重新授权"))
+}
+
+/// 撤销刷新令牌（断开连接时调用）
+pub async fn revoke(refresh_token: &str) -> Result<()> {
+    let response = Client::new()
+        .post(REVOKE_URL)
+        .form(&[("token", refresh_token)])
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("撤销授权失败: {}", response.status().as_u16()));
+    }
+    Ok(())
+}
+
+/// 接收浏览器回调，返回授权码（忽略 favicon 等无关请求）
+async fn wait_for_code(listener: &TcpListener, state: &str) -> Result<String> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let mut buffer = vec![0u8; 8192];
+        let length = stream.read(&mut buffer).await?;
+        let callback = parse_callback(&String::from_utf8_lossy(&buffer[..length]), state);
+
+        let (status, message) = match &callback {
+            Some(Ok(_)) => ("200 OK", "授权完成，可以关闭此页面并返回 Screen Analyzer。"),
+            Some(Err(_)) => (
+                "400 Bad Request",
+                "授权失败，请返回 Screen Analyzer 查看原因。",
+            ),
+            None => ("404 Not Found", ""),
+        };
+        let html = format!(
+            "<!DOCTYPE html><html><head><meta charset=\"utf-8\"></head><body><p>{}</p></body></html>",
+            message
+        );
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            html.len(),
+            html
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+
+        if let Some(result) = callback {
+            return result;
+        }
+    }
+}
+
+/// 解析回调请求行中的授权码；不是授权回调时返回 None
+fn parse_callback(request: &str, state: &str) -> Option<Result<String>> {
+    let path = request.lines().next()?.split_whitespace().nth(1)?;
+    let url = Url::parse(&format!("http://127.0.0.1{}", path)).ok()?;
+    let param = |key: &str| {
+        url.query_pairs()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.into_owned())
+    };
+
+    if let Some(error) = param("error") {
+        return Some(Err(anyhow!("授权被拒绝: {}", error)));
+    }
+    let code = param("code")?;
+    if param("state").as_deref() != Some(state) {
+        return Some(Err(anyhow!("授权回调校验失败，请重新连接")));
+    }
+    Some(Ok(code))
+}
+
+/// Google 日历只读客户端
+pub struct GoogleCalendarClient {
+    config: GoogleCalendarConfig,
+    client: Client,
+}
+
+impl GoogleCalendarClient {
+    pub fn new(config: GoogleCalendarConfig) -> Result<Self> {
+        credentials(&config)?;
+        if config.refresh_token.trim().is_empty() {
+            return Err(anyhow!("请先连接 Google 日历"));
+        }
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()?;
+        Ok(Self { config, client })
+    }
+
+    async fn access_token(&self) -> Result<String> {
+        let (client_id, client_secret) = credentials(&self.config)?;
+        let token = request_token(
+            &self.client,
+            &[
+                ("client_id", client_id),
+                ("client_secret", client_secret),
+                ("refresh_token", self.config.refresh_token.trim()),
+                ("grant_type", "refresh_token"),
+            ],
+        )
+        .await?;
+        Ok(token.access_token)
+    }
+
+    /// 获取某一天（本地时间）的会议
+    pub async fn fetch_events(&self, date: NaiveDate) -> Result<Vec<CalendarEventRecord>> {
+        let day_start = local_midnight(date)?;
+        let day_end = local_midnight(date + Duration::days(1))?;
+        let calendar_id = match self.config.calendar_id.trim() {
+            "" => "primary",
+            calendar_id => calendar_id,
+        };
+        let mut url = Url::parse(CALENDARS_URL)?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("日历地址无效"))?
+            .push(calendar_id)
+            .push("events");
+
+        let access_token = self.access_token().await?;
+        let date_key = date.format("%Y-%m-%d").to_string();
+        let synced_at = local_now();
+        let mut events = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut request = self
+                .client
+                .get(url.clone())
+                .bearer_auth(&access_token)
+                .query(&[
+                    ("timeMin", day_start.to_rfc3339()),
+                    ("timeMax", day_end.to_rfc3339()),
+                    // 展开重复会议为单次事件
+                    ("singleEvents", "true".to_string()),
+                    ("orderBy", "startTime".to_string()),
+                    ("maxResults", "250".to_string()),
+                ]);
+            if let Some(token) = &page_token {
+                request = request.query(&[("pageToken", token)]);
+            }
+            let response = request.send().await?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                return Err(anyhow!("获取日历事件失败 {}: {}", status.as_u16(), body));
+            }
+
+            let list: EventList = response.json().await?;
+            events.extend(
+                list.items
+                    .into_iter()
+                    .filter_map(|event| meeting_record(&date_key, event, synced_at)),
+            );
+            match list.next_page_token {
+                Some(token) => page_token = Some(token),
+                None => break,
+            }
+        }
+        Ok(events)
+    }
+}
+
+fn local_midnight(date: NaiveDate) -> Result<DateTime<Local>> {
+    date.and_hms_opt(0, 0, 0)
+        .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+        .ok_or_else(|| anyhow!("无法换算本地时间: {}", date))
+}
+
+/// 带时区的事件时间 -> 数据库存储的本地时间值
+fn to_stored_time(time: DateTime<FixedOffset>) -> DateTime<Utc> {
+    time.with_timezone(&Local).naive_local().and_utc()
+}
+
+/// 转换为会议记录；跳过已取消、全天以及本人已拒绝的事件
+fn meeting_record(
+    date: &str,
+    event: GoogleEvent,
+    synced_at: DateTime<Utc>,
+) -> Option<CalendarEventRecord> {
+    if event.status == "cancelled" {
+        return None;
+    }
+    let (start, end) = (event.start.date_time?, event.end.date_time?);
+    if event
+        .attendees
+        .iter()
+        .any(|attendee| attendee.is_self && attendee.response_status == "declined")
+    {
+        return None;
+    }
+    let title = match event.summary.trim() {
+        "" => "（无标题）".to_string(),
+        title => title.to_string(),
+    };
+    Some(CalendarEventRecord {
+        id: None,
+        event_id: event.id,
+        date: date.to_string(),
+        title,
+        location: event.location.trim().to_string(),
+        start_time: to_stored_time(start),
+        end_time: to_stored_time(end),
+        synced_at,
+    })
+}
+
+fn overlap_seconds(start: NaiveDateTime, end: NaiveDateTime, meeting: &CalendarEventRecord) -> i64 {
+    let overlap_start = start.max(meeting.start_time.naive_utc());
+    let overlap_end = end.min(meeting.end_time.naive_utc());
+    (overlap_end - overlap_start).num_seconds().max(0)
+}
+
+/// 与卡片重叠最多的会议（重叠需超过卡片时长的一半）
+pub fn meeting_for_card<'a>(
+    card: &TimelineCardRecord,
+    meetings: &'a [CalendarEventRecord],
+) -> Option<&'a CalendarEventRecord> {
+    let start = DateTime::parse_from_rfc3339(&card.start_time)
+        .ok()?
+        .naive_local();
+    let end = DateTime::parse_from_rfc3339(&card.end_time)
+        .ok()?
+        .naive_local();
+    let duration = (end - start).num_seconds();
+    meetings
+        .iter()
+        .map(|meeting| (meeting, overlap_seconds(start, end, meeting)))
+        .filter(|(_, overlap)| *overlap > 0 && *overlap * 2 > duration)
+        .max_by_key(|(_, overlap)| *overlap)
+        .map(|(meeting, _)| meeting)
+}
+
+/// 与会话时段有重叠的会议标题（供每日总结参考）
+pub fn session_meetings<'a>(
+    session: &Session,
+    meetings: &'a [CalendarEventRecord],
+) -> Vec<&'a str> {
+    meetings
+        .iter()
+        .filter(|meeting| {
+            overlap_seconds(
+                session.start_time.naive_utc(),
+                session.end_time.naive_utc(),
+                meeting,
+            ) > 0
+        })
+        .map(|meeting| meeting.title.as_str())
+        .collect()
+}
+
+/// 用会议标题标注与会议时段重叠的时间线卡片，返回更新的卡片数
+pub async fn label_meeting_cards(
+    db: &Database,
+    sessions: &[Session],
+    meetings: &[CalendarEventRecord],
+) -> Result<usize> {
+    let mut labeled = 0;
+    for session in sessions {
+        let Some(session_id) = session.id else {
+            continue;
+        };
+        if session_meetings(session, meetings).is_empty() {
+            continue;
+        }
+        for card in db.get_timeline_cards_by_session(session_id).await? {
+            let (Some(card_id), Some(meeting)) = (card.id, meeting_for_card(&card, meetings))
+            else {
+                continue;
+            };
+            let title = format!("{}{}", MEETING_TITLE_PREFIX, meeting.title);
+            if card.title != title {
+                db.update_timeline_card_title(card_id, &title).await?;
+                labeled += 1;
+            }
+        }
+    }
+    Ok(labeled)
+}
+
+/// 同步指定日期的会议并标注时间线卡片，返回（会议数, 标注卡片数）
+pub async fn sync_day(
+    db: &Database,
+    config: &GoogleCalendarConfig,
+    date: &str,
+) -> Result<(usize, usize)> {
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")?;
+    let meetings = GoogleCalendarClient::new(config.clone())?
+        .fetch_events(day)
+        .await?;
+    db.replace_calendar_events(date, &meetings).await?;
+    let sessions = db.get_sessions_by_date(date).await?;
+    let labeled = label_meeting_cards(db, &sessions, &meetings).await?;
+    Ok((meetings.len(), labeled))
+}
+
+/// 日历同步任务 - 已连接时定时同步当天会议（新生成的卡片随之标注）
+pub struct CalendarSyncer {
+    settings: Arc<SettingsManager>,
+    db: Arc<Database>,
+}
+
+impl CalendarSyncer {
+    pub fn new(settings: Arc<SettingsManager>, db: Arc<Database>) -> Self {
+        Self { settings, db }
+    }
+
+    pub fn start(self: Arc<Self>) {
+        tokio::spawn(async move {
+            info!("Google 日历同步任务已启动");
+            loop {
+                let config = self
+                    .settings
+                    .get()
+                    .await
+                    .calendar_config
+                    .unwrap_or_default();
+                if config.enabled && !config.refresh_token.is_empty() {
+                    let date = local_now().format("%Y-%m-%d").to_string();
+                    match sync_day(&self.db, &config, &date).await {
+                        Ok((meetings, labeled)) => info!(
+                            "Google 日历已同步 {}: {} 个会议，标注 {} 张卡片",
+                            date, meetings, labeled
+                        ),
+                        Err(e) => warn!("Google 日历同步失败: {}", e),
+                    }
+                }
+                tokio::time::sleep(SYNC_INTERVAL).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meeting(start: &str, end: &str, title: &str) -> CalendarEventRecord {
+        let time = |value: &str| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M")
+                .unwrap()
+                .and_utc()
+        };
+        CalendarEventRecord {
+            id: None,
+            event_id: title.to_string(),
+            date: "2024-05-12".to_string(),
+            title: title.to_string(),
+            location: String::new(),
+            start_time: time(start),
+            end_time: time(end),
+            synced_at: time(start),
+        }
+    }
+
+    #[test]
+    fn test_parse_callback() {
+        let request = |path: &str| format!("GET {} HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n", path);
+        assert_eq!(
+            parse_callback(&request("/?state=abc&code=4%2F0Ad"), "abc")
+                .unwrap()
+                .unwrap(),
+            "4/0Ad"
+        );
+        assert!(parse_callback(&request("/?state=xyz&code=1"), "abc")
+            .unwrap()
+            .is_err());
+        assert!(parse_callback(&request("/?error=access_denied"), "abc")
+            .unwrap()
+            .is_err());
+        assert!(parse_callback(&request("/favicon.ico"), "abc").is_none());
+    }
+
+    #[test]
+    fn test_meeting_record() {
+        let synced_at = local_now();
+        let event = |json: serde_json::Value| serde_json::from_value::<GoogleEvent>(json).unwrap();
+
+        let record = meeting_record(
+            "2024-05-12",
+            event(serde_json::json!({
+                "id": "e1",
+                "status": "confirmed",
+                "summary": " 产品评审 ",
+                "start": { "dateTime": "2024-05-12T10:00:00+08:00" },
+                "end": { "dateTime": "2024-05-12T11:00:00+08:00" },
+                "attendees": [{ "self": true, "responseStatus": "accepted" }]
+            })),
+            synced_at,
+        )
+        .unwrap();
+        assert_eq!(record.title, "产品评审");
+        assert_eq!(
+            record.start_time,
+            to_stored_time(DateTime::parse_from_rfc3339("2024-05-12T10:00:00+08:00").unwrap())
+        );
+
+        // 全天事件、已取消与已拒绝的会议不计入
+        for json in [
+            serde_json::json!({ "start": { "date": "2024-05-12" }, "end": { "date": "2024-05-13" } }),
+            serde_json::json!({
+                "status": "cancelled",
+                "start": { "dateTime": "2024-05-12T10:00:00+08:00" },
+                "end": { "dateTime": "2024-05-12T11:00:00+08:00" }
+            }),
+            serde_json::json!({
+                "start": { "dateTime": "2024-05-12T10:00:00+08:00" },
+                "end": { "dateTime": "2024-05-12T11:00:00+08:00" },
+                "attendees": [{ "self": true, "responseStatus": "declined" }]
+            }),
+        ] {
+            assert!(meeting_record("2024-05-12", event(json), synced_at).is_none());
+        }
+    }
+
+    #[test]
+    fn test_meeting_for_card() {
+        let meetings = vec![
+            meeting("2024-05-12 10:00", "2024-05-12 11:00", "产品评审"),
+            meeting("2024-05-12 11:00", "2024-05-12 11:30", "一对一"),
+        ];
+        let card = |start: &str, end: &str| TimelineCardRecord {
+            id: Some(1),
+            session_id: 1,
+            llm_call_id: None,
+            start_time: format!("2024-05-12T{}:00+08:00", start),
+            end_time: format!("2024-05-12T{}:00+08:00", end),
+            category: "Work".to_string(),
+            subcategory: String::new(),
+            title: "浏览文档".to_string(),
+            summary: String::new(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: String::new(),
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+            created_at: local_now(),
+        };
+
+        let matched = |start: &str, end: &str| {
+            meeting_for_card(&card(start, end), &meetings).map(|meeting| meeting.title.as_str())
+        };
+        assert_eq!(matched("10:10", "10:40"), Some("产品评审"));
+        assert_eq!(matched("10:50", "11:25"), Some("一对一"));
+        // 会议只覆盖卡片的一小部分时不标注
+        assert_eq!(matched("09:30", "10:10"), None);
+    }
+}
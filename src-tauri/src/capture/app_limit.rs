@@ -0,0 +1,245 @@
+// 应用使用时长上限 - 每次截屏后按前台应用累计当天的使用时长（每帧计到下一帧，最长按
+// `window::MAX_FRAME_SECONDS` 计，空闲帧不计），达到配置的每日上限时发出提醒（每个应用每天一次）；
+// 启动时从当天的窗口记录文件恢复已用时长，恢复期间已达上限的应用视为已提醒
+
+use super::{window, ScreenFrame};
+use crate::models::{AppLimit, AppLimitConfig};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+use tracing::info;
+
+/// 事件通道容量
+const EVENT_CAPACITY: usize = 8;
+
+/// 应用达到每日上限的提醒
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppLimitAlert {
+    pub app_name: String,
+    pub used_minutes: i64,
+    pub limit_minutes: u32,
+}
+
+#[derive(Default)]
+struct LimitState {
+    config: AppLimitConfig,
+    date: Option<NaiveDate>,
+    /// 小写应用名 -> 当天累计秒数
+    usage: HashMap<String, i64>,
+    /// 上一帧的时间与应用（小写，空闲帧为空）
+    last: Option<(DateTime<Utc>, Option<String>)>,
+    /// 当天已提醒的应用（小写）
+    alerted: HashSet<String>,
+}
+
+impl LimitState {
+    /// 记录一帧：上一帧的时长计入上一帧的应用，返回该应用与当天累计秒数
+    fn record(&mut self, timestamp: DateTime<Utc>, app: Option<String>) -> Option<(String, i64)> {
+        let date = timestamp.date_naive();
+        if self.date != Some(date) {
+            self.date = Some(date);
+            self.usage.clear();
+            self.alerted.clear();
+            self.last = None;
+        }
+
+        let previous = self.last.replace((timestamp, app));
+        let (last_time, Some(last_app)) = previous? else {
+            return None;
+        };
+        let seconds = (timestamp - last_time)
+            .num_seconds()
+            .clamp(0, window::MAX_FRAME_SECONDS);
+        let used = self.usage.entry(last_app.clone()).or_default();
+        *used += seconds;
+        Some((last_app, *used))
+    }
+
+    fn limit_for(&self, app: &str) -> Option<&AppLimit> {
+        crate::app_usage::limit_for(&self.config.limits, app)
+    }
+}
+
+/// 应用时长上限监测器
+pub struct AppLimitWatcher {
+    state: Mutex<LimitState>,
+    events: broadcast::Sender<AppLimitAlert>,
+}
+
+impl Default for AppLimitWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AppLimitWatcher {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        Self {
+            state: Mutex::new(LimitState::default()),
+            events,
+        }
+    }
+
+    /// 订阅达到上限的提醒
+    pub fn subscribe(&self) -> broadcast::Receiver<AppLimitAlert> {
+        self.events.subscribe()
+    }
+
+    /// 更新配置（上限调低后，已达上限但未提醒的应用在下一次记录时提醒）
+    pub fn update_config(&self, config: AppLimitConfig) {
+        self.state.lock().unwrap().config = config;
+    }
+
+    /// 用当天已记录的窗口信息恢复累计时长（不发提醒，已达上限的应用视为已提醒）
+    pub fn restore(&self, records: &[(DateTime<Utc>, window::WindowInfo)]) {
+        let mut state = self.state.lock().unwrap();
+        for (timestamp, info) in records {
+            if let Some((app, used)) = state.record(*timestamp, Some(app_key(&info.app_name))) {
+                if let Some(limit) = state.limit_for(&app) {
+                    if used >= i64::from(limit.daily_minutes) * 60 {
+                        state.alerted.insert(app);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 记录一帧，上一帧的应用当天累计时长首次达到上限时发出提醒
+    pub fn observe_frame(&self, frame: &ScreenFrame) -> Option<AppLimitAlert> {
+        let mut state = self.state.lock().unwrap();
+        let app = frame
+            .window
+            .as_ref()
+            .filter(|_| frame.idle_seconds.is_none())
+            .map(|window| app_key(&window.app_name));
+        let (app, used) = state.record(frame.timestamp, app)?;
+
+        let limit = state.limit_for(&app)?.clone();
+        if used < i64::from(limit.daily_minutes) * 60 || !state.alerted.insert(app) {
+            return None;
+        }
+        let alert = AppLimitAlert {
+            app_name: limit.app_name.trim().to_string(),
+            used_minutes: used / 60,
+            limit_minutes: limit.daily_minutes,
+        };
+        info!(
+            "{} 今日已使用 {} 分钟，达到上限 {} 分钟",
+            alert.app_name, alert.used_minutes, alert.limit_minutes
+        );
+        if state.config.notify {
+            let _ = self.events.send(alert.clone());
+        }
+        Some(alert)
+    }
+
+    /// 当天各应用的累计分钟数（小写应用名）
+    pub fn usage_minutes(&self) -> HashMap<String, i64> {
+        let state = self.state.lock().unwrap();
+        state
+            .usage
+            .iter()
+            .map(|(app, seconds)| (app.clone(), seconds / 60))
+            .collect()
+    }
+}
+
+fn app_key(app_name: &str) -> String {
+    app_name.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::window::WindowInfo;
+    use chrono::Duration;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(20, 0, 0)
+            .unwrap()
+            .and_utc()
+            + Duration::minutes(minutes)
+    }
+
+    fn frame(timestamp: DateTime<Utc>, app: &str) -> ScreenFrame {
+        ScreenFrame {
+            timestamp,
+            file_path: String::new(),
+            screen_id: 0,
+            window: Some(WindowInfo {
+                app_name: app.to_string(),
+                ..Default::default()
+            }),
+            domain: None,
+            idle_seconds: None,
+            repeat: None,
+            meeting: None,
+            focus_block: None,
+        }
+    }
+
+    fn watcher() -> AppLimitWatcher {
+        let watcher = AppLimitWatcher::new();
+        watcher.update_config(AppLimitConfig {
+            notify: true,
+            limits: vec![AppLimit {
+                app_name: "Steam".to_string(),
+                daily_minutes: 3,
+            }],
+        });
+        watcher
+    }
+
+    #[test]
+    fn test_alert_once_per_day_after_limit() {
+        let watcher = watcher();
+        let mut events = watcher.subscribe();
+        let mut fired = Vec::new();
+        // 每分钟一帧，应用名不区分大小写
+        for minute in 0..=6 {
+            let app = if minute % 2 == 0 { "steam" } else { "Steam" };
+            if let Some(alert) = watcher.observe_frame(&frame(at(minute), app)) {
+                fired.push((minute, alert));
+            }
+        }
+        assert_eq!(fired.len(), 1);
+        let (minute, alert) = &fired[0];
+        assert_eq!(*minute, 3);
+        assert_eq!(alert.app_name, "Steam");
+        assert_eq!((alert.used_minutes, alert.limit_minutes), (3, 3));
+        assert!(events.try_recv().is_ok());
+        assert_eq!(watcher.usage_minutes()["steam"], 6);
+
+        // 第二天重新计时
+        for minute in 0..=3 {
+            let timestamp = at(24 * 60 + minute);
+            let alert = watcher.observe_frame(&frame(timestamp, "Steam"));
+            assert_eq!(alert.is_some(), minute == 3);
+        }
+    }
+
+    #[test]
+    fn test_restore_counts_usage_without_alerting() {
+        let watcher = watcher();
+        let records: Vec<_> = (0..=4)
+            .map(|minute| {
+                (
+                    at(minute),
+                    WindowInfo {
+                        app_name: "Steam".to_string(),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect();
+        watcher.restore(&records);
+        assert_eq!(watcher.usage_minutes()["steam"], 4);
+        // 恢复时已达上限，不再提醒；长时间间隔只按单帧上限计
+        assert!(watcher.observe_frame(&frame(at(30), "Code")).is_none());
+        assert_eq!(watcher.usage_minutes()["steam"], 5);
+    }
+}
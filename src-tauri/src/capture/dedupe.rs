@@ -0,0 +1,310 @@
+// 重复帧抑制 - 用感知哈希（dHash）比较同一显示器相邻截图，画面几乎不变时不再保存，
+// 被跳过的时刻按天写入帧目录下的 `duplicate_frames_<日期>.jsonl`（随帧文件按保留期清理），
+// 会话分析时据此延长保留帧代表的时长，应用统计与视频时间线保持准确
+
+use super::window::WindowInfo;
+use super::ScreenFrame;
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use image::{imageops, DynamicImage, GenericImageView};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 重复记录文件名前缀
+const DUPLICATE_LOG_PREFIX: &str = "duplicate_frames_";
+
+/// 画面不变时至少每 4 分钟保存一帧，保证每个会话时间窗（最短 5 分钟）都有帧
+pub const MAX_SKIP_SECONDS: i64 = 240;
+
+/// 保留帧被后续重复截图延长的信息
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameRepeat {
+    /// 被跳过的重复截图数
+    pub count: u32,
+    /// 最后一张重复截图的时间
+    pub until: DateTime<Utc>,
+}
+
+/// 重复记录文件中的一行
+#[derive(Debug, Serialize, Deserialize)]
+struct DuplicateRecord {
+    /// 被跳过的截图时间
+    timestamp_ms: i64,
+    /// 被延长的保留帧时间
+    frame_ms: i64,
+    screen_id: usize,
+}
+
+/// 截图时的上下文，前台窗口、空闲或会议状态变化时即使画面相同也保存新帧
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameContext {
+    pub window: Option<WindowInfo>,
+    pub idle: bool,
+    pub meeting: bool,
+}
+
+struct LastFrame {
+    hash: u64,
+    timestamp: DateTime<Utc>,
+    context: FrameContext,
+}
+
+/// 按显示器记录上一张保存的帧
+#[derive(Default)]
+pub struct DuplicateFilter {
+    last: HashMap<usize, LastFrame>,
+}
+
+impl DuplicateFilter {
+    /// 判断截图是否与该显示器上一张保存的帧重复：重复时返回保留帧的时间，否则记为新的保留帧
+    pub fn check(
+        &mut self,
+        screen_id: usize,
+        hash: u64,
+        timestamp: DateTime<Utc>,
+        context: FrameContext,
+        threshold: u32,
+    ) -> Option<DateTime<Utc>> {
+        if let Some(last) = self.last.get(&screen_id) {
+            if last.context == context
+                && hamming_distance(last.hash, hash) <= threshold
+                && timestamp - last.timestamp < Duration::seconds(MAX_SKIP_SECONDS)
+            {
+                return Some(last.timestamp);
+            }
+        }
+
+        self.last.insert(
+            screen_id,
+            LastFrame {
+                hash,
+                timestamp,
+                context,
+            },
+        );
+        None
+    }
+}
+
+/// 差值哈希：缩放为 9x8 灰度图，逐行比较相邻像素亮度
+pub fn dhash(image: &DynamicImage) -> u64 {
+    let small = image
+        .grayscale()
+        .resize_exact(9, 8, imageops::FilterType::Triangle);
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = small.get_pixel(x, y)[0];
+            let right = small.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | u64::from(left > right);
+        }
+    }
+    hash
+}
+
+/// 两个哈希不同的位数
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// 某天的重复记录文件
+fn duplicate_log_path(frames_dir: &Path, date: NaiveDate) -> PathBuf {
+    frames_dir.join(format!(
+        "{}{}.jsonl",
+        DUPLICATE_LOG_PREFIX,
+        date.format("%Y-%m-%d")
+    ))
+}
+
+/// 追加一条重复记录
+pub fn append_duplicate_record(
+    frames_dir: &Path,
+    timestamp: DateTime<Utc>,
+    frame_timestamp: DateTime<Utc>,
+    screen_id: usize,
+) -> Result<()> {
+    let record = DuplicateRecord {
+        timestamp_ms: timestamp.timestamp_millis(),
+        frame_ms: frame_timestamp.timestamp_millis(),
+        screen_id,
+    };
+    let mut line = serde_json::to_string(&record)?;
+    line.push('\n');
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(duplicate_log_path(frames_dir, timestamp.date_naive()))?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// 读取重复记录并填入 `ScreenFrame::repeat`（按保留帧的毫秒时间戳与显示器匹配）
+pub async fn attach_repeats(frames_dir: &Path, frames: &mut [ScreenFrame]) {
+    let (Some(first), Some(last)) = (
+        frames.iter().map(|f| f.timestamp).min(),
+        frames.iter().map(|f| f.timestamp).max(),
+    ) else {
+        return;
+    };
+
+    // 重复截图晚于保留帧，可能落在次日的记录文件中
+    let last = last + Duration::seconds(MAX_SKIP_SECONDS);
+    let mut repeats: HashMap<(i64, usize), FrameRepeat> = HashMap::new();
+    for date in first
+        .date_naive()
+        .iter_days()
+        .take_while(|date| *date <= last.date_naive())
+    {
+        let Ok(data) = tokio::fs::read_to_string(duplicate_log_path(frames_dir, date)).await else {
+            continue;
+        };
+        for line in data.lines() {
+            let Ok(record) = serde_json::from_str::<DuplicateRecord>(line) else {
+                continue;
+            };
+            let Some(timestamp) = DateTime::<Utc>::from_timestamp_millis(record.timestamp_ms)
+            else {
+                continue;
+            };
+            let repeat = repeats
+                .entry((record.frame_ms, record.screen_id))
+                .or_insert(FrameRepeat {
+                    count: 0,
+                    until: timestamp,
+                });
+            repeat.count += 1;
+            repeat.until = repeat.until.max(timestamp);
+        }
+    }
+
+    for frame in frames.iter_mut() {
+        if frame.repeat.is_none() {
+            frame.repeat = repeats
+                .get(&(frame.timestamp.timestamp_millis(), frame.screen_id))
+                .copied();
+        }
+    }
+}
+
+/// 生成视频用的帧路径：被跳过的重复截图以保留帧补齐，视频时长与实际时间保持一致
+pub fn expand_repeats(frames: &[ScreenFrame]) -> Vec<String> {
+    frames
+        .iter()
+        .flat_map(|frame| {
+            let count = 1 + frame.repeat.map_or(0, |repeat| repeat.count as usize);
+            std::iter::repeat_n(frame.file_path.clone(), count)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    fn start() -> DateTime<Utc> {
+        chrono::NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(23, 58, 0)
+            .unwrap()
+            .and_utc()
+    }
+
+    fn gradient(shift: u32) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(160, 90, |x, y| {
+            let value = ((x * 3 + y + shift) % 256) as u8;
+            Rgba([value, value, value, 255])
+        }))
+    }
+
+    fn context(app: &str) -> FrameContext {
+        FrameContext {
+            window: Some(WindowInfo {
+                app_name: app.to_string(),
+                window_title: String::new(),
+                process_path: None,
+            }),
+            idle: false,
+            meeting: false,
+        }
+    }
+
+    #[test]
+    fn test_duplicate_filter() {
+        let same = dhash(&gradient(0));
+        assert_eq!(hamming_distance(same, dhash(&gradient(0))), 0);
+        let changed = dhash(&DynamicImage::ImageRgba8(RgbaImage::from_fn(
+            160,
+            90,
+            |x, _| {
+                let value = if x < 80 { 255 } else { 0 };
+                Rgba([value, value, value, 255])
+            },
+        )));
+        assert!(hamming_distance(same, changed) > 5);
+
+        let at = |seconds: i64| start() + Duration::seconds(seconds);
+        let mut filter = DuplicateFilter::default();
+        assert_eq!(filter.check(0, same, at(0), context("Code"), 5), None);
+        assert_eq!(
+            filter.check(0, same, at(1), context("Code"), 5),
+            Some(at(0))
+        );
+        // 其他显示器、画面变化、前台应用变化时保存新帧
+        assert_eq!(filter.check(1, same, at(2), context("Code"), 5), None);
+        assert_eq!(filter.check(0, changed, at(3), context("Code"), 5), None);
+        assert_eq!(filter.check(0, changed, at(4), context("Chrome"), 5), None);
+        // 超过最长跳过时间后强制保存
+        assert_eq!(
+            filter.check(0, changed, at(200), context("Chrome"), 5),
+            Some(at(4))
+        );
+        assert_eq!(
+            filter.check(0, changed, at(250), context("Chrome"), 5),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repeats_extend_frames() {
+        let dir = tempfile::tempdir().unwrap();
+        let kept = start();
+        // 重复截图跨过午夜，写入次日的记录文件
+        for seconds in 1..=150 {
+            append_duplicate_record(dir.path(), kept + Duration::seconds(seconds), kept, 0)
+                .unwrap();
+        }
+
+        let frame = |timestamp: DateTime<Utc>| ScreenFrame {
+            timestamp,
+            file_path: format!("{}.jpg", timestamp.timestamp_millis()),
+            screen_id: 0,
+            window: None,
+            domain: None,
+            idle_seconds: None,
+            repeat: None,
+            meeting: None,
+            focus_block: None,
+        };
+        let next = kept + Duration::seconds(151);
+        let mut frames = vec![frame(kept), frame(next)];
+        attach_repeats(dir.path(), &mut frames).await;
+        assert_eq!(
+            frames[0].repeat,
+            Some(FrameRepeat {
+                count: 150,
+                until: kept + Duration::seconds(150),
+            })
+        );
+        assert_eq!(frames[1].repeat, None);
+
+        // 保留帧的时长延长到下一帧，而不是截断为 60 秒
+        let durations = super::super::window::frame_durations(&frames, next + Duration::seconds(1));
+        assert_eq!(durations[0].1, 151);
+        assert_eq!(expand_repeats(&frames).len(), 152);
+    }
+}
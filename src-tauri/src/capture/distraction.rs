@@ -0,0 +1,285 @@
+// 分心提醒 - 每次截屏后按本地启发式分类判断当前活动，累计最近一段时间内干扰类活动的时长
+// （每帧覆盖到下一帧，最长按 MAX_SAMPLE_SECONDS 计），工作时段内超过阈值时发出提醒，按冷却间隔限流
+
+use super::{heuristic, quiet, ScreenFrame};
+use crate::models::DistractionAlertConfig;
+use chrono::{DateTime, Datelike, Duration, Utc};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+use tracing::info;
+
+/// 单帧最多代表的秒数（重复画面未保存或截屏暂停时不无限延长）
+const MAX_SAMPLE_SECONDS: i64 = 300;
+
+/// 提醒中列出的干扰应用数量
+const MAX_ALERT_APPS: usize = 3;
+
+/// 事件通道容量
+const EVENT_CAPACITY: usize = 8;
+
+/// 一帧的分类结果
+#[derive(Debug, Clone)]
+struct Sample {
+    timestamp: DateTime<Utc>,
+    /// 干扰类活动时的前台应用（或类别）
+    distraction: Option<String>,
+}
+
+/// 分心提醒
+#[derive(Debug, Clone)]
+pub struct DistractionAlert {
+    /// 统计窗口内的干扰时长（分钟）
+    pub distraction_minutes: i64,
+    /// 统计窗口（分钟）
+    pub window_minutes: u32,
+    /// 占用时间最多的干扰应用
+    pub apps: Vec<String>,
+}
+
+#[derive(Default)]
+struct WatcherState {
+    config: DistractionAlertConfig,
+    samples: VecDeque<Sample>,
+    last_alert: Option<DateTime<Utc>>,
+}
+
+/// 分心监测器
+pub struct DistractionWatcher {
+    state: Mutex<WatcherState>,
+    events: broadcast::Sender<DistractionAlert>,
+}
+
+impl Default for DistractionWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DistractionWatcher {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        Self {
+            state: Mutex::new(WatcherState::default()),
+            events,
+        }
+    }
+
+    /// 订阅分心提醒
+    pub fn subscribe(&self) -> broadcast::Receiver<DistractionAlert> {
+        self.events.subscribe()
+    }
+
+    /// 更新配置（关闭时清空已记录的帧）
+    pub fn update_config(&self, config: DistractionAlertConfig) {
+        let mut state = self.state.lock().unwrap();
+        if !config.enabled {
+            state.samples.clear();
+        }
+        state.config = config;
+    }
+
+    /// 记录一帧，工作时段内干扰时长超过阈值且已过冷却间隔时发出提醒
+    pub fn observe_frame(&self, frame: &ScreenFrame) -> Option<DistractionAlert> {
+        let mut state = self.state.lock().unwrap();
+        if !state.config.enabled {
+            return None;
+        }
+
+        let now = frame.timestamp;
+        let window = Duration::minutes(state.config.window_minutes as i64);
+        while state
+            .samples
+            .front()
+            .is_some_and(|sample| now - sample.timestamp > window)
+        {
+            state.samples.pop_front();
+        }
+
+        // 空闲帧不计入干扰
+        let distraction = if frame.idle_seconds.is_some() {
+            None
+        } else {
+            let category = heuristic::classify_frame(frame, None).category;
+            state
+                .config
+                .categories
+                .iter()
+                .any(|c| c == category)
+                .then(|| {
+                    frame
+                        .window
+                        .as_ref()
+                        .map(|w| w.app_name.clone())
+                        .filter(|app| !app.trim().is_empty())
+                        .unwrap_or_else(|| category.to_string())
+                })
+        };
+        state.samples.push_back(Sample {
+            timestamp: now,
+            distraction,
+        });
+
+        if !in_work_hours(&state.config, now) {
+            return None;
+        }
+        let cooldown = Duration::minutes(state.config.cooldown_minutes as i64);
+        if state.last_alert.is_some_and(|last| now - last < cooldown) {
+            return None;
+        }
+
+        let apps = distraction_seconds(&state.samples);
+        let total: i64 = apps.iter().map(|(_, seconds)| seconds).sum();
+        if total < state.config.threshold_minutes as i64 * 60 || total == 0 {
+            return None;
+        }
+
+        state.last_alert = Some(now);
+        let alert = DistractionAlert {
+            distraction_minutes: total / 60,
+            window_minutes: state.config.window_minutes,
+            apps: apps
+                .into_iter()
+                .take(MAX_ALERT_APPS)
+                .map(|(app, _)| app)
+                .collect(),
+        };
+        info!(
+            "最近 {} 分钟干扰 {} 分钟，发出分心提醒",
+            alert.window_minutes, alert.distraction_minutes
+        );
+        let _ = self.events.send(alert.clone());
+        Some(alert)
+    }
+}
+
+/// 是否处于配置的工作时段（工作日且在每日时段内）
+pub fn in_work_hours(config: &DistractionAlertConfig, now: DateTime<Utc>) -> bool {
+    let now = now.naive_utc();
+    let weekday = now.weekday().number_from_monday() as u8;
+    config.work_days.contains(&weekday)
+        && quiet::in_daily_range(&config.work_start, &config.work_end, now)
+}
+
+/// 各干扰应用的累计秒数（按时长降序）
+fn distraction_seconds(samples: &VecDeque<Sample>) -> Vec<(String, i64)> {
+    let mut apps: Vec<(String, i64)> = Vec::new();
+    for (sample, next) in samples.iter().zip(samples.iter().skip(1)) {
+        let Some(app) = &sample.distraction else {
+            continue;
+        };
+        let seconds = (next.timestamp - sample.timestamp)
+            .num_seconds()
+            .clamp(0, MAX_SAMPLE_SECONDS);
+        match apps.iter_mut().find(|(existing, _)| existing == app) {
+            Some((_, total)) => *total += seconds,
+            None => apps.push((app.clone(), seconds)),
+        }
+    }
+    apps.sort_by_key(|(_, seconds)| std::cmp::Reverse(*seconds));
+    apps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::window::WindowInfo;
+    use chrono::NaiveDate;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        // 2026-03-02 为周一
+        NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap()
+            .and_utc()
+            + Duration::minutes(minutes)
+    }
+
+    fn frame(timestamp: DateTime<Utc>, app: &str) -> ScreenFrame {
+        ScreenFrame {
+            timestamp,
+            file_path: String::new(),
+            screen_id: 0,
+            window: Some(WindowInfo {
+                app_name: app.to_string(),
+                ..Default::default()
+            }),
+            domain: None,
+            idle_seconds: None,
+            repeat: None,
+            meeting: None,
+            focus_block: None,
+        }
+    }
+
+    fn watcher() -> DistractionWatcher {
+        let watcher = DistractionWatcher::new();
+        watcher.update_config(DistractionAlertConfig {
+            enabled: true,
+            window_minutes: 30,
+            threshold_minutes: 10,
+            cooldown_minutes: 30,
+            ..DistractionAlertConfig::default()
+        });
+        watcher
+    }
+
+    #[test]
+    fn test_alert_after_threshold_with_cooldown() {
+        let watcher = watcher();
+        // 每分钟一帧：前 5 分钟工作，之后看视频
+        for minute in 0..5 {
+            assert!(watcher.observe_frame(&frame(at(minute), "Code")).is_none());
+        }
+        let mut fired = None;
+        for minute in 5..=20 {
+            if let Some(alert) = watcher.observe_frame(&frame(at(minute), "Netflix")) {
+                fired.get_or_insert((minute, alert));
+            }
+        }
+        let (minute, alert) = fired.unwrap();
+        assert_eq!(minute, 15);
+        assert_eq!(alert.distraction_minutes, 10);
+        assert_eq!(alert.apps, vec!["Netflix".to_string()]);
+
+        // 冷却期间不重复提醒，冷却结束后继续分心再次提醒
+        assert!(watcher.observe_frame(&frame(at(40), "Netflix")).is_none());
+        assert!(watcher.observe_frame(&frame(at(46), "Netflix")).is_some());
+    }
+
+    #[test]
+    fn test_no_alert_outside_work_hours_or_when_disabled() {
+        let watcher = watcher();
+        // 周六
+        let saturday = at(5 * 24 * 60);
+        for minute in 0..=20 {
+            let timestamp = saturday + Duration::minutes(minute);
+            assert!(watcher
+                .observe_frame(&frame(timestamp, "Netflix"))
+                .is_none());
+        }
+
+        watcher.update_config(DistractionAlertConfig::default());
+        for minute in 0..=20 {
+            assert!(watcher
+                .observe_frame(&frame(at(minute), "Netflix"))
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn test_long_gaps_are_capped() {
+        let samples: VecDeque<Sample> = [(0, true), (30, false)]
+            .into_iter()
+            .map(|(minute, distraction)| Sample {
+                timestamp: at(minute),
+                distraction: distraction.then(|| "Netflix".to_string()),
+            })
+            .collect();
+        assert_eq!(
+            distraction_seconds(&samples),
+            vec![("Netflix".to_string(), MAX_SAMPLE_SECONDS)]
+        );
+    }
+}
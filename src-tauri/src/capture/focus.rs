@@ -0,0 +1,426 @@
+// 专注时段（番茄钟）- 用户开始 25/50 分钟的专注时段后，计时期间截取的帧标记为该时段：
+// 标记按天写入帧目录下的 `focus_activity_<日期>.jsonl`（随帧文件按保留期清理），会话分析时填入帧；
+// 时段内按本地启发式分类判定为干扰类别的帧计入干扰记录，并按配置发出提醒（按冷却间隔限流）
+
+use super::{heuristic, ScreenFrame};
+use crate::models::FocusConfig;
+use crate::storage::FocusBlockRecord;
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tracing::info;
+
+/// 专注时段记录文件名前缀
+const FOCUS_LOG_PREFIX: &str = "focus_activity_";
+
+/// 专注时段可选时长（分钟）
+pub const FOCUS_BLOCK_PRESETS: [i64; 2] = [25, 50];
+
+/// 专注时段最长时长（分钟）
+pub const MAX_FOCUS_MINUTES: i64 = 240;
+
+/// 事件通道容量
+const EVENT_CAPACITY: usize = 16;
+
+/// 专注时段内的一次干扰
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Distraction {
+    pub timestamp: DateTime<Utc>,
+    /// 启发式分类得到的活动类别
+    pub category: String,
+    /// 前台应用
+    #[serde(default)]
+    pub app: Option<String>,
+}
+
+/// 进行中的专注时段
+#[derive(Debug, Clone)]
+struct ActiveBlock {
+    record: FocusBlockRecord,
+    config: FocusConfig,
+    distractions: Vec<Distraction>,
+    last_alert: Option<DateTime<Utc>>,
+}
+
+impl ActiveBlock {
+    fn id(&self) -> i64 {
+        self.record.id.unwrap_or_default()
+    }
+
+    fn planned_end(&self) -> DateTime<Utc> {
+        self.record.started_at + Duration::minutes(self.record.planned_minutes)
+    }
+
+    /// 结束时段，生成要写回数据库的记录
+    fn finish(self, status: &str, ended_at: DateTime<Utc>) -> FocusBlockRecord {
+        FocusBlockRecord {
+            ended_at: Some(ended_at.min(self.planned_end())),
+            status: status.to_string(),
+            distraction_count: self.distractions.len() as i64,
+            distractions: serde_json::to_string(&self.distractions)
+                .unwrap_or_else(|_| "[]".to_string()),
+            ..self.record
+        }
+    }
+}
+
+/// 当前专注时段状态（供前端显示倒计时）
+#[derive(Debug, Clone, Serialize)]
+pub struct FocusStatus {
+    pub id: i64,
+    pub label: String,
+    pub planned_minutes: i64,
+    pub started_at: String,
+    pub planned_end: String,
+    pub remaining_seconds: i64,
+    pub distraction_count: usize,
+}
+
+/// 专注计时器事件
+#[derive(Debug, Clone)]
+pub enum FocusEvent {
+    /// 时段内出现干扰类活动（已按配置与冷却间隔过滤，需提醒用户）
+    Distraction {
+        block_id: i64,
+        label: String,
+        distraction: Distraction,
+    },
+    /// 时段结束（计时完成或用户停止），记录需写回数据库
+    Ended(FocusBlockRecord),
+}
+
+/// 专注计时器（同一时间只有一个进行中的时段）
+pub struct FocusTimer {
+    active: Mutex<Option<ActiveBlock>>,
+    events: broadcast::Sender<FocusEvent>,
+}
+
+impl Default for FocusTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FocusTimer {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        Self {
+            active: Mutex::new(None),
+            events,
+        }
+    }
+
+    /// 订阅计时器事件
+    pub fn subscribe(&self) -> broadcast::Receiver<FocusEvent> {
+        self.events.subscribe()
+    }
+
+    /// 开始专注时段（记录需已写入数据库并带有 ID），到时自动结束
+    pub fn start(self: &Arc<Self>, record: FocusBlockRecord, config: FocusConfig) -> Result<()> {
+        let id = record
+            .id
+            .ok_or_else(|| anyhow::anyhow!("专注时段缺少 ID"))?;
+        let block = ActiveBlock {
+            record,
+            config,
+            distractions: Vec::new(),
+            last_alert: None,
+        };
+        let planned_end = block.planned_end();
+        {
+            let mut active = self.active.lock().unwrap();
+            if active.is_some() {
+                return Err(anyhow::anyhow!("已有进行中的专注时段"));
+            }
+            *active = Some(block);
+        }
+        info!("专注时段 #{} 开始，预计 {} 结束", id, planned_end);
+
+        let timer = Arc::clone(self);
+        tokio::spawn(async move {
+            let wait = (planned_end - crate::storage::local_now())
+                .to_std()
+                .unwrap_or_default();
+            tokio::time::sleep(wait).await;
+            timer.finish(id, "completed", planned_end);
+        });
+        Ok(())
+    }
+
+    /// 提前停止当前专注时段
+    pub fn stop(&self) -> Option<FocusBlockRecord> {
+        let id = self.active.lock().unwrap().as_ref()?.id();
+        self.finish(id, "cancelled", crate::storage::local_now())
+    }
+
+    /// 结束指定时段（已被停止或换成其他时段时忽略），并发出结束事件
+    fn finish(&self, id: i64, status: &str, ended_at: DateTime<Utc>) -> Option<FocusBlockRecord> {
+        let block = {
+            let mut active = self.active.lock().unwrap();
+            if active.as_ref().map(ActiveBlock::id) != Some(id) {
+                return None;
+            }
+            active.take()?
+        };
+        let record = block.finish(status, ended_at);
+        info!(
+            "专注时段 #{} 结束（{}），干扰 {} 次",
+            id, status, record.distraction_count
+        );
+        let _ = self.events.send(FocusEvent::Ended(record.clone()));
+        Some(record)
+    }
+
+    /// 当前专注时段状态
+    pub fn status(&self, now: DateTime<Utc>) -> Option<FocusStatus> {
+        let active = self.active.lock().unwrap();
+        let block = active.as_ref()?;
+        let planned_end = block.planned_end();
+        Some(FocusStatus {
+            id: block.id(),
+            label: block.record.label.clone(),
+            planned_minutes: block.record.planned_minutes,
+            started_at: block.record.started_at.naive_utc().to_string(),
+            planned_end: planned_end.naive_utc().to_string(),
+            remaining_seconds: (planned_end - now).num_seconds().max(0),
+            distraction_count: block.distractions.len(),
+        })
+    }
+
+    /// 记录一帧：返回所属专注时段 ID；干扰类别的帧计入干扰记录，需要提醒时发出事件
+    pub fn observe_frame(&self, frame: &ScreenFrame) -> Option<i64> {
+        let mut active = self.active.lock().unwrap();
+        let block = active.as_mut()?;
+        if frame.timestamp < block.record.started_at || frame.timestamp >= block.planned_end() {
+            return None;
+        }
+
+        let class = heuristic::classify_frame(frame, None);
+        if block
+            .config
+            .distraction_categories
+            .iter()
+            .any(|category| category == class.category)
+        {
+            let distraction = Distraction {
+                timestamp: frame.timestamp,
+                category: class.category.to_string(),
+                app: frame.window.as_ref().map(|w| w.app_name.clone()),
+            };
+            block.distractions.push(distraction.clone());
+
+            let cooldown = Duration::minutes(block.config.alert_cooldown_minutes as i64);
+            let cooled = block
+                .last_alert
+                .is_none_or(|last| frame.timestamp - last >= cooldown);
+            if block.config.notify_distractions && cooled {
+                block.last_alert = Some(frame.timestamp);
+                let _ = self.events.send(FocusEvent::Distraction {
+                    block_id: block.id(),
+                    label: block.record.label.clone(),
+                    distraction,
+                });
+            }
+        }
+
+        Some(block.id())
+    }
+}
+
+/// 新建专注时段记录（尚未写入数据库）
+pub fn new_block(label: &str, planned_minutes: i64, started_at: DateTime<Utc>) -> FocusBlockRecord {
+    FocusBlockRecord {
+        id: None,
+        label: label.trim().to_string(),
+        planned_minutes: planned_minutes.clamp(1, MAX_FOCUS_MINUTES),
+        started_at,
+        ended_at: None,
+        status: "running".to_string(),
+        distraction_count: 0,
+        distractions: "[]".to_string(),
+    }
+}
+
+/// 时段的结束时间（异常退出未写回结束时间时按计划时长计）
+pub fn block_end(block: &FocusBlockRecord) -> DateTime<Utc> {
+    block
+        .ended_at
+        .unwrap_or_else(|| block.started_at + Duration::minutes(block.planned_minutes))
+}
+
+/// 解析时段的干扰记录
+pub fn distractions(block: &FocusBlockRecord) -> Vec<Distraction> {
+    serde_json::from_str(&block.distractions).unwrap_or_default()
+}
+
+/// 专注时段记录文件中的一行
+#[derive(Debug, Serialize, Deserialize)]
+struct FocusFrameRecord {
+    timestamp_ms: i64,
+    block_id: i64,
+}
+
+/// 某天的专注时段记录文件
+fn focus_log_path(frames_dir: &Path, date: NaiveDate) -> PathBuf {
+    frames_dir.join(format!(
+        "{}{}.jsonl",
+        FOCUS_LOG_PREFIX,
+        date.format("%Y-%m-%d")
+    ))
+}
+
+/// 追加一帧的专注时段记录
+pub fn append_focus_record(
+    frames_dir: &Path,
+    timestamp: DateTime<Utc>,
+    block_id: i64,
+) -> Result<()> {
+    let record = FocusFrameRecord {
+        timestamp_ms: timestamp.timestamp_millis(),
+        block_id,
+    };
+    let mut line = serde_json::to_string(&record)?;
+    line.push('\n');
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(focus_log_path(frames_dir, timestamp.date_naive()))?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// 读取帧对应的专注时段记录并填入 `ScreenFrame::focus_block`（按毫秒时间戳精确匹配）
+pub async fn attach_focus_marks(frames_dir: &Path, frames: &mut [ScreenFrame]) {
+    let (Some(first), Some(last)) = (
+        frames.iter().map(|f| f.timestamp).min(),
+        frames.iter().map(|f| f.timestamp).max(),
+    ) else {
+        return;
+    };
+
+    let mut records: HashMap<i64, i64> = HashMap::new();
+    for date in first
+        .date_naive()
+        .iter_days()
+        .take_while(|date| *date <= last.date_naive())
+    {
+        let Ok(data) = tokio::fs::read_to_string(focus_log_path(frames_dir, date)).await else {
+            continue;
+        };
+        for line in data.lines() {
+            if let Ok(record) = serde_json::from_str::<FocusFrameRecord>(line) {
+                records.insert(record.timestamp_ms, record.block_id);
+            }
+        }
+    }
+
+    for frame in frames.iter_mut() {
+        if frame.focus_block.is_none() {
+            frame.focus_block = records.get(&frame.timestamp.timestamp_millis()).copied();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::window::WindowInfo;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_utc()
+            + Duration::minutes(minutes)
+    }
+
+    fn frame(minutes: i64, app: &str) -> ScreenFrame {
+        ScreenFrame {
+            timestamp: at(minutes),
+            file_path: format!("{}.jpg", minutes),
+            screen_id: 0,
+            window: Some(WindowInfo {
+                app_name: app.to_string(),
+                ..Default::default()
+            }),
+            domain: None,
+            idle_seconds: None,
+            repeat: None,
+            meeting: None,
+            focus_block: None,
+        }
+    }
+
+    fn running_timer(config: FocusConfig) -> FocusTimer {
+        let timer = FocusTimer::new();
+        let mut record = new_block("写周报", 25, at(0));
+        record.id = Some(7);
+        *timer.active.lock().unwrap() = Some(ActiveBlock {
+            record,
+            config,
+            distractions: Vec::new(),
+            last_alert: None,
+        });
+        timer
+    }
+
+    #[test]
+    fn test_observe_frame_tags_and_throttles_distractions() {
+        let timer = running_timer(FocusConfig {
+            distraction_categories: vec!["personal".to_string()],
+            alert_cooldown_minutes: 5,
+            ..FocusConfig::default()
+        });
+        let mut events = timer.subscribe();
+
+        assert_eq!(timer.observe_frame(&frame(1, "Code")), Some(7));
+        assert_eq!(timer.observe_frame(&frame(2, "Netflix")), Some(7));
+        assert_eq!(timer.observe_frame(&frame(4, "Netflix")), Some(7));
+        assert_eq!(timer.observe_frame(&frame(8, "Netflix")), Some(7));
+        // 计划结束之后的帧不属于该时段
+        assert_eq!(timer.observe_frame(&frame(30, "Code")), None);
+
+        // 三次干扰都记录，提醒按冷却间隔只发两次
+        let mut alerts = Vec::new();
+        while let Ok(FocusEvent::Distraction { distraction, .. }) = events.try_recv() {
+            alerts.push(distraction.timestamp);
+        }
+        assert_eq!(alerts, vec![at(2), at(8)]);
+
+        let record = timer.stop().unwrap();
+        assert_eq!(record.status, "cancelled");
+        assert_eq!(record.distraction_count, 3);
+        assert_eq!(distractions(&record)[0].app.as_deref(), Some("Netflix"));
+        assert!(matches!(events.try_recv(), Ok(FocusEvent::Ended(_))));
+        assert!(timer.status(at(10)).is_none());
+    }
+
+    #[test]
+    fn test_finish_ignores_stale_block_and_clamps_end() {
+        let timer = running_timer(FocusConfig::default());
+        assert!(timer.finish(8, "completed", at(25)).is_none());
+        assert_eq!(timer.status(at(10)).unwrap().remaining_seconds, 15 * 60);
+
+        let record = timer.finish(7, "completed", at(26)).unwrap();
+        assert_eq!(record.ended_at, Some(at(25)));
+        assert_eq!(block_end(&record), at(25));
+    }
+
+    #[tokio::test]
+    async fn test_focus_marks_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        append_focus_record(dir.path(), at(1), 7).unwrap();
+
+        let mut frames = vec![frame(1, "Code"), frame(2, "Code")];
+        attach_focus_marks(dir.path(), &mut frames).await;
+        assert_eq!(frames[0].focus_block, Some(7));
+        assert_eq!(frames[1].focus_block, None);
+    }
+}
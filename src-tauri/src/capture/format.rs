@@ -0,0 +1,146 @@
+// 截图帧格式 - 按配置将帧编码为 JPEG/WebP/AVIF，
+// 并将较旧的帧转码为压缩率更高的格式（由存储清理任务定期执行），降低长期磁盘占用
+
+use crate::frame_crypto;
+use crate::models::FrameFormat;
+use anyhow::Result;
+use image::codecs::jpeg::JpegEncoder;
+use image::DynamicImage;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// 帧文件可能的扩展名
+pub const FRAME_EXTENSIONS: [&str; 3] = ["jpg", "webp", "avif"];
+
+/// 当前构建实际可用的格式（未启用 avif 特性时回退为 JPEG）
+pub fn effective_format(format: FrameFormat) -> FrameFormat {
+    if format == FrameFormat::Avif && !cfg!(feature = "avif") {
+        return FrameFormat::Jpeg;
+    }
+    format
+}
+
+/// 格式对应的帧文件扩展名
+pub fn extension(format: FrameFormat) -> &'static str {
+    match format {
+        FrameFormat::Jpeg => "jpg",
+        FrameFormat::Webp => "webp",
+        FrameFormat::Avif => "avif",
+    }
+}
+
+/// 是否为帧文件扩展名（不区分大小写）
+pub fn is_frame_extension(extension: &str) -> bool {
+    FRAME_EXTENSIONS
+        .iter()
+        .any(|candidate| candidate.eq_ignore_ascii_case(extension))
+}
+
+/// 按格式与质量(1-100)编码图像并写入文件（启用截图加密时加密后写入）
+pub fn encode_frame(
+    image: &DynamicImage,
+    path: &Path,
+    format: FrameFormat,
+    quality: u8,
+) -> Result<()> {
+    let quality = quality.clamp(1, 100);
+    let mut encoded = Vec::new();
+    let writer = &mut encoded;
+
+    match effective_format(format) {
+        // image 0.24 将有损 WebP 标记为 deprecated，但仍是唯一的有损编码入口
+        #[allow(deprecated)]
+        FrameFormat::Webp => {
+            let quality = image::codecs::webp::WebPQuality::lossy(quality);
+            let encoder = image::codecs::webp::WebPEncoder::new_with_quality(writer, quality);
+            image.write_with_encoder(encoder)?;
+        }
+        #[cfg(feature = "avif")]
+        FrameFormat::Avif => {
+            let encoder =
+                image::codecs::avif::AvifEncoder::new_with_speed_quality(writer, 8, quality);
+            image.write_with_encoder(encoder)?;
+        }
+        _ => {
+            let mut encoder = JpegEncoder::new_with_quality(writer, quality);
+            encoder.encode(
+                image.as_bytes(),
+                image.width(),
+                image.height(),
+                image.color(),
+            )?;
+        }
+    }
+    std::fs::write(path, frame_crypto::seal(encoded)?)
+        .map_err(|e| anyhow::anyhow!("创建文件失败: {}", e))?;
+    Ok(())
+}
+
+/// 将帧文件转码为目标格式，返回新文件路径（原文件保留，由调用方更新记录后删除）。
+/// 已是目标格式或转码后体积没有变小时返回 None；新文件沿用原文件的修改时间，不影响按保留期清理
+pub fn recompress_frame(
+    source: &Path,
+    format: FrameFormat,
+    quality: u8,
+) -> Result<Option<PathBuf>> {
+    let format = effective_format(format);
+    let current = source
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    if current.eq_ignore_ascii_case(extension(format)) {
+        return Ok(None);
+    }
+
+    let metadata = std::fs::metadata(source)?;
+    let image = frame_crypto::open_image(source)?;
+    let target = source.with_extension(extension(format));
+    encode_frame(&image, &target, format, quality)?;
+
+    if std::fs::metadata(&target)?.len() >= metadata.len() {
+        std::fs::remove_file(&target)?;
+        return Ok(None);
+    }
+    File::options()
+        .write(true)
+        .open(&target)?
+        .set_modified(metadata.modified()?)?;
+    Ok(Some(target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    fn sample() -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(320, 180, |x, y| {
+            Rgb([(x % 256) as u8, (y % 256) as u8, ((x + y) % 256) as u8])
+        }))
+    }
+
+    #[test]
+    fn test_recompress_frame_to_webp() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("1700000000000_1.jpg");
+        encode_frame(&sample(), &source, FrameFormat::Jpeg, 95).unwrap();
+
+        let target = recompress_frame(&source, FrameFormat::Webp, 40)
+            .unwrap()
+            .unwrap();
+        assert_eq!(target, dir.path().join("1700000000000_1.webp"));
+        assert!(source.exists(), "原文件由调用方删除");
+        assert!(
+            std::fs::metadata(&target).unwrap().len() < std::fs::metadata(&source).unwrap().len()
+        );
+        assert!(std::fs::read(&target).unwrap().starts_with(b"RIFF"));
+
+        // 已是目标格式时不再转码
+        assert_eq!(
+            recompress_frame(&target, FrameFormat::Webp, 40).unwrap(),
+            None
+        );
+        assert!(is_frame_extension("WEBP"));
+        assert!(!is_frame_extension("png"));
+    }
+}
@@ -0,0 +1,562 @@
+// 本地启发式分类 - 未配置 AI 或 AI 调用失败（如断网）时，按前台应用名称、网站域名、
+// 窗口标题与 OCR 文字中的关键词为每帧打分归类，相邻的同类同应用帧合并为时间线卡片；
+// 生成的卡片标记为启发式结果，在数据库与导出中与 AI 分析结果区分
+
+use super::idle::{period_card, IdlePeriod};
+use super::ScreenFrame;
+use crate::llm::plugin::TimelineCard;
+use crate::ocr::FrameOcr;
+use chrono::{DateTime, Utc};
+
+/// 应用名称关键词（小写，按最长匹配归类）
+const APP_KEYWORDS: [(&str, &[&str]); 4] = [
+    (
+        "work",
+        &[
+            "code",
+            "cursor",
+            "intellij",
+            "pycharm",
+            "webstorm",
+            "goland",
+            "clion",
+            "rider",
+            "android studio",
+            "terminal",
+            "iterm",
+            "warp",
+            "powershell",
+            "sublime",
+            "vim",
+            "emacs",
+            "zed",
+            "excel",
+            "word",
+            "powerpoint",
+            "wps",
+            "figma",
+            "sketch",
+            "photoshop",
+            "postman",
+            "docker",
+            "dbeaver",
+            "navicat",
+            "notion",
+            "obsidian",
+        ],
+    ),
+    (
+        "communication",
+        &[
+            "slack",
+            "teams",
+            "zoom",
+            "wechat",
+            "微信",
+            "企业微信",
+            "wecom",
+            "qq",
+            "dingtalk",
+            "钉钉",
+            "feishu",
+            "飞书",
+            "lark",
+            "discord",
+            "telegram",
+            "whatsapp",
+            "skype",
+            "outlook",
+            "mail",
+            "邮件",
+            "thunderbird",
+        ],
+    ),
+    (
+        "learning",
+        &[
+            "anki", "kindle", "zotero", "mendeley", "calibre", "books", "duolingo",
+        ],
+    ),
+    (
+        "personal",
+        &[
+            "spotify",
+            "music",
+            "网易云音乐",
+            "qq音乐",
+            "qqmusic",
+            "steam",
+            "vlc",
+            "iina",
+            "potplayer",
+            "netflix",
+            "bilibili",
+            "哔哩哔哩",
+            "抖音",
+            "photos",
+        ],
+    ),
+];
+
+/// 网站域名关键词（小写，按最长匹配归类）
+const DOMAIN_KEYWORDS: [(&str, &[&str]); 4] = [
+    (
+        "work",
+        &[
+            "github.com",
+            "gitlab",
+            "bitbucket.org",
+            "atlassian.net",
+            "vercel.com",
+            "console.aws.amazon.com",
+            "console.cloud.google.com",
+            "portal.azure.com",
+            "figma.com",
+            "docs.google.com",
+            "notion.so",
+            "linear.app",
+            "sentry.io",
+            "localhost",
+        ],
+    ),
+    (
+        "communication",
+        &[
+            "mail.google.com",
+            "outlook.",
+            "slack.com",
+            "teams.microsoft.com",
+            "discord.com",
+            "web.whatsapp.com",
+            "web.telegram.org",
+            "feishu.cn",
+            "dingtalk.com",
+            "zoom.us",
+            "meet.google.com",
+        ],
+    ),
+    (
+        "learning",
+        &[
+            "stackoverflow.com",
+            "developer.mozilla.org",
+            "docs.rs",
+            "wikipedia.org",
+            "coursera.org",
+            "udemy.com",
+            "edx.org",
+            "leetcode",
+            "arxiv.org",
+            "readthedocs",
+            "khanacademy.org",
+            "juejin.cn",
+            "csdn.net",
+        ],
+    ),
+    (
+        "personal",
+        &[
+            "youtube.com",
+            "bilibili.com",
+            "netflix.com",
+            "twitch.tv",
+            "twitter.com",
+            "weibo.com",
+            "reddit.com",
+            "instagram.com",
+            "facebook.com",
+            "tiktok.com",
+            "douyin.com",
+            "xiaohongshu.com",
+            "taobao.com",
+            "jd.com",
+        ],
+    ),
+];
+
+/// 窗口标题与 OCR 文字关键词（小写）
+const TEXT_KEYWORDS: [(&str, &[&str]); 4] = [
+    (
+        "work",
+        &[
+            "pull request",
+            "merge request",
+            "commit",
+            "deploy",
+            "debug",
+            "cargo",
+            "npm",
+            "代码",
+            "需求",
+            "部署",
+            "测试",
+            "报表",
+        ],
+    ),
+    (
+        "communication",
+        &[
+            "inbox",
+            "收件箱",
+            "reply",
+            "回复",
+            "meeting",
+            "会议",
+            "聊天",
+            "消息",
+        ],
+    ),
+    (
+        "learning",
+        &[
+            "tutorial",
+            "教程",
+            "course",
+            "课程",
+            "documentation",
+            "lecture",
+            "学习",
+            "论文",
+        ],
+    ),
+    (
+        "personal",
+        &[
+            "video", "视频", "music", "音乐", "game", "游戏", "购物", "直播", "电影",
+        ],
+    ),
+];
+
+/// 应用名称或域名命中时的得分
+const SOURCE_SCORE: f32 = 3.0;
+
+/// 窗口标题每命中一个关键词的得分
+const TITLE_SCORE: f32 = 1.0;
+
+/// OCR 文字每命中一个关键词的得分及上限（屏幕文字噪声较大）
+const OCR_SCORE: f32 = 0.5;
+const OCR_SCORE_CAP: f32 = 2.0;
+
+/// OCR 文字对其后多少秒内的帧有效
+const OCR_VALID_SECONDS: i64 = 300;
+
+/// 短于该秒数的时段并入前一时段，避免卡片碎片化
+const MIN_PERIOD_SECONDS: i64 = 60;
+
+/// 未命中任何关键词时的置信度
+const UNKNOWN_CONFIDENCE: f32 = 0.2;
+
+/// 单帧的分类结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameClass {
+    pub category: &'static str,
+    /// 粗略置信度（0-1）
+    pub confidence: f32,
+}
+
+/// 一段连续归为同一类别、同一应用的时段
+#[derive(Debug, Clone, PartialEq)]
+struct HeuristicPeriod {
+    period: IdlePeriod,
+    category: &'static str,
+    label: String,
+    /// 按时长加权的置信度之和
+    weighted_confidence: f64,
+    seconds: i64,
+}
+
+/// 最长命中的关键词所属类别
+fn longest_match(value: &str, table: &[(&'static str, &[&str])]) -> Option<&'static str> {
+    table
+        .iter()
+        .flat_map(|(category, keywords)| keywords.iter().map(move |keyword| (*category, keyword)))
+        .filter(|(_, keyword)| value.contains(*keyword))
+        .max_by_key(|(_, keyword)| keyword.chars().count())
+        .map(|(category, _)| category)
+}
+
+/// 各类别命中的关键词数
+fn keyword_hits(text: &str, table: &[(&'static str, &[&str])]) -> Vec<(&'static str, usize)> {
+    table
+        .iter()
+        .map(|(category, keywords)| {
+            let hits = keywords
+                .iter()
+                .filter(|keyword| text.contains(**keyword))
+                .count();
+            (*category, hits)
+        })
+        .filter(|(_, hits)| *hits > 0)
+        .collect()
+}
+
+/// 按应用名称、域名、窗口标题与 OCR 文字为单帧归类
+pub fn classify_frame(frame: &ScreenFrame, ocr_text: Option<&str>) -> FrameClass {
+    let mut scores: Vec<(&'static str, f32)> = Vec::new();
+    let mut add = |category: &'static str, score: f32| match scores
+        .iter_mut()
+        .find(|(existing, _)| *existing == category)
+    {
+        Some((_, total)) => *total += score,
+        None => scores.push((category, score)),
+    };
+
+    if let Some(window) = &frame.window {
+        if let Some(category) = longest_match(&window.app_name.to_lowercase(), &APP_KEYWORDS) {
+            add(category, SOURCE_SCORE);
+        }
+        for (category, hits) in keyword_hits(&window.window_title.to_lowercase(), &TEXT_KEYWORDS) {
+            add(category, hits as f32 * TITLE_SCORE);
+        }
+    }
+    if let Some(domain) = frame.domain.as_deref().filter(|domain| !domain.is_empty()) {
+        if let Some(category) = longest_match(&domain.to_lowercase(), &DOMAIN_KEYWORDS) {
+            add(category, SOURCE_SCORE);
+        }
+    }
+    if let Some(text) = ocr_text {
+        for (category, hits) in keyword_hits(&text.to_lowercase(), &TEXT_KEYWORDS) {
+            add(category, (hits as f32 * OCR_SCORE).min(OCR_SCORE_CAP));
+        }
+    }
+
+    let total: f32 = scores.iter().map(|(_, score)| score).sum();
+    // 同分时取先出现的类别（应用名称优先于域名与文字）
+    let best = scores.iter().fold(
+        None::<(&'static str, f32)>,
+        |best, &(category, score)| match best {
+            Some((_, top)) if top >= score => best,
+            _ => Some((category, score)),
+        },
+    );
+    match best {
+        Some((category, score)) if total > 0.0 => FrameClass {
+            category,
+            // 领先幅度越大、证据越多越可信，最高 0.9
+            confidence: ((score / total) * (score / (score + 1.5))).min(0.9),
+        },
+        _ => FrameClass {
+            category: "other",
+            confidence: UNKNOWN_CONFIDENCE,
+        },
+    }
+}
+
+/// 帧的应用或网站名称（用作卡片标题）
+fn frame_label(frame: &ScreenFrame) -> String {
+    frame
+        .domain
+        .clone()
+        .filter(|domain| !domain.is_empty())
+        .or_else(|| {
+            frame
+                .window
+                .as_ref()
+                .map(|window| window.app_name.clone())
+                .filter(|app| !app.is_empty())
+        })
+        .unwrap_or_else(|| "未知应用".to_string())
+}
+
+/// 帧之前最近一次的同屏 OCR 文字
+fn frame_ocr<'a>(frame: &ScreenFrame, texts: &'a [FrameOcr]) -> Option<&'a str> {
+    texts
+        .iter()
+        .filter(|ocr| {
+            ocr.screen_id == frame.screen_id
+                && ocr.timestamp <= frame.timestamp
+                && (frame.timestamp - ocr.timestamp).num_seconds() <= OCR_VALID_SECONDS
+        })
+        .max_by_key(|ocr| ocr.timestamp)
+        .map(|ocr| ocr.text.as_str())
+}
+
+/// 按帧分类结果计算时段（空闲与会议帧跳过，由后续检测覆盖）
+fn heuristic_periods(
+    frames: &[ScreenFrame],
+    ocr_texts: &[FrameOcr],
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+) -> Vec<HeuristicPeriod> {
+    let mut periods: Vec<HeuristicPeriod> = Vec::new();
+    for (frame, seconds) in super::window::frame_durations(frames, window_end) {
+        if frame.idle_seconds.is_some() || frame.meeting.is_some() {
+            continue;
+        }
+        let start = frame.timestamp.max(window_start);
+        let end = (frame.timestamp + chrono::Duration::seconds(seconds)).min(window_end);
+        if end <= start {
+            continue;
+        }
+        let class = classify_frame(frame, frame_ocr(frame, ocr_texts));
+        let label = frame_label(frame);
+        let seconds = (end - start).num_seconds();
+
+        match periods.last_mut() {
+            Some(last)
+                if last.category == class.category
+                    && last.label == label
+                    && start <= last.period.end =>
+            {
+                last.period.end = last.period.end.max(end);
+                last.weighted_confidence += class.confidence as f64 * seconds as f64;
+                last.seconds += seconds;
+            }
+            _ => periods.push(HeuristicPeriod {
+                period: IdlePeriod { start, end },
+                category: class.category,
+                label,
+                weighted_confidence: class.confidence as f64 * seconds as f64,
+                seconds,
+            }),
+        }
+    }
+
+    // 过短的时段并入前一个相邻时段
+    let mut merged: Vec<HeuristicPeriod> = Vec::new();
+    for period in periods {
+        match merged.last_mut() {
+            Some(last)
+                if (period.period.end - period.period.start).num_seconds() < MIN_PERIOD_SECONDS
+                    && period.period.start <= last.period.end =>
+            {
+                last.period.end = last.period.end.max(period.period.end);
+            }
+            _ => merged.push(period),
+        }
+    }
+    merged
+}
+
+/// 按本地启发式分类生成时间线卡片（时间为绝对时间）
+pub fn heuristic_cards(
+    frames: &[ScreenFrame],
+    ocr_texts: &[FrameOcr],
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+) -> Vec<TimelineCard> {
+    heuristic_periods(frames, ocr_texts, window_start, window_end)
+        .into_iter()
+        .map(|period| {
+            let minutes = (period.period.end - period.period.start).num_minutes();
+            let mut card = period_card(
+                &period.period,
+                period.category,
+                "",
+                &period.label,
+                format!("使用 {} {} 分钟", period.label, minutes),
+                "该时段由本地启发式分类生成（按应用名称、网站、窗口标题与屏幕文字关键词归类），未进行 AI 分析",
+            );
+            card.app_sites.primary = period.label;
+            card.confidence = Some((period.weighted_confidence / period.seconds.max(1) as f64) as f32);
+            card.heuristic = true;
+            card
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::window::WindowInfo;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        chrono::NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_utc()
+            + chrono::Duration::minutes(minutes)
+    }
+
+    fn frame(minutes: i64, app: &str, title: &str, domain: Option<&str>) -> ScreenFrame {
+        let timestamp = at(minutes);
+        ScreenFrame {
+            timestamp,
+            file_path: format!("{}.jpg", timestamp.timestamp_millis()),
+            screen_id: 0,
+            window: Some(WindowInfo {
+                app_name: app.to_string(),
+                window_title: title.to_string(),
+                process_path: None,
+            }),
+            domain: domain.map(str::to_string),
+            idle_seconds: None,
+            repeat: None,
+            meeting: None,
+            focus_block: None,
+        }
+    }
+
+    #[test]
+    fn test_classify_frame() {
+        let class = classify_frame(&frame(0, "Code", "main.rs - screen-analyzer", None), None);
+        assert_eq!(class.category, "work");
+        assert!(class.confidence > 0.5);
+
+        // 最长匹配：QQ音乐不会被当作 QQ 聊天
+        assert_eq!(
+            classify_frame(&frame(0, "QQ音乐", "", None), None).category,
+            "personal"
+        );
+
+        // 浏览器按域名归类，标题关键词辅助
+        let class = classify_frame(
+            &frame(
+                0,
+                "Google Chrome",
+                "Rust 教程 - YouTube",
+                Some("www.youtube.com"),
+            ),
+            None,
+        );
+        assert_eq!(class.category, "personal");
+
+        // 只有屏幕文字时置信度较低
+        let class = classify_frame(
+            &frame(0, "Preview", "", None),
+            Some("Lecture 3: Course overview"),
+        );
+        assert_eq!(class.category, "learning");
+        assert!(class.confidence < 0.5);
+
+        let unknown = classify_frame(&frame(0, "Finder", "", None), None);
+        assert_eq!(unknown.category, "other");
+        assert_eq!(unknown.confidence, UNKNOWN_CONFIDENCE);
+    }
+
+    #[test]
+    fn test_heuristic_cards() {
+        let mut frames: Vec<ScreenFrame> =
+            (0..5).map(|m| frame(m, "Code", "lib.rs", None)).collect();
+        frames.extend((5..10).map(|m| frame(m, "Slack", "general", None)));
+        frames.extend((10..15).map(|m| frame(m, "Google Chrome", "", Some("github.com"))));
+        // 不足 1 分钟的切换并入前一时段
+        let mut finder = frame(14, "Finder", "", None);
+        finder.timestamp += chrono::Duration::seconds(30);
+        frames.push(finder);
+        let ocr = vec![FrameOcr {
+            timestamp: at(5),
+            screen_id: 0,
+            text: "回复 消息".to_string(),
+        }];
+
+        let cards = heuristic_cards(&frames, &ocr, at(0), at(15));
+        let summary: Vec<(&str, &str, bool)> = cards
+            .iter()
+            .map(|card| (card.category.as_str(), card.title.as_str(), card.heuristic))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("work", "Code", true),
+                ("communication", "Slack", true),
+                ("work", "github.com", true),
+            ]
+        );
+        assert_eq!(cards[0].app_sites.primary, "Code");
+        assert_eq!(cards[2].summary, "使用 github.com 5 分钟");
+        // OCR 文字提高了聊天时段的置信度
+        assert!(cards[1].confidence.unwrap() > cards[0].confidence.unwrap());
+    }
+}
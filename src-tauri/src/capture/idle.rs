@@ -0,0 +1,508 @@
+// 空闲检测 - 截屏时通过平台接口读取距最后一次键鼠输入的时长，
+// 超过阈值的帧按天写入帧目录下的 `idle_activity_<日期>.jsonl`（随帧文件按保留期清理），
+// 会话分析时据此把离开的时段替换为空闲卡片，长时间离开时拆分会话
+
+use super::scheduler::SessionWindow;
+use super::ScreenFrame;
+use crate::llm::plugin::{AppSites, TimelineCard};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 空闲记录文件名前缀
+const IDLE_LOG_PREFIX: &str = "idle_activity_";
+
+/// 扣除空闲时段后，短于该秒数的卡片片段直接丢弃
+const MIN_CARD_SECONDS: i64 = 60;
+
+/// 空闲记录文件中的一行
+#[derive(Debug, Serialize, Deserialize)]
+struct IdleRecord {
+    timestamp_ms: i64,
+    idle_seconds: u64,
+}
+
+/// 一段连续无输入（或会议中）的时段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdlePeriod {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+/// 距最后一次键盘或鼠标输入的秒数（阻塞调用，不支持的平台或获取失败时返回 None）
+#[cfg(windows)]
+pub fn idle_seconds() -> Option<u64> {
+    #[repr(C)]
+    struct LastInputInfo {
+        cb_size: u32,
+        dw_time: u32,
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetLastInputInfo(info: *mut LastInputInfo) -> i32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetTickCount() -> u32;
+    }
+
+    let mut info = LastInputInfo {
+        cb_size: std::mem::size_of::<LastInputInfo>() as u32,
+        dw_time: 0,
+    };
+    // SAFETY: 结构体布局与 LASTINPUTINFO 一致，cb_size 已按要求设置
+    unsafe {
+        if GetLastInputInfo(&mut info) == 0 {
+            return None;
+        }
+        // 两者均为开机以来的毫秒数，约 49.7 天回绕一次
+        Some(u64::from(GetTickCount().wrapping_sub(info.dw_time)) / 1000)
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn idle_seconds() -> Option<u64> {
+    // kCGEventSourceStateCombinedSessionState
+    const COMBINED_SESSION_STATE: i32 = 0;
+    // kCGAnyInputEventType
+    const ANY_INPUT_EVENT: u32 = u32::MAX;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventSourceSecondsSinceLastEventType(state_id: i32, event_type: u32) -> f64;
+    }
+
+    // SAFETY: 只读查询，不涉及指针参数
+    let seconds =
+        unsafe { CGEventSourceSecondsSinceLastEventType(COMBINED_SESSION_STATE, ANY_INPUT_EVENT) };
+    (seconds.is_finite() && seconds >= 0.0).then_some(seconds as u64)
+}
+
+#[cfg(target_os = "linux")]
+pub fn idle_seconds() -> Option<u64> {
+    // 依赖 xprintidle（X11，输出毫秒）；Wayland 或未安装时返回 None
+    let output = std::process::Command::new("xprintidle").output().ok()?;
+    if !output.status.success() {
+        tracing::trace!(
+            "获取空闲时长失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return None;
+    }
+
+    let millis: u64 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    Some(millis / 1000)
+}
+
+#[cfg(not(any(target_os = "macos", windows, target_os = "linux")))]
+pub fn idle_seconds() -> Option<u64> {
+    None
+}
+
+/// 某天的空闲记录文件
+fn idle_log_path(frames_dir: &Path, date: NaiveDate) -> PathBuf {
+    frames_dir.join(format!(
+        "{}{}.jsonl",
+        IDLE_LOG_PREFIX,
+        date.format("%Y-%m-%d")
+    ))
+}
+
+/// 追加一帧的空闲记录
+pub fn append_idle_record(
+    frames_dir: &Path,
+    timestamp: DateTime<Utc>,
+    idle_seconds: u64,
+) -> Result<()> {
+    let record = IdleRecord {
+        timestamp_ms: timestamp.timestamp_millis(),
+        idle_seconds,
+    };
+    let mut line = serde_json::to_string(&record)?;
+    line.push('\n');
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(idle_log_path(frames_dir, timestamp.date_naive()))?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// 读取帧对应的空闲记录并填入 `ScreenFrame::idle_seconds`（按毫秒时间戳精确匹配）
+pub async fn attach_idle_marks(frames_dir: &Path, frames: &mut [ScreenFrame]) {
+    let (Some(first), Some(last)) = (
+        frames.iter().map(|f| f.timestamp).min(),
+        frames.iter().map(|f| f.timestamp).max(),
+    ) else {
+        return;
+    };
+
+    let mut records: HashMap<i64, u64> = HashMap::new();
+    for date in first
+        .date_naive()
+        .iter_days()
+        .take_while(|date| *date <= last.date_naive())
+    {
+        let Ok(data) = tokio::fs::read_to_string(idle_log_path(frames_dir, date)).await else {
+            continue;
+        };
+        for line in data.lines() {
+            if let Ok(record) = serde_json::from_str::<IdleRecord>(line) {
+                records.insert(record.timestamp_ms, record.idle_seconds);
+            }
+        }
+    }
+
+    for frame in frames.iter_mut() {
+        if frame.idle_seconds.is_none() {
+            frame.idle_seconds = records.get(&frame.timestamp.timestamp_millis()).copied();
+        }
+    }
+}
+
+/// 按空闲帧计算会话内的离开时段：从最后一次输入起，到最后一个空闲帧结束（见 `frame_durations`）
+pub fn idle_periods(
+    frames: &[ScreenFrame],
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+) -> Vec<IdlePeriod> {
+    let mut periods: Vec<IdlePeriod> = Vec::new();
+    for (frame, seconds) in super::window::frame_durations(frames, window_end) {
+        let Some(idle) = frame.idle_seconds else {
+            continue;
+        };
+        let start = (frame.timestamp - Duration::seconds(idle as i64)).max(window_start);
+        let end = (frame.timestamp + Duration::seconds(seconds)).min(window_end);
+        if end <= start {
+            continue;
+        }
+
+        match periods.last_mut() {
+            Some(last) if start <= last.end => {
+                last.start = last.start.min(start);
+                last.end = last.end.max(end);
+            }
+            _ => periods.push(IdlePeriod { start, end }),
+        }
+    }
+    periods
+}
+
+/// 从时间线卡片中扣除离开时段，并为每个时段插入一张空闲卡片（卡片时间为 RFC3339）
+pub fn mark_idle_cards(cards: Vec<TimelineCard>, periods: &[IdlePeriod]) -> Vec<TimelineCard> {
+    let replacements = periods
+        .iter()
+        .map(|period| {
+            let minutes = (period.end - period.start).num_minutes();
+            let card = (minutes >= 1).then(|| {
+                period_card(
+                    period,
+                    "idle",
+                    "away",
+                    "离开",
+                    format!("无键鼠输入 {} 分钟", minutes),
+                    "该时段未检测到键盘或鼠标输入，判定为离开电脑",
+                )
+            });
+            (*period, card)
+        })
+        .collect();
+    replace_periods(cards, replacements)
+}
+
+/// 时段对应的卡片
+pub fn period_card(
+    period: &IdlePeriod,
+    category: &str,
+    subcategory: &str,
+    title: &str,
+    summary: String,
+    detailed_summary: &str,
+) -> TimelineCard {
+    TimelineCard {
+        start_time: format_card_time(period.start),
+        end_time: format_card_time(period.end),
+        category: category.to_string(),
+        subcategory: subcategory.to_string(),
+        title: title.to_string(),
+        summary,
+        detailed_summary: detailed_summary.to_string(),
+        distractions: None,
+        app_sites: AppSites {
+            primary: String::new(),
+            secondary: None,
+        },
+        confidence: None,
+        heuristic: false,
+        video_preview_path: None,
+    }
+}
+
+/// 从时间线卡片中扣除给定时段，并插入各时段的卡片（为空时只扣除）
+pub fn replace_periods(
+    cards: Vec<TimelineCard>,
+    replacements: Vec<(IdlePeriod, Option<TimelineCard>)>,
+) -> Vec<TimelineCard> {
+    if replacements.is_empty() {
+        return cards;
+    }
+    let periods: Vec<IdlePeriod> = replacements.iter().map(|(period, _)| *period).collect();
+
+    let mut marked: Vec<(DateTime<Utc>, TimelineCard)> = Vec::new();
+    for card in cards {
+        let (Some(start), Some(end)) = (
+            parse_card_time(&card.start_time),
+            parse_card_time(&card.end_time),
+        ) else {
+            marked.push((DateTime::<Utc>::MIN_UTC, card));
+            continue;
+        };
+
+        let mut pieces = vec![(start, end)];
+        for period in &periods {
+            pieces = pieces
+                .into_iter()
+                .flat_map(|(s, e)| {
+                    if period.end <= s || period.start >= e {
+                        return vec![(s, e)];
+                    }
+                    let mut rest = Vec::new();
+                    if period.start > s {
+                        rest.push((s, period.start));
+                    }
+                    if period.end < e {
+                        rest.push((period.end, e));
+                    }
+                    rest
+                })
+                .collect();
+        }
+
+        if pieces == [(start, end)] {
+            marked.push((start, card));
+            continue;
+        }
+        for (s, e) in pieces {
+            if (e - s).num_seconds() < MIN_CARD_SECONDS {
+                continue;
+            }
+            let mut piece = card.clone();
+            piece.start_time = format_card_time(s);
+            piece.end_time = format_card_time(e);
+            marked.push((s, piece));
+        }
+    }
+
+    for (period, card) in replacements {
+        if let Some(card) = card {
+            marked.push((period.start, card));
+        }
+    }
+
+    marked.sort_by_key(|(start, _)| *start);
+    marked.into_iter().map(|(_, card)| card).collect()
+}
+
+/// 按长时间离开拆分会话时间窗：离开超过 `pause_seconds` 时结束当前会话，
+/// 恢复输入后的第一帧开始新会话；没有活跃帧的时间窗不再分析
+pub fn active_windows(
+    frames: &[ScreenFrame],
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+    pause_seconds: u64,
+) -> Vec<SessionWindow> {
+    let mut sorted: Vec<&ScreenFrame> = frames.iter().collect();
+    sorted.sort_by_key(|frame| frame.timestamp);
+
+    let has_active = |start: DateTime<Utc>, end: DateTime<Utc>| {
+        sorted.iter().any(|frame| {
+            frame.idle_seconds.is_none() && frame.timestamp >= start && frame.timestamp < end
+        })
+    };
+
+    let pause = Duration::seconds(pause_seconds as i64);
+    let mut windows = Vec::new();
+    let mut start = window_start;
+    for (index, frame) in sorted.iter().enumerate() {
+        let Some(idle) = frame.idle_seconds else {
+            continue;
+        };
+        let away_start = frame.timestamp - Duration::seconds(idle as i64);
+        let next = sorted
+            .get(index + 1)
+            .map(|next| next.timestamp)
+            .unwrap_or(window_end);
+        if next - away_start < pause {
+            continue;
+        }
+
+        let end = away_start.max(start);
+        if has_active(start, end) {
+            windows.push(SessionWindow {
+                start,
+                end,
+                title: None,
+            });
+        }
+        start = next;
+    }
+
+    if start < window_end && has_active(start, window_end) {
+        windows.push(SessionWindow {
+            start,
+            end: window_end,
+            title: None,
+        });
+    }
+    windows
+}
+
+/// 解析卡片时间（带时区的 RFC3339），转回与截屏时间戳一致的本地时间值
+fn parse_card_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|time| time.naive_local().and_utc())
+}
+
+/// 将本地时间值格式化为带时区标记的 RFC3339
+fn format_card_time(time: DateTime<Utc>) -> String {
+    Local
+        .from_local_datetime(&time.naive_local())
+        .earliest()
+        .map(|local| local.to_rfc3339())
+        .unwrap_or_else(|| time.to_rfc3339())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start() -> DateTime<Utc> {
+        chrono::NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_utc()
+    }
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        start() + Duration::minutes(minutes)
+    }
+
+    fn frame(minutes: i64, idle_minutes: Option<u64>) -> ScreenFrame {
+        let timestamp = at(minutes);
+        ScreenFrame {
+            timestamp,
+            file_path: format!("{}.jpg", timestamp.timestamp_millis()),
+            screen_id: 0,
+            window: None,
+            domain: None,
+            idle_seconds: idle_minutes.map(|minutes| minutes * 60),
+            repeat: None,
+            meeting: None,
+            focus_block: None,
+        }
+    }
+
+    fn card(start: i64, end: i64, title: &str) -> TimelineCard {
+        TimelineCard {
+            start_time: format_card_time(at(start)),
+            end_time: format_card_time(at(end)),
+            category: "work".to_string(),
+            subcategory: "coding".to_string(),
+            title: title.to_string(),
+            summary: String::new(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: AppSites {
+                primary: "Code".to_string(),
+                secondary: None,
+            },
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+        }
+    }
+
+    fn span(card: &TimelineCard) -> (DateTime<Utc>, DateTime<Utc>) {
+        (
+            parse_card_time(&card.start_time).unwrap(),
+            parse_card_time(&card.end_time).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_idle_periods_and_cards() {
+        // 每分钟一帧；3 分钟后停止输入，8、9、10 分钟的帧超过 5 分钟阈值
+        let mut frames: Vec<ScreenFrame> = (0..8).map(|m| frame(m, None)).collect();
+        frames.extend([frame(8, Some(5)), frame(9, Some(6)), frame(10, Some(7))]);
+        frames.push(frame(11, None));
+
+        let periods = idle_periods(&frames, at(0), at(15));
+        assert_eq!(
+            periods,
+            vec![IdlePeriod {
+                start: at(3),
+                end: at(11)
+            }]
+        );
+
+        let cards = mark_idle_cards(
+            vec![card(0, 10, "编写代码"), card(10, 15, "代码评审")],
+            &periods,
+        );
+        let titles: Vec<&str> = cards.iter().map(|card| card.title.as_str()).collect();
+        assert_eq!(titles, vec!["编写代码", "离开", "代码评审"]);
+        assert_eq!(span(&cards[0]), (at(0), at(3)));
+        assert_eq!(span(&cards[1]), (at(3), at(11)));
+        assert_eq!(cards[1].category, "idle");
+        assert_eq!(cards[1].summary, "无键鼠输入 8 分钟");
+        assert_eq!(span(&cards[2]), (at(11), at(15)));
+
+        // 没有空闲时段时卡片保持原样
+        let untouched = mark_idle_cards(vec![card(0, 15, "编写代码")], &[]);
+        assert_eq!(untouched[0].start_time, format_card_time(at(0)));
+    }
+
+    #[test]
+    fn test_active_windows_split_on_long_idle() {
+        // 2 分钟后离开，7~16 分钟为空闲帧，17 分钟起暂停截屏，25 分钟恢复
+        let mut frames: Vec<ScreenFrame> = (0..7).map(|m| frame(m, None)).collect();
+        frames.extend((7..17).map(|m| frame(m, Some(m as u64 - 2))));
+        frames.extend((25..30).map(|m| frame(m, None)));
+
+        let windows = active_windows(&frames, at(0), at(30), 15 * 60);
+        assert_eq!(windows.len(), 2);
+        assert_eq!((windows[0].start, windows[0].end), (at(0), at(2)));
+        assert_eq!((windows[1].start, windows[1].end), (at(25), at(30)));
+
+        // 离开未达到暂停时长时不拆分
+        let windows = active_windows(&frames[..12], at(0), at(15), 15 * 60);
+        assert_eq!(windows.len(), 1);
+        assert_eq!((windows[0].start, windows[0].end), (at(0), at(15)));
+
+        // 整个时间窗都处于离开状态时不产生会话
+        let idle_only: Vec<ScreenFrame> = (0..5).map(|m| frame(m, Some(20))).collect();
+        assert!(active_windows(&idle_only, at(0), at(15), 15 * 60).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_idle_log_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        append_idle_record(dir.path(), at(8), 300).unwrap();
+
+        let mut frames = vec![frame(8, None), frame(9, None)];
+        attach_idle_marks(dir.path(), &mut frames).await;
+        assert_eq!(frames[0].idle_seconds, Some(300));
+        assert_eq!(frames[1].idle_seconds, None);
+    }
+}
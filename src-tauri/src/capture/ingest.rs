@@ -0,0 +1,282 @@
+// 外部截图导入 - 接收其他录屏工具（如 Screenpipe、Rewind）采集的帧，
+// 统一转码为帧目录下的 `<毫秒时间戳>.jpg`，由调度器按时间窗归入会话并走常规分析流程
+
+use super::ScreenCapture;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use tracing::{info, warn};
+
+/// 外部帧来源索引文件（位于帧目录，记录原始路径与元数据）
+pub const EXTERNAL_INDEX_FILE: &str = "external_frames.jsonl";
+
+/// 报告中最多保留的错误条数
+const MAX_REPORTED_ERRORS: usize = 5;
+
+/// 不带时区的时间格式（按本地时间处理）
+const TIME_FORMATS: [&str; 3] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+];
+
+/// 外部采集的一帧
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalFrame {
+    /// 图片路径（清单导入时可相对于清单文件）
+    pub path: String,
+    /// 采集时间：RFC3339、本地时间字符串或 Unix 时间戳（秒/毫秒）
+    pub timestamp: String,
+    /// 来源工具附带的元数据（应用名、窗口标题等），原样写入来源索引
+    #[serde(default)]
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+/// 来源索引中的一条记录
+#[derive(Debug, Serialize)]
+struct ExternalFrameIndexEntry<'a> {
+    timestamp_ms: i64,
+    source_path: &'a str,
+    metadata: &'a serde_json::Map<String, serde_json::Value>,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IngestReport {
+    pub received: usize,
+    pub ingested: usize,
+    /// 同一时间戳已有帧而跳过
+    pub duplicates: usize,
+    pub failed: usize,
+    pub errors: Vec<String>,
+    pub first_timestamp: Option<String>,
+    pub last_timestamp: Option<String>,
+}
+
+impl IngestReport {
+    /// 渲染提示信息
+    pub fn render_message(&self) -> String {
+        let mut message = match (&self.first_timestamp, &self.last_timestamp) {
+            (Some(first), Some(last)) if self.ingested > 0 => {
+                format!("已导入 {} 帧: {} ~ {}", self.ingested, first, last)
+            }
+            _ => "没有导入新的帧".to_string(),
+        };
+        if self.duplicates > 0 {
+            message.push_str(&format!("，跳过 {} 个重复帧", self.duplicates));
+        }
+        if self.failed > 0 {
+            message.push_str(&format!("，{} 帧失败", self.failed));
+        }
+        message
+    }
+
+    fn record_error(&mut self, error: String) {
+        warn!("外部帧导入失败: {}", error);
+        self.failed += 1;
+        if self.errors.len() < MAX_REPORTED_ERRORS {
+            self.errors.push(error);
+        }
+    }
+}
+
+/// 解析采集时间，返回与实时截屏一致的本地墙钟时间
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+
+    if value.bytes().all(|b| b.is_ascii_digit()) {
+        let raw: i64 = value.parse().ok()?;
+        // 10 位及以下视为秒级时间戳
+        let millis = if value.len() <= 10 { raw * 1000 } else { raw };
+        let instant = Utc.timestamp_millis_opt(millis).single()?;
+        return Some(instant.with_timezone(&Local).naive_local().and_utc());
+    }
+
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(value) {
+        return Some(parsed.with_timezone(&Local).naive_local().and_utc());
+    }
+
+    TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|naive| naive.and_utc())
+}
+
+/// 解析帧清单，支持 JSON 数组或每行一个对象的 JSONL
+pub fn parse_manifest(data: &str) -> Result<Vec<ExternalFrame>> {
+    let trimmed = data.trim_start_matches('\u{feff}').trim();
+    if trimmed.starts_with('[') {
+        return serde_json::from_str(trimmed).context("清单 JSON 解析失败");
+    }
+
+    trimmed
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line.trim())
+                .with_context(|| format!("清单第 {} 行解析失败", index + 1))
+        })
+        .collect()
+}
+
+/// 导入一批外部帧
+pub async fn ingest_frames(
+    capture: &ScreenCapture,
+    frames: Vec<ExternalFrame>,
+) -> Result<IngestReport> {
+    ingest_frames_from(capture, frames, None).await
+}
+
+/// 读取清单文件并导入其中的帧，相对路径以清单所在目录为基准
+pub async fn ingest_manifest(capture: &ScreenCapture, path: &Path) -> Result<IngestReport> {
+    let data = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("读取清单失败: {}", path.display()))?;
+    let frames = parse_manifest(&data)?;
+    ingest_frames_from(capture, frames, path.parent()).await
+}
+
+async fn ingest_frames_from(
+    capture: &ScreenCapture,
+    frames: Vec<ExternalFrame>,
+    base_dir: Option<&Path>,
+) -> Result<IngestReport> {
+    let mut report = IngestReport {
+        received: frames.len(),
+        ..IngestReport::default()
+    };
+    let now = crate::storage::local_now();
+    let mut index_lines = String::new();
+    let mut ingested_range: Option<(DateTime<Utc>, DateTime<Utc>)> = None;
+
+    for frame in &frames {
+        let Some(timestamp) = parse_timestamp(&frame.timestamp) else {
+            report.record_error(format!("无法解析时间: {}", frame.timestamp));
+            continue;
+        };
+        if timestamp > now {
+            report.record_error(format!("时间晚于当前时间: {}", frame.timestamp));
+            continue;
+        }
+        if capture.frame_exists(timestamp) {
+            report.duplicates += 1;
+            continue;
+        }
+
+        let source = resolve_path(&frame.path, base_dir);
+        let image = match tokio::task::spawn_blocking({
+            let source = source.clone();
+            move || image::open(&source)
+        })
+        .await?
+        {
+            Ok(image) => image,
+            Err(e) => {
+                report.record_error(format!("{}: {}", source.display(), e));
+                continue;
+            }
+        };
+
+        if let Err(e) = capture.ingest_image(image, timestamp).await {
+            report.record_error(format!("{}: {}", source.display(), e));
+            continue;
+        }
+
+        let entry = ExternalFrameIndexEntry {
+            timestamp_ms: timestamp.timestamp_millis(),
+            source_path: &frame.path,
+            metadata: &frame.metadata,
+        };
+        index_lines.push_str(&serde_json::to_string(&entry)?);
+        index_lines.push('\n');
+
+        report.ingested += 1;
+        ingested_range = Some(match ingested_range {
+            Some((first, last)) => (first.min(timestamp), last.max(timestamp)),
+            None => (timestamp, timestamp),
+        });
+    }
+
+    if !index_lines.is_empty() {
+        let index_path = capture.frames_dir().join(EXTERNAL_INDEX_FILE);
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&index_path)
+            .await
+            .map_err(|e| anyhow!("写入来源索引失败: {}", e))?;
+        file.write_all(index_lines.as_bytes()).await?;
+    }
+
+    if let Some((first, last)) = ingested_range {
+        report.first_timestamp = Some(first.format("%Y-%m-%d %H:%M:%S").to_string());
+        report.last_timestamp = Some(last.format("%Y-%m-%d %H:%M:%S").to_string());
+    }
+
+    info!(
+        "外部帧导入完成: 收到 {} 帧，导入 {}，重复 {}，失败 {}",
+        report.received, report.ingested, report.duplicates, report.failed
+    );
+    Ok(report)
+}
+
+fn resolve_path(path: &str, base_dir: Option<&Path>) -> PathBuf {
+    let path = PathBuf::from(path.trim());
+    match base_dir {
+        Some(base) if path.is_relative() => base.join(path),
+        _ => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp_formats() {
+        let naive = parse_timestamp("2026-03-02 09:15:30").unwrap();
+        assert_eq!(naive.format("%F %T").to_string(), "2026-03-02 09:15:30");
+
+        let instant = Utc.with_ymd_and_hms(2026, 3, 2, 1, 15, 30).unwrap();
+        let expected = instant.with_timezone(&Local).naive_local().and_utc();
+        assert_eq!(parse_timestamp("2026-03-02T01:15:30Z"), Some(expected));
+        assert_eq!(
+            parse_timestamp(&instant.timestamp().to_string()),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_timestamp(&instant.timestamp_millis().to_string()),
+            Some(expected)
+        );
+
+        assert_eq!(parse_timestamp(""), None);
+        assert_eq!(parse_timestamp("yesterday"), None);
+    }
+
+    #[test]
+    fn test_parse_manifest_json_and_jsonl() {
+        let array = r#"[{"path": "a.png", "timestamp": "2026-03-02 09:00:00"}]"#;
+        let frames = parse_manifest(array).unwrap();
+        assert_eq!(frames.len(), 1);
+        assert!(frames[0].metadata.is_empty());
+
+        let lines = "{\"path\": \"a.png\", \"timestamp\": \"1772442000\", \"metadata\": {\"app\": \"Code\"}}\n\n\
+                     {\"path\": \"b.png\", \"timestamp\": \"1772442001\"}\n";
+        let frames = parse_manifest(lines).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].metadata["app"], "Code");
+        assert_eq!(
+            resolve_path(&frames[1].path, Some(Path::new("/data/rec"))),
+            PathBuf::from("/data/rec/b.png")
+        );
+
+        let err = parse_manifest("{\"path\": \"a.png\"}\nnot json").unwrap_err();
+        assert!(err.to_string().contains("第 1 行"));
+    }
+}
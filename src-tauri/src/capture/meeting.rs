@@ -0,0 +1,595 @@
+// 会议检测 - 截屏前检查摄像头、麦克风是否正在使用以及是否处于全屏演示，
+// 按配置暂停截屏，或继续截屏并把帧标记为会议：标记按天写入帧目录下的
+// `meeting_activity_<日期>.jsonl`（随帧文件按保留期清理），会话分析时会议时段以会议卡片代替屏幕内容
+
+use super::idle::{period_card, replace_periods, IdlePeriod};
+use super::ScreenFrame;
+use crate::llm::plugin::TimelineCard;
+use crate::models::MeetingDetection;
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// 会议记录文件名前缀
+const MEETING_LOG_PREFIX: &str = "meeting_activity_";
+
+/// 两次检测的最短间隔（秒），期间沿用上次结果
+const DETECT_INTERVAL_SECS: u64 = 5;
+
+/// 判定为会议的依据
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MeetingSignal {
+    /// 摄像头使用中
+    Camera,
+    /// 麦克风使用中
+    Microphone,
+    /// 全屏演示
+    Presentation,
+}
+
+impl MeetingSignal {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MeetingSignal::Camera => "摄像头使用中",
+            MeetingSignal::Microphone => "麦克风使用中",
+            MeetingSignal::Presentation => "全屏演示",
+        }
+    }
+}
+
+/// 会议记录文件中的一行
+#[derive(Debug, Serialize, Deserialize)]
+struct MeetingRecord {
+    timestamp_ms: i64,
+    signal: MeetingSignal,
+}
+
+/// 带缓存的会议检测器（截屏间隔很短时避免每帧都查询系统）
+#[derive(Default)]
+pub struct MeetingDetector {
+    last: Option<(Instant, Option<MeetingSignal>)>,
+}
+
+impl MeetingDetector {
+    /// 检测当前是否处于会议中
+    pub async fn detect(&mut self, config: &MeetingDetection) -> Option<MeetingSignal> {
+        if let Some((checked_at, signal)) = self.last {
+            if checked_at.elapsed().as_secs() < DETECT_INTERVAL_SECS {
+                return signal;
+            }
+        }
+
+        let config = config.clone();
+        let signal = tokio::task::spawn_blocking(move || detect(&config))
+            .await
+            .unwrap_or_default();
+        self.last = Some((Instant::now(), signal));
+        signal
+    }
+}
+
+/// 按配置依次检查各项依据（阻塞调用，不支持的检测项视为未使用）
+pub fn detect(config: &MeetingDetection) -> Option<MeetingSignal> {
+    if config.camera && camera_in_use() {
+        return Some(MeetingSignal::Camera);
+    }
+    if config.microphone && microphone_in_use() {
+        return Some(MeetingSignal::Microphone);
+    }
+    if config.presentation && presentation_active() {
+        return Some(MeetingSignal::Presentation);
+    }
+    None
+}
+
+#[cfg(windows)]
+fn camera_in_use() -> bool {
+    capability_in_use("webcam")
+}
+
+#[cfg(windows)]
+fn microphone_in_use() -> bool {
+    capability_in_use("microphone")
+}
+
+/// 系统隐私设置记录了各应用使用设备的起止时间，结束时间为 0 表示仍在使用
+#[cfg(windows)]
+fn capability_in_use(capability: &str) -> bool {
+    use winreg::enums::HKEY_CURRENT_USER;
+    use winreg::RegKey;
+
+    fn key_in_use(key: &RegKey) -> bool {
+        key.get_value::<u64, _>("LastUsedTimeStop")
+            .is_ok_and(|stop| stop == 0)
+            && key
+                .get_value::<u64, _>("LastUsedTimeStart")
+                .is_ok_and(|start| start > 0)
+    }
+
+    let path = format!(
+        r"Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\{}",
+        capability
+    );
+    let Ok(store) = RegKey::predef(HKEY_CURRENT_USER).open_subkey(path) else {
+        return false;
+    };
+
+    store.enum_keys().flatten().any(|name| {
+        let Ok(app) = store.open_subkey(&name) else {
+            return false;
+        };
+        // 非商店应用按可执行文件路径再分一层
+        if name == "NonPackaged" {
+            app.enum_keys()
+                .flatten()
+                .any(|name| app.open_subkey(name).is_ok_and(|key| key_in_use(&key)))
+        } else {
+            key_in_use(&app)
+        }
+    })
+}
+
+#[cfg(windows)]
+fn presentation_active() -> bool {
+    // QUNS_BUSY（全屏应用）、QUNS_RUNNING_D3D_FULL_SCREEN、QUNS_PRESENTATION_MODE
+    const FULL_SCREEN_STATES: [i32; 3] = [2, 3, 4];
+
+    #[link(name = "shell32")]
+    extern "system" {
+        fn SHQueryUserNotificationState(state: *mut i32) -> i32;
+    }
+
+    let mut state = 0;
+    // SAFETY: 只写入一个整数
+    let result = unsafe { SHQueryUserNotificationState(&mut state) };
+    result == 0 && FULL_SCREEN_STATES.contains(&state)
+}
+
+#[cfg(target_os = "macos")]
+mod mac {
+    use std::ffi::c_void;
+
+    /// AudioObjectPropertyAddress / CMIOObjectPropertyAddress
+    #[repr(C)]
+    struct PropertyAddress {
+        selector: u32,
+        scope: u32,
+        element: u32,
+    }
+
+    const fn four_cc(code: &[u8; 4]) -> u32 {
+        u32::from_be_bytes(*code)
+    }
+
+    const SYSTEM_OBJECT: u32 = 1;
+    const SCOPE_GLOBAL: u32 = four_cc(b"glob");
+    const DEFAULT_INPUT_DEVICE: u32 = four_cc(b"dIn ");
+    const DEVICES: u32 = four_cc(b"dev#");
+    const IS_RUNNING_SOMEWHERE: u32 = four_cc(b"gone");
+
+    #[link(name = "CoreAudio", kind = "framework")]
+    extern "C" {
+        fn AudioObjectGetPropertyData(
+            object_id: u32,
+            address: *const PropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: *mut u32,
+            data: *mut c_void,
+        ) -> i32;
+    }
+
+    #[link(name = "CoreMediaIO", kind = "framework")]
+    extern "C" {
+        fn CMIOObjectGetPropertyDataSize(
+            object_id: u32,
+            address: *const PropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: *mut u32,
+        ) -> i32;
+        fn CMIOObjectGetPropertyData(
+            object_id: u32,
+            address: *const PropertyAddress,
+            qualifier_size: u32,
+            qualifier: *const c_void,
+            data_size: u32,
+            data_used: *mut u32,
+            data: *mut c_void,
+        ) -> i32;
+    }
+
+    fn address(selector: u32) -> PropertyAddress {
+        PropertyAddress {
+            selector,
+            scope: SCOPE_GLOBAL,
+            element: 0,
+        }
+    }
+
+    fn audio_u32(object_id: u32, selector: u32) -> Option<u32> {
+        let mut value = 0u32;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        // SAFETY: 输出缓冲区为一个 u32，大小已传入
+        let status = unsafe {
+            AudioObjectGetPropertyData(
+                object_id,
+                &address(selector),
+                0,
+                std::ptr::null(),
+                &mut size,
+                &mut value as *mut u32 as *mut c_void,
+            )
+        };
+        (status == 0).then_some(value)
+    }
+
+    fn cmio_u32s(object_id: u32, selector: u32) -> Vec<u32> {
+        let address = address(selector);
+        let mut size = 0u32;
+        // SAFETY: 只读取属性大小
+        let status = unsafe {
+            CMIOObjectGetPropertyDataSize(object_id, &address, 0, std::ptr::null(), &mut size)
+        };
+        if status != 0 || size == 0 {
+            return Vec::new();
+        }
+
+        let mut values = vec![0u32; size as usize / std::mem::size_of::<u32>()];
+        let mut used = 0u32;
+        // SAFETY: 缓冲区按属性大小分配
+        let status = unsafe {
+            CMIOObjectGetPropertyData(
+                object_id,
+                &address,
+                0,
+                std::ptr::null(),
+                size,
+                &mut used,
+                values.as_mut_ptr() as *mut c_void,
+            )
+        };
+        if status != 0 {
+            return Vec::new();
+        }
+        values.truncate(used as usize / std::mem::size_of::<u32>());
+        values
+    }
+
+    /// 任一摄像头正被某个进程使用
+    pub fn camera_in_use() -> bool {
+        cmio_u32s(SYSTEM_OBJECT, DEVICES).into_iter().any(|device| {
+            cmio_u32s(device, IS_RUNNING_SOMEWHERE)
+                .first()
+                .is_some_and(|running| *running != 0)
+        })
+    }
+
+    /// 默认输入设备正被某个进程使用
+    pub fn microphone_in_use() -> bool {
+        audio_u32(SYSTEM_OBJECT, DEFAULT_INPUT_DEVICE)
+            .filter(|device| *device != 0)
+            .and_then(|device| audio_u32(device, IS_RUNNING_SOMEWHERE))
+            .is_some_and(|running| running != 0)
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn camera_in_use() -> bool {
+    mac::camera_in_use()
+}
+
+#[cfg(target_os = "macos")]
+fn microphone_in_use() -> bool {
+    mac::microphone_in_use()
+}
+
+#[cfg(target_os = "macos")]
+fn presentation_active() -> bool {
+    // macOS 没有公开的演示模式查询接口
+    false
+}
+
+/// 任一进程打开了视频采集设备
+#[cfg(target_os = "linux")]
+fn camera_in_use() -> bool {
+    video_device_open(Path::new("/proc"))
+}
+
+#[cfg(target_os = "linux")]
+fn microphone_in_use() -> bool {
+    capture_stream_running(Path::new("/proc/asound"))
+}
+
+/// 前台窗口处于全屏状态（X11，依赖 xdotool 与 xprop）
+#[cfg(target_os = "linux")]
+fn presentation_active() -> bool {
+    let Ok(output) = std::process::Command::new("xdotool")
+        .arg("getactivewindow")
+        .output()
+    else {
+        return false;
+    };
+    let window_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || window_id.is_empty() {
+        return false;
+    }
+
+    std::process::Command::new("xprop")
+        .args(["-id", &window_id, "_NET_WM_STATE"])
+        .output()
+        .is_ok_and(|output| {
+            String::from_utf8_lossy(&output.stdout).contains("_NET_WM_STATE_FULLSCREEN")
+        })
+}
+
+#[cfg(target_os = "linux")]
+fn video_device_open(proc_root: &Path) -> bool {
+    let Ok(processes) = std::fs::read_dir(proc_root) else {
+        return false;
+    };
+    processes.flatten().any(|process| {
+        // 只有当前用户的进程可读，其余忽略
+        let Ok(fds) = std::fs::read_dir(process.path().join("fd")) else {
+            return false;
+        };
+        fds.flatten().any(|fd| {
+            std::fs::read_link(fd.path())
+                .is_ok_and(|target| target.to_string_lossy().starts_with("/dev/video"))
+        })
+    })
+}
+
+/// ALSA 采集子设备处于运行状态（PulseAudio/PipeWire 录音时同样可见）
+#[cfg(target_os = "linux")]
+fn capture_stream_running(asound_root: &Path) -> bool {
+    let Ok(cards) = std::fs::read_dir(asound_root) else {
+        return false;
+    };
+    let children = |path: PathBuf, prefix: &'static str, suffix: &'static str| {
+        std::fs::read_dir(path)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(move |entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                name.starts_with(prefix) && name.ends_with(suffix)
+            })
+            .map(|entry| entry.path())
+    };
+
+    cards
+        .flatten()
+        .filter(|card| card.file_name().to_string_lossy().starts_with("card"))
+        .flat_map(|card| children(card.path(), "pcm", "c"))
+        .flat_map(|pcm| children(pcm, "sub", ""))
+        .any(|sub| {
+            std::fs::read_to_string(sub.join("status"))
+                .is_ok_and(|status| status.contains("state: RUNNING"))
+        })
+}
+
+#[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+fn camera_in_use() -> bool {
+    false
+}
+
+#[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+fn microphone_in_use() -> bool {
+    false
+}
+
+#[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+fn presentation_active() -> bool {
+    false
+}
+
+/// 某天的会议记录文件
+fn meeting_log_path(frames_dir: &Path, date: NaiveDate) -> PathBuf {
+    frames_dir.join(format!(
+        "{}{}.jsonl",
+        MEETING_LOG_PREFIX,
+        date.format("%Y-%m-%d")
+    ))
+}
+
+/// 追加一帧的会议记录
+pub fn append_meeting_record(
+    frames_dir: &Path,
+    timestamp: DateTime<Utc>,
+    signal: MeetingSignal,
+) -> Result<()> {
+    let record = MeetingRecord {
+        timestamp_ms: timestamp.timestamp_millis(),
+        signal,
+    };
+    let mut line = serde_json::to_string(&record)?;
+    line.push('\n');
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(meeting_log_path(frames_dir, timestamp.date_naive()))?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// 读取帧对应的会议记录并填入 `ScreenFrame::meeting`（按毫秒时间戳精确匹配）
+pub async fn attach_meeting_marks(frames_dir: &Path, frames: &mut [ScreenFrame]) {
+    let (Some(first), Some(last)) = (
+        frames.iter().map(|f| f.timestamp).min(),
+        frames.iter().map(|f| f.timestamp).max(),
+    ) else {
+        return;
+    };
+
+    let mut records: HashMap<i64, MeetingSignal> = HashMap::new();
+    for date in first
+        .date_naive()
+        .iter_days()
+        .take_while(|date| *date <= last.date_naive())
+    {
+        let Ok(data) = tokio::fs::read_to_string(meeting_log_path(frames_dir, date)).await else {
+            continue;
+        };
+        for line in data.lines() {
+            if let Ok(record) = serde_json::from_str::<MeetingRecord>(line) {
+                records.insert(record.timestamp_ms, record.signal);
+            }
+        }
+    }
+
+    for frame in frames.iter_mut() {
+        if frame.meeting.is_none() {
+            frame.meeting = records.get(&frame.timestamp.timestamp_millis()).copied();
+        }
+    }
+}
+
+/// 按会议帧计算会话内的会议时段（每帧覆盖到下一帧，见 `frame_durations`），附带首帧的判定依据
+pub fn meeting_periods(
+    frames: &[ScreenFrame],
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+) -> Vec<(IdlePeriod, MeetingSignal)> {
+    let mut periods: Vec<(IdlePeriod, MeetingSignal)> = Vec::new();
+    for (frame, seconds) in super::window::frame_durations(frames, window_end) {
+        let Some(signal) = frame.meeting else {
+            continue;
+        };
+        let start = frame.timestamp.max(window_start);
+        let end = (frame.timestamp + Duration::seconds(seconds)).min(window_end);
+        if end <= start {
+            continue;
+        }
+
+        match periods.last_mut() {
+            Some((last, _)) if start <= last.end => {
+                last.end = last.end.max(end);
+            }
+            _ => periods.push((IdlePeriod { start, end }, signal)),
+        }
+    }
+    periods
+}
+
+/// 从时间线卡片中扣除会议时段，并为每个时段插入一张会议卡片
+pub fn mark_meeting_cards(
+    cards: Vec<TimelineCard>,
+    periods: &[(IdlePeriod, MeetingSignal)],
+) -> Vec<TimelineCard> {
+    let replacements = periods
+        .iter()
+        .map(|(period, signal)| {
+            let minutes = (period.end - period.start).num_minutes();
+            let card = (minutes >= 1).then(|| {
+                period_card(
+                    period,
+                    "communication",
+                    "meeting",
+                    "会议",
+                    format!("会议 {} 分钟（{}）", minutes, signal.label()),
+                    "该时段检测到视频通话或演示，判定为会议，不记录屏幕内容",
+                )
+            });
+            (*period, card)
+        })
+        .collect();
+    replace_periods(cards, replacements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        chrono::NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_utc()
+            + Duration::minutes(minutes)
+    }
+
+    fn frame(minutes: i64, meeting: Option<MeetingSignal>) -> ScreenFrame {
+        let timestamp = at(minutes);
+        ScreenFrame {
+            timestamp,
+            file_path: format!("{}.jpg", timestamp.timestamp_millis()),
+            screen_id: 0,
+            window: None,
+            domain: None,
+            idle_seconds: None,
+            repeat: None,
+            meeting,
+            focus_block: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_meeting_periods_and_cards() {
+        let dir = tempfile::tempdir().unwrap();
+        for minutes in 3..6 {
+            append_meeting_record(dir.path(), at(minutes), MeetingSignal::Camera).unwrap();
+        }
+
+        // 每分钟一帧，3-5 分钟的帧处于会议中
+        let mut frames: Vec<ScreenFrame> = (0..10).map(|m| frame(m, None)).collect();
+        attach_meeting_marks(dir.path(), &mut frames).await;
+        assert_eq!(frames[3].meeting, Some(MeetingSignal::Camera));
+        assert_eq!(frames[6].meeting, None);
+
+        let periods = meeting_periods(&frames, at(0), at(10));
+        assert_eq!(
+            periods,
+            vec![(
+                IdlePeriod {
+                    start: at(3),
+                    end: at(6)
+                },
+                MeetingSignal::Camera
+            )]
+        );
+
+        let work = period_card(
+            &IdlePeriod {
+                start: at(0),
+                end: at(10),
+            },
+            "work",
+            "coding",
+            "编写代码",
+            String::new(),
+            "",
+        );
+        let cards = mark_meeting_cards(vec![work], &periods);
+        let titles: Vec<&str> = cards.iter().map(|card| card.title.as_str()).collect();
+        assert_eq!(titles, vec!["编写代码", "会议", "编写代码"]);
+        assert_eq!(cards[1].summary, "会议 3 分钟（摄像头使用中）");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_capture_stream_running() {
+        let dir = tempfile::tempdir().unwrap();
+        let sub = dir.path().join("card0").join("pcm0c").join("sub0");
+        std::fs::create_dir_all(&sub).unwrap();
+        std::fs::write(sub.join("status"), "closed\n").unwrap();
+        assert!(!capture_stream_running(dir.path()));
+
+        std::fs::write(sub.join("status"), "state: RUNNING\nowner_pid   : 42\n").unwrap();
+        assert!(capture_stream_running(dir.path()));
+
+        // 播放设备不算
+        let playback = dir.path().join("card1").join("pcm0p").join("sub0");
+        std::fs::create_dir_all(&playback).unwrap();
+        std::fs::write(sub.join("status"), "closed\n").unwrap();
+        std::fs::write(playback.join("status"), "state: RUNNING\n").unwrap();
+        assert!(!capture_stream_running(dir.path()));
+    }
+}
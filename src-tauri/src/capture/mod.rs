@@ -1,6 +1,6 @@
 // 截屏模块 - 负责定时捕获屏幕截图
 
-use crate::models::CaptureSettings;
+use crate::models::{CaptureSettings, MeetingAction};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use image::imageops;
@@ -18,7 +18,20 @@ use std::process::Command;
 #[cfg(not(target_os = "macos"))]
 use tracing::debug;
 
+pub mod app_limit;
+pub mod dedupe;
+pub mod distraction;
+pub mod focus;
+pub mod format;
+pub mod heuristic;
+pub mod idle;
+pub mod ingest;
+pub mod meeting;
+pub mod privacy;
+pub mod quiet;
+pub mod rules;
 pub mod scheduler;
+pub mod window;
 
 /// 截屏帧数据结构
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
@@ -29,6 +42,69 @@ pub struct ScreenFrame {
     pub file_path: String,
     /// 屏幕ID
     pub screen_id: usize,
+    /// 截屏时的前台窗口
+    #[serde(default)]
+    pub window: Option<window::WindowInfo>,
+    /// 截屏时浏览器扩展上报的当前标签页域名
+    #[serde(default)]
+    pub domain: Option<String>,
+    /// 截屏时已无键鼠输入的秒数（仅超过空闲阈值的帧记录）
+    #[serde(default)]
+    pub idle_seconds: Option<u64>,
+    /// 之后被跳过的重复截图（会话分析时从重复记录填入）
+    #[serde(default)]
+    pub repeat: Option<dedupe::FrameRepeat>,
+    /// 截屏时检测到的会议（仅会议检测为标记模式时记录）
+    #[serde(default)]
+    pub meeting: Option<meeting::MeetingSignal>,
+    /// 截屏时所处的专注时段 ID
+    #[serde(default)]
+    pub focus_block: Option<i64>,
+}
+
+/// 显示器信息（供设置界面选择要截取的显示器）
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct MonitorInfo {
+    /// 显示器序号（与 `ScreenFrame::screen_id` 一致）
+    pub index: usize,
+    pub width: u32,
+    pub height: u32,
+    pub x: i32,
+    pub y: i32,
+    pub is_primary: bool,
+}
+
+/// 解析帧文件名（`<毫秒时间戳>.jpg` 或按显示器保存的 `<毫秒时间戳>_<显示器序号>.jpg`）
+pub fn parse_frame_file_stem(stem: &str) -> Option<(i64, usize)> {
+    match stem.split_once('_') {
+        Some((timestamp, screen_id)) => Some((timestamp.parse().ok()?, screen_id.parse().ok()?)),
+        None => Some((stem.parse().ok()?, 0)),
+    }
+}
+
+/// 主显示器（序号最小）的帧；多屏合成为一张图时即全部帧
+pub fn primary_screen_frames(frames: &[ScreenFrame]) -> Vec<ScreenFrame> {
+    let Some(primary) = frames.iter().map(|f| f.screen_id).min() else {
+        return Vec::new();
+    };
+    frames
+        .iter()
+        .filter(|f| f.screen_id == primary)
+        .cloned()
+        .collect()
+}
+
+/// 主显示器以外各显示器的帧（按显示器序号分组）
+pub fn secondary_screen_frames(frames: &[ScreenFrame]) -> Vec<Vec<ScreenFrame>> {
+    let mut grouped: std::collections::BTreeMap<usize, Vec<ScreenFrame>> =
+        std::collections::BTreeMap::new();
+    for frame in frames {
+        grouped
+            .entry(frame.screen_id)
+            .or_default()
+            .push(frame.clone());
+    }
+    grouped.into_values().skip(1).collect()
 }
 
 /// 截屏管理器
@@ -41,6 +117,18 @@ pub struct ScreenCapture {
     current_session: Arc<Mutex<Vec<ScreenFrame>>>,
     /// 截屏配置
     capture_settings: Arc<Mutex<CaptureSettings>>,
+    /// 前台窗口采集器
+    window_collector: Arc<window::WindowCollector>,
+    /// 各显示器上一张保存的帧（重复帧判断）
+    duplicate_filter: Mutex<dedupe::DuplicateFilter>,
+    /// 会议检测器
+    meeting_detector: Mutex<meeting::MeetingDetector>,
+    /// 专注计时器
+    focus_timer: Arc<focus::FocusTimer>,
+    /// 分心监测器
+    distraction_watcher: Arc<distraction::DistractionWatcher>,
+    /// 应用时长上限监测器
+    app_limit_watcher: Arc<app_limit::AppLimitWatcher>,
 }
 
 impl ScreenCapture {
@@ -68,9 +156,49 @@ impl ScreenCapture {
             output_dir,
             current_session: Arc::new(Mutex::new(Vec::new())),
             capture_settings: Arc::new(Mutex::new(CaptureSettings::default())),
+            window_collector: Arc::new(window::WindowCollector::new()),
+            duplicate_filter: Mutex::new(dedupe::DuplicateFilter::default()),
+            meeting_detector: Mutex::new(meeting::MeetingDetector::default()),
+            focus_timer: Arc::new(focus::FocusTimer::new()),
+            distraction_watcher: Arc::new(distraction::DistractionWatcher::new()),
+            app_limit_watcher: Arc::new(app_limit::AppLimitWatcher::new()),
         })
     }
 
+    /// 可用显示器列表
+    pub fn monitors(&self) -> Vec<MonitorInfo> {
+        self.screens
+            .iter()
+            .enumerate()
+            .map(|(index, screen)| {
+                let info = screen.display_info;
+                MonitorInfo {
+                    index,
+                    width: info.width,
+                    height: info.height,
+                    x: info.x,
+                    y: info.y,
+                    is_primary: info.is_primary,
+                }
+            })
+            .collect()
+    }
+
+    /// 专注计时器
+    pub fn focus_timer(&self) -> Arc<focus::FocusTimer> {
+        self.focus_timer.clone()
+    }
+
+    /// 分心监测器
+    pub fn distraction_watcher(&self) -> Arc<distraction::DistractionWatcher> {
+        self.distraction_watcher.clone()
+    }
+
+    /// 应用时长上限监测器
+    pub fn app_limit_watcher(&self) -> Arc<app_limit::AppLimitWatcher> {
+        self.app_limit_watcher.clone()
+    }
+
     /// 更新截屏配置
     pub async fn update_settings(&self, settings: CaptureSettings) {
         let mut current = self.capture_settings.lock().await;
@@ -213,7 +341,12 @@ impl ScreenCapture {
         is_black
     }
 
-    /// 捕获单个帧
+    /// 当前截屏配置
+    pub async fn settings(&self) -> CaptureSettings {
+        self.capture_settings.lock().await.clone()
+    }
+
+    /// 捕获单个帧（按显示器分别保存时返回第一个显示器的帧）
     pub async fn capture_frame(&self) -> Result<ScreenFrame> {
         let timestamp = crate::storage::local_now();
 
@@ -221,80 +354,271 @@ impl ScreenCapture {
             return Err(anyhow::anyhow!("未找到可用屏幕"));
         }
 
-        // 所有平台统一使用 screenshots crate 进行多屏幕截图
-        let combined = {
-            let mut captures = Vec::new();
+        let settings = self.capture_settings.lock().await.clone();
 
-            for (index, screen) in self.screens.iter().enumerate() {
-                match screen.capture() {
-                    Ok(image) => {
-                        let info = screen.display_info;
-                        captures.push((info, DynamicImage::ImageRgba8(image)));
-                        trace!("截取屏幕 #{} 成功", index);
-                    }
-                    Err(err) => {
-                        warn!("截取屏幕 #{} 失败: {}", index, err);
+        // 长时间无键鼠输入时暂停截屏，超过空闲阈值的帧标记为空闲
+        let idle_seconds = if settings.idle_detection {
+            tokio::task::spawn_blocking(idle::idle_seconds)
+                .await
+                .unwrap_or_default()
+        } else {
+            None
+        };
+        if let Some(seconds) = idle_seconds {
+            if seconds >= settings.idle_pause_seconds() {
+                // 恢复输入后的第一帧总是保存
+                *self.duplicate_filter.lock().await = dedupe::DuplicateFilter::default();
+                return Err(anyhow::anyhow!("用户空闲 {} 秒，暂停截屏", seconds));
+            }
+        }
+        let idle_seconds =
+            idle_seconds.filter(|seconds| *seconds >= settings.idle_threshold_seconds());
+
+        // 视频通话或演示时按配置暂停截屏，或把帧标记为会议
+        let meeting = if settings.meeting_detection.enabled {
+            self.meeting_detector
+                .lock()
+                .await
+                .detect(&settings.meeting_detection)
+                .await
+        } else {
+            None
+        };
+        if let Some(signal) = meeting {
+            if settings.meeting_detection.action == MeetingAction::Pause {
+                *self.duplicate_filter.lock().await = dedupe::DuplicateFilter::default();
+                return Err(anyhow::anyhow!("会议中（{}），暂停截屏", signal.label()));
+            }
+        }
+
+        // 与截图同时记录前台窗口
+        let window_info = if settings.capture_window_info {
+            let collector = self.window_collector.clone();
+            tokio::task::spawn_blocking(move || collector.active_window())
+                .await
+                .unwrap_or_default()
+        } else {
+            None
+        };
+
+        // 所有平台统一使用 screenshots crate 截取所选显示器
+        let mut captures = Vec::new();
+        for index in settings.selected_monitors(self.screens.len()) {
+            let screen = &self.screens[index];
+            match screen.capture() {
+                Ok(image) => {
+                    // 隐私区域在合成与保存前处理，敏感内容不会写入磁盘
+                    let mut image = DynamicImage::ImageRgba8(image);
+                    privacy::apply_privacy_zones(&mut image, index, &settings.privacy_zones);
+                    captures.push((index, screen.display_info, image));
+                    trace!("截取屏幕 #{} 成功", index);
+                }
+                Err(err) => {
+                    warn!("截取屏幕 #{} 失败: {}", index, err);
+                }
+            }
+        }
+
+        if captures.is_empty() {
+            return Err(anyhow::anyhow!("未能获取到任何屏幕截图"));
+        }
+
+        // 每个显示器单独保存，或合成为一张图
+        let images: Vec<(Option<usize>, DynamicImage)> = if settings.per_monitor_frames {
+            captures
+                .into_iter()
+                .map(|(index, _, image)| (Some(index), image))
+                .collect()
+        } else {
+            let combined = self.combine_screens(
+                captures
+                    .into_iter()
+                    .map(|(_, info, image)| (info, image))
+                    .collect(),
+            )?;
+            vec![(None, combined)]
+        };
+
+        let context = dedupe::FrameContext {
+            window: window_info.clone(),
+            idle: idle_seconds.is_some(),
+            meeting: meeting.is_some(),
+        };
+        let mut frames = Vec::with_capacity(images.len());
+        let mut duplicates = 0;
+        for (screen_id, image) in images {
+            // 根据配置调整分辨率
+            let resized = if let Some((width, height)) = settings.resolution.dimensions() {
+                self.resize_image(image, width, height)?
+            } else {
+                // 原始分辨率，不调整
+                image
+            };
+
+            // 检测是否为黑屏
+            if self.is_black_screen(&resized).await {
+                info!("检测到黑屏，跳过保存");
+                continue;
+            }
+
+            // 与该显示器上一帧几乎相同时只记录重复，不保存图片
+            if settings.dedupe_frames {
+                let screen = screen_id.unwrap_or(0);
+                let kept = self.duplicate_filter.lock().await.check(
+                    screen,
+                    dedupe::dhash(&resized),
+                    timestamp,
+                    context.clone(),
+                    settings.dedupe_threshold,
+                );
+                if let Some(kept) = kept {
+                    if let Err(e) =
+                        dedupe::append_duplicate_record(&self.output_dir, timestamp, kept, screen)
+                    {
+                        warn!("写入重复帧记录失败: {}", e);
                     }
+                    duplicates += 1;
+                    continue;
                 }
             }
 
-            if captures.is_empty() {
-                return Err(anyhow::anyhow!("未能获取到任何屏幕截图"));
+            frames.push(self.write_frame(&resized, timestamp, &settings, screen_id)?);
+        }
+
+        if frames.is_empty() {
+            if duplicates > 0 {
+                return Err(anyhow::anyhow!("重复画面，已跳过"));
             }
+            return Err(anyhow::anyhow!("黑屏图像，已跳过"));
+        }
 
-            self.combine_screens(captures)?
-        };
+        // 前台窗口与空闲记录按时间戳保存一次，同一时刻各显示器的帧共用
+        if let Some(info) = &window_info {
+            if let Err(e) = window::append_window_record(&self.output_dir, timestamp, info) {
+                warn!("写入前台窗口记录失败: {}", e);
+            }
+        }
+        if let Some(seconds) = idle_seconds {
+            if let Err(e) = idle::append_idle_record(&self.output_dir, timestamp, seconds) {
+                warn!("写入空闲记录失败: {}", e);
+            }
+        }
+        if let Some(signal) = meeting {
+            if let Err(e) = meeting::append_meeting_record(&self.output_dir, timestamp, signal) {
+                warn!("写入会议记录失败: {}", e);
+            }
+        }
+        for frame in &mut frames {
+            frame.window = window_info.clone();
+            frame.idle_seconds = idle_seconds;
+            frame.meeting = meeting;
+        }
+
+        // 专注时段内的帧标记时段 ID，干扰类活动计入该时段的干扰记录
+        if let Some(block_id) = self.focus_timer.observe_frame(&frames[0]) {
+            if let Err(e) = focus::append_focus_record(&self.output_dir, timestamp, block_id) {
+                warn!("写入专注时段记录失败: {}", e);
+            }
+            for frame in &mut frames {
+                frame.focus_block = Some(block_id);
+            }
+        }
+        self.distraction_watcher.observe_frame(&frames[0]);
+        self.app_limit_watcher.observe_frame(&frames[0]);
+
+        // 添加到当前会话
+        self.current_session
+            .lock()
+            .await
+            .extend(frames.iter().cloned());
+
+        let frame = frames.swap_remove(0);
+        trace!("截屏保存成功: {}", frame.file_path);
+        Ok(frame)
+    }
 
-        // 根据配置调整分辨率
+    /// 导入外部采集的图像，按截屏配置调整分辨率后写入帧目录
+    pub async fn ingest_image(
+        &self,
+        image: DynamicImage,
+        timestamp: DateTime<Utc>,
+    ) -> Result<ScreenFrame> {
         let settings = self.capture_settings.lock().await.clone();
         let resized = if let Some((width, height)) = settings.resolution.dimensions() {
-            self.resize_image(combined, width, height)?
+            self.resize_image(image, width, height)?
         } else {
-            // 原始分辨率，不调整
-            combined
+            image
         };
+        // 外部图像格式各异（16位、灰度+透明等），统一转为 RGB 再编码
+        let resized = DynamicImage::ImageRgb8(resized.to_rgb8());
 
-        // 检测是否为黑屏
-        if self.is_black_screen(&resized).await {
-            info!("检测到黑屏，跳过保存");
-            return Err(anyhow::anyhow!("黑屏图像，已跳过"));
-        }
+        let frame = self.write_frame(&resized, timestamp, &settings, None)?;
 
-        // 生成文件名
-        let file_name = format!("{}.jpg", timestamp.timestamp_millis());
-        let file_path = self.output_dir.join(&file_name);
+        // 外部帧可能晚于实时截屏到达，插入后保持时间顺序
+        let mut session = self.current_session.lock().await;
+        session.push(frame.clone());
+        session.sort_by_key(|f| f.timestamp);
 
-        // 保存为JPEG格式，使用配置的质量
-        // 使用 JpegEncoder 来指定质量参数
-        use image::codecs::jpeg::JpegEncoder;
-        use std::fs::File;
-        use std::io::BufWriter;
+        trace!("外部帧保存成功: {}", frame.file_path);
+        Ok(frame)
+    }
 
-        let output_file =
-            File::create(&file_path).map_err(|e| anyhow::anyhow!("创建文件失败: {}", e))?;
-        let writer = BufWriter::new(output_file);
-        let mut encoder = JpegEncoder::new_with_quality(writer, settings.image_quality);
+    /// 帧文件路径（文件名为毫秒时间戳，调度器据此归入会话时间窗）
+    pub fn frame_path(&self, timestamp: DateTime<Utc>, extension: &str) -> PathBuf {
+        self.output_dir
+            .join(format!("{}.{}", timestamp.timestamp_millis(), extension))
+    }
 
-        encoder.encode(
-            resized.as_bytes(),
-            resized.width(),
-            resized.height(),
-            resized.color(),
-        )?;
+    /// 单个显示器的帧文件路径（`<毫秒时间戳>_<显示器序号>.<扩展名>`）
+    pub fn monitor_frame_path(
+        &self,
+        timestamp: DateTime<Utc>,
+        screen_id: usize,
+        extension: &str,
+    ) -> PathBuf {
+        self.output_dir.join(format!(
+            "{}_{}.{}",
+            timestamp.timestamp_millis(),
+            screen_id,
+            extension
+        ))
+    }
 
-        let file_path_str = file_path.to_string_lossy().to_string().replace('\\', "/");
+    /// 该时刻的帧文件是否已存在（任意保存格式）
+    pub fn frame_exists(&self, timestamp: DateTime<Utc>) -> bool {
+        format::FRAME_EXTENSIONS
+            .iter()
+            .any(|extension| self.frame_path(timestamp, extension).exists())
+    }
 
-        let frame = ScreenFrame {
-            timestamp,
-            file_path: file_path_str,
-            screen_id: 0,
+    /// 按配置的格式与质量保存帧文件（指定显示器时按显示器命名）
+    fn write_frame(
+        &self,
+        image: &DynamicImage,
+        timestamp: DateTime<Utc>,
+        settings: &CaptureSettings,
+        screen_id: Option<usize>,
+    ) -> Result<ScreenFrame> {
+        let frame_format = format::effective_format(settings.frame_format);
+        let extension = format::extension(frame_format);
+        let file_path = match screen_id {
+            Some(screen_id) => self.monitor_frame_path(timestamp, screen_id, extension),
+            None => self.frame_path(timestamp, extension),
         };
 
-        // 添加到当前会话
-        self.current_session.lock().await.push(frame.clone());
+        format::encode_frame(image, &file_path, frame_format, settings.image_quality)?;
 
-        trace!("截屏保存成功: {}", frame.file_path);
-        Ok(frame)
+        Ok(ScreenFrame {
+            timestamp,
+            file_path: file_path.to_string_lossy().to_string().replace('\\', "/"),
+            screen_id: screen_id.unwrap_or(0),
+            window: None,
+            domain: None,
+            idle_seconds: None,
+            repeat: None,
+            meeting: None,
+            focus_block: None,
+        })
     }
 
     fn combine_screens(&self, captures: Vec<(DisplayInfo, DynamicImage)>) -> Result<DynamicImage> {
@@ -431,4 +755,54 @@ mod tests {
         let capture = ScreenCapture::new(temp_dir.path().to_path_buf());
         assert!(capture.is_ok());
     }
+
+    #[test]
+    fn test_frame_file_names_and_monitor_selection() {
+        assert_eq!(
+            parse_frame_file_stem("1772442000000"),
+            Some((1772442000000, 0))
+        );
+        assert_eq!(
+            parse_frame_file_stem("1772442000000_2"),
+            Some((1772442000000, 2))
+        );
+        assert_eq!(parse_frame_file_stem("1772442000000_x"), None);
+        assert_eq!(parse_frame_file_stem("preview"), None);
+
+        let mut settings = CaptureSettings::default();
+        assert_eq!(settings.selected_monitors(3), vec![0, 1, 2]);
+        settings.monitors = vec![2, 0, 2, 5];
+        assert_eq!(settings.selected_monitors(3), vec![0, 2]);
+        // 所选显示器均已断开时回退为全部
+        settings.monitors = vec![4];
+        assert_eq!(settings.selected_monitors(2), vec![0, 1]);
+
+        let frame = |seconds: i64, screen_id: usize| ScreenFrame {
+            timestamp: DateTime::<Utc>::from_timestamp(1_772_442_000 + seconds, 0).unwrap(),
+            file_path: format!("{}_{}.jpg", seconds, screen_id),
+            screen_id,
+            window: None,
+            domain: None,
+            idle_seconds: None,
+            repeat: None,
+            meeting: None,
+            focus_block: None,
+        };
+        let frames = vec![
+            frame(0, 2),
+            frame(0, 1),
+            frame(1, 1),
+            frame(1, 2),
+            frame(1, 3),
+        ];
+        let primary: Vec<String> = primary_screen_frames(&frames)
+            .into_iter()
+            .map(|f| f.file_path)
+            .collect();
+        assert_eq!(primary, vec!["0_1.jpg", "1_1.jpg"]);
+        let secondary = secondary_screen_frames(&frames);
+        assert_eq!(secondary.len(), 2);
+        assert_eq!(secondary[0].len(), 2);
+        assert_eq!(secondary[1][0].file_path, "1_3.jpg");
+    }
 }
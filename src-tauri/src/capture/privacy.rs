@@ -0,0 +1,128 @@
+// 隐私区域 - 截图写入磁盘前按配置遮挡或模糊指定区域（如聊天侧边栏），
+// 在各显示器的原始截图上处理，合成、缩放与保存时敏感内容已不存在
+
+use crate::models::{PrivacyZone, PrivacyZoneMode};
+use image::{imageops, DynamicImage, GenericImage, Rgba};
+
+/// 模糊时先缩小的倍数，越大越模糊
+const BLUR_FACTOR: u32 = 24;
+
+/// 区域换算为像素矩形 (x, y, 宽, 高)，超出图像的部分被裁掉；无效或为空时返回 None
+fn zone_rect(zone: &PrivacyZone, width: u32, height: u32) -> Option<(u32, u32, u32, u32)> {
+    let values = [zone.x, zone.y, zone.width, zone.height];
+    if values.iter().any(|value| !value.is_finite()) {
+        return None;
+    }
+
+    let left = zone.x.clamp(0.0, 1.0);
+    let top = zone.y.clamp(0.0, 1.0);
+    let right = (zone.x + zone.width).clamp(0.0, 1.0);
+    let bottom = (zone.y + zone.height).clamp(0.0, 1.0);
+
+    let x0 = (left * width as f32).floor() as u32;
+    let y0 = (top * height as f32).floor() as u32;
+    let x1 = ((right * width as f32).ceil() as u32).min(width);
+    let y1 = ((bottom * height as f32).ceil() as u32).min(height);
+    (x1 > x0 && y1 > y0).then_some((x0, y0, x1 - x0, y1 - y0))
+}
+
+/// 对一个显示器的截图应用隐私区域
+pub fn apply_privacy_zones(image: &mut DynamicImage, screen_id: usize, zones: &[PrivacyZone]) {
+    let (width, height) = (image.width(), image.height());
+
+    for zone in zones
+        .iter()
+        .filter(|zone| zone.monitor.is_none() || zone.monitor == Some(screen_id))
+    {
+        let Some((x, y, w, h)) = zone_rect(zone, width, height) else {
+            continue;
+        };
+
+        match zone.mode {
+            PrivacyZoneMode::Exclude => {
+                for py in y..y + h {
+                    for px in x..x + w {
+                        image.put_pixel(px, py, Rgba([0, 0, 0, 255]));
+                    }
+                }
+            }
+            PrivacyZoneMode::Blur => {
+                // 缩小后再放大，细节（文字）不可恢复
+                let region = image.crop_imm(x, y, w, h);
+                let small = region.resize_exact(
+                    (w / BLUR_FACTOR).max(1),
+                    (h / BLUR_FACTOR).max(1),
+                    imageops::FilterType::Triangle,
+                );
+                let blurred = small.resize_exact(w, h, imageops::FilterType::Triangle);
+                imageops::replace(image, &blurred, x as i64, y as i64);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GenericImageView, RgbaImage};
+
+    fn zone(monitor: Option<usize>, rect: [f32; 4], mode: PrivacyZoneMode) -> PrivacyZone {
+        PrivacyZone {
+            monitor,
+            x: rect[0],
+            y: rect[1],
+            width: rect[2],
+            height: rect[3],
+            mode,
+        }
+    }
+
+    /// 黑白相间的条纹图，模糊后条纹应消失
+    fn striped(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(width, height, |x, _| {
+            if x % 2 == 0 {
+                Rgba([255, 255, 255, 255])
+            } else {
+                Rgba([0, 0, 0, 255])
+            }
+        }))
+    }
+
+    #[test]
+    fn test_zone_rect_clamps_to_image() {
+        let rect =
+            |values: [f32; 4]| zone_rect(&zone(None, values, PrivacyZoneMode::Blur), 200, 100);
+        assert_eq!(rect([0.75, 0.0, 0.25, 1.0]), Some((150, 0, 50, 100)));
+        assert_eq!(rect([0.9, -0.5, 0.5, 1.0]), Some((180, 0, 20, 50)));
+        assert_eq!(rect([0.5, 0.5, 0.0, 0.5]), None);
+        assert_eq!(rect([f32::NAN, 0.0, 1.0, 1.0]), None);
+    }
+
+    #[test]
+    fn test_apply_privacy_zones() {
+        let zones = vec![
+            // 右侧四分之一模糊，仅对显示器 0 生效
+            zone(Some(0), [0.75, 0.0, 0.25, 1.0], PrivacyZoneMode::Blur),
+            // 左上角遮挡，对所有显示器生效
+            zone(None, [0.0, 0.0, 0.1, 0.1], PrivacyZoneMode::Exclude),
+        ];
+
+        let mut image = striped(200, 100);
+        apply_privacy_zones(&mut image, 0, &zones);
+        assert_eq!(image.get_pixel(5, 5), Rgba([0, 0, 0, 255]));
+        assert_eq!(image.get_pixel(22, 20), Rgba([255, 255, 255, 255]));
+        let (a, b) = (image.get_pixel(170, 50), image.get_pixel(171, 50));
+        assert!(
+            a[0].abs_diff(b[0]) < 32,
+            "模糊后相邻像素应接近: {:?} {:?}",
+            a,
+            b
+        );
+
+        // 其他显示器只应用通用区域
+        let mut image = striped(200, 100);
+        apply_privacy_zones(&mut image, 1, &zones);
+        assert_eq!(image.get_pixel(5, 5), Rgba([0, 0, 0, 255]));
+        assert_eq!(image.get_pixel(170, 50), Rgba([255, 255, 255, 255]));
+    }
+}
@@ -0,0 +1,85 @@
+// 免打扰时段 - 按配置的每日时段与休息日判断当前是否应停止截屏，
+// 由截屏调度器在每次截屏前检查，时段内不产生任何帧
+
+use crate::models::QuietHours;
+use chrono::{Datelike, NaiveDateTime, NaiveTime};
+
+/// 解析 HH:MM 格式的时间
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+/// 给定本地时间是否处于免打扰时段
+pub fn is_quiet(quiet: &QuietHours, now: NaiveDateTime) -> bool {
+    if !quiet.enabled {
+        return false;
+    }
+
+    let weekday = now.weekday().number_from_monday() as u8;
+    if quiet.days_off.contains(&weekday) {
+        return true;
+    }
+
+    in_daily_range(&quiet.start, &quiet.end, now)
+}
+
+/// 给定本地时间是否处于每天的 HH:MM 时段内（结束早于开始表示跨午夜；时间无效或开始等于结束时为否）
+pub fn in_daily_range(start: &str, end: &str, now: NaiveDateTime) -> bool {
+    let (Some(start), Some(end)) = (parse_time(start), parse_time(end)) else {
+        return false;
+    };
+    let time = now.time();
+    if start < end {
+        start <= time && time < end
+    } else if start > end {
+        time >= start || time < end
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // 2026-03-02 为周一
+        NaiveDate::from_ymd_opt(2026, 3, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_is_quiet() {
+        let mut quiet = QuietHours {
+            enabled: true,
+            ..QuietHours::default()
+        };
+
+        // 跨午夜的时段
+        assert!(is_quiet(&quiet, at(2, 22, 0)));
+        assert!(is_quiet(&quiet, at(3, 7, 59)));
+        assert!(!is_quiet(&quiet, at(3, 8, 0)));
+        assert!(!is_quiet(&quiet, at(4, 21, 59)));
+        // 周六、周日全天
+        assert!(is_quiet(&quiet, at(7, 12, 0)));
+        assert!(is_quiet(&quiet, at(8, 12, 0)));
+
+        // 当天内的时段
+        quiet.start = "12:00".to_string();
+        quiet.end = "13:30".to_string();
+        quiet.days_off.clear();
+        assert!(is_quiet(&quiet, at(2, 12, 45)));
+        assert!(!is_quiet(&quiet, at(2, 13, 30)));
+        assert!(!is_quiet(&quiet, at(7, 9, 0)));
+
+        // 无效时间与未启用
+        quiet.end = "25:00".to_string();
+        assert!(!is_quiet(&quiet, at(2, 12, 45)));
+        quiet.end = "13:30".to_string();
+        quiet.enabled = false;
+        assert!(!is_quiet(&quiet, at(2, 12, 45)));
+    }
+}
@@ -0,0 +1,282 @@
+// 预分类规则 - 按前台应用、窗口标题或网站域名的正则表达式直接给帧归类：
+// 会话内所有活跃帧都命中规则时不调用 LLM，直接按规则生成时间线卡片；
+// 部分命中时以规则卡片覆盖对应时段，已知应用的分类结果保持稳定
+
+use super::idle::{period_card, replace_periods, IdlePeriod};
+use super::ScreenFrame;
+use crate::llm::plugin::TimelineCard;
+use crate::storage::ClassificationRuleRecord;
+use chrono::{DateTime, Utc};
+use regex::{Regex, RegexBuilder};
+use tracing::warn;
+
+/// 规则可匹配的字段：应用名称、窗口标题、网站域名
+pub const RULE_MATCH_FIELDS: [&str; 3] = ["app", "title", "url"];
+
+/// 编译后的预分类规则
+#[derive(Debug, Clone)]
+pub struct ClassificationRule {
+    pub match_field: String,
+    pub pattern: Regex,
+    pub category: String,
+    pub subcategory: String,
+}
+
+impl ClassificationRule {
+    /// 编译规则表达式（忽略大小写）
+    pub fn new(record: &ClassificationRuleRecord) -> Result<Self, regex::Error> {
+        Ok(Self {
+            match_field: record.match_field.clone(),
+            pattern: RegexBuilder::new(&record.pattern)
+                .case_insensitive(true)
+                .build()?,
+            category: record.category.clone(),
+            subcategory: record.subcategory.clone(),
+        })
+    }
+
+    /// 编译全部规则，表达式无效的规则跳过
+    pub fn compile(records: &[ClassificationRuleRecord]) -> Vec<Self> {
+        records
+            .iter()
+            .filter_map(|record| match Self::new(record) {
+                Ok(rule) => Some(rule),
+                Err(e) => {
+                    warn!("预分类规则 {:?} 无效，已跳过: {}", record.pattern, e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// 帧是否命中规则
+    fn matches(&self, frame: &ScreenFrame) -> bool {
+        let value = match self.match_field.as_str() {
+            "app" => frame.window.as_ref().map(|w| w.app_name.as_str()),
+            "title" => frame.window.as_ref().map(|w| w.window_title.as_str()),
+            "url" => frame.domain.as_deref(),
+            _ => None,
+        };
+        value.is_some_and(|value| !value.is_empty() && self.pattern.is_match(value))
+    }
+}
+
+/// 一段连续命中同一规则的时段
+#[derive(Debug, Clone, PartialEq)]
+pub struct RulePeriod {
+    pub period: IdlePeriod,
+    /// 命中的规则序号
+    pub rule: usize,
+    /// 时段内的主要应用或网站
+    pub label: String,
+}
+
+/// 帧命中的第一条规则序号
+fn match_frame(rules: &[ClassificationRule], frame: &ScreenFrame) -> Option<usize> {
+    rules.iter().position(|rule| rule.matches(frame))
+}
+
+/// 帧的应用或网站名称（用作规则卡片标题）
+fn frame_label(rule: &ClassificationRule, frame: &ScreenFrame) -> String {
+    let domain = frame.domain.clone().filter(|domain| !domain.is_empty());
+    let app = frame.window.as_ref().map(|w| w.app_name.clone());
+    let label = if rule.match_field == "url" {
+        domain.or(app)
+    } else {
+        app.or(domain)
+    };
+    label.unwrap_or_else(|| rule.category.clone())
+}
+
+/// 是否可以完全按规则归类：至少一帧命中，且除空闲和会议帧外的所有帧都命中规则
+pub fn fully_classified(rules: &[ClassificationRule], frames: &[ScreenFrame]) -> bool {
+    if rules.is_empty() {
+        return false;
+    }
+    let active: Vec<&ScreenFrame> = frames
+        .iter()
+        .filter(|frame| frame.idle_seconds.is_none() && frame.meeting.is_none())
+        .collect();
+    !active.is_empty()
+        && active
+            .iter()
+            .all(|frame| match_frame(rules, frame).is_some())
+}
+
+/// 按命中规则的帧计算时段，相邻且命中同一规则、同一应用的帧合并
+pub fn rule_periods(
+    rules: &[ClassificationRule],
+    frames: &[ScreenFrame],
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+) -> Vec<RulePeriod> {
+    if rules.is_empty() {
+        return Vec::new();
+    }
+    let mut periods: Vec<RulePeriod> = Vec::new();
+    for (frame, seconds) in super::window::frame_durations(frames, window_end) {
+        let Some(rule) = match_frame(rules, frame) else {
+            continue;
+        };
+        let start = frame.timestamp.max(window_start);
+        let end = (frame.timestamp + chrono::Duration::seconds(seconds)).min(window_end);
+        if end <= start {
+            continue;
+        }
+        let label = frame_label(&rules[rule], frame);
+
+        match periods.last_mut() {
+            Some(last) if last.rule == rule && last.label == label && start <= last.period.end => {
+                last.period.end = last.period.end.max(end);
+            }
+            _ => periods.push(RulePeriod {
+                period: IdlePeriod { start, end },
+                rule,
+                label,
+            }),
+        }
+    }
+    periods
+}
+
+/// 时段对应的规则卡片
+fn rule_card(rules: &[ClassificationRule], period: &RulePeriod) -> TimelineCard {
+    let rule = &rules[period.rule];
+    let minutes = (period.period.end - period.period.start).num_minutes();
+    let mut card = period_card(
+        &period.period,
+        &rule.category,
+        &rule.subcategory,
+        &period.label,
+        format!("使用 {} {} 分钟", period.label, minutes),
+        &format!(
+            "该时段命中预分类规则（{}: {}），按规则归类，未进行 AI 分析",
+            rule.match_field,
+            rule.pattern.as_str()
+        ),
+    );
+    card.app_sites.primary = period.label.clone();
+    card
+}
+
+/// 完全按规则归类时的时间线卡片
+pub fn rule_cards(rules: &[ClassificationRule], periods: &[RulePeriod]) -> Vec<TimelineCard> {
+    periods
+        .iter()
+        .map(|period| rule_card(rules, period))
+        .collect()
+}
+
+/// 以规则卡片覆盖 LLM 生成的卡片（不足 1 分钟的时段保留 LLM 结果，避免卡片碎片化）
+pub fn mark_rule_cards(
+    cards: Vec<TimelineCard>,
+    rules: &[ClassificationRule],
+    periods: &[RulePeriod],
+) -> Vec<TimelineCard> {
+    let replacements = periods
+        .iter()
+        .filter(|period| (period.period.end - period.period.start).num_minutes() >= 1)
+        .map(|period| (period.period, Some(rule_card(rules, period))))
+        .collect();
+    replace_periods(cards, replacements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::window::WindowInfo;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        chrono::NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_utc()
+            + chrono::Duration::minutes(minutes)
+    }
+
+    fn frame(minutes: i64, app: &str, domain: Option<&str>) -> ScreenFrame {
+        let timestamp = at(minutes);
+        ScreenFrame {
+            timestamp,
+            file_path: format!("{}.jpg", timestamp.timestamp_millis()),
+            screen_id: 0,
+            window: Some(WindowInfo {
+                app_name: app.to_string(),
+                window_title: String::new(),
+                process_path: None,
+            }),
+            domain: domain.map(str::to_string),
+            idle_seconds: None,
+            repeat: None,
+            meeting: None,
+            focus_block: None,
+        }
+    }
+
+    fn rule(match_field: &str, pattern: &str, category: &str) -> ClassificationRuleRecord {
+        ClassificationRuleRecord {
+            id: None,
+            match_field: match_field.to_string(),
+            pattern: pattern.to_string(),
+            category: category.to_string(),
+            subcategory: "Rule".to_string(),
+            created_at: at(0),
+        }
+    }
+
+    #[test]
+    fn test_rule_periods_and_cards() {
+        let rules = ClassificationRule::compile(&[
+            rule("app", "^code$", "work"),
+            rule("url", r"youtube\.com$", "personal"),
+            rule("title", "(", "other"),
+        ]);
+        assert_eq!(rules.len(), 2);
+
+        // 0-2 分钟 VS Code，3-4 分钟浏览器看视频，5 分钟起在未知应用
+        let mut frames: Vec<ScreenFrame> = (0..3).map(|m| frame(m, "Code", None)).collect();
+        frames.extend((3..5).map(|m| frame(m, "Chrome", Some("www.youtube.com"))));
+        assert!(fully_classified(&rules, &frames));
+        frames.push(frame(5, "Figma", None));
+        assert!(!fully_classified(&rules, &frames));
+
+        let periods = rule_periods(&rules, &frames, at(0), at(8));
+        assert_eq!(
+            periods
+                .iter()
+                .map(|p| (p.period.start, p.period.end, p.rule, p.label.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (at(0), at(3), 0, "Code"),
+                (at(3), at(5), 1, "www.youtube.com"),
+            ]
+        );
+
+        let design = period_card(
+            &IdlePeriod {
+                start: at(0),
+                end: at(8),
+            },
+            "work",
+            "design",
+            "设计稿",
+            String::new(),
+            "",
+        );
+        let cards = mark_rule_cards(vec![design], &rules, &periods);
+        let summary: Vec<(&str, &str)> = cards
+            .iter()
+            .map(|card| (card.category.as_str(), card.title.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("work", "Code"),
+                ("personal", "www.youtube.com"),
+                ("work", "设计稿"),
+            ]
+        );
+        assert_eq!(cards[0].summary, "使用 Code 3 分钟");
+    }
+}
@@ -5,11 +5,15 @@
 
 use super::ScreenCapture;
 use crate::event_bus::{AppEvent, EventBus};
+use crate::llm::SessionSummary;
+use crate::storage::local_now;
 use anyhow::{anyhow, Result};
-use std::collections::{BTreeMap, HashSet, VecDeque};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
 use std::sync::{
-    atomic::{AtomicBool, AtomicU64, Ordering},
-    Arc,
+    atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering},
+    Arc, Mutex,
 };
 use tokio::time::Duration;
 use tracing::{debug, error, info, trace};
@@ -58,6 +62,28 @@ impl WindowTracker {
     }
 }
 
+/// 截屏暂停状态
+#[derive(Debug, Clone, Serialize)]
+pub struct CapturePauseState {
+    /// 是否已手动暂停
+    pub paused: bool,
+    /// 自动恢复时间（为空时需手动恢复）
+    pub resume_at: Option<DateTime<Utc>>,
+    /// 当前是否处于免打扰时段
+    pub quiet_hours: bool,
+}
+
+/// 手动会话（自定义标题）
+#[derive(Debug, Clone, Serialize)]
+pub struct ManualSession {
+    /// 会话标题
+    pub title: String,
+    /// 开始时间
+    pub start: DateTime<Utc>,
+    /// 结束时间（进行中为空）
+    pub end: Option<DateTime<Utc>>,
+}
+
 /// 截屏调度器
 pub struct CaptureScheduler {
     /// 截屏管理器
@@ -68,6 +94,16 @@ pub struct CaptureScheduler {
     session_duration: Arc<AtomicU64>,
     /// 是否启用截屏
     capture_enabled: Arc<AtomicBool>,
+    /// 暂停后自动恢复的时间（毫秒时间戳，0 为不自动恢复）
+    resume_at_ms: Arc<AtomicI64>,
+    /// 手动开始新会话的时间点（毫秒时间戳），会话时间窗在此拆分
+    session_breaks: Arc<Mutex<BTreeSet<i64>>>,
+    /// 下一帧是否开始新会话
+    new_session_requested: Arc<AtomicBool>,
+    /// 当前会话的开始时间（毫秒时间戳，0 为暂停中或尚未截屏）
+    session_started_ms: Arc<AtomicI64>,
+    /// 手动会话，时间段内的会话使用自定义标题
+    manual_sessions: Arc<Mutex<Vec<ManualSession>>>,
 }
 
 impl CaptureScheduler {
@@ -78,6 +114,11 @@ impl CaptureScheduler {
             capture_interval: Arc::new(AtomicU64::new(1)), // 默认1秒一次（1 FPS）
             session_duration: Arc::new(AtomicU64::new(15)), // 默认15分钟一个会话
             capture_enabled: Arc::new(AtomicBool::new(true)),
+            resume_at_ms: Arc::new(AtomicI64::new(0)),
+            session_breaks: Arc::new(Mutex::new(BTreeSet::new())),
+            new_session_requested: Arc::new(AtomicBool::new(false)),
+            session_started_ms: Arc::new(AtomicI64::new(0)),
+            manual_sessions: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
@@ -97,14 +138,141 @@ impl CaptureScheduler {
 
     /// 更新截屏启用状态
     pub fn set_capture_enabled(&self, enabled: bool) {
+        self.resume_at_ms.store(0, Ordering::Relaxed);
         self.capture_enabled.store(enabled, Ordering::Relaxed);
     }
 
+    /// 暂停截屏，`minutes` 为空时直到手动恢复
+    pub fn pause(&self, minutes: Option<u64>) {
+        let resume_at = minutes.map_or(0, |minutes| {
+            (local_now() + chrono::Duration::minutes(minutes.max(1) as i64)).timestamp_millis()
+        });
+        self.resume_at_ms.store(resume_at, Ordering::Relaxed);
+        self.capture_enabled.store(false, Ordering::Relaxed);
+    }
+
+    /// 从现在起开始新会话：之前的截图归入上一个会话，下一帧开始新会话
+    pub fn start_new_session(&self) -> DateTime<Utc> {
+        let now = local_now();
+        if let Ok(mut breaks) = self.session_breaks.lock() {
+            breaks.insert(now.timestamp_millis());
+        }
+        self.new_session_requested.store(true, Ordering::Relaxed);
+        now
+    }
+
+    /// 以自定义标题开始手动会话：从现在起开始新会话，手动结束前的会话都使用该标题
+    /// （已有进行中的手动会话时先将其结束）
+    pub fn start_manual_session(&self, title: String) -> ManualSession {
+        let start = self.start_new_session();
+        let session = ManualSession {
+            title,
+            start,
+            end: None,
+        };
+        if let Ok(mut sessions) = self.manual_sessions.lock() {
+            for active in sessions.iter_mut().filter(|s| s.end.is_none()) {
+                active.end = Some(start);
+            }
+            sessions.push(session.clone());
+        }
+        session
+    }
+
+    /// 结束进行中的手动会话，之后的截图开始新的普通会话；没有进行中的手动会话时返回空
+    pub fn stop_manual_session(&self) -> Option<ManualSession> {
+        let mut sessions = self.manual_sessions.lock().ok()?;
+        let active = sessions.iter_mut().find(|s| s.end.is_none())?;
+        active.end = Some(self.start_new_session());
+        Some(active.clone())
+    }
+
+    /// 进行中的手动会话
+    pub fn manual_session(&self) -> Option<ManualSession> {
+        self.manual_sessions
+            .lock()
+            .ok()?
+            .iter()
+            .find(|s| s.end.is_none())
+            .cloned()
+    }
+
+    /// 当前会话的开始时间（暂停中或尚未截屏时为空）
+    pub fn current_session_start(&self) -> Option<DateTime<Utc>> {
+        match self.session_started_ms.load(Ordering::Relaxed) {
+            0 => None,
+            ms => DateTime::<Utc>::from_timestamp_millis(ms),
+        }
+    }
+
+    /// 获取暂停状态（已到自动恢复时间的暂停视为已恢复）
+    pub async fn pause_state(&self) -> CapturePauseState {
+        let paused = !Self::check_auto_resume(&self.capture_enabled, &self.resume_at_ms);
+        let resume_at = match self.resume_at_ms.load(Ordering::Relaxed) {
+            0 => None,
+            ms => DateTime::<Utc>::from_timestamp_millis(ms),
+        };
+        let quiet_hours = super::quiet::is_quiet(
+            &self.capture.settings().await.quiet_hours,
+            local_now().naive_utc(),
+        );
+
+        CapturePauseState {
+            paused,
+            resume_at: resume_at.filter(|_| paused),
+            quiet_hours,
+        }
+    }
+
+    /// 到达自动恢复时间时恢复截屏，返回当前是否启用截屏
+    fn check_auto_resume(capture_enabled: &AtomicBool, resume_at_ms: &AtomicI64) -> bool {
+        if capture_enabled.load(Ordering::Relaxed) {
+            return true;
+        }
+
+        let resume_at = resume_at_ms.load(Ordering::Relaxed);
+        if resume_at == 0 || local_now().timestamp_millis() < resume_at {
+            return false;
+        }
+        if resume_at_ms
+            .compare_exchange(resume_at, 0, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+        {
+            info!("暂停时间已到，自动恢复截屏");
+            capture_enabled.store(true, Ordering::Relaxed);
+        }
+        true
+    }
+
+    /// 当前是否允许截屏（未暂停且不在免打扰时段）
+    async fn capture_allowed(
+        capture: &ScreenCapture,
+        capture_enabled: &AtomicBool,
+        resume_at_ms: &AtomicI64,
+    ) -> bool {
+        if !Self::check_auto_resume(capture_enabled, resume_at_ms) {
+            trace!("截屏已暂停，跳过截屏");
+            return false;
+        }
+        if super::quiet::is_quiet(
+            &capture.settings().await.quiet_hours,
+            local_now().naive_utc(),
+        ) {
+            trace!("免打扰时段，跳过截屏");
+            return false;
+        }
+        true
+    }
+
     /// 启动截屏任务
-    pub fn start_capture_task(self: Arc<Self>) {
+    pub fn start_capture_task(self: Arc<Self>, event_bus: Arc<EventBus>) {
         let capture = self.capture.clone();
         let interval_secs = self.capture_interval.clone();
+        let session_mins = self.session_duration.clone();
         let capture_enabled = self.capture_enabled.clone();
+        let resume_at_ms = self.resume_at_ms.clone();
+        let new_session_requested = self.new_session_requested.clone();
+        let session_started_ms = self.session_started_ms.clone();
 
         info!(
             "准备启动截屏任务，间隔: {}秒",
@@ -118,20 +286,41 @@ impl CaptureScheduler {
                 interval_secs.load(Ordering::Relaxed)
             );
 
-            // 立即执行第一次截屏（检查锁屏状态）
-            if !capture_enabled.load(Ordering::Relaxed) {
-                trace!("截屏已暂停，跳过初始截屏");
+            // 最近一次截图所在的时间窗，用于发现新会话的开始
+            let mut current_window = None;
+            // 是否因长时间无输入、手动暂停或免打扰时段停止了截屏，恢复后的第一帧开始新会话
+            let mut paused = false;
+
+            // 立即执行第一次截屏（检查暂停、免打扰与锁屏状态）
+            if !CaptureScheduler::capture_allowed(&capture, &capture_enabled, &resume_at_ms).await {
+                paused = true;
             } else if super::ScreenCapture::is_screen_locked() {
                 trace!("系统锁屏中，跳过初始截屏");
             } else {
                 match capture.capture_frame().await {
                     Ok(frame) => {
                         trace!("初始截屏成功: {}", frame.timestamp);
+                        CaptureScheduler::notify_session_start(
+                            &event_bus,
+                            &mut current_window,
+                            &session_started_ms,
+                            &frame,
+                            session_mins.load(Ordering::Relaxed).max(1),
+                            new_session_requested.swap(false, Ordering::Relaxed),
+                        );
                     }
                     Err(e) => {
                         // 黑屏不是真正的错误，只记录trace级别日志
                         if e.to_string().contains("黑屏") {
                             debug!("初始截屏检测到黑屏，已跳过");
+                        } else if e.to_string().contains("重复") {
+                            debug!("初始截屏与上一帧重复，已跳过");
+                        } else if e.to_string().contains("空闲") {
+                            debug!("初始截屏时用户空闲，已跳过");
+                            paused = true;
+                        } else if e.to_string().contains("会议") {
+                            debug!("初始截屏时检测到会议，已跳过");
+                            paused = true;
                         } else {
                             error!("初始截屏失败: {}", e);
                         }
@@ -143,8 +332,11 @@ impl CaptureScheduler {
                 let next_interval = interval_secs.load(Ordering::Relaxed).max(1);
                 tokio::time::sleep(Duration::from_secs(next_interval)).await;
 
-                if !capture_enabled.load(Ordering::Relaxed) {
-                    trace!("截屏已暂停，跳过截屏");
+                if !CaptureScheduler::capture_allowed(&capture, &capture_enabled, &resume_at_ms)
+                    .await
+                {
+                    paused = true;
+                    session_started_ms.store(0, Ordering::Relaxed);
                     continue;
                 }
 
@@ -157,16 +349,37 @@ impl CaptureScheduler {
                 match capture.capture_frame().await {
                     Ok(frame) => {
                         trace!("自动截屏成功: {}", frame.timestamp);
+                        let resumed = std::mem::take(&mut paused)
+                            | new_session_requested.swap(false, Ordering::Relaxed);
+                        CaptureScheduler::notify_session_start(
+                            &event_bus,
+                            &mut current_window,
+                            &session_started_ms,
+                            &frame,
+                            session_mins.load(Ordering::Relaxed).max(1),
+                            resumed,
+                        );
                     }
                     Err(e) => {
                         // 黑屏不是真正的错误，只记录trace级别日志
                         if e.to_string().contains("黑屏") {
                             trace!("跳过黑屏图像");
+                        } else if e.to_string().contains("重复") {
+                            trace!("跳过重复画面");
+                        } else if e.to_string().contains("空闲") {
+                            trace!("用户空闲，暂停截屏");
+                            paused = true;
+                        } else if e.to_string().contains("会议") {
+                            trace!("会议中，暂停截屏");
+                            paused = true;
                         } else {
                             error!("自动截屏失败: {}", e);
                         }
                     }
                 }
+                if paused {
+                    session_started_ms.store(0, Ordering::Relaxed);
+                }
             }
         });
     }
@@ -175,6 +388,8 @@ impl CaptureScheduler {
     pub fn start_session_task(self: Arc<Self>, event_bus: Arc<EventBus>) {
         let capture = self.capture.clone();
         let session_mins = self.session_duration.clone();
+        let session_breaks = self.session_breaks.clone();
+        let manual_sessions = self.manual_sessions.clone();
 
         tokio::task::spawn(async move {
             // 使用 WindowTracker 限制内存使用，最多保留 1000 个窗口记录
@@ -189,6 +404,8 @@ impl CaptureScheduler {
                     event_bus.clone(),
                     session_mins.load(Ordering::Relaxed).max(1),
                     &mut processed_windows,
+                    &session_breaks,
+                    &manual_sessions,
                 )
                 .await
                 {
@@ -205,7 +422,7 @@ impl CaptureScheduler {
         info!("启动截屏调度器（事件驱动模式）...");
 
         // 启动截屏任务
-        self.clone().start_capture_task();
+        self.clone().start_capture_task(event_bus.clone());
 
         // 启动会话处理任务
         self.start_session_task(event_bus);
@@ -213,11 +430,49 @@ impl CaptureScheduler {
         info!("所有调度任务已启动");
     }
 
+    /// 截图落入新的时间窗，或空闲/手动暂停/免打扰结束后恢复截屏、手动开始新会话时发布SessionStarted事件
+    fn notify_session_start(
+        event_bus: &EventBus,
+        current_window: &mut Option<i64>,
+        session_started_ms: &AtomicI64,
+        frame: &super::ScreenFrame,
+        session_duration: u64,
+        resumed: bool,
+    ) {
+        let bucket_start_ms = window_bucket(
+            frame.timestamp.timestamp_millis(),
+            session_duration as i64 * 60_000,
+        );
+        let (session_id, window_start) = if *current_window != Some(bucket_start_ms) {
+            let Some(window_start) =
+                chrono::DateTime::<chrono::Utc>::from_timestamp_millis(bucket_start_ms)
+            else {
+                return;
+            };
+            (bucket_start_ms, window_start)
+        } else if resumed {
+            // 同一时间窗内离开后回来，会话从恢复后的第一帧开始（与scan_pending_sessions的拆分一致）
+            (frame.timestamp.timestamp_millis(), frame.timestamp)
+        } else {
+            return;
+        };
+        *current_window = Some(bucket_start_ms);
+        session_started_ms.store(session_id, Ordering::Relaxed);
+
+        debug!("新会话开始: {} (session_id: {})", window_start, session_id);
+        event_bus.publish(AppEvent::SessionStarted {
+            session_id,
+            window_start,
+        });
+    }
+
     async fn scan_pending_sessions(
         capture: Arc<ScreenCapture>,
         event_bus: Arc<EventBus>,
         session_duration: u64,
         processed_windows: &mut WindowTracker,
+        session_breaks: &Mutex<BTreeSet<i64>>,
+        manual_sessions: &Mutex<Vec<ManualSession>>,
     ) -> Result<()> {
         use chrono::{TimeZone, Utc};
 
@@ -242,7 +497,7 @@ impl CaptureScheduler {
             }
 
             let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
-            if !extension.eq_ignore_ascii_case("jpg") {
+            if !super::format::is_frame_extension(extension) {
                 continue;
             }
 
@@ -250,7 +505,7 @@ impl CaptureScheduler {
                 continue;
             };
 
-            let Ok(timestamp_ms) = stem.parse::<i64>() else {
+            let Some((timestamp_ms, screen_id)) = super::parse_frame_file_stem(stem) else {
                 trace!("无法解析文件名中的时间戳: {}", stem);
                 continue;
             };
@@ -263,10 +518,16 @@ impl CaptureScheduler {
             let frame = super::ScreenFrame {
                 timestamp,
                 file_path: path.to_string_lossy().to_string(),
-                screen_id: 0,
+                screen_id,
+                window: None,
+                domain: None,
+                idle_seconds: None,
+                repeat: None,
+                meeting: None,
+                focus_block: None,
             };
 
-            let bucket = (timestamp_ms / interval_ms) * interval_ms;
+            let bucket = window_bucket(timestamp_ms, interval_ms);
             grouped.entry(bucket).or_default().push(frame);
         }
 
@@ -275,6 +536,7 @@ impl CaptureScheduler {
             return Ok(());
         }
 
+        let settings = capture.settings().await;
         let now_ms = crate::storage::local_now().timestamp_millis();
         let cutoff_ms = now_ms - 30_000; // 留出缓冲，避免处理仍在写入的区间
 
@@ -301,33 +563,89 @@ impl CaptureScheduler {
                 continue;
             };
 
-            let window = SessionWindow {
-                start: window_start,
-                end: window_end,
+            // 长时间离开时按空闲记录拆分为多个会话
+            let windows = if settings.idle_detection {
+                super::idle::attach_idle_marks(&frames_dir, &mut frames).await;
+                super::idle::active_windows(
+                    &frames,
+                    window_start,
+                    window_end,
+                    settings.idle_pause_seconds(),
+                )
+            } else {
+                vec![SessionWindow {
+                    start: window_start,
+                    end: window_end,
+                    title: None,
+                }]
             };
+            // 手动开始新会话的时间点再拆分一次
+            let breaks: Vec<i64> = session_breaks
+                .lock()
+                .map(|breaks| {
+                    breaks
+                        .range(bucket_start_ms..bucket_end_ms)
+                        .copied()
+                        .collect()
+                })
+                .unwrap_or_default();
+            let mut windows = split_at_breaks(windows, &frames, &breaks);
+            // 手动会话时间段内的会话使用自定义标题
+            if let Ok(sessions) = manual_sessions.lock() {
+                for window in windows.iter_mut() {
+                    window.title = manual_title(&sessions, window.start);
+                }
+            }
+            if windows.is_empty() {
+                info!(
+                    "时间段 {} - {} 内用户一直离开，跳过分析",
+                    window_start, window_end
+                );
+            }
 
-            let frame_count = frames.len();
-            info!(
-                "发现待处理会话: {} - {}, 帧数 {}",
-                window.start, window.end, frame_count
-            );
-
-            // 发布SessionCompleted事件（事件驱动架构）
-            // 不再直接调用processor，而是发布事件让订阅者处理
-            event_bus.publish(AppEvent::SessionCompleted {
-                session_id: bucket_start_ms, // 使用bucket_start_ms作为临时session_id
-                frame_count,
-                window_start: window.start,
-                window_end: window.end,
-            });
+            for window in windows {
+                // 第一段沿用bucket_start_ms作为临时session_id，后续分段使用其开始时间
+                let session_id = if window.start == window_start {
+                    bucket_start_ms
+                } else {
+                    window.start.timestamp_millis()
+                };
+                let frame_count = frames
+                    .iter()
+                    .filter(|f| f.timestamp >= window.start && f.timestamp < window.end)
+                    .count();
+                info!(
+                    "发现待处理会话: {} - {}, 帧数 {}",
+                    window.start, window.end, frame_count
+                );
+
+                // 发布SessionCompleted事件（事件驱动架构）
+                // 不再直接调用processor，而是发布事件让订阅者处理
+                event_bus.publish(AppEvent::SessionCompleted {
+                    session_id,
+                    frame_count,
+                    window_start: window.start,
+                    window_end: window.end,
+                    title: window.title.clone(),
+                });
+
+                info!(
+                    "会话事件已发布: {} - {} (session_id: {})",
+                    window.start, window.end, session_id
+                );
+            }
 
-            // 标记为已处理
+            // 标记为已处理，之前的拆分点不再需要
             processed_windows.insert(bucket_start_ms);
-
-            info!(
-                "会话事件已发布: {} - {} (session_id: {})",
-                window.start, window.end, bucket_start_ms
-            );
+            if let Ok(mut breaks) = session_breaks.lock() {
+                breaks.retain(|ms| *ms >= bucket_end_ms);
+            }
+            if let Ok(mut sessions) = manual_sessions.lock() {
+                sessions.retain(|s| {
+                    s.end
+                        .is_none_or(|end| end.timestamp_millis() > bucket_end_ms)
+                });
+            }
 
             // 注意：不再在这里清理图片，由事件订阅者（LLMProcessor）处理后决定是否清理
         }
@@ -336,24 +654,85 @@ impl CaptureScheduler {
     }
 }
 
+/// 时间戳所在时间窗的起点（毫秒），与SessionCompleted的session_id一致
+fn window_bucket(timestamp_ms: i64, interval_ms: i64) -> i64 {
+    (timestamp_ms / interval_ms) * interval_ms
+}
+
+/// 在手动开始新会话的时间点拆分会话时间窗：拆分点之前有截图时结束当前会话，
+/// 新会话从拆分点之后的第一帧开始（与SessionStarted的session_id一致）
+fn split_at_breaks(
+    windows: Vec<SessionWindow>,
+    frames: &[super::ScreenFrame],
+    breaks: &[i64],
+) -> Vec<SessionWindow> {
+    let mut split = Vec::with_capacity(windows.len());
+    for window in windows {
+        let mut start = window.start;
+        for &break_ms in breaks {
+            let Some(at) = DateTime::<Utc>::from_timestamp_millis(break_ms) else {
+                continue;
+            };
+            if at <= start || at >= window.end {
+                continue;
+            }
+            let has_before = frames
+                .iter()
+                .any(|frame| frame.timestamp >= start && frame.timestamp < at);
+            let Some(next) = frames
+                .iter()
+                .map(|frame| frame.timestamp)
+                .filter(|timestamp| *timestamp >= at && *timestamp < window.end)
+                .min()
+            else {
+                continue;
+            };
+            if has_before {
+                split.push(SessionWindow {
+                    start,
+                    end: at,
+                    title: None,
+                });
+                start = next;
+            }
+        }
+        split.push(SessionWindow {
+            start,
+            end: window.end,
+            title: None,
+        });
+    }
+    split
+}
+
+/// 开始时间落在手动会话时间段内的会话标题
+fn manual_title(sessions: &[ManualSession], start: DateTime<Utc>) -> Option<String> {
+    sessions
+        .iter()
+        .find(|s| s.start <= start && s.end.is_none_or(|end| start < end))
+        .map(|s| s.title.clone())
+}
+
 /// 会话时间窗
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SessionWindow {
     /// 会话开始时间
     pub start: chrono::DateTime<chrono::Utc>,
     /// 会话结束时间
     pub end: chrono::DateTime<chrono::Utc>,
+    /// 手动会话的自定义标题（为空时使用分析生成的标题）
+    pub title: Option<String>,
 }
 
 /// 会话处理器trait
 #[async_trait::async_trait]
 pub trait SessionProcessor {
-    /// 处理一个会话的截图
+    /// 处理一个会话的截图，返回会话总结
     async fn process_session(
         &self,
         frames: Vec<super::ScreenFrame>,
         window: SessionWindow,
-    ) -> Result<()>;
+    ) -> Result<SessionSummary>;
 }
 
 /// 默认会话处理器（用于测试）
@@ -365,7 +744,7 @@ impl SessionProcessor for DefaultSessionProcessor {
         &self,
         frames: Vec<super::ScreenFrame>,
         window: SessionWindow,
-    ) -> Result<()> {
+    ) -> Result<SessionSummary> {
         info!(
             "处理会话: {} 帧, 时间段 {} - {}",
             frames.len(),
@@ -373,6 +752,95 @@ impl SessionProcessor for DefaultSessionProcessor {
             window.end
         );
         // 这里将由LLMProcessor实现实际的处理逻辑
-        Ok(())
+        Ok(SessionSummary {
+            start_time: window.start,
+            end_time: window.end,
+            ..SessionSummary::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        chrono::NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_utc()
+            + chrono::Duration::minutes(minutes)
+    }
+
+    fn frame(minutes: i64) -> super::super::ScreenFrame {
+        let timestamp = at(minutes);
+        super::super::ScreenFrame {
+            timestamp,
+            file_path: format!("{}.jpg", timestamp.timestamp_millis()),
+            screen_id: 0,
+            window: None,
+            domain: None,
+            idle_seconds: None,
+            repeat: None,
+            meeting: None,
+            focus_block: None,
+        }
+    }
+
+    #[test]
+    fn test_split_at_breaks() {
+        let window = |start: DateTime<Utc>, end: DateTime<Utc>| SessionWindow {
+            start,
+            end,
+            title: None,
+        };
+        let half = |minutes: i64| at(minutes) + chrono::Duration::seconds(30);
+        let frames: Vec<_> = [0, 2, 4, 9, 11, 13].into_iter().map(frame).collect();
+        let breaks: Vec<i64> = [2, 9, 13]
+            .into_iter()
+            .map(|minutes| half(minutes).timestamp_millis())
+            .collect();
+
+        // 新会话从拆分点之后的第一帧开始；之后没有截图的拆分点不生效
+        assert_eq!(
+            split_at_breaks(vec![window(at(0), at(15))], &frames, &breaks),
+            vec![
+                window(at(0), half(2)),
+                window(at(4), half(9)),
+                window(at(11), at(15)),
+            ]
+        );
+
+        // 拆分点之前没有截图时不拆分
+        assert_eq!(
+            split_at_breaks(
+                vec![window(at(4), at(15))],
+                &frames,
+                &[half(3).timestamp_millis()]
+            ),
+            vec![window(at(4), at(15))]
+        );
+    }
+
+    #[test]
+    fn test_manual_title() {
+        let sessions = vec![
+            ManualSession {
+                title: "需求评审".to_string(),
+                start: at(2),
+                end: Some(at(9)),
+            },
+            ManualSession {
+                title: "写周报".to_string(),
+                start: at(20),
+                end: None,
+            },
+        ];
+
+        assert_eq!(manual_title(&sessions, at(0)), None);
+        assert_eq!(manual_title(&sessions, at(4)).as_deref(), Some("需求评审"));
+        assert_eq!(manual_title(&sessions, at(9)), None);
+        assert_eq!(manual_title(&sessions, at(45)).as_deref(), Some("写周报"));
     }
 }
@@ -0,0 +1,464 @@
+// 前台窗口采集 - 截屏时记录前台应用名、窗口标题与进程路径，
+// 按天写入帧目录下的 `window_activity_<日期>.jsonl`（随帧文件按保留期清理），
+// 会话分析时按时间戳关联回帧，并汇总出各应用的使用时长
+
+use super::ScreenFrame;
+use crate::storage::AppUsageRecord;
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 窗口记录文件名前缀
+const WINDOW_LOG_PREFIX: &str = "window_activity_";
+
+/// 单帧最多计入的时长（秒），避免截屏中断期间的时长被算到前一帧
+pub const MAX_FRAME_SECONDS: i64 = 60;
+
+/// 前台窗口信息
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowInfo {
+    /// 应用名称（如 Code、Google Chrome）
+    pub app_name: String,
+    /// 窗口标题
+    pub window_title: String,
+    /// 进程可执行文件路径
+    pub process_path: Option<String>,
+}
+
+/// 窗口记录文件中的一行
+#[derive(Debug, Serialize, Deserialize)]
+struct WindowRecord {
+    timestamp_ms: i64,
+    #[serde(flatten)]
+    info: WindowInfo,
+}
+
+/// 平台接口返回的前台窗口
+struct ForegroundWindow {
+    pid: u32,
+    title: String,
+    /// 平台直接给出的应用名（macOS），其他平台取进程名
+    app_name: Option<String>,
+}
+
+/// 前台窗口采集器（复用 sysinfo 进程表查询进程名与路径）
+pub struct WindowCollector {
+    system: Mutex<sysinfo::System>,
+}
+
+impl Default for WindowCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WindowCollector {
+    pub fn new() -> Self {
+        Self {
+            system: Mutex::new(sysinfo::System::new()),
+        }
+    }
+
+    /// 获取当前前台窗口（阻塞调用，不支持的平台或获取失败时返回 None）
+    pub fn active_window(&self) -> Option<WindowInfo> {
+        use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, UpdateKind};
+
+        let window = foreground_window()?;
+        let pid = Pid::from_u32(window.pid);
+
+        let (process_name, process_path) = {
+            let mut system = self.system.lock().ok()?;
+            system.refresh_processes_specifics(
+                ProcessesToUpdate::Some(&[pid]),
+                ProcessRefreshKind::new().with_exe(UpdateKind::OnlyIfNotSet),
+            );
+            match system.process(pid) {
+                Some(process) => (
+                    Some(process.name().to_string_lossy().to_string()),
+                    process
+                        .exe()
+                        .map(|path| path.to_string_lossy().replace('\\', "/")),
+                ),
+                None => (None, None),
+            }
+        };
+
+        let app_name = window
+            .app_name
+            .or_else(|| process_name.map(|name| display_app_name(&name)))
+            .filter(|name| !name.trim().is_empty())?;
+
+        Some(WindowInfo {
+            app_name: app_name.trim().to_string(),
+            window_title: window.title.trim().to_string(),
+            process_path,
+        })
+    }
+}
+
+/// 进程名去掉 Windows 可执行文件后缀
+fn display_app_name(process_name: &str) -> String {
+    let trimmed = process_name.trim();
+    match trimmed.len().checked_sub(4) {
+        Some(split)
+            if trimmed.is_char_boundary(split) && trimmed[split..].eq_ignore_ascii_case(".exe") =>
+        {
+            trimmed[..split].to_string()
+        }
+        _ => trimmed.to_string(),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn foreground_window() -> Option<ForegroundWindow> {
+    // 需要“辅助功能”权限才能读取窗口标题，未授权时标题为空
+    const SCRIPT: &str = r#"
+        tell application "System Events"
+            set frontApp to first application process whose frontmost is true
+            set appName to name of frontApp
+            set appPid to unix id of frontApp
+            set windowTitle to ""
+            try
+                set windowTitle to name of front window of frontApp
+            end try
+        end tell
+        return (appPid as text) & linefeed & appName & linefeed & windowTitle
+    "#;
+
+    let output = std::process::Command::new("osascript")
+        .arg("-e")
+        .arg(SCRIPT)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        tracing::trace!(
+            "获取前台窗口失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return None;
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut lines = text.lines();
+    let pid = lines.next()?.trim().parse().ok()?;
+    let app_name = lines.next().map(|name| name.trim().to_string());
+    let title = lines.collect::<Vec<_>>().join(" ");
+    Some(ForegroundWindow {
+        pid,
+        title,
+        app_name,
+    })
+}
+
+#[cfg(windows)]
+fn foreground_window() -> Option<ForegroundWindow> {
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetForegroundWindow() -> isize;
+        fn GetWindowTextW(hwnd: isize, text: *mut u16, max_count: i32) -> i32;
+        fn GetWindowThreadProcessId(hwnd: isize, process_id: *mut u32) -> u32;
+    }
+
+    // SAFETY: 仅调用只读的 user32 接口，缓冲区长度与传入的 max_count 一致
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd == 0 {
+            return None;
+        }
+
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, &mut pid);
+        if pid == 0 {
+            return None;
+        }
+
+        let mut buffer = [0u16; 512];
+        let len = GetWindowTextW(hwnd, buffer.as_mut_ptr(), buffer.len() as i32);
+        let title = String::from_utf16_lossy(&buffer[..len.max(0) as usize]);
+
+        Some(ForegroundWindow {
+            pid,
+            title,
+            app_name: None,
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn foreground_window() -> Option<ForegroundWindow> {
+    // 依赖 xdotool（X11）；Wayland 下无法获取其他应用的窗口，返回 None
+    let output = std::process::Command::new("xdotool")
+        .args(["getactivewindow", "getwindowpid", "getwindowname"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        tracing::trace!(
+            "获取前台窗口失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return None;
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut lines = text.lines();
+    let pid = lines.next()?.trim().parse().ok()?;
+    let title = lines.collect::<Vec<_>>().join(" ");
+    Some(ForegroundWindow {
+        pid,
+        title,
+        app_name: None,
+    })
+}
+
+#[cfg(not(any(target_os = "macos", windows, target_os = "linux")))]
+fn foreground_window() -> Option<ForegroundWindow> {
+    None
+}
+
+/// 某天的窗口记录文件
+fn window_log_path(frames_dir: &Path, date: NaiveDate) -> PathBuf {
+    frames_dir.join(format!(
+        "{}{}.jsonl",
+        WINDOW_LOG_PREFIX,
+        date.format("%Y-%m-%d")
+    ))
+}
+
+/// 追加一帧的窗口记录
+pub fn append_window_record(
+    frames_dir: &Path,
+    timestamp: DateTime<Utc>,
+    info: &WindowInfo,
+) -> Result<()> {
+    let record = WindowRecord {
+        timestamp_ms: timestamp.timestamp_millis(),
+        info: info.clone(),
+    };
+    let mut line = serde_json::to_string(&record)?;
+    line.push('\n');
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(window_log_path(frames_dir, timestamp.date_naive()))?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// 读取某天的全部窗口记录（按时间排序）
+pub async fn read_window_records(
+    frames_dir: &Path,
+    date: NaiveDate,
+) -> Vec<(DateTime<Utc>, WindowInfo)> {
+    let Ok(data) = tokio::fs::read_to_string(window_log_path(frames_dir, date)).await else {
+        return Vec::new();
+    };
+    let mut records: Vec<(DateTime<Utc>, WindowInfo)> = data
+        .lines()
+        .filter_map(|line| serde_json::from_str::<WindowRecord>(line).ok())
+        .filter_map(|record| {
+            DateTime::from_timestamp_millis(record.timestamp_ms).map(|time| (time, record.info))
+        })
+        .collect();
+    records.sort_by_key(|(time, _)| *time);
+    records
+}
+
+/// 读取帧对应的窗口记录并填入 `ScreenFrame::window`（按毫秒时间戳精确匹配）
+pub async fn attach_window_info(frames_dir: &Path, frames: &mut [ScreenFrame]) {
+    let (Some(first), Some(last)) = (
+        frames.iter().map(|f| f.timestamp).min(),
+        frames.iter().map(|f| f.timestamp).max(),
+    ) else {
+        return;
+    };
+
+    let mut records: HashMap<i64, WindowInfo> = HashMap::new();
+    for date in first
+        .date_naive()
+        .iter_days()
+        .take_while(|date| *date <= last.date_naive())
+    {
+        let Ok(data) = tokio::fs::read_to_string(window_log_path(frames_dir, date)).await else {
+            continue;
+        };
+        for line in data.lines() {
+            if let Ok(record) = serde_json::from_str::<WindowRecord>(line) {
+                records.insert(record.timestamp_ms, record.info);
+            }
+        }
+    }
+
+    for frame in frames.iter_mut() {
+        if frame.window.is_none() {
+            frame.window = records.get(&frame.timestamp.timestamp_millis()).cloned();
+        }
+    }
+}
+
+/// 按时间排序帧，并计算每帧代表的时长（秒）：计到下一帧（或会话结束），单帧最多 60 秒，
+/// 跳过重复截图的帧再加上被跳过的时长
+pub fn frame_durations(
+    frames: &[ScreenFrame],
+    window_end: DateTime<Utc>,
+) -> Vec<(&ScreenFrame, i64)> {
+    let mut sorted: Vec<&ScreenFrame> = frames.iter().collect();
+    sorted.sort_by_key(|frame| frame.timestamp);
+
+    sorted
+        .iter()
+        .enumerate()
+        .map(|(index, frame)| {
+            let next = sorted
+                .get(index + 1)
+                .map(|next| next.timestamp)
+                .unwrap_or(window_end);
+            let repeated = frame.repeat.map_or(0, |repeat| {
+                (repeat.until - frame.timestamp).num_seconds().max(0)
+            });
+            let seconds = (next - frame.timestamp)
+                .num_seconds()
+                .clamp(0, MAX_FRAME_SECONDS + repeated);
+            (*frame, seconds)
+        })
+        .collect()
+}
+
+/// 按前台应用汇总会话时长（见 `frame_durations`）；没有窗口信息或已标记空闲的帧不计入
+pub fn app_breakdown(
+    session_id: i64,
+    frames: &[ScreenFrame],
+    window_end: DateTime<Utc>,
+) -> Vec<AppUsageRecord> {
+    // 应用名 -> (进程路径, 总秒数, 各窗口标题的秒数)
+    type AppTotals<'a> = (Option<&'a str>, i64, HashMap<&'a str, i64>);
+    let mut apps: HashMap<&str, AppTotals> = HashMap::new();
+    for (frame, seconds) in frame_durations(frames, window_end) {
+        let Some(window) = frame
+            .window
+            .as_ref()
+            .filter(|_| frame.idle_seconds.is_none())
+        else {
+            continue;
+        };
+
+        let entry = apps
+            .entry(window.app_name.as_str())
+            .or_insert_with(|| (None, 0, HashMap::new()));
+        if entry.0.is_none() {
+            entry.0 = window.process_path.as_deref();
+        }
+        entry.1 += seconds;
+        *entry.2.entry(window.window_title.as_str()).or_default() += seconds;
+    }
+
+    let mut records: Vec<AppUsageRecord> = apps
+        .into_iter()
+        .filter(|(_, (_, seconds, _))| *seconds > 0)
+        .map(|(app_name, (process_path, seconds, titles))| {
+            let window_title = titles
+                .into_iter()
+                .filter(|(title, _)| !title.is_empty())
+                .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0)))
+                .map(|(title, _)| title.to_string())
+                .unwrap_or_default();
+            AppUsageRecord {
+                session_id,
+                app_name: app_name.to_string(),
+                process_path: process_path.map(str::to_string),
+                window_title,
+                seconds,
+            }
+        })
+        .collect();
+
+    records.sort_by(|a, b| {
+        b.seconds
+            .cmp(&a.seconds)
+            .then_with(|| a.app_name.cmp(&b.app_name))
+    });
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(timestamp: DateTime<Utc>, app: Option<(&str, &str)>) -> ScreenFrame {
+        ScreenFrame {
+            timestamp,
+            file_path: format!("{}.jpg", timestamp.timestamp_millis()),
+            screen_id: 0,
+            domain: None,
+            idle_seconds: None,
+            repeat: None,
+            meeting: None,
+            focus_block: None,
+            window: app.map(|(app_name, title)| WindowInfo {
+                app_name: app_name.to_string(),
+                window_title: title.to_string(),
+                process_path: Some(format!("/usr/bin/{}", app_name.to_lowercase())),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_app_breakdown() {
+        let start = chrono::NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_utc();
+        let at = |seconds: i64| start + chrono::Duration::seconds(seconds);
+        let frames = vec![
+            frame(at(20), Some(("Chrome", "Rust 文档"))),
+            frame(at(0), Some(("Code", "main.rs"))),
+            frame(at(10), Some(("Code", "lib.rs"))),
+            frame(at(15), Some(("Code", "main.rs"))),
+            frame(at(30), None),
+            // 截屏中断 10 分钟，只计 60 秒
+            frame(at(40), Some(("Code", "main.rs"))),
+        ];
+
+        let records = app_breakdown(7, &frames, at(640));
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].app_name, "Code");
+        assert_eq!(records[0].seconds, 10 + 5 + 5 + 60);
+        assert_eq!(records[0].window_title, "main.rs");
+        assert_eq!(records[0].process_path.as_deref(), Some("/usr/bin/code"));
+        assert_eq!(records[1].app_name, "Chrome");
+        assert_eq!(records[1].seconds, 10);
+        assert!(records.iter().all(|record| record.session_id == 7));
+
+        assert_eq!(display_app_name("Code.EXE"), "Code");
+        assert_eq!(display_app_name("firefox"), "firefox");
+    }
+
+    #[tokio::test]
+    async fn test_window_log_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let timestamp = chrono::NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_milli_opt(23, 59, 59, 500)
+            .unwrap()
+            .and_utc();
+        let info = WindowInfo {
+            app_name: "Code".to_string(),
+            window_title: "main.rs — screen-analyzer".to_string(),
+            process_path: None,
+        };
+        append_window_record(dir.path(), timestamp, &info).unwrap();
+
+        let mut frames = vec![
+            frame(timestamp, None),
+            frame(timestamp + chrono::Duration::seconds(1), None),
+        ];
+        attach_window_info(dir.path(), &mut frames).await;
+        assert_eq!(frames[0].window.as_ref(), Some(&info));
+        assert_eq!(frames[1].window, None);
+    }
+}
@@ -0,0 +1,414 @@
+// Slack / Discord 每日总结推送 - 每天结束时把当日总结与关键指标按模板发送到各工作区的 Incoming Webhook
+
+use anyhow::{anyhow, Result};
+use chrono::{Duration, NaiveDateTime};
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+use crate::actors::LLMHandle;
+use crate::digest::{format_minutes, parse_send_time, DayMetrics};
+use crate::exporter::day_summary;
+use crate::models::{ChatPlatform, ChatSummaryConfig, ChatWorkspace};
+use crate::obsidian::compact_summary_text;
+use crate::settings::SettingsManager;
+use crate::storage::{local_now, Database};
+
+/// 默认消息模板，可用占位符：{title} {date} {sessions} {total} {focus} {focus_ratio} {categories} {summary}
+pub const DEFAULT_TEMPLATE: &str = "{title}\n会话 {sessions} 个 · 记录 {total} · 专注 {focus}（{focus_ratio}%）\n分类：{categories}\n\n{summary}";
+
+/// 消息中总结的最大长度，保持消息简洁
+const SUMMARY_MAX_LEN: usize = 600;
+
+/// Discord 单条消息的长度上限
+const DISCORD_MAX_LEN: usize = 2000;
+
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+const RETRY_DELAY_MINUTES: i64 = 15;
+
+/// 单次推送的结果
+#[derive(Debug, Default)]
+pub struct ChatPostReport {
+    pub posted: Vec<String>,
+    /// （工作区, 错误）
+    pub failed: Vec<(String, String)>,
+}
+
+impl ChatPostReport {
+    pub fn render_message(&self) -> String {
+        let mut message = format!("已推送到 {} 个工作区", self.posted.len());
+        for (name, error) in &self.failed {
+            message.push_str(&format!("\n⚠ {}: {}", name, error));
+        }
+        message
+    }
+}
+
+/// 工作区显示名称（未命名时使用平台与序号）
+pub fn workspace_label(workspace: &ChatWorkspace, index: usize) -> String {
+    match workspace.name.trim() {
+        "" => format!("{:?} #{}", workspace.platform, index + 1),
+        name => name.to_string(),
+    }
+}
+
+/// 按工作区模板渲染消息
+pub fn render_message(
+    workspace: &ChatWorkspace,
+    date: &str,
+    metrics: &DayMetrics,
+    summary: &str,
+) -> String {
+    let template = match workspace.template.trim() {
+        "" => DEFAULT_TEMPLATE,
+        template => template,
+    };
+    // Slack 文本需转义 & < >；Discord 直接使用 Markdown
+    let value = |text: &str| match workspace.platform {
+        ChatPlatform::Slack => text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;"),
+        ChatPlatform::Discord => text.to_string(),
+    };
+    let title = format!("屏幕活动日报 {}", date);
+    let title = match workspace.platform {
+        ChatPlatform::Slack => format!("*{}*", title),
+        ChatPlatform::Discord => format!("**{}**", title),
+    };
+    let categories = match metrics.categories_line() {
+        line if line.is_empty() => "-".to_string(),
+        line => line,
+    };
+
+    let message = template
+        .replace("{title}", &title)
+        .replace("{date}", date)
+        .replace("{sessions}", &metrics.session_count.to_string())
+        .replace("{total}", &format_minutes(metrics.total_minutes))
+        .replace("{focus}", &format_minutes(metrics.focus_minutes))
+        .replace("{focus_ratio}", &metrics.focus_ratio.to_string())
+        .replace("{categories}", &value(&categories))
+        .replace(
+            "{summary}",
+            &value(&compact_summary_text(summary.trim(), SUMMARY_MAX_LEN)),
+        );
+    let message = message.trim().to_string();
+
+    match workspace.platform {
+        ChatPlatform::Discord if message.chars().count() > DISCORD_MAX_LEN => {
+            let truncated: String = message.chars().take(DISCORD_MAX_LEN - 3).collect();
+            format!("{}...", truncated)
+        }
+        _ => message,
+    }
+}
+
+/// Incoming Webhook 请求体
+fn payload(platform: ChatPlatform, text: &str) -> Value {
+    match platform {
+        ChatPlatform::Slack => json!({ "text": text }),
+        // 禁止解析 @everyone 等提及，避免总结内容误触发通知
+        ChatPlatform::Discord => json!({
+            "content": text,
+            "allowed_mentions": { "parse": [] },
+        }),
+    }
+}
+
+/// Webhook 推送客户端
+pub struct ChatPoster {
+    client: Client,
+}
+
+impl ChatPoster {
+    pub fn new() -> Result<Self> {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()?;
+        Ok(Self { client })
+    }
+
+    pub async fn post(&self, workspace: &ChatWorkspace, text: &str) -> Result<()> {
+        let url = workspace.webhook_url.trim();
+        if url.is_empty() {
+            return Err(anyhow!("未配置 Webhook 地址"));
+        }
+        if !url.starts_with("https://") {
+            return Err(anyhow!("Webhook 地址需以 https:// 开头"));
+        }
+        let response = self
+            .client
+            .post(url)
+            .json(&payload(workspace.platform, text))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("返回 {}: {}", status.as_u16(), body));
+        }
+        Ok(())
+    }
+}
+
+/// 将指定日期的总结推送到给定工作区，当天没有会话时返回 None
+///
+/// 单个工作区失败不影响其他工作区，结果记入报告。
+pub async fn post_daily_summary(
+    db: Arc<Database>,
+    llm_handle: LLMHandle,
+    workspaces: &[(String, ChatWorkspace)],
+    date: &str,
+) -> Result<Option<ChatPostReport>> {
+    let sessions = db.get_sessions_by_date(date).await?;
+    if sessions.is_empty() {
+        return Ok(None);
+    }
+    let metrics = DayMetrics::from_sessions(&sessions);
+    let summary = match day_summary(db, llm_handle, date, false).await {
+        Ok(summary) => summary.summary_text,
+        Err(e) => {
+            warn!("生成 {} 的每日总结失败: {}", date, e);
+            String::new()
+        }
+    };
+
+    let poster = ChatPoster::new()?;
+    let mut report = ChatPostReport::default();
+    for (label, workspace) in workspaces {
+        let text = render_message(workspace, date, &metrics, &summary);
+        match poster.post(workspace, &text).await {
+            Ok(()) => {
+                info!("每日总结已推送到 {}", label);
+                report.posted.push(label.clone());
+            }
+            Err(e) => {
+                warn!("推送每日总结到 {} 失败: {}", label, e);
+                report.failed.push((label.clone(), e.to_string()));
+            }
+        }
+    }
+    Ok(Some(report))
+}
+
+/// 当前时刻应推送的工作区：[(名称, 工作区)]（已推送过当天总结的跳过）
+fn due_workspaces(
+    config: &ChatSummaryConfig,
+    last_posted: &HashMap<String, String>,
+    now: NaiveDateTime,
+) -> Vec<(String, ChatWorkspace)> {
+    let Some(send_time) = parse_send_time(&config.send_time) else {
+        return Vec::new();
+    };
+    if now.time() < send_time {
+        return Vec::new();
+    }
+    let today = now.date().format("%Y-%m-%d").to_string();
+    config
+        .workspaces
+        .iter()
+        .enumerate()
+        .filter(|(_, workspace)| workspace.enabled)
+        .map(|(index, workspace)| (workspace_label(workspace, index), workspace.clone()))
+        .filter(|(label, _)| last_posted.get(label) != Some(&today))
+        .collect()
+}
+
+/// 每日总结推送调度器 - 每分钟检查一次，到达推送时间后推送当天总结
+pub struct ChatSummaryScheduler {
+    settings: Arc<SettingsManager>,
+    db: Arc<Database>,
+    llm_handle: LLMHandle,
+    /// 各工作区最近推送的日期（持久化，避免重启后重复推送）
+    state_path: PathBuf,
+    /// 推送失败后的下一次重试时间
+    retry_after: Mutex<Option<NaiveDateTime>>,
+}
+
+impl ChatSummaryScheduler {
+    pub fn new(
+        settings: Arc<SettingsManager>,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        state_path: PathBuf,
+    ) -> Self {
+        Self {
+            settings,
+            db,
+            llm_handle,
+            state_path,
+            retry_after: Mutex::new(None),
+        }
+    }
+
+    /// 启动定时推送任务
+    pub fn start(self: Arc<Self>) {
+        tokio::spawn(async move {
+            info!("每日总结推送任务已启动");
+            loop {
+                if let Err(e) = self.tick().await {
+                    error!(
+                        "每日总结推送失败: {}，{} 分钟后重试",
+                        e, RETRY_DELAY_MINUTES
+                    );
+                }
+                tokio::time::sleep(CHECK_INTERVAL).await;
+            }
+        });
+    }
+
+    async fn tick(&self) -> Result<()> {
+        let config = self.settings.get().await.chat_config.unwrap_or_default();
+        if !config.enabled {
+            return Ok(());
+        }
+        let now = local_now().naive_utc();
+        let mut retry_after = self.retry_after.lock().await;
+        if retry_after.is_some_and(|time| now < time) {
+            return Ok(());
+        }
+
+        let mut last_posted = self.load_state().await;
+        let due = due_workspaces(&config, &last_posted, now);
+        if due.is_empty() {
+            return Ok(());
+        }
+
+        let date = now.date().format("%Y-%m-%d").to_string();
+        let report = post_daily_summary(self.db.clone(), self.llm_handle.clone(), &due, &date)
+            .await?
+            .unwrap_or_else(|| {
+                info!("{} 没有会话，跳过每日总结推送", date);
+                // 没有会话时视为已推送，当天不再检查
+                ChatPostReport {
+                    posted: due.iter().map(|(label, _)| label.clone()).collect(),
+                    failed: Vec::new(),
+                }
+            });
+
+        for label in report.posted {
+            last_posted.insert(label, date.clone());
+        }
+        tokio::fs::write(
+            &self.state_path,
+            serde_json::to_string_pretty(&last_posted)?,
+        )
+        .await?;
+
+        if report.failed.is_empty() {
+            *retry_after = None;
+            Ok(())
+        } else {
+            *retry_after = Some(now + Duration::minutes(RETRY_DELAY_MINUTES));
+            Err(anyhow!(
+                "{}",
+                report
+                    .failed
+                    .iter()
+                    .map(|(label, error)| format!("{}: {}", label, error))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ))
+        }
+    }
+
+    async fn load_state(&self) -> HashMap<String, String> {
+        match tokio::fs::read(&self.state_path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_default(),
+            Err(_) => HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn metrics() -> DayMetrics {
+        DayMetrics {
+            session_count: 4,
+            total_minutes: 180,
+            focus_minutes: 150,
+            focus_ratio: 83,
+            categories: vec![("工作".to_string(), 150), ("个人".to_string(), 30)],
+        }
+    }
+
+    #[test]
+    fn test_render_message() {
+        let slack = ChatWorkspace {
+            name: "team".to_string(),
+            webhook_url: "https://hooks.slack.com/services/T/B/X".to_string(),
+            ..ChatWorkspace::default()
+        };
+        assert_eq!(
+            render_message(&slack, "2024-05-12", &metrics(), "修复 <导出> 问题\n完成评审"),
+            "*屏幕活动日报 2024-05-12*\n会话 4 个 · 记录 3h · 专注 2h 30m（83%）\n分类：工作 2h 30m · 个人 30m\n\n修复 &lt;导出&gt; 问题 完成评审"
+        );
+
+        let discord = ChatWorkspace {
+            platform: ChatPlatform::Discord,
+            template: "{title} {focus_ratio}%\n{summary}".to_string(),
+            ..slack
+        };
+        assert_eq!(
+            render_message(&discord, "2024-05-12", &metrics(), ""),
+            "**屏幕活动日报 2024-05-12** 83%"
+        );
+        let long = render_message(&discord, "2024-05-12", &metrics(), &"很长".repeat(2000));
+        assert!(long.chars().count() <= DISCORD_MAX_LEN);
+        assert_eq!(
+            payload(ChatPlatform::Discord, "hi")["allowed_mentions"]["parse"],
+            json!([])
+        );
+    }
+
+    #[test]
+    fn test_due_workspaces() {
+        let config = ChatSummaryConfig {
+            enabled: true,
+            send_time: "22:00".to_string(),
+            workspaces: vec![
+                ChatWorkspace {
+                    name: "team".to_string(),
+                    ..ChatWorkspace::default()
+                },
+                ChatWorkspace {
+                    platform: ChatPlatform::Discord,
+                    ..ChatWorkspace::default()
+                },
+                ChatWorkspace {
+                    name: "paused".to_string(),
+                    enabled: false,
+                    ..ChatWorkspace::default()
+                },
+            ],
+        };
+        let at = |hour: u32| {
+            NaiveDate::from_ymd_opt(2024, 5, 12)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+        };
+
+        assert!(due_workspaces(&config, &HashMap::new(), at(21)).is_empty());
+        let labels = |due: Vec<(String, ChatWorkspace)>| {
+            due.into_iter().map(|(label, _)| label).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            labels(due_workspaces(&config, &HashMap::new(), at(22))),
+            vec!["team", "Discord #2"]
+        );
+        let posted = HashMap::from([("team".to_string(), "2024-05-12".to_string())]);
+        assert_eq!(
+            labels(due_workspaces(&config, &posted, at(23))),
+            vec!["Discord #2"]
+        );
+    }
+}
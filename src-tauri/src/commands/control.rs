@@ -491,6 +491,8 @@ pub async fn regenerate_timeline(
                             serde_json::to_string(&card.distractions).unwrap_or_default(),
                         ),
                         app_sites: serde_json::to_string(&card.app_sites).unwrap_or_default(),
+                        confidence: card.confidence.map(f64::from),
+                        heuristic: card.heuristic,
                         video_preview_path: None,
                         created_at: storage::local_now(),
                     }
@@ -81,17 +81,17 @@ pub async fn refresh_device_info(state: tauri::State<'_, AppState>) -> Result<u6
         .map_err(|e| e.to_string())
 }
 
-/// 同步 SQLite 数据到 MariaDB
+/// 同步 SQLite 数据到 MariaDB / PostgreSQL
 #[tauri::command]
 pub async fn sync_data_to_mariadb(
     state: tauri::State<'_, AppState>,
     app: tauri::AppHandle,
 ) -> Result<String, String> {
-    info!("开始同步数据到 MariaDB");
+    info!("开始同步数据到远程数据库");
 
-    // 检查当前是否为 MariaDB 模式
-    if !state.storage_domain.get_db().await?.is_mariadb() {
-        return Err("当前不是 MariaDB 模式，无法同步数据".to_string());
+    // 检查当前是否为 MariaDB / PostgreSQL 模式
+    if state.storage_domain.get_db().await?.is_sqlite() {
+        return Err("当前不是 MariaDB 或 PostgreSQL 模式，无法同步数据".to_string());
     }
 
     // 获取 SQLite 数据库路径
@@ -225,7 +225,9 @@ pub async fn test_generate_videos(
             .map(|e| e.to_ascii_lowercase())
             .unwrap_or_default();
 
-        if !matches!(ext.as_str(), "jpg" | "jpeg" | "png") {
+        if !matches!(ext.as_str(), "jpeg" | "png")
+            && !crate::capture::format::is_frame_extension(&ext)
+        {
             continue;
         }
 
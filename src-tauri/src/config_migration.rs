@@ -3,8 +3,13 @@
 use serde::{Deserialize, Serialize};
 
 use crate::models::{
-    AppConfig, CaptureSettings, DatabaseConfig, LoggerSettings, NotionConfig, ObsidianExportConfig,
-    PersistedAppConfig, UISettings,
+    AppConfig, AppLimitConfig, BackupConfig, BrowserActivityConfig, CaptureSettings,
+    ChatSummaryConfig, DatabaseConfig, DigestConfig, DistractionAlertConfig, EmbeddingConfig,
+    FocusConfig, FramePrepConfig, FrameStoreConfig, GoogleCalendarConfig, HeuristicConfig,
+    HtmlExportConfig, IssueLinkConfig, LanSyncConfig, LlmCacheConfig, LoggerSettings,
+    LogseqExportConfig, MqttConfig, NotionConfig, ObsidianExportConfig, OcrConfig,
+    PersistedAppConfig, ReviewQueueConfig, StorageBudgetConfig, SummaryQueueConfig,
+    TimeTrackingExportConfig, UISettings, WebhookExportConfig, WellbeingConfig,
 };
 
 /// 配置导出包
@@ -25,11 +30,69 @@ pub fn strip_secrets(config: &mut PersistedAppConfig) {
 
     if let Some(notion_config) = config.notion_config.as_mut() {
         notion_config.api_token.clear();
+        notion_config.s3.secret_access_key.clear();
+    }
+
+    if let Some(webhook_config) = config.webhook_config.as_mut() {
+        webhook_config.secret.clear();
+    }
+
+    if let Some(mqtt_config) = config.mqtt_config.as_mut() {
+        mqtt_config.password.clear();
+    }
+
+    if let Some(digest_config) = config.digest_config.as_mut() {
+        digest_config.password.clear();
+    }
+
+    // Webhook 地址本身即是凭据
+    if let Some(chat_config) = config.chat_config.as_mut() {
+        for workspace in &mut chat_config.workspaces {
+            workspace.webhook_url.clear();
+        }
+    }
+
+    if let Some(calendar_config) = config.calendar_config.as_mut() {
+        calendar_config.client_secret.clear();
+        calendar_config.refresh_token.clear();
+    }
+
+    if let Some(issue_link_config) = config.issue_link_config.as_mut() {
+        issue_link_config.github_token.clear();
+        issue_link_config.jira_api_token.clear();
+    }
+
+    if let Some(time_tracking_config) = config.time_tracking_config.as_mut() {
+        time_tracking_config.api_token.clear();
+    }
+
+    if let Some(embedding_config) = config.embedding_config.as_mut() {
+        embedding_config.api_key.clear();
+    }
+
+    if let Some(browser_config) = config.browser_config.as_mut() {
+        browser_config.token.clear();
+    }
+
+    if let Some(lan_sync_config) = config.lan_sync_config.as_mut() {
+        lan_sync_config.token.clear();
+    }
+
+    if let Some(backup_config) = config.backup_config.as_mut() {
+        backup_config.s3.secret_access_key.clear();
+        backup_config.webdav_password.clear();
+        backup_config.passphrase.clear();
+    }
+
+    if let Some(frame_store_config) = config.frame_store_config.as_mut() {
+        frame_store_config.s3.secret_access_key.clear();
     }
 
     if let Some(database_config) = config.database_config.as_mut() {
-        if let DatabaseConfig::MariaDB { password, .. } = database_config {
-            password.clear();
+        match database_config {
+            DatabaseConfig::MariaDB { password, .. }
+            | DatabaseConfig::PostgreSQL { password, .. } => password.clear(),
+            DatabaseConfig::SQLite { .. } => {}
         }
     }
 }
@@ -56,6 +119,106 @@ pub fn normalize_imported_config(mut config: PersistedAppConfig) -> PersistedApp
         config.obsidian_config = Some(ObsidianExportConfig::default());
     }
 
+    if config.logseq_config.is_none() {
+        config.logseq_config = Some(LogseqExportConfig::default());
+    }
+
+    if config.html_config.is_none() {
+        config.html_config = Some(HtmlExportConfig::default());
+    }
+
+    if config.webhook_config.is_none() {
+        config.webhook_config = Some(WebhookExportConfig::default());
+    }
+
+    if config.mqtt_config.is_none() {
+        config.mqtt_config = Some(MqttConfig::default());
+    }
+
+    if config.digest_config.is_none() {
+        config.digest_config = Some(DigestConfig::default());
+    }
+
+    if config.chat_config.is_none() {
+        config.chat_config = Some(ChatSummaryConfig::default());
+    }
+
+    if config.calendar_config.is_none() {
+        config.calendar_config = Some(GoogleCalendarConfig::default());
+    }
+
+    if config.issue_link_config.is_none() {
+        config.issue_link_config = Some(IssueLinkConfig::default());
+    }
+
+    if config.time_tracking_config.is_none() {
+        config.time_tracking_config = Some(TimeTrackingExportConfig::default());
+    }
+
+    if config.embedding_config.is_none() {
+        config.embedding_config = Some(EmbeddingConfig::default());
+    }
+
+    if config.ocr_config.is_none() {
+        config.ocr_config = Some(OcrConfig::default());
+    }
+
+    if config.browser_config.is_none() {
+        config.browser_config = Some(BrowserActivityConfig::default());
+    }
+
+    if config.storage_budget_config.is_none() {
+        config.storage_budget_config = Some(StorageBudgetConfig::default());
+    }
+
+    if config.summary_queue_config.is_none() {
+        config.summary_queue_config = Some(SummaryQueueConfig::default());
+    }
+
+    if config.frame_prep_config.is_none() {
+        config.frame_prep_config = Some(FramePrepConfig::default());
+    }
+
+    if config.llm_cache_config.is_none() {
+        config.llm_cache_config = Some(LlmCacheConfig::default());
+    }
+
+    if config.review_queue_config.is_none() {
+        config.review_queue_config = Some(ReviewQueueConfig::default());
+    }
+
+    if config.heuristic_config.is_none() {
+        config.heuristic_config = Some(HeuristicConfig::default());
+    }
+
+    if config.focus_config.is_none() {
+        config.focus_config = Some(FocusConfig::default());
+    }
+
+    if config.distraction_alert_config.is_none() {
+        config.distraction_alert_config = Some(DistractionAlertConfig::default());
+    }
+
+    if config.app_limit_config.is_none() {
+        config.app_limit_config = Some(AppLimitConfig::default());
+    }
+
+    if config.wellbeing_config.is_none() {
+        config.wellbeing_config = Some(WellbeingConfig::default());
+    }
+
+    if config.lan_sync_config.is_none() {
+        config.lan_sync_config = Some(LanSyncConfig::default());
+    }
+
+    if config.backup_config.is_none() {
+        config.backup_config = Some(BackupConfig::default());
+    }
+
+    if config.frame_store_config.is_none() {
+        config.frame_store_config = Some(FrameStoreConfig::default());
+    }
+
     config
 }
 
@@ -74,5 +237,30 @@ pub fn persisted_to_app_config(config: PersistedAppConfig) -> AppConfig {
         database_config: config.database_config,
         notion_config: config.notion_config,
         obsidian_config: config.obsidian_config,
+        logseq_config: config.logseq_config,
+        html_config: config.html_config,
+        webhook_config: config.webhook_config,
+        mqtt_config: config.mqtt_config,
+        digest_config: config.digest_config,
+        chat_config: config.chat_config,
+        calendar_config: config.calendar_config,
+        issue_link_config: config.issue_link_config,
+        time_tracking_config: config.time_tracking_config,
+        embedding_config: config.embedding_config,
+        ocr_config: config.ocr_config,
+        browser_config: config.browser_config,
+        storage_budget_config: config.storage_budget_config,
+        summary_queue_config: config.summary_queue_config,
+        frame_prep_config: config.frame_prep_config,
+        llm_cache_config: config.llm_cache_config,
+        review_queue_config: config.review_queue_config,
+        heuristic_config: config.heuristic_config,
+        focus_config: config.focus_config,
+        distraction_alert_config: config.distraction_alert_config,
+        app_limit_config: config.app_limit_config,
+        wellbeing_config: config.wellbeing_config,
+        lan_sync_config: config.lan_sync_config,
+        backup_config: config.backup_config,
+        frame_store_config: config.frame_store_config,
     }
 }
@@ -0,0 +1,287 @@
+// 自定义指标 - 用户以类别分钟数的简单表达式定义指标（如 `work + learning - communication * 0.5`），
+// 导出时与专注度统计一同计算，作为模板占位符 `{{metric_<名称>}}` 与 frontmatter 字段输出
+
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+
+use crate::models::CustomMetric;
+
+/// 表达式可用的变量（均为分钟数）
+pub const VARIABLES: [&str; 9] = [
+    "total",
+    "work",
+    "learning",
+    "communication",
+    "personal",
+    "idle",
+    "other",
+    "focus",
+    "distraction",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Variable(String),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, char, Box<Expr>),
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let ch = chars[index];
+        if ch.is_whitespace() {
+            index += 1;
+        } else if ch.is_ascii_digit() || ch == '.' {
+            let start = index;
+            while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.') {
+                index += 1;
+            }
+            let text: String = chars[start..index].iter().collect();
+            let value = text
+                .parse::<f64>()
+                .map_err(|_| anyhow!("无效的数字: {}", text))?;
+            tokens.push(Token::Number(value));
+        } else if ch.is_ascii_alphabetic() || ch == '_' {
+            let start = index;
+            while index < chars.len()
+                && (chars[index].is_ascii_alphanumeric() || chars[index] == '_')
+            {
+                index += 1;
+            }
+            let name: String = chars[start..index].iter().collect();
+            tokens.push(Token::Ident(name.to_lowercase()));
+        } else if "+-*/()".contains(ch) {
+            tokens.push(Token::Op(ch));
+            index += 1;
+        } else {
+            return Err(anyhow!("不支持的字符: {}", ch));
+        }
+    }
+    Ok(tokens)
+}
+
+/// 递归下降解析：expr = term (('+' | '-') term)*，term = factor (('*' | '/') factor)*，
+/// factor = '-' factor | 数字 | 变量 | '(' expr ')'
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek_op(&self) -> Option<char> {
+        match self.tokens.get(self.position) {
+            Some(Token::Op(op)) => Some(*op),
+            _ => None,
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        let mut left = self.term()?;
+        while let Some(op @ ('+' | '-')) = self.peek_op() {
+            self.position += 1;
+            left = Expr::Binary(Box::new(left), op, Box::new(self.term()?));
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<Expr> {
+        let mut left = self.factor()?;
+        while let Some(op @ ('*' | '/')) = self.peek_op() {
+            self.position += 1;
+            left = Expr::Binary(Box::new(left), op, Box::new(self.factor()?));
+        }
+        Ok(left)
+    }
+
+    fn factor(&mut self) -> Result<Expr> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| anyhow!("表达式不完整"))?;
+        self.position += 1;
+        match token {
+            Token::Number(value) => Ok(Expr::Number(value)),
+            Token::Ident(name) => {
+                if !VARIABLES.contains(&name.as_str()) {
+                    return Err(anyhow!(
+                        "未知变量: {}（可用: {}）",
+                        name,
+                        VARIABLES.join(", ")
+                    ));
+                }
+                Ok(Expr::Variable(name))
+            }
+            Token::Op('-') => Ok(Expr::Neg(Box::new(self.factor()?))),
+            Token::Op('(') => {
+                let inner = self.expr()?;
+                if self.peek_op() != Some(')') {
+                    return Err(anyhow!("缺少右括号"));
+                }
+                self.position += 1;
+                Ok(inner)
+            }
+            Token::Op(op) => Err(anyhow!("意外的运算符: {}", op)),
+        }
+    }
+}
+
+/// 解析后的指标公式
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    expr: Expr,
+}
+
+impl Formula {
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            position: 0,
+        };
+        if parser.tokens.is_empty() {
+            return Err(anyhow!("表达式为空"));
+        }
+        let expr = parser.expr()?;
+        if parser.position < parser.tokens.len() {
+            return Err(anyhow!("表达式末尾有多余内容"));
+        }
+        Ok(Self { expr })
+    }
+
+    /// 按变量取值计算（未提供的变量按 0 计），除以 0 时返回 None
+    pub fn evaluate(&self, variables: &[(&str, f64)]) -> Option<f64> {
+        fn eval(expr: &Expr, variables: &[(&str, f64)]) -> Option<f64> {
+            match expr {
+                Expr::Number(value) => Some(*value),
+                Expr::Variable(name) => Some(
+                    variables
+                        .iter()
+                        .find(|(key, _)| key == name)
+                        .map_or(0.0, |(_, value)| *value),
+                ),
+                Expr::Neg(inner) => eval(inner, variables).map(|value| -value),
+                Expr::Binary(left, op, right) => {
+                    let (left, right) = (eval(left, variables)?, eval(right, variables)?);
+                    match op {
+                        '+' => Some(left + right),
+                        '-' => Some(left - right),
+                        '*' => Some(left * right),
+                        _ => (right != 0.0).then(|| left / right),
+                    }
+                }
+            }
+        }
+        eval(&self.expr, variables)
+    }
+}
+
+/// 指标名称：字母或下划线开头，只含字母、数字与下划线
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+/// 校验自定义指标（名称合法且不重复，公式可解析）
+pub fn validate(metrics: &[CustomMetric]) -> Result<()> {
+    let mut names = HashSet::new();
+    for metric in metrics {
+        let name = metric.name.trim();
+        if !is_valid_name(name) {
+            return Err(anyhow!(
+                "指标名称「{}」无效：只能包含字母、数字与下划线，且不能以数字开头",
+                metric.name
+            ));
+        }
+        if !names.insert(name.to_lowercase()) {
+            return Err(anyhow!("指标名称「{}」重复", name));
+        }
+        Formula::parse(&metric.expression).map_err(|e| anyhow!("指标「{}」: {}", name, e))?;
+    }
+    Ok(())
+}
+
+/// 计算全部指标，返回（名称，取值）；公式无效的指标跳过
+pub fn evaluate_all(
+    metrics: &[CustomMetric],
+    variables: &[(&str, f64)],
+) -> Vec<(String, Option<f64>)> {
+    metrics
+        .iter()
+        .filter_map(|metric| {
+            let formula = Formula::parse(&metric.expression)
+                .map_err(|e| tracing::warn!("自定义指标 {} 公式无效: {}", metric.name, e))
+                .ok()?;
+            Some((metric.name.trim().to_string(), formula.evaluate(variables)))
+        })
+        .collect()
+}
+
+/// 输出格式：保留一位小数，整数不带小数部分，无法计算时为 "-"
+pub fn format_value(value: Option<f64>) -> String {
+    match value {
+        Some(value) => {
+            let rounded = (value * 10.0).round() / 10.0;
+            if rounded.fract() == 0.0 {
+                format!("{:.0}", rounded)
+            } else {
+                format!("{:.1}", rounded)
+            }
+        }
+        None => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metric(name: &str, expression: &str) -> CustomMetric {
+        CustomMetric {
+            name: name.to_string(),
+            expression: expression.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_formula_precedence_and_variables() {
+        let variables = [("work", 240.0), ("learning", 60.0), ("communication", 90.0)];
+        let formula = Formula::parse("work + learning - Communication*0.5").unwrap();
+        assert_eq!(formula.evaluate(&variables), Some(255.0));
+        let formula = Formula::parse("-(work - learning) / 2 + idle").unwrap();
+        assert_eq!(formula.evaluate(&variables), Some(-90.0));
+        assert_eq!(
+            Formula::parse("work / idle").unwrap().evaluate(&variables),
+            None
+        );
+
+        assert!(Formula::parse("work +").is_err());
+        assert!(Formula::parse("(work").is_err());
+        assert!(Formula::parse("work learning").is_err());
+        assert!(Formula::parse("coding * 2").is_err());
+        assert_eq!(format_value(Some(12.345)), "12.3");
+        assert_eq!(format_value(Some(255.0)), "255");
+    }
+
+    #[test]
+    fn test_validate_metric_names() {
+        assert!(validate(&[metric("deep_work", "work + learning")]).is_ok());
+        assert!(validate(&[metric("2x", "work")]).is_err());
+        assert!(validate(&[metric("deep work", "work")]).is_err());
+        assert!(validate(&[metric("a", "work"), metric("A", "idle")]).is_err());
+        assert!(validate(&[metric("a", "work *")]).is_err());
+    }
+}
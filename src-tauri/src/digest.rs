@@ -0,0 +1,712 @@
+// 邮件摘要 - 按计划将每日/每周总结渲染为 HTML 邮件（内联指标）并通过 SMTP 发送
+
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+use crate::actors::LLMHandle;
+use crate::exporter::{day_summary, session_minutes};
+use crate::html::escape_html;
+use crate::llm::{ActivityCategory, ActivityTag};
+use crate::models::{DigestConfig, ObsidianExportConfig, PersistedAppConfig, SmtpSecurity};
+use crate::obsidian::{compact_summary_text, ObsidianExporter};
+use crate::settings::SettingsManager;
+use crate::storage::{local_now, Database, Session};
+
+/// 检查是否到达发送时间的间隔
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// 发送失败后等待多久再重试
+const RETRY_DELAY_MINUTES: i64 = 15;
+
+/// 周报中每日总结的最大长度
+const DAY_HIGHLIGHT_LEN: usize = 140;
+
+/// 摘要类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestKind {
+    Daily,
+    Weekly,
+}
+
+/// 渲染好的摘要邮件
+#[derive(Debug, Clone)]
+pub struct DigestEmail {
+    pub subject: String,
+    pub html: String,
+    /// 不支持 HTML 的客户端显示的纯文本版本
+    pub text: String,
+}
+
+/// 周报邮件所需的汇总指标
+#[derive(Debug, Clone)]
+pub struct WeekDigestData {
+    pub week_label: String,
+    pub week_start: String,
+    pub week_end: String,
+    pub session_count: i64,
+    pub total_minutes: i64,
+    pub avg_session_minutes: i64,
+    pub focus_minutes: i64,
+    pub focus_ratio: i64,
+    pub productivity_score: i64,
+    pub top_categories: String,
+    /// 每天的总结摘录（日期, 摘录）
+    pub days: Vec<(String, String)>,
+}
+
+/// 生成指定日期（或其所在周）的摘要邮件，没有会话时返回 None
+pub async fn build_digest(
+    kind: DigestKind,
+    db: Arc<Database>,
+    llm_handle: LLMHandle,
+    app_config: &PersistedAppConfig,
+    date: &str,
+) -> Result<Option<DigestEmail>> {
+    match kind {
+        DigestKind::Daily => build_daily_digest(db, llm_handle, date).await,
+        DigestKind::Weekly => {
+            build_weekly_digest(
+                &db,
+                app_config.obsidian_config.clone().unwrap_or_default(),
+                date,
+            )
+            .await
+        }
+    }
+}
+
+async fn build_daily_digest(
+    db: Arc<Database>,
+    llm_handle: LLMHandle,
+    date: &str,
+) -> Result<Option<DigestEmail>> {
+    let sessions = db.get_sessions_by_date(date).await?;
+    if sessions.is_empty() {
+        return Ok(None);
+    }
+    // 总结生成失败时仍发送指标与会话列表
+    let summary_text = match day_summary(db, llm_handle, date, false).await {
+        Ok(summary) => summary.summary_text,
+        Err(e) => {
+            warn!("生成 {} 的每日总结失败: {}", date, e);
+            String::new()
+        }
+    };
+    Ok(Some(render_daily_digest(date, &summary_text, &sessions)))
+}
+
+async fn build_weekly_digest(
+    db: &Database,
+    obsidian_config: ObsidianExportConfig,
+    date: &str,
+) -> Result<Option<DigestEmail>> {
+    let week = ObsidianExporter::new(obsidian_config)
+        .week_summary(db, date)
+        .await?;
+    if week.total_sessions == 0 {
+        return Ok(None);
+    }
+
+    let week_start = NaiveDate::parse_from_str(&week.week_start, "%Y-%m-%d")
+        .map_err(|_| anyhow!("日期格式错误: {}", week.week_start))?;
+    let mut days = Vec::new();
+    for offset in 0..7 {
+        let day = (week_start + Duration::days(offset))
+            .format("%Y-%m-%d")
+            .to_string();
+        if let Ok(Some(summary)) = db.get_day_summary(&day).await {
+            days.push((
+                day,
+                compact_summary_text(&summary.summary_text, DAY_HIGHLIGHT_LEN),
+            ));
+        }
+    }
+
+    Ok(Some(render_weekly_digest(&WeekDigestData {
+        week_label: week.week_label.clone(),
+        week_start: week.week_start.clone(),
+        week_end: week.week_end.clone(),
+        session_count: i64::from(week.total_sessions),
+        total_minutes: i64::from(week.total_minutes),
+        avg_session_minutes: i64::from(week.avg_session_minutes),
+        focus_minutes: week.focus_minutes(),
+        focus_ratio: week.focus_ratio(),
+        productivity_score: week.productivity_score(),
+        top_categories: week.top_categories.clone(),
+        days,
+    })))
+}
+
+/// 会话的主分类（第一个标签）
+fn main_category(session: &Session) -> Option<ActivityCategory> {
+    serde_json::from_str::<Vec<ActivityTag>>(&session.tags)
+        .ok()?
+        .into_iter()
+        .next()
+        .map(|tag| tag.category)
+}
+
+/// 单日指标（邮件日报与聊天机器人消息共用）
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayMetrics {
+    pub session_count: usize,
+    pub total_minutes: i64,
+    /// 专注时长（工作 + 学习）
+    pub focus_minutes: i64,
+    /// 专注占比（%）
+    pub focus_ratio: i64,
+    /// 各分类时长（中文名, 分钟），按时长降序
+    pub categories: Vec<(String, i64)>,
+}
+
+impl DayMetrics {
+    pub fn from_sessions(sessions: &[Session]) -> Self {
+        let total_minutes: i64 = sessions.iter().map(session_minutes).sum();
+        let mut category_minutes: BTreeMap<String, i64> = BTreeMap::new();
+        let mut focus_minutes = 0;
+        for session in sessions {
+            let minutes = session_minutes(session);
+            let category = main_category(session).unwrap_or(ActivityCategory::Other);
+            if matches!(
+                category,
+                ActivityCategory::Work | ActivityCategory::Learning
+            ) {
+                focus_minutes += minutes;
+            }
+            *category_minutes
+                .entry(category.to_chinese().to_string())
+                .or_default() += minutes;
+        }
+        let mut categories = category_minutes.into_iter().collect::<Vec<_>>();
+        categories.sort_by_key(|(_, minutes)| std::cmp::Reverse(*minutes));
+
+        Self {
+            session_count: sessions.len(),
+            total_minutes,
+            focus_minutes,
+            focus_ratio: ratio(focus_minutes, total_minutes),
+            categories,
+        }
+    }
+
+    /// 分类时长，如 "工作 2h · 个人 1h"
+    pub fn categories_line(&self) -> String {
+        self.categories
+            .iter()
+            .map(|(label, minutes)| format!("{} {}", label, format_minutes(*minutes)))
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+}
+
+/// 渲染日报邮件：指标卡片、分类时长、当日总结与会话列表
+pub fn render_daily_digest(date: &str, summary_text: &str, sessions: &[Session]) -> DigestEmail {
+    let day = DayMetrics::from_sessions(sessions);
+    let metrics = [
+        ("会话", day.session_count.to_string()),
+        ("记录时长", format_minutes(day.total_minutes)),
+        ("专注时长", format_minutes(day.focus_minutes)),
+        ("专注占比", format!("{}%", day.focus_ratio)),
+    ];
+
+    let mut body = render_metrics(&metrics);
+    let mut text = format!("屏幕活动日报 {}\n\n", date);
+    for (label, value) in &metrics {
+        text.push_str(&format!("{}: {}\n", label, value));
+    }
+
+    if !day.categories.is_empty() {
+        let categories = day.categories_line();
+        body.push_str(&section_title("分类时长"));
+        body.push_str(&format!(
+            "<p style=\"margin:0;line-height:1.8;\">{}</p>\n",
+            escape_html(&categories)
+        ));
+        text.push_str(&format!("分类时长: {}\n", categories));
+    }
+
+    if !summary_text.trim().is_empty() {
+        body.push_str(&section_title("当日总结"));
+        body.push_str(&format!(
+            "<p style=\"margin:0;line-height:1.6;white-space:pre-wrap;\">{}</p>\n",
+            escape_html(summary_text.trim())
+        ));
+        text.push_str(&format!("\n当日总结\n{}\n", summary_text.trim()));
+    }
+
+    body.push_str(&section_title("会话"));
+    body.push_str("<ul style=\"margin:0;padding-left:20px;line-height:1.8;\">\n");
+    text.push_str("\n会话\n");
+    for session in sessions {
+        let range = format!(
+            "{}-{}",
+            session.start_time.format("%H:%M"),
+            session.end_time.format("%H:%M")
+        );
+        let title = match session.title.trim() {
+            "" => "未命名会话",
+            title => title,
+        };
+        body.push_str(&format!(
+            "<li><span style=\"color:#909399;\">{}</span> {}</li>\n",
+            range,
+            escape_html(title)
+        ));
+        text.push_str(&format!("- {} {}\n", range, title));
+    }
+    body.push_str("</ul>\n");
+
+    let title = format!("屏幕活动日报 {}", date);
+    DigestEmail {
+        html: render_email(&title, &body),
+        subject: title,
+        text,
+    }
+}
+
+/// 渲染周报邮件：指标卡片、主要分类与每日总结摘录
+pub fn render_weekly_digest(week: &WeekDigestData) -> DigestEmail {
+    let metrics = [
+        ("会话", week.session_count.to_string()),
+        ("记录时长", format_minutes(week.total_minutes)),
+        ("专注占比", format!("{}%", week.focus_ratio)),
+        ("生产力评分", week.productivity_score.to_string()),
+    ];
+
+    let mut body = format!(
+        "<p style=\"margin:0 0 12px;color:#909399;\">{} ~ {} · 平均每个会话 {} · 专注 {}</p>\n",
+        week.week_start,
+        week.week_end,
+        format_minutes(week.avg_session_minutes),
+        format_minutes(week.focus_minutes)
+    );
+    body.push_str(&render_metrics(&metrics));
+    let mut text = format!(
+        "屏幕活动周报 {}（{} ~ {}）\n\n",
+        week.week_label, week.week_start, week.week_end
+    );
+    for (label, value) in &metrics {
+        text.push_str(&format!("{}: {}\n", label, value));
+    }
+
+    if !week.top_categories.trim().is_empty() {
+        body.push_str(&section_title("主要分类"));
+        body.push_str(&format!(
+            "<p style=\"margin:0;line-height:1.8;\">{}</p>\n",
+            escape_html(week.top_categories.trim())
+        ));
+        text.push_str(&format!("主要分类: {}\n", week.top_categories.trim()));
+    }
+
+    if !week.days.is_empty() {
+        body.push_str(&section_title("每日总结"));
+        body.push_str("<ul style=\"margin:0;padding-left:20px;line-height:1.6;\">\n");
+        text.push_str("\n每日总结\n");
+        for (date, summary) in &week.days {
+            body.push_str(&format!(
+                "<li><strong>{}</strong> {}</li>\n",
+                date,
+                escape_html(summary)
+            ));
+            text.push_str(&format!("- {} {}\n", date, summary));
+        }
+        body.push_str("</ul>\n");
+    }
+
+    let title = format!("屏幕活动周报 {}", week.week_label);
+    DigestEmail {
+        html: render_email(&title, &body),
+        subject: title,
+        text,
+    }
+}
+
+/// 指标卡片（邮件客户端普遍不支持外部样式，全部使用表格与内联样式）
+fn render_metrics(metrics: &[(&str, String)]) -> String {
+    let mut html = String::from(
+        "<table role=\"presentation\" width=\"100%\" cellspacing=\"8\" style=\"border-collapse:separate;\"><tr>",
+    );
+    for (label, value) in metrics {
+        html.push_str(&format!(
+            "<td style=\"background:#f5f7fa;border-radius:6px;padding:10px;text-align:center;\"><div style=\"font-size:20px;font-weight:bold;color:#303133;\">{}</div><div style=\"font-size:12px;color:#909399;\">{}</div></td>",
+            escape_html(value),
+            label
+        ));
+    }
+    html.push_str("</tr></table>\n");
+    html
+}
+
+fn section_title(title: &str) -> String {
+    format!(
+        "<h2 style=\"margin:20px 0 8px;font-size:16px;color:#303133;\">{}</h2>\n",
+        title
+    )
+}
+
+fn render_email(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n</head>\n<body style=\"margin:0;padding:16px;background:#f0f2f5;\">\n<div style=\"max-width:640px;margin:0 auto;background:#ffffff;border-radius:8px;padding:20px;font-family:-apple-system,'Segoe UI','PingFang SC','Microsoft YaHei',sans-serif;font-size:14px;color:#303133;\">\n<h1 style=\"margin:0 0 12px;font-size:20px;\">{title}</h1>\n{body}</div>\n</body>\n</html>\n",
+        title = escape_html(title),
+        body = body
+    )
+}
+
+/// 分钟数格式化为 "2h 30m"
+pub fn format_minutes(minutes: i64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
+fn ratio(part: i64, total: i64) -> i64 {
+    if total > 0 {
+        part * 100 / total
+    } else {
+        0
+    }
+}
+
+/// 解析发送时间（HH:MM）
+pub(crate) fn parse_send_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").ok()
+}
+
+/// SMTP 发送器
+pub struct DigestMailer {
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl DigestMailer {
+    pub fn new(config: &DigestConfig) -> Result<Self> {
+        let host = config.smtp_host.trim();
+        if host.is_empty() {
+            return Err(anyhow!("请先配置 SMTP 服务器地址"));
+        }
+        let from = config
+            .from
+            .trim()
+            .parse::<Mailbox>()
+            .map_err(|e| anyhow!("发件人地址无效: {}", e))?;
+        let to = config
+            .to
+            .split([',', ';'])
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(|address| {
+                address
+                    .parse::<Mailbox>()
+                    .map_err(|e| anyhow!("收件人地址无效（{}）: {}", address, e))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if to.is_empty() {
+            return Err(anyhow!("请至少填写一个收件人"));
+        }
+
+        let mut builder = match config.security {
+            SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+            SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+        }
+        .port(config.smtp_port)
+        .timeout(Some(std::time::Duration::from_secs(30)));
+        if !config.username.trim().is_empty() {
+            builder = builder.credentials(Credentials::new(
+                config.username.trim().to_string(),
+                config.password.clone(),
+            ));
+        }
+
+        Ok(Self {
+            from,
+            to,
+            transport: builder.build(),
+        })
+    }
+
+    pub async fn send(&self, email: &DigestEmail) -> Result<()> {
+        let mut message = Message::builder()
+            .from(self.from.clone())
+            .subject(email.subject.clone());
+        for recipient in &self.to {
+            message = message.to(recipient.clone());
+        }
+        let message = message.multipart(MultiPart::alternative_plain_html(
+            email.text.clone(),
+            email.html.clone(),
+        ))?;
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| anyhow!("SMTP 发送失败: {}", e))?;
+        Ok(())
+    }
+}
+
+/// 已发送记录（持久化到应用数据目录，避免重启后重复发送）
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DigestState {
+    /// 最近发送日报的日期
+    last_daily: Option<String>,
+    /// 最近发送周报的 ISO 周
+    last_weekly: Option<String>,
+}
+
+/// 当前时刻应发送的摘要：[(类型, 周期标识)]
+fn due_digests(
+    config: &DigestConfig,
+    state: &DigestState,
+    now: NaiveDateTime,
+) -> Vec<(DigestKind, String)> {
+    let Some(send_time) = parse_send_time(&config.send_time) else {
+        return Vec::new();
+    };
+    if now.time() < send_time {
+        return Vec::new();
+    }
+
+    let mut due = Vec::new();
+    let today = now.date().format("%Y-%m-%d").to_string();
+    if config.daily && state.last_daily.as_deref() != Some(today.as_str()) {
+        due.push((DigestKind::Daily, today));
+    }
+    let week_label = now.date().format("%G-W%V").to_string();
+    if config.weekly
+        && now.date().weekday().number_from_monday() == config.weekly_weekday
+        && state.last_weekly.as_deref() != Some(week_label.as_str())
+    {
+        due.push((DigestKind::Weekly, week_label));
+    }
+    due
+}
+
+/// 邮件摘要调度器 - 每分钟检查一次，到达发送时间后发送当天日报与当周周报
+pub struct DigestScheduler {
+    settings: Arc<SettingsManager>,
+    db: Arc<Database>,
+    llm_handle: LLMHandle,
+    state_path: PathBuf,
+    /// 发送失败后的下一次重试时间
+    retry_after: Mutex<Option<NaiveDateTime>>,
+}
+
+impl DigestScheduler {
+    pub fn new(
+        settings: Arc<SettingsManager>,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        state_path: PathBuf,
+    ) -> Self {
+        Self {
+            settings,
+            db,
+            llm_handle,
+            state_path,
+            retry_after: Mutex::new(None),
+        }
+    }
+
+    /// 启动定时发送任务
+    pub fn start(self: Arc<Self>) {
+        tokio::spawn(async move {
+            info!("邮件摘要调度任务已启动");
+            loop {
+                if let Err(e) = self.tick().await {
+                    error!(
+                        "邮件摘要发送失败: {}，{} 分钟后重试",
+                        e, RETRY_DELAY_MINUTES
+                    );
+                }
+                tokio::time::sleep(CHECK_INTERVAL).await;
+            }
+        });
+    }
+
+    async fn tick(&self) -> Result<()> {
+        let app_config = self.settings.get().await;
+        let config = app_config.digest_config.clone().unwrap_or_default();
+        if !config.enabled {
+            return Ok(());
+        }
+        let now = local_now().naive_utc();
+        let mut retry_after = self.retry_after.lock().await;
+        if retry_after.is_some_and(|time| now < time) {
+            return Ok(());
+        }
+
+        let mut state = self.load_state().await;
+        let due = due_digests(&config, &state, now);
+        if due.is_empty() {
+            return Ok(());
+        }
+
+        let date = now.date().format("%Y-%m-%d").to_string();
+        let result = async {
+            let mailer = DigestMailer::new(&config)?;
+            for (kind, period) in due {
+                match build_digest(
+                    kind,
+                    self.db.clone(),
+                    self.llm_handle.clone(),
+                    &app_config,
+                    &date,
+                )
+                .await?
+                {
+                    Some(email) => {
+                        mailer.send(&email).await?;
+                        info!("邮件摘要已发送: {}", email.subject);
+                    }
+                    None => info!("{:?} 摘要 {} 没有会话，跳过发送", kind, period),
+                }
+                match kind {
+                    DigestKind::Daily => state.last_daily = Some(period),
+                    DigestKind::Weekly => state.last_weekly = Some(period),
+                }
+                self.save_state(&state).await?;
+            }
+            Ok(())
+        }
+        .await;
+
+        *retry_after = if result.is_err() {
+            Some(now + Duration::minutes(RETRY_DELAY_MINUTES))
+        } else {
+            None
+        };
+        result
+    }
+
+    async fn load_state(&self) -> DigestState {
+        match tokio::fs::read(&self.state_path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_default(),
+            Err(_) => DigestState::default(),
+        }
+    }
+
+    async fn save_state(&self, state: &DigestState) -> Result<()> {
+        tokio::fs::write(&self.state_path, serde_json::to_string_pretty(state)?).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn sample_session(start_hour: u32, end_hour: u32, title: &str, tags: &str) -> Session {
+        Session {
+            id: Some(i64::from(start_hour)),
+            start_time: Utc.with_ymd_and_hms(2024, 5, 12, start_hour, 0, 0).unwrap(),
+            end_time: Utc.with_ymd_and_hms(2024, 5, 12, end_hour, 0, 0).unwrap(),
+            title: title.to_string(),
+            summary: String::new(),
+            video_path: None,
+            tags: tags.to_string(),
+            created_at: None,
+            device_name: None,
+            device_type: None,
+        }
+    }
+
+    #[test]
+    fn test_render_daily_digest() {
+        let sessions = vec![
+            sample_session(
+                9,
+                11,
+                "编写 <导出> 模块",
+                r#"[{"category":"work","confidence":0.9,"keywords":[]}]"#,
+            ),
+            sample_session(
+                14,
+                15,
+                "",
+                r#"[{"category":"personal","confidence":0.8,"keywords":[]}]"#,
+            ),
+        ];
+        let email = render_daily_digest("2024-05-12", "上午专注开发。", &sessions);
+
+        assert_eq!(email.subject, "屏幕活动日报 2024-05-12");
+        assert!(email.html.contains("编写 &lt;导出&gt; 模块"));
+        assert!(email.html.contains("未命名会话"));
+        assert!(email.text.contains("记录时长: 3h\n"));
+        assert!(email.text.contains("专注时长: 2h\n专注占比: 66%\n"));
+        assert!(email.text.contains("分类时长: 工作 2h · 个人 1h\n"));
+        assert!(email.text.contains("- 09:00-11:00 编写 <导出> 模块\n"));
+    }
+
+    #[test]
+    fn test_due_digests() {
+        let config = DigestConfig {
+            enabled: true,
+            weekly: true,
+            ..DigestConfig::default()
+        };
+        // 2024-05-12 是周日（ISO 2024-W19）
+        let at = |hour: u32| {
+            NaiveDate::from_ymd_opt(2024, 5, 12)
+                .unwrap()
+                .and_hms_opt(hour, 30, 0)
+                .unwrap()
+        };
+
+        assert!(due_digests(&config, &DigestState::default(), at(20)).is_empty());
+        assert_eq!(
+            due_digests(&config, &DigestState::default(), at(21)),
+            vec![
+                (DigestKind::Daily, "2024-05-12".to_string()),
+                (DigestKind::Weekly, "2024-W19".to_string()),
+            ]
+        );
+
+        let sent = DigestState {
+            last_daily: Some("2024-05-12".to_string()),
+            last_weekly: Some("2024-W19".to_string()),
+        };
+        assert!(due_digests(&config, &sent, at(22)).is_empty());
+
+        let monday = NaiveDate::from_ymd_opt(2024, 5, 13)
+            .unwrap()
+            .and_hms_opt(21, 0, 0)
+            .unwrap();
+        assert_eq!(
+            due_digests(&config, &sent, monday),
+            vec![(DigestKind::Daily, "2024-05-13".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_mailer_validation() {
+        let config = DigestConfig {
+            enabled: true,
+            smtp_host: "smtp.example.com".to_string(),
+            from: "Screen Analyzer <me@example.com>".to_string(),
+            to: "a@example.com; b@example.com".to_string(),
+            ..DigestConfig::default()
+        };
+        let mailer = DigestMailer::new(&config).unwrap();
+        assert_eq!(mailer.to.len(), 2);
+
+        let invalid = DigestConfig {
+            to: "not-an-address".to_string(),
+            ..config
+        };
+        assert!(DigestMailer::new(&invalid).is_err());
+    }
+}
@@ -1,10 +1,10 @@
 // 分析领域管理器
 //
 // 负责 LLM 分析和视频处理相关的功能
-// 包含 LLMHandle 和 VideoProcessor 两个核心组件
+// 包含 LLMHandle、总结队列和 VideoProcessor 三个核心组件
 // 使用Actor模式管理LLM状态，消除锁竞争
 
-use crate::actors::LLMHandle;
+use crate::actors::{LLMHandle, SummaryQueueHandle};
 use crate::video::processor::VideoProcessor;
 use std::sync::Arc;
 
@@ -12,14 +12,20 @@ use std::sync::Arc;
 #[derive(Clone)]
 pub struct AnalysisDomain {
     llm_handle: LLMHandle,
+    summary_queue: SummaryQueueHandle,
     video_processor: Arc<VideoProcessor>,
 }
 
 impl AnalysisDomain {
     /// 创建新的分析领域管理器
-    pub fn new(llm_handle: LLMHandle, video_processor: Arc<VideoProcessor>) -> Self {
+    pub fn new(
+        llm_handle: LLMHandle,
+        summary_queue: SummaryQueueHandle,
+        video_processor: Arc<VideoProcessor>,
+    ) -> Self {
         Self {
             llm_handle,
+            summary_queue,
             video_processor,
         }
     }
@@ -29,6 +35,11 @@ impl AnalysisDomain {
         &self.llm_handle
     }
 
+    /// 获取总结队列 Handle
+    pub fn get_summary_queue(&self) -> &SummaryQueueHandle {
+        &self.summary_queue
+    }
+
     /// 获取视频处理器
     pub fn get_video_processor(&self) -> &Arc<VideoProcessor> {
         &self.video_processor
@@ -12,5 +12,7 @@ pub mod system;
 pub use analysis::AnalysisDomain;
 pub use capture::CaptureDomain;
 pub use storage::StorageDomain;
-pub use summary::{DaySummary, DeviceStat, ParallelWork, SummaryGenerator, UsagePattern};
+pub use summary::{
+    DaySummary, DeviceStat, ParallelWork, SummaryGenerator, SummaryJobRunner, UsagePattern,
+};
 pub use system::SystemDomain;
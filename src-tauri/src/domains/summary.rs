@@ -1,8 +1,15 @@
 // 总结领域 - 负责生成每日活动总结、统计分析等
 
+use crate::actors::summary_queue::{JobExecutor, SummaryJob, SummaryJobKind};
 use crate::actors::LLMHandle;
-use crate::storage::{Database, Session};
-use chrono::Timelike;
+use crate::app_usage::{limit_breaches, AppLimitBreach};
+use crate::llm::plugin::{ActivityCategory, ActivityTag};
+use crate::models::AppLimit;
+use crate::storage::{
+    AppUsageRecord, CalendarEventRecord, DailyFocusMetricsRecord, Database, ProjectRuleRecord,
+    Session, TimelineCardRecord,
+};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -24,6 +31,12 @@ pub struct DaySummary {
     pub usage_patterns: Vec<UsagePattern>,
     /// 活跃设备数量
     pub active_device_count: usize,
+    /// 网站域名访问统计（按时长降序，来自浏览器扩展上报，不缓存）
+    #[serde(default)]
+    pub domain_stats: Vec<DomainStat>,
+    /// 达到每日上限的应用（按配置的上限实时比较，不缓存）
+    #[serde(default)]
+    pub app_limit_breaches: Vec<AppLimitBreach>,
 }
 
 /// 设备统计
@@ -41,6 +54,63 @@ pub struct DeviceStat {
     pub total_time: String,
     /// 截图数量
     pub screenshots: i64,
+    /// 专注时长（工作 + 学习，按时间线卡片统计，分钟）
+    #[serde(default)]
+    pub focus_minutes: i64,
+    /// 专注占比（%）
+    #[serde(default)]
+    pub focus_ratio: i64,
+}
+
+/// 单台设备的专注度指标（按该设备会话的时间线卡片统计）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceFocusStat {
+    /// 设备名称
+    pub name: String,
+    /// 设备类型
+    #[serde(rename = "type")]
+    pub device_type: String,
+    /// 会话数量
+    pub session_count: i64,
+    /// 时间线卡片总时长（分钟）
+    pub total_minutes: i64,
+    pub work_minutes: i64,
+    pub learning_minutes: i64,
+    pub communication_minutes: i64,
+    pub personal_minutes: i64,
+    pub idle_minutes: i64,
+    pub other_minutes: i64,
+}
+
+impl DeviceFocusStat {
+    /// 专注时长（工作 + 学习，分钟）
+    pub fn focus_minutes(&self) -> i64 {
+        self.work_minutes + self.learning_minutes
+    }
+
+    /// 专注占比（%）
+    pub fn focus_ratio(&self) -> i64 {
+        if self.total_minutes == 0 {
+            0
+        } else {
+            (self.focus_minutes() * 100 / self.total_minutes).max(0)
+        }
+    }
+}
+
+/// 网站域名访问统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DomainStat {
+    /// 域名
+    pub domain: String,
+    /// 访问时长（分钟）
+    pub total_minutes: i64,
+    /// 格式化的时长字符串 (如 "1h 5m")
+    pub total_time: String,
+    /// 涉及的会话数量
+    pub session_count: i64,
 }
 
 /// 并行工作
@@ -71,6 +141,7 @@ pub struct UsagePattern {
 pub struct SummaryGenerator {
     db: Arc<Database>,
     llm_handle: Option<LLMHandle>,
+    app_limits: Vec<AppLimit>,
 }
 
 impl SummaryGenerator {
@@ -78,6 +149,7 @@ impl SummaryGenerator {
         Self {
             db,
             llm_handle: None,
+            app_limits: Vec::new(),
         }
     }
 
@@ -85,9 +157,16 @@ impl SummaryGenerator {
         Self {
             db,
             llm_handle: Some(llm_handle),
+            app_limits: Vec::new(),
         }
     }
 
+    /// 设置应用每日上限，总结中标出当天达到上限的应用
+    pub fn with_app_limits(mut self, limits: Vec<AppLimit>) -> Self {
+        self.app_limits = limits;
+        self
+    }
+
     /// 生成每日总结
     ///
     /// # 参数
@@ -100,6 +179,17 @@ impl SummaryGenerator {
     ) -> Result<DaySummary, String> {
         info!("生成每日总结: {} (force_refresh={})", date, force_refresh);
 
+        // 专注度指标按当前时间线卡片刷新（与总结缓存无关），周报直接汇总该表
+        if let Err(e) = self.refresh_focus_metrics(date).await {
+            warn!("刷新专注度指标失败: {}", e);
+        }
+        // 评估当天的目标（已结束日期的结果会被保存，供连续天数与周报复用）
+        if let Ok(day) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            if let Err(e) = crate::goals::day_goals(&self.db, day).await {
+                warn!("评估目标失败: {}", e);
+            }
+        }
+
         // 如果不是强制刷新，先尝试从数据库读取缓存
         if !force_refresh {
             match self.db.get_day_summary(date).await {
@@ -121,6 +211,8 @@ impl SummaryGenerator {
                         parallel_work,
                         usage_patterns,
                         active_device_count: cached.active_device_count as usize,
+                        domain_stats: self.domain_stats(date).await,
+                        app_limit_breaches: self.app_limit_breaches(date).await,
                     });
                 }
                 Ok(None) => {
@@ -147,6 +239,8 @@ impl SummaryGenerator {
                 parallel_work: vec![],
                 usage_patterns: vec![],
                 active_device_count: 0,
+                domain_stats: vec![],
+                app_limit_breaches: vec![],
             });
         }
 
@@ -166,8 +260,25 @@ impl SummaryGenerator {
         // 分析使用模式
         let usage_patterns = self.analyze_usage_patterns(&sessions, active_device_count);
 
+        // 当天同步的日历会议：标注时间线卡片，并作为总结的上下文
+        let meetings = self
+            .db
+            .get_calendar_events_by_date(date)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("读取日历会议失败: {}", e);
+                Vec::new()
+            });
+        if !meetings.is_empty() {
+            match crate::calendar::label_meeting_cards(&self.db, &sessions, &meetings).await {
+                Ok(labeled) if labeled > 0 => info!("已用会议标题标注 {} 张时间线卡片", labeled),
+                Ok(_) => {}
+                Err(e) => warn!("标注会议卡片失败: {}", e),
+            }
+        }
+
         // 生成总结文本
-        let summary_text = self.generate_summary_text(date, &sessions).await;
+        let summary_text = self.generate_summary_text(date, &sessions, &meetings).await;
 
         let summary = DaySummary {
             date: date.to_string(),
@@ -176,6 +287,8 @@ impl SummaryGenerator {
             parallel_work: parallel_work.clone(),
             usage_patterns: usage_patterns.clone(),
             active_device_count,
+            domain_stats: self.domain_stats(date).await,
+            app_limit_breaches: self.app_limit_breaches(date).await,
         };
 
         // 保存到数据库
@@ -206,12 +319,80 @@ impl SummaryGenerator {
         Ok(summary)
     }
 
+    /// 统计某天时间线卡片的类别时长并写入 daily_focus_metrics 缓存
+    pub async fn refresh_focus_metrics(
+        &self,
+        date: &str,
+    ) -> Result<DailyFocusMetricsRecord, String> {
+        let naive_date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|e| format!("日期格式错误: {}", e))?;
+        let sessions = self
+            .db
+            .get_sessions_by_date(date)
+            .await
+            .map_err(|e| format!("获取会话失败: {}", e))?;
+
+        let mut cards = Vec::new();
+        for session_id in sessions.iter().filter_map(|session| session.id) {
+            cards.extend(
+                self.db
+                    .get_timeline_cards_by_session(session_id)
+                    .await
+                    .map_err(|e| format!("获取时间线卡片失败: {}", e))?,
+            );
+        }
+
+        let metrics = focus_metrics_from_cards(naive_date, &cards);
+        self.db
+            .save_daily_focus_metrics(date, &metrics)
+            .await
+            .map_err(|e| format!("保存专注度指标失败: {}", e))?;
+        Ok(metrics)
+    }
+
+    /// 当天各网站域名的访问统计（读取失败时为空）
+    async fn domain_stats(&self, date: &str) -> Vec<DomainStat> {
+        match self.db.get_domain_usage_summary(date, date).await {
+            Ok(summaries) => summaries
+                .into_iter()
+                .map(|summary| {
+                    let minutes = (summary.seconds + 30) / 60;
+                    DomainStat {
+                        domain: summary.domain,
+                        total_minutes: minutes,
+                        total_time: format_duration(minutes),
+                        session_count: summary.session_count,
+                    }
+                })
+                .collect(),
+            Err(e) => {
+                warn!("读取域名访问统计失败: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// 当天达到上限的应用（未设置上限或读取失败时为空）
+    async fn app_limit_breaches(&self, date: &str) -> Vec<AppLimitBreach> {
+        if self.app_limits.is_empty() {
+            return Vec::new();
+        }
+        match self.db.get_daily_app_usage(date, date).await {
+            Ok(usage) => limit_breaches(&usage, &self.app_limits),
+            Err(e) => {
+                warn!("读取应用使用时长失败: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
     /// 计算设备统计
     async fn calculate_device_stats(
         &self,
         sessions: &[Session],
     ) -> Result<Vec<DeviceStat>, String> {
         let mut device_map: HashMap<String, (String, i64, i64)> = HashMap::new();
+        let mut cards = Vec::new();
 
         for session in sessions {
             let device_name = session
@@ -227,6 +408,10 @@ impl SummaryGenerator {
 
             // 获取截图数量（从数据库查询）
             let screenshot_count = if let Some(session_id) = session.id {
+                match self.db.get_timeline_cards_by_session(session_id).await {
+                    Ok(session_cards) => cards.extend(session_cards),
+                    Err(e) => warn!("获取会话 {} 的时间线卡片失败: {}", session_id, e),
+                }
                 match self.db.get_frames_by_session(session_id).await {
                     Ok(frames) => frames.len() as i64,
                     Err(_) => {
@@ -246,11 +431,15 @@ impl SummaryGenerator {
             entry.2 += screenshot_count;
         }
 
+        let focus = device_focus_from_cards(sessions, &cards);
         let mut stats: Vec<DeviceStat> = device_map
             .into_iter()
             .map(|(name, (device_type, minutes, screenshots))| {
                 let total_time = format_duration(minutes);
+                let device_focus = focus.iter().find(|stat| stat.name == name);
                 DeviceStat {
+                    focus_minutes: device_focus.map_or(0, DeviceFocusStat::focus_minutes),
+                    focus_ratio: device_focus.map_or(0, DeviceFocusStat::focus_ratio),
                     name,
                     device_type,
                     total_minutes: minutes,
@@ -437,7 +626,12 @@ impl SummaryGenerator {
     }
 
     /// 生成总结文本（优先使用LLM，fallback到规则）
-    async fn generate_summary_text(&self, date: &str, sessions: &[Session]) -> String {
+    async fn generate_summary_text(
+        &self,
+        date: &str,
+        sessions: &[Session],
+        meetings: &[CalendarEventRecord],
+    ) -> String {
         // 计算总时长
         let total_minutes: i64 = sessions
             .iter()
@@ -447,7 +641,7 @@ impl SummaryGenerator {
         // 如果有 LLM handle，尝试使用 LLM 生成
         if let Some(llm_handle) = &self.llm_handle {
             match self
-                .generate_summary_with_llm(llm_handle, date, sessions)
+                .generate_summary_with_llm(llm_handle, date, sessions, meetings)
                 .await
             {
                 Ok(summary) => {
@@ -478,17 +672,30 @@ impl SummaryGenerator {
         llm_handle: &LLMHandle,
         date: &str,
         sessions: &[Session],
+        meetings: &[CalendarEventRecord],
     ) -> Result<String, String> {
-        // 将 Session 转换为 SessionBrief
-        let session_briefs: Vec<crate::llm::SessionBrief> = sessions
-            .iter()
-            .map(|s| crate::llm::SessionBrief {
+        // 将 Session 转换为 SessionBrief（附上同时段的会议标题与用户备注）
+        let mut session_briefs: Vec<crate::llm::SessionBrief> = Vec::with_capacity(sessions.len());
+        for s in sessions {
+            let mut summary = s.summary.clone();
+            let session_meetings = crate::calendar::session_meetings(s, meetings);
+            if !session_meetings.is_empty() {
+                summary = format!("{}\n（期间会议：{}）", summary, session_meetings.join("、"));
+            }
+            if let Some(id) = s.id {
+                match self.db.get_session_note(id).await {
+                    Ok(Some(note)) => summary = format!("{}\n（{}）", summary, note.prompt_text()),
+                    Ok(None) => {}
+                    Err(e) => warn!("读取会话 {} 的备注失败: {}", id, e),
+                }
+            }
+            session_briefs.push(crate::llm::SessionBrief {
                 start_time: s.start_time,
                 end_time: s.end_time,
                 title: s.title.clone(),
-                summary: s.summary.clone(),
-            })
-            .collect();
+                summary,
+            });
+        }
 
         // 调用 LLM
         llm_handle
@@ -576,6 +783,9 @@ fn extract_activity_name(session: &Session) -> String {
 
 /// 获取类别显示名称
 fn get_category_display_name(category: &str) -> String {
+    if let Some(custom) = crate::taxonomy::find_custom_category(category) {
+        return custom.name;
+    }
     match category.to_lowercase().as_str() {
         "work" | "coding" => "工作".to_string(),
         "communication" | "meeting" => "会议".to_string(),
@@ -585,3 +795,345 @@ fn get_category_display_name(category: &str) -> String {
         _ => "其他".to_string(),
     }
 }
+
+/// 将时间线卡片的类别归一为活动类别
+pub fn normalize_timeline_category(raw: &str) -> ActivityCategory {
+    if let Some(category) = crate::taxonomy::custom_bucket(raw) {
+        return category;
+    }
+    match raw.to_lowercase().as_str() {
+        "work" => ActivityCategory::Work,
+        "communication" | "meeting" => ActivityCategory::Communication,
+        "learning" | "research" => ActivityCategory::Learning,
+        "personal" => ActivityCategory::Personal,
+        "idle" | "break" => ActivityCategory::Idle,
+        _ => ActivityCategory::Other,
+    }
+}
+
+/// 时间线卡片时长（分钟），时间无法解析时为 0
+pub fn parse_card_minutes(card: &TimelineCardRecord) -> i64 {
+    let start = chrono::DateTime::parse_from_rfc3339(&card.start_time).ok();
+    let end = chrono::DateTime::parse_from_rfc3339(&card.end_time).ok();
+    match (start, end) {
+        (Some(s), Some(e)) => (e - s).num_minutes().max(0),
+        _ => 0,
+    }
+}
+
+/// 按类别汇总时间线卡片时长
+pub fn focus_metrics_from_cards(
+    date: chrono::NaiveDate,
+    cards: &[TimelineCardRecord],
+) -> DailyFocusMetricsRecord {
+    let mut metrics = DailyFocusMetricsRecord {
+        date,
+        total_minutes: 0,
+        work_minutes: 0,
+        learning_minutes: 0,
+        communication_minutes: 0,
+        personal_minutes: 0,
+        idle_minutes: 0,
+        other_minutes: 0,
+        updated_at: crate::storage::local_now(),
+    };
+    for card in cards {
+        let minutes = parse_card_minutes(card);
+        if minutes <= 0 {
+            continue;
+        }
+        metrics.total_minutes += minutes;
+        match normalize_timeline_category(&card.category) {
+            ActivityCategory::Work => metrics.work_minutes += minutes,
+            ActivityCategory::Learning => metrics.learning_minutes += minutes,
+            ActivityCategory::Communication => metrics.communication_minutes += minutes,
+            ActivityCategory::Personal => metrics.personal_minutes += minutes,
+            ActivityCategory::Idle => metrics.idle_minutes += minutes,
+            ActivityCategory::Other => metrics.other_minutes += minutes,
+        }
+    }
+    metrics
+}
+
+/// 按会话所属设备汇总时间线卡片的类别时长（未记录设备名的会话归为 "Unknown Device"），
+/// 按专注时长降序
+pub fn device_focus_from_cards(
+    sessions: &[Session],
+    cards: &[TimelineCardRecord],
+) -> Vec<DeviceFocusStat> {
+    let mut stats: Vec<DeviceFocusStat> = Vec::new();
+    let mut device_of_session = HashMap::new();
+    for session in sessions {
+        let name = session
+            .device_name
+            .clone()
+            .unwrap_or_else(|| "Unknown Device".to_string());
+        let index = match stats.iter().position(|stat| stat.name == name) {
+            Some(index) => index,
+            None => {
+                stats.push(DeviceFocusStat {
+                    name,
+                    device_type: session
+                        .device_type
+                        .clone()
+                        .unwrap_or_else(|| "unknown".to_string()),
+                    ..Default::default()
+                });
+                stats.len() - 1
+            }
+        };
+        stats[index].session_count += 1;
+        if let Some(session_id) = session.id {
+            device_of_session.insert(session_id, index);
+        }
+    }
+
+    for card in cards {
+        let Some(&index) = device_of_session.get(&card.session_id) else {
+            continue;
+        };
+        let minutes = parse_card_minutes(card);
+        if minutes <= 0 {
+            continue;
+        }
+        let stat = &mut stats[index];
+        stat.total_minutes += minutes;
+        match normalize_timeline_category(&card.category) {
+            ActivityCategory::Work => stat.work_minutes += minutes,
+            ActivityCategory::Learning => stat.learning_minutes += minutes,
+            ActivityCategory::Communication => stat.communication_minutes += minutes,
+            ActivityCategory::Personal => stat.personal_minutes += minutes,
+            ActivityCategory::Idle => stat.idle_minutes += minutes,
+            ActivityCategory::Other => stat.other_minutes += minutes,
+        }
+    }
+
+    stats.sort_by(|a, b| {
+        b.focus_minutes()
+            .cmp(&a.focus_minutes())
+            .then_with(|| b.total_minutes.cmp(&a.total_minutes))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    stats
+}
+
+/// 日期范围内（含首尾，按会话开始日期）各设备的专注度指标
+pub async fn device_focus_stats(
+    db: &Database,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> anyhow::Result<Vec<DeviceFocusStat>> {
+    let mut sessions = Vec::new();
+    let mut cards = Vec::new();
+    for day in start.iter_days().take_while(|day| *day <= end) {
+        let day_sessions = db
+            .get_sessions_by_date(&day.format("%Y-%m-%d").to_string())
+            .await?;
+        for session_id in day_sessions.iter().filter_map(|session| session.id) {
+            cards.extend(db.get_timeline_cards_by_session(session_id).await?);
+        }
+        sessions.extend(day_sessions);
+    }
+    Ok(device_focus_from_cards(&sessions, &cards))
+}
+
+/// 项目规则可匹配的字段
+pub const PROJECT_MATCH_FIELDS: [&str; 4] = ["tag", "app", "title", "process"];
+
+/// 按规则判断会话所属项目：规则按顺序匹配，先命中者优先，关键词忽略大小写
+///
+/// - tag：会话标签的关键词包含关键词，或类别名与关键词相同
+/// - app：时间线卡片的主要/次要应用包含关键词
+/// - title：会话标题或时间线卡片标题包含关键词
+/// - process：截屏时记录的前台应用名或进程路径包含关键词
+pub fn match_session_project<'a>(
+    session: &Session,
+    cards: &[TimelineCardRecord],
+    apps: &[AppUsageRecord],
+    rules: &'a [ProjectRuleRecord],
+) -> Option<&'a str> {
+    rules
+        .iter()
+        .find(|rule| project_rule_matches(rule, session, cards, apps))
+        .map(|rule| rule.project.trim())
+        .filter(|project| !project.is_empty())
+}
+
+fn project_rule_matches(
+    rule: &ProjectRuleRecord,
+    session: &Session,
+    cards: &[TimelineCardRecord],
+    apps: &[AppUsageRecord],
+) -> bool {
+    let pattern = rule.pattern.trim().to_lowercase();
+    if pattern.is_empty() {
+        return false;
+    }
+    let contains = |text: &str| text.to_lowercase().contains(&pattern);
+
+    match rule.match_field.as_str() {
+        "tag" => serde_json::from_str::<Vec<ActivityTag>>(&session.tags)
+            .unwrap_or_default()
+            .iter()
+            .any(|tag| {
+                tag.keywords.iter().any(|keyword| contains(keyword))
+                    || category_key(&tag.category) == pattern
+            }),
+        "app" => cards.iter().any(|card| {
+            let Ok(sites) = serde_json::from_str::<serde_json::Value>(&card.app_sites) else {
+                return false;
+            };
+            let primary = sites.get("primary").and_then(|v| v.as_str());
+            let secondary = sites
+                .get("secondary")
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.as_str());
+            primary.into_iter().chain(secondary).any(contains)
+        }),
+        "title" => contains(&session.title) || cards.iter().any(|card| contains(&card.title)),
+        "process" => apps.iter().any(|app| {
+            contains(&app.app_name) || app.process_path.as_deref().is_some_and(contains)
+        }),
+        _ => false,
+    }
+}
+
+/// 类别的英文键（与 serde 序列化一致）
+pub fn category_key(category: &ActivityCategory) -> &'static str {
+    match category {
+        ActivityCategory::Work => "work",
+        ActivityCategory::Communication => "communication",
+        ActivityCategory::Learning => "learning",
+        ActivityCategory::Personal => "personal",
+        ActivityCategory::Idle => "idle",
+        ActivityCategory::Other => "other",
+    }
+}
+
+/// 归入项目的一次会话
+#[derive(Debug, Clone)]
+pub struct ProjectSession {
+    pub project: String,
+    pub session_id: i64,
+    pub title: String,
+    /// 会话开始时间（导出时区下的本地时间）
+    pub start: NaiveDateTime,
+    pub minutes: i64,
+}
+
+/// 单个项目的汇总
+#[derive(Debug, Clone)]
+pub struct ProjectSummary {
+    pub name: String,
+    pub total_minutes: i64,
+    pub session_count: usize,
+    /// 最近一次会话的日期
+    pub last_active: NaiveDate,
+    /// 最近的会话（最新在前）
+    pub recent_sessions: Vec<ProjectSession>,
+    /// 截至结束日期所在周的逐周时长（周一日期，最早在前，含无记录的周）
+    pub weekly_minutes: Vec<(NaiveDate, i64)>,
+}
+
+/// 按项目汇总会话：总时长、最近会话与最近 `weeks` 周的逐周时长，按总时长降序
+pub fn summarize_projects(
+    sessions: Vec<ProjectSession>,
+    end: NaiveDate,
+    weeks: usize,
+    recent_limit: usize,
+) -> Vec<ProjectSummary> {
+    let last_week = end - chrono::Duration::days(end.weekday().num_days_from_monday() as i64);
+    let first_week = last_week - chrono::Duration::weeks(weeks.saturating_sub(1) as i64);
+
+    let mut by_project: HashMap<String, Vec<ProjectSession>> = HashMap::new();
+    for session in sessions {
+        by_project
+            .entry(session.project.clone())
+            .or_default()
+            .push(session);
+    }
+
+    let mut summaries = by_project
+        .into_iter()
+        .map(|(name, mut sessions)| {
+            sessions.sort_by_key(|session| std::cmp::Reverse(session.start));
+            let weekly_minutes = (0..weeks)
+                .map(|index| {
+                    let week_start = first_week + chrono::Duration::weeks(index as i64);
+                    let minutes = sessions
+                        .iter()
+                        .filter(|session| {
+                            let days = (session.start.date() - week_start).num_days();
+                            (0..7).contains(&days)
+                        })
+                        .map(|session| session.minutes)
+                        .sum();
+                    (week_start, minutes)
+                })
+                .collect();
+            ProjectSummary {
+                total_minutes: sessions.iter().map(|session| session.minutes).sum(),
+                session_count: sessions.len(),
+                last_active: sessions[0].start.date(),
+                recent_sessions: sessions.into_iter().take(recent_limit).collect(),
+                weekly_minutes,
+                name,
+            }
+        })
+        .collect::<Vec<_>>();
+    summaries.sort_by(|a, b| {
+        b.total_minutes
+            .cmp(&a.total_minutes)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    summaries
+}
+
+/// 总结队列的任务执行器：重新生成每日总结或按天导出到指定目标
+pub struct SummaryJobRunner {
+    storage: Arc<crate::domains::StorageDomain>,
+    llm_handle: LLMHandle,
+}
+
+impl SummaryJobRunner {
+    pub fn new(storage: Arc<crate::domains::StorageDomain>, llm_handle: LLMHandle) -> Self {
+        Self {
+            storage,
+            llm_handle,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl JobExecutor for SummaryJobRunner {
+    async fn run(&self, job: &SummaryJob) -> anyhow::Result<String> {
+        let db = self
+            .storage
+            .get_db()
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let date = job.date.format("%Y-%m-%d").to_string();
+
+        match &job.kind {
+            SummaryJobKind::Resummarize => {
+                SummaryGenerator::with_llm(db, self.llm_handle.clone())
+                    .generate_day_summary(&date, true)
+                    .await
+                    .map_err(|e| anyhow::anyhow!(e))?;
+                Ok(format!("{} 的每日总结已重新生成", date))
+            }
+            SummaryJobKind::Export { target } => {
+                // 每个任务读取最新配置，导出过程中修改的设置对后续任务生效
+                let config = self.storage.get_settings().get().await;
+                let registry = crate::exporter::ExporterRegistry::from_config(&config);
+                let exporter = registry.get(target)?;
+                let report = exporter
+                    .export_day(db, self.llm_handle.clone(), &date, false)
+                    .await?;
+                Ok(report.render_message())
+            }
+        }
+    }
+}
@@ -0,0 +1,605 @@
+// 语义向量 - 为会话总结与时间线卡片生成向量（复用 LLM 提供商的 OpenAI 兼容接口，或本地 Ollama 模型），
+// 存入 vectors 表，按余弦相似度检索会话
+
+use anyhow::{anyhow, Context, Result};
+use chrono::NaiveDate;
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::event_bus::{AppEvent, EventBus};
+use crate::models::{EmbeddingConfig, LLMProviderConfig};
+use crate::settings::SettingsManager;
+use crate::storage::{Database, EmbeddingRecord, Session, SessionSearchHit, TimelineCardRecord};
+
+/// 单次请求最多提交的文本条数（DashScope 向量接口上限为 10）
+const BATCH_SIZE: usize = 10;
+
+/// 单条文本的最大字符数，超出部分截断
+const MAX_TEXT_CHARS: usize = 2000;
+
+/// 结果片段的最大字符数
+const SNIPPET_CHARS: usize = 120;
+
+/// 相似度低于该值的结果不返回
+const MIN_SCORE: f32 = 0.2;
+
+/// 向量服务
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddingProvider {
+    /// 复用 LLM 配置（OpenAI 兼容接口，如通义千问）
+    Llm,
+    /// 自定义 OpenAI 兼容接口
+    OpenAi,
+    /// 本地 Ollama
+    Ollama,
+}
+
+impl EmbeddingProvider {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "llm" => Some(Self::Llm),
+            "openai" => Some(Self::OpenAi),
+            "ollama" => Some(Self::Ollama),
+            _ => None,
+        }
+    }
+}
+
+/// 向量接口客户端
+pub struct EmbeddingClient {
+    provider: EmbeddingProvider,
+    endpoint: String,
+    api_key: String,
+    model: String,
+    client: Client,
+}
+
+impl EmbeddingClient {
+    /// 根据向量配置创建客户端，llm 模式下从 LLM 配置读取接口地址与密钥
+    pub fn from_config(
+        config: &EmbeddingConfig,
+        llm_provider: &str,
+        llm_config: Option<&LLMProviderConfig>,
+    ) -> Result<Self> {
+        let provider = EmbeddingProvider::parse(&config.provider)
+            .ok_or_else(|| anyhow!("不支持的向量服务: {}", config.provider))?;
+        let model = config.model.trim().to_string();
+        if model.is_empty() {
+            return Err(anyhow!("向量模型未配置"));
+        }
+
+        let (endpoint, api_key) = match provider {
+            EmbeddingProvider::Llm => {
+                if llm_provider != "openai" {
+                    return Err(anyhow!(
+                        "当前 LLM 提供商不提供向量接口，请改用 OpenAI 兼容接口或 Ollama"
+                    ));
+                }
+                let llm = llm_config
+                    .filter(|llm| !llm.api_key.trim().is_empty())
+                    .ok_or_else(|| anyhow!("LLM API Key 未配置"))?;
+                (
+                    embeddings_endpoint(&llm.base_url),
+                    llm.api_key.trim().to_string(),
+                )
+            }
+            EmbeddingProvider::OpenAi => {
+                if config.base_url.trim().is_empty() {
+                    return Err(anyhow!("向量接口地址未配置"));
+                }
+                (
+                    embeddings_endpoint(&config.base_url),
+                    config.api_key.trim().to_string(),
+                )
+            }
+            EmbeddingProvider::Ollama => {
+                let base_url = match config.base_url.trim() {
+                    "" => "http://localhost:11434",
+                    url => url,
+                };
+                (
+                    format!("{}/api/embed", base_url.trim_end_matches('/')),
+                    String::new(),
+                )
+            }
+        };
+
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(60))
+            .build()?;
+        Ok(Self {
+            provider,
+            endpoint,
+            api_key,
+            model,
+            client,
+        })
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// 批量生成向量，返回顺序与输入一致
+    pub async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(BATCH_SIZE) {
+            let mut request = self.client.post(&self.endpoint).json(&json!({
+                "model": self.model,
+                "input": batch,
+            }));
+            if !self.api_key.is_empty() {
+                request = request.bearer_auth(&self.api_key);
+            }
+
+            let response = request.send().await?;
+            let status = response.status();
+            let body: Value = response.json().await.unwrap_or(Value::Null);
+            if !status.is_success() {
+                return Err(anyhow!("向量接口返回错误 ({}): {}", status, body));
+            }
+
+            let batch_vectors = match self.provider {
+                EmbeddingProvider::Ollama => parse_ollama_response(&body)?,
+                EmbeddingProvider::Llm | EmbeddingProvider::OpenAi => parse_openai_response(&body)?,
+            };
+            if batch_vectors.len() != batch.len() {
+                return Err(anyhow!(
+                    "向量数量不匹配: 期望 {}，实际 {}",
+                    batch.len(),
+                    batch_vectors.len()
+                ));
+            }
+            vectors.extend(batch_vectors);
+        }
+        Ok(vectors)
+    }
+}
+
+/// OpenAI 兼容的向量接口地址（LLM 配置中通常填写的是 chat/completions 地址）
+fn embeddings_endpoint(base_url: &str) -> String {
+    let base = base_url.trim().trim_end_matches('/');
+    let base = base.strip_suffix("/chat/completions").unwrap_or(base);
+    let base = base.strip_suffix("/embeddings").unwrap_or(base);
+    format!("{}/embeddings", base)
+}
+
+fn parse_openai_response(body: &Value) -> Result<Vec<Vec<f32>>> {
+    let data = body["data"]
+        .as_array()
+        .ok_or_else(|| anyhow!("向量接口响应缺少 data 字段"))?;
+    let mut items: Vec<(u64, Vec<f32>)> = data
+        .iter()
+        .enumerate()
+        .map(|(position, item)| {
+            let index = item["index"].as_u64().unwrap_or(position as u64);
+            parse_vector(&item["embedding"]).map(|vector| (index, vector))
+        })
+        .collect::<Result<_>>()?;
+    items.sort_by_key(|(index, _)| *index);
+    Ok(items.into_iter().map(|(_, vector)| vector).collect())
+}
+
+fn parse_ollama_response(body: &Value) -> Result<Vec<Vec<f32>>> {
+    body["embeddings"]
+        .as_array()
+        .ok_or_else(|| anyhow!("Ollama 响应缺少 embeddings 字段"))?
+        .iter()
+        .map(parse_vector)
+        .collect()
+}
+
+fn parse_vector(value: &Value) -> Result<Vec<f32>> {
+    value
+        .as_array()
+        .ok_or_else(|| anyhow!("向量格式错误"))?
+        .iter()
+        .map(|v| {
+            v.as_f64()
+                .map(|v| v as f32)
+                .ok_or_else(|| anyhow!("向量格式错误"))
+        })
+        .collect()
+}
+
+/// 向量编码为 f32 小端序字节
+pub fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// 从 f32 小端序字节解码向量
+pub fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+/// 余弦相似度（维度不一致或零向量时返回 0）
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+fn content_hash(model: &str, text: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(model.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(text.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((index, _)) => format!("{}…", &text[..index]),
+        None => text.to_string(),
+    }
+}
+
+/// 待生成向量的文本
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingSource {
+    pub owner_kind: &'static str,
+    pub owner_id: i64,
+    pub text: String,
+}
+
+/// 会话需要生成向量的文本：会话标题与总结、每张时间线卡片
+pub fn embedding_sources(session: &Session, cards: &[TimelineCardRecord]) -> Vec<EmbeddingSource> {
+    let mut sources = Vec::new();
+    if let Some(session_id) = session.id {
+        let text = format!("{}\n{}", session.title.trim(), session.summary.trim());
+        if !text.trim().is_empty() {
+            sources.push(EmbeddingSource {
+                owner_kind: "session",
+                owner_id: session_id,
+                text: truncate_chars(text.trim(), MAX_TEXT_CHARS),
+            });
+        }
+    }
+    for card in cards {
+        let Some(card_id) = card.id else {
+            continue;
+        };
+        let text = [&card.title, &card.summary, &card.detailed_summary]
+            .iter()
+            .map(|part| part.trim())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if !text.is_empty() {
+            sources.push(EmbeddingSource {
+                owner_kind: "card",
+                owner_id: card_id,
+                text: truncate_chars(&text, MAX_TEXT_CHARS),
+            });
+        }
+    }
+    sources
+}
+
+/// 为会话生成向量（文本未变化的条目复用已有向量），返回新生成的条数
+pub async fn index_session(
+    db: &Database,
+    client: &EmbeddingClient,
+    session_id: i64,
+) -> Result<usize> {
+    let session = db.get_session(session_id).await?;
+    let cards = db.get_timeline_cards_by_session(session_id).await?;
+    let sources = embedding_sources(&session, &cards);
+
+    let existing: HashMap<(String, i64), EmbeddingRecord> = db
+        .get_embeddings_by_session(session_id)
+        .await?
+        .into_iter()
+        .map(|record| ((record.owner_kind.clone(), record.owner_id), record))
+        .collect();
+
+    let now = crate::storage::local_now();
+    let mut records = Vec::with_capacity(sources.len());
+    let mut pending = Vec::new();
+    for source in &sources {
+        let hash = content_hash(client.model(), &source.text);
+        match existing.get(&(source.owner_kind.to_string(), source.owner_id)) {
+            Some(record) if record.content_hash == hash && record.model == client.model() => {
+                records.push(record.clone());
+            }
+            _ => pending.push((source, hash)),
+        }
+    }
+
+    let unchanged = pending.is_empty() && records.len() == existing.len();
+    if unchanged {
+        return Ok(0);
+    }
+
+    let texts: Vec<String> = pending
+        .iter()
+        .map(|(source, _)| source.text.clone())
+        .collect();
+    let vectors = client.embed(&texts).await?;
+    for ((source, hash), vector) in pending.iter().zip(vectors) {
+        records.push(EmbeddingRecord {
+            session_id,
+            owner_kind: source.owner_kind.to_string(),
+            owner_id: source.owner_id,
+            model: client.model().to_string(),
+            content_hash: hash.clone(),
+            vector: encode_vector(&vector),
+            updated_at: now,
+        });
+    }
+
+    db.replace_embeddings(session_id, &records).await?;
+    Ok(pending.len())
+}
+
+/// 批量生成向量的结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EmbeddingIndexReport {
+    pub sessions: usize,
+    pub embedded: usize,
+    pub failed: usize,
+}
+
+impl EmbeddingIndexReport {
+    pub fn render_message(&self) -> String {
+        let mut message = format!(
+            "已检查 {} 个会话，新生成 {} 条向量",
+            self.sessions, self.embedded
+        );
+        if self.failed > 0 {
+            message.push_str(&format!("，{} 个会话失败", self.failed));
+        }
+        message
+    }
+}
+
+/// 为日期范围内（含首尾）的会话补齐向量
+pub async fn index_range(
+    db: &Database,
+    client: &EmbeddingClient,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+) -> Result<EmbeddingIndexReport> {
+    let mut report = EmbeddingIndexReport::default();
+    let sessions = db.get_all_sessions().await?;
+    for session in sessions {
+        let date = session.start_time.date_naive();
+        let Some(session_id) = session.id else {
+            continue;
+        };
+        if date < start_date || date > end_date {
+            continue;
+        }
+
+        report.sessions += 1;
+        match index_session(db, client, session_id).await {
+            Ok(count) => report.embedded += count,
+            Err(e) => {
+                warn!("会话 {} 生成向量失败: {}", session_id, e);
+                report.failed += 1;
+            }
+        }
+    }
+    info!("向量补齐完成: {}", report.render_message());
+    Ok(report)
+}
+
+/// 按语义检索会话：每个会话取最相似条目的得分，片段为对应的总结或卡片文本
+pub async fn semantic_search(
+    db: &Database,
+    client: &EmbeddingClient,
+    query: &str,
+    start_date: &str,
+    end_date: &str,
+    limit: usize,
+) -> Result<Vec<SessionSearchHit>> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let query_vector = client
+        .embed(&[query.to_string()])
+        .await?
+        .into_iter()
+        .next()
+        .context("向量接口未返回查询向量")?;
+    let records = db
+        .get_embeddings(client.model(), start_date, end_date)
+        .await?;
+
+    let ranked = rank_records(&query_vector, &records, limit);
+
+    let mut hits = Vec::with_capacity(ranked.len());
+    for (record, score) in ranked {
+        let session = match db.get_session(record.session_id).await {
+            Ok(session) => session,
+            Err(e) => {
+                warn!("读取会话 {} 失败: {}", record.session_id, e);
+                continue;
+            }
+        };
+        let snippet = if record.owner_kind == "card" {
+            db.get_timeline_cards_by_session(record.session_id)
+                .await?
+                .into_iter()
+                .find(|card| card.id == Some(record.owner_id))
+                .map(|card| format!("{}：{}", card.title, card.summary))
+                .unwrap_or_default()
+        } else {
+            session.summary.clone()
+        };
+
+        hits.push(SessionSearchHit {
+            session_id: record.session_id,
+            title: session.title,
+            start_time: session.start_time,
+            end_time: session.end_time,
+            snippet: truncate_chars(&snippet, SNIPPET_CHARS),
+            score: score as f64,
+        });
+    }
+    Ok(hits)
+}
+
+/// 计算相似度并按会话去重，返回每个会话得分最高的条目
+fn rank_records<'a>(
+    query_vector: &[f32],
+    records: &'a [EmbeddingRecord],
+    limit: usize,
+) -> Vec<(&'a EmbeddingRecord, f32)> {
+    let mut best: BTreeMap<i64, (&EmbeddingRecord, f32)> = BTreeMap::new();
+    for record in records {
+        let score = cosine_similarity(query_vector, &decode_vector(&record.vector));
+        if score < MIN_SCORE {
+            continue;
+        }
+        let entry = best.entry(record.session_id).or_insert((record, score));
+        if score > entry.1 {
+            *entry = (record, score);
+        }
+    }
+
+    let mut ranked: Vec<_> = best.into_values().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked.truncate(limit);
+    ranked
+}
+
+/// 会话分析完成后自动生成向量
+pub struct EmbeddingIndexer {
+    settings: Arc<SettingsManager>,
+    db: Arc<Database>,
+}
+
+impl EmbeddingIndexer {
+    pub fn new(settings: Arc<SettingsManager>, db: Arc<Database>) -> Self {
+        Self { settings, db }
+    }
+
+    pub fn start(self: Arc<Self>, event_bus: Arc<EventBus>) {
+        let mut receiver = event_bus.subscribe();
+
+        tokio::spawn(async move {
+            info!("语义向量监听器已启动");
+            while let Ok(event) = receiver.recv().await {
+                let AppEvent::AnalysisCompleted { session_id, .. } = event else {
+                    continue;
+                };
+                let app_config = self.settings.get().await;
+                let config = app_config.embedding_config.clone().unwrap_or_default();
+                if !config.enabled {
+                    continue;
+                }
+                let client = match EmbeddingClient::from_config(
+                    &config,
+                    &app_config.llm_provider,
+                    app_config.llm_config.as_ref(),
+                ) {
+                    Ok(client) => client,
+                    Err(e) => {
+                        warn!("语义向量配置无效: {}", e);
+                        continue;
+                    }
+                };
+                match index_session(&self.db, &client, session_id).await {
+                    Ok(count) if count > 0 => {
+                        info!("会话 {} 生成了 {} 条向量", session_id, count)
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("会话 {} 生成向量失败: {}", session_id, e),
+                }
+            }
+            warn!("语义向量监听器已停止");
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_record(
+        session_id: i64,
+        owner_kind: &str,
+        owner_id: i64,
+        vector: &[f32],
+    ) -> EmbeddingRecord {
+        EmbeddingRecord {
+            session_id,
+            owner_kind: owner_kind.to_string(),
+            owner_id,
+            model: "test".to_string(),
+            content_hash: String::new(),
+            vector: encode_vector(vector),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_vector_roundtrip_and_similarity() {
+        let vector = vec![0.5f32, -1.25, 3.0];
+        assert_eq!(decode_vector(&encode_vector(&vector)), vector);
+        assert!((cosine_similarity(&vector, &vector) - 1.0).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+
+        assert_eq!(
+            embeddings_endpoint(
+                "https://dashscope.aliyuncs.com/compatible-mode/v1/chat/completions"
+            ),
+            "https://dashscope.aliyuncs.com/compatible-mode/v1/embeddings"
+        );
+        assert_eq!(
+            embeddings_endpoint("https://api.openai.com/v1/"),
+            "https://api.openai.com/v1/embeddings"
+        );
+
+        let body = json!({"data": [
+            {"index": 1, "embedding": [0.0, 1.0]},
+            {"index": 0, "embedding": [1.0, 0.0]}
+        ]});
+        assert_eq!(
+            parse_openai_response(&body).unwrap(),
+            vec![vec![1.0, 0.0], vec![0.0, 1.0]]
+        );
+    }
+
+    #[test]
+    fn test_rank_records_keeps_best_match_per_session() {
+        let records = vec![
+            test_record(1, "session", 1, &[1.0, 0.0]),
+            test_record(1, "card", 10, &[0.6, 0.8]),
+            test_record(2, "card", 20, &[0.9, 0.1]),
+            test_record(3, "session", 3, &[-1.0, 0.0]),
+        ];
+        let ranked = rank_records(&[0.0, 1.0], &records, 10);
+        let ids: Vec<(i64, i64)> = ranked
+            .iter()
+            .map(|(record, _)| (record.session_id, record.owner_id))
+            .collect();
+        assert_eq!(ids, vec![(1, 10)]);
+
+        let ranked = rank_records(&[1.0, 0.0], &records, 1);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].0.owner_id, 1);
+    }
+}
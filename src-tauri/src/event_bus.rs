@@ -12,6 +12,12 @@ use tokio::sync::broadcast;
 #[derive(Debug, Clone)]
 pub enum AppEvent {
     // --- 捕获事件 ---
+    /// 会话开始事件（新时间窗的第一张截图完成时发布）
+    SessionStarted {
+        session_id: i64,
+        window_start: DateTime<Utc>,
+    },
+
     /// 截屏完成事件
     ScreenshotCaptured {
         session_id: i64,
@@ -25,6 +31,8 @@ pub enum AppEvent {
         frame_count: usize,
         window_start: DateTime<Utc>,
         window_end: DateTime<Utc>,
+        /// 手动会话的自定义标题
+        title: Option<String>,
     },
 
     // --- 分析事件 ---
@@ -150,6 +158,7 @@ mod tests {
             frame_count: 10,
             window_start: crate::storage::local_now(),
             window_end: crate::storage::local_now(),
+            title: None,
         });
 
         // 两个订阅者都应该收到事件
@@ -0,0 +1,492 @@
+// 笔记导出目标 - Obsidian、Notion、Logseq、HTML 报告、Webhook、工时服务等导出器共用的接口、数据加载与注册表
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::actors::LLMHandle;
+use crate::domains::summary::{category_key, DaySummary, SummaryGenerator};
+use crate::frame_store;
+use crate::html::HtmlExporter;
+use crate::logseq::LogseqExporter;
+use crate::models::{ActivityTag, PersistedAppConfig};
+use crate::notion::NotionExporter;
+use crate::obsidian::ObsidianExporter;
+use crate::storage::{
+    AppUsageRecord, Database, DomainUsageRecord, IssueLinkRecord, Session, TimelineCardRecord,
+};
+use crate::time_tracking::TimeTrackingExporter;
+use crate::webhook::WebhookExporter;
+
+/// 批量导出单次允许的最大天数
+pub const MAX_RANGE_DAYS: i64 = 366;
+
+/// 生成（或读取已缓存的）当天总结，各导出目标共用同一条总结流程
+pub async fn day_summary(
+    db: Arc<Database>,
+    llm_handle: LLMHandle,
+    date: &str,
+    force_refresh: bool,
+) -> Result<DaySummary> {
+    SummaryGenerator::with_llm(db, llm_handle)
+        .generate_day_summary(date, force_refresh)
+        .await
+        .map_err(|e| anyhow!(e))
+}
+
+/// 单个会话的导出数据（会话、时间线卡片与关联工单）
+pub struct SessionExportData {
+    pub session: Session,
+    pub cards: Vec<TimelineCardRecord>,
+    pub issue_links: Vec<IssueLinkRecord>,
+    /// 前台应用使用时长（按时长降序）
+    pub apps: Vec<AppUsageRecord>,
+    /// 网站域名访问时长（按时长降序）
+    pub domains: Vec<DomainUsageRecord>,
+}
+
+/// 加载当天的会话及其时间线卡片、关联工单、应用与域名使用时长（查询失败时视为没有）
+pub async fn load_day_sessions(db: &Database, date: &str) -> Result<Vec<SessionExportData>> {
+    let mut sessions = Vec::new();
+    for session in db.get_sessions_by_date(date).await? {
+        let (cards, issue_links, apps, domains) = match session.id {
+            Some(session_id) => (
+                db.get_timeline_cards_by_session(session_id)
+                    .await
+                    .unwrap_or_default(),
+                db.get_issue_links_by_session(session_id)
+                    .await
+                    .unwrap_or_default(),
+                db.get_app_usage_by_session(session_id)
+                    .await
+                    .unwrap_or_default(),
+                db.get_domain_usage_by_session(session_id)
+                    .await
+                    .unwrap_or_default(),
+            ),
+            None => (Vec::new(), Vec::new(), Vec::new(), Vec::new()),
+        };
+        sessions.push(SessionExportData {
+            session,
+            cards,
+            issue_links,
+            apps,
+            domains,
+        });
+    }
+    Ok(sessions)
+}
+
+/// 会话时长（分钟）
+pub fn session_minutes(session: &Session) -> i64 {
+    (session.end_time - session.start_time).num_minutes().max(0)
+}
+
+/// 会话的主分类（取第一个标签）
+pub fn session_category(session: &Session) -> Option<&'static str> {
+    serde_json::from_str::<Vec<ActivityTag>>(&session.tags)
+        .ok()?
+        .first()
+        .map(|tag| category_key(&tag.category))
+}
+
+/// 时间线卡片时间（RFC3339）-> HH:MM，无法解析时原样返回
+pub fn card_clock(time: &str) -> String {
+    DateTime::parse_from_rfc3339(time)
+        .map(|parsed| parsed.format("%H:%M").to_string())
+        .unwrap_or_else(|_| time.to_string())
+}
+
+/// 解析批量导出的日期范围（含首尾）
+pub fn range_days(start_date: &str, end_date: &str) -> Result<Vec<NaiveDate>> {
+    let start = NaiveDate::parse_from_str(start_date, "%Y-%m-%d")
+        .map_err(|_| anyhow!("无效的开始日期: {}", start_date))?;
+    let end = NaiveDate::parse_from_str(end_date, "%Y-%m-%d")
+        .map_err(|_| anyhow!("无效的结束日期: {}", end_date))?;
+    if start > end {
+        return Err(anyhow!("开始日期不能晚于结束日期"));
+    }
+    if (end - start).num_days() >= MAX_RANGE_DAYS {
+        return Err(anyhow!("单次最多导出 {} 天", MAX_RANGE_DAYS));
+    }
+    Ok(start.iter_days().take_while(|day| *day <= end).collect())
+}
+
+/// 导出预览中的单个笔记（不写入目标）
+#[derive(Debug, Clone, Serialize)]
+pub struct NotePreview {
+    pub path: PathBuf,
+    pub content: String,
+    /// 文件已存在且内容将发生变化
+    pub would_overwrite: bool,
+}
+
+/// 导出结果（各导出目标通用的精简视图）
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExportReport {
+    /// 导出目标名称
+    pub target: String,
+    pub exported_dates: Vec<String>,
+    /// 写入的当天笔记（Obsidian 每日笔记 / Logseq 日志页面；Notion 为空）
+    pub note_paths: Vec<PathBuf>,
+    /// 导出的会话数量
+    pub session_count: usize,
+    /// 同步失败的条目数量（其余条目照常导出，失败原因记录在警告中）
+    pub failed_count: usize,
+    /// 本次写入的全部文件
+    pub written_paths: Vec<PathBuf>,
+    pub warnings: Vec<String>,
+}
+
+impl ExportReport {
+    /// 合并批量导出中某一天的结果，警告加上日期前缀
+    fn merge(&mut self, date: &str, other: ExportReport) {
+        self.exported_dates.extend(other.exported_dates);
+        self.note_paths.extend(other.note_paths);
+        self.session_count += other.session_count;
+        self.failed_count += other.failed_count;
+        self.written_paths.extend(other.written_paths);
+        self.warnings.extend(
+            other
+                .warnings
+                .into_iter()
+                .map(|warning| format!("[{}] {}", date, warning)),
+        );
+    }
+
+    /// 渲染提示信息
+    pub fn render_message(&self) -> String {
+        let mut message = match self.exported_dates.as_slice() {
+            [first, .., last] => format!(
+                "已批量导出到 {} {} 天: {} ~ {}",
+                self.target,
+                self.exported_dates.len(),
+                first,
+                last
+            ),
+            [date] => format!("已导出到 {}: {}", self.target, date),
+            [] => format!("已导出到 {}", self.target),
+        };
+        message.push_str(&format!("\n会话数量: {}", self.session_count));
+        if self.failed_count > 0 {
+            message.push_str(&format!(
+                "\n部分导出: {} 项失败，其余内容已导出",
+                self.failed_count
+            ));
+        }
+        if let [path] = self.note_paths.as_slice() {
+            message.push_str("\n笔记: ");
+            message.push_str(&path.to_string_lossy());
+        } else if !self.note_paths.is_empty() {
+            message.push_str(&format!("\n笔记: {} 个", self.note_paths.len()));
+        }
+        if !self.warnings.is_empty() {
+            message.push_str("\n\n警告:\n");
+            for warning in &self.warnings {
+                message.push_str("- ");
+                message.push_str(warning);
+                message.push('\n');
+            }
+        }
+        message
+    }
+}
+
+/// 笔记导出目标
+#[async_trait]
+pub trait Exporter: Send + Sync {
+    /// 导出目标名称（用于提示信息与日志）
+    fn target_name(&self) -> &'static str;
+
+    /// 导出指定日期：生成当天总结并写入目标
+    async fn export_day(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        date: &str,
+        force_refresh: bool,
+    ) -> Result<ExportReport>;
+
+    /// 批量导出日期范围（含首尾）
+    ///
+    /// 默认逐日调用 `export_day`（复用已缓存的每日总结），跳过没有会话的日期；
+    /// 单日失败只记录警告，不中断整体导出。
+    async fn export_range(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<ExportReport> {
+        let mut report = ExportReport {
+            target: self.target_name().to_string(),
+            ..ExportReport::default()
+        };
+        let mut failures = Vec::new();
+        for day in range_days(start_date, end_date)? {
+            let date = day.format("%Y-%m-%d").to_string();
+            if let Ok(sessions) = db.get_sessions_by_date(&date).await {
+                if sessions.is_empty() {
+                    continue;
+                }
+            }
+            match self
+                .export_day(db.clone(), llm_handle.clone(), &date, false)
+                .await
+            {
+                Ok(day_report) => report.merge(&date, day_report),
+                Err(err) => failures.push(format!("[{}] 导出失败: {}", date, err)),
+            }
+        }
+
+        if report.exported_dates.is_empty() {
+            return Err(match failures.first() {
+                Some(failure) => anyhow!("日期范围内没有导出成功的日期: {}", failure),
+                None => anyhow!("{} ~ {} 没有会话记录", start_date, end_date),
+            });
+        }
+        report.warnings.extend(failures);
+        Ok(report)
+    }
+
+    /// 预览指定日期将要写入的内容（不写入目标）
+    async fn preview_day(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        date: &str,
+    ) -> Result<Vec<NotePreview>>;
+}
+
+/// 导出目标描述（供前端统一列出）
+#[derive(Debug, Clone, Serialize)]
+pub struct ExporterInfo {
+    /// 导出目标标识（obsidian / notion / logseq / html / webhook / time_tracking）
+    pub id: &'static str,
+    pub name: &'static str,
+    pub enabled: bool,
+    /// 已启用且配置完整，可以直接导出
+    pub ready: bool,
+    /// 无法导出时的原因
+    pub status: Option<String>,
+}
+
+struct RegistryEntry {
+    info: ExporterInfo,
+    exporter: Option<Box<dyn Exporter>>,
+}
+
+/// 导出目标注册表：按当前配置构建各导出器
+pub struct ExporterRegistry {
+    entries: Vec<RegistryEntry>,
+}
+
+impl ExporterRegistry {
+    /// 按应用配置构建注册表（未启用或配置不完整的目标只保留描述）
+    pub fn from_config(config: &PersistedAppConfig) -> Self {
+        let frame_store =
+            frame_store::from_config(&config.frame_store_config.clone().unwrap_or_default());
+
+        let obsidian = config.obsidian_config.clone().unwrap_or_default();
+        let obsidian_entry = RegistryEntry::build("obsidian", "Obsidian", obsidian.enabled, || {
+            let exporter =
+                ObsidianExporter::new(obsidian.clone()).with_frame_store(frame_store.clone());
+            exporter.check_target()?;
+            Ok(Box::new(exporter) as Box<dyn Exporter>)
+        });
+
+        let notion = config.notion_config.clone().unwrap_or_default();
+        let notion_entry = RegistryEntry::build("notion", "Notion", notion.enabled, || {
+            let exporter = NotionExporter::new(notion.clone(), obsidian.clone())?
+                .with_frame_store(frame_store.clone());
+            Ok(Box::new(exporter) as Box<dyn Exporter>)
+        });
+
+        let logseq = config.logseq_config.clone().unwrap_or_default();
+        let logseq_entry = RegistryEntry::build("logseq", "Logseq", logseq.enabled, || {
+            let exporter =
+                LogseqExporter::new(logseq.clone()).with_frame_store(frame_store.clone());
+            exporter.resolve_graph()?;
+            Ok(Box::new(exporter) as Box<dyn Exporter>)
+        });
+
+        let html = config.html_config.clone().unwrap_or_default();
+        let html_entry = RegistryEntry::build("html", "HTML", html.enabled, || {
+            let exporter = HtmlExporter::new(html.clone()).with_frame_store(frame_store.clone());
+            exporter.resolve_output_dir()?;
+            Ok(Box::new(exporter) as Box<dyn Exporter>)
+        });
+
+        let webhook = config.webhook_config.clone().unwrap_or_default();
+        let webhook_entry = RegistryEntry::build("webhook", "Webhook", webhook.enabled, || {
+            let exporter = WebhookExporter::new(webhook.clone())?;
+            Ok(Box::new(exporter) as Box<dyn Exporter>)
+        });
+
+        let time_tracking = config.time_tracking_config.clone().unwrap_or_default();
+        let time_tracking_entry = RegistryEntry::build(
+            "time_tracking",
+            "Toggl / Clockify",
+            time_tracking.enabled,
+            || {
+                let exporter = TimeTrackingExporter::new(time_tracking.clone())?;
+                Ok(Box::new(exporter) as Box<dyn Exporter>)
+            },
+        );
+
+        Self {
+            entries: vec![
+                obsidian_entry,
+                notion_entry,
+                logseq_entry,
+                html_entry,
+                webhook_entry,
+                time_tracking_entry,
+            ],
+        }
+    }
+
+    /// 列出全部导出目标
+    pub fn list(&self) -> Vec<ExporterInfo> {
+        self.entries
+            .iter()
+            .map(|entry| entry.info.clone())
+            .collect()
+    }
+
+    /// 获取可用的导出器；未知、未启用或配置不完整时返回原因
+    pub fn get(&self, id: &str) -> Result<&dyn Exporter> {
+        let entry = self
+            .entries
+            .iter()
+            .find(|entry| entry.info.id == id)
+            .ok_or_else(|| anyhow!("未知的导出目标: {}", id))?;
+        match &entry.exporter {
+            Some(exporter) => Ok(exporter.as_ref()),
+            None => Err(anyhow!("{}", entry.info.status.clone().unwrap_or_default())),
+        }
+    }
+}
+
+impl RegistryEntry {
+    fn build(
+        id: &'static str,
+        name: &'static str,
+        enabled: bool,
+        build: impl FnOnce() -> Result<Box<dyn Exporter>>,
+    ) -> Self {
+        let result = if enabled {
+            build()
+        } else {
+            Err(anyhow!("{} 导出未启用，请在设置中开启", name))
+        };
+        let (exporter, status) = match result {
+            Ok(exporter) => (Some(exporter), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        Self {
+            info: ExporterInfo {
+                id,
+                name,
+                enabled,
+                ready: exporter.is_some(),
+                status,
+            },
+            exporter,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{LogseqExportConfig, ObsidianExportConfig};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_registry_lists_targets_with_status() {
+        let graph = tempdir().unwrap();
+        let config = PersistedAppConfig {
+            obsidian_config: Some(ObsidianExportConfig {
+                enabled: true,
+                ..ObsidianExportConfig::default()
+            }),
+            logseq_config: Some(LogseqExportConfig {
+                enabled: true,
+                graph_path: graph.path().to_string_lossy().to_string(),
+                include_screenshots: false,
+            }),
+            ..PersistedAppConfig::default()
+        };
+        let registry = ExporterRegistry::from_config(&config);
+
+        let infos = registry.list();
+        assert_eq!(
+            infos.iter().map(|info| info.id).collect::<Vec<_>>(),
+            vec![
+                "obsidian",
+                "notion",
+                "logseq",
+                "html",
+                "webhook",
+                "time_tracking"
+            ]
+        );
+        // Obsidian 已启用但未配置 Vault
+        assert!(infos[0].enabled && !infos[0].ready);
+        assert_eq!(
+            infos[0].status.as_deref(),
+            Some("请先配置 Obsidian Vault 路径")
+        );
+        assert!(!infos[1].enabled && !infos[1].ready);
+        assert!(infos[2].ready && infos[2].status.is_none());
+        assert!(!infos[3].enabled && !infos[3].ready);
+        assert!(!infos[4].enabled && !infos[4].ready);
+        assert!(!infos[5].enabled && !infos[5].ready);
+
+        assert_eq!(registry.get("logseq").unwrap().target_name(), "Logseq");
+        assert_eq!(
+            registry.get("notion").err().unwrap().to_string(),
+            "Notion 导出未启用，请在设置中开启"
+        );
+        assert!(registry.get("pdf").is_err());
+    }
+
+    #[test]
+    fn test_range_days() {
+        let days = range_days("2024-02-28", "2024-03-01").unwrap();
+        assert_eq!(
+            days.iter()
+                .map(|day| day.format("%m-%d").to_string())
+                .collect::<Vec<_>>(),
+            vec!["02-28", "02-29", "03-01"]
+        );
+        assert!(range_days("2024-03-02", "2024-03-01").is_err());
+        assert!(range_days("2024-01-01", "2025-01-01").is_err());
+    }
+
+    #[test]
+    fn test_merge_partial_results() {
+        let mut report = ExportReport {
+            target: "Notion".to_string(),
+            ..ExportReport::default()
+        };
+        report.merge(
+            "2024-05-01",
+            ExportReport {
+                exported_dates: vec!["2024-05-01".to_string()],
+                session_count: 3,
+                failed_count: 1,
+                warnings: vec!["会话 7 同步失败: rate_limited".to_string()],
+                ..ExportReport::default()
+            },
+        );
+        assert_eq!(report.failed_count, 1);
+        let message = report.render_message();
+        assert!(message.contains("1 项失败"));
+        assert!(message.contains("[2024-05-01] 会话 7 同步失败"));
+    }
+}
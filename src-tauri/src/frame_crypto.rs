@@ -0,0 +1,286 @@
+// 截图静态加密 - 帧文件写入时以 AES-256-GCM 加密，密钥保存在系统钥匙串
+//
+// 加密文件格式：魔数 + 12 字节随机数 + 密文（含认证标签），扩展名保持不变。读取时按魔数识别，
+// 未加密的截图照常读取；关闭加密后仍会加载已有密钥，此前加密的截图依旧可读。
+// 进程内的读取（LLM、导出、查看）只在内存中解密；ffmpeg、tesseract 等外部程序需要文件路径，
+// 解密到临时目录并在使用后删除
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use image::DynamicImage;
+use std::io::Read;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use tempfile::TempDir;
+use tracing::{info, warn};
+
+use crate::keychain;
+
+/// 加密帧文件头（含格式版本）
+const MAGIC: &[u8; 8] = b"SAFRAME1";
+
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + NONCE_LEN;
+
+/// 认证标签长度
+const TAG_LEN: usize = 16;
+
+/// 钥匙串中的密钥名
+const KEYCHAIN_ACCOUNT: &str = "frame-encryption-key";
+
+/// 当前加载的密钥（未启用且从未生成过密钥时为空）
+static CIPHER: RwLock<Option<Arc<Aes256Gcm>>> = RwLock::new(None);
+
+/// 新写入的截图是否加密
+static ENCRYPT_ON_WRITE: AtomicBool = AtomicBool::new(false);
+
+/// 按截屏设置启用或关闭加密（会访问系统钥匙串，需在阻塞线程中调用）
+///
+/// 启用时从钥匙串读取密钥，不存在则生成并保存；关闭时只尝试读取已有密钥
+pub fn configure(enabled: bool) -> Result<()> {
+    let key = match keychain::load_key(KEYCHAIN_ACCOUNT, enabled) {
+        Ok(key) => key,
+        Err(e) if !enabled => {
+            warn!("读取截图加密密钥失败，已加密的截图将无法查看: {}", e);
+            None
+        }
+        Err(e) => return Err(e),
+    };
+    let cipher = key
+        .map(|key| Aes256Gcm::new_from_slice(&key).map(Arc::new))
+        .transpose()
+        .map_err(|e| anyhow!("初始化截图加密失败: {}", e))?;
+
+    if let Ok(mut current) = CIPHER.write() {
+        *current = cipher;
+    }
+    if ENCRYPT_ON_WRITE.swap(enabled, Ordering::SeqCst) != enabled {
+        info!("截图静态加密已{}", if enabled { "启用" } else { "关闭" });
+    }
+    Ok(())
+}
+
+/// 新写入的截图是否加密
+pub fn is_enabled() -> bool {
+    ENCRYPT_ON_WRITE.load(Ordering::SeqCst)
+}
+
+/// 内容是否为加密帧
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// 按当前设置处理待写入的帧内容（未启用加密时原样返回）
+pub fn seal(plain: Vec<u8>) -> Result<Vec<u8>> {
+    if !is_enabled() {
+        return Ok(plain);
+    }
+    encrypt(cipher()?.as_ref(), &plain)
+}
+
+/// 还原帧内容（未加密时原样返回）
+pub fn open(data: Vec<u8>) -> Result<Vec<u8>> {
+    if !is_encrypted(&data) {
+        return Ok(data);
+    }
+    decrypt(cipher()?.as_ref(), &data)
+}
+
+/// 读取帧文件的明文内容
+pub async fn read_frame(path: impl AsRef<Path>) -> Result<Vec<u8>> {
+    open(tokio::fs::read(path).await?)
+}
+
+/// 读取帧文件的明文内容（阻塞版本）
+pub fn read_frame_blocking(path: impl AsRef<Path>) -> Result<Vec<u8>> {
+    open(std::fs::read(path)?)
+}
+
+/// 解码帧图片
+pub fn open_image(path: impl AsRef<Path>) -> Result<DynamicImage> {
+    Ok(image::load_from_memory(&read_frame_blocking(path)?)?)
+}
+
+/// 帧文件的明文大小（只读取文件头）
+pub async fn plaintext_len(path: impl AsRef<Path>) -> Result<u64> {
+    let path = path.as_ref().to_path_buf();
+    tokio::task::spawn_blocking(move || -> Result<u64> {
+        let len = std::fs::metadata(&path)?.len();
+        Ok(if file_is_encrypted(&path)? {
+            len.saturating_sub((HEADER_LEN + TAG_LEN) as u64)
+        } else {
+            len
+        })
+    })
+    .await?
+}
+
+/// 加密未加密的帧文件（保留修改时间，不影响按保留期清理），返回是否做了加密
+pub fn encrypt_file(path: &Path) -> Result<bool> {
+    if file_is_encrypted(path)? {
+        return Ok(false);
+    }
+    let metadata = std::fs::metadata(path)?;
+    let sealed = encrypt(cipher()?.as_ref(), &std::fs::read(path)?)?;
+
+    // 先写临时文件再替换，中途失败不会留下残缺的截图
+    let staging = path.with_extension("encrypting");
+    std::fs::write(&staging, sealed)?;
+    std::fs::File::options()
+        .write(true)
+        .open(&staging)?
+        .set_modified(metadata.modified()?)?;
+    std::fs::rename(&staging, path)?;
+    Ok(true)
+}
+
+/// 供外部程序读取的帧文件：加密的帧解密到临时目录，临时目录在该结构释放时删除
+pub struct PlaintextFrames {
+    _dir: Option<TempDir>,
+    pub paths: Vec<String>,
+}
+
+/// 准备外部程序（ffmpeg、tesseract、Codex CLI）使用的帧路径，未加密的帧沿用原路径
+pub async fn plaintext_frames(paths: &[String]) -> Result<PlaintextFrames> {
+    let paths = paths.to_vec();
+    tokio::task::spawn_blocking(move || -> Result<PlaintextFrames> {
+        let mut dir: Option<TempDir> = None;
+        let mut output = Vec::with_capacity(paths.len());
+        for (index, path) in paths.into_iter().enumerate() {
+            // 读取失败的帧保留原路径，由外部程序按原有方式报错
+            if !file_is_encrypted(Path::new(&path)).unwrap_or(false) {
+                output.push(path);
+                continue;
+            }
+            let dir = match &mut dir {
+                Some(dir) => dir,
+                None => dir.insert(
+                    tempfile::Builder::new()
+                        .prefix("screen-analyzer-plain-")
+                        .tempdir()?,
+                ),
+            };
+            let name = Path::new(&path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| "frame.jpg".to_string());
+            let target = dir.path().join(format!("{:05}_{}", index, name));
+            std::fs::write(&target, read_frame_blocking(&path)?)
+                .with_context(|| format!("解密截图失败: {}", path))?;
+            output.push(target.to_string_lossy().to_string());
+        }
+        Ok(PlaintextFrames {
+            _dir: dir,
+            paths: output,
+        })
+    })
+    .await?
+}
+
+/// 以 data URL 形式返回帧图片（供界面显示加密的截图）
+pub async fn data_url(path: impl AsRef<Path>) -> Result<String> {
+    let bytes = read_frame(path).await?;
+    let mime = image::guess_format(&bytes)
+        .map(|format| format.to_mime_type())
+        .unwrap_or("image/jpeg");
+    Ok(format!(
+        "data:{};base64,{}",
+        mime,
+        general_purpose::STANDARD.encode(bytes)
+    ))
+}
+
+/// 文件是否为加密帧（只读取文件头）
+pub fn file_is_encrypted(path: &Path) -> Result<bool> {
+    let mut header = [0u8; MAGIC.len()];
+    let mut file = std::fs::File::open(path)?;
+    match file.read_exact(&mut header) {
+        Ok(()) => Ok(&header == MAGIC),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn cipher() -> Result<Arc<Aes256Gcm>> {
+    CIPHER
+        .read()
+        .ok()
+        .and_then(|cipher| cipher.clone())
+        .ok_or_else(|| anyhow!("缺少截图解密密钥，请确认系统钥匙串中的密钥未被删除"))
+}
+
+fn encrypt(cipher: &Aes256Gcm, plain: &[u8]) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+
+    let mut output = Vec::with_capacity(HEADER_LEN + plain.len() + TAG_LEN);
+    output.extend_from_slice(MAGIC);
+    output.extend_from_slice(&nonce);
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plain,
+                aad: &output,
+            },
+        )
+        .map_err(|_| anyhow!("加密截图失败"))?;
+    output.extend_from_slice(&ciphertext);
+    Ok(output)
+}
+
+fn decrypt(cipher: &Aes256Gcm, data: &[u8]) -> Result<Vec<u8>> {
+    if data.len() < HEADER_LEN + TAG_LEN {
+        return Err(anyhow!("加密截图不完整"));
+    }
+    let (header, ciphertext) = data.split_at(HEADER_LEN);
+    cipher
+        .decrypt(
+            Nonce::from_slice(&header[MAGIC.len()..]),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| anyhow!("截图解密失败：密钥不匹配或文件已损坏"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let cipher = Aes256Gcm::new_from_slice(&[7u8; 32]).unwrap();
+        let plain = b"\xff\xd8\xff\xe0 jpeg".to_vec();
+
+        let sealed = encrypt(&cipher, &plain).unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(!is_encrypted(&plain));
+        assert_eq!(sealed.len(), plain.len() + HEADER_LEN + TAG_LEN);
+        assert_eq!(decrypt(&cipher, &sealed).unwrap(), plain);
+        // 每次加密使用不同的随机数
+        assert_ne!(encrypt(&cipher, &plain).unwrap(), sealed);
+
+        // 密钥错误、内容篡改或截断都会认证失败
+        let other = Aes256Gcm::new_from_slice(&[8u8; 32]).unwrap();
+        assert!(decrypt(&other, &sealed).is_err());
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt(&cipher, &tampered).is_err());
+        assert!(decrypt(&cipher, &sealed[..HEADER_LEN]).is_err());
+
+        // 未加密的内容原样读取
+        assert_eq!(open(plain.clone()).unwrap(), plain);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("1700000000000.jpg");
+        std::fs::write(&path, &sealed).unwrap();
+        assert!(file_is_encrypted(&path).unwrap());
+        std::fs::write(&path, b"jp").unwrap();
+        assert!(!file_is_encrypted(&path).unwrap());
+    }
+}
@@ -0,0 +1,327 @@
+// 视觉调用前的帧预处理 - 按画面变化挑选每个会话窗口的代表帧，缩放到提供商的最佳分辨率，
+// 代表帧多于单次调用的图片上限时拼成网格（按从左到右、从上到下的时间顺序），
+// 处理结果写入临时目录，分析结束后随目录一并删除
+
+use crate::capture::dedupe::{dhash, hamming_distance};
+use crate::capture::ScreenFrame;
+use crate::models::FramePrepConfig;
+use anyhow::{anyhow, Context, Result};
+use image::codecs::jpeg::JpegEncoder;
+use image::{imageops, DynamicImage, GenericImageView, Rgb, RgbImage};
+use std::fs::File;
+use std::io::BufWriter;
+use tempfile::TempDir;
+use tracing::{info, warn};
+
+/// 网格每行最多的图片数（3x3 时单格仍能辨认窗口布局）
+const MAX_GRID_COLUMNS: u32 = 3;
+
+/// 网格中相邻图片的间隔像素
+const GRID_GAP: u32 = 4;
+
+/// 网格背景色
+const GRID_BACKGROUND: Rgb<u8> = Rgb([32, 32, 32]);
+
+/// 粗略估算图片 token 数时每个 token 对应的像素数
+const PIXELS_PER_TOKEN: u64 = 750;
+
+/// 预处理统计
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PrepStats {
+    /// 输入帧数
+    pub input_frames: usize,
+    /// 挑选出的代表帧数
+    pub selected_frames: usize,
+    /// 最终发送的图片数（拼图后）
+    pub output_images: usize,
+    /// 每张网格图的列数，1 表示未拼图
+    pub grid_columns: u32,
+    /// 处理前的估算图片 token 数
+    pub tokens_before: u64,
+    /// 处理后的估算图片 token 数
+    pub tokens_after: u64,
+}
+
+/// 预处理后的图片，临时目录在该结构释放时删除
+pub struct PreparedFrames {
+    _dir: TempDir,
+    pub paths: Vec<String>,
+    pub stats: PrepStats,
+}
+
+/// 各提供商的最佳长边像素（配置为 0 时使用）
+pub fn optimal_edge(provider: &str) -> u32 {
+    match provider {
+        // Claude 超过约 1.15MP 会在服务端再次缩放
+        "claude" => 1456,
+        // Gemini 按 768 像素切片计费，1536 正好切成 2x2
+        "gemini" => 1536,
+        // 本地视觉模型的输入分辨率普遍较低
+        "ollama" => 896,
+        _ => 1280,
+    }
+}
+
+/// 按画面变化挑选代表帧：首帧始终保留，与上一张保留帧几乎相同的帧被跳过，超出 limit 时均匀抽取
+pub fn select_representative(hashes: &[u64], threshold: u32, limit: usize) -> Vec<usize> {
+    let Some(&first) = hashes.first() else {
+        return Vec::new();
+    };
+
+    let mut kept = vec![0];
+    let mut last = first;
+    for (index, &hash) in hashes.iter().enumerate().skip(1) {
+        if hamming_distance(last, hash) > threshold {
+            kept.push(index);
+            last = hash;
+        }
+    }
+
+    let limit = limit.max(1);
+    if kept.len() <= limit {
+        return kept;
+    }
+    (0..limit).map(|i| kept[i * kept.len() / limit]).collect()
+}
+
+/// 代表帧数超过图片上限时每张网格图的列数，不需要拼图时返回 1
+pub fn grid_columns(selected: usize, max_images: usize) -> u32 {
+    let max_images = max_images.max(1);
+    if selected <= max_images {
+        return 1;
+    }
+    let per_image = selected.div_ceil(max_images);
+    let mut columns = 1;
+    while (columns * columns) < per_image as u32 && columns < MAX_GRID_COLUMNS {
+        columns += 1;
+    }
+    columns
+}
+
+/// 保持宽高比缩放到长边不超过 max_edge
+pub fn fit_size(width: u32, height: u32, max_edge: u32) -> (u32, u32) {
+    let long_edge = width.max(height);
+    if long_edge <= max_edge || long_edge == 0 {
+        return (width, height);
+    }
+    let scale = max_edge as f64 / long_edge as f64;
+    (
+        ((width as f64 * scale).round() as u32).max(1),
+        ((height as f64 * scale).round() as u32).max(1),
+    )
+}
+
+/// 粗略估算单张图片的 token 数
+pub fn estimate_tokens(width: u32, height: u32) -> u64 {
+    (width as u64 * height as u64).div_ceil(PIXELS_PER_TOKEN)
+}
+
+/// 缩放单张图片
+fn downscale(image: &DynamicImage, max_edge: u32) -> DynamicImage {
+    let (width, height) = image.dimensions();
+    let (target_w, target_h) = fit_size(width, height, max_edge);
+    if (target_w, target_h) == (width, height) {
+        return image.clone();
+    }
+    image.resize_exact(target_w, target_h, imageops::FilterType::Triangle)
+}
+
+/// 把多张图片按时间顺序拼成网格，整张图的长边不超过 max_edge
+pub fn stitch_grid(images: &[DynamicImage], columns: u32, max_edge: u32) -> RgbImage {
+    let columns = columns.clamp(1, images.len().max(1) as u32);
+    let rows = (images.len() as u32).div_ceil(columns).max(1);
+
+    // 单格尺寸以第一张图的宽高比为准
+    let (width, height) = images
+        .first()
+        .map(|image| image.dimensions())
+        .unwrap_or((1, 1));
+    let cell_edge =
+        (max_edge.saturating_sub(GRID_GAP * (columns.max(rows) - 1)) / columns.max(rows)).max(1);
+    let (cell_w, cell_h) = fit_size(width, height, cell_edge);
+
+    let mut canvas = RgbImage::from_pixel(
+        columns * cell_w + GRID_GAP * (columns - 1),
+        rows * cell_h + GRID_GAP * (rows - 1),
+        GRID_BACKGROUND,
+    );
+    for (index, image) in images.iter().enumerate() {
+        let tile = image
+            .resize(cell_w, cell_h, imageops::FilterType::Triangle)
+            .to_rgb8();
+        let column = index as u32 % columns;
+        let row = index as u32 / columns;
+        // 宽高比不同的图片在格内居中
+        let x = column * (cell_w + GRID_GAP) + (cell_w - tile.width()) / 2;
+        let y = row * (cell_h + GRID_GAP) + (cell_h - tile.height()) / 2;
+        imageops::replace(&mut canvas, &tile, x as i64, y as i64);
+    }
+    canvas
+}
+
+fn write_jpeg(image: &RgbImage, path: &std::path::Path, quality: u8) -> Result<()> {
+    let file = File::create(path).with_context(|| format!("无法创建 {}", path.display()))?;
+    let mut encoder = JpegEncoder::new_with_quality(BufWriter::new(file), quality.clamp(1, 100));
+    encoder.encode_image(image)?;
+    Ok(())
+}
+
+/// 预处理会话的采样帧；allow_grid 为 false 时（例如按视频帧序列发送）只挑选与缩放
+pub async fn prepare_frames(
+    config: &FramePrepConfig,
+    provider: &str,
+    frames: &[ScreenFrame],
+    allow_grid: bool,
+) -> Result<PreparedFrames> {
+    let config = config.clone();
+    let max_edge = if config.max_edge == 0 {
+        optimal_edge(provider)
+    } else {
+        config.max_edge
+    };
+    let paths: Vec<String> = frames.iter().map(|f| f.file_path.clone()).collect();
+
+    let prepared = tokio::task::spawn_blocking(move || {
+        prepare_blocking(&config, max_edge, &paths, allow_grid)
+    })
+    .await??;
+
+    let stats = &prepared.stats;
+    info!(
+        "帧预处理: {} 帧 → {} 张代表帧 → {} 张图片（{}列网格，长边 {}px），估算图片 token {} → {}",
+        stats.input_frames,
+        stats.selected_frames,
+        stats.output_images,
+        stats.grid_columns,
+        max_edge,
+        stats.tokens_before,
+        stats.tokens_after
+    );
+    Ok(prepared)
+}
+
+fn prepare_blocking(
+    config: &FramePrepConfig,
+    max_edge: u32,
+    paths: &[String],
+    allow_grid: bool,
+) -> Result<PreparedFrames> {
+    // 读取后立即缩放，避免同时持有多张原图
+    let mut images = Vec::with_capacity(paths.len());
+    let mut tokens_before = 0;
+    for path in paths {
+        match crate::frame_crypto::open_image(path) {
+            Ok(image) => {
+                let (width, height) = image.dimensions();
+                tokens_before += estimate_tokens(width, height);
+                images.push(downscale(&image, max_edge));
+            }
+            Err(e) => warn!("帧预处理读取失败 {}: {}", path, e),
+        }
+    }
+    if images.is_empty() {
+        return Err(anyhow!("没有可读取的截图帧"));
+    }
+
+    let max_images = config.max_frames.max(1);
+    let limit = if allow_grid && config.grid {
+        max_images * (MAX_GRID_COLUMNS * MAX_GRID_COLUMNS) as usize
+    } else {
+        max_images
+    };
+    let hashes: Vec<u64> = images.iter().map(dhash).collect();
+    let selected = select_representative(&hashes, config.change_threshold, limit);
+    let columns = if allow_grid && config.grid {
+        grid_columns(selected.len(), max_images)
+    } else {
+        1
+    };
+
+    let selected_images: Vec<DynamicImage> = images
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| selected.contains(index))
+        .map(|(_, image)| image)
+        .collect();
+
+    let outputs: Vec<RgbImage> = if columns > 1 {
+        let per_image = selected_images.len().div_ceil(max_images);
+        selected_images
+            .chunks(per_image)
+            .map(|chunk| stitch_grid(chunk, columns, max_edge))
+            .collect()
+    } else {
+        selected_images
+            .iter()
+            .map(|image| image.to_rgb8())
+            .collect()
+    };
+
+    let dir = tempfile::Builder::new()
+        .prefix("screen-analyzer-frames-")
+        .tempdir()?;
+    let mut output_paths = Vec::with_capacity(outputs.len());
+    let mut tokens_after = 0;
+    for (index, image) in outputs.iter().enumerate() {
+        let path = dir.path().join(format!("frame_{:03}.jpg", index));
+        write_jpeg(image, &path, config.jpeg_quality)?;
+        tokens_after += estimate_tokens(image.width(), image.height());
+        output_paths.push(path.to_string_lossy().to_string());
+    }
+
+    Ok(PreparedFrames {
+        _dir: dir,
+        paths: output_paths,
+        stats: PrepStats {
+            input_frames: paths.len(),
+            selected_frames: selected.len(),
+            output_images: outputs.len(),
+            grid_columns: columns,
+            tokens_before,
+            tokens_after,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_representative_and_grid() {
+        // 0..4 画面不变，第 4 帧起切换窗口，第 7 帧再次变化
+        let hashes = [0u64, 0, 1, 0, u64::MAX, u64::MAX, u64::MAX, 0xFFFF];
+        assert_eq!(select_representative(&hashes, 4, 10), vec![0, 4, 7]);
+        assert_eq!(select_representative(&hashes, 4, 2), vec![0, 4]);
+        assert_eq!(select_representative(&hashes, 0, 10), vec![0, 2, 3, 4, 7]);
+        assert!(select_representative(&[], 4, 10).is_empty());
+
+        assert_eq!(grid_columns(12, 12), 1);
+        assert_eq!(grid_columns(13, 12), 2);
+        assert_eq!(grid_columns(48, 12), 2);
+        assert_eq!(grid_columns(49, 12), 3);
+        assert_eq!(grid_columns(500, 12), MAX_GRID_COLUMNS);
+
+        assert_eq!(fit_size(2560, 1440, 1280), (1280, 720));
+        assert_eq!(fit_size(800, 600, 1280), (800, 600));
+        assert_eq!(estimate_tokens(1500, 1000), 2000);
+    }
+
+    #[test]
+    fn test_stitch_grid_keeps_long_edge() {
+        let frame = DynamicImage::ImageRgb8(RgbImage::from_pixel(1920, 1080, Rgb([200, 0, 0])));
+        let grid = stitch_grid(&vec![frame.clone(); 3], 2, 1280);
+        assert!(grid.width().max(grid.height()) <= 1280);
+        assert_eq!(grid.width(), 2 * 638 + GRID_GAP);
+        // 最后一格为空，保留背景色
+        assert_eq!(
+            *grid.get_pixel(grid.width() - 1, grid.height() - 1),
+            GRID_BACKGROUND
+        );
+        assert_eq!(*grid.get_pixel(0, 0), Rgb([200, 0, 0]));
+
+        // 网格总像素远小于原图之和
+        let before = 3 * estimate_tokens(1920, 1080);
+        assert!(estimate_tokens(grid.width(), grid.height()) * 4 < before);
+    }
+}
@@ -0,0 +1,318 @@
+// 截图存储后端 - 截图可转存到 NAS 共享目录或 S3 兼容对象存储，释放系统盘空间
+//
+// 截图始终先写入本机 frames 目录（视频生成、OCR 与转码都依赖本机文件），会话结束超过
+// 设定时长后由存储清理器转存到配置的后端，并更新数据库中的帧路径。导出器与会话详情页
+// 通过 FrameStore 解析帧路径：对象存储中的截图按需下载到本机缓存目录
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs;
+use tracing::warn;
+
+use crate::models::{FrameStoreBackend, FrameStoreConfig, S3StorageConfig};
+use crate::notion::client::image_content_type;
+use crate::notion::s3::S3Uploader;
+
+/// 对象存储中截图的帧路径前缀（`s3://<bucket>/<key>`）
+const S3_SCHEME: &str = "s3://";
+
+/// 截图存储后端
+#[async_trait]
+pub trait FrameStore: Send + Sync {
+    /// 后端标识（local / nas / s3）
+    fn kind(&self) -> &'static str;
+
+    /// 将本机截图复制到存储后端，返回写入数据库的帧路径（不删除本机文件）
+    async fn store(&self, local: &Path, date: &str) -> Result<String>;
+
+    /// 帧路径对应的本机可读文件（对象存储中的截图下载到缓存目录）
+    async fn local_path(&self, path: &str) -> Result<PathBuf>;
+
+    /// 截图的外链地址（仅对象存储中的截图有，需 Bucket 可公开读取或配置了公开访问域名）
+    fn public_url(&self, path: &str) -> Option<String>;
+
+    /// 删除截图（文件不存在时忽略）
+    async fn delete(&self, path: &str) -> Result<()>;
+}
+
+/// 本机存储：截图保留在 frames 目录
+pub struct LocalFrameStore;
+
+#[async_trait]
+impl FrameStore for LocalFrameStore {
+    fn kind(&self) -> &'static str {
+        "local"
+    }
+
+    async fn store(&self, local: &Path, _date: &str) -> Result<String> {
+        Ok(local.to_string_lossy().replace('\\', "/"))
+    }
+
+    async fn local_path(&self, path: &str) -> Result<PathBuf> {
+        filesystem_path(path)
+    }
+
+    fn public_url(&self, _path: &str) -> Option<String> {
+        None
+    }
+
+    async fn delete(&self, path: &str) -> Result<()> {
+        delete_file(path).await
+    }
+}
+
+/// NAS 存储：截图按日期转存到已挂载的共享目录（SMB 挂载点或 UNC 路径）
+pub struct NasFrameStore {
+    root: PathBuf,
+}
+
+impl NasFrameStore {
+    pub fn new(root: &str) -> Result<Self> {
+        let root = root.trim();
+        if root.is_empty() {
+            bail!("NAS 截图目录不能为空");
+        }
+        Ok(Self {
+            root: PathBuf::from(root),
+        })
+    }
+}
+
+#[async_trait]
+impl FrameStore for NasFrameStore {
+    fn kind(&self) -> &'static str {
+        "nas"
+    }
+
+    async fn store(&self, local: &Path, date: &str) -> Result<String> {
+        // 共享目录未挂载时不自动创建，避免截图被写回系统盘上的同名空目录
+        if !fs::metadata(&self.root).await.is_ok_and(|m| m.is_dir()) {
+            bail!("NAS 目录不可访问: {}", self.root.display());
+        }
+        let dir = self.root.join(date);
+        fs::create_dir_all(&dir).await?;
+        let target = dir.join(file_name(local)?);
+        fs::copy(local, &target).await?;
+        Ok(target.to_string_lossy().replace('\\', "/"))
+    }
+
+    async fn local_path(&self, path: &str) -> Result<PathBuf> {
+        filesystem_path(path)
+    }
+
+    fn public_url(&self, _path: &str) -> Option<String> {
+        None
+    }
+
+    async fn delete(&self, path: &str) -> Result<()> {
+        delete_file(path).await
+    }
+}
+
+/// S3 兼容对象存储：对象键为 `<前缀>/frames/<日期>/<文件名>`
+pub struct S3FrameStore {
+    uploader: S3Uploader,
+    bucket: String,
+    /// 下载的截图缓存目录
+    cache_dir: PathBuf,
+}
+
+impl S3FrameStore {
+    pub fn new(config: S3StorageConfig) -> Result<Self> {
+        let bucket = config.bucket.trim().to_string();
+        Ok(Self {
+            uploader: S3Uploader::new(config)?,
+            bucket,
+            cache_dir: std::env::temp_dir()
+                .join("screen-analyzer")
+                .join("frame-cache"),
+        })
+    }
+
+    /// 本存储中对象的键（其他 Bucket 的帧路径视为错误）
+    fn object_key<'a>(&self, path: &'a str) -> Result<Option<&'a str>> {
+        match parse_s3_path(path) {
+            Some((bucket, key)) if bucket == self.bucket => Ok(Some(key)),
+            Some((bucket, _)) => Err(anyhow!(
+                "截图位于 Bucket {}，与当前配置的 {} 不一致",
+                bucket,
+                self.bucket
+            )),
+            None => Ok(None),
+        }
+    }
+}
+
+#[async_trait]
+impl FrameStore for S3FrameStore {
+    fn kind(&self) -> &'static str {
+        "s3"
+    }
+
+    async fn store(&self, local: &Path, date: &str) -> Result<String> {
+        let name = file_name(local)?;
+        let extension = local
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("jpg")
+            .to_lowercase();
+        let key = self
+            .uploader
+            .object_key(&format!("frames/{}/{}", date, name.to_string_lossy()));
+        let bytes = fs::read(local).await?;
+        self.uploader
+            .upload(&key, bytes, image_content_type(&extension))
+            .await?;
+        Ok(format!("{}{}/{}", S3_SCHEME, self.bucket, key))
+    }
+
+    async fn local_path(&self, path: &str) -> Result<PathBuf> {
+        let Some(key) = self.object_key(path)? else {
+            return filesystem_path(path);
+        };
+        let cached = self.cache_dir.join(&self.bucket).join(key);
+        if fs::metadata(&cached).await.is_ok() {
+            return Ok(cached);
+        }
+        let bytes = self
+            .uploader
+            .download(key)
+            .await?
+            .ok_or_else(|| anyhow!("S3 存储中不存在截图: {}", key))?;
+        if let Some(parent) = cached.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::write(&cached, bytes).await?;
+        Ok(cached)
+    }
+
+    fn public_url(&self, path: &str) -> Option<String> {
+        let key = self.object_key(path).ok()??;
+        Some(self.uploader.public_url(key))
+    }
+
+    async fn delete(&self, path: &str) -> Result<()> {
+        let Some(key) = self.object_key(path)? else {
+            return delete_file(path).await;
+        };
+        self.uploader.delete(key).await?;
+        let _ = fs::remove_file(self.cache_dir.join(&self.bucket).join(key)).await;
+        Ok(())
+    }
+}
+
+/// 按配置创建存储后端
+pub fn build(config: &FrameStoreConfig) -> Result<Arc<dyn FrameStore>> {
+    Ok(match config.backend {
+        FrameStoreBackend::Local => Arc::new(LocalFrameStore),
+        FrameStoreBackend::Nas => Arc::new(NasFrameStore::new(&config.nas_path)?),
+        FrameStoreBackend::S3 => Arc::new(S3FrameStore::new(config.s3.clone())?),
+    })
+}
+
+/// 按配置创建存储后端，配置不完整时退回本机存储
+pub fn from_config(config: &FrameStoreConfig) -> Arc<dyn FrameStore> {
+    build(config).unwrap_or_else(|e| {
+        warn!("截图存储配置无效，使用本机存储: {}", e);
+        Arc::new(LocalFrameStore)
+    })
+}
+
+/// 拆分 `s3://<bucket>/<key>` 形式的帧路径
+pub fn parse_s3_path(path: &str) -> Option<(&str, &str)> {
+    path.strip_prefix(S3_SCHEME)?
+        .split_once('/')
+        .filter(|(bucket, key)| !bucket.is_empty() && !key.is_empty())
+}
+
+/// 文件系统中的帧路径（对象存储中的截图需要在设置中选择对应的 S3 存储）
+fn filesystem_path(path: &str) -> Result<PathBuf> {
+    if path.starts_with(S3_SCHEME) {
+        bail!(
+            "截图保存在 S3 存储中，请在设置中配置对应的截图存储: {}",
+            path
+        );
+    }
+    Ok(PathBuf::from(path))
+}
+
+async fn delete_file(path: &str) -> Result<()> {
+    match fs::remove_file(filesystem_path(path)?).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+fn file_name(path: &Path) -> Result<&std::ffi::OsStr> {
+    path.file_name()
+        .ok_or_else(|| anyhow!("截图路径缺少文件名: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_nas_store_round_trip() {
+        let local_dir = tempfile::tempdir().unwrap();
+        let nas_dir = tempfile::tempdir().unwrap();
+        let local = local_dir.path().join("frame_1.jpg");
+        std::fs::write(&local, b"jpeg").unwrap();
+
+        let store = NasFrameStore::new(nas_dir.path().to_str().unwrap()).unwrap();
+        let stored = store.store(&local, "2024-05-01").await.unwrap();
+        assert!(stored.ends_with("2024-05-01/frame_1.jpg"));
+        let resolved = store.local_path(&stored).await.unwrap();
+        assert_eq!(std::fs::read(&resolved).unwrap(), b"jpeg");
+        assert!(store.public_url(&stored).is_none());
+
+        store.delete(&stored).await.unwrap();
+        assert!(!resolved.exists());
+        // 重复删除不报错
+        store.delete(&stored).await.unwrap();
+
+        // 共享目录未挂载时拒绝转存
+        let offline = NasFrameStore::new(nas_dir.path().join("missing").to_str().unwrap()).unwrap();
+        assert!(offline.store(&local, "2024-05-01").await.is_err());
+        assert!(NasFrameStore::new("  ").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_s3_frame_paths() {
+        assert_eq!(
+            parse_s3_path("s3://shots/screen/frames/2024-05-01/a.jpg"),
+            Some(("shots", "screen/frames/2024-05-01/a.jpg"))
+        );
+        assert_eq!(parse_s3_path("s3://shots"), None);
+        assert_eq!(parse_s3_path("/data/frames/a.jpg"), None);
+
+        // 本机存储无法读取对象存储中的截图
+        assert!(LocalFrameStore
+            .local_path("s3://shots/frames/a.jpg")
+            .await
+            .is_err());
+
+        let store = S3FrameStore::new(S3StorageConfig {
+            endpoint: "https://s3.example.com".to_string(),
+            bucket: "shots".to_string(),
+            access_key_id: "key".to_string(),
+            secret_access_key: "secret".to_string(),
+            public_base_url: "https://cdn.example.com".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            store.public_url("s3://shots/frames/2024-05-01/a.jpg"),
+            Some("https://cdn.example.com/frames/2024-05-01/a.jpg".to_string())
+        );
+        assert!(store.public_url("s3://other/frames/a.jpg").is_none());
+        // 转存前的本机截图仍按文件路径读取
+        assert_eq!(
+            store.local_path("/data/frames/a.jpg").await.unwrap(),
+            PathBuf::from("/data/frames/a.jpg")
+        );
+        assert!(store.local_path("s3://other/frames/a.jpg").await.is_err());
+    }
+}
@@ -0,0 +1,642 @@
+// 目标追踪 - 用户定义的每日目标（如「工作日专注工作 ≥ 3 小时」「社交网站 ≤ 1 小时」），
+// 按当天时间线卡片与网站访问时长评估是否达成，并统计连续达成天数；
+// 已结束日期的评估结果保存到数据库，时间线卡片变化时随专注度指标一起失效
+
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::domains::summary::{category_key, normalize_timeline_category, parse_card_minutes};
+use crate::llm::plugin::ActivityCategory;
+use crate::storage::{
+    Database, DomainUsageSummary, GoalRecord, GoalResultRecord, TimelineCardRecord,
+};
+
+/// 支持的指标：专注（工作 + 学习）、活跃（空闲除外）、类别、应用、网站域名
+pub const GOAL_METRICS: [&str; 5] = ["focus", "active", "category", "app", "domain"];
+
+/// 支持的比较方式
+pub const GOAL_COMPARISONS: [&str; 2] = ["at_least", "at_most"];
+
+/// 支持的生效日
+pub const GOAL_SCHEDULES: [&str; 3] = ["daily", "weekdays", "weekends"];
+
+/// 连续达成天数最多向前回溯的天数
+const MAX_STREAK_DAYS: i64 = 366;
+
+/// 目标在某天的达成情况
+#[derive(Debug, Clone, Serialize)]
+pub struct GoalStatus {
+    pub goal: GoalRecord,
+    pub date: String,
+    /// 当天是否为目标的生效日
+    pub scheduled: bool,
+    pub actual_minutes: i64,
+    pub passed: bool,
+    /// 当天尚未结束（未达成的「至少」目标仍可能达成）
+    pub in_progress: bool,
+    /// 截至当天的连续达成天数（只计生效日；进行中且未达成时从前一个生效日起算）
+    pub streak: u32,
+}
+
+/// 目标在一周内的达成情况
+#[derive(Debug, Clone, Serialize)]
+pub struct GoalWeekStatus {
+    pub goal: GoalRecord,
+    /// 已评估的生效日天数（不含尚未结束且未达成的今天）
+    pub scheduled_days: u32,
+    pub passed_days: u32,
+    /// 截至本周最后一个已评估日的连续达成天数
+    pub streak: u32,
+}
+
+/// 前端提交的目标（id 为空时新增）
+#[derive(Debug, Clone, Deserialize)]
+pub struct GoalInput {
+    pub id: Option<i64>,
+    pub name: String,
+    pub metric: String,
+    #[serde(default)]
+    pub target: String,
+    pub comparison: String,
+    pub target_minutes: i64,
+    #[serde(default = "default_schedule")]
+    pub schedule: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_schedule() -> String {
+    "daily".to_string()
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl GoalInput {
+    /// 转换为目标记录（创建时间只在新增时写入）
+    pub fn into_record(self) -> GoalRecord {
+        GoalRecord {
+            id: self.id,
+            name: self.name,
+            metric: self.metric,
+            target: self.target,
+            comparison: self.comparison,
+            target_minutes: self.target_minutes,
+            schedule: self.schedule,
+            enabled: self.enabled,
+            created_at: crate::storage::local_now(),
+        }
+    }
+}
+
+/// 校验并规范化目标：类别目标统一为内置类别键或自定义类别名称，关键词去除首尾空白
+pub fn normalize_goal(mut goal: GoalRecord) -> Result<GoalRecord> {
+    goal.name = goal.name.trim().to_string();
+    goal.target = goal.target.trim().to_string();
+    if goal.name.is_empty() {
+        return Err(anyhow!("目标名称不能为空"));
+    }
+    if !GOAL_METRICS.contains(&goal.metric.as_str()) {
+        return Err(anyhow!("不支持的目标指标: {}", goal.metric));
+    }
+    if !GOAL_COMPARISONS.contains(&goal.comparison.as_str()) {
+        return Err(anyhow!("不支持的比较方式: {}", goal.comparison));
+    }
+    if !GOAL_SCHEDULES.contains(&goal.schedule.as_str()) {
+        return Err(anyhow!("不支持的生效日: {}", goal.schedule));
+    }
+    if !(1..=24 * 60).contains(&goal.target_minutes) {
+        return Err(anyhow!("目标时长需在 1-1440 分钟之间"));
+    }
+
+    match goal.metric.as_str() {
+        "category" => {
+            goal.target = crate::llm::plugin::normalize_card_category(
+                &goal.target,
+                &crate::taxonomy::custom_categories(),
+            )
+            .ok_or_else(|| anyhow!("未知类别: {}", goal.target))?;
+        }
+        "app" | "domain" if keywords(&goal.target).is_empty() => {
+            return Err(anyhow!("请填写应用或域名关键词"));
+        }
+        "focus" | "active" => goal.target.clear(),
+        _ => {}
+    }
+    Ok(goal)
+}
+
+/// 目标在指定日期是否生效
+pub fn applies_on(goal: &GoalRecord, date: NaiveDate) -> bool {
+    let weekend = matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+    match goal.schedule.as_str() {
+        "weekdays" => !weekend,
+        "weekends" => weekend,
+        _ => true,
+    }
+}
+
+/// 实际时长是否满足目标
+pub fn meets(goal: &GoalRecord, actual_minutes: i64) -> bool {
+    match goal.comparison.as_str() {
+        "at_most" => actual_minutes <= goal.target_minutes,
+        _ => actual_minutes >= goal.target_minutes,
+    }
+}
+
+/// 比较方式的符号（≥ / ≤）
+pub fn comparison_symbol(goal: &GoalRecord) -> &'static str {
+    if goal.comparison == "at_most" {
+        "≤"
+    } else {
+        "≥"
+    }
+}
+
+/// 计算目标指标当天的实际时长（分钟）
+pub fn measure_minutes(
+    goal: &GoalRecord,
+    cards: &[TimelineCardRecord],
+    domains: &[DomainUsageSummary],
+) -> i64 {
+    let words = keywords(&goal.target);
+    if goal.metric == "domain" {
+        let seconds: i64 = domains
+            .iter()
+            .filter(|usage| {
+                let domain = usage.domain.to_lowercase();
+                words.iter().any(|word| domain.contains(word.as_str()))
+            })
+            .map(|usage| usage.seconds)
+            .sum();
+        return (seconds + 30) / 60;
+    }
+
+    cards
+        .iter()
+        .filter(|card| match goal.metric.as_str() {
+            "focus" => matches!(
+                normalize_timeline_category(&card.category),
+                ActivityCategory::Work | ActivityCategory::Learning
+            ),
+            "active" => !matches!(
+                normalize_timeline_category(&card.category),
+                ActivityCategory::Idle
+            ),
+            "category" => {
+                card.category.eq_ignore_ascii_case(&goal.target)
+                    || category_key(&normalize_timeline_category(&card.category)) == goal.target
+            }
+            "app" => card_apps(card).iter().any(|app| {
+                let app = app.to_lowercase();
+                words.iter().any(|word| app.contains(word.as_str()))
+            }),
+            _ => false,
+        })
+        .map(parse_card_minutes)
+        .sum()
+}
+
+/// 以逗号分隔的关键词（小写，忽略空项）
+fn keywords(target: &str) -> Vec<String> {
+    target
+        .split([',', '，'])
+        .map(|word| word.trim().to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// 卡片的主要与次要应用或网站
+fn card_apps(card: &TimelineCardRecord) -> Vec<String> {
+    let Ok(sites) = serde_json::from_str::<serde_json::Value>(&card.app_sites) else {
+        return Vec::new();
+    };
+    let mut apps: Vec<String> = sites
+        .get("primary")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .into_iter()
+        .collect();
+    match sites.get("secondary") {
+        Some(serde_json::Value::Array(items)) => {
+            apps.extend(items.iter().filter_map(|v| v.as_str()).map(str::to_string))
+        }
+        Some(serde_json::Value::String(text)) => apps.extend(
+            text.split(',')
+                .map(|app| app.trim().to_string())
+                .filter(|app| !app.is_empty()),
+        ),
+        _ => {}
+    }
+    apps
+}
+
+/// 某天用于评估的数据
+struct DayData {
+    cards: Vec<TimelineCardRecord>,
+    domains: Vec<DomainUsageSummary>,
+}
+
+/// 目标评估器：同一次评估中按日期缓存时间线与网站数据，并复用已保存的结果
+pub struct GoalTracker<'a> {
+    db: &'a Database,
+    today: NaiveDate,
+    days: HashMap<NaiveDate, DayData>,
+    results: HashMap<(i64, NaiveDate), GoalResultRecord>,
+    /// 已加载保存结果的最早日期
+    loaded_from: Option<NaiveDate>,
+}
+
+impl<'a> GoalTracker<'a> {
+    pub fn new(db: &'a Database, today: NaiveDate) -> Self {
+        Self {
+            db,
+            today,
+            days: HashMap::new(),
+            results: HashMap::new(),
+            loaded_from: None,
+        }
+    }
+
+    /// 全部启用目标在指定日期的达成情况
+    pub async fn day_status(
+        &mut self,
+        goals: &[GoalRecord],
+        date: NaiveDate,
+    ) -> Result<Vec<GoalStatus>> {
+        self.load_results(date - chrono::Duration::days(MAX_STREAK_DAYS), date)
+            .await?;
+
+        let mut statuses = Vec::new();
+        for goal in goals.iter().filter(|goal| goal.enabled) {
+            let scheduled = applies_on(goal, date);
+            let result = self.result(goal, date).await?;
+            let in_progress = date >= self.today;
+            let streak_from = if result.passed || !in_progress {
+                date
+            } else {
+                date - chrono::Duration::days(1)
+            };
+            let streak = if scheduled && !result.passed && !in_progress {
+                0
+            } else {
+                self.streak(goal, streak_from).await?
+            };
+            statuses.push(GoalStatus {
+                goal: goal.clone(),
+                date: date.format("%Y-%m-%d").to_string(),
+                scheduled,
+                actual_minutes: result.actual_minutes,
+                passed: result.passed,
+                in_progress,
+                streak,
+            });
+        }
+        Ok(statuses)
+    }
+
+    /// 全部启用目标在日期范围（含首尾）内的达成情况
+    pub async fn week_status(
+        &mut self,
+        goals: &[GoalRecord],
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<GoalWeekStatus>> {
+        let last = end.min(self.today);
+        self.load_results(
+            start.min(last) - chrono::Duration::days(MAX_STREAK_DAYS),
+            last,
+        )
+        .await?;
+
+        let mut statuses = Vec::new();
+        for goal in goals.iter().filter(|goal| goal.enabled) {
+            let first = start.max(goal.created_at.date_naive());
+            let mut status = GoalWeekStatus {
+                goal: goal.clone(),
+                scheduled_days: 0,
+                passed_days: 0,
+                streak: 0,
+            };
+            let mut day = first;
+            let mut last_evaluated = None;
+            while day <= last {
+                if applies_on(goal, day) {
+                    let result = self.result(goal, day).await?;
+                    if day < self.today || result.passed {
+                        status.scheduled_days += 1;
+                        status.passed_days += u32::from(result.passed);
+                        last_evaluated = Some(day);
+                    }
+                }
+                day += chrono::Duration::days(1);
+            }
+            if let Some(day) = last_evaluated {
+                status.streak = self.streak(goal, day).await?;
+            }
+            statuses.push(status);
+        }
+        Ok(statuses)
+    }
+
+    /// 截至指定日期（含）的连续达成天数，不早于目标创建日
+    async fn streak(&mut self, goal: &GoalRecord, from: NaiveDate) -> Result<u32> {
+        let created = goal.created_at.date_naive();
+        let mut streak = 0;
+        let mut day = from;
+        for _ in 0..MAX_STREAK_DAYS {
+            if day < created {
+                break;
+            }
+            if applies_on(goal, day) {
+                if !self.result(goal, day).await?.passed {
+                    break;
+                }
+                streak += 1;
+            }
+            day -= chrono::Duration::days(1);
+        }
+        Ok(streak)
+    }
+
+    /// 单日评估结果：已结束的日期优先复用保存的结果，重新计算后保存
+    async fn result(&mut self, goal: &GoalRecord, date: NaiveDate) -> Result<GoalResultRecord> {
+        let goal_id = goal.id.unwrap_or_default();
+        if let Some(result) = self.results.get(&(goal_id, date)) {
+            return Ok(result.clone());
+        }
+
+        let data = self.day(date).await?;
+        let actual_minutes = measure_minutes(goal, &data.cards, &data.domains);
+        let result = GoalResultRecord {
+            goal_id,
+            date: date.format("%Y-%m-%d").to_string(),
+            actual_minutes,
+            passed: meets(goal, actual_minutes),
+            evaluated_at: crate::storage::local_now(),
+        };
+        if date < self.today && goal.id.is_some() {
+            self.db.save_goal_result(&result).await?;
+            self.results.insert((goal_id, date), result.clone());
+        }
+        Ok(result)
+    }
+
+    async fn day(&mut self, date: NaiveDate) -> Result<&DayData> {
+        if !self.days.contains_key(&date) {
+            let text = date.format("%Y-%m-%d").to_string();
+            let mut cards = Vec::new();
+            for session in self.db.get_sessions_by_date(&text).await? {
+                if let Some(session_id) = session.id {
+                    cards.extend(self.db.get_timeline_cards_by_session(session_id).await?);
+                }
+            }
+            let domains = self.db.get_domain_usage_summary(&text, &text).await?;
+            self.days.insert(date, DayData { cards, domains });
+        }
+        Ok(&self.days[&date])
+    }
+
+    /// 加载日期范围内保存的结果（只保留当天结束后评估的结果）
+    async fn load_results(&mut self, start: NaiveDate, end: NaiveDate) -> Result<()> {
+        if self.loaded_from.is_some_and(|from| from <= start) {
+            return Ok(());
+        }
+        let records = self
+            .db
+            .get_goal_results(
+                &start.format("%Y-%m-%d").to_string(),
+                &end.format("%Y-%m-%d").to_string(),
+            )
+            .await?;
+        for record in records {
+            let Ok(date) = NaiveDate::parse_from_str(&record.date, "%Y-%m-%d") else {
+                continue;
+            };
+            if record.evaluated_at.date_naive() > date {
+                self.results.entry((record.goal_id, date)).or_insert(record);
+            }
+        }
+        self.loaded_from = Some(start);
+        Ok(())
+    }
+}
+
+/// 全部启用目标在指定日期的达成情况（没有目标时为空）
+pub async fn day_goals(db: &Database, date: NaiveDate) -> Result<Vec<GoalStatus>> {
+    let goals = db.get_goals().await?;
+    if goals.iter().all(|goal| !goal.enabled) {
+        return Ok(Vec::new());
+    }
+    GoalTracker::new(db, crate::storage::local_now().date_naive())
+        .day_status(&goals, date)
+        .await
+}
+
+/// 全部启用目标在一周内的达成情况（没有目标时为空）
+pub async fn week_goals(
+    db: &Database,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<GoalWeekStatus>> {
+    let goals = db.get_goals().await?;
+    if goals.iter().all(|goal| !goal.enabled) {
+        return Ok(Vec::new());
+    }
+    GoalTracker::new(db, crate::storage::local_now().date_naive())
+        .week_status(&goals, start, end)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Session;
+    use chrono::{TimeZone, Utc};
+
+    fn goal(metric: &str, target: &str, comparison: &str, minutes: i64) -> GoalRecord {
+        GoalRecord {
+            id: None,
+            name: "目标".to_string(),
+            metric: metric.to_string(),
+            target: target.to_string(),
+            comparison: comparison.to_string(),
+            target_minutes: minutes,
+            schedule: "daily".to_string(),
+            enabled: true,
+            created_at: Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap(),
+        }
+    }
+
+    fn card(
+        session_id: i64,
+        day: u32,
+        start: &str,
+        end: &str,
+        category: &str,
+        app: &str,
+    ) -> TimelineCardRecord {
+        TimelineCardRecord {
+            id: None,
+            session_id,
+            llm_call_id: None,
+            start_time: format!("2024-05-{:02}T{}:00+08:00", day, start),
+            end_time: format!("2024-05-{:02}T{}:00+08:00", day, end),
+            category: category.to_string(),
+            subcategory: String::new(),
+            title: "活动".to_string(),
+            summary: String::new(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: format!(r#"{{"primary":"{}","secondary":[]}}"#, app),
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_measure_and_validate_goals() {
+        let cards = vec![
+            card(1, 13, "09:00", "11:00", "work", "Visual Studio Code"),
+            card(1, 13, "11:00", "11:30", "learning", "docs.rs"),
+            card(1, 13, "11:30", "12:00", "personal", "weibo.com"),
+            card(1, 13, "12:00", "13:00", "idle", ""),
+        ];
+        let domains = vec![
+            DomainUsageSummary {
+                domain: "weibo.com".to_string(),
+                seconds: 1800,
+                session_count: 1,
+            },
+            DomainUsageSummary {
+                domain: "www.reddit.com".to_string(),
+                seconds: 1200,
+                session_count: 1,
+            },
+        ];
+
+        assert_eq!(
+            measure_minutes(&goal("focus", "", "at_least", 180), &cards, &domains),
+            150
+        );
+        assert_eq!(
+            measure_minutes(&goal("active", "", "at_least", 60), &cards, &domains),
+            180
+        );
+        assert_eq!(
+            measure_minutes(&goal("category", "work", "at_least", 60), &cards, &domains),
+            120
+        );
+        assert_eq!(
+            measure_minutes(&goal("app", "code", "at_least", 60), &cards, &domains),
+            120
+        );
+        let social = goal("domain", "weibo.com，reddit", "at_most", 60);
+        assert_eq!(measure_minutes(&social, &cards, &domains), 50);
+        assert!(meets(&social, 50));
+        assert!(!meets(&goal("focus", "", "at_least", 180), 150));
+
+        let mut weekdays = goal("focus", "", "at_least", 180);
+        weekdays.schedule = "weekdays".to_string();
+        assert!(applies_on(
+            &weekdays,
+            NaiveDate::from_ymd_opt(2024, 5, 13).unwrap()
+        ));
+        assert!(!applies_on(
+            &weekdays,
+            NaiveDate::from_ymd_opt(2024, 5, 12).unwrap()
+        ));
+
+        assert_eq!(
+            normalize_goal(goal("category", " Work ", "at_least", 60))
+                .unwrap()
+                .target,
+            "work"
+        );
+        assert!(normalize_goal(goal("category", "unknown", "at_least", 60)).is_err());
+        assert!(normalize_goal(goal("app", " , ", "at_least", 60)).is_err());
+        assert!(normalize_goal(goal("focus", "", "more", 60)).is_err());
+        assert!(normalize_goal(goal("focus", "", "at_least", 0)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_goal_streaks_and_week_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new_sqlite(dir.path().join("test.db").to_str().unwrap())
+            .await
+            .unwrap();
+        // 5 月 13-16 日（周一至周四）每天工作 3 小时，15 日只有 1 小时
+        for day in 13..=16 {
+            let session_id = db
+                .insert_session(&Session {
+                    id: None,
+                    start_time: Utc.with_ymd_and_hms(2024, 5, day, 1, 0, 0).unwrap(),
+                    end_time: Utc.with_ymd_and_hms(2024, 5, day, 4, 0, 0).unwrap(),
+                    title: "编码".to_string(),
+                    summary: String::new(),
+                    video_path: None,
+                    tags: "[]".to_string(),
+                    created_at: None,
+                    device_name: None,
+                    device_type: None,
+                })
+                .await
+                .unwrap();
+            let end = if day == 15 { "10:00" } else { "12:00" };
+            db.insert_timeline_card(&card(session_id, day, "09:00", end, "work", "Code"))
+                .await
+                .unwrap();
+        }
+
+        let mut record = goal("focus", "", "at_least", 180);
+        record.schedule = "weekdays".to_string();
+        record.id = Some(db.insert_goal(&record).await.unwrap());
+        let goals = vec![record];
+        let date = |day| NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+
+        // 17 日（进行中）未达成时连续天数从前一天起算
+        let mut tracker = GoalTracker::new(&db, date(17));
+        let status = tracker.day_status(&goals, date(17)).await.unwrap();
+        assert!(status[0].in_progress && !status[0].passed);
+        assert_eq!(status[0].streak, 1);
+        let status = tracker.day_status(&goals, date(15)).await.unwrap();
+        assert_eq!((status[0].actual_minutes, status[0].streak), (60, 0));
+
+        // 已评估的已结束日期（连续天数回溯到 15 日为止）保存结果，周末不计入
+        let saved = db
+            .get_goal_results("2024-05-13", "2024-05-16")
+            .await
+            .unwrap();
+        assert_eq!(saved.len(), 2);
+        let week = GoalTracker::new(&db, date(20))
+            .week_status(&goals, date(13), date(19))
+            .await
+            .unwrap();
+        assert_eq!((week[0].scheduled_days, week[0].passed_days), (5, 3));
+        assert_eq!(week[0].streak, 0);
+
+        // 卡片类别变化后当天的结果失效，重新评估
+        let sessions = db.get_sessions_by_date("2024-05-16").await.unwrap();
+        let mut cards = db
+            .get_timeline_cards_by_session(sessions[0].id.unwrap())
+            .await
+            .unwrap();
+        cards[0].category = "personal".to_string();
+        db.update_timeline_card_categories(&cards).await.unwrap();
+        assert!(db
+            .get_goal_results("2024-05-16", "2024-05-16")
+            .await
+            .unwrap()
+            .is_empty());
+        let status = GoalTracker::new(&db, date(17))
+            .day_status(&goals, date(17))
+            .await
+            .unwrap();
+        assert_eq!(status[0].streak, 0);
+    }
+}
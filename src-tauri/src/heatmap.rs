@@ -0,0 +1,226 @@
+// 专注热力图 - 按「星期 × 小时」汇总日期范围内时间线卡片的专注（工作 + 学习）时长，
+// 卡片跨整点时按实际时长拆分到各小时（按卡片记录的本地时间），用于查看一周中深度工作集中的时段
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+use serde::Serialize;
+
+use crate::domains::summary::normalize_timeline_category;
+use crate::llm::plugin::ActivityCategory;
+use crate::storage::{Database, TimelineCardRecord};
+
+/// 单次查询的最大天数
+pub const MAX_HEATMAP_DAYS: i64 = 366;
+
+/// 热力图中的一格
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct HeatmapCell {
+    /// 星期（0 = 周一）
+    pub weekday: usize,
+    pub hour: usize,
+    pub minutes: i64,
+}
+
+/// 专注热力图
+#[derive(Debug, Clone, Serialize)]
+pub struct FocusHeatmap {
+    pub start_date: String,
+    pub end_date: String,
+    /// 7 × 24 矩阵，行为星期（周一起），列为小时，值为专注分钟数
+    pub minutes: [[i64; 24]; 7],
+    pub total_minutes: i64,
+    /// 专注时长最多的一格（没有专注记录时为空）
+    pub peak: Option<HeatmapCell>,
+}
+
+impl FocusHeatmap {
+    /// 单格最大分钟数
+    pub fn max_minutes(&self) -> i64 {
+        self.peak.map_or(0, |cell| cell.minutes)
+    }
+}
+
+/// 按秒累计，最后换算为分钟，避免短卡片拆分后被截断
+struct HeatmapBuilder {
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    seconds: [[i64; 24]; 7],
+}
+
+impl HeatmapBuilder {
+    fn new(start: NaiveDate, end: NaiveDate) -> Self {
+        Self {
+            start: start.and_hms_opt(0, 0, 0).unwrap(),
+            end: (end + Duration::days(1)).and_hms_opt(0, 0, 0).unwrap(),
+            seconds: [[0; 24]; 7],
+        }
+    }
+
+    /// 累计一张卡片（非专注类别或时间无法解析时忽略，超出日期范围的部分截掉）
+    fn add_card(&mut self, card: &TimelineCardRecord) {
+        if !matches!(
+            normalize_timeline_category(&card.category),
+            ActivityCategory::Work | ActivityCategory::Learning
+        ) {
+            return;
+        }
+        let (Ok(start), Ok(end)) = (
+            DateTime::parse_from_rfc3339(&card.start_time),
+            DateTime::parse_from_rfc3339(&card.end_time),
+        ) else {
+            return;
+        };
+
+        let mut cursor = start.naive_local().max(self.start);
+        let end = end.naive_local().min(self.end);
+        while cursor < end {
+            let hour_start = cursor.date().and_hms_opt(cursor.hour(), 0, 0).unwrap();
+            let slot_end = (hour_start + Duration::hours(1)).min(end);
+            let weekday = cursor.weekday().num_days_from_monday() as usize;
+            self.seconds[weekday][cursor.hour() as usize] += (slot_end - cursor).num_seconds();
+            cursor = slot_end;
+        }
+    }
+
+    fn build(self) -> FocusHeatmap {
+        let minutes = self.seconds.map(|row| row.map(|seconds| seconds / 60));
+        let mut peak: Option<HeatmapCell> = None;
+        for (weekday, row) in minutes.iter().enumerate() {
+            for (hour, value) in row.iter().enumerate() {
+                if *value > 0 && peak.is_none_or(|cell| *value > cell.minutes) {
+                    peak = Some(HeatmapCell {
+                        weekday,
+                        hour,
+                        minutes: *value,
+                    });
+                }
+            }
+        }
+        FocusHeatmap {
+            start_date: self.start.format("%Y-%m-%d").to_string(),
+            end_date: (self.end - Duration::days(1))
+                .format("%Y-%m-%d")
+                .to_string(),
+            total_minutes: self.seconds.iter().flatten().sum::<i64>() / 60,
+            minutes,
+            peak,
+        }
+    }
+}
+
+/// 按卡片计算热力图（只统计 start..=end 范围内的时长）
+pub fn heatmap_from_cards(
+    cards: &[TimelineCardRecord],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> FocusHeatmap {
+    let mut builder = HeatmapBuilder::new(start, end);
+    for card in cards {
+        builder.add_card(card);
+    }
+    builder.build()
+}
+
+/// 日期范围内（含首尾）的专注热力图
+pub async fn focus_heatmap(
+    db: &Database,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<FocusHeatmap> {
+    if end < start {
+        return Err(anyhow!("结束日期早于开始日期"));
+    }
+    if (end - start).num_days() >= MAX_HEATMAP_DAYS {
+        return Err(anyhow!("热力图查询最多 {} 天", MAX_HEATMAP_DAYS));
+    }
+
+    // 前一天开始的会话可能跨过零点，一并读取后按时间截掉范围外的部分
+    let mut cards = Vec::new();
+    for day in (start - Duration::days(1))
+        .iter_days()
+        .take_while(|day| *day <= end)
+    {
+        let sessions = db
+            .get_sessions_by_date(&day.format("%Y-%m-%d").to_string())
+            .await?;
+        for session_id in sessions.iter().filter_map(|session| session.id) {
+            cards.extend(db.get_timeline_cards_by_session(session_id).await?);
+        }
+    }
+    Ok(heatmap_from_cards(&cards, start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(category: &str, start: &str, end: &str) -> TimelineCardRecord {
+        TimelineCardRecord {
+            id: None,
+            session_id: 1,
+            llm_call_id: None,
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            category: category.to_string(),
+            subcategory: String::new(),
+            title: String::new(),
+            summary: String::new(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: "[]".to_string(),
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+            created_at: crate::storage::local_now(),
+        }
+    }
+
+    #[test]
+    fn test_cards_split_across_hours_and_days() {
+        // 2024-05-06 为周一
+        let cards = vec![
+            card(
+                "work",
+                "2024-05-06T09:30:00+08:00",
+                "2024-05-06T11:15:00+08:00",
+            ),
+            card(
+                "learning",
+                "2024-05-07T10:00:00+08:00",
+                "2024-05-07T10:40:00+08:00",
+            ),
+            // 非专注类别不计入
+            card(
+                "personal",
+                "2024-05-06T12:00:00+08:00",
+                "2024-05-06T13:00:00+08:00",
+            ),
+            // 跨零点：只计范围内的周日部分
+            card(
+                "work",
+                "2024-05-12T23:30:00+08:00",
+                "2024-05-13T00:30:00+08:00",
+            ),
+        ];
+        let start = NaiveDate::from_ymd_opt(2024, 5, 6).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 5, 12).unwrap();
+        let heatmap = heatmap_from_cards(&cards, start, end);
+
+        assert_eq!(heatmap.minutes[0][9], 30);
+        assert_eq!(heatmap.minutes[0][10], 60);
+        assert_eq!(heatmap.minutes[0][11], 15);
+        assert_eq!(heatmap.minutes[0][12], 0);
+        assert_eq!(heatmap.minutes[1][10], 40);
+        assert_eq!(heatmap.minutes[6][23], 30);
+        assert_eq!(heatmap.total_minutes, 175);
+        assert_eq!(
+            heatmap.peak,
+            Some(HeatmapCell {
+                weekday: 0,
+                hour: 10,
+                minutes: 60
+            })
+        );
+        assert_eq!(heatmap.end_date, "2024-05-12");
+    }
+}
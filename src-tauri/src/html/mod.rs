@@ -0,0 +1,558 @@
+// HTML 报告导出模块 - 生成自包含的每日/每周静态报告（内联样式、base64 缩略图、可交互时间线）
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{Datelike, Duration, NaiveDate};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs;
+
+use crate::actors::LLMHandle;
+use crate::exporter::{
+    card_clock, day_summary, load_day_sessions, session_category, session_minutes, ExportReport,
+    Exporter, NotePreview, SessionExportData,
+};
+use crate::frame_store::{FrameStore, LocalFrameStore};
+use crate::models::HtmlExportConfig;
+use crate::obsidian::pick_screenshots;
+use crate::storage::{Database, Session};
+
+/// 缩略图最大尺寸（保持比例缩放）
+const THUMBNAIL_WIDTH: u32 = 320;
+const THUMBNAIL_HEIGHT: u32 = 200;
+const THUMBNAIL_QUALITY: u8 = 70;
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+
+/// 报告共用样式（内联到每个页面，不依赖外部资源）
+const REPORT_STYLE: &str = r#"
+body { margin: 0; padding: 24px; font-family: -apple-system, "Segoe UI", "PingFang SC", "Microsoft YaHei", sans-serif; background: #f5f6f8; color: #303133; }
+main { max-width: 960px; margin: 0 auto; }
+h1 { margin: 0 0 4px; font-size: 24px; }
+h2 { margin: 28px 0 12px; font-size: 18px; }
+.meta { color: #909399; margin: 0; }
+.summary { background: #fff; border-radius: 8px; padding: 12px 16px; white-space: pre-wrap; line-height: 1.6; }
+.timeline { position: relative; height: 28px; background: #e4e7ed; border-radius: 4px; overflow: hidden; }
+.timeline a { position: absolute; top: 0; bottom: 0; min-width: 2px; opacity: 0.85; }
+.timeline a:hover, .timeline a.active { opacity: 1; outline: 2px solid #303133; }
+.axis { display: flex; justify-content: space-between; color: #909399; font-size: 12px; margin-top: 4px; }
+.week-row { display: grid; grid-template-columns: 120px 1fr 80px; gap: 12px; align-items: center; margin-bottom: 8px; }
+.week-row .total { text-align: right; color: #606266; }
+.session { background: #fff; border-radius: 8px; padding: 12px 16px; margin-bottom: 12px; border-left: 4px solid #c0c4cc; }
+.session.active { box-shadow: 0 0 0 2px #409eff; }
+.session h3 { margin: 0; font-size: 16px; cursor: pointer; }
+.session.collapsed .details { display: none; }
+.session .time { color: #909399; font-weight: normal; margin-right: 8px; }
+.session .tag { font-size: 12px; font-weight: normal; color: #fff; border-radius: 4px; padding: 1px 6px; margin-left: 8px; }
+.session p { white-space: pre-wrap; line-height: 1.6; }
+.cards { padding-left: 20px; color: #606266; }
+.cards .heuristic { color: #909399; font-size: 12px; }
+.thumbs img { max-width: 320px; border-radius: 4px; margin: 4px 8px 0 0; }
+.cat-work { background: #409eff; } .cat-border-work { border-left-color: #409eff; }
+.cat-communication { background: #67c23a; } .cat-border-communication { border-left-color: #67c23a; }
+.cat-learning { background: #e6a23c; } .cat-border-learning { border-left-color: #e6a23c; }
+.cat-personal { background: #f56c6c; } .cat-border-personal { border-left-color: #f56c6c; }
+.cat-idle, .cat-other { background: #909399; }
+"#;
+
+/// 时间线交互：点击会话标题折叠详情，悬停/点击时间段高亮对应会话
+const REPORT_SCRIPT: &str = r#"
+document.querySelectorAll('.session h3').forEach(function (title) {
+  title.addEventListener('click', function () { title.parentElement.classList.toggle('collapsed'); });
+});
+document.querySelectorAll('.timeline a').forEach(function (segment) {
+  var id = segment.getAttribute('href').split('#')[1];
+  segment.addEventListener('mouseenter', function () { toggle(id, true); });
+  segment.addEventListener('mouseleave', function () { toggle(id, false); });
+});
+function toggle(id, active) {
+  var session = document.getElementById(id);
+  if (session) { session.classList.toggle('active', active); }
+}
+function highlight() {
+  document.querySelectorAll('.session.active').forEach(function (el) { el.classList.remove('active'); });
+  if (location.hash) { toggle(location.hash.slice(1), true); }
+}
+window.addEventListener('hashchange', highlight);
+highlight();
+"#;
+
+/// HTML 报告导出器
+pub struct HtmlExporter {
+    config: HtmlExportConfig,
+    /// 截图存储后端（生成缩略图前解析已转存截图的本机路径）
+    frame_store: Arc<dyn FrameStore>,
+}
+
+/// 每日报告中的单个会话
+struct SessionSection {
+    data: SessionExportData,
+    /// 缩略图 data URI
+    thumbnails: Vec<String>,
+}
+
+impl HtmlExporter {
+    /// 创建新的导出器
+    pub fn new(config: HtmlExportConfig) -> Self {
+        Self {
+            config,
+            frame_store: Arc::new(LocalFrameStore),
+        }
+    }
+
+    /// 指定截图存储后端（默认读取本机文件）
+    pub fn with_frame_store(mut self, frame_store: Arc<dyn FrameStore>) -> Self {
+        self.frame_store = frame_store;
+        self
+    }
+
+    /// 解析报告输出目录
+    pub fn resolve_output_dir(&self) -> Result<PathBuf> {
+        let output_dir = self.config.output_dir.trim();
+        if output_dir.is_empty() {
+            return Err(anyhow!("请先配置 HTML 报告输出目录"));
+        }
+        Ok(PathBuf::from(output_dir))
+    }
+
+    /// 每日报告路径
+    pub fn daily_report_path(&self, output_dir: &Path, date: &str) -> PathBuf {
+        output_dir.join("daily").join(format!("{}.html", date))
+    }
+
+    /// 每周报告路径（ISO 周，如 2024-W19）
+    pub fn weekly_report_path(&self, output_dir: &Path, week_label: &str) -> PathBuf {
+        output_dir
+            .join("weekly")
+            .join(format!("{}.html", week_label))
+    }
+
+    /// 会话首尾截图的缩略图，失败时记录警告
+    async fn session_thumbnails(
+        &self,
+        db: &Database,
+        session_id: i64,
+        warnings: &mut Vec<String>,
+    ) -> Vec<String> {
+        let frames = db
+            .get_frames_by_session(session_id)
+            .await
+            .unwrap_or_default();
+        let mut thumbnails = Vec::new();
+        for frame in pick_screenshots(&frames) {
+            let result = match self.frame_store.local_path(&frame.file_path).await {
+                Ok(source) => thumbnail_data_uri(&source).await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(uri) => thumbnails.push(uri),
+                Err(e) => warnings.push(format!("会话 {} 缩略图生成失败: {}", session_id, e)),
+            }
+        }
+        thumbnails
+    }
+
+    /// 生成当天报告及（可选）所在周的周报，返回 [(路径, 内容)] 与导出结果
+    async fn build_reports(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        date: &str,
+        force_refresh: bool,
+    ) -> Result<(Vec<(PathBuf, String)>, ExportReport)> {
+        let output_dir = self.resolve_output_dir()?;
+        let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| anyhow!("日期格式错误: {}", date))?;
+        let summary = day_summary(db.clone(), llm_handle, date, force_refresh).await?;
+
+        let mut report = ExportReport {
+            target: self.target_name().to_string(),
+            exported_dates: vec![date.to_string()],
+            ..ExportReport::default()
+        };
+        let mut sections = Vec::new();
+        for data in load_day_sessions(&db, date).await? {
+            let thumbnails = match (self.config.include_thumbnails, data.session.id) {
+                (true, Some(session_id)) => {
+                    self.session_thumbnails(&db, session_id, &mut report.warnings)
+                        .await
+                }
+                _ => Vec::new(),
+            };
+            sections.push(SessionSection { data, thumbnails });
+        }
+        report.session_count = sections.len();
+
+        let mut reports = vec![(
+            self.daily_report_path(&output_dir, date),
+            render_daily_report(day, &summary.summary_text, &sections),
+        )];
+
+        if self.config.generate_weekly {
+            let week_start = day - Duration::days(day.weekday().num_days_from_monday() as i64);
+            let mut days = Vec::new();
+            for offset in 0..7 {
+                let current = week_start + Duration::days(offset);
+                let sessions = db
+                    .get_sessions_by_date(&current.format("%Y-%m-%d").to_string())
+                    .await?;
+                days.push((current, sessions));
+            }
+            let week_label = day.format("%G-W%V").to_string();
+            reports.push((
+                self.weekly_report_path(&output_dir, &week_label),
+                render_weekly_report(&week_label, &days),
+            ));
+        }
+
+        Ok((reports, report))
+    }
+}
+
+#[async_trait]
+impl Exporter for HtmlExporter {
+    fn target_name(&self) -> &'static str {
+        "HTML"
+    }
+
+    async fn export_day(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        date: &str,
+        force_refresh: bool,
+    ) -> Result<ExportReport> {
+        let (reports, mut report) = self
+            .build_reports(db, llm_handle, date, force_refresh)
+            .await?;
+        for (index, (path, content)) in reports.into_iter().enumerate() {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).await?;
+            }
+            fs::write(&path, content).await?;
+            if index == 0 {
+                report.note_paths.push(path.clone());
+            }
+            report.written_paths.push(path);
+        }
+        Ok(report)
+    }
+
+    async fn preview_day(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        date: &str,
+    ) -> Result<Vec<NotePreview>> {
+        let (reports, _) = self.build_reports(db, llm_handle, date, false).await?;
+        let mut previews = Vec::new();
+        for (path, content) in reports {
+            let would_overwrite = match fs::read_to_string(&path).await {
+                Ok(existing) => existing != content,
+                Err(_) => false,
+            };
+            previews.push(NotePreview {
+                path,
+                content,
+                would_overwrite,
+            });
+        }
+        Ok(previews)
+    }
+}
+
+/// 生成截图缩略图并编码为 JPEG data URI（在阻塞线程中执行）
+async fn thumbnail_data_uri(source: &Path) -> Result<String> {
+    let source = source.to_path_buf();
+    tokio::task::spawn_blocking(move || -> Result<String> {
+        let thumbnail = crate::frame_crypto::open_image(&source)?
+            .thumbnail(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT)
+            .to_rgb8();
+        let mut bytes = Vec::new();
+        image::DynamicImage::ImageRgb8(thumbnail).write_to(
+            &mut std::io::Cursor::new(&mut bytes),
+            image::ImageOutputFormat::Jpeg(THUMBNAIL_QUALITY),
+        )?;
+        Ok(format!(
+            "data:image/jpeg;base64,{}",
+            general_purpose::STANDARD.encode(&bytes)
+        ))
+    })
+    .await?
+}
+
+/// 渲染每日报告
+fn render_daily_report(day: NaiveDate, summary_text: &str, sections: &[SessionSection]) -> String {
+    let total_minutes: i64 = sections
+        .iter()
+        .map(|section| session_minutes(&section.data.session))
+        .sum();
+    let sessions = sections
+        .iter()
+        .map(|section| &section.data.session)
+        .collect::<Vec<_>>();
+
+    let mut body = format!(
+        "<header>\n<h1>屏幕活动日报</h1>\n<p class=\"meta\">{} · {} 个会话 · 共 {} 分钟</p>\n</header>\n",
+        day.format("%Y-%m-%d"),
+        sections.len(),
+        total_minutes
+    );
+    if !summary_text.trim().is_empty() {
+        body.push_str(&format!(
+            "<h2>当日总结</h2>\n<div class=\"summary\">{}</div>\n",
+            escape_html(summary_text.trim())
+        ));
+    }
+
+    body.push_str("<h2>时间线</h2>\n");
+    body.push_str(&render_timeline(day, &sessions, ""));
+    body.push_str(AXIS);
+
+    body.push_str("<h2>会话</h2>\n");
+    if sections.is_empty() {
+        body.push_str("<p class=\"meta\">当天没有会话记录</p>\n");
+    }
+    for section in sections {
+        body.push_str(&render_session(section));
+    }
+
+    render_page(&format!("屏幕活动日报 {}", day.format("%Y-%m-%d")), &body)
+}
+
+/// 渲染每周报告：每天一行时间线，时间段链接到对应的每日报告
+fn render_weekly_report(week_label: &str, days: &[(NaiveDate, Vec<Session>)]) -> String {
+    let week_minutes: i64 = days
+        .iter()
+        .flat_map(|(_, sessions)| sessions)
+        .map(session_minutes)
+        .sum();
+    let mut body = format!(
+        "<header>\n<h1>屏幕活动周报</h1>\n<p class=\"meta\">{} · 共 {} 分钟</p>\n</header>\n<h2>每日时间线</h2>\n",
+        escape_html(week_label),
+        week_minutes
+    );
+
+    for (day, sessions) in days {
+        let date = day.format("%Y-%m-%d").to_string();
+        let sessions = sessions.iter().collect::<Vec<_>>();
+        let minutes: i64 = sessions
+            .iter()
+            .map(|session| session_minutes(session))
+            .sum();
+        body.push_str(&format!(
+            "<div class=\"week-row\">\n<a href=\"../daily/{}.html\">{}</a>\n{}<span class=\"total\">{} 分钟</span>\n</div>\n",
+            date,
+            day.format("%m-%d %a"),
+            render_timeline(*day, &sessions, &format!("../daily/{}.html", date)),
+            minutes
+        ));
+    }
+    body.push_str(AXIS);
+
+    render_page(&format!("屏幕活动周报 {}", week_label), &body)
+}
+
+const AXIS: &str = "<div class=\"axis\"><span>00:00</span><span>06:00</span><span>12:00</span><span>18:00</span><span>24:00</span></div>\n";
+
+/// 渲染 24 小时时间线；每个会话是一段按起止时间定位的链接
+fn render_timeline(day: NaiveDate, sessions: &[&Session], link_base: &str) -> String {
+    let day_start = day.and_hms_opt(0, 0, 0).unwrap_or_default();
+    let mut timeline = String::from("<div class=\"timeline\">");
+    for session in sessions {
+        let start = (session.start_time.naive_utc() - day_start)
+            .num_minutes()
+            .clamp(0, MINUTES_PER_DAY);
+        let end = (session.end_time.naive_utc() - day_start)
+            .num_minutes()
+            .clamp(start, MINUTES_PER_DAY);
+        timeline.push_str(&format!(
+            "<a class=\"cat-{}\" href=\"{}#session-{}\" style=\"left:{:.2}%;width:{:.2}%\" title=\"{} {}\"></a>",
+            session_category(session).unwrap_or("other"),
+            link_base,
+            session.id.unwrap_or(0),
+            start as f64 * 100.0 / MINUTES_PER_DAY as f64,
+            (end - start) as f64 * 100.0 / MINUTES_PER_DAY as f64,
+            session_range(session),
+            escape_html(session_title(session))
+        ));
+    }
+    timeline.push_str("</div>\n");
+    timeline
+}
+
+/// 渲染单个会话卡片（摘要、时间线卡片与缩略图）
+fn render_session(section: &SessionSection) -> String {
+    let session = &section.data.session;
+    let category = session_category(session).unwrap_or("other");
+    let mut html = format!(
+        "<article class=\"session cat-border-{category}\" id=\"session-{}\">\n<h3><span class=\"time\">{}</span>{}<span class=\"tag cat-{category}\">{category}</span></h3>\n<div class=\"details\">\n",
+        session.id.unwrap_or(0),
+        session_range(session),
+        escape_html(session_title(session)),
+    );
+    if !session.summary.trim().is_empty() {
+        html.push_str(&format!("<p>{}</p>\n", escape_html(session.summary.trim())));
+    }
+    if !section.data.cards.is_empty() {
+        html.push_str("<ul class=\"cards\">\n");
+        for card in &section.data.cards {
+            html.push_str(&format!(
+                "<li>{}-{} {}{}</li>\n",
+                card_clock(&card.start_time),
+                card_clock(&card.end_time),
+                escape_html(card.title.trim()),
+                if card.heuristic {
+                    "<span class=\"heuristic\">（本地分类）</span>"
+                } else {
+                    ""
+                }
+            ));
+        }
+        html.push_str("</ul>\n");
+    }
+    if !section.thumbnails.is_empty() {
+        html.push_str("<div class=\"thumbs\">");
+        for uri in &section.thumbnails {
+            html.push_str(&format!("<img src=\"{}\" alt=\"截图\">", uri));
+        }
+        html.push_str("</div>\n");
+    }
+    html.push_str("</div>\n</article>\n");
+    html
+}
+
+/// 组装完整页面（样式与脚本内联）
+fn render_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<main>\n{}</main>\n<script>{}</script>\n</body>\n</html>\n",
+        escape_html(title),
+        REPORT_STYLE,
+        body,
+        REPORT_SCRIPT
+    )
+}
+
+fn session_title(session: &Session) -> &str {
+    if session.title.trim().is_empty() {
+        "未命名会话"
+    } else {
+        session.title.trim()
+    }
+}
+
+fn session_range(session: &Session) -> String {
+    format!(
+        "{}-{}",
+        session.start_time.format("%H:%M"),
+        session.end_time.format("%H:%M")
+    )
+}
+
+/// 转义 HTML 文本与属性值
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::TimelineCardRecord;
+    use chrono::{TimeZone, Utc};
+
+    fn sample_session(id: i64, start_hour: u32, end_hour: u32, title: &str) -> Session {
+        Session {
+            id: Some(id),
+            start_time: Utc.with_ymd_and_hms(2024, 5, 13, start_hour, 0, 0).unwrap(),
+            end_time: Utc.with_ymd_and_hms(2024, 5, 13, end_hour, 0, 0).unwrap(),
+            title: title.to_string(),
+            summary: "拆分鉴权模块".to_string(),
+            video_path: None,
+            tags: r#"[{"category":"work","confidence":0.9,"keywords":["rust"]}]"#.to_string(),
+            created_at: None,
+            device_name: None,
+            device_type: None,
+        }
+    }
+
+    #[test]
+    fn test_render_daily_report() {
+        let day = NaiveDate::from_ymd_opt(2024, 5, 13).unwrap();
+        let section = SessionSection {
+            data: SessionExportData {
+                session: sample_session(3, 6, 12, "重构 <auth> & 测试"),
+                cards: vec![TimelineCardRecord {
+                    id: None,
+                    session_id: 3,
+                    llm_call_id: None,
+                    start_time: "2024-05-13T06:00:00+08:00".to_string(),
+                    end_time: "2024-05-13T07:30:00+08:00".to_string(),
+                    category: "work".to_string(),
+                    subcategory: String::new(),
+                    title: "编写 API".to_string(),
+                    summary: String::new(),
+                    detailed_summary: String::new(),
+                    distractions: None,
+                    app_sites: "{}".to_string(),
+                    confidence: None,
+                    heuristic: false,
+                    video_preview_path: None,
+                    created_at: Utc::now(),
+                }],
+                issue_links: Vec::new(),
+                apps: Vec::new(),
+                domains: Vec::new(),
+            },
+            thumbnails: vec!["data:image/jpeg;base64,AAAA".to_string()],
+        };
+
+        let html = render_daily_report(day, "专注于后端开发", &[section]);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>屏幕活动日报 2024-05-13</title>"));
+        assert!(html.contains("2024-05-13 · 1 个会话 · 共 360 分钟"));
+        assert!(html.contains(
+            "<a class=\"cat-work\" href=\"#session-3\" style=\"left:25.00%;width:25.00%\" title=\"06:00-12:00 重构 &lt;auth&gt; &amp; 测试\"></a>"
+        ));
+        assert!(html.contains("<li>06:00-07:30 编写 API</li>"));
+        assert!(html.contains("<img src=\"data:image/jpeg;base64,AAAA\" alt=\"截图\">"));
+        // 自包含：不引用外部样式、脚本或图片
+        assert!(!html.contains("http://") && !html.contains("https://"));
+    }
+
+    #[test]
+    fn test_render_weekly_report_links_daily_reports() {
+        let monday = NaiveDate::from_ymd_opt(2024, 5, 13).unwrap();
+        let days = (0..7)
+            .map(|offset| {
+                let sessions = if offset == 0 {
+                    vec![
+                        sample_session(3, 6, 12, "接口重构"),
+                        sample_session(4, 18, 20, "代码评审"),
+                    ]
+                } else {
+                    Vec::new()
+                };
+                (monday + Duration::days(offset), sessions)
+            })
+            .collect::<Vec<_>>();
+
+        let html = render_weekly_report("2024-W20", &days);
+        assert!(html.contains("2024-W20 · 共 480 分钟"));
+        assert!(html.contains("<a href=\"../daily/2024-05-13.html\">05-13 Mon</a>"));
+        assert!(html.contains("href=\"../daily/2024-05-13.html#session-4\""));
+        assert!(html.contains("<a href=\"../daily/2024-05-19.html\">05-19 Sun</a>"));
+        assert_eq!(html.matches("class=\"week-row\"").count(), 7);
+    }
+}
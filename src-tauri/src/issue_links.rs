@@ -0,0 +1,584 @@
+// 工单关联 - 从会话文本中识别 GitHub / Jira 工单引用，经 API 解析标题与状态后关联到会话
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use std::sync::{Arc, OnceLock};
+use tracing::{info, warn};
+
+use crate::event_bus::{AppEvent, EventBus};
+use crate::models::IssueLinkConfig;
+use crate::settings::SettingsManager;
+use crate::storage::{
+    local_now, Database, Frame, FrameText, IssueLinkRecord, Session, TimelineCardRecord,
+};
+
+const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// 识别到的工单目标
+#[derive(Debug, Clone, PartialEq)]
+pub enum IssueTarget {
+    /// repo 为 owner/repo
+    GitHub {
+        repo: String,
+        number: u64,
+    },
+    Jira {
+        key: String,
+    },
+}
+
+/// 文本中的一个工单引用
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssueRef {
+    pub target: IssueTarget,
+    pub url: String,
+    /// 文本中出现的是完整链接（而非 owner/repo#123、ABC-123 这类简写）
+    pub from_url: bool,
+}
+
+impl IssueRef {
+    pub fn provider(&self) -> &'static str {
+        match self.target {
+            IssueTarget::GitHub { .. } => "github",
+            IssueTarget::Jira { .. } => "jira",
+        }
+    }
+
+    /// 显示用编号：owner/repo#123 或 ABC-123
+    pub fn key(&self) -> String {
+        match &self.target {
+            IssueTarget::GitHub { repo, number } => format!("{}#{}", repo, number),
+            IssueTarget::Jira { key } => key.clone(),
+        }
+    }
+}
+
+/// 工单解析结果
+#[derive(Debug, Clone)]
+pub struct ResolvedIssue {
+    pub title: String,
+    pub state: String,
+    pub url: String,
+}
+
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).unwrap())
+}
+
+fn jira_base_url(config: &IssueLinkConfig) -> Option<&str> {
+    match config.jira_base_url.trim().trim_end_matches('/') {
+        "" => None,
+        base => Some(base),
+    }
+}
+
+/// 识别文本中的工单引用（按类别与出现顺序，去重）
+///
+/// - GitHub：issue / PR 链接与 owner/repo#123 简写
+/// - Jira：/browse/ABC-123 链接；配置了站点地址时还识别 ABC-123 编号（可按项目键过滤）
+pub fn detect_issue_refs(text: &str, config: &IssueLinkConfig) -> Vec<IssueRef> {
+    static GITHUB_URL: OnceLock<Regex> = OnceLock::new();
+    static GITHUB_SHORT: OnceLock<Regex> = OnceLock::new();
+    static JIRA_URL: OnceLock<Regex> = OnceLock::new();
+    static JIRA_KEY: OnceLock<Regex> = OnceLock::new();
+
+    let mut refs: Vec<IssueRef> = Vec::new();
+    let mut push = |issue: IssueRef| {
+        if !refs.iter().any(|existing| existing.target == issue.target) {
+            refs.push(issue);
+        }
+    };
+
+    for caps in regex(
+        &GITHUB_URL,
+        r"https?://github\.com/([A-Za-z0-9_.-]+/[A-Za-z0-9_.-]+)/(issues|pull)/(\d+)",
+    )
+    .captures_iter(text)
+    {
+        let Ok(number) = caps[3].parse() else {
+            continue;
+        };
+        push(IssueRef {
+            url: caps[0].to_string(),
+            target: IssueTarget::GitHub {
+                repo: caps[1].to_string(),
+                number,
+            },
+            from_url: true,
+        });
+    }
+    for caps in regex(
+        &GITHUB_SHORT,
+        r"(?:^|[\s(\[（])([A-Za-z0-9_.-]+/[A-Za-z0-9_.-]+)#(\d+)(?-u:\b)",
+    )
+    .captures_iter(text)
+    {
+        let Ok(number) = caps[2].parse() else {
+            continue;
+        };
+        push(IssueRef {
+            url: format!("https://github.com/{}/issues/{}", &caps[1], number),
+            target: IssueTarget::GitHub {
+                repo: caps[1].to_string(),
+                number,
+            },
+            from_url: false,
+        });
+    }
+
+    for caps in regex(
+        &JIRA_URL,
+        r"https?://[^\s/]+/browse/([A-Z][A-Z0-9]+-\d+)(?-u:\b)",
+    )
+    .captures_iter(text)
+    {
+        push(IssueRef {
+            url: caps[0].to_string(),
+            target: IssueTarget::Jira {
+                key: caps[1].to_string(),
+            },
+            from_url: true,
+        });
+    }
+    if let Some(base) = jira_base_url(config) {
+        let project_keys = config
+            .jira_project_keys
+            .split([',', '，', ' '])
+            .map(|key| key.trim().to_uppercase())
+            .filter(|key| !key.is_empty())
+            .collect::<Vec<_>>();
+        // ASCII 单词边界：中文紧邻编号时（如“修复ABC-12问题”）也能识别
+        for caps in regex(&JIRA_KEY, r"(?-u:\b)([A-Z][A-Z0-9]+)-(\d+)(?-u:\b)").captures_iter(text)
+        {
+            if !project_keys.is_empty() && !project_keys.iter().any(|key| key == &caps[1]) {
+                continue;
+            }
+            push(IssueRef {
+                url: format!("{}/browse/{}", base, &caps[0]),
+                target: IssueTarget::Jira {
+                    key: caps[0].to_string(),
+                },
+                from_url: false,
+            });
+        }
+    }
+
+    refs
+}
+
+/// 会话中可用于识别工单的文本：标题、总结，时间线卡片的标题、描述与应用/网站，
+/// 以及截屏时的窗口标题（去重）与 OCR 识别的屏幕文字
+pub fn session_text(
+    session: &Session,
+    cards: &[TimelineCardRecord],
+    frames: &[Frame],
+    frame_texts: &[FrameText],
+) -> String {
+    let mut parts = vec![session.title.as_str(), session.summary.as_str()];
+    for card in cards {
+        parts.extend([
+            card.title.as_str(),
+            card.summary.as_str(),
+            card.detailed_summary.as_str(),
+            card.app_sites.as_str(),
+        ]);
+    }
+    let mut window_titles: Vec<&str> = frames
+        .iter()
+        .filter_map(|frame| frame.window_title.as_deref())
+        .filter(|title| !title.is_empty())
+        .collect();
+    window_titles.sort_unstable();
+    window_titles.dedup();
+    parts.extend(window_titles);
+    parts.extend(frame_texts.iter().map(|text| text.text.as_str()));
+    parts.join("\n")
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubIssue {
+    title: String,
+    state: String,
+    html_url: String,
+    pull_request: Option<GitHubPullRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubPullRef {
+    merged_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraIssue {
+    fields: JiraFields,
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraFields {
+    summary: String,
+    status: Option<JiraStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JiraStatus {
+    name: String,
+}
+
+/// 工单解析器（GitHub REST API / Jira REST API v2）
+pub struct IssueResolver {
+    config: IssueLinkConfig,
+    client: Client,
+}
+
+impl IssueResolver {
+    pub fn new(config: IssueLinkConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(15))
+            .user_agent(concat!("screen-analyzer/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        Ok(Self { config, client })
+    }
+
+    /// 解析工单标题与状态，工单不存在时返回 None
+    pub async fn resolve(&self, issue: &IssueRef) -> Result<Option<ResolvedIssue>> {
+        match &issue.target {
+            IssueTarget::GitHub { repo, number } => self.resolve_github(repo, *number).await,
+            IssueTarget::Jira { key } => self.resolve_jira(key).await,
+        }
+    }
+
+    async fn resolve_github(&self, repo: &str, number: u64) -> Result<Option<ResolvedIssue>> {
+        // issues 接口同样返回 PR（带 pull_request 字段）
+        let mut request = self
+            .client
+            .get(format!(
+                "{}/repos/{}/issues/{}",
+                GITHUB_API_URL, repo, number
+            ))
+            .header("Accept", "application/vnd.github+json");
+        if !self.config.github_token.trim().is_empty() {
+            request = request.bearer_auth(self.config.github_token.trim());
+        }
+        let response = request.send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!("GitHub 返回 {}", response.status().as_u16()));
+        }
+
+        let issue: GitHubIssue = response.json().await?;
+        let merged = issue
+            .pull_request
+            .as_ref()
+            .is_some_and(|pull| pull.merged_at.is_some());
+        Ok(Some(ResolvedIssue {
+            title: issue.title,
+            state: if merged {
+                "merged".to_string()
+            } else {
+                issue.state
+            },
+            url: issue.html_url,
+        }))
+    }
+
+    async fn resolve_jira(&self, key: &str) -> Result<Option<ResolvedIssue>> {
+        let Some(base) = jira_base_url(&self.config) else {
+            return Err(anyhow!("未配置 Jira 站点地址"));
+        };
+        let mut request = self
+            .client
+            .get(format!("{}/rest/api/2/issue/{}", base, key))
+            .query(&[("fields", "summary,status")]);
+        if !self.config.jira_api_token.trim().is_empty() {
+            request = request.basic_auth(
+                self.config.jira_email.trim(),
+                Some(self.config.jira_api_token.trim()),
+            );
+        }
+        let response = request.send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!("Jira 返回 {}", response.status().as_u16()));
+        }
+
+        let issue: JiraIssue = response.json().await?;
+        Ok(Some(ResolvedIssue {
+            title: issue.fields.summary,
+            state: issue
+                .fields
+                .status
+                .map(|status| status.name)
+                .unwrap_or_default(),
+            url: format!("{}/browse/{}", base, key),
+        }))
+    }
+}
+
+/// 识别并解析会话关联的工单，替换数据库中该会话的旧记录
+///
+/// 简写编号只有解析成功才保留（避免 UTF-8 之类的误识别）；完整链接即使解析失败也保留。
+pub async fn link_session(
+    db: &Database,
+    config: &IssueLinkConfig,
+    session_id: i64,
+) -> Result<Vec<IssueLinkRecord>> {
+    let session = db.get_session(session_id).await?;
+    let cards = db
+        .get_timeline_cards_by_session(session_id)
+        .await
+        .unwrap_or_default();
+    let frames = db
+        .get_frames_by_session(session_id)
+        .await
+        .unwrap_or_default();
+    let frame_texts = db
+        .get_frame_texts_by_session(session_id)
+        .await
+        .unwrap_or_default();
+    let refs = detect_issue_refs(
+        &session_text(&session, &cards, &frames, &frame_texts),
+        config,
+    );
+
+    let resolver = IssueResolver::new(config.clone())?;
+    let mut links = Vec::new();
+    for issue in refs {
+        let resolved = match resolver.resolve(&issue).await {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                warn!("解析工单 {} 失败: {}", issue.key(), e);
+                None
+            }
+        };
+        if resolved.is_none() && !issue.from_url {
+            continue;
+        }
+        let (title, state, url) = match resolved {
+            Some(resolved) => (resolved.title, resolved.state, resolved.url),
+            None => (String::new(), String::new(), issue.url.clone()),
+        };
+        links.push(IssueLinkRecord {
+            session_id,
+            provider: issue.provider().to_string(),
+            issue_key: issue.key(),
+            title,
+            state,
+            url,
+            resolved_at: local_now(),
+        });
+    }
+
+    db.replace_issue_links(session_id, &links).await?;
+    Ok(links)
+}
+
+/// 渲染为 Markdown 列表项内容：[编号](链接) 标题（状态）
+pub fn render_issue_link(link: &IssueLinkRecord) -> String {
+    let mut line = format!("[{}]({})", link.issue_key, link.url);
+    if !link.title.is_empty() {
+        line.push(' ');
+        line.push_str(&link.title);
+    }
+    if !link.state.is_empty() {
+        line.push_str(&format!("（{}）", link.state));
+    }
+    line
+}
+
+/// 工单关联器 - 会话分析完成后自动识别关联工单
+pub struct IssueLinker {
+    settings: Arc<SettingsManager>,
+    db: Arc<Database>,
+}
+
+impl IssueLinker {
+    pub fn new(settings: Arc<SettingsManager>, db: Arc<Database>) -> Self {
+        Self { settings, db }
+    }
+
+    pub fn start(self: Arc<Self>, event_bus: Arc<EventBus>) {
+        let mut receiver = event_bus.subscribe();
+
+        tokio::spawn(async move {
+            info!("工单关联监听器已启动");
+            while let Ok(event) = receiver.recv().await {
+                let AppEvent::AnalysisCompleted { session_id, .. } = event else {
+                    continue;
+                };
+                let config = self
+                    .settings
+                    .get()
+                    .await
+                    .issue_link_config
+                    .unwrap_or_default();
+                if !config.enabled {
+                    continue;
+                }
+                match link_session(&self.db, &config, session_id).await {
+                    Ok(links) if !links.is_empty() => {
+                        info!("会话 {} 关联了 {} 个工单", session_id, links.len())
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("会话 {} 工单关联失败: {}", session_id, e),
+                }
+            }
+            warn!("工单关联监听器已停止");
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn keys(text: &str, config: &IssueLinkConfig) -> Vec<(String, bool)> {
+        detect_issue_refs(text, config)
+            .iter()
+            .map(|issue| (issue.key(), issue.from_url))
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_issue_refs() {
+        let text = "审阅 https://github.com/tauri-apps/tauri/pull/8123 后，\
+                    回到 (rust-lang/rust#1024) 讨论；\
+                    浏览器打开 https://team.atlassian.net/browse/OPS-7\n\
+                    修复ABC-12问题，顺带处理 UTF-8 编码与 ABC-12";
+
+        // 未配置 Jira 站点时只识别 Jira 链接
+        assert_eq!(
+            keys(text, &IssueLinkConfig::default()),
+            vec![
+                ("tauri-apps/tauri#8123".to_string(), true),
+                ("rust-lang/rust#1024".to_string(), false),
+                ("OPS-7".to_string(), true),
+            ]
+        );
+
+        let config = IssueLinkConfig {
+            jira_base_url: "https://team.atlassian.net/".to_string(),
+            ..IssueLinkConfig::default()
+        };
+        let refs = detect_issue_refs(text, &config);
+        assert_eq!(
+            refs.iter().map(IssueRef::key).collect::<Vec<_>>(),
+            vec![
+                "tauri-apps/tauri#8123",
+                "rust-lang/rust#1024",
+                "OPS-7",
+                "ABC-12",
+                "UTF-8"
+            ]
+        );
+        assert_eq!(refs[1].url, "https://github.com/rust-lang/rust/issues/1024");
+        assert_eq!(refs[3].url, "https://team.atlassian.net/browse/ABC-12");
+
+        // 按项目键过滤简写编号
+        let config = IssueLinkConfig {
+            jira_project_keys: "abc, ops".to_string(),
+            ..config
+        };
+        assert_eq!(
+            keys(text, &config)
+                .into_iter()
+                .map(|(key, _)| key)
+                .collect::<Vec<_>>(),
+            vec![
+                "tauri-apps/tauri#8123",
+                "rust-lang/rust#1024",
+                "OPS-7",
+                "ABC-12"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_session_text_includes_window_titles_and_ocr() {
+        let timestamp = Utc.with_ymd_and_hms(2024, 5, 12, 9, 0, 0).unwrap();
+        let session = Session {
+            id: Some(1),
+            start_time: timestamp,
+            end_time: timestamp,
+            title: "接口重构".to_string(),
+            summary: String::new(),
+            video_path: None,
+            tags: String::new(),
+            created_at: None,
+            device_name: None,
+            device_type: None,
+        };
+        let frame = |window_title: Option<&str>| Frame {
+            id: None,
+            session_id: 1,
+            timestamp,
+            file_path: String::new(),
+            app_name: None,
+            window_title: window_title.map(str::to_string),
+            process_path: None,
+            domain: None,
+            screen_id: 0,
+        };
+        let frames = vec![
+            frame(Some("OPS-7 修复登录超时 - Jira")),
+            frame(Some("OPS-7 修复登录超时 - Jira")),
+            frame(None),
+        ];
+        let frame_texts = vec![FrameText {
+            id: None,
+            session_id: 1,
+            timestamp,
+            text: "参见 https://github.com/tauri-apps/tauri/pull/8123".to_string(),
+        }];
+
+        let text = session_text(&session, &[], &frames, &frame_texts);
+        assert_eq!(text.matches("OPS-7").count(), 1);
+        assert_eq!(
+            keys(&text, &IssueLinkConfig::default()),
+            vec![("tauri-apps/tauri#8123".to_string(), true)]
+        );
+        let config = IssueLinkConfig {
+            jira_base_url: "https://team.atlassian.net".to_string(),
+            ..IssueLinkConfig::default()
+        };
+        assert_eq!(
+            keys(&text, &config),
+            vec![
+                ("tauri-apps/tauri#8123".to_string(), true),
+                ("OPS-7".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_issue_link() {
+        let link = IssueLinkRecord {
+            session_id: 1,
+            provider: "github".to_string(),
+            issue_key: "tauri-apps/tauri#8123".to_string(),
+            title: "Fix tray icon".to_string(),
+            state: "merged".to_string(),
+            url: "https://github.com/tauri-apps/tauri/pull/8123".to_string(),
+            resolved_at: local_now(),
+        };
+        assert_eq!(
+            render_issue_link(&link),
+            "[tauri-apps/tauri#8123](https://github.com/tauri-apps/tauri/pull/8123) Fix tray icon（merged）"
+        );
+        let unresolved = IssueLinkRecord {
+            title: String::new(),
+            state: String::new(),
+            ..link
+        };
+        assert_eq!(
+            render_issue_link(&unresolved),
+            "[tauri-apps/tauri#8123](https://github.com/tauri-apps/tauri/pull/8123)"
+        );
+    }
+}
@@ -0,0 +1,226 @@
+// JSON 导出 - 按日期范围导出带结构版本的每日总结、会话与时间线卡片，供下游工具消费
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::actors::LLMHandle;
+use crate::exporter::{
+    day_summary, load_day_sessions, range_days, session_category, session_minutes,
+    SessionExportData,
+};
+use crate::models::{
+    ActivityTag, JsonExportDay, JsonExportDocument, JsonExportDomainUsage, JsonExportSession,
+    JsonExportTimelineCard, JSON_EXPORT_SCHEMA_VERSION,
+};
+use crate::storage::{local_now, Database, TimelineCardRecord};
+
+/// 构建日期范围（含首尾）的 JSON 导出文档，跳过没有会话的日期
+///
+/// 每日总结复用已缓存的结果；单日总结生成失败时该日 summary 为空，不中断导出。
+pub async fn build_document(
+    db: Arc<Database>,
+    llm_handle: LLMHandle,
+    start_date: &str,
+    end_date: &str,
+) -> Result<JsonExportDocument> {
+    let mut days = Vec::new();
+    for day in range_days(start_date, end_date)? {
+        let date = day.format("%Y-%m-%d").to_string();
+        if let Some(day) = build_day(db.clone(), llm_handle.clone(), &date, false).await? {
+            days.push(day);
+        }
+    }
+
+    Ok(JsonExportDocument {
+        schema_version: JSON_EXPORT_SCHEMA_VERSION,
+        generated_at: local_now().naive_utc(),
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        days,
+    })
+}
+
+/// 构建单日的导出结构（Webhook 共用），当天没有会话时返回 None
+pub async fn build_day(
+    db: Arc<Database>,
+    llm_handle: LLMHandle,
+    date: &str,
+    force_refresh: bool,
+) -> Result<Option<JsonExportDay>> {
+    let sessions = load_day_sessions(&db, date).await?;
+    if sessions.is_empty() {
+        return Ok(None);
+    }
+    let summary = match day_summary(db, llm_handle, date, force_refresh).await {
+        Ok(summary) => Some(summary),
+        Err(e) => {
+            warn!("JSON 导出生成每日总结失败 {}: {}", date, e);
+            None
+        }
+    };
+    Ok(Some(JsonExportDay {
+        date: date.to_string(),
+        summary,
+        sessions: sessions.iter().map(export_session).collect(),
+    }))
+}
+
+/// 写入 JSON 文件（带缩进），返回文件路径
+pub fn write_document(
+    document: &JsonExportDocument,
+    output_dir: &Path,
+    stem: &str,
+) -> Result<PathBuf> {
+    std::fs::create_dir_all(output_dir)?;
+    let path = output_dir.join(format!("{}.json", stem));
+    std::fs::write(&path, serde_json::to_string_pretty(document)?)?;
+    Ok(path)
+}
+
+fn export_session(data: &SessionExportData) -> JsonExportSession {
+    let session = &data.session;
+    JsonExportSession {
+        id: session.id,
+        start_time: session.start_time.naive_utc(),
+        end_time: session.end_time.naive_utc(),
+        duration_minutes: session_minutes(session),
+        title: session.title.clone(),
+        summary: session.summary.clone(),
+        category: session_category(session).map(str::to_string),
+        tags: serde_json::from_str::<Vec<ActivityTag>>(&session.tags).unwrap_or_default(),
+        device_name: session.device_name.clone(),
+        device_type: session.device_type.clone(),
+        timeline_cards: data.cards.iter().map(export_card).collect(),
+        domains: data
+            .domains
+            .iter()
+            .map(|usage| JsonExportDomainUsage {
+                domain: usage.domain.clone(),
+                seconds: usage.seconds,
+            })
+            .collect(),
+    }
+}
+
+/// 卡片中以字符串存储的 JSON 字段解析为结构化值，无法解析时保留原文
+fn export_card(card: &TimelineCardRecord) -> JsonExportTimelineCard {
+    let parse = |raw: &str| {
+        serde_json::from_str(raw).unwrap_or_else(|_| serde_json::Value::String(raw.to_string()))
+    };
+    JsonExportTimelineCard {
+        id: card.id,
+        start_time: card.start_time.clone(),
+        end_time: card.end_time.clone(),
+        category: card.category.clone(),
+        subcategory: card.subcategory.clone(),
+        title: card.title.clone(),
+        summary: card.summary.clone(),
+        detailed_summary: card.detailed_summary.clone(),
+        distractions: card.distractions.as_deref().map(parse),
+        app_sites: parse(&card.app_sites),
+        heuristic: card.heuristic,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{DomainUsageRecord, Session};
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    #[test]
+    fn test_export_session_schema() {
+        let data = SessionExportData {
+            session: Session {
+                id: Some(3),
+                start_time: Utc.with_ymd_and_hms(2024, 5, 12, 9, 0, 0).unwrap(),
+                end_time: Utc.with_ymd_and_hms(2024, 5, 12, 10, 30, 0).unwrap(),
+                title: "接口重构".to_string(),
+                summary: "拆分鉴权模块".to_string(),
+                video_path: None,
+                tags: r#"[{"category":"work","confidence":0.5,"keywords":["rust"]}]"#.to_string(),
+                created_at: None,
+                device_name: Some("MacBook".to_string()),
+                device_type: None,
+            },
+            cards: vec![TimelineCardRecord {
+                id: Some(8),
+                session_id: 3,
+                llm_call_id: None,
+                start_time: "2024-05-12T09:00:00+08:00".to_string(),
+                end_time: "2024-05-12T09:45:00+08:00".to_string(),
+                category: "work".to_string(),
+                subcategory: "coding".to_string(),
+                title: "编写 API".to_string(),
+                summary: String::new(),
+                detailed_summary: String::new(),
+                distractions: None,
+                app_sites: r#"{"primary":"vscode"}"#.to_string(),
+                confidence: None,
+                heuristic: false,
+                video_preview_path: None,
+                created_at: Utc::now(),
+            }],
+            issue_links: Vec::new(),
+            apps: Vec::new(),
+            domains: vec![DomainUsageRecord {
+                session_id: 3,
+                domain: "docs.rs".to_string(),
+                seconds: 600,
+            }],
+        };
+
+        let value = serde_json::to_value(export_session(&data)).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "id": 3,
+                "startTime": "2024-05-12T09:00:00",
+                "endTime": "2024-05-12T10:30:00",
+                "durationMinutes": 90,
+                "title": "接口重构",
+                "summary": "拆分鉴权模块",
+                "category": "work",
+                "tags": [{ "category": "work", "confidence": 0.5, "keywords": ["rust"] }],
+                "deviceName": "MacBook",
+                "deviceType": null,
+                "timelineCards": [{
+                    "id": 8,
+                    "startTime": "2024-05-12T09:00:00+08:00",
+                    "endTime": "2024-05-12T09:45:00+08:00",
+                    "category": "work",
+                    "subcategory": "coding",
+                    "title": "编写 API",
+                    "summary": "",
+                    "detailedSummary": "",
+                    "distractions": null,
+                    "appSites": { "primary": "vscode" },
+                    "heuristic": false
+                }],
+                "domains": [{ "domain": "docs.rs", "seconds": 600 }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_write_document_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let document = JsonExportDocument {
+            schema_version: JSON_EXPORT_SCHEMA_VERSION,
+            generated_at: local_now().naive_utc(),
+            start_date: "2024-05-12".to_string(),
+            end_date: "2024-05-13".to_string(),
+            days: Vec::new(),
+        };
+
+        let path = write_document(&document, temp_dir.path(), "export").unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("\"schemaVersion\": 1"));
+        let parsed: JsonExportDocument = serde_json::from_str(&content).unwrap();
+        assert_eq!(parsed.start_date, "2024-05-12");
+        assert!(parsed.days.is_empty());
+    }
+}
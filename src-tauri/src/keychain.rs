@@ -0,0 +1,39 @@
+// 系统钥匙串 - 保存本机加密使用的随机密钥（截图加密、SQLCipher 数据库加密）
+//
+// 密钥以 Base64 文本保存在 macOS 钥匙串、Windows 凭据管理器或 Secret Service 中，不写入配置文件
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose, Engine as _};
+use tracing::info;
+
+/// 钥匙串中的服务名
+const SERVICE: &str = "screen-analyzer";
+
+/// 读取 256 位密钥；create 为 true 且不存在时生成新密钥并保存，否则返回 None
+pub fn load_key(account: &str, create: bool) -> Result<Option<[u8; 32]>> {
+    let entry =
+        keyring::Entry::new(SERVICE, account).map_err(|e| anyhow!("无法访问系统钥匙串: {}", e))?;
+    match entry.get_password() {
+        Ok(encoded) => {
+            let key = general_purpose::STANDARD
+                .decode(encoded.trim())
+                .ok()
+                .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                .ok_or_else(|| anyhow!("钥匙串中的密钥 {} 无效", account))?;
+            Ok(Some(key))
+        }
+        Err(keyring::Error::NoEntry) if create => {
+            let mut key = [0u8; 32];
+            OsRng.fill_bytes(&mut key);
+            entry
+                .set_password(&general_purpose::STANDARD.encode(key))
+                .map_err(|e| anyhow!("保存密钥到系统钥匙串失败: {}", e))?;
+            info!("已生成密钥 {} 并保存到系统钥匙串", account);
+            Ok(Some(key))
+        }
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(anyhow!("读取系统钥匙串失败: {}", e)),
+    }
+}
@@ -0,0 +1,726 @@
+// 局域网多设备同步 - 从设备定时把本机最近的会话与时间线卡片（不含截图与视频）推送到主设备，
+// 主设备按（设备名，开始时间）去重写入并清除涉及日期的每日总结缓存，使每日/周报覆盖全部设备
+//
+// 主设备接口（监听配置的地址与端口）：
+// - `GET /sync/status`，用于从设备检测连接
+// - `POST /sync/sessions`，请求体见 `SyncBatch`，返回 `SyncResult`
+// 请求需携带 `Authorization: Bearer <令牌>`；令牌为空时主设备不启动端点，从设备也不推送
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::browser::{read_request, token_matches, HttpRequest};
+use crate::domains::StorageDomain;
+use crate::event_bus::{AppEvent, EventBus};
+use crate::models::{LanSyncConfig, LanSyncRole};
+use crate::storage::{local_now, Database, Session, TimelineCardRecord};
+
+/// 访问令牌请求头
+const AUTH_HEADER: &str = "authorization";
+
+/// 单个推送请求的最大字节数
+const MAX_BATCH_BYTES: usize = 8 * 1024 * 1024;
+
+/// 每个推送请求包含的会话数
+const BATCH_SIZE: usize = 20;
+
+/// 读取单个请求的超时
+const READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// 推送请求的超时
+const PUSH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// 同步的时间线卡片（不含本地视频路径与 LLM 调用记录）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncCard {
+    pub start_time: String,
+    pub end_time: String,
+    pub category: String,
+    pub subcategory: String,
+    pub title: String,
+    pub summary: String,
+    pub detailed_summary: String,
+    pub distractions: Option<String>,
+    pub app_sites: String,
+    pub confidence: Option<f64>,
+    pub heuristic: bool,
+}
+
+impl SyncCard {
+    fn from_record(card: &TimelineCardRecord) -> Self {
+        Self {
+            start_time: card.start_time.clone(),
+            end_time: card.end_time.clone(),
+            category: card.category.clone(),
+            subcategory: card.subcategory.clone(),
+            title: card.title.clone(),
+            summary: card.summary.clone(),
+            detailed_summary: card.detailed_summary.clone(),
+            distractions: card.distractions.clone(),
+            app_sites: card.app_sites.clone(),
+            confidence: card.confidence,
+            heuristic: card.heuristic,
+        }
+    }
+
+    fn into_record(self, session_id: i64) -> TimelineCardRecord {
+        TimelineCardRecord {
+            id: None,
+            session_id,
+            llm_call_id: None,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            category: self.category,
+            subcategory: self.subcategory,
+            title: self.title,
+            summary: self.summary,
+            detailed_summary: self.detailed_summary,
+            distractions: self.distractions,
+            app_sites: self.app_sites,
+            confidence: self.confidence,
+            heuristic: self.heuristic,
+            video_preview_path: None,
+            created_at: local_now(),
+        }
+    }
+}
+
+/// 同步的会话（时间为设备本地时间，与数据库存储一致）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncSession {
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub title: String,
+    pub summary: String,
+    /// JSON 序列化的标签
+    pub tags: String,
+    pub device_type: Option<String>,
+    pub cards: Vec<SyncCard>,
+}
+
+/// 一次推送的内容：某台设备的若干会话
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncBatch {
+    pub device_name: String,
+    pub sessions: Vec<SyncSession>,
+}
+
+/// 推送结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncResult {
+    /// 新写入的会话数
+    pub inserted: usize,
+    /// 内容有变化而覆盖更新的会话数
+    pub updated: usize,
+    /// 与主设备已有数据相同的会话数
+    pub unchanged: usize,
+}
+
+impl SyncResult {
+    fn merge(&mut self, other: &SyncResult) {
+        self.inserted += other.inserted;
+        self.updated += other.updated;
+        self.unchanged += other.unchanged;
+    }
+}
+
+/// 校验同步配置（启用时令牌不能为空，从设备须填写主设备地址）
+pub fn validate(config: &LanSyncConfig) -> Result<()> {
+    if !config.enabled {
+        return Ok(());
+    }
+    if config.token.trim().is_empty() {
+        return Err(anyhow!("启用局域网同步时必须设置访问令牌"));
+    }
+    if config.role == LanSyncRole::Client {
+        let url = reqwest::Url::parse(config.primary_url.trim())
+            .map_err(|e| anyhow!("主设备地址无效: {}", e))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(anyhow!("主设备地址须以 http:// 或 https:// 开头"));
+        }
+    }
+    Ok(())
+}
+
+fn authorized(request: &HttpRequest, token: &str) -> bool {
+    let token = token.trim();
+    !token.is_empty()
+        && request
+            .headers
+            .get(AUTH_HEADER)
+            .and_then(|value| value.trim().strip_prefix("Bearer "))
+            .is_some_and(|provided| token_matches(provided.trim(), token))
+}
+
+/// 本机最近 `lookback_days` 天（含今天）的会话与时间线卡片；其他设备同步来的会话不推送
+pub async fn collect_batch(
+    db: &Database,
+    device_name: &str,
+    today: NaiveDate,
+    lookback_days: u32,
+) -> Result<SyncBatch> {
+    let start = today - chrono::Duration::days(i64::from(lookback_days.max(1)) - 1);
+    let mut sessions = Vec::new();
+    for day in start.iter_days().take_while(|day| *day <= today) {
+        let day_sessions = db
+            .get_sessions_by_date(&day.format("%Y-%m-%d").to_string())
+            .await?;
+        for session in day_sessions {
+            if session
+                .device_name
+                .as_deref()
+                .is_some_and(|name| name != device_name)
+            {
+                continue;
+            }
+            let cards = match session.id {
+                Some(session_id) => db.get_timeline_cards_by_session(session_id).await?,
+                None => Vec::new(),
+            };
+            sessions.push(SyncSession {
+                start_time: session.start_time,
+                end_time: session.end_time,
+                title: session.title,
+                summary: session.summary,
+                tags: session.tags,
+                device_type: session.device_type,
+                cards: cards.iter().map(SyncCard::from_record).collect(),
+            });
+        }
+    }
+    sessions.sort_by_key(|session| session.start_time);
+    Ok(SyncBatch {
+        device_name: device_name.to_string(),
+        sessions,
+    })
+}
+
+/// 主设备写入推送的会话：按设备名与开始时间匹配已有会话，内容有变化时覆盖会话与时间线卡片，
+/// 并清除涉及日期的每日总结缓存（下次查看时重新生成）
+pub async fn import_batch(
+    db: &Database,
+    batch: SyncBatch,
+    local_device: &str,
+) -> Result<SyncResult> {
+    let device_name = batch.device_name.trim().to_string();
+    if device_name.is_empty() {
+        return Err(anyhow!("设备名称为空"));
+    }
+    if device_name == local_device {
+        return Err(anyhow!(
+            "设备名称与主设备相同（{}），无法区分同步的数据",
+            device_name
+        ));
+    }
+
+    let mut result = SyncResult::default();
+    let mut changed_dates = BTreeSet::new();
+    // 该设备已同步的会话，按日期缓存（开始时间 -> 会话），每个日期只查询一次
+    let mut known: HashMap<String, HashMap<DateTime<Utc>, Session>> = HashMap::new();
+    for incoming in batch.sessions {
+        let date = incoming.start_time.format("%Y-%m-%d").to_string();
+        if !known.contains_key(&date) {
+            let sessions = db
+                .get_sessions_by_date(&date)
+                .await?
+                .into_iter()
+                .filter(|session| session.device_name.as_deref() == Some(device_name.as_str()))
+                .map(|session| (session.start_time, session))
+                .collect();
+            known.insert(date.clone(), sessions);
+        }
+        let day_sessions = known.entry(date.clone()).or_default();
+        let existing = day_sessions.get(&incoming.start_time).cloned();
+
+        match existing.and_then(|session| session.id.map(|id| (id, session))) {
+            Some((session_id, session)) => {
+                let cards: Vec<SyncCard> = db
+                    .get_timeline_cards_by_session(session_id)
+                    .await?
+                    .iter()
+                    .map(SyncCard::from_record)
+                    .collect();
+                if session.end_time == incoming.end_time
+                    && session.title == incoming.title
+                    && session.summary == incoming.summary
+                    && session.tags == incoming.tags
+                    && cards == incoming.cards
+                {
+                    result.unchanged += 1;
+                    continue;
+                }
+                // 会话内容与卡片在同一事务中替换，中途失败不会留下缺卡片的会话
+                let updated = Session {
+                    end_time: incoming.end_time,
+                    title: incoming.title,
+                    summary: incoming.summary,
+                    tags: incoming.tags,
+                    ..session
+                };
+                let records = card_records(session_id, incoming.cards);
+                db.replace_session_content(session_id, &updated, &records)
+                    .await?;
+                day_sessions.insert(updated.start_time, updated);
+                result.updated += 1;
+            }
+            None => {
+                let mut session = Session {
+                    id: None,
+                    start_time: incoming.start_time,
+                    end_time: incoming.end_time,
+                    title: incoming.title,
+                    summary: incoming.summary,
+                    video_path: None,
+                    tags: incoming.tags,
+                    created_at: None,
+                    device_name: Some(device_name.clone()),
+                    device_type: incoming.device_type,
+                };
+                // 会话与卡片在同一事务中写入，失败时不会留下没有卡片的会话
+                let records = card_records(0, incoming.cards);
+                let session_id = db.insert_session_with_cards(&session, &records).await?;
+                session.id = Some(session_id);
+                day_sessions.insert(session.start_time, session);
+                result.inserted += 1;
+            }
+        }
+        changed_dates.insert(date);
+    }
+
+    for date in changed_dates {
+        if let Err(e) = db.delete_day_summary(&date).await {
+            warn!("清除每日总结缓存失败 ({}): {}", date, e);
+        }
+    }
+    Ok(result)
+}
+
+fn card_records(session_id: i64, cards: Vec<SyncCard>) -> Vec<TimelineCardRecord> {
+    cards
+        .into_iter()
+        .map(|card| card.into_record(session_id))
+        .collect()
+}
+
+/// 把本机最近的会话分批推送到主设备
+pub async fn push_recent(db: &Database, config: &LanSyncConfig) -> Result<SyncResult> {
+    validate(config)?;
+    if !config.enabled || config.role != LanSyncRole::Client {
+        return Err(anyhow!("本机未启用局域网同步的从设备模式"));
+    }
+    let (device_name, _) = crate::storage::get_device_info();
+    let batch = collect_batch(
+        db,
+        &device_name,
+        local_now().date_naive(),
+        config.lookback_days,
+    )
+    .await?;
+
+    let client = reqwest::Client::builder()
+        .timeout(PUSH_TIMEOUT)
+        .build()
+        .map_err(|e| anyhow!("创建 HTTP 客户端失败: {}", e))?;
+    let url = format!(
+        "{}/sync/sessions",
+        config.primary_url.trim().trim_end_matches('/')
+    );
+    let mut result = SyncResult::default();
+    for sessions in batch.sessions.chunks(BATCH_SIZE) {
+        let response = client
+            .post(&url)
+            .bearer_auth(config.token.trim())
+            .json(&SyncBatch {
+                device_name: batch.device_name.clone(),
+                sessions: sessions.to_vec(),
+            })
+            .send()
+            .await
+            .map_err(|e| anyhow!("连接主设备失败: {}", e))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("主设备拒绝同步 ({}): {}", status, body));
+        }
+        result.merge(
+            &response
+                .json::<SyncResult>()
+                .await
+                .map_err(|e| anyhow!("解析主设备响应失败: {}", e))?,
+        );
+    }
+    Ok(result)
+}
+
+/// 处理一个请求，返回状态行与 JSON 响应体
+async fn handle_request(
+    request: &HttpRequest,
+    token: &str,
+    db: Option<&Database>,
+    local_device: &str,
+) -> (&'static str, String) {
+    if !authorized(request, token) {
+        return (
+            "401 Unauthorized",
+            json!({ "error": "访问令牌无效" }).to_string(),
+        );
+    }
+
+    let path = request.path.split('?').next().unwrap_or_default();
+    match (request.method.as_str(), path) {
+        ("GET", "/sync/status") => (
+            "200 OK",
+            json!({ "ok": true, "device": local_device }).to_string(),
+        ),
+        ("POST", "/sync/sessions") => {
+            let batch = match serde_json::from_slice::<SyncBatch>(&request.body) {
+                Ok(batch) => batch,
+                Err(e) => {
+                    return (
+                        "400 Bad Request",
+                        json!({ "error": format!("请求体无效: {}", e) }).to_string(),
+                    )
+                }
+            };
+            let Some(db) = db else {
+                return (
+                    "503 Service Unavailable",
+                    json!({ "error": "数据库未就绪" }).to_string(),
+                );
+            };
+            let device = batch.device_name.clone();
+            match import_batch(db, batch, local_device).await {
+                Ok(result) => {
+                    info!(
+                        "已接收设备 {} 的同步数据: 新增 {} 个会话，更新 {} 个",
+                        device, result.inserted, result.updated
+                    );
+                    ("200 OK", json!(result).to_string())
+                }
+                Err(e) => {
+                    warn!("写入设备 {} 的同步数据失败: {}", device, e);
+                    (
+                        "422 Unprocessable Entity",
+                        json!({ "error": e.to_string() }).to_string(),
+                    )
+                }
+            }
+        }
+        _ => ("404 Not Found", json!({ "error": "not found" }).to_string()),
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    token: &str,
+    storage_domain: &StorageDomain,
+) -> Result<()> {
+    let request = tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream, MAX_BATCH_BYTES))
+        .await
+        .map_err(|_| anyhow!("读取请求超时"))??;
+    let db = storage_domain.try_get_db().await;
+    let (device_name, _) = crate::storage::get_device_info();
+    let (status, body) = handle_request(&request, token, db.as_deref(), &device_name).await;
+
+    let response = format!(
+        "HTTP/1.1 {}\r\n\
+         Content-Type: application/json; charset=utf-8\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    Ok(())
+}
+
+/// 正在运行的任务（主设备为监听端点，从设备为定时推送；配置变更时重建）
+struct RunningTask {
+    config: LanSyncConfig,
+    task: JoinHandle<()>,
+}
+
+/// 局域网同步服务 - 按当前配置作为主设备接收推送，或作为从设备定时推送
+pub struct LanSyncService {
+    storage_domain: Arc<StorageDomain>,
+    running: Mutex<Option<RunningTask>>,
+}
+
+impl LanSyncService {
+    pub fn new(storage_domain: Arc<StorageDomain>) -> Self {
+        Self {
+            storage_domain,
+            running: Mutex::new(None),
+        }
+    }
+
+    /// 启动（配置更新时重建）
+    pub fn start(self: Arc<Self>, event_bus: Arc<EventBus>) {
+        let mut receiver = event_bus.subscribe();
+
+        tokio::spawn(async move {
+            if let Err(e) = self.refresh().await {
+                warn!("局域网同步启动失败: {}", e);
+            }
+
+            while let Ok(event) = receiver.recv().await {
+                if let AppEvent::ConfigUpdated { .. } = event {
+                    if let Err(e) = self.refresh().await {
+                        warn!("局域网同步启动失败: {}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// 按最新配置启动、重启或停止
+    async fn refresh(&self) -> Result<()> {
+        let config = self
+            .storage_domain
+            .get_settings()
+            .get()
+            .await
+            .lan_sync_config
+            .unwrap_or_default();
+        let mut running = self.running.lock().await;
+        if config.enabled
+            && running
+                .as_ref()
+                .is_some_and(|current| current.config == config && !current.task.is_finished())
+        {
+            return Ok(());
+        }
+
+        if let Some(current) = running.take() {
+            current.task.abort();
+            info!("局域网同步已停止");
+        }
+        if !config.enabled {
+            return Ok(());
+        }
+        validate(&config)?;
+
+        let task = match config.role {
+            LanSyncRole::Primary => self.spawn_listener(&config).await?,
+            LanSyncRole::Client => self.spawn_pusher(&config),
+        };
+        *running = Some(RunningTask { config, task });
+        Ok(())
+    }
+
+    async fn spawn_listener(&self, config: &LanSyncConfig) -> Result<JoinHandle<()>> {
+        let address = config.bind_address.trim();
+        let tcp = TcpListener::bind((address, config.port))
+            .await
+            .map_err(|e| anyhow!("无法监听 {}:{}: {}", address, config.port, e))?;
+        info!("局域网同步端点已启动: http://{}:{}", address, config.port);
+
+        let token = config.token.clone();
+        let storage_domain = self.storage_domain.clone();
+        Ok(tokio::spawn(async move {
+            loop {
+                match tcp.accept().await {
+                    Ok((stream, peer)) => {
+                        let token = token.clone();
+                        let storage_domain = storage_domain.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = handle_connection(stream, &token, &storage_domain).await
+                            {
+                                debug!("局域网同步请求处理失败 ({}): {}", peer, e);
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        warn!("局域网同步端点接受连接失败: {}", e);
+                        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                    }
+                }
+            }
+        }))
+    }
+
+    fn spawn_pusher(&self, config: &LanSyncConfig) -> JoinHandle<()> {
+        info!("局域网同步已启动，推送到 {}", config.primary_url.trim());
+        let config = config.clone();
+        let storage_domain = self.storage_domain.clone();
+        let interval =
+            std::time::Duration::from_secs(u64::from(config.interval_minutes.max(1)) * 60);
+        tokio::spawn(async move {
+            loop {
+                match storage_domain.get_db().await {
+                    Ok(db) => match push_recent(&db, &config).await {
+                        Ok(result) => debug!(
+                            "局域网同步完成: 新增 {}，更新 {}，未变化 {}",
+                            result.inserted, result.updated, result.unchanged
+                        ),
+                        Err(e) => warn!("局域网同步失败: {}", e),
+                    },
+                    Err(e) => warn!("局域网同步跳过，数据库未就绪: {}", e),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::tempdir;
+
+    fn sync_session(hour: u32, title: &str, category: &str) -> SyncSession {
+        SyncSession {
+            start_time: Utc.with_ymd_and_hms(2024, 5, 8, hour, 0, 0).unwrap(),
+            end_time: Utc.with_ymd_and_hms(2024, 5, 8, hour + 1, 0, 0).unwrap(),
+            title: title.to_string(),
+            summary: String::new(),
+            tags: "[]".to_string(),
+            device_type: Some("macos".to_string()),
+            cards: vec![SyncCard {
+                start_time: format!("2024-05-08T{:02}:00:00+00:00", hour),
+                end_time: format!("2024-05-08T{:02}:45:00+00:00", hour),
+                category: category.to_string(),
+                subcategory: String::new(),
+                title: title.to_string(),
+                summary: String::new(),
+                detailed_summary: String::new(),
+                distractions: None,
+                app_sites: "{}".to_string(),
+                confidence: Some(0.9),
+                heuristic: false,
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_batch_upserts_sessions() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db = Database::new_sqlite(db_path.to_str().unwrap())
+            .await
+            .unwrap();
+        let batch = |sessions: Vec<SyncSession>| SyncBatch {
+            device_name: "work-laptop".to_string(),
+            sessions,
+        };
+
+        let first = vec![
+            sync_session(9, "写代码", "work"),
+            sync_session(13, "阅读", "learning"),
+        ];
+        let result = import_batch(&db, batch(first.clone()), "desktop")
+            .await
+            .unwrap();
+        assert_eq!(
+            (result.inserted, result.updated, result.unchanged),
+            (2, 0, 0)
+        );
+
+        // 重复推送不产生新会话；内容变化的会话覆盖更新
+        let mut second = first.clone();
+        second[1].title = "阅读文档".to_string();
+        second[1].cards[0].category = "work".to_string();
+        let result = import_batch(&db, batch(second), "desktop").await.unwrap();
+        assert_eq!(
+            (result.inserted, result.updated, result.unchanged),
+            (0, 1, 1)
+        );
+
+        let sessions = db.get_sessions_by_date("2024-05-08").await.unwrap();
+        assert_eq!(sessions.len(), 2);
+        let updated = sessions.iter().find(|s| s.title == "阅读文档").unwrap();
+        assert_eq!(updated.device_name.as_deref(), Some("work-laptop"));
+        let cards = db
+            .get_timeline_cards_by_session(updated.id.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].category, "work");
+
+        // 从设备收集时跳过其他设备同步来的会话
+        let collected = collect_batch(
+            &db,
+            "home-pc",
+            NaiveDate::from_ymd_opt(2024, 5, 8).unwrap(),
+            1,
+        )
+        .await
+        .unwrap();
+        assert!(collected.sessions.is_empty());
+        let collected = collect_batch(
+            &db,
+            "work-laptop",
+            NaiveDate::from_ymd_opt(2024, 5, 9).unwrap(),
+            2,
+        )
+        .await
+        .unwrap();
+        assert_eq!(collected.sessions.len(), 2);
+        assert_eq!(collected.sessions[0].cards, first[0].cards);
+
+        // 同一批次内重复的会话按已写入的结果比较，不会重复插入
+        let mut repeated = vec![sync_session(16, "开会", "meeting")];
+        repeated.push(repeated[0].clone());
+        let result = import_batch(&db, batch(repeated), "desktop").await.unwrap();
+        assert_eq!(
+            (result.inserted, result.updated, result.unchanged),
+            (1, 0, 1)
+        );
+        assert_eq!(
+            db.get_sessions_by_date("2024-05-08").await.unwrap().len(),
+            3
+        );
+
+        assert!(import_batch(&db, batch(first), "work-laptop")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_requests_require_token() {
+        let request = |auth: Option<&str>| HttpRequest {
+            method: "GET".to_string(),
+            path: "/sync/status".to_string(),
+            headers: auth
+                .map(|value| (AUTH_HEADER.to_string(), value.to_string()))
+                .into_iter()
+                .collect(),
+            body: Vec::new(),
+        };
+        let status = |auth, token| async move {
+            handle_request(&request(auth), token, None, "desktop")
+                .await
+                .0
+        };
+        assert_eq!(status(None, "secret").await, "401 Unauthorized");
+        assert_eq!(
+            status(Some("Bearer wrong"), "secret").await,
+            "401 Unauthorized"
+        );
+        assert_eq!(status(Some("Bearer secret"), "").await, "401 Unauthorized");
+        assert_eq!(status(Some("Bearer secret"), "secret").await, "200 OK");
+
+        let mut config = LanSyncConfig {
+            enabled: true,
+            token: "secret".to_string(),
+            role: LanSyncRole::Client,
+            ..LanSyncConfig::default()
+        };
+        assert!(validate(&config).is_err());
+        config.primary_url = "http://192.168.1.10:38463".to_string();
+        assert!(validate(&config).is_ok());
+        config.token.clear();
+        assert!(validate(&config).is_err());
+    }
+}
@@ -2,39 +2,76 @@
 
 // 声明模块
 pub mod actors;
+pub mod anki;
+pub mod app_usage;
+pub mod backup;
+pub mod browser;
+pub mod calendar;
 pub mod capture;
+pub mod chat_summary;
 pub mod config_migration;
+pub mod custom_metrics;
+pub mod digest;
 pub mod domains;
+pub mod embeddings;
 pub mod event_bus;
+pub mod exporter;
+pub mod frame_crypto;
+pub mod frame_prep;
+pub mod frame_store;
+pub mod goals;
+pub mod heatmap;
+pub mod html;
+pub mod issue_links;
+pub mod json_export;
+pub mod keychain;
+pub mod lan_sync;
 pub mod llm;
 pub mod logger;
+pub mod logseq;
 pub mod models;
+pub mod mqtt;
 pub mod notion;
 pub mod obsidian;
+pub mod ocr;
+pub mod raw_export;
+pub mod recategorize;
+pub mod rescuetime;
+pub mod review;
 pub mod settings;
 pub mod storage;
+pub mod taxonomy;
+pub mod time_tracking;
+pub mod tray;
+pub mod trends;
 pub mod video;
+pub mod webhook;
+pub mod wellbeing;
 
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use serde::{Deserialize, Serialize};
 use tauri::Manager;
 // Actor模式不再需要Mutex和RwLock
 // use tokio::sync::{Mutex, RwLock};
 use tracing::{debug, error, info, warn};
 
 // 导入必要的类型
-use capture::{scheduler::CaptureScheduler, ScreenCapture};
-use domains::{AnalysisDomain, CaptureDomain, StorageDomain, SystemDomain};
-use event_bus::EventBus;
-use llm::{plugin::LLMProvider, CodexProvider, LLMManager};
-use models::*;
+use capture::{
+    scheduler::{CapturePauseState, CaptureScheduler, ManualSession},
+    ScreenCapture,
+};
 use config_migration::{
     normalize_imported_config, persisted_to_app_config, strip_secrets, ConfigExportPackage,
 };
+use domains::{AnalysisDomain, CaptureDomain, StorageDomain, SystemDomain};
+use event_bus::{AppEvent, EventBus};
+use exporter::ExporterRegistry;
+use llm::{plugin::LLMProvider, CodexProvider, LLMManager};
+use models::*;
 use obsidian::ObsidianExporter;
 use settings::SettingsManager;
-use storage::{Database, StorageCleaner};
+use storage::{Database, SessionNoteRecord, StorageCleaner};
 use video::VideoProcessor;
 
 // 视频帧采样相关常量
@@ -73,6 +110,80 @@ pub enum FolderType {
     Videos,
 }
 
+/// Obsidian 回顾笔记周期
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObsidianRollupPeriod {
+    /// 季度回顾
+    Quarter,
+    /// 年度回顾
+    Year,
+}
+
+/// Obsidian 导出结束事件（obsidian-export-complete）的负载
+#[derive(Debug, Clone, Serialize)]
+struct ObsidianExportComplete {
+    success: bool,
+    message: String,
+}
+
+/// 将导出事件转发为前端的 obsidian-export-progress 事件，通道关闭后任务结束
+fn spawn_obsidian_progress_forwarder(
+    app: tauri::AppHandle,
+) -> (
+    tokio::sync::mpsc::UnboundedSender<obsidian::ExportEvent>,
+    tokio::task::JoinHandle<()>,
+) {
+    use tauri::Emitter;
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let handle = tokio::spawn(async move {
+        let mut tracker = obsidian::ExportProgressTracker::default();
+        while let Some(event) = rx.recv().await {
+            let _ = app.emit("obsidian-export-progress", tracker.observe(event));
+        }
+    });
+    (tx, handle)
+}
+
+/// 等待进度转发完成后发送 obsidian-export-complete 事件，并返回命令结果
+async fn finish_obsidian_export(
+    app: &tauri::AppHandle,
+    forwarder: tokio::task::JoinHandle<()>,
+    result: anyhow::Result<obsidian::ExportOutcome>,
+) -> Result<String, String> {
+    use tauri::Emitter;
+
+    // 确保所有进度事件先于完成事件送达
+    let _ = forwarder.await;
+    let result = result
+        .map(|outcome| outcome.render_message())
+        .map_err(|e| e.to_string());
+    let payload = match &result {
+        Ok(message) => ObsidianExportComplete {
+            success: true,
+            message: message.clone(),
+        },
+        Err(message) => ObsidianExportComplete {
+            success: false,
+            message: message.clone(),
+        },
+    };
+    let _ = app.emit("obsidian-export-complete", payload);
+    result
+}
+
+/// 写入 Obsidian 导出历史（失败仅记录日志，不影响导出结果）
+async fn record_obsidian_export(db: &Database, run: obsidian::ExportRun) {
+    let result = match run.to_record() {
+        Ok(record) => db.insert_export_history(&record).await.map(|_| ()),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        warn!("写入 Obsidian 导出历史失败: {}", e);
+    }
+}
+
 // ==================== 输入验证辅助函数 ====================
 
 /// 验证会话ID是否有效（防止SQL注入和无效输入）
@@ -133,6 +244,94 @@ async fn get_day_sessions(
         .map_err(|e| e.to_string())
 }
 
+/// 全文搜索会话（标题、总结与时间线卡片），按相关度返回带片段的结果
+///
+/// # 参数
+/// * `query` - 搜索词，空白分隔的多个词需同时命中
+/// * `date_range` - 可选的日期范围 [开始, 结束] (YYYY-MM-DD，含首尾)
+/// * `limit` - 最多返回条数（默认 50）
+#[tauri::command]
+async fn search_sessions(
+    state: tauri::State<'_, AppState>,
+    query: String,
+    date_range: Option<(String, String)>,
+    limit: Option<i64>,
+) -> Result<Vec<storage::SessionSearchHit>, String> {
+    let (start_date, end_date) =
+        date_range.unwrap_or_else(|| ("0001-01-01".to_string(), "9999-12-31".to_string()));
+    state
+        .storage_domain
+        .get_db()
+        .await?
+        .search_sessions(
+            query.trim(),
+            &start_date,
+            &end_date,
+            limit.unwrap_or(50).clamp(1, 200),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 按当前配置创建向量接口客户端
+async fn embedding_client(state: &AppState) -> Result<embeddings::EmbeddingClient, String> {
+    let config = state.storage_domain.get_settings().get().await;
+    embeddings::EmbeddingClient::from_config(
+        &config.embedding_config.clone().unwrap_or_default(),
+        &config.llm_provider,
+        config.llm_config.as_ref(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 语义搜索会话，如 "when did I debug the payment webhook"
+///
+/// # 参数
+/// * `query` - 自然语言描述
+/// * `date_range` - 可选的日期范围 [开始, 结束] (YYYY-MM-DD，含首尾)
+/// * `limit` - 最多返回条数（默认 20）
+#[tauri::command]
+async fn semantic_search(
+    state: tauri::State<'_, AppState>,
+    query: String,
+    date_range: Option<(String, String)>,
+    limit: Option<i64>,
+) -> Result<Vec<storage::SessionSearchHit>, String> {
+    let (start_date, end_date) =
+        date_range.unwrap_or_else(|| ("0001-01-01".to_string(), "9999-12-31".to_string()));
+    let client = embedding_client(&state).await?;
+    let db = state.storage_domain.get_db().await?;
+    embeddings::semantic_search(
+        &db,
+        &client,
+        &query,
+        &start_date,
+        &end_date,
+        limit.unwrap_or(20).clamp(1, 100) as usize,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// 为日期范围内的会话补齐语义向量（文本未变化的会话跳过）
+#[tauri::command]
+async fn rebuild_embeddings(
+    state: tauri::State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+) -> Result<String, String> {
+    let start = chrono::NaiveDate::parse_from_str(&start_date, "%Y-%m-%d")
+        .map_err(|e| format!("开始日期格式错误: {}", e))?;
+    let end = chrono::NaiveDate::parse_from_str(&end_date, "%Y-%m-%d")
+        .map_err(|e| format!("结束日期格式错误: {}", e))?;
+    let client = embedding_client(&state).await?;
+    let db = state.storage_domain.get_db().await?;
+    let report = embeddings::index_range(&db, &client, start, end)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(report.render_message())
+}
+
 /// 获取某天的总结数据
 ///
 /// # 参数
@@ -146,19 +345,1719 @@ async fn get_day_summary(
 ) -> Result<domains::summary::DaySummary, String> {
     let db = state.storage_domain.get_db().await?;
     let llm_handle = state.analysis_domain.get_llm_handle();
-    let generator = domains::summary::SummaryGenerator::with_llm(db, llm_handle.clone());
+    let app_limits = state
+        .storage_domain
+        .get_settings()
+        .get()
+        .await
+        .app_limit_config
+        .unwrap_or_default()
+        .limits;
+    let generator = domains::summary::SummaryGenerator::with_llm(db, llm_handle.clone())
+        .with_app_limits(app_limits);
     generator
         .generate_day_summary(&date, force_refresh.unwrap_or(false))
         .await
 }
 
-/// 导出指定日期到 Obsidian
+/// 日期范围内有会话记录的日期（用于按天排队）
+async fn queue_dates(
+    state: &AppState,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<chrono::NaiveDate>, String> {
+    let db = state.storage_domain.get_db().await?;
+    let mut dates = Vec::new();
+    for day in exporter::range_days(start_date, end_date).map_err(|e| e.to_string())? {
+        let date = day.format("%Y-%m-%d").to_string();
+        match db.get_sessions_by_date(&date).await {
+            Ok(sessions) if sessions.is_empty() => continue,
+            _ => dates.push(day),
+        }
+    }
+    if dates.is_empty() {
+        return Err(format!("{} ~ {} 没有会话记录", start_date, end_date));
+    }
+    Ok(dates)
+}
+
+/// 将日期范围内的每日总结加入重新生成队列（今天优先，进度通过 summary-queue-progress 事件推送）
+#[tauri::command]
+async fn queue_day_summaries(
+    state: tauri::State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+) -> Result<actors::summary_queue::EnqueueResult, String> {
+    let dates = queue_dates(&state, &start_date, &end_date).await?;
+    state
+        .analysis_domain
+        .get_summary_queue()
+        .enqueue(dates, actors::summary_queue::SummaryJobKind::Resummarize)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 将日期范围按天加入批量导出队列
+#[tauri::command]
+async fn queue_export_range(
+    state: tauri::State<'_, AppState>,
+    target: String,
+    start_date: String,
+    end_date: String,
+) -> Result<actors::summary_queue::EnqueueResult, String> {
+    // 入队前先确认导出目标可用，避免整批任务失败
+    let config = state.storage_domain.get_settings().get().await;
+    ExporterRegistry::from_config(&config)
+        .get(&target)
+        .map_err(|e| e.to_string())?;
+
+    let dates = queue_dates(&state, &start_date, &end_date).await?;
+    state
+        .analysis_domain
+        .get_summary_queue()
+        .enqueue(
+            dates,
+            actors::summary_queue::SummaryJobKind::Export { target },
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 取消总结队列中的任务（batch_id 为空时取消全部），返回取消的任务数
+#[tauri::command]
+async fn cancel_summary_jobs(
+    state: tauri::State<'_, AppState>,
+    batch_id: Option<u64>,
+) -> Result<usize, String> {
+    state
+        .analysis_domain
+        .get_summary_queue()
+        .cancel(batch_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取总结队列状态（运行中与排队中的任务）
+#[tauri::command]
+async fn get_summary_queue_status(
+    state: tauri::State<'_, AppState>,
+) -> Result<actors::summary_queue::QueueStatus, String> {
+    state
+        .analysis_domain
+        .get_summary_queue()
+        .status()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 清空 LLM 响应缓存，返回删除的条数
+#[tauri::command]
+async fn clear_llm_cache(state: tauri::State<'_, AppState>) -> Result<u64, String> {
+    let db = state.storage_domain.get_db().await?;
+    let count = db.prune_llm_cache(None).await.map_err(|e| e.to_string())?;
+    info!("已清空 {} 条 LLM 响应缓存", count);
+    Ok(count)
+}
+
+/// 获取最近未通过结构校验的 LLM 响应（含修复重试结果）
+#[tauri::command]
+async fn get_llm_parse_failures(
+    state: tauri::State<'_, AppState>,
+    limit: Option<i64>,
+) -> Result<Vec<storage::LlmParseFailure>, String> {
+    let db = state.storage_domain.get_db().await?;
+    db.get_llm_parse_failures(limit.unwrap_or(50).clamp(1, 500))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取置信度低于阈值、等待确认或修正类别的时间线卡片
+#[tauri::command]
+async fn get_review_queue(
+    state: tauri::State<'_, AppState>,
+    limit: Option<i64>,
+) -> Result<Vec<storage::TimelineCardRecord>, String> {
+    let config = state.storage_domain.get_settings().get().await;
+    let db = state.storage_domain.get_db().await?;
+    review::review_queue(
+        &db,
+        &config.review_queue_config.unwrap_or_default(),
+        limit.unwrap_or(50).clamp(1, 500),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// 确认或修正待复核卡片的类别（category 为空表示确认当前类别），一致的复核会生成预分类规则
+#[tauri::command]
+async fn review_timeline_card(
+    state: tauri::State<'_, AppState>,
+    card_id: i64,
+    category: Option<String>,
+    subcategory: Option<String>,
+) -> Result<review::ReviewOutcome, String> {
+    let config = state.storage_domain.get_settings().get().await;
+    let db = state.storage_domain.get_db().await?;
+    review::review_card(
+        &db,
+        &config.review_queue_config.unwrap_or_default(),
+        card_id,
+        category,
+        subcategory,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// 导出指定日期到 Obsidian
+#[tauri::command]
+async fn export_obsidian_day(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    date: String,
+    force_refresh: Option<bool>,
+) -> Result<String, String> {
+    let config = state.storage_domain.get_settings().get().await;
+    let obsidian_config = config.obsidian_config.unwrap_or_default();
+
+    if !obsidian_config.enabled {
+        return Err("Obsidian 导出未启用，请在设置中开启".to_string());
+    }
+
+    let to_archive = obsidian_config.export_target == ObsidianExportTarget::Zip;
+    if !to_archive && obsidian_config.vault_path.trim().is_empty() {
+        return Err("请先配置 Obsidian Vault 路径".to_string());
+    }
+
+    let db = state.storage_domain.get_db().await?;
+    let llm_handle = state.analysis_domain.get_llm_handle();
+    let target = obsidian_config.export_target;
+    let exporter = ObsidianExporter::new(obsidian_config).with_frame_store(
+        frame_store::from_config(&config.frame_store_config.unwrap_or_default()),
+    );
+    let (tx, forwarder) = spawn_obsidian_progress_forwarder(app.clone());
+    let force_refresh = force_refresh.unwrap_or(false);
+    let result = if to_archive {
+        exporter
+            .export_to_archive(
+                db.clone(),
+                llm_handle.clone(),
+                &date,
+                &date,
+                force_refresh,
+                tx,
+            )
+            .await
+    } else {
+        exporter
+            .export_day_with_events(db.clone(), llm_handle.clone(), &date, force_refresh, tx)
+            .await
+    };
+
+    let request = obsidian::ExportRequest::Day {
+        date,
+        force_refresh,
+    };
+    record_obsidian_export(
+        &db,
+        obsidian::ExportRun::from_result(request, target, &result),
+    )
+    .await;
+    finish_obsidian_export(&app, forwarder, result).await
+}
+
+/// 预览指定日期的 Obsidian 导出内容（不写入 Vault）
+#[tauri::command]
+async fn preview_obsidian_export(
+    state: tauri::State<'_, AppState>,
+    date: String,
+) -> Result<Vec<obsidian::NotePreview>, String> {
+    let config = state.storage_domain.get_settings().get().await;
+    let obsidian_config = config.obsidian_config.unwrap_or_default();
+
+    if !obsidian_config.enabled {
+        return Err("Obsidian 导出未启用，请在设置中开启".to_string());
+    }
+
+    let db = state.storage_domain.get_db().await?;
+    let llm_handle = state.analysis_domain.get_llm_handle();
+    let exporter = ObsidianExporter::new(obsidian_config).with_frame_store(
+        frame_store::from_config(&config.frame_store_config.unwrap_or_default()),
+    );
+    exporter
+        .preview_day(db, llm_handle.clone(), &date)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 批量导出日期范围到 Obsidian（用于回填历史）
+#[tauri::command]
+async fn export_obsidian_range(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+) -> Result<String, String> {
+    let config = state.storage_domain.get_settings().get().await;
+    let obsidian_config = config.obsidian_config.unwrap_or_default();
+
+    if !obsidian_config.enabled {
+        return Err("Obsidian 导出未启用，请在设置中开启".to_string());
+    }
+
+    let to_archive = obsidian_config.export_target == ObsidianExportTarget::Zip;
+    if !to_archive && obsidian_config.vault_path.trim().is_empty() {
+        return Err("请先配置 Obsidian Vault 路径".to_string());
+    }
+
+    let db = state.storage_domain.get_db().await?;
+    let llm_handle = state.analysis_domain.get_llm_handle();
+    let target = obsidian_config.export_target;
+    let exporter = ObsidianExporter::new(obsidian_config).with_frame_store(
+        frame_store::from_config(&config.frame_store_config.unwrap_or_default()),
+    );
+    let (tx, forwarder) = spawn_obsidian_progress_forwarder(app.clone());
+    let result = if to_archive {
+        exporter
+            .export_to_archive(
+                db.clone(),
+                llm_handle.clone(),
+                &start_date,
+                &end_date,
+                false,
+                tx,
+            )
+            .await
+    } else {
+        exporter
+            .export_range_with_events(db.clone(), llm_handle.clone(), &start_date, &end_date, tx)
+            .await
+    };
+
+    let request = obsidian::ExportRequest::Range {
+        start_date,
+        end_date,
+    };
+    record_obsidian_export(
+        &db,
+        obsidian::ExportRun::from_result(request, target, &result),
+    )
+    .await;
+    finish_obsidian_export(&app, forwarder, result).await
+}
+
+/// 导出指定日期所在季度/年度的 Obsidian 回顾笔记
+#[tauri::command]
+async fn export_obsidian_rollup(
+    state: tauri::State<'_, AppState>,
+    period: ObsidianRollupPeriod,
+    date: String,
+) -> Result<String, String> {
+    let config = state.storage_domain.get_settings().get().await;
+    let obsidian_config = config.obsidian_config.unwrap_or_default();
+
+    if !obsidian_config.enabled {
+        return Err("Obsidian 导出未启用，请在设置中开启".to_string());
+    }
+
+    let db = state.storage_domain.get_db().await?;
+    let exporter = ObsidianExporter::new(obsidian_config);
+    let root = exporter.resolve_root().map_err(|e| e.to_string())?;
+    let (request, result) = match period {
+        ObsidianRollupPeriod::Quarter => (
+            obsidian::ExportRequest::Quarter { date: date.clone() },
+            exporter.export_quarterly_note(&db, &date, &root).await,
+        ),
+        ObsidianRollupPeriod::Year => (
+            obsidian::ExportRequest::Year { date: date.clone() },
+            exporter.export_yearly_note(&db, &date, &root).await,
+        ),
+    };
+    let result = result.map(|path| (format!("已导出回顾笔记: {}", path.to_string_lossy()), path));
+
+    let run = obsidian::ExportRun {
+        request,
+        target: ObsidianExportTarget::Vault,
+        success: result.is_ok(),
+        message: match &result {
+            Ok((message, _)) => message.clone(),
+            Err(e) => e.to_string(),
+        },
+        written_paths: result.iter().map(|(_, path)| path.clone()).collect(),
+        warnings: Vec::new(),
+        failed_sessions: Vec::new(),
+    };
+    record_obsidian_export(&db, run).await;
+
+    result
+        .map(|(message, _)| message)
+        .map_err(|e| e.to_string())
+}
+
+/// 仅导出选中的会话笔记（含截图），不重新生成每日总结与索引
+#[tauri::command]
+async fn export_obsidian_sessions(
+    state: tauri::State<'_, AppState>,
+    session_ids: Vec<i64>,
+) -> Result<String, String> {
+    if session_ids.is_empty() {
+        return Err("请至少选择一个会话".to_string());
+    }
+    for session_id in &session_ids {
+        validate_session_id(*session_id)?;
+    }
+
+    let config = state.storage_domain.get_settings().get().await;
+    let obsidian_config = config.obsidian_config.unwrap_or_default();
+
+    if !obsidian_config.enabled {
+        return Err("Obsidian 导出未启用，请在设置中开启".to_string());
+    }
+
+    if obsidian_config.vault_path.trim().is_empty() {
+        return Err("请先配置 Obsidian Vault 路径".to_string());
+    }
+
+    let db = state.storage_domain.get_db().await?;
+    let exporter = ObsidianExporter::new(obsidian_config).with_frame_store(
+        frame_store::from_config(&config.frame_store_config.unwrap_or_default()),
+    );
+    let root = exporter.resolve_root().map_err(|e| e.to_string())?;
+    let result = exporter
+        .export_sessions(db.clone(), &root, &session_ids)
+        .await;
+
+    let request = obsidian::ExportRequest::Sessions { session_ids };
+    let run = obsidian::ExportRun::from_result(request, ObsidianExportTarget::Vault, &result);
+    record_obsidian_export(&db, run).await;
+    result
+        .map(|outcome| outcome.render_message())
+        .map_err(|e| e.to_string())
+}
+
+/// 仅重新导出此前导出失败的 Obsidian 会话
+#[tauri::command]
+async fn retry_obsidian_sessions(
+    state: tauri::State<'_, AppState>,
+    session_ids: Vec<i64>,
+) -> Result<String, String> {
+    for session_id in &session_ids {
+        validate_session_id(*session_id)?;
+    }
+
+    let config = state.storage_domain.get_settings().get().await;
+    let obsidian_config = config.obsidian_config.unwrap_or_default();
+
+    if !obsidian_config.enabled {
+        return Err("Obsidian 导出未启用，请在设置中开启".to_string());
+    }
+
+    let db = state.storage_domain.get_db().await?;
+    let exporter = ObsidianExporter::new(obsidian_config).with_frame_store(
+        frame_store::from_config(&config.frame_store_config.unwrap_or_default()),
+    );
+    let root = exporter.resolve_root().map_err(|e| e.to_string())?;
+    let result = exporter
+        .retry_failed_sessions(db.clone(), &root, &session_ids)
+        .await;
+
+    let request = obsidian::ExportRequest::Retry { session_ids };
+    let run = obsidian::ExportRun::from_result(request, ObsidianExportTarget::Vault, &result);
+    record_obsidian_export(&db, run).await;
+    result
+        .map(|outcome| outcome.render_message())
+        .map_err(|e| e.to_string())
+}
+
+/// 获取最近的 Obsidian 导出历史（含写入路径与警告）
+#[tauri::command]
+async fn get_obsidian_export_history(
+    state: tauri::State<'_, AppState>,
+    limit: Option<i64>,
+) -> Result<Vec<storage::ExportHistoryRecord>, String> {
+    let db = state.storage_domain.get_db().await?;
+    db.get_export_history(limit.unwrap_or(50).clamp(1, 500))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 重新运行导出历史中失败的导出（部分会话失败时仅重试这些会话）
+#[tauri::command]
+async fn rerun_obsidian_export(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    history_id: i64,
+) -> Result<String, String> {
+    let db = state.storage_domain.get_db().await?;
+    let record = db
+        .get_export_history_entry(history_id)
+        .await
+        .map_err(|e| format!("导出历史不存在: {}", e))?;
+    let request = obsidian::rerun_request(&record).map_err(|e| e.to_string())?;
+
+    match request {
+        obsidian::ExportRequest::Day {
+            date,
+            force_refresh,
+        } => export_obsidian_day(app, state, date, Some(force_refresh)).await,
+        obsidian::ExportRequest::Range {
+            start_date,
+            end_date,
+        } => export_obsidian_range(app, state, start_date, end_date).await,
+        obsidian::ExportRequest::Quarter { date } => {
+            export_obsidian_rollup(state, ObsidianRollupPeriod::Quarter, date).await
+        }
+        obsidian::ExportRequest::Year { date } => {
+            export_obsidian_rollup(state, ObsidianRollupPeriod::Year, date).await
+        }
+        obsidian::ExportRequest::Sessions { session_ids } => {
+            export_obsidian_sessions(state, session_ids).await
+        }
+        obsidian::ExportRequest::Retry { session_ids } => {
+            retry_obsidian_sessions(state, session_ids).await
+        }
+    }
+}
+
+/// 列出全部导出目标（Obsidian / Notion / Logseq）及其可用状态
+#[tauri::command]
+async fn list_exporters(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<exporter::ExporterInfo>, String> {
+    let config = state.storage_domain.get_settings().get().await;
+    Ok(ExporterRegistry::from_config(&config).list())
+}
+
+/// 通过注册表导出到指定目标；end_date 为空时只导出 start_date 当天
+#[tauri::command]
+async fn run_export(
+    state: tauri::State<'_, AppState>,
+    target: String,
+    start_date: String,
+    end_date: Option<String>,
+    force_refresh: Option<bool>,
+) -> Result<String, String> {
+    let config = state.storage_domain.get_settings().get().await;
+    let registry = ExporterRegistry::from_config(&config);
+    let exporter = registry.get(&target).map_err(|e| e.to_string())?;
+
+    let db = state.storage_domain.get_db().await?;
+    let llm_handle = state.analysis_domain.get_llm_handle().clone();
+    let report = match end_date.filter(|end_date| *end_date != start_date) {
+        Some(end_date) => {
+            exporter
+                .export_range(db, llm_handle, &start_date, &end_date)
+                .await
+        }
+        None => {
+            exporter
+                .export_day(db, llm_handle, &start_date, force_refresh.unwrap_or(false))
+                .await
+        }
+    }
+    .map_err(|e| e.to_string())?;
+
+    info!(
+        "{} 导出完成: {} 天, {} 个会话",
+        exporter.target_name(),
+        report.exported_dates.len(),
+        report.session_count
+    );
+    Ok(report.render_message())
+}
+
+/// 预览导出到指定目标的内容（不写入）
+#[tauri::command]
+async fn preview_export(
+    state: tauri::State<'_, AppState>,
+    target: String,
+    date: String,
+) -> Result<Vec<exporter::NotePreview>, String> {
+    let config = state.storage_domain.get_settings().get().await;
+    let registry = ExporterRegistry::from_config(&config);
+    let exporter = registry.get(&target).map_err(|e| e.to_string())?;
+
+    let db = state.storage_domain.get_db().await?;
+    let llm_handle = state.analysis_domain.get_llm_handle().clone();
+    exporter
+        .preview_day(db, llm_handle, &date)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 导出日期范围内的会话、截图元数据与时间线卡片为 CSV
+#[tauri::command]
+async fn export_sessions_csv(
+    state: tauri::State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+    output_dir: String,
+) -> Result<String, String> {
+    export_raw_data(
+        state,
+        start_date,
+        end_date,
+        output_dir,
+        RawExportFormat::Csv,
+    )
+    .await
+}
+
+/// 导出日期范围内的会话、截图元数据与时间线卡片为 Parquet
+#[tauri::command]
+async fn export_timeline_parquet(
+    state: tauri::State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+    output_dir: String,
+) -> Result<String, String> {
+    export_raw_data(
+        state,
+        start_date,
+        end_date,
+        output_dir,
+        RawExportFormat::Parquet,
+    )
+    .await
+}
+
+enum RawExportFormat {
+    Csv,
+    Parquet,
+}
+
+async fn export_raw_data(
+    state: tauri::State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+    output_dir: String,
+    format: RawExportFormat,
+) -> Result<String, String> {
+    if output_dir.trim().is_empty() {
+        return Err("请先选择导出目录".to_string());
+    }
+    let db = state.storage_domain.get_db().await?;
+    let dataset = raw_export::RawDataset::load(&db, &start_date, &end_date)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let output_dir = PathBuf::from(output_dir.trim());
+    let stem = raw_export::export_stem(&start_date, &end_date);
+    let outcome = tokio::task::spawn_blocking(move || match format {
+        RawExportFormat::Csv => dataset.write_csv(&output_dir, &stem),
+        RawExportFormat::Parquet => dataset.write_parquet(&output_dir, &stem),
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+
+    info!(
+        "原始数据导出完成: {} ~ {}, {} 个会话",
+        start_date, end_date, outcome.session_count
+    );
+    Ok(outcome.render_message())
+}
+
+/// 导入 RescueTime 导出的 CSV，按小时回填会话、时间线卡片与每日指标
+#[tauri::command]
+async fn import_rescuetime_csv(
+    state: tauri::State<'_, AppState>,
+    path: String,
+) -> Result<String, String> {
+    let path = PathBuf::from(path.trim());
+    if !path.is_file() {
+        return Err("CSV 文件不存在".to_string());
+    }
+    let db = state.storage_domain.get_db().await?;
+    let report = rescuetime::import_csv(db, &path)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(report.render_message())
+}
+
+/// 导入其他录屏工具采集的帧（路径 + 时间 + 元数据），并入常规会话分析流程
+#[tauri::command]
+async fn ingest_external_frames(
+    state: tauri::State<'_, AppState>,
+    frames: Vec<capture::ingest::ExternalFrame>,
+) -> Result<capture::ingest::IngestReport, String> {
+    capture::ingest::ingest_frames(state.capture_domain.get_capture(), frames)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 从清单文件（JSON 数组或 JSONL）导入外部采集的帧
+#[tauri::command]
+async fn ingest_external_manifest(
+    state: tauri::State<'_, AppState>,
+    path: String,
+) -> Result<String, String> {
+    let path = PathBuf::from(path.trim());
+    if !path.is_file() {
+        return Err("清单文件不存在".to_string());
+    }
+    let report = capture::ingest::ingest_manifest(state.capture_domain.get_capture(), &path)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(report.render_message())
+}
+
+/// 导出日期范围内的每日总结、会话与时间线卡片为带结构版本的 JSON
+#[tauri::command]
+async fn export_day_summaries_json(
+    state: tauri::State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+    output_dir: String,
+) -> Result<String, String> {
+    if output_dir.trim().is_empty() {
+        return Err("请先选择导出目录".to_string());
+    }
+    let db = state.storage_domain.get_db().await?;
+    let llm_handle = state.analysis_domain.get_llm_handle().clone();
+    let document = json_export::build_document(db, llm_handle, &start_date, &end_date)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let stem = raw_export::export_stem(&start_date, &end_date);
+    let path = json_export::write_document(&document, Path::new(output_dir.trim()), &stem)
+        .map_err(|e| e.to_string())?;
+    info!(
+        "JSON 导出完成: {} ~ {}, {} 天",
+        start_date,
+        end_date,
+        document.days.len()
+    );
+    Ok(format!(
+        "已导出 {} 天的总结与会话（结构版本 {}）\n{}",
+        document.days.len(),
+        document.schema_version,
+        path.to_string_lossy()
+    ))
+}
+
+/// 将日期范围内学习类会话的总结提炼为问答卡片，导出为 Anki 可导入的 TSV 文本
+#[tauri::command]
+async fn export_learning_flashcards(
+    state: tauri::State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+    output_dir: String,
+) -> Result<String, String> {
+    if output_dir.trim().is_empty() {
+        return Err("请先选择导出目录".to_string());
+    }
+    let db = state.storage_domain.get_db().await?;
+    let llm_handle = state.analysis_domain.get_llm_handle().clone();
+    let (notes, session_count, warnings) =
+        anki::collect_notes(&db, &llm_handle, &start_date, &end_date)
+            .await
+            .map_err(|e| e.to_string())?;
+    if session_count == 0 {
+        return Err("所选日期范围内没有学习类会话".to_string());
+    }
+
+    let stem = raw_export::export_stem(&start_date, &end_date);
+    let path =
+        anki::write_tsv(&notes, Path::new(output_dir.trim()), &stem).map_err(|e| e.to_string())?;
+    info!(
+        "Anki 导出完成: {} ~ {}, {} 个学习会话, {} 张卡片",
+        start_date,
+        end_date,
+        session_count,
+        notes.len()
+    );
+    Ok(anki::AnkiExportOutcome {
+        path,
+        session_count,
+        card_count: notes.len(),
+        warnings,
+    }
+    .render_message())
+}
+
+/// 重新分类日期范围内的时间线卡片（修改分类规则或切换 LLM 提供商后使用），
+/// 更新卡片与会话标签，并清除相关日期的每日总结与专注度指标缓存
+#[tauri::command]
+async fn recategorize_timeline_cards(
+    state: tauri::State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+) -> Result<String, String> {
+    let db = state.storage_domain.get_db().await?;
+    let llm_handle = state.analysis_domain.get_llm_handle().clone();
+    let report = recategorize::recategorize_range(&db, &llm_handle, &start_date, &end_date)
+        .await
+        .map_err(|e| e.to_string())?;
+    if report.card_count == 0 {
+        return Err("所选日期范围内没有可重新分类的时间线卡片".to_string());
+    }
+    Ok(report.render_message())
+}
+
+/// 立即发送邮件摘要（选中日期的日报或其所在周的周报），用于验证 SMTP 配置
+#[tauri::command]
+async fn send_digest_email(
+    state: tauri::State<'_, AppState>,
+    kind: digest::DigestKind,
+    date: String,
+) -> Result<String, String> {
+    let app_config = state.storage_domain.get_settings().get().await;
+    let digest_config = app_config.digest_config.clone().unwrap_or_default();
+    let mailer = digest::DigestMailer::new(&digest_config).map_err(|e| e.to_string())?;
+    let db = state.storage_domain.get_db().await?;
+    let email = digest::build_digest(
+        kind,
+        db,
+        state.analysis_domain.get_llm_handle().clone(),
+        &app_config,
+        &date,
+    )
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| "所选日期没有会话，未发送".to_string())?;
+    mailer.send(&email).await.map_err(|e| e.to_string())?;
+    info!("邮件摘要已发送: {}", email.subject);
+    Ok(format!("已发送：{}", email.subject))
+}
+
+/// 立即推送选中日期的每日总结到 Slack / Discord（指定序号时只推送该工作区，用于验证配置）
+#[tauri::command]
+async fn post_chat_summary(
+    state: tauri::State<'_, AppState>,
+    date: String,
+    workspace_index: Option<usize>,
+) -> Result<String, String> {
+    let chat_config = state
+        .storage_domain
+        .get_settings()
+        .get()
+        .await
+        .chat_config
+        .unwrap_or_default();
+    let workspaces: Vec<_> = chat_config
+        .workspaces
+        .into_iter()
+        .enumerate()
+        .filter(|(index, workspace)| match workspace_index {
+            Some(target) => *index == target,
+            None => workspace.enabled,
+        })
+        .map(|(index, workspace)| (chat_summary::workspace_label(&workspace, index), workspace))
+        .collect();
+    if workspaces.is_empty() {
+        return Err("没有可推送的工作区".to_string());
+    }
+
+    let db = state.storage_domain.get_db().await?;
+    let report = chat_summary::post_daily_summary(
+        db,
+        state.analysis_domain.get_llm_handle().clone(),
+        &workspaces,
+        &date,
+    )
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| "所选日期没有会话，未推送".to_string())?;
+    if report.posted.is_empty() {
+        return Err(report.render_message());
+    }
+    Ok(report.render_message())
+}
+
+/// 连接 Google 日历：在系统浏览器中完成只读授权并保存刷新令牌，返回更新后的配置
+#[tauri::command]
+async fn connect_google_calendar(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<models::GoogleCalendarConfig, String> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let settings = state.storage_domain.get_settings();
+    let mut config = settings.get().await.calendar_config.unwrap_or_default();
+    config.refresh_token = calendar::authorize(&config, |url| {
+        app.opener()
+            .open_url(url, None::<&str>)
+            .map_err(|e| anyhow::anyhow!("打开浏览器失败: {}", e))
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    config.enabled = true;
+
+    settings
+        .update(AppConfig {
+            calendar_config: Some(config.clone()),
+            ..Default::default()
+        })
+        .await
+        .map_err(|e| format!("保存配置失败: {}", e))?;
+    info!("Google 日历已连接");
+    Ok(config)
+}
+
+/// 断开 Google 日历：撤销授权并清除刷新令牌
+#[tauri::command]
+async fn disconnect_google_calendar(
+    state: tauri::State<'_, AppState>,
+) -> Result<models::GoogleCalendarConfig, String> {
+    let settings = state.storage_domain.get_settings();
+    let mut config = settings.get().await.calendar_config.unwrap_or_default();
+    if !config.refresh_token.is_empty() {
+        if let Err(e) = calendar::revoke(&config.refresh_token).await {
+            warn!("撤销 Google 日历授权失败: {}", e);
+        }
+    }
+    config.refresh_token.clear();
+    config.enabled = false;
+
+    settings
+        .update(AppConfig {
+            calendar_config: Some(config.clone()),
+            ..Default::default()
+        })
+        .await
+        .map_err(|e| format!("保存配置失败: {}", e))?;
+    Ok(config)
+}
+
+/// 立即同步指定日期的会议，并用会议标题标注时间线卡片
+#[tauri::command]
+async fn sync_google_calendar(
+    state: tauri::State<'_, AppState>,
+    date: String,
+) -> Result<String, String> {
+    let config = state
+        .storage_domain
+        .get_settings()
+        .get()
+        .await
+        .calendar_config
+        .unwrap_or_default();
+    let db = state.storage_domain.get_db().await?;
+    let (meetings, labeled) = calendar::sync_day(&db, &config, &date)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(format!(
+        "已同步 {} 个会议，标注 {} 张时间线卡片",
+        meetings, labeled
+    ))
+}
+
+/// 重新识别并解析会话关联的 GitHub / Jira 工单
+#[tauri::command]
+async fn link_session_issues(
+    state: tauri::State<'_, AppState>,
+    session_id: i64,
+) -> Result<Vec<storage::IssueLinkRecord>, String> {
+    let config = state
+        .storage_domain
+        .get_settings()
+        .get()
+        .await
+        .issue_link_config
+        .unwrap_or_default();
+    let db = state.storage_domain.get_db().await?;
+    issue_links::link_session(&db, &config, session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取会话关联的工单
+#[tauri::command]
+async fn get_session_issue_links(
+    state: tauri::State<'_, AppState>,
+    session_id: i64,
+) -> Result<Vec<storage::IssueLinkRecord>, String> {
+    let db = state.storage_domain.get_db().await?;
+    db.get_issue_links_by_session(session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取会话内各前台应用的使用时长
+#[tauri::command]
+async fn get_session_app_usage(
+    state: tauri::State<'_, AppState>,
+    session_id: i64,
+) -> Result<Vec<storage::AppUsageRecord>, String> {
+    let db = state.storage_domain.get_db().await?;
+    db.get_app_usage_by_session(session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 按应用汇总日期范围内的使用时长（日期为 YYYY-MM-DD，含首尾）
+#[tauri::command]
+async fn get_app_usage_summary(
+    state: tauri::State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<storage::AppUsageSummary>, String> {
+    let db = state.storage_domain.get_db().await?;
+    db.get_app_usage_summary(&start_date, &end_date)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取会话内各网站域名的访问时长
+#[tauri::command]
+async fn get_session_domain_usage(
+    state: tauri::State<'_, AppState>,
+    session_id: i64,
+) -> Result<Vec<storage::DomainUsageRecord>, String> {
+    let db = state.storage_domain.get_db().await?;
+    db.get_domain_usage_by_session(session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 按域名汇总日期范围内的访问时长（日期为 YYYY-MM-DD，含首尾）
+#[tauri::command]
+async fn get_domain_usage_summary(
+    state: tauri::State<'_, AppState>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<storage::DomainUsageSummary>, String> {
+    let db = state.storage_domain.get_db().await?;
+    db.get_domain_usage_summary(&start_date, &end_date)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取项目归类规则（按创建顺序，先匹配者优先）
+#[tauri::command]
+async fn get_project_rules(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<storage::ProjectRuleRecord>, String> {
+    let db = state.storage_domain.get_db().await?;
+    db.get_project_rules().await.map_err(|e| e.to_string())
+}
+
+/// 添加项目归类规则（match_field: tag/app/title/process）
+#[tauri::command]
+async fn add_project_rule(
+    state: tauri::State<'_, AppState>,
+    project: String,
+    match_field: String,
+    pattern: String,
+) -> Result<i64, String> {
+    let project = project.trim();
+    let pattern = pattern.trim();
+    if project.is_empty() || pattern.is_empty() {
+        return Err("项目名称与关键词不能为空".to_string());
+    }
+    if !domains::summary::PROJECT_MATCH_FIELDS.contains(&match_field.as_str()) {
+        return Err(format!("不支持的匹配字段: {}", match_field));
+    }
+
+    let db = state.storage_domain.get_db().await?;
+    db.insert_project_rule(&storage::ProjectRuleRecord {
+        id: None,
+        project: project.to_string(),
+        match_field,
+        pattern: pattern.to_string(),
+        created_at: storage::local_now(),
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// 删除项目归类规则
+#[tauri::command]
+async fn delete_project_rule(state: tauri::State<'_, AppState>, id: i64) -> Result<(), String> {
+    let db = state.storage_domain.get_db().await?;
+    db.delete_project_rule(id).await.map_err(|e| e.to_string())
+}
+
+/// 获取自定义类别
+#[tauri::command]
+async fn get_custom_categories(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<storage::CustomCategoryRecord>, String> {
+    let db = state.storage_domain.get_db().await?;
+    db.get_custom_categories().await.map_err(|e| e.to_string())
+}
+
+/// 添加自定义类别（bucket 为归入的内置类别：work/communication/learning/personal/idle/other）
+#[tauri::command]
+async fn add_custom_category(
+    state: tauri::State<'_, AppState>,
+    name: String,
+    bucket: String,
+    description: String,
+    subcategories: Vec<String>,
+) -> Result<i64, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("类别名称不能为空".to_string());
+    }
+    if llm::plugin::CARD_CATEGORIES
+        .iter()
+        .any(|builtin| builtin.eq_ignore_ascii_case(name))
+    {
+        return Err(format!("{} 是内置类别", name));
+    }
+    if !llm::plugin::CARD_CATEGORIES.contains(&bucket.as_str()) {
+        return Err(format!("不支持的内置类别: {}", bucket));
+    }
+    let mut subcategory_list: Vec<String> = Vec::new();
+    for subcategory in subcategories {
+        let subcategory = subcategory.trim();
+        if !subcategory.is_empty() && !subcategory_list.iter().any(|item| item == subcategory) {
+            subcategory_list.push(subcategory.to_string());
+        }
+    }
+
+    let db = state.storage_domain.get_db().await?;
+    let id = db
+        .insert_custom_category(&storage::CustomCategoryRecord {
+            id: None,
+            name: name.to_string(),
+            bucket,
+            description: description.trim().to_string(),
+            subcategories: serde_json::to_string(&subcategory_list).map_err(|e| e.to_string())?,
+            created_at: storage::local_now(),
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+    taxonomy::reload(&db).await.map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+/// 删除自定义类别（已归入该类别的卡片保留名称，统计时按"其他"处理，可重新分类）
+#[tauri::command]
+async fn delete_custom_category(state: tauri::State<'_, AppState>, id: i64) -> Result<(), String> {
+    let db = state.storage_domain.get_db().await?;
+    db.delete_custom_category(id)
+        .await
+        .map_err(|e| e.to_string())?;
+    taxonomy::reload(&db).await.map_err(|e| e.to_string())
+}
+
+/// 获取预分类规则（按创建顺序，先匹配者优先）
+#[tauri::command]
+async fn get_classification_rules(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<storage::ClassificationRuleRecord>, String> {
+    let db = state.storage_domain.get_db().await?;
+    db.get_classification_rules()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 添加预分类规则（match_field: app/title/url，pattern 为忽略大小写的正则表达式）
+#[tauri::command]
+async fn add_classification_rule(
+    state: tauri::State<'_, AppState>,
+    match_field: String,
+    pattern: String,
+    category: String,
+    subcategory: String,
+) -> Result<i64, String> {
+    if !capture::rules::RULE_MATCH_FIELDS.contains(&match_field.as_str()) {
+        return Err(format!("不支持的匹配字段: {}", match_field));
+    }
+    let record = storage::ClassificationRuleRecord {
+        id: None,
+        match_field,
+        pattern: pattern.trim().to_string(),
+        category: llm::plugin::normalize_card_category(&category, &taxonomy::custom_categories())
+            .ok_or_else(|| format!("未知类别: {}", category))?,
+        subcategory: subcategory.trim().to_string(),
+        created_at: storage::local_now(),
+    };
+    if record.pattern.is_empty() {
+        return Err("正则表达式不能为空".to_string());
+    }
+    capture::rules::ClassificationRule::new(&record)
+        .map_err(|e| format!("正则表达式无效: {}", e))?;
+
+    let db = state.storage_domain.get_db().await?;
+    db.insert_classification_rule(&record)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 删除预分类规则
+#[tauri::command]
+async fn delete_classification_rule(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+) -> Result<(), String> {
+    let db = state.storage_domain.get_db().await?;
+    db.delete_classification_rule(id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取全部目标
+#[tauri::command]
+async fn get_goals(state: tauri::State<'_, AppState>) -> Result<Vec<storage::GoalRecord>, String> {
+    let db = state.storage_domain.get_db().await?;
+    db.get_goals().await.map_err(|e| e.to_string())
+}
+
+/// 添加或更新目标（id 为空时新增），返回目标ID；更新后该目标的历史评估结果会重新计算
+#[tauri::command]
+async fn save_goal(
+    state: tauri::State<'_, AppState>,
+    goal: goals::GoalInput,
+) -> Result<i64, String> {
+    let goal = goals::normalize_goal(goal.into_record()).map_err(|e| e.to_string())?;
+    let db = state.storage_domain.get_db().await?;
+    match goal.id {
+        Some(id) => {
+            db.update_goal(&goal).await.map_err(|e| e.to_string())?;
+            Ok(id)
+        }
+        None => db.insert_goal(&goal).await.map_err(|e| e.to_string()),
+    }
+}
+
+/// 删除目标及其评估结果
+#[tauri::command]
+async fn delete_goal(state: tauri::State<'_, AppState>, id: i64) -> Result<(), String> {
+    let db = state.storage_domain.get_db().await?;
+    db.delete_goal(id).await.map_err(|e| e.to_string())
+}
+
+/// 获取启用目标在指定日期（默认今天）的达成情况与连续达成天数
+#[tauri::command]
+async fn get_goal_progress(
+    state: tauri::State<'_, AppState>,
+    date: Option<String>,
+) -> Result<Vec<goals::GoalStatus>, String> {
+    let date = match date {
+        Some(date) => chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|e| format!("日期格式错误: {}", e))?,
+        None => storage::local_now().date_naive(),
+    };
+    let db = state.storage_domain.get_db().await?;
+    goals::day_goals(&db, date).await.map_err(|e| e.to_string())
+}
+
+/// 开始专注时段（番茄钟），计时期间截取的帧标记为该时段，到时自动结束
+#[tauri::command]
+async fn start_focus_block(
+    state: tauri::State<'_, AppState>,
+    minutes: i64,
+    label: Option<String>,
+) -> Result<capture::focus::FocusStatus, String> {
+    if !(1..=capture::focus::MAX_FOCUS_MINUTES).contains(&minutes) {
+        return Err(format!(
+            "专注时长需在 1-{} 分钟之间",
+            capture::focus::MAX_FOCUS_MINUTES
+        ));
+    }
+    let timer = state.capture_domain.get_capture().focus_timer();
+    if timer.status(storage::local_now()).is_some() {
+        return Err("已有进行中的专注时段".to_string());
+    }
+
+    let config = state
+        .storage_domain
+        .get_settings()
+        .get()
+        .await
+        .focus_config
+        .unwrap_or_default();
+    let db = state.storage_domain.get_db().await?;
+    let mut record = capture::focus::new_block(
+        label.as_deref().unwrap_or_default(),
+        minutes,
+        storage::local_now(),
+    );
+    record.id = Some(
+        db.insert_focus_block(&record)
+            .await
+            .map_err(|e| format!("保存专注时段失败: {}", e))?,
+    );
+
+    if let Err(e) = timer.start(record.clone(), config) {
+        record.status = "cancelled".to_string();
+        record.ended_at = Some(record.started_at);
+        let _ = db.update_focus_block(&record).await;
+        return Err(e.to_string());
+    }
+    timer
+        .status(storage::local_now())
+        .ok_or_else(|| "专注时段已结束".to_string())
+}
+
+/// 提前停止当前专注时段（结束记录由专注事件转发任务写回数据库）
+#[tauri::command]
+async fn stop_focus_block(
+    state: tauri::State<'_, AppState>,
+) -> Result<Option<storage::FocusBlockRecord>, String> {
+    Ok(state.capture_domain.get_capture().focus_timer().stop())
+}
+
+/// 获取当前专注时段状态（无进行中的时段时为空）
+#[tauri::command]
+async fn get_focus_status(
+    state: tauri::State<'_, AppState>,
+) -> Result<Option<capture::focus::FocusStatus>, String> {
+    Ok(state
+        .capture_domain
+        .get_capture()
+        .focus_timer()
+        .status(storage::local_now()))
+}
+
+/// 获取某天（默认今天）的专注时段
+#[tauri::command]
+async fn get_focus_blocks(
+    state: tauri::State<'_, AppState>,
+    date: Option<String>,
+) -> Result<Vec<storage::FocusBlockRecord>, String> {
+    let date = match date {
+        Some(date) => chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|e| format!("日期格式错误: {}", e))?,
+        None => storage::local_now().date_naive(),
+    };
+    let start = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let db = state.storage_domain.get_db().await?;
+    db.get_focus_blocks(start, start + chrono::Duration::days(1))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取每日专注趋势（默认最近 30 天）：专注占比、投入时长与生产力评分，含 7/30 日移动平均与百分位
+#[tauri::command]
+async fn get_focus_trend(
+    state: tauri::State<'_, AppState>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<trends::TrendSeries, String> {
+    let parse = |date: &str| {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|e| format!("日期格式错误: {}", e))
+    };
+    let end = match end_date {
+        Some(date) => parse(&date)?,
+        None => storage::local_now().date_naive(),
+    };
+    let start = match start_date {
+        Some(date) => parse(&date)?,
+        None => end - chrono::Duration::days(29),
+    };
+    let config = state.storage_domain.get_settings().get().await;
+    let weights = trends::ScoreWeights::from_config(&config.obsidian_config.unwrap_or_default());
+    let db = state.storage_domain.get_db().await?;
+    trends::focus_trend(&db, start, end, weights)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 立即把本机最近的会话推送到局域网主设备（仅从设备模式）
+#[tauri::command]
+async fn lan_sync_now(state: tauri::State<'_, AppState>) -> Result<lan_sync::SyncResult, String> {
+    let config = state
+        .storage_domain
+        .get_settings()
+        .get()
+        .await
+        .lan_sync_config
+        .unwrap_or_default();
+    let db = state.storage_domain.get_db().await?;
+    lan_sync::push_recent(&db, &config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 立即执行一次加密云备份（使用已保存的配置，未启用定时备份时也可执行）
+#[tauri::command]
+async fn run_cloud_backup(
+    state: tauri::State<'_, AppState>,
+) -> Result<backup::BackupEntry, String> {
+    let settings = state.storage_domain.get_settings();
+    let config = settings.get().await.backup_config.unwrap_or_default();
+    let db = state.storage_domain.get_db().await?;
+    let frames_dir = state.capture_domain.get_capture().frames_dir();
+    backup::run_backup(&db, &config, settings.path(), &frames_dir)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 列出远端的云备份（从新到旧）
+#[tauri::command]
+async fn list_cloud_backups(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<backup::BackupEntry>, String> {
+    let config = state
+        .storage_domain
+        .get_settings()
+        .get()
+        .await
+        .backup_config
+        .unwrap_or_default();
+    backup::list_backups(&config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 下载并解密指定云备份，重启应用后替换当前数据
+#[tauri::command]
+async fn restore_cloud_backup(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    name: String,
+    passphrase: String,
+) -> Result<(), String> {
+    let config = state
+        .storage_domain
+        .get_settings()
+        .get()
+        .await
+        .backup_config
+        .unwrap_or_default();
+    let app_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用目录失败: {}", e))?;
+    backup::stage_restore(&config, &name, &passphrase, &app_dir)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 备份数据库到指定文件（SQLite 为数据库文件，MariaDB / PostgreSQL 为 SQL 转储）；目录路径时自动生成文件名
+#[tauri::command]
+async fn backup_database(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    path: String,
+) -> Result<backup::DatabaseBackupInfo, String> {
+    if path.trim().is_empty() {
+        return Err("请填写备份路径".to_string());
+    }
+    let db = state.storage_domain.get_db().await?;
+    let mut target = resolve_config_path(&app, &path)?;
+    if target.is_dir() || target.extension().is_none() {
+        target = target.join(format!(
+            "screen-analyzer-{}.{}",
+            storage::local_now().format("%Y%m%d-%H%M%S"),
+            backup::database_backup_extension(&db)
+        ));
+    }
+    backup::backup_database(&db, &target)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 从备份文件恢复数据库（校验完整性并迁移表结构；SQLite 重启后生效）
+#[tauri::command]
+async fn restore_database(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    path: String,
+) -> Result<backup::DatabaseRestoreOutcome, String> {
+    if path.trim().is_empty() {
+        return Err("请填写备份文件路径".to_string());
+    }
+    let db = state.storage_domain.get_db().await?;
+    let source = resolve_config_path(&app, &path)?;
+    let app_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用目录失败: {}", e))?;
+    backup::restore_database(&db, &source, &app_dir)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取出现过的设备名称（用于按设备筛选）
+#[tauri::command]
+async fn get_device_names(state: tauri::State<'_, AppState>) -> Result<Vec<String>, String> {
+    let db = state.storage_domain.get_db().await?;
+    db.get_device_names().await.map_err(|e| e.to_string())
+}
+
+/// 获取日期范围内（默认最近 7 天）各设备的专注度指标
+#[tauri::command]
+async fn get_device_focus_stats(
+    state: tauri::State<'_, AppState>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<Vec<domains::summary::DeviceFocusStat>, String> {
+    let parse = |date: &str| {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|e| format!("日期格式错误: {}", e))
+    };
+    let end = match end_date {
+        Some(date) => parse(&date)?,
+        None => storage::local_now().date_naive(),
+    };
+    let start = match start_date {
+        Some(date) => parse(&date)?,
+        None => end - chrono::Duration::days(6),
+    };
+    if end < start {
+        return Err("结束日期早于开始日期".to_string());
+    }
+    if (end - start).num_days() >= 366 {
+        return Err("设备统计最多查询 366 天".to_string());
+    }
+    let db = state.storage_domain.get_db().await?;
+    domains::summary::device_focus_stats(&db, start, end)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取日期范围内的应用使用排行（默认最近 7 天），附各应用每天的时长与是否达到上限
+#[tauri::command]
+async fn get_app_leaderboard(
+    state: tauri::State<'_, AppState>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<app_usage::AppLeaderboardEntry>, String> {
+    let parse = |date: &str| {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|e| format!("日期格式错误: {}", e))
+    };
+    let end = match end_date {
+        Some(date) => parse(&date)?,
+        None => storage::local_now().date_naive(),
+    };
+    let start = match start_date {
+        Some(date) => parse(&date)?,
+        None => end - chrono::Duration::days(6),
+    };
+    let config = state.storage_domain.get_settings().get().await;
+    let limits = config.app_limit_config.unwrap_or_default().limits;
+    let db = state.storage_domain.get_db().await?;
+    app_usage::app_leaderboard(
+        &db,
+        start,
+        end,
+        &limits,
+        limit.unwrap_or(app_usage::DEFAULT_LEADERBOARD_SIZE),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// 获取专注热力图（星期 × 小时的专注分钟数，默认最近 4 周）
+#[tauri::command]
+async fn get_focus_heatmap(
+    state: tauri::State<'_, AppState>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<heatmap::FocusHeatmap, String> {
+    let parse = |date: &str| {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|e| format!("日期格式错误: {}", e))
+    };
+    let end = match end_date {
+        Some(date) => parse(&date)?,
+        None => storage::local_now().date_naive(),
+    };
+    let start = match start_date {
+        Some(date) => parse(&date)?,
+        None => end - chrono::Duration::days(27),
+    };
+    let db = state.storage_domain.get_db().await?;
+    heatmap::focus_heatmap(&db, start, end)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 通过 app-notification 事件向前端推送通知（窗口不在前台时由前端转为系统通知）
+fn emit_notification(
+    app_handle: &tauri::AppHandle,
+    title: &str,
+    message: String,
+    notification_type: NotificationType,
+) {
+    use tauri::Emitter;
+
+    let notification = Notification {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: title.to_string(),
+        message,
+        notification_type,
+        timestamp: storage::local_now(),
+        actions: Vec::new(),
+    };
+    let _ = app_handle.emit("app-notification", notification);
+}
+
+/// 转发专注计时器事件：时段结束时写回数据库，干扰提醒与完成通知推送到前端
+async fn forward_focus_events(
+    app_handle: tauri::AppHandle,
+    storage_domain: Arc<StorageDomain>,
+    mut events: tokio::sync::broadcast::Receiver<capture::focus::FocusEvent>,
+) {
+    use capture::focus::FocusEvent;
+
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("专注时段事件积压，跳过 {} 条", skipped);
+                continue;
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+        };
+
+        let (title, message, notification_type) = match event {
+            FocusEvent::Distraction {
+                label, distraction, ..
+            } => {
+                let target = distraction.app.unwrap_or(distraction.category);
+                let message = if label.is_empty() {
+                    format!("专注时段中切换到了 {}", target)
+                } else {
+                    format!("正在专注「{}」，切换到了 {}", label, target)
+                };
+                ("专注提醒", message, NotificationType::Warning)
+            }
+            FocusEvent::Ended(record) => {
+                match storage_domain.get_db().await {
+                    Ok(db) => {
+                        if let Err(e) = db.update_focus_block(&record).await {
+                            warn!("保存专注时段结果失败: {}", e);
+                        }
+                    }
+                    Err(e) => warn!("保存专注时段结果失败: {}", e),
+                }
+                if record.status != "completed" {
+                    continue;
+                }
+                (
+                    "专注时段完成",
+                    format!(
+                        "{} 分钟专注已完成，期间干扰 {} 次",
+                        record.planned_minutes, record.distraction_count
+                    ),
+                    NotificationType::Success,
+                )
+            }
+        };
+
+        emit_notification(&app_handle, title, message, notification_type);
+    }
+}
+
+/// 转发分心提醒到前端
+async fn forward_distraction_alerts(
+    app_handle: tauri::AppHandle,
+    mut alerts: tokio::sync::broadcast::Receiver<capture::distraction::DistractionAlert>,
+) {
+    loop {
+        let alert = match alerts.recv().await {
+            Ok(alert) => alert,
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("分心提醒事件积压，跳过 {} 条", skipped);
+                continue;
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+        };
+
+        let mut message = format!(
+            "最近 {} 分钟里有 {} 分钟在干扰类活动上",
+            alert.window_minutes, alert.distraction_minutes
+        );
+        if !alert.apps.is_empty() {
+            message.push_str(&format!("（{}）", alert.apps.join("、")));
+        }
+        emit_notification(&app_handle, "分心提醒", message, NotificationType::Warning);
+    }
+}
+
+/// 转发应用达到每日时长上限的提醒到前端
+async fn forward_app_limit_alerts(
+    app_handle: tauri::AppHandle,
+    mut alerts: tokio::sync::broadcast::Receiver<capture::app_limit::AppLimitAlert>,
+) {
+    loop {
+        let alert = match alerts.recv().await {
+            Ok(alert) => alert,
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("应用超时提醒事件积压，跳过 {} 条", skipped);
+                continue;
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+        };
+
+        let message = format!(
+            "{} 今天已使用 {} 分钟，已达到设定的 {} 分钟上限",
+            alert.app_name, alert.used_minutes, alert.limit_minutes
+        );
+        emit_notification(
+            &app_handle,
+            "应用使用超时",
+            message,
+            NotificationType::Warning,
+        );
+    }
+}
+
+/// 每天检查一次前一天出现的过劳迹象并推送通知（已检查的日期记录在 marker 文件中，重启后不重复提醒）
+async fn run_wellbeing_checks(
+    app_handle: tauri::AppHandle,
+    storage_domain: Arc<StorageDomain>,
+    marker: PathBuf,
+) {
+    loop {
+        let yesterday = storage::local_now().date_naive() - chrono::Duration::days(1);
+        let checked = tokio::fs::read_to_string(&marker)
+            .await
+            .ok()
+            .and_then(|text| chrono::NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d").ok());
+        if checked.is_none_or(|checked| checked < yesterday) {
+            let config = storage_domain
+                .get_settings()
+                .get()
+                .await
+                .wellbeing_config
+                .unwrap_or_default();
+            let result = if config.notify {
+                match storage_domain.get_db().await {
+                    Ok(db) => wellbeing::wellbeing_report(&db, yesterday, &config)
+                        .await
+                        .map(Some)
+                        .map_err(|e| e.to_string()),
+                    Err(e) => Err(e),
+                }
+            } else {
+                Ok(None)
+            };
+
+            match result {
+                Ok(report) => {
+                    let lines: Vec<String> = report
+                        .iter()
+                        .flat_map(|report| &report.warnings)
+                        .filter(|warning| warning.concerns(yesterday))
+                        .map(|warning| warning.describe())
+                        .collect();
+                    if !lines.is_empty() {
+                        emit_notification(
+                            &app_handle,
+                            "注意休息",
+                            lines.join("；"),
+                            NotificationType::Warning,
+                        );
+                    }
+                    let date = yesterday.format("%Y-%m-%d").to_string();
+                    if let Err(e) = tokio::fs::write(&marker, date).await {
+                        warn!("记录过劳迹象检查日期失败: {}", e);
+                    }
+                }
+                Err(e) => warn!("检查过劳迹象失败: {}", e),
+            }
+        }
+        tokio::time::sleep(tokio::time::Duration::from_secs(30 * 60)).await;
+    }
+}
+
+/// 检查 Obsidian 导出目录中的失效链接与缺失附件，repair 为 true 时替换为占位文本
 #[tauri::command]
-async fn export_obsidian_day(
+async fn obsidian_audit_vault(
     state: tauri::State<'_, AppState>,
-    date: String,
-    force_refresh: Option<bool>,
-) -> Result<String, String> {
+    repair: Option<bool>,
+) -> Result<obsidian::VaultAuditReport, String> {
     let config = state.storage_domain.get_settings().get().await;
     let obsidian_config = config.obsidian_config.unwrap_or_default();
 
@@ -166,19 +2065,11 @@ async fn export_obsidian_day(
         return Err("Obsidian 导出未启用，请在设置中开启".to_string());
     }
 
-    if obsidian_config.vault_path.trim().is_empty() {
-        return Err("请先配置 Obsidian Vault 路径".to_string());
-    }
-
-    let db = state.storage_domain.get_db().await?;
-    let llm_handle = state.analysis_domain.get_llm_handle();
     let exporter = ObsidianExporter::new(obsidian_config);
-    let result = exporter
-        .export_day(db, llm_handle.clone(), &date, force_refresh.unwrap_or(false))
+    exporter
+        .audit_vault(repair.unwrap_or(false))
         .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(result.render_message())
+        .map_err(|e| e.to_string())
 }
 
 #[derive(Debug, Serialize)]
@@ -244,38 +2135,43 @@ async fn get_obsidian_preview(
 
     let week = day.iso_week();
     let week_label = format!("{:04}-W{:02}", week.year(), week.week());
+    let week_start =
+        chrono::NaiveDate::from_isoywd_opt(week.year(), week.week(), chrono::Weekday::Mon)
+            .unwrap_or(day)
+            .format("%Y-%m-%d")
+            .to_string();
     let month_label = format!("{:04}-{:02}", day.year(), day.month());
     let day_label = day.format("%Y-%m-%d").to_string();
 
     preview.root_path = Some(root.to_string_lossy().to_string());
     preview.day_note_path = Some(
-        root.join("Daily")
-            .join(format!("{}.md", day_label))
+        exporter
+            .daily_note_path(&root, &day_label)
             .to_string_lossy()
             .to_string(),
     );
     preview.week_label = Some(week_label.clone());
     preview.week_index_path = Some(
-        root.join("Index")
-            .join(format!("weeks-{}.md", week_label))
+        exporter
+            .index_path(&root, &format!("weeks-{}.md", week_label))
             .to_string_lossy()
             .to_string(),
     );
     preview.weekly_note_path = Some(
-        root.join("Weekly")
-            .join(format!("{}.md", week_label))
+        exporter
+            .weekly_note_path(&root, &week_label, &week_start)
             .to_string_lossy()
             .to_string(),
     );
     preview.overview_path = Some(
-        root.join("Index")
-            .join("overview.md")
+        exporter
+            .index_path(&root, "overview.md")
             .to_string_lossy()
             .to_string(),
     );
     preview.month_index_path = Some(
-        root.join("Index")
-            .join(format!("sessions-{}.md", month_label))
+        exporter
+            .index_path(&root, &format!("sessions-{}.md", month_label))
             .to_string_lossy()
             .to_string(),
     );
@@ -389,9 +2285,7 @@ fn resolve_export_path(
     app: &tauri::AppHandle,
     output_path: Option<String>,
 ) -> Result<PathBuf, String> {
-    let timestamp = storage::local_now()
-        .format("%Y%m%d-%H%M%S")
-        .to_string();
+    let timestamp = storage::local_now().format("%Y%m%d-%H%M%S").to_string();
     let default_name = format!("screen-analyzer-config-{}.json", timestamp);
 
     let base_path = if let Some(path) = output_path {
@@ -419,13 +2313,42 @@ fn resolve_export_path(
     };
 
     if let Some(parent) = target_path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("创建导出目录失败: {}", e))?;
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建导出目录失败: {}", e))?;
     }
 
     Ok(target_path)
 }
 
+/// 从保存的 LLM 配置还原 Ollama 配置（缺少详细配置时使用模型与地址字段）
+fn ollama_config_from_persisted(llm_config: &models::LLMProviderConfig) -> llm::OllamaConfig {
+    llm_config
+        .ollama_config
+        .clone()
+        .and_then(|raw| serde_json::from_value(raw).ok())
+        .unwrap_or_else(|| llm::OllamaConfig {
+            base_url: llm_config.base_url.clone(),
+            model: llm_config.model.clone(),
+            ..llm::OllamaConfig::default()
+        })
+}
+
+/// 从保存的 LLM 配置还原 Gemini 配置
+fn gemini_config_from_persisted(llm_config: &models::LLMProviderConfig) -> llm::GeminiConfig {
+    let config = llm_config
+        .gemini_config
+        .clone()
+        .and_then(|raw| serde_json::from_value(raw).ok())
+        .unwrap_or_else(|| llm::GeminiConfig {
+            model: llm_config.model.clone(),
+            base_url: llm_config.base_url.clone(),
+            ..llm::GeminiConfig::default()
+        });
+    llm::GeminiConfig {
+        api_key: llm_config.api_key.clone(),
+        ..config
+    }
+}
+
 async fn apply_llm_config_from_persisted(
     state: &AppState,
     config: &PersistedAppConfig,
@@ -476,8 +2399,8 @@ async fn apply_llm_config_from_persisted(
                 .codex_config
                 .clone()
                 .ok_or_else(|| "未找到 Codex 配置".to_string())?;
-            let codex_config: llm::CodexConfig = serde_json::from_value(raw)
-                .map_err(|e| format!("Codex 配置解析失败: {}", e))?;
+            let codex_config: llm::CodexConfig =
+                serde_json::from_value(raw).map_err(|e| format!("Codex 配置解析失败: {}", e))?;
 
             state
                 .analysis_domain
@@ -486,11 +2409,28 @@ async fn apply_llm_config_from_persisted(
                 .await
                 .map_err(|e| e.to_string())?;
         }
+        "ollama" => {
+            let ollama_config = ollama_config_from_persisted(llm_config);
+
+            state
+                .analysis_domain
+                .get_llm_handle()
+                .configure_ollama(ollama_config)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        "gemini" => {
+            let gemini_config = gemini_config_from_persisted(llm_config);
+
+            state
+                .analysis_domain
+                .get_llm_handle()
+                .configure_gemini(gemini_config)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
         _ => {
-            return Err(format!(
-                "不支持的 LLM 提供商: {}",
-                config.llm_provider
-            ));
+            return Err(format!("不支持的 LLM 提供商: {}", config.llm_provider));
         }
     }
 
@@ -674,6 +2614,44 @@ async fn get_session_detail(
         .map_err(|e| e.to_string())
 }
 
+/// 解析帧路径为本机可读文件（已转存到对象存储的截图下载到缓存目录），失败时返回原路径；
+/// 加密的截图返回 data URL
+#[tauri::command]
+async fn resolve_frame_paths(
+    state: tauri::State<'_, AppState>,
+    paths: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let config = state
+        .storage_domain
+        .get_settings()
+        .get()
+        .await
+        .frame_store_config
+        .unwrap_or_default();
+    let store = frame_store::from_config(&config);
+
+    let mut resolved = Vec::with_capacity(paths.len());
+    for path in paths {
+        match store.local_path(&path).await {
+            Ok(local) if frame_crypto::file_is_encrypted(&local).unwrap_or(false) => {
+                match frame_crypto::data_url(&local).await {
+                    Ok(url) => resolved.push(url),
+                    Err(e) => {
+                        warn!("解密截图失败 {}: {}", path, e);
+                        resolved.push(path);
+                    }
+                }
+            }
+            Ok(local) => resolved.push(local.to_string_lossy().to_string()),
+            Err(e) => {
+                warn!("解析截图路径失败 {}: {}", path, e);
+                resolved.push(path);
+            }
+        }
+    }
+    Ok(resolved)
+}
+
 /// 获取应用配置
 #[tauri::command]
 async fn get_app_config(state: tauri::State<'_, AppState>) -> Result<PersistedAppConfig, String> {
@@ -683,7 +2661,13 @@ async fn get_app_config(state: tauri::State<'_, AppState>) -> Result<PersistedAp
 /// 获取支持的 LLM 提供商列表
 #[tauri::command]
 fn get_llm_providers() -> Vec<String> {
-    vec!["openai".to_string(), "claude".to_string(), "codex".to_string()]
+    vec![
+        "openai".to_string(),
+        "claude".to_string(),
+        "codex".to_string(),
+        "ollama".to_string(),
+        "gemini".to_string(),
+    ]
 }
 
 /// 更新配置
@@ -692,6 +2676,27 @@ async fn update_config(
     state: tauri::State<'_, AppState>,
     config: AppConfig,
 ) -> Result<PersistedAppConfig, String> {
+    if let Some(obsidian) = &config.obsidian_config {
+        custom_metrics::validate(&obsidian.custom_metrics).map_err(|e| e.to_string())?;
+    }
+    if let Some(lan_sync) = &config.lan_sync_config {
+        lan_sync::validate(lan_sync).map_err(|e| e.to_string())?;
+    }
+    if let Some(backup) = &config.backup_config {
+        backup::validate(backup).map_err(|e| e.to_string())?;
+    }
+    if let Some(frame_store) = &config.frame_store_config {
+        frame_store::build(frame_store).map_err(|e| e.to_string())?;
+    }
+    // 启用截图加密需要访问系统钥匙串，失败时不保存配置
+    if let Some(capture_settings) = &config.capture_settings {
+        let encrypt = capture_settings.encrypt_frames;
+        tokio::task::spawn_blocking(move || frame_crypto::configure(encrypt))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())?;
+    }
+
     let updated_config = state
         .storage_domain
         .get_settings()
@@ -711,6 +2716,45 @@ async fn update_config(
             .map_err(|e| e.to_string())?;
     }
 
+    // 更新磁盘空间预算
+    if let Some(budget) = config.storage_budget_config {
+        state
+            .storage_domain
+            .get_cleaner()
+            .await?
+            .set_storage_budget(budget)
+            .await;
+    }
+
+    // 更新截图存储后端
+    if let Some(frame_store) = &config.frame_store_config {
+        state
+            .storage_domain
+            .get_cleaner()
+            .await?
+            .set_frame_store(frame_store)
+            .await;
+    }
+
+    // 更新 LLM 响应缓存配置
+    if let Some(llm_cache) = config.llm_cache_config {
+        state
+            .analysis_domain
+            .get_llm_handle()
+            .configure_response_cache(llm_cache, None)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    // 更新总结队列并发数
+    if let Some(queue) = config.summary_queue_config {
+        state
+            .analysis_domain
+            .get_summary_queue()
+            .set_concurrency(queue.concurrency)
+            .await;
+    }
+
     // 更新LLM配置（现在只有Qwen）
     if let Some(_llm_provider) = config.llm_provider {
         // 现在只支持Qwen，不需要切换provider
@@ -732,9 +2776,35 @@ async fn update_config(
             .get_capture()
             .update_settings(capture_settings.clone())
             .await;
+        if let Ok(cleaner) = state.storage_domain.get_cleaner().await {
+            cleaner.set_recompression(&capture_settings).await;
+        }
         info!("截屏配置已更新: {:?}", capture_settings);
     }
 
+    // 更新分心提醒配置
+    if let Some(distraction_alert) = config.distraction_alert_config {
+        state
+            .capture_domain
+            .get_capture()
+            .distraction_watcher()
+            .update_config(distraction_alert);
+    }
+
+    // 更新应用时长上限配置
+    if let Some(app_limit) = config.app_limit_config {
+        state
+            .capture_domain
+            .get_capture()
+            .app_limit_watcher()
+            .update_config(app_limit);
+    }
+
+    // 更新过劳迹象提醒配置
+    if let Some(wellbeing_config) = config.wellbeing_config {
+        wellbeing::set_config(wellbeing_config);
+    }
+
     // 更新日志配置
     if let Some(logger_settings) = config.logger_settings {
         state
@@ -747,6 +2817,13 @@ async fn update_config(
         );
     }
 
+    // 通知 MQTT 状态发布器按新配置重连
+    if config.mqtt_config.is_some() {
+        state.event_bus.publish(AppEvent::ConfigUpdated {
+            config_type: "mqtt".to_string(),
+        });
+    }
+
     Ok(updated_config)
 }
 
@@ -807,87 +2884,311 @@ async fn add_manual_tag(
     Ok(())
 }
 
-/// 删除标签
+/// 删除标签
+#[tauri::command]
+async fn remove_tag(
+    state: tauri::State<'_, AppState>,
+    session_id: i64,
+    tag_index: usize,
+) -> Result<(), String> {
+    validate_session_id(session_id)?;
+    // 获取当前会话
+    let session_detail = state
+        .storage_domain
+        .get_db()
+        .await?
+        .get_session_detail(session_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // 删除指定索引的标签
+    let mut tags = session_detail.tags;
+    if tag_index >= tags.len() {
+        return Err("标签索引超出范围".to_string());
+    }
+    tags.remove(tag_index);
+
+    // 更新数据库
+    let tags_json = serde_json::to_string(&tags).map_err(|e| e.to_string())?;
+
+    state
+        .storage_domain
+        .get_db()
+        .await?
+        .update_session_tags(session_id, &tags_json)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// 获取系统状态
+#[tauri::command]
+async fn get_system_status(state: tauri::State<'_, AppState>) -> Result<SystemStatus, String> {
+    let status_handle = state.system_domain.get_status_handle();
+    let mut status = status_handle.get().await;
+
+    // 定时暂停到期后由调度器自动恢复，这里同步截屏状态（启动完成前不处理）
+    let pause = state.capture_domain.get_scheduler().pause_state().await;
+    if !status.is_capturing && !pause.paused && status.last_capture_time.is_some() {
+        status_handle.set_capturing(true).await;
+        status.is_capturing = true;
+    }
+
+    // 获取存储统计信息
+    if let Ok(cleaner) = state.storage_domain.get_cleaner().await {
+        if let Ok(storage_stats) = cleaner.get_storage_stats().await {
+            status.storage_usage = StorageUsage {
+                database_size: storage_stats.database_size as u64,
+                frames_size: storage_stats.frames_size as u64,
+                videos_size: storage_stats.videos_size as u64,
+                total_size: storage_stats.total_size as u64,
+                session_count: storage_stats.session_count as u32,
+                frame_count: storage_stats.frame_count as u32,
+            };
+        }
+    }
+
+    Ok(status)
+}
+
+/// 切换截屏状态（暂停/恢复）
+#[tauri::command]
+async fn toggle_capture(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
+    state
+        .system_domain
+        .get_status_handle()
+        .set_capturing(enabled)
+        .await;
+
+    if enabled {
+        info!("恢复截屏");
+    } else {
+        info!("暂停截屏");
+    }
+
+    state
+        .capture_domain
+        .get_scheduler()
+        .set_capture_enabled(enabled);
+    tray::request_refresh(&app);
+
+    Ok(())
+}
+
+/// 暂停截屏，`minutes` 为空时直到手动恢复
+#[tauri::command]
+async fn pause_capture(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    minutes: Option<u64>,
+) -> Result<CapturePauseState, String> {
+    let scheduler = state.capture_domain.get_scheduler();
+    scheduler.pause(minutes);
+    state
+        .system_domain
+        .get_status_handle()
+        .set_capturing(false)
+        .await;
+
+    match minutes {
+        Some(minutes) => info!("暂停截屏 {} 分钟", minutes),
+        None => info!("暂停截屏，直到手动恢复"),
+    }
+    tray::request_refresh(&app);
+    Ok(scheduler.pause_state().await)
+}
+
+/// 恢复截屏
+#[tauri::command]
+async fn resume_capture(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<CapturePauseState, String> {
+    let scheduler = state.capture_domain.get_scheduler();
+    scheduler.set_capture_enabled(true);
+    state
+        .system_domain
+        .get_status_handle()
+        .set_capturing(true)
+        .await;
+
+    info!("恢复截屏");
+    tray::request_refresh(&app);
+    Ok(scheduler.pause_state().await)
+}
+
+/// 获取截屏暂停与免打扰状态
+#[tauri::command]
+async fn get_capture_pause_state(
+    state: tauri::State<'_, AppState>,
+) -> Result<CapturePauseState, String> {
+    Ok(state.capture_domain.get_scheduler().pause_state().await)
+}
+
+/// 从现在起开始新会话（之前的截图归入上一个会话），返回新会话的开始时间
+#[tauri::command]
+async fn start_new_session(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    let started_at = state.capture_domain.get_scheduler().start_new_session();
+    info!("手动开始新会话: {}", started_at);
+    tray::request_refresh(&app);
+    Ok(started_at)
+}
+
+/// 获取托盘显示的状态（截屏状态、当前会话时长、今日专注占比）
+#[tauri::command]
+async fn get_tray_status(state: tauri::State<'_, AppState>) -> Result<tray::TrayStatus, String> {
+    Ok(tray::tray_status(&state).await)
+}
+
+/// 以自定义标题开始手动会话，手动结束前的会话都使用该标题
+#[tauri::command]
+async fn start_manual_session(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    title: String,
+) -> Result<ManualSession, String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err("会话标题不能为空".to_string());
+    }
+    let session = state
+        .capture_domain
+        .get_scheduler()
+        .start_manual_session(title.to_string());
+    info!("开始手动会话「{}」: {}", session.title, session.start);
+    tray::request_refresh(&app);
+    Ok(session)
+}
+
+/// 结束进行中的手动会话
+#[tauri::command]
+async fn stop_manual_session(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<ManualSession, String> {
+    let session = state
+        .capture_domain
+        .get_scheduler()
+        .stop_manual_session()
+        .ok_or_else(|| "没有进行中的手动会话".to_string())?;
+    info!("结束手动会话「{}」: {:?}", session.title, session.end);
+    tray::request_refresh(&app);
+    Ok(session)
+}
+
+/// 获取进行中的手动会话
+#[tauri::command]
+async fn get_manual_session(
+    state: tauri::State<'_, AppState>,
+) -> Result<Option<ManualSession>, String> {
+    Ok(state.capture_domain.get_scheduler().manual_session())
+}
+
+/// 在指定时间（本地时间，YYYY-MM-DD HH:mm:ss）拆分会话，返回新会话 ID
 #[tauri::command]
-async fn remove_tag(
+async fn split_session(
     state: tauri::State<'_, AppState>,
     session_id: i64,
-    tag_index: usize,
-) -> Result<(), String> {
+    at: String,
+) -> Result<i64, String> {
     validate_session_id(session_id)?;
-    // 获取当前会话
-    let session_detail = state
-        .storage_domain
-        .get_db()
-        .await?
-        .get_session_detail(session_id)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    // 删除指定索引的标签
-    let mut tags = session_detail.tags;
-    if tag_index >= tags.len() {
-        return Err("标签索引超出范围".to_string());
-    }
-    tags.remove(tag_index);
-
-    // 更新数据库
-    let tags_json = serde_json::to_string(&tags).map_err(|e| e.to_string())?;
+    let at = chrono::NaiveDateTime::parse_from_str(at.trim(), "%Y-%m-%d %H:%M:%S")
+        .map_err(|e| format!("拆分时间格式错误: {}", e))?
+        .and_utc();
+    info!("拆分会话: {} @ {}", session_id, at);
 
     state
         .storage_domain
         .get_db()
         .await?
-        .update_session_tags(session_id, &tags_json)
+        .split_session(session_id, at)
         .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(())
+        .map_err(|e| format!("拆分会话失败: {}", e))
 }
 
-/// 获取系统状态
+/// 合并两个相邻会话，返回保留的（较早的）会话 ID
 #[tauri::command]
-async fn get_system_status(state: tauri::State<'_, AppState>) -> Result<SystemStatus, String> {
-    let mut status = state.system_domain.get_status_handle().get().await;
+async fn merge_sessions(
+    state: tauri::State<'_, AppState>,
+    first_session_id: i64,
+    second_session_id: i64,
+) -> Result<i64, String> {
+    validate_session_id(first_session_id)?;
+    validate_session_id(second_session_id)?;
+    info!("合并会话: {} + {}", first_session_id, second_session_id);
 
-    // 获取存储统计信息
-    if let Ok(cleaner) = state.storage_domain.get_cleaner().await {
-        if let Ok(storage_stats) = cleaner.get_storage_stats().await {
-            status.storage_usage = StorageUsage {
-                database_size: storage_stats.database_size as u64,
-                frames_size: storage_stats.frames_size as u64,
-                videos_size: storage_stats.videos_size as u64,
-                total_size: storage_stats.total_size as u64,
-                session_count: storage_stats.session_count as u32,
-                frame_count: storage_stats.frame_count as u32,
-            };
+    let db = state.storage_domain.get_db().await?;
+    let mut videos = Vec::new();
+    for session_id in [first_session_id, second_session_id] {
+        let session = db
+            .get_session(session_id)
+            .await
+            .map_err(|e| format!("获取会话失败: {}", e))?;
+        videos.extend(session.video_path);
+    }
+
+    let kept_id = db
+        .merge_sessions(first_session_id, second_session_id)
+        .await
+        .map_err(|e| format!("合并会话失败: {}", e))?;
+
+    // 合并后不再被引用的视频文件一并删除
+    let kept_video = db
+        .get_session(kept_id)
+        .await
+        .ok()
+        .and_then(|session| session.video_path);
+    for video in videos {
+        if kept_video.as_ref() != Some(&video) {
+            if let Err(e) = tokio::fs::remove_file(&video).await {
+                error!("删除视频文件失败: {}", e);
+            }
         }
     }
 
-    Ok(status)
+    Ok(kept_id)
 }
 
-/// 切换截屏状态（暂停/恢复）
+/// 获取会话备注
 #[tauri::command]
-async fn toggle_capture(state: tauri::State<'_, AppState>, enabled: bool) -> Result<(), String> {
+async fn get_session_note(
+    state: tauri::State<'_, AppState>,
+    session_id: i64,
+) -> Result<Option<SessionNoteRecord>, String> {
+    validate_session_id(session_id)?;
     state
-        .system_domain
-        .get_status_handle()
-        .set_capturing(enabled)
-        .await;
-
-    if enabled {
-        info!("恢复截屏");
-    } else {
-        info!("暂停截屏");
-    }
+        .storage_domain
+        .get_db()
+        .await?
+        .get_session_note(session_id)
+        .await
+        .map_err(|e| format!("获取会话备注失败: {}", e))
+}
 
+/// 保存会话备注与自定义标签（都为空时删除备注）
+#[tauri::command]
+async fn save_session_note(
+    state: tauri::State<'_, AppState>,
+    session_id: i64,
+    note: String,
+    tags: Vec<String>,
+) -> Result<Option<SessionNoteRecord>, String> {
+    validate_session_id(session_id)?;
     state
-        .capture_domain
-        .get_scheduler()
-        .set_capture_enabled(enabled);
-
-    Ok(())
+        .storage_domain
+        .get_db()
+        .await?
+        .save_session_note(session_id, &note, &tags)
+        .await
+        .map_err(|e| format!("保存会话备注失败: {}", e))
 }
 
 /// 手动触发分析 - 分析video文件夹中未分析的视频
@@ -1283,7 +3584,7 @@ async fn test_generate_videos(
         .await
         .map_err(|e| e.to_string())?;
 
-    let mut frames: Vec<(chrono::DateTime<Utc>, PathBuf)> = Vec::new();
+    let mut frames: Vec<(chrono::DateTime<Utc>, usize, PathBuf)> = Vec::new();
 
     while let Some(entry) = dir.next_entry().await.map_err(|e| e.to_string())? {
         let path = entry.path();
@@ -1297,7 +3598,7 @@ async fn test_generate_videos(
             .map(|e| e.to_ascii_lowercase())
             .unwrap_or_default();
 
-        if !matches!(ext.as_str(), "jpg" | "jpeg" | "png") {
+        if !matches!(ext.as_str(), "jpeg" | "png") && !capture::format::is_frame_extension(&ext) {
             continue;
         }
 
@@ -1306,21 +3607,27 @@ async fn test_generate_videos(
             None => continue,
         };
 
-        let timestamp_ms: i64 = match file_stem.parse() {
-            Ok(ts) => ts,
-            Err(_) => continue,
+        let (timestamp_ms, screen_id) = match capture::parse_frame_file_stem(file_stem) {
+            Some(parsed) => parsed,
+            None => continue,
         };
 
         let timestamp = match chrono::DateTime::<Utc>::from_timestamp_millis(timestamp_ms) {
             Some(ts) => ts,
             None => continue,
         };
-        frames.push((timestamp, path));
+        frames.push((timestamp, screen_id, path));
     }
 
-    if frames.is_empty() {
+    // 按显示器分别保存的帧只用主显示器（序号最小）生成视频
+    let Some(primary_screen) = frames.iter().map(|(_, screen_id, _)| *screen_id).min() else {
         return Ok(vec![]);
-    }
+    };
+    let mut frames: Vec<(chrono::DateTime<Utc>, PathBuf)> = frames
+        .into_iter()
+        .filter(|(_, screen_id, _)| *screen_id == primary_screen)
+        .map(|(timestamp, _, path)| (timestamp, path))
+        .collect();
 
     frames.sort_by_key(|(ts, _)| *ts);
 
@@ -1460,7 +3767,28 @@ async fn cleanup_storage(state: tauri::State<'_, AppState>) -> Result<(), String
         .await?
         .trigger_cleanup()
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+
+    // 原始截图已按保留策略删除，同步清理 Vault 中的副本
+    let config = state.storage_domain.get_settings().get().await;
+    if let Some(obsidian_config) = config.obsidian_config {
+        if obsidian_config.enabled && obsidian_config.prune_expired_assets {
+            let cutoff =
+                (storage::local_now() - chrono::Duration::days(config.retention_days)).date_naive();
+            match ObsidianExporter::new(obsidian_config)
+                .prune_assets(cutoff)
+                .await
+            {
+                Ok(outcome) => info!(
+                    "已清理 Obsidian 过期截图 {} 个，更新笔记 {} 篇",
+                    outcome.removed_assets, outcome.updated_notes
+                ),
+                Err(e) => warn!("清理 Obsidian 过期截图失败: {}", e),
+            }
+        }
+    }
+
+    Ok(())
 }
 
 /// 获取存储统计
@@ -1516,17 +3844,17 @@ async fn refresh_device_info(state: tauri::State<'_, AppState>) -> Result<u64, S
         .map_err(|e| e.to_string())
 }
 
-/// 同步 SQLite 数据到 MariaDB
+/// 同步 SQLite 数据到 MariaDB / PostgreSQL
 #[tauri::command]
 async fn sync_data_to_mariadb(
     state: tauri::State<'_, AppState>,
     app: tauri::AppHandle,
 ) -> Result<String, String> {
-    info!("开始同步数据到 MariaDB");
+    info!("开始同步数据到远程数据库");
 
-    // 检查当前是否为 MariaDB 模式
-    if !state.storage_domain.get_db().await?.is_mariadb() {
-        return Err("当前不是 MariaDB 模式，无法同步数据".to_string());
+    // 检查当前是否为 MariaDB / PostgreSQL 模式
+    if state.storage_domain.get_db().await?.is_sqlite() {
+        return Err("当前不是 MariaDB 或 PostgreSQL 模式，无法同步数据".to_string());
     }
 
     // 获取 SQLite 数据库路径
@@ -1619,6 +3947,8 @@ async fn configure_llm_provider(
                 use_video_mode: qwen_config.use_video_mode,
                 auth_token: String::new(), // Qwen 不使用 auth_token
                 codex_config: None,
+                ollama_config: None,
+                gemini_config: None,
             }
         }
         "claude" => {
@@ -1662,6 +3992,8 @@ async fn configure_llm_provider(
                 use_video_mode: true, // Claude 支持视频模式
                 auth_token,           // 添加 auth_token 字段
                 codex_config: None,
+                ollama_config: None,
+                gemini_config: None,
             }
         }
         "codex" => {
@@ -1685,6 +4017,61 @@ async fn configure_llm_provider(
                 use_video_mode: false,
                 auth_token: String::new(),
                 codex_config: Some(stored),
+                ollama_config: None,
+                gemini_config: None,
+            }
+        }
+        "ollama" => {
+            let ollama_config: llm::OllamaConfig = serde_json::from_value(config.clone())
+                .map_err(|e| format!("Ollama 配置解析失败: {}", e))?;
+
+            state
+                .analysis_domain
+                .get_llm_handle()
+                .configure_ollama(ollama_config.clone())
+                .await
+                .map_err(|e| format!("{}", e))?;
+
+            let stored = serde_json::to_value(&ollama_config)
+                .map_err(|e| format!("Ollama 配置序列化失败: {}", e))?;
+
+            models::LLMProviderConfig {
+                api_key: String::new(),
+                model: ollama_config.model.clone(),
+                base_url: ollama_config.base_url.clone(),
+                use_video_mode: false,
+                auth_token: String::new(),
+                codex_config: None,
+                ollama_config: Some(stored),
+                gemini_config: None,
+            }
+        }
+        "gemini" => {
+            let gemini_config: llm::GeminiConfig = serde_json::from_value(config.clone())
+                .map_err(|e| format!("Gemini 配置解析失败: {}", e))?;
+
+            state
+                .analysis_domain
+                .get_llm_handle()
+                .configure_gemini(gemini_config.clone())
+                .await
+                .map_err(|e| format!("{}", e))?;
+
+            let stored = serde_json::to_value(llm::GeminiConfig {
+                api_key: String::new(),
+                ..gemini_config.clone()
+            })
+            .map_err(|e| format!("Gemini 配置序列化失败: {}", e))?;
+
+            models::LLMProviderConfig {
+                api_key: gemini_config.api_key.clone(),
+                model: gemini_config.model.clone(),
+                base_url: gemini_config.base_url.clone(),
+                use_video_mode: false,
+                auth_token: String::new(),
+                codex_config: None,
+                ollama_config: None,
+                gemini_config: Some(stored),
             }
         }
         _ => {
@@ -1705,6 +4092,31 @@ async fn configure_llm_provider(
         database_config: None,
         notion_config: None,
         obsidian_config: None,
+        logseq_config: None,
+        html_config: None,
+        webhook_config: None,
+        mqtt_config: None,
+        digest_config: None,
+        chat_config: None,
+        calendar_config: None,
+        issue_link_config: None,
+        time_tracking_config: None,
+        embedding_config: None,
+        ocr_config: None,
+        browser_config: None,
+        storage_budget_config: None,
+        summary_queue_config: None,
+        frame_prep_config: None,
+        llm_cache_config: None,
+        review_queue_config: None,
+        heuristic_config: None,
+        focus_config: None,
+        distraction_alert_config: None,
+        app_limit_config: None,
+        wellbeing_config: None,
+        lan_sync_config: None,
+        backup_config: None,
+        frame_store_config: None,
     };
 
     state
@@ -1757,6 +4169,14 @@ async fn configure_llm_provider(
     Ok(())
 }
 
+/// 获取可用显示器列表
+#[tauri::command]
+async fn get_monitors(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<capture::MonitorInfo>, String> {
+    Ok(state.capture_domain.get_capture().monitors())
+}
+
 /// 测试截屏功能
 #[tauri::command]
 async fn test_capture(state: tauri::State<'_, AppState>) -> Result<String, String> {
@@ -1976,6 +4396,8 @@ async fn regenerate_timeline(
                             serde_json::to_string(&card.distractions).unwrap_or_default(),
                         ),
                         app_sites: serde_json::to_string(&card.app_sites).unwrap_or_default(),
+                        confidence: card.confidence.map(f64::from),
+                        heuristic: card.heuristic,
                         video_preview_path: None,
                         created_at: storage::local_now(),
                     }
@@ -2109,6 +4531,8 @@ async fn test_llm_api(
             test_claude_sdk_api(config).await
         }
         "codex" => test_codex_cli(config).await,
+        "ollama" => test_ollama_api(config).await,
+        "gemini" => test_gemini_api(config).await,
         _ => Err(format!("不支持的提供商: {}", provider)),
     };
 
@@ -2142,6 +4566,39 @@ async fn test_codex_cli(config: serde_json::Value) -> Result<String, String> {
     Ok(response.trim().to_string())
 }
 
+async fn test_ollama_api(config: serde_json::Value) -> Result<String, String> {
+    let mut provider = llm::OllamaProvider::new(reqwest::Client::new());
+
+    provider
+        .configure(config)
+        .map_err(|e| format!("配置 Ollama 失败: {}", e))?;
+
+    let response = provider
+        .run_text_prompt("请仅回复“ollama-ok”确认连接。", "test_connection")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(response.trim().to_string())
+}
+
+async fn test_gemini_api(config: serde_json::Value) -> Result<String, String> {
+    let mut provider = llm::GeminiProvider::new(reqwest::Client::new());
+
+    provider
+        .configure(config)
+        .map_err(|e| format!("配置 Gemini 失败: {}", e))?;
+    if !provider.is_configured() {
+        return Err("请先填写 Gemini API Key".to_string());
+    }
+
+    let response = provider
+        .run_text_prompt("请仅回复“gemini-ok”确认连接。", "test_connection")
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(response.trim().to_string())
+}
+
 async fn test_openai_text_api(config: serde_json::Value) -> Result<String, String> {
     use reqwest::Client;
     use serde_json::json;
@@ -2487,6 +4944,10 @@ async fn test_notion_connection(
         database_id: String::new(), // 测试时不需要
         sync_options: Default::default(),
         max_retries: 3,
+        daily_database_id: String::new(),
+        weekly_database_id: String::new(),
+        screenshot_upload: Default::default(),
+        s3: Default::default(),
     };
 
     // 创建临时客户端测试连接
@@ -2567,6 +5028,23 @@ async fn create_notion_database(
         .map_err(|e| e.to_string())
 }
 
+/// 在指定页面下一键创建会话、每日总结与周报数据库（属性结构由应用生成）
+#[tauri::command]
+async fn setup_notion_databases(
+    state: tauri::State<'_, AppState>,
+    api_token: String,
+    parent_page_id: String,
+) -> Result<notion::NotionDatabaseSetup, String> {
+    info!("在页面 {} 下初始化 Notion 数据库", parent_page_id);
+
+    state
+        .storage_domain
+        .get_notion_manager()
+        .setup_databases(&api_token, &parent_page_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 // ==================== 辅助函数 ====================
 
 /// 处理历史图片，生成视频并清理
@@ -2577,6 +5055,10 @@ async fn process_historical_frames(state: &AppState) -> Result<(), String> {
         info!("跳过历史图片处理（仅 SQLite 模式支持）");
         return Ok(());
     }
+    if frame_crypto::is_enabled() {
+        info!("已启用截图加密，跳过历史图片处理");
+        return Ok(());
+    }
 
     info!("开始处理历史图片");
 
@@ -2915,6 +5397,7 @@ pub fn run() {
                 state,
                 llm_actor,
                 status_actor,
+                summary_queue_actor,
                 llm_provider_name,
                 llm_config_to_load,
                 db_config_to_load,
@@ -2924,6 +5407,13 @@ pub fn run() {
                 // 先初始化设置管理器，以便读取数据库配置
                 let settings_path = resolve_settings_path(&app_dir);
                 info!("使用配置路径: {}", settings_path.to_string_lossy());
+
+                // 应用上次下载的云备份（需在读取配置与打开数据库之前）
+                match backup::apply_pending_restore(&app_dir, &settings_path) {
+                    Ok(true) => info!("已从云备份恢复数据"),
+                    Ok(false) => {}
+                    Err(e) => error!("从云备份恢复失败: {}", e),
+                }
                 let settings = Arc::new(
                     SettingsManager::new(settings_path)
                         .await
@@ -2963,7 +5453,34 @@ pub fn run() {
                 if let Some(capture_settings) = initial_config.capture_settings.clone() {
                     capture.update_settings(capture_settings.clone()).await;
                     info!("已加载截屏配置: {:?}", capture_settings);
+
+                    let encrypt = capture_settings.encrypt_frames;
+                    match tokio::task::spawn_blocking(move || frame_crypto::configure(encrypt))
+                        .await
+                    {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => error!("初始化截图加密失败: {}", e),
+                        Err(e) => error!("初始化截图加密失败: {}", e),
+                    }
                 }
+                capture.distraction_watcher().update_config(
+                    initial_config
+                        .distraction_alert_config
+                        .clone()
+                        .unwrap_or_default(),
+                );
+                wellbeing::set_config(initial_config.wellbeing_config.clone().unwrap_or_default());
+                // 应用时长上限：从当天的窗口记录恢复已用时长
+                let app_limit_watcher = capture.app_limit_watcher();
+                app_limit_watcher
+                    .update_config(initial_config.app_limit_config.clone().unwrap_or_default());
+                app_limit_watcher.restore(
+                    &capture::window::read_window_records(
+                        &capture.frames_dir(),
+                        storage::local_now().date_naive(),
+                    )
+                    .await,
+                );
 
                 // 保存 LLM 配置（在 Actor 启动后再配置）
                 let llm_provider_name = initial_config.llm_provider.clone();
@@ -3003,15 +5520,29 @@ pub fn run() {
                 let capture_domain =
                     Arc::new(CaptureDomain::new(capture.clone(), scheduler.clone()));
 
+                // 创建存储领域（数据库未初始化）
+                let storage_domain = Arc::new(StorageDomain::new_pending(settings.clone()));
+
+                // 初始化总结队列（重新生成总结与批量导出共用，Actor 同样在后台运行时中启动）
+                let (summary_queue_actor, summary_queue_handle) = actors::SummaryQueueActor::new(
+                    Arc::new(domains::SummaryJobRunner::new(
+                        storage_domain.clone(),
+                        llm_handle.clone(),
+                    )),
+                    initial_config
+                        .summary_queue_config
+                        .clone()
+                        .unwrap_or_default()
+                        .concurrency,
+                );
+
                 // 创建分析领域（使用LLM Handle）
                 let analysis_domain = Arc::new(AnalysisDomain::new(
                     llm_handle.clone(),
+                    summary_queue_handle,
                     video_processor.clone(),
                 ));
 
-                // 创建存储领域（数据库未初始化）
-                let storage_domain = Arc::new(StorageDomain::new_pending(settings.clone()));
-
                 // 创建系统领域（使用SystemStatus Handle）
                 let system_domain = Arc::new(SystemDomain::new(
                     status_handle.clone(),
@@ -3032,11 +5563,12 @@ pub fn run() {
                     event_bus,
                 };
 
-                // 返回 AppState、两个 Actor、LLM provider、LLM 配置、数据库配置和目录路径
+                // 返回 AppState、三个 Actor、LLM provider、LLM 配置、数据库配置和目录路径
                 (
                     app_state,
                     llm_actor,
                     status_actor,
+                    summary_queue_actor,
                     llm_provider_name,
                     llm_config_to_load,
                     db_config_to_load,
@@ -3049,6 +5581,8 @@ pub fn run() {
             {
                 let state_clone = state.clone();
                 let app_dir_clone = app_dir.clone();
+                let queue_app_handle = app.handle().clone();
+                let notify_app_handle = app.handle().clone();
                 std::thread::spawn(move || {
                     let rt = tokio::runtime::Runtime::new()
                         .expect("无法创建 Tokio 运行时，程序无法继续运行");
@@ -3059,7 +5593,7 @@ pub fn run() {
                         info!("开始异步初始化数据库...");
                         let db_result = if let Some(mut db_config) = db_config_to_load {
                             // 如果是 SQLite，检查路径是否为相对路径，如果是则转换为应用数据目录下的绝对路径
-                            if let crate::storage::config::DatabaseConfig::SQLite { ref mut db_path } = db_config {
+                            if let crate::storage::config::DatabaseConfig::SQLite { ref mut db_path, .. } = db_config {
                                 let path = std::path::Path::new(db_path.as_str());
                                 if path.is_relative() {
                                     let absolute_path = app_dir_clone.join(path);
@@ -3082,6 +5616,11 @@ pub fn run() {
                                 // 设置数据库到 StorageDomain
                                 state_clone.storage_domain.set_database(db.clone()).await;
 
+                                // 加载自定义类别
+                                if let Err(e) = taxonomy::reload(&db).await {
+                                    error!("加载自定义类别失败: {}", e);
+                                }
+
                                 // 初始化存储清理器
                                 let cleaner = Arc::new(StorageCleaner::new(
                                     db.clone(),
@@ -3089,16 +5628,27 @@ pub fn run() {
                                     videos_dir_clone.clone(),
                                 ));
 
-                                // 从配置读取保留天数
-                                let retention_days = state_clone
-                                    .storage_domain
-                                    .get_settings()
-                                    .get()
-                                    .await
-                                    .retention_days;
-                                if let Err(e) = cleaner.set_retention_days(retention_days).await {
+                                // 从配置读取保留天数与旧截图转码配置
+                                let app_config =
+                                    state_clone.storage_domain.get_settings().get().await;
+                                if let Err(e) =
+                                    cleaner.set_retention_days(app_config.retention_days).await
+                                {
                                     error!("设置保留天数失败: {}", e);
                                 }
+                                if let Some(capture_settings) = &app_config.capture_settings {
+                                    cleaner.set_recompression(capture_settings).await;
+                                }
+                                cleaner
+                                    .set_storage_budget(
+                                        app_config.storage_budget_config.unwrap_or_default(),
+                                    )
+                                    .await;
+                                cleaner
+                                    .set_frame_store(
+                                        &app_config.frame_store_config.unwrap_or_default(),
+                                    )
+                                    .await;
 
                                 // 设置清理器到 StorageDomain
                                 state_clone.storage_domain.set_cleaner(cleaner).await;
@@ -3117,11 +5667,66 @@ pub fn run() {
                         }
 
                         // 启动 Actor（在这个长期运行的运行时中）
-                        info!("启动 LLM Manager Actor 和 System Status Actor...");
+                        info!("启动 LLM Manager Actor、System Status Actor 和 Summary Queue Actor...");
                         tokio::spawn(llm_actor.run());
                         tokio::spawn(status_actor.run());
+                        tokio::spawn(summary_queue_actor.run());
                         info!("Actors 已启动");
 
+                        // 转发总结队列进度事件到前端
+                        {
+                            use tauri::Emitter;
+
+                            let mut queue_events =
+                                state_clone.analysis_domain.get_summary_queue().subscribe();
+                            tokio::spawn(async move {
+                                loop {
+                                    match queue_events.recv().await {
+                                        Ok(event) => {
+                                            let _ = queue_app_handle
+                                                .emit("summary-queue-progress", event);
+                                        }
+                                        Err(tokio::sync::broadcast::error::RecvError::Lagged(
+                                            skipped,
+                                        )) => {
+                                            warn!("总结队列进度事件积压，跳过 {} 条", skipped);
+                                        }
+                                        Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                                            break
+                                        }
+                                    }
+                                }
+                            });
+                        }
+
+                        // 转发专注计时器事件、分心提醒与应用超时提醒到前端
+                        tokio::spawn(forward_focus_events(
+                            notify_app_handle.clone(),
+                            state_clone.storage_domain.clone(),
+                            state_clone.capture_domain.get_capture().focus_timer().subscribe(),
+                        ));
+                        tokio::spawn(forward_distraction_alerts(
+                            notify_app_handle.clone(),
+                            state_clone
+                                .capture_domain
+                                .get_capture()
+                                .distraction_watcher()
+                                .subscribe(),
+                        ));
+                        tokio::spawn(forward_app_limit_alerts(
+                            notify_app_handle.clone(),
+                            state_clone
+                                .capture_domain
+                                .get_capture()
+                                .app_limit_watcher()
+                                .subscribe(),
+                        ));
+                        tokio::spawn(run_wellbeing_checks(
+                            notify_app_handle,
+                            state_clone.storage_domain.clone(),
+                            app_dir_clone.join("wellbeing_last_check"),
+                        ));
+
                         // 配置 LLM（Actor 启动后才能配置）
                         // 1. 根据配置切换 provider
                         let provider = llm_provider_name.as_str();
@@ -3201,14 +5806,57 @@ pub fn run() {
                                         info!("已从配置文件加载 Codex 设置");
                                     }
                                 }
+                                "ollama" => {
+                                    let ollama_config = ollama_config_from_persisted(&llm_config);
+
+                                    if let Err(e) = state_clone
+                                        .analysis_domain
+                                        .get_llm_handle()
+                                        .configure_ollama(ollama_config)
+                                        .await
+                                    {
+                                        error!("加载 Ollama 配置失败: {}", e);
+                                    } else {
+                                        info!("已从配置文件加载 Ollama 设置");
+                                    }
+                                }
+                                "gemini" => {
+                                    let gemini_config = gemini_config_from_persisted(&llm_config);
+
+                                    if let Err(e) = state_clone
+                                        .analysis_domain
+                                        .get_llm_handle()
+                                        .configure_gemini(gemini_config)
+                                        .await
+                                    {
+                                        error!("加载 Gemini 配置失败: {}", e);
+                                    } else {
+                                        info!("已从配置文件加载 Gemini 设置");
+                                    }
+                                }
                                 _ => {
                                     warn!("未知的 LLM provider: {}", provider);
                                 }
                             }
                         }
 
-                        // 初始化 Notion 集成
+                        // 配置 LLM 响应缓存
                         let config = state_clone.storage_domain.get_settings().get().await;
+                        if let Ok(db) = state_clone.storage_domain.get_db().await {
+                            if let Err(e) = state_clone
+                                .analysis_domain
+                                .get_llm_handle()
+                                .configure_response_cache(
+                                    config.llm_cache_config.clone().unwrap_or_default(),
+                                    Some(db),
+                                )
+                                .await
+                            {
+                                error!("配置 LLM 响应缓存失败: {}", e);
+                            }
+                        }
+
+                        // 初始化 Notion 集成
                         if let Some(notion_config) = config.notion_config {
                             if notion_config.enabled {
                                 if let Err(e) = state_clone
@@ -3224,6 +5872,32 @@ pub fn run() {
                             }
                         }
 
+                        // 启动 MQTT 状态发布（未启用时仅监听配置变更）
+                        Arc::new(mqtt::MqttPublisher::new(
+                            state_clone.storage_domain.get_settings().clone(),
+                        ))
+                        .start(state_clone.event_bus.clone());
+
+                        // 启动局域网同步（主设备接收推送 / 从设备定时推送，未启用时仅监听配置变更）
+                        Arc::new(lan_sync::LanSyncService::new(
+                            state_clone.storage_domain.clone(),
+                        ))
+                        .start(state_clone.event_bus.clone());
+
+                        // 启动定时云备份（未启用时仅监听配置变更）
+                        Arc::new(backup::BackupService::new(
+                            state_clone.storage_domain.clone(),
+                            frames_dir_clone.clone(),
+                        ))
+                        .start(state_clone.event_bus.clone());
+
+                        // 启动浏览器活动接收端点（未启用时仅监听配置变更）
+                        Arc::new(browser::BrowserActivityServer::new(
+                            state_clone.storage_domain.get_settings().clone(),
+                            state_clone.capture_domain.get_capture().frames_dir(),
+                        ))
+                        .start(state_clone.event_bus.clone());
+
                         // 仅在数据库就绪时启动依赖数据库的组件
                         if let Some(db) = state_clone.storage_domain.try_get_db().await {
                             // 创建LLMProcessor并启动事件监听器（包含 Notion 支持）
@@ -3259,6 +5933,45 @@ pub fn run() {
                             } else {
                                 error!("存储清理器未就绪");
                             }
+
+                            // 启动邮件摘要定时发送
+                            Arc::new(digest::DigestScheduler::new(
+                                state_clone.storage_domain.get_settings().clone(),
+                                db.clone(),
+                                state_clone.analysis_domain.get_llm_handle().clone(),
+                                app_dir_clone.join("digest_state.json"),
+                            ))
+                            .start();
+
+                            // 启动 Google 日历定时同步
+                            Arc::new(calendar::CalendarSyncer::new(
+                                state_clone.storage_domain.get_settings().clone(),
+                                db.clone(),
+                            ))
+                            .start();
+
+                            // 启动 Slack / Discord 每日总结推送
+                            Arc::new(chat_summary::ChatSummaryScheduler::new(
+                                state_clone.storage_domain.get_settings().clone(),
+                                db.clone(),
+                                state_clone.analysis_domain.get_llm_handle().clone(),
+                                app_dir_clone.join("chat_summary_state.json"),
+                            ))
+                            .start();
+
+                            // 启动工单关联（会话分析完成后识别 GitHub / Jira 引用）
+                            Arc::new(issue_links::IssueLinker::new(
+                                state_clone.storage_domain.get_settings().clone(),
+                                db.clone(),
+                            ))
+                            .start(state_clone.event_bus.clone());
+
+                            // 启动语义向量生成（会话分析完成后为总结与卡片生成向量）
+                            Arc::new(embeddings::EmbeddingIndexer::new(
+                                state_clone.storage_domain.get_settings().clone(),
+                                db.clone(),
+                            ))
+                            .start(state_clone.event_bus.clone());
                         } else {
                             error!("数据库未就绪，跳过数据库相关组件的启动");
                         }
@@ -3375,6 +6088,11 @@ pub fn run() {
             }
 
             app.manage(state);
+
+            // 托盘菜单（失败不影响启动）
+            if let Err(e) = tray::setup(app.handle()) {
+                warn!("初始化托盘菜单失败: {}", e);
+            }
             Ok(())
         })
         .plugin(tauri_plugin_opener::init())
@@ -3382,8 +6100,77 @@ pub fn run() {
             get_database_status,
             get_activities,
             get_day_sessions,
+            search_sessions,
+            semantic_search,
+            rebuild_embeddings,
             get_day_summary,
             export_obsidian_day,
+            queue_day_summaries,
+            queue_export_range,
+            cancel_summary_jobs,
+            get_summary_queue_status,
+            clear_llm_cache,
+            get_llm_parse_failures,
+            get_review_queue,
+            review_timeline_card,
+            export_obsidian_range,
+            preview_obsidian_export,
+            export_obsidian_rollup,
+            export_obsidian_sessions,
+            retry_obsidian_sessions,
+            get_obsidian_export_history,
+            rerun_obsidian_export,
+            list_exporters,
+            run_export,
+            preview_export,
+            export_sessions_csv,
+            export_timeline_parquet,
+            export_day_summaries_json,
+            import_rescuetime_csv,
+            ingest_external_frames,
+            ingest_external_manifest,
+            export_learning_flashcards,
+            recategorize_timeline_cards,
+            send_digest_email,
+            post_chat_summary,
+            connect_google_calendar,
+            disconnect_google_calendar,
+            sync_google_calendar,
+            link_session_issues,
+            get_session_issue_links,
+            get_session_app_usage,
+            get_app_usage_summary,
+            get_session_domain_usage,
+            get_domain_usage_summary,
+            get_project_rules,
+            add_project_rule,
+            delete_project_rule,
+            get_custom_categories,
+            add_custom_category,
+            delete_custom_category,
+            get_classification_rules,
+            add_classification_rule,
+            delete_classification_rule,
+            get_goals,
+            save_goal,
+            delete_goal,
+            get_goal_progress,
+            start_focus_block,
+            stop_focus_block,
+            get_focus_status,
+            get_focus_blocks,
+            get_focus_trend,
+            get_focus_heatmap,
+            get_app_leaderboard,
+            get_device_names,
+            get_device_focus_stats,
+            lan_sync_now,
+            run_cloud_backup,
+            list_cloud_backups,
+            restore_cloud_backup,
+            backup_database,
+            restore_database,
+            obsidian_audit_vault,
             get_obsidian_preview,
             export_config,
             import_config,
@@ -3391,6 +6178,7 @@ pub fn run() {
             set_config_location,
             reset_config_location,
             get_session_detail,
+            resolve_frame_paths,
             get_app_config,
             get_llm_providers,
             update_config,
@@ -3399,6 +6187,18 @@ pub fn run() {
             remove_tag,
             get_system_status,
             toggle_capture,
+            pause_capture,
+            resume_capture,
+            get_capture_pause_state,
+            start_new_session,
+            get_tray_status,
+            start_manual_session,
+            stop_manual_session,
+            get_manual_session,
+            split_session,
+            merge_sessions,
+            get_session_note,
+            save_session_note,
             trigger_analysis,
             generate_video,
             get_video_url,
@@ -3412,6 +6212,7 @@ pub fn run() {
             configure_qwen,
             configure_llm_provider,
             test_capture,
+            get_monitors,
             test_llm_api,
             retry_session_analysis,
             regenerate_timeline,
@@ -3423,6 +6224,7 @@ pub fn run() {
             update_notion_config,
             search_notion_pages,
             create_notion_database,
+            setup_notion_databases,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -3667,6 +6469,8 @@ async fn analyze_video_once(
                     .distractions
                     .as_ref()
                     .map(|d| serde_json::to_string(d).unwrap_or_else(|_| "[]".to_string())),
+                confidence: card.confidence.map(f64::from),
+                heuristic: card.heuristic,
                 video_preview_path: Some(video_path_str.clone()),
                 app_sites: serde_json::to_string(&card.app_sites)
                     .unwrap_or_else(|_| "{}".to_string()),
@@ -3,6 +3,7 @@
 // 参考 Ollama provider 的图片处理逻辑：将帧图片转为 base64 发送给 Claude API
 
 use super::plugin::*;
+use super::structured::{self, ParseContext};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use base64::{engine::general_purpose, Engine as _};
@@ -159,6 +160,27 @@ impl ClaudeProvider {
         self.current_session_id = Some(session_id);
     }
 
+    /// 结构化响应校验的上下文
+    fn parse_context(&self) -> ParseContext<'_> {
+        ParseContext {
+            db: self.db.as_ref(),
+            session_id: self.current_session_id,
+            provider: "claude",
+            model: &self.model,
+        }
+    }
+
+    /// 请模型按 Schema 修复上一次的 JSON 输出
+    async fn repair_response(&self, prompt: String, call_type: &str) -> Result<String> {
+        let user_content = vec![json!({
+            "type": "text",
+            "text": prompt
+        })];
+        let system_prompt = "You are fixing JSON output so that it matches a schema.".to_string();
+        self.call_claude_api_with_retry(system_prompt, user_content, call_type)
+            .await
+    }
+
     fn reset_call_id(&self, call_type: &str) {
         if let Ok(mut map) = self.last_call_ids.lock() {
             map.remove(call_type);
@@ -180,7 +202,7 @@ impl ClaudeProvider {
 
     /// 将图片文件转换为 base64
     async fn image_to_base64(&self, path: &str) -> Result<String> {
-        let image_data = tokio::fs::read(path).await?;
+        let image_data = crate::frame_crypto::read_frame(path).await?;
         Ok(general_purpose::STANDARD.encode(&image_data))
     }
 
@@ -659,7 +681,12 @@ impl ClaudeProvider {
                             }
                             AgentMessage::Result { usage, .. } => {
                                 if let Some(value) = usage {
-                                    usage_snapshot = Some(value.to_string());
+                                    // 换算为统一格式（输入含缓存读写 token），无法识别时保留原始用量
+                                    usage_snapshot = Some(
+                                        TokenUsage::from_anthropic(&value)
+                                            .map(|usage| usage.to_record())
+                                            .unwrap_or_else(|| value.to_string()),
+                                    );
                                 }
                                 finished = true;
                             }
@@ -1148,9 +1175,10 @@ Analyze these screenshots from a screen recording session and create meaningful
 
 ## Rules:
 - Output must be a JSON array with **exactly one** object (数组长度必须为1)。
-- 每个字段必须存在：`startTime`、`endTime`、`category`、`subcategory`、`title`、`summary`、`detailedSummary`、`distractions`、`appSites`、`appSites.primary`、`appSites.secondary`、`isUpdated`。
+- 每个字段必须存在：`startTime`、`endTime`、`category`、`subcategory`、`title`、`summary`、`detailedSummary`、`distractions`、`appSites`、`appSites.primary`、`appSites.secondary`、`confidence`、`isUpdated`。
 - `startTime` = 各 segment 最早开始时间 (MM:SS 相对时间)，`endTime` = 各 segment 最晚结束时间 (MM:SS 相对时间)。
 - `category` 必须从 [work, communication, learning, personal, idle, other] 中选择最符合的一个。
+- `confidence` 为 0-1 之间的小数，表示对 `category` 判断的把握程度；画面信息不足或多种活动混杂时给出较低的值。
 - 所有文本字段使用中文描述，`summary` 为一句话概述，`detailedSummary` 需包含各 segment 的时间点与活动内容，并引用相对时间（例如 "00:00-05:00"）。
 - `distractions` 必须是数组，若无干扰请返回 []；如果存在干扰对象，必须包含 `startTime`、`endTime`、`title`、`summary` 字段，均使用 MM:SS 相对时间和中文描述。
 - `appSites.secondary` 必须是数组，若无元素返回 []，不要使用字符串。
@@ -1168,6 +1196,7 @@ Analyze these screenshots from a screen recording session and create meaningful
     "startTime": "00:00",
     "endTime": "15:00",
     "category": "work",
+    "confidence": 0.9,
     "subcategory": "Development",
     "title": "功能开发",
     "summary": "持续开发新功能模块",
@@ -1203,6 +1232,8 @@ Return ONLY the JSON array (确保startTime/endTime等字段存在，并使用
                     primary: "unknown".to_string(),
                     secondary: Some(vec![]),
                 },
+                confidence: None,
+                heuristic: false,
                 video_preview_path: None,
             };
         }
@@ -1265,6 +1296,10 @@ Return ONLY the JSON array (确保startTime/endTime等字段存在，并使用
             .unwrap_or_else(|| first.app_sites.primary.clone());
 
         let video_preview_path = cards.iter().find_map(|c| c.video_preview_path.clone());
+        let confidence = cards
+            .iter()
+            .map(|c| c.confidence)
+            .fold(None, lower_confidence);
 
         TimelineCard {
             start_time: first.start_time.clone(),
@@ -1283,6 +1318,8 @@ Return ONLY the JSON array (确保startTime/endTime等字段存在，并使用
                     Some(secondary_vec)
                 },
             },
+            confidence,
+            heuristic: false,
             video_preview_path,
         }
     }
@@ -1550,30 +1587,12 @@ Return ONLY the JSON object."#
             .call_claude_api_with_retry(system_prompt, user_content, "generate_timeline")
             .await?;
 
-        let mut json_value = match Self::extract_json(&response) {
-            Ok(value) => value,
-            Err(err) => {
-                info!("Claude generate_timeline 原始响应: {}", response);
-                return Err(err);
-            }
-        };
-        crate::llm::plugin::normalize_timeline_cards_value(&mut json_value);
-
-        let mut cards: Vec<TimelineCard> = match serde_json::from_value(json_value.clone()) {
-            Ok(cards) => cards,
-            Err(err) => {
-                error!("无法解析 Claude generate_timeline JSON: {}", err);
-                let raw_json = serde_json::to_string_pretty(&json_value)
-                    .unwrap_or_else(|_| json_value.to_string());
-                info!("Claude generate_timeline 原始 JSON: {}", raw_json);
-                info!("Claude generate_timeline 原始文本: {}", response);
-                return Err(err.into());
-            }
-        };
-
-        if cards.is_empty() {
-            return Err(anyhow!("Claude 未返回任何时间线卡片"));
-        }
+        // 解析并校验响应（未通过时请模型修复一次，原始响应写入解析失败记录）
+        let mut cards =
+            structured::parse_timeline(&response, &self.parse_context(), |prompt, call_type| {
+                self.repair_response(prompt, call_type)
+            })
+            .await?;
 
         if cards.len() > 1 {
             info!(
@@ -1643,6 +1662,50 @@ Return ONLY the JSON object."#
         Ok(response.trim().to_string())
     }
 
+    async fn generate_flashcards(
+        &self,
+        session: &SessionBrief,
+        max_cards: usize,
+    ) -> Result<Vec<Flashcard>> {
+        let user_content = vec![json!({
+            "type": "text",
+            "text": flashcard_prompt(session, max_cards)
+        })];
+        let system_prompt =
+            "You are turning study notes into spaced-repetition flashcards.".to_string();
+
+        let response = self
+            .call_claude_api_with_retry(system_prompt, user_content, "generate_flashcards")
+            .await?;
+
+        parse_flashcards(&response)
+    }
+
+    async fn categorize_cards(
+        &self,
+        cards: &[CardBrief],
+        custom: &[CustomCategory],
+    ) -> Result<Vec<CardCategory>> {
+        let user_content = vec![json!({
+            "type": "text",
+            "text": categorize_prompt(cards, custom)
+        })];
+        let system_prompt =
+            "You are classifying screen activity timeline cards into fixed categories.".to_string();
+
+        let response = self
+            .call_claude_api_with_retry(system_prompt, user_content, "categorize_cards")
+            .await?;
+
+        structured::parse_categories(
+            &response,
+            custom,
+            &self.parse_context(),
+            |prompt, call_type| self.repair_response(prompt, call_type),
+        )
+        .await
+    }
+
     fn name(&self) -> &str {
         "Claude"
     }
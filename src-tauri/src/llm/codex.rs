@@ -1,6 +1,7 @@
 // Codex CLI 提供商实现 - 使用 codex exec 无头模式
 
 use super::plugin::*;
+use super::structured::{self, ParseContext};
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Duration as ChronoDuration, Utc};
@@ -72,6 +73,16 @@ impl CodexProvider {
         self.current_session_id = Some(session_id);
     }
 
+    /// 结构化响应校验的上下文
+    fn parse_context(&self) -> ParseContext<'_> {
+        ParseContext {
+            db: self.db.as_ref(),
+            session_id: self.current_session_id,
+            provider: "codex",
+            model: self.model.as_deref().unwrap_or("codex-cli"),
+        }
+    }
+
     fn reset_call_id(&self, call_type: &str) {
         if let Ok(mut map) = self.last_call_ids.lock() {
             map.remove(call_type);
@@ -153,8 +164,9 @@ impl CodexProvider {
 2. 合并相邻、主题一致的活动
 3. 如有轻微分心，请在 card 内描述，不单独拆分
 4. category 使用 snake_case：work / communication / learning / personal / idle / other
-5. 字段：startTime、endTime、category、subcategory、title、summary、detailedSummary、distractions、appSites
-6. 仅返回 JSON 数组
+5. 字段：startTime、endTime、category、confidence、subcategory、title、summary、detailedSummary、distractions、appSites
+6. confidence 为 0-1，表示对 category 判断的把握，画面信息不足或活动混杂时给较低的值
+7. 仅返回 JSON 数组
 
 历史卡片（可选，便于合并）：
 {}
@@ -439,6 +451,8 @@ impl CodexProvider {
                     primary: "unknown".to_string(),
                     secondary: None,
                 },
+                confidence: None,
+                heuristic: false,
                 video_preview_path: None,
             })
             .collect()
@@ -457,7 +471,9 @@ impl LLMProvider for CodexProvider {
         }
 
         let sampled = self.sample_frames(&frames);
-        let images = self.canonicalize_paths(&sampled);
+        // Codex CLI 直接读取图片文件，加密的截图先解密到临时目录（执行结束后删除）
+        let plaintext = crate::frame_crypto::plaintext_frames(&sampled).await?;
+        let images = self.canonicalize_paths(&plaintext.paths);
         if images.is_empty() {
             return Err(anyhow!("采样后没有有效图片路径"));
         }
@@ -492,7 +508,9 @@ impl LLMProvider for CodexProvider {
         }
 
         let sampled = self.sample_frames(&frames);
-        let images = self.canonicalize_paths(&sampled);
+        // Codex CLI 直接读取图片文件，加密的截图先解密到临时目录（执行结束后删除）
+        let plaintext = crate::frame_crypto::plaintext_frames(&sampled).await?;
+        let images = self.canonicalize_paths(&plaintext.paths);
         if images.is_empty() {
             return Err(anyhow!("采样后没有有效图片路径"));
         }
@@ -527,20 +545,20 @@ impl LLMProvider for CodexProvider {
             .run_codex_exec(&prompt, &[], "generate_timeline")
             .await?;
 
-        let cards: Vec<TimelineCard> = match self.parse_json(&response) {
-            Ok(data) => data,
+        // 未通过结构校验时请模型修复一次，仍失败再使用回退结果
+        let parsed = structured::parse_timeline(
+            &response,
+            &self.parse_context(),
+            |prompt, call_type| async move { self.run_codex_exec(&prompt, &[], call_type).await },
+        )
+        .await;
+        match parsed {
+            Ok(cards) => Ok(cards),
             Err(err) => {
                 warn!("解析 Codex 时间线失败，使用回退：{}", err);
-                return Ok(self.fallback_timeline(&segments));
+                Ok(self.fallback_timeline(&segments))
             }
-        };
-
-        if cards.is_empty() {
-            warn!("Codex 时间线为空，使用回退结果");
-            return Ok(self.fallback_timeline(&segments));
         }
-
-        Ok(cards)
     }
 
     fn set_session_window(&mut self, start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>) {
@@ -663,6 +681,36 @@ impl LLMProvider for CodexProvider {
             .await?;
         Ok(response.trim().to_string())
     }
+
+    async fn generate_flashcards(
+        &self,
+        session: &SessionBrief,
+        max_cards: usize,
+    ) -> Result<Vec<Flashcard>> {
+        let prompt = flashcard_prompt(session, max_cards);
+        let response = self
+            .run_codex_exec(&prompt, &[], "generate_flashcards")
+            .await?;
+        parse_flashcards(&response)
+    }
+
+    async fn categorize_cards(
+        &self,
+        cards: &[CardBrief],
+        custom: &[CustomCategory],
+    ) -> Result<Vec<CardCategory>> {
+        let prompt = categorize_prompt(cards, custom);
+        let response = self
+            .run_codex_exec(&prompt, &[], "categorize_cards")
+            .await?;
+        structured::parse_categories(
+            &response,
+            custom,
+            &self.parse_context(),
+            |prompt, call_type| async move { self.run_codex_exec(&prompt, &[], call_type).await },
+        )
+        .await
+    }
 }
 
 fn truncate_for_log(input: &str, max_len: usize) -> String {
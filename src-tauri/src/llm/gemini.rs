@@ -0,0 +1,773 @@
+// Google Gemini 提供商实现 - 调用 Generative Language API 的 streamGenerateContent（SSE 流式）接口，
+// 截图以 inline_data 形式随请求发送，token 用量从 usageMetadata 换算
+
+use super::plugin::*;
+use super::structured::{self, ParseContext};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use llm_json::{loads, repair_json, RepairOptions};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+/// 默认接口地址
+pub const DEFAULT_GEMINI_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+/// 默认模型
+pub const DEFAULT_GEMINI_MODEL: &str = "gemini-2.5-flash";
+
+/// 限流或服务端错误时的最大重试次数
+const MAX_RETRIES: u32 = 2;
+
+/// Gemini 提供商
+pub struct GeminiProvider {
+    client: reqwest::Client,
+    api_key: String,
+    base_url: String,
+    model: String,
+    max_images: usize,
+    timeout_secs: u64,
+    db: Option<Arc<crate::storage::Database>>,
+    current_session_id: Option<i64>,
+    last_call_ids: Mutex<HashMap<String, i64>>,
+    session_window_start: Option<DateTime<Utc>>,
+    session_window_end: Option<DateTime<Utc>>,
+}
+
+/// 流式响应的累积结果
+#[derive(Debug, Default)]
+struct StreamOutput {
+    text: String,
+    usage: Option<Value>,
+    finish_reason: Option<String>,
+    block_reason: Option<String>,
+    events: usize,
+}
+
+impl StreamOutput {
+    /// 处理一行 SSE 数据（仅处理 data: 行）
+    fn push_line(&mut self, line: &str) -> Result<()> {
+        let Some(data) = line.trim_end_matches('\r').strip_prefix("data:") else {
+            return Ok(());
+        };
+        let data = data.trim();
+        if data.is_empty() || data == "[DONE]" {
+            return Ok(());
+        }
+
+        let chunk: Value =
+            serde_json::from_str(data).map_err(|e| anyhow!("Gemini 流式数据格式错误: {}", e))?;
+        self.events += 1;
+
+        if let Some(error) = chunk.get("error") {
+            return Err(anyhow!("Gemini 返回错误: {}", error));
+        }
+        if let Some(reason) = chunk
+            .pointer("/promptFeedback/blockReason")
+            .and_then(|v| v.as_str())
+        {
+            self.block_reason = Some(reason.to_string());
+        }
+        if let Some(candidate) = chunk.pointer("/candidates/0") {
+            if let Some(parts) = candidate
+                .pointer("/content/parts")
+                .and_then(|v| v.as_array())
+            {
+                for part in parts {
+                    // 跳过思考摘要，只保留最终输出
+                    if part.get("thought").and_then(|v| v.as_bool()) == Some(true) {
+                        continue;
+                    }
+                    if let Some(text) = part.get("text").and_then(|v| v.as_str()) {
+                        self.text.push_str(text);
+                    }
+                }
+            }
+            if let Some(reason) = candidate.get("finishReason").and_then(|v| v.as_str()) {
+                self.finish_reason = Some(reason.to_string());
+            }
+        }
+        // 每个分块都带累计用量，保留最后一次
+        if let Some(usage) = chunk.get("usageMetadata") {
+            self.usage = Some(usage.clone());
+        }
+        Ok(())
+    }
+
+    fn token_usage(&self) -> Option<TokenUsage> {
+        self.usage.as_ref().and_then(TokenUsage::from_gemini)
+    }
+}
+
+impl GeminiProvider {
+    /// 创建新的 Gemini 提供商
+    pub fn new(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            api_key: String::new(),
+            base_url: DEFAULT_GEMINI_URL.to_string(),
+            model: DEFAULT_GEMINI_MODEL.to_string(),
+            max_images: 30,
+            timeout_secs: 300,
+            db: None,
+            current_session_id: None,
+            last_call_ids: Mutex::new(HashMap::new()),
+            session_window_start: None,
+            session_window_end: None,
+        }
+    }
+
+    /// 设置数据库连接
+    pub fn set_database(&mut self, db: Arc<crate::storage::Database>) {
+        self.db = Some(db);
+    }
+
+    /// 设置当前会话 ID
+    pub fn set_session_id(&mut self, session_id: i64) {
+        self.current_session_id = Some(session_id);
+    }
+
+    /// 结构化响应校验的上下文
+    fn parse_context(&self) -> ParseContext<'_> {
+        ParseContext {
+            db: self.db.as_ref(),
+            session_id: self.current_session_id,
+            provider: "gemini",
+            model: &self.model,
+        }
+    }
+
+    fn reset_call_id(&self, call_type: &str) {
+        if let Ok(mut map) = self.last_call_ids.lock() {
+            map.remove(call_type);
+        }
+    }
+
+    fn record_call_id(&self, call_type: &str, id: i64) {
+        if let Ok(mut map) = self.last_call_ids.lock() {
+            map.insert(call_type.to_string(), id);
+        }
+    }
+
+    /// 均匀采样不超过 max_images 张图片
+    fn sample_frames(&self, frames: &[String]) -> Vec<String> {
+        if frames.len() <= self.max_images {
+            return frames.to_vec();
+        }
+
+        let step = frames.len() as f32 / self.max_images as f32;
+        (0..self.max_images)
+            .map(|i| {
+                let index = ((i as f32 + 0.5) * step).floor() as usize;
+                frames[index.min(frames.len() - 1)].clone()
+            })
+            .collect()
+    }
+
+    /// 读取图片并转为 inline_data 片段（读取失败的图片跳过）
+    async fn image_parts(&self, frames: &[String]) -> Vec<Value> {
+        let mut parts = Vec::new();
+        for path in self.sample_frames(frames) {
+            match crate::frame_crypto::read_frame(&path).await {
+                Ok(bytes) => parts.push(json!({
+                    "inline_data": {
+                        "mime_type": mime_type(&path),
+                        "data": general_purpose::STANDARD.encode(bytes)
+                    }
+                })),
+                Err(e) => warn!("读取图片失败 {}: {}", path, e),
+            }
+        }
+        parts
+    }
+
+    /// 调用接口（限流和服务端错误时退避重试）
+    async fn generate(
+        &self,
+        prompt: &str,
+        images: Vec<Value>,
+        json_output: bool,
+        call_type: &str,
+    ) -> Result<String> {
+        let mut parts = images;
+        parts.push(json!({ "text": prompt }));
+
+        let mut generation_config = json!({ "temperature": 0.3 });
+        if json_output {
+            generation_config["responseMimeType"] = json!("application/json");
+        }
+        let request_body = json!({
+            "contents": [{ "role": "user", "parts": parts }],
+            "generationConfig": generation_config
+        });
+
+        let mut attempt = 0;
+        loop {
+            match self.stream_generate(&request_body, call_type).await {
+                Err(GeminiError::Retryable(e)) if attempt < MAX_RETRIES => {
+                    attempt += 1;
+                    let delay = Duration::from_secs(5 * attempt as u64);
+                    warn!(
+                        "Gemini 调用失败，{} 秒后重试 ({}/{}): {}",
+                        delay.as_secs(),
+                        attempt,
+                        MAX_RETRIES,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(GeminiError::Retryable(e)) | Err(GeminiError::Fatal(e)) => return Err(e),
+                Ok(text) => return Ok(text),
+            }
+        }
+    }
+
+    /// 发送一次流式请求，边读取边拼接输出，并记录调用
+    async fn stream_generate(
+        &self,
+        request_body: &Value,
+        call_type: &str,
+    ) -> std::result::Result<String, GeminiError> {
+        if self.api_key.is_empty() {
+            return Err(GeminiError::Fatal(anyhow!("未配置 Gemini API Key")));
+        }
+        self.reset_call_id(call_type);
+
+        let mut record = crate::storage::LLMCallRecord {
+            id: None,
+            session_id: self.current_session_id,
+            provider: "gemini".to_string(),
+            model: self.model.clone(),
+            call_type: call_type.to_string(),
+            request_headers: json!({
+                "Content-Type": "application/json",
+                "x-goog-api-key": "***",
+                "mode": "sse"
+            })
+            .to_string(),
+            request_body: crate::llm::sanitize_request_body(request_body),
+            response_headers: None,
+            response_body: None,
+            status_code: None,
+            error_message: None,
+            latency_ms: None,
+            token_usage: None,
+            created_at: crate::storage::local_now(),
+        };
+
+        let start = Instant::now();
+        let result = self
+            .client
+            .post(stream_endpoint(&self.base_url, &self.model))
+            .header("x-goog-api-key", &self.api_key)
+            .timeout(Duration::from_secs(self.timeout_secs))
+            .json(request_body)
+            .send()
+            .await;
+        let mut response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                record.error_message = Some(e.to_string());
+                record.latency_ms = Some(start.elapsed().as_millis() as i64);
+                self.save_record(&record, call_type, false).await;
+                let err = anyhow!("Gemini 请求失败: {}", e);
+                return Err(if e.is_timeout() || e.is_connect() {
+                    GeminiError::Retryable(err)
+                } else {
+                    GeminiError::Fatal(err)
+                });
+            }
+        };
+
+        let status = response.status();
+        record.status_code = Some(status.as_u16() as i32);
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            error!("Gemini API错误: {}", text);
+            record.error_message = Some(text.clone());
+            record.latency_ms = Some(start.elapsed().as_millis() as i64);
+            self.save_record(&record, call_type, false).await;
+            let err = anyhow!("Gemini API调用失败 ({}): {}", status, text);
+            return Err(if status.as_u16() == 429 || status.is_server_error() {
+                GeminiError::Retryable(err)
+            } else {
+                GeminiError::Fatal(err)
+            });
+        }
+
+        // 按行解析 SSE，分块可能在任意字节处截断
+        let mut output = StreamOutput::default();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut stream_error: Option<anyhow::Error> = None;
+        loop {
+            match response.chunk().await {
+                Ok(Some(bytes)) => {
+                    buffer.extend_from_slice(&bytes);
+                    while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                        let line: Vec<u8> = buffer.drain(..=pos).collect();
+                        let line = String::from_utf8_lossy(&line[..line.len() - 1]).to_string();
+                        if let Err(e) = output.push_line(&line) {
+                            stream_error.get_or_insert(e);
+                        }
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    stream_error = Some(anyhow!("读取 Gemini 流失败: {}", e));
+                    break;
+                }
+            }
+        }
+        if !buffer.is_empty() {
+            if let Err(e) = output.push_line(&String::from_utf8_lossy(&buffer)) {
+                stream_error.get_or_insert(e);
+            }
+        }
+
+        record.latency_ms = Some(start.elapsed().as_millis() as i64);
+        record.token_usage = output.token_usage().map(|usage| usage.to_record());
+        record.response_body = Some(
+            json!({
+                "text": output.text,
+                "finishReason": output.finish_reason,
+                "usageMetadata": output.usage,
+                "events": output.events
+            })
+            .to_string(),
+        );
+
+        if output.finish_reason.as_deref() == Some("MAX_TOKENS") {
+            warn!("Gemini 响应因达到 token 限制而被截断");
+        }
+
+        let text = output.text.trim().to_string();
+        if text.is_empty() {
+            let err = stream_error.unwrap_or_else(|| match &output.block_reason {
+                Some(reason) => anyhow!("Gemini 拒绝了请求: {}", reason),
+                None => anyhow!(
+                    "Gemini 未返回任何内容 (finishReason={})",
+                    output.finish_reason.as_deref().unwrap_or("unknown")
+                ),
+            });
+            record.error_message = Some(err.to_string());
+            self.save_record(&record, call_type, false).await;
+            return Err(GeminiError::Fatal(err));
+        }
+
+        record.error_message = stream_error.map(|e| e.to_string());
+        self.save_record(&record, call_type, true).await;
+        Ok(text)
+    }
+
+    async fn save_record(
+        &self,
+        record: &crate::storage::LLMCallRecord,
+        call_type: &str,
+        success: bool,
+    ) {
+        let Some(db) = &self.db else {
+            return;
+        };
+        match db.insert_llm_call(record).await {
+            Ok(id) if success => self.record_call_id(call_type, id),
+            Ok(_) => {}
+            Err(e) => error!("保存LLM调用记录失败: {}", e),
+        }
+    }
+
+    /// 执行纯文本提示词（无图），用于连接测试
+    pub async fn run_text_prompt(&self, prompt: &str, call_type: &str) -> Result<String> {
+        self.generate(prompt, Vec::new(), false, call_type).await
+    }
+
+    fn segment_prompt(duration: u32) -> String {
+        format!(
+            r#"These images are chronological screenshots of the user's screen covering about {duration} minutes.
+Split the whole time range into 3-8 activity segments. Only start a new segment when the main activity clearly changes (different task, application, document or topic).
+Describe each segment in 1-2 Chinese sentences, naming the concrete applications, websites, files or topics visible on screen.
+
+Return a JSON array, timestamps in MM:SS relative to the start:
+[{{"startTimestamp": "00:00", "endTimestamp": "05:00", "description": "描述"}}]"#,
+            duration = duration
+        )
+    }
+
+    fn timeline_prompt(
+        segments: &[VideoSegment],
+        previous_cards: &Option<Vec<TimelineCard>>,
+    ) -> Result<String> {
+        let previous = match previous_cards {
+            Some(cards) if !cards.is_empty() => format!(
+                "\nPrevious cards (merge with adjacent activities on the same topic):\n{}\n",
+                serde_json::to_string(cards)?
+            ),
+            _ => String::new(),
+        };
+        Ok(format!(
+            r#"Create timeline cards from the screen activity segments below. Merge adjacent segments on the same topic; all text must be in Chinese.
+{previous}
+Segments:
+{segments}
+
+category must be one of work / communication / learning / personal / idle / other.
+confidence (0-1) is how sure you are about category; use a low value when the screen is ambiguous or several activities are mixed.
+Return a JSON array of cards:
+[{{"startTime": "00:00", "endTime": "05:00", "category": "work", "confidence": 0.9, "subcategory": "Development",
+  "title": "标题", "summary": "一句话摘要", "detailedSummary": "详细描述",
+  "distractions": [], "appSites": {{"primary": "主要应用或网站", "secondary": []}}}}]"#,
+            previous = previous,
+            segments = serde_json::to_string_pretty(segments)?
+        ))
+    }
+
+    fn summary_prompt() -> &'static str {
+        r#"Analyze these screenshots and summarize what the user accomplished. Return a JSON object:
+{"title": "活动标题", "summary": "详细描述",
+ "tags": [{"category": "work", "confidence": 0.8, "keywords": ["关键字"]}],
+ "productivity_score": 75, "focus_score": 80}
+category must be one of work / communication / learning / personal / idle / other; all text in Chinese."#
+    }
+
+    fn day_summary_prompt(date: &str, sessions: &[SessionBrief]) -> String {
+        let lines = sessions
+            .iter()
+            .map(|session| {
+                format!(
+                    "- {} - {}: {} —— {}",
+                    session.start_time.format("%H:%M"),
+                    session.end_time.format("%H:%M"),
+                    session.title,
+                    session.summary
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!(
+            "基于以下 {} 个会话，用 200 字以内的中文概括 {} 的主要工作，重点描述完成的事情与未完成事项，直接输出正文：\n{}",
+            sessions.len(),
+            date,
+            lines
+        )
+    }
+}
+
+/// 调用失败的类型：可重试（限流、超时、服务端错误）或不可重试
+enum GeminiError {
+    Retryable(anyhow::Error),
+    Fatal(anyhow::Error),
+}
+
+#[async_trait]
+impl LLMProvider for GeminiProvider {
+    fn as_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    async fn analyze_frames(&self, frames: Vec<String>) -> Result<SessionSummary> {
+        let images = self.image_parts(&frames).await;
+        if images.is_empty() {
+            return Err(anyhow!("没有可分析的帧图像"));
+        }
+
+        let response = self
+            .generate(Self::summary_prompt(), images, true, "analyze_frames")
+            .await?;
+        let payload: SummaryPayload = parse_json(&response)?;
+
+        let now = crate::storage::local_now();
+        Ok(SessionSummary {
+            title: payload.title.unwrap_or_else(|| "未命名会话".to_string()),
+            summary: payload.summary.unwrap_or_default(),
+            tags: payload
+                .tags
+                .into_iter()
+                .map(|tag| ActivityTag {
+                    category: crate::llm::map_category(&tag.category),
+                    confidence: tag.confidence.unwrap_or(0.5).clamp(0.0, 1.0),
+                    keywords: tag.keywords,
+                })
+                .collect(),
+            start_time: self
+                .session_window_start
+                .unwrap_or_else(|| now - ChronoDuration::minutes(15)),
+            end_time: self.session_window_end.unwrap_or(now),
+            key_moments: Vec::new(),
+            productivity_score: payload.productivity_score,
+            focus_score: payload.focus_score,
+        })
+    }
+
+    async fn segment_video(&self, frames: Vec<String>, duration: u32) -> Result<Vec<VideoSegment>> {
+        let images = self.image_parts(&frames).await;
+        if images.is_empty() {
+            return Err(anyhow!("没有可分析的帧图像"));
+        }
+
+        info!("使用 Gemini ({}) 分析 {} 张截图", self.model, images.len());
+        let response = self
+            .generate(
+                &Self::segment_prompt(duration),
+                images,
+                true,
+                "segment_video",
+            )
+            .await?;
+        let segments: Vec<VideoSegment> = parse_json(&response)?;
+        if segments.is_empty() {
+            return Err(anyhow!("Gemini 未返回任何分段"));
+        }
+        Ok(segments)
+    }
+
+    async fn generate_timeline(
+        &self,
+        segments: Vec<VideoSegment>,
+        previous_cards: Option<Vec<TimelineCard>>,
+    ) -> Result<Vec<TimelineCard>> {
+        let prompt = Self::timeline_prompt(&segments, &previous_cards)?;
+        let response = self
+            .generate(&prompt, Vec::new(), true, "generate_timeline")
+            .await?;
+
+        // 解析并校验响应（未通过时请模型修复一次，原始响应写入解析失败记录）
+        structured::parse_timeline(&response, &self.parse_context(), |prompt, call_type| async move {
+            self.generate(&prompt, Vec::new(), true, call_type).await
+        })
+        .await
+    }
+
+    fn set_session_window(&mut self, start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>) {
+        self.session_window_start = start;
+        self.session_window_end = end;
+    }
+
+    fn name(&self) -> &str {
+        "Gemini"
+    }
+
+    fn configure(&mut self, config: Value) -> Result<()> {
+        if let Some(api_key) = config.get("api_key").and_then(|v| v.as_str()) {
+            self.api_key = api_key.trim().to_string();
+        }
+        if self.api_key.is_empty() {
+            if let Ok(api_key) = std::env::var("GEMINI_API_KEY") {
+                self.api_key = api_key.trim().to_string();
+            }
+        }
+
+        if let Some(base_url) = config.get("base_url").and_then(|v| v.as_str()) {
+            let base_url = base_url.trim().trim_end_matches('/');
+            self.base_url = if base_url.is_empty() {
+                DEFAULT_GEMINI_URL.to_string()
+            } else {
+                base_url.to_string()
+            };
+        }
+
+        if let Some(model) = config.get("model").and_then(|v| v.as_str()) {
+            let model = model.trim().trim_start_matches("models/");
+            if !model.is_empty() {
+                self.model = model.to_string();
+            }
+        }
+
+        if let Some(max_images) = config.get("max_images").and_then(|v| v.as_u64()) {
+            if max_images > 0 {
+                self.max_images = max_images.min(100) as usize;
+            }
+        }
+
+        if let Some(timeout) = config.get("timeout_secs").and_then(|v| v.as_u64()) {
+            if timeout >= 30 {
+                self.timeout_secs = timeout;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_configured(&self) -> bool {
+        !self.api_key.is_empty()
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            vision_support: true,
+            batch_analysis: true,
+            streaming: true,
+            max_input_tokens: 1_000_000,
+            supported_image_formats: vec![
+                "jpg".to_string(),
+                "jpeg".to_string(),
+                "png".to_string(),
+                "webp".to_string(),
+            ],
+        }
+    }
+
+    fn last_llm_call_id(&self, call_type: &str) -> Option<i64> {
+        self.last_call_ids
+            .lock()
+            .ok()
+            .and_then(|map| map.get(call_type).copied())
+    }
+
+    async fn generate_day_summary(&self, date: &str, sessions: &[SessionBrief]) -> Result<String> {
+        if sessions.is_empty() {
+            return Ok(format!("{} 当天没有记录到任何屏幕活动。", date));
+        }
+
+        let response = self
+            .generate(
+                &Self::day_summary_prompt(date, sessions),
+                Vec::new(),
+                false,
+                "generate_day_summary",
+            )
+            .await?;
+        Ok(response.trim().to_string())
+    }
+
+    async fn generate_flashcards(
+        &self,
+        session: &SessionBrief,
+        max_cards: usize,
+    ) -> Result<Vec<Flashcard>> {
+        let response = self
+            .generate(
+                &flashcard_prompt(session, max_cards),
+                Vec::new(),
+                true,
+                "generate_flashcards",
+            )
+            .await?;
+        parse_flashcards(&response)
+    }
+
+    async fn categorize_cards(
+        &self,
+        cards: &[CardBrief],
+        custom: &[CustomCategory],
+    ) -> Result<Vec<CardCategory>> {
+        let response = self
+            .generate(
+                &categorize_prompt(cards, custom),
+                Vec::new(),
+                true,
+                "categorize_cards",
+            )
+            .await?;
+        structured::parse_categories(
+            &response,
+            custom,
+            &self.parse_context(),
+            |prompt, call_type| async move {
+                self.generate(&prompt, Vec::new(), true, call_type).await
+            },
+        )
+        .await
+    }
+}
+
+/// 流式接口地址
+fn stream_endpoint(base_url: &str, model: &str) -> String {
+    format!(
+        "{}/models/{}:streamGenerateContent?alt=sse",
+        base_url.trim().trim_end_matches('/'),
+        model
+    )
+}
+
+/// 按扩展名推断图片类型
+fn mime_type(path: &str) -> &'static str {
+    let lower = path.to_lowercase();
+    if lower.ends_with(".png") {
+        "image/png"
+    } else if lower.ends_with(".webp") {
+        "image/webp"
+    } else {
+        "image/jpeg"
+    }
+}
+
+/// 解析模型输出的 JSON（偶尔带代码块或格式瑕疵，先尝试修复）
+fn parse_json<T: DeserializeOwned>(raw: &str) -> Result<T> {
+    let trimmed = raw.trim();
+    let cleaned = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .map(|body| body.trim_end().trim_end_matches("```").trim())
+        .unwrap_or(trimmed);
+
+    if let Ok(value) = serde_json::from_str::<T>(cleaned) {
+        return Ok(value);
+    }
+    let repaired = repair_json(cleaned, &RepairOptions::default())
+        .map_err(|e| anyhow!("无法修复 Gemini 返回的 JSON: {}", e))?;
+    let value = loads(&repaired, &RepairOptions::default())
+        .map_err(|e| anyhow!("解析修复后的 JSON 失败: {}", e))?;
+    serde_json::from_value(value).map_err(|e| anyhow!("JSON 结构不符合预期: {}", e))
+}
+
+#[derive(Debug, Deserialize)]
+struct SummaryPayload {
+    title: Option<String>,
+    summary: Option<String>,
+    #[serde(default)]
+    tags: Vec<TagPayload>,
+    productivity_score: Option<f32>,
+    focus_score: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TagPayload {
+    category: String,
+    confidence: Option<f32>,
+    #[serde(default)]
+    keywords: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_output() {
+        assert_eq!(
+            stream_endpoint("https://generativelanguage.googleapis.com/v1beta/", "gemini-2.5-flash"),
+            "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.5-flash:streamGenerateContent?alt=sse"
+        );
+
+        let mut output = StreamOutput::default();
+        for line in [
+            r#"data: {"candidates":[{"content":{"parts":[{"text":"思考中","thought":true}],"role":"model"}}]}"#,
+            "",
+            r#"data: {"candidates":[{"content":{"parts":[{"text":"[{\"startTimestamp\":\"00:00\","}],"role":"model"}}],"usageMetadata":{"promptTokenCount":2580,"candidatesTokenCount":8}}"#,
+            "\r",
+            r#"data: {"candidates":[{"content":{"parts":[{"text":"\"endTimestamp\":\"04:00\",\"description\":\"阅读文档\"}]"}],"role":"model"},"finishReason":"STOP"}],"usageMetadata":{"promptTokenCount":2580,"candidatesTokenCount":30,"thoughtsTokenCount":90,"totalTokenCount":2700}}"#,
+        ] {
+            output.push_line(line).unwrap();
+        }
+        assert_eq!(output.events, 3);
+        assert_eq!(output.finish_reason.as_deref(), Some("STOP"));
+
+        let segments: Vec<VideoSegment> = parse_json(&output.text).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].description, "阅读文档");
+
+        let usage = output.token_usage().unwrap();
+        assert_eq!(usage.completion_tokens, 120);
+        assert_eq!(usage.total_tokens, 2700);
+
+        let mut failed = StreamOutput::default();
+        assert!(failed
+            .push_line(r#"data: {"error":{"code":429,"message":"quota"}}"#)
+            .is_err());
+    }
+}
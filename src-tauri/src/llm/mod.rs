@@ -2,16 +2,23 @@
 
 pub mod claude;
 pub mod codex;
+pub mod gemini;
+pub mod ollama;
 pub mod plugin;
 pub mod qwen;
+pub mod response_cache;
+pub mod structured;
 
 pub use claude::ClaudeProvider;
 pub use codex::CodexProvider;
+pub use gemini::GeminiProvider;
+pub use ollama::OllamaProvider;
 pub use plugin::{
-    ActivityCategory, ActivityTag, AppSites, Distraction, KeyMoment, LLMProvider, SessionBrief,
-    SessionSummary, TimelineCard, VideoSegment,
+    ActivityCategory, ActivityTag, AppSites, CardBrief, CardCategory, Distraction, Flashcard,
+    KeyMoment, LLMProvider, SessionBrief, SessionSummary, TimelineCard, TokenUsage, VideoSegment,
 };
 pub use qwen::QwenProvider;
+pub use response_cache::ResponseCache;
 
 use crate::capture::scheduler::SessionProcessor;
 use crate::settings::SettingsManager;
@@ -30,12 +37,24 @@ pub struct LLMManager {
     config_lock: Arc<RwLock<LLMConfig>>,
     /// HTTP 客户端（用于 Qwen provider）
     http_client: Option<reqwest::Client>,
+    /// 下一次生成时间线使用的 OCR 上下文
+    ocr_context: Option<crate::ocr::OcrContext>,
+    /// 当前会话时间范围（参与响应缓存键计算）
+    session_window: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    /// 视频速率乘数（参与响应缓存键计算）
+    video_speed: f32,
+    /// 响应缓存配置
+    cache_config: crate::models::LlmCacheConfig,
+    /// 响应缓存使用的数据库
+    cache_db: Option<Arc<crate::storage::Database>>,
+    /// LLM 响应缓存（未启用或数据库未就绪时为空）
+    response_cache: Option<ResponseCache>,
 }
 
 /// LLM配置
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct LLMConfig {
-    /// 当前使用的 provider: "qwen" 或 "claude"
+    /// 当前使用的 provider: "qwen"、"claude"、"codex"、"ollama" 或 "gemini"
     #[serde(default = "default_provider")]
     pub provider: String,
     /// Qwen配置
@@ -46,6 +65,12 @@ pub struct LLMConfig {
     /// Codex配置
     #[serde(default)]
     pub codex: CodexConfig,
+    /// Ollama配置
+    #[serde(default)]
+    pub ollama: OllamaConfig,
+    /// Gemini配置
+    #[serde(default)]
+    pub gemini: GeminiConfig,
     /// 分析参数
     pub analysis_params: AnalysisParams,
 }
@@ -90,6 +115,76 @@ pub struct CodexConfig {
     pub day_summary_prompt: Option<String>,
 }
 
+/// Ollama配置（本地模型，无需 API Key）
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct OllamaConfig {
+    #[serde(default = "default_ollama_url")]
+    pub base_url: String,
+    #[serde(default = "default_ollama_model")]
+    pub model: String,
+    /// 每次请求最多附带的截图数
+    #[serde(default)]
+    pub max_images: Option<usize>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+impl Default for OllamaConfig {
+    fn default() -> Self {
+        Self {
+            base_url: default_ollama_url(),
+            model: default_ollama_model(),
+            max_images: None,
+            timeout_secs: None,
+        }
+    }
+}
+
+fn default_ollama_url() -> String {
+    ollama::DEFAULT_OLLAMA_URL.to_string()
+}
+
+fn default_ollama_model() -> String {
+    ollama::DEFAULT_OLLAMA_MODEL.to_string()
+}
+
+/// Gemini配置
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct GeminiConfig {
+    /// 为空时读取环境变量 GEMINI_API_KEY
+    #[serde(default)]
+    pub api_key: String,
+    #[serde(default = "default_gemini_model")]
+    pub model: String,
+    #[serde(default = "default_gemini_url")]
+    pub base_url: String,
+    /// 每次请求最多附带的截图数
+    #[serde(default)]
+    pub max_images: Option<usize>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+impl Default for GeminiConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            model: default_gemini_model(),
+            base_url: default_gemini_url(),
+            max_images: None,
+            timeout_secs: None,
+        }
+    }
+}
+
+fn default_gemini_url() -> String {
+    gemini::DEFAULT_GEMINI_URL.to_string()
+}
+
+fn default_gemini_model() -> String {
+    gemini::DEFAULT_GEMINI_MODEL.to_string()
+}
+
 /// Qwen配置
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct QwenConfig {
@@ -159,12 +254,51 @@ impl LLMManager {
                 },
                 claude: ClaudeConfig::default(),
                 codex: CodexConfig::default(),
+                ollama: OllamaConfig::default(),
+                gemini: GeminiConfig::default(),
                 analysis_params: AnalysisParams::default(),
             })),
             http_client: Some(client),
+            ocr_context: None,
+            session_window: None,
+            video_speed: 1.0,
+            cache_config: crate::models::LlmCacheConfig::default(),
+            cache_db: None,
+            response_cache: None,
+        }
+    }
+
+    /// 配置响应缓存（db 为空时沿用已有的数据库连接），并清理过期缓存
+    pub async fn configure_response_cache(
+        &mut self,
+        config: crate::models::LlmCacheConfig,
+        db: Option<Arc<crate::storage::Database>>,
+    ) {
+        self.cache_config = config;
+        if db.is_some() {
+            self.cache_db = db;
+        }
+        self.rebuild_response_cache();
+
+        if let Some(cache) = &self.response_cache {
+            match cache.prune().await {
+                Ok(0) => {}
+                Ok(count) => info!("已清理 {} 条过期的 LLM 响应缓存", count),
+                Err(e) => warn!("清理 LLM 响应缓存失败: {}", e),
+            }
         }
     }
 
+    fn rebuild_response_cache(&mut self) {
+        self.response_cache = match &self.cache_db {
+            Some(db) if self.cache_config.enabled => Some(ResponseCache::new(
+                db.clone(),
+                self.cache_config.max_age_days,
+            )),
+            _ => None,
+        };
+    }
+
     /// 配置 LLM（支持多 provider）
     pub async fn configure(&mut self, config: QwenConfig) -> Result<()> {
         // 获取当前 provider 类型
@@ -239,6 +373,26 @@ impl LLMManager {
             "codex" => {
                 self.provider = Box::new(CodexProvider::new());
             }
+            "ollama" => {
+                let client = self
+                    .http_client
+                    .clone()
+                    .ok_or_else(|| anyhow!("无法切换到 Ollama provider: HTTP 客户端未初始化"))?;
+                let mut provider = OllamaProvider::new(client);
+                let config = self.config_lock.read().await.ollama.clone();
+                provider.configure(serde_json::to_value(&config)?)?;
+                self.provider = Box::new(provider);
+            }
+            "gemini" => {
+                let client = self
+                    .http_client
+                    .clone()
+                    .ok_or_else(|| anyhow!("无法切换到 Gemini provider: HTTP 客户端未初始化"))?;
+                let mut provider = GeminiProvider::new(client);
+                let config = self.config_lock.read().await.gemini.clone();
+                provider.configure(serde_json::to_value(&config)?)?;
+                self.provider = Box::new(provider);
+            }
             _ => {
                 return Err(anyhow!("不支持的 provider: {}", provider_name));
             }
@@ -291,6 +445,43 @@ impl LLMManager {
         Ok(())
     }
 
+    /// 配置 Ollama provider
+    pub async fn configure_ollama(&mut self, config: OllamaConfig) -> Result<()> {
+        info!(
+            "配置 Ollama provider: {} ({})",
+            config.model, config.base_url
+        );
+
+        if let Some(provider) = self.provider.as_any().downcast_mut::<OllamaProvider>() {
+            provider.configure(serde_json::to_value(&config)?)?;
+        } else {
+            warn!("当前 provider 不是 Ollama，暂存配置待切换后生效");
+        }
+
+        let mut current_config = self.config_lock.write().await;
+        current_config.ollama = config;
+
+        info!("Ollama 配置已更新");
+        Ok(())
+    }
+
+    /// 配置 Gemini provider
+    pub async fn configure_gemini(&mut self, config: GeminiConfig) -> Result<()> {
+        info!("配置 Gemini provider: {}", config.model);
+
+        if let Some(provider) = self.provider.as_any().downcast_mut::<GeminiProvider>() {
+            provider.configure(serde_json::to_value(&config)?)?;
+        } else {
+            warn!("当前 provider 不是 Gemini，暂存配置待切换后生效");
+        }
+
+        let mut current_config = self.config_lock.write().await;
+        current_config.gemini = config;
+
+        info!("Gemini 配置已更新");
+        Ok(())
+    }
+
     pub fn set_video_path(&mut self, video_path: Option<String>) {
         if let Some(provider) = self.provider.as_any().downcast_mut::<QwenProvider>() {
             provider.set_video_path(video_path.clone());
@@ -303,6 +494,7 @@ impl LLMManager {
 
     /// 设置视频速率乘数
     pub fn set_video_speed(&mut self, speed_multiplier: f32) {
+        self.video_speed = speed_multiplier;
         // 只有 Qwen provider 需要视频速率
         if let Some(provider) = self.provider.as_any().downcast_mut::<QwenProvider>() {
             provider.set_video_speed(speed_multiplier);
@@ -311,9 +503,15 @@ impl LLMManager {
 
     /// 设置会话时间范围（用于提示词中的绝对时间）
     pub fn set_session_window(&mut self, start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>) {
+        self.session_window = start.zip(end);
         self.provider.set_session_window(start, end);
     }
 
+    /// 设置下一次生成时间线使用的 OCR 上下文
+    pub fn set_ocr_context(&mut self, context: Option<crate::ocr::OcrContext>) {
+        self.ocr_context = context;
+    }
+
     /// 分析帧数据
     pub async fn analyze_frames(&mut self, frames: Vec<String>) -> Result<SessionSummary> {
         let provider_name = {
@@ -347,12 +545,21 @@ impl LLMManager {
         self.config_lock.read().await.clone()
     }
 
+    /// 当前提供商是否已配置（如 API Key 已填写）
+    pub fn is_configured(&self) -> bool {
+        self.provider.is_configured()
+    }
+
     /// 设置provider的数据库连接
     pub fn set_provider_database(
         &mut self,
         db: Arc<crate::storage::Database>,
         session_id: Option<i64>,
     ) {
+        // 响应缓存跟随当前数据库
+        self.cache_db = Some(db.clone());
+        self.rebuild_response_cache();
+
         // Qwen provider
         if let Some(provider) = self.provider.as_any().downcast_mut::<QwenProvider>() {
             provider.set_database(db.clone());
@@ -381,6 +588,23 @@ impl LLMManager {
             info!("已为 Codex provider 设置数据库连接");
             return;
         }
+        // Ollama provider
+        if let Some(provider) = self.provider.as_any().downcast_mut::<OllamaProvider>() {
+            provider.set_database(db.clone());
+            if let Some(sid) = session_id {
+                provider.set_session_id(sid);
+            }
+            info!("已为 Ollama provider 设置数据库连接");
+            return;
+        }
+        // Gemini provider
+        if let Some(provider) = self.provider.as_any().downcast_mut::<GeminiProvider>() {
+            provider.set_database(db.clone());
+            if let Some(sid) = session_id {
+                provider.set_session_id(sid);
+            }
+            info!("已为 Gemini provider 设置数据库连接");
+        }
     }
 
     /// 生成时间线卡片（公开方法）
@@ -405,7 +629,82 @@ impl LLMManager {
         date: &str,
         sessions: &[SessionBrief],
     ) -> Result<String> {
-        self.provider.generate_day_summary(date, sessions).await
+        let cache_key = match &self.response_cache {
+            Some(_) => {
+                let config = self.config_lock.read().await;
+                Some(
+                    response_cache::CacheKeyBuilder::new("day_summary", &config)
+                        .text(date)
+                        .json(sessions)
+                        .finish(),
+                )
+            }
+            None => None,
+        };
+        if let (Some(cache), Some(key)) = (&self.response_cache, &cache_key) {
+            if let Some(summary) = cache.get::<String>(key).await {
+                return Ok(summary);
+            }
+        }
+
+        let summary = self.provider.generate_day_summary(date, sessions).await?;
+        if let (Some(cache), Some(key)) = (&self.response_cache, &cache_key) {
+            cache.put(key, &summary).await;
+        }
+        Ok(summary)
+    }
+
+    /// 从学习会话提取学习卡片（调用LLM）
+    pub async fn generate_flashcards(
+        &self,
+        session: &SessionBrief,
+        max_cards: usize,
+    ) -> Result<Vec<Flashcard>> {
+        self.provider.generate_flashcards(session, max_cards).await
+    }
+
+    /// 重新判断时间线卡片的类别（调用LLM）
+    pub async fn categorize_cards(&self, cards: &[CardBrief]) -> Result<Vec<CardCategory>> {
+        let custom = crate::taxonomy::custom_categories();
+        self.provider.categorize_cards(cards, &custom).await
+    }
+
+    /// 按自定义类别重新判断新生成卡片的类别（空闲卡片不参与）
+    async fn apply_custom_categories(
+        &self,
+        cards: &mut [TimelineCard],
+        custom: &[plugin::CustomCategory],
+    ) -> Result<()> {
+        let briefs: Vec<CardBrief> = cards
+            .iter()
+            .enumerate()
+            .filter(|(_, card)| !card.category.eq_ignore_ascii_case("idle"))
+            .map(|(index, card)| {
+                let mut apps = vec![card.app_sites.primary.clone()];
+                apps.extend(card.app_sites.secondary.clone().unwrap_or_default());
+                CardBrief {
+                    id: index as i64,
+                    title: card.title.clone(),
+                    summary: card.summary.clone(),
+                    category: card.category.clone(),
+                    subcategory: card.subcategory.clone(),
+                    apps: apps.join(", "),
+                }
+            })
+            .collect();
+        if briefs.is_empty() {
+            return Ok(());
+        }
+
+        for result in self.provider.categorize_cards(&briefs, custom).await? {
+            if let Some(card) = cards.get_mut(result.id as usize) {
+                card.category = result.category;
+                if !result.subcategory.is_empty() {
+                    card.subcategory = result.subcategory;
+                }
+            }
+        }
+        Ok(())
     }
 
     /// 分析视频并生成时间线（两阶段处理）
@@ -415,10 +714,56 @@ impl LLMManager {
         duration: u32,
         previous_cards: Option<Vec<TimelineCard>>,
     ) -> Result<TimelineAnalysis> {
+        // OCR 上下文只用于本次分析，分段失败时也不能残留到下一个会话
+        let ocr_context = self.ocr_context.take();
         let provider_name = {
             let config = self.config_lock.read().await;
             config.provider.clone()
         };
+        let custom = crate::taxonomy::custom_categories();
+
+        // 输入帧与上下文都未变化时复用缓存的分段与时间线
+        let cache_key = match &self.response_cache {
+            Some(_) => match response_cache::frame_hashes(&frames).await {
+                Some(hashes) => {
+                    let config = self.config_lock.read().await;
+                    let ocr_texts: Vec<(i64, usize, &str)> = ocr_context
+                        .iter()
+                        .flat_map(|context| &context.texts)
+                        .map(|text| {
+                            (
+                                text.timestamp.timestamp(),
+                                text.screen_id,
+                                text.text.as_str(),
+                            )
+                        })
+                        .collect();
+                    Some(
+                        response_cache::CacheKeyBuilder::new("timeline", &config)
+                            .frames(&hashes)
+                            .text(&duration.to_string())
+                            .json(&self.session_window)
+                            .json(&self.video_speed)
+                            .json(&ocr_texts)
+                            .json(&previous_cards)
+                            .json(&custom)
+                            .finish(),
+                    )
+                }
+                None => None,
+            },
+            None => None,
+        };
+        if let (Some(cache), Some(key)) = (&self.response_cache, &cache_key) {
+            if let Some(cached) = cache.get::<response_cache::CachedTimeline>(key).await {
+                return Ok(TimelineAnalysis {
+                    segments: cached.segments,
+                    timeline_cards: cached.timeline_cards,
+                    segment_call_id: None,
+                    timeline_call_id: None,
+                });
+            }
+        }
 
         info!(
             "使用 {} 进行视频分段分析: {} 帧, 时长 {} 分钟",
@@ -443,10 +788,17 @@ impl LLMManager {
             }
         };
 
-        // 第二阶段：生成时间线
-        let timeline_cards = match self
+        // 第二阶段：生成时间线（有 OCR 文字时附加到分段描述，保存的分段保持原样）
+        let timeline_segments = match ocr_context {
+            Some(context) if !context.texts.is_empty() => {
+                info!("附加 {} 段 OCR 文字到时间线输入", context.texts.len());
+                crate::ocr::enrich_segments(&segments, &context)
+            }
+            _ => segments.clone(),
+        };
+        let mut timeline_cards = match self
             .provider
-            .generate_timeline(segments.clone(), previous_cards)
+            .generate_timeline(timeline_segments, previous_cards)
             .await
         {
             Ok(cards) => {
@@ -459,6 +811,28 @@ impl LLMManager {
             }
         };
 
+        // 有自定义类别时再判断一次卡片类别，失败不影响时间线结果
+        if !custom.is_empty() {
+            if let Err(e) = self
+                .apply_custom_categories(&mut timeline_cards, &custom)
+                .await
+            {
+                warn!("自定义类别判断失败，保留原类别: {}", e);
+            }
+        }
+
+        if let (Some(cache), Some(key)) = (&self.response_cache, &cache_key) {
+            cache
+                .put(
+                    key,
+                    &response_cache::CachedTimeline {
+                        segments: segments.clone(),
+                        timeline_cards: timeline_cards.clone(),
+                    },
+                )
+                .await;
+        }
+
         let segment_call_id = self.provider.last_llm_call_id("segment_video");
         let timeline_call_id = self.provider.last_llm_call_id("generate_timeline");
 
@@ -512,25 +886,18 @@ pub fn build_session_summary(
                 break;
             }
 
-            let category = match category_str.as_str() {
-                "work" | "coding" | "writing" | "design" | "planning" | "data_analysis" => {
-                    ActivityCategory::Work
-                }
-                "communication" | "meeting" => ActivityCategory::Communication,
-                "learning" | "research" => ActivityCategory::Learning,
-                "personal" | "entertainment" | "social_media" | "shopping" | "finance" => {
-                    ActivityCategory::Personal
-                }
-                "idle" => ActivityCategory::Idle,
-                _ => ActivityCategory::Other,
-            };
+            let category = map_category(category_str);
 
-            // 根据类别收集关键词
-            let keywords = timeline_cards
-                .iter()
-                .filter(|card| card.category.to_lowercase() == *category_str)
-                .map(|card| card.subcategory.clone())
-                .collect::<Vec<_>>();
+            // 根据类别收集关键词（自定义类别名称放在最前，以便区分归入同一内置类别的自定义类别）
+            let mut keywords = crate::taxonomy::find_custom_category(category_str)
+                .map(|custom| vec![custom.name])
+                .unwrap_or_default();
+            keywords.extend(
+                timeline_cards
+                    .iter()
+                    .filter(|card| card.category.to_lowercase() == *category_str)
+                    .map(|card| card.subcategory.clone()),
+            );
 
             tags.push(ActivityTag {
                 category,
@@ -624,7 +991,10 @@ fn parse_duration(start: &str, end: &str) -> Option<f32> {
 }
 
 // 辅助函数：映射类别
-fn map_category(category_str: &str) -> ActivityCategory {
+pub(crate) fn map_category(category_str: &str) -> ActivityCategory {
+    if let Some(category) = crate::taxonomy::custom_bucket(category_str) {
+        return category;
+    }
     match category_str.to_lowercase().as_str() {
         "work" | "coding" | "writing" | "design" | "planning" | "data_analysis" => {
             ActivityCategory::Work
@@ -723,6 +1093,7 @@ pub struct LLMProcessor {
 }
 
 /// LLM两阶段分析的聚合结果
+#[derive(Default)]
 pub struct TimelineAnalysis {
     pub segments: Vec<VideoSegment>,
     pub timeline_cards: Vec<TimelineCard>,
@@ -795,6 +1166,7 @@ impl LLMProcessor {
                         frame_count,
                         window_start,
                         window_end,
+                        title,
                     } => {
                         info!(
                             "收到会话完成事件: session_id={}, frames={}, 时间段: {} - {}",
@@ -839,15 +1211,18 @@ impl LLMProcessor {
                         let window = crate::capture::scheduler::SessionWindow {
                             start: window_start,
                             end: window_end,
+                            title,
                         };
 
                         // 执行分析
                         match self.process_session(frames, window).await {
-                            Ok(_) => {
+                            Ok(summary) => {
                                 info!("会话分析完成: session_id={}", session_id);
-                                // 注意：AnalysisCompleted事件将在未来由独立的分析流程发布
-                                // 当前process_session包含了完整的处理，包括视频生成
-                                // 这里暂时不发布AnalysisCompleted，避免重复处理
+                                // 供状态发布等订阅者使用（process_session已完成视频生成与入库）
+                                event_bus.publish(crate::event_bus::AppEvent::AnalysisCompleted {
+                                    session_id,
+                                    summary,
+                                });
                             }
                             Err(e) => {
                                 error!("会话分析失败: session_id={}, 错误: {}", session_id, e);
@@ -894,7 +1269,7 @@ impl LLMProcessor {
             }
 
             let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
-            if !extension.eq_ignore_ascii_case("jpg") {
+            if !crate::capture::format::is_frame_extension(extension) {
                 continue;
             }
 
@@ -902,7 +1277,8 @@ impl LLMProcessor {
                 continue;
             };
 
-            let Ok(timestamp_ms) = stem.parse::<i64>() else {
+            let Some((timestamp_ms, screen_id)) = crate::capture::parse_frame_file_stem(stem)
+            else {
                 continue;
             };
 
@@ -916,13 +1292,25 @@ impl LLMProcessor {
                 frames.push(crate::capture::ScreenFrame {
                     timestamp,
                     file_path: path.to_string_lossy().to_string(),
-                    screen_id: 0,
+                    screen_id,
+                    window: None,
+                    domain: None,
+                    idle_seconds: None,
+                    repeat: None,
+                    meeting: None,
+                    focus_block: None,
                 });
             }
         }
 
-        // 按时间排序
+        // 按时间排序，并关联截屏时记录的前台窗口、浏览器域名、空闲、会议与专注时段标记、被跳过的重复截图
         frames.sort_by_key(|f| f.timestamp);
+        crate::capture::window::attach_window_info(&frames_dir, &mut frames).await;
+        crate::browser::attach_domains(&frames_dir, &mut frames).await;
+        crate::capture::idle::attach_idle_marks(&frames_dir, &mut frames).await;
+        crate::capture::meeting::attach_meeting_marks(&frames_dir, &mut frames).await;
+        crate::capture::focus::attach_focus_marks(&frames_dir, &mut frames).await;
+        crate::capture::dedupe::attach_repeats(&frames_dir, &mut frames).await;
 
         info!(
             "加载了 {} 个frames用于会话分析 (session_id={})",
@@ -939,13 +1327,37 @@ impl crate::capture::scheduler::SessionProcessor for LLMProcessor {
         &self,
         frames: Vec<crate::capture::ScreenFrame>,
         window: crate::capture::scheduler::SessionWindow,
-    ) -> Result<()> {
+    ) -> Result<SessionSummary> {
         // 获取配置
         let config = self.llm_handle.get_config().await?;
         let params = &config.analysis_params;
 
+        // 按显示器分别保存时，视频与画面分析使用主显示器的帧，其他显示器通过 OCR 补充上下文
+        let primary_frames = crate::capture::primary_screen_frames(&frames);
+
         // 采样帧
-        let sampled_frames = self.sample_frames(&frames, params.frame_sampling_interval as usize);
+        let sampled_frames =
+            self.sample_frames(&primary_frames, params.frame_sampling_interval as usize);
+
+        // 预分类规则：所有活跃帧都命中规则时不调用 LLM
+        let rules = match self.db.get_classification_rules().await {
+            Ok(records) => crate::capture::rules::ClassificationRule::compile(&records),
+            Err(e) => {
+                error!("加载预分类规则失败: {}", e);
+                Vec::new()
+            }
+        };
+        let rule_only = crate::capture::rules::fully_classified(&rules, &primary_frames);
+
+        // 未配置 AI 提供商时改用本地启发式分类
+        let heuristic_config = self
+            .settings
+            .get()
+            .await
+            .heuristic_config
+            .unwrap_or_default();
+        let llm_configured = self.llm_handle.is_configured().await.unwrap_or(true);
+        let mut use_heuristic = !rule_only && !llm_configured && heuristic_config.enabled;
 
         // 提取文件路径
         let frame_paths: Vec<String> = sampled_frames.iter().map(|f| f.file_path.clone()).collect();
@@ -954,26 +1366,76 @@ impl crate::capture::scheduler::SessionProcessor for LLMProcessor {
         let duration = window.end - window.start;
         let duration_minutes = (duration.num_seconds().max(0) as f64 / 60.0).ceil() as u32;
 
-        // 提取所有帧路径用于视频生成
+        // 提取所有帧路径用于视频生成后清理，视频本身只使用主显示器的帧，
+        // 被跳过的重复截图以保留帧补齐
         let all_frame_paths: Vec<String> = frames.iter().map(|f| f.file_path.clone()).collect();
+        let video_frame_paths = crate::capture::dedupe::expand_repeats(&primary_frames);
+
+        // OCR 识别各显示器的采样帧（需在生成视频删除原图之前完成）
+        let ocr_texts = {
+            let ocr_config = self.settings.get().await.ocr_config.unwrap_or_default();
+            match crate::ocr::OcrEngine::from_config(&ocr_config) {
+                Some(engine) if !rule_only => {
+                    let mut ocr_frames = sampled_frames.clone();
+                    for screen_frames in crate::capture::secondary_screen_frames(&frames) {
+                        ocr_frames.extend(self.sample_frames(
+                            &screen_frames,
+                            params.frame_sampling_interval as usize,
+                        ));
+                    }
+                    engine.recognize_frames(&ocr_frames).await
+                }
+                _ => Vec::new(),
+            }
+        };
+
+        // 帧预处理：挑选代表帧并缩放/拼图（需在生成视频删除原图之前完成），失败时回退为原图
+        let frame_prep_config = self
+            .settings
+            .get()
+            .await
+            .frame_prep_config
+            .unwrap_or_default();
+        let prepared_frames = if frame_prep_config.enabled && !rule_only && !use_heuristic {
+            // 通义千问视频模式按帧序列理解画面，不拼图
+            let allow_grid = !(config.provider == "openai" && config.qwen.use_video_mode);
+            match crate::frame_prep::prepare_frames(
+                &frame_prep_config,
+                &config.provider,
+                &sampled_frames,
+                allow_grid,
+            )
+            .await
+            {
+                Ok(prepared) => Some(prepared),
+                Err(e) => {
+                    warn!("帧预处理失败，使用原始采样帧: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
 
         // 先生成视频（如果配置了视频处理器）
         let mut video_path = None;
         let mut should_persist_frames = true;
         if let Some(ref video_processor) = self.video_processor {
             let app_config = self.settings.get().await;
-            if app_config.video_config.auto_generate {
+            if app_config.video_config.auto_generate && crate::frame_crypto::is_enabled() {
+                info!("已启用截图加密，跳过视频生成");
+            } else if app_config.video_config.auto_generate {
                 info!("自动生成会话视频...");
 
                 // 应用帧过滤：每5秒选择一张图片（假设原始截图是1fps）
                 let filtered_frame_paths = crate::video::filter_frames_by_interval(
-                    all_frame_paths.clone(),
+                    video_frame_paths.clone(),
                     5, // 每5秒取一帧
                 );
 
                 info!(
                     "视频抽帧：原始 {} 帧，抽样后 {} 帧（每5秒取一帧）",
-                    all_frame_paths.len(),
+                    video_frame_paths.len(),
                     filtered_frame_paths.len()
                 );
 
@@ -1057,14 +1519,43 @@ impl crate::capture::scheduler::SessionProcessor for LLMProcessor {
         let speed_multiplier = app_config.video_config.speed_multiplier;
         self.llm_handle.set_video_speed(speed_multiplier).await?;
 
-        // 使用两阶段分析：先分段，再生成时间线
-        let analysis = {
+        // 设置 OCR 上下文，供生成时间线时参考屏幕文字
+        let ocr_context = (!ocr_texts.is_empty()).then(|| crate::ocr::OcrContext {
+            window_start: window.start,
+            texts: ocr_texts.clone(),
+        });
+        self.llm_handle.set_ocr_context(ocr_context).await?;
+
+        // 使用两阶段分析：先分段，再生成时间线（完全按规则归类时跳过，卡片在下方按规则生成）
+        let analysis = if rule_only || use_heuristic {
+            if rule_only {
+                info!("所有截图帧均命中预分类规则，跳过 AI 分析");
+            } else {
+                info!("未配置 AI 提供商，使用本地启发式分类");
+            }
+            TimelineAnalysis::default()
+        } else {
             match self
                 .llm_handle
-                .segment_video_and_generate_timeline(frame_paths, duration_minutes, None)
+                .segment_video_and_generate_timeline(
+                    prepared_frames
+                        .as_ref()
+                        .map(|prepared| prepared.paths.clone())
+                        .unwrap_or(frame_paths),
+                    duration_minutes,
+                    None,
+                )
                 .await
             {
                 Ok(result) => result,
+                Err(e)
+                    if heuristic_config.on_llm_error
+                        && !e.to_string().contains("VIDEO_TOO_SHORT") =>
+                {
+                    warn!("AI 分析失败，回退为本地启发式分类: {}", e);
+                    use_heuristic = true;
+                    TimelineAnalysis::default()
+                }
                 Err(e) => {
                     // 如果是视频过短错误，清理已创建的资源
                     if e.to_string().contains("VIDEO_TOO_SHORT") {
@@ -1148,6 +1639,44 @@ impl crate::capture::scheduler::SessionProcessor for LLMProcessor {
             }
         }
 
+        // 本地启发式分类按帧生成卡片，之后仍由预分类规则、空闲与会议检测覆盖
+        if use_heuristic {
+            timeline_cards = crate::capture::heuristic::heuristic_cards(
+                &primary_frames,
+                &ocr_texts,
+                window.start,
+                window.end,
+            );
+            info!("本地启发式分类生成 {} 张时间线卡片", timeline_cards.len());
+        }
+
+        // 命中预分类规则的时段按规则生成卡片
+        let rule_periods =
+            crate::capture::rules::rule_periods(&rules, &primary_frames, window.start, window.end);
+        if rule_only {
+            timeline_cards = crate::capture::rules::rule_cards(&rules, &rule_periods);
+        } else if !rule_periods.is_empty() {
+            info!("{} 段时段命中预分类规则", rule_periods.len());
+            timeline_cards =
+                crate::capture::rules::mark_rule_cards(timeline_cards, &rules, &rule_periods);
+        }
+
+        // 无键鼠输入的时段改记为空闲，避免离开时屏幕内容被计入专注时长
+        let idle_periods = crate::capture::idle::idle_periods(&frames, window.start, window.end);
+        if !idle_periods.is_empty() {
+            info!("检测到 {} 段离开时段，标记为空闲", idle_periods.len());
+            timeline_cards = crate::capture::idle::mark_idle_cards(timeline_cards, &idle_periods);
+        }
+
+        // 标记为会议的时段以会议卡片代替屏幕内容
+        let meeting_periods =
+            crate::capture::meeting::meeting_periods(&frames, window.start, window.end);
+        if !meeting_periods.is_empty() {
+            info!("检测到 {} 段会议时段，标记为会议", meeting_periods.len());
+            timeline_cards =
+                crate::capture::meeting::mark_meeting_cards(timeline_cards, &meeting_periods);
+        }
+
         // 保存segments到数据库
         if !segments.is_empty() {
             let segment_records: Vec<crate::storage::VideoSegmentRecord> = segments
@@ -1188,6 +1717,8 @@ impl crate::capture::scheduler::SessionProcessor for LLMProcessor {
                             .as_ref()
                             .map(|d| serde_json::to_string(d).unwrap_or_default()),
                         app_sites: serde_json::to_string(&card.app_sites).unwrap_or_default(),
+                        confidence: card.confidence.map(f64::from),
+                        heuristic: card.heuristic,
                         video_preview_path: video_path.clone(), // 使用已生成的视频路径
                         created_at: crate::storage::local_now(),
                     }
@@ -1199,7 +1730,12 @@ impl crate::capture::scheduler::SessionProcessor for LLMProcessor {
         }
 
         // 从timeline卡片生成总结（使用第一个卡片的信息）
-        let summary = build_session_summary(window.start, window.end, &segments, &timeline_cards);
+        let mut summary =
+            build_session_summary(window.start, window.end, &segments, &timeline_cards);
+        // 手动会话使用自定义标题
+        if let Some(title) = &window.title {
+            summary.title = title.clone();
+        }
 
         // 更新会话信息（之前已经创建了临时会话）
         self.db
@@ -1221,12 +1757,53 @@ impl crate::capture::scheduler::SessionProcessor for LLMProcessor {
                     session_id,
                     timestamp: f.timestamp,
                     file_path: f.file_path.clone(),
+                    app_name: f.window.as_ref().map(|w| w.app_name.clone()),
+                    window_title: f.window.as_ref().map(|w| w.window_title.clone()),
+                    process_path: f.window.as_ref().and_then(|w| w.process_path.clone()),
+                    domain: f.domain.clone(),
+                    screen_id: f.screen_id as i64,
                 })
                 .collect();
 
             self.db.insert_frames(&db_frames).await?;
         }
 
+        // 保存各前台应用的使用时长（不依赖原图是否保留）
+        let app_usage = crate::capture::window::app_breakdown(session_id, &frames, window.end);
+        if !app_usage.is_empty() {
+            if let Err(e) = self.db.replace_app_usage(session_id, &app_usage).await {
+                error!("保存应用使用时长失败: {}", e);
+            }
+        }
+
+        // 保存各网站域名的访问时长
+        let domain_usage = crate::browser::domain_breakdown(session_id, &frames, window.end);
+        if !domain_usage.is_empty() {
+            if let Err(e) = self
+                .db
+                .replace_domain_usage(session_id, &domain_usage)
+                .await
+            {
+                error!("保存域名访问时长失败: {}", e);
+            }
+        }
+
+        // 保存 OCR 文字（不依赖原图是否保留）
+        if !ocr_texts.is_empty() {
+            let frame_texts: Vec<crate::storage::FrameText> = ocr_texts
+                .into_iter()
+                .map(|ocr| crate::storage::FrameText {
+                    id: None,
+                    session_id,
+                    timestamp: ocr.timestamp,
+                    text: ocr.text,
+                })
+                .collect();
+            if let Err(e) = self.db.insert_frame_texts(&frame_texts).await {
+                error!("保存 OCR 文字失败: {}", e);
+            }
+        }
+
         info!(
             "会话已保存到数据库: ID={}, 标题={}",
             session_id, summary.title
@@ -1245,7 +1822,7 @@ impl crate::capture::scheduler::SessionProcessor for LLMProcessor {
 
         // 清理provider的视频路径，避免影响后续会话
         self.llm_handle.set_video_path(None).await?;
-        Ok(())
+        Ok(summary)
     }
 }
 
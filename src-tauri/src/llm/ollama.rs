@@ -0,0 +1,613 @@
+// Ollama 提供商实现 - 调用本地 Ollama 的 /api/chat 接口（支持 llava、qwen2.5vl 等视觉模型），
+// 截图不离开本机，无需云端 API Key
+
+use super::plugin::*;
+use super::structured::{self, ParseContext};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use llm_json::{loads, repair_json, RepairOptions};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+/// 默认 Ollama 地址
+pub const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
+
+/// 默认视觉模型
+pub const DEFAULT_OLLAMA_MODEL: &str = "qwen2.5vl";
+
+/// Ollama 提供商
+pub struct OllamaProvider {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    max_images: usize,
+    timeout_secs: u64,
+    db: Option<Arc<crate::storage::Database>>,
+    current_session_id: Option<i64>,
+    last_call_ids: Mutex<HashMap<String, i64>>,
+    session_window_start: Option<DateTime<Utc>>,
+    session_window_end: Option<DateTime<Utc>>,
+}
+
+/// /api/chat 非流式响应
+#[derive(Debug, Deserialize)]
+struct OllamaChatResponse {
+    message: OllamaMessage,
+    /// 输入 token 数
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    /// 输出 token 数
+    #[serde(default)]
+    eval_count: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaMessage {
+    #[serde(default)]
+    content: String,
+}
+
+impl OllamaChatResponse {
+    /// 换算为与其他提供商一致的 token 用量格式
+    fn token_usage(&self) -> Option<TokenUsage> {
+        if self.prompt_eval_count.is_none() && self.eval_count.is_none() {
+            return None;
+        }
+        Some(TokenUsage::new(
+            self.prompt_eval_count.unwrap_or(0),
+            self.eval_count.unwrap_or(0),
+        ))
+    }
+}
+
+impl OllamaProvider {
+    /// 创建新的 Ollama 提供商
+    pub fn new(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            base_url: DEFAULT_OLLAMA_URL.to_string(),
+            model: DEFAULT_OLLAMA_MODEL.to_string(),
+            max_images: 8,
+            timeout_secs: 600,
+            db: None,
+            current_session_id: None,
+            last_call_ids: Mutex::new(HashMap::new()),
+            session_window_start: None,
+            session_window_end: None,
+        }
+    }
+
+    /// 设置数据库连接
+    pub fn set_database(&mut self, db: Arc<crate::storage::Database>) {
+        self.db = Some(db);
+    }
+
+    /// 设置当前会话 ID
+    pub fn set_session_id(&mut self, session_id: i64) {
+        self.current_session_id = Some(session_id);
+    }
+
+    /// 结构化响应校验的上下文
+    fn parse_context(&self) -> ParseContext<'_> {
+        ParseContext {
+            db: self.db.as_ref(),
+            session_id: self.current_session_id,
+            provider: "ollama",
+            model: &self.model,
+        }
+    }
+
+    fn reset_call_id(&self, call_type: &str) {
+        if let Ok(mut map) = self.last_call_ids.lock() {
+            map.remove(call_type);
+        }
+    }
+
+    fn record_call_id(&self, call_type: &str, id: i64) {
+        if let Ok(mut map) = self.last_call_ids.lock() {
+            map.insert(call_type.to_string(), id);
+        }
+    }
+
+    /// 均匀采样不超过 max_images 张图片（本地模型上下文有限）
+    fn sample_frames(&self, frames: &[String]) -> Vec<String> {
+        if frames.len() <= self.max_images {
+            return frames.to_vec();
+        }
+
+        let step = frames.len() as f32 / self.max_images as f32;
+        (0..self.max_images)
+            .map(|i| {
+                let index = ((i as f32 + 0.5) * step).floor() as usize;
+                frames[index.min(frames.len() - 1)].clone()
+            })
+            .collect()
+    }
+
+    /// 读取图片并编码为 base64（读取失败的图片跳过）
+    async fn encode_images(&self, frames: &[String]) -> Vec<String> {
+        let mut images = Vec::new();
+        for path in self.sample_frames(frames) {
+            match crate::frame_crypto::read_frame(&path).await {
+                Ok(bytes) => images.push(general_purpose::STANDARD.encode(bytes)),
+                Err(e) => warn!("读取图片失败 {}: {}", path, e),
+            }
+        }
+        images
+    }
+
+    /// 调用 /api/chat（非流式），记录调用并返回模型输出
+    async fn chat(&self, prompt: &str, images: Vec<String>, call_type: &str) -> Result<String> {
+        self.reset_call_id(call_type);
+
+        let mut message = json!({ "role": "user", "content": prompt });
+        if !images.is_empty() {
+            message["images"] = json!(images);
+        }
+        let request_body = json!({
+            "model": self.model,
+            "messages": [message],
+            "stream": false,
+            "options": { "temperature": 0.3 }
+        });
+
+        let mut record = crate::storage::LLMCallRecord {
+            id: None,
+            session_id: self.current_session_id,
+            provider: "ollama".to_string(),
+            model: self.model.clone(),
+            call_type: call_type.to_string(),
+            request_headers: json!({ "Content-Type": "application/json" }).to_string(),
+            request_body: crate::llm::sanitize_request_body(&request_body),
+            response_headers: None,
+            response_body: None,
+            status_code: None,
+            error_message: None,
+            latency_ms: None,
+            token_usage: None,
+            created_at: crate::storage::local_now(),
+        };
+
+        let start = Instant::now();
+        let result = self
+            .client
+            .post(chat_endpoint(&self.base_url))
+            .timeout(Duration::from_secs(self.timeout_secs))
+            .json(&request_body)
+            .send()
+            .await;
+        let response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                record.error_message = Some(e.to_string());
+                record.latency_ms = Some(start.elapsed().as_millis() as i64);
+                self.save_record(&record, call_type, false).await;
+                return Err(anyhow!(
+                    "无法连接 Ollama（{}），请确认已启动 ollama serve: {}",
+                    self.base_url,
+                    e
+                ));
+            }
+        };
+
+        let status = response.status();
+        record.status_code = Some(status.as_u16() as i32);
+        let text = response.text().await?;
+        record.latency_ms = Some(start.elapsed().as_millis() as i64);
+
+        if !status.is_success() {
+            error!("Ollama API错误: {}", text);
+            record.error_message = Some(text.clone());
+            self.save_record(&record, call_type, false).await;
+            return Err(anyhow!("Ollama API调用失败 ({}): {}", status, text));
+        }
+
+        let parsed: OllamaChatResponse =
+            serde_json::from_str(&text).map_err(|e| anyhow!("Ollama 响应格式错误: {}", e))?;
+        record.response_body = Some(text);
+        record.token_usage = parsed.token_usage().map(|usage| usage.to_record());
+        self.save_record(&record, call_type, true).await;
+
+        Ok(parsed.message.content)
+    }
+
+    async fn save_record(
+        &self,
+        record: &crate::storage::LLMCallRecord,
+        call_type: &str,
+        success: bool,
+    ) {
+        let Some(db) = &self.db else {
+            return;
+        };
+        match db.insert_llm_call(record).await {
+            Ok(id) if success => self.record_call_id(call_type, id),
+            Ok(_) => {}
+            Err(e) => error!("保存LLM调用记录失败: {}", e),
+        }
+    }
+
+    /// 执行纯文本提示词（无图），用于连接测试
+    pub async fn run_text_prompt(&self, prompt: &str, call_type: &str) -> Result<String> {
+        self.chat(prompt, Vec::new(), call_type).await
+    }
+
+    fn segment_prompt(duration: u32) -> String {
+        format!(
+            r#"这些图片是用户屏幕按时间顺序的截图，总时长约 {duration} 分钟。
+请把这段时间划分为 2-6 个活动段落，只在主要活动明显变化时切分，覆盖整个时间范围。
+每个段落用 1-2 句中文描述用户在做什么（应用、网站、文件或主题）。
+
+只返回 JSON 数组，时间格式 MM:SS：
+[{{"startTimestamp": "00:00", "endTimestamp": "05:00", "description": "描述"}}]"#,
+            duration = duration
+        )
+    }
+
+    fn timeline_prompt(
+        segments: &[VideoSegment],
+        previous_cards: &Option<Vec<TimelineCard>>,
+    ) -> Result<String> {
+        let previous = match previous_cards {
+            Some(cards) if !cards.is_empty() => format!(
+                "\n之前的卡片（可与相邻且主题一致的活动合并）：\n{}\n",
+                serde_json::to_string(cards)?
+            ),
+            _ => String::new(),
+        };
+        Ok(format!(
+            r#"根据以下屏幕活动分段生成时间线卡片（中文），合并相邻且主题一致的分段。
+{previous}
+分段：
+{segments}
+
+category 只能是 work / communication / learning / personal / idle / other。
+confidence 为 0-1，表示对 category 判断的把握，画面信息不足或活动混杂时给较低的值。
+只返回 JSON 数组，每张卡片字段如下：
+[{{"startTime": "00:00", "endTime": "05:00", "category": "work", "confidence": 0.9, "subcategory": "Development",
+  "title": "标题", "summary": "一句话摘要", "detailedSummary": "详细描述",
+  "distractions": [], "appSites": {{"primary": "主要应用或网站", "secondary": []}}}}]"#,
+            previous = previous,
+            segments = serde_json::to_string_pretty(segments)?
+        ))
+    }
+
+    fn summary_prompt() -> &'static str {
+        r#"分析这些屏幕截图，总结用户在这段时间完成了什么。只返回 JSON：
+{"title": "活动标题", "summary": "详细描述",
+ "tags": [{"category": "work", "confidence": 0.8, "keywords": ["关键字"]}],
+ "productivity_score": 75, "focus_score": 80}
+category 只能是 work / communication / learning / personal / idle / other，文字使用中文。"#
+    }
+
+    fn day_summary_prompt(date: &str, sessions: &[SessionBrief]) -> String {
+        let lines = sessions
+            .iter()
+            .map(|session| {
+                format!(
+                    "- {} - {}: {} —— {}",
+                    session.start_time.format("%H:%M"),
+                    session.end_time.format("%H:%M"),
+                    session.title,
+                    session.summary
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!(
+            "基于以下 {} 个会话，用 150 字以内的中文概括 {} 的主要工作，重点描述完成的事情与未完成事项，直接输出正文：\n{}",
+            sessions.len(),
+            date,
+            lines
+        )
+    }
+
+    fn fallback_timeline(segments: &[VideoSegment]) -> Vec<TimelineCard> {
+        segments
+            .iter()
+            .map(|segment| TimelineCard {
+                start_time: segment.start_timestamp.clone(),
+                end_time: segment.end_timestamp.clone(),
+                category: "other".to_string(),
+                subcategory: "General".to_string(),
+                title: "主要活动".to_string(),
+                summary: segment.description.clone(),
+                detailed_summary: segment.description.clone(),
+                distractions: None,
+                app_sites: AppSites {
+                    primary: "unknown".to_string(),
+                    secondary: None,
+                },
+                confidence: None,
+                heuristic: false,
+                video_preview_path: None,
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl LLMProvider for OllamaProvider {
+    fn as_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    async fn analyze_frames(&self, frames: Vec<String>) -> Result<SessionSummary> {
+        let images = self.encode_images(&frames).await;
+        if images.is_empty() {
+            return Err(anyhow!("没有可分析的帧图像"));
+        }
+
+        let response = self
+            .chat(Self::summary_prompt(), images, "analyze_frames")
+            .await?;
+        let payload: SummaryPayload = parse_json(&response)?;
+
+        let now = crate::storage::local_now();
+        Ok(SessionSummary {
+            title: payload.title.unwrap_or_else(|| "未命名会话".to_string()),
+            summary: payload.summary.unwrap_or_default(),
+            tags: payload
+                .tags
+                .into_iter()
+                .map(|tag| ActivityTag {
+                    category: crate::llm::map_category(&tag.category),
+                    confidence: tag.confidence.unwrap_or(0.5).clamp(0.0, 1.0),
+                    keywords: tag.keywords,
+                })
+                .collect(),
+            start_time: self
+                .session_window_start
+                .unwrap_or_else(|| now - ChronoDuration::minutes(15)),
+            end_time: self.session_window_end.unwrap_or(now),
+            key_moments: Vec::new(),
+            productivity_score: payload.productivity_score,
+            focus_score: payload.focus_score,
+        })
+    }
+
+    async fn segment_video(&self, frames: Vec<String>, duration: u32) -> Result<Vec<VideoSegment>> {
+        let images = self.encode_images(&frames).await;
+        if images.is_empty() {
+            return Err(anyhow!("没有可分析的帧图像"));
+        }
+
+        info!("使用 Ollama ({}) 分析 {} 张截图", self.model, images.len());
+        let response = self
+            .chat(&Self::segment_prompt(duration), images, "segment_video")
+            .await?;
+        let segments: Vec<VideoSegment> = parse_json(&response)?;
+        if segments.is_empty() {
+            warn!("Ollama 未返回分段信息，使用兜底结果");
+            return Ok(vec![VideoSegment {
+                start_timestamp: "00:00".to_string(),
+                end_timestamp: format!("{:02}:00", duration),
+                description: "基于截图生成的兜底描述".to_string(),
+            }]);
+        }
+        Ok(segments)
+    }
+
+    async fn generate_timeline(
+        &self,
+        segments: Vec<VideoSegment>,
+        previous_cards: Option<Vec<TimelineCard>>,
+    ) -> Result<Vec<TimelineCard>> {
+        let prompt = Self::timeline_prompt(&segments, &previous_cards)?;
+        let response = self.chat(&prompt, Vec::new(), "generate_timeline").await?;
+
+        // 未通过结构校验时请模型修复一次，仍失败再使用回退结果
+        let cards = structured::parse_timeline(
+            &response,
+            &self.parse_context(),
+            |prompt, call_type| async move { self.chat(&prompt, Vec::new(), call_type).await },
+        )
+        .await;
+        match cards {
+            Ok(cards) => Ok(cards),
+            Err(e) => {
+                warn!("解析 Ollama 时间线失败，使用回退结果: {}", e);
+                Ok(Self::fallback_timeline(&segments))
+            }
+        }
+    }
+
+    fn set_session_window(&mut self, start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>) {
+        self.session_window_start = start;
+        self.session_window_end = end;
+    }
+
+    fn name(&self) -> &str {
+        "Ollama"
+    }
+
+    fn configure(&mut self, config: Value) -> Result<()> {
+        if let Some(base_url) = config.get("base_url").and_then(|v| v.as_str()) {
+            let base_url = base_url.trim().trim_end_matches('/');
+            self.base_url = if base_url.is_empty() {
+                DEFAULT_OLLAMA_URL.to_string()
+            } else {
+                base_url.to_string()
+            };
+        }
+
+        if let Some(model) = config.get("model").and_then(|v| v.as_str()) {
+            if !model.trim().is_empty() {
+                self.model = model.trim().to_string();
+            }
+        }
+
+        if let Some(max_images) = config.get("max_images").and_then(|v| v.as_u64()) {
+            if max_images > 0 {
+                self.max_images = max_images.min(32) as usize;
+            }
+        }
+
+        if let Some(timeout) = config.get("timeout_secs").and_then(|v| v.as_u64()) {
+            if timeout >= 30 {
+                self.timeout_secs = timeout;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_configured(&self) -> bool {
+        !self.model.is_empty()
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            vision_support: true,
+            batch_analysis: false,
+            streaming: false,
+            max_input_tokens: 32000,
+            supported_image_formats: vec!["jpg".to_string(), "jpeg".to_string(), "png".to_string()],
+        }
+    }
+
+    fn last_llm_call_id(&self, call_type: &str) -> Option<i64> {
+        self.last_call_ids
+            .lock()
+            .ok()
+            .and_then(|map| map.get(call_type).copied())
+    }
+
+    async fn generate_day_summary(&self, date: &str, sessions: &[SessionBrief]) -> Result<String> {
+        if sessions.is_empty() {
+            return Ok(format!("{} 当天没有记录到任何屏幕活动。", date));
+        }
+
+        let response = self
+            .chat(
+                &Self::day_summary_prompt(date, sessions),
+                Vec::new(),
+                "generate_day_summary",
+            )
+            .await?;
+        Ok(response.trim().to_string())
+    }
+
+    async fn generate_flashcards(
+        &self,
+        session: &SessionBrief,
+        max_cards: usize,
+    ) -> Result<Vec<Flashcard>> {
+        let response = self
+            .chat(
+                &flashcard_prompt(session, max_cards),
+                Vec::new(),
+                "generate_flashcards",
+            )
+            .await?;
+        parse_flashcards(&response)
+    }
+
+    async fn categorize_cards(
+        &self,
+        cards: &[CardBrief],
+        custom: &[CustomCategory],
+    ) -> Result<Vec<CardCategory>> {
+        let response = self
+            .chat(
+                &categorize_prompt(cards, custom),
+                Vec::new(),
+                "categorize_cards",
+            )
+            .await?;
+        structured::parse_categories(
+            &response,
+            custom,
+            &self.parse_context(),
+            |prompt, call_type| async move { self.chat(&prompt, Vec::new(), call_type).await },
+        )
+        .await
+    }
+}
+
+/// 接口地址：兼容填写根地址或 /api/chat 完整地址
+fn chat_endpoint(base_url: &str) -> String {
+    let base = base_url.trim().trim_end_matches('/');
+    if base.ends_with("/api/chat") {
+        base.to_string()
+    } else {
+        format!("{}/api/chat", base.trim_end_matches("/api"))
+    }
+}
+
+/// 解析模型输出的 JSON（本地模型常带代码块或格式瑕疵，先尝试修复）
+fn parse_json<T: DeserializeOwned>(raw: &str) -> Result<T> {
+    let trimmed = raw.trim();
+    let cleaned = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .map(|body| body.trim_end().trim_end_matches("```").trim())
+        .unwrap_or(trimmed);
+    if cleaned.is_empty() {
+        return Err(anyhow!("Ollama 没有返回内容"));
+    }
+
+    if let Ok(value) = serde_json::from_str::<T>(cleaned) {
+        return Ok(value);
+    }
+    let repaired = repair_json(cleaned, &RepairOptions::default())
+        .map_err(|e| anyhow!("无法修复 Ollama 返回的 JSON: {}", e))?;
+    let value = loads(&repaired, &RepairOptions::default())
+        .map_err(|e| anyhow!("解析修复后的 JSON 失败: {}", e))?;
+    serde_json::from_value(value).map_err(|e| anyhow!("JSON 结构不符合预期: {}", e))
+}
+
+#[derive(Debug, Deserialize)]
+struct SummaryPayload {
+    title: Option<String>,
+    summary: Option<String>,
+    #[serde(default)]
+    tags: Vec<TagPayload>,
+    productivity_score: Option<f32>,
+    focus_score: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TagPayload {
+    category: String,
+    confidence: Option<f32>,
+    #[serde(default)]
+    keywords: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ollama_response() {
+        assert_eq!(
+            chat_endpoint("http://localhost:11434/"),
+            "http://localhost:11434/api/chat"
+        );
+        assert_eq!(
+            chat_endpoint("http://gpu-box:11434/api"),
+            "http://gpu-box:11434/api/chat"
+        );
+        assert_eq!(
+            chat_endpoint("http://gpu-box:11434/api/chat"),
+            "http://gpu-box:11434/api/chat"
+        );
+
+        let response: OllamaChatResponse = serde_json::from_str(
+            r#"{"model":"qwen2.5vl","message":{"role":"assistant","content":"```json\n[{\"startTimestamp\":\"00:00\",\"endTimestamp\":\"03:00\",\"description\":\"编写代码\"}]\n```"},"done":true,"prompt_eval_count":1200,"eval_count":80}"#,
+        )
+        .unwrap();
+        assert_eq!(response.token_usage().unwrap().total_tokens, 1280);
+        let segments: Vec<VideoSegment> = parse_json(&response.message.content).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].description, "编写代码");
+    }
+}
@@ -1,6 +1,6 @@
 // LLM插件系统 - 定义提供商接口和数据结构
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{
@@ -70,7 +70,7 @@ pub struct AppSites {
     /// 主要应用/网站
     pub primary: String,
     /// 次要应用/网站列表
-    #[serde(deserialize_with = "deserialize_secondary_apps")]
+    #[serde(default, deserialize_with = "deserialize_secondary_apps")]
     pub secondary: Option<Vec<String>>,
 }
 
@@ -100,11 +100,29 @@ pub struct TimelineCard {
     /// 使用的应用和网站
     #[serde(rename = "appSites")]
     pub app_sites: AppSites,
+    /// 类别判断的置信度（0-1），低于阈值的卡片进入复核队列
+    #[serde(
+        default,
+        deserialize_with = "deserialize_confidence",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub confidence: Option<f32>,
+    /// 由本地启发式分类生成（未调用 AI）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub heuristic: bool,
     /// 视频预览路径（本地视频文件）
     #[serde(rename = "videoPreviewPath", skip_serializing_if = "Option::is_none")]
     pub video_preview_path: Option<String>,
 }
 
+/// 合并两张卡片时取较低的置信度
+pub fn lower_confidence(a: Option<f32>, b: Option<f32>) -> Option<f32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
 /// 归一化时间线卡片中的字段（主要处理distractions字符串场景）
 pub(crate) fn normalize_timeline_cards_value(value: &mut Value) {
     match value {
@@ -193,6 +211,23 @@ fn normalize_distractions_value(value: Value) -> Value {
     }
 }
 
+/// 置信度兼容数字或数字字符串，百分制换算为 0-1
+fn deserialize_confidence<'de, D>(deserializer: D) -> Result<Option<f32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<Value>::deserialize(deserializer)?;
+    let number = match value {
+        Some(Value::Number(number)) => number.as_f64(),
+        Some(Value::String(text)) => text.trim().trim_end_matches('%').parse::<f64>().ok(),
+        _ => None,
+    };
+    Ok(number
+        .filter(|value| value.is_finite())
+        .map(|value| if value > 1.0 { value / 100.0 } else { value })
+        .map(|value| value.clamp(0.0, 1.0) as f32))
+}
+
 fn deserialize_secondary_apps<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
 where
     D: Deserializer<'de>,
@@ -476,6 +511,94 @@ mod tests {
         assert_eq!(distractions.len(), 1);
         assert!(distractions[0].summary.contains("查阅Google"));
     }
+
+    #[test]
+    fn test_parse_flashcards() {
+        let text = r#"以下是卡片：
+```json
+[
+  {"question": "Rust 中 Arc 的作用？", "answer": "线程安全的引用计数指针"},
+  {"question": "  ", "answer": "空问题会被丢弃"},
+  {"question": "借用检查在什么阶段进行？", "answer": "编译期"}
+]
+```"#;
+        let cards = parse_flashcards(text).unwrap();
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].question, "Rust 中 Arc 的作用？");
+        assert_eq!(cards[1].answer, "编译期");
+
+        assert!(parse_flashcards("没有卡片").is_err());
+    }
+
+    #[test]
+    fn test_parse_card_categories() {
+        let text = r#"```json
+[{"id": 3, "category": " Learning ", "subcategory": "Docs"},
+ {"id": 4, "category": "gaming"},
+ {"id": 5, "category": "work"}]
+```"#;
+        let categories = parse_card_categories(text, &[]).unwrap();
+        assert_eq!(
+            categories,
+            vec![
+                CardCategory {
+                    id: 3,
+                    category: "learning".to_string(),
+                    subcategory: "Docs".to_string(),
+                },
+                CardCategory {
+                    id: 5,
+                    category: "work".to_string(),
+                    subcategory: String::new(),
+                },
+            ]
+        );
+        assert!(parse_card_categories("无法分类", &[]).is_err());
+
+        let custom = vec![CustomCategory {
+            name: "Client A".to_string(),
+            bucket: "work".to_string(),
+            description: "A 公司咨询项目".to_string(),
+            subcategories: vec!["Workshop".to_string()],
+        }];
+        let categories = parse_card_categories(
+            r#"[{"id": 1, "category": "client a"}, {"id": 2, "category": "Client B"}]"#,
+            &custom,
+        )
+        .unwrap();
+        assert_eq!(categories.len(), 1);
+        assert_eq!(categories[0].category, "Client A");
+        assert!(categorize_prompt(&[], &custom)
+            .contains("- Client A（归入 work）：A 公司咨询项目；子类别：Workshop"));
+    }
+
+    #[test]
+    fn test_token_usage() {
+        let claude = TokenUsage::from_anthropic(&json!({
+            "input_tokens": 12,
+            "cache_creation_input_tokens": 300,
+            "cache_read_input_tokens": 1500,
+            "output_tokens": 250
+        }))
+        .unwrap();
+        assert_eq!(claude.prompt_tokens, 1812);
+        assert_eq!(claude.total_tokens, 2062);
+        assert_eq!(claude.cached_tokens, Some(1500));
+
+        let gemini = TokenUsage::from_gemini(&json!({
+            "promptTokenCount": 2600,
+            "candidatesTokenCount": 400,
+            "thoughtsTokenCount": 120,
+            "totalTokenCount": 3120
+        }))
+        .unwrap();
+        assert_eq!(gemini.completion_tokens, 520);
+        assert_eq!(gemini.total_tokens, 3120);
+        assert_eq!(gemini.reasoning_tokens, Some(120));
+        assert!(!gemini.to_record().contains("cached_tokens"));
+
+        assert!(TokenUsage::from_gemini(&json!({})).is_none());
+    }
 }
 
 impl ActivityCategory {
@@ -528,6 +651,190 @@ pub struct SessionBrief {
     pub summary: String,
 }
 
+/// 学习卡片（问答形式，用于导出到 Anki）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Flashcard {
+    /// 问题（正面）
+    pub question: String,
+    /// 答案（背面）
+    pub answer: String,
+}
+
+/// 从学习会话提取学习卡片的提示词（各提供商共用）
+pub fn flashcard_prompt(session: &SessionBrief, max_cards: usize) -> String {
+    format!(
+        r#"以下是一段学习会话的屏幕活动记录：
+
+标题: {}
+时长: {} 分钟
+摘要:
+{}
+
+请从中提取最多 {} 个值得记忆的关键知识点，做成间隔重复用的问答卡片。
+
+要求：
+1. 只基于摘要中出现的具体内容，不要编造
+2. 问题简洁明确，答案简短（一两句话）
+3. 使用与摘要相同的语言
+4. 没有可提取的知识点时返回空数组
+
+请只返回 JSON 数组，格式: [{{"question": "问题", "answer": "答案"}}]"#,
+        session.title,
+        (session.end_time - session.start_time).num_minutes(),
+        session.summary,
+        max_cards
+    )
+}
+
+/// 解析模型返回的学习卡片 JSON 数组（容忍代码块包裹与前后说明文字，丢弃空卡片）
+pub fn parse_flashcards(text: &str) -> Result<Vec<Flashcard>> {
+    let start = text.find('[');
+    let end = text.rfind(']');
+    let json = match (start, end) {
+        (Some(start), Some(end)) if start < end => &text[start..=end],
+        _ => return Err(anyhow!("未找到学习卡片 JSON 数组")),
+    };
+    let cards: Vec<Flashcard> = serde_json::from_str(json)?;
+    Ok(cards
+        .into_iter()
+        .map(|card| Flashcard {
+            question: card.question.trim().to_string(),
+            answer: card.answer.trim().to_string(),
+        })
+        .filter(|card| !card.question.is_empty() && !card.answer.is_empty())
+        .collect())
+}
+
+/// 时间线卡片可选的类别（与生成时间线时的类别一致）
+pub const CARD_CATEGORIES: [&str; 6] = [
+    "work",
+    "communication",
+    "learning",
+    "personal",
+    "idle",
+    "other",
+];
+
+/// 待重新分类的时间线卡片
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CardBrief {
+    /// 卡片 ID
+    pub id: i64,
+    /// 标题
+    pub title: String,
+    /// 摘要
+    pub summary: String,
+    /// 当前类别
+    pub category: String,
+    /// 当前子类别
+    pub subcategory: String,
+    /// 主要应用或网站
+    pub apps: String,
+}
+
+/// 卡片的重新分类结果
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CardCategory {
+    /// 卡片 ID
+    pub id: i64,
+    /// 类别
+    pub category: String,
+    /// 子类别
+    #[serde(default)]
+    pub subcategory: String,
+}
+
+/// 用户自定义类别（名称作为卡片类别，按 bucket 归入内置类别统计）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CustomCategory {
+    /// 类别名称，如 "Client A"
+    pub name: String,
+    /// 归入的内置类别（CARD_CATEGORIES 之一）
+    pub bucket: String,
+    /// 说明
+    pub description: String,
+    /// 可选子类别
+    pub subcategories: Vec<String>,
+}
+
+/// 自定义类别的提示词片段（无自定义类别时为空）
+pub fn custom_categories_prompt(custom: &[CustomCategory]) -> String {
+    if custom.is_empty() {
+        return String::new();
+    }
+    let lines = custom
+        .iter()
+        .map(|category| {
+            let mut line = format!("- {}（归入 {}）", category.name, category.bucket);
+            if !category.description.is_empty() {
+                line.push_str(&format!("：{}", category.description));
+            }
+            if !category.subcategories.is_empty() {
+                line.push_str(&format!("；子类别：{}", category.subcategories.join(", ")));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "\n用户自定义类别（符合描述时优先使用自定义类别名称，而不是其归入的内置类别）：\n{}\n",
+        lines
+    )
+}
+
+/// 将模型返回的类别规范化：内置类别转小写，自定义类别按名称（忽略大小写）还原写法，未知类别返回 None
+pub fn normalize_card_category(raw: &str, custom: &[CustomCategory]) -> Option<String> {
+    let raw = raw.trim();
+    if let Some(category) = custom
+        .iter()
+        .find(|category| category.name.eq_ignore_ascii_case(raw))
+    {
+        return Some(category.name.clone());
+    }
+    let lower = raw.to_lowercase();
+    CARD_CATEGORIES.contains(&lower.as_str()).then_some(lower)
+}
+
+/// 重新分类时间线卡片的提示词（各提供商共用）
+pub fn categorize_prompt(cards: &[CardBrief], custom: &[CustomCategory]) -> String {
+    let lines = cards
+        .iter()
+        .map(|card| {
+            format!(
+                "- id: {} | 标题: {} | 摘要: {} | 应用: {} | 当前: {}/{}",
+                card.id, card.title, card.summary, card.apps, card.category, card.subcategory
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        r#"以下是屏幕活动时间线卡片，请重新判断每张卡片的类别：
+
+{}
+
+可选类别：{}
+类别选择指导：
+- 编程、开发、调试、写作、设计、规划 → work
+- 邮件、聊天、视频会议、团队协作 → communication
+- 教程、文档阅读、研究、学习新技能 → learning
+- 游戏、视频、购物、社交媒体 → personal
+- 锁屏、无活动 → idle
+- 只有真正无法归类的才用 other
+
+{}
+子类别沿用当前写法，除非与新类别明显不符。
+请只返回 JSON 数组，每张卡片一项，格式: [{{"id": 1, "category": "work", "subcategory": "Development"}}]"#,
+        lines,
+        CARD_CATEGORIES.join(", "),
+        custom_categories_prompt(custom)
+    )
+}
+
+/// 解析模型返回的卡片类别 JSON 数组（容忍代码块包裹，丢弃未知类别）
+pub fn parse_card_categories(text: &str, custom: &[CustomCategory]) -> Result<Vec<CardCategory>> {
+    super::structured::check_categories(text, custom).map(|checked| checked.items)
+}
+
 /// 会话总结
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SessionSummary {
@@ -626,6 +933,8 @@ pub trait LLMProvider: Send + Sync + std::any::Any {
                     primary: "unknown".to_string(),
                     secondary: None,
                 },
+                confidence: None,
+                heuristic: false,
                 video_preview_path: None,
             });
         }
@@ -686,6 +995,32 @@ pub trait LLMProvider: Send + Sync + std::any::Any {
             total_minutes
         ))
     }
+
+    /// 从学习会话摘要中提取问答学习卡片
+    ///
+    /// # 参数
+    /// * `session` - 学习会话的简要信息
+    /// * `max_cards` - 最多生成的卡片数量
+    async fn generate_flashcards(
+        &self,
+        _session: &SessionBrief,
+        _max_cards: usize,
+    ) -> Result<Vec<Flashcard>> {
+        Err(anyhow!("{} 不支持生成学习卡片", self.name()))
+    }
+
+    /// 重新判断时间线卡片的类别（修改分类规则或切换提供商后批量重新分类）
+    ///
+    /// # 参数
+    /// * `cards` - 待分类的卡片
+    /// * `custom` - 用户自定义类别
+    async fn categorize_cards(
+        &self,
+        _cards: &[CardBrief],
+        _custom: &[CustomCategory],
+    ) -> Result<Vec<CardCategory>> {
+        Err(anyhow!("{} 不支持重新分类时间线卡片", self.name()))
+    }
 }
 
 /// 提供商能力
@@ -721,6 +1056,77 @@ impl Default for ProviderCapabilities {
     }
 }
 
+/// 统一的 token 用量（各提供商的用量字段换算为 OpenAI 风格后写入 LLM 调用记录）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    /// 输入 token 数（含缓存命中部分）
+    pub prompt_tokens: u64,
+    /// 输出 token 数（含思考 token）
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    /// 命中提示缓存的输入 token 数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_tokens: Option<u64>,
+    /// 思考模型的推理 token 数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_tokens: Option<u64>,
+}
+
+impl TokenUsage {
+    pub fn new(prompt_tokens: u64, completion_tokens: u64) -> Self {
+        Self {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            cached_tokens: None,
+            reasoning_tokens: None,
+        }
+    }
+
+    /// Anthropic Messages API 的 usage（input_tokens 不含缓存写入和缓存读取部分）
+    pub fn from_anthropic(usage: &Value) -> Option<Self> {
+        let count = |key: &str| usage.get(key).and_then(|v| v.as_u64());
+        let input = count("input_tokens");
+        let output = count("output_tokens");
+        if input.is_none() && output.is_none() {
+            return None;
+        }
+        let cache_write = count("cache_creation_input_tokens").unwrap_or(0);
+        let cache_read = count("cache_read_input_tokens").unwrap_or(0);
+
+        let mut result = Self::new(
+            input.unwrap_or(0) + cache_write + cache_read,
+            output.unwrap_or(0),
+        );
+        result.cached_tokens = (cache_read > 0).then_some(cache_read);
+        Some(result)
+    }
+
+    /// Gemini 的 usageMetadata（思考 token 单独计数，按输出计费）
+    pub fn from_gemini(usage: &Value) -> Option<Self> {
+        let count = |key: &str| usage.get(key).and_then(|v| v.as_u64());
+        let prompt = count("promptTokenCount");
+        let candidates = count("candidatesTokenCount");
+        if prompt.is_none() && candidates.is_none() {
+            return None;
+        }
+        let thoughts = count("thoughtsTokenCount").unwrap_or(0);
+
+        let mut result = Self::new(prompt.unwrap_or(0), candidates.unwrap_or(0) + thoughts);
+        if let Some(total) = count("totalTokenCount") {
+            result.total_tokens = total;
+        }
+        result.cached_tokens = count("cachedContentTokenCount").filter(|cached| *cached > 0);
+        result.reasoning_tokens = (thoughts > 0).then_some(thoughts);
+        Some(result)
+    }
+
+    /// 写入 LLM 调用记录的 JSON 字符串
+    pub fn to_record(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
 /// 分析请求
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnalysisRequest {
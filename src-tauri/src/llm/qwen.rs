@@ -1,6 +1,7 @@
 // 阿里通义千问提供商实现 - 支持视频直接上传分析
 
 use super::plugin::*;
+use super::structured::{self, ParseContext};
 use anyhow::Result;
 use async_trait::async_trait;
 use base64::{engine::general_purpose, Engine as _};
@@ -61,6 +62,16 @@ impl QwenProvider {
         self.current_session_id = Some(session_id);
     }
 
+    /// 结构化响应校验的上下文
+    fn parse_context(&self) -> ParseContext<'_> {
+        ParseContext {
+            db: self.db.as_ref(),
+            session_id: self.current_session_id,
+            provider: "qwen",
+            model: &self.model,
+        }
+    }
+
     /// 设置API密钥
     pub fn set_api_key(&mut self, api_key: String) {
         self.api_key = Some(api_key);
@@ -189,7 +200,7 @@ impl QwenProvider {
 
     /// 将图片文件转换为base64
     async fn image_to_base64(&self, path: &str) -> Result<String> {
-        let image_data = tokio::fs::read(path).await?;
+        let image_data = crate::frame_crypto::read_frame(path).await?;
         Ok(general_purpose::STANDARD.encode(&image_data))
     }
 
@@ -335,6 +346,7 @@ Create long, meaningful cards that represent cohesive sessions of activity, idea
     "startTime": "根据输入格式返回",  // 如 "00:00" (MM:SS) 或 "2025-09-28T10:00:00+00:00" (ISO)
     "endTime": "根据输入格式返回",    // 如 "30:00" (MM:SS) 表示30分钟，可跨越多个片段
     "category": "work",  // 必须从6个类别中选择最合适的，避免滥用other
+    "confidence": 0.9,  // 对category判断的把握（0-1），画面信息不足或活动混杂时给较低的值
     "subcategory": "Development",
     "title": "功能开发",
     "summary": "持续开发新功能模块",
@@ -351,20 +363,6 @@ Create long, meaningful cards that represent cohesive sessions of activity, idea
         )
     }
 
-    fn parse_timeline_cards(value: serde_json::Value) -> Result<Vec<TimelineCard>> {
-        match value {
-            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
-                Ok(serde_json::from_value(value)?)
-            }
-            serde_json::Value::String(text) => {
-                let mut inner_value = serde_json::from_str::<serde_json::Value>(&text)?;
-                crate::llm::plugin::normalize_timeline_cards_value(&mut inner_value);
-                Ok(serde_json::from_value(inner_value)?)
-            }
-            other => Err(anyhow::anyhow!("Qwen时间线返回格式不正确: {}", other)),
-        }
-    }
-
     fn fallback_timeline_cards(value: &serde_json::Value) -> Option<Vec<TimelineCard>> {
         let entries: Vec<serde_json::Value> = match value {
             serde_json::Value::Array(arr) => arr.clone(),
@@ -435,6 +433,8 @@ Create long, meaningful cards that represent cohesive sessions of activity, idea
                 primary: "unknown".to_string(),
                 secondary: None,
             },
+            confidence: None,
+            heuristic: false,
             video_preview_path: None,
         })
     }
@@ -462,7 +462,7 @@ Create long, meaningful cards that represent cohesive sessions of activity, idea
         if self.use_video_mode && !images_base64.is_empty() {
             let image_urls: Vec<String> = images_base64
                 .iter()
-                .map(|base64| format!("data:image/jpeg;base64,{}", base64))
+                .map(|base64| format!("data:{};base64,{}", image_mime_type(base64), base64))
                 .collect();
 
             content_parts.push(json!({
@@ -475,7 +475,7 @@ Create long, meaningful cards that represent cohesive sessions of activity, idea
                 content_parts.push(json!({
                     "type": "image_url",
                     "image_url": {
-                        "url": format!("data:image/jpeg;base64,{}", base64)
+                        "url": format!("data:{};base64,{}", image_mime_type(&base64), base64)
                     }
                 }));
             }
@@ -869,6 +869,7 @@ Create long, meaningful cards that represent cohesive sessions of activity, idea
             {
                 // 合并卡片
                 current.end_time = next_card.end_time;
+                current.confidence = lower_confidence(current.confidence, next_card.confidence);
                 current.detailed_summary = format!(
                     "{}；{}",
                     current.detailed_summary, next_card.detailed_summary
@@ -924,6 +925,8 @@ Create long, meaningful cards that represent cohesive sessions of activity, idea
                 // 合并
                 let mut merged_card = last_prev.clone();
                 merged_card.end_time = first_new.end_time.clone();
+                merged_card.confidence =
+                    lower_confidence(last_prev.confidence, first_new.confidence);
                 merged_card.detailed_summary = format!(
                     "{}；{}",
                     last_prev.detailed_summary, first_new.detailed_summary
@@ -948,6 +951,17 @@ Create long, meaningful cards that represent cohesive sessions of activity, idea
     }
 }
 
+/// 根据图片内容判断 MIME 类型（截图可能保存为 JPEG/WebP/AVIF），无法识别时按 JPEG 处理
+fn image_mime_type(base64: &str) -> &'static str {
+    // 64 个 base64 字符对应 48 字节，足够识别文件头
+    let prefix: String = base64.chars().take(64).collect();
+    general_purpose::STANDARD
+        .decode(prefix)
+        .ok()
+        .and_then(|bytes| image::guess_format(&bytes).ok())
+        .map_or("image/jpeg", |format| format.to_mime_type())
+}
+
 #[async_trait]
 impl LLMProvider for QwenProvider {
     fn as_any(&mut self) -> &mut dyn std::any::Any {
@@ -1157,24 +1171,20 @@ impl LLMProvider for QwenProvider {
             .call_qwen_api(prompt, vec![], "generate_timeline")
             .await?;
 
-        // 解析响应
-        let json_str = if let Some(start) = response.find('[') {
-            if let Some(end) = response.rfind(']') {
-                &response[start..=end]
-            } else {
-                &response[start..]
-            }
-        } else {
-            &response
-        };
-
-        let mut timeline_value = serde_json::from_str::<serde_json::Value>(json_str)?;
-        crate::llm::plugin::normalize_timeline_cards_value(&mut timeline_value);
+        // 解析并校验响应（未通过时请模型修复一次）
+        let parsed =
+            structured::parse_timeline(&response, &self.parse_context(), |prompt, call_type| {
+                self.call_qwen_api(prompt, vec![], call_type)
+            })
+            .await;
 
-        let raw_cards = match Self::parse_timeline_cards(timeline_value.clone()) {
+        let raw_cards = match parsed {
             Ok(cards) => cards,
             Err(err) => {
-                if let Some(fallback) = Self::fallback_timeline_cards(&timeline_value) {
+                // 模型偶尔直接返回分段结构，按分段描述生成卡片
+                if let Some(fallback) = structured::extract_json(&response)
+                    .and_then(|value| Self::fallback_timeline_cards(&value))
+                {
                     info!(
                         "Timeline解析失败，使用回退结果生成 {} 张卡片: {}",
                         fallback.len(),
@@ -1345,7 +1355,55 @@ impl LLMProvider for QwenProvider {
 
         info!("使用Qwen生成每日总结: {}", date);
 
-        // 调用API
+        let summary = self.complete_text(api_key, &prompt).await?;
+        info!("生成的每日总结: {}", summary);
+        Ok(summary)
+    }
+
+    async fn generate_flashcards(
+        &self,
+        session: &crate::llm::SessionBrief,
+        max_cards: usize,
+    ) -> Result<Vec<Flashcard>> {
+        let api_key = self
+            .api_key
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Qwen API Key未配置"))?;
+
+        info!("使用Qwen生成学习卡片: {}", session.title);
+        let response = self
+            .complete_text(api_key, &flashcard_prompt(session, max_cards))
+            .await?;
+        parse_flashcards(&response)
+    }
+
+    async fn categorize_cards(
+        &self,
+        cards: &[CardBrief],
+        custom: &[CustomCategory],
+    ) -> Result<Vec<CardCategory>> {
+        let api_key = self
+            .api_key
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Qwen API Key未配置"))?;
+
+        info!("使用Qwen重新分类 {} 张时间线卡片", cards.len());
+        let response = self
+            .complete_text(api_key, &categorize_prompt(cards, custom))
+            .await?;
+        structured::parse_categories(
+            &response,
+            custom,
+            &self.parse_context(),
+            |prompt, _| async move { self.complete_text(api_key, &prompt).await },
+        )
+        .await
+    }
+}
+
+impl QwenProvider {
+    /// 纯文本对话补全（每日总结、学习卡片等不需要图片的请求）
+    async fn complete_text(&self, api_key: &str, prompt: &str) -> Result<String> {
         let request_body = json!({
             "model": self.model,
             "messages": [
@@ -1380,9 +1438,7 @@ impl LLMProvider for QwenProvider {
         let result: QwenResponse = response.json().await?;
 
         if let Some(choice) = result.choices.first() {
-            let summary = choice.message.content.trim().to_string();
-            info!("生成的每日总结: {}", summary);
-            Ok(summary)
+            Ok(choice.message.content.trim().to_string())
         } else {
             Err(anyhow::anyhow!("Qwen API 返回空结果"))
         }
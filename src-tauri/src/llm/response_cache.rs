@@ -0,0 +1,290 @@
+// LLM 响应缓存 - 以输入帧的感知哈希、提示词版本、提供商与模型配置计算缓存键，
+// 重新分析（重试时间线、强制刷新每日总结、重新导出）未变化的数据时直接复用已保存的响应，不再重复计费
+
+use super::{LLMConfig, TimelineCard, VideoSegment};
+use crate::capture::dedupe::dhash;
+use crate::storage::{Database, LlmCacheEntry};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// 提示词版本：修改任一提供商的内置提示词或响应结构后递增，使旧缓存失效
+pub const PROMPT_VERSION: u32 = 2;
+
+/// 缓存的分段与时间线（自定义类别已应用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedTimeline {
+    pub segments: Vec<VideoSegment>,
+    pub timeline_cards: Vec<TimelineCard>,
+}
+
+/// 缓存键
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheKey {
+    pub key: String,
+    pub task: &'static str,
+    pub provider: String,
+    pub model: String,
+}
+
+/// 缓存键构建器：依次写入影响响应的全部输入
+pub struct CacheKeyBuilder {
+    hasher: Sha256,
+    task: &'static str,
+    provider: String,
+    model: String,
+}
+
+impl CacheKeyBuilder {
+    /// 以调用类型与当前提供商配置开始构建（API Key 不参与计算）
+    pub fn new(task: &'static str, config: &LLMConfig) -> Self {
+        let (model, mut settings) = provider_settings(config);
+        if let Some(map) = settings.as_object_mut() {
+            map.remove("api_key");
+            map.remove("video_path");
+        }
+
+        Self {
+            hasher: Sha256::new(),
+            task,
+            provider: config.provider.clone(),
+            model,
+        }
+        .text(&PROMPT_VERSION.to_string())
+        .text(task)
+        .text(&config.provider)
+        .json(&settings)
+    }
+
+    /// 写入一段文本（以 0 字节分隔，避免拼接歧义）
+    pub fn text(mut self, value: &str) -> Self {
+        self.hasher.update(value.as_bytes());
+        self.hasher.update([0u8]);
+        self
+    }
+
+    /// 写入可序列化的值
+    pub fn json<T: Serialize + ?Sized>(self, value: &T) -> Self {
+        let text = serde_json::to_string(value).unwrap_or_default();
+        self.text(&text)
+    }
+
+    /// 写入帧感知哈希
+    pub fn frames(mut self, hashes: &[u64]) -> Self {
+        for hash in hashes {
+            self.hasher.update(hash.to_le_bytes());
+        }
+        self.hasher.update([0u8]);
+        self
+    }
+
+    pub fn finish(self) -> CacheKey {
+        CacheKey {
+            key: hex::encode(self.hasher.finalize()),
+            task: self.task,
+            provider: self.provider,
+            model: self.model,
+        }
+    }
+}
+
+/// 当前提供商的模型名与配置
+fn provider_settings(config: &LLMConfig) -> (String, serde_json::Value) {
+    let (model, settings) = match config.provider.as_str() {
+        "claude" => (
+            config.claude.model.clone().unwrap_or_default(),
+            serde_json::to_value(&config.claude),
+        ),
+        "codex" => (
+            config.codex.model.clone().unwrap_or_default(),
+            serde_json::to_value(&config.codex),
+        ),
+        "ollama" => (
+            config.ollama.model.clone(),
+            serde_json::to_value(&config.ollama),
+        ),
+        "gemini" => (
+            config.gemini.model.clone(),
+            serde_json::to_value(&config.gemini),
+        ),
+        _ => (
+            config.qwen.model.clone(),
+            serde_json::to_value(&config.qwen),
+        ),
+    };
+    (model, settings.unwrap_or_default())
+}
+
+/// 计算输入帧的感知哈希，任一帧无法读取时返回 None（不使用缓存）
+pub async fn frame_hashes(paths: &[String]) -> Option<Vec<u64>> {
+    if paths.is_empty() {
+        return None;
+    }
+    let paths = paths.to_vec();
+    tokio::task::spawn_blocking(move || {
+        paths
+            .iter()
+            .map(|path| {
+                crate::frame_crypto::open_image(path)
+                    .ok()
+                    .map(|image| dhash(&image))
+            })
+            .collect::<Option<Vec<u64>>>()
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+/// 基于数据库的响应缓存
+#[derive(Clone)]
+pub struct ResponseCache {
+    db: Arc<Database>,
+    /// 缓存有效天数，0 表示不过期
+    max_age_days: u32,
+}
+
+impl ResponseCache {
+    pub fn new(db: Arc<Database>, max_age_days: u32) -> Self {
+        Self { db, max_age_days }
+    }
+
+    /// 读取未过期的缓存，读取或解析失败时视为未命中
+    pub async fn get<T: DeserializeOwned>(&self, key: &CacheKey) -> Option<T> {
+        let entry = match self.db.get_llm_cache(&key.key).await {
+            Ok(entry) => entry?,
+            Err(e) => {
+                warn!("读取 LLM 响应缓存失败: {}", e);
+                return None;
+            }
+        };
+        if self.max_age_days > 0
+            && entry.created_at
+                < crate::storage::local_now() - chrono::Duration::days(self.max_age_days as i64)
+        {
+            return None;
+        }
+
+        match serde_json::from_str(&entry.response) {
+            Ok(value) => {
+                info!(
+                    "命中 LLM 响应缓存: {} ({} / {})",
+                    key.task, key.provider, key.model
+                );
+                Some(value)
+            }
+            Err(e) => {
+                warn!("解析 LLM 响应缓存失败: {}", e);
+                None
+            }
+        }
+    }
+
+    /// 保存响应，失败只记录日志
+    pub async fn put<T: Serialize>(&self, key: &CacheKey, value: &T) {
+        let response = match serde_json::to_string(value) {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("序列化 LLM 响应失败: {}", e);
+                return;
+            }
+        };
+        let entry = LlmCacheEntry {
+            cache_key: key.key.clone(),
+            task: key.task.to_string(),
+            provider: key.provider.clone(),
+            model: key.model.clone(),
+            response,
+            created_at: crate::storage::local_now(),
+        };
+        if let Err(e) = self.db.save_llm_cache(&entry).await {
+            warn!("保存 LLM 响应缓存失败: {}", e);
+        }
+    }
+
+    /// 删除过期缓存
+    pub async fn prune(&self) -> anyhow::Result<u64> {
+        if self.max_age_days == 0 {
+            return Ok(0);
+        }
+        let before = crate::storage::local_now() - chrono::Duration::days(self.max_age_days as i64);
+        self.db.prune_llm_cache(Some(before)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cache_key_inputs() {
+        let defaults = crate::llm::LLMManager::new(reqwest::Client::new())
+            .get_config()
+            .await;
+        let config = |provider: &str| LLMConfig {
+            provider: provider.to_string(),
+            ..defaults.clone()
+        };
+        let qwen = config("openai");
+        let key = |config: &LLMConfig, frames: &[u64]| {
+            CacheKeyBuilder::new("timeline", config)
+                .frames(frames)
+                .text("15")
+                .finish()
+        };
+
+        let base = key(&qwen, &[1, 2, 3]);
+        assert_eq!(base, key(&qwen, &[1, 2, 3]));
+        assert_eq!(base.model, qwen.qwen.model);
+        assert_ne!(base.key, key(&qwen, &[1, 2, 4]).key);
+
+        // API Key 不影响缓存键，模型与提供商会影响
+        let mut rotated = qwen.clone();
+        rotated.qwen.api_key = "sk-new".to_string();
+        assert_eq!(base.key, key(&rotated, &[1, 2, 3]).key);
+        let mut other_model = qwen.clone();
+        other_model.qwen.model = "qwen-vl-plus".to_string();
+        assert_ne!(base.key, key(&other_model, &[1, 2, 3]).key);
+        assert_ne!(base.key, key(&config("gemini"), &[1, 2, 3]).key);
+
+        // 不同调用类型互不命中
+        let summary = CacheKeyBuilder::new("day_summary", &qwen)
+            .frames(&[1, 2, 3])
+            .text("15")
+            .finish();
+        assert_ne!(base.key, summary.key);
+    }
+
+    #[tokio::test]
+    async fn test_response_cache_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(
+            Database::new_sqlite(dir.path().join("test.db").to_str().unwrap())
+                .await
+                .unwrap(),
+        );
+        let key = CacheKey {
+            key: "k1".to_string(),
+            task: "day_summary",
+            provider: "openai".to_string(),
+            model: "qwen-vl-max-latest".to_string(),
+        };
+
+        let cache = ResponseCache::new(db.clone(), 30);
+        assert_eq!(cache.get::<String>(&key).await, None);
+        cache.put(&key, &"今天主要在写代码".to_string()).await;
+        assert_eq!(
+            cache.get::<String>(&key).await.as_deref(),
+            Some("今天主要在写代码")
+        );
+
+        // 过期的缓存不再命中，并会被清理
+        let mut stale = db.get_llm_cache("k1").await.unwrap().unwrap();
+        stale.created_at = crate::storage::local_now() - chrono::Duration::days(31);
+        db.save_llm_cache(&stale).await.unwrap();
+        assert_eq!(cache.get::<String>(&key).await, None);
+        assert_eq!(cache.prune().await.unwrap(), 1);
+        assert!(db.get_llm_cache("k1").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,587 @@
+// 结构化输出校验 - 按 JSON Schema 校验时间线卡片与重新分类的模型响应，
+// 解析或校验失败时附带错误与 Schema 请模型修复一次，仍未通过的原始响应写入数据库便于排查，而不是静默丢弃卡片
+
+use super::plugin::{
+    normalize_card_category, normalize_timeline_cards_value, CardCategory, CustomCategory,
+    TimelineCard, CARD_CATEGORIES,
+};
+use crate::storage::{Database, LlmParseFailure};
+use anyhow::{anyhow, Result};
+use llm_json::{loads, repair_json, RepairOptions};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// 修复提示词中原始响应的最大字符数
+const MAX_RAW_IN_PROMPT: usize = 8000;
+
+/// 结构化响应类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseKind {
+    /// 时间线卡片
+    Timeline,
+    /// 卡片重新分类结果
+    Categories,
+}
+
+impl ResponseKind {
+    /// 原始调用类型（写入失败记录）
+    pub fn task(self) -> &'static str {
+        match self {
+            Self::Timeline => "generate_timeline",
+            Self::Categories => "categorize_cards",
+        }
+    }
+
+    /// 修复重试的调用类型
+    pub fn repair_task(self) -> &'static str {
+        match self {
+            Self::Timeline => "repair_timeline",
+            Self::Categories => "repair_categories",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Timeline => "时间线",
+            Self::Categories => "卡片分类",
+        }
+    }
+
+    /// 响应的 JSON Schema
+    pub fn schema(self) -> Value {
+        let text = json!({ "type": "string", "minLength": 1 });
+        match self {
+            Self::Timeline => json!({
+                "type": "array",
+                "minItems": 1,
+                "items": {
+                    "type": "object",
+                    "required": [
+                        "startTime", "endTime", "category", "subcategory",
+                        "title", "summary", "detailedSummary", "appSites"
+                    ],
+                    "properties": {
+                        "startTime": text,
+                        "endTime": text,
+                        "category": text,
+                        "subcategory": { "type": "string" },
+                        "title": text,
+                        "summary": text,
+                        "detailedSummary": { "type": "string" },
+                        "confidence": { "type": ["number", "string", "null"] },
+                        "distractions": { "type": ["array", "null"] },
+                        "appSites": {
+                            "type": "object",
+                            "required": ["primary"],
+                            "properties": {
+                                "primary": { "type": "string" },
+                                "secondary": { "type": ["array", "string", "null"] }
+                            }
+                        }
+                    }
+                }
+            }),
+            Self::Categories => json!({
+                "type": "array",
+                "minItems": 1,
+                "items": {
+                    "type": "object",
+                    "required": ["id", "category"],
+                    "properties": {
+                        "id": { "type": "integer" },
+                        "category": text,
+                        "subcategory": { "type": "string" }
+                    }
+                }
+            }),
+        }
+    }
+}
+
+/// 校验结果：通过校验的条目与其余条目的错误
+#[derive(Debug)]
+pub struct Checked<T> {
+    pub items: Vec<T>,
+    pub errors: Vec<String>,
+}
+
+/// 按 JSON Schema 子集（type / required / properties / items / enum / minItems / minLength）校验，错误带 JSON 路径
+pub fn validate_schema(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(|name| name.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.iter().any(|name| type_matches(name, value)) {
+            errors.push(format!(
+                "{}: 应为 {}，实际为 {}",
+                path,
+                allowed.join(" 或 "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(|v| v.as_array()) {
+        if !options.contains(value) {
+            errors.push(format!("{}: 取值 {} 不在允许范围内", path, value));
+        }
+    }
+
+    match value {
+        Value::String(text) => {
+            if let Some(min) = schema.get("minLength").and_then(|v| v.as_u64()) {
+                if (text.trim().chars().count() as u64) < min {
+                    errors.push(format!("{}: 不能为空", path));
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(min) = schema.get("minItems").and_then(|v| v.as_u64()) {
+                if (items.len() as u64) < min {
+                    errors.push(format!("{}: 至少需要 {} 项", path, min));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_schema(item_schema, item, &format!("{}[{}]", path, index), errors);
+                }
+            }
+        }
+        Value::Object(map) => {
+            if let Some(required) = schema.get("required").and_then(|v| v.as_array()) {
+                for key in required.iter().filter_map(|key| key.as_str()) {
+                    if !map.contains_key(key) {
+                        errors.push(format!("{}: 缺少字段 {}", path, key));
+                    }
+                }
+            }
+            if let Some(properties) = schema.get("properties").and_then(|v| v.as_object()) {
+                for (key, property_schema) in properties {
+                    if let Some(field) = map.get(key) {
+                        validate_schema(
+                            property_schema,
+                            field,
+                            &format!("{}.{}", path, key),
+                            errors,
+                        );
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn type_matches(name: &str, value: &Value) -> bool {
+    match name {
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// 从模型输出中提取 JSON（容忍代码块、前后说明文字与常见格式瑕疵）
+pub fn extract_json(raw: &str) -> Option<Value> {
+    let trimmed = raw.trim();
+    let cleaned = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .map(|body| body.trim_end().trim_end_matches("```").trim())
+        .unwrap_or(trimmed);
+    if cleaned.is_empty() {
+        return None;
+    }
+    if let Ok(value) = serde_json::from_str(cleaned) {
+        return Some(value);
+    }
+
+    // 截取最外层的数组或对象
+    let start = cleaned.find(['[', '{'])?;
+    let close = if cleaned[start..].starts_with('[') {
+        ']'
+    } else {
+        '}'
+    };
+    if let Some(end) = cleaned.rfind(close).filter(|end| *end > start) {
+        if let Ok(value) = serde_json::from_str(&cleaned[start..=end]) {
+            return Some(value);
+        }
+    }
+
+    let repaired = repair_json(&cleaned[start..], &RepairOptions::default()).ok()?;
+    loads(&repaired, &RepairOptions::default()).ok()
+}
+
+/// 取出响应中的条目数组：包装对象（如 {"cards": [...]}）取唯一的数组字段，单个对象视为一项
+fn into_items(value: Value) -> Result<Vec<Value>> {
+    match value {
+        Value::Array(items) => Ok(items),
+        Value::Object(map) => {
+            let arrays: Vec<&Value> = map.values().filter(|v| v.is_array()).collect();
+            if arrays.len() == 1 && map.len() == 1 {
+                into_items(arrays[0].clone())
+            } else {
+                Ok(vec![Value::Object(map)])
+            }
+        }
+        Value::String(text) => match serde_json::from_str(&text) {
+            Ok(inner) => into_items(inner),
+            Err(_) => Err(anyhow!("响应不是 JSON 数组")),
+        },
+        other => Err(anyhow!("响应不是 JSON 数组，实际为 {}", type_name(&other))),
+    }
+}
+
+/// 逐项校验并反序列化，accept 用于额外的语义检查
+fn check_items<T: DeserializeOwned>(
+    kind: ResponseKind,
+    raw: &str,
+    mut accept: impl FnMut(T) -> std::result::Result<T, String>,
+) -> Result<Checked<T>> {
+    let mut value = extract_json(raw).ok_or_else(|| anyhow!("响应中没有可解析的 JSON"))?;
+    if kind == ResponseKind::Timeline {
+        normalize_timeline_cards_value(&mut value);
+    }
+    let items = into_items(value)?;
+    if items.is_empty() {
+        return Err(anyhow!("响应数组为空"));
+    }
+
+    let schema = kind.schema();
+    let item_schema = &schema["items"];
+    let mut checked = Checked {
+        items: Vec::new(),
+        errors: Vec::new(),
+    };
+    for (index, item) in items.into_iter().enumerate() {
+        let path = format!("[{}]", index);
+        let mut errors = Vec::new();
+        validate_schema(item_schema, &item, &path, &mut errors);
+        if errors.is_empty() {
+            match serde_json::from_value::<T>(item)
+                .map_err(|e| e.to_string())
+                .and_then(&mut accept)
+            {
+                Ok(parsed) => checked.items.push(parsed),
+                Err(e) => errors.push(format!("{}: {}", path, e)),
+            }
+        }
+        checked.errors.extend(errors);
+    }
+    Ok(checked)
+}
+
+/// 校验时间线卡片响应
+pub fn check_timeline(raw: &str) -> Result<Checked<TimelineCard>> {
+    check_items(ResponseKind::Timeline, raw, Ok)
+}
+
+/// 校验卡片分类响应（类别规范化为内置类别或自定义类别名称，未知类别记为错误）
+pub fn check_categories(raw: &str, custom: &[CustomCategory]) -> Result<Checked<CardCategory>> {
+    check_items(
+        ResponseKind::Categories,
+        raw,
+        |item: CardCategory| match normalize_card_category(&item.category, custom) {
+            Some(category) => Ok(CardCategory {
+                category,
+                subcategory: item.subcategory.trim().to_string(),
+                ..item
+            }),
+            None => {
+                let allowed: Vec<&str> = CARD_CATEGORIES
+                    .iter()
+                    .copied()
+                    .chain(custom.iter().map(|c| c.name.as_str()))
+                    .collect();
+                Err(format!(
+                    "未知类别 {}（可选：{}）",
+                    item.category,
+                    allowed.join(", ")
+                ))
+            }
+        },
+    )
+}
+
+/// 请模型按 Schema 修复响应的提示词
+pub fn repair_prompt(kind: ResponseKind, raw: &str, errors: &[String]) -> String {
+    let raw: String = raw.chars().take(MAX_RAW_IN_PROMPT).collect();
+    let schema = serde_json::to_string_pretty(&kind.schema()).unwrap_or_default();
+    let errors = errors
+        .iter()
+        .map(|error| format!("- {}", error))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        r#"你上一次返回的{}结果未通过结构校验。
+
+校验错误：
+{}
+
+上一次的返回内容：
+{}
+
+请按以下 JSON Schema 修正格式，保留原有内容，不要重新编造或删减条目：
+{}
+
+只返回修正后的 JSON 数组，不要附加任何说明，也不要使用代码块。"#,
+        kind.label(),
+        errors,
+        raw,
+        schema
+    )
+}
+
+/// 失败记录与修复请求的上下文
+pub struct ParseContext<'a> {
+    pub db: Option<&'a Arc<Database>>,
+    pub session_id: Option<i64>,
+    pub provider: &'a str,
+    pub model: &'a str,
+}
+
+/// 校验响应，未通过时请模型修复一次；修复后仍有无效条目时保留有效条目并记录原始响应，全部无效时返回错误
+pub async fn parse_with_repair<T, F, Fut>(
+    kind: ResponseKind,
+    raw: &str,
+    ctx: &ParseContext<'_>,
+    check: impl Fn(&str) -> Result<Checked<T>>,
+    repair: F,
+) -> Result<Vec<T>>
+where
+    F: FnOnce(String, &'static str) -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    let mut result = check(raw);
+    let mut errors = match &result {
+        Ok(checked) if checked.errors.is_empty() => return result.map(|checked| checked.items),
+        Ok(checked) => checked.errors.clone(),
+        Err(e) => vec![e.to_string()],
+    };
+    warn!(
+        "{} {}响应未通过结构校验（{} 处错误），请求修复: {}",
+        ctx.provider,
+        kind.label(),
+        errors.len(),
+        errors.join("; ")
+    );
+
+    let repaired_response =
+        match repair(repair_prompt(kind, raw, &errors), kind.repair_task()).await {
+            Ok(response) => Some(response),
+            Err(e) => {
+                warn!("{} 修复请求失败: {}", kind.label(), e);
+                None
+            }
+        };
+
+    let mut repaired = false;
+    if let Some(response) = &repaired_response {
+        match check(response) {
+            Ok(checked) => {
+                repaired = checked.errors.is_empty();
+                errors.extend(checked.errors.iter().map(|e| format!("修复后 {}", e)));
+                let better = match &result {
+                    Ok(previous) => checked.items.len() >= previous.items.len(),
+                    Err(_) => true,
+                };
+                if better {
+                    result = Ok(checked);
+                }
+            }
+            Err(e) => errors.push(format!("修复后 {}", e)),
+        }
+    }
+
+    record_failure(ctx, kind, raw, repaired_response, &errors, repaired).await;
+
+    let checked = match result {
+        Ok(checked) if !checked.items.is_empty() => checked,
+        _ => {
+            return Err(anyhow!(
+                "{}响应未通过结构校验: {}",
+                kind.label(),
+                errors.join("; ")
+            ))
+        }
+    };
+    if repaired {
+        info!("{}响应修复成功: {} 项", kind.label(), checked.items.len());
+    } else {
+        warn!(
+            "{}响应修复后仍有 {} 项无效，保留 {} 项有效结果，原始响应已记录",
+            kind.label(),
+            checked.errors.len(),
+            checked.items.len()
+        );
+    }
+    Ok(checked.items)
+}
+
+/// 解析时间线卡片响应（失败时修复重试一次）
+pub async fn parse_timeline<F, Fut>(
+    raw: &str,
+    ctx: &ParseContext<'_>,
+    repair: F,
+) -> Result<Vec<TimelineCard>>
+where
+    F: FnOnce(String, &'static str) -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    parse_with_repair(ResponseKind::Timeline, raw, ctx, check_timeline, repair).await
+}
+
+/// 解析卡片分类响应（失败时修复重试一次）
+pub async fn parse_categories<F, Fut>(
+    raw: &str,
+    custom: &[CustomCategory],
+    ctx: &ParseContext<'_>,
+    repair: F,
+) -> Result<Vec<CardCategory>>
+where
+    F: FnOnce(String, &'static str) -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    parse_with_repair(
+        ResponseKind::Categories,
+        raw,
+        ctx,
+        |text| check_categories(text, custom),
+        repair,
+    )
+    .await
+}
+
+/// 保存失败记录，失败只记录日志
+async fn record_failure(
+    ctx: &ParseContext<'_>,
+    kind: ResponseKind,
+    raw: &str,
+    repaired_response: Option<String>,
+    errors: &[String],
+    repaired: bool,
+) {
+    let Some(db) = ctx.db else {
+        return;
+    };
+    let failure = LlmParseFailure {
+        id: None,
+        session_id: ctx.session_id,
+        provider: ctx.provider.to_string(),
+        model: ctx.model.to_string(),
+        task: kind.task().to_string(),
+        raw_response: raw.to_string(),
+        repaired_response,
+        errors: errors.join("\n"),
+        repaired,
+        created_at: crate::storage::local_now(),
+    };
+    if let Err(e) = db.insert_llm_parse_failure(&failure).await {
+        warn!("保存 LLM 解析失败记录失败: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARD: &str = r#"{"startTime": "10:00", "endTime": "10:30", "category": "Work",
+        "subcategory": "Coding", "title": "编写解析器", "summary": "实现 JSON 校验",
+        "detailedSummary": "", "distractions": null, "appSites": {"primary": "vscode"}}"#;
+
+    #[test]
+    fn test_check_timeline_schema() {
+        let checked = check_timeline(&format!("```json\n[{}]\n```", CARD)).unwrap();
+        assert_eq!(checked.items.len(), 1);
+        assert!(checked.errors.is_empty());
+
+        // 包装对象与缺字段的卡片
+        let broken = r#"{"startTime": "10:30", "endTime": "11:00", "category": "Work", "subcategory": "",
+            "title": " ", "summary": "开会", "detailedSummary": "", "appSites": {}}"#;
+        let checked = check_timeline(&format!(r#"{{"cards": [{}, {}]}}"#, CARD, broken)).unwrap();
+        assert_eq!(checked.items.len(), 1);
+        assert_eq!(checked.errors.len(), 2);
+        assert!(checked.errors.contains(&"[1].title: 不能为空".to_string()));
+        assert!(checked
+            .errors
+            .contains(&"[1].appSites: 缺少字段 primary".to_string()));
+
+        assert!(check_timeline("抱歉，无法生成时间线").is_err());
+        assert!(check_timeline("[]").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parse_with_repair_records_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(
+            Database::new_sqlite(dir.path().join("test.db").to_str().unwrap())
+                .await
+                .unwrap(),
+        );
+        let ctx = ParseContext {
+            db: Some(&db),
+            session_id: Some(7),
+            provider: "qwen",
+            model: "qwen-vl-max-latest",
+        };
+
+        // 通过校验时不发起修复
+        let cards = parse_timeline(&format!("[{}]", CARD), &ctx, |_, _| async {
+            Err(anyhow!("不应请求修复"))
+        })
+        .await
+        .unwrap();
+        assert_eq!(cards.len(), 1);
+        assert!(db.get_llm_parse_failures(10).await.unwrap().is_empty());
+
+        // 未知类别触发修复，修复成功后记录原始响应
+        let raw = r#"[{"id": 1, "category": "gaming"}]"#;
+        let categories = parse_categories(raw, &[], &ctx, |prompt, call_type| async move {
+            assert_eq!(call_type, "repair_categories");
+            assert!(prompt.contains("未知类别 gaming"));
+            Ok(r#"[{"id": 1, "category": "personal"}]"#.to_string())
+        })
+        .await
+        .unwrap();
+        assert_eq!(categories[0].category, "personal");
+
+        // 修复仍失败时返回错误，不静默丢弃
+        let result = parse_timeline("无法识别", &ctx, |_, _| async {
+            Ok("仍然不是 JSON".to_string())
+        })
+        .await;
+        assert!(result.is_err());
+
+        let failures = db.get_llm_parse_failures(10).await.unwrap();
+        assert_eq!(failures.len(), 2);
+        let repaired = failures.iter().find(|f| f.repaired).unwrap();
+        assert_eq!(repaired.task, "categorize_cards");
+        assert_eq!(repaired.raw_response, raw);
+        assert_eq!(repaired.session_id, Some(7));
+        let failed = failures.iter().find(|f| !f.repaired).unwrap();
+        assert_eq!(failed.task, "generate_timeline");
+        assert_eq!(failed.repaired_response.as_deref(), Some("仍然不是 JSON"));
+    }
+}
@@ -0,0 +1,472 @@
+// Logseq 导出模块 - 以块语法生成日志页面（journals/）
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs;
+
+use crate::actors::LLMHandle;
+use crate::domains::summary::DaySummary;
+use crate::exporter::{
+    card_clock, day_summary, load_day_sessions, session_category, session_minutes, ExportReport,
+    Exporter, NotePreview, SessionExportData,
+};
+use crate::frame_store::{FrameStore, LocalFrameStore};
+use crate::issue_links::render_issue_link;
+use crate::models::LogseqExportConfig;
+use crate::obsidian::pick_screenshots;
+use crate::storage::{Database, Session};
+
+/// 导出块的标记属性（重新导出时据此替换旧块，保留用户自己写的块）
+const SOURCE_PROPERTY: &str = "source:: screen-analyzer";
+
+/// 截图在图谱 assets/ 下的子目录
+const ASSETS_FOLDER: &str = "screen-analyzer";
+
+/// Logseq 导出器
+pub struct LogseqExporter {
+    config: LogseqExportConfig,
+    /// 截图存储后端（复制前解析已转存截图的本机路径）
+    frame_store: Arc<dyn FrameStore>,
+}
+
+/// 日志页面中的单个会话块
+struct SessionBlock {
+    data: SessionExportData,
+    /// 相对日志页面的截图链接
+    screenshots: Vec<String>,
+}
+
+impl LogseqExporter {
+    /// 创建新的导出器
+    pub fn new(config: LogseqExportConfig) -> Self {
+        Self {
+            config,
+            frame_store: Arc::new(LocalFrameStore),
+        }
+    }
+
+    /// 指定截图存储后端（默认读取本机文件）
+    pub fn with_frame_store(mut self, frame_store: Arc<dyn FrameStore>) -> Self {
+        self.frame_store = frame_store;
+        self
+    }
+
+    /// 解析图谱目录
+    pub fn resolve_graph(&self) -> Result<PathBuf> {
+        let graph_path = self.config.graph_path.trim();
+        if graph_path.is_empty() {
+            return Err(anyhow!("请先配置 Logseq 图谱路径"));
+        }
+        let graph = PathBuf::from(graph_path);
+        if !graph.is_dir() {
+            return Err(anyhow!("Logseq 图谱目录不存在: {}", graph_path));
+        }
+        Ok(graph)
+    }
+
+    /// 日志页面路径（Logseq 默认文件名格式 yyyy_MM_dd）
+    pub fn journal_path(&self, graph: &Path, day: NaiveDate) -> PathBuf {
+        graph
+            .join("journals")
+            .join(format!("{}.md", day.format("%Y_%m_%d")))
+    }
+
+    /// 复制会话首尾截图到 assets/，返回（链接, 写入路径）；预览时只计算链接不复制
+    async fn copy_screenshots(
+        &self,
+        db: &Database,
+        graph: &Path,
+        date: &str,
+        session_id: i64,
+        dry_run: bool,
+        warnings: &mut Vec<String>,
+    ) -> Vec<(String, PathBuf)> {
+        let frames = db
+            .get_frames_by_session(session_id)
+            .await
+            .unwrap_or_default();
+        let dir = graph.join("assets").join(ASSETS_FOLDER).join(date);
+
+        let mut copied = Vec::new();
+        for (index, frame) in pick_screenshots(&frames).iter().enumerate() {
+            let source = Path::new(&frame.file_path);
+            let extension = source
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or("jpg");
+            let file_name = format!("session-{}-{}.{}", session_id, index + 1, extension);
+            let target = dir.join(&file_name);
+            let link = format!("../assets/{}/{}/{}", ASSETS_FOLDER, date, file_name);
+            if dry_run {
+                copied.push((link, target));
+                continue;
+            }
+            let result = async {
+                let source = self.frame_store.local_path(&frame.file_path).await?;
+                fs::create_dir_all(&dir).await?;
+                fs::write(&target, crate::frame_crypto::read_frame(source).await?).await?;
+                anyhow::Ok(())
+            }
+            .await;
+            match result {
+                Ok(_) => copied.push((link, target)),
+                Err(e) => warnings.push(format!("会话 {} 截图复制失败: {}", session_id, e)),
+            }
+        }
+        copied
+    }
+
+    /// 生成当天日志页面（与已有页面合并后的完整内容）
+    ///
+    /// 返回（日志页面路径, 页面内容, 导出结果）；导出结果中的写入路径只含截图。
+    async fn build_journal(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        date: &str,
+        force_refresh: bool,
+        dry_run: bool,
+    ) -> Result<(PathBuf, String, ExportReport)> {
+        let graph = self.resolve_graph()?;
+        let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| anyhow!("日期格式错误: {}", date))?;
+        let summary = day_summary(db.clone(), llm_handle, date, force_refresh).await?;
+
+        let mut report = ExportReport {
+            target: self.target_name().to_string(),
+            exported_dates: vec![date.to_string()],
+            ..ExportReport::default()
+        };
+        let mut blocks = Vec::new();
+        for data in load_day_sessions(&db, date).await? {
+            let mut screenshots = Vec::new();
+            if self.config.include_screenshots {
+                let session_id = data.session.id.unwrap_or(0);
+                for (link, path) in self
+                    .copy_screenshots(&db, &graph, date, session_id, dry_run, &mut report.warnings)
+                    .await
+                {
+                    screenshots.push(link);
+                    report.written_paths.push(path);
+                }
+            }
+            blocks.push(SessionBlock { data, screenshots });
+        }
+        report.session_count = blocks.len();
+
+        let journal_path = self.journal_path(&graph, day);
+        let existing = if journal_path.exists() {
+            fs::read_to_string(&journal_path).await?
+        } else {
+            String::new()
+        };
+        let content = merge_journal(&existing, &render_journal_block(&summary, &blocks));
+        Ok((journal_path, content, report))
+    }
+}
+
+#[async_trait]
+impl Exporter for LogseqExporter {
+    fn target_name(&self) -> &'static str {
+        "Logseq"
+    }
+
+    async fn export_day(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        date: &str,
+        force_refresh: bool,
+    ) -> Result<ExportReport> {
+        let (journal_path, content, mut report) = self
+            .build_journal(db, llm_handle, date, force_refresh, false)
+            .await?;
+        if let Some(dir) = journal_path.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        fs::write(&journal_path, content).await?;
+        report.written_paths.insert(0, journal_path.clone());
+        report.note_paths.push(journal_path);
+        Ok(report)
+    }
+
+    async fn preview_day(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        date: &str,
+    ) -> Result<Vec<NotePreview>> {
+        let (journal_path, content, _) = self
+            .build_journal(db, llm_handle, date, false, true)
+            .await?;
+        let would_overwrite = match fs::read_to_string(&journal_path).await {
+            Ok(existing) => existing != content,
+            Err(_) => false,
+        };
+        Ok(vec![NotePreview {
+            path: journal_path,
+            content,
+            would_overwrite,
+        }])
+    }
+}
+
+/// 渲染导出块：顶层块携带当天统计属性，每个会话是带 org 时间戳的子块
+fn render_journal_block(summary: &DaySummary, sessions: &[SessionBlock]) -> String {
+    let total_minutes: i64 = sessions
+        .iter()
+        .map(|block| session_minutes(&block.data.session))
+        .sum();
+
+    let mut lines = Vec::new();
+    push_block(&mut lines, 0, "屏幕活动总结 [[Screen Analyzer]]");
+    push_properties(
+        &mut lines,
+        0,
+        &[
+            ("source", "screen-analyzer".to_string()),
+            ("total-minutes", total_minutes.to_string()),
+            ("session-count", sessions.len().to_string()),
+        ],
+    );
+    if !summary.summary_text.trim().is_empty() {
+        push_block(&mut lines, 1, summary.summary_text.trim());
+    }
+    if sessions.is_empty() {
+        push_block(&mut lines, 1, "当天没有会话记录");
+    }
+
+    for block in sessions {
+        let session = &block.data.session;
+        let title = if session.title.trim().is_empty() {
+            "未命名会话"
+        } else {
+            session.title.trim()
+        };
+        push_block(
+            &mut lines,
+            1,
+            &format!("{} {}", org_timestamp(session), title),
+        );
+
+        let mut properties = vec![
+            ("session-id", session.id.unwrap_or(0).to_string()),
+            ("duration", session_minutes(session).to_string()),
+        ];
+        if let Some(category) = session_category(session) {
+            properties.push(("category", category.to_string()));
+        }
+        push_properties(&mut lines, 1, &properties);
+
+        if !session.summary.trim().is_empty() {
+            push_block(&mut lines, 2, session.summary.trim());
+        }
+        for card in &block.data.cards {
+            push_block(
+                &mut lines,
+                2,
+                &format!(
+                    "{}-{} {}{}",
+                    card_clock(&card.start_time),
+                    card_clock(&card.end_time),
+                    card.title.trim(),
+                    if card.heuristic {
+                        "（本地分类）"
+                    } else {
+                        ""
+                    }
+                ),
+            );
+        }
+        if !block.data.issue_links.is_empty() {
+            push_block(&mut lines, 2, "相关工单");
+            for link in &block.data.issue_links {
+                push_block(&mut lines, 3, &render_issue_link(link));
+            }
+        }
+        for link in &block.screenshots {
+            push_block(&mut lines, 2, &format!("![截图]({})", link));
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// 追加一个块；多行内容的后续行缩进到块内容位置
+fn push_block(lines: &mut Vec<String>, depth: usize, text: &str) {
+    let indent = "\t".repeat(depth);
+    for (index, line) in text.lines().enumerate() {
+        if index == 0 {
+            lines.push(format!("{}- {}", indent, line));
+        } else {
+            lines.push(format!("{}  {}", indent, line));
+        }
+    }
+}
+
+/// 追加块属性（`key:: value`），紧跟在块首行之后
+fn push_properties(lines: &mut Vec<String>, depth: usize, properties: &[(&str, String)]) {
+    let indent = "\t".repeat(depth);
+    for (key, value) in properties {
+        lines.push(format!("{}  {}:: {}", indent, key, value));
+    }
+}
+
+/// 将导出块写入日志页面：替换此前导出的块，保留页面中其它块
+fn merge_journal(existing: &str, block: &str) -> String {
+    let trimmed = existing.trim();
+    if trimmed.is_empty() || trimmed == "-" {
+        return format!("{}\n", block);
+    }
+
+    let lines = existing.lines().collect::<Vec<_>>();
+    let is_top_level = |line: &str| line == "-" || line.starts_with("- ");
+    let start = lines.iter().enumerate().position(|(index, line)| {
+        is_top_level(line)
+            && lines[index + 1..]
+                .iter()
+                .take_while(|next| next.starts_with("  ") && next.contains(":: "))
+                .any(|next| next.trim() == SOURCE_PROPERTY)
+    });
+
+    let Some(start) = start else {
+        return format!("{}\n{}\n", existing.trim_end(), block);
+    };
+    let end = lines[start + 1..]
+        .iter()
+        .position(|line| is_top_level(line))
+        .map(|offset| start + 1 + offset)
+        .unwrap_or(lines.len());
+
+    let mut merged = lines[..start].to_vec();
+    merged.extend(block.lines());
+    merged.extend(&lines[end..]);
+    format!("{}\n", merged.join("\n"))
+}
+
+/// org 风格时间戳，如 `<2024-05-12 Sun 09:00-10:00>`
+fn org_timestamp(session: &Session) -> String {
+    format!(
+        "<{}-{}>",
+        session.start_time.format("%Y-%m-%d %a %H:%M"),
+        session.end_time.format("%H:%M")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{IssueLinkRecord, TimelineCardRecord};
+    use chrono::{TimeZone, Utc};
+
+    fn sample_block() -> SessionBlock {
+        SessionBlock {
+            data: SessionExportData {
+                session: Session {
+                    id: Some(3),
+                    start_time: Utc.with_ymd_and_hms(2024, 5, 12, 9, 0, 0).unwrap(),
+                    end_time: Utc.with_ymd_and_hms(2024, 5, 12, 10, 30, 0).unwrap(),
+                    title: "接口重构".to_string(),
+                    summary: "拆分鉴权模块\n补充单元测试".to_string(),
+                    video_path: None,
+                    tags: r#"[{"category":"work","confidence":0.9,"keywords":["rust"]}]"#
+                        .to_string(),
+                    created_at: None,
+                    device_name: None,
+                    device_type: None,
+                },
+                cards: vec![TimelineCardRecord {
+                    id: None,
+                    session_id: 3,
+                    llm_call_id: None,
+                    start_time: "2024-05-12T09:00:00+08:00".to_string(),
+                    end_time: "2024-05-12T09:45:00+08:00".to_string(),
+                    category: "work".to_string(),
+                    subcategory: String::new(),
+                    title: "编写 API".to_string(),
+                    summary: String::new(),
+                    detailed_summary: String::new(),
+                    distractions: None,
+                    app_sites: "{}".to_string(),
+                    confidence: None,
+                    heuristic: false,
+                    video_preview_path: None,
+                    created_at: Utc::now(),
+                }],
+                issue_links: vec![IssueLinkRecord {
+                    session_id: 3,
+                    provider: "jira".to_string(),
+                    issue_key: "API-42".to_string(),
+                    title: "拆分鉴权服务".to_string(),
+                    state: "进行中".to_string(),
+                    url: "https://team.atlassian.net/browse/API-42".to_string(),
+                    resolved_at: Utc::now(),
+                }],
+                apps: Vec::new(),
+                domains: Vec::new(),
+            },
+            screenshots: vec!["../assets/screen-analyzer/2024-05-12/session-3-1.png".to_string()],
+        }
+    }
+
+    fn sample_summary() -> DaySummary {
+        DaySummary {
+            date: "2024-05-12".to_string(),
+            summary_text: "专注于后端开发".to_string(),
+            device_stats: Vec::new(),
+            parallel_work: Vec::new(),
+            usage_patterns: Vec::new(),
+            active_device_count: 1,
+            domain_stats: Vec::new(),
+            app_limit_breaches: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_render_journal_block() {
+        let content = render_journal_block(&sample_summary(), &[sample_block()]);
+        assert_eq!(
+            content,
+            "- 屏幕活动总结 [[Screen Analyzer]]\n\
+             \x20 source:: screen-analyzer\n\
+             \x20 total-minutes:: 90\n\
+             \x20 session-count:: 1\n\
+             \t- 专注于后端开发\n\
+             \t- <2024-05-12 Sun 09:00-10:30> 接口重构\n\
+             \t  session-id:: 3\n\
+             \t  duration:: 90\n\
+             \t  category:: work\n\
+             \t\t- 拆分鉴权模块\n\
+             \t\t  补充单元测试\n\
+             \t\t- 09:00-09:45 编写 API\n\
+             \t\t- 相关工单\n\
+             \t\t\t- [API-42](https://team.atlassian.net/browse/API-42) 拆分鉴权服务（进行中）\n\
+             \t\t- ![截图](../assets/screen-analyzer/2024-05-12/session-3-1.png)"
+        );
+    }
+
+    #[test]
+    fn test_merge_journal_replaces_exported_block() {
+        let block = "- 屏幕活动总结 [[Screen Analyzer]]\n  source:: screen-analyzer\n\t- 新内容";
+
+        // 空日志页面（Logseq 新建时只有 "-"）
+        assert_eq!(merge_journal("-\n", block), format!("{}\n", block));
+
+        // 没有旧块时追加到末尾
+        let existing = "- 早会记录\n\t- 讨论排期\n";
+        assert_eq!(
+            merge_journal(existing, block),
+            format!("- 早会记录\n\t- 讨论排期\n{}\n", block)
+        );
+
+        // 替换旧块，前后用户块保持不变
+        let existing = "- 早会记录\n- 屏幕活动总结 [[Screen Analyzer]]\n  source:: screen-analyzer\n  total-minutes:: 30\n\t- 旧内容\n\t\t- 旧会话\n- 晚间复盘\n";
+        assert_eq!(
+            merge_journal(existing, block),
+            format!("- 早会记录\n{}\n- 晚间复盘\n", block)
+        );
+    }
+}
@@ -2,6 +2,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 // 重新导出其他模块的类型
 pub use crate::llm::plugin::{ActivityCategory, ActivityTag, KeyMoment};
@@ -34,6 +35,56 @@ pub struct AppConfig {
     pub notion_config: Option<NotionConfig>,
     /// Obsidian 导出配置
     pub obsidian_config: Option<ObsidianExportConfig>,
+    /// Logseq 导出配置
+    pub logseq_config: Option<LogseqExportConfig>,
+    /// HTML 报告导出配置
+    pub html_config: Option<HtmlExportConfig>,
+    /// Webhook 导出配置
+    pub webhook_config: Option<WebhookExportConfig>,
+    /// MQTT 状态发布配置
+    pub mqtt_config: Option<MqttConfig>,
+    /// 邮件摘要配置
+    pub digest_config: Option<DigestConfig>,
+    /// Slack / Discord 每日总结推送配置
+    pub chat_config: Option<ChatSummaryConfig>,
+    /// Google 日历配置
+    pub calendar_config: Option<GoogleCalendarConfig>,
+    /// 工单关联配置
+    pub issue_link_config: Option<IssueLinkConfig>,
+    /// Toggl / Clockify 工时导出配置
+    pub time_tracking_config: Option<TimeTrackingExportConfig>,
+    /// 语义搜索向量配置
+    pub embedding_config: Option<EmbeddingConfig>,
+    /// 截图 OCR 配置
+    pub ocr_config: Option<OcrConfig>,
+    /// 浏览器活动接收配置
+    pub browser_config: Option<BrowserActivityConfig>,
+    /// 磁盘空间预算配置
+    pub storage_budget_config: Option<StorageBudgetConfig>,
+    /// 总结重新生成与批量导出队列配置
+    pub summary_queue_config: Option<SummaryQueueConfig>,
+    /// 视觉调用前的帧预处理配置
+    pub frame_prep_config: Option<FramePrepConfig>,
+    /// LLM 响应缓存配置
+    pub llm_cache_config: Option<LlmCacheConfig>,
+    /// 低置信度卡片复核配置
+    pub review_queue_config: Option<ReviewQueueConfig>,
+    /// 本地启发式分类配置
+    pub heuristic_config: Option<HeuristicConfig>,
+    /// 专注时段配置
+    pub focus_config: Option<FocusConfig>,
+    /// 分心提醒配置
+    pub distraction_alert_config: Option<DistractionAlertConfig>,
+    /// 应用使用时长上限配置
+    pub app_limit_config: Option<AppLimitConfig>,
+    /// 过劳迹象提醒配置
+    pub wellbeing_config: Option<WellbeingConfig>,
+    /// 局域网多设备同步配置
+    pub lan_sync_config: Option<LanSyncConfig>,
+    /// 加密云备份配置
+    pub backup_config: Option<BackupConfig>,
+    /// 截图存储后端配置
+    pub frame_store_config: Option<FrameStoreConfig>,
 }
 
 /// 日志设置
@@ -68,6 +119,217 @@ pub struct CaptureSettings {
     pub detect_black_screen: bool,
     /// 黑屏检测阈值(0-255)
     pub black_screen_threshold: u8,
+    /// 是否记录前台应用与窗口标题
+    #[serde(default = "default_capture_window_info")]
+    pub capture_window_info: bool,
+    /// 是否根据键鼠输入检测空闲
+    #[serde(default = "default_idle_detection")]
+    pub idle_detection: bool,
+    /// 无输入超过该分钟数后，截图标记为空闲
+    #[serde(default = "default_idle_threshold_minutes")]
+    pub idle_threshold_minutes: u32,
+    /// 无输入超过该分钟数后暂停截屏，恢复输入时开始新会话
+    #[serde(default = "default_idle_pause_minutes")]
+    pub idle_pause_minutes: u32,
+    /// 要截取的显示器序号（为空时截取全部显示器）
+    #[serde(default)]
+    pub monitors: Vec<usize>,
+    /// 是否为每个显示器单独保存截图（关闭时合成为一张图）
+    #[serde(default)]
+    pub per_monitor_frames: bool,
+    /// 隐私区域（截图写入磁盘前遮挡或模糊）
+    #[serde(default)]
+    pub privacy_zones: Vec<PrivacyZone>,
+    /// 是否跳过与上一帧几乎相同的截图
+    #[serde(default)]
+    pub dedupe_frames: bool,
+    /// 判定为重复的感知哈希差异位数上限（0-64）
+    #[serde(default = "default_dedupe_threshold")]
+    pub dedupe_threshold: u32,
+    /// 截图保存格式（质量使用 `image_quality`）
+    #[serde(default)]
+    pub frame_format: FrameFormat,
+    /// 截图保存超过该小时数后转码为压缩率更高的格式（0 为关闭）
+    #[serde(default)]
+    pub recompress_after_hours: u32,
+    /// 旧截图转码的目标格式
+    #[serde(default = "default_recompress_format")]
+    pub recompress_format: FrameFormat,
+    /// 旧截图转码的质量(1-100)
+    #[serde(default = "default_recompress_quality")]
+    pub recompress_quality: u8,
+    /// 免打扰时段（时段内不截屏）
+    #[serde(default)]
+    pub quiet_hours: QuietHours,
+    /// 会议检测（视频通话或演示时暂停截屏或标记为会议）
+    #[serde(default)]
+    pub meeting_detection: MeetingDetection,
+    /// 是否加密保存截图（AES-256-GCM，密钥保存在系统钥匙串）
+    #[serde(default)]
+    pub encrypt_frames: bool,
+}
+
+/// 会议检测配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeetingDetection {
+    /// 是否启用
+    pub enabled: bool,
+    /// 摄像头使用中视为会议
+    pub camera: bool,
+    /// 麦克风使用中视为会议
+    pub microphone: bool,
+    /// 全屏演示（Windows 演示模式或前台窗口全屏）视为会议
+    pub presentation: bool,
+    /// 检测到会议时的处理方式
+    pub action: MeetingAction,
+}
+
+impl Default for MeetingDetection {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            camera: true,
+            microphone: true,
+            presentation: false,
+            action: MeetingAction::Pause,
+        }
+    }
+}
+
+/// 检测到会议时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MeetingAction {
+    /// 暂停截屏
+    #[default]
+    Pause,
+    /// 继续截屏，会话分析时会议时段以会议卡片代替屏幕内容
+    Mark,
+}
+
+/// 免打扰时段：每天的固定时段（可跨午夜）与整天不截屏的星期
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuietHours {
+    /// 是否启用
+    pub enabled: bool,
+    /// 开始时间（HH:MM，本地时间）
+    pub start: String,
+    /// 结束时间（HH:MM，早于开始时间表示跨午夜）
+    pub end: String,
+    /// 整天免打扰的星期（1=周一 … 7=周日）
+    pub days_off: Vec<u8>,
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: "22:00".to_string(),
+            end: "08:00".to_string(),
+            days_off: vec![6, 7],
+        }
+    }
+}
+
+/// 截图帧的保存格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameFormat {
+    /// JPEG
+    #[default]
+    Jpeg,
+    /// 有损 WebP，同等画质下体积更小
+    Webp,
+    /// AVIF（需启用 `avif` 编译特性，否则回退为 JPEG），压缩率最高但编码较慢
+    Avif,
+}
+
+/// 隐私区域：坐标与尺寸为相对显示器宽高的比例（0-1），不受分辨率与缩放影响
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrivacyZone {
+    /// 生效的显示器序号，为空时对所有显示器生效
+    #[serde(default)]
+    pub monitor: Option<usize>,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// 处理方式
+    #[serde(default)]
+    pub mode: PrivacyZoneMode,
+}
+
+/// 隐私区域处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivacyZoneMode {
+    /// 模糊（保留大致布局，文字不可辨认）
+    #[default]
+    Blur,
+    /// 排除（填充为黑色）
+    Exclude,
+}
+
+fn default_capture_window_info() -> bool {
+    true
+}
+
+fn default_idle_detection() -> bool {
+    true
+}
+
+fn default_idle_threshold_minutes() -> u32 {
+    5
+}
+
+fn default_idle_pause_minutes() -> u32 {
+    15
+}
+
+fn default_dedupe_threshold() -> u32 {
+    5
+}
+
+fn default_recompress_format() -> FrameFormat {
+    FrameFormat::Webp
+}
+
+fn default_recompress_quality() -> u8 {
+    50
+}
+
+impl CaptureSettings {
+    /// 空闲判定阈值（秒，至少 1 分钟）
+    pub fn idle_threshold_seconds(&self) -> u64 {
+        u64::from(self.idle_threshold_minutes.max(1)) * 60
+    }
+
+    /// 暂停截屏的空闲时长（秒），不小于空闲判定阈值加 1 分钟
+    pub fn idle_pause_seconds(&self) -> u64 {
+        u64::from(
+            self.idle_pause_minutes
+                .max(self.idle_threshold_minutes.max(1) + 1),
+        ) * 60
+    }
+
+    /// 实际要截取的显示器序号：忽略已不存在的显示器，未选择或全部失效时截取全部
+    pub fn selected_monitors(&self, available: usize) -> Vec<usize> {
+        let mut selected: Vec<usize> = self
+            .monitors
+            .iter()
+            .copied()
+            .filter(|index| *index < available)
+            .collect();
+        selected.sort_unstable();
+        selected.dedup();
+        if selected.is_empty() {
+            (0..available).collect()
+        } else {
+            selected
+        }
+    }
 }
 
 impl Default for CaptureSettings {
@@ -77,6 +339,22 @@ impl Default for CaptureSettings {
             image_quality: 85,
             detect_black_screen: true,
             black_screen_threshold: 5,
+            capture_window_info: true,
+            idle_detection: true,
+            idle_threshold_minutes: default_idle_threshold_minutes(),
+            idle_pause_minutes: default_idle_pause_minutes(),
+            monitors: Vec::new(),
+            per_monitor_frames: false,
+            privacy_zones: Vec::new(),
+            dedupe_frames: false,
+            dedupe_threshold: default_dedupe_threshold(),
+            frame_format: FrameFormat::Jpeg,
+            recompress_after_hours: 0,
+            recompress_format: default_recompress_format(),
+            recompress_quality: default_recompress_quality(),
+            quiet_hours: QuietHours::default(),
+            meeting_detection: MeetingDetection::default(),
+            encrypt_frames: false,
         }
     }
 }
@@ -166,6 +444,56 @@ pub struct PersistedAppConfig {
     pub notion_config: Option<NotionConfig>,
     /// Obsidian 导出配置
     pub obsidian_config: Option<ObsidianExportConfig>,
+    /// Logseq 导出配置
+    pub logseq_config: Option<LogseqExportConfig>,
+    /// HTML 报告导出配置
+    pub html_config: Option<HtmlExportConfig>,
+    /// Webhook 导出配置
+    pub webhook_config: Option<WebhookExportConfig>,
+    /// MQTT 状态发布配置
+    pub mqtt_config: Option<MqttConfig>,
+    /// 邮件摘要配置
+    pub digest_config: Option<DigestConfig>,
+    /// Slack / Discord 每日总结推送配置
+    pub chat_config: Option<ChatSummaryConfig>,
+    /// Google 日历配置
+    pub calendar_config: Option<GoogleCalendarConfig>,
+    /// 工单关联配置
+    pub issue_link_config: Option<IssueLinkConfig>,
+    /// Toggl / Clockify 工时导出配置
+    pub time_tracking_config: Option<TimeTrackingExportConfig>,
+    /// 语义搜索向量配置
+    pub embedding_config: Option<EmbeddingConfig>,
+    /// 截图 OCR 配置
+    pub ocr_config: Option<OcrConfig>,
+    /// 浏览器活动接收配置
+    pub browser_config: Option<BrowserActivityConfig>,
+    /// 磁盘空间预算配置
+    pub storage_budget_config: Option<StorageBudgetConfig>,
+    /// 总结重新生成与批量导出队列配置
+    pub summary_queue_config: Option<SummaryQueueConfig>,
+    /// 视觉调用前的帧预处理配置
+    pub frame_prep_config: Option<FramePrepConfig>,
+    /// LLM 响应缓存配置
+    pub llm_cache_config: Option<LlmCacheConfig>,
+    /// 低置信度卡片复核配置
+    pub review_queue_config: Option<ReviewQueueConfig>,
+    /// 本地启发式分类配置
+    pub heuristic_config: Option<HeuristicConfig>,
+    /// 专注时段配置
+    pub focus_config: Option<FocusConfig>,
+    /// 分心提醒配置
+    pub distraction_alert_config: Option<DistractionAlertConfig>,
+    /// 应用使用时长上限配置
+    pub app_limit_config: Option<AppLimitConfig>,
+    /// 过劳迹象提醒配置
+    pub wellbeing_config: Option<WellbeingConfig>,
+    /// 局域网多设备同步配置
+    pub lan_sync_config: Option<LanSyncConfig>,
+    /// 加密云备份配置
+    pub backup_config: Option<BackupConfig>,
+    /// 截图存储后端配置
+    pub frame_store_config: Option<FrameStoreConfig>,
 }
 
 impl Default for PersistedAppConfig {
@@ -183,6 +511,31 @@ impl Default for PersistedAppConfig {
             database_config: None,
             notion_config: Some(NotionConfig::default()),
             obsidian_config: Some(ObsidianExportConfig::default()),
+            logseq_config: Some(LogseqExportConfig::default()),
+            html_config: Some(HtmlExportConfig::default()),
+            webhook_config: Some(WebhookExportConfig::default()),
+            mqtt_config: Some(MqttConfig::default()),
+            digest_config: Some(DigestConfig::default()),
+            chat_config: Some(ChatSummaryConfig::default()),
+            calendar_config: Some(GoogleCalendarConfig::default()),
+            issue_link_config: Some(IssueLinkConfig::default()),
+            time_tracking_config: Some(TimeTrackingExportConfig::default()),
+            embedding_config: Some(EmbeddingConfig::default()),
+            ocr_config: Some(OcrConfig::default()),
+            browser_config: Some(BrowserActivityConfig::default()),
+            storage_budget_config: Some(StorageBudgetConfig::default()),
+            summary_queue_config: Some(SummaryQueueConfig::default()),
+            frame_prep_config: Some(FramePrepConfig::default()),
+            llm_cache_config: Some(LlmCacheConfig::default()),
+            review_queue_config: Some(ReviewQueueConfig::default()),
+            heuristic_config: Some(HeuristicConfig::default()),
+            focus_config: Some(FocusConfig::default()),
+            distraction_alert_config: Some(DistractionAlertConfig::default()),
+            app_limit_config: Some(AppLimitConfig::default()),
+            wellbeing_config: Some(WellbeingConfig::default()),
+            lan_sync_config: Some(LanSyncConfig::default()),
+            backup_config: Some(BackupConfig::default()),
+            frame_store_config: Some(FrameStoreConfig::default()),
         }
     }
 }
@@ -202,6 +555,12 @@ pub struct LLMProviderConfig {
     pub auth_token: String,
     #[serde(default)]
     pub codex_config: Option<serde_json::Value>,
+    /// Ollama 本地模型配置
+    #[serde(default)]
+    pub ollama_config: Option<serde_json::Value>,
+    /// Gemini 配置（API Key 保存在 api_key 字段）
+    #[serde(default)]
+    pub gemini_config: Option<serde_json::Value>,
 }
 
 /// UI设置
@@ -505,6 +864,18 @@ pub struct NotionConfig {
     pub sync_options: NotionSyncOptions,
     /// 失败重试次数
     pub max_retries: u32,
+    /// 每日总结数据库 ID（为空时写入会话数据库）
+    #[serde(default)]
+    pub daily_database_id: String,
+    /// 周报数据库 ID（为空时写入会话数据库）
+    #[serde(default)]
+    pub weekly_database_id: String,
+    /// 关键截图的上传方式
+    #[serde(default)]
+    pub screenshot_upload: NotionScreenshotUpload,
+    /// S3 兼容存储配置（截图上传方式为 s3 时使用）
+    #[serde(default)]
+    pub s3: S3StorageConfig,
 }
 
 impl Default for NotionConfig {
@@ -515,10 +886,42 @@ impl Default for NotionConfig {
             database_id: String::new(),
             sync_options: NotionSyncOptions::default(),
             max_retries: 3,
+            daily_database_id: String::new(),
+            weekly_database_id: String::new(),
+            screenshot_upload: NotionScreenshotUpload::default(),
+            s3: S3StorageConfig::default(),
         }
     }
 }
 
+/// Notion 关键截图上传方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotionScreenshotUpload {
+    /// 通过 Notion 文件上传 API 上传，图片保存在 Notion 中
+    #[default]
+    Notion,
+    /// 通过预签名 URL 上传到 S3 兼容存储，页面以外链引用图片
+    S3,
+}
+
+/// S3 兼容存储配置（AWS S3、MinIO、Cloudflare R2 等）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct S3StorageConfig {
+    /// 服务地址，如 https://s3.us-east-1.amazonaws.com
+    pub endpoint: String,
+    /// 区域（R2 填 auto）
+    pub region: String,
+    pub bucket: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// 图片对外访问地址前缀（需可公开读取），为空时使用 endpoint/bucket
+    pub public_base_url: String,
+    /// 对象键前缀
+    pub key_prefix: String,
+}
+
 /// Notion 同步选项
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NotionSyncOptions {
@@ -532,6 +935,9 @@ pub struct NotionSyncOptions {
     pub sync_screenshots: bool,
     /// 视频大小限制（MB）
     pub video_size_limit_mb: u32,
+    /// 同步周报（专注时长、生产力评分等汇总）
+    #[serde(default)]
+    pub sync_weekly_summary: bool,
 }
 
 impl Default for NotionSyncOptions {
@@ -542,6 +948,7 @@ impl Default for NotionSyncOptions {
             sync_daily_summary: false, // 默认不同步每日总结（Notion 会自动总结）
             sync_screenshots: true,
             video_size_limit_mb: 5,
+            sync_weekly_summary: false,
         }
     }
 }
@@ -560,6 +967,143 @@ impl Default for ObsidianExportMode {
     }
 }
 
+/// 每日笔记会话索引的分组方式
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionGrouping {
+    #[default]
+    None,
+    Project,
+    Category,
+}
+
+/// Obsidian 导出目标
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObsidianExportTarget {
+    /// 直接写入 Vault
+    #[default]
+    Vault,
+    /// 打包为 zip 压缩包（同样的 Daily/Sessions/Assets 目录结构）
+    Zip,
+}
+
+/// 导出文件已存在且内容不同时的冲突处理策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportConflictPolicy {
+    /// 直接覆盖
+    #[default]
+    Overwrite,
+    /// 保留已有文件，不写入
+    Skip,
+    /// 合并：仅替换标记块内的生成内容，保留自定义 frontmatter 与手写内容
+    Merge,
+    /// 另存为 `-v2`、`-v3` 等新版本文件
+    NewVersion,
+}
+
+/// 导出笔记中标题、标签等固定文本使用的语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportLanguage {
+    /// 简体中文
+    #[default]
+    Zh,
+    /// 英文
+    En,
+}
+
+/// 会话笔记的截图选取策略
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenshotStrategy {
+    /// 首尾两帧
+    #[default]
+    FirstLast,
+    /// 均匀选取 n 帧
+    EvenlySpaced(usize),
+    /// 每个时间线卡片一帧（取最接近卡片中点的帧）
+    PerTimelineCard,
+    /// 画面变化最大的若干帧
+    HighestActivity,
+}
+
+/// 复制模式下导出截图的图片格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssetFormat {
+    /// 保持原始 JPEG
+    #[default]
+    Original,
+    /// 转码为 WebP
+    Webp,
+    /// 转码为 AVIF（需启用 `avif` 编译特性，否则回退为原始格式）
+    Avif,
+}
+
+/// 截图在笔记中的链接写法
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssetLinkStyle {
+    /// 相对路径的 Markdown 图片链接：`![](Assets/...)`
+    #[default]
+    Markdown,
+    /// Obsidian 维基嵌入：`![[session-1-0.jpg]]`，笔记移动后仍可解析
+    Wiki,
+}
+
+/// 每日笔记已存在时的更新方式
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DailyNoteUpdateMode {
+    /// 整体覆盖
+    #[default]
+    Overwrite,
+    /// 合并 frontmatter，仅替换标记块内的生成内容，保留手写笔记
+    Merge,
+}
+
+fn default_project_pattern() -> String {
+    r"^\s*\[([^\]]+)\]".to_string()
+}
+
+fn default_daily_path_template() -> String {
+    "Daily/{{date}}".to_string()
+}
+
+fn default_session_path_template() -> String {
+    "Sessions/{{date}}".to_string()
+}
+
+fn default_assets_path_template() -> String {
+    "Assets/{{date}}".to_string()
+}
+
+fn default_weekly_path_template() -> String {
+    "Weekly/{{week}}".to_string()
+}
+
+fn default_index_folder() -> String {
+    "Index".to_string()
+}
+
+fn default_projects_folder() -> String {
+    "Projects".to_string()
+}
+
+fn default_session_title_max_len() -> usize {
+    40
+}
+
+fn default_asset_quality() -> u8 {
+    75
+}
+
+fn default_tag_prefix() -> String {
+    "screen-analyzer".to_string()
+}
+
 /// Obsidian 导出配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ObsidianExportConfig {
@@ -583,6 +1127,129 @@ pub struct ObsidianExportConfig {
     pub daily_template: Option<String>,
     /// 自定义会话模板（可选）
     pub session_template: Option<String>,
+    /// 类别显示名映射（键忽略大小写与首尾空白，值为规范显示名）
+    #[serde(default)]
+    pub category_labels: HashMap<String, String>,
+    /// 是否在笔记末尾附加"生成来源"页脚
+    #[serde(default)]
+    pub include_footer: bool,
+    /// 会话索引分组方式（none/project/category）
+    #[serde(default)]
+    pub group_sessions_by: SessionGrouping,
+    /// 项目提取规则（正则，取第一个捕获组，匹配会话标题）
+    #[serde(default = "default_project_pattern")]
+    pub project_pattern: String,
+    /// 增量同步：内容未变化的笔记不再重写，避免污染 Obsidian 同步历史
+    #[serde(default)]
+    pub incremental_sync: bool,
+    /// 每日笔记路径模板（相对根目录，不含 .md），支持 {{year}} {{month}} {{day}} {{date}}
+    #[serde(default = "default_daily_path_template")]
+    pub daily_path_template: String,
+    /// 会话笔记目录模板（相对根目录），占位符同上
+    #[serde(default = "default_session_path_template")]
+    pub session_path_template: String,
+    /// 截图资源目录模板（相对根目录），占位符同上
+    #[serde(default = "default_assets_path_template")]
+    pub assets_path_template: String,
+    /// 周报路径模板（相对根目录，不含 .md），额外支持 {{week}}（如 2024-W19）
+    #[serde(default = "default_weekly_path_template")]
+    pub weekly_path_template: String,
+    /// 索引目录名（相对根目录）
+    #[serde(default = "default_index_folder")]
+    pub index_folder: String,
+    /// 每日笔记更新方式（overwrite/merge）
+    #[serde(default)]
+    pub daily_note_update_mode: DailyNoteUpdateMode,
+    /// 截图选取策略
+    #[serde(default)]
+    pub screenshot_strategy: ScreenshotStrategy,
+    /// 复制截图时的输出格式（original/webp/avif）
+    #[serde(default)]
+    pub asset_format: AssetFormat,
+    /// 转码质量（1-100）
+    #[serde(default = "default_asset_quality")]
+    pub asset_quality: u8,
+    /// 截图链接写法（markdown/wiki）
+    #[serde(default)]
+    pub asset_link_style: AssetLinkStyle,
+    /// 截图存放到 Vault 配置的附件目录（.obsidian/app.json 中的 attachmentFolderPath）
+    #[serde(default)]
+    pub use_vault_attachment_folder: bool,
+    /// 在会话笔记正文中额外输出 Dataview 行内字段（如 `duration:: 45`）
+    #[serde(default)]
+    pub dataview_inline_fields: bool,
+    /// 在笔记正文中输出 Obsidian 标签（如 `#screen-analyzer/work`），便于关系图与标签面板导航
+    #[serde(default)]
+    pub emit_body_tags: bool,
+    /// 正文标签前缀（为空时不加前缀）
+    #[serde(default = "default_tag_prefix")]
+    pub tag_prefix: String,
+    /// 在每日笔记中输出"应用使用"分布
+    #[serde(default)]
+    pub include_app_breakdown: bool,
+    /// 为每周生成 Obsidian Canvas（.canvas）视图
+    #[serde(default)]
+    pub generate_week_canvas: bool,
+    /// 在会话与每日笔记中嵌入 Mermaid 甘特图时间线
+    #[serde(default)]
+    pub include_mermaid_timeline: bool,
+    /// 存储清理时一并删除过期会话复制到 Vault 的截图，并移除笔记中的嵌入
+    #[serde(default)]
+    pub prune_expired_assets: bool,
+    /// 笔记固定文本（标题、小节名、日期格式）的语言
+    #[serde(default)]
+    pub export_language: ExportLanguage,
+    /// 导出时区（相对 UTC 的分钟偏移，如 480 表示 UTC+8）；为空时沿用记录时的系统本地时区
+    #[serde(default)]
+    pub timezone_offset_minutes: Option<i32>,
+    /// 导出目标：写入 Vault 或打包为 zip
+    #[serde(default)]
+    pub export_target: ObsidianExportTarget,
+    /// zip 导出时压缩包的输出路径
+    #[serde(default)]
+    pub archive_path: String,
+    /// 每日笔记文件名格式（strftime，如 `%Y-%m-%d %A`，兼容 Periodic Notes/Daily Notes 插件的命名）；
+    /// 为空时沿用路径模板中的文件名
+    #[serde(default)]
+    pub daily_filename_format: String,
+    /// 每日笔记 frontmatter 输出 `aliases:`（日期与完整日期），合并模式下与已有别名取并集
+    #[serde(default)]
+    pub daily_note_aliases: bool,
+    /// 会话笔记与每日笔记已存在时的冲突处理策略（每日笔记更新方式为合并时以合并为准）
+    #[serde(default)]
+    pub conflict_policy: ExportConflictPolicy,
+    /// 会话笔记文件名追加会话标题 slug（如 `..._session-42_api-refactor.md`），便于浏览 Sessions 目录
+    #[serde(default)]
+    pub session_filename_title: bool,
+    /// 文件名中标题 slug 的最大字符数
+    #[serde(default = "default_session_title_max_len")]
+    pub session_title_max_len: usize,
+    /// 每日笔记以 Obsidian callout（`> [!work]`）逐条渲染当天时间线
+    #[serde(default)]
+    pub timeline_callouts: bool,
+    /// 类别到 callout 类型的映射（键忽略大小写与首尾空白），未配置的类别使用类别名本身
+    #[serde(default)]
+    pub category_callouts: HashMap<String, String>,
+    /// 按项目规则生成项目汇总笔记（累计时长、最近会话与周趋势）
+    #[serde(default)]
+    pub generate_project_notes: bool,
+    /// 项目笔记目录（相对根目录）
+    #[serde(default = "default_projects_folder")]
+    pub projects_folder: String,
+    /// 自定义指标（按类别分钟数的表达式），输出到每日/周报 frontmatter 并可在模板中以
+    /// `{{metric_<名称>}}` 引用
+    #[serde(default)]
+    pub custom_metrics: Vec<CustomMetric>,
+    /// 只导出指定设备的会话与统计（如仅工作笔记本），为空时导出全部设备
+    #[serde(default)]
+    pub device_filter: Option<String>,
+}
+
+/// 自定义指标：名称与表达式（如 `deep_work` = `work + learning - communication * 0.5`）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomMetric {
+    pub name: String,
+    pub expression: String,
 }
 
 impl Default for ObsidianExportConfig {
@@ -598,6 +1265,900 @@ impl Default for ObsidianExportConfig {
             weekly_target_minutes: 2400,
             daily_template: None,
             session_template: None,
+            category_labels: HashMap::new(),
+            include_footer: false,
+            group_sessions_by: SessionGrouping::None,
+            project_pattern: default_project_pattern(),
+            incremental_sync: false,
+            daily_path_template: default_daily_path_template(),
+            session_path_template: default_session_path_template(),
+            assets_path_template: default_assets_path_template(),
+            weekly_path_template: default_weekly_path_template(),
+            index_folder: default_index_folder(),
+            daily_note_update_mode: DailyNoteUpdateMode::Overwrite,
+            screenshot_strategy: ScreenshotStrategy::FirstLast,
+            asset_format: AssetFormat::Original,
+            asset_quality: default_asset_quality(),
+            asset_link_style: AssetLinkStyle::Markdown,
+            use_vault_attachment_folder: false,
+            dataview_inline_fields: false,
+            emit_body_tags: false,
+            tag_prefix: default_tag_prefix(),
+            include_app_breakdown: false,
+            generate_week_canvas: false,
+            include_mermaid_timeline: false,
+            prune_expired_assets: false,
+            export_language: ExportLanguage::Zh,
+            timezone_offset_minutes: None,
+            export_target: ObsidianExportTarget::Vault,
+            archive_path: String::new(),
+            daily_filename_format: String::new(),
+            daily_note_aliases: false,
+            conflict_policy: ExportConflictPolicy::Overwrite,
+            session_filename_title: false,
+            session_title_max_len: default_session_title_max_len(),
+            timeline_callouts: false,
+            category_callouts: HashMap::new(),
+            generate_project_notes: false,
+            projects_folder: default_projects_folder(),
+            custom_metrics: Vec::new(),
+            device_filter: None,
+        }
+    }
+}
+
+/// Logseq 导出配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogseqExportConfig {
+    /// 是否启用 Logseq 导出
+    #[serde(default)]
+    pub enabled: bool,
+    /// 图谱目录（包含 journals/ 与 assets/ 的目录）
+    #[serde(default)]
+    pub graph_path: String,
+    /// 复制会话首尾截图到 assets/ 并在日志中嵌入
+    #[serde(default)]
+    pub include_screenshots: bool,
+}
+
+/// HTML 报告导出配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HtmlExportConfig {
+    /// 是否启用 HTML 报告导出
+    #[serde(default)]
+    pub enabled: bool,
+    /// 报告输出目录（不存在时自动创建）
+    #[serde(default)]
+    pub output_dir: String,
+    /// 以内嵌 base64 缩略图的形式附带会话首尾截图
+    #[serde(default)]
+    pub include_thumbnails: bool,
+    /// 导出每日报告时同时更新所在周的周报
+    #[serde(default)]
+    pub generate_weekly: bool,
+}
+
+/// Webhook 导出配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookExportConfig {
+    /// 是否启用 Webhook 导出
+    pub enabled: bool,
+    /// 接收导出数据的地址（n8n / Zapier / 自建服务等）
+    pub url: String,
+    /// 签名密钥，非空时以 HMAC-SHA256 签名请求体
+    pub secret: String,
+    /// 失败重试次数（仅网络错误、429 与 5xx 重试）
+    pub max_retries: u32,
+}
+
+impl Default for WebhookExportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            secret: String::new(),
+            max_retries: 3,
+        }
+    }
+}
+
+/// MQTT 状态发布配置（供 Home Assistant 等家庭自动化订阅）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+    /// 是否启用 MQTT 状态发布
+    pub enabled: bool,
+    /// Broker 地址
+    pub host: String,
+    /// Broker 端口
+    pub port: u16,
+    /// 用户名（为空时匿名连接）
+    pub username: String,
+    /// 密码
+    pub password: String,
+    /// 主题前缀，如 screen-analyzer/category
+    pub topic_prefix: String,
+    /// 客户端 ID（为空时自动生成）
+    pub client_id: String,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: 1883,
+            username: String::new(),
+            password: String::new(),
+            topic_prefix: "screen-analyzer".to_string(),
+            client_id: String::new(),
+        }
+    }
+}
+
+/// SMTP 连接加密方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// 明文连接后升级为 TLS（通常为 587 端口）
+    #[default]
+    StartTls,
+    /// 直接 TLS 连接（通常为 465 端口）
+    Tls,
+    /// 不加密（仅用于本地中继）
+    None,
+}
+
+/// 邮件摘要配置（按计划通过 SMTP 发送每日/每周总结）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DigestConfig {
+    /// 是否启用邮件摘要
+    pub enabled: bool,
+    /// SMTP 服务器地址
+    pub smtp_host: String,
+    /// SMTP 端口
+    pub smtp_port: u16,
+    /// 加密方式
+    pub security: SmtpSecurity,
+    /// 登录用户名（为空时不认证）
+    pub username: String,
+    /// 登录密码或授权码
+    pub password: String,
+    /// 发件人，如 "Screen Analyzer <me@example.com>"
+    pub from: String,
+    /// 收件人，多个地址以逗号分隔
+    pub to: String,
+    /// 每天发送当天的日报
+    pub daily: bool,
+    /// 每周发送当周的周报
+    pub weekly: bool,
+    /// 发送时间（HH:MM，本地时间）
+    pub send_time: String,
+    /// 周报发送日（1 = 周一 … 7 = 周日）
+    pub weekly_weekday: u32,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            smtp_host: String::new(),
+            smtp_port: 587,
+            security: SmtpSecurity::default(),
+            username: String::new(),
+            password: String::new(),
+            from: String::new(),
+            to: String::new(),
+            daily: true,
+            weekly: false,
+            send_time: "21:00".to_string(),
+            weekly_weekday: 7,
+        }
+    }
+}
+
+/// 聊天平台
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatPlatform {
+    #[default]
+    Slack,
+    Discord,
+}
+
+/// 单个 Slack 工作区 / Discord 频道的推送配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatWorkspace {
+    /// 显示名称（用于区分多个工作区）
+    pub name: String,
+    pub platform: ChatPlatform,
+    /// Incoming Webhook 地址
+    pub webhook_url: String,
+    /// 是否参与定时推送
+    pub enabled: bool,
+    /// 消息模板，为空时使用默认模板
+    pub template: String,
+}
+
+impl Default for ChatWorkspace {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            platform: ChatPlatform::default(),
+            webhook_url: String::new(),
+            enabled: true,
+            template: String::new(),
+        }
+    }
+}
+
+/// Slack / Discord 每日总结推送配置（每天结束时推送当日总结与关键指标）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatSummaryConfig {
+    /// 是否启用定时推送
+    pub enabled: bool,
+    /// 推送时间（HH:MM，本地时间）
+    pub send_time: String,
+    pub workspaces: Vec<ChatWorkspace>,
+}
+
+impl Default for ChatSummaryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            send_time: "22:00".to_string(),
+            workspaces: Vec::new(),
+        }
+    }
+}
+
+/// Google 日历配置（只读授权，同步当天会议用于标注时间线卡片与每日总结）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GoogleCalendarConfig {
+    /// 是否定时同步
+    pub enabled: bool,
+    /// OAuth 客户端 ID（Google Cloud 控制台中创建的“桌面应用”凭据）
+    pub client_id: String,
+    pub client_secret: String,
+    /// 授权后获得的刷新令牌，为空表示尚未连接
+    pub refresh_token: String,
+    /// 要同步的日历，默认为主日历
+    pub calendar_id: String,
+}
+
+impl Default for GoogleCalendarConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            client_id: String::new(),
+            client_secret: String::new(),
+            refresh_token: String::new(),
+            calendar_id: "primary".to_string(),
+        }
+    }
+}
+
+/// 工单关联配置（识别会话中的 GitHub / Jira 引用，经 API 解析标题与状态）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IssueLinkConfig {
+    /// 是否在会话分析完成后自动识别
+    pub enabled: bool,
+    /// GitHub 个人访问令牌（可选，私有仓库需要）
+    pub github_token: String,
+    /// Jira 站点地址，如 https://your-team.atlassian.net；为空时不识别 Jira 编号
+    pub jira_base_url: String,
+    pub jira_email: String,
+    pub jira_api_token: String,
+    /// 识别的 Jira 项目键（逗号分隔），为空时识别所有形如 ABC-123 的编号
+    pub jira_project_keys: String,
+}
+
+/// 工时导出配置（将会话作为工时条目推送到 Toggl Track 或 Clockify）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeTrackingExportConfig {
+    /// 是否启用工时导出
+    pub enabled: bool,
+    /// 工时服务：toggl 或 clockify
+    pub provider: String,
+    /// Toggl API Token 或 Clockify API Key
+    pub api_token: String,
+    /// 工作区 ID，为空时使用账号的默认工作区
+    pub workspace_id: String,
+    /// 未命中项目规则时使用的项目名称，为空则不设置项目
+    pub default_project: String,
+    /// 是否标记为可计费
+    pub billable: bool,
+    /// 跳过时长不足该值（分钟）的会话
+    pub min_minutes: i64,
+    /// 不导出的活动类别（如 idle、personal）
+    pub skip_categories: Vec<String>,
+}
+
+impl Default for TimeTrackingExportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: "toggl".to_string(),
+            api_token: String::new(),
+            workspace_id: String::new(),
+            default_project: String::new(),
+            billable: false,
+            min_minutes: 1,
+            skip_categories: vec!["idle".to_string()],
+        }
+    }
+}
+
+/// 语义搜索向量配置（为会话总结与时间线卡片生成向量，按语义检索会话）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingConfig {
+    /// 是否在会话分析完成后自动生成向量
+    pub enabled: bool,
+    /// 向量服务：llm（复用 LLM 配置的 OpenAI 兼容接口）、openai（自定义 OpenAI 兼容接口）或 ollama（本地模型）
+    pub provider: String,
+    /// openai / ollama 的接口地址，如 https://api.openai.com/v1 或 http://localhost:11434
+    pub base_url: String,
+    /// openai 的 API Key
+    pub api_key: String,
+    /// 向量模型名称
+    pub model: String,
+}
+
+impl Default for EmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: "llm".to_string(),
+            base_url: String::new(),
+            api_key: String::new(),
+            model: "text-embedding-v3".to_string(),
+        }
+    }
+}
+
+/// 截图 OCR 配置（提取采样帧上的文字，用于时间线生成与全文搜索）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OcrConfig {
+    /// 是否在会话分析前识别采样帧
+    pub enabled: bool,
+    /// tesseract 可执行文件路径，留空时从 PATH 查找
+    pub binary_path: String,
+    /// 识别语言（tesseract 语言包，多个用 + 连接）
+    pub languages: String,
+}
+
+impl Default for OcrConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            binary_path: String::new(),
+            languages: "chi_sim+eng".to_string(),
+        }
+    }
+}
+
+/// 浏览器活动接收配置（本地 HTTP 端点，供浏览器扩展上报当前标签页地址）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrowserActivityConfig {
+    /// 是否启动本地接收端点
+    pub enabled: bool,
+    /// 监听端口（仅绑定 127.0.0.1）
+    pub port: u16,
+    /// 访问令牌，扩展需在 X-Screen-Analyzer-Token 请求头中携带；启用时为空会自动生成
+    pub token: String,
+}
+
+impl Default for BrowserActivityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 38462,
+            token: String::new(),
+        }
+    }
+}
+
+/// 磁盘空间预算：截图、视频与数据库合计超出上限时，从最旧的会话开始分级清理，会话总结始终保留
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageBudgetConfig {
+    /// 是否启用空间预算
+    pub enabled: bool,
+    /// 空间上限（GB）
+    pub max_gb: f64,
+    /// 第一级清理时，旧会话的截图每隔多少分钟保留一张
+    pub keep_every_minutes: u32,
+}
+
+impl Default for StorageBudgetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_gb: 20.0,
+            keep_every_minutes: 10,
+        }
+    }
+}
+
+/// 总结重新生成与批量导出队列：按天排队，今天优先，限制同时调用 LLM 的任务数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SummaryQueueConfig {
+    /// 同时运行的任务数
+    pub concurrency: usize,
+}
+
+impl Default for SummaryQueueConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: crate::actors::summary_queue::DEFAULT_QUEUE_CONCURRENCY,
+        }
+    }
+}
+
+/// 视觉调用前的帧预处理：挑选代表帧、缩放到提供商的最佳分辨率，代表帧过多时拼成网格
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FramePrepConfig {
+    /// 是否启用预处理（关闭时按采样间隔发送原图）
+    pub enabled: bool,
+    /// 每个会话窗口最多发送的图片数
+    pub max_frames: usize,
+    /// 图片长边像素，0 表示按提供商自动选择
+    pub max_edge: u32,
+    /// 画面变化阈值（感知哈希汉明距离），不超过该值的相邻帧视为同一画面
+    pub change_threshold: u32,
+    /// 代表帧多于 max_frames 时是否拼成网格
+    pub grid: bool,
+    /// JPEG 质量（1-100）
+    pub jpeg_quality: u8,
+}
+
+impl Default for FramePrepConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_frames: 12,
+            max_edge: 0,
+            change_threshold: 6,
+            grid: true,
+            jpeg_quality: 80,
+        }
+    }
+}
+
+/// LLM 响应缓存：输入帧与上下文未变化时复用已保存的分析结果与每日总结
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LlmCacheConfig {
+    /// 是否启用缓存
+    pub enabled: bool,
+    /// 缓存有效天数，0 表示不过期
+    pub max_age_days: u32,
+}
+
+impl Default for LlmCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_age_days: 30,
+        }
+    }
+}
+
+/// 低置信度卡片复核：置信度低于阈值的时间线卡片进入复核队列，
+/// 同一应用或网站被多次确认为相同类别后自动生成预分类规则
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReviewQueueConfig {
+    /// 是否启用复核队列
+    pub enabled: bool,
+    /// 置信度阈值（0-1），低于该值的卡片需要复核
+    pub threshold: f32,
+    /// 同一应用累计多少次一致的复核后生成规则，0 表示不生成
+    pub rule_after: u32,
+}
+
+impl Default for ReviewQueueConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 0.6,
+            rule_after: 2,
+        }
+    }
+}
+
+/// 本地启发式分类：按应用名称、窗口标题关键词、网站域名与 OCR 文字在本机归类，
+/// 未配置 AI 或 AI 调用失败（如断网）时代替 AI 生成时间线卡片
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeuristicConfig {
+    /// 未配置 AI 提供商时使用本地分类
+    pub enabled: bool,
+    /// AI 调用失败时回退为本地分类（否则本次分析失败）
+    pub on_llm_error: bool,
+}
+
+impl Default for HeuristicConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            on_llm_error: true,
+        }
+    }
+}
+
+/// 专注时段（番茄钟）配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusConfig {
+    /// 专注时段内出现干扰类活动时发送提醒
+    pub notify_distractions: bool,
+    /// 视为干扰的活动类别（按本地启发式分类判定）
+    pub distraction_categories: Vec<String>,
+    /// 同一专注时段内两次干扰提醒的最短间隔（分钟）
+    pub alert_cooldown_minutes: u32,
+}
+
+impl Default for FocusConfig {
+    fn default() -> Self {
+        Self {
+            notify_distractions: true,
+            distraction_categories: vec!["personal".to_string()],
+            alert_cooldown_minutes: 5,
         }
     }
 }
+
+/// 分心提醒配置：工作时段内最近一段时间的干扰类活动累计超过阈值时发送通知
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DistractionAlertConfig {
+    /// 是否启用
+    pub enabled: bool,
+    /// 统计最近多少分钟的活动
+    pub window_minutes: u32,
+    /// 干扰时长达到多少分钟时提醒
+    pub threshold_minutes: u32,
+    /// 视为干扰的活动类别（按本地启发式分类判定）
+    pub categories: Vec<String>,
+    /// 工作时段开始（HH:MM，本地时间）
+    pub work_start: String,
+    /// 工作时段结束（HH:MM，早于开始表示跨午夜）
+    pub work_end: String,
+    /// 工作日（1=周一 … 7=周日）
+    pub work_days: Vec<u8>,
+    /// 两次提醒的最短间隔（分钟）
+    pub cooldown_minutes: u32,
+}
+
+impl Default for DistractionAlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_minutes: 30,
+            threshold_minutes: 10,
+            categories: vec!["personal".to_string()],
+            work_start: "09:00".to_string(),
+            work_end: "18:00".to_string(),
+            work_days: vec![1, 2, 3, 4, 5],
+            cooldown_minutes: 30,
+        }
+    }
+}
+
+/// 单个应用的每日使用时长软上限
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppLimit {
+    /// 应用名称（与前台窗口采集到的应用名比较，不区分大小写）
+    pub app_name: String,
+    /// 每日上限（分钟）
+    pub daily_minutes: u32,
+}
+
+/// 应用使用时长上限配置：达到上限时发送通知（每个应用每天一次），并在每日总结中标出
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppLimitConfig {
+    /// 达到上限时是否发送通知
+    pub notify: bool,
+    pub limits: Vec<AppLimit>,
+}
+
+impl Default for AppLimitConfig {
+    fn default() -> Self {
+        Self {
+            notify: true,
+            limits: Vec::new(),
+        }
+    }
+}
+
+/// 过劳迹象提醒配置：超长屏幕日、连续深夜工作与休息时间逐周减少时在周报中提醒，可选每日通知
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WellbeingConfig {
+    /// 是否在周报中输出提醒
+    pub enabled: bool,
+    /// 是否每天推送前一天出现的迹象
+    pub notify: bool,
+    /// 单日屏幕时长上限（分钟）
+    pub max_screen_minutes: u32,
+    /// 深夜开始时刻（5-23 点，至次日凌晨 5 点）
+    pub late_night_hour: u32,
+    /// 深夜活动达到多少分钟计为深夜工作日
+    pub late_night_minutes: u32,
+    /// 连续多少天深夜工作时提醒
+    pub late_streak_days: u32,
+    /// 每小时休息时长较上一周减少多少百分比时提醒
+    pub break_drop_percent: u32,
+}
+
+impl Default for WellbeingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            notify: false,
+            max_screen_minutes: 600,
+            late_night_hour: 22,
+            late_night_minutes: 30,
+            late_streak_days: 3,
+            break_drop_percent: 30,
+        }
+    }
+}
+
+/// 局域网同步中本机的角色
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LanSyncRole {
+    /// 主设备：接收其他设备推送的会话与时间线，每日/周报覆盖全部设备
+    #[default]
+    Primary,
+    /// 从设备：定时把本机的会话与时间线推送到主设备
+    Client,
+}
+
+/// 局域网多设备同步配置：从设备通过带令牌的 HTTP 请求把会话与时间线卡片（不含截图）推送到主设备
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LanSyncConfig {
+    /// 是否启用同步
+    pub enabled: bool,
+    /// 本机角色
+    pub role: LanSyncRole,
+    /// 主设备监听地址（默认所有网卡）
+    pub bind_address: String,
+    /// 主设备监听端口
+    pub port: u16,
+    /// 主设备地址（从设备使用，如 `http://192.168.1.10:38463`）
+    pub primary_url: String,
+    /// 共享访问令牌（主从设备须一致，启用时不能为空）
+    pub token: String,
+    /// 从设备推送间隔（分钟）
+    pub interval_minutes: u32,
+    /// 每次推送最近多少天的会话（已推送的会话在主设备上按设备与开始时间覆盖更新）
+    pub lookback_days: u32,
+}
+
+impl Default for LanSyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            role: LanSyncRole::Primary,
+            bind_address: "0.0.0.0".to_string(),
+            port: 38463,
+            primary_url: String::new(),
+            token: String::new(),
+            interval_minutes: 15,
+            lookback_days: 2,
+        }
+    }
+}
+
+/// 云备份目标
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupTarget {
+    /// S3 兼容存储（AWS S3、MinIO、Cloudflare R2 等）
+    #[default]
+    S3,
+    /// WebDAV（Nextcloud、坚果云等）
+    WebDav,
+}
+
+/// 加密云备份配置：定时把数据库快照（可选附带配置与截图）打包，
+/// 用口令派生的密钥加密后上传，远端只保存密文
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupConfig {
+    /// 是否启用定时备份
+    pub enabled: bool,
+    /// 备份目标
+    pub target: BackupTarget,
+    /// S3 兼容存储配置（目标为 s3 时使用，`public_base_url` 不使用）
+    pub s3: S3StorageConfig,
+    /// WebDAV 目录地址，如 `https://dav.example.com/remote.php/dav/files/me/backup`
+    pub webdav_url: String,
+    pub webdav_username: String,
+    pub webdav_password: String,
+    /// 加密口令（丢失后备份无法恢复）
+    pub passphrase: String,
+    /// 备份间隔（小时）
+    pub interval_hours: u32,
+    /// 远端保留的备份份数，超出时删除最旧的
+    pub keep_count: u32,
+    /// 附带应用配置文件
+    pub include_config: bool,
+    /// 附带截图目录（体积可能很大）
+    pub include_screenshots: bool,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target: BackupTarget::S3,
+            s3: S3StorageConfig::default(),
+            webdav_url: String::new(),
+            webdav_username: String::new(),
+            webdav_password: String::new(),
+            passphrase: String::new(),
+            interval_hours: 24,
+            keep_count: 7,
+            include_config: true,
+            include_screenshots: false,
+        }
+    }
+}
+
+/// 截图存储后端
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameStoreBackend {
+    /// 本机 frames 目录
+    #[default]
+    Local,
+    /// NAS 共享目录（SMB 挂载点或 UNC 路径）
+    Nas,
+    /// S3 兼容对象存储
+    S3,
+}
+
+/// 截图存储配置：截图先写入本机，会话结束超过设定时长后转存到 NAS 或对象存储，释放系统盘空间
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FrameStoreConfig {
+    /// 存储后端
+    pub backend: FrameStoreBackend,
+    /// NAS 目录，如 `\\nas\share\screen-analyzer` 或 `/Volumes/share/screen-analyzer`
+    pub nas_path: String,
+    /// S3 兼容存储配置（Obsidian 链接模式通过公开访问地址引用截图）
+    pub s3: S3StorageConfig,
+    /// 会话结束超过该小时数后转存（转存前仍可生成视频、OCR 与转码）
+    pub offload_after_hours: u32,
+}
+
+impl Default for FrameStoreConfig {
+    fn default() -> Self {
+        Self {
+            backend: FrameStoreBackend::Local,
+            nas_path: String::new(),
+            s3: S3StorageConfig::default(),
+            offload_after_hours: 24,
+        }
+    }
+}
+
+/// JSON 导出的结构版本（字段发生不兼容变更时递增）
+pub const JSON_EXPORT_SCHEMA_VERSION: u32 = 1;
+
+/// JSON 导出文档（面向下游工具的稳定结构，字段统一为 camelCase）
+///
+/// ```json
+/// {
+///   "schemaVersion": 1,
+///   "generatedAt": "2024-05-13T08:00:00",
+///   "startDate": "2024-05-12",
+///   "endDate": "2024-05-12",
+///   "days": [{
+///     "date": "2024-05-12",
+///     "summary": { "date": "2024-05-12", "summaryText": "...", "deviceStats": [], ... },
+///     "sessions": [{
+///       "id": 3, "startTime": "2024-05-12T09:00:00", "endTime": "2024-05-12T10:30:00",
+///       "durationMinutes": 90, "title": "...", "summary": "...", "category": "work",
+///       "tags": [{ "category": "work", "confidence": 0.9, "keywords": ["rust"] }],
+///       "deviceName": null, "deviceType": null,
+///       "timelineCards": [{ "startTime": "2024-05-12T09:00:00+08:00", ... }],
+///       "domains": [{ "domain": "github.com", "seconds": 1200 }]
+///     }]
+///   }]
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonExportDocument {
+    /// 结构版本，见 `JSON_EXPORT_SCHEMA_VERSION`
+    pub schema_version: u32,
+    /// 导出时间（本地时间，无时区）
+    pub generated_at: chrono::NaiveDateTime,
+    pub start_date: String,
+    pub end_date: String,
+    /// 日期范围内有会话记录的日期（按日期升序）
+    pub days: Vec<JsonExportDay>,
+}
+
+/// JSON 导出中的单日数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonExportDay {
+    /// 日期（YYYY-MM-DD）
+    pub date: String,
+    /// 每日总结；生成失败时为 null
+    pub summary: Option<crate::domains::summary::DaySummary>,
+    pub sessions: Vec<JsonExportSession>,
+}
+
+/// JSON 导出中的会话
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonExportSession {
+    pub id: Option<i64>,
+    /// 开始时间（记录时的本地时间，无时区）
+    pub start_time: chrono::NaiveDateTime,
+    /// 结束时间（记录时的本地时间，无时区）
+    pub end_time: chrono::NaiveDateTime,
+    pub duration_minutes: i64,
+    pub title: String,
+    pub summary: String,
+    /// 主分类（第一个标签的类别）
+    pub category: Option<String>,
+    pub tags: Vec<ActivityTag>,
+    pub device_name: Option<String>,
+    pub device_type: Option<String>,
+    pub timeline_cards: Vec<JsonExportTimelineCard>,
+    /// 网站域名访问时长（按时长降序，未接入浏览器扩展时为空）
+    #[serde(default)]
+    pub domains: Vec<JsonExportDomainUsage>,
+}
+
+/// JSON 导出中的域名访问时长
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonExportDomainUsage {
+    pub domain: String,
+    pub seconds: i64,
+}
+
+/// JSON 导出中的时间线卡片
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonExportTimelineCard {
+    pub id: Option<i64>,
+    /// 开始时间（RFC3339，含时区）
+    pub start_time: String,
+    /// 结束时间（RFC3339，含时区）
+    pub end_time: String,
+    pub category: String,
+    pub subcategory: String,
+    pub title: String,
+    pub summary: String,
+    pub detailed_summary: String,
+    /// 干扰活动（原始 JSON，未记录时为 null）
+    pub distractions: Option<serde_json::Value>,
+    /// 应用/网站信息（原始 JSON）
+    pub app_sites: serde_json::Value,
+    /// 由本地启发式分类生成（未经 AI 分析）
+    pub heuristic: bool,
+}
@@ -0,0 +1,381 @@
+// MQTT 状态发布 - 将当前活动类别、专注状态与会话开始/结束事件发布到 MQTT Broker，
+// 供 Home Assistant 等家庭自动化订阅（如专注时自动开启勿扰灯）
+
+use anyhow::{anyhow, Result};
+use rumqttc::{AsyncClient, LastWill, MqttOptions, QoS};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::domains::summary::category_key;
+use crate::event_bus::{AppEvent, EventBus};
+use crate::models::MqttConfig;
+use crate::settings::SettingsManager;
+
+/// 计为专注状态的活动类别
+const FOCUS_CATEGORIES: [&str; 2] = ["work", "learning"];
+
+/// 在线状态（保留消息，断线时由遗嘱消息置为 offline）
+const STATUS_ONLINE: &str = "online";
+const STATUS_OFFLINE: &str = "offline";
+
+/// 连接断开后的重连间隔
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// 一条待发布的消息
+#[derive(Debug, Clone, PartialEq)]
+pub struct MqttMessage {
+    pub topic: String,
+    pub payload: String,
+    pub retain: bool,
+}
+
+impl MqttMessage {
+    fn new(prefix: &str, topic: &str, payload: impl Into<String>, retain: bool) -> Self {
+        Self {
+            topic: format!("{}/{}", prefix, topic),
+            payload: payload.into(),
+            retain,
+        }
+    }
+}
+
+/// 规范化主题前缀（去除首尾的 /，为空时使用默认值）
+pub fn topic_prefix(config: &MqttConfig) -> String {
+    match config.topic_prefix.trim().trim_matches('/') {
+        "" => MqttConfig::default().topic_prefix,
+        prefix => prefix.to_string(),
+    }
+}
+
+/// 将应用事件转换为 MQTT 消息，无关事件返回空列表
+///
+/// - `<prefix>/session`：会话开始/结束事件（JSON）
+/// - `<prefix>/category`：最近一次会话的主分类（保留消息）
+/// - `<prefix>/focus`：是否处于专注状态，ON / OFF（保留消息）
+/// - `<prefix>/activity`：最近一次会话的完整状态（JSON，保留消息）
+pub fn event_messages(prefix: &str, event: &AppEvent) -> Vec<MqttMessage> {
+    match event {
+        AppEvent::SessionStarted {
+            session_id,
+            window_start,
+        } => vec![MqttMessage::new(
+            prefix,
+            "session",
+            json!({
+                "event": "start",
+                "sessionId": session_id,
+                "windowStart": window_start.naive_utc(),
+            })
+            .to_string(),
+            false,
+        )],
+        AppEvent::SessionCompleted {
+            session_id,
+            frame_count,
+            window_start,
+            window_end,
+            title,
+        } => vec![MqttMessage::new(
+            prefix,
+            "session",
+            json!({
+                "event": "stop",
+                "sessionId": session_id,
+                "windowStart": window_start.naive_utc(),
+                "windowEnd": window_end.naive_utc(),
+                "frameCount": frame_count,
+                "title": title,
+            })
+            .to_string(),
+            false,
+        )],
+        AppEvent::AnalysisCompleted { summary, .. } => {
+            // 标签按时间占比排序，第一个即主分类
+            let Some(tag) = summary.tags.first() else {
+                return Vec::new();
+            };
+            let category = category_key(&tag.category);
+            let focus = FOCUS_CATEGORIES.contains(&category);
+            vec![
+                MqttMessage::new(prefix, "category", category, true),
+                MqttMessage::new(prefix, "focus", if focus { "ON" } else { "OFF" }, true),
+                MqttMessage::new(
+                    prefix,
+                    "activity",
+                    json!({
+                        "category": category,
+                        "focus": focus,
+                        "title": summary.title,
+                        "focusScore": summary.focus_score,
+                        "productivityScore": summary.productivity_score,
+                        "windowStart": summary.start_time.naive_utc(),
+                        "windowEnd": summary.end_time.naive_utc(),
+                    })
+                    .to_string(),
+                    true,
+                ),
+            ]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// 与 Broker 的一个连接（配置变更时重建）
+struct MqttConnection {
+    config: MqttConfig,
+    prefix: String,
+    client: AsyncClient,
+    event_loop: JoinHandle<()>,
+}
+
+impl MqttConnection {
+    fn open(config: MqttConfig) -> Result<Self> {
+        let host = config.host.trim();
+        if host.is_empty() {
+            return Err(anyhow!("请先配置 MQTT Broker 地址"));
+        }
+        let prefix = topic_prefix(&config);
+        let status_topic = format!("{}/status", prefix);
+        let client_id = match config.client_id.trim() {
+            "" => format!(
+                "screen-analyzer-{}",
+                &uuid::Uuid::new_v4().simple().to_string()[..8]
+            ),
+            client_id => client_id.to_string(),
+        };
+
+        let mut options = MqttOptions::new(client_id, host, config.port);
+        options.set_keep_alive(Duration::from_secs(30));
+        options.set_last_will(LastWill::new(
+            &status_topic,
+            STATUS_OFFLINE,
+            QoS::AtLeastOnce,
+            true,
+        ));
+        if !config.username.is_empty() {
+            options.set_credentials(&config.username, &config.password);
+        }
+
+        let (client, mut event_loop) = AsyncClient::new(options, 32);
+        let status_client = client.clone();
+        let address = format!("{}:{}", host, config.port);
+        let event_loop = tokio::spawn(async move {
+            loop {
+                match event_loop.poll().await {
+                    Ok(rumqttc::Event::Incoming(rumqttc::Packet::ConnAck(_))) => {
+                        info!("已连接 MQTT Broker: {}", address);
+                        // 每次（重）连接后刷新在线状态，覆盖遗嘱消息
+                        if let Err(e) = status_client.try_publish(
+                            &status_topic,
+                            QoS::AtLeastOnce,
+                            true,
+                            STATUS_ONLINE,
+                        ) {
+                            warn!("发布 MQTT 在线状态失败: {}", e);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        warn!(
+                            "MQTT 连接异常: {}，{}秒后重连",
+                            e,
+                            RECONNECT_DELAY.as_secs()
+                        );
+                        tokio::time::sleep(RECONNECT_DELAY).await;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            config,
+            prefix,
+            client,
+            event_loop,
+        })
+    }
+
+    fn publish(&self, message: &MqttMessage) -> Result<()> {
+        self.client
+            .try_publish(
+                &message.topic,
+                QoS::AtLeastOnce,
+                message.retain,
+                message.payload.as_bytes(),
+            )
+            .map_err(|e| anyhow!("发布 MQTT 消息失败（{}）: {}", message.topic, e))
+    }
+
+    /// 发布离线状态并断开，稍后停止事件循环
+    fn close(self) {
+        let _ = self.client.try_publish(
+            format!("{}/status", self.prefix),
+            QoS::AtLeastOnce,
+            true,
+            STATUS_OFFLINE,
+        );
+        let _ = self.client.try_disconnect();
+        tokio::spawn(async move {
+            // 留出时间发送离线状态与断开请求
+            tokio::time::sleep(Duration::from_secs(2)).await;
+            self.event_loop.abort();
+        });
+    }
+}
+
+/// MQTT 状态发布器 - 订阅事件总线，按当前配置连接 Broker 并发布状态
+pub struct MqttPublisher {
+    settings: Arc<SettingsManager>,
+    connection: Mutex<Option<MqttConnection>>,
+}
+
+impl MqttPublisher {
+    pub fn new(settings: Arc<SettingsManager>) -> Self {
+        Self {
+            settings,
+            connection: Mutex::new(None),
+        }
+    }
+
+    /// 启动事件监听（配置更新时重建连接）
+    pub fn start(self: Arc<Self>, event_bus: Arc<EventBus>) {
+        let mut receiver = event_bus.subscribe();
+
+        tokio::spawn(async move {
+            if let Err(e) = self.refresh().await {
+                warn!("MQTT 连接失败: {}", e);
+            }
+            info!("MQTT 状态发布监听器已启动");
+
+            while let Ok(event) = receiver.recv().await {
+                let result = match &event {
+                    AppEvent::ConfigUpdated { .. } => self.refresh().await,
+                    event => self.publish_event(event).await,
+                };
+                if let Err(e) = result {
+                    warn!("MQTT 状态发布失败: {}", e);
+                }
+            }
+
+            warn!("MQTT 状态发布监听器已停止");
+        });
+    }
+
+    /// 按最新配置建立、重建或关闭连接
+    async fn refresh(&self) -> Result<()> {
+        let config = self.settings.get().await.mqtt_config.unwrap_or_default();
+        let mut connection = self.connection.lock().await;
+        if config.enabled
+            && connection
+                .as_ref()
+                .is_some_and(|current| current.config == config)
+        {
+            return Ok(());
+        }
+
+        if let Some(current) = connection.take() {
+            current.close();
+            info!("MQTT 连接已关闭");
+        }
+        if config.enabled {
+            *connection = Some(MqttConnection::open(config)?);
+        }
+        Ok(())
+    }
+
+    async fn publish_event(&self, event: &AppEvent) -> Result<()> {
+        let connection = self.connection.lock().await;
+        let Some(connection) = connection.as_ref() else {
+            return Ok(());
+        };
+        for message in event_messages(&connection.prefix, event) {
+            debug!("MQTT 发布: {} {}", message.topic, message.payload);
+            connection.publish(&message)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{ActivityCategory, ActivityTag, SessionSummary};
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_topic_prefix() {
+        let config = |prefix: &str| MqttConfig {
+            topic_prefix: prefix.to_string(),
+            ..MqttConfig::default()
+        };
+        assert_eq!(topic_prefix(&config("/home/office/")), "home/office");
+        assert_eq!(topic_prefix(&config("  ")), "screen-analyzer");
+    }
+
+    #[test]
+    fn test_event_messages() {
+        let start = Utc.with_ymd_and_hms(2024, 5, 12, 9, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 5, 12, 9, 15, 0).unwrap();
+
+        let messages = event_messages(
+            "sa",
+            &AppEvent::SessionStarted {
+                session_id: 1,
+                window_start: start,
+            },
+        );
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].topic, "sa/session");
+        assert!(!messages[0].retain);
+        let payload: serde_json::Value = serde_json::from_str(&messages[0].payload).unwrap();
+        assert_eq!(payload["event"], "start");
+        assert_eq!(payload["windowStart"], "2024-05-12T09:00:00");
+
+        let summary = SessionSummary {
+            title: "编写导出模块".to_string(),
+            tags: vec![ActivityTag {
+                category: ActivityCategory::Learning,
+                confidence: 0.8,
+                keywords: vec![],
+            }],
+            start_time: start,
+            end_time: end,
+            ..SessionSummary::default()
+        };
+        let messages = event_messages(
+            "sa",
+            &AppEvent::AnalysisCompleted {
+                session_id: 1,
+                summary: summary.clone(),
+            },
+        );
+        assert_eq!(
+            messages[..2],
+            [
+                MqttMessage::new("sa", "category", "learning", true),
+                MqttMessage::new("sa", "focus", "ON", true),
+            ]
+        );
+        let payload: serde_json::Value = serde_json::from_str(&messages[2].payload).unwrap();
+        assert_eq!(payload["title"], "编写导出模块");
+        assert_eq!(payload["windowEnd"], "2024-05-12T09:15:00");
+
+        let untagged = SessionSummary {
+            tags: vec![],
+            ..summary
+        };
+        assert!(event_messages(
+            "sa",
+            &AppEvent::AnalysisCompleted {
+                session_id: 1,
+                summary: untagged,
+            },
+        )
+        .is_empty());
+        assert!(event_messages("sa", &AppEvent::StorageCleanupStarted).is_empty());
+    }
+}
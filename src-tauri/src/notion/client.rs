@@ -2,17 +2,70 @@
 // 负责与 Notion API 交互，实现数据同步功能
 
 use anyhow::{anyhow, Result};
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::Serialize;
 use serde_json::{json, Value};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::fs;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
 use tracing::{error, info, warn};
 
-use crate::models::{NotionConfig, Session};
+use super::s3::S3Uploader;
+use crate::models::{NotionConfig, NotionScreenshotUpload, Session};
+use crate::storage::SessionNoteRecord;
 
 const NOTION_API_VERSION: &str = "2022-06-28";
 const NOTION_API_BASE: &str = "https://api.notion.com/v1";
 
+/// 相邻请求的最小间隔（Notion 平均限流为每秒 3 个请求）
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_millis(350);
+
+/// 被限流（429）后的最大重试次数
+const MAX_RATE_LIMIT_RETRIES: u32 = 5;
+
+/// 指数退避的初始等待与上限（响应带 Retry-After 时以其为准）
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(32);
+
+/// Retry-After 的上限，避免异常的响应头让同步任务长时间挂起
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(600);
+
+/// 所有 Notion 请求共用的发送队列：记录下一个请求最早的发送时间，按排队顺序依次放行
+static NEXT_REQUEST_AT: Mutex<Option<Instant>> = Mutex::const_new(None);
+
+/// 排队等待发送时机
+async fn wait_for_slot() {
+    let mut next = NEXT_REQUEST_AT.lock().await;
+    if let Some(at) = *next {
+        tokio::time::sleep_until(at).await;
+    }
+    *next = Some(Instant::now() + MIN_REQUEST_INTERVAL);
+}
+
+/// 被限流后推迟队列中所有请求
+async fn delay_requests(delay: Duration) {
+    let mut next = NEXT_REQUEST_AT.lock().await;
+    let resume_at = Instant::now() + delay;
+    if next.is_none_or(|at| at < resume_at) {
+        *next = Some(resume_at);
+    }
+}
+
+/// 第 attempt 次重试前的等待时间（attempt 从 1 开始）
+fn retry_delay(retry_after: Option<&str>, attempt: u32) -> Duration {
+    retry_after
+        .and_then(|value| value.trim().parse::<f64>().ok())
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .map(|delay| delay.min(MAX_RETRY_AFTER))
+        .unwrap_or_else(|| {
+            INITIAL_BACKOFF
+                .saturating_mul(1 << (attempt - 1).min(16))
+                .min(MAX_BACKOFF)
+        })
+}
+
 /// 获取系统时区
 fn get_system_timezone() -> String {
     // 尝试获取系统时区
@@ -82,6 +135,151 @@ pub struct NotionPage {
     pub icon: Option<String>, // emoji 或 URL
 }
 
+/// 周报页面数据（由周报汇总生成）
+#[derive(Debug, Clone)]
+pub struct WeeklySummaryPage {
+    pub week_label: String,
+    pub week_start: String,
+    pub week_end: String,
+    pub session_count: i64,
+    pub total_minutes: i64,
+    pub avg_session_minutes: i64,
+    pub focus_minutes: i64,
+    /// 专注占比（%）
+    pub focus_ratio: i64,
+    pub productivity_score: i64,
+    pub top_categories: String,
+}
+
+/// 一键创建的 Notion 数据库 ID
+#[derive(Debug, Clone, Serialize)]
+pub struct NotionDatabaseSetup {
+    pub sessions_database_id: String,
+    pub daily_database_id: String,
+    pub weekly_database_id: String,
+}
+
+/// 会话数据库属性定义
+fn session_database_schema() -> Value {
+    json!({
+        "标题": { "title": {} },
+        "日期": { "date": { "time_zone": get_system_timezone() } },
+        "总结": { "rich_text": {} },
+        "设备": { "select": {} },
+        "本地ID": { "rich_text": {} },
+        "类别": { "select": {} },
+        "关键词": { "multi_select": {} },
+        "时长": { "number": { "format": "number" } },
+        "类型": { "select": {} }
+    })
+}
+
+/// 每日总结数据库属性定义
+fn daily_database_schema() -> Value {
+    json!({
+        "标题": { "title": {} },
+        "日期": { "date": {} },
+        "总结": { "rich_text": {} },
+        "类型": { "select": {} }
+    })
+}
+
+/// 周报数据库属性定义（标题属性 + 周报所需属性）
+fn weekly_database_schema() -> Value {
+    let mut properties = serde_json::Map::new();
+    properties.insert("标题".to_string(), json!({ "title": {} }));
+    for (name, schema) in weekly_property_schema() {
+        properties.insert(name.to_string(), schema);
+    }
+    Value::Object(properties)
+}
+
+/// 周报页面需要的数据库属性（属性名, 属性定义）；缺失时自动添加
+fn weekly_property_schema() -> Vec<(&'static str, Value)> {
+    let number = || json!({ "number": { "format": "number" } });
+    vec![
+        ("日期", json!({ "date": {} })),
+        ("总结", json!({ "rich_text": {} })),
+        ("类型", json!({ "select": {} })),
+        ("时长", number()),
+        ("session_count", number()),
+        ("avg_session_minutes", number()),
+        ("focus_minutes", number()),
+        ("focus_ratio", number()),
+        ("productivity_score", number()),
+    ]
+}
+
+/// 构建周报页面属性；title_property 为目标数据库的标题属性名
+fn build_weekly_properties(title_property: &str, page: &WeeklySummaryPage) -> Value {
+    let mut properties = json!({
+        "日期": {
+            "date": {
+                "start": page.week_start,
+                "end": page.week_end
+            }
+        },
+        "总结": {
+            "rich_text": [{
+                "text": { "content": page.top_categories }
+            }]
+        },
+        "类型": {
+            "select": {
+                "name": "周报"
+            }
+        },
+        "时长": { "number": page.total_minutes },
+        "session_count": { "number": page.session_count },
+        "avg_session_minutes": { "number": page.avg_session_minutes },
+        "focus_minutes": { "number": page.focus_minutes },
+        "focus_ratio": { "number": page.focus_ratio },
+        "productivity_score": { "number": page.productivity_score },
+    });
+    properties[title_property] = json!({
+        "title": [{
+            "text": { "content": format!("周报 - {}", page.week_label) }
+        }]
+    });
+    properties
+}
+
+/// 截图扩展名对应的 MIME 类型
+pub(crate) fn image_content_type(extension: &str) -> &'static str {
+    match extension {
+        "png" => "image/png",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        _ => "image/jpeg",
+    }
+}
+
+/// 每日总结页面属性
+fn build_daily_properties(date: &str, summary: &str) -> Value {
+    json!({
+        "标题": {
+            "title": [{
+                "text": { "content": format!("每日总结 - {}", date) }
+            }]
+        },
+        "日期": {
+            "date": {
+                "start": date
+            }
+        },
+        "总结": {
+            "rich_text": [{
+                "text": { "content": summary }
+            }]
+        },
+        "类型": {
+            "select": {
+                "name": "每日总结"
+            }
+        }
+    })
+}
+
 /// Notion API 客户端
 #[derive(Clone)]
 pub struct NotionClient {
@@ -104,6 +302,47 @@ impl NotionClient {
         Ok(Self { config, client })
     }
 
+    /// 经发送队列发送请求，被限流时按 Retry-After 或指数退避重试；
+    /// 重试用尽时返回最后一次的响应，由调用方处理错误状态
+    async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        self.send_with(|| {
+            request
+                .try_clone()
+                .ok_or_else(|| anyhow!("请求体无法复制，multipart 上传请使用 send_with"))
+        })
+        .await
+    }
+
+    /// 同 send，但每次尝试都重新构建请求（multipart 表单无法复制，重试时需重建）
+    async fn send_with(&self, build: impl Fn() -> Result<RequestBuilder>) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            let request = build()?;
+            wait_for_slot().await;
+            let response = request.send().await?;
+            if response.status() != StatusCode::TOO_MANY_REQUESTS {
+                return Ok(response);
+            }
+            if attempt >= MAX_RATE_LIMIT_RETRIES {
+                warn!("Notion API 限流，已重试 {} 次", attempt);
+                return Ok(response);
+            }
+            attempt += 1;
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok());
+            let delay = retry_delay(retry_after, attempt);
+            warn!(
+                "Notion API 限流，{:.1} 秒后重试（第 {}/{} 次）",
+                delay.as_secs_f64(),
+                attempt,
+                MAX_RATE_LIMIT_RETRIES
+            );
+            delay_requests(delay).await;
+        }
+    }
+
     /// 获取配置信息（用于调试）
     pub fn get_config(&self) -> &NotionConfig {
         &self.config
@@ -114,11 +353,12 @@ impl NotionClient {
         let url = format!("{}/databases/{}", NOTION_API_BASE, self.config.database_id);
 
         let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_token))
-            .header("Notion-Version", NOTION_API_VERSION)
-            .send()
+            .send(
+                self.client
+                    .get(&url)
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+                    .header("Notion-Version", NOTION_API_VERSION),
+            )
             .await?;
 
         if !response.status().is_success() {
@@ -136,8 +376,12 @@ impl NotionClient {
         Ok(format!("连接成功！数据库名称: {}", title))
     }
 
-    /// 同步会话到 Notion
-    pub async fn sync_session(&self, session: &Session) -> Result<String> {
+    /// 同步会话到 Notion（附带用户备注时一并写入）
+    pub async fn sync_session(
+        &self,
+        session: &Session,
+        note: Option<&SessionNoteRecord>,
+    ) -> Result<String> {
         if !self.config.enabled {
             return Ok("Notion 同步已禁用".to_string());
         }
@@ -149,7 +393,7 @@ impl NotionClient {
         info!("开始同步会话 {:?} 到 Notion", session.id);
 
         // 构建 Notion 页面属性
-        let properties = self.build_session_properties(session)?;
+        let properties = self.build_session_properties(session, note)?;
 
         // 创建 Notion 页面
         let url = format!("{}/pages", NOTION_API_BASE);
@@ -159,13 +403,14 @@ impl NotionClient {
         });
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_token))
-            .header("Notion-Version", NOTION_API_VERSION)
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
+            .send(
+                self.client
+                    .post(&url)
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+                    .header("Notion-Version", NOTION_API_VERSION)
+                    .header("Content-Type", "application/json")
+                    .json(&payload),
+            )
             .await?;
 
         if !response.status().is_success() {
@@ -197,8 +442,12 @@ impl NotionClient {
         Ok(page_id)
     }
 
-    /// 构建会话的 Notion 属性
-    fn build_session_properties(&self, session: &Session) -> Result<Value> {
+    /// 构建会话的 Notion 属性（用户备注追加到总结，自定义标签并入关键词）
+    fn build_session_properties(
+        &self,
+        session: &Session,
+        note: Option<&SessionNoteRecord>,
+    ) -> Result<Value> {
         use chrono::Local;
 
         // 问题分析：
@@ -225,6 +474,11 @@ impl NotionClient {
         let start_time_str = start_utc.format("%Y-%m-%dT%H:%M:%S").to_string();
         let end_time_str = end_utc.format("%Y-%m-%dT%H:%M:%S").to_string();
 
+        let summary = match note.map(|note| note.note.trim()).filter(|n| !n.is_empty()) {
+            Some(note) => format!("{}\n\n备注：{}", session.summary, note),
+            None => session.summary.clone(),
+        };
+
         let mut properties = json!({
             // 标题（必需）
             "标题": {
@@ -242,7 +496,7 @@ impl NotionClient {
             // 总结
             "总结": {
                 "rich_text": [{
-                    "text": { "content": session.summary.clone() }
+                    "text": { "content": summary }
                 }]
             },
             // 设备信息
@@ -262,6 +516,7 @@ impl NotionClient {
         });
 
         // 添加类别和关键词（解析 JSON 格式的标签）
+        let mut keywords = Vec::new();
         if !session.tags.is_empty() {
             // 尝试解析 JSON 格式的标签
             if let Ok(activity_tags) =
@@ -285,26 +540,34 @@ impl NotionClient {
                     });
 
                     // 收集所有关键词（翻译为中文）
-                    let mut keywords = Vec::new();
                     for tag in activity_tags {
                         for keyword in tag.keywords {
                             if !keyword.trim().is_empty() {
                                 // 英文关键词映射为中文
-                                let cn_keyword = translate_keyword(&keyword);
-                                keywords.push(json!({ "name": cn_keyword }));
+                                keywords.push(translate_keyword(&keyword));
                             }
                         }
                     }
-
-                    if !keywords.is_empty() {
-                        properties["关键词"] = json!({
-                            "multi_select": keywords
-                        });
-                    }
                 }
             }
         }
 
+        // 用户自定义标签
+        for tag in note.map(|note| note.tag_list()).unwrap_or_default() {
+            if !keywords.contains(&tag) {
+                keywords.push(tag);
+            }
+        }
+
+        if !keywords.is_empty() {
+            properties["关键词"] = json!({
+                "multi_select": keywords
+                    .iter()
+                    .map(|keyword| json!({ "name": keyword }))
+                    .collect::<Vec<_>>()
+            });
+        }
+
         // 计算时长（分钟）
         let duration_secs = (session.end_time - session.start_time).num_seconds();
         let duration_minutes = duration_secs / 60;
@@ -380,13 +643,14 @@ impl NotionClient {
         };
 
         let response = self
-            .client
-            .post(&create_url)
-            .header("Authorization", format!("Bearer {}", self.config.api_token))
-            .header("Notion-Version", NOTION_API_VERSION)
-            .header("Content-Type", "application/json")
-            .json(&create_payload)
-            .send()
+            .send(
+                self.client
+                    .post(&create_url)
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+                    .header("Notion-Version", NOTION_API_VERSION)
+                    .header("Content-Type", "application/json")
+                    .json(&create_payload),
+            )
             .await?;
 
         if !response.status().is_success() {
@@ -434,21 +698,25 @@ impl NotionClient {
                 // 带重试的上传逻辑
                 let mut retry_count = 0;
                 let upload_success = loop {
-                    let form = reqwest::multipart::Form::new()
-                        .text("part_number", part_number.to_string())
-                        .part(
-                            "file",
-                            reqwest::multipart::Part::bytes(chunk.to_vec())
-                                .file_name(file_name.to_string())
-                                .mime_str("video/mp4")?,
-                        );
-
-                    let upload_response = upload_client
-                        .post(upload_url)
-                        .header("Authorization", format!("Bearer {}", self.config.api_token))
-                        .header("Notion-Version", NOTION_API_VERSION)
-                        .multipart(form)
-                        .send()
+                    let upload_response = self
+                        .send_with(|| {
+                            let form = reqwest::multipart::Form::new()
+                                .text("part_number", part_number.to_string())
+                                .part(
+                                    "file",
+                                    reqwest::multipart::Part::bytes(chunk.to_vec())
+                                        .file_name(file_name.to_string())
+                                        .mime_str("video/mp4")?,
+                                );
+                            Ok(upload_client
+                                .post(upload_url)
+                                .header(
+                                    "Authorization",
+                                    format!("Bearer {}", self.config.api_token),
+                                )
+                                .header("Notion-Version", NOTION_API_VERSION)
+                                .multipart(form))
+                        })
                         .await;
 
                     match upload_response {
@@ -515,11 +783,13 @@ impl NotionClient {
                 NOTION_API_BASE, file_upload_id
             );
 
-            let complete_response = upload_client
-                .post(&complete_url)
-                .header("Authorization", format!("Bearer {}", self.config.api_token))
-                .header("Notion-Version", NOTION_API_VERSION)
-                .send()
+            let complete_response = self
+                .send(
+                    upload_client
+                        .post(&complete_url)
+                        .header("Authorization", format!("Bearer {}", self.config.api_token))
+                        .header("Notion-Version", NOTION_API_VERSION),
+                )
                 .await?;
 
             if !complete_response.status().is_success() {
@@ -531,19 +801,20 @@ impl NotionClient {
             info!("Multi-part 上传完成");
         } else {
             // Single-part 模式：一次性上传
-            let form = reqwest::multipart::Form::new().part(
-                "file",
-                reqwest::multipart::Part::bytes(file_bytes)
-                    .file_name(file_name.to_string())
-                    .mime_str("video/mp4")?,
-            );
-
-            let upload_response = upload_client
-                .post(upload_url)
-                .header("Authorization", format!("Bearer {}", self.config.api_token))
-                .header("Notion-Version", NOTION_API_VERSION)
-                .multipart(form)
-                .send()
+            let upload_response = self
+                .send_with(|| {
+                    let form = reqwest::multipart::Form::new().part(
+                        "file",
+                        reqwest::multipart::Part::bytes(file_bytes.clone())
+                            .file_name(file_name.to_string())
+                            .mime_str("video/mp4")?,
+                    );
+                    Ok(upload_client
+                        .post(upload_url)
+                        .header("Authorization", format!("Bearer {}", self.config.api_token))
+                        .header("Notion-Version", NOTION_API_VERSION)
+                        .multipart(form))
+                })
                 .await?;
 
             if !upload_response.status().is_success() {
@@ -586,13 +857,14 @@ impl NotionClient {
         });
 
         let blocks_response = self
-            .client
-            .patch(&blocks_url)
-            .header("Authorization", format!("Bearer {}", self.config.api_token))
-            .header("Notion-Version", NOTION_API_VERSION)
-            .header("Content-Type", "application/json")
-            .json(&blocks)
-            .send()
+            .send(
+                self.client
+                    .patch(&blocks_url)
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+                    .header("Notion-Version", NOTION_API_VERSION)
+                    .header("Content-Type", "application/json")
+                    .json(&blocks),
+            )
             .await?;
 
         if !blocks_response.status().is_success() {
@@ -649,6 +921,148 @@ impl NotionClient {
         Ok("视频上传功能待实现".to_string())
     }
 
+    /// 上传关键截图并以图片块追加到页面，返回成功添加的截图数量
+    ///
+    /// 单张截图上传失败时跳过；全部失败时返回最后一个错误。
+    pub async fn add_screenshots_to_page(
+        &self,
+        page_id: &str,
+        screenshots: &[PathBuf],
+        name_prefix: &str,
+    ) -> Result<usize> {
+        if screenshots.is_empty() {
+            return Ok(0);
+        }
+        let uploader = match self.config.screenshot_upload {
+            NotionScreenshotUpload::Notion => None,
+            NotionScreenshotUpload::S3 => Some(S3Uploader::new(self.config.s3.clone())?),
+        };
+
+        let mut images = Vec::new();
+        let mut last_error = None;
+        for (index, path) in screenshots.iter().enumerate() {
+            let extension = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or("jpg")
+                .to_lowercase();
+            let file_name = format!("{}-{}.{}", name_prefix, index + 1, extension);
+            let content_type = image_content_type(&extension);
+            let result = async {
+                let bytes = crate::frame_crypto::read_frame(path).await?;
+                match &uploader {
+                    Some(uploader) => {
+                        let key = uploader.object_key(&file_name);
+                        let url = uploader.upload(&key, bytes, content_type).await?;
+                        Ok(json!({ "type": "external", "external": { "url": url } }))
+                    }
+                    None => {
+                        let id = self.upload_file(&file_name, bytes, content_type).await?;
+                        Ok(json!({ "type": "file_upload", "file_upload": { "id": id } }))
+                    }
+                }
+            }
+            .await;
+            match result {
+                Ok(image) => images.push(json!({
+                    "object": "block",
+                    "type": "image",
+                    "image": image,
+                })),
+                Err(e) => {
+                    warn!("截图 {} 上传失败: {}", path.display(), e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        if images.is_empty() {
+            return Err(last_error.unwrap_or_else(|| anyhow!("没有可上传的截图")));
+        }
+
+        let count = images.len();
+        let mut children = vec![json!({
+            "object": "block",
+            "type": "heading_2",
+            "heading_2": {
+                "rich_text": [{
+                    "type": "text",
+                    "text": { "content": "🖼️ 关键截图" }
+                }]
+            }
+        })];
+        children.extend(images);
+
+        let response = self
+            .send(
+                self.client
+                    .patch(format!("{}/blocks/{}/children", NOTION_API_BASE, page_id))
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+                    .header("Notion-Version", NOTION_API_VERSION)
+                    .header("Content-Type", "application/json")
+                    .json(&json!({ "children": children })),
+            )
+            .await?;
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow!("添加截图块失败: {}", error_text));
+        }
+        info!("已向页面 {} 添加 {} 张截图", page_id, count);
+        Ok(count)
+    }
+
+    /// 通过 Notion 文件上传 API 上传小文件（单次上传），返回 file upload ID
+    async fn upload_file(
+        &self,
+        file_name: &str,
+        bytes: Vec<u8>,
+        content_type: &str,
+    ) -> Result<String> {
+        let response = self
+            .send(
+                self.client
+                    .post(format!("{}/file_uploads", NOTION_API_BASE))
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+                    .header("Notion-Version", NOTION_API_VERSION)
+                    .header("Content-Type", "application/json")
+                    .json(&json!({ "filename": file_name, "content_type": content_type })),
+            )
+            .await?;
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow!("创建 FileUpload 失败: {}", error_text));
+        }
+        let file_upload: Value = response.json().await?;
+        let upload_url = file_upload["upload_url"]
+            .as_str()
+            .ok_or_else(|| anyhow!("未获取到 upload_url"))?;
+        let file_upload_id = file_upload["id"]
+            .as_str()
+            .ok_or_else(|| anyhow!("未获取到 file upload id"))?
+            .to_string();
+
+        let response = self
+            .send_with(|| {
+                let form = reqwest::multipart::Form::new().part(
+                    "file",
+                    reqwest::multipart::Part::bytes(bytes.clone())
+                        .file_name(file_name.to_string())
+                        .mime_str(content_type)?,
+                );
+                Ok(self
+                    .client
+                    .post(upload_url)
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+                    .header("Notion-Version", NOTION_API_VERSION)
+                    .multipart(form))
+            })
+            .await?;
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow!("上传文件内容失败: {}", error_text));
+        }
+        Ok(file_upload_id)
+    }
+
     /// 同步每日总结到 Notion
     pub async fn sync_daily_summary(&self, date: &str, summary: &str) -> Result<String> {
         if !self.config.sync_options.sync_daily_summary {
@@ -657,43 +1071,23 @@ impl NotionClient {
 
         info!("开始同步每日总结 {} 到 Notion", date);
 
-        let properties = json!({
-            "标题": {
-                "title": [{
-                    "text": { "content": format!("每日总结 - {}", date) }
-                }]
-            },
-            "日期": {
-                "date": {
-                    "start": date
-                }
-            },
-            "总结": {
-                "rich_text": [{
-                    "text": { "content": summary }
-                }]
-            },
-            "类型": {
-                "select": {
-                    "name": "每日总结"
-                }
-            }
-        });
+        let properties = build_daily_properties(date, summary);
 
         let url = format!("{}/pages", NOTION_API_BASE);
         let payload = json!({
-            "parent": { "database_id": self.config.database_id },
+            "parent": { "database_id": self.daily_database_id() },
             "properties": properties,
         });
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_token))
-            .header("Notion-Version", NOTION_API_VERSION)
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
+            .send(
+                self.client
+                    .post(&url)
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+                    .header("Notion-Version", NOTION_API_VERSION)
+                    .header("Content-Type", "application/json")
+                    .json(&payload),
+            )
             .await?;
 
         if !response.status().is_success() {
@@ -709,6 +1103,188 @@ impl NotionClient {
         Ok(page_id.to_string())
     }
 
+    /// 每日总结写入的数据库（未单独配置时使用会话数据库）
+    fn daily_database_id(&self) -> &str {
+        let daily = self.config.daily_database_id.trim();
+        if daily.is_empty() {
+            &self.config.database_id
+        } else {
+            daily
+        }
+    }
+
+    /// 周报写入的数据库（未单独配置时使用会话数据库）
+    fn weekly_database_id(&self) -> &str {
+        let weekly = self.config.weekly_database_id.trim();
+        if weekly.is_empty() {
+            &self.config.database_id
+        } else {
+            weekly
+        }
+    }
+
+    /// 确保周报数据库包含所需属性，返回其标题属性名
+    async fn ensure_weekly_properties(&self, database_id: &str) -> Result<String> {
+        let url = format!("{}/databases/{}", NOTION_API_BASE, database_id);
+        let response = self
+            .send(
+                self.client
+                    .get(&url)
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+                    .header("Notion-Version", NOTION_API_VERSION),
+            )
+            .await?;
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow!("读取周报数据库失败: {}", error_text));
+        }
+
+        let database: Value = response.json().await?;
+        let existing = database["properties"]
+            .as_object()
+            .cloned()
+            .unwrap_or_default();
+        let title_property = existing
+            .iter()
+            .find(|(_, property)| property["type"] == "title")
+            .map(|(name, _)| name.clone())
+            .ok_or_else(|| anyhow!("周报数据库缺少标题属性"))?;
+
+        let missing = weekly_property_schema()
+            .into_iter()
+            .filter(|(name, _)| !existing.contains_key(*name))
+            .map(|(name, schema)| (name.to_string(), schema))
+            .collect::<serde_json::Map<_, _>>();
+        if !missing.is_empty() {
+            info!(
+                "为周报数据库添加属性: {:?}",
+                missing.keys().collect::<Vec<_>>()
+            );
+            let response = self
+                .send(
+                    self.client
+                        .patch(&url)
+                        .header("Authorization", format!("Bearer {}", self.config.api_token))
+                        .header("Notion-Version", NOTION_API_VERSION)
+                        .header("Content-Type", "application/json")
+                        .json(&json!({ "properties": missing })),
+                )
+                .await?;
+            if !response.status().is_success() {
+                let error_text = response.text().await?;
+                return Err(anyhow!("更新周报数据库属性失败: {}", error_text));
+            }
+        }
+        Ok(title_property)
+    }
+
+    /// 同步周报到 Notion（专注时长、生产力评分等写入数字属性）
+    pub async fn sync_weekly_summary(&self, page: &WeeklySummaryPage) -> Result<String> {
+        let database_id = self.weekly_database_id().to_string();
+        info!("开始同步周报 {} 到 Notion", page.week_label);
+
+        let title_property = self.ensure_weekly_properties(&database_id).await?;
+        let payload = json!({
+            "parent": { "database_id": database_id },
+            "properties": build_weekly_properties(&title_property, page),
+        });
+
+        let response = self
+            .send(
+                self.client
+                    .post(format!("{}/pages", NOTION_API_BASE))
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+                    .header("Notion-Version", NOTION_API_VERSION)
+                    .header("Content-Type", "application/json")
+                    .json(&payload),
+            )
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            error!("同步周报到 Notion 失败: {}", error_text);
+            return Err(anyhow!("同步失败: {}", error_text));
+        }
+
+        let created: Value = response.json().await?;
+        let page_id = created["id"].as_str().unwrap_or("unknown");
+        info!(
+            "周报 {} 成功同步到 Notion，页面 ID: {}",
+            page.week_label, page_id
+        );
+        Ok(page_id.to_string())
+    }
+
+    /// 更新已有页面的属性
+    async fn update_page(&self, page_id: &str, properties: Value) -> Result<()> {
+        self.patch_page(page_id, json!({ "properties": properties }))
+            .await
+    }
+
+    /// 归档页面（Notion 中移入回收站）
+    pub async fn archive_page(&self, page_id: &str) -> Result<()> {
+        info!("归档 Notion 页面 {}", page_id);
+        self.patch_page(page_id, json!({ "archived": true })).await
+    }
+
+    async fn patch_page(&self, page_id: &str, payload: Value) -> Result<()> {
+        let response = self
+            .send(
+                self.client
+                    .patch(format!("{}/pages/{}", NOTION_API_BASE, page_id))
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+                    .header("Notion-Version", NOTION_API_VERSION)
+                    .header("Content-Type", "application/json")
+                    .json(&payload),
+            )
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            error!("更新 Notion 页面 {} 失败: {}", page_id, error_text);
+            return Err(anyhow!("更新页面失败: {}", error_text));
+        }
+        Ok(())
+    }
+
+    /// 更新已同步的会话页面
+    pub async fn update_session(
+        &self,
+        page_id: &str,
+        session: &Session,
+        note: Option<&SessionNoteRecord>,
+    ) -> Result<()> {
+        info!("更新 Notion 会话页面 {:?} -> {}", session.id, page_id);
+        let properties = self.build_session_properties(session, note)?;
+        self.update_page(page_id, properties).await
+    }
+
+    /// 更新已同步的每日总结页面
+    pub async fn update_daily_summary(
+        &self,
+        page_id: &str,
+        date: &str,
+        summary: &str,
+    ) -> Result<()> {
+        info!("更新 Notion 每日总结 {} -> {}", date, page_id);
+        self.update_page(page_id, build_daily_properties(date, summary))
+            .await
+    }
+
+    /// 更新已同步的周报页面
+    pub async fn update_weekly_summary(
+        &self,
+        page_id: &str,
+        page: &WeeklySummaryPage,
+    ) -> Result<()> {
+        info!("更新 Notion 周报 {} -> {}", page.week_label, page_id);
+        let title_property = self
+            .ensure_weekly_properties(self.weekly_database_id())
+            .await?;
+        self.update_page(page_id, build_weekly_properties(&title_property, page))
+            .await
+    }
+
     /// 搜索可用的页面和数据库
     pub async fn search_pages(&self) -> Result<Vec<NotionPage>> {
         let url = format!("{}/search", NOTION_API_BASE);
@@ -722,13 +1298,14 @@ impl NotionClient {
         });
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_token))
-            .header("Notion-Version", NOTION_API_VERSION)
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
+            .send(
+                self.client
+                    .post(&url)
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+                    .header("Notion-Version", NOTION_API_VERSION)
+                    .header("Content-Type", "application/json")
+                    .json(&payload),
+            )
             .await?;
 
         if !response.status().is_success() {
@@ -785,10 +1362,52 @@ impl NotionClient {
         parent_page_id: &str,
         database_name: &str,
     ) -> Result<String> {
-        let url = format!("{}/databases", NOTION_API_BASE);
+        self.create_database_with_schema(parent_page_id, database_name, session_database_schema())
+            .await
+    }
 
-        // 获取系统时区
-        let system_timezone = get_system_timezone();
+    /// 在指定页面下一次创建会话、每日总结与周报三个数据库（属性类型与同步时写入的一致）
+    pub async fn setup_databases(
+        &self,
+        parent_page_id: &str,
+        name_prefix: &str,
+    ) -> Result<NotionDatabaseSetup> {
+        let sessions_database_id = self
+            .create_database_with_schema(
+                parent_page_id,
+                &format!("{} 会话记录", name_prefix),
+                session_database_schema(),
+            )
+            .await?;
+        let daily_database_id = self
+            .create_database_with_schema(
+                parent_page_id,
+                &format!("{} 每日总结", name_prefix),
+                daily_database_schema(),
+            )
+            .await?;
+        let weekly_database_id = self
+            .create_database_with_schema(
+                parent_page_id,
+                &format!("{} 周报", name_prefix),
+                weekly_database_schema(),
+            )
+            .await?;
+
+        Ok(NotionDatabaseSetup {
+            sessions_database_id,
+            daily_database_id,
+            weekly_database_id,
+        })
+    }
+
+    async fn create_database_with_schema(
+        &self,
+        parent_page_id: &str,
+        database_name: &str,
+        properties: Value,
+    ) -> Result<String> {
+        let url = format!("{}/databases", NOTION_API_BASE);
 
         let payload = json!({
             "parent": {
@@ -799,49 +1418,18 @@ impl NotionClient {
                 "type": "text",
                 "text": { "content": database_name }
             }],
-            "properties": {
-                "标题": {
-                    "title": {}
-                },
-                "日期": {
-                    "date": {
-                        "time_zone": system_timezone
-                    }
-                },
-                "总结": {
-                    "rich_text": {}
-                },
-                "设备": {
-                    "select": {}
-                },
-                "本地ID": {
-                    "rich_text": {}
-                },
-                "类别": {
-                    "select": {}
-                },
-                "关键词": {
-                    "multi_select": {}
-                },
-                "时长": {
-                    "number": {
-                        "format": "number"
-                    }
-                },
-                "类型": {
-                    "select": {}
-                }
-            }
+            "properties": properties
         });
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_token))
-            .header("Notion-Version", NOTION_API_VERSION)
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
+            .send(
+                self.client
+                    .post(&url)
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+                    .header("Notion-Version", NOTION_API_VERSION)
+                    .header("Content-Type", "application/json")
+                    .json(&payload),
+            )
             .await?;
 
         if !response.status().is_success() {
@@ -853,12 +1441,17 @@ impl NotionClient {
         let database: Value = response.json().await?;
         let database_id = database["id"].as_str().unwrap_or("").replace("-", ""); // 移除 ID 中的连字符
 
-        info!("成功创建数据库，ID: {}", database_id);
+        info!("成功创建数据库 {}，ID: {}", database_name, database_id);
         Ok(database_id)
     }
 
     /// 检查会话是否已经同步（通过本地ID）
     pub async fn is_session_synced(&self, session_id: i64) -> Result<bool> {
+        Ok(self.find_session_page(session_id).await?.is_some())
+    }
+
+    /// 按本地ID查找已同步的会话页面（兼容没有本地同步记录的旧页面）
+    pub async fn find_session_page(&self, session_id: i64) -> Result<Option<String>> {
         let url = format!(
             "{}/databases/{}/query",
             NOTION_API_BASE, self.config.database_id
@@ -874,13 +1467,14 @@ impl NotionClient {
         });
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_token))
-            .header("Notion-Version", NOTION_API_VERSION)
-            .header("Content-Type", "application/json")
-            .json(&filter)
-            .send()
+            .send(
+                self.client
+                    .post(&url)
+                    .header("Authorization", format!("Bearer {}", self.config.api_token))
+                    .header("Notion-Version", NOTION_API_VERSION)
+                    .header("Content-Type", "application/json")
+                    .json(&filter),
+            )
             .await?;
 
         if !response.status().is_success() {
@@ -889,10 +1483,11 @@ impl NotionClient {
         }
 
         let result: Value = response.json().await?;
-        let empty_vec = vec![];
-        let results = result["results"].as_array().unwrap_or(&empty_vec);
-
-        Ok(!results.is_empty())
+        Ok(result["results"]
+            .as_array()
+            .and_then(|results| results.first())
+            .and_then(|page| page["id"].as_str())
+            .map(str::to_string))
     }
 }
 
@@ -925,4 +1520,144 @@ mod tests {
         let client = NotionClient::new(config);
         assert!(client.is_err());
     }
+
+    #[test]
+    fn test_session_properties_with_note() {
+        let client = NotionClient::new(NotionConfig {
+            enabled: true,
+            api_token: "test_token".to_string(),
+            database_id: "test_db_id".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 5, 12)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_utc();
+        let session = Session {
+            id: Some(7),
+            start_time: start,
+            end_time: start + chrono::Duration::minutes(30),
+            title: "接口重构".to_string(),
+            summary: "拆分用户模块".to_string(),
+            video_path: None,
+            tags: r#"[{"category":"work","confidence":0.9,"keywords":["coding"]}]"#.to_string(),
+            created_at: None,
+            device_name: None,
+            device_type: None,
+        };
+        let note =
+            SessionNoteRecord::new(7, "等待评审", &["开发".to_string(), "客户A".to_string()]);
+
+        let properties = client
+            .build_session_properties(&session, note.as_ref())
+            .unwrap();
+        assert_eq!(
+            properties["总结"]["rich_text"][0]["text"]["content"],
+            "拆分用户模块\n\n备注：等待评审"
+        );
+        assert_eq!(
+            properties["关键词"]["multi_select"],
+            json!([{ "name": "开发" }, { "name": "客户A" }])
+        );
+
+        let plain = client.build_session_properties(&session, None).unwrap();
+        assert_eq!(
+            plain["总结"]["rich_text"][0]["text"]["content"],
+            "拆分用户模块"
+        );
+    }
+
+    #[test]
+    fn test_build_weekly_properties() {
+        let page = WeeklySummaryPage {
+            week_label: "2024-W19".to_string(),
+            week_start: "2024-05-06".to_string(),
+            week_end: "2024-05-12".to_string(),
+            session_count: 12,
+            total_minutes: 900,
+            avg_session_minutes: 75,
+            focus_minutes: 600,
+            focus_ratio: 66,
+            productivity_score: 72,
+            top_categories: "工作 8 次".to_string(),
+        };
+
+        let properties = build_weekly_properties("Name", &page);
+        assert_eq!(
+            properties["Name"]["title"][0]["text"]["content"],
+            "周报 - 2024-W19"
+        );
+        assert_eq!(properties["日期"]["date"]["end"], "2024-05-12");
+        assert_eq!(properties["类型"]["select"]["name"], "周报");
+        assert_eq!(properties["focus_minutes"]["number"], 600);
+        assert_eq!(properties["productivity_score"]["number"], 72);
+
+        // 写入的属性都在自动补齐的数据库结构中
+        let schema = weekly_property_schema()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        for name in properties.as_object().unwrap().keys() {
+            assert!(
+                name == "Name" || schema.contains(&name.as_str()),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_bootstrap_schemas_cover_written_properties() {
+        let daily_schema = daily_database_schema();
+        for name in build_daily_properties("2024-05-12", "总结")
+            .as_object()
+            .unwrap()
+            .keys()
+        {
+            assert!(daily_schema.get(name).is_some(), "{}", name);
+        }
+
+        let page = WeeklySummaryPage {
+            week_label: "2024-W19".to_string(),
+            week_start: "2024-05-06".to_string(),
+            week_end: "2024-05-12".to_string(),
+            session_count: 1,
+            total_minutes: 60,
+            avg_session_minutes: 60,
+            focus_minutes: 30,
+            focus_ratio: 50,
+            productivity_score: 60,
+            top_categories: String::new(),
+        };
+        let weekly_schema = weekly_database_schema();
+        assert!(weekly_schema["标题"]["title"].is_object());
+        for name in build_weekly_properties("标题", &page)
+            .as_object()
+            .unwrap()
+            .keys()
+        {
+            assert!(weekly_schema.get(name).is_some(), "{}", name);
+        }
+
+        assert!(session_database_schema()["本地ID"]["rich_text"].is_object());
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        // 没有 Retry-After 时指数退避，并限制上限
+        assert_eq!(retry_delay(None, 1), Duration::from_secs(1));
+        assert_eq!(retry_delay(None, 3), Duration::from_secs(4));
+        assert_eq!(retry_delay(None, 10), MAX_BACKOFF);
+        // Retry-After 优先（支持小数秒，且不受退避上限约束），无效值退回指数退避
+        assert_eq!(retry_delay(Some("2.5"), 1), Duration::from_millis(2500));
+        assert_eq!(retry_delay(Some("600"), 1), Duration::from_secs(600));
+        assert_eq!(retry_delay(Some("soon"), 2), Duration::from_secs(2));
+        // 过大的 Retry-After 截断到上限，负数、NaN 与无穷大退回指数退避
+        assert_eq!(retry_delay(Some("1e20"), 1), MAX_RETRY_AFTER);
+        assert_eq!(retry_delay(Some("-5"), 1), Duration::from_secs(1));
+        assert_eq!(retry_delay(Some("NaN"), 1), Duration::from_secs(1));
+        assert_eq!(retry_delay(Some("inf"), 1), Duration::from_secs(1));
+    }
 }
@@ -2,15 +2,29 @@
 // 提供与 Notion 的数据同步功能
 
 pub mod client;
+pub mod s3;
 
-pub use client::{NotionClient, NotionPage};
+pub use client::{NotionClient, NotionDatabaseSetup, NotionPage, WeeklySummaryPage};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::collections::HashSet;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-use crate::models::{NotionConfig, Session};
+use crate::actors::LLMHandle;
+use crate::exporter::{day_summary, ExportReport, Exporter, NotePreview};
+use crate::frame_store::{FrameStore, LocalFrameStore};
+use crate::models::{NotionConfig, ObsidianExportConfig, Session};
+use crate::obsidian::{ObsidianExporter, WeekSummaryData};
+use crate::storage::{Database, NotionSyncRecord};
+
+/// Notion 同步记录类型
+const SYNC_KIND_SESSION: &str = "session";
+const SYNC_KIND_DAY: &str = "day";
+const SYNC_KIND_WEEK: &str = "week";
 
 /// Notion 同步管理器
 pub struct NotionManager {
@@ -73,7 +87,7 @@ impl NotionManager {
                     cfg.sync_options.sync_videos, cfg.sync_options.video_size_limit_mb
                 );
 
-                match c.sync_session(&session).await {
+                match c.sync_session(&session, None).await {
                     Ok(page_id) => {
                         info!(
                             "会话 {:?} 成功同步到 Notion，页面 ID: {}",
@@ -92,7 +106,7 @@ impl NotionManager {
     pub async fn sync_session(&self, session: &Session) -> Result<String> {
         let client = self.client.read().await;
         match &*client {
-            Some(c) => c.sync_session(session).await,
+            Some(c) => c.sync_session(session, None).await,
             None => Ok("Notion 客户端未初始化".to_string()),
         }
     }
@@ -111,19 +125,18 @@ impl NotionManager {
         self.client.read().await.is_some()
     }
 
-    /// 搜索可用的页面和数据库
-    pub async fn search_pages(&self, api_token: &str) -> Result<Vec<client::NotionPage>> {
-        // 创建临时客户端用于搜索
-        let temp_config = NotionConfig {
+    /// 创建只带 API Token 的临时客户端（搜索页面、创建数据库时不需要 database_id）
+    fn temp_client(api_token: &str) -> Result<NotionClient> {
+        NotionClient::new(NotionConfig {
             enabled: true,
             api_token: api_token.to_string(),
-            database_id: String::new(), // 搜索时不需要 database_id
-            sync_options: Default::default(),
-            max_retries: 3,
-        };
+            ..NotionConfig::default()
+        })
+    }
 
-        let temp_client = NotionClient::new(temp_config)?;
-        temp_client.search_pages().await
+    /// 搜索可用的页面和数据库
+    pub async fn search_pages(&self, api_token: &str) -> Result<Vec<client::NotionPage>> {
+        Self::temp_client(api_token)?.search_pages().await
     }
 
     /// 在指定页面下创建数据库
@@ -133,18 +146,388 @@ impl NotionManager {
         parent_page_id: &str,
         database_name: &str,
     ) -> Result<String> {
-        // 创建临时客户端用于创建数据库
-        let temp_config = NotionConfig {
-            enabled: true,
-            api_token: api_token.to_string(),
-            database_id: String::new(),
-            sync_options: Default::default(),
-            max_retries: 3,
+        Self::temp_client(api_token)?
+            .create_database(parent_page_id, database_name)
+            .await
+    }
+
+    /// 在指定页面下一键创建会话、每日总结与周报数据库
+    pub async fn setup_databases(
+        &self,
+        api_token: &str,
+        parent_page_id: &str,
+    ) -> Result<NotionDatabaseSetup> {
+        Self::temp_client(api_token)?
+            .setup_databases(parent_page_id, "Screen Analyzer")
+            .await
+    }
+}
+
+/// Notion 导出目标：按日期同步会话、每日总结与周报
+///
+/// 已同步的页面 ID 记录在 notion_sync 表中，重新导出时更新原页面而不是重复创建，
+/// 本地已删除的会话对应页面会被归档。
+pub struct NotionExporter {
+    client: NotionClient,
+    /// 周报汇总与截图选取沿用 Obsidian 导出配置
+    obsidian: ObsidianExporter,
+    /// 截图存储后端（上传前解析已转存截图的本机路径）
+    frame_store: Arc<dyn FrameStore>,
+}
+
+impl NotionExporter {
+    pub fn new(config: NotionConfig, obsidian_config: ObsidianExportConfig) -> Result<Self> {
+        if config.database_id.trim().is_empty() {
+            return Err(anyhow!("请先配置 Notion 数据库 ID"));
+        }
+        Ok(Self {
+            client: NotionClient::new(config)?,
+            obsidian: ObsidianExporter::new(obsidian_config),
+            frame_store: Arc::new(LocalFrameStore),
+        })
+    }
+
+    /// 指定截图存储后端（默认读取本机文件）
+    pub fn with_frame_store(mut self, frame_store: Arc<dyn FrameStore>) -> Self {
+        self.obsidian = self.obsidian.with_frame_store(frame_store.clone());
+        self.frame_store = frame_store;
+        self
+    }
+
+    /// 会话已同步的页面 ID：优先使用本地同步记录，其次按本地ID查询 Notion（兼容旧版本同步的页面）
+    async fn session_page(&self, db: &Database, session_id: i64) -> Result<Option<String>> {
+        if let Some(record) = db
+            .get_notion_sync(SYNC_KIND_SESSION, &session_id.to_string())
+            .await?
+        {
+            return Ok(Some(record.page_id));
+        }
+        self.client.find_session_page(session_id).await
+    }
+
+    /// 同步单个会话：已有页面时更新属性，否则创建新页面并附上关键截图
+    ///
+    /// 截图只在创建页面时上传，重复导出不会追加重复的截图块；截图失败记为警告。
+    async fn upsert_session(
+        &self,
+        db: &Database,
+        date: &str,
+        session: &Session,
+        warnings: &mut Vec<String>,
+    ) -> Result<()> {
+        let session_id = session.id.ok_or_else(|| anyhow!("会话缺少 ID"))?;
+        let note = db.get_session_note(session_id).await?;
+        let page_id = match self.session_page(db, session_id).await? {
+            Some(page_id) => {
+                self.client
+                    .update_session(&page_id, session, note.as_ref())
+                    .await?;
+                page_id
+            }
+            None => {
+                let page_id = self.client.sync_session(session, note.as_ref()).await?;
+                if self.client.get_config().sync_options.sync_screenshots {
+                    let screenshots = self.session_screenshots(db, session_id).await;
+                    let prefix = format!("{}-session-{}", date, session_id);
+                    if let Err(e) = self
+                        .client
+                        .add_screenshots_to_page(&page_id, &screenshots, &prefix)
+                        .await
+                    {
+                        warnings.push(format!("会话 {} 截图上传失败: {}", session_id, e));
+                    }
+                }
+                page_id
+            }
         };
+        record_sync(
+            db,
+            SYNC_KIND_SESSION,
+            &session_id.to_string(),
+            date,
+            page_id,
+        )
+        .await
+    }
 
-        let temp_client = NotionClient::new(temp_config)?;
-        temp_client
-            .create_database(parent_page_id, database_name)
+    /// 按 Obsidian 截图策略选取的会话代表截图（无法读取的截图跳过）
+    async fn session_screenshots(&self, db: &Database, session_id: i64) -> Vec<PathBuf> {
+        let frames = db
+            .get_frames_by_session(session_id)
+            .await
+            .unwrap_or_default();
+        let cards = db
+            .get_timeline_cards_by_session(session_id)
+            .await
+            .unwrap_or_default();
+        let mut screenshots = Vec::new();
+        for frame in self.obsidian.select_screenshots(&frames, &cards).await {
+            match self.frame_store.local_path(&frame.file_path).await {
+                Ok(path) => screenshots.push(path),
+                Err(e) => warn!("读取截图失败 {}: {}", frame.file_path, e),
+            }
+        }
+        screenshots
+    }
+
+    /// 归档当天已在本地删除的会话页面，返回失败信息
+    async fn archive_deleted_sessions(
+        &self,
+        db: &Database,
+        date: &str,
+        sessions: &[Session],
+    ) -> Result<Vec<String>> {
+        let live: HashSet<String> = sessions
+            .iter()
+            .filter_map(|session| session.id)
+            .map(|id| id.to_string())
+            .collect();
+        let mut warnings = Vec::new();
+        for record in db.get_notion_syncs_by_date(SYNC_KIND_SESSION, date).await? {
+            if live.contains(&record.local_key) {
+                continue;
+            }
+            match self.client.archive_page(&record.page_id).await {
+                Ok(()) => {
+                    db.delete_notion_sync(SYNC_KIND_SESSION, &record.local_key)
+                        .await?
+                }
+                Err(e) => warnings.push(format!(
+                    "已删除会话 {} 的页面归档失败: {}",
+                    record.local_key, e
+                )),
+            }
+        }
+        Ok(warnings)
+    }
+
+    /// 同步每日总结：已有页面时更新
+    async fn upsert_day(&self, db: &Database, date: &str, summary: &str) -> Result<()> {
+        let page_id = match db.get_notion_sync(SYNC_KIND_DAY, date).await? {
+            Some(record) => {
+                self.client
+                    .update_daily_summary(&record.page_id, date, summary)
+                    .await?;
+                record.page_id
+            }
+            None => self.client.sync_daily_summary(date, summary).await?,
+        };
+        record_sync(db, SYNC_KIND_DAY, date, date, page_id).await
+    }
+
+    /// 同步指定日期所在周的周报：已有页面时更新
+    async fn sync_week(&self, db: &Database, date: &str) -> Result<()> {
+        let week = self.obsidian.week_summary(db, date).await?;
+        let page = weekly_page(&week);
+        let page_id = match db.get_notion_sync(SYNC_KIND_WEEK, &page.week_label).await? {
+            Some(record) => {
+                self.client
+                    .update_weekly_summary(&record.page_id, &page)
+                    .await?;
+                record.page_id
+            }
+            None => self.client.sync_weekly_summary(&page).await?,
+        };
+        record_sync(
+            db,
+            SYNC_KIND_WEEK,
+            &page.week_label,
+            &page.week_start,
+            page_id,
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl Exporter for NotionExporter {
+    fn target_name(&self) -> &'static str {
+        "Notion"
+    }
+
+    async fn export_day(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        date: &str,
+        force_refresh: bool,
+    ) -> Result<ExportReport> {
+        let mut report = ExportReport {
+            target: self.target_name().to_string(),
+            exported_dates: vec![date.to_string()],
+            ..ExportReport::default()
+        };
+        let sync_options = &self.client.get_config().sync_options;
+
+        if sync_options.sync_sessions {
+            let sessions = db.get_sessions_by_date(date).await?;
+            for session in &sessions {
+                match self
+                    .upsert_session(&db, date, session, &mut report.warnings)
+                    .await
+                {
+                    Ok(()) => report.session_count += 1,
+                    Err(e) => {
+                        report.failed_count += 1;
+                        report.warnings.push(format!(
+                            "会话 {} 同步失败: {}",
+                            session.id.unwrap_or(0),
+                            e
+                        ));
+                    }
+                }
+            }
+            let archive_warnings = self.archive_deleted_sessions(&db, date, &sessions).await?;
+            report.failed_count += archive_warnings.len();
+            report.warnings.extend(archive_warnings);
+        }
+
+        if sync_options.sync_daily_summary {
+            let summary = day_summary(db.clone(), llm_handle, date, force_refresh).await?;
+            if let Err(e) = self.upsert_day(&db, date, &summary.summary_text).await {
+                report.failed_count += 1;
+                report.warnings.push(format!("每日总结同步失败: {}", e));
+            }
+        }
+
+        if sync_options.sync_weekly_summary {
+            if let Err(e) = self.sync_week(&db, date).await {
+                report.failed_count += 1;
+                report.warnings.push(format!("周报同步失败: {}", e));
+            }
+        }
+        Ok(report)
+    }
+
+    /// 列出将要创建或更新的会话页面（标题与总结）
+    async fn preview_day(
+        &self,
+        db: Arc<Database>,
+        _llm_handle: LLMHandle,
+        date: &str,
+    ) -> Result<Vec<NotePreview>> {
+        let mut previews = Vec::new();
+        for session in db.get_sessions_by_date(date).await? {
+            let existing = self
+                .session_page(&db, session.id.unwrap_or(0))
+                .await
+                .unwrap_or(None);
+            previews.push(NotePreview {
+                path: PathBuf::from(format!("Notion/{}", session.title)),
+                content: session.summary,
+                would_overwrite: existing.is_some(),
+            });
+        }
+        Ok(previews)
+    }
+}
+
+/// 记录本地实体对应的 Notion 页面
+async fn record_sync(
+    db: &Database,
+    kind: &str,
+    local_key: &str,
+    date: &str,
+    page_id: String,
+) -> Result<()> {
+    db.save_notion_sync(&NotionSyncRecord {
+        kind: kind.to_string(),
+        local_key: local_key.to_string(),
+        date: date.to_string(),
+        page_id,
+        synced_at: chrono::Utc::now(),
+    })
+    .await
+}
+
+/// 周报汇总 -> Notion 周报页面数据
+fn weekly_page(week: &WeekSummaryData) -> WeeklySummaryPage {
+    WeeklySummaryPage {
+        week_label: week.week_label.clone(),
+        week_start: week.week_start.clone(),
+        week_end: week.week_end.clone(),
+        session_count: i64::from(week.total_sessions),
+        total_minutes: i64::from(week.total_minutes),
+        avg_session_minutes: i64::from(week.avg_session_minutes),
+        focus_minutes: week.focus_minutes(),
+        focus_ratio: week.focus_ratio(),
+        productivity_score: week.productivity_score(),
+        top_categories: week.top_categories.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_notion_sync_records() {
+        let temp_dir = tempdir().unwrap();
+        let db = Database::new_sqlite(temp_dir.path().join("test.db").to_str().unwrap())
+            .await
+            .unwrap();
+
+        record_sync(
+            &db,
+            SYNC_KIND_SESSION,
+            "3",
+            "2024-05-12",
+            "page-a".to_string(),
+        )
+        .await
+        .unwrap();
+        record_sync(
+            &db,
+            SYNC_KIND_SESSION,
+            "7",
+            "2024-05-12",
+            "page-b".to_string(),
+        )
+        .await
+        .unwrap();
+        record_sync(
+            &db,
+            SYNC_KIND_DAY,
+            "2024-05-12",
+            "2024-05-12",
+            "page-c".to_string(),
+        )
+        .await
+        .unwrap();
+        // 重新导出覆盖同一会话的记录
+        record_sync(
+            &db,
+            SYNC_KIND_SESSION,
+            "3",
+            "2024-05-12",
+            "page-d".to_string(),
+        )
+        .await
+        .unwrap();
+
+        let record = db
+            .get_notion_sync(SYNC_KIND_SESSION, "3")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.page_id, "page-d");
+        let sessions = db
+            .get_notion_syncs_by_date(SYNC_KIND_SESSION, "2024-05-12")
+            .await
+            .unwrap();
+        assert_eq!(sessions.len(), 2);
+
+        db.delete_notion_sync(SYNC_KIND_SESSION, "7").await.unwrap();
+        assert!(db
+            .get_notion_sync(SYNC_KIND_SESSION, "7")
+            .await
+            .unwrap()
+            .is_none());
+        assert!(db
+            .get_notion_sync(SYNC_KIND_DAY, "2024-05-12")
             .await
+            .unwrap()
+            .is_some());
     }
 }
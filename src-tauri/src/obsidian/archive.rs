@@ -0,0 +1,168 @@
+// Obsidian 导出 - 打包为 zip 压缩包（不直接写入 Vault）
+
+use anyhow::{anyhow, Result};
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedSender;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use super::{ExportEvent, ExportOutcome, ObsidianExporter};
+use crate::actors::LLMHandle;
+use crate::models::{ObsidianExportMode, ObsidianExportTarget};
+use crate::storage::Database;
+
+/// 需要压缩的文本类文件扩展名（截图等已压缩的文件直接存储）
+const DEFLATE_EXTENSIONS: [&str; 3] = ["md", "canvas", "json"];
+
+impl ObsidianExporter {
+    /// 将日期范围导出为 zip 压缩包
+    ///
+    /// 先按原有逻辑导出到临时目录（截图一律复制，保证压缩包自包含），再打包为 `archive_path`；
+    /// 单日导出（起止相同）保留 `force_refresh` 语义。
+    pub async fn export_to_archive(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        start_date: &str,
+        end_date: &str,
+        force_refresh: bool,
+        tx: UnboundedSender<ExportEvent>,
+    ) -> Result<ExportOutcome> {
+        let archive_path = self.archive_path()?;
+        let staging = tempfile::tempdir()?;
+
+        let mut config = self.config.clone();
+        config.vault_path = staging.path().to_string_lossy().into_owned();
+        config.export_target = ObsidianExportTarget::Vault;
+        config.export_mode = ObsidianExportMode::Copy;
+        config.use_vault_attachment_folder = false;
+        let staged = ObsidianExporter::new(config).with_frame_store(self.frame_store.clone());
+
+        let mut outcome = if start_date == end_date {
+            staged
+                .export_day_with_events(db, llm_handle, start_date, force_refresh, tx)
+                .await?
+        } else {
+            staged
+                .export_range_with_events(db, llm_handle, start_date, end_date, tx)
+                .await?
+        };
+
+        let source = staging.path().to_path_buf();
+        let target = archive_path.clone();
+        tokio::task::spawn_blocking(move || write_archive(&source, &target))
+            .await
+            .map_err(|e| anyhow!("压缩包写入任务失败: {}", e))??;
+
+        relativize_outcome(&mut outcome, staging.path());
+        outcome.archive_path = Some(archive_path);
+        Ok(outcome)
+    }
+
+    /// 压缩包输出路径（缺少 .zip 扩展名时自动补全）
+    pub(super) fn archive_path(&self) -> Result<PathBuf> {
+        let raw = self.config.archive_path.trim();
+        if raw.is_empty() {
+            return Err(anyhow!("未配置压缩包导出路径"));
+        }
+        let path = PathBuf::from(raw);
+        let is_zip = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+        let path = if is_zip {
+            path
+        } else {
+            PathBuf::from(format!("{}.zip", raw))
+        };
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            if !parent.is_dir() {
+                return Err(anyhow!("压缩包所在目录不存在: {}", parent.display()));
+            }
+        }
+        Ok(path)
+    }
+}
+
+/// 将 `source` 下的全部文件写入压缩包（路径使用 `/` 分隔），返回文件数
+///
+/// 先写入同目录下的临时文件再重命名，避免失败时留下不完整的压缩包。
+fn write_archive(source: &Path, target: &Path) -> Result<usize> {
+    let mut files = Vec::new();
+    collect_files(source, &mut files)?;
+    files.sort();
+
+    let partial = target.with_extension("zip.partial");
+    let result = (|| -> Result<()> {
+        let mut zip = ZipWriter::new(File::create(&partial)?);
+        for path in &files {
+            let name = path
+                .strip_prefix(source)?
+                .components()
+                .map(|part| part.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let compress = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| DEFLATE_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+            let options = SimpleFileOptions::default().compression_method(if compress {
+                CompressionMethod::Deflated
+            } else {
+                CompressionMethod::Stored
+            });
+            zip.start_file(name, options)?;
+            io::copy(&mut File::open(path)?, &mut zip)?;
+        }
+        zip.finish()?;
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => {
+            std::fs::rename(&partial, target)?;
+            Ok(files.len())
+        }
+        Err(err) => {
+            let _ = std::fs::remove_file(&partial);
+            Err(err)
+        }
+    }
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// 将导出结果中的临时目录路径改写为压缩包内的相对路径
+fn relativize_outcome(outcome: &mut ExportOutcome, staging: &Path) {
+    let relative = |path: &mut PathBuf| {
+        if let Ok(inner) = path.strip_prefix(staging) {
+            *path = inner.to_path_buf();
+        }
+    };
+    relative(&mut outcome.daily_note_path);
+    outcome.session_paths.iter_mut().for_each(relative);
+    outcome.project_paths.iter_mut().for_each(relative);
+    [
+        &mut outcome.index_note_path,
+        &mut outcome.week_index_path,
+        &mut outcome.weekly_note_path,
+        &mut outcome.overview_path,
+    ]
+    .into_iter()
+    .flatten()
+    .for_each(relative);
+}
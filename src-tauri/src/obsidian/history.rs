@@ -0,0 +1,252 @@
+// Obsidian 导出 - 导出历史（记录每次导出的参数、写入文件与警告，支持重新运行）
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use super::ExportOutcome;
+use crate::models::ObsidianExportTarget;
+use crate::storage::{local_now, ExportHistoryRecord};
+
+/// 导出参数（序列化后存入导出历史，用于重新运行）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExportRequest {
+    /// 单日导出
+    Day { date: String, force_refresh: bool },
+    /// 日期范围批量导出
+    Range {
+        start_date: String,
+        end_date: String,
+    },
+    /// 季度回顾
+    Quarter { date: String },
+    /// 年度回顾
+    Year { date: String },
+    /// 仅导出选中的会话
+    Sessions { session_ids: Vec<i64> },
+    /// 重试此前失败的会话
+    Retry { session_ids: Vec<i64> },
+}
+
+impl ExportRequest {
+    fn kind(&self) -> &'static str {
+        match self {
+            ExportRequest::Day { .. } => "day",
+            ExportRequest::Range { .. } => "range",
+            ExportRequest::Quarter { .. } => "quarter",
+            ExportRequest::Year { .. } => "year",
+            ExportRequest::Sessions { .. } => "sessions",
+            ExportRequest::Retry { .. } => "retry",
+        }
+    }
+}
+
+/// 一次导出运行的结果（写入导出历史）
+#[derive(Debug)]
+pub struct ExportRun {
+    pub request: ExportRequest,
+    pub target: ObsidianExportTarget,
+    pub success: bool,
+    pub message: String,
+    pub written_paths: Vec<PathBuf>,
+    pub warnings: Vec<String>,
+    pub failed_sessions: Vec<i64>,
+}
+
+impl ExportRun {
+    /// 由导出结果生成运行记录，失败时仅保留错误信息
+    pub fn from_result(
+        request: ExportRequest,
+        target: ObsidianExportTarget,
+        result: &Result<ExportOutcome>,
+    ) -> Self {
+        match result {
+            Ok(outcome) => Self {
+                request,
+                target,
+                success: true,
+                message: outcome.render_message(),
+                written_paths: outcome.written_paths(),
+                warnings: outcome.warnings.clone(),
+                failed_sessions: outcome
+                    .failed_sessions
+                    .iter()
+                    .map(|failure| failure.session_id)
+                    .collect(),
+            },
+            Err(err) => Self {
+                request,
+                target,
+                success: false,
+                message: err.to_string(),
+                written_paths: Vec::new(),
+                warnings: Vec::new(),
+                failed_sessions: Vec::new(),
+            },
+        }
+    }
+
+    /// 转换为数据库记录（列表字段存为 JSON 数组）
+    pub fn to_record(&self) -> Result<ExportHistoryRecord> {
+        let target = match self.target {
+            ObsidianExportTarget::Vault => "vault",
+            ObsidianExportTarget::Zip => "zip",
+        };
+        let written_paths = self
+            .written_paths
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        Ok(ExportHistoryRecord {
+            id: None,
+            kind: self.request.kind().to_string(),
+            target: target.to_string(),
+            request: serde_json::to_string(&self.request)?,
+            success: self.success,
+            message: self.message.clone(),
+            written_paths: serde_json::to_string(&written_paths)?,
+            warnings: serde_json::to_string(&self.warnings)?,
+            failed_sessions: serde_json::to_string(&self.failed_sessions)?,
+            created_at: local_now(),
+        })
+    }
+}
+
+/// 重新运行导出历史：整体失败时按原参数重新导出，部分会话失败时仅重试这些会话
+pub fn rerun_request(record: &ExportHistoryRecord) -> Result<ExportRequest> {
+    let request: ExportRequest = serde_json::from_str(&record.request)
+        .map_err(|e| anyhow!("导出历史参数无法解析: {}", e))?;
+    if !record.success {
+        return Ok(request);
+    }
+
+    let failed_sessions: Vec<i64> = serde_json::from_str(&record.failed_sessions)?;
+    if failed_sessions.is_empty() {
+        return Err(anyhow!("该次导出已成功完成，无需重新运行"));
+    }
+    Ok(ExportRequest::Retry {
+        session_ids: failed_sessions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obsidian::{ConflictCounts, SessionExportError};
+    use crate::storage::Database;
+    use tempfile::tempdir;
+
+    fn outcome() -> ExportOutcome {
+        ExportOutcome {
+            daily_note_path: PathBuf::from("/vault/Daily/2024-05-12.md"),
+            session_paths: vec![PathBuf::from("/vault/Sessions/2024-05-12/a.md")],
+            index_note_path: Some(PathBuf::from("/vault/Index/2024-05.md")),
+            week_index_path: None,
+            weekly_note_path: None,
+            overview_path: None,
+            project_paths: Vec::new(),
+            warnings: vec!["截图缺失".to_string()],
+            failed_sessions: vec![SessionExportError {
+                session_id: 7,
+                message: "写入失败".to_string(),
+            }],
+            exported_dates: Vec::new(),
+            skipped_files: 0,
+            archive_path: None,
+            conflicts: ConflictCounts::default(),
+        }
+    }
+
+    #[test]
+    fn test_export_run_record_and_rerun() {
+        let request = ExportRequest::Day {
+            date: "2024-05-12".to_string(),
+            force_refresh: false,
+        };
+
+        let record =
+            ExportRun::from_result(request.clone(), ObsidianExportTarget::Vault, &Ok(outcome()))
+                .to_record()
+                .unwrap();
+        assert_eq!(record.kind, "day");
+        assert_eq!(record.target, "vault");
+        assert!(record.success);
+        assert_eq!(
+            record.written_paths,
+            r#"["/vault/Daily/2024-05-12.md","/vault/Sessions/2024-05-12/a.md","/vault/Index/2024-05.md"]"#
+        );
+        assert_eq!(record.warnings, r#"["截图缺失"]"#);
+        assert_eq!(record.failed_sessions, "[7]");
+        // 部分会话失败：只重试失败的会话
+        assert_eq!(
+            rerun_request(&record).unwrap(),
+            ExportRequest::Retry {
+                session_ids: vec![7]
+            }
+        );
+
+        let failed = ExportRun::from_result(
+            request.clone(),
+            ObsidianExportTarget::Zip,
+            &Err(anyhow!("未配置压缩包导出路径")),
+        )
+        .to_record()
+        .unwrap();
+        assert!(!failed.success);
+        assert_eq!(failed.message, "未配置压缩包导出路径");
+        assert_eq!(rerun_request(&failed).unwrap(), request);
+
+        let mut clean = outcome();
+        clean.failed_sessions.clear();
+        let record = ExportRun::from_result(request, ObsidianExportTarget::Vault, &Ok(clean))
+            .to_record()
+            .unwrap();
+        assert!(rerun_request(&record).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_export_history_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let db = Database::new_sqlite(temp_dir.path().join("test.db").to_str().unwrap())
+            .await
+            .unwrap();
+
+        let request = ExportRequest::Sessions {
+            session_ids: vec![3, 7],
+        };
+        let run = ExportRun::from_result(request, ObsidianExportTarget::Vault, &Ok(outcome()));
+        let first = db
+            .insert_export_history(&run.to_record().unwrap())
+            .await
+            .unwrap();
+        let failed = ExportRun::from_result(
+            ExportRequest::Quarter {
+                date: "2024-05-12".to_string(),
+            },
+            ObsidianExportTarget::Vault,
+            &Err(anyhow!("未配置 Vault 路径")),
+        );
+        let second = db
+            .insert_export_history(&failed.to_record().unwrap())
+            .await
+            .unwrap();
+
+        let history = db.get_export_history(10).await.unwrap();
+        assert_eq!(
+            history.iter().map(|record| record.id).collect::<Vec<_>>(),
+            vec![Some(second), Some(first)]
+        );
+
+        let entry = db.get_export_history_entry(first).await.unwrap();
+        assert_eq!(entry.kind, "sessions");
+        assert!(entry.success);
+        assert_eq!(
+            rerun_request(&entry).unwrap(),
+            ExportRequest::Retry {
+                session_ids: vec![7]
+            }
+        );
+        assert!(db.get_export_history_entry(999).await.is_err());
+    }
+}
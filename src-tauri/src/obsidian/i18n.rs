@@ -0,0 +1,498 @@
+// Obsidian 导出文本 - 笔记标题/标签的多语言字符串表
+
+use chrono::{Datelike, NaiveDate, Weekday};
+
+use crate::models::ExportLanguage;
+
+/// 笔记中使用的固定文本；含 `{{key}}` 占位符的条目通过 `fill` 填充
+pub(super) struct NoteStrings {
+    // 每日笔记
+    pub daily_title: &'static str,
+    pub session_index: &'static str,
+    pub usage_patterns: &'static str,
+    pub device_stats: &'static str,
+    pub app_usage: &'static str,
+    pub timeline_chart: &'static str,
+    pub meetings: &'static str,
+    pub no_sessions: &'static str,
+    pub no_usage_patterns: &'static str,
+    pub no_device_stats: &'static str,
+    pub device_line: &'static str,
+    pub ungrouped: &'static str,
+    pub no_app_usage: &'static str,
+    pub unknown_app: &'static str,
+    pub app_line: &'static str,
+    pub app_title_line: &'static str,
+    pub app_usage_estimated: &'static str,
+    pub websites: &'static str,
+    pub website_line: &'static str,
+    pub goals: &'static str,
+    pub goal_line: &'static str,
+    pub goal_streak: &'static str,
+
+    // 会话笔记
+    pub untitled_session: &'static str,
+    pub no_summary: &'static str,
+    pub metrics: &'static str,
+    pub timeline: &'static str,
+    pub related_issues: &'static str,
+    pub notes: &'static str,
+    pub focus_blocks: &'static str,
+    pub focus_block_line: &'static str,
+    pub focus_untitled: &'static str,
+    /// 专注时段状态：进行中/已完成/提前结束
+    pub focus_statuses: [&'static str; 3],
+    pub focus_distraction_apps: &'static str,
+    pub video: &'static str,
+    pub screenshots: &'static str,
+    pub replay_video: &'static str,
+    pub no_video: &'static str,
+    pub no_screenshots: &'static str,
+    pub screenshot_failed: &'static str,
+    pub screenshots_pruned: &'static str,
+    pub broken_attachment: &'static str,
+    pub broken_link: &'static str,
+    pub no_timeline: &'static str,
+    /// 本地启发式分类生成的卡片标题后缀
+    pub heuristic_card: &'static str,
+    pub untitled_segment: &'static str,
+    pub no_metrics: &'static str,
+    pub metrics_lines: &'static str,
+    /// 碎片化等级：低/中/高
+    pub fragmentation_levels: [&'static str; 3],
+    pub footer: &'static str,
+
+    // 索引、周报与回顾
+    pub month_index_title: &'static str,
+    pub week_index_title: &'static str,
+    pub weekly_title: &'static str,
+    pub quarter_title: &'static str,
+    pub year_title: &'static str,
+    pub overview: &'static str,
+    pub overview_lines: &'static str,
+    pub rollup_overview_lines: &'static str,
+    pub daily_details: &'static str,
+    pub focus_heatmap: &'static str,
+    /// 热力图行名（周一起）
+    pub heatmap_weekdays: [&'static str; 7],
+    pub heatmap_legend: &'static str,
+    pub heatmap_peak: &'static str,
+    pub focus: &'static str,
+    pub weekly_insights: &'static str,
+    pub score_notes: &'static str,
+    pub score_lines: &'static str,
+    pub daily_highlights: &'static str,
+    pub week_index: &'static str,
+    pub month_trend: &'static str,
+    pub week_trend: &'static str,
+    pub quarter_reviews: &'static str,
+    pub no_daily_summaries: &'static str,
+    pub no_insights: &'static str,
+    pub none: &'static str,
+    pub list_separator: &'static str,
+    pub day_table_header: &'static str,
+    pub month_table_header: &'static str,
+    pub week_table_header: &'static str,
+    pub no_focus_metrics: &'static str,
+    pub focus_metrics_lines: &'static str,
+    pub week_comparison: &'static str,
+    pub comparison_table_header: &'static str,
+    /// 对比行名称：总时长/专注占比/生产力评分
+    pub comparison_labels: [&'static str; 3],
+    pub percentage_points: &'static str,
+    pub no_previous_week: &'static str,
+    pub goal_table_header: &'static str,
+    pub daily_trend: &'static str,
+    pub trend_table_header: &'static str,
+    pub trend_best_day: &'static str,
+    pub device_focus: &'static str,
+    pub device_focus_line: &'static str,
+    pub wellbeing: &'static str,
+    pub wellbeing_long_day: &'static str,
+    pub wellbeing_late_streak: &'static str,
+    pub wellbeing_shrinking_breaks: &'static str,
+    pub insights: WeekInsightStrings,
+
+    // 总览
+    pub overview_title: &'static str,
+    pub overview_links: &'static str,
+    pub recent_stats: &'static str,
+    pub recent_table_header: &'static str,
+    pub last_days: &'static str,
+    pub current_streak: &'static str,
+    pub longest_streak: &'static str,
+    pub focus_trend: &'static str,
+    pub sparkline_note: &'static str,
+    pub latest_line: &'static str,
+    pub latest_focus: &'static str,
+    pub no_focus_data: &'static str,
+    pub recent_weeks: &'static str,
+    pub recent_week_line: &'static str,
+    pub no_weekly_notes: &'static str,
+
+    // 项目
+    pub project_title: &'static str,
+    pub project_overview_lines: &'static str,
+    pub project_trend: &'static str,
+    pub project_week_header: &'static str,
+    pub project_sessions: &'static str,
+    pub project_session_line: &'static str,
+}
+
+/// 周报摘要中的自动结论
+pub(super) struct WeekInsightStrings {
+    pub high_focus: &'static str,
+    pub low_focus: &'static str,
+    pub medium_focus: &'static str,
+    pub high_productivity: &'static str,
+    pub low_productivity: &'static str,
+    pub low_effort: &'static str,
+    pub high_effort: &'static str,
+    pub short_sessions: &'static str,
+    pub long_sessions: &'static str,
+    pub baseline_above: &'static str,
+    pub baseline_below: &'static str,
+    pub baseline_typical: &'static str,
+    pub baseline_points: &'static str,
+    pub baseline_minutes: &'static str,
+    /// 依次为专注时长、专注占比、生产力评分、总时长、平均会话时长
+    pub baseline_metrics: [&'static str; 5],
+}
+
+static ZH: NoteStrings = NoteStrings {
+    daily_title: "{{date}} 屏幕活动总结",
+    session_index: "会话索引",
+    usage_patterns: "使用模式",
+    device_stats: "设备统计",
+    app_usage: "应用使用",
+    timeline_chart: "时间线图",
+    meetings: "会议",
+    no_sessions: "当天没有会话记录",
+    no_usage_patterns: "暂无统计",
+    no_device_stats: "暂无设备统计",
+    device_line: "{{name}} ({{device_type}})：{{time}}，截图 {{screenshots}} 张",
+    ungrouped: "未分组",
+    no_app_usage: "暂无应用使用数据",
+    unknown_app: "未知应用",
+    app_line: "{{app}}：{{minutes}} 分钟（{{percent}}%）",
+    app_title_line: "{{app}}（{{title}}）：{{minutes}} 分钟（{{percent}}%）",
+    app_usage_estimated: "未采集窗口信息，以下按时间线卡片的主要应用估算",
+    websites: "网站访问",
+    website_line: "{{domain}}：{{time}}",
+    goals: "目标",
+    goal_line: "{{status}} {{name}}：{{actual}} 分钟（目标 {{comparison}} {{target}} 分钟）",
+    goal_streak: "，已连续达成 {{days}} 天",
+
+    untitled_session: "未命名会话",
+    no_summary: "暂无总结",
+    metrics: "指标",
+    timeline: "时间线",
+    related_issues: "相关工单",
+    notes: "备注",
+    focus_blocks: "专注时段",
+    focus_block_line: "{{start}}-{{end}} {{label}}：计划 {{planned}} 分钟，{{status}}，干扰 {{count}} 次",
+    focus_untitled: "专注",
+    focus_statuses: ["进行中", "已完成", "提前结束"],
+    focus_distraction_apps: "（{{apps}}）",
+    video: "视频",
+    screenshots: "截图",
+    replay_video: "回放视频",
+    no_video: "暂无视频",
+    no_screenshots: "暂无可用截图",
+    screenshot_failed: "截图处理失败",
+    screenshots_pruned: "截图已按保留策略清理",
+    broken_attachment: "*（附件已失效: {{name}}）*",
+    broken_link: "{{label}}（链接已失效）",
+    no_timeline: "无可用时间线",
+    heuristic_card: "（本地分类）",
+    untitled_segment: "未命名片段",
+    no_metrics: "暂无指标",
+    metrics_lines: "- 片段数量: {{cards}}\n- 上下文切换: {{switches}}\n- 平均片段时长: {{avg}} 分钟\n- 碎片化等级: {{level}}",
+    fragmentation_levels: ["低", "中", "高"],
+    footer: "由 Screen Analyzer v{{version}} 生成 · 数据截至 {{time}}",
+
+    month_index_title: "{{month}} 月度索引",
+    week_index_title: "{{week}} 周度索引",
+    weekly_title: "{{week}} 周报",
+    quarter_title: "{{year}} 年第 {{quarter}} 季度回顾",
+    year_title: "{{year}} 年度回顾",
+    overview: "概览",
+    overview_lines: "- 会话总数：{{sessions}}\n- 总时长：{{minutes}} 分钟\n- 平均会话时长：{{avg_session}} 分钟\n- 主要类别：{{top_categories}}",
+    rollup_overview_lines: "- 统计周数：{{week_count}}\n- 会话总数：{{sessions}}\n- 总时长：{{minutes}} 分钟\n- 专注占比：{{focus_ratio}}%\n- 平均生产力评分：{{avg_productivity}}",
+    daily_details: "每日明细",
+    focus_heatmap: "专注热力图",
+    heatmap_weekdays: ["周一", "周二", "周三", "周四", "周五", "周六", "周日"],
+    heatmap_legend: "每格为一小时的专注（工作 + 学习）时长：⬜ 无 · 🟨 较少 · 🟧 中等 · 🟥 集中（最多 {{max}} 分钟）",
+    heatmap_peak: "- 专注高峰: {{weekday}} {{hour}}:00-{{next_hour}}:00，共 {{minutes}} 分钟",
+    focus: "专注度",
+    weekly_insights: "周报摘要",
+    score_notes: "评分说明",
+    score_lines: "- 专注评分 = 专注占比\n- 投入时长评分：以 {{target_minutes}} 分钟为 100 分，上限封顶\n- 生产力评分 = 专注评分 {{focus_weight}}% + 投入时长评分 {{effort_weight}}%",
+    daily_highlights: "每日要点",
+    week_index: "周索引",
+    month_trend: "月度趋势",
+    week_trend: "周趋势",
+    quarter_reviews: "季度回顾",
+    no_daily_summaries: "暂无每日总结",
+    no_insights: "暂无摘要",
+    none: "暂无",
+    list_separator: "、",
+    day_table_header: "| 日期 | 会话数 | 总时长(分钟) | 主要类别 |",
+    month_table_header: "| 月份 | 周数 | 会话数 | 总时长(分钟) | 专注占比 | 平均生产力评分 |",
+    week_table_header: "| 周 | 会话数 | 总时长(分钟) | 专注占比 | 生产力评分 |",
+    no_focus_metrics: "暂无可用专注度数据",
+    focus_metrics_lines: "- 专注时长: {{focus_minutes}} 分钟 ({{focus_ratio}}%)\n- 沟通时长: {{communication_minutes}} 分钟\n- 分心时长: {{distraction_minutes}} 分钟 ({{distraction_ratio}}%)\n- 专注评分: {{focus_score}} / 100\n- 投入时长评分: {{effort_score}} / 100（目标 {{target_minutes}} 分钟）\n- 生产力评分: {{productivity_score}} / 100（权重 {{focus_weight}}% / {{effort_weight}}%）\n- 细分: 工作 {{work}} / 学习 {{learning}} / 个人 {{personal}} / 空闲 {{idle}} / 其他 {{other}}",
+    week_comparison: "与上周对比",
+    comparison_table_header: "| 指标 | 本周 | 上周 | 变化 |",
+    comparison_labels: ["总时长(分钟)", "专注占比", "生产力评分"],
+    percentage_points: "{{delta}} 个百分点",
+    no_previous_week: "上周（{{week}}）没有记录，暂无对比",
+    goal_table_header: "| 目标 | 目标时长(分钟) | 达成天数 | 连续达成(天) |",
+    daily_trend: "每日趋势",
+    trend_table_header: "| 指标 | 7 日均值 | 30 日均值 | 走势 |",
+    trend_best_day: "- 本周最佳: {{date}}，生产力评分 {{score}}（近 90 天第 {{percentile}} 百分位）",
+    device_focus: "设备专注度",
+    device_focus_line: "- {{name}}：专注 {{focus}} 分钟（{{ratio}}%），共 {{total}} 分钟，{{sessions}} 个会话",
+    wellbeing: "注意休息",
+    wellbeing_long_day: "- ⚠️ {{date}} 屏幕时间 {{minutes}} 分钟，超过 {{limit}} 分钟",
+    wellbeing_late_streak: "- ⚠️ {{start}} 至 {{end}} 连续 {{days}} 天深夜工作",
+    wellbeing_shrinking_breaks: "- ⚠️ 每小时屏幕时间的休息从上周的 {{previous}} 分钟减少到 {{current}} 分钟（-{{percent}}%）",
+    insights: WeekInsightStrings {
+        high_focus: "本周专注度较高，建议保持当前节奏",
+        low_focus: "本周专注度偏低，建议减少高干扰活动",
+        medium_focus: "本周专注度处于中等水平，可优化任务切换",
+        high_productivity: "生产力评分较高，投入与产出较为平衡",
+        low_productivity: "生产力评分偏低，需关注投入时长与专注占比",
+        low_effort: "本周投入时长偏少，可能处于低负荷状态",
+        high_effort: "本周投入时长较高，注意避免过度疲劳",
+        short_sessions: "平均会话较短，存在碎片化倾向",
+        long_sessions: "平均会话较长，体现深度工作趋势",
+        baseline_above: "{{metric}}比近 {{weeks}} 周的平常水平高 {{delta}}（z = {{z}}）",
+        baseline_below: "{{metric}}比近 {{weeks}} 周的平常水平低 {{delta}}（z = {{z}}）",
+        baseline_typical: "本周各项指标都在近 {{weeks}} 周的平常范围内",
+        baseline_points: "{{delta}} 分",
+        baseline_minutes: "{{delta}} 分钟",
+        baseline_metrics: ["专注时长", "专注占比", "生产力评分", "总时长", "平均会话时长"],
+    },
+
+    overview_title: "Screen Analyzer 总览",
+    overview_links: "- 今日：{{daily_link}}\n- 本周：{{week_link}}\n- 本周索引：{{week_index_link}}\n- 本月索引：{{month_index_link}}",
+    recent_stats: "近期统计",
+    recent_table_header: "| 区间 | 会话数 | 总时长(分钟) | 活跃天数 |",
+    last_days: "近 {{days}} 天",
+    current_streak: "当前连续记录：{{days}} 天",
+    longest_streak: "近 30 天最长连续：{{days}} 天",
+    focus_trend: "专注度趋势",
+    sparkline_note: "近 {{weeks}} 周（最新在右，· 表示无数据）",
+    latest_line: "最近：{{focus}}",
+    latest_focus: "{{week}} 专注占比 {{ratio}}%",
+    no_focus_data: "暂无专注度数据",
+    recent_weeks: "最近周报",
+    recent_week_line: "{{link}}：{{sessions}} 个会话，{{minutes}} 分钟",
+    no_weekly_notes: "暂无周报",
+
+    project_title: "项目：{{name}}",
+    project_overview_lines: "- 累计时长：{{minutes}} 分钟\n- 会话数：{{sessions}}\n- 最近活动：{{last_active}}",
+    project_trend: "近 {{weeks}} 周趋势",
+    project_week_header: "| 周 | 时长(分钟) |",
+    project_sessions: "最近会话",
+    project_session_line: "{{date}} {{time}} {{link}}（{{minutes}} 分钟）",
+};
+
+static EN: NoteStrings = NoteStrings {
+    daily_title: "Screen Activity Summary — {{long_date}}",
+    session_index: "Sessions",
+    usage_patterns: "Usage Patterns",
+    device_stats: "Devices",
+    app_usage: "App Usage",
+    timeline_chart: "Timeline Chart",
+    meetings: "Meetings",
+    no_sessions: "No sessions recorded",
+    no_usage_patterns: "No statistics yet",
+    no_device_stats: "No device statistics",
+    device_line: "{{name}} ({{device_type}}): {{time}}, {{screenshots}} screenshots",
+    ungrouped: "Ungrouped",
+    no_app_usage: "No application data",
+    unknown_app: "Unknown app",
+    app_line: "{{app}}: {{minutes}} min ({{percent}}%)",
+    app_title_line: "{{app}} ({{title}}): {{minutes}} min ({{percent}}%)",
+    app_usage_estimated: "No window data captured; estimated from the primary app of timeline cards",
+    websites: "Websites",
+    website_line: "{{domain}}: {{time}}",
+    goals: "Goals",
+    goal_line: "{{status}} {{name}}: {{actual}} min (target {{comparison}} {{target}} min)",
+    goal_streak: ", {{days}}-day streak",
+
+    untitled_session: "Untitled session",
+    no_summary: "No summary yet",
+    metrics: "Metrics",
+    timeline: "Timeline",
+    related_issues: "Related Issues",
+    notes: "Notes",
+    focus_blocks: "Focus Blocks",
+    focus_block_line: "{{start}}-{{end}} {{label}}: planned {{planned}} min, {{status}}, {{count}} distractions",
+    focus_untitled: "Focus",
+    focus_statuses: ["running", "completed", "ended early"],
+    focus_distraction_apps: " ({{apps}})",
+    video: "Video",
+    screenshots: "Screenshots",
+    replay_video: "Replay video",
+    no_video: "No video",
+    no_screenshots: "No screenshots available",
+    screenshot_failed: "Screenshot failed",
+    screenshots_pruned: "Screenshots removed by the retention policy",
+    broken_attachment: "*(missing attachment: {{name}})*",
+    broken_link: "{{label}} (broken link)",
+    no_timeline: "No timeline available",
+    heuristic_card: " (heuristic)",
+    untitled_segment: "Untitled segment",
+    no_metrics: "No metrics",
+    metrics_lines: "- Segments: {{cards}}\n- Context switches: {{switches}}\n- Average segment: {{avg}} min\n- Fragmentation: {{level}}",
+    fragmentation_levels: ["low", "medium", "high"],
+    footer: "Generated by Screen Analyzer v{{version}} · data as of {{time}}",
+
+    month_index_title: "{{month}} Monthly Index",
+    week_index_title: "{{week}} Weekly Index",
+    weekly_title: "{{week}} Weekly Review",
+    quarter_title: "{{year}} Q{{quarter}} Review",
+    year_title: "{{year}} Year in Review",
+    overview: "Overview",
+    overview_lines: "- Sessions: {{sessions}}\n- Total time: {{minutes}} min\n- Average session: {{avg_session}} min\n- Top categories: {{top_categories}}",
+    rollup_overview_lines: "- Weeks: {{week_count}}\n- Sessions: {{sessions}}\n- Total time: {{minutes}} min\n- Focus ratio: {{focus_ratio}}%\n- Average productivity: {{avg_productivity}}",
+    daily_details: "Daily Breakdown",
+    focus_heatmap: "Focus Heatmap",
+    heatmap_weekdays: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+    heatmap_legend: "Focused (work + learning) time per hour: ⬜ none · 🟨 light · 🟧 moderate · 🟥 deep (max {{max}} min)",
+    heatmap_peak: "- Peak: {{weekday}} {{hour}}:00-{{next_hour}}:00, {{minutes}} min in total",
+    focus: "Focus",
+    weekly_insights: "Highlights",
+    score_notes: "Scoring",
+    score_lines: "- Focus score = focus ratio\n- Effort score: {{target_minutes}} min = 100, capped\n- Productivity = focus score {{focus_weight}}% + effort score {{effort_weight}}%",
+    daily_highlights: "Daily Notes",
+    week_index: "Week Index",
+    month_trend: "Monthly Trend",
+    week_trend: "Weekly Trend",
+    quarter_reviews: "Quarterly Reviews",
+    no_daily_summaries: "No daily summaries",
+    no_insights: "No highlights",
+    none: "None",
+    list_separator: ", ",
+    day_table_header: "| Date | Sessions | Minutes | Top categories |",
+    month_table_header: "| Month | Weeks | Sessions | Minutes | Focus ratio | Avg productivity |",
+    week_table_header: "| Week | Sessions | Minutes | Focus ratio | Productivity |",
+    no_focus_metrics: "No focus data available",
+    focus_metrics_lines: "- Focus time: {{focus_minutes}} min ({{focus_ratio}}%)\n- Communication: {{communication_minutes}} min\n- Distraction: {{distraction_minutes}} min ({{distraction_ratio}}%)\n- Focus score: {{focus_score}} / 100\n- Effort score: {{effort_score}} / 100 (target {{target_minutes}} min)\n- Productivity: {{productivity_score}} / 100 (weights {{focus_weight}}% / {{effort_weight}}%)\n- Breakdown: work {{work}} / learning {{learning}} / personal {{personal}} / idle {{idle}} / other {{other}}",
+    week_comparison: "Week over Week",
+    comparison_table_header: "| Metric | This week | Last week | Change |",
+    comparison_labels: ["Minutes", "Focus ratio", "Productivity"],
+    percentage_points: "{{delta}} pp",
+    no_previous_week: "No activity recorded last week ({{week}})",
+    goal_table_header: "| Goal | Target (min) | Days met | Streak (days) |",
+    daily_trend: "Daily Trend",
+    trend_table_header: "| Metric | 7-day avg | 30-day avg | Direction |",
+    trend_best_day: "- Best day: {{date}}, productivity {{score}} ({{percentile}}th percentile of the last 90 days)",
+    device_focus: "Focus by device",
+    device_focus_line: "- {{name}}: {{focus}} focus min ({{ratio}}%) of {{total}} min, {{sessions}} sessions",
+    wellbeing: "Wellbeing",
+    wellbeing_long_day: "- ⚠️ {{date}}: {{minutes}} min of screen time, over the {{limit}} min limit",
+    wellbeing_late_streak: "- ⚠️ Worked late {{days}} nights in a row ({{start}} to {{end}})",
+    wellbeing_shrinking_breaks: "- ⚠️ Breaks dropped from {{previous}} to {{current}} min per screen hour compared with last week (-{{percent}}%)",
+    insights: WeekInsightStrings {
+        high_focus: "Focus was high this week; keep the current rhythm",
+        low_focus: "Focus was low this week; try to cut down on distractions",
+        medium_focus: "Focus was moderate this week; reducing task switching may help",
+        high_productivity: "Productivity score is high; effort and output are well balanced",
+        low_productivity: "Productivity score is low; watch both time invested and focus ratio",
+        low_effort: "Little time was logged this week; workload may be light",
+        high_effort: "A lot of time was logged this week; watch out for fatigue",
+        short_sessions: "Sessions were short on average, suggesting fragmentation",
+        long_sessions: "Sessions were long on average, suggesting deep work",
+        baseline_above: "{{metric}} {{delta}} above your usual over the last {{weeks}} weeks (z = {{z}})",
+        baseline_below: "{{metric}} {{delta}} below your usual over the last {{weeks}} weeks (z = {{z}})",
+        baseline_typical: "All key metrics are within your usual range over the last {{weeks}} weeks",
+        baseline_points: "{{delta}} points",
+        baseline_minutes: "{{delta}} min",
+        baseline_metrics: ["Focus time", "Focus ratio", "Productivity score", "Total time", "Average session length"],
+    },
+
+    overview_title: "Screen Analyzer Overview",
+    overview_links: "- Today: {{daily_link}}\n- This week: {{week_link}}\n- This week's index: {{week_index_link}}\n- This month's index: {{month_index_link}}",
+    recent_stats: "Recent Activity",
+    recent_table_header: "| Period | Sessions | Minutes | Active days |",
+    last_days: "Last {{days}} days",
+    current_streak: "Current streak: {{days}} days",
+    longest_streak: "Longest streak (30 days): {{days}} days",
+    focus_trend: "Focus Trend",
+    sparkline_note: "last {{weeks}} weeks (newest on the right, · = no data)",
+    latest_line: "Latest: {{focus}}",
+    latest_focus: "{{week}} focus ratio {{ratio}}%",
+    no_focus_data: "No focus data yet",
+    recent_weeks: "Recent Weeks",
+    recent_week_line: "{{link}}: {{sessions}} sessions, {{minutes}} min",
+    no_weekly_notes: "No weekly notes yet",
+
+    project_title: "Project: {{name}}",
+    project_overview_lines: "- Total time: {{minutes}} min\n- Sessions: {{sessions}}\n- Last active: {{last_active}}",
+    project_trend: "Last {{weeks}} weeks",
+    project_week_header: "| Week | Minutes |",
+    project_sessions: "Recent sessions",
+    project_session_line: "{{date}} {{time}} {{link}} ({{minutes}} min)",
+};
+
+/// 按语言获取字符串表
+pub(super) fn strings(language: ExportLanguage) -> &'static NoteStrings {
+    match language {
+        ExportLanguage::Zh => &ZH,
+        ExportLanguage::En => &EN,
+    }
+}
+
+/// 填充 `{{key}}` 占位符
+pub(super) fn fill(template: &str, values: &[(&str, String)]) -> String {
+    super::render_template(Some(template), "", values)
+}
+
+/// 本地化的星期名称
+pub(super) fn weekday_name(language: ExportLanguage, weekday: Weekday) -> &'static str {
+    const ZH_WEEKDAYS: [&str; 7] = [
+        "星期一",
+        "星期二",
+        "星期三",
+        "星期四",
+        "星期五",
+        "星期六",
+        "星期日",
+    ];
+    const EN_WEEKDAYS: [&str; 7] = [
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+        "Sunday",
+    ];
+    let index = weekday.num_days_from_monday() as usize;
+    match language {
+        ExportLanguage::Zh => ZH_WEEKDAYS[index],
+        ExportLanguage::En => EN_WEEKDAYS[index],
+    }
+}
+
+/// 本地化的完整日期（含星期），如"2024年5月12日 星期日" / "Sunday, May 12, 2024"
+pub(super) fn long_date(language: ExportLanguage, date: NaiveDate) -> String {
+    match language {
+        ExportLanguage::Zh => format!(
+            "{}年{}月{}日 {}",
+            date.year(),
+            date.month(),
+            date.day(),
+            weekday_name(language, date.weekday())
+        ),
+        ExportLanguage::En => format!(
+            "{}, {}",
+            weekday_name(language, date.weekday()),
+            date.format("%B %-d, %Y")
+        ),
+    }
+}
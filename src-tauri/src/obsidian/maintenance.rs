@@ -0,0 +1,502 @@
+// Obsidian Vault 维护 - 断链检查与修复、过期截图清理
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+use super::i18n::{fill, NoteStrings};
+use super::{split_frontmatter, ObsidianExporter};
+
+/// 视为截图/附件的扩展名
+const ASSET_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "webp", "avif", "gif"];
+
+/// Vault 断链检查结果
+#[derive(Debug, Default, Serialize)]
+pub struct VaultAuditReport {
+    /// 扫描的笔记数量
+    pub scanned_notes: usize,
+    /// 指向不存在笔记的 [[链接]]
+    pub broken_links: Vec<VaultLinkIssue>,
+    /// 缺失的截图等附件（如保留策略清理了原始截图）
+    pub missing_assets: Vec<VaultLinkIssue>,
+    /// 修复模式下替换为占位文本的链接数
+    pub repaired: usize,
+}
+
+/// 单个失效链接
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultLinkIssue {
+    /// 所在笔记
+    pub note: PathBuf,
+    /// 链接目标（原文）
+    pub target: String,
+}
+
+/// 过期截图清理结果
+#[derive(Debug, Default, Serialize)]
+pub struct AssetPruneOutcome {
+    /// 删除的截图文件数
+    pub removed_assets: usize,
+    /// 移除了截图嵌入的笔记数
+    pub updated_notes: usize,
+}
+
+/// 笔记中的一处链接
+struct NoteLink {
+    /// 链接在笔记中的原文（用于替换）
+    raw: String,
+    target: String,
+    /// 显示文本（别名或 Markdown 链接文字）
+    label: Option<String>,
+    embed: bool,
+    /// Markdown 语法 `[]()`（否则为 wiki 语法 `[[ ]]`）
+    markdown: bool,
+}
+
+impl ObsidianExporter {
+    /// 扫描导出目录下的笔记，找出失效的 [[链接]] 与缺失的附件
+    ///
+    /// `repair` 为 true 时将失效链接替换为占位文本（原始截图已删除时无法重新导出）。
+    pub async fn audit_vault(&self, repair: bool) -> Result<VaultAuditReport> {
+        let root = self.resolve_root()?;
+        let vault_root = PathBuf::from(self.config.vault_path.trim());
+
+        let (notes, file_names) = scan_vault(&vault_root, &root).await?;
+
+        let mut report = VaultAuditReport {
+            scanned_notes: notes.len(),
+            ..Default::default()
+        };
+
+        for note in notes {
+            let Ok(content) = fs::read_to_string(&note).await else {
+                continue;
+            };
+            let note_dir = note.parent().unwrap_or(&root).to_path_buf();
+            let mut repaired_content = content.clone();
+            let mut repaired = 0;
+
+            for link in parse_links(&content) {
+                let candidates = [note_dir.as_path(), root.as_path(), vault_root.as_path()];
+                if resolve_link(&link, &candidates, &file_names).is_some() {
+                    continue;
+                }
+
+                let issue = VaultLinkIssue {
+                    note: note.clone(),
+                    target: link.target.clone(),
+                };
+                if link.embed && has_extension(Path::new(&link.target), &ASSET_EXTENSIONS) {
+                    report.missing_assets.push(issue);
+                } else {
+                    report.broken_links.push(issue);
+                }
+
+                if repair && repaired_content.contains(&link.raw) {
+                    repaired_content =
+                        repaired_content.replace(&link.raw, &placeholder(self.text(), &link));
+                    repaired += 1;
+                }
+            }
+
+            if repaired > 0 {
+                fs::write(&note, repaired_content).await?;
+                report.repaired += repaired;
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+impl ObsidianExporter {
+    /// 清理早于 `older_than` 的会话笔记所复制的截图，并从笔记中移除对应的嵌入
+    ///
+    /// 仅删除 Vault 内的副本；链接模式下指向原始截图的 file:// 链接不受影响。
+    pub async fn prune_assets(&self, older_than: NaiveDate) -> Result<AssetPruneOutcome> {
+        let root = self.resolve_root()?;
+        let vault_root = PathBuf::from(self.config.vault_path.trim());
+        let (notes, file_names) = scan_vault(&vault_root, &root).await?;
+
+        let mut outcome = AssetPruneOutcome::default();
+        for note in notes {
+            let Ok(content) = fs::read_to_string(&note).await else {
+                continue;
+            };
+            let expired = session_note_date(&content).is_some_and(|date| date < older_than);
+            if !expired {
+                continue;
+            }
+
+            let note_dir = note.parent().unwrap_or(&root).to_path_buf();
+            let candidates = [note_dir.as_path(), root.as_path(), vault_root.as_path()];
+            let mut pruned_embeds = HashSet::new();
+            for link in parse_links(&content) {
+                let is_asset = link.embed
+                    && !link.target.starts_with("file://")
+                    && has_extension(Path::new(&link.target), &ASSET_EXTENSIONS);
+                if !is_asset {
+                    continue;
+                }
+                let resolved = resolve_link(&link, &candidates, &file_names)
+                    .filter(|path| path.starts_with(&vault_root));
+                if let Some(path) = resolved {
+                    fs::remove_file(&path).await?;
+                    outcome.removed_assets += 1;
+                }
+                // 文件已被删除的嵌入同样移除
+                pruned_embeds.insert(link.raw);
+            }
+
+            if !pruned_embeds.is_empty() {
+                fs::write(&note, remove_embeds(self.text(), &content, &pruned_embeds)).await?;
+                outcome.updated_notes += 1;
+            }
+        }
+
+        Ok(outcome)
+    }
+}
+
+/// 会话笔记 frontmatter 中的日期（非会话笔记返回 None）
+fn session_note_date(content: &str) -> Option<NaiveDate> {
+    let (frontmatter, _) = split_frontmatter(content);
+    let frontmatter = frontmatter?;
+    if !frontmatter
+        .lines()
+        .any(|line| line.trim() == "type: screen-analyzer-session")
+    {
+        return None;
+    }
+    frontmatter
+        .lines()
+        .find_map(|line| line.strip_prefix("date:"))
+        .and_then(|date| NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok())
+}
+
+/// 移除笔记中的截图嵌入；截图段落因此变空时写入说明
+fn remove_embeds(text: &NoteStrings, content: &str, embeds: &HashSet<String>) -> String {
+    let mut lines = Vec::new();
+    for line in content.lines() {
+        if embeds.contains(line.trim()) {
+            continue;
+        }
+        let mut line = line.to_string();
+        for embed in embeds {
+            line = line.replace(embed.as_str(), "");
+        }
+        lines.push(line);
+    }
+
+    let heading_line = format!("## {}", text.screenshots);
+    if let Some(heading) = lines.iter().position(|line| *line == heading_line) {
+        let section_empty = lines[heading + 1..]
+            .iter()
+            .take_while(|line| !line.starts_with("## ") && !line.starts_with("---"))
+            .all(|line| line.trim().is_empty());
+        if section_empty {
+            lines.insert(heading + 1, format!("- {}", text.screenshots_pruned));
+        }
+    }
+
+    let mut result = lines.join("\n");
+    if content.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// 扫描 Vault：返回导出目录下的笔记，以及 Vault 内全部文件的"文件名 -> 路径"索引
+async fn scan_vault(
+    vault_root: &Path,
+    root: &Path,
+) -> Result<(Vec<PathBuf>, HashMap<String, PathBuf>)> {
+    let vault_files = collect_files(vault_root).await?;
+    // Obsidian 按"最短路径"解析链接，只要文件名在 Vault 内存在即可
+    let file_names = vault_files
+        .iter()
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?.to_lowercase();
+            Some((name, path.clone()))
+        })
+        .collect::<HashMap<_, _>>();
+    let notes = vault_files
+        .into_iter()
+        .filter(|path| path.starts_with(root) && has_extension(path, &["md"]))
+        .collect::<Vec<_>>();
+    Ok((notes, file_names))
+}
+
+/// 递归列出目录下的全部文件（跳过 .obsidian 等隐藏目录）
+async fn collect_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let mut entries = fs::read_dir(&current).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            let hidden = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with('.'));
+            if hidden {
+                continue;
+            }
+            match entry.file_type().await {
+                Ok(file_type) if file_type.is_dir() => pending.push(path),
+                Ok(file_type) if file_type.is_file() => files.push(path),
+                _ => {}
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// 提取笔记中的 wiki 链接、wiki 嵌入与 Markdown 图片
+fn parse_links(content: &str) -> Vec<NoteLink> {
+    let wiki =
+        Regex::new(r"(!?)\[\[([^\]\|#]+)(#[^\]\|]*)?(\|[^\]]*)?\]\]").expect("wiki 链接正则无效");
+    let markdown = Regex::new(r"!\[([^\]]*)\]\(([^)\s]+)\)").expect("Markdown 图片正则无效");
+
+    let mut links = Vec::new();
+    for caps in wiki.captures_iter(content) {
+        // 表格内的别名写作 `[[目标\|别名]]`
+        let target = caps[2].trim().trim_end_matches('\\').trim().to_string();
+        if target.is_empty() {
+            continue;
+        }
+        links.push(NoteLink {
+            raw: caps[0].to_string(),
+            target,
+            label: caps
+                .get(4)
+                .map(|alias| alias.as_str().trim_start_matches('|').trim().to_string())
+                .filter(|alias| !alias.is_empty()),
+            embed: !caps[1].is_empty(),
+            markdown: false,
+        });
+    }
+    for caps in markdown.captures_iter(content) {
+        let target = caps[2].trim().to_string();
+        // 远程图片不检查
+        if target.starts_with("http://") || target.starts_with("https://") {
+            continue;
+        }
+        links.push(NoteLink {
+            raw: caps[0].to_string(),
+            target,
+            label: Some(caps[1].trim().to_string()).filter(|label| !label.is_empty()),
+            embed: true,
+            markdown: true,
+        });
+    }
+    links
+}
+
+/// 将链接解析为实际文件路径，无法解析时返回 None
+fn resolve_link(
+    link: &NoteLink,
+    bases: &[&Path],
+    file_names: &HashMap<String, PathBuf>,
+) -> Option<PathBuf> {
+    if link.target.starts_with("file://") {
+        let path = file_url_to_path(&link.target);
+        return path.exists().then_some(path);
+    }
+
+    let target = link.target.replace("%20", " ");
+    let target = if !link.markdown && Path::new(&target).extension().is_none() {
+        format!("{}.md", target)
+    } else {
+        target
+    };
+
+    let target_path = Path::new(&target);
+    if target_path.is_absolute() && target_path.exists() {
+        return Some(target_path.to_path_buf());
+    }
+    if let Some(path) = bases
+        .iter()
+        .map(|base| base.join(&target))
+        .find(|path| path.exists())
+    {
+        return Some(path);
+    }
+    let name = target_path.file_name()?.to_str()?.to_lowercase();
+    file_names.get(&name).cloned()
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| extensions.contains(&ext.to_lowercase().as_str()))
+}
+
+/// file:// 链接还原为本地路径（与导出时的 `to_file_url` 对应）
+fn file_url_to_path(url: &str) -> PathBuf {
+    let path = url.trim_start_matches("file://").replace("%20", " ");
+    // Windows 路径写作 file:///C:/...
+    let path = match path.strip_prefix('/') {
+        Some(rest) if rest.contains(":/") => rest.to_string(),
+        _ => path,
+    };
+    PathBuf::from(path)
+}
+
+/// 失效链接的占位文本
+fn placeholder(text: &NoteStrings, link: &NoteLink) -> String {
+    let name = Path::new(&link.target)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(&link.target)
+        .to_string();
+    if link.embed {
+        fill(text.broken_attachment, &[("name", name)])
+    } else {
+        fill(
+            text.broken_link,
+            &[("label", link.label.clone().unwrap_or(name))],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AssetLinkStyle, ObsidianExportConfig};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_audit_vault_reports_and_repairs_broken_links() {
+        let temp_dir = tempdir().unwrap();
+        let vault = temp_dir.path().join("vault");
+        let root = vault.join("ScreenAnalyzer");
+        std::fs::create_dir_all(root.join("Daily")).unwrap();
+        std::fs::create_dir_all(root.join("Assets/2024-05-12")).unwrap();
+        std::fs::create_dir_all(vault.join(".obsidian")).unwrap();
+        std::fs::write(root.join("Assets/2024-05-12/session-1-0.jpg"), b"jpg").unwrap();
+        std::fs::write(root.join("Daily/2024-05-11.md"), "# 昨天").unwrap();
+
+        let note = root.join("Daily/2024-05-12.md");
+        std::fs::write(
+            &note,
+            "- [[Daily/2024-05-11]]\n\
+- [[Daily/2024-05-10|周五]]\n\
+![](Assets/2024-05-12/session-1-0.jpg)\n\
+![](Assets/2024-05-12/session-1-1.jpg)\n\
+![[session-1-2.webp]]\n\
+![](file:///definitely/missing/frame.jpg)\n",
+        )
+        .unwrap();
+
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            vault_path: vault.to_string_lossy().to_string(),
+            ..ObsidianExportConfig::default()
+        });
+
+        let report = exporter.audit_vault(false).await.unwrap();
+        assert_eq!(report.scanned_notes, 2);
+        assert_eq!(
+            report
+                .broken_links
+                .iter()
+                .map(|issue| issue.target.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Daily/2024-05-10"]
+        );
+        assert_eq!(report.missing_assets.len(), 3);
+        assert_eq!(report.repaired, 0);
+
+        let report = exporter.audit_vault(true).await.unwrap();
+        assert_eq!(report.repaired, 4);
+        let repaired = std::fs::read_to_string(&note).unwrap();
+        assert_eq!(
+            repaired,
+            "- [[Daily/2024-05-11]]\n\
+- 周五（链接已失效）\n\
+![](Assets/2024-05-12/session-1-0.jpg)\n\
+*（附件已失效: session-1-1.jpg）*\n\
+*（附件已失效: session-1-2.webp）*\n\
+*（附件已失效: frame.jpg）*\n"
+        );
+
+        let report = exporter.audit_vault(false).await.unwrap();
+        assert!(report.broken_links.is_empty() && report.missing_assets.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_prune_assets_removes_expired_copies() {
+        let temp_dir = tempdir().unwrap();
+        let vault = temp_dir.path().join("vault");
+        let root = vault.join("ScreenAnalyzer");
+        std::fs::create_dir_all(root.join("Sessions")).unwrap();
+        std::fs::create_dir_all(root.join("Assets")).unwrap();
+        for name in ["session-1-0.jpg", "session-1-1.jpg", "session-2-0.jpg"] {
+            std::fs::write(root.join("Assets").join(name), b"jpg").unwrap();
+        }
+
+        let session_note = |date: &str, screenshots: &str| {
+            format!(
+                "---\ntype: screen-analyzer-session\ndate: {}\n---\n\n# 会话\n\n## 截图\n{}\n",
+                date, screenshots
+            )
+        };
+        let old_note = root.join("Sessions/old.md");
+        let new_note = root.join("Sessions/new.md");
+        std::fs::write(
+            &old_note,
+            session_note(
+                "2024-04-01",
+                "![](Assets/session-1-0.jpg)\n![[session-1-1.jpg]]\n![](file:///frames/a.jpg)",
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            &new_note,
+            session_note("2024-05-12", "![[session-2-0.jpg]]"),
+        )
+        .unwrap();
+
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            vault_path: vault.to_string_lossy().to_string(),
+            asset_link_style: AssetLinkStyle::Wiki,
+            ..ObsidianExportConfig::default()
+        });
+        let outcome = exporter
+            .prune_assets(NaiveDate::from_ymd_opt(2024, 5, 1).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.removed_assets, 2);
+        assert_eq!(outcome.updated_notes, 1);
+        assert!(!root.join("Assets/session-1-0.jpg").exists());
+        assert!(!root.join("Assets/session-1-1.jpg").exists());
+        assert!(root.join("Assets/session-2-0.jpg").exists());
+        assert_eq!(
+            std::fs::read_to_string(&old_note).unwrap(),
+            session_note("2024-04-01", "![](file:///frames/a.jpg)")
+        );
+
+        // 全部截图被清理后写入说明
+        std::fs::write(
+            &old_note,
+            session_note("2024-04-01", "![[session-9-0.jpg]]"),
+        )
+        .unwrap();
+        exporter
+            .prune_assets(NaiveDate::from_ymd_opt(2024, 5, 1).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&old_note).unwrap(),
+            session_note("2024-04-01", "- 截图已按保留策略清理")
+        );
+    }
+}
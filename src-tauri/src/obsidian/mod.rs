@@ -1,32 +1,255 @@
 // Obsidian 导出模块 - 生成 Markdown 文件
 
+mod archive;
+mod history;
+mod i18n;
+mod maintenance;
+
+pub use crate::exporter::NotePreview;
+pub use history::{rerun_request, ExportRequest, ExportRun};
+pub use maintenance::{AssetPruneOutcome, VaultAuditReport, VaultLinkIssue};
+
 use anyhow::{anyhow, Result};
-use chrono::{DateTime, Datelike, NaiveDate, Utc, Weekday};
+use async_trait::async_trait;
+use chrono::{
+    DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday,
+};
 use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use tokio::fs;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
 
 use crate::actors::LLMHandle;
-use crate::domains::summary::SummaryGenerator;
+use crate::custom_metrics::format_value;
+use crate::domains::summary::{
+    device_focus_from_cards, focus_metrics_from_cards, match_session_project,
+    normalize_timeline_category, parse_card_minutes, summarize_projects, DeviceFocusStat,
+    ProjectSession, ProjectSummary,
+};
+use crate::exporter::{day_summary, range_days, ExportReport, Exporter};
+use crate::frame_crypto;
+use crate::frame_store::{FrameStore, LocalFrameStore};
+use crate::goals::{comparison_symbol, GoalStatus, GoalWeekStatus};
+use crate::heatmap::FocusHeatmap;
+use crate::issue_links::render_issue_link;
 use crate::llm::plugin::ActivityCategory;
-use crate::models::{ActivityTag, ObsidianExportConfig, ObsidianExportMode};
-use crate::storage::{Database, Frame, Session, TimelineCardRecord};
+use crate::models::{
+    ActivityTag, AssetFormat, AssetLinkStyle, DailyNoteUpdateMode, ExportConflictPolicy,
+    ObsidianExportConfig, ObsidianExportMode, ObsidianExportTarget, ScreenshotStrategy,
+    SessionGrouping, WellbeingConfig,
+};
+use crate::storage::{
+    Activity, AppUsageRecord, CalendarEventRecord, DailyFocusMetricsRecord, Database,
+    FocusBlockRecord, Frame, Session, SessionNoteRecord, TimelineCardRecord,
+};
+use crate::trends::{ScoreWeights, TrendSeries};
+use crate::wellbeing::{WellbeingReport, WellbeingWarning};
+use i18n::{fill, NoteStrings};
+
+/// 每日笔记中生成内容的起止标记（合并模式下仅替换标记之间的内容）
+const MANAGED_START: &str = "<!-- screen-analyzer:start -->";
+const MANAGED_END: &str = "<!-- screen-analyzer:end -->";
+/// 页脚分隔（水平线 + 引用行），合并时用于把页脚放到标记块之外
+const FOOTER_SEPARATOR: &str = "\n---\n> ";
+
+/// HighestActivity 策略选取的截图数量
+const HIGHEST_ACTIVITY_COUNT: usize = 3;
+
+/// 总览仪表盘统计的周数（含本周），同时决定"最近周报"与专注度趋势的长度
+const OVERVIEW_WEEKS: i64 = 8;
+
+/// 项目笔记的趋势周数与"最近会话"条数
+const PROJECT_TREND_WEEKS: usize = 8;
+const PROJECT_RECENT_SESSIONS: usize = 10;
+
+/// 周画布中展示的会话笔记数量（按时长取前 N 个）
+const CANVAS_TOP_SESSIONS: usize = 8;
+/// 周画布节点尺寸与间距
+const CANVAS_NODE_WIDTH: i64 = 360;
+const CANVAS_NODE_HEIGHT: i64 = 240;
+const CANVAS_GAP: i64 = 80;
 
 /// Obsidian 导出器
 pub struct ObsidianExporter {
     config: ObsidianExportConfig,
+    /// 增量同步时因内容未变化而跳过写入的文件数
+    skipped_files: AtomicUsize,
+    /// 按冲突策略处理的文件计数
+    conflicts: Mutex<ConflictCounts>,
+    /// 预览模式：记录待写入的笔记而不落盘
+    dry_run: Option<Mutex<Vec<NotePreview>>>,
+    /// 截图存储后端（解析已转存截图的本机路径与外链地址）
+    frame_store: Arc<dyn FrameStore>,
 }
 
 /// 导出结果摘要
 pub struct ExportOutcome {
+    /// 每日总结路径（仅导出会话笔记时为空）
     pub daily_note_path: PathBuf,
     pub session_paths: Vec<PathBuf>,
     pub index_note_path: Option<PathBuf>,
     pub week_index_path: Option<PathBuf>,
     pub weekly_note_path: Option<PathBuf>,
     pub overview_path: Option<PathBuf>,
+    /// 项目汇总笔记（Projects/<项目>.md）
+    pub project_paths: Vec<PathBuf>,
     pub warnings: Vec<String>,
+    pub failed_sessions: Vec<SessionExportError>,
+    /// 本次导出涉及的日期（批量导出时包含多天）
+    pub exported_dates: Vec<String>,
+    /// 增量同步时内容未变化而跳过的文件数
+    pub skipped_files: usize,
+    /// 导出为 zip 时的压缩包路径（此时其余路径为压缩包内的相对路径）
+    pub archive_path: Option<PathBuf>,
+    /// 已存在且内容不同的文件按冲突策略处理的计数
+    pub conflicts: ConflictCounts,
+}
+
+/// 冲突处理计数（仅统计已存在且内容将发生变化的文件）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConflictCounts {
+    pub skipped: usize,
+    pub overwritten: usize,
+    pub merged: usize,
+    pub versioned: usize,
+}
+
+impl ConflictCounts {
+    fn total(&self) -> usize {
+        self.skipped + self.overwritten + self.merged + self.versioned
+    }
+
+    fn add(&mut self, other: ConflictCounts) {
+        self.skipped += other.skipped;
+        self.overwritten += other.overwritten;
+        self.merged += other.merged;
+        self.versioned += other.versioned;
+    }
+
+    /// 相对 `before` 的增量
+    fn since(&self, before: ConflictCounts) -> ConflictCounts {
+        ConflictCounts {
+            skipped: self.skipped - before.skipped,
+            overwritten: self.overwritten - before.overwritten,
+            merged: self.merged - before.merged,
+            versioned: self.versioned - before.versioned,
+        }
+    }
+}
+
+/// 截图存放位置
+struct AssetLocation {
+    dir: PathBuf,
+    /// Markdown 链接使用的目录前缀
+    link_prefix: String,
+}
+
+/// 每日笔记中的会话索引条目
+struct SessionEntry {
+    link: String,
+    /// 分组名（按配置提取的项目或类别）
+    group: Option<String>,
+}
+
+/// 单个会话导出失败的结构化信息（用于前端"重试失败"）
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionExportError {
+    pub session_id: i64,
+    pub message: String,
+}
+
+/// 导出过程事件（可逐行序列化为 NDJSON，供前端或外部工具消费）
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ExportEvent {
+    /// 开始导出
+    Started { date: String, session_count: usize },
+    /// 单个会话导出完成
+    SessionExported { session_id: i64, path: PathBuf },
+    /// 索引/汇总文件写入完成（kind: daily/month_index/week_index/weekly_note/overview）
+    IndexWritten { kind: String, path: PathBuf },
+    /// 单个会话导出失败
+    SessionFailed { session_id: i64, message: String },
+    /// 警告（包括会话导出失败）
+    Warning { message: String },
+    /// 开始批量导出日期范围（total_sessions 为范围内全部会话数）
+    RangeStarted {
+        start_date: String,
+        end_date: String,
+        total_sessions: usize,
+    },
+    /// 导出结束
+    Finished {
+        exported_sessions: usize,
+        failed_sessions: usize,
+        warnings: usize,
+    },
+}
+
+impl ExportEvent {
+    /// 序列化为一行 NDJSON（含结尾换行）
+    pub fn to_ndjson(&self) -> String {
+        let mut line = serde_json::to_string(self).unwrap_or_default();
+        line.push('\n');
+        line
+    }
+}
+
+/// 导出进度快照（随每个导出事件推送给前端，用于显示进度条）
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportProgress {
+    /// 当前正在导出的日期
+    pub current_date: Option<String>,
+    /// 已处理（成功或失败）的会话数
+    pub completed: usize,
+    /// 需要处理的会话总数
+    pub total: usize,
+    pub event: ExportEvent,
+}
+
+/// 根据导出事件流累计进度
+#[derive(Debug, Default)]
+pub struct ExportProgressTracker {
+    current_date: Option<String>,
+    completed: usize,
+    total: usize,
+    in_range: bool,
+}
+
+impl ExportProgressTracker {
+    /// 记录一个事件并返回最新进度
+    pub fn observe(&mut self, event: ExportEvent) -> ExportProgress {
+        match &event {
+            ExportEvent::RangeStarted { total_sessions, .. } => {
+                self.in_range = true;
+                self.total = *total_sessions;
+            }
+            ExportEvent::Started {
+                date,
+                session_count,
+            } => {
+                self.current_date = Some(date.clone());
+                if !self.in_range {
+                    self.total = *session_count;
+                }
+            }
+            ExportEvent::SessionExported { .. } | ExportEvent::SessionFailed { .. } => {
+                self.completed += 1;
+                // 范围导出时以实际处理数为准，避免预估总数偏小
+                self.total = self.total.max(self.completed);
+            }
+            _ => {}
+        }
+        ExportProgress {
+            current_date: self.current_date.clone(),
+            completed: self.completed,
+            total: self.total,
+            event,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -48,16 +271,86 @@ pub struct WeekSummaryPreview {
     pub focus_weight: i64,
     pub effort_weight: i64,
     pub target_minutes: i64,
+    /// 与上周对比（上周没有记录时为空）
+    pub comparison: Option<WeekComparisonPreview>,
+}
+
+/// 周报预览中的上周对比数据
+#[derive(Debug, Serialize)]
+pub struct WeekComparisonPreview {
+    pub previous_week_label: String,
+    pub previous_total_minutes: i32,
+    pub previous_focus_ratio: i64,
+    pub previous_productivity_score: i64,
+    pub total_minutes_delta: i64,
+    /// 总时长变化百分比（上周时长为 0 时为空）
+    pub total_minutes_change_percent: Option<i64>,
+    pub focus_ratio_delta: i64,
+    pub productivity_score_delta: i64,
 }
 
 impl ExportOutcome {
+    /// 转换为各导出目标通用的导出结果
+    fn into_report(self) -> ExportReport {
+        let written_paths = self.written_paths();
+        let note_paths = match &self.archive_path {
+            Some(path) => vec![path.clone()],
+            None if self.exported_dates.len() <= 1
+                && !self.daily_note_path.as_os_str().is_empty() =>
+            {
+                vec![self.daily_note_path.clone()]
+            }
+            None => Vec::new(),
+        };
+        ExportReport {
+            target: "Obsidian".to_string(),
+            exported_dates: self.exported_dates,
+            note_paths,
+            session_count: self.session_paths.len(),
+            failed_count: 0,
+            written_paths,
+            warnings: self.warnings,
+        }
+    }
+
+    /// 本次导出写入的全部文件路径（导出为 zip 时首项为压缩包）
+    pub fn written_paths(&self) -> Vec<PathBuf> {
+        self.archive_path
+            .iter()
+            .chain(Some(&self.daily_note_path).filter(|path| !path.as_os_str().is_empty()))
+            .chain(&self.session_paths)
+            .chain(&self.index_note_path)
+            .chain(&self.week_index_path)
+            .chain(&self.weekly_note_path)
+            .chain(&self.overview_path)
+            .chain(&self.project_paths)
+            .cloned()
+            .collect()
+    }
+
     /// 渲染提示信息
     pub fn render_message(&self) -> String {
-        let mut message = format!(
-            "已导出每日总结: {}\n会话数量: {}",
-            self.daily_note_path.to_string_lossy(),
-            self.session_paths.len()
-        );
+        let mut message = if let [first, .., last] = self.exported_dates.as_slice() {
+            format!(
+                "已批量导出 {} 天: {} ~ {}\n会话数量: {}",
+                self.exported_dates.len(),
+                first,
+                last,
+                self.session_paths.len()
+            )
+        } else if self.daily_note_path.as_os_str().is_empty() {
+            format!("已导出会话笔记: {} 个", self.session_paths.len())
+        } else {
+            format!(
+                "已导出每日总结: {}\n会话数量: {}",
+                self.daily_note_path.to_string_lossy(),
+                self.session_paths.len()
+            )
+        };
+        if let Some(path) = &self.archive_path {
+            message.push_str("\n压缩包: ");
+            message.push_str(&path.to_string_lossy());
+        }
         if let Some(path) = &self.index_note_path {
             message.push_str("\n索引文件: ");
             message.push_str(&path.to_string_lossy());
@@ -74,6 +367,31 @@ impl ExportOutcome {
             message.push_str("\n总览文件: ");
             message.push_str(&path.to_string_lossy());
         }
+        if !self.project_paths.is_empty() {
+            message.push_str(&format!("\n项目笔记: {} 个", self.project_paths.len()));
+        }
+        if self.skipped_files > 0 {
+            message.push_str(&format!("\n未变化跳过: {} 个文件", self.skipped_files));
+        }
+        if self.conflicts.total() > 0 {
+            message.push_str(&format!(
+                "\n冲突处理: 跳过 {} / 覆盖 {} / 合并 {} / 新版本 {}",
+                self.conflicts.skipped,
+                self.conflicts.overwritten,
+                self.conflicts.merged,
+                self.conflicts.versioned
+            ));
+        }
+        if !self.failed_sessions.is_empty() {
+            let ids = self
+                .failed_sessions
+                .iter()
+                .map(|failure| failure.session_id.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            message.push_str("\n失败会话: ");
+            message.push_str(&ids);
+        }
         if !self.warnings.is_empty() {
             message.push_str("\n\n警告:\n");
             for warning in &self.warnings {
@@ -86,20 +404,196 @@ impl ExportOutcome {
     }
 }
 
-impl ObsidianExporter {
-    /// 创建新的导出器
-    pub fn new(config: ObsidianExportConfig) -> Self {
-        Self { config }
+#[async_trait]
+impl Exporter for ObsidianExporter {
+    fn target_name(&self) -> &'static str {
+        "Obsidian"
     }
 
-    /// 导出指定日期的数据
-    pub async fn export_day(
+    /// 按导出目标写入 Vault 或打包为 zip
+    async fn export_day(
         &self,
         db: Arc<Database>,
         llm_handle: LLMHandle,
         date: &str,
         force_refresh: bool,
-    ) -> Result<ExportOutcome> {
+    ) -> Result<ExportReport> {
+        let outcome = if self.config.export_target == ObsidianExportTarget::Zip {
+            let (tx, _rx) = unbounded_channel();
+            self.export_to_archive(db, llm_handle, date, date, force_refresh, tx)
+                .await?
+        } else {
+            ObsidianExporter::export_day(self, db, llm_handle, date, force_refresh).await?
+        };
+        Ok(outcome.into_report())
+    }
+
+    async fn export_range(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<ExportReport> {
+        let outcome = if self.config.export_target == ObsidianExportTarget::Zip {
+            let (tx, _rx) = unbounded_channel();
+            self.export_to_archive(db, llm_handle, start_date, end_date, false, tx)
+                .await?
+        } else {
+            ObsidianExporter::export_range(self, db, llm_handle, start_date, end_date).await?
+        };
+        Ok(outcome.into_report())
+    }
+
+    async fn preview_day(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        date: &str,
+    ) -> Result<Vec<NotePreview>> {
+        ObsidianExporter::preview_day(self, db, llm_handle, date).await
+    }
+}
+
+impl ObsidianExporter {
+    /// 创建新的导出器
+    pub fn new(config: ObsidianExportConfig) -> Self {
+        Self {
+            config,
+            skipped_files: AtomicUsize::new(0),
+            conflicts: Mutex::new(ConflictCounts::default()),
+            dry_run: None,
+            frame_store: Arc::new(LocalFrameStore),
+        }
+    }
+
+    /// 指定截图存储后端（默认读取本机文件）
+    pub fn with_frame_store(mut self, frame_store: Arc<dyn FrameStore>) -> Self {
+        self.frame_store = frame_store;
+        self
+    }
+
+    /// 当前导出语言的字符串表
+    fn text(&self) -> &'static NoteStrings {
+        i18n::strings(self.config.export_language)
+    }
+
+    /// 按配置的导出时区换算时间
+    fn clock(&self) -> ExportClock {
+        ExportClock {
+            offset: self
+                .config
+                .timezone_offset_minutes
+                .and_then(|minutes| FixedOffset::east_opt(minutes.saturating_mul(60))),
+        }
+    }
+
+    /// 配置的设备过滤（去除首尾空白，为空时不过滤）
+    fn device_filter(&self) -> Option<&str> {
+        self.config
+            .device_filter
+            .as_deref()
+            .map(str::trim)
+            .filter(|device| !device.is_empty())
+    }
+
+    /// 会话是否属于过滤的设备（未设置过滤时总是 true）
+    fn includes_device(&self, session: &Session) -> bool {
+        self.device_filter()
+            .is_none_or(|device| session.device_name.as_deref() == Some(device))
+    }
+
+    /// 日期范围内的每日活动统计（设置了设备过滤时只统计该设备）
+    async fn activities(
+        &self,
+        db: &Database,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<Activity>> {
+        let activities = match self.device_filter() {
+            Some(device) => db.get_device_activities(start_date, end_date, device).await,
+            None => db.get_activities(start_date, end_date).await,
+        };
+        activities.map_err(|e| anyhow!(e))
+    }
+
+    /// 会话在导出时区下所属的日期
+    fn session_date(&self, session: &Session) -> String {
+        self.clock()
+            .session_time(session.start_time)
+            .format("%Y-%m-%d")
+            .to_string()
+    }
+
+    /// 读取导出时区下某一天的会话（设置了设备过滤时只保留该设备的会话）
+    ///
+    /// 数据库按本地时间值的日期查询；配置了其他时区时，前后各多取一天再按换算后的日期过滤，
+    /// 使跨零点的会话归入正确的日期（以及每日/每周边界）。
+    async fn sessions_on(&self, db: &Database, date: &str) -> Result<Vec<Session>> {
+        let clock = self.clock();
+        if clock.offset.is_none() {
+            let mut sessions = db
+                .get_sessions_by_date(date)
+                .await
+                .map_err(|e| anyhow!(e))?;
+            sessions.retain(|session| self.includes_device(session));
+            return Ok(sessions);
+        }
+        let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| anyhow!("日期格式错误: {}", date))?;
+
+        let mut sessions = Vec::new();
+        for offset in -1..=1 {
+            let stored_date = (day + chrono::Duration::days(offset))
+                .format("%Y-%m-%d")
+                .to_string();
+            sessions.extend(
+                db.get_sessions_by_date(&stored_date)
+                    .await
+                    .map_err(|e| anyhow!(e))?
+                    .into_iter()
+                    .filter(|session| clock.session_time(session.start_time).date() == day)
+                    .filter(|session| self.includes_device(session)),
+            );
+        }
+        sessions.sort_by_key(|session| session.start_time);
+        Ok(sessions)
+    }
+
+    /// 日期相关占位符：date / long_date / weekday（日期无法解析时后两者退化为原文/空）
+    fn date_values(&self, date: &str) -> Vec<(&'static str, String)> {
+        let language = self.config.export_language;
+        let parsed = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+        vec![
+            ("date", date.to_string()),
+            (
+                "long_date",
+                parsed
+                    .map(|day| i18n::long_date(language, day))
+                    .unwrap_or_else(|| date.to_string()),
+            ),
+            (
+                "weekday",
+                parsed
+                    .map(|day| i18n::weekday_name(language, day.weekday()).to_string())
+                    .unwrap_or_default(),
+            ),
+        ]
+    }
+
+    /// 检查导出目标配置是否完整（Vault 路径或压缩包路径）
+    pub fn check_target(&self) -> Result<()> {
+        if self.config.export_target == ObsidianExportTarget::Zip {
+            self.archive_path().map(|_| ())
+        } else if self.config.vault_path.trim().is_empty() {
+            Err(anyhow!("请先配置 Obsidian Vault 路径"))
+        } else {
+            self.resolve_root().map(|_| ())
+        }
+    }
+
+    /// 解析导出根目录（Vault + 根目录名）
+    pub fn resolve_root(&self) -> Result<PathBuf> {
         let vault_root = PathBuf::from(self.config.vault_path.trim());
         if vault_root.as_os_str().is_empty() {
             return Err(anyhow!("未配置 Obsidian Vault 路径"));
@@ -108,105 +602,380 @@ impl ObsidianExporter {
             return Err(anyhow!("Obsidian Vault 路径不存在"));
         }
 
-        let root = if self.config.root_folder.trim().is_empty() {
+        Ok(if self.config.root_folder.trim().is_empty() {
             vault_root
         } else {
             vault_root.join(self.config.root_folder.trim())
-        };
+        })
+    }
+
+    /// 每日笔记文件路径
+    pub fn daily_note_path(&self, root: &Path, date: &str) -> PathBuf {
+        root.join(format!("{}.md", self.daily_note_rel(date)))
+    }
 
-        let daily_dir = root.join("Daily");
-        let sessions_dir = root.join("Sessions").join(date);
-        let assets_dir = root.join("Assets").join(date);
+    /// 周报文件路径
+    pub fn weekly_note_path(&self, root: &Path, week_label: &str, week_start: &str) -> PathBuf {
+        root.join(format!(
+            "{}.md",
+            self.weekly_note_rel(week_label, week_start)
+        ))
+    }
 
-        fs::create_dir_all(&daily_dir).await?;
-        fs::create_dir_all(&sessions_dir).await?;
-        if self.config.include_screenshots {
-            fs::create_dir_all(&assets_dir).await?;
+    /// 周画布文件路径（与周报同目录同名，扩展名为 .canvas）
+    pub fn week_canvas_path(&self, root: &Path, week_label: &str, week_start: &str) -> PathBuf {
+        root.join(format!(
+            "{}.canvas",
+            self.weekly_note_rel(week_label, week_start)
+        ))
+    }
+
+    /// 索引目录下的文件路径
+    pub fn index_path(&self, root: &Path, file_name: &str) -> PathBuf {
+        root.join(self.index_rel(file_name))
+    }
+
+    /// 每日笔记相对路径（不含 .md），同时用作 wiki 链接
+    fn daily_note_rel(&self, date: &str) -> String {
+        let rel = render_path_template(
+            &self.config.daily_path_template,
+            "Daily/{{date}}",
+            date,
+            None,
+        );
+        // 配置了文件名格式时，替换模板的最后一段（格式中的 `/` 视为子目录，与插件行为一致）
+        match format_daily_filename(&self.config.daily_filename_format, date) {
+            Some(name) => match rel.rsplit_once('/') {
+                Some((dir, _)) => format!("{}/{}", dir, name),
+                None => name,
+            },
+            None => rel,
         }
+    }
 
-        let summary_generator = SummaryGenerator::with_llm(db.clone(), llm_handle);
-        let day_summary = summary_generator
-            .generate_day_summary(date, force_refresh)
-            .await
-            .map_err(|e| anyhow!(e))?;
+    fn sessions_rel(&self, date: &str) -> String {
+        render_path_template(
+            &self.config.session_path_template,
+            "Sessions/{{date}}",
+            date,
+            None,
+        )
+    }
+
+    fn assets_rel(&self, date: &str) -> String {
+        render_path_template(
+            &self.config.assets_path_template,
+            "Assets/{{date}}",
+            date,
+            None,
+        )
+    }
+
+    /// 截图存放目录：默认按资源模板，启用后使用 Vault 的附件目录设置
+    fn asset_location(&self, root: &Path, date: &str) -> AssetLocation {
+        let assets_rel = self.assets_rel(date);
+        if !self.config.use_vault_attachment_folder {
+            return AssetLocation {
+                dir: root.join(&assets_rel),
+                link_prefix: assets_rel,
+            };
+        }
+
+        let vault_root = PathBuf::from(self.config.vault_path.trim());
+        let note_dir = root.join(self.sessions_rel(date));
+        let dir = resolve_attachment_folder(&vault_root, &note_dir);
+        let link_prefix = dir
+            .strip_prefix(&vault_root)
+            .map(|relative| {
+                relative
+                    .components()
+                    .map(|part| part.as_os_str().to_string_lossy().to_string())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .unwrap_or(assets_rel);
+        AssetLocation { dir, link_prefix }
+    }
+
+    fn weekly_note_rel(&self, week_label: &str, week_start: &str) -> String {
+        render_path_template(
+            &self.config.weekly_path_template,
+            "Weekly/{{week}}",
+            week_start,
+            Some(week_label),
+        )
+    }
+
+    /// 相对根目录的路径转换为相对 Vault 的路径（Canvas 的 file 节点要求完整路径）
+    fn vault_rel(&self, rel: &str) -> String {
+        let root_folder = self.config.root_folder.trim().trim_matches('/');
+        if root_folder.is_empty() {
+            rel.to_string()
+        } else {
+            format!("{}/{}", root_folder, rel)
+        }
+    }
+
+    fn index_rel(&self, file_name: &str) -> String {
+        let folder = render_path_template(&self.config.index_folder, "Index", "", None);
+        format!("{}/{}", folder, file_name)
+    }
+
+    fn project_rel(&self, project: &str) -> String {
+        let folder = render_path_template(&self.config.projects_folder, "Projects", "", None);
+        format!("{}/{}.md", folder, sanitize_filename(project.trim()))
+    }
+
+    /// 导出指定日期的数据
+    pub async fn export_day(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        date: &str,
+        force_refresh: bool,
+    ) -> Result<ExportOutcome> {
+        let (tx, mut rx) = unbounded_channel();
+        let outcome = self
+            .export_day_with_events(db, llm_handle, date, force_refresh, tx)
+            .await;
+        // 丢弃过程事件，仅返回结果
+        while rx.try_recv().is_ok() {}
+        outcome
+    }
 
-        let sessions = db
-            .get_sessions_by_date(date)
+    /// 导出指定日期的数据，并通过通道逐步发送导出事件
+    pub async fn export_day_with_events(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        date: &str,
+        force_refresh: bool,
+        tx: UnboundedSender<ExportEvent>,
+    ) -> Result<ExportOutcome> {
+        self.export_day_notes(db, llm_handle, date, force_refresh, tx, true)
             .await
-            .map_err(|e| anyhow!(e))?;
+    }
+
+    /// 单日导出；`include_projects` 为 false 时不生成项目笔记（批量导出仅在最后一天生成）
+    async fn export_day_notes(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        date: &str,
+        force_refresh: bool,
+        tx: UnboundedSender<ExportEvent>,
+        include_projects: bool,
+    ) -> Result<ExportOutcome> {
+        let root = self.resolve_root()?;
+        let skipped_before = self.skipped_files.load(Ordering::Relaxed);
+        let conflicts_before = self.conflict_counts();
+
+        let daily_note_path = self.daily_note_path(&root, date);
+        let sessions_dir = root.join(self.sessions_rel(date));
+        let assets = self.asset_location(&root, date);
+
+        if let Some(daily_dir) = daily_note_path.parent() {
+            self.ensure_dir(daily_dir).await?;
+        }
+        self.ensure_dir(&sessions_dir).await?;
+        if self.config.include_screenshots {
+            self.ensure_dir(&assets.dir).await?;
+        }
+
+        let mut day_summary = day_summary(db.clone(), llm_handle, date, force_refresh).await?;
+        if let Some(device) = self.device_filter() {
+            day_summary.device_stats.retain(|stat| stat.name == device);
+        }
+
+        let sessions = self.sessions_on(db.as_ref(), date).await?;
+        let _ = tx.send(ExportEvent::Started {
+            date: date.to_string(),
+            session_count: sessions.len(),
+        });
+
+        let day_cards = if self.wants_day_cards() {
+            collect_day_cards(db.as_ref(), &sessions).await
+        } else {
+            Vec::new()
+        };
+        let day_apps = if self.wants_app_breakdown() {
+            collect_day_apps(db.as_ref(), &sessions).await
+        } else {
+            Vec::new()
+        };
 
         let mut warnings = Vec::new();
         let mut session_paths = Vec::new();
-        let mut session_links = Vec::new();
+        let mut session_entries = Vec::new();
+        let mut failed_sessions = Vec::new();
 
         for session in sessions {
             match self
-                .export_session(&db, &session, &sessions_dir, &assets_dir)
+                .export_session(&db, &session, &sessions_dir, &assets)
                 .await
             {
                 Ok((session_path, link)) => {
+                    let _ = tx.send(ExportEvent::SessionExported {
+                        session_id: session.id.unwrap_or(0),
+                        path: session_path.clone(),
+                    });
                     session_paths.push(session_path);
-                    session_links.push(link);
+                    session_entries.push(SessionEntry {
+                        link,
+                        group: self.session_group(&session),
+                    });
                 }
                 Err(e) => {
-                    warnings.push(format!("会话 {} 导出失败: {}", session.id.unwrap_or(0), e));
+                    let session_id = session.id.unwrap_or(0);
+                    let _ = tx.send(ExportEvent::SessionFailed {
+                        session_id,
+                        message: e.to_string(),
+                    });
+                    push_warning(
+                        &mut warnings,
+                        &tx,
+                        format!("会话 {} 导出失败: {}", session_id, e),
+                    );
+                    failed_sessions.push(SessionExportError {
+                        session_id,
+                        message: e.to_string(),
+                    });
                 }
             }
         }
 
-        let daily_note_path = daily_dir.join(format!("{}.md", sanitize_filename(date)));
-        let daily_content = self.render_daily_note(&day_summary, &session_links);
-        fs::write(&daily_note_path, daily_content).await?;
+        let meetings = db
+            .get_calendar_events_by_date(date)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("读取日历会议失败: {}", e);
+                Vec::new()
+            });
+        let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| anyhow!("日期格式错误: {}", date))?;
+        let goals = crate::goals::day_goals(db.as_ref(), day)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("评估目标失败: {}", e);
+                Vec::new()
+            });
+        let daily_content = self.render_daily_note(
+            &day_summary,
+            &session_entries,
+            &day_cards,
+            &day_apps,
+            &meetings,
+            &goals,
+        );
+        let daily_policy = if self.config.daily_note_update_mode == DailyNoteUpdateMode::Merge {
+            ExportConflictPolicy::Merge
+        } else {
+            self.config.conflict_policy
+        };
+        let daily_note_path = self
+            .write_note_with_policy(&daily_note_path, daily_content, daily_policy)
+            .await?;
+        emit_written(&tx, "daily", &daily_note_path);
 
         let index_note_path = match self.export_month_index(db.as_ref(), date, &root).await {
-            Ok(path) => Some(path),
+            Ok(path) => {
+                emit_written(&tx, "month_index", &path);
+                Some(path)
+            }
             Err(err) => {
-                warnings.push(format!("索引生成失败: {}", err));
+                push_warning(&mut warnings, &tx, format!("索引生成失败: {}", err));
                 None
             }
         };
 
         let mut week_summary: Option<WeekSummaryData> = None;
-        let (week_index_path, weekly_note_path) =
-            match self.build_week_summary(db.as_ref(), date, &self.config).await {
-                Ok(summary) => {
-                    week_summary = Some(summary);
-                    let summary_ref = week_summary.as_ref().expect("周报摘要缺失");
-                    let index_path =
-                        match self.export_week_index_with_summary(summary_ref, &root).await {
-                            Ok(path) => Some(path),
-                            Err(err) => {
-                                warnings.push(format!("周索引生成失败: {}", err));
-                                None
-                            }
-                        };
-                    let weekly_note_path =
-                        match self.export_weekly_note_with_summary(summary_ref, &root).await {
-                            Ok(path) => Some(path),
-                            Err(err) => {
-                                warnings.push(format!("周报生成失败: {}", err));
-                                None
-                            }
-                        };
-                    (index_path, weekly_note_path)
-                }
-                Err(err) => {
-                    warnings.push(format!("周报数据生成失败: {}", err));
-                    (None, None)
+        let (week_index_path, weekly_note_path) = match self
+            .build_week_summary_with_previous(db.as_ref(), date, &self.config)
+            .await
+        {
+            Ok(summary) => {
+                week_summary = Some(summary);
+                let summary_ref = week_summary.as_ref().expect("周报摘要缺失");
+                let index_path = match self
+                    .export_week_index_with_summary(summary_ref, &root)
+                    .await
+                {
+                    Ok(path) => {
+                        emit_written(&tx, "week_index", &path);
+                        Some(path)
+                    }
+                    Err(err) => {
+                        push_warning(&mut warnings, &tx, format!("周索引生成失败: {}", err));
+                        None
+                    }
+                };
+                let weekly_note_path = match self
+                    .export_weekly_note_with_summary(summary_ref, &root)
+                    .await
+                {
+                    Ok(path) => {
+                        emit_written(&tx, "weekly_note", &path);
+                        Some(path)
+                    }
+                    Err(err) => {
+                        push_warning(&mut warnings, &tx, format!("周报生成失败: {}", err));
+                        None
+                    }
+                };
+                if self.config.generate_week_canvas {
+                    match self
+                        .export_week_canvas(db.as_ref(), summary_ref, &root)
+                        .await
+                    {
+                        Ok(path) => emit_written(&tx, "week_canvas", &path),
+                        Err(err) => {
+                            push_warning(&mut warnings, &tx, format!("周画布生成失败: {}", err))
+                        }
+                    }
                 }
-            };
+                (index_path, weekly_note_path)
+            }
+            Err(err) => {
+                push_warning(&mut warnings, &tx, format!("周报数据生成失败: {}", err));
+                (None, None)
+            }
+        };
 
         let overview_path = match self
-            .export_overview_index(date, week_summary.as_ref(), &root)
+            .export_overview_index(db.as_ref(), date, week_summary.as_ref(), &root)
             .await
         {
-            Ok(path) => Some(path),
+            Ok(path) => {
+                emit_written(&tx, "overview", &path);
+                Some(path)
+            }
             Err(err) => {
-                warnings.push(format!("总览生成失败: {}", err));
+                push_warning(&mut warnings, &tx, format!("总览生成失败: {}", err));
                 None
             }
         };
 
+        let mut project_paths = Vec::new();
+        if include_projects && self.config.generate_project_notes {
+            match self.export_project_notes(db.as_ref(), date, &root).await {
+                Ok(paths) => {
+                    for path in &paths {
+                        emit_written(&tx, "project", path);
+                    }
+                    project_paths = paths;
+                }
+                Err(err) => {
+                    push_warning(&mut warnings, &tx, format!("项目笔记生成失败: {}", err));
+                }
+            }
+        }
+
+        let _ = tx.send(ExportEvent::Finished {
+            exported_sessions: session_paths.len(),
+            failed_sessions: failed_sessions.len(),
+            warnings: warnings.len(),
+        });
+
         Ok(ExportOutcome {
             daily_note_path,
             session_paths,
@@ -214,40 +983,317 @@ impl ObsidianExporter {
             week_index_path,
             weekly_note_path,
             overview_path,
+            project_paths,
             warnings,
+            failed_sessions,
+            exported_dates: vec![date.to_string()],
+            skipped_files: self.skipped_files.load(Ordering::Relaxed) - skipped_before,
+            archive_path: None,
+            conflicts: self.conflict_counts().since(conflicts_before),
         })
     }
 
-    pub async fn preview_week_summary(
+    /// 预览指定日期的导出结果：在内存中渲染全部笔记，不写入 Vault
+    pub async fn preview_day(
         &self,
-        db: &Database,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
         date: &str,
-    ) -> Result<WeekSummaryPreview> {
-        let summary = self.build_week_summary(db, date, &self.config).await?;
-        let focus_minutes = summary.focus_metrics.focus_minutes();
-        let distraction_minutes = summary.focus_metrics.distraction_minutes();
-        let focus_ratio = summary.focus_metrics.focus_ratio();
-        let distraction_ratio = summary.focus_metrics.distraction_ratio();
-        let focus_score = summary.focus_metrics.focus_score();
-        let effort_score = summary
-            .focus_metrics
-            .effort_score(summary.score_config.target_minutes);
-        let productivity_score = summary.focus_metrics.productivity_score(
-            summary.score_config.focus_weight,
-            summary.score_config.effort_weight,
-            summary.score_config.target_minutes,
-        );
+    ) -> Result<Vec<NotePreview>> {
+        let preview = ObsidianExporter {
+            config: self.config.clone(),
+            skipped_files: AtomicUsize::new(0),
+            conflicts: Mutex::new(ConflictCounts::default()),
+            dry_run: Some(Mutex::new(Vec::new())),
+            frame_store: self.frame_store.clone(),
+        };
+        preview.export_day(db, llm_handle, date, false).await?;
 
-        Ok(WeekSummaryPreview {
-            week_label: summary.week_label,
-            week_start: summary.week_start,
-            week_end: summary.week_end,
-            total_sessions: summary.total_sessions,
-            total_minutes: summary.total_minutes,
-            avg_session_minutes: summary.avg_session_minutes,
-            top_categories: summary.top_categories,
-            focus_minutes,
-            distraction_minutes,
+        let previews = preview
+            .dry_run
+            .map(|previews| previews.into_inner().unwrap_or_default())
+            .unwrap_or_default();
+        Ok(previews)
+    }
+
+    /// 批量导出日期范围（含首尾），用于回填历史数据
+    ///
+    /// 逐日调用 `export_day`（不强制刷新，复用已缓存的每日总结），没有会话的日期会被跳过；
+    /// 单日失败只记录警告，不中断整体导出。
+    pub async fn export_range(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<ExportOutcome> {
+        let (tx, mut rx) = unbounded_channel();
+        let outcome = self
+            .export_range_with_events(db, llm_handle, start_date, end_date, tx)
+            .await;
+        while rx.try_recv().is_ok() {}
+        outcome
+    }
+
+    /// 批量导出日期范围，并通过通道逐步发送导出事件
+    ///
+    /// 先发送 `RangeStarted`（含范围内会话总数），随后转发每一天的导出事件。
+    pub async fn export_range_with_events(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        start_date: &str,
+        end_date: &str,
+        tx: UnboundedSender<ExportEvent>,
+    ) -> Result<ExportOutcome> {
+        let mut days = Vec::new();
+        let mut total_sessions = 0;
+        for day in range_days(start_date, end_date)? {
+            let date = day.format("%Y-%m-%d").to_string();
+            match self.sessions_on(db.as_ref(), &date).await {
+                Ok(sessions) if sessions.is_empty() => continue,
+                Ok(sessions) => total_sessions += sessions.len(),
+                // 查询失败时仍尝试导出，由单日导出给出具体错误
+                Err(_) => {}
+            }
+            days.push(date);
+        }
+        let _ = tx.send(ExportEvent::RangeStarted {
+            start_date: start_date.to_string(),
+            end_date: end_date.to_string(),
+            total_sessions,
+        });
+
+        let mut combined: Option<ExportOutcome> = None;
+        let mut warnings = Vec::new();
+
+        let last_index = days.len().saturating_sub(1);
+        for (index, date) in days.into_iter().enumerate() {
+            match self
+                .export_day_notes(
+                    db.clone(),
+                    llm_handle.clone(),
+                    &date,
+                    false,
+                    tx.clone(),
+                    index == last_index,
+                )
+                .await
+            {
+                Ok(outcome) => {
+                    warnings.extend(
+                        outcome
+                            .warnings
+                            .iter()
+                            .map(|warning| format!("[{}] {}", date, warning)),
+                    );
+                    combined = Some(match combined.take() {
+                        None => outcome,
+                        Some(mut acc) => {
+                            acc.daily_note_path = outcome.daily_note_path;
+                            acc.session_paths.extend(outcome.session_paths);
+                            acc.index_note_path = outcome.index_note_path.or(acc.index_note_path);
+                            acc.week_index_path = outcome.week_index_path.or(acc.week_index_path);
+                            acc.weekly_note_path =
+                                outcome.weekly_note_path.or(acc.weekly_note_path);
+                            acc.overview_path = outcome.overview_path.or(acc.overview_path);
+                            acc.project_paths.extend(outcome.project_paths);
+                            acc.failed_sessions.extend(outcome.failed_sessions);
+                            acc.exported_dates.extend(outcome.exported_dates);
+                            acc.skipped_files += outcome.skipped_files;
+                            acc.conflicts.add(outcome.conflicts);
+                            acc
+                        }
+                    });
+                }
+                Err(err) => warnings.push(format!("[{}] 导出失败: {}", date, err)),
+            }
+        }
+
+        let mut outcome = combined.ok_or_else(|| match warnings.first() {
+            Some(warning) => anyhow!("日期范围内没有导出成功的日期: {}", warning),
+            None => anyhow!("{} ~ {} 没有会话记录", start_date, end_date),
+        })?;
+        outcome.warnings = warnings;
+        Ok(outcome)
+    }
+
+    /// 仅导出指定会话的笔记与附件，不重新生成每日总结、周报与索引
+    pub async fn export_sessions(
+        &self,
+        db: Arc<Database>,
+        root: &Path,
+        session_ids: &[i64],
+    ) -> Result<ExportOutcome> {
+        let (sessions, failed_sessions) = load_sessions(&db, session_ids).await;
+        if sessions.is_empty() {
+            return Err(anyhow!("未找到需要导出的会话"));
+        }
+        let (outcome, _) = self
+            .export_session_notes(&db, root, sessions, failed_sessions)
+            .await?;
+        Ok(outcome)
+    }
+
+    /// 仅重新导出指定的（此前失败的）会话，并补全每日总结中的会话索引；
+    /// 会话全部无法读取时同样返回逐个会话的失败原因
+    pub async fn retry_failed_sessions(
+        &self,
+        db: Arc<Database>,
+        root: &Path,
+        session_ids: &[i64],
+    ) -> Result<ExportOutcome> {
+        let (sessions, failed_sessions) = load_sessions(&db, session_ids).await;
+        let (mut outcome, links_by_date) = self
+            .export_session_notes(&db, root, sessions, failed_sessions)
+            .await?;
+        if let Some(first_date) = links_by_date.keys().next() {
+            outcome.daily_note_path = self.daily_note_path(root, first_date);
+        }
+
+        for (date, links) in links_by_date {
+            let daily_path = self.daily_note_path(root, &date);
+            if !daily_path.exists() {
+                outcome
+                    .warnings
+                    .push(format!("每日总结 {} 不存在，跳过会话索引更新", date));
+                continue;
+            }
+            if let Err(err) = update_daily_session_links(self.text(), &daily_path, &links).await {
+                outcome
+                    .warnings
+                    .push(format!("每日总结 {} 会话索引更新失败: {}", date, err));
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    /// 逐个导出会话笔记（含截图附件），返回导出结果与按日期分组的会话链接；
+    /// failed_sessions 为读取失败的会话，一并计入导出结果
+    async fn export_session_notes(
+        &self,
+        db: &Arc<Database>,
+        root: &Path,
+        sessions: Vec<Session>,
+        mut failed_sessions: Vec<SessionExportError>,
+    ) -> Result<(ExportOutcome, BTreeMap<String, Vec<String>>)> {
+        let skipped_before = self.skipped_files.load(Ordering::Relaxed);
+        let conflicts_before = self.conflict_counts();
+        let mut warnings: Vec<String> = failed_sessions
+            .iter()
+            .map(|failed| format!("会话 {} 读取失败: {}", failed.session_id, failed.message))
+            .collect();
+
+        let mut session_paths = Vec::new();
+        let mut links_by_date: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        for session in sessions {
+            let session_id = session.id.unwrap_or(0);
+            let date = self.session_date(&session);
+            let sessions_dir = root.join(self.sessions_rel(&date));
+            let assets = self.asset_location(root, &date);
+
+            let result = async {
+                fs::create_dir_all(&sessions_dir).await?;
+                if self.config.include_screenshots {
+                    fs::create_dir_all(&assets.dir).await?;
+                }
+                self.export_session(db, &session, &sessions_dir, &assets)
+                    .await
+            }
+            .await;
+
+            match result {
+                Ok((session_path, link)) => {
+                    session_paths.push(session_path);
+                    links_by_date.entry(date).or_default().push(link);
+                }
+                Err(e) => {
+                    warnings.push(format!("会话 {} 导出失败: {}", session_id, e));
+                    failed_sessions.push(SessionExportError {
+                        session_id,
+                        message: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        let outcome = ExportOutcome {
+            daily_note_path: PathBuf::new(),
+            session_paths,
+            index_note_path: None,
+            week_index_path: None,
+            weekly_note_path: None,
+            overview_path: None,
+            project_paths: Vec::new(),
+            warnings,
+            failed_sessions,
+            exported_dates: Vec::new(),
+            skipped_files: self.skipped_files.load(Ordering::Relaxed) - skipped_before,
+            archive_path: None,
+            conflicts: self.conflict_counts().since(conflicts_before),
+        };
+        Ok((outcome, links_by_date))
+    }
+
+    pub async fn preview_week_summary(
+        &self,
+        db: &Database,
+        date: &str,
+    ) -> Result<WeekSummaryPreview> {
+        let summary = self
+            .build_week_summary_with_previous(db, date, &self.config)
+            .await?;
+        let comparison = summary
+            .previous_week
+            .as_ref()
+            .filter(|previous| previous.has_activity())
+            .map(|previous| {
+                let previous_productivity_score =
+                    previous.productivity_score(&summary.score_config);
+                let total_minutes_delta =
+                    i64::from(summary.total_minutes) - i64::from(previous.total_minutes);
+                WeekComparisonPreview {
+                    previous_week_label: previous.week_label.clone(),
+                    previous_total_minutes: previous.total_minutes,
+                    previous_focus_ratio: previous.focus_metrics.focus_ratio(),
+                    previous_productivity_score,
+                    total_minutes_delta,
+                    total_minutes_change_percent: change_percent(
+                        total_minutes_delta,
+                        i64::from(previous.total_minutes),
+                    ),
+                    focus_ratio_delta: summary.focus_metrics.focus_ratio()
+                        - previous.focus_metrics.focus_ratio(),
+                    productivity_score_delta: week_productivity_score(&summary)
+                        - previous_productivity_score,
+                }
+            });
+        let focus_minutes = summary.focus_metrics.focus_minutes();
+        let distraction_minutes = summary.focus_metrics.distraction_minutes();
+        let focus_ratio = summary.focus_metrics.focus_ratio();
+        let distraction_ratio = summary.focus_metrics.distraction_ratio();
+        let focus_score = summary.focus_metrics.focus_score();
+        let effort_score = summary
+            .focus_metrics
+            .effort_score(summary.score_config.target_minutes);
+        let productivity_score = summary.focus_metrics.productivity_score(
+            summary.score_config.focus_weight,
+            summary.score_config.effort_weight,
+            summary.score_config.target_minutes,
+        );
+
+        Ok(WeekSummaryPreview {
+            week_label: summary.week_label,
+            week_start: summary.week_start,
+            week_end: summary.week_end,
+            total_sessions: summary.total_sessions,
+            total_minutes: summary.total_minutes,
+            avg_session_minutes: summary.avg_session_minutes,
+            top_categories: summary.top_categories,
+            focus_minutes,
+            distraction_minutes,
             focus_ratio,
             distraction_ratio,
             focus_score,
@@ -256,6 +1302,7 @@ impl ObsidianExporter {
             focus_weight: summary.score_config.focus_weight,
             effort_weight: summary.score_config.effort_weight,
             target_minutes: summary.score_config.target_minutes,
+            comparison,
         })
     }
 
@@ -264,30 +1311,17 @@ impl ObsidianExporter {
         db: &Arc<Database>,
         session: &Session,
         sessions_dir: &Path,
-        assets_dir: &Path,
+        assets: &AssetLocation,
     ) -> Result<(PathBuf, String)> {
         let session_id = session.id.unwrap_or(0);
-        let start_time = format_time(session.start_time);
-        let end_time = format_time(session.end_time);
+        let clock = self.clock();
+        let start_time = format_time(clock, session.start_time);
+        let end_time = format_time(clock, session.end_time);
         let duration_minutes = (session.end_time - session.start_time).num_minutes().max(0);
 
-        let filename = format!(
-            "{}_{}-{}_session-{}.md",
-            sanitize_filename(&session.start_time.format("%Y-%m-%d").to_string()),
-            sanitize_filename(&session.start_time.format("%H%M").to_string()),
-            sanitize_filename(&session.end_time.format("%H%M").to_string()),
-            session_id
-        );
+        let filename = self.session_filename(session);
 
         let session_path = sessions_dir.join(filename);
-        let link = format!(
-            "Sessions/{}/{}",
-            session.start_time.format("%Y-%m-%d"),
-            session_path
-                .file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or("session.md")
-        );
 
         let tags = parse_tags(&session.tags);
         let tags_text = format_tags(&tags);
@@ -297,22 +1331,59 @@ impl ObsidianExporter {
             .await
             .unwrap_or_default();
 
-        let timeline_text = render_timeline(&timeline_cards);
-        let metrics = build_session_metrics(&timeline_cards, duration_minutes);
-        let metrics_text = render_metrics(&metrics);
+        let body_tags = if self.config.emit_body_tags {
+            render_body_tags(&self.config.tag_prefix, &tags, &timeline_cards)
+        } else {
+            String::new()
+        };
+
+        let text = self.text();
+        let mut timeline_text = render_timeline(text, clock, &timeline_cards);
+        if self.config.include_mermaid_timeline {
+            let mermaid = render_mermaid_timeline(text, clock, &timeline_cards);
+            if !mermaid.is_empty() {
+                timeline_text = format!("{}\n\n{}", timeline_text, mermaid);
+            }
+        }
+        let metrics = build_session_metrics(text, &timeline_cards, duration_minutes);
+        let metrics_text = render_metrics(text, &metrics);
+
+        let related_issues = db
+            .get_issue_links_by_session(session_id)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .map(|link| format!("- {}", render_issue_link(link)))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let notes = db
+            .get_session_note(session_id)
+            .await
+            .ok()
+            .flatten()
+            .map(|note| render_session_notes(&note))
+            .unwrap_or_default();
+
+        let focus_blocks = db
+            .get_focus_blocks(session.start_time, session.end_time)
+            .await
+            .unwrap_or_default();
+        let focus_blocks_text = render_focus_blocks(text, clock, &focus_blocks, session);
 
         let video_link = if self.config.include_video_link {
             session
                 .video_path
                 .as_ref()
-                .map(|path| format_markdown_link("回放视频", &to_file_url(path)))
-                .unwrap_or_else(|| "暂无视频".to_string())
+                .map(|path| format_markdown_link(text.replay_video, &to_file_url(path)))
+                .unwrap_or_else(|| text.no_video.to_string())
         } else {
             String::new()
         };
 
         let screenshots_section = if self.config.include_screenshots {
-            self.render_screenshots(db, session_id, assets_dir).await
+            self.render_screenshots(db, session_id, &timeline_cards, assets)
+                .await
         } else {
             String::new()
         };
@@ -323,35 +1394,104 @@ impl ObsidianExporter {
             &end_time,
             duration_minutes,
             &tags_text,
+            &body_tags,
             &timeline_text,
+            &related_issues,
+            &notes,
+            &focus_blocks_text,
             &metrics_text,
             &metrics,
             &video_link,
             &screenshots_section,
         );
 
-        fs::write(&session_path, content).await?;
+        // 另存新版本时链接指向实际写入的文件
+        let session_path = self
+            .write_note_with_policy(&session_path, content, self.config.conflict_policy)
+            .await?;
+        let link = format!(
+            "{}/{}",
+            self.sessions_rel(&self.session_date(session)),
+            session_path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or("session.md")
+        );
 
         Ok((session_path, format!("[[{}]]", link)))
     }
 
+    /// 会话笔记文件名（按配置追加标题 slug）
+    fn session_filename(&self, session: &Session) -> String {
+        let title_max_len = self
+            .config
+            .session_filename_title
+            .then_some(self.config.session_title_max_len);
+        session_note_filename(self.clock(), session, title_max_len)
+    }
+
+    /// 按配置计算会话所属分组
+    fn session_group(&self, session: &Session) -> Option<String> {
+        match self.config.group_sessions_by {
+            SessionGrouping::None => None,
+            SessionGrouping::Project => {
+                extract_project(&session.title, &self.config.project_pattern)
+            }
+            SessionGrouping::Category => parse_tags(&session.tags).first().and_then(|tag| {
+                canonical_category(
+                    category_to_string(&tag.category),
+                    &self.config.category_labels,
+                )
+                .map(|(_, label)| label)
+            }),
+        }
+    }
+
+    /// 每日笔记是否需要当天的时间线卡片（应用使用分布、Mermaid 时间线或自定义指标，
+    /// 包括自定义模板引用了 {{app_breakdown}} / {{mermaid_timeline}} 的情况）
+    fn wants_day_cards(&self) -> bool {
+        self.wants_app_breakdown()
+            || self.config.include_mermaid_timeline
+            || !self.config.custom_metrics.is_empty()
+            || self.daily_template_uses("{{mermaid_timeline}}")
+    }
+
+    /// 每日笔记是否输出应用使用分布（需要当天各会话按帧统计的应用时长）
+    fn wants_app_breakdown(&self) -> bool {
+        self.config.include_app_breakdown || self.daily_template_uses("{{app_breakdown}}")
+    }
+
+    fn daily_template_uses(&self, placeholder: &str) -> bool {
+        self.config
+            .daily_template
+            .as_deref()
+            .is_some_and(|t| t.contains(placeholder))
+    }
+
     fn render_daily_note(
         &self,
         summary: &crate::domains::summary::DaySummary,
-        session_links: &[String],
+        session_entries: &[SessionEntry],
+        day_cards: &[TimelineCardRecord],
+        day_apps: &[AppUsageRecord],
+        meetings: &[CalendarEventRecord],
+        goals: &[GoalStatus],
     ) -> String {
-        let session_list = if session_links.is_empty() {
-            "- 当天没有会话记录".to_string()
-        } else {
-            session_links
+        let text = self.text();
+        let session_list = if session_entries.is_empty() {
+            format!("- {}", text.no_sessions)
+        } else if self.config.group_sessions_by == SessionGrouping::None {
+            session_entries
                 .iter()
-                .map(|link| format!("- {}", link))
+                .map(|entry| format!("- {}", entry.link))
                 .collect::<Vec<_>>()
                 .join("\n")
+        } else {
+            render_grouped_sessions(text, session_entries)
         };
 
         let usage_patterns = if summary.usage_patterns.is_empty() {
-            "暂无统计".to_string()
+            text.no_usage_patterns.to_string()
         } else {
             summary
                 .usage_patterns
@@ -362,69 +1502,185 @@ impl ObsidianExporter {
         };
 
         let device_stats = if summary.device_stats.is_empty() {
-            "暂无设备统计".to_string()
+            text.no_device_stats.to_string()
         } else {
             summary
                 .device_stats
                 .iter()
                 .map(|stat| {
-                    format!(
-                        "- {} ({})：{}，截图 {} 张",
-                        stat.name, stat.device_type, stat.total_time, stat.screenshots
-                    )
+                    let line = fill(
+                        text.device_line,
+                        &[
+                            ("name", stat.name.clone()),
+                            ("device_type", stat.device_type.clone()),
+                            ("time", stat.total_time.clone()),
+                            ("screenshots", stat.screenshots.to_string()),
+                        ],
+                    );
+                    format!("- {}", line)
                 })
                 .collect::<Vec<_>>()
                 .join("\n")
         };
 
+        let app_breakdown = render_app_breakdown(text, day_apps, day_cards);
+        let app_block = if self.config.include_app_breakdown {
+            format!("\n## {}\n{}\n", text.app_usage, app_breakdown)
+        } else {
+            String::new()
+        };
+
+        // 浏览器扩展上报的域名统计，没有数据时不输出该节
+        let website_breakdown = summary
+            .domain_stats
+            .iter()
+            .map(|stat| {
+                let line = fill(
+                    text.website_line,
+                    &[
+                        ("domain", stat.domain.clone()),
+                        ("time", stat.total_time.clone()),
+                    ],
+                );
+                format!("- {}", line)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let website_block = if website_breakdown.is_empty() {
+            String::new()
+        } else {
+            format!("\n## {}\n{}\n", text.websites, website_breakdown)
+        };
+
+        let mermaid_timeline = render_mermaid_timeline(text, self.clock(), day_cards);
+        let mermaid_block = if self.config.include_mermaid_timeline && !mermaid_timeline.is_empty()
+        {
+            format!("\n## {}\n{}\n", text.timeline_chart, mermaid_timeline)
+        } else {
+            String::new()
+        };
+
+        let meeting_list = render_meetings(self.clock(), meetings);
+        let meetings_block = if meetings.is_empty() {
+            String::new()
+        } else {
+            format!("\n## {}\n{}\n", text.meetings, meeting_list)
+        };
+
+        let goal_list = render_goals(text, goals);
+        let goals_block = if goal_list.is_empty() {
+            String::new()
+        } else {
+            format!("\n## {}\n{}\n", text.goals, goal_list)
+        };
+
+        let timeline_callouts = render_timeline_callouts(
+            text,
+            self.clock(),
+            day_cards,
+            &self.config.category_callouts,
+        );
+        let callout_block = if self.config.timeline_callouts {
+            format!("\n## {}\n{}\n", text.timeline, timeline_callouts)
+        } else {
+            String::new()
+        };
+
+        let mut day_metrics = WeekFocusMetrics::default();
+        day_metrics.add_cards(day_cards);
+        let custom_metrics = self.custom_metric_values(&day_metrics);
+        let custom_placeholders = custom_metric_placeholders(&custom_metrics);
+
+        let date_values = self.date_values(&summary.date);
+        let aliases = daily_aliases(&date_values);
+        let aliases_line = if self.config.daily_note_aliases {
+            format!("aliases: {}\n", aliases)
+        } else {
+            String::new()
+        };
         let default_template = format!(
             "---\n\
 type: screen-analyzer-daily\n\
 date: {date}\n\
+{aliases_line}\
 session_count: {session_count}\n\
 active_device_count: {device_count}\n\
+{custom_frontmatter}\
 source: screen-analyzer\n\
 ---\n\
 \n\
-# {date} 屏幕活动总结\n\
+# {title}\n\
 \n\
 {summary}\n\
 \n\
-## 会话索引\n\
+## {session_index}\n\
 {session_list}\n\
+{goals_block}\
+{meetings_block}\
+{callout_block}\
+{mermaid_block}\
 \n\
-## 使用模式\n\
+## {usage_patterns_heading}\n\
 {usage_patterns}\n\
 \n\
-## 设备统计\n\
-{device_stats}\n",
+## {device_stats_heading}\n\
+{device_stats}\n\
+{app_block}\
+{website_block}",
             date = summary.date,
-            session_count = session_links.len(),
+            aliases_line = aliases_line,
+            title = fill(text.daily_title, &date_values),
+            session_index = text.session_index,
+            usage_patterns_heading = text.usage_patterns,
+            device_stats_heading = text.device_stats,
+            session_count = session_entries.len(),
             device_count = summary.active_device_count,
+            custom_frontmatter = custom_metric_frontmatter(&custom_metrics),
             summary = summary.summary_text,
             session_list = session_list,
             usage_patterns = usage_patterns,
-            device_stats = device_stats
+            device_stats = device_stats,
+            app_block = app_block,
+            website_block = website_block,
+            goals_block = goals_block,
+            meetings_block = meetings_block,
+            callout_block = callout_block,
+            mermaid_block = mermaid_block
         );
 
-        render_template(
+        let content = render_template(
             self.config
                 .daily_template
                 .as_deref()
                 .filter(|t| !t.trim().is_empty()),
             &default_template,
             &[
-                ("date", summary.date.clone()),
                 ("summary", summary.summary_text.clone()),
                 ("session_list", session_list),
                 ("usage_patterns", usage_patterns),
                 ("device_stats", device_stats),
+                ("app_breakdown", app_breakdown),
+                ("website_breakdown", website_breakdown),
+                ("mermaid_timeline", mermaid_timeline),
+                ("timeline_callouts", timeline_callouts),
+                ("meetings", meeting_list),
+                ("goals", goal_list),
+                ("aliases", aliases),
                 (
                     "active_device_count",
                     summary.active_device_count.to_string(),
                 ),
-            ],
-        )
+            ]
+            .into_iter()
+            .chain(date_values)
+            .chain(
+                custom_placeholders
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), value.clone())),
+            )
+            .collect::<Vec<_>>(),
+        );
+        self.append_footer(content, &summary.date)
     }
 
     fn render_session_note(
@@ -434,20 +1690,26 @@ source: screen-analyzer\n\
         end: &str,
         duration_minutes: i64,
         tags: &str,
+        body_tags: &str,
         timeline: &str,
+        related_issues: &str,
+        notes: &str,
+        focus_blocks: &str,
         metrics_text: &str,
         metrics: &SessionMetrics,
         video_link: &str,
         screenshots_section: &str,
     ) -> String {
+        let text = self.text();
+        let session_date = self.session_date(session);
         let title = if session.title.trim().is_empty() {
-            "未命名会话".to_string()
+            text.untitled_session.to_string()
         } else {
             session.title.clone()
         };
 
         let summary_text = if session.summary.trim().is_empty() {
-            "暂无总结".to_string()
+            text.no_summary.to_string()
         } else {
             session.summary.clone()
         };
@@ -455,13 +1717,43 @@ source: screen-analyzer\n\
         let video_block = if video_link.trim().is_empty() {
             String::new()
         } else {
-            format!("\n## 视频\n{}\n", video_link)
+            format!("\n## {}\n{}\n", text.video, video_link)
         };
 
         let screenshots_block = if screenshots_section.trim().is_empty() {
             String::new()
         } else {
-            format!("\n## 截图\n{}\n", screenshots_section)
+            format!("\n## {}\n{}\n", text.screenshots, screenshots_section)
+        };
+
+        let issues_block = if related_issues.trim().is_empty() {
+            String::new()
+        } else {
+            format!("\n## {}\n{}\n", text.related_issues, related_issues)
+        };
+
+        let notes_block = if notes.trim().is_empty() {
+            String::new()
+        } else {
+            format!("\n## {}\n{}\n", text.notes, notes)
+        };
+
+        let focus_blocks_block = if focus_blocks.trim().is_empty() {
+            String::new()
+        } else {
+            format!("\n## {}\n{}\n", text.focus_blocks, focus_blocks)
+        };
+
+        let body_tags_block = if body_tags.is_empty() {
+            String::new()
+        } else {
+            format!("{}\n\n", body_tags)
+        };
+
+        let inline_fields = if self.config.dataview_inline_fields {
+            render_inline_fields(&session_date, duration_minutes, tags, metrics)
+        } else {
+            String::new()
         };
 
         let default_template = format!(
@@ -481,16 +1773,21 @@ source: screen-analyzer\n\
 \n\
 # {title}\n\
 \n\
+{body_tags_block}\
+{inline_fields}\
 {summary}\n\
+{notes_block}\
 \n\
-## 指标\n\
+## {metrics_heading}\n\
 {metrics}\n\
 \n\
-## 时间线\n\
+## {timeline_heading}\n\
 {timeline}\n\
+{focus_blocks_block}\
+{issues_block}\
 {video_block}\
 {screenshots_block}",
-            date = session.start_time.format("%Y-%m-%d"),
+            date = session_date,
             session_id = session.id.unwrap_or(0),
             start = start,
             end = end,
@@ -500,21 +1797,28 @@ source: screen-analyzer\n\
             fragmentation_level = metrics.fragmentation_level,
             tags = tags,
             title = title,
+            body_tags_block = body_tags_block,
+            inline_fields = inline_fields,
             summary = summary_text,
+            notes_block = notes_block,
+            metrics_heading = text.metrics,
             metrics = metrics_text,
+            timeline_heading = text.timeline,
             timeline = timeline,
+            focus_blocks_block = focus_blocks_block,
+            issues_block = issues_block,
             video_block = video_block,
             screenshots_block = screenshots_block
         );
 
-        render_template(
+        let content = render_template(
             self.config
                 .session_template
                 .as_deref()
                 .filter(|t| !t.trim().is_empty()),
             &default_template,
             &[
-                ("date", session.start_time.format("%Y-%m-%d").to_string()),
+                ("date", session_date.clone()),
                 ("session_id", session.id.unwrap_or(0).to_string()),
                 ("start", start.to_string()),
                 ("end", end.to_string()),
@@ -523,33 +1827,94 @@ source: screen-analyzer\n\
                 ("summary", summary_text),
                 ("tags", tags.to_string()),
                 ("timeline", timeline.to_string()),
+                ("related_issues", related_issues.to_string()),
+                ("notes", notes.to_string()),
+                ("focus_blocks", focus_blocks.to_string()),
                 ("metrics", metrics_text.to_string()),
                 ("context_switches", metrics.context_switches.to_string()),
                 ("fragmentation_level", metrics.fragmentation_level.to_string()),
                 ("video_link", video_link.to_string()),
                 ("screenshots", screenshots_section.to_string()),
+                ("inline_fields", inline_fields.trim_end().to_string()),
+                ("body_tags", body_tags.to_string()),
             ],
-        )
+        );
+        let data_time = self
+            .clock()
+            .session_time(session.end_time)
+            .format("%Y-%m-%d %H:%M")
+            .to_string();
+        self.append_footer(content, &data_time)
+    }
+
+    /// 在笔记末尾附加生成来源页脚（时间取自数据本身，保证内容稳定）
+    fn append_footer(&self, mut content: String, data_time: &str) -> String {
+        if !self.config.include_footer {
+            return content;
+        }
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        let footer = fill(
+            self.text().footer,
+            &[
+                ("version", env!("CARGO_PKG_VERSION").to_string()),
+                ("time", data_time.to_string()),
+            ],
+        );
+        content.push_str(&format!("{}{}\n", FOOTER_SEPARATOR, footer));
+        content
+    }
+
+    /// 按配置的截图策略选取会话的代表截图（Notion 导出共用）
+    pub(crate) async fn select_screenshots(
+        &self,
+        frames: &[Frame],
+        cards: &[TimelineCardRecord],
+    ) -> Vec<Frame> {
+        match &self.config.screenshot_strategy {
+            ScreenshotStrategy::FirstLast => pick_screenshots(frames),
+            ScreenshotStrategy::EvenlySpaced(count) => pick_evenly_spaced(frames, *count),
+            ScreenshotStrategy::PerTimelineCard => pick_per_timeline_card(frames, cards),
+            ScreenshotStrategy::HighestActivity => {
+                let mut sizes = Vec::with_capacity(frames.len());
+                for frame in frames {
+                    let size = fs::metadata(&frame.file_path)
+                        .await
+                        .map(|meta| meta.len())
+                        .unwrap_or(0);
+                    sizes.push(size);
+                }
+                pick_highest_activity(frames, &sizes, HIGHEST_ACTIVITY_COUNT)
+            }
+        }
     }
 
     async fn render_screenshots(
         &self,
         db: &Arc<Database>,
         session_id: i64,
-        assets_dir: &Path,
+        cards: &[TimelineCardRecord],
+        assets: &AssetLocation,
     ) -> String {
-        let frames = db.get_frames_by_session(session_id).await.unwrap_or_default();
-        let targets = pick_screenshots(&frames);
+        let frames = db
+            .get_frames_by_session(session_id)
+            .await
+            .unwrap_or_default();
+        let targets = self.select_screenshots(&frames, cards).await;
 
         if targets.is_empty() {
-            return "暂无可用截图".to_string();
+            return self.text().no_screenshots.to_string();
         }
 
         let mut links = Vec::new();
         for (index, frame) in targets.iter().enumerate() {
-            match self.prepare_screenshot(frame, assets_dir, session_id, index).await {
+            match self
+                .prepare_screenshot(frame, assets, session_id, index)
+                .await
+            {
                 Ok(link) => links.push(link),
-                Err(err) => links.push(format!("截图处理失败: {}", err)),
+                Err(err) => links.push(format!("{}: {}", self.text().screenshot_failed, err)),
             }
         }
 
@@ -559,37 +1924,70 @@ source: screen-analyzer\n\
     async fn prepare_screenshot(
         &self,
         frame: &Frame,
-        assets_dir: &Path,
+        assets: &AssetLocation,
         session_id: i64,
         index: usize,
     ) -> Result<String> {
-        let frame_path = PathBuf::from(&frame.file_path);
+        // 对象存储中的截图在链接模式下直接引用外链（启用截图加密时外链内容不可读）
+        if matches!(self.config.export_mode, ObsidianExportMode::Link)
+            && !frame_crypto::is_enabled()
+        {
+            if let Some(url) = self.frame_store.public_url(&frame.file_path) {
+                return Ok(format!("![]({})", url));
+            }
+        }
+
+        let frame_path = self.frame_store.local_path(&frame.file_path).await?;
         if !frame_path.exists() {
             return Err(anyhow!("截图文件不存在"));
         }
+        // Obsidian 无法直接显示加密的截图，链接模式下改为复制解密后的截图
+        let encrypted = frame_crypto::file_is_encrypted(&frame_path).unwrap_or(false);
 
         match self.config.export_mode {
-            ObsidianExportMode::Copy => {
-                let target_name = format!("session-{}-{}.jpg", session_id, index);
-                let target_path = assets_dir.join(target_name);
-                fs::copy(&frame_path, &target_path).await?;
-                let relative = format!(
-                    "Assets/{}/{}",
-                    assets_dir
-                        .file_name()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or(""),
-                    target_path
-                        .file_name()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("")
-                );
-                Ok(format!("![]({})", relative))
-            }
-            ObsidianExportMode::Link => {
+            ObsidianExportMode::Link if !encrypted => {
                 let file_url = to_file_url(&frame.file_path);
                 Ok(format!("![]({})", file_url))
             }
+            _ => {
+                let format = effective_asset_format(self.config.asset_format);
+                let target_name = format!(
+                    "session-{}-{}.{}",
+                    session_id,
+                    index,
+                    asset_extension(format)
+                );
+                let target_path = assets.dir.join(&target_name);
+                // 截图生成后不再变化，转码产物存在即可视为最新
+                let unchanged = self.config.incremental_sync
+                    && match format {
+                        AssetFormat::Original => same_file_size(&frame_path, &target_path).await,
+                        _ => target_path.exists(),
+                    };
+                if !unchanged && self.dry_run.is_none() {
+                    if format == AssetFormat::Original {
+                        fs::write(&target_path, frame_crypto::read_frame(&frame_path).await?)
+                            .await?;
+                    } else {
+                        transcode_image(
+                            &frame_path,
+                            &target_path,
+                            format,
+                            self.config.asset_quality,
+                        )
+                        .await?;
+                    }
+                }
+                match self.config.asset_link_style {
+                    AssetLinkStyle::Wiki => Ok(format!("![[{}]]", target_name)),
+                    AssetLinkStyle::Markdown if assets.link_prefix.is_empty() => {
+                        Ok(format!("![]({})", target_name))
+                    }
+                    AssetLinkStyle::Markdown => {
+                        Ok(format!("![]({}/{})", assets.link_prefix, target_name))
+                    }
+                }
+            }
         }
     }
 
@@ -616,10 +2014,7 @@ source: screen-analyzer\n\
         let start_date = month_start.format("%Y-%m-%d").to_string();
         let end_date = month_end.format("%Y-%m-%d").to_string();
 
-        let mut activities = db
-            .get_activities(&start_date, &end_date)
-            .await
-            .map_err(|e| anyhow!(e))?;
+        let mut activities = self.activities(db, &start_date, &end_date).await?;
 
         activities.sort_by(|a, b| a.date.cmp(&b.date));
 
@@ -631,41 +2026,21 @@ source: screen-analyzer\n\
             0
         };
 
-        let mut category_counts: std::collections::HashMap<String, usize> =
-            std::collections::HashMap::new();
-        for activity in &activities {
-            for category in &activity.main_categories {
-                *category_counts.entry(category.clone()).or_insert(0) += 1;
-            }
-        }
-
-        let mut categories: Vec<(String, usize)> = category_counts.into_iter().collect();
-        categories.sort_by(|a, b| b.1.cmp(&a.1));
-        let top_categories = if categories.is_empty() {
-            "暂无".to_string()
-        } else {
-            categories
-                .iter()
-                .take(5)
-                .map(|(name, count)| format!("{}({})", name, count))
-                .collect::<Vec<_>>()
-                .join("、")
-        };
+        let categories = count_categories(&activities, &self.config.category_labels);
+        let text = self.text();
+        let top_categories = format_top_categories(text, &categories);
 
         let mut table_lines = Vec::new();
-        table_lines.push("| 日期 | 会话数 | 总时长(分钟) | 主要类别 |".to_string());
+        table_lines.push(text.day_table_header.to_string());
         table_lines.push("| --- | --- | --- | --- |".to_string());
 
         if activities.is_empty() {
             table_lines.push("| - | 0 | 0 | - |".to_string());
         } else {
             for activity in &activities {
-                let date_link = format!("[[Daily/{}]]", activity.date);
-                let categories = if activity.main_categories.is_empty() {
-                    "-".to_string()
-                } else {
-                    activity.main_categories.join(", ")
-                };
+                let date_link = format!("[[{}]]", self.daily_note_rel(&activity.date));
+                let categories =
+                    format_day_categories(&activity.main_categories, &self.config.category_labels);
                 table_lines.push(format!(
                     "| {} | {} | {} | {} |",
                     date_link, activity.session_count, activity.total_duration_minutes, categories
@@ -673,6 +2048,17 @@ source: screen-analyzer\n\
             }
         }
 
+        let heatmap_block = match crate::heatmap::focus_heatmap(db, month_start, month_end).await {
+            Ok(heatmap) => render_focus_heatmap(text, &heatmap)
+                .map(|heatmap| format!("## {}\n{}\n\n", text.focus_heatmap, heatmap))
+                .unwrap_or_default(),
+            Err(e) => {
+                tracing::warn!("计算专注热力图失败: {}", e);
+                String::new()
+            }
+        };
+
+        let month_label = format!("{:04}-{:02}", year, month);
         let content = format!(
             "---\n\
 type: screen-analyzer-index\n\
@@ -683,29 +2069,33 @@ avg_session_minutes: {avg_session}\n\
 source: screen-analyzer\n\
 ---\n\
 \n\
-# {month} 月度索引\n\
+# {title}\n\
 \n\
-## 概览\n\
-- 会话总数：{sessions}\n\
-- 总时长：{minutes} 分钟\n\
-- 平均会话时长：{avg_session} 分钟\n\
-- 主要类别：{top_categories}\n\
+## {overview_heading}\n\
+{overview}\n\
 \n\
-## 每日明细\n\
+{heatmap_block}\
+## {details_heading}\n\
 {table}\n",
-            month = format!("{:04}-{:02}", year, month),
+            month = month_label,
             sessions = total_sessions,
             minutes = total_minutes,
             avg_session = avg_session_minutes,
-            top_categories = top_categories,
+            title = fill(text.month_index_title, &[("month", month_label.clone())]),
+            overview_heading = text.overview,
+            overview = self.overview_lines(
+                total_sessions,
+                total_minutes,
+                avg_session_minutes,
+                &top_categories
+            ),
+            heatmap_block = heatmap_block,
+            details_heading = text.daily_details,
             table = table_lines.join("\n")
         );
 
-        let index_path = root.join("Index").join(format!(
-            "sessions-{:04}-{:02}.md",
-            year, month
-        ));
-        export_index_file(&index_path, content).await
+        let index_path = self.index_path(root, &format!("sessions-{:04}-{:02}.md", year, month));
+        self.export_index_file(&index_path, content).await
     }
 
     async fn export_week_index_with_summary(
@@ -713,8 +2103,13 @@ source: screen-analyzer\n\
         summary: &WeekSummaryData,
         root: &Path,
     ) -> Result<PathBuf> {
-        let focus_summary =
-            render_week_focus_metrics(&summary.focus_metrics, &summary.score_config);
+        let text = self.text();
+        let custom_metrics = self.custom_metric_values(&summary.focus_metrics);
+        let focus_summary = format!(
+            "{}{}",
+            render_week_focus_metrics(text, &summary.focus_metrics, &summary.score_config),
+            custom_metric_list(&custom_metrics)
+        );
         let focus_minutes = summary.focus_metrics.focus_minutes();
         let distraction_minutes = summary.focus_metrics.distraction_minutes();
         let focus_ratio = summary.focus_metrics.focus_ratio();
@@ -750,21 +2145,19 @@ productivity_score: {productivity_score}\n\
 focus_weight: {focus_weight}\n\
 effort_weight: {effort_weight}\n\
 target_minutes: {target_minutes}\n\
+{custom_frontmatter}\
 source: screen-analyzer\n\
 ---\n\
 \n\
-# {week} 周度索引\n\
+# {title}\n\
 \n\
-## 概览\n\
-- 会话总数：{sessions}\n\
-- 总时长：{minutes} 分钟\n\
-- 平均会话时长：{avg_session} 分钟\n\
-- 主要类别：{top_categories}\n\
+## {overview_heading}\n\
+{overview}\n\
 \n\
-## 专注度\n\
+## {focus_heading}\n\
 {focus_summary}\n\
 \n\
-## 每日明细\n\
+## {details_heading}\n\
 {table}\n",
             week = summary.week_label,
             week_start = summary.week_start,
@@ -783,15 +2176,21 @@ source: screen-analyzer\n\
             focus_weight = summary.score_config.focus_weight,
             effort_weight = summary.score_config.effort_weight,
             target_minutes = summary.score_config.target_minutes,
-            top_categories = summary.top_categories,
+            custom_frontmatter = custom_metric_frontmatter(&custom_metrics),
+            title = fill(
+                text.week_index_title,
+                &[("week", summary.week_label.clone())]
+            ),
+            overview_heading = text.overview,
+            overview = self.week_overview_lines(summary),
+            focus_heading = text.focus,
             focus_summary = focus_summary,
+            details_heading = text.daily_details,
             table = summary.table_lines.join("\n")
         );
 
-        let index_path = root
-            .join("Index")
-            .join(format!("weeks-{}.md", summary.week_label));
-        export_index_file(&index_path, content).await
+        let index_path = self.index_path(root, &format!("weeks-{}.md", summary.week_label));
+        self.export_index_file(&index_path, content).await
     }
 
     async fn export_weekly_note_with_summary(
@@ -799,20 +2198,175 @@ source: screen-analyzer\n\
         summary: &WeekSummaryData,
         root: &Path,
     ) -> Result<PathBuf> {
-        let weekly_dir = root.join("Weekly");
-        fs::create_dir_all(&weekly_dir).await?;
-
-        let weekly_path = weekly_dir.join(format!("{}.md", summary.week_label));
+        let weekly_path = self.weekly_note_path(root, &summary.week_label, &summary.week_start);
+        if let Some(weekly_dir) = weekly_path.parent() {
+            self.ensure_dir(weekly_dir).await?;
+        }
         let content = self.render_weekly_note(summary);
-        fs::write(&weekly_path, content).await?;
+        self.write_note(&weekly_path, content).await?;
         Ok(weekly_path)
     }
 
-    fn render_weekly_note(&self, summary: &WeekSummaryData) -> String {
-        let focus_summary =
-            render_week_focus_metrics(&summary.focus_metrics, &summary.score_config);
-        let focus_minutes = summary.focus_metrics.focus_minutes();
-        let distraction_minutes = summary.focus_metrics.distraction_minutes();
+    /// 生成周画布：周报居上，每日笔记按星期排成一行，时长最长的会话挂在所属日期下方
+    async fn export_week_canvas(
+        &self,
+        db: &Database,
+        summary: &WeekSummaryData,
+        root: &Path,
+    ) -> Result<PathBuf> {
+        let week_start = NaiveDate::parse_from_str(&summary.week_start, "%Y-%m-%d")
+            .map_err(|_| anyhow!("周起始日期无效: {}", summary.week_start))?;
+
+        let mut days = Vec::new();
+        for offset in 0..7 {
+            let date = (week_start + chrono::Duration::days(offset))
+                .format("%Y-%m-%d")
+                .to_string();
+            let sessions = self.sessions_on(db, &date).await?;
+            days.push((date, sessions));
+        }
+
+        let canvas = self.render_week_canvas(summary, &days);
+        let canvas_path = self.week_canvas_path(root, &summary.week_label, &summary.week_start);
+        if let Some(canvas_dir) = canvas_path.parent() {
+            self.ensure_dir(canvas_dir).await?;
+        }
+        self.write_note(&canvas_path, canvas).await?;
+        Ok(canvas_path)
+    }
+
+    /// 渲染 Canvas JSON；days 为周一到周日的 (日期, 会话列表)
+    fn render_week_canvas(
+        &self,
+        summary: &WeekSummaryData,
+        days: &[(String, Vec<Session>)],
+    ) -> String {
+        let column_step = CANVAS_NODE_WIDTH + CANVAS_GAP;
+        let row_step = CANVAS_NODE_HEIGHT + CANVAS_GAP;
+        let total_width = column_step * 7 - CANVAS_GAP;
+
+        let mut nodes = vec![serde_json::json!({
+            "id": "week",
+            "type": "file",
+            "file": self.vault_rel(&format!(
+                "{}.md",
+                self.weekly_note_rel(&summary.week_label, &summary.week_start)
+            )),
+            "x": (total_width - CANVAS_NODE_WIDTH) / 2,
+            "y": 0,
+            "width": CANVAS_NODE_WIDTH,
+            "height": CANVAS_NODE_HEIGHT,
+        })];
+        let mut edges = Vec::new();
+
+        let mut top_sessions = days
+            .iter()
+            .flat_map(|(_, sessions)| sessions.iter())
+            .filter(|session| session.id.is_some())
+            .collect::<Vec<_>>();
+        top_sessions.sort_by_key(|session| {
+            (
+                std::cmp::Reverse((session.end_time - session.start_time).num_minutes()),
+                session.start_time,
+            )
+        });
+        top_sessions.truncate(CANVAS_TOP_SESSIONS);
+
+        for (column, (date, sessions)) in days.iter().enumerate() {
+            if sessions.is_empty() {
+                continue;
+            }
+            let x = column as i64 * column_step;
+            let day_id = format!("day-{}", date);
+            nodes.push(serde_json::json!({
+                "id": day_id,
+                "type": "file",
+                "file": self.vault_rel(&format!("{}.md", self.daily_note_rel(date))),
+                "x": x,
+                "y": row_step,
+                "width": CANVAS_NODE_WIDTH,
+                "height": CANVAS_NODE_HEIGHT,
+            }));
+            edges.push(serde_json::json!({
+                "id": format!("week-{}", day_id),
+                "fromNode": "week",
+                "fromSide": "bottom",
+                "toNode": day_id,
+                "toSide": "top",
+            }));
+
+            let mut day_top = sessions
+                .iter()
+                .filter(|session| top_sessions.iter().any(|top| top.id == session.id))
+                .collect::<Vec<_>>();
+            day_top.sort_by_key(|session| session.start_time);
+            for (row, session) in day_top.into_iter().enumerate() {
+                let session_id = format!("session-{}", session.id.unwrap_or(0));
+                nodes.push(serde_json::json!({
+                    "id": session_id,
+                    "type": "file",
+                    "file": self.vault_rel(&format!(
+                        "{}/{}",
+                        self.sessions_rel(date),
+                        self.session_filename(session)
+                    )),
+                    "x": x,
+                    "y": row_step * (row as i64 + 2),
+                    "width": CANVAS_NODE_WIDTH,
+                    "height": CANVAS_NODE_HEIGHT,
+                }));
+                edges.push(serde_json::json!({
+                    "id": format!("{}-{}", day_id, session_id),
+                    "fromNode": day_id,
+                    "fromSide": "bottom",
+                    "toNode": session_id,
+                    "toSide": "top",
+                }));
+            }
+        }
+
+        let canvas = serde_json::json!({ "nodes": nodes, "edges": edges });
+        serde_json::to_string_pretty(&canvas).unwrap_or_default()
+    }
+
+    /// 概览列表：会话总数 / 总时长 / 平均会话时长 / 主要类别
+    fn overview_lines(
+        &self,
+        sessions: i32,
+        minutes: i32,
+        avg_session: i32,
+        top_categories: &str,
+    ) -> String {
+        fill(
+            self.text().overview_lines,
+            &[
+                ("sessions", sessions.to_string()),
+                ("minutes", minutes.to_string()),
+                ("avg_session", avg_session.to_string()),
+                ("top_categories", top_categories.to_string()),
+            ],
+        )
+    }
+
+    fn week_overview_lines(&self, summary: &WeekSummaryData) -> String {
+        self.overview_lines(
+            summary.total_sessions,
+            summary.total_minutes,
+            summary.avg_session_minutes,
+            &summary.top_categories,
+        )
+    }
+
+    fn render_weekly_note(&self, summary: &WeekSummaryData) -> String {
+        let text = self.text();
+        let custom_metrics = self.custom_metric_values(&summary.focus_metrics);
+        let focus_summary = format!(
+            "{}{}",
+            render_week_focus_metrics(text, &summary.focus_metrics, &summary.score_config),
+            custom_metric_list(&custom_metrics)
+        );
+        let focus_minutes = summary.focus_metrics.focus_minutes();
+        let distraction_minutes = summary.focus_metrics.distraction_minutes();
         let focus_ratio = summary.focus_metrics.focus_ratio();
         let distraction_ratio = summary.focus_metrics.distraction_ratio();
         let focus_score = summary.focus_metrics.focus_score();
@@ -826,13 +2380,13 @@ source: screen-analyzer\n\
             summary.score_config.target_minutes,
         );
         let highlights = if summary.daily_highlights.is_empty() {
-            "- 暂无每日总结".to_string()
+            format!("- {}", text.no_daily_summaries)
         } else {
             summary.daily_highlights.join("\n")
         };
-        let insights = build_week_insights(summary);
+        let insights = build_week_insights(text, summary);
         let insight_text = if insights.is_empty() {
-            "- 暂无摘要".to_string()
+            format!("- {}", text.no_insights)
         } else {
             insights
                 .into_iter()
@@ -840,9 +2394,33 @@ source: screen-analyzer\n\
                 .collect::<Vec<_>>()
                 .join("\n")
         };
-        let week_index_link = format!("[[Index/weeks-{}.md]]", summary.week_label);
+        let week_index_link = format!(
+            "[[{}]]",
+            self.index_rel(&format!("weeks-{}.md", summary.week_label))
+        );
+        let comparison_block = render_week_comparison(text, summary)
+            .map(|comparison| format!("## {}\n{}\n\n", text.week_comparison, comparison))
+            .unwrap_or_default();
+        let goals_block = render_week_goals(text, &summary.goals)
+            .map(|goals| format!("## {}\n{}\n\n", text.goals, goals))
+            .unwrap_or_default();
+        let trend_block = summary
+            .trend
+            .as_ref()
+            .and_then(|trend| render_week_trend(text, trend))
+            .map(|trend| format!("## {}\n{}\n\n", text.daily_trend, trend))
+            .unwrap_or_default();
+        let device_block = render_week_devices(text, &summary.device_focus)
+            .map(|devices| format!("## {}\n{}\n\n", text.device_focus, devices))
+            .unwrap_or_default();
+        let wellbeing_block = summary
+            .wellbeing
+            .as_ref()
+            .and_then(|report| render_week_wellbeing(text, report, &crate::wellbeing::config()))
+            .map(|wellbeing| format!("## {}\n{}\n\n", text.wellbeing, wellbeing))
+            .unwrap_or_default();
 
-        format!(
+        let content = format!(
             "---\n\
 type: screen-analyzer-weekly\n\
 week: {week}\n\
@@ -862,32 +2440,33 @@ productivity_score: {productivity_score}\n\
 focus_weight: {focus_weight}\n\
 effort_weight: {effort_weight}\n\
 target_minutes: {target_minutes}\n\
+{custom_frontmatter}\
 source: screen-analyzer\n\
 ---\n\
 \n\
-# {week} 周报\n\
+# {title}\n\
 \n\
-## 概览\n\
-- 会话总数：{sessions}\n\
-- 总时长：{minutes} 分钟\n\
-- 平均会话时长：{avg_session} 分钟\n\
-- 主要类别：{top_categories}\n\
+## {overview_heading}\n\
+{overview}\n\
 \n\
-## 专注度\n\
+## {focus_heading}\n\
 {focus_summary}\n\
 \n\
-## 周报摘要\n\
+{comparison_block}\
+{goals_block}\
+{trend_block}\
+{device_block}\
+{wellbeing_block}\
+## {insights_heading}\n\
 {insight_text}\n\
 \n\
-## 评分说明\n\
-- 专注评分 = 专注占比\n\
-- 投入时长评分：以 {target_minutes} 分钟为 100 分，上限封顶\n\
-- 生产力评分 = 专注评分 {focus_weight}% + 投入时长评分 {effort_weight}%\n\
+## {score_heading}\n\
+{score_lines}\n\
 \n\
-## 每日要点\n\
+## {highlights_heading}\n\
 {highlights}\n\
 \n\
-## 周索引\n\
+## {week_index_heading}\n\
 - {week_index_link}\n",
             week = summary.week_label,
             week_start = summary.week_start,
@@ -906,12 +2485,266 @@ source: screen-analyzer\n\
             focus_weight = summary.score_config.focus_weight,
             effort_weight = summary.score_config.effort_weight,
             target_minutes = summary.score_config.target_minutes,
-            top_categories = summary.top_categories,
+            custom_frontmatter = custom_metric_frontmatter(&custom_metrics),
+            title = fill(text.weekly_title, &[("week", summary.week_label.clone())]),
+            overview_heading = text.overview,
+            overview = self.week_overview_lines(summary),
+            focus_heading = text.focus,
             focus_summary = focus_summary,
+            comparison_block = comparison_block,
+            goals_block = goals_block,
+            trend_block = trend_block,
+            device_block = device_block,
+            wellbeing_block = wellbeing_block,
+            insights_heading = text.weekly_insights,
             insight_text = insight_text,
+            score_heading = text.score_notes,
+            score_lines = fill(
+                text.score_lines,
+                &[
+                    (
+                        "target_minutes",
+                        summary.score_config.target_minutes.to_string()
+                    ),
+                    (
+                        "focus_weight",
+                        summary.score_config.focus_weight.to_string()
+                    ),
+                    (
+                        "effort_weight",
+                        summary.score_config.effort_weight.to_string()
+                    ),
+                ]
+            ),
+            highlights_heading = text.daily_highlights,
             highlights = highlights,
+            week_index_heading = text.week_index,
             week_index_link = week_index_link
-        )
+        );
+        self.append_footer(content, &summary.week_end)
+    }
+
+    /// 导出季度回顾笔记（按周汇总趋势）
+    pub async fn export_quarterly_note(
+        &self,
+        db: &Database,
+        date: &str,
+        root: &Path,
+    ) -> Result<PathBuf> {
+        let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| anyhow!("日期格式错误: {}", date))?;
+        let quarter = (day.month() - 1) / 3 + 1;
+        let start = NaiveDate::from_ymd_opt(day.year(), (quarter - 1) * 3 + 1, 1)
+            .ok_or_else(|| anyhow!("季度起始日期无效"))?;
+        let end = if quarter == 4 {
+            NaiveDate::from_ymd_opt(day.year(), 12, 31)
+        } else {
+            NaiveDate::from_ymd_opt(day.year(), quarter * 3 + 1, 1).and_then(|d| d.pred_opt())
+        }
+        .ok_or_else(|| anyhow!("季度结束日期无效"))?;
+
+        let period = RollupPeriod {
+            kind: "quarterly",
+            label: format!("{:04}-Q{}", day.year(), quarter),
+            title: fill(
+                self.text().quarter_title,
+                &[
+                    ("year", day.year().to_string()),
+                    ("quarter", quarter.to_string()),
+                ],
+            ),
+            start,
+            end,
+        };
+        let weeks = self.collect_period_weeks(db, start, end).await?;
+
+        let quarterly_dir = root.join("Quarterly");
+        fs::create_dir_all(&quarterly_dir).await?;
+        let path = quarterly_dir.join(format!("{}.md", period.label));
+        let content = self.render_rollup_note(&period, &weeks, true);
+        self.write_note(&path, content).await?;
+        Ok(path)
+    }
+
+    /// 导出年度回顾笔记（按月汇总趋势）
+    pub async fn export_yearly_note(
+        &self,
+        db: &Database,
+        date: &str,
+        root: &Path,
+    ) -> Result<PathBuf> {
+        let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| anyhow!("日期格式错误: {}", date))?;
+        let start =
+            NaiveDate::from_ymd_opt(day.year(), 1, 1).ok_or_else(|| anyhow!("年度日期无效"))?;
+        let end =
+            NaiveDate::from_ymd_opt(day.year(), 12, 31).ok_or_else(|| anyhow!("年度日期无效"))?;
+
+        let period = RollupPeriod {
+            kind: "yearly",
+            label: format!("{:04}", day.year()),
+            title: fill(self.text().year_title, &[("year", day.year().to_string())]),
+            start,
+            end,
+        };
+        let weeks = self.collect_period_weeks(db, start, end).await?;
+
+        let yearly_dir = root.join("Yearly");
+        fs::create_dir_all(&yearly_dir).await?;
+        let path = yearly_dir.join(format!("{}.md", period.label));
+        let content = self.render_rollup_note(&period, &weeks, false);
+        self.write_note(&path, content).await?;
+        Ok(path)
+    }
+
+    /// 收集周期内的所有周（按 ISO 规则，周四落在周期内的周归属该周期）
+    async fn collect_period_weeks(
+        &self,
+        db: &Database,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<WeekSummaryData>> {
+        let mut thursday = start;
+        while thursday.weekday() != Weekday::Thu {
+            thursday += chrono::Duration::days(1);
+        }
+
+        let mut weeks = Vec::new();
+        while thursday <= end {
+            let date = thursday.format("%Y-%m-%d").to_string();
+            weeks.push(self.build_week_summary(db, &date, &self.config).await?);
+            thursday += chrono::Duration::days(7);
+        }
+        Ok(weeks)
+    }
+
+    fn render_rollup_note(
+        &self,
+        period: &RollupPeriod,
+        weeks: &[WeekSummaryData],
+        include_week_table: bool,
+    ) -> String {
+        let text = self.text();
+        let mut total_metrics = WeekFocusMetrics::default();
+        for week in weeks {
+            total_metrics.merge(&week.focus_metrics);
+        }
+        let total_sessions: i32 = weeks.iter().map(|week| week.total_sessions).sum();
+        let total_minutes: i32 = weeks.iter().map(|week| week.total_minutes).sum();
+        let avg_productivity = average_productivity_score(weeks.iter());
+
+        // 按周四所在月份归档到月度趋势
+        let mut months: Vec<(u32, Vec<&WeekSummaryData>)> = Vec::new();
+        for week in weeks {
+            let month = week_month(week);
+            match months.last_mut() {
+                Some((last, items)) if *last == month => items.push(week),
+                _ => months.push((month, vec![week])),
+            }
+        }
+
+        let mut month_lines = vec![
+            text.month_table_header.to_string(),
+            "| --- | --- | --- | --- | --- | --- |".to_string(),
+        ];
+        if months.is_empty() {
+            month_lines.push("| - | 0 | 0 | 0 | 0% | 0 |".to_string());
+        }
+        for (month, items) in &months {
+            let mut metrics = WeekFocusMetrics::default();
+            for week in items {
+                metrics.merge(&week.focus_metrics);
+            }
+            month_lines.push(format!(
+                "| {:04}-{:02} | {} | {} | {} | {}% | {} |",
+                period.start.year(),
+                month,
+                items.len(),
+                items.iter().map(|week| week.total_sessions).sum::<i32>(),
+                items.iter().map(|week| week.total_minutes).sum::<i32>(),
+                metrics.focus_ratio(),
+                average_productivity_score(items.iter().copied())
+            ));
+        }
+
+        let mut sections = vec![format!(
+            "## {}\n{}",
+            text.month_trend,
+            month_lines.join("\n")
+        )];
+        if include_week_table {
+            let mut week_lines = vec![
+                text.week_table_header.to_string(),
+                "| --- | --- | --- | --- | --- |".to_string(),
+            ];
+            if weeks.is_empty() {
+                week_lines.push("| - | 0 | 0 | 0% | 0 |".to_string());
+            }
+            for week in weeks {
+                week_lines.push(format!(
+                    "| [[{}]] | {} | {} | {}% | {} |",
+                    self.weekly_note_rel(&week.week_label, &week.week_start),
+                    week.total_sessions,
+                    week.total_minutes,
+                    week.focus_metrics.focus_ratio(),
+                    week_productivity_score(week)
+                ));
+            }
+            sections.push(format!("## {}\n{}", text.week_trend, week_lines.join("\n")));
+        } else {
+            let quarter_links = (1..=4)
+                .map(|quarter| format!("- [[Quarterly/{}-Q{}]]", period.label, quarter))
+                .collect::<Vec<_>>()
+                .join("\n");
+            sections.push(format!("## {}\n{}", text.quarter_reviews, quarter_links));
+        }
+
+        let period_start = period.start.format("%Y-%m-%d").to_string();
+        let period_end = period.end.format("%Y-%m-%d").to_string();
+        let content = format!(
+            "---\n\
+type: screen-analyzer-{kind}\n\
+period: {label}\n\
+period_start: {period_start}\n\
+period_end: {period_end}\n\
+weeks: {week_count}\n\
+total_sessions: {sessions}\n\
+total_minutes: {minutes}\n\
+focus_ratio: {focus_ratio}\n\
+avg_productivity_score: {avg_productivity}\n\
+source: screen-analyzer\n\
+---\n\
+\n\
+# {title}\n\
+\n\
+## {overview_heading}\n\
+{overview}\n\
+\n\
+{sections}\n",
+            kind = period.kind,
+            label = period.label,
+            title = period.title,
+            period_start = period_start,
+            period_end = period_end,
+            week_count = weeks.len(),
+            sessions = total_sessions,
+            minutes = total_minutes,
+            focus_ratio = total_metrics.focus_ratio(),
+            avg_productivity = avg_productivity,
+            overview_heading = text.overview,
+            overview = fill(
+                text.rollup_overview_lines,
+                &[
+                    ("week_count", weeks.len().to_string()),
+                    ("sessions", total_sessions.to_string()),
+                    ("minutes", total_minutes.to_string()),
+                    ("focus_ratio", total_metrics.focus_ratio().to_string()),
+                    ("avg_productivity", avg_productivity.to_string()),
+                ]
+            ),
+            sections = sections.join("\n\n")
+        );
+        self.append_footer(content, &period_end)
     }
 
     async fn build_week_summary(
@@ -934,10 +2767,7 @@ source: screen-analyzer\n\
         let start_date = week_start.format("%Y-%m-%d").to_string();
         let end_date = week_end.format("%Y-%m-%d").to_string();
 
-        let mut activities = db
-            .get_activities(&start_date, &end_date)
-            .await
-            .map_err(|e| anyhow!(e))?;
+        let mut activities = self.activities(db, &start_date, &end_date).await?;
 
         activities.sort_by(|a, b| a.date.cmp(&b.date));
 
@@ -962,41 +2792,21 @@ source: screen-analyzer\n\
             target_minutes,
         };
 
-        let mut category_counts: std::collections::HashMap<String, usize> =
-            std::collections::HashMap::new();
-        for activity in &activities {
-            for category in &activity.main_categories {
-                *category_counts.entry(category.clone()).or_insert(0) += 1;
-            }
-        }
-
-        let mut categories: Vec<(String, usize)> = category_counts.into_iter().collect();
-        categories.sort_by(|a, b| b.1.cmp(&a.1));
-        let top_categories = if categories.is_empty() {
-            "暂无".to_string()
-        } else {
-            categories
-                .iter()
-                .take(5)
-                .map(|(name, count)| format!("{}({})", name, count))
-                .collect::<Vec<_>>()
-                .join("、")
-        };
+        let categories = count_categories(&activities, &self.config.category_labels);
+        let text = self.text();
+        let top_categories = format_top_categories(text, &categories);
 
         let mut table_lines = Vec::new();
-        table_lines.push("| 日期 | 会话数 | 总时长(分钟) | 主要类别 |".to_string());
+        table_lines.push(text.day_table_header.to_string());
         table_lines.push("| --- | --- | --- | --- |".to_string());
 
         if activities.is_empty() {
             table_lines.push("| - | 0 | 0 | - |".to_string());
         } else {
             for activity in &activities {
-                let date_link = format!("[[Daily/{}]]", activity.date);
-                let categories = if activity.main_categories.is_empty() {
-                    "-".to_string()
-                } else {
-                    activity.main_categories.join(", ")
-                };
+                let date_link = format!("[[{}]]", self.daily_note_rel(&activity.date));
+                let categories =
+                    format_day_categories(&activity.main_categories, &self.config.category_labels);
                 table_lines.push(format!(
                     "| {} | {} | {} | {} |",
                     date_link, activity.session_count, activity.total_duration_minutes, categories
@@ -1008,10 +2818,10 @@ source: screen-analyzer\n\
         let mut cursor = week_start;
         while cursor <= week_end {
             let date_text = cursor.format("%Y-%m-%d").to_string();
-            let link = format!("[[Daily/{}]]", date_text);
+            let link = format!("[[{}]]", self.daily_note_rel(&date_text));
             let summary_text = match db.get_day_summary(&date_text).await {
                 Ok(Some(summary)) => compact_summary_text(&summary.summary_text, 140),
-                _ => "暂无总结".to_string(),
+                _ => text.no_summary.to_string(),
             };
             daily_highlights.push(format!("- {}: {}", link, summary_text));
             cursor += chrono::Duration::days(1);
@@ -1029,11 +2839,78 @@ source: screen-analyzer\n\
             focus_metrics,
             score_config,
             daily_highlights,
+            previous_week: None,
+            stats_baseline: None,
+            goals: Vec::new(),
+            trend: None,
+            wellbeing: None,
+            device_focus: Vec::new(),
         })
     }
 
+    /// 指定日期所在周的周报汇总（评分按当前导出配置计算）
+    pub(crate) async fn week_summary(&self, db: &Database, date: &str) -> Result<WeekSummaryData> {
+        self.build_week_summary(db, date, &self.config).await
+    }
+
+    /// 周报摘要，并附带上周数据用于对比（季度/年度回顾逐周汇总时不需要）
+    async fn build_week_summary_with_previous(
+        &self,
+        db: &Database,
+        date: &str,
+        config: &ObsidianExportConfig,
+    ) -> Result<WeekSummaryData> {
+        let mut summary = self.build_week_summary(db, date, config).await?;
+        let week_start = NaiveDate::parse_from_str(&summary.week_start, "%Y-%m-%d")
+            .map_err(|_| anyhow!("日期格式错误: {}", summary.week_start))?;
+
+        // 之前 8 周的数据：最近一周用于与上周对比，有记录的周构成滚动基准
+        let mut history = Vec::with_capacity(BASELINE_WEEKS);
+        for offset in 1..=BASELINE_WEEKS as i64 {
+            let start = week_start - chrono::Duration::days(7 * offset);
+            history.push(self.week_baseline(db, start).await?);
+        }
+        summary.stats_baseline = WeekStatsBaseline::from_weeks(&history, &summary.score_config);
+        summary.previous_week = history.into_iter().next();
+
+        let week_end = NaiveDate::parse_from_str(&summary.week_end, "%Y-%m-%d")
+            .map_err(|_| anyhow!("日期格式错误: {}", summary.week_end))?;
+        summary.device_focus = self.week_device_focus(db, week_start, week_end).await;
+        summary.goals = crate::goals::week_goals(db, week_start, week_end)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("评估本周目标失败: {}", e);
+                Vec::new()
+            });
+
+        // 趋势截至今天（本周尚未结束时不计未来日期）
+        let trend_end = week_end.min(crate::storage::local_now().date_naive());
+        if trend_end >= week_start {
+            let weights = ScoreWeights::from_weekly(
+                summary.score_config.focus_weight,
+                summary.score_config.effort_weight,
+                summary.score_config.target_minutes,
+            );
+            summary.trend = crate::trends::focus_trend(db, week_start, trend_end, weights)
+                .await
+                .map_err(|e| tracing::warn!("计算本周专注趋势失败: {}", e))
+                .ok();
+
+            let wellbeing_config = crate::wellbeing::config();
+            if wellbeing_config.enabled {
+                summary.wellbeing =
+                    crate::wellbeing::wellbeing_report(db, trend_end, &wellbeing_config)
+                        .await
+                        .map_err(|e| tracing::warn!("分析本周过劳迹象失败: {}", e))
+                        .ok();
+            }
+        }
+        Ok(summary)
+    }
+
     async fn export_overview_index(
         &self,
+        db: &Database,
         date: &str,
         week_summary: Option<&WeekSummaryData>,
         root: &Path,
@@ -1041,16 +2918,30 @@ source: screen-analyzer\n\
         let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
             .map_err(|_| anyhow!("日期格式错误: {}", date))?;
         let month_label = format!("{:04}-{:02}", day.year(), day.month());
-        let updated_at = crate::storage::local_now().format("%Y-%m-%d %H:%M").to_string();
+        let updated_at = self.clock().now().format("%Y-%m-%d %H:%M").to_string();
+        let text = self.text();
 
-        let daily_link = format!("[[Daily/{}]]", date);
+        let daily_link = format!("[[{}]]", self.daily_note_rel(date));
         let week_link = week_summary
-            .map(|summary| format!("[[Weekly/{}]]", summary.week_label))
-            .unwrap_or_else(|| "暂无".to_string());
+            .map(|summary| {
+                format!(
+                    "[[{}]]",
+                    self.weekly_note_rel(&summary.week_label, &summary.week_start)
+                )
+            })
+            .unwrap_or_else(|| text.none.to_string());
         let week_index_link = week_summary
-            .map(|summary| format!("[[Index/weeks-{}.md]]", summary.week_label))
-            .unwrap_or_else(|| "暂无".to_string());
-        let month_index_link = format!("[[Index/sessions-{}.md]]", month_label);
+            .map(|summary| {
+                format!(
+                    "[[{}]]",
+                    self.index_rel(&format!("weeks-{}.md", summary.week_label))
+                )
+            })
+            .unwrap_or_else(|| text.none.to_string());
+        let month_index_link = format!(
+            "[[{}]]",
+            self.index_rel(&format!("sessions-{}.md", month_label))
+        );
 
         let content = format!(
             "---\n\
@@ -1059,438 +2950,5094 @@ updated_at: {updated_at}\n\
 source: screen-analyzer\n\
 ---\n\
 \n\
-# Screen Analyzer 总览\n\
+# {title}\n\
+\n\
+{links}\n\
 \n\
-- 今日：{daily_link}\n\
-- 本周：{week_link}\n\
-- 本周索引：{week_index_link}\n\
-- 本月索引：{month_index_link}\n",
+{dashboard}",
             updated_at = updated_at,
-            daily_link = daily_link,
-            week_link = week_link,
-            week_index_link = week_index_link,
-            month_index_link = month_index_link
+            title = text.overview_title,
+            links = fill(
+                text.overview_links,
+                &[
+                    ("daily_link", daily_link),
+                    ("week_link", week_link),
+                    ("week_index_link", week_index_link),
+                    ("month_index_link", month_index_link),
+                ]
+            ),
+            dashboard = self.render_overview_dashboard(db, day).await
         );
 
-        let index_path = root.join("Index").join("overview.md");
-        export_index_file(&index_path, content).await
+        let index_path = self.index_path(root, "overview.md");
+        self.export_index_file(&index_path, content).await
     }
 
-    async fn compute_week_focus_metrics(
-        &self,
-        db: &Database,
-        week_start: NaiveDate,
-        week_end: NaiveDate,
-    ) -> WeekFocusMetrics {
-        let mut metrics = WeekFocusMetrics::default();
-        let mut cursor = week_start;
+    /// 总览仪表盘：近 7/30 天统计、连续记录天数、专注度趋势与最近周报
+    async fn render_overview_dashboard(&self, db: &Database, day: NaiveDate) -> String {
+        let text = self.text();
+        let current_week_start =
+            day - chrono::Duration::days(day.weekday().num_days_from_monday() as i64);
+        let first_day = current_week_start - chrono::Duration::weeks(OVERVIEW_WEEKS - 1);
+
+        let mut days = Vec::new();
+        for offset in 0..=(day - first_day).num_days() {
+            let date = first_day + chrono::Duration::days(offset);
+            let sessions = self
+                .sessions_on(db, &date.format("%Y-%m-%d").to_string())
+                .await
+                .unwrap_or_default();
+            let minutes = sessions
+                .iter()
+                .map(|session| (session.end_time - session.start_time).num_minutes().max(0))
+                .sum::<i64>();
+            let mut focus = WeekFocusMetrics::default();
+            focus.add_cards(&collect_day_cards(db, &sessions).await);
+            days.push((date, sessions.len(), minutes, focus));
+        }
 
-        while cursor <= week_end {
-            let date = cursor.format("%Y-%m-%d").to_string();
-            if let Ok(sessions) = db.get_sessions_by_date(&date).await {
-                for session in sessions {
-                    let session_id = match session.id {
-                        Some(id) => id,
-                        None => continue,
-                    };
-                    if let Ok(cards) = db.get_timeline_cards_by_session(session_id).await {
-                        metrics.add_cards(&cards);
-                    }
-                }
+        let days_label = |window: i64| fill(text.last_days, &[("days", window.to_string())]);
+        let window_row = |label: &str, window: i64| {
+            let recent = days
+                .iter()
+                .filter(|(date, ..)| (day - *date).num_days() < window)
+                .collect::<Vec<_>>();
+            format!(
+                "| {} | {} | {} | {} |",
+                label,
+                recent.iter().map(|(_, count, ..)| count).sum::<usize>(),
+                recent.iter().map(|(_, _, minutes, _)| minutes).sum::<i64>(),
+                recent.iter().filter(|(_, count, ..)| *count > 0).count()
+            )
+        };
+
+        let active = days
+            .iter()
+            .filter(|(date, ..)| (day - *date).num_days() < 30)
+            .map(|(_, count, ..)| *count > 0)
+            .collect::<Vec<_>>();
+        let current_streak = active.iter().rev().take_while(|active| **active).count();
+        let longest_streak = active
+            .split(|active| !*active)
+            .map(|run| run.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut weeks = Vec::new();
+        for index in 0..OVERVIEW_WEEKS {
+            let week_start = first_day + chrono::Duration::weeks(index);
+            let mut focus = WeekFocusMetrics::default();
+            let mut sessions = 0;
+            let mut minutes = 0;
+            for (_, count, day_minutes, day_focus) in days
+                .iter()
+                .filter(|(date, ..)| *date >= week_start && (*date - week_start).num_days() < 7)
+            {
+                sessions += count;
+                minutes += day_minutes;
+                focus.merge(day_focus);
             }
-            cursor += chrono::Duration::days(1);
+            let iso_week = week_start.iso_week();
+            let week_label = format!("{:04}-W{:02}", iso_week.year(), iso_week.week());
+            weeks.push((week_label, week_start, sessions, minutes, focus));
         }
 
-        metrics
-    }
-}
+        let sparkline = weeks
+            .iter()
+            .map(|(_, _, sessions, _, focus)| {
+                if *sessions == 0 || focus.total_minutes == 0 {
+                    '·'
+                } else {
+                    sparkline_char(focus.focus_score())
+                }
+            })
+            .collect::<String>();
+        let latest_focus = weeks
+            .iter()
+            .rev()
+            .find(|(_, _, _, _, focus)| focus.total_minutes > 0)
+            .map(|(label, _, _, _, focus)| {
+                fill(
+                    text.latest_focus,
+                    &[
+                        ("week", label.clone()),
+                        ("ratio", focus.focus_score().to_string()),
+                    ],
+                )
+            })
+            .unwrap_or_else(|| text.no_focus_data.to_string());
+
+        let recent_weeks = weeks
+            .iter()
+            .rev()
+            .filter(|(_, _, sessions, ..)| *sessions > 0)
+            .map(|(label, week_start, sessions, minutes, _)| {
+                let link = format!(
+                    "[[{}]]",
+                    self.weekly_note_rel(label, &week_start.format("%Y-%m-%d").to_string())
+                );
+                let line = fill(
+                    text.recent_week_line,
+                    &[
+                        ("link", link),
+                        ("sessions", sessions.to_string()),
+                        ("minutes", minutes.to_string()),
+                    ],
+                );
+                format!("- {}", line)
+            })
+            .collect::<Vec<_>>();
+        let recent_weeks = if recent_weeks.is_empty() {
+            format!("- {}", text.no_weekly_notes)
+        } else {
+            recent_weeks.join("\n")
+        };
 
-async fn export_index_file(path: &Path, content: String) -> Result<PathBuf> {
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).await?;
+        format!(
+            "## {recent_stats}\n\
+{table_header}\n\
+| --- | --- | --- | --- |\n\
+{last_7}\n\
+{last_30}\n\
+\n\
+- {current_streak}\n\
+- {longest_streak}\n\
+\n\
+## {focus_trend}\n\
+`{sparkline}` {sparkline_note}\n\
+- {latest_focus}\n\
+\n\
+## {recent_weeks_heading}\n\
+{recent_weeks}\n",
+            recent_stats = text.recent_stats,
+            table_header = text.recent_table_header,
+            last_7 = window_row(&days_label(7), 7),
+            last_30 = window_row(&days_label(30), 30),
+            current_streak = fill(text.current_streak, &[("days", current_streak.to_string())]),
+            longest_streak = fill(text.longest_streak, &[("days", longest_streak.to_string())]),
+            focus_trend = text.focus_trend,
+            sparkline = sparkline,
+            sparkline_note = fill(
+                text.sparkline_note,
+                &[("weeks", OVERVIEW_WEEKS.to_string())]
+            ),
+            latest_focus = fill(text.latest_line, &[("focus", latest_focus)]),
+            recent_weeks_heading = text.recent_weeks,
+            recent_weeks = recent_weeks
+        )
     }
-    fs::write(path, content).await?;
-    Ok(path.to_path_buf())
-}
 
-fn format_time(dt: DateTime<Utc>) -> String {
-    dt.format("%H:%M").to_string()
-}
+    /// 按项目规则汇总截至 `date` 的全部会话，为每个项目生成 `Projects/<项目>.md`
+    async fn export_project_notes(
+        &self,
+        db: &Database,
+        date: &str,
+        root: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        let rules = db.get_project_rules().await?;
+        if rules.is_empty() {
+            return Ok(Vec::new());
+        }
+        let end = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| anyhow!("日期格式错误: {}", date))?;
+        let needs_cards = rules
+            .iter()
+            .any(|rule| rule.match_field == "app" || rule.match_field == "title");
+        let needs_apps = rules.iter().any(|rule| rule.match_field == "process");
+
+        let clock = self.clock();
+        let mut sessions_by_id = HashMap::new();
+        let mut project_sessions = Vec::new();
+        for session in db.get_all_sessions().await? {
+            let start = clock.session_time(session.start_time);
+            if start.date() > end || !self.includes_device(&session) {
+                continue;
+            }
+            let session_id = session.id.unwrap_or(0);
+            let cards = if needs_cards {
+                db.get_timeline_cards_by_session(session_id)
+                    .await
+                    .unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+            let apps = if needs_apps {
+                db.get_app_usage_by_session(session_id)
+                    .await
+                    .unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+            if let Some(project) = match_session_project(&session, &cards, &apps, &rules) {
+                project_sessions.push(ProjectSession {
+                    project: project.to_string(),
+                    session_id,
+                    title: session.title.clone(),
+                    start,
+                    minutes: (session.end_time - session.start_time).num_minutes().max(0),
+                });
+                sessions_by_id.insert(session_id, session);
+            }
+        }
 
-fn render_timeline(cards: &[TimelineCardRecord]) -> String {
-    if cards.is_empty() {
-        return "- 无可用时间线".to_string();
+        let mut paths = Vec::new();
+        for summary in summarize_projects(
+            project_sessions,
+            end,
+            PROJECT_TREND_WEEKS,
+            PROJECT_RECENT_SESSIONS,
+        ) {
+            let path = root.join(self.project_rel(&summary.name));
+            if let Some(dir) = path.parent() {
+                self.ensure_dir(dir).await?;
+            }
+            let content = self.render_project_note(&summary, &sessions_by_id);
+            paths.push(self.export_index_file(&path, content).await?);
+        }
+        Ok(paths)
     }
 
-    let mut lines = Vec::new();
-    for card in cards {
-        let (start, end) = format_time_range(&card.start_time, &card.end_time);
-        let line = format!(
-            "- {}-{} [{} / {}] {}：{}",
-            start, end, card.category, card.subcategory, card.title, card.summary
+    fn render_project_note(
+        &self,
+        summary: &ProjectSummary,
+        sessions_by_id: &HashMap<i64, Session>,
+    ) -> String {
+        let text = self.text();
+        let max_week = summary
+            .weekly_minutes
+            .iter()
+            .map(|(_, minutes)| *minutes)
+            .max()
+            .unwrap_or(0);
+        let sparkline = summary
+            .weekly_minutes
+            .iter()
+            .map(|(_, minutes)| {
+                if *minutes == 0 {
+                    '·'
+                } else {
+                    sparkline_char(minutes * 100 / max_week.max(1))
+                }
+            })
+            .collect::<String>();
+        let week_rows = summary
+            .weekly_minutes
+            .iter()
+            .map(|(week_start, minutes)| {
+                let iso_week = week_start.iso_week();
+                format!(
+                    "| {:04}-W{:02} | {} |",
+                    iso_week.year(),
+                    iso_week.week(),
+                    minutes
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let recent_sessions = summary
+            .recent_sessions
+            .iter()
+            .map(|entry| {
+                let title = if entry.title.trim().is_empty() {
+                    text.untitled_session
+                } else {
+                    entry.title.trim()
+                };
+                let link = match sessions_by_id.get(&entry.session_id) {
+                    Some(session) => format!(
+                        "[[{}/{}|{}]]",
+                        self.sessions_rel(&self.session_date(session)),
+                        self.session_filename(session),
+                        title
+                    ),
+                    None => title.to_string(),
+                };
+                let line = fill(
+                    text.project_session_line,
+                    &[
+                        ("date", entry.start.format("%Y-%m-%d").to_string()),
+                        ("time", entry.start.format("%H:%M").to_string()),
+                        ("link", link),
+                        ("minutes", entry.minutes.to_string()),
+                    ],
+                );
+                format!("- {}", line)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let last_active = summary.last_active.format("%Y-%m-%d").to_string();
+        let content = format!(
+            "---\n\
+type: screen-analyzer-project\n\
+project: {project}\n\
+total_minutes: {total_minutes}\n\
+session_count: {session_count}\n\
+last_active: {last_active}\n\
+source: screen-analyzer\n\
+---\n\
+\n\
+# {title}\n\
+\n\
+{overview}\n\
+\n\
+## {trend_heading}\n\
+`{sparkline}`\n\
+\n\
+{week_header}\n\
+| --- | --- |\n\
+{week_rows}\n\
+\n\
+## {recent_heading}\n\
+{recent_sessions}\n",
+            project = yaml_quote(&summary.name),
+            total_minutes = summary.total_minutes,
+            session_count = summary.session_count,
+            last_active = last_active,
+            title = fill(text.project_title, &[("name", summary.name.clone())]),
+            overview = fill(
+                text.project_overview_lines,
+                &[
+                    ("minutes", summary.total_minutes.to_string()),
+                    ("sessions", summary.session_count.to_string()),
+                    ("last_active", last_active.clone()),
+                ],
+            ),
+            trend_heading = fill(
+                text.project_trend,
+                &[("weeks", summary.weekly_minutes.len().to_string())]
+            ),
+            sparkline = sparkline,
+            week_header = text.project_week_header,
+            week_rows = week_rows,
+            recent_heading = text.project_sessions,
+            recent_sessions = recent_sessions,
         );
-        lines.push(line);
+        self.append_footer(content, &last_active)
     }
-    lines.join("\n")
-}
-
-/// 会话指标
-struct SessionMetrics {
-    timeline_cards: usize,
-    context_switches: usize,
-    avg_segment_minutes: i64,
-    fragmentation_level: String,
-}
-
-#[derive(Default)]
-struct WeekFocusMetrics {
-    total_minutes: i64,
-    work_minutes: i64,
-    learning_minutes: i64,
-    communication_minutes: i64,
-    personal_minutes: i64,
-    idle_minutes: i64,
-    other_minutes: i64,
-}
-
-struct WeekSummaryData {
-    week_label: String,
-    week_start: String,
-    week_end: String,
-    total_sessions: i32,
-    total_minutes: i32,
-    avg_session_minutes: i32,
-    top_categories: String,
-    table_lines: Vec<String>,
-    focus_metrics: WeekFocusMetrics,
-    score_config: WeekScoreConfig,
-    daily_highlights: Vec<String>,
-}
 
-struct WeekScoreConfig {
-    focus_weight: i64,
-    effort_weight: i64,
-    target_minutes: i64,
-}
+    async fn export_index_file(&self, path: &Path, content: String) -> Result<PathBuf> {
+        if let Some(parent) = path.parent() {
+            self.ensure_dir(parent).await?;
+        }
+        self.write_note(path, content).await?;
+        Ok(path.to_path_buf())
+    }
 
-impl WeekFocusMetrics {
-    fn add_cards(&mut self, cards: &[TimelineCardRecord]) {
-        for card in cards {
-            self.add_card(card);
+    /// 创建目录（预览模式下跳过）
+    async fn ensure_dir(&self, dir: &Path) -> Result<()> {
+        if self.dry_run.is_none() {
+            fs::create_dir_all(dir).await?;
         }
+        Ok(())
     }
 
-    fn add_card(&mut self, card: &TimelineCardRecord) {
-        let minutes = parse_card_minutes(card);
-        if minutes <= 0 {
-            return;
+    /// 写入笔记；增量同步模式下内容未变化则跳过写入，预览模式下仅记录
+    async fn write_note(&self, path: &Path, content: String) -> Result<()> {
+        if let Some(previews) = &self.dry_run {
+            let would_overwrite = match fs::read_to_string(path).await {
+                Ok(existing) => !note_unchanged(&existing, &content),
+                Err(_) => false,
+            };
+            previews
+                .lock()
+                .map_err(|_| anyhow!("预览记录锁已损坏"))?
+                .push(NotePreview {
+                    path: path.to_path_buf(),
+                    content,
+                    would_overwrite,
+                });
+            return Ok(());
         }
-        self.total_minutes += minutes;
-
-        match normalize_timeline_category(&card.category) {
-            ActivityCategory::Work => self.work_minutes += minutes,
-            ActivityCategory::Learning => self.learning_minutes += minutes,
-            ActivityCategory::Communication => self.communication_minutes += minutes,
-            ActivityCategory::Personal => self.personal_minutes += minutes,
-            ActivityCategory::Idle => self.idle_minutes += minutes,
-            ActivityCategory::Other => self.other_minutes += minutes,
+        if self.config.incremental_sync {
+            if let Ok(existing) = fs::read_to_string(path).await {
+                if note_unchanged(&existing, &content) {
+                    self.skipped_files.fetch_add(1, Ordering::Relaxed);
+                    return Ok(());
+                }
+            }
         }
+        fs::write(path, content).await?;
+        Ok(())
     }
 
-    fn focus_minutes(&self) -> i64 {
-        self.work_minutes + self.learning_minutes
+    /// 按冲突策略写入笔记，返回实际写入（或保留）的文件路径
+    ///
+    /// 文件不存在或内容未变化时不算冲突，按普通写入处理；合并策略下新文件同样包裹标记块。
+    async fn write_note_with_policy(
+        &self,
+        path: &Path,
+        content: String,
+        policy: ExportConflictPolicy,
+    ) -> Result<PathBuf> {
+        let existing = fs::read_to_string(path).await.ok();
+        let content = if policy == ExportConflictPolicy::Merge {
+            merge_daily_note(existing.as_deref(), &content)
+        } else {
+            content
+        };
+        let conflicting = existing
+            .as_deref()
+            .is_some_and(|existing| !note_unchanged(existing, &content));
+        if !conflicting {
+            self.write_note(path, content).await?;
+            return Ok(path.to_path_buf());
+        }
+
+        let path = match policy {
+            ExportConflictPolicy::Overwrite => {
+                self.write_note(path, content).await?;
+                self.record_conflict(|counts| counts.overwritten += 1)?;
+                path.to_path_buf()
+            }
+            ExportConflictPolicy::Skip => {
+                self.record_conflict(|counts| counts.skipped += 1)?;
+                path.to_path_buf()
+            }
+            ExportConflictPolicy::Merge => {
+                self.write_note(path, content).await?;
+                self.record_conflict(|counts| counts.merged += 1)?;
+                path.to_path_buf()
+            }
+            ExportConflictPolicy::NewVersion => {
+                // 复用内容相同的已有版本，避免每次导出都新增文件
+                let mut version = 2;
+                loop {
+                    let candidate = versioned_path(path, version);
+                    match fs::read_to_string(&candidate).await {
+                        Ok(existing) if note_unchanged(&existing, &content) => {
+                            self.write_note(&candidate, content).await?;
+                            break candidate;
+                        }
+                        Ok(_) => version += 1,
+                        Err(_) => {
+                            self.write_note(&candidate, content).await?;
+                            self.record_conflict(|counts| counts.versioned += 1)?;
+                            break candidate;
+                        }
+                    }
+                }
+            }
+        };
+        Ok(path)
     }
 
-    fn distraction_minutes(&self) -> i64 {
-        self.personal_minutes + self.idle_minutes + self.other_minutes
+    fn conflict_counts(&self) -> ConflictCounts {
+        self.conflicts
+            .lock()
+            .map(|counts| *counts)
+            .unwrap_or_default()
     }
 
-    fn focus_ratio(&self) -> i64 {
-        if self.total_minutes == 0 {
-            0
-        } else {
-            (self.focus_minutes() * 100 / self.total_minutes).max(0)
-        }
+    fn record_conflict(&self, update: impl FnOnce(&mut ConflictCounts)) -> Result<()> {
+        let mut counts = self
+            .conflicts
+            .lock()
+            .map_err(|_| anyhow!("冲突计数锁已损坏"))?;
+        update(&mut counts);
+        Ok(())
     }
 
-    fn distraction_ratio(&self) -> i64 {
-        if self.total_minutes == 0 {
-            0
-        } else {
-            (self.distraction_minutes() * 100 / self.total_minutes).max(0)
-        }
+    /// 按配置的自定义指标计算（名称，取值）
+    fn custom_metric_values(&self, metrics: &WeekFocusMetrics) -> Vec<(String, Option<f64>)> {
+        crate::custom_metrics::evaluate_all(
+            &self.config.custom_metrics,
+            &metrics.metric_variables(),
+        )
     }
 
-    fn effort_score(&self, target_minutes: i64) -> i64 {
-        if self.total_minutes == 0 || target_minutes <= 0 {
-            return 0;
-        }
-        let score = self.total_minutes * 100 / target_minutes;
-        score.min(100).max(0)
+    /// 从 `week_start` 开始的一周的汇总（用于对比与基准）
+    async fn week_baseline(&self, db: &Database, week_start: NaiveDate) -> Result<WeekBaseline> {
+        let week_end = week_start + chrono::Duration::days(6);
+        let activities = self
+            .activities(
+                db,
+                &week_start.format("%Y-%m-%d").to_string(),
+                &week_end.format("%Y-%m-%d").to_string(),
+            )
+            .await?;
+        let focus_metrics = self
+            .compute_week_focus_metrics(db, week_start, week_end)
+            .await;
+        let iso_week = week_start.iso_week();
+
+        Ok(WeekBaseline {
+            week_label: format!("{:04}-W{:02}", iso_week.year(), iso_week.week()),
+            total_sessions: activities.iter().map(|a| a.session_count).sum(),
+            total_minutes: activities.iter().map(|a| a.total_duration_minutes).sum(),
+            focus_metrics,
+        })
     }
 
-    fn focus_score(&self) -> i64 {
-        self.focus_ratio()
+    /// 日期范围内各设备的专注度（按导出时区的日期划分，设置了设备过滤时只有该设备）
+    async fn week_device_focus(
+        &self,
+        db: &Database,
+        week_start: NaiveDate,
+        week_end: NaiveDate,
+    ) -> Vec<DeviceFocusStat> {
+        let mut sessions = Vec::new();
+        let mut cards = Vec::new();
+        for day in week_start.iter_days().take_while(|day| *day <= week_end) {
+            if let Ok(day_sessions) = self
+                .sessions_on(db, &day.format("%Y-%m-%d").to_string())
+                .await
+            {
+                cards.extend(collect_day_cards(db, &day_sessions).await);
+                sessions.extend(day_sessions);
+            }
+        }
+        device_focus_from_cards(&sessions, &cards)
     }
 
-    fn productivity_score(
+    async fn compute_week_focus_metrics(
         &self,
-        focus_weight: i64,
-        effort_weight: i64,
-        target_minutes: i64,
-    ) -> i64 {
-        let total_weight = (focus_weight + effort_weight).max(1);
-        let focus_score = self.focus_score();
-        let effort_score = self.effort_score(target_minutes);
-        (focus_score * focus_weight + effort_score * effort_weight) / total_weight
+        db: &Database,
+        week_start: NaiveDate,
+        week_end: NaiveDate,
+    ) -> WeekFocusMetrics {
+        let mut metrics = WeekFocusMetrics::default();
+        // 未设置导出时区时导出日期即存储日期，可直接汇总 daily_focus_metrics 缓存；
+        // 缓存缺失的日期现场统计并回填。缓存按全部设备统计，设置了设备过滤时不使用
+        let use_cache = self.clock().offset.is_none() && self.device_filter().is_none();
+        let cached: HashMap<NaiveDate, DailyFocusMetricsRecord> = if use_cache {
+            db.get_daily_focus_metrics(
+                &week_start.format("%Y-%m-%d").to_string(),
+                &week_end.format("%Y-%m-%d").to_string(),
+            )
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|record| (record.date, record))
+            .collect()
+        } else {
+            HashMap::new()
+        };
+
+        for day in week_start.iter_days().take_while(|day| *day <= week_end) {
+            if let Some(record) = cached.get(&day) {
+                metrics.add_record(record);
+                continue;
+            }
+            let date = day.format("%Y-%m-%d").to_string();
+            if let Ok(sessions) = self.sessions_on(db, &date).await {
+                let record = focus_metrics_from_cards(day, &collect_day_cards(db, &sessions).await);
+                metrics.add_record(&record);
+                if use_cache && !sessions.is_empty() {
+                    let _ = db.save_daily_focus_metrics(&date, &record).await;
+                }
+            }
+        }
+
+        metrics
     }
 }
 
-fn build_session_metrics(cards: &[TimelineCardRecord], duration_minutes: i64) -> SessionMetrics {
-    let timeline_cards = cards.len();
-    let context_switches = count_context_switches(cards);
-    let avg_segment_minutes = if timeline_cards == 0 {
-        0
-    } else {
-        (duration_minutes / timeline_cards as i64).max(0)
+/// 合并每日笔记：frontmatter 按键合并（生成的键覆盖同名键，用户自定义键保留），
+/// 正文仅替换标记块；旧版生成的笔记（无标记但来源为 screen-analyzer）整体替换
+///
+/// 生成来源页脚不放进标记块，而是紧跟在 `MANAGED_END` 之后，合并时随标记块一起替换
+fn merge_daily_note(existing: Option<&str>, rendered: &str) -> String {
+    let (rendered_frontmatter, rendered_body) = split_frontmatter(rendered);
+    let (rendered_body, footer) = split_footer(rendered_body);
+    let managed = format!(
+        "{}\n{}\n{}\n{}",
+        MANAGED_START,
+        rendered_body.trim_matches('\n'),
+        MANAGED_END,
+        footer
+    );
+
+    let Some(existing) = existing else {
+        return join_frontmatter(rendered_frontmatter.map(str::to_string), &managed);
     };
-    let fragmentation_level = match context_switches {
-        0..=1 => "低",
-        2..=3 => "中",
-        _ => "高",
-    }
-    .to_string();
 
-    SessionMetrics {
-        timeline_cards,
-        context_switches,
-        avg_segment_minutes,
-        fragmentation_level,
-    }
-}
+    let (existing_frontmatter, existing_body) = split_frontmatter(existing);
+    let frontmatter = merge_frontmatter(existing_frontmatter, rendered_frontmatter);
+
+    let markers = existing_body.find(MANAGED_START).and_then(|start| {
+        existing_body[start..]
+            .find(MANAGED_END)
+            .map(|offset| (start, start + offset + MANAGED_END.len()))
+    });
+    let body = match markers {
+        Some((start, end)) => {
+            let after = existing_body[end..]
+                .strip_prefix('\n')
+                .unwrap_or(&existing_body[end..]);
+            let after = split_footer_prefix(after);
+            format!("{}{}{}", &existing_body[..start], managed, after)
+        }
+        None if existing_frontmatter
+            .map(|frontmatter| frontmatter.contains("source: screen-analyzer"))
+            .unwrap_or(false) =>
+        {
+            managed
+        }
+        None => format!("{}\n{}", managed, existing_body.trim_start_matches('\n')),
+    };
 
-fn render_metrics(metrics: &SessionMetrics) -> String {
-    if metrics.timeline_cards == 0 {
-        return "暂无指标".to_string();
-    }
+    join_frontmatter(frontmatter, &body)
+}
 
-    format!(
-        "- 片段数量: {}\n- 上下文切换: {}\n- 平均片段时长: {} 分钟\n- 碎片化等级: {}",
-        metrics.timeline_cards,
-        metrics.context_switches,
-        metrics.avg_segment_minutes,
-        metrics.fragmentation_level
-    )
+/// 判断是否为 `append_footer` 生成的页脚行（各语言文案均包含应用名与版本前缀）
+fn is_footer_line(line: &str) -> bool {
+    line.contains("Screen Analyzer v")
 }
 
-fn render_week_focus_metrics(metrics: &WeekFocusMetrics, score: &WeekScoreConfig) -> String {
-    if metrics.total_minutes == 0 {
-        return "暂无可用专注度数据".to_string();
+/// 拆分正文末尾的生成来源页脚，返回（正文，页脚）；无页脚时页脚为空串
+fn split_footer(body: &str) -> (&str, &str) {
+    if let Some(start) = body.rfind(FOOTER_SEPARATOR) {
+        let line = body[start + FOOTER_SEPARATOR.len()..].trim_end_matches('\n');
+        if !line.contains('\n') && is_footer_line(line) {
+            return (&body[..start], &body[start..]);
+        }
     }
+    (body, "")
+}
 
-    let focus_score = metrics.focus_score();
-    let effort_score = metrics.effort_score(score.target_minutes);
-    let productivity_score =
-        metrics.productivity_score(score.focus_weight, score.effort_weight, score.target_minutes);
-
-    format!(
-        "- 专注时长: {} 分钟 ({}%)\n- 沟通时长: {} 分钟\n- 分心时长: {} 分钟 ({}%)\n- 专注评分: {} / 100\n- 投入时长评分: {} / 100（目标 {} 分钟）\n- 生产力评分: {} / 100（权重 {}% / {}%）\n- 细分: 工作 {} / 学习 {} / 个人 {} / 空闲 {} / 其他 {}",
-        metrics.focus_minutes(),
-        metrics.focus_ratio(),
-        metrics.communication_minutes,
-        metrics.distraction_minutes(),
-        metrics.distraction_ratio(),
-        focus_score,
-        effort_score,
-        score.target_minutes,
-        productivity_score,
-        score.focus_weight,
-        score.effort_weight,
-        metrics.work_minutes,
-        metrics.learning_minutes,
-        metrics.personal_minutes,
-        metrics.idle_minutes,
-        metrics.other_minutes
-    )
+/// 去掉紧跟在标记块之后的旧页脚，返回其后的用户内容
+fn split_footer_prefix(text: &str) -> &str {
+    if let Some(rest) = text.strip_prefix(FOOTER_SEPARATOR) {
+        let (line, after) = rest.split_once('\n').unwrap_or((rest, ""));
+        if is_footer_line(line) {
+            return after;
+        }
+    }
+    text
 }
 
-fn build_week_insights(summary: &WeekSummaryData) -> Vec<String> {
-    let mut insights = Vec::new();
-    let focus_ratio = summary.focus_metrics.focus_ratio();
-    let productivity_score = summary.focus_metrics.productivity_score(
-        summary.score_config.focus_weight,
-        summary.score_config.effort_weight,
-        summary.score_config.target_minutes,
-    );
-    let total_minutes = summary.total_minutes;
-    let avg_session_minutes = summary.avg_session_minutes;
+/// 拆分 frontmatter（不含 `---` 分隔行）与正文
+fn split_frontmatter(text: &str) -> (Option<&str>, &str) {
+    if let Some(rest) = text.strip_prefix("---\n") {
+        if let Some(end) = rest.find("\n---\n") {
+            return (Some(&rest[..end + 1]), &rest[end + 5..]);
+        }
+        if let Some(frontmatter) = rest.strip_suffix("\n---") {
+            return (Some(frontmatter), "");
+        }
+    }
+    (None, text)
+}
 
-    if focus_ratio >= 70 {
-        insights.push("本周专注度较高，建议保持当前节奏".to_string());
-    } else if focus_ratio <= 40 {
-        insights.push("本周专注度偏低，建议减少高干扰活动".to_string());
-    } else {
-        insights.push("本周专注度处于中等水平，可优化任务切换".to_string());
+/// 按顶层键合并 frontmatter，缩进行或列表项归属上一个键
+fn merge_frontmatter(existing: Option<&str>, rendered: Option<&str>) -> Option<String> {
+    fn parse(text: &str) -> Vec<(String, String)> {
+        let mut entries: Vec<(String, String)> = Vec::new();
+        for line in text.lines() {
+            let is_continuation = line.starts_with(' ') || line.starts_with('-');
+            match entries.last_mut() {
+                Some((_, block)) if is_continuation => {
+                    block.push('\n');
+                    block.push_str(line);
+                }
+                _ => {
+                    let key = line.split(':').next().unwrap_or("").trim().to_string();
+                    entries.push((key, line.to_string()));
+                }
+            }
+        }
+        entries
     }
 
-    if productivity_score >= 70 {
-        insights.push("生产力评分较高，投入与产出较为平衡".to_string());
-    } else if productivity_score <= 40 {
-        insights.push("生产力评分偏低，需关注投入时长与专注占比".to_string());
+    let mut entries = parse(existing.unwrap_or(""));
+    for (key, block) in parse(rendered.unwrap_or("")) {
+        match entries
+            .iter_mut()
+            .find(|(existing_key, _)| *existing_key == key)
+        {
+            // 别名取并集，保留用户在日记插件中维护的别名
+            Some(entry) if key == "aliases" => {
+                let mut aliases = parse_yaml_list(&entry.1);
+                for alias in parse_yaml_list(&block) {
+                    if !aliases.contains(&alias) {
+                        aliases.push(alias);
+                    }
+                }
+                entry.1 = format!("aliases: {}", format_yaml_list(&aliases));
+            }
+            Some(entry) => entry.1 = block,
+            None => entries.push((key, block)),
+        }
     }
 
-    if total_minutes < 300 {
-        insights.push("本周投入时长偏少，可能处于低负荷状态".to_string());
-    } else if total_minutes >= 1200 {
-        insights.push("本周投入时长较高，注意避免过度疲劳".to_string());
+    if entries.is_empty() {
+        None
+    } else {
+        Some(entries.into_iter().map(|(_, block)| block + "\n").collect())
     }
+}
 
-    if avg_session_minutes < 20 {
-        insights.push("平均会话较短，存在碎片化倾向".to_string());
-    } else if avg_session_minutes >= 60 {
-        insights.push("平均会话较长，体现深度工作趋势".to_string());
+/// 每日笔记别名：ISO 日期与本地化完整日期（去重）
+fn daily_aliases(date_values: &[(&str, String)]) -> String {
+    let mut aliases: Vec<String> = Vec::new();
+    for (key, value) in date_values {
+        if matches!(*key, "date" | "long_date") && !value.is_empty() && !aliases.contains(value) {
+            aliases.push(value.clone());
+        }
     }
+    format_yaml_list(&aliases)
+}
 
-    insights
+/// 输出 YAML 行内列表，元素一律加双引号（完整日期中可能含逗号）
+fn format_yaml_list(items: &[String]) -> String {
+    let quoted = items
+        .iter()
+        .map(|item| yaml_quote(item))
+        .collect::<Vec<_>>();
+    format!("[{}]", quoted.join(", "))
 }
 
-fn count_context_switches(cards: &[TimelineCardRecord]) -> usize {
-    let mut switches = 0usize;
-    let mut last_category: Option<String> = None;
+/// 双引号 YAML 字符串（转义反斜杠与引号）
+fn yaml_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
 
-    for card in cards {
-        let category = card.category.to_lowercase();
-        if let Some(last) = &last_category {
-            if last != &category {
-                switches += 1;
-            }
+/// 解析 frontmatter 中的列表值，兼容行内 `[a, "b"]`、单个标量与 `- a` 块列表
+fn parse_yaml_list(block: &str) -> Vec<String> {
+    fn unquote(raw: &str) -> String {
+        let raw = raw.trim();
+        if let Some(inner) = raw.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            inner.replace("\\\"", "\"").replace("\\\\", "\\")
+        } else if let Some(inner) = raw.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+            inner.replace("''", "'")
+        } else {
+            raw.to_string()
         }
-        last_category = Some(category);
     }
 
-    switches
-}
-
-fn compact_summary_text(text: &str, max_len: usize) -> String {
-    let cleaned = text.replace('\n', " ").replace('\r', " ");
-    if cleaned.chars().count() <= max_len {
-        return cleaned;
+    let rest = block.split_once(':').map(|(_, rest)| rest).unwrap_or("");
+    let mut lines = rest.lines();
+    let first = lines.next().unwrap_or("").trim();
+    let mut items = Vec::new();
+    if let Some(inner) = first.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        let mut current = String::new();
+        let mut quote: Option<char> = None;
+        let mut escaped = false;
+        for c in inner.chars() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if quote == Some('"') => escaped = true,
+                '"' | '\'' if quote.is_none() => quote = Some(c),
+                _ if quote == Some(c) => quote = None,
+                ',' if quote.is_none() => {
+                    items.push(unquote(&current));
+                    current.clear();
+                    continue;
+                }
+                _ => {}
+            }
+            current.push(c);
+        }
+        items.push(unquote(&current));
+    } else if !first.is_empty() {
+        items.push(unquote(first));
     }
-    let truncated: String = cleaned.chars().take(max_len).collect();
-    format!("{}...", truncated)
+    for line in lines {
+        if let Some(item) = line.trim().strip_prefix('-') {
+            items.push(unquote(item));
+        }
+    }
+    items.retain(|item| !item.is_empty());
+    items
 }
 
-fn normalize_timeline_category(raw: &str) -> ActivityCategory {
-    match raw.to_lowercase().as_str() {
-        "work" => ActivityCategory::Work,
-        "communication" | "meeting" => ActivityCategory::Communication,
-        "learning" | "research" => ActivityCategory::Learning,
-        "personal" => ActivityCategory::Personal,
-        "idle" | "break" => ActivityCategory::Idle,
-        _ => ActivityCategory::Other,
+fn join_frontmatter(frontmatter: Option<String>, body: &str) -> String {
+    match frontmatter {
+        Some(frontmatter) => format!(
+            "---\n{}---\n\n{}",
+            frontmatter,
+            body.trim_start_matches('\n')
+        ),
+        None => body.to_string(),
     }
 }
 
-fn parse_card_minutes(card: &TimelineCardRecord) -> i64 {
-    let start = chrono::DateTime::parse_from_rfc3339(&card.start_time).ok();
-    let end = chrono::DateTime::parse_from_rfc3339(&card.end_time).ok();
-    match (start, end) {
-        (Some(s), Some(e)) => (e - s).num_minutes().max(0),
-        _ => 0,
+/// 按分组渲染会话索引（分组按首次出现顺序，未分组放最后）
+fn render_grouped_sessions(text: &NoteStrings, entries: &[SessionEntry]) -> String {
+    let mut groups: Vec<(&str, Vec<&str>)> = Vec::new();
+    let mut ungrouped: Vec<&str> = Vec::new();
+    for entry in entries {
+        match entry.group.as_deref() {
+            Some(name) => match groups.iter_mut().find(|(group, _)| *group == name) {
+                Some((_, links)) => links.push(&entry.link),
+                None => groups.push((name, vec![&entry.link])),
+            },
+            None => ungrouped.push(&entry.link),
+        }
+    }
+    if !ungrouped.is_empty() {
+        groups.push((text.ungrouped, ungrouped));
     }
+
+    groups
+        .iter()
+        .map(|(name, links)| {
+            let items = links
+                .iter()
+                .map(|link| format!("- {}", link))
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!("### {}\n{}", name, items)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
 }
 
-fn format_time_range(start: &str, end: &str) -> (String, String) {
-    let format = |value: &str| -> String {
-        chrono::DateTime::parse_from_rfc3339(value)
-            .map(|dt| dt.format("%H:%M").to_string())
-            .unwrap_or_else(|_| value.to_string())
+/// 按正则从会话标题中提取项目名（取第一个捕获组）
+fn extract_project(title: &str, pattern: &str) -> Option<String> {
+    let regex = match regex::Regex::new(pattern) {
+        Ok(regex) => regex,
+        Err(err) => {
+            tracing::warn!("项目提取规则无效: {} ({})", pattern, err);
+            return None;
+        }
     };
-    (format(start), format(end))
+    regex
+        .captures(title)
+        .and_then(|caps| caps.get(1).or_else(|| caps.get(0)))
+        .map(|m| m.as_str().trim().to_string())
+        .filter(|name| !name.is_empty())
 }
 
-fn parse_tags(raw: &str) -> Vec<ActivityTag> {
-    serde_json::from_str::<Vec<ActivityTag>>(raw).unwrap_or_default()
+/// 读取指定会话，读取失败的会话记为导出失败
+async fn load_sessions(
+    db: &Database,
+    session_ids: &[i64],
+) -> (Vec<Session>, Vec<SessionExportError>) {
+    let mut sessions = Vec::new();
+    let mut failed_sessions = Vec::new();
+    for &session_id in session_ids {
+        match db.get_session(session_id).await {
+            Ok(session) => sessions.push(session),
+            Err(e) => failed_sessions.push(SessionExportError {
+                session_id,
+                message: e.to_string(),
+            }),
+        }
+    }
+    (sessions, failed_sessions)
 }
 
-fn format_tags(tags: &[ActivityTag]) -> String {
-    if tags.is_empty() {
-        return "[]".to_string();
+/// 将缺失的会话链接补充到每日总结的会话索引中
+async fn update_daily_session_links(
+    text: &NoteStrings,
+    path: &Path,
+    links: &[String],
+) -> Result<()> {
+    let content = fs::read_to_string(path).await?;
+    let missing: Vec<&String> = links
+        .iter()
+        .filter(|link| !content.contains(link.as_str()))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
     }
 
-    let values = tags
+    fs::write(path, insert_session_links(text, &content, &missing)).await?;
+    Ok(())
+}
+
+fn insert_session_links(text: &NoteStrings, content: &str, links: &[&String]) -> String {
+    let session_header = format!("## {}\n", text.session_index);
+    let empty_placeholder = format!("- {}\n", text.no_sessions);
+
+    let new_lines = links
         .iter()
-        .map(|tag| category_to_string(&tag.category))
+        .map(|link| format!("- {}\n", link))
+        .collect::<String>();
+
+    // 同步 frontmatter 中的会话数量
+    let content = content
+        .lines()
+        .map(|line| match line.strip_prefix("session_count: ") {
+            Some(count) => match count.trim().parse::<usize>() {
+                Ok(count) => format!("session_count: {}", count + links.len()),
+                Err(_) => line.to_string(),
+            },
+            None => line.to_string(),
+        })
         .collect::<Vec<_>>()
-        .join(", ");
-    format!("[{}]", values)
+        .join("\n")
+        + "\n";
+
+    match content.find(&session_header) {
+        Some(pos) => {
+            let insert_at = pos + session_header.len();
+            let rest = &content[insert_at..];
+            let rest = rest
+                .strip_prefix(empty_placeholder.as_str())
+                .unwrap_or(rest);
+            format!("{}{}{}", &content[..insert_at], new_lines, rest)
+        }
+        None => format!("{}\n\n{}{}", content.trim_end(), session_header, new_lines),
+    }
 }
 
-fn category_to_string(category: &ActivityCategory) -> &'static str {
-    match category {
-        ActivityCategory::Work => "work",
-        ActivityCategory::Communication => "communication",
-        ActivityCategory::Learning => "learning",
-        ActivityCategory::Personal => "personal",
-        ActivityCategory::Idle => "idle",
-        ActivityCategory::Other => "other",
-    }
+/// 导出时间换算
+///
+/// 会话与截图时间以本地时间值存储（见 `storage::local_now`），时间线卡片为带时区的 RFC3339。
+/// 未配置导出时区时原样使用记录时的本地时间，否则统一换算到配置的时区。
+#[derive(Debug, Clone, Copy, Default)]
+struct ExportClock {
+    offset: Option<FixedOffset>,
 }
 
-fn pick_screenshots(frames: &[Frame]) -> Vec<Frame> {
-    if frames.is_empty() {
-        return Vec::new();
-    }
-    if frames.len() == 1 {
-        return vec![frames[0].clone()];
+impl ExportClock {
+    /// 数据库中存储的本地时间值 -> 导出时区下的时间
+    fn session_time(&self, stored: DateTime<Utc>) -> NaiveDateTime {
+        let naive = stored.naive_utc();
+        match self.offset {
+            None => naive,
+            Some(offset) => Local
+                .from_local_datetime(&naive)
+                .earliest()
+                .map(|local| local.with_timezone(&offset).naive_local())
+                .unwrap_or(naive),
+        }
     }
 
-    let first = frames.first().cloned();
-    let last = frames.last().cloned();
-
-    let mut selected = Vec::new();
-    if let Some(frame) = first {
-        selected.push(frame);
-    }
-    if let Some(frame) = last {
-        if selected
-            .last()
-            .map(|f| f.file_path != frame.file_path)
-            .unwrap_or(true)
-        {
-            selected.push(frame);
+    /// 带时区的时间线卡片时间 -> 导出时区下的时间
+    fn card_time(&self, time: DateTime<FixedOffset>) -> NaiveDateTime {
+        match self.offset {
+            None => time.naive_local(),
+            Some(offset) => time.with_timezone(&offset).naive_local(),
         }
     }
-    selected
+
+    fn now(&self) -> NaiveDateTime {
+        self.session_time(crate::storage::local_now())
+    }
 }
 
-fn sanitize_filename(raw: &str) -> String {
+fn format_time(clock: ExportClock, dt: DateTime<Utc>) -> String {
+    clock.session_time(dt).format("%H:%M").to_string()
+}
+
+/// 卡片标题，本地启发式分类生成的卡片加注后缀
+fn card_title(text: &NoteStrings, card: &TimelineCardRecord) -> String {
+    if card.heuristic {
+        format!("{}{}", card.title.trim(), text.heuristic_card)
+    } else {
+        card.title.trim().to_string()
+    }
+}
+
+fn render_timeline(text: &NoteStrings, clock: ExportClock, cards: &[TimelineCardRecord]) -> String {
+    if cards.is_empty() {
+        return format!("- {}", text.no_timeline);
+    }
+
+    let mut lines = Vec::new();
+    for card in cards {
+        let (start, end) = format_time_range(clock, &card.start_time, &card.end_time);
+        let line = format!(
+            "- {}-{} [{} / {}] {}：{}",
+            start,
+            end,
+            card.category,
+            card.subcategory,
+            card_title(text, card),
+            card.summary
+        );
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// 以 Obsidian callout 渲染时间线，每张卡片一个 callout，按类别映射 callout 类型
+fn render_timeline_callouts(
+    text: &NoteStrings,
+    clock: ExportClock,
+    cards: &[TimelineCardRecord],
+    callouts: &HashMap<String, String>,
+) -> String {
+    if cards.is_empty() {
+        return format!("- {}", text.no_timeline);
+    }
+
+    cards
+        .iter()
+        .map(|card| {
+            let (start, end) = format_time_range(clock, &card.start_time, &card.end_time);
+            let mut lines = vec![format!(
+                "> [!{}] {}-{} {}",
+                callout_type(&card.category, callouts),
+                start,
+                end,
+                card_title(text, card)
+            )];
+            let category = [card.category.trim(), card.subcategory.trim()]
+                .into_iter()
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(" / ");
+            if !category.is_empty() {
+                lines.push(format!("> {}", category));
+            }
+            lines.extend(
+                card.summary
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(|line| format!("> {}", line.trim_end())),
+            );
+            lines.join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// 类别对应的 callout 类型：优先使用映射，否则为类别 slug，均为空时为 `note`
+fn callout_type(category: &str, callouts: &HashMap<String, String>) -> String {
+    let key = category.trim().to_lowercase();
+    let mapped = callouts
+        .iter()
+        .find(|(alias, _)| alias.trim().to_lowercase() == key)
+        .map(|(_, callout)| tag_slug(callout))
+        .filter(|callout| !callout.is_empty());
+    mapped
+        .or_else(|| Some(tag_slug(category)).filter(|slug| !slug.is_empty()))
+        .unwrap_or_else(|| "note".to_string())
+}
+
+/// 0-100 的分值映射为文本迷你图字符
+fn sparkline_char(score: i64) -> char {
+    const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    LEVELS[(score.clamp(0, 100) as usize * (LEVELS.len() - 1) + 50) / 100]
+}
+
+/// 与会话时间重叠的专注时段，每行一个时段，附干扰应用
+fn render_focus_blocks(
+    text: &NoteStrings,
+    clock: ExportClock,
+    blocks: &[FocusBlockRecord],
+    session: &Session,
+) -> String {
+    blocks
+        .iter()
+        .filter(|block| crate::capture::focus::block_end(block) > session.start_time)
+        .map(|block| {
+            let status = match block.status.as_str() {
+                "running" if block.ended_at.is_none() => text.focus_statuses[0],
+                "completed" => text.focus_statuses[1],
+                _ => text.focus_statuses[2],
+            };
+            let label = if block.label.trim().is_empty() {
+                text.focus_untitled.to_string()
+            } else {
+                block.label.clone()
+            };
+            let mut line = fill(
+                text.focus_block_line,
+                &[
+                    ("start", format_time(clock, block.started_at)),
+                    (
+                        "end",
+                        format_time(clock, crate::capture::focus::block_end(block)),
+                    ),
+                    ("label", label),
+                    ("planned", block.planned_minutes.to_string()),
+                    ("status", status.to_string()),
+                    ("count", block.distraction_count.to_string()),
+                ],
+            );
+
+            let mut apps: Vec<String> = Vec::new();
+            for distraction in crate::capture::focus::distractions(block) {
+                let app = distraction.app.unwrap_or(distraction.category);
+                if !apps.contains(&app) {
+                    apps.push(app);
+                }
+            }
+            if !apps.is_empty() {
+                line.push_str(&fill(
+                    text.focus_distraction_apps,
+                    &[("apps", apps.join(", "))],
+                ));
+            }
+            format!("- {}", line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 当天生效目标的达成情况，每行一个目标（进行中且未达成的标记为 ⏳）
+fn render_goals(text: &NoteStrings, goals: &[GoalStatus]) -> String {
+    goals
+        .iter()
+        .filter(|status| status.scheduled)
+        .map(|status| {
+            let icon = if status.passed {
+                "✅"
+            } else if status.in_progress {
+                "⏳"
+            } else {
+                "❌"
+            };
+            let mut line = fill(
+                text.goal_line,
+                &[
+                    ("status", icon.to_string()),
+                    ("name", status.goal.name.clone()),
+                    ("actual", status.actual_minutes.to_string()),
+                    ("comparison", comparison_symbol(&status.goal).to_string()),
+                    ("target", status.goal.target_minutes.to_string()),
+                ],
+            );
+            if status.streak > 0 {
+                line.push_str(&fill(
+                    text.goal_streak,
+                    &[("days", status.streak.to_string())],
+                ));
+            }
+            format!("- {}", line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 本周目标达成表格；没有启用的目标时返回 None
+fn render_week_goals(text: &NoteStrings, goals: &[GoalWeekStatus]) -> Option<String> {
+    if goals.is_empty() {
+        return None;
+    }
+    let mut lines = vec![
+        text.goal_table_header.to_string(),
+        "| --- | --- | --- | --- |".to_string(),
+    ];
+    for status in goals {
+        lines.push(format!(
+            "| {} | {} {} | {}/{} | {} |",
+            status.goal.name,
+            comparison_symbol(&status.goal),
+            status.goal.target_minutes,
+            status.passed_days,
+            status.scheduled_days,
+            status.streak
+        ));
+    }
+    Some(lines.join("\n"))
+}
+
+/// 本周各设备的专注时长与占比，每台设备一行；少于两台设备时返回 None
+fn render_week_devices(text: &NoteStrings, devices: &[DeviceFocusStat]) -> Option<String> {
+    if devices.len() < 2 {
+        return None;
+    }
+    let lines: Vec<String> = devices
+        .iter()
+        .map(|device| {
+            fill(
+                text.device_focus_line,
+                &[
+                    ("name", device.name.clone()),
+                    ("focus", device.focus_minutes().to_string()),
+                    ("ratio", device.focus_ratio().to_string()),
+                    ("total", device.total_minutes.to_string()),
+                    ("sessions", device.session_count.to_string()),
+                ],
+            )
+        })
+        .collect();
+    Some(lines.join("\n"))
+}
+
+/// 本周出现的过劳迹象，每条一行；没有迹象时返回 None
+fn render_week_wellbeing(
+    text: &NoteStrings,
+    report: &WellbeingReport,
+    config: &WellbeingConfig,
+) -> Option<String> {
+    if report.warnings.is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = report
+        .warnings
+        .iter()
+        .map(|warning| match warning {
+            WellbeingWarning::LongDay {
+                date,
+                screen_minutes,
+            } => fill(
+                text.wellbeing_long_day,
+                &[
+                    ("date", date.clone()),
+                    ("minutes", screen_minutes.to_string()),
+                    ("limit", config.max_screen_minutes.to_string()),
+                ],
+            ),
+            WellbeingWarning::LateNightStreak {
+                start_date,
+                end_date,
+                days,
+            } => fill(
+                text.wellbeing_late_streak,
+                &[
+                    ("start", start_date.clone()),
+                    ("end", end_date.clone()),
+                    ("days", days.to_string()),
+                ],
+            ),
+            WellbeingWarning::ShrinkingBreaks {
+                previous_per_hour,
+                current_per_hour,
+                drop_percent,
+            } => fill(
+                text.wellbeing_shrinking_breaks,
+                &[
+                    ("previous", format!("{:.0}", previous_per_hour)),
+                    ("current", format!("{:.0}", current_per_hour)),
+                    ("percent", drop_percent.to_string()),
+                ],
+            ),
+        })
+        .collect();
+    Some(lines.join("\n"))
+}
+
+/// 截至本周最后一天的 7/30 日移动平均与走势，附本周生产力评分最高的一天；近 7 天没有记录时返回 None
+fn render_week_trend(text: &NoteStrings, trend: &TrendSeries) -> Option<String> {
+    let last = trend.points.last()?;
+    let (ma7, ma30) = (last.ma7?, last.ma30?);
+    let [minutes_label, focus_label, productivity_label] = text.comparison_labels;
+    let row = |label: &str, short: f64, long: f64, unit: &str| {
+        format!(
+            "| {} | {:.0}{} | {:.0}{} | {} |",
+            label,
+            short,
+            unit,
+            long,
+            unit,
+            format_delta(short.round() as i64, long.round() as i64, false)
+        )
+    };
+    let mut lines = vec![
+        text.trend_table_header.to_string(),
+        "| --- | --- | --- | --- |".to_string(),
+        row(minutes_label, ma7.total_minutes, ma30.total_minutes, ""),
+        row(focus_label, ma7.focus_ratio, ma30.focus_ratio, "%"),
+        row(
+            productivity_label,
+            ma7.productivity_score,
+            ma30.productivity_score,
+            "",
+        ),
+    ];
+
+    let best = trend
+        .points
+        .iter()
+        .filter(|point| point.active)
+        .max_by_key(|point| point.productivity_score);
+    if let Some((best, percentile)) =
+        best.and_then(|point| point.percentile.map(|percentile| (point, percentile)))
+    {
+        lines.push(String::new());
+        lines.push(fill(
+            text.trend_best_day,
+            &[
+                ("date", best.date.clone()),
+                ("score", best.productivity_score.to_string()),
+                (
+                    "percentile",
+                    format!("{:.0}", percentile.productivity_score),
+                ),
+            ],
+        ));
+    }
+    Some(lines.join("\n"))
+}
+
+/// 专注热力图：代码块中每行一个星期、每格一小时，按相对最深一格的比例分四档；没有专注记录时返回 None
+fn render_focus_heatmap(text: &NoteStrings, heatmap: &FocusHeatmap) -> Option<String> {
+    const LEVELS: [&str; 4] = ["⬜", "🟨", "🟧", "🟥"];
+    let peak = heatmap.peak?;
+    let max = heatmap.max_minutes();
+    // 行名按显示宽度对齐（中文字符与方块均占两列）
+    let label_width = |label: &str| -> usize {
+        label
+            .chars()
+            .map(|c| if c.is_ascii() { 1 } else { 2 })
+            .sum()
+    };
+    let width = text
+        .heatmap_weekdays
+        .iter()
+        .map(|label| label_width(label))
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = vec!["```".to_string()];
+    let hours: String = (0..24)
+        .step_by(6)
+        .map(|hour| format!("{:<12}", format!("{:02}", hour)))
+        .collect();
+    lines.push(format!("{} {}", " ".repeat(width), hours.trim_end()));
+    for (label, row) in text.heatmap_weekdays.iter().zip(heatmap.minutes.iter()) {
+        let cells: String = row
+            .iter()
+            .map(|minutes| match *minutes {
+                0 => LEVELS[0],
+                m if m * 3 <= max => LEVELS[1],
+                m if m * 3 <= max * 2 => LEVELS[2],
+                _ => LEVELS[3],
+            })
+            .collect();
+        lines.push(format!(
+            "{}{} {}",
+            label,
+            " ".repeat(width - label_width(label)),
+            cells
+        ));
+    }
+    lines.push("```".to_string());
+    lines.push(fill(text.heatmap_legend, &[("max", max.to_string())]));
+    lines.push(fill(
+        text.heatmap_peak,
+        &[
+            ("weekday", text.heatmap_weekdays[peak.weekday].to_string()),
+            ("hour", format!("{:02}", peak.hour)),
+            ("next_hour", format!("{:02}", peak.hour + 1)),
+            ("minutes", peak.minutes.to_string()),
+        ],
+    ));
+    Some(lines.join("\n"))
+}
+
+/// 当天会议列表（来自日历同步），每行一个会议
+fn render_meetings(clock: ExportClock, meetings: &[CalendarEventRecord]) -> String {
+    meetings
+        .iter()
+        .map(|meeting| {
+            let location = if meeting.location.is_empty() {
+                String::new()
+            } else {
+                format!(" · {}", meeting.location)
+            };
+            format!(
+                "- {}-{} {}{}",
+                clock.session_time(meeting.start_time).format("%H:%M"),
+                clock.session_time(meeting.end_time).format("%H:%M"),
+                meeting.title,
+                location
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 将时间线卡片渲染为 Mermaid 甘特图（按类别分 section），没有可用卡片时返回空字符串
+fn render_mermaid_timeline(
+    text: &NoteStrings,
+    clock: ExportClock,
+    cards: &[TimelineCardRecord],
+) -> String {
+    let mut sections: Vec<(String, Vec<String>)> = Vec::new();
+    for card in cards {
+        let start = chrono::DateTime::parse_from_rfc3339(&card.start_time).ok();
+        let end = chrono::DateTime::parse_from_rfc3339(&card.end_time).ok();
+        let (Some(start), Some(end)) = (start, end) else {
+            continue;
+        };
+        let (start, end) = (clock.card_time(start), clock.card_time(end));
+        if end <= start {
+            continue;
+        }
+        // 跨天的卡片截断到当天结束，避免 HH:mm 格式下结束早于开始
+        let end_text = if end.date() == start.date() {
+            end.format("%H:%M").to_string()
+        } else {
+            "23:59".to_string()
+        };
+
+        let title = if card.title.trim().is_empty() {
+            text.untitled_segment.to_string()
+        } else {
+            mermaid_text(&card.title)
+        };
+        let task = format!("    {} :{}, {}", title, start.format("%H:%M"), end_text);
+
+        let section = mermaid_text(&card.category);
+        let section = if section.is_empty() {
+            "other".to_string()
+        } else {
+            section
+        };
+        match sections.iter_mut().find(|(name, _)| *name == section) {
+            Some((_, tasks)) => tasks.push(task),
+            None => sections.push((section, vec![task])),
+        }
+    }
+
+    if sections.is_empty() {
+        return String::new();
+    }
+
+    let mut lines = vec![
+        "```mermaid".to_string(),
+        "gantt".to_string(),
+        "    dateFormat HH:mm".to_string(),
+        "    axisFormat %H:%M".to_string(),
+    ];
+    for (section, tasks) in sections {
+        lines.push(format!("    section {}", section));
+        lines.extend(tasks);
+    }
+    lines.push("```".to_string());
+    lines.join("\n")
+}
+
+/// 去除会破坏 Mermaid 语法的字符（`:` `#` `;` 与换行）
+fn mermaid_text(raw: &str) -> String {
     raw.chars()
-        .map(|c| match c {
-            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
-            _ => c,
+        .map(|ch| match ch {
+            ':' | '#' | ';' | '\n' | '\r' => ' ',
+            _ => ch,
         })
-        .collect()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
-fn to_file_url(path: &str) -> String {
-    let normalized = path.replace('\\', "/").replace(' ', "%20");
-    if normalized.contains(":/") {
-        format!("file:///{}", normalized)
-    } else {
-        format!("file://{}", normalized)
+/// 会话指标
+struct SessionMetrics {
+    timeline_cards: usize,
+    context_switches: usize,
+    avg_segment_minutes: i64,
+    fragmentation_level: String,
+    /// 专注（工作+学习）时长占比（%）
+    focus_ratio: i64,
+}
+
+#[derive(Default)]
+struct WeekFocusMetrics {
+    total_minutes: i64,
+    work_minutes: i64,
+    learning_minutes: i64,
+    communication_minutes: i64,
+    personal_minutes: i64,
+    idle_minutes: i64,
+    other_minutes: i64,
+}
+
+/// 季度/年度回顾的统计周期
+struct RollupPeriod {
+    kind: &'static str,
+    label: String,
+    title: String,
+    start: NaiveDate,
+    end: NaiveDate,
+}
+
+/// 周报汇总数据（Obsidian 周报与 Notion 周报页面共用）
+pub(crate) struct WeekSummaryData {
+    pub(crate) week_label: String,
+    pub(crate) week_start: String,
+    pub(crate) week_end: String,
+    pub(crate) total_sessions: i32,
+    pub(crate) total_minutes: i32,
+    pub(crate) avg_session_minutes: i32,
+    pub(crate) top_categories: String,
+    table_lines: Vec<String>,
+    focus_metrics: WeekFocusMetrics,
+    score_config: WeekScoreConfig,
+    daily_highlights: Vec<String>,
+    /// 上周数据（仅周报与预览加载）
+    previous_week: Option<WeekBaseline>,
+    /// 近 8 周的滚动基准（仅周报与预览加载，有记录的周不足时为空）
+    stats_baseline: Option<WeekStatsBaseline>,
+    /// 本周目标达成情况（仅周报与预览加载）
+    goals: Vec<GoalWeekStatus>,
+    /// 本周每日专注趋势（仅周报与预览加载）
+    trend: Option<TrendSeries>,
+    /// 本周过劳迹象（仅周报与预览加载）
+    wellbeing: Option<WellbeingReport>,
+    /// 本周各设备的专注度（仅周报与预览加载）
+    device_focus: Vec<DeviceFocusStat>,
+}
+
+impl WeekSummaryData {
+    /// 专注时长（工作 + 学习，分钟）
+    pub(crate) fn focus_minutes(&self) -> i64 {
+        self.focus_metrics.focus_minutes()
+    }
+
+    /// 专注占比（%）
+    pub(crate) fn focus_ratio(&self) -> i64 {
+        self.focus_metrics.focus_ratio()
+    }
+
+    /// 生产力评分（0-100）
+    pub(crate) fn productivity_score(&self) -> i64 {
+        week_productivity_score(self)
+    }
+
+    /// 与基准比较的各项指标（顺序见 `WeekStatsBaseline::metrics`）
+    fn metric_values(&self) -> [f64; BASELINE_METRICS] {
+        week_metric_values(
+            self.total_minutes,
+            self.avg_session_minutes,
+            &self.focus_metrics,
+            &self.score_config,
+        )
     }
 }
 
-fn format_markdown_link(label: &str, url: &str) -> String {
-    format!("[{}]({})", label, url)
+/// 上周的对比基准
+struct WeekBaseline {
+    week_label: String,
+    total_sessions: i32,
+    total_minutes: i32,
+    focus_metrics: WeekFocusMetrics,
 }
 
-fn render_template(template: Option<&str>, fallback: &str, values: &[(&str, String)]) -> String {
-    let mut content = template.unwrap_or(fallback).to_string();
-    for (key, value) in values {
-        let placeholder = format!("{{{{{}}}}}", key);
-        content = content.replace(&placeholder, value);
+impl WeekBaseline {
+    fn has_activity(&self) -> bool {
+        self.total_minutes > 0 || self.focus_metrics.total_minutes > 0
+    }
+
+    fn metric_values(&self, config: &WeekScoreConfig) -> [f64; BASELINE_METRICS] {
+        let avg_session_minutes = if self.total_sessions > 0 {
+            self.total_minutes / self.total_sessions
+        } else {
+            0
+        };
+        week_metric_values(
+            self.total_minutes,
+            avg_session_minutes,
+            &self.focus_metrics,
+            config,
+        )
+    }
+
+    /// 按本周的评分配置计算，保证两周可比
+    fn productivity_score(&self, config: &WeekScoreConfig) -> i64 {
+        self.focus_metrics.productivity_score(
+            config.focus_weight,
+            config.effort_weight,
+            config.target_minutes,
+        )
+    }
+}
+
+/// 滚动基准的周数（不含本周）
+const BASELINE_WEEKS: usize = 8;
+
+/// 有记录的周数少于该值时退回固定阈值
+const MIN_BASELINE_WEEKS: usize = 3;
+
+/// 偏离平常水平达到该标准分时写入洞察
+const BASELINE_Z_THRESHOLD: f64 = 1.0;
+
+/// 与基准比较的指标数
+const BASELINE_METRICS: usize = 5;
+
+/// 依次为专注时长、专注占比、生产力评分、总时长、平均会话时长
+fn week_metric_values(
+    total_minutes: i32,
+    avg_session_minutes: i32,
+    focus_metrics: &WeekFocusMetrics,
+    config: &WeekScoreConfig,
+) -> [f64; BASELINE_METRICS] {
+    [
+        focus_metrics.focus_minutes() as f64,
+        focus_metrics.focus_ratio() as f64,
+        focus_metrics.productivity_score(
+            config.focus_weight,
+            config.effort_weight,
+            config.target_minutes,
+        ) as f64,
+        f64::from(total_minutes),
+        f64::from(avg_session_minutes),
+    ]
+}
+
+/// 单项指标在基准期内的均值与标准差
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MetricBaseline {
+    mean: f64,
+    std_dev: f64,
+}
+
+impl MetricBaseline {
+    fn from_values(values: &[f64]) -> Self {
+        let count = values.len().max(1) as f64;
+        let mean = values.iter().sum::<f64>() / count;
+        let variance = values
+            .iter()
+            .map(|value| (value - mean).powi(2))
+            .sum::<f64>()
+            / (count - 1.0).max(1.0);
+        Self {
+            mean,
+            std_dev: variance.sqrt(),
+        }
+    }
+
+    /// 标准分；标准差过小时按均值的 10%（至少为 1）计，避免轻微波动被放大
+    fn z_score(&self, value: f64) -> f64 {
+        let spread = self.std_dev.max(self.mean.abs() * 0.1).max(1.0);
+        (value - self.mean) / spread
+    }
+}
+
+/// 近 8 周（不含本周，只计有记录的周）的滚动基准
+struct WeekStatsBaseline {
+    weeks: usize,
+    /// 依次为专注时长、专注占比、生产力评分、总时长、平均会话时长
+    metrics: [MetricBaseline; BASELINE_METRICS],
+}
+
+impl WeekStatsBaseline {
+    /// 按本周的评分配置计算各周评分；有记录的周不足 `MIN_BASELINE_WEEKS` 时返回 None
+    fn from_weeks(weeks: &[WeekBaseline], config: &WeekScoreConfig) -> Option<Self> {
+        let values: Vec<[f64; BASELINE_METRICS]> = weeks
+            .iter()
+            .filter(|week| week.has_activity())
+            .map(|week| week.metric_values(config))
+            .collect();
+        if values.len() < MIN_BASELINE_WEEKS {
+            return None;
+        }
+        let metrics = std::array::from_fn(|index| {
+            MetricBaseline::from_values(&values.iter().map(|week| week[index]).collect::<Vec<_>>())
+        });
+        Some(Self {
+            weeks: values.len(),
+            metrics,
+        })
+    }
+}
+
+struct WeekScoreConfig {
+    focus_weight: i64,
+    effort_weight: i64,
+    target_minutes: i64,
+}
+
+impl WeekFocusMetrics {
+    fn add_cards(&mut self, cards: &[TimelineCardRecord]) {
+        for card in cards {
+            self.add_card(card);
+        }
+    }
+
+    fn add_card(&mut self, card: &TimelineCardRecord) {
+        let minutes = parse_card_minutes(card);
+        if minutes <= 0 {
+            return;
+        }
+        self.total_minutes += minutes;
+
+        match normalize_timeline_category(&card.category) {
+            ActivityCategory::Work => self.work_minutes += minutes,
+            ActivityCategory::Learning => self.learning_minutes += minutes,
+            ActivityCategory::Communication => self.communication_minutes += minutes,
+            ActivityCategory::Personal => self.personal_minutes += minutes,
+            ActivityCategory::Idle => self.idle_minutes += minutes,
+            ActivityCategory::Other => self.other_minutes += minutes,
+        }
+    }
+
+    fn add_record(&mut self, record: &DailyFocusMetricsRecord) {
+        self.total_minutes += record.total_minutes;
+        self.work_minutes += record.work_minutes;
+        self.learning_minutes += record.learning_minutes;
+        self.communication_minutes += record.communication_minutes;
+        self.personal_minutes += record.personal_minutes;
+        self.idle_minutes += record.idle_minutes;
+        self.other_minutes += record.other_minutes;
+    }
+
+    fn merge(&mut self, other: &WeekFocusMetrics) {
+        self.total_minutes += other.total_minutes;
+        self.work_minutes += other.work_minutes;
+        self.learning_minutes += other.learning_minutes;
+        self.communication_minutes += other.communication_minutes;
+        self.personal_minutes += other.personal_minutes;
+        self.idle_minutes += other.idle_minutes;
+        self.other_minutes += other.other_minutes;
+    }
+
+    fn focus_minutes(&self) -> i64 {
+        self.work_minutes + self.learning_minutes
+    }
+
+    /// 自定义指标公式可用的变量（见 `custom_metrics::VARIABLES`）
+    fn metric_variables(&self) -> [(&'static str, f64); 9] {
+        [
+            ("total", self.total_minutes as f64),
+            ("work", self.work_minutes as f64),
+            ("learning", self.learning_minutes as f64),
+            ("communication", self.communication_minutes as f64),
+            ("personal", self.personal_minutes as f64),
+            ("idle", self.idle_minutes as f64),
+            ("other", self.other_minutes as f64),
+            ("focus", self.focus_minutes() as f64),
+            ("distraction", self.distraction_minutes() as f64),
+        ]
+    }
+
+    fn distraction_minutes(&self) -> i64 {
+        self.personal_minutes + self.idle_minutes + self.other_minutes
+    }
+
+    fn focus_ratio(&self) -> i64 {
+        if self.total_minutes == 0 {
+            0
+        } else {
+            (self.focus_minutes() * 100 / self.total_minutes).max(0)
+        }
+    }
+
+    fn distraction_ratio(&self) -> i64 {
+        if self.total_minutes == 0 {
+            0
+        } else {
+            (self.distraction_minutes() * 100 / self.total_minutes).max(0)
+        }
+    }
+
+    fn effort_score(&self, target_minutes: i64) -> i64 {
+        if self.total_minutes == 0 || target_minutes <= 0 {
+            return 0;
+        }
+        let score = self.total_minutes * 100 / target_minutes;
+        score.min(100).max(0)
+    }
+
+    fn focus_score(&self) -> i64 {
+        self.focus_ratio()
+    }
+
+    fn productivity_score(
+        &self,
+        focus_weight: i64,
+        effort_weight: i64,
+        target_minutes: i64,
+    ) -> i64 {
+        let total_weight = (focus_weight + effort_weight).max(1);
+        let focus_score = self.focus_score();
+        let effort_score = self.effort_score(target_minutes);
+        (focus_score * focus_weight + effort_score * effort_weight) / total_weight
+    }
+}
+
+/// 渲染"与上周对比"表格；未加载上周数据时返回 None
+fn render_week_comparison(text: &NoteStrings, summary: &WeekSummaryData) -> Option<String> {
+    let previous = summary.previous_week.as_ref()?;
+    if !previous.has_activity() {
+        return Some(format!(
+            "- {}",
+            fill(
+                text.no_previous_week,
+                &[("week", previous.week_label.clone())]
+            )
+        ));
+    }
+
+    let [minutes_label, focus_label, productivity_label] = text.comparison_labels;
+    let current_focus = summary.focus_metrics.focus_ratio();
+    let previous_focus = previous.focus_metrics.focus_ratio();
+    let current_productivity = week_productivity_score(summary);
+    let previous_productivity = previous.productivity_score(&summary.score_config);
+
+    let rows = [
+        text.comparison_table_header.to_string(),
+        "| --- | --- | --- | --- |".to_string(),
+        format!(
+            "| {} | {} | {} | {} |",
+            minutes_label,
+            summary.total_minutes,
+            previous.total_minutes,
+            format_delta(
+                i64::from(summary.total_minutes),
+                i64::from(previous.total_minutes),
+                true
+            )
+        ),
+        format!(
+            "| {} | {}% | {}% | {} |",
+            focus_label,
+            current_focus,
+            previous_focus,
+            fill(
+                text.percentage_points,
+                &[("delta", format_delta(current_focus, previous_focus, false))]
+            )
+        ),
+        format!(
+            "| {} | {} | {} | {} |",
+            productivity_label,
+            current_productivity,
+            previous_productivity,
+            format_delta(current_productivity, previous_productivity, true)
+        ),
+    ];
+    Some(rows.join("\n"))
+}
+
+/// 变化量：箭头 + 带符号差值，`with_percent` 且基数大于 0 时附带百分比
+fn format_delta(current: i64, previous: i64, with_percent: bool) -> String {
+    let delta = current - previous;
+    let arrow = match delta.cmp(&0) {
+        std::cmp::Ordering::Greater => "↑",
+        std::cmp::Ordering::Less => "↓",
+        std::cmp::Ordering::Equal => "→",
+    };
+    let mut text = format!("{} {:+}", arrow, delta);
+    if with_percent {
+        if let Some(percent) = change_percent(delta, previous) {
+            text.push_str(&format!(" ({:+}%)", percent));
+        }
+    }
+    text
+}
+
+/// 相对基数的变化百分比（四舍五入），基数不大于 0 时为 None
+fn change_percent(delta: i64, base: i64) -> Option<i64> {
+    (base > 0).then(|| (delta as f64 * 100.0 / base as f64).round() as i64)
+}
+
+fn week_productivity_score(week: &WeekSummaryData) -> i64 {
+    week.focus_metrics.productivity_score(
+        week.score_config.focus_weight,
+        week.score_config.effort_weight,
+        week.score_config.target_minutes,
+    )
+}
+
+fn average_productivity_score<'a>(weeks: impl Iterator<Item = &'a WeekSummaryData>) -> i64 {
+    let scores = weeks.map(week_productivity_score).collect::<Vec<_>>();
+    if scores.is_empty() {
+        0
+    } else {
+        scores.iter().sum::<i64>() / scores.len() as i64
+    }
+}
+
+/// 周所属月份（以周四为准）
+fn week_month(week: &WeekSummaryData) -> u32 {
+    NaiveDate::parse_from_str(&week.week_start, "%Y-%m-%d")
+        .map(|start| (start + chrono::Duration::days(3)).month())
+        .unwrap_or(1)
+}
+
+fn build_session_metrics(
+    text: &NoteStrings,
+    cards: &[TimelineCardRecord],
+    duration_minutes: i64,
+) -> SessionMetrics {
+    let timeline_cards = cards.len();
+    let context_switches = count_context_switches(cards);
+    let avg_segment_minutes = if timeline_cards == 0 {
+        0
+    } else {
+        (duration_minutes / timeline_cards as i64).max(0)
+    };
+    let fragmentation_level = match context_switches {
+        0..=1 => text.fragmentation_levels[0],
+        2..=3 => text.fragmentation_levels[1],
+        _ => text.fragmentation_levels[2],
+    }
+    .to_string();
+
+    let mut focus = WeekFocusMetrics::default();
+    focus.add_cards(cards);
+
+    SessionMetrics {
+        timeline_cards,
+        context_switches,
+        avg_segment_minutes,
+        fragmentation_level,
+        focus_ratio: focus.focus_ratio(),
+    }
+}
+
+/// 会话笔记文件名：{日期}_{开始}-{结束}_session-{id}.md
+///
+/// `title_max_len` 不为空时追加标题 slug（`_{slug}`），标题为空或全为符号时不追加。
+fn session_note_filename(
+    clock: ExportClock,
+    session: &Session,
+    title_max_len: Option<usize>,
+) -> String {
+    let start = clock.session_time(session.start_time);
+    let end = clock.session_time(session.end_time);
+    let title_suffix = title_max_len
+        .map(|max_len| title_slug(&session.title, max_len))
+        .filter(|slug| !slug.is_empty())
+        .map(|slug| format!("_{}", slug))
+        .unwrap_or_default();
+    format!(
+        "{}_{}-{}_session-{}{}.md",
+        sanitize_filename(&start.format("%Y-%m-%d").to_string()),
+        sanitize_filename(&start.format("%H%M").to_string()),
+        sanitize_filename(&end.format("%H%M").to_string()),
+        session.id.unwrap_or(0),
+        title_suffix
+    )
+}
+
+/// 文件名用标题 slug：小写、符号折叠为 `-`，按字符截断到 `max_len`
+fn title_slug(title: &str, max_len: usize) -> String {
+    let slug = tag_slug(title).replace('_', "-");
+    let truncated = slug.chars().take(max_len).collect::<String>();
+    truncated.trim_matches('-').to_string()
+}
+
+/// 读取当天全部会话按帧统计的应用使用记录
+async fn collect_day_apps(db: &Database, sessions: &[Session]) -> Vec<AppUsageRecord> {
+    let mut apps = Vec::new();
+    for session in sessions {
+        let Some(session_id) = session.id else {
+            continue;
+        };
+        apps.extend(
+            db.get_app_usage_by_session(session_id)
+                .await
+                .unwrap_or_default(),
+        );
+    }
+    apps
+}
+
+/// 读取当天全部会话的时间线卡片（按会话顺序）
+async fn collect_day_cards(db: &Database, sessions: &[Session]) -> Vec<TimelineCardRecord> {
+    let mut cards = Vec::new();
+    for session in sessions {
+        let Some(session_id) = session.id else {
+            continue;
+        };
+        cards.extend(
+            db.get_timeline_cards_by_session(session_id)
+                .await
+                .unwrap_or_default(),
+        );
+    }
+    cards
+}
+
+/// 应用使用分布中的一行
+#[derive(Debug, PartialEq)]
+struct AppUsageLine {
+    app: String,
+    /// 停留最久的窗口标题（仅按帧统计时有）
+    window_title: Option<String>,
+    minutes: i64,
+}
+
+/// 单个应用的累计时长
+#[derive(Default)]
+struct AppUsageTotal<'a> {
+    /// 总秒数
+    seconds: i64,
+    /// 最长的窗口标题及其秒数
+    longest: Option<(&'a str, i64)>,
+}
+
+/// 按应用汇总当天各会话的帧统计时长（分钟），窗口标题取该应用停留最久的一条，按时长降序
+fn aggregate_app_usage(text: &NoteStrings, apps: &[AppUsageRecord]) -> Vec<AppUsageLine> {
+    let mut usage: HashMap<String, AppUsageTotal> = HashMap::new();
+    for record in apps {
+        if record.seconds <= 0 {
+            continue;
+        }
+        let app = Some(record.app_name.trim())
+            .filter(|app| !app.is_empty())
+            .unwrap_or(text.unknown_app)
+            .to_string();
+        let entry = usage.entry(app).or_default();
+        entry.seconds += record.seconds;
+        let title = record.window_title.trim();
+        if !title.is_empty()
+            && entry
+                .longest
+                .is_none_or(|(_, seconds)| record.seconds > seconds)
+        {
+            entry.longest = Some((title, record.seconds));
+        }
+    }
+
+    let mut usage = usage
+        .into_iter()
+        .map(|(app, total)| AppUsageLine {
+            app,
+            window_title: total.longest.map(|(title, _)| title.to_string()),
+            minutes: (total.seconds + 30) / 60,
+        })
+        .filter(|line| line.minutes > 0)
+        .collect::<Vec<_>>();
+    usage.sort_by(|a, b| b.minutes.cmp(&a.minutes).then_with(|| a.app.cmp(&b.app)));
+    usage
+}
+
+/// 按主要应用汇总时间线卡片时长（分钟），按时长降序
+///
+/// 没有帧级应用数据（未开启窗口采集的旧会话）时的估算：以时间线卡片的 app_sites.primary 作为应用归属。
+fn aggregate_card_app_usage(text: &NoteStrings, cards: &[TimelineCardRecord]) -> Vec<AppUsageLine> {
+    let mut usage: HashMap<String, i64> = HashMap::new();
+    for card in cards {
+        let minutes = parse_card_minutes(card);
+        if minutes <= 0 {
+            continue;
+        }
+        let app = serde_json::from_str::<serde_json::Value>(&card.app_sites)
+            .ok()
+            .and_then(|sites| {
+                sites
+                    .get("primary")?
+                    .as_str()
+                    .map(str::trim)
+                    .map(String::from)
+            })
+            .filter(|app| !app.is_empty())
+            .unwrap_or_else(|| text.unknown_app.to_string());
+        *usage.entry(app).or_insert(0) += minutes;
+    }
+
+    let mut usage = usage
+        .into_iter()
+        .map(|(app, minutes)| AppUsageLine {
+            app,
+            window_title: None,
+            minutes,
+        })
+        .collect::<Vec<_>>();
+    usage.sort_by(|a, b| b.minutes.cmp(&a.minutes).then_with(|| a.app.cmp(&b.app)));
+    usage
+}
+
+/// 渲染应用使用分布：优先使用帧统计的应用与窗口标题，没有时退回时间线卡片估算并注明
+fn render_app_breakdown(
+    text: &NoteStrings,
+    apps: &[AppUsageRecord],
+    cards: &[TimelineCardRecord],
+) -> String {
+    let mut app_usage = aggregate_app_usage(text, apps);
+    let estimated = app_usage.is_empty();
+    if estimated {
+        app_usage = aggregate_card_app_usage(text, cards);
+    }
+
+    let total: i64 = app_usage.iter().map(|line| line.minutes).sum();
+    if total == 0 {
+        return text.no_app_usage.to_string();
+    }
+    let lines = app_usage
+        .iter()
+        .map(|line| {
+            let (template, title) = match &line.window_title {
+                Some(title) => (text.app_title_line, title.clone()),
+                None => (text.app_line, String::new()),
+            };
+            let rendered = fill(
+                template,
+                &[
+                    ("app", line.app.clone()),
+                    ("title", title),
+                    ("minutes", line.minutes.to_string()),
+                    ("percent", (line.minutes * 100 / total).to_string()),
+                ],
+            );
+            format!("- {}", rendered)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if estimated {
+        format!("> {}\n\n{}", text.app_usage_estimated, lines)
+    } else {
+        lines
+    }
+}
+
+/// 渲染正文标签行：类别标签（`#前缀/work`）+ 主要应用标签（`#前缀/app/vscode`），去重并保持顺序
+fn render_body_tags(prefix: &str, tags: &[ActivityTag], cards: &[TimelineCardRecord]) -> String {
+    let prefix = prefix.trim().trim_matches('/').trim_start_matches('#');
+    let with_prefix = |tag: String| {
+        if prefix.is_empty() {
+            format!("#{}", tag)
+        } else {
+            format!("#{}/{}", prefix, tag)
+        }
+    };
+
+    let categories = tags
+        .iter()
+        .map(|tag| category_to_string(&tag.category).to_string());
+    let apps = cards.iter().filter_map(|card| {
+        // 仅取主要应用；secondary 字段历史格式不一，这里不做强类型解析
+        let sites = serde_json::from_str::<serde_json::Value>(&card.app_sites).ok()?;
+        let slug = tag_slug(sites.get("primary")?.as_str()?);
+        (!slug.is_empty()).then(|| format!("app/{}", slug))
+    });
+
+    let mut seen = Vec::new();
+    for tag in categories.chain(apps) {
+        if !seen.contains(&tag) {
+            seen.push(tag);
+        }
+    }
+    seen.into_iter()
+        .map(with_prefix)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 将任意文本转换为合法的 Obsidian 标签片段（小写，非字母数字替换为 `-`）
+fn tag_slug(raw: &str) -> String {
+    let mut slug = String::new();
+    for ch in raw.trim().chars().flat_map(char::to_lowercase) {
+        if ch.is_alphanumeric() || ch == '_' {
+            slug.push(ch);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// 渲染会话备注：备注原文，自定义标签转为正文标签
+fn render_session_notes(note: &SessionNoteRecord) -> String {
+    let tags = note
+        .tag_list()
+        .iter()
+        .map(|tag| tag_slug(tag))
+        .filter(|slug| !slug.is_empty())
+        .map(|slug| format!("#{}", slug))
+        .collect::<Vec<_>>()
+        .join(" ");
+    [note.note.trim(), tags.as_str()]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// 渲染 Dataview 行内字段块（每行 `key:: value`，末尾空一行）
+fn render_inline_fields(
+    date: &str,
+    duration_minutes: i64,
+    tags: &str,
+    metrics: &SessionMetrics,
+) -> String {
+    let categories = tags.trim_start_matches('[').trim_end_matches(']');
+    let mut fields = vec![
+        format!("date:: {}", date),
+        format!("duration:: {}", duration_minutes),
+        format!("focus-ratio:: {}", metrics.focus_ratio),
+        format!("context-switches:: {}", metrics.context_switches),
+        format!("fragmentation:: {}", metrics.fragmentation_level),
+    ];
+    if !categories.is_empty() {
+        fields.push(format!("category:: {}", categories));
+    }
+    format!("{}\n\n", fields.join("\n"))
+}
+
+fn render_metrics(text: &NoteStrings, metrics: &SessionMetrics) -> String {
+    if metrics.timeline_cards == 0 {
+        return text.no_metrics.to_string();
+    }
+
+    fill(
+        text.metrics_lines,
+        &[
+            ("cards", metrics.timeline_cards.to_string()),
+            ("switches", metrics.context_switches.to_string()),
+            ("avg", metrics.avg_segment_minutes.to_string()),
+            ("level", metrics.fragmentation_level.clone()),
+        ],
+    )
+}
+
+fn render_week_focus_metrics(
+    text: &NoteStrings,
+    metrics: &WeekFocusMetrics,
+    score: &WeekScoreConfig,
+) -> String {
+    if metrics.total_minutes == 0 {
+        return text.no_focus_metrics.to_string();
+    }
+
+    let focus_score = metrics.focus_score();
+    let effort_score = metrics.effort_score(score.target_minutes);
+    let productivity_score =
+        metrics.productivity_score(score.focus_weight, score.effort_weight, score.target_minutes);
+
+    fill(
+        text.focus_metrics_lines,
+        &[
+            ("focus_minutes", metrics.focus_minutes().to_string()),
+            ("focus_ratio", metrics.focus_ratio().to_string()),
+            (
+                "communication_minutes",
+                metrics.communication_minutes.to_string(),
+            ),
+            (
+                "distraction_minutes",
+                metrics.distraction_minutes().to_string(),
+            ),
+            ("distraction_ratio", metrics.distraction_ratio().to_string()),
+            ("focus_score", focus_score.to_string()),
+            ("effort_score", effort_score.to_string()),
+            ("target_minutes", score.target_minutes.to_string()),
+            ("productivity_score", productivity_score.to_string()),
+            ("focus_weight", score.focus_weight.to_string()),
+            ("effort_weight", score.effort_weight.to_string()),
+            ("work", metrics.work_minutes.to_string()),
+            ("learning", metrics.learning_minutes.to_string()),
+            ("personal", metrics.personal_minutes.to_string()),
+            ("idle", metrics.idle_minutes.to_string()),
+            ("other", metrics.other_minutes.to_string()),
+        ],
+    )
+}
+
+/// 与近 8 周平常水平的比较：按偏离程度从大到小列出标准分达到阈值的指标，都在平常范围内时给出一句说明
+fn baseline_insights(
+    text: &NoteStrings,
+    values: &[f64; BASELINE_METRICS],
+    baseline: &WeekStatsBaseline,
+) -> Vec<String> {
+    let phrases = &text.insights;
+    let weeks = baseline.weeks.to_string();
+    let mut deviations: Vec<(usize, f64)> = baseline
+        .metrics
+        .iter()
+        .zip(values)
+        .map(|(metric, value)| metric.z_score(*value))
+        .enumerate()
+        .filter(|(_, z)| z.abs() >= BASELINE_Z_THRESHOLD)
+        .collect();
+    if deviations.is_empty() {
+        return vec![fill(phrases.baseline_typical, &[("weeks", weeks)])];
+    }
+    deviations.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()));
+
+    deviations
+        .into_iter()
+        .map(|(index, z)| {
+            let mean = baseline.metrics[index].mean;
+            let diff = (values[index] - mean).abs();
+            let delta = match index {
+                // 占比按百分点、评分按分数，时长按相对平常水平的百分比
+                1 => fill(text.percentage_points, &[("delta", format!("{:.0}", diff))]),
+                2 => fill(
+                    phrases.baseline_points,
+                    &[("delta", format!("{:.0}", diff))],
+                ),
+                _ if mean > 0.0 => format!("{:.0}%", diff * 100.0 / mean),
+                _ => fill(
+                    phrases.baseline_minutes,
+                    &[("delta", format!("{:.0}", diff))],
+                ),
+            };
+            let template = if z > 0.0 {
+                phrases.baseline_above
+            } else {
+                phrases.baseline_below
+            };
+            fill(
+                template,
+                &[
+                    ("metric", phrases.baseline_metrics[index].to_string()),
+                    ("weeks", weeks.clone()),
+                    ("delta", delta),
+                    ("z", format!("{:+.1}", z)),
+                ],
+            )
+        })
+        .collect()
+}
+
+/// 周报洞察：有足够的历史时与近 8 周的平常水平比较，否则按固定阈值判断
+fn build_week_insights(text: &NoteStrings, summary: &WeekSummaryData) -> Vec<String> {
+    if let Some(baseline) = &summary.stats_baseline {
+        return baseline_insights(text, &summary.metric_values(), baseline);
+    }
+
+    let phrases = &text.insights;
+    let mut insights = Vec::new();
+    let focus_ratio = summary.focus_metrics.focus_ratio();
+    let productivity_score = summary.focus_metrics.productivity_score(
+        summary.score_config.focus_weight,
+        summary.score_config.effort_weight,
+        summary.score_config.target_minutes,
+    );
+    let total_minutes = summary.total_minutes;
+    let avg_session_minutes = summary.avg_session_minutes;
+
+    if focus_ratio >= 70 {
+        insights.push(phrases.high_focus.to_string());
+    } else if focus_ratio <= 40 {
+        insights.push(phrases.low_focus.to_string());
+    } else {
+        insights.push(phrases.medium_focus.to_string());
+    }
+
+    if productivity_score >= 70 {
+        insights.push(phrases.high_productivity.to_string());
+    } else if productivity_score <= 40 {
+        insights.push(phrases.low_productivity.to_string());
+    }
+
+    if total_minutes < 300 {
+        insights.push(phrases.low_effort.to_string());
+    } else if total_minutes >= 1200 {
+        insights.push(phrases.high_effort.to_string());
+    }
+
+    if avg_session_minutes < 20 {
+        insights.push(phrases.short_sessions.to_string());
+    } else if avg_session_minutes >= 60 {
+        insights.push(phrases.long_sessions.to_string());
+    }
+
+    insights
+}
+
+fn count_context_switches(cards: &[TimelineCardRecord]) -> usize {
+    let mut switches = 0usize;
+    let mut last_category: Option<String> = None;
+
+    for card in cards {
+        let category = card.category.to_lowercase();
+        if let Some(last) = &last_category {
+            if last != &category {
+                switches += 1;
+            }
+        }
+        last_category = Some(category);
+    }
+
+    switches
+}
+
+pub(crate) fn compact_summary_text(text: &str, max_len: usize) -> String {
+    let cleaned = text.replace('\n', " ").replace('\r', " ");
+    if cleaned.chars().count() <= max_len {
+        return cleaned;
+    }
+    let truncated: String = cleaned.chars().take(max_len).collect();
+    format!("{}...", truncated)
+}
+
+/// 归一化类别名称：返回（匹配键，规范显示名），空白类别返回 None
+fn canonical_category(raw: &str, labels: &HashMap<String, String>) -> Option<(String, String)> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let key = trimmed.to_lowercase();
+    let label = labels
+        .iter()
+        .find(|(alias, _)| alias.trim().to_lowercase() == key)
+        .map(|(_, label)| label.trim().to_string())
+        .filter(|label| !label.is_empty())
+        .unwrap_or_else(|| trimmed.to_string());
+    Some((key, label))
+}
+
+/// 统计类别出现次数，大小写/空白不同的同一类别合并为一行
+fn count_categories(
+    activities: &[Activity],
+    labels: &HashMap<String, String>,
+) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, (String, usize)> = HashMap::new();
+    for activity in activities {
+        for raw in &activity.main_categories {
+            if let Some((key, label)) = canonical_category(raw, labels) {
+                // 未配置映射时保留首次出现的写法作为规范名称
+                counts.entry(key).or_insert((label, 0)).1 += 1;
+            }
+        }
+    }
+
+    let mut categories: Vec<(String, usize)> = counts.into_values().collect();
+    categories.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    categories
+}
+
+fn format_top_categories(text: &NoteStrings, categories: &[(String, usize)]) -> String {
+    if categories.is_empty() {
+        return text.none.to_string();
+    }
+    categories
+        .iter()
+        .take(5)
+        .map(|(name, count)| format!("{}({})", name, count))
+        .collect::<Vec<_>>()
+        .join(text.list_separator)
+}
+
+/// 渲染单日类别列（去重后的规范名称）
+fn format_day_categories(raw_categories: &[String], labels: &HashMap<String, String>) -> String {
+    let mut seen = std::collections::HashSet::new();
+    let categories = raw_categories
+        .iter()
+        .filter_map(|raw| canonical_category(raw, labels))
+        .filter(|(key, _)| seen.insert(key.clone()))
+        .map(|(_, label)| label)
+        .collect::<Vec<_>>();
+    if categories.is_empty() {
+        "-".to_string()
+    } else {
+        categories.join(", ")
+    }
+}
+
+fn format_time_range(clock: ExportClock, start: &str, end: &str) -> (String, String) {
+    let format = |value: &str| -> String {
+        chrono::DateTime::parse_from_rfc3339(value)
+            .map(|dt| clock.card_time(dt).format("%H:%M").to_string())
+            .unwrap_or_else(|_| value.to_string())
+    };
+    (format(start), format(end))
+}
+
+fn parse_tags(raw: &str) -> Vec<ActivityTag> {
+    serde_json::from_str::<Vec<ActivityTag>>(raw).unwrap_or_default()
+}
+
+fn format_tags(tags: &[ActivityTag]) -> String {
+    if tags.is_empty() {
+        return "[]".to_string();
+    }
+
+    let values = tags
+        .iter()
+        .map(|tag| category_to_string(&tag.category))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("[{}]", values)
+}
+
+fn category_to_string(category: &ActivityCategory) -> &'static str {
+    match category {
+        ActivityCategory::Work => "work",
+        ActivityCategory::Communication => "communication",
+        ActivityCategory::Learning => "learning",
+        ActivityCategory::Personal => "personal",
+        ActivityCategory::Idle => "idle",
+        ActivityCategory::Other => "other",
+    }
+}
+
+/// 均匀选取 count 帧（包含首尾）
+fn pick_evenly_spaced(frames: &[Frame], count: usize) -> Vec<Frame> {
+    let count = count.min(frames.len());
+    match count {
+        0 => Vec::new(),
+        1 => vec![frames[frames.len() / 2].clone()],
+        _ => {
+            let mut indices = (0..count)
+                .map(|i| i * (frames.len() - 1) / (count - 1))
+                .collect::<Vec<_>>();
+            indices.dedup();
+            indices.into_iter().map(|i| frames[i].clone()).collect()
+        }
+    }
+}
+
+/// 每个时间线卡片选取最接近其中点的一帧（卡片时间范围内无帧则跳过）
+fn pick_per_timeline_card(frames: &[Frame], cards: &[TimelineCardRecord]) -> Vec<Frame> {
+    let mut selected: Vec<Frame> = Vec::new();
+    for card in cards {
+        let (Ok(start), Ok(end)) = (
+            DateTime::parse_from_rfc3339(&card.start_time),
+            DateTime::parse_from_rfc3339(&card.end_time),
+        ) else {
+            continue;
+        };
+        // 截图时间以本地时间值存储，卡片时间取其本地时刻比较
+        let (start, end) = (start.naive_local().and_utc(), end.naive_local().and_utc());
+        let middle = start + (end - start) / 2;
+
+        let closest = frames
+            .iter()
+            .filter(|frame| frame.timestamp >= start && frame.timestamp <= end)
+            .min_by_key(|frame| (frame.timestamp - middle).num_milliseconds().abs());
+        if let Some(frame) = closest {
+            if !selected.iter().any(|f| f.file_path == frame.file_path) {
+                selected.push(frame.clone());
+            }
+        }
+    }
+    selected
+}
+
+/// 选取与前一帧文件大小差异最大的若干帧（JPEG 体积变化近似画面变化），按时间排序
+fn pick_highest_activity(frames: &[Frame], sizes: &[u64], count: usize) -> Vec<Frame> {
+    if frames.len() <= count {
+        return frames.to_vec();
+    }
+
+    let mut scored = (0..frames.len())
+        .map(|index| {
+            let size = sizes.get(index).copied().unwrap_or(0);
+            let previous = if index == 0 {
+                size
+            } else {
+                sizes.get(index - 1).copied().unwrap_or(0)
+            };
+            (index, size.abs_diff(previous))
+        })
+        .collect::<Vec<_>>();
+    // 分数相同时优先较早的帧
+    scored.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut indices = scored
+        .into_iter()
+        .take(count)
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    indices.sort_unstable();
+    indices.into_iter().map(|i| frames[i].clone()).collect()
+}
+
+pub(crate) fn pick_screenshots(frames: &[Frame]) -> Vec<Frame> {
+    if frames.is_empty() {
+        return Vec::new();
+    }
+    if frames.len() == 1 {
+        return vec![frames[0].clone()];
+    }
+
+    let first = frames.first().cloned();
+    let last = frames.last().cloned();
+
+    let mut selected = Vec::new();
+    if let Some(frame) = first {
+        selected.push(frame);
+    }
+    if let Some(frame) = last {
+        if selected
+            .last()
+            .map(|f| f.file_path != frame.file_path)
+            .unwrap_or(true)
+        {
+            selected.push(frame);
+        }
+    }
+    selected
+}
+
+/// 渲染路径模板，替换日期占位符并清理非法片段（空模板回退到默认布局）
+/// 按 strftime 格式生成每日笔记文件名；格式为空、含非法占位符或日期无法解析时返回 None
+fn format_daily_filename(pattern: &str, date: &str) -> Option<String> {
+    use std::fmt::Write;
+
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return None;
+    }
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    // 非法格式在 to_string 中会 panic，这里通过 write! 捕获错误
+    let mut formatted = String::new();
+    write!(formatted, "{}", day.format(pattern)).ok()?;
+    let name = render_path_template(&formatted, "", date, None);
+    (!name.is_empty()).then_some(name)
+}
+
+fn render_path_template(template: &str, fallback: &str, date: &str, week: Option<&str>) -> String {
+    let template = if template.trim().is_empty() {
+        fallback
+    } else {
+        template.trim()
+    };
+    let mut rendered = template.replace("{{date}}", date);
+    if let Ok(day) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        rendered = rendered
+            .replace("{{year}}", &format!("{:04}", day.year()))
+            .replace("{{month}}", &format!("{:02}", day.month()))
+            .replace("{{day}}", &format!("{:02}", day.day()));
+    }
+    if let Some(week) = week {
+        rendered = rendered.replace("{{week}}", week);
+    }
+
+    // 按段清理，禁止 `..` 等跳出根目录的片段
+    rendered
+        .split(['/', '\\'])
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty() && *segment != "." && *segment != "..")
+        .map(sanitize_filename)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 新版本文件路径：`note.md` -> `note-v2.md`
+fn versioned_path(path: &Path, version: usize) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}-v{}.md", stem, version))
+}
+
+/// 比较笔记内容是否一致（忽略每次导出都会变化的 updated_at 行）
+fn note_unchanged(existing: &str, rendered: &str) -> bool {
+    let stable = |text: &str| {
+        text.lines()
+            .filter(|line| !line.starts_with("updated_at:"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    existing == rendered || stable(existing) == stable(rendered)
+}
+
+/// 解析 Obsidian 附件目录（.obsidian/app.json 的 attachmentFolderPath）
+///
+/// - 未配置或 `/`：Vault 根目录
+/// - `./` 开头：相对当前笔记所在目录
+/// - 其他：相对 Vault 根目录
+fn resolve_attachment_folder(vault_root: &Path, note_dir: &Path) -> PathBuf {
+    let setting = std::fs::read_to_string(vault_root.join(".obsidian").join("app.json"))
+        .ok()
+        .and_then(|raw| serde_json::from_str::<serde_json::Value>(&raw).ok())
+        .and_then(|value| {
+            value
+                .get("attachmentFolderPath")
+                .and_then(|path| path.as_str())
+                .map(str::to_string)
+        })
+        .unwrap_or_default();
+    let setting = setting.trim();
+
+    let (base, relative) = match setting.strip_prefix("./") {
+        Some(relative) => (note_dir, relative),
+        None if setting == "." => (note_dir, ""),
+        None => (vault_root, setting.trim_start_matches('/')),
+    };
+    relative
+        .split(['/', '\\'])
+        .filter(|segment| !segment.is_empty() && *segment != "." && *segment != "..")
+        .fold(base.to_path_buf(), |path, segment| path.join(segment))
+}
+
+/// 当前构建实际可用的截图格式（未启用 avif 特性时回退为原始格式）
+fn effective_asset_format(format: AssetFormat) -> AssetFormat {
+    if format == AssetFormat::Avif && !cfg!(feature = "avif") {
+        tracing::warn!("当前构建未启用 AVIF 编码，截图将保持原始格式");
+        return AssetFormat::Original;
+    }
+    format
+}
+
+fn asset_extension(format: AssetFormat) -> &'static str {
+    match format {
+        AssetFormat::Original => "jpg",
+        AssetFormat::Webp => "webp",
+        AssetFormat::Avif => "avif",
+    }
+}
+
+/// 将截图转码为 WebP/AVIF（在阻塞线程中执行编码）
+async fn transcode_image(
+    source: &Path,
+    target: &Path,
+    format: AssetFormat,
+    quality: u8,
+) -> Result<()> {
+    let (source, target) = (source.to_path_buf(), target.to_path_buf());
+    let quality = quality.clamp(1, 100);
+
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let image = image::DynamicImage::ImageRgb8(frame_crypto::open_image(&source)?.to_rgb8());
+        let writer = std::io::BufWriter::new(std::fs::File::create(&target)?);
+        match format {
+            // image 0.24 将有损 WebP 标记为 deprecated，但仍是唯一的有损编码入口
+            #[allow(deprecated)]
+            AssetFormat::Webp => {
+                let quality = image::codecs::webp::WebPQuality::lossy(quality);
+                let encoder = image::codecs::webp::WebPEncoder::new_with_quality(writer, quality);
+                image.write_with_encoder(encoder)?;
+            }
+            #[cfg(feature = "avif")]
+            AssetFormat::Avif => {
+                let encoder =
+                    image::codecs::avif::AvifEncoder::new_with_speed_quality(writer, 8, quality);
+                image.write_with_encoder(encoder)?;
+            }
+            _ => {
+                drop(writer);
+                std::fs::write(&target, frame_crypto::read_frame_blocking(&source)?)?;
+            }
+        }
+        Ok(())
+    })
+    .await?
+}
+
+/// 目标文件已存在且大小一致时视为截图未变化
+async fn same_file_size(source: &Path, target: &Path) -> bool {
+    match (
+        frame_crypto::plaintext_len(source).await,
+        fs::metadata(target).await,
+    ) {
+        (Ok(source), Ok(target)) => source == target.len(),
+        _ => false,
+    }
+}
+
+/// 记录警告并同步发送警告事件
+fn push_warning(warnings: &mut Vec<String>, tx: &UnboundedSender<ExportEvent>, message: String) {
+    let _ = tx.send(ExportEvent::Warning {
+        message: message.clone(),
+    });
+    warnings.push(message);
+}
+
+/// 发送文件写入事件
+fn emit_written(tx: &UnboundedSender<ExportEvent>, kind: &str, path: &Path) {
+    let _ = tx.send(ExportEvent::IndexWritten {
+        kind: kind.to_string(),
+        path: path.to_path_buf(),
+    });
+}
+
+fn sanitize_filename(raw: &str) -> String {
+    raw.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            _ => c,
+        })
+        .collect()
+}
+
+fn to_file_url(path: &str) -> String {
+    let normalized = path.replace('\\', "/").replace(' ', "%20");
+    if normalized.contains(":/") {
+        format!("file:///{}", normalized)
+    } else {
+        format!("file://{}", normalized)
+    }
+}
+
+fn format_markdown_link(label: &str, url: &str) -> String {
+    format!("[{}]({})", label, url)
+}
+
+/// 自定义指标的 frontmatter 行（`metric_<名称>: 值`，无法计算时为 null）
+fn custom_metric_frontmatter(values: &[(String, Option<f64>)]) -> String {
+    values
+        .iter()
+        .map(|(name, value)| {
+            let value = if value.is_some() {
+                format_value(*value)
+            } else {
+                "null".to_string()
+            };
+            format!("metric_{}: {}\n", name, value)
+        })
+        .collect()
+}
+
+/// 自定义指标列表（接在专注度统计之后，每项一行）
+fn custom_metric_list(values: &[(String, Option<f64>)]) -> String {
+    values
+        .iter()
+        .map(|(name, value)| format!("\n- {}: {}", name, format_value(*value)))
+        .collect()
+}
+
+/// 自定义指标的模板占位符（`{{metric_<名称>}}`）
+fn custom_metric_placeholders(values: &[(String, Option<f64>)]) -> Vec<(String, String)> {
+    values
+        .iter()
+        .map(|(name, value)| (format!("metric_{}", name), format_value(*value)))
+        .collect()
+}
+
+fn render_template(template: Option<&str>, fallback: &str, values: &[(&str, String)]) -> String {
+    let mut content = template.unwrap_or(fallback).to_string();
+    for (key, value) in values {
+        let placeholder = format!("{{{{{}}}}}", key);
+        content = content.replace(&placeholder, value);
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::summary::SummaryGenerator;
+    use crate::storage::ProjectRuleRecord;
+    use chrono::TimeZone;
+    use tempfile::tempdir;
+
+    fn zh() -> &'static NoteStrings {
+        i18n::strings(crate::models::ExportLanguage::Zh)
+    }
+
+    async fn create_test_db(dir: &Path) -> Arc<Database> {
+        let db_path = dir.join("test.db");
+        Arc::new(
+            Database::new_sqlite(db_path.to_str().unwrap())
+                .await
+                .unwrap(),
+        )
+    }
+
+    fn sample_session(title: &str, start_hour: u32, end_hour: u32) -> Session {
+        Session {
+            id: None,
+            start_time: Utc.with_ymd_and_hms(2024, 5, 12, start_hour, 0, 0).unwrap(),
+            end_time: Utc.with_ymd_and_hms(2024, 5, 12, end_hour, 0, 0).unwrap(),
+            title: title.to_string(),
+            summary: format!("{} 的总结", title),
+            video_path: None,
+            tags: "[]".to_string(),
+            created_at: None,
+            device_name: None,
+            device_type: None,
+        }
+    }
+
+    /// LLM Actor 立即退出，摘要生成回退到规则模式
+    fn offline_llm_handle() -> LLMHandle {
+        let (actor, llm_handle) = crate::actors::LLMManagerActor::new(crate::llm::LLMManager::new(
+            reqwest::Client::new(),
+        ));
+        drop(actor);
+        llm_handle
+    }
+
+    fn vault_exporter(dir: &Path) -> ObsidianExporter {
+        let vault = dir.join("vault");
+        std::fs::create_dir_all(&vault).unwrap();
+        ObsidianExporter::new(ObsidianExportConfig {
+            vault_path: vault.to_string_lossy().to_string(),
+            ..ObsidianExportConfig::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_retry_failed_sessions() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        let session_id = db
+            .insert_session(&sample_session("接口重构", 9, 10))
+            .await
+            .unwrap();
+
+        let root = temp_dir.path().join("vault");
+        let daily_path = root.join("Daily").join("2024-05-12.md");
+        fs::create_dir_all(daily_path.parent().unwrap())
+            .await
+            .unwrap();
+        fs::write(
+            &daily_path,
+            "---\nsession_count: 0\n---\n\n## 会话索引\n- 当天没有会话记录\n\n## 使用模式\n暂无统计\n",
+        )
+        .await
+        .unwrap();
+
+        // 在会话文件位置放一个目录，使首次导出失败
+        let session_path = root
+            .join("Sessions")
+            .join("2024-05-12")
+            .join(format!("2024-05-12_0900-1000_session-{}.md", session_id));
+        fs::create_dir_all(&session_path).await.unwrap();
+
+        let exporter = ObsidianExporter::new(ObsidianExportConfig::default());
+        let outcome = exporter
+            .retry_failed_sessions(db.clone(), &root, &[session_id])
+            .await
+            .unwrap();
+        assert!(outcome.session_paths.is_empty());
+        assert_eq!(outcome.failed_sessions.len(), 1);
+        assert_eq!(outcome.failed_sessions[0].session_id, session_id);
+
+        fs::remove_dir(&session_path).await.unwrap();
+        let outcome = exporter
+            .retry_failed_sessions(db.clone(), &root, &[session_id])
+            .await
+            .unwrap();
+        assert!(outcome.failed_sessions.is_empty());
+        assert_eq!(outcome.session_paths, vec![session_path.clone()]);
+        assert!(session_path.is_file());
+
+        let daily = fs::read_to_string(&daily_path).await.unwrap();
+        assert!(daily.contains(&format!(
+            "- [[Sessions/2024-05-12/2024-05-12_0900-1000_session-{}.md]]",
+            session_id
+        )));
+        assert!(daily.contains("session_count: 1"));
+        assert!(!daily.contains("当天没有会话记录"));
+
+        // 会话已不存在时同样返回逐个会话的失败原因
+        let outcome = exporter
+            .retry_failed_sessions(db.clone(), &root, &[9999])
+            .await
+            .unwrap();
+        assert!(outcome.session_paths.is_empty());
+        assert_eq!(outcome.failed_sessions.len(), 1);
+        assert_eq!(outcome.failed_sessions[0].session_id, 9999);
+    }
+
+    #[tokio::test]
+    async fn test_export_sessions_only_writes_session_notes() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        let first = db
+            .insert_session(&sample_session("接口重构", 9, 10))
+            .await
+            .unwrap();
+        db.insert_session(&sample_session("代码评审", 14, 15))
+            .await
+            .unwrap();
+
+        let exporter = vault_exporter(temp_dir.path());
+        let root = exporter.resolve_root().unwrap();
+        let outcome = exporter
+            .export_sessions(db.clone(), &root, &[first, 9999])
+            .await
+            .unwrap();
+
+        let session_path = root
+            .join("Sessions")
+            .join("2024-05-12")
+            .join(format!("2024-05-12_0900-1000_session-{}.md", first));
+        assert_eq!(outcome.session_paths, vec![session_path.clone()]);
+        assert!(session_path.is_file());
+        assert_eq!(outcome.failed_sessions.len(), 1);
+        assert_eq!(outcome.failed_sessions[0].session_id, 9999);
+        assert!(outcome.render_message().starts_with("已导出会话笔记: 1 个"));
+
+        // 不生成每日总结、索引与另一个会话
+        assert!(!root.join("Daily").exists());
+        assert!(outcome.index_note_path.is_none());
+        assert_eq!(
+            std::fs::read_dir(session_path.parent().unwrap())
+                .unwrap()
+                .count(),
+            1
+        );
+
+        assert!(exporter.export_sessions(db, &root, &[9999]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_export_project_notes() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        let first = db
+            .insert_session(&sample_session("API 接口重构", 9, 11))
+            .await
+            .unwrap();
+        db.insert_session(&sample_session("api 联调", 14, 15))
+            .await
+            .unwrap();
+        db.insert_session(&sample_session("午休", 12, 13))
+            .await
+            .unwrap();
+        db.insert_project_rule(&ProjectRuleRecord {
+            id: None,
+            project: "Backend/API".to_string(),
+            match_field: "title".to_string(),
+            pattern: "API".to_string(),
+            created_at: Utc::now(),
+        })
+        .await
+        .unwrap();
+
+        let exporter = vault_exporter(temp_dir.path());
+        let root = exporter.resolve_root().unwrap();
+        // 早于会话日期时不计入
+        assert!(exporter
+            .export_project_notes(&db, "2024-05-11", &root)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let paths = exporter
+            .export_project_notes(&db, "2024-05-12", &root)
+            .await
+            .unwrap();
+        assert_eq!(paths, vec![root.join("Projects").join("Backend_API.md")]);
+
+        let content = std::fs::read_to_string(&paths[0]).unwrap();
+        assert!(content.contains("project: \"Backend/API\""));
+        assert!(content.contains("total_minutes: 180"));
+        assert!(content.contains("session_count: 2"));
+        assert!(content.contains("# 项目：Backend/API"));
+        assert!(content.contains("| 2024-W19 | 180 |"));
+        assert!(content.contains(&format!(
+            "- 2024-05-12 09:00 [[Sessions/2024-05-12/2024-05-12_0900-1100_session-{}.md|API 接口重构]]（120 分钟）",
+            first
+        )));
+        assert!(!content.contains("午休"));
+    }
+
+    #[test]
+    fn test_session_filename_title_slug() {
+        let clock = ExportClock::default();
+        let mut session = sample_session("API Refactor: auth/login 模块", 9, 10);
+        session.id = Some(42);
+
+        assert_eq!(
+            session_note_filename(clock, &session, None),
+            "2024-05-12_0900-1000_session-42.md"
+        );
+        assert_eq!(
+            session_note_filename(clock, &session, Some(40)),
+            "2024-05-12_0900-1000_session-42_api-refactor-auth-login-模块.md"
+        );
+        // 截断后不以连字符结尾
+        assert_eq!(
+            session_note_filename(clock, &session, Some(13)),
+            "2024-05-12_0900-1000_session-42_api-refactor.md"
+        );
+
+        session.title = " ?? ".to_string();
+        assert_eq!(
+            session_note_filename(clock, &session, Some(40)),
+            "2024-05-12_0900-1000_session-42.md"
+        );
+    }
+
+    #[test]
+    fn test_mixed_case_categories_collapse() {
+        let activities = vec![
+            Activity {
+                date: "2024-05-12".to_string(),
+                session_count: 2,
+                total_duration_minutes: 90,
+                main_categories: vec![" Work".to_string(), "learning".to_string()],
+            },
+            Activity {
+                date: "2024-05-13".to_string(),
+                session_count: 1,
+                total_duration_minutes: 30,
+                main_categories: vec!["work ".to_string(), "WORK".to_string()],
+            },
+        ];
+
+        let mut labels = HashMap::new();
+        labels.insert("Work".to_string(), "工作".to_string());
+
+        let categories = count_categories(&activities, &labels);
+        assert_eq!(
+            categories,
+            vec![("工作".to_string(), 3), ("learning".to_string(), 1)]
+        );
+        assert_eq!(
+            format_top_categories(zh(), &categories),
+            "工作(3)、learning(1)"
+        );
+        assert_eq!(
+            format_day_categories(&activities[1].main_categories, &labels),
+            "工作"
+        );
+
+        // 未配置映射时保留首次出现的写法
+        let categories = count_categories(&activities, &HashMap::new());
+        assert_eq!(categories[0], ("Work".to_string(), 3));
+    }
+
+    #[test]
+    fn test_session_note_footer_snapshot() {
+        let mut session = sample_session("接口重构", 9, 10);
+        session.id = Some(42);
+        let metrics = build_session_metrics(zh(), &[], 60);
+
+        let render = |config: ObsidianExportConfig| {
+            ObsidianExporter::new(config).render_session_note(
+                &session,
+                "09:00",
+                "10:00",
+                60,
+                "[]",
+                "",
+                "- 无可用时间线",
+                "",
+                "",
+                "",
+                "暂无指标",
+                &metrics,
+                "",
+                "",
+            )
+        };
+
+        let plain = render(ObsidianExportConfig::default());
+        assert!(!plain.contains("由 Screen Analyzer"));
+
+        let with_footer = render(ObsidianExportConfig {
+            include_footer: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            with_footer,
+            format!(
+                "{}\n---\n> 由 Screen Analyzer v{} 生成 · 数据截至 2024-05-12 10:00\n",
+                plain,
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+
+    #[test]
+    fn test_session_note_dataview_inline_fields() {
+        let session = sample_session("接口重构", 9, 10);
+        let card = |category: &str, start: &str, end: &str| TimelineCardRecord {
+            id: None,
+            session_id: 1,
+            llm_call_id: None,
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            category: category.to_string(),
+            subcategory: String::new(),
+            title: String::new(),
+            summary: String::new(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: "[]".to_string(),
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+            created_at: Utc::now(),
+        };
+        let cards = vec![
+            card(
+                "work",
+                "2024-05-12T09:00:00+00:00",
+                "2024-05-12T09:45:00+00:00",
+            ),
+            card(
+                "personal",
+                "2024-05-12T09:45:00+00:00",
+                "2024-05-12T10:00:00+00:00",
+            ),
+        ];
+        let metrics = build_session_metrics(zh(), &cards, 60);
+        assert_eq!(metrics.focus_ratio, 75);
+
+        let render = |dataview_inline_fields: bool| {
+            ObsidianExporter::new(ObsidianExportConfig {
+                dataview_inline_fields,
+                ..Default::default()
+            })
+            .render_session_note(
+                &session, "09:00", "10:00", 60, "[work]", "", "", "", "", "", "", &metrics, "", "",
+            )
+        };
+
+        assert!(!render(false).contains("::"));
+        let note = render(true);
+        assert!(note.contains(
+            "# 接口重构\n\n\
+date:: 2024-05-12\n\
+duration:: 60\n\
+focus-ratio:: 75\n\
+context-switches:: 1\n\
+fragmentation:: 低\n\
+category:: work\n\n"
+        ));
+    }
+
+    #[test]
+    fn test_session_note_notes_block() {
+        let session = sample_session("接口重构", 9, 10);
+        let metrics = build_session_metrics(zh(), &[], 60);
+        let note = SessionNoteRecord::new(
+            1,
+            "和后端对齐了分页参数",
+            &["客户 A".to_string(), "#发布".to_string()],
+        )
+        .unwrap();
+        let notes = render_session_notes(&note);
+        assert_eq!(notes, "和后端对齐了分页参数\n\n#客户-a #发布");
+
+        let render = |notes: &str| {
+            ObsidianExporter::new(ObsidianExportConfig::default()).render_session_note(
+                &session, "09:00", "10:00", 60, "[]", "", "", "", notes, "", "", &metrics, "", "",
+            )
+        };
+        assert!(!render("").contains("## 备注"));
+        assert!(render(&notes).contains("\n## 备注\n和后端对齐了分页参数\n\n#客户-a #发布\n"));
+    }
+
+    #[test]
+    fn test_session_note_focus_blocks() {
+        let at =
+            |hour: u32, minute: u32| Utc.with_ymd_and_hms(2024, 5, 12, hour, minute, 0).unwrap();
+        let session = sample_session("接口重构", 9, 10);
+        let block = |label: &str, start: DateTime<Utc>, status: &str| FocusBlockRecord {
+            id: Some(1),
+            label: label.to_string(),
+            planned_minutes: 25,
+            started_at: start,
+            ended_at: (status != "running").then(|| start + chrono::Duration::minutes(20)),
+            status: status.to_string(),
+            distraction_count: 2,
+            distractions: format!(
+                r#"[{{"timestamp":"{0}","category":"personal","app":"Netflix"}},{{"timestamp":"{0}","category":"personal","app":"Netflix"}}]"#,
+                start.to_rfc3339()
+            ),
+        };
+        let blocks = vec![
+            // 异常退出未写回结束时间的旧时段按计划时长计，不与会话重叠
+            block("旧时段", at(8, 0), "running"),
+            block("写周报", at(9, 10), "cancelled"),
+        ];
+
+        let exporter = ObsidianExporter::new(ObsidianExportConfig::default());
+        let rendered = render_focus_blocks(zh(), exporter.clock(), &blocks, &session);
+        assert_eq!(
+            rendered,
+            "- 09:10-09:30 写周报：计划 25 分钟，提前结束，干扰 2 次（Netflix）"
+        );
+
+        let metrics = build_session_metrics(zh(), &[], 60);
+        let note = exporter.render_session_note(
+            &session, "09:00", "10:00", 60, "[]", "", "", "", "", &rendered, "", &metrics, "", "",
+        );
+        assert!(note.contains("\n## 专注时段\n- 09:10-09:30 写周报"));
+    }
+
+    #[test]
+    fn test_render_body_tags() {
+        let card = |app_sites: &str| TimelineCardRecord {
+            id: None,
+            session_id: 1,
+            llm_call_id: None,
+            start_time: String::new(),
+            end_time: String::new(),
+            category: "work".to_string(),
+            subcategory: String::new(),
+            title: String::new(),
+            summary: String::new(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: app_sites.to_string(),
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+            created_at: Utc::now(),
+        };
+        let cards = vec![
+            card(r#"{"primary":"VSCode","secondary":["Terminal"]}"#),
+            card(r#"{"primary":"github.com"}"#),
+            card(r#"{"primary":"VSCode"}"#),
+            card("无效 JSON"),
+        ];
+        let tags = parse_tags(r#"[{"category":"work","confidence":0.9,"keywords":[]}]"#);
+
+        assert_eq!(
+            render_body_tags("screen-analyzer", &tags, &cards),
+            "#screen-analyzer/work #screen-analyzer/app/vscode #screen-analyzer/app/github-com"
+        );
+        assert_eq!(render_body_tags("#sa/", &tags, &[]), "#sa/work");
+        assert_eq!(render_body_tags("", &tags, &[]), "#work");
+        assert_eq!(tag_slug(" Google Chrome "), "google-chrome");
+    }
+
+    #[tokio::test]
+    async fn test_daily_note_app_breakdown() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        let session_id = db
+            .insert_session(&sample_session("编写文档", 9, 10))
+            .await
+            .unwrap();
+        let card = |app_sites: &str, start: &str, end: &str| TimelineCardRecord {
+            id: None,
+            session_id,
+            llm_call_id: None,
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            category: "work".to_string(),
+            subcategory: String::new(),
+            title: String::new(),
+            summary: String::new(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: app_sites.to_string(),
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+            created_at: Utc::now(),
+        };
+        for record in [
+            card(
+                r#"{"primary":"VSCode","secondary":[]}"#,
+                "2024-05-12T09:00:00+00:00",
+                "2024-05-12T09:30:00+00:00",
+            ),
+            card(
+                r#"{"primary":"Chrome","secondary":[]}"#,
+                "2024-05-12T09:30:00+00:00",
+                "2024-05-12T09:45:00+00:00",
+            ),
+            card(
+                r#"{"primary":"VSCode","secondary":[]}"#,
+                "2024-05-12T09:45:00+00:00",
+                "2024-05-12T10:00:00+00:00",
+            ),
+        ] {
+            db.insert_timeline_card(&record).await.unwrap();
+        }
+
+        let vault = temp_dir.path().join("vault");
+        std::fs::create_dir_all(&vault).unwrap();
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            vault_path: vault.to_string_lossy().to_string(),
+            include_app_breakdown: true,
+            ..ObsidianExportConfig::default()
+        });
+        let outcome = exporter
+            .export_day(db.clone(), offline_llm_handle(), "2024-05-12", false)
+            .await
+            .unwrap();
+
+        // 没有帧级应用数据时按时间线卡片估算并注明
+        let content = std::fs::read_to_string(&outcome.daily_note_path).unwrap();
+        assert!(content.contains(
+            "## 应用使用\n\
+> 未采集窗口信息，以下按时间线卡片的主要应用估算\n\n\
+- VSCode：45 分钟（75%）\n\
+- Chrome：15 分钟（25%）\n"
+        ));
+
+        // 有按帧统计的应用使用时以其为准，并带出停留最久的窗口标题
+        let usage = |app: &str, title: &str, seconds: i64| AppUsageRecord {
+            session_id,
+            app_name: app.to_string(),
+            process_path: None,
+            window_title: title.to_string(),
+            seconds,
+        };
+        db.replace_app_usage(
+            session_id,
+            &[
+                usage("Code", "README.md - screen-analyzer", 2400),
+                usage("Firefox", "docs.rs", 1200),
+            ],
+        )
+        .await
+        .unwrap();
+        let outcome = exporter
+            .export_day(db, offline_llm_handle(), "2024-05-12", false)
+            .await
+            .unwrap();
+        let content = std::fs::read_to_string(outcome.daily_note_path).unwrap();
+        assert!(content.contains(
+            "## 应用使用\n\
+- Code（README.md - screen-analyzer）：40 分钟（66%）\n\
+- Firefox（docs.rs）：20 分钟（33%）\n"
+        ));
+        assert!(!content.contains("VSCode"));
+    }
+
+    #[test]
+    fn test_aggregate_app_usage_merges_sessions() {
+        let usage = |session_id: i64, app: &str, title: &str, seconds: i64| AppUsageRecord {
+            session_id,
+            app_name: app.to_string(),
+            process_path: None,
+            window_title: title.to_string(),
+            seconds,
+        };
+        let lines = aggregate_app_usage(
+            zh(),
+            &[
+                usage(1, "Code", "main.rs", 600),
+                usage(2, "Code", "lib.rs", 900),
+                usage(2, "", "", 120),
+                usage(3, "Slack", "", 10),
+            ],
+        );
+        assert_eq!(
+            lines,
+            vec![
+                AppUsageLine {
+                    app: "Code".to_string(),
+                    window_title: Some("lib.rs".to_string()),
+                    minutes: 25,
+                },
+                AppUsageLine {
+                    app: "未知应用".to_string(),
+                    window_title: None,
+                    minutes: 2,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_week_canvas_links_daily_and_session_notes() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        let long_id = db
+            .insert_session(&sample_session("编写文档", 9, 12))
+            .await
+            .unwrap();
+        let mut monday = sample_session("代码评审", 14, 15);
+        monday.start_time = Utc.with_ymd_and_hms(2024, 5, 6, 14, 0, 0).unwrap();
+        monday.end_time = Utc.with_ymd_and_hms(2024, 5, 6, 15, 0, 0).unwrap();
+        let monday_id = db.insert_session(&monday).await.unwrap();
+
+        let vault = temp_dir.path().join("vault");
+        std::fs::create_dir_all(&vault).unwrap();
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            vault_path: vault.to_string_lossy().to_string(),
+            generate_week_canvas: true,
+            ..ObsidianExportConfig::default()
+        });
+        exporter
+            .export_day(db, offline_llm_handle(), "2024-05-12", false)
+            .await
+            .unwrap();
+
+        let canvas_path = vault.join("ScreenAnalyzer/Weekly/2024-W19.canvas");
+        let canvas: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(canvas_path).unwrap()).unwrap();
+        let files = canvas["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|node| node["file"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            files,
+            vec![
+                "ScreenAnalyzer/Weekly/2024-W19.md".to_string(),
+                "ScreenAnalyzer/Daily/2024-05-06.md".to_string(),
+                format!(
+                    "ScreenAnalyzer/Sessions/2024-05-06/2024-05-06_1400-1500_session-{}.md",
+                    monday_id
+                ),
+                "ScreenAnalyzer/Daily/2024-05-12.md".to_string(),
+                format!(
+                    "ScreenAnalyzer/Sessions/2024-05-12/2024-05-12_0900-1200_session-{}.md",
+                    long_id
+                ),
+            ]
+        );
+        assert_eq!(canvas["edges"].as_array().unwrap().len(), 4);
+        assert_eq!(canvas["nodes"][1]["x"], 0);
+        assert_eq!(
+            canvas["nodes"][3]["x"],
+            6 * (CANVAS_NODE_WIDTH + CANVAS_GAP)
+        );
+    }
+
+    #[test]
+    fn test_render_timeline_callouts() {
+        let card = |category: &str, title: &str, summary: &str, start: &str, end: &str| {
+            TimelineCardRecord {
+                id: None,
+                session_id: 1,
+                llm_call_id: None,
+                start_time: start.to_string(),
+                end_time: end.to_string(),
+                category: category.to_string(),
+                subcategory: if category == "Work" {
+                    "coding".to_string()
+                } else {
+                    String::new()
+                },
+                title: title.to_string(),
+                summary: summary.to_string(),
+                detailed_summary: String::new(),
+                distractions: None,
+                app_sites: "[]".to_string(),
+                confidence: None,
+                heuristic: false,
+                video_preview_path: None,
+                created_at: Utc::now(),
+            }
+        };
+        let cards = vec![
+            card(
+                "Work",
+                "接口重构",
+                "拆分鉴权模块\n补充测试",
+                "2024-05-12T09:00:00+08:00",
+                "2024-05-12T10:00:00+08:00",
+            ),
+            card(
+                "idle",
+                "离开",
+                "",
+                "2024-05-12T10:00:00+08:00",
+                "2024-05-12T10:15:00+08:00",
+            ),
+            card(
+                "",
+                "未分类",
+                "",
+                "2024-05-12T11:00:00+08:00",
+                "2024-05-12T11:05:00+08:00",
+            ),
+        ];
+        let callouts = HashMap::from([(" IDLE ".to_string(), "Quote".to_string())]);
+
+        assert_eq!(
+            render_timeline_callouts(zh(), ExportClock::default(), &cards, &callouts),
+            "> [!work] 09:00-10:00 接口重构\n\
+> Work / coding\n\
+> 拆分鉴权模块\n\
+> 补充测试\n\
+\n\
+> [!quote] 10:00-10:15 离开\n\
+> idle\n\
+\n\
+> [!note] 11:00-11:05 未分类"
+        );
+        assert_eq!(
+            render_timeline_callouts(zh(), ExportClock::default(), &[], &callouts),
+            "- 无可用时间线"
+        );
+    }
+
+    #[test]
+    fn test_render_mermaid_timeline() {
+        let card = |category: &str, title: &str, start: &str, end: &str| TimelineCardRecord {
+            id: None,
+            session_id: 1,
+            llm_call_id: None,
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            category: category.to_string(),
+            subcategory: String::new(),
+            title: title.to_string(),
+            summary: String::new(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: "[]".to_string(),
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+            created_at: Utc::now(),
+        };
+        let cards = vec![
+            card(
+                "work",
+                "修复 #12: 登录",
+                "2024-05-12T09:00:00+08:00",
+                "2024-05-12T09:30:00+08:00",
+            ),
+            card(
+                "personal",
+                "午休",
+                "2024-05-12T12:00:00+08:00",
+                "2024-05-12T13:00:00+08:00",
+            ),
+            card(
+                "work",
+                "加班",
+                "2024-05-12T23:00:00+08:00",
+                "2024-05-13T00:30:00+08:00",
+            ),
+            card("work", "无效时间", "bad", "2024-05-12T10:00:00+08:00"),
+        ];
+
+        assert_eq!(
+            render_mermaid_timeline(zh(), ExportClock::default(), &cards),
+            [
+                "```mermaid",
+                "gantt",
+                "    dateFormat HH:mm",
+                "    axisFormat %H:%M",
+                "    section work",
+                "    修复 12 登录 :09:00, 09:30",
+                "    加班 :23:00, 23:59",
+                "    section personal",
+                "    午休 :12:00, 13:00",
+                "```",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            render_mermaid_timeline(zh(), ExportClock::default(), &[]),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_export_clock_converts_card_times() {
+        let card = TimelineCardRecord {
+            id: None,
+            session_id: 1,
+            llm_call_id: None,
+            start_time: "2024-05-12T15:30:00+00:00".to_string(),
+            end_time: "2024-05-12T16:45:00+00:00".to_string(),
+            category: "work".to_string(),
+            subcategory: "coding".to_string(),
+            title: "开发".to_string(),
+            summary: "实现功能".to_string(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: "[]".to_string(),
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+            created_at: Utc::now(),
+        };
+
+        // 未配置时区：按卡片自带的时区显示
+        assert_eq!(
+            render_timeline(zh(), ExportClock::default(), std::slice::from_ref(&card)),
+            "- 15:30-16:45 [work / coding] 开发：实现功能"
+        );
+
+        // UTC+8：跨过零点后按换算后的日期截断
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            timezone_offset_minutes: Some(480),
+            ..Default::default()
+        });
+        let clock = exporter.clock();
+        assert_eq!(
+            render_timeline(zh(), clock, std::slice::from_ref(&card)),
+            "- 23:30-00:45 [work / coding] 开发：实现功能"
+        );
+        assert!(render_mermaid_timeline(zh(), clock, &[card]).contains("    开发 :23:30, 23:59"));
+
+        // 超出范围的偏移视为未配置
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            timezone_offset_minutes: Some(100 * 60),
+            ..Default::default()
+        });
+        assert!(exporter.clock().offset.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_overview_dashboard_statistics() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        let today_id = db
+            .insert_session(&sample_session("编写文档", 9, 10))
+            .await
+            .unwrap();
+        for (day, title) in [(11, "代码评审"), (3, "需求分析")] {
+            let mut session = sample_session(title, 14, 16);
+            session.start_time = Utc.with_ymd_and_hms(2024, 5, day, 14, 0, 0).unwrap();
+            session.end_time = Utc.with_ymd_and_hms(2024, 5, day, 16, 0, 0).unwrap();
+            db.insert_session(&session).await.unwrap();
+        }
+        db.insert_timeline_card(&TimelineCardRecord {
+            id: None,
+            session_id: today_id,
+            llm_call_id: None,
+            start_time: "2024-05-12T09:00:00+00:00".to_string(),
+            end_time: "2024-05-12T10:00:00+00:00".to_string(),
+            category: "work".to_string(),
+            subcategory: String::new(),
+            title: String::new(),
+            summary: String::new(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: "[]".to_string(),
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+            created_at: Utc::now(),
+        })
+        .await
+        .unwrap();
+
+        let exporter = vault_exporter(temp_dir.path());
+        let outcome = exporter
+            .export_day(db, offline_llm_handle(), "2024-05-12", false)
+            .await
+            .unwrap();
+        let overview = std::fs::read_to_string(outcome.overview_path.unwrap()).unwrap();
+
+        assert!(overview.contains("| 近 7 天 | 2 | 180 | 2 |\n| 近 30 天 | 3 | 300 | 3 |"));
+        assert!(overview.contains("- 当前连续记录：2 天\n- 近 30 天最长连续：2 天"));
+        assert!(overview.contains("`·······█` 近 8 周"));
+        assert!(overview.contains("- 最近：2024-W19 专注占比 100%"));
+        assert!(overview.contains(
+            "## 最近周报\n\
+- [[Weekly/2024-W19]]：2 个会话，180 分钟\n\
+- [[Weekly/2024-W18]]：1 个会话，120 分钟\n"
+        ));
+    }
+
+    #[test]
+    fn test_daily_note_groups_sessions_by_project() {
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            group_sessions_by: SessionGrouping::Project,
+            ..Default::default()
+        });
+
+        let titles = [
+            "[Alpha] 接口设计",
+            "[Beta] 修复崩溃",
+            "[Alpha] 代码评审",
+            "午休",
+        ];
+        let entries = titles
+            .iter()
+            .enumerate()
+            .map(|(index, title)| {
+                let mut session = sample_session(title, 9, 10);
+                session.id = Some(index as i64 + 1);
+                SessionEntry {
+                    link: format!("[[Sessions/2024-05-12/session-{}.md]]", index + 1),
+                    group: exporter.session_group(&session),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let summary = crate::domains::summary::DaySummary {
+            date: "2024-05-12".to_string(),
+            summary_text: "测试总结".to_string(),
+            device_stats: vec![],
+            parallel_work: vec![],
+            usage_patterns: vec![],
+            active_device_count: 1,
+            domain_stats: Vec::new(),
+            app_limit_breaches: Vec::new(),
+        };
+        let content = exporter.render_daily_note(&summary, &entries, &[], &[], &[], &[]);
+
+        assert!(content.contains(
+            "## 会话索引\n\
+### Alpha\n\
+- [[Sessions/2024-05-12/session-1.md]]\n\
+- [[Sessions/2024-05-12/session-3.md]]\n\
+\n\
+### Beta\n\
+- [[Sessions/2024-05-12/session-2.md]]\n\
+\n\
+### 未分组\n\
+- [[Sessions/2024-05-12/session-4.md]]\n"
+        ));
+        assert!(content.contains("session_count: 4"));
+    }
+
+    #[test]
+    fn test_daily_note_meetings() {
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            include_footer: false,
+            ..Default::default()
+        });
+        let summary = crate::domains::summary::DaySummary {
+            date: "2024-05-12".to_string(),
+            summary_text: "测试总结".to_string(),
+            device_stats: vec![],
+            parallel_work: vec![],
+            usage_patterns: vec![],
+            active_device_count: 1,
+            domain_stats: Vec::new(),
+            app_limit_breaches: Vec::new(),
+        };
+        let meeting = |start: u32, end: u32, title: &str, location: &str| CalendarEventRecord {
+            id: None,
+            event_id: title.to_string(),
+            date: "2024-05-12".to_string(),
+            title: title.to_string(),
+            location: location.to_string(),
+            start_time: Utc.with_ymd_and_hms(2024, 5, 12, start, 0, 0).unwrap(),
+            end_time: Utc.with_ymd_and_hms(2024, 5, 12, end, 30, 0).unwrap(),
+            synced_at: Utc.with_ymd_and_hms(2024, 5, 12, 8, 0, 0).unwrap(),
+        };
+        let meetings = vec![
+            meeting(10, 10, "站会", ""),
+            meeting(14, 15, "产品评审", "会议室 A"),
+        ];
+
+        let content = exporter.render_daily_note(&summary, &[], &[], &[], &meetings, &[]);
+        assert!(content.contains(
+            "## 会议\n\
+- 10:00-10:30 站会\n\
+- 14:00-15:30 产品评审 · 会议室 A\n"
+        ));
+        let content = exporter.render_daily_note(&summary, &[], &[], &[], &[], &[]);
+        assert!(!content.contains("## 会议"));
+    }
+
+    #[test]
+    fn test_daily_note_goals() {
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            include_footer: false,
+            ..Default::default()
+        });
+        let summary = crate::domains::summary::DaySummary {
+            date: "2024-05-13".to_string(),
+            summary_text: "测试总结".to_string(),
+            device_stats: vec![],
+            parallel_work: vec![],
+            usage_patterns: vec![],
+            active_device_count: 1,
+            domain_stats: Vec::new(),
+            app_limit_breaches: Vec::new(),
+        };
+        let status =
+            |name: &str, comparison: &str, actual: i64, passed: bool, streak: u32| GoalStatus {
+                goal: crate::storage::GoalRecord {
+                    id: Some(1),
+                    name: name.to_string(),
+                    metric: "focus".to_string(),
+                    target: String::new(),
+                    comparison: comparison.to_string(),
+                    target_minutes: if comparison == "at_most" { 60 } else { 180 },
+                    schedule: "daily".to_string(),
+                    enabled: true,
+                    created_at: Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap(),
+                },
+                date: "2024-05-13".to_string(),
+                scheduled: true,
+                actual_minutes: actual,
+                passed,
+                in_progress: false,
+                streak,
+            };
+        let mut skipped = status("周末休息", "at_most", 0, true, 0);
+        skipped.scheduled = false;
+        let goals = vec![
+            status("专注编码", "at_least", 200, true, 4),
+            status("社交网站", "at_most", 75, false, 0),
+            skipped,
+        ];
+
+        let content = exporter.render_daily_note(&summary, &[], &[], &[], &[], &goals);
+        assert!(content.contains(
+            "## 目标\n\
+- ✅ 专注编码：200 分钟（目标 ≥ 180 分钟），已连续达成 4 天\n\
+- ❌ 社交网站：75 分钟（目标 ≤ 60 分钟）\n"
+        ));
+        assert!(!content.contains("周末休息"));
+        let content = exporter.render_daily_note(&summary, &[], &[], &[], &[], &[]);
+        assert!(!content.contains("## 目标"));
+    }
+
+    #[test]
+    fn test_daily_note_websites() {
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            include_footer: false,
+            ..Default::default()
+        });
+        let mut summary = crate::domains::summary::DaySummary {
+            date: "2024-05-12".to_string(),
+            summary_text: "测试总结".to_string(),
+            device_stats: vec![],
+            parallel_work: vec![],
+            usage_patterns: vec![],
+            active_device_count: 1,
+            domain_stats: Vec::new(),
+            app_limit_breaches: Vec::new(),
+        };
+        let content = exporter.render_daily_note(&summary, &[], &[], &[], &[], &[]);
+        assert!(!content.contains("## 网站访问"));
+
+        summary.domain_stats = vec![crate::domains::summary::DomainStat {
+            domain: "github.com".to_string(),
+            total_minutes: 75,
+            total_time: "1h 15m".to_string(),
+            session_count: 2,
+        }];
+        let content = exporter.render_daily_note(&summary, &[], &[], &[], &[], &[]);
+        assert!(content.ends_with("## 网站访问\n- github.com：1h 15m\n"));
+    }
+
+    #[test]
+    fn test_daily_note_custom_metrics() {
+        let card = |category: &str, start: &str, end: &str| TimelineCardRecord {
+            id: None,
+            session_id: 1,
+            llm_call_id: None,
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            category: category.to_string(),
+            subcategory: String::new(),
+            title: String::new(),
+            summary: String::new(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: "[]".to_string(),
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+            created_at: Utc::now(),
+        };
+        let cards = vec![
+            card(
+                "work",
+                "2024-05-12T09:00:00+00:00",
+                "2024-05-12T11:00:00+00:00",
+            ),
+            card(
+                "communication",
+                "2024-05-12T11:00:00+00:00",
+                "2024-05-12T11:45:00+00:00",
+            ),
+        ];
+        let metrics = vec![
+            crate::models::CustomMetric {
+                name: "deep_work".to_string(),
+                expression: "work + learning - communication * 0.5".to_string(),
+            },
+            crate::models::CustomMetric {
+                name: "per_idle".to_string(),
+                expression: "work / idle".to_string(),
+            },
+        ];
+        let summary = crate::domains::summary::DaySummary {
+            date: "2024-05-12".to_string(),
+            summary_text: String::new(),
+            device_stats: vec![],
+            parallel_work: vec![],
+            usage_patterns: vec![],
+            active_device_count: 1,
+            domain_stats: Vec::new(),
+            app_limit_breaches: Vec::new(),
+        };
+
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            custom_metrics: metrics.clone(),
+            ..Default::default()
+        });
+        assert!(exporter.wants_day_cards());
+        let content = exporter.render_daily_note(&summary, &[], &cards, &[], &[], &[]);
+        assert!(content.contains("metric_deep_work: 97.5\nmetric_per_idle: null\nsource:"));
+
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            custom_metrics: metrics,
+            daily_template: Some("深度工作 {{metric_deep_work}} / {{metric_per_idle}}".to_string()),
+            include_footer: false,
+            ..Default::default()
+        });
+        let content = exporter.render_daily_note(&summary, &[], &cards, &[], &[], &[]);
+        assert_eq!(content, "深度工作 97.5 / -");
+    }
+
+    #[test]
+    fn test_daily_note_english_strings() {
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            export_language: crate::models::ExportLanguage::En,
+            daily_template: Some("{{weekday}} | {{long_date}}".to_string()),
+            include_footer: false,
+            ..Default::default()
+        });
+        let summary = crate::domains::summary::DaySummary {
+            date: "2024-05-12".to_string(),
+            summary_text: "Test summary".to_string(),
+            device_stats: vec![],
+            parallel_work: vec![],
+            usage_patterns: vec![],
+            active_device_count: 1,
+            domain_stats: Vec::new(),
+            app_limit_breaches: Vec::new(),
+        };
+
+        let content = exporter.render_daily_note(&summary, &[], &[], &[], &[], &[]);
+        assert_eq!(content, "Sunday | Sunday, May 12, 2024");
+
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            export_language: crate::models::ExportLanguage::En,
+            include_footer: false,
+            ..Default::default()
+        });
+        let content = exporter.render_daily_note(&summary, &[], &[], &[], &[], &[]);
+        assert!(content.contains("# Screen Activity Summary — Sunday, May 12, 2024\n"));
+        assert!(content.contains("## Sessions\n- No sessions recorded\n"));
+        assert!(content.contains("## Usage Patterns\nNo statistics yet\n"));
+        assert!(!content.contains("会话索引"));
+
+        // 追加会话链接时按当前语言识别会话索引标题并替换空占位
+        let updated = insert_session_links(exporter.text(), &content, &[&"[[s1]]".to_string()]);
+        assert!(updated.contains("## Sessions\n- [[s1]]\n\n"));
+        assert!(updated.contains("session_count: 1"));
+    }
+
+    #[tokio::test]
+    async fn test_export_day_event_order() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        db.insert_session(&sample_session("编写文档", 9, 10))
+            .await
+            .unwrap();
+        db.insert_session(&sample_session("代码评审", 14, 15))
+            .await
+            .unwrap();
+
+        let llm_handle = offline_llm_handle();
+        let exporter = vault_exporter(temp_dir.path());
+
+        let (tx, mut rx) = unbounded_channel();
+        let outcome = exporter
+            .export_day_with_events(db, llm_handle, "2024-05-12", false, tx)
+            .await
+            .unwrap();
+
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+        let tags = events
+            .iter()
+            .map(|event| {
+                let value: serde_json::Value =
+                    serde_json::from_str(event.to_ndjson().trim_end()).unwrap();
+                value["event"].as_str().unwrap().to_string()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tags,
+            vec![
+                "started",
+                "session_exported",
+                "session_exported",
+                "index_written",
+                "index_written",
+                "index_written",
+                "index_written",
+                "index_written",
+                "finished",
+            ]
+        );
+        assert!(matches!(
+            events[0],
+            ExportEvent::Started {
+                session_count: 2,
+                ..
+            }
+        ));
+        assert!(matches!(
+            events.last(),
+            Some(ExportEvent::Finished {
+                exported_sessions: 2,
+                failed_sessions: 0,
+                warnings: 0,
+            })
+        ));
+        assert_eq!(outcome.session_paths.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_export_to_archive_writes_zip() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        db.insert_session(&sample_session("编写文档", 9, 10))
+            .await
+            .unwrap();
+
+        let archive = temp_dir.path().join("export");
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            export_target: crate::models::ObsidianExportTarget::Zip,
+            archive_path: archive.to_string_lossy().to_string(),
+            ..ObsidianExportConfig::default()
+        });
+        let (tx, _rx) = unbounded_channel();
+        let outcome = exporter
+            .export_to_archive(
+                db,
+                offline_llm_handle(),
+                "2024-05-12",
+                "2024-05-12",
+                false,
+                tx,
+            )
+            .await
+            .unwrap();
+
+        let zip_path = temp_dir.path().join("export.zip");
+        assert_eq!(outcome.archive_path.as_deref(), Some(zip_path.as_path()));
+        assert_eq!(
+            outcome.daily_note_path,
+            Path::new("ScreenAnalyzer/Daily/2024-05-12.md")
+        );
+        assert!(!temp_dir.path().join("export.zip.partial").exists());
+
+        let mut zip = zip::ZipArchive::new(std::fs::File::open(&zip_path).unwrap()).unwrap();
+        let names = zip.file_names().map(String::from).collect::<Vec<_>>();
+        assert!(names.contains(&"ScreenAnalyzer/Daily/2024-05-12.md".to_string()));
+        assert!(names
+            .iter()
+            .any(|name| name.starts_with("ScreenAnalyzer/Sessions/2024-05-12/")));
+
+        let mut daily = String::new();
+        std::io::Read::read_to_string(
+            &mut zip.by_name("ScreenAnalyzer/Daily/2024-05-12.md").unwrap(),
+            &mut daily,
+        )
+        .unwrap();
+        assert!(daily.contains("# 2024-05-12 屏幕活动总结"));
+
+        // 未配置路径时报错
+        let exporter = ObsidianExporter::new(ObsidianExportConfig::default());
+        assert!(exporter.archive_path().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_export_range_skips_empty_days() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        db.insert_session(&sample_session("编写文档", 9, 10))
+            .await
+            .unwrap();
+        let mut later = sample_session("代码评审", 14, 15);
+        later.start_time = Utc.with_ymd_and_hms(2024, 5, 14, 14, 0, 0).unwrap();
+        later.end_time = Utc.with_ymd_and_hms(2024, 5, 14, 15, 0, 0).unwrap();
+        db.insert_session(&later).await.unwrap();
+
+        let exporter = vault_exporter(temp_dir.path());
+        let outcome = exporter
+            .export_range(db, offline_llm_handle(), "2024-05-12", "2024-05-14")
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.exported_dates, vec!["2024-05-12", "2024-05-14"]);
+        assert_eq!(outcome.session_paths.len(), 2);
+        assert!(outcome.daily_note_path.ends_with("Daily/2024-05-14.md"));
+        assert!(!temp_dir.path().join("vault/Daily/2024-05-13.md").exists());
+        assert!(outcome.render_message().starts_with("已批量导出 2 天"));
+    }
+
+    #[tokio::test]
+    async fn test_export_range_progress_events() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        db.insert_session(&sample_session("编写文档", 9, 10))
+            .await
+            .unwrap();
+        let mut later = sample_session("代码评审", 14, 15);
+        later.start_time = Utc.with_ymd_and_hms(2024, 5, 13, 14, 0, 0).unwrap();
+        later.end_time = Utc.with_ymd_and_hms(2024, 5, 13, 15, 0, 0).unwrap();
+        db.insert_session(&later).await.unwrap();
+
+        let exporter = vault_exporter(temp_dir.path());
+        let (tx, mut rx) = unbounded_channel();
+        exporter
+            .export_range_with_events(db, offline_llm_handle(), "2024-05-12", "2024-05-14", tx)
+            .await
+            .unwrap();
+
+        let mut tracker = ExportProgressTracker::default();
+        let mut progress = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            progress.push(tracker.observe(event));
+        }
+
+        assert!(matches!(
+            progress[0].event,
+            ExportEvent::RangeStarted {
+                total_sessions: 2,
+                ..
+            }
+        ));
+        let session_steps = progress
+            .iter()
+            .filter(|p| matches!(p.event, ExportEvent::SessionExported { .. }))
+            .map(|p| (p.current_date.clone().unwrap(), p.completed, p.total))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            session_steps,
+            vec![
+                ("2024-05-12".to_string(), 1, 2),
+                ("2024-05-13".to_string(), 2, 2),
+            ]
+        );
+        let last = progress.last().unwrap();
+        assert!(matches!(last.event, ExportEvent::Finished { .. }));
+        assert_eq!((last.completed, last.total), (2, 2));
+    }
+
+    #[tokio::test]
+    async fn test_incremental_sync_skips_unchanged_notes() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        db.insert_session(&sample_session("编写文档", 9, 10))
+            .await
+            .unwrap();
+
+        let vault = temp_dir.path().join("vault");
+        std::fs::create_dir_all(&vault).unwrap();
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            vault_path: vault.to_string_lossy().to_string(),
+            incremental_sync: true,
+            ..ObsidianExportConfig::default()
+        });
+
+        let first = exporter
+            .export_day(db.clone(), offline_llm_handle(), "2024-05-12", false)
+            .await
+            .unwrap();
+        assert_eq!(first.skipped_files, 0);
+
+        let session_path = first.session_paths[0].clone();
+        let modified = std::fs::metadata(&session_path)
+            .unwrap()
+            .modified()
+            .unwrap();
+
+        let second = exporter
+            .export_day(db.clone(), offline_llm_handle(), "2024-05-12", false)
+            .await
+            .unwrap();
+        // 每日总结、会话、月索引、周索引、周报、总览均未变化
+        assert_eq!(second.skipped_files, 6);
+        assert_eq!(
+            std::fs::metadata(&session_path)
+                .unwrap()
+                .modified()
+                .unwrap(),
+            modified
+        );
+        assert!(second.render_message().contains("未变化跳过: 6 个文件"));
+    }
+
+    #[tokio::test]
+    async fn test_quarterly_and_yearly_rollups() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        let mut session = sample_session("编写文档", 9, 11);
+        session.start_time = Utc.with_ymd_and_hms(2024, 5, 8, 9, 0, 0).unwrap();
+        session.end_time = Utc.with_ymd_and_hms(2024, 5, 8, 11, 0, 0).unwrap();
+        db.insert_session(&session).await.unwrap();
+
+        let root = temp_dir.path().join("vault");
+        let exporter = ObsidianExporter::new(ObsidianExportConfig::default());
+
+        let quarterly_path = exporter
+            .export_quarterly_note(db.as_ref(), "2024-05-12", &root)
+            .await
+            .unwrap();
+        assert!(quarterly_path.ends_with("Quarterly/2024-Q2.md"));
+        let quarterly = fs::read_to_string(&quarterly_path).await.unwrap();
+        assert!(quarterly.contains("period_start: 2024-04-01"));
+        assert!(quarterly.contains("period_end: 2024-06-30"));
+        assert!(quarterly.contains("weeks: 13"));
+        assert!(quarterly.contains("| 2024-04 | 4 |"));
+        assert!(quarterly.contains("| 2024-05 | 5 | 1 | 120 |"));
+        assert!(quarterly.contains("| [[Weekly/2024-W19]] | 1 | 120 |"));
+
+        let yearly_path = exporter
+            .export_yearly_note(db.as_ref(), "2024-05-12", &root)
+            .await
+            .unwrap();
+        assert!(yearly_path.ends_with("Yearly/2024.md"));
+        let yearly = fs::read_to_string(&yearly_path).await.unwrap();
+        assert!(yearly.contains("weeks: 52"));
+        assert!(yearly.contains("total_minutes: 120"));
+        assert!(yearly.contains("- [[Quarterly/2024-Q4]]"));
+        assert!(!yearly.contains("## 周趋势"));
+    }
+
+    #[tokio::test]
+    async fn test_custom_folder_layout() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        let session_id = db
+            .insert_session(&sample_session("编写文档", 9, 10))
+            .await
+            .unwrap();
+
+        let vault = temp_dir.path().join("vault");
+        std::fs::create_dir_all(&vault).unwrap();
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            vault_path: vault.to_string_lossy().to_string(),
+            root_folder: String::new(),
+            daily_path_template: "Journal/{{year}}/{{month}}/{{date}}".to_string(),
+            session_path_template: "Journal/{{year}}/{{month}}/sessions".to_string(),
+            weekly_path_template: "../Reviews/{{year}}/{{week}}".to_string(),
+            index_folder: "  ".to_string(),
+            ..ObsidianExportConfig::default()
+        });
+
+        let outcome = exporter
+            .export_day(db, offline_llm_handle(), "2024-05-12", false)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            outcome.daily_note_path,
+            vault.join("Journal/2024/05/2024-05-12.md")
+        );
+        assert_eq!(
+            outcome.session_paths,
+            vec![vault.join(format!(
+                "Journal/2024/05/sessions/2024-05-12_0900-1000_session-{}.md",
+                session_id
+            ))]
+        );
+        // `..` 片段被忽略，周报仍落在根目录内
+        assert_eq!(
+            outcome.weekly_note_path,
+            Some(vault.join("Reviews/2024/2024-W19.md"))
+        );
+        // 空索引目录回退到默认布局
+        assert_eq!(outcome.overview_path, Some(vault.join("Index/overview.md")));
+
+        let daily = fs::read_to_string(&outcome.daily_note_path).await.unwrap();
+        assert!(daily.contains(&format!(
+            "[[Journal/2024/05/sessions/2024-05-12_0900-1000_session-{}.md]]",
+            session_id
+        )));
+        let overview = fs::read_to_string(vault.join("Index/overview.md"))
+            .await
+            .unwrap();
+        assert!(overview.contains("[[Journal/2024/05/2024-05-12]]"));
+        assert!(overview.contains("[[Reviews/2024/2024-W19]]"));
+    }
+
+    #[tokio::test]
+    async fn test_daily_filename_format_and_aliases() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        db.insert_session(&sample_session("编写文档", 9, 10))
+            .await
+            .unwrap();
+
+        let vault = temp_dir.path().join("vault");
+        std::fs::create_dir_all(vault.join("Journal")).unwrap();
+        // 已有的日记插件笔记，带用户自定义别名
+        std::fs::write(
+            vault.join("Journal/2024-05-12 Sunday.md"),
+            "---\naliases:\n  - 母亲节\n---\n\n早上跑步\n",
+        )
+        .unwrap();
+
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            vault_path: vault.to_string_lossy().to_string(),
+            root_folder: String::new(),
+            daily_path_template: "Journal/{{date}}".to_string(),
+            daily_filename_format: "%Y-%m-%d %A".to_string(),
+            daily_note_aliases: true,
+            daily_note_update_mode: DailyNoteUpdateMode::Merge,
+            ..ObsidianExportConfig::default()
+        });
+
+        let outcome = exporter
+            .export_day(db, offline_llm_handle(), "2024-05-12", false)
+            .await
+            .unwrap();
+        assert_eq!(
+            outcome.daily_note_path,
+            vault.join("Journal/2024-05-12 Sunday.md")
+        );
+
+        let daily = fs::read_to_string(&outcome.daily_note_path).await.unwrap();
+        assert!(daily.contains("aliases: [\"母亲节\", \"2024-05-12\", \"2024年5月12日 星期日\"]\n"));
+        assert!(daily.ends_with("早上跑步\n"));
+
+        // 格式中的 `/` 视为子目录；非法格式回退到路径模板
+        assert_eq!(
+            format_daily_filename("%Y/%m/%Y-%m-%d", "2024-05-12"),
+            Some("2024/05/2024-05-12".to_string())
+        );
+        assert_eq!(format_daily_filename("%Q", "2024-05-12"), None);
+        assert_eq!(format_daily_filename("  ", "2024-05-12"), None);
+        assert_eq!(
+            parse_yaml_list("aliases: [a, \"b, c\", 'd']"),
+            vec!["a", "b, c", "d"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_week_focus_metrics_use_daily_cache() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        let session_id = db
+            .insert_session(&sample_session("编写文档", 9, 10))
+            .await
+            .unwrap();
+        let card = |category: &str, start: &str, end: &str| TimelineCardRecord {
+            id: None,
+            session_id,
+            llm_call_id: None,
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            category: category.to_string(),
+            subcategory: String::new(),
+            title: String::new(),
+            summary: String::new(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: "[]".to_string(),
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+            created_at: Utc::now(),
+        };
+        db.insert_timeline_card(&card(
+            "work",
+            "2024-05-12T09:00:00+00:00",
+            "2024-05-12T09:40:00+00:00",
+        ))
+        .await
+        .unwrap();
+
+        // 生成总结时写入缓存
+        let generator = SummaryGenerator::new(db.clone());
+        let record = generator.refresh_focus_metrics("2024-05-12").await.unwrap();
+        assert_eq!((record.total_minutes, record.work_minutes), (40, 40));
+
+        // 周汇总直接读取缓存（篡改缓存可观察到）
+        let exporter = ObsidianExporter::new(ObsidianExportConfig::default());
+        let (monday, sunday) = (
+            NaiveDate::from_ymd_opt(2024, 5, 6).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 5, 12).unwrap(),
+        );
+        let tampered = DailyFocusMetricsRecord {
+            work_minutes: 100,
+            total_minutes: 100,
+            ..record
+        };
+        db.save_daily_focus_metrics("2024-05-12", &tampered)
+            .await
+            .unwrap();
+        let metrics = exporter
+            .compute_week_focus_metrics(&db, monday, sunday)
+            .await;
+        assert_eq!(metrics.work_minutes, 100);
+
+        // 新增卡片使当天缓存失效，重新统计后回填
+        db.insert_timeline_card(&card(
+            "personal",
+            "2024-05-12T09:40:00+00:00",
+            "2024-05-12T10:00:00+00:00",
+        ))
+        .await
+        .unwrap();
+        assert!(db
+            .get_daily_focus_metrics("2024-05-06", "2024-05-12")
+            .await
+            .unwrap()
+            .is_empty());
+        let metrics = exporter
+            .compute_week_focus_metrics(&db, monday, sunday)
+            .await;
+        assert_eq!((metrics.work_minutes, metrics.personal_minutes), (40, 20));
+        let cached = db
+            .get_daily_focus_metrics("2024-05-06", "2024-05-12")
+            .await
+            .unwrap();
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].total_minutes, 60);
+    }
+
+    #[tokio::test]
+    async fn test_device_filter_limits_week_statistics() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        for (device, start, end, category) in [
+            ("work-laptop", 9, 11, "work"),
+            ("home-pc", 13, 14, "personal"),
+        ] {
+            let mut session = sample_session(device, start, end);
+            session.start_time = Utc.with_ymd_and_hms(2024, 5, 8, start, 0, 0).unwrap();
+            session.end_time = Utc.with_ymd_and_hms(2024, 5, 8, end, 0, 0).unwrap();
+            session.device_name = Some(device.to_string());
+            session.device_type = Some("laptop".to_string());
+            let session_id = db.insert_session(&session).await.unwrap();
+            db.insert_timeline_card(&TimelineCardRecord {
+                id: None,
+                session_id,
+                llm_call_id: None,
+                start_time: session.start_time.to_rfc3339(),
+                end_time: session.end_time.to_rfc3339(),
+                category: category.to_string(),
+                subcategory: String::new(),
+                title: String::new(),
+                summary: String::new(),
+                detailed_summary: String::new(),
+                distractions: None,
+                app_sites: "[]".to_string(),
+                confidence: None,
+                heuristic: false,
+                video_preview_path: None,
+                created_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+        }
+        assert_eq!(
+            db.get_device_names().await.unwrap(),
+            vec!["home-pc".to_string(), "work-laptop".to_string()]
+        );
+        let (monday, sunday) = (
+            NaiveDate::from_ymd_opt(2024, 5, 6).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 5, 12).unwrap(),
+        );
+
+        let exporter = ObsidianExporter::new(ObsidianExportConfig::default());
+        let devices = exporter.week_device_focus(&db, monday, sunday).await;
+        assert_eq!(
+            render_week_devices(zh(), &devices).unwrap(),
+            "- work-laptop：专注 120 分钟（100%），共 120 分钟，1 个会话\n\
+             - home-pc：专注 0 分钟（0%），共 60 分钟，1 个会话"
+        );
+
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            device_filter: Some(" work-laptop ".to_string()),
+            ..ObsidianExportConfig::default()
+        });
+        let sessions = exporter.sessions_on(&db, "2024-05-08").await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        let summary = exporter.week_summary(&db, "2024-05-08").await.unwrap();
+        assert_eq!((summary.total_sessions, summary.total_minutes), (1, 120));
+        assert_eq!(summary.focus_metrics.total_minutes, 120);
+        let devices = exporter.week_device_focus(&db, monday, sunday).await;
+        assert_eq!(devices.len(), 1);
+        assert!(render_week_devices(zh(), &devices).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_weekly_note_compares_previous_week() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        let session_on = |title: &str, day: u32, hours: u32| {
+            let mut session = sample_session(title, 9, 9 + hours);
+            session.start_time = Utc.with_ymd_and_hms(2024, 5, day, 9, 0, 0).unwrap();
+            session.end_time = Utc.with_ymd_and_hms(2024, 5, day, 9 + hours, 0, 0).unwrap();
+            session
+        };
+        db.insert_session(&session_on("编写文档", 8, 2))
+            .await
+            .unwrap();
+        let exporter = vault_exporter(temp_dir.path());
+
+        // 上周没有记录
+        let outcome = exporter
+            .export_day(db.clone(), offline_llm_handle(), "2024-05-08", false)
+            .await
+            .unwrap();
+        let weekly = std::fs::read_to_string(outcome.weekly_note_path.unwrap()).unwrap();
+        assert!(weekly.contains("## 与上周对比\n- 上周（2024-W18）没有记录，暂无对比\n"));
+        let preview = exporter
+            .preview_week_summary(db.as_ref(), "2024-05-08")
+            .await
+            .unwrap();
+        assert!(preview.comparison.is_none());
+
+        for day in [1, 2] {
+            db.insert_session(&session_on("需求分析", day, 2))
+                .await
+                .unwrap();
+        }
+        let outcome = exporter
+            .export_day(db.clone(), offline_llm_handle(), "2024-05-08", false)
+            .await
+            .unwrap();
+        let weekly = std::fs::read_to_string(outcome.weekly_note_path.unwrap()).unwrap();
+        assert!(weekly.contains(
+            "## 与上周对比\n\
+| 指标 | 本周 | 上周 | 变化 |\n\
+| --- | --- | --- | --- |\n\
+| 总时长(分钟) | 120 | 240 | ↓ -120 (-50%) |\n\
+| 专注占比 | 0% | 0% | → +0 个百分点 |\n"
+        ));
+
+        let comparison = exporter
+            .preview_week_summary(db.as_ref(), "2024-05-08")
+            .await
+            .unwrap()
+            .comparison
+            .unwrap();
+        assert_eq!(comparison.previous_week_label, "2024-W18");
+        assert_eq!(comparison.total_minutes_delta, -120);
+        assert_eq!(comparison.total_minutes_change_percent, Some(-50));
+
+        assert_eq!(format_delta(75, 60, true), "↑ +15 (+25%)");
+        assert_eq!(format_delta(5, 0, true), "↑ +5");
+    }
+
+    #[test]
+    fn test_week_trend_block() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2024, 5, d).unwrap();
+        let record = |d: u32, total: i64, work: i64| DailyFocusMetricsRecord {
+            date: day(d),
+            total_minutes: total,
+            work_minutes: work,
+            learning_minutes: 0,
+            communication_minutes: 0,
+            personal_minutes: total - work,
+            idle_minutes: 0,
+            other_minutes: 0,
+            updated_at: Utc::now(),
+        };
+        // 月初两天 + 本周两天
+        let records = vec![
+            record(1, 480, 240),
+            record(2, 480, 240),
+            record(14, 480, 480),
+            record(15, 240, 240),
+        ];
+        let weights = ScoreWeights::from_weekly(50, 50, 2400);
+        let text = i18n::strings(crate::models::ExportLanguage::Zh);
+        let trend = crate::trends::build_series(&records, weights, day(13), day(15));
+        assert_eq!(
+            render_week_trend(text, &trend).unwrap(),
+            "| 指标 | 7 日均值 | 30 日均值 | 走势 |\n\
+| --- | --- | --- | --- |\n\
+| 总时长(分钟) | 360 | 420 | ↓ -60 |\n\
+| 专注占比 | 100% | 75% | ↑ +25 |\n\
+| 生产力评分 | 88 | 81 | ↑ +7 |\n\
+\n\
+- 本周最佳: 2024-05-14，生产力评分 100（近 90 天第 100 百分位）"
+        );
+
+        // 近 7 天没有记录时不显示趋势
+        let quiet = crate::trends::build_series(&records[..2], weights, day(13), day(15));
+        assert!(render_week_trend(text, &quiet).is_none());
+    }
+
+    #[test]
+    fn test_week_wellbeing_block() {
+        let text = i18n::strings(crate::models::ExportLanguage::Zh);
+        let config = WellbeingConfig::default();
+        let mut report = WellbeingReport {
+            start_date: "2024-05-13".to_string(),
+            end_date: "2024-05-19".to_string(),
+            days: Vec::new(),
+            warnings: Vec::new(),
+        };
+        assert!(render_week_wellbeing(text, &report, &config).is_none());
+
+        report.warnings = vec![
+            WellbeingWarning::LongDay {
+                date: "2024-05-14".to_string(),
+                screen_minutes: 660,
+            },
+            WellbeingWarning::LateNightStreak {
+                start_date: "2024-05-15".to_string(),
+                end_date: "2024-05-17".to_string(),
+                days: 3,
+            },
+            WellbeingWarning::ShrinkingBreaks {
+                previous_per_hour: 10.0,
+                current_per_hour: 4.6,
+                drop_percent: 54,
+            },
+        ];
+        assert_eq!(
+            render_week_wellbeing(text, &report, &config).unwrap(),
+            "- ⚠️ 2024-05-14 屏幕时间 660 分钟，超过 600 分钟\n\
+- ⚠️ 2024-05-15 至 2024-05-17 连续 3 天深夜工作\n\
+- ⚠️ 每小时屏幕时间的休息从上周的 10 分钟减少到 5 分钟（-54%）"
+        );
+    }
+
+    #[test]
+    fn test_week_insights_against_baseline() {
+        let week = |total: i32, work: i64| WeekBaseline {
+            week_label: String::new(),
+            total_sessions: 10,
+            total_minutes: total,
+            focus_metrics: WeekFocusMetrics {
+                total_minutes: i64::from(total),
+                work_minutes: work,
+                ..Default::default()
+            },
+        };
+        let config = WeekScoreConfig {
+            focus_weight: 50,
+            effort_weight: 50,
+            target_minutes: 1200,
+        };
+        // 没有记录的周不计入基准
+        let weeks = vec![
+            week(600, 300),
+            week(0, 0),
+            week(660, 330),
+            week(540, 270),
+            week(600, 300),
+        ];
+        assert!(WeekStatsBaseline::from_weeks(&weeks[..3], &config).is_none());
+        let baseline = WeekStatsBaseline::from_weeks(&weeks, &config).unwrap();
+        assert_eq!(baseline.weeks, 4);
+
+        let text = i18n::strings(crate::models::ExportLanguage::Zh);
+        let mut values = baseline.metrics.map(|metric| metric.mean);
+        assert_eq!(
+            baseline_insights(text, &values, &baseline),
+            vec!["本周各项指标都在近 4 周的平常范围内".to_string()]
+        );
+
+        values[0] = 369.0;
+        values[4] = 48.0;
+        assert_eq!(
+            baseline_insights(text, &values, &baseline),
+            vec![
+                "专注时长比近 4 周的平常水平高 23%（z = +2.3）".to_string(),
+                "平均会话时长比近 4 周的平常水平低 20%（z = -2.0）".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_focus_heatmap_block() {
+        let mut minutes = [[0; 24]; 7];
+        minutes[0][9] = 60;
+        minutes[0][10] = 30;
+        minutes[2][14] = 10;
+        let heatmap = FocusHeatmap {
+            start_date: "2024-05-01".to_string(),
+            end_date: "2024-05-31".to_string(),
+            minutes,
+            total_minutes: 100,
+            peak: Some(crate::heatmap::HeatmapCell {
+                weekday: 0,
+                hour: 9,
+                minutes: 60,
+            }),
+        };
+        let text = i18n::strings(crate::models::ExportLanguage::Zh);
+        let block = render_focus_heatmap(text, &heatmap).unwrap();
+        let lines: Vec<&str> = block.lines().collect();
+        assert_eq!(lines[1], "     00          06          12          18");
+        assert_eq!(
+            lines[2],
+            format!("周一 {}🟥🟧{}", "⬜".repeat(9), "⬜".repeat(13))
+        );
+        assert_eq!(
+            lines[4],
+            format!("周三 {}🟨{}", "⬜".repeat(14), "⬜".repeat(9))
+        );
+        assert!(block.ends_with("- 专注高峰: 周一 09:00-10:00，共 60 分钟"));
+
+        let english =
+            render_focus_heatmap(i18n::strings(crate::models::ExportLanguage::En), &heatmap)
+                .unwrap();
+        assert!(english.contains("\nMon ⬜"));
+
+        let empty = FocusHeatmap {
+            minutes: [[0; 24]; 7],
+            total_minutes: 0,
+            peak: None,
+            ..heatmap
+        };
+        assert!(render_focus_heatmap(text, &empty).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_conflict_policies() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        db.insert_session(&sample_session("编写文档", 9, 10))
+            .await
+            .unwrap();
+        let vault = temp_dir.path().join("vault");
+        std::fs::create_dir_all(&vault).unwrap();
+        let exporter_with = |policy| {
+            ObsidianExporter::new(ObsidianExportConfig {
+                vault_path: vault.to_string_lossy().to_string(),
+                conflict_policy: policy,
+                ..ObsidianExportConfig::default()
+            })
+        };
+
+        let first = exporter_with(ExportConflictPolicy::Overwrite)
+            .export_day(db.clone(), offline_llm_handle(), "2024-05-12", false)
+            .await
+            .unwrap();
+        assert_eq!(first.conflicts, ConflictCounts::default());
+        let session_path = first.session_paths[0].clone();
+        let daily_path = first.daily_note_path.clone();
+
+        // 跳过：手动修改的文件保持不变
+        std::fs::write(&session_path, "手动修改").unwrap();
+        let skipped = exporter_with(ExportConflictPolicy::Skip)
+            .export_day(db.clone(), offline_llm_handle(), "2024-05-12", false)
+            .await
+            .unwrap();
+        assert_eq!(skipped.conflicts.skipped, 1);
+        assert_eq!(std::fs::read_to_string(&session_path).unwrap(), "手动修改");
+
+        // 新版本：写入 -v2，每日笔记链接到新版本；再次导出复用 -v2
+        let exporter = exporter_with(ExportConflictPolicy::NewVersion);
+        let versioned = exporter
+            .export_day(db.clone(), offline_llm_handle(), "2024-05-12", false)
+            .await
+            .unwrap();
+        let v2 = versioned_path(&session_path, 2);
+        assert_eq!(versioned.session_paths, vec![v2.clone()]);
+        assert_eq!(versioned.conflicts.versioned, 2);
+        assert_eq!(versioned.daily_note_path, versioned_path(&daily_path, 2));
+        let daily_v2 = std::fs::read_to_string(&versioned.daily_note_path).unwrap();
+        assert!(daily_v2.contains(&format!("{}]]", v2.file_name().unwrap().to_string_lossy())));
+        let again = exporter
+            .export_day(db.clone(), offline_llm_handle(), "2024-05-12", false)
+            .await
+            .unwrap();
+        assert_eq!(again.conflicts.versioned, 0);
+        assert!(!versioned_path(&session_path, 3).exists());
+
+        // 合并：手写内容保留在标记块之后
+        let merged = exporter_with(ExportConflictPolicy::Merge)
+            .export_day(db, offline_llm_handle(), "2024-05-12", false)
+            .await
+            .unwrap();
+        assert_eq!(merged.conflicts.merged, 2);
+        let session = std::fs::read_to_string(&session_path).unwrap();
+        assert!(session.contains(MANAGED_END));
+        assert!(session.ends_with("手动修改"));
+    }
+
+    #[test]
+    fn test_merge_daily_note_preserves_manual_content() {
+        let first = "---\ntype: screen-analyzer-daily\nsession_count: 1\nsource: screen-analyzer\n---\n\n# 总结\n旧内容\n";
+        let rendered = "---\ntype: screen-analyzer-daily\nsession_count: 2\nsource: screen-analyzer\n---\n\n# 总结\n新内容\n";
+
+        // 首次写入：正文被标记包裹
+        let created = merge_daily_note(None, first);
+        assert!(created.contains(&format!(
+            "{}\n# 总结\n旧内容\n{}\n",
+            MANAGED_START, MANAGED_END
+        )));
+
+        // 用户添加了自定义键与手写笔记
+        let edited = created.replace(
+            "source: screen-analyzer\n",
+            "source: screen-analyzer\nmood: 不错\ntags:\n  - review\n",
+        ) + "\n## 我的笔记\n今天很充实\n";
+
+        let merged = merge_daily_note(Some(&edited), rendered);
+        assert!(merged.starts_with("---\ntype: screen-analyzer-daily\nsession_count: 2\n"));
+        assert!(merged.contains("mood: 不错\ntags:\n  - review\n---\n"));
+        assert!(merged.contains("新内容"));
+        assert!(!merged.contains("旧内容"));
+        assert!(merged.ends_with("## 我的笔记\n今天很充实\n"));
+
+        // 再次合并结果稳定
+        assert_eq!(merge_daily_note(Some(&merged), rendered), merged);
+
+        // 没有标记的手写笔记：生成内容放在前面，原文保留
+        let manual = "---\nmood: 一般\n---\n\n随手记录\n";
+        let merged = merge_daily_note(Some(manual), rendered);
+        assert!(merged.starts_with("---\nmood: 一般\ntype: screen-analyzer-daily\n"));
+        assert!(merged.ends_with(&format!("{}\n\n随手记录\n", MANAGED_END)));
+    }
+
+    #[test]
+    fn test_merge_daily_note_keeps_footer_outside_markers() {
+        let footer = "\n---\n> 由 Screen Analyzer v0.1.0 生成 · 数据截至 2024-05-12\n";
+        let rendered = format!(
+            "---\ntype: screen-analyzer-daily\nsource: screen-analyzer\n---\n\n# 总结\n旧内容\n{}",
+            footer
+        );
+        let created = merge_daily_note(None, &rendered);
+        assert!(created.ends_with(&format!("旧内容\n{}\n{}", MANAGED_END, footer)));
+
+        // 用户在标记块下方添加手写内容后，页脚更新并仍紧跟结束标记，手写内容保留在其后
+        let edited = created + "\n## 我的笔记\n今天很充实\n";
+        let updated_footer = "\n---\n> 由 Screen Analyzer v0.2.0 生成 · 数据截至 2024-05-12\n";
+        let rendered = rendered
+            .replace("旧内容", "新内容")
+            .replace(footer, updated_footer);
+        let merged = merge_daily_note(Some(&edited), &rendered);
+        let end = merged.find(MANAGED_END).unwrap();
+        let block = &merged[merged.find(MANAGED_START).unwrap()..end];
+        assert!(block.contains("新内容"));
+        assert!(!block.contains("Screen Analyzer v"));
+        assert!(merged[end..].starts_with(&format!(
+            "{}\n{}\n## 我的笔记\n今天很充实\n",
+            MANAGED_END, updated_footer
+        )));
+        assert!(!merged.contains("v0.1.0"));
+        assert_eq!(merged.matches("Screen Analyzer v").count(), 1);
+
+        // 再次合并结果稳定
+        assert_eq!(merge_daily_note(Some(&merged), &rendered), merged);
+    }
+
+    #[tokio::test]
+    async fn test_preview_day_does_not_touch_vault() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        db.insert_session(&sample_session("编写文档", 9, 10))
+            .await
+            .unwrap();
+        let exporter = vault_exporter(temp_dir.path());
+        let vault = temp_dir.path().join("vault");
+
+        let previews = exporter
+            .preview_day(db.clone(), offline_llm_handle(), "2024-05-12")
+            .await
+            .unwrap();
+        assert_eq!(previews.len(), 6);
+        assert!(previews.iter().all(|preview| !preview.would_overwrite));
+        assert_eq!(std::fs::read_dir(&vault).unwrap().count(), 0);
+
+        let outcome = exporter
+            .export_day(db.clone(), offline_llm_handle(), "2024-05-12", false)
+            .await
+            .unwrap();
+        fs::write(&outcome.daily_note_path, "手动修改")
+            .await
+            .unwrap();
+
+        let previews = exporter
+            .preview_day(db, offline_llm_handle(), "2024-05-12")
+            .await
+            .unwrap();
+        let overwritten = previews
+            .iter()
+            .filter(|preview| preview.would_overwrite)
+            .map(|preview| preview.path.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(overwritten, vec![outcome.daily_note_path.clone()]);
+        assert_eq!(
+            std::fs::read_to_string(&outcome.daily_note_path).unwrap(),
+            "手动修改"
+        );
+    }
+
+    #[test]
+    fn test_screenshot_strategies() {
+        let frame = |minute: u32, path: &str| Frame {
+            id: None,
+            session_id: 1,
+            timestamp: Utc.with_ymd_and_hms(2024, 5, 12, 9, minute, 0).unwrap(),
+            file_path: path.to_string(),
+            app_name: None,
+            window_title: None,
+            process_path: None,
+            domain: None,
+            screen_id: 0,
+        };
+        let frames = (0..10)
+            .map(|i| frame(i * 5, &format!("frame-{}.jpg", i)))
+            .collect::<Vec<_>>();
+        let paths = |selected: Vec<Frame>| {
+            selected
+                .into_iter()
+                .map(|frame| frame.file_path)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            paths(pick_evenly_spaced(&frames, 4)),
+            vec!["frame-0.jpg", "frame-3.jpg", "frame-6.jpg", "frame-9.jpg"]
+        );
+        assert_eq!(paths(pick_evenly_spaced(&frames, 1)), vec!["frame-5.jpg"]);
+        assert_eq!(pick_evenly_spaced(&frames, 50).len(), 10);
+
+        let card = |start: &str, end: &str| TimelineCardRecord {
+            id: None,
+            session_id: 1,
+            llm_call_id: None,
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            category: "work".to_string(),
+            subcategory: String::new(),
+            title: String::new(),
+            summary: String::new(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: "[]".to_string(),
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+            created_at: Utc::now(),
+        };
+        let cards = vec![
+            card("2024-05-12T09:00:00+00:00", "2024-05-12T09:20:00+00:00"),
+            card("2024-05-12T09:20:00+00:00", "2024-05-12T09:46:00+00:00"),
+            // 卡片范围内没有截图
+            card("2024-05-12T11:00:00+00:00", "2024-05-12T11:30:00+00:00"),
+        ];
+        assert_eq!(
+            paths(pick_per_timeline_card(&frames, &cards)),
+            vec!["frame-2.jpg", "frame-7.jpg"]
+        );
+
+        let sizes = [100, 100, 900, 880, 100, 120, 125, 600, 590, 580];
+        assert_eq!(
+            paths(pick_highest_activity(&frames, &sizes, 3)),
+            vec!["frame-2.jpg", "frame-4.jpg", "frame-7.jpg"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transcode_screenshot_to_webp() {
+        let temp_dir = tempdir().unwrap();
+        let source = temp_dir.path().join("frame.jpg");
+        image::RgbImage::from_fn(32, 32, |x, y| image::Rgb([x as u8 * 8, y as u8 * 8, 128]))
+            .save(&source)
+            .unwrap();
+
+        let target = temp_dir.path().join("session-1-0.webp");
+        transcode_image(&source, &target, AssetFormat::Webp, 75)
+            .await
+            .unwrap();
+        let encoded = std::fs::read(&target).unwrap();
+        assert!(encoded.starts_with(b"RIFF"));
+
+        assert_eq!(asset_extension(AssetFormat::Webp), "webp");
+        if !cfg!(feature = "avif") {
+            assert_eq!(
+                effective_asset_format(AssetFormat::Avif),
+                AssetFormat::Original
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wiki_embeds_in_vault_attachment_folder() {
+        let temp_dir = tempdir().unwrap();
+        let db = create_test_db(temp_dir.path()).await;
+        let session_id = db
+            .insert_session(&sample_session("编写文档", 9, 10))
+            .await
+            .unwrap();
+        let frame_path = temp_dir.path().join("frame.jpg");
+        std::fs::write(&frame_path, b"jpeg").unwrap();
+        db.insert_frame(&Frame {
+            id: None,
+            session_id,
+            timestamp: Utc.with_ymd_and_hms(2024, 5, 12, 9, 30, 0).unwrap(),
+            file_path: frame_path.to_string_lossy().to_string(),
+            app_name: None,
+            window_title: None,
+            process_path: None,
+            domain: None,
+            screen_id: 0,
+        })
+        .await
+        .unwrap();
+
+        let vault = temp_dir.path().join("vault");
+        std::fs::create_dir_all(vault.join(".obsidian")).unwrap();
+        std::fs::write(
+            vault.join(".obsidian/app.json"),
+            r#"{"attachmentFolderPath":"attachments/screens"}"#,
+        )
+        .unwrap();
+        let exporter = ObsidianExporter::new(ObsidianExportConfig {
+            vault_path: vault.to_string_lossy().to_string(),
+            export_mode: ObsidianExportMode::Copy,
+            include_screenshots: true,
+            asset_link_style: AssetLinkStyle::Wiki,
+            use_vault_attachment_folder: true,
+            ..ObsidianExportConfig::default()
+        });
+
+        let outcome = exporter
+            .export_day(db, offline_llm_handle(), "2024-05-12", false)
+            .await
+            .unwrap();
+
+        let asset_name = format!("session-{}-0.jpg", session_id);
+        assert!(vault
+            .join("attachments/screens")
+            .join(&asset_name)
+            .is_file());
+        let note = fs::read_to_string(&outcome.session_paths[0]).await.unwrap();
+        assert!(note.contains(&format!("![[{}]]", asset_name)));
+
+        // 相对笔记目录的附件设置
+        assert_eq!(
+            resolve_attachment_folder(&temp_dir.path().join("missing"), Path::new("/notes/day")),
+            temp_dir.path().join("missing")
+        );
+        std::fs::write(
+            vault.join(".obsidian/app.json"),
+            r#"{"attachmentFolderPath":"./assets"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            resolve_attachment_folder(&vault, Path::new("/notes/day")),
+            PathBuf::from("/notes/day/assets")
+        );
     }
-    content
 }
@@ -0,0 +1,306 @@
+// 截图 OCR - 调用 tesseract 识别采样帧上的文字，
+// 识别结果按会话保存，并在生成时间线时附加到对应分段的描述中，同时进入全文索引
+
+use crate::capture::ScreenFrame;
+use crate::llm::plugin::VideoSegment;
+use crate::models::OcrConfig;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 默认的 tesseract 可执行文件名
+const DEFAULT_BINARY: &str = "tesseract";
+
+/// 单帧识别超时
+const RECOGNIZE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 单帧保留的最大字符数
+const MAX_FRAME_TEXT_CHARS: usize = 2000;
+
+/// 附加到单个分段描述的最大 OCR 字符数
+const MAX_SEGMENT_TEXT_CHARS: usize = 1500;
+
+/// 一帧的识别结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameOcr {
+    pub timestamp: DateTime<Utc>,
+    /// 显示器序号（见 `ScreenFrame::screen_id`）
+    pub screen_id: usize,
+    pub text: String,
+}
+
+/// 会话的 OCR 上下文（供第二阶段生成时间线使用）
+#[derive(Debug, Clone)]
+pub struct OcrContext {
+    pub window_start: DateTime<Utc>,
+    pub texts: Vec<FrameOcr>,
+}
+
+/// tesseract 识别引擎
+#[derive(Debug, Clone)]
+pub struct OcrEngine {
+    binary: String,
+    languages: String,
+}
+
+impl OcrEngine {
+    /// 根据配置创建，未启用时返回 None
+    pub fn from_config(config: &OcrConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let binary = config.binary_path.trim();
+        let languages = config.languages.trim();
+        Some(Self {
+            binary: if binary.is_empty() {
+                DEFAULT_BINARY.to_string()
+            } else {
+                binary.to_string()
+            },
+            languages: if languages.is_empty() {
+                OcrConfig::default().languages
+            } else {
+                languages.to_string()
+            },
+        })
+    }
+
+    /// 识别单张图片，返回规整后的文字
+    pub async fn recognize(&self, image_path: &str) -> Result<String> {
+        // tesseract 直接读取图片文件，加密的截图先解密到临时目录
+        let plaintext = crate::frame_crypto::plaintext_frames(&[image_path.to_string()]).await?;
+        let mut command = tokio::process::Command::new(&self.binary);
+        command
+            .arg(&plaintext.paths[0])
+            .arg("stdout")
+            .arg("-l")
+            .arg(&self.languages)
+            .kill_on_drop(true);
+
+        let output = tokio::time::timeout(RECOGNIZE_TIMEOUT, command.output())
+            .await
+            .map_err(|_| anyhow!("OCR 超时: {}", image_path))?
+            .with_context(|| format!("无法启动 {}", self.binary))?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "OCR 失败 ({}): {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        Ok(normalize_text(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// 依次识别多帧，跳过无文字或与同一显示器上一帧相同的结果；找不到 tesseract 时提前结束
+    pub async fn recognize_frames(&self, frames: &[ScreenFrame]) -> Vec<FrameOcr> {
+        let mut texts: Vec<FrameOcr> = Vec::new();
+        let mut failed = 0usize;
+
+        for frame in frames {
+            match self.recognize(&frame.file_path).await {
+                Ok(text) => {
+                    let previous = texts
+                        .iter()
+                        .rev()
+                        .find(|last| last.screen_id == frame.screen_id);
+                    if text.is_empty() || previous.is_some_and(|last| last.text == text) {
+                        continue;
+                    }
+                    texts.push(FrameOcr {
+                        timestamp: frame.timestamp,
+                        screen_id: frame.screen_id,
+                        text,
+                    });
+                }
+                Err(e) => {
+                    warn!("帧 OCR 失败 {}: {:#}", frame.file_path, e);
+                    failed += 1;
+                    let missing = e.chain().any(|cause| {
+                        cause
+                            .downcast_ref::<std::io::Error>()
+                            .is_some_and(|io| io.kind() == std::io::ErrorKind::NotFound)
+                    });
+                    if missing {
+                        warn!("未找到 {}，跳过本次会话的 OCR", self.binary);
+                        break;
+                    }
+                }
+            }
+        }
+
+        info!(
+            "OCR 完成: {} 帧，提取 {} 段文字，失败 {}",
+            frames.len(),
+            texts.len(),
+            failed
+        );
+        texts
+    }
+}
+
+/// 规整识别结果：合并空白、去掉中文字符间多余的空格、丢弃无有效字符的行并限制长度
+pub fn normalize_text(raw: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in raw.lines() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let mut merged = String::new();
+        for word in words {
+            let joins_cjk = merged.chars().last().is_some_and(is_cjk)
+                && word.chars().next().is_some_and(is_cjk);
+            if !merged.is_empty() && !joins_cjk {
+                merged.push(' ');
+            }
+            merged.push_str(word);
+        }
+
+        // 至少 2 个字母、数字或汉字才保留，过滤图标、边框等识别噪声
+        if merged.chars().filter(|c| c.is_alphanumeric()).count() >= 2 {
+            lines.push(merged);
+        }
+    }
+
+    truncate_chars(&lines.join("\n"), MAX_FRAME_TEXT_CHARS)
+}
+
+/// 将 OCR 文字按时间归入各分段，附加到分段描述末尾；来自多个显示器时按显示器分别列出
+pub fn enrich_segments(segments: &[VideoSegment], context: &OcrContext) -> Vec<VideoSegment> {
+    // 分段时间为相对会话开始的 MM:SS，上限取一天即可
+    let window_end = context.window_start + chrono::Duration::days(1);
+    segments
+        .iter()
+        .map(|segment| {
+            let start = crate::llm::relative_to_absolute(
+                context.window_start,
+                window_end,
+                &segment.start_timestamp,
+            );
+            let end = crate::llm::relative_to_absolute(
+                context.window_start,
+                window_end,
+                &segment.end_timestamp,
+            );
+
+            let mut screens: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+            for text in context
+                .texts
+                .iter()
+                .filter(|text| text.timestamp >= start && text.timestamp <= end.max(start))
+            {
+                screens
+                    .entry(text.screen_id)
+                    .or_default()
+                    .push(text.text.as_str());
+            }
+
+            let mut enriched = segment.clone();
+            if screens.len() == 1 {
+                let texts = screens.into_values().next().unwrap_or_default();
+                enriched.description = format!(
+                    "{}\n屏幕文字（OCR）：\n{}",
+                    segment.description,
+                    truncate_chars(&texts.join("\n"), MAX_SEGMENT_TEXT_CHARS)
+                );
+            } else if screens.len() > 1 {
+                let per_screen_chars = MAX_SEGMENT_TEXT_CHARS / screens.len();
+                for (screen_id, texts) in screens {
+                    enriched.description.push_str(&format!(
+                        "\n显示器 {} 屏幕文字（OCR）：\n{}",
+                        screen_id + 1,
+                        truncate_chars(&texts.join("\n"), per_screen_chars)
+                    ));
+                }
+            }
+            enriched
+        })
+        .collect()
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c, '\u{4e00}'..='\u{9fff}' | '\u{3400}'..='\u{4dbf}' | '\u{3000}'..='\u{303f}' | '\u{ff00}'..='\u{ffef}')
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((index, _)) => format!("{}…", &text[..index]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_text() {
+        let raw = "  代 码 评 审   PR #42 \n\n | — \n src/main.rs  第 12 行 \n";
+        assert_eq!(normalize_text(raw), "代码评审 PR #42\nsrc/main.rs 第 12 行");
+        assert_eq!(normalize_text(" \n@ \n"), "");
+
+        let long = "字".repeat(MAX_FRAME_TEXT_CHARS + 10);
+        let truncated = normalize_text(&long);
+        assert_eq!(truncated.chars().count(), MAX_FRAME_TEXT_CHARS + 1);
+        assert!(truncated.ends_with('…'));
+    }
+
+    #[test]
+    fn test_enrich_segments_by_time() {
+        let window_start = chrono::NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_utc();
+        let segment = |start: &str, end: &str, description: &str| VideoSegment {
+            start_timestamp: start.to_string(),
+            end_timestamp: end.to_string(),
+            description: description.to_string(),
+        };
+        let text = |minutes: i64, text: &str| FrameOcr {
+            timestamp: window_start + chrono::Duration::minutes(minutes),
+            screen_id: 0,
+            text: text.to_string(),
+        };
+        let context = OcrContext {
+            window_start,
+            texts: vec![
+                text(1, "cargo build"),
+                text(4, "error[E0308]"),
+                text(10, "周会纪要"),
+            ],
+        };
+
+        let enriched = enrich_segments(
+            &[
+                segment("00:00", "05:00", "编写代码"),
+                segment("05:00", "08:00", "浏览网页"),
+                segment("08:00", "15:00", "撰写文档"),
+            ],
+            &context,
+        );
+
+        assert_eq!(
+            enriched[0].description,
+            "编写代码\n屏幕文字（OCR）：\ncargo build\nerror[E0308]"
+        );
+        assert_eq!(enriched[1].description, "浏览网页");
+        assert!(enriched[2].description.ends_with("周会纪要"));
+        assert_eq!(enriched[2].start_timestamp, "08:00");
+
+        // 多显示器的文字按显示器分别列出
+        let mut context = context;
+        context.texts.push(FrameOcr {
+            timestamp: window_start + chrono::Duration::minutes(2),
+            screen_id: 1,
+            text: "docs.rs tokio::spawn".to_string(),
+        });
+        let enriched = enrich_segments(&[segment("00:00", "05:00", "编写代码")], &context);
+        assert_eq!(
+            enriched[0].description,
+            "编写代码\n显示器 1 屏幕文字（OCR）：\ncargo build\nerror[E0308]\n显示器 2 屏幕文字（OCR）：\ndocs.rs tokio::spawn"
+        );
+    }
+}
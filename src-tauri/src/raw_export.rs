@@ -0,0 +1,531 @@
+// 原始数据导出 - 将会话、截图元数据与时间线卡片按日期范围导出为 CSV / Parquet，便于 pandas、DuckDB 分析
+
+use anyhow::Result;
+use arrow_array::{
+    ArrayRef, BooleanArray, Int64Array, RecordBatch, StringArray, TimestampMillisecondArray,
+};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use chrono::{DateTime, NaiveDateTime};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::exporter::{range_days, session_category, session_minutes};
+use crate::storage::{Database, Frame, Session, TimelineCardRecord};
+
+/// 会话表的一行（会话时间按记录时的本地时间导出）
+#[derive(Debug, Clone, Serialize)]
+struct SessionRow {
+    id: Option<i64>,
+    start_time: NaiveDateTime,
+    end_time: NaiveDateTime,
+    duration_minutes: i64,
+    title: String,
+    summary: String,
+    category: Option<&'static str>,
+    /// 原始标签 JSON
+    tags: String,
+    device_name: Option<String>,
+    device_type: Option<String>,
+    video_path: Option<String>,
+}
+
+/// 截图元数据表的一行
+#[derive(Debug, Clone, Serialize)]
+struct FrameRow {
+    id: Option<i64>,
+    session_id: i64,
+    timestamp: NaiveDateTime,
+    file_path: String,
+    app_name: Option<String>,
+    window_title: Option<String>,
+    process_path: Option<String>,
+    domain: Option<String>,
+    screen_id: i64,
+}
+
+/// 时间线卡片表的一行（时间保留 RFC3339 原文，含时区）
+#[derive(Debug, Clone, Serialize)]
+struct CardRow {
+    id: Option<i64>,
+    session_id: i64,
+    start_time: String,
+    end_time: String,
+    category: String,
+    subcategory: String,
+    title: String,
+    summary: String,
+    detailed_summary: String,
+    distractions: Option<String>,
+    app_sites: String,
+    heuristic: bool,
+}
+
+/// 日期范围内的原始数据
+#[derive(Debug, Default)]
+pub struct RawDataset {
+    sessions: Vec<SessionRow>,
+    frames: Vec<FrameRow>,
+    cards: Vec<CardRow>,
+}
+
+/// 原始数据导出结果
+#[derive(Debug)]
+pub struct RawExportOutcome {
+    pub files: Vec<PathBuf>,
+    pub session_count: usize,
+    pub frame_count: usize,
+    pub card_count: usize,
+}
+
+impl RawExportOutcome {
+    /// 渲染提示信息
+    pub fn render_message(&self) -> String {
+        let mut message = format!(
+            "已导出 {} 个会话、{} 条截图记录、{} 张时间线卡片",
+            self.session_count, self.frame_count, self.card_count
+        );
+        for path in &self.files {
+            message.push_str("\n- ");
+            message.push_str(&path.to_string_lossy());
+        }
+        message
+    }
+}
+
+impl RawDataset {
+    /// 加载日期范围（含首尾）内的会话及其截图、时间线卡片
+    pub async fn load(db: &Database, start_date: &str, end_date: &str) -> Result<Self> {
+        let mut dataset = Self::default();
+        for day in range_days(start_date, end_date)? {
+            let date = day.format("%Y-%m-%d").to_string();
+            for session in db.get_sessions_by_date(&date).await? {
+                if let Some(session_id) = session.id {
+                    let frames = db.get_frames_by_session(session_id).await?;
+                    dataset.frames.extend(frames.iter().map(FrameRow::from));
+                    let cards = db.get_timeline_cards_by_session(session_id).await?;
+                    dataset.cards.extend(cards.iter().map(CardRow::from));
+                }
+                dataset.sessions.push(SessionRow::from(&session));
+            }
+        }
+        Ok(dataset)
+    }
+
+    fn outcome(&self, files: Vec<PathBuf>) -> RawExportOutcome {
+        RawExportOutcome {
+            files,
+            session_count: self.sessions.len(),
+            frame_count: self.frames.len(),
+            card_count: self.cards.len(),
+        }
+    }
+
+    /// 写入 CSV：sessions / frames / timeline_cards 各一个文件（带表头）
+    pub fn write_csv(&self, output_dir: &Path, stem: &str) -> Result<RawExportOutcome> {
+        std::fs::create_dir_all(output_dir)?;
+        let files = vec![
+            write_csv_file(
+                &output_dir.join(format!("{}_sessions.csv", stem)),
+                &self.sessions,
+            )?,
+            write_csv_file(
+                &output_dir.join(format!("{}_frames.csv", stem)),
+                &self.frames,
+            )?,
+            write_csv_file(
+                &output_dir.join(format!("{}_timeline_cards.csv", stem)),
+                &self.cards,
+            )?,
+        ];
+        Ok(self.outcome(files))
+    }
+
+    /// 写入 Parquet（Snappy 压缩）：sessions / frames / timeline_cards 各一个文件
+    pub fn write_parquet(&self, output_dir: &Path, stem: &str) -> Result<RawExportOutcome> {
+        std::fs::create_dir_all(output_dir)?;
+        let files = vec![
+            write_parquet_file(
+                &output_dir.join(format!("{}_sessions.parquet", stem)),
+                self.sessions_batch()?,
+            )?,
+            write_parquet_file(
+                &output_dir.join(format!("{}_frames.parquet", stem)),
+                self.frames_batch()?,
+            )?,
+            write_parquet_file(
+                &output_dir.join(format!("{}_timeline_cards.parquet", stem)),
+                self.cards_batch()?,
+            )?,
+        ];
+        Ok(self.outcome(files))
+    }
+
+    fn sessions_batch(&self) -> Result<RecordBatch> {
+        let rows = &self.sessions;
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, true),
+            Field::new("start_time", local_timestamp_type(), false),
+            Field::new("end_time", local_timestamp_type(), false),
+            Field::new("duration_minutes", DataType::Int64, false),
+            Field::new("title", DataType::Utf8, false),
+            Field::new("summary", DataType::Utf8, false),
+            Field::new("category", DataType::Utf8, true),
+            Field::new("tags", DataType::Utf8, false),
+            Field::new("device_name", DataType::Utf8, true),
+            Field::new("device_type", DataType::Utf8, true),
+            Field::new("video_path", DataType::Utf8, true),
+        ]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from_iter(rows.iter().map(|row| row.id))),
+            Arc::new(local_timestamps(rows.iter().map(|row| row.start_time))),
+            Arc::new(local_timestamps(rows.iter().map(|row| row.end_time))),
+            Arc::new(Int64Array::from_iter_values(
+                rows.iter().map(|row| row.duration_minutes),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| &row.title),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| &row.summary),
+            )),
+            Arc::new(StringArray::from_iter(rows.iter().map(|row| row.category))),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| &row.tags),
+            )),
+            Arc::new(StringArray::from_iter(
+                rows.iter().map(|row| row.device_name.as_deref()),
+            )),
+            Arc::new(StringArray::from_iter(
+                rows.iter().map(|row| row.device_type.as_deref()),
+            )),
+            Arc::new(StringArray::from_iter(
+                rows.iter().map(|row| row.video_path.as_deref()),
+            )),
+        ];
+        Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+    }
+
+    fn frames_batch(&self) -> Result<RecordBatch> {
+        let rows = &self.frames;
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, true),
+            Field::new("session_id", DataType::Int64, false),
+            Field::new("timestamp", local_timestamp_type(), false),
+            Field::new("file_path", DataType::Utf8, false),
+            Field::new("app_name", DataType::Utf8, true),
+            Field::new("window_title", DataType::Utf8, true),
+            Field::new("process_path", DataType::Utf8, true),
+            Field::new("domain", DataType::Utf8, true),
+            Field::new("screen_id", DataType::Int64, false),
+        ]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from_iter(rows.iter().map(|row| row.id))),
+            Arc::new(Int64Array::from_iter_values(
+                rows.iter().map(|row| row.session_id),
+            )),
+            Arc::new(local_timestamps(rows.iter().map(|row| row.timestamp))),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| &row.file_path),
+            )),
+            Arc::new(StringArray::from_iter(
+                rows.iter().map(|row| row.app_name.as_deref()),
+            )),
+            Arc::new(StringArray::from_iter(
+                rows.iter().map(|row| row.window_title.as_deref()),
+            )),
+            Arc::new(StringArray::from_iter(
+                rows.iter().map(|row| row.process_path.as_deref()),
+            )),
+            Arc::new(StringArray::from_iter(
+                rows.iter().map(|row| row.domain.as_deref()),
+            )),
+            Arc::new(Int64Array::from_iter_values(
+                rows.iter().map(|row| row.screen_id),
+            )),
+        ];
+        Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+    }
+
+    /// 时间线卡片时间解析为 UTC 时间戳，无法解析时为空值
+    fn cards_batch(&self) -> Result<RecordBatch> {
+        let rows = &self.cards;
+        let utc_timestamp = DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into()));
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, true),
+            Field::new("session_id", DataType::Int64, false),
+            Field::new("start_time", utc_timestamp.clone(), true),
+            Field::new("end_time", utc_timestamp, true),
+            Field::new("category", DataType::Utf8, false),
+            Field::new("subcategory", DataType::Utf8, false),
+            Field::new("title", DataType::Utf8, false),
+            Field::new("summary", DataType::Utf8, false),
+            Field::new("detailed_summary", DataType::Utf8, false),
+            Field::new("distractions", DataType::Utf8, true),
+            Field::new("app_sites", DataType::Utf8, false),
+            Field::new("heuristic", DataType::Boolean, false),
+        ]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from_iter(rows.iter().map(|row| row.id))),
+            Arc::new(Int64Array::from_iter_values(
+                rows.iter().map(|row| row.session_id),
+            )),
+            Arc::new(utc_timestamps(rows.iter().map(|row| &row.start_time))),
+            Arc::new(utc_timestamps(rows.iter().map(|row| &row.end_time))),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| &row.category),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| &row.subcategory),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| &row.title),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| &row.summary),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| &row.detailed_summary),
+            )),
+            Arc::new(StringArray::from_iter(
+                rows.iter().map(|row| row.distractions.as_deref()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| &row.app_sites),
+            )),
+            Arc::new(BooleanArray::from(
+                rows.iter().map(|row| row.heuristic).collect::<Vec<_>>(),
+            )),
+        ];
+        Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+    }
+}
+
+impl From<&Session> for SessionRow {
+    fn from(session: &Session) -> Self {
+        Self {
+            id: session.id,
+            start_time: session.start_time.naive_utc(),
+            end_time: session.end_time.naive_utc(),
+            duration_minutes: session_minutes(session),
+            title: session.title.clone(),
+            summary: session.summary.clone(),
+            category: session_category(session),
+            tags: session.tags.clone(),
+            device_name: session.device_name.clone(),
+            device_type: session.device_type.clone(),
+            video_path: session.video_path.clone(),
+        }
+    }
+}
+
+impl From<&Frame> for FrameRow {
+    fn from(frame: &Frame) -> Self {
+        Self {
+            id: frame.id,
+            session_id: frame.session_id,
+            timestamp: frame.timestamp.naive_utc(),
+            file_path: frame.file_path.clone(),
+            app_name: frame.app_name.clone(),
+            window_title: frame.window_title.clone(),
+            process_path: frame.process_path.clone(),
+            domain: frame.domain.clone(),
+            screen_id: frame.screen_id,
+        }
+    }
+}
+
+impl From<&TimelineCardRecord> for CardRow {
+    fn from(card: &TimelineCardRecord) -> Self {
+        Self {
+            id: card.id,
+            session_id: card.session_id,
+            start_time: card.start_time.clone(),
+            end_time: card.end_time.clone(),
+            category: card.category.clone(),
+            subcategory: card.subcategory.clone(),
+            title: card.title.clone(),
+            summary: card.summary.clone(),
+            detailed_summary: card.detailed_summary.clone(),
+            distractions: card.distractions.clone(),
+            app_sites: card.app_sites.clone(),
+            heuristic: card.heuristic,
+        }
+    }
+}
+
+/// 导出文件名前缀，如 `screen-analyzer_2024-05-01_2024-05-31`
+pub fn export_stem(start_date: &str, end_date: &str) -> String {
+    format!("screen-analyzer_{}_{}", start_date, end_date)
+}
+
+fn write_csv_file<T: Serialize>(path: &Path, rows: &[T]) -> Result<PathBuf> {
+    let mut writer = csv::Writer::from_path(path)?;
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+    Ok(path.to_path_buf())
+}
+
+fn write_parquet_file(path: &Path, batch: RecordBatch) -> Result<PathBuf> {
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let file = std::fs::File::create(path)?;
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), Some(properties))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(path.to_path_buf())
+}
+
+/// 本地时间（无时区）时间戳列类型
+fn local_timestamp_type() -> DataType {
+    DataType::Timestamp(TimeUnit::Millisecond, None)
+}
+
+fn local_timestamps(values: impl Iterator<Item = NaiveDateTime>) -> TimestampMillisecondArray {
+    TimestampMillisecondArray::from_iter_values(
+        values.map(|value| value.and_utc().timestamp_millis()),
+    )
+}
+
+fn utc_timestamps<'a>(values: impl Iterator<Item = &'a String>) -> TimestampMillisecondArray {
+    TimestampMillisecondArray::from_iter(values.map(|value| {
+        DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|parsed| parsed.timestamp_millis())
+    }))
+    .with_timezone("UTC")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use tempfile::tempdir;
+
+    async fn seeded_db(dir: &Path) -> Database {
+        let db = Database::new_sqlite(dir.join("test.db").to_str().unwrap())
+            .await
+            .unwrap();
+        let session_id = db
+            .insert_session(&Session {
+                id: None,
+                start_time: Utc.with_ymd_and_hms(2024, 5, 12, 9, 0, 0).unwrap(),
+                end_time: Utc.with_ymd_and_hms(2024, 5, 12, 10, 30, 0).unwrap(),
+                title: "接口重构, \"鉴权\"".to_string(),
+                summary: "拆分鉴权模块".to_string(),
+                video_path: None,
+                tags: r#"[{"category":"work","confidence":0.9,"keywords":["rust"]}]"#.to_string(),
+                created_at: None,
+                device_name: Some("MacBook".to_string()),
+                device_type: None,
+            })
+            .await
+            .unwrap();
+        for minute in [0, 30] {
+            db.insert_frame(&Frame {
+                id: None,
+                session_id,
+                timestamp: Utc.with_ymd_and_hms(2024, 5, 12, 9, minute, 0).unwrap(),
+                file_path: format!("/frames/{}.jpg", minute),
+                app_name: None,
+                window_title: None,
+                process_path: None,
+                domain: None,
+                screen_id: 0,
+            })
+            .await
+            .unwrap();
+        }
+        db.insert_timeline_card(&TimelineCardRecord {
+            id: None,
+            session_id,
+            llm_call_id: None,
+            start_time: "2024-05-12T09:00:00+08:00".to_string(),
+            end_time: "2024-05-12T09:45:00+08:00".to_string(),
+            category: "work".to_string(),
+            subcategory: String::new(),
+            title: "编写 API".to_string(),
+            summary: String::new(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: "{}".to_string(),
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+            created_at: Utc::now(),
+        })
+        .await
+        .unwrap();
+        db
+    }
+
+    #[tokio::test]
+    async fn test_export_csv() {
+        let temp_dir = tempdir().unwrap();
+        let db = seeded_db(temp_dir.path()).await;
+        let dataset = RawDataset::load(&db, "2024-05-11", "2024-05-13")
+            .await
+            .unwrap();
+
+        let output = temp_dir.path().join("out");
+        let outcome = dataset
+            .write_csv(&output, &export_stem("2024-05-11", "2024-05-13"))
+            .unwrap();
+        assert_eq!(
+            (
+                outcome.session_count,
+                outcome.frame_count,
+                outcome.card_count
+            ),
+            (1, 2, 1)
+        );
+
+        let sessions = std::fs::read_to_string(
+            output.join("screen-analyzer_2024-05-11_2024-05-13_sessions.csv"),
+        )
+        .unwrap();
+        let mut lines = sessions.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "id,start_time,end_time,duration_minutes,title,summary,category,tags,device_name,device_type,video_path"
+        );
+        let row = lines.next().unwrap();
+        assert!(row.contains(",2024-05-12T09:00:00,2024-05-12T10:30:00,90,\"接口重构, \"\"鉴权\"\"\",拆分鉴权模块,work,"));
+        assert!(row.ends_with(",MacBook,,"));
+
+        let frames = std::fs::read_to_string(&outcome.files[1]).unwrap();
+        assert_eq!(frames.lines().count(), 3);
+        assert!(frames.contains("2024-05-12T09:30:00,/frames/30.jpg"));
+    }
+
+    #[tokio::test]
+    async fn test_export_parquet() {
+        let temp_dir = tempdir().unwrap();
+        let db = seeded_db(temp_dir.path()).await;
+        let dataset = RawDataset::load(&db, "2024-05-12", "2024-05-12")
+            .await
+            .unwrap();
+
+        let outcome = dataset
+            .write_parquet(temp_dir.path(), &export_stem("2024-05-12", "2024-05-12"))
+            .unwrap();
+        let row_counts = outcome
+            .files
+            .iter()
+            .map(|path| {
+                let reader = SerializedFileReader::new(std::fs::File::open(path).unwrap()).unwrap();
+                reader.metadata().file_metadata().num_rows()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(row_counts, vec![1, 2, 1]);
+        assert!(outcome.files[2]
+            .ends_with("screen-analyzer_2024-05-12_2024-05-12_timeline_cards.parquet"));
+    }
+}
@@ -0,0 +1,283 @@
+// 时间线卡片重新分类 - 修改分类规则或切换 LLM 提供商后，按日期范围批量重新判断已有卡片的类别，
+// 更新卡片与会话标签，并清除每日总结与专注度指标缓存（周报按每日指标汇总，随之重新计算）
+
+use anyhow::Result;
+use chrono::DateTime;
+use serde::Serialize;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+use crate::actors::LLMHandle;
+use crate::exporter::range_days;
+use crate::llm::{ActivityTag, CardBrief, CardCategory};
+use crate::storage::{Database, TimelineCardRecord};
+
+/// 每次请求最多包含的卡片数量
+const CARDS_PER_REQUEST: usize = 40;
+
+/// 由空闲或会议检测生成的卡片类别，不交给 LLM 重新分类
+const DETECTED_CATEGORIES: [&str; 2] = ["idle", "meeting"];
+
+/// 重新分类结果
+#[derive(Debug, Default, Serialize)]
+pub struct RecategorizeReport {
+    /// 检查的卡片数
+    pub card_count: usize,
+    /// 类别或子类别发生变化的卡片数
+    pub changed_cards: usize,
+    /// 标签被更新的会话数
+    pub changed_sessions: usize,
+    /// 清除了缓存的日期
+    pub invalidated_dates: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl RecategorizeReport {
+    /// 渲染提示信息
+    pub fn render_message(&self) -> String {
+        let mut message = format!(
+            "已检查 {} 张时间线卡片，{} 张类别有变化，更新了 {} 个会话",
+            self.card_count, self.changed_cards, self.changed_sessions
+        );
+        for warning in &self.warnings {
+            message.push_str("\n⚠ ");
+            message.push_str(warning);
+        }
+        message
+    }
+}
+
+/// 重新分类日期范围（含首尾）内的时间线卡片
+///
+/// 单批请求失败时记为警告并继续；只有卡片确实变化的日期才清除每日总结缓存。
+pub async fn recategorize_range(
+    db: &Database,
+    llm_handle: &LLMHandle,
+    start_date: &str,
+    end_date: &str,
+) -> Result<RecategorizeReport> {
+    let mut report = RecategorizeReport::default();
+    for day in range_days(start_date, end_date)? {
+        let date = day.format("%Y-%m-%d").to_string();
+        let mut day_changed = false;
+        for session in db.get_sessions_by_date(&date).await? {
+            let Some(session_id) = session.id else {
+                continue;
+            };
+            let mut cards = db.get_timeline_cards_by_session(session_id).await?;
+            let briefs: Vec<CardBrief> = cards.iter().filter_map(card_brief).collect();
+            if briefs.is_empty() {
+                continue;
+            }
+            report.card_count += briefs.len();
+
+            let mut categories = Vec::new();
+            for chunk in briefs.chunks(CARDS_PER_REQUEST) {
+                match llm_handle.categorize_cards(chunk).await {
+                    Ok(result) => categories.extend(result),
+                    Err(e) => {
+                        warn!("会话 {} 重新分类失败: {}", session_id, e);
+                        report
+                            .warnings
+                            .push(format!("{} {}: {}", date, session.title, e));
+                    }
+                }
+            }
+
+            let changed = apply_categories(&mut cards, &categories);
+            if changed.is_empty() {
+                continue;
+            }
+            db.update_timeline_card_categories(&changed).await?;
+            let tags = serde_json::to_string(&session_tags(&cards))?;
+            db.update_session_tags(session_id, &tags).await?;
+            report.changed_cards += changed.len();
+            report.changed_sessions += 1;
+            day_changed = true;
+        }
+
+        if day_changed {
+            db.delete_day_summary(&date).await?;
+            report.invalidated_dates.push(date);
+        }
+    }
+
+    info!(
+        "重新分类完成: {} ~ {}, {} 张卡片中 {} 张有变化",
+        start_date, end_date, report.card_count, report.changed_cards
+    );
+    Ok(report)
+}
+
+/// 卡片摘要（检测生成的卡片返回 None）
+fn card_brief(card: &TimelineCardRecord) -> Option<CardBrief> {
+    let id = card.id?;
+    if DETECTED_CATEGORIES.contains(&card.category.to_lowercase().as_str()) {
+        return None;
+    }
+    let apps = serde_json::from_str::<serde_json::Value>(&card.app_sites)
+        .ok()
+        .map(|sites| {
+            ["primary", "secondary"]
+                .iter()
+                .filter_map(|key| sites.get(key)?.as_str().map(str::to_string))
+                .filter(|site| !site.is_empty())
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .unwrap_or_default();
+    Some(CardBrief {
+        id,
+        title: card.title.clone(),
+        summary: card.summary.clone(),
+        category: card.category.clone(),
+        subcategory: card.subcategory.clone(),
+        apps,
+    })
+}
+
+/// 将分类结果写回卡片，返回发生变化的卡片（未返回子类别时保留原值）
+fn apply_categories(
+    cards: &mut [TimelineCardRecord],
+    categories: &[CardCategory],
+) -> Vec<TimelineCardRecord> {
+    let mut changed = Vec::new();
+    for card in cards.iter_mut() {
+        let Some(result) = categories.iter().find(|item| Some(item.id) == card.id) else {
+            continue;
+        };
+        let subcategory = if result.subcategory.is_empty() {
+            card.subcategory.clone()
+        } else {
+            result.subcategory.clone()
+        };
+        if card.category != result.category || card.subcategory != subcategory {
+            card.category = result.category.clone();
+            card.subcategory = subcategory;
+            changed.push(card.clone());
+        }
+    }
+    changed
+}
+
+/// 按卡片时长重新计算会话标签：取时长占比不低于 10% 的前 3 个类别，自定义类别名称和子类别作为关键词
+pub(crate) fn session_tags(cards: &[TimelineCardRecord]) -> Vec<ActivityTag> {
+    let mut minutes: HashMap<String, i64> = HashMap::new();
+    let mut keywords: HashMap<String, Vec<String>> = HashMap::new();
+    for card in cards {
+        let duration = match (
+            DateTime::parse_from_rfc3339(&card.start_time),
+            DateTime::parse_from_rfc3339(&card.end_time),
+        ) {
+            (Ok(start), Ok(end)) => (end - start).num_minutes().max(1),
+            _ => 1,
+        };
+        let category = card.category.to_lowercase();
+        *minutes.entry(category.clone()).or_default() += duration;
+        let list = keywords.entry(category).or_insert_with(|| {
+            // 自定义类别名称作为首个关键词，区分归入同一内置类别的自定义类别
+            crate::taxonomy::find_custom_category(&card.category)
+                .map(|custom| vec![custom.name])
+                .unwrap_or_default()
+        });
+        if !card.subcategory.is_empty() && !list.contains(&card.subcategory) {
+            list.push(card.subcategory.clone());
+        }
+    }
+
+    let total: i64 = minutes.values().sum();
+    if total == 0 {
+        return Vec::new();
+    }
+    let mut weights: Vec<(String, i64)> = minutes.into_iter().collect();
+    weights.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    weights
+        .into_iter()
+        .take(3)
+        .map(|(category, minutes)| (category, minutes as f32 / total as f32))
+        .filter(|(_, weight)| *weight >= 0.1)
+        .map(|(category, weight)| ActivityTag {
+            category: crate::llm::map_category(&category),
+            confidence: weight,
+            keywords: keywords.remove(&category).unwrap_or_default(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(id: i64, category: &str, start: &str, end: &str) -> TimelineCardRecord {
+        TimelineCardRecord {
+            id: Some(id),
+            session_id: 1,
+            llm_call_id: None,
+            start_time: format!("2026-03-02T{}:00+08:00", start),
+            end_time: format!("2026-03-02T{}:00+08:00", end),
+            category: category.to_string(),
+            subcategory: "Development".to_string(),
+            title: "编写代码".to_string(),
+            summary: String::new(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: r#"{"primary":"vscode","secondary":"github.com"}"#.to_string(),
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_recategorize_cards() {
+        let mut cards = vec![
+            card(1, "work", "09:00", "09:45"),
+            card(2, "idle", "09:45", "09:50"),
+            card(3, "other", "09:50", "10:00"),
+        ];
+        let briefs: Vec<CardBrief> = cards.iter().filter_map(card_brief).collect();
+        assert_eq!(
+            briefs.iter().map(|brief| brief.id).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+        assert_eq!(briefs[0].apps, "vscode, github.com");
+
+        let changed = apply_categories(
+            &mut cards,
+            &[
+                CardCategory {
+                    id: 1,
+                    category: "work".to_string(),
+                    subcategory: String::new(),
+                },
+                CardCategory {
+                    id: 3,
+                    category: "learning".to_string(),
+                    subcategory: "Docs".to_string(),
+                },
+            ],
+        );
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].id, Some(3));
+        assert_eq!(cards[2].subcategory, "Docs");
+
+        let tags = session_tags(&cards);
+        let tags: Vec<(String, Vec<String>)> = tags
+            .iter()
+            .map(|tag| {
+                (
+                    serde_json::to_value(&tag.category).unwrap().to_string(),
+                    tag.keywords.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            tags,
+            vec![
+                ("\"work\"".to_string(), vec!["Development".to_string()]),
+                ("\"learning\"".to_string(), vec!["Docs".to_string()]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,503 @@
+// RescueTime 导入 - 解析 RescueTime 导出的 CSV，按小时回填会话、时间线卡片与每日指标，
+// 让已有 RescueTime 历史的用户立即获得历史周报
+
+use anyhow::{anyhow, Result};
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::domains::summary::{category_key, SummaryGenerator};
+use crate::models::{ActivityCategory, ActivityTag};
+use crate::storage::{Database, Session, TimelineCardRecord};
+
+/// 导入会话的设备名称（用于识别已导入的会话，避免重复导入）
+pub const RESCUETIME_DEVICE: &str = "RescueTime";
+
+/// 支持的时间格式（RescueTime 按账号时区导出本地时间）
+const TIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%m/%d/%Y %H:%M:%S",
+];
+
+/// RescueTime 导出中的一条活动记录
+#[derive(Debug, Clone, PartialEq)]
+pub struct RescueTimeRow {
+    pub start: NaiveDateTime,
+    pub seconds: i64,
+    pub activity: String,
+    pub category: String,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RescueTimeImportReport {
+    pub rows: usize,
+    /// 无法解析而跳过的行
+    pub skipped_rows: usize,
+    pub imported_sessions: usize,
+    /// 此前已导入而跳过的会话
+    pub duplicate_sessions: usize,
+    pub dates: Vec<String>,
+}
+
+impl RescueTimeImportReport {
+    /// 渲染提示信息
+    pub fn render_message(&self) -> String {
+        let mut message = match self.dates.as_slice() {
+            [] => "没有导入新的会话".to_string(),
+            [date] => format!("已导入 {} 个会话: {}", self.imported_sessions, date),
+            [first, .., last] => format!(
+                "已导入 {} 个会话，共 {} 天: {} ~ {}",
+                self.imported_sessions,
+                self.dates.len(),
+                first,
+                last
+            ),
+        };
+        if self.duplicate_sessions > 0 {
+            message.push_str(&format!("\n跳过已导入的会话: {}", self.duplicate_sessions));
+        }
+        if self.skipped_rows > 0 {
+            message.push_str(&format!("\n无法解析的行: {}", self.skipped_rows));
+        }
+        message
+    }
+}
+
+/// RescueTime 类别 -> 活动类别
+pub fn map_category(category: &str) -> ActivityCategory {
+    match category.trim().to_lowercase().as_str() {
+        "software development" | "design & composition" | "business" => ActivityCategory::Work,
+        "reference & learning" => ActivityCategory::Learning,
+        "communication & scheduling" => ActivityCategory::Communication,
+        "social networking" | "entertainment" | "news & opinion" | "shopping" => {
+            ActivityCategory::Personal
+        }
+        _ => ActivityCategory::Other,
+    }
+}
+
+fn parse_time(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+}
+
+/// 解析 CSV，返回记录与跳过的行数
+///
+/// 支持两种导出格式（按表头识别，忽略大小写）：
+/// - 按时段汇总：Date, Time Spent (seconds), Activity, Category, ...
+/// - 完整日志：Activity, Category, Start Time, End Time, ...
+pub fn parse_csv(data: &[u8]) -> Result<(Vec<RescueTimeRow>, usize)> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(data);
+    let headers = reader
+        .headers()?
+        .iter()
+        .map(|header| header.trim_start_matches('\u{feff}').to_lowercase())
+        .collect::<Vec<_>>();
+    let column = |name: &str| headers.iter().position(|header| header == name);
+
+    let activity = column("activity").ok_or_else(|| anyhow!("CSV 缺少 Activity 列"))?;
+    let category = column("category");
+    let interval = column("date").zip(column("time spent (seconds)"));
+    let log = column("start time").zip(column("end time"));
+    if interval.is_none() && log.is_none() {
+        return Err(anyhow!(
+            "无法识别的 RescueTime CSV：需要 Date + Time Spent (seconds) 或 Start Time + End Time 列"
+        ));
+    }
+
+    let mut rows = Vec::new();
+    let mut skipped = 0;
+    for record in reader.records() {
+        let Ok(record) = record else {
+            skipped += 1;
+            continue;
+        };
+        let field = |index: usize| record.get(index).unwrap_or_default();
+        let span = match (interval, log) {
+            (Some((date, spent)), _) => parse_time(field(date)).zip(
+                field(spent)
+                    .parse::<f64>()
+                    .ok()
+                    .map(|seconds| seconds as i64),
+            ),
+            (None, Some((start, end))) => parse_time(field(start)).and_then(|start| {
+                parse_time(field(end)).map(|end| (start, (end - start).num_seconds()))
+            }),
+            (None, None) => None,
+        };
+        match span {
+            Some((start, seconds)) if seconds > 0 && !field(activity).is_empty() => {
+                rows.push(RescueTimeRow {
+                    start,
+                    seconds,
+                    activity: field(activity).to_string(),
+                    category: category.map(field).unwrap_or_default().to_string(),
+                });
+            }
+            _ => skipped += 1,
+        }
+    }
+    Ok((rows, skipped))
+}
+
+/// 一个类别在某小时内的汇总
+struct CategoryUsage {
+    category: ActivityCategory,
+    /// RescueTime 原始类别（取时长最长者）
+    source_category: String,
+    seconds: i64,
+    /// 按时长降序的活动
+    activities: Vec<(String, i64)>,
+}
+
+/// 待导入的会话及其时间线卡片（卡片 session_id 在写入时回填）
+pub struct ImportedSession {
+    pub session: Session,
+    pub cards: Vec<TimelineCardRecord>,
+}
+
+fn minutes_text(seconds: i64) -> String {
+    format!("{} 分钟", (seconds + 30) / 60)
+}
+
+fn card_time(time: NaiveDateTime) -> String {
+    Local
+        .from_local_datetime(&time)
+        .earliest()
+        .map(|local| local.to_rfc3339())
+        .unwrap_or_else(|| time.and_utc().to_rfc3339())
+}
+
+/// 按小时归并记录：每小时一个会话，每个类别一张时间线卡片（按时长依次排布，不超过一小时）
+pub fn build_sessions(rows: &[RescueTimeRow]) -> Vec<ImportedSession> {
+    let mut hours: BTreeMap<NaiveDateTime, BTreeMap<&'static str, Vec<&RescueTimeRow>>> =
+        BTreeMap::new();
+    for row in rows {
+        let Some(hour) = row
+            .start
+            .with_minute(0)
+            .and_then(|time| time.with_second(0))
+        else {
+            continue;
+        };
+        hours
+            .entry(hour)
+            .or_default()
+            .entry(category_key(&map_category(&row.category)))
+            .or_default()
+            .push(row);
+    }
+
+    let created_at = crate::storage::local_now();
+    hours
+        .into_iter()
+        .filter_map(|(hour, categories)| {
+            let mut usages = categories
+                .into_values()
+                .map(|rows| {
+                    let mut activities: BTreeMap<&str, i64> = BTreeMap::new();
+                    let mut sources: BTreeMap<&str, i64> = BTreeMap::new();
+                    for row in &rows {
+                        *activities.entry(row.activity.as_str()).or_default() += row.seconds;
+                        *sources.entry(row.category.as_str()).or_default() += row.seconds;
+                    }
+                    let mut activities = activities
+                        .into_iter()
+                        .map(|(activity, seconds)| (activity.to_string(), seconds))
+                        .collect::<Vec<_>>();
+                    activities.sort_by_key(|(_, seconds)| std::cmp::Reverse(*seconds));
+                    let source_category = sources
+                        .into_iter()
+                        .max_by_key(|(_, seconds)| *seconds)
+                        .map(|(source, _)| source.to_string())
+                        .unwrap_or_default();
+                    CategoryUsage {
+                        category: map_category(&rows[0].category),
+                        source_category,
+                        seconds: activities.iter().map(|(_, seconds)| seconds).sum(),
+                        activities,
+                    }
+                })
+                .collect::<Vec<_>>();
+            usages.sort_by_key(|usage| std::cmp::Reverse(usage.seconds));
+
+            let total: i64 = usages
+                .iter()
+                .map(|usage| usage.seconds)
+                .sum::<i64>()
+                .min(3600);
+            if total < 60 {
+                return None;
+            }
+
+            let mut cards = Vec::new();
+            let mut cursor = hour;
+            let hour_end = hour + chrono::Duration::hours(1);
+            for usage in &usages {
+                let end = (cursor + chrono::Duration::seconds(usage.seconds)).min(hour_end);
+                if end <= cursor {
+                    break;
+                }
+                let (primary, _) = &usage.activities[0];
+                let secondary = usage
+                    .activities
+                    .iter()
+                    .skip(1)
+                    .take(4)
+                    .map(|(activity, _)| activity.as_str())
+                    .collect::<Vec<_>>();
+                cards.push(TimelineCardRecord {
+                    id: None,
+                    session_id: 0,
+                    llm_call_id: None,
+                    start_time: card_time(cursor),
+                    end_time: card_time(end),
+                    category: category_key(&usage.category).to_string(),
+                    subcategory: usage.source_category.clone(),
+                    title: primary.clone(),
+                    summary: usage
+                        .activities
+                        .iter()
+                        .take(5)
+                        .map(|(activity, seconds)| {
+                            format!("{} {}", activity, minutes_text(*seconds))
+                        })
+                        .collect::<Vec<_>>()
+                        .join("，"),
+                    detailed_summary: String::new(),
+                    distractions: None,
+                    app_sites: serde_json::json!({
+                        "primary": primary,
+                        "secondary": secondary,
+                    })
+                    .to_string(),
+                    confidence: None,
+                    heuristic: false,
+                    video_preview_path: None,
+                    created_at,
+                });
+                cursor = end;
+            }
+
+            let tags = usages
+                .iter()
+                .map(|usage| ActivityTag {
+                    category: usage.category.clone(),
+                    confidence: ((usage.seconds as f32 / total.max(1) as f32) * 100.0).round()
+                        / 100.0,
+                    keywords: usage
+                        .activities
+                        .iter()
+                        .take(3)
+                        .map(|(activity, _)| activity.clone())
+                        .collect(),
+                })
+                .collect::<Vec<_>>();
+            let summary = usages
+                .iter()
+                .map(|usage| {
+                    format!(
+                        "{} {}：{}",
+                        usage.source_category,
+                        minutes_text(usage.seconds),
+                        usage
+                            .activities
+                            .iter()
+                            .take(3)
+                            .map(|(activity, _)| activity.as_str())
+                            .collect::<Vec<_>>()
+                            .join("、")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            Some(ImportedSession {
+                session: Session {
+                    id: None,
+                    start_time: hour.and_utc(),
+                    end_time: (hour + chrono::Duration::seconds(total)).and_utc(),
+                    title: format!("{}（RescueTime）", usages[0].activities[0].0),
+                    summary,
+                    video_path: None,
+                    tags: serde_json::to_string(&tags).unwrap_or_else(|_| "[]".to_string()),
+                    created_at: None,
+                    device_name: Some(RESCUETIME_DEVICE.to_string()),
+                    device_type: Some("import".to_string()),
+                },
+                cards,
+            })
+        })
+        .collect()
+}
+
+/// 导入 RescueTime CSV：写入会话与时间线卡片，并重新统计涉及日期的每日指标
+///
+/// 同一小时已导入过的会话会被跳过，因此可以重复导入有重叠的导出文件。
+pub async fn import_csv(db: Arc<Database>, path: &Path) -> Result<RescueTimeImportReport> {
+    let data = tokio::fs::read(path)
+        .await
+        .map_err(|e| anyhow!("读取 CSV 失败: {}", e))?;
+    let (rows, skipped_rows) = parse_csv(&data)?;
+    let mut report = RescueTimeImportReport {
+        rows: rows.len(),
+        skipped_rows,
+        ..RescueTimeImportReport::default()
+    };
+
+    let mut dates = BTreeSet::new();
+    // 已导入会话的开始时间（按日期懒加载）
+    let mut loaded_dates = BTreeSet::new();
+    let mut imported_starts = BTreeSet::new();
+    for imported in build_sessions(&rows) {
+        let date = imported.session.start_time.format("%Y-%m-%d").to_string();
+        if loaded_dates.insert(date.clone()) {
+            imported_starts.extend(
+                db.get_sessions_by_date(&date)
+                    .await?
+                    .into_iter()
+                    .filter(|session| session.device_name.as_deref() == Some(RESCUETIME_DEVICE))
+                    .map(|session| session.start_time),
+            );
+        }
+        if !imported_starts.insert(imported.session.start_time) {
+            report.duplicate_sessions += 1;
+            continue;
+        }
+
+        let session_id = db.insert_session(&imported.session).await?;
+        let cards = imported
+            .cards
+            .into_iter()
+            .map(|card| TimelineCardRecord { session_id, ..card })
+            .collect::<Vec<_>>();
+        db.insert_timeline_cards(&cards).await?;
+        report.imported_sessions += 1;
+        dates.insert(date);
+    }
+
+    let generator = SummaryGenerator::new(db);
+    for date in &dates {
+        if let Err(e) = generator.refresh_focus_metrics(date).await {
+            warn!("回填 {} 的每日指标失败: {}", date, e);
+        }
+    }
+    report.dates = dates.into_iter().collect();
+    info!(
+        "RescueTime 导入完成: {} 条记录, {} 个会话, {} 天",
+        report.rows,
+        report.imported_sessions,
+        report.dates.len()
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const INTERVAL_CSV: &str =
+        "\u{feff}Date,Time Spent (seconds),Number of People,Activity,Category,Productivity\n\
+2024-05-13T09:00:00,1800,1,VS Code,Software Development,2\n\
+2024-05-13T09:00:00,600,1,Slack,Communication & Scheduling,1\n\
+2024-05-13T09:00:00,300,1,vs code,Software Development,2\n\
+2024-05-13T10:00:00,900,1,YouTube,Entertainment,-2\n\
+2024-05-13T11:00:00,20,1,Finder,Utilities,0\n\
+bad-date,60,1,Terminal,Software Development,2\n";
+
+    #[test]
+    fn test_parse_csv_formats() {
+        let (rows, skipped) = parse_csv(INTERVAL_CSV.as_bytes()).unwrap();
+        assert_eq!((rows.len(), skipped), (5, 1));
+        assert_eq!(rows[1].activity, "Slack");
+        assert_eq!(rows[1].seconds, 600);
+
+        let log = "Activity,Details,Category,Start Time,End Time\n\
+Chrome,docs.rs,Reference & Learning,2024-05-13 14:05:00,2024-05-13 14:35:00\n";
+        let (rows, skipped) = parse_csv(log.as_bytes()).unwrap();
+        assert_eq!(skipped, 0);
+        assert_eq!(rows[0].seconds, 1800);
+        assert_eq!(category_key(&map_category(&rows[0].category)), "learning");
+
+        assert!(parse_csv(b"Name,Value\nfoo,1\n").is_err());
+    }
+
+    #[test]
+    fn test_build_sessions() {
+        let (rows, _) = parse_csv(INTERVAL_CSV.as_bytes()).unwrap();
+        let sessions = build_sessions(&rows);
+        // 11 点仅 20 秒，不生成会话
+        assert_eq!(sessions.len(), 2);
+
+        let first = &sessions[0];
+        assert_eq!(first.session.title, "VS Code（RescueTime）");
+        assert_eq!(
+            (first.session.end_time - first.session.start_time).num_minutes(),
+            45
+        );
+        let tags: Vec<ActivityTag> = serde_json::from_str(&first.session.tags).unwrap();
+        assert_eq!(category_key(&tags[0].category), "work");
+        assert_eq!(tags[0].confidence, 0.78);
+        assert_eq!(
+            first
+                .cards
+                .iter()
+                .map(|card| (card.category.as_str(), card.title.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("work", "VS Code"), ("communication", "Slack")]
+        );
+        // 卡片依次排布
+        assert_eq!(first.cards[0].end_time, first.cards[1].start_time);
+        assert_eq!(first.cards[0].summary, "VS Code 30 分钟，vs code 5 分钟");
+        assert_eq!(
+            category_key(&map_category(&sessions[1].cards[0].subcategory)),
+            "personal"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_skips_duplicates() {
+        let dir = tempdir().unwrap();
+        let db = Arc::new(
+            Database::new_sqlite(dir.path().join("test.db").to_str().unwrap())
+                .await
+                .unwrap(),
+        );
+        let csv_path = dir.path().join("rescuetime.csv");
+        std::fs::write(&csv_path, INTERVAL_CSV).unwrap();
+
+        let report = import_csv(db.clone(), &csv_path).await.unwrap();
+        assert_eq!(report.imported_sessions, 2);
+        assert_eq!(report.dates, vec!["2024-05-13"]);
+        let metrics = db
+            .get_daily_focus_metrics("2024-05-13", "2024-05-13")
+            .await
+            .unwrap();
+        assert_eq!(metrics[0].work_minutes, 35);
+        assert_eq!(metrics[0].personal_minutes, 15);
+
+        let again = import_csv(db.clone(), &csv_path).await.unwrap();
+        assert_eq!((again.imported_sessions, again.duplicate_sessions), (0, 2));
+        assert_eq!(
+            db.get_sessions_by_date("2024-05-13").await.unwrap().len(),
+            2
+        );
+    }
+}
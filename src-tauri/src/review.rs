@@ -0,0 +1,276 @@
+// 低置信度卡片复核 - LLM 对类别判断的置信度低于阈值的时间线卡片进入复核队列，
+// 用户确认或修正类别后更新卡片与会话标签；同一应用或网站多次被确认为相同类别时，
+// 自动生成预分类规则，之后的会话直接按规则归类
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use tracing::info;
+
+use crate::models::ReviewQueueConfig;
+use crate::storage::{CardReviewRecord, ClassificationRuleRecord, Database, TimelineCardRecord};
+
+/// 复核结果
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ReviewOutcome {
+    /// 卡片类别或子类别是否被修改
+    pub changed: bool,
+    /// 新生成的预分类规则 ID
+    pub rule_id: Option<i64>,
+}
+
+/// 卡片的主要应用或网站
+pub fn primary_app(card: &TimelineCardRecord) -> String {
+    serde_json::from_str::<serde_json::Value>(&card.app_sites)
+        .ok()
+        .and_then(|sites| Some(sites.get("primary")?.as_str()?.trim().to_string()))
+        .unwrap_or_default()
+}
+
+/// 最近 rule_after 次复核的类别一致时返回该类别与最近一次的子类别（复核记录按时间升序）
+pub fn learned_category(reviews: &[CardReviewRecord], rule_after: u32) -> Option<(String, String)> {
+    let rule_after = rule_after as usize;
+    if rule_after == 0 || reviews.len() < rule_after {
+        return None;
+    }
+    let recent = &reviews[reviews.len() - rule_after..];
+    let last = recent.last()?;
+    recent
+        .iter()
+        .all(|review| review.category == last.category)
+        .then(|| (last.category.clone(), last.subcategory.clone()))
+}
+
+/// 由应用或网站生成预分类规则：像域名的按网站匹配（含子域名），否则按应用名称完整匹配
+pub fn rule_record(app: &str, category: &str, subcategory: &str) -> ClassificationRuleRecord {
+    let escaped = regex::escape(app);
+    let is_domain = app.contains('.') && !app.contains(char::is_whitespace);
+    let (match_field, pattern) = if is_domain {
+        ("url", format!(r"(^|\.){}$", escaped))
+    } else {
+        ("app", format!("^{}$", escaped))
+    };
+    ClassificationRuleRecord {
+        id: None,
+        match_field: match_field.to_string(),
+        pattern,
+        category: category.to_string(),
+        subcategory: subcategory.to_string(),
+        created_at: crate::storage::local_now(),
+    }
+}
+
+/// 获取待复核的卡片（未启用时为空）
+pub async fn review_queue(
+    db: &Database,
+    config: &ReviewQueueConfig,
+    limit: i64,
+) -> Result<Vec<TimelineCardRecord>> {
+    if !config.enabled {
+        return Ok(Vec::new());
+    }
+    db.get_review_queue(config.threshold.clamp(0.0, 1.0) as f64, limit)
+        .await
+}
+
+/// 确认或修正卡片类别（category 为空表示确认当前类别）
+///
+/// 类别变化时同步更新会话标签并清除当天的每日总结缓存；
+/// 同一应用的复核达到 rule_after 次且类别一致时生成预分类规则（已存在相同规则时跳过）。
+pub async fn review_card(
+    db: &Database,
+    config: &ReviewQueueConfig,
+    card_id: i64,
+    category: Option<String>,
+    subcategory: Option<String>,
+) -> Result<ReviewOutcome> {
+    let mut card = db
+        .get_timeline_card(card_id)
+        .await?
+        .ok_or_else(|| anyhow!("时间线卡片不存在: {}", card_id))?;
+    let original_category = card.category.clone();
+
+    let category = match category.as_deref().map(str::trim) {
+        Some(raw) if !raw.is_empty() => {
+            crate::llm::plugin::normalize_card_category(raw, &crate::taxonomy::custom_categories())
+                .ok_or_else(|| anyhow!("未知类别: {}", raw))?
+        }
+        _ => card.category.clone(),
+    };
+    let subcategory = match subcategory {
+        Some(subcategory) => subcategory.trim().to_string(),
+        None if category == card.category => card.subcategory.clone(),
+        None => String::new(),
+    };
+
+    let mut outcome = ReviewOutcome::default();
+    if category != card.category || subcategory != card.subcategory {
+        card.category = category.clone();
+        card.subcategory = subcategory.clone();
+        db.update_timeline_card_categories(std::slice::from_ref(&card))
+            .await?;
+
+        let cards = db.get_timeline_cards_by_session(card.session_id).await?;
+        let tags = serde_json::to_string(&crate::recategorize::session_tags(&cards))?;
+        db.update_session_tags(card.session_id, &tags).await?;
+        if let Some(date) = card.start_time.get(..10) {
+            db.delete_day_summary(date).await?;
+        }
+        outcome.changed = true;
+    }
+
+    let app = primary_app(&card);
+    db.save_card_review(&CardReviewRecord {
+        card_id,
+        session_id: card.session_id,
+        app: app.clone(),
+        original_category,
+        category,
+        subcategory,
+        reviewed_at: crate::storage::local_now(),
+    })
+    .await?;
+
+    if app.is_empty() {
+        return Ok(outcome);
+    }
+    let reviews = db.get_card_reviews_by_app(&app).await?;
+    if let Some((category, subcategory)) = learned_category(&reviews, config.rule_after) {
+        let rule = rule_record(&app, &category, &subcategory);
+        let exists = db.get_classification_rules().await?.iter().any(|existing| {
+            existing.match_field == rule.match_field && existing.pattern == rule.pattern
+        });
+        if !exists {
+            let id = db.insert_classification_rule(&rule).await?;
+            info!(
+                "{} 已被确认 {} 次为 {}，生成预分类规则 {}",
+                app, config.rule_after, rule.category, rule.pattern
+            );
+            outcome.rule_id = Some(id);
+        }
+    }
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Session;
+    use chrono::{TimeZone, Utc};
+
+    fn card(session_id: i64, start: &str, app: &str, confidence: f64) -> TimelineCardRecord {
+        TimelineCardRecord {
+            id: None,
+            session_id,
+            llm_call_id: None,
+            start_time: format!("2024-05-12T{}:00+08:00", start),
+            end_time: format!("2024-05-12T{}:30+08:00", start),
+            category: "other".to_string(),
+            subcategory: String::new(),
+            title: "浏览网页".to_string(),
+            summary: String::new(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: format!(r#"{{"primary":"{}","secondary":""}}"#, app),
+            confidence: Some(confidence),
+            heuristic: false,
+            video_preview_path: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_rule_record_and_learned_category() {
+        let rule = rule_record("docs.rs", "learning", "Rust");
+        assert_eq!(rule.match_field, "url");
+        assert_eq!(rule.pattern, r"(^|\.)docs\.rs$");
+        let compiled = crate::capture::rules::ClassificationRule::new(&rule).unwrap();
+        assert!(compiled.pattern.is_match("docs.rs"));
+        assert!(!compiled.pattern.is_match("docs.rsx"));
+
+        let rule = rule_record("Visual Studio Code", "work", "");
+        assert_eq!(rule.match_field, "app");
+        assert_eq!(rule.pattern, "^Visual Studio Code$");
+
+        let review = |category: &str| CardReviewRecord {
+            card_id: 1,
+            session_id: 1,
+            app: "docs.rs".to_string(),
+            original_category: "other".to_string(),
+            category: category.to_string(),
+            subcategory: String::new(),
+            reviewed_at: Utc::now(),
+        };
+        let reviews = vec![review("other"), review("learning"), review("learning")];
+        assert_eq!(
+            learned_category(&reviews, 2).map(|(category, _)| category),
+            Some("learning".to_string())
+        );
+        assert_eq!(learned_category(&reviews, 3), None);
+        assert_eq!(learned_category(&reviews, 0), None);
+    }
+
+    #[tokio::test]
+    async fn test_review_cards_and_learn_rule() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new_sqlite(dir.path().join("test.db").to_str().unwrap())
+            .await
+            .unwrap();
+        let session_id = db
+            .insert_session(&Session {
+                id: None,
+                start_time: Utc.with_ymd_and_hms(2024, 5, 12, 1, 0, 0).unwrap(),
+                end_time: Utc.with_ymd_and_hms(2024, 5, 12, 2, 0, 0).unwrap(),
+                title: "查阅文档".to_string(),
+                summary: String::new(),
+                video_path: None,
+                tags: "[]".to_string(),
+                created_at: None,
+                device_name: None,
+                device_type: None,
+            })
+            .await
+            .unwrap();
+        let first = db
+            .insert_timeline_card(&card(session_id, "09:00", "docs.rs", 0.4))
+            .await
+            .unwrap();
+        let second = db
+            .insert_timeline_card(&card(session_id, "09:30", "docs.rs", 0.5))
+            .await
+            .unwrap();
+        db.insert_timeline_card(&card(session_id, "10:00", "docs.rs", 0.9))
+            .await
+            .unwrap();
+
+        let config = ReviewQueueConfig::default();
+        let queue = review_queue(&db, &config, 10).await.unwrap();
+        assert_eq!(
+            queue.iter().filter_map(|card| card.id).collect::<Vec<_>>(),
+            vec![second, first]
+        );
+
+        let outcome = review_card(&db, &config, first, Some("Learning".into()), None)
+            .await
+            .unwrap();
+        assert!(outcome.changed);
+        assert_eq!(outcome.rule_id, None);
+        let updated = db.get_timeline_card(first).await.unwrap().unwrap();
+        assert_eq!(updated.category, "learning");
+
+        // 第二次一致的复核生成规则，复核过的卡片不再出现在队列中
+        let outcome = review_card(&db, &config, second, Some("learning".into()), None)
+            .await
+            .unwrap();
+        assert!(outcome.rule_id.is_some());
+        assert!(review_queue(&db, &config, 10).await.unwrap().is_empty());
+        let rules = db.get_classification_rules().await.unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].match_field, "url");
+
+        assert!(
+            review_card(&db, &config, first, Some("unknown".into()), None)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -34,6 +34,11 @@ impl SettingsManager {
         })
     }
 
+    /// 配置文件路径
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
     pub async fn get(&self) -> PersistedAppConfig {
         self.data.read().await.clone()
     }
@@ -77,6 +82,81 @@ impl SettingsManager {
         if let Some(obsidian) = update.obsidian_config {
             config.obsidian_config = Some(obsidian);
         }
+        if let Some(logseq) = update.logseq_config {
+            config.logseq_config = Some(logseq);
+        }
+        if let Some(html) = update.html_config {
+            config.html_config = Some(html);
+        }
+        if let Some(webhook) = update.webhook_config {
+            config.webhook_config = Some(webhook);
+        }
+        if let Some(mqtt) = update.mqtt_config {
+            config.mqtt_config = Some(mqtt);
+        }
+        if let Some(digest) = update.digest_config {
+            config.digest_config = Some(digest);
+        }
+        if let Some(chat) = update.chat_config {
+            config.chat_config = Some(chat);
+        }
+        if let Some(calendar) = update.calendar_config {
+            config.calendar_config = Some(calendar);
+        }
+        if let Some(issue_link) = update.issue_link_config {
+            config.issue_link_config = Some(issue_link);
+        }
+        if let Some(time_tracking) = update.time_tracking_config {
+            config.time_tracking_config = Some(time_tracking);
+        }
+        if let Some(embedding) = update.embedding_config {
+            config.embedding_config = Some(embedding);
+        }
+        if let Some(ocr) = update.ocr_config {
+            config.ocr_config = Some(ocr);
+        }
+        if let Some(browser) = update.browser_config {
+            config.browser_config = Some(browser);
+        }
+        if let Some(budget) = update.storage_budget_config {
+            config.storage_budget_config = Some(budget);
+        }
+        if let Some(queue) = update.summary_queue_config {
+            config.summary_queue_config = Some(queue);
+        }
+        if let Some(frame_prep) = update.frame_prep_config {
+            config.frame_prep_config = Some(frame_prep);
+        }
+        if let Some(llm_cache) = update.llm_cache_config {
+            config.llm_cache_config = Some(llm_cache);
+        }
+        if let Some(review_queue) = update.review_queue_config {
+            config.review_queue_config = Some(review_queue);
+        }
+        if let Some(heuristic) = update.heuristic_config {
+            config.heuristic_config = Some(heuristic);
+        }
+        if let Some(focus) = update.focus_config {
+            config.focus_config = Some(focus);
+        }
+        if let Some(distraction_alert) = update.distraction_alert_config {
+            config.distraction_alert_config = Some(distraction_alert);
+        }
+        if let Some(app_limit) = update.app_limit_config {
+            config.app_limit_config = Some(app_limit);
+        }
+        if let Some(wellbeing) = update.wellbeing_config {
+            config.wellbeing_config = Some(wellbeing);
+        }
+        if let Some(lan_sync) = update.lan_sync_config {
+            config.lan_sync_config = Some(lan_sync);
+        }
+        if let Some(backup) = update.backup_config {
+            config.backup_config = Some(backup);
+        }
+        if let Some(frame_store) = update.frame_store_config {
+            config.frame_store_config = Some(frame_store);
+        }
 
         self.save(&config).await?;
         Ok(config.clone())
@@ -0,0 +1,396 @@
+// 会话边界调整 - 手动拆分、合并会话时重新分配时间线卡片、应用/域名使用时长与标签，
+// 由 `Database::split_session` / `Database::merge_sessions` 调用（帧与 OCR 文字按时间戳直接迁移）
+
+use super::models::{AppUsageRecord, DomainUsageRecord, SessionNoteRecord, TimelineCardRecord};
+use chrono::{DateTime, TimeZone, Utc};
+use std::collections::HashMap;
+
+/// 解析卡片时间（带时区的 RFC3339），转回与会话、帧时间戳一致的本地时间值
+fn card_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|time| time.naive_local().and_utc())
+}
+
+/// 按 `template` 的时区将本地时间值格式化为 RFC3339
+fn format_like(time: DateTime<Utc>, template: &str) -> String {
+    DateTime::parse_from_rfc3339(template)
+        .ok()
+        .and_then(|parsed| {
+            parsed
+                .offset()
+                .from_local_datetime(&time.naive_utc())
+                .single()
+        })
+        .map(|local| local.to_rfc3339())
+        .unwrap_or_else(|| time.to_rfc3339())
+}
+
+/// 在 `at` 处拆分时间线卡片：之前的留在原会话，之后的归入新会话，跨越拆分点的卡片一分为二。
+/// 返回的卡片 ID 均为空，由调用方删除原卡片后重新插入
+pub fn split_cards(
+    cards: &[TimelineCardRecord],
+    at: DateTime<Utc>,
+    new_session_id: i64,
+) -> (Vec<TimelineCardRecord>, Vec<TimelineCardRecord>) {
+    let mut before = Vec::new();
+    let mut after = Vec::new();
+    for card in cards {
+        let card = TimelineCardRecord {
+            id: None,
+            ..card.clone()
+        };
+        let (Some(start), Some(end)) = (card_time(&card.start_time), card_time(&card.end_time))
+        else {
+            before.push(card);
+            continue;
+        };
+
+        if end <= at {
+            before.push(card);
+        } else if start >= at {
+            after.push(TimelineCardRecord {
+                session_id: new_session_id,
+                ..card
+            });
+        } else {
+            after.push(TimelineCardRecord {
+                session_id: new_session_id,
+                start_time: format_like(at, &card.start_time),
+                ..card.clone()
+            });
+            before.push(TimelineCardRecord {
+                end_time: format_like(at, &card.end_time),
+                ..card
+            });
+        }
+    }
+    (before, after)
+}
+
+/// 拆分点之前的时长占会话总时长的比例
+pub fn time_share(start: DateTime<Utc>, at: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+    let total = (end - start).num_seconds();
+    if total <= 0 {
+        return 1.0;
+    }
+    ((at - start).num_seconds() as f64 / total as f64).clamp(0.0, 1.0)
+}
+
+/// 按比例拆分秒数（四舍五入，两部分之和不变）
+fn split_seconds(seconds: i64, share: f64) -> (i64, i64) {
+    let before = (seconds as f64 * share).round() as i64;
+    (before, seconds - before)
+}
+
+/// 按时长比例拆分应用使用时长（拆分后为 0 秒的记录丢弃）
+pub fn split_app_usage(
+    records: &[AppUsageRecord],
+    share: f64,
+    new_session_id: i64,
+) -> (Vec<AppUsageRecord>, Vec<AppUsageRecord>) {
+    let mut before = Vec::new();
+    let mut after = Vec::new();
+    for record in records {
+        let (first, second) = split_seconds(record.seconds, share);
+        if first > 0 {
+            before.push(AppUsageRecord {
+                seconds: first,
+                ..record.clone()
+            });
+        }
+        if second > 0 {
+            after.push(AppUsageRecord {
+                session_id: new_session_id,
+                seconds: second,
+                ..record.clone()
+            });
+        }
+    }
+    (before, after)
+}
+
+/// 按时长比例拆分域名访问时长（拆分后为 0 秒的记录丢弃）
+pub fn split_domain_usage(
+    records: &[DomainUsageRecord],
+    share: f64,
+    new_session_id: i64,
+) -> (Vec<DomainUsageRecord>, Vec<DomainUsageRecord>) {
+    let mut before = Vec::new();
+    let mut after = Vec::new();
+    for record in records {
+        let (first, second) = split_seconds(record.seconds, share);
+        if first > 0 {
+            before.push(DomainUsageRecord {
+                seconds: first,
+                ..record.clone()
+            });
+        }
+        if second > 0 {
+            after.push(DomainUsageRecord {
+                session_id: new_session_id,
+                seconds: second,
+                ..record.clone()
+            });
+        }
+    }
+    (before, after)
+}
+
+/// 合并两个会话的应用使用时长：同一应用的秒数相加，窗口标题取时长更长的一条
+pub fn merge_app_usage(session_id: i64, records: Vec<AppUsageRecord>) -> Vec<AppUsageRecord> {
+    let mut merged: HashMap<String, (AppUsageRecord, i64)> = HashMap::new();
+    for record in records {
+        match merged.get_mut(&record.app_name) {
+            Some((existing, longest)) => {
+                existing.seconds += record.seconds;
+                if existing.process_path.is_none() {
+                    existing.process_path = record.process_path.clone();
+                }
+                if record.seconds > *longest {
+                    *longest = record.seconds;
+                    existing.window_title = record.window_title;
+                }
+            }
+            None => {
+                let seconds = record.seconds;
+                merged.insert(
+                    record.app_name.clone(),
+                    (
+                        AppUsageRecord {
+                            session_id,
+                            ..record
+                        },
+                        seconds,
+                    ),
+                );
+            }
+        }
+    }
+
+    let mut records: Vec<AppUsageRecord> = merged.into_values().map(|(record, _)| record).collect();
+    records.sort_by(|a, b| {
+        b.seconds
+            .cmp(&a.seconds)
+            .then_with(|| a.app_name.cmp(&b.app_name))
+    });
+    records
+}
+
+/// 合并两个会话的域名访问时长
+pub fn merge_domain_usage(
+    session_id: i64,
+    records: Vec<DomainUsageRecord>,
+) -> Vec<DomainUsageRecord> {
+    let mut seconds: HashMap<String, i64> = HashMap::new();
+    for record in records {
+        *seconds.entry(record.domain).or_default() += record.seconds;
+    }
+
+    let mut records: Vec<DomainUsageRecord> = seconds
+        .into_iter()
+        .map(|(domain, seconds)| DomainUsageRecord {
+            session_id,
+            domain,
+            seconds,
+        })
+        .collect();
+    records.sort_by(|a, b| {
+        b.seconds
+            .cmp(&a.seconds)
+            .then_with(|| a.domain.cmp(&b.domain))
+    });
+    records
+}
+
+/// 合并两个会话的标签（JSON 数组），同一类别只保留先出现的一个
+pub fn merge_tags(first: &str, second: &str) -> String {
+    let parse =
+        |tags: &str| serde_json::from_str::<Vec<serde_json::Value>>(tags).unwrap_or_default();
+    let mut merged = parse(first);
+    for tag in parse(second) {
+        let exists = merged
+            .iter()
+            .any(|existing| existing.get("category") == tag.get("category"));
+        if !exists {
+            merged.push(tag);
+        }
+    }
+    serde_json::to_string(&merged).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// 合并两个会话的总结（去掉空白与重复的部分）
+pub fn merge_summaries(first: &str, second: &str) -> String {
+    let (first, second) = (first.trim(), second.trim());
+    if second.is_empty() || second == first {
+        first.to_string()
+    } else if first.is_empty() {
+        second.to_string()
+    } else {
+        format!("{}\n\n{}", first, second)
+    }
+}
+
+/// 合并两个会话的备注：文字按总结的方式拼接，自定义标签去重
+pub fn merge_notes(
+    session_id: i64,
+    first: Option<SessionNoteRecord>,
+    second: Option<SessionNoteRecord>,
+) -> Option<SessionNoteRecord> {
+    let (note, tags) = match (first, second) {
+        (Some(first), Some(second)) => (
+            merge_summaries(&first.note, &second.note),
+            [first.tag_list(), second.tag_list()].concat(),
+        ),
+        (Some(note), None) | (None, Some(note)) => {
+            let tags = note.tag_list();
+            (note.note, tags)
+        }
+        (None, None) => return None,
+    };
+    SessionNoteRecord::new(session_id, &note, &tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        chrono::NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_utc()
+            + Duration::minutes(minutes)
+    }
+
+    fn card(start: i64, end: i64, title: &str) -> TimelineCardRecord {
+        let format = |minutes: i64| format_like(at(minutes), "2026-03-02T09:00:00+08:00");
+        TimelineCardRecord {
+            id: Some(start),
+            session_id: 1,
+            llm_call_id: None,
+            start_time: format(start),
+            end_time: format(end),
+            category: "work".to_string(),
+            subcategory: "coding".to_string(),
+            title: title.to_string(),
+            summary: String::new(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: "{}".to_string(),
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+            created_at: at(0),
+        }
+    }
+
+    #[test]
+    fn test_split_cards() {
+        let cards = vec![
+            card(0, 5, "编写代码"),
+            card(5, 12, "代码评审"),
+            card(12, 15, "写文档"),
+        ];
+        let (before, after) = split_cards(&cards, at(8), 2);
+
+        let spans = |cards: &[TimelineCardRecord]| -> Vec<(String, i64, String)> {
+            cards
+                .iter()
+                .map(|card| (card.title.clone(), card.session_id, card.end_time.clone()))
+                .collect()
+        };
+        assert_eq!(
+            spans(&before),
+            vec![
+                (
+                    "编写代码".to_string(),
+                    1,
+                    "2026-03-02T09:05:00+08:00".to_string()
+                ),
+                (
+                    "代码评审".to_string(),
+                    1,
+                    "2026-03-02T09:08:00+08:00".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            spans(&after),
+            vec![
+                (
+                    "代码评审".to_string(),
+                    2,
+                    "2026-03-02T09:12:00+08:00".to_string()
+                ),
+                (
+                    "写文档".to_string(),
+                    2,
+                    "2026-03-02T09:15:00+08:00".to_string()
+                ),
+            ]
+        );
+        assert_eq!(after[0].start_time, "2026-03-02T09:08:00+08:00");
+        assert!(before.iter().chain(&after).all(|card| card.id.is_none()));
+    }
+
+    #[test]
+    fn test_split_and_merge_usage() {
+        let usage = |session_id: i64, app: &str, title: &str, seconds: i64| AppUsageRecord {
+            session_id,
+            app_name: app.to_string(),
+            process_path: None,
+            window_title: title.to_string(),
+            seconds,
+        };
+
+        let share = time_share(at(0), at(10), at(15));
+        let (before, after) = split_app_usage(&[usage(1, "Code", "main.rs", 601)], share, 2);
+        assert_eq!((before[0].seconds, after[0].seconds), (401, 200));
+        assert_eq!(after[0].session_id, 2);
+
+        let merged = merge_app_usage(
+            1,
+            vec![
+                usage(1, "Code", "main.rs", 300),
+                usage(1, "Chrome", "docs", 100),
+                usage(2, "Code", "lib.rs", 500),
+            ],
+        );
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].seconds, 800);
+        assert_eq!(merged[0].window_title, "lib.rs");
+        assert!(merged.iter().all(|record| record.session_id == 1));
+
+        let tags = merge_tags(
+            r#"[{"category":"work","confidence":0.9,"keywords":[]}]"#,
+            r#"[{"category":"work","confidence":0.5,"keywords":[]},{"category":"learning","confidence":0.6,"keywords":[]}]"#,
+        );
+        let tags: Vec<serde_json::Value> = serde_json::from_str(&tags).unwrap();
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0]["confidence"], 0.9);
+        assert_eq!(merge_summaries("写代码", " "), "写代码");
+    }
+
+    #[test]
+    fn test_merge_notes() {
+        let tags = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        assert!(SessionNoteRecord::new(1, "  ", &tags(&[" ", "#"])).is_none());
+
+        let first = SessionNoteRecord::new(1, " 排查登录问题 ", &tags(&["#bug", "客户A", "bug"]));
+        assert_eq!(first.as_ref().unwrap().note, "排查登录问题");
+        assert_eq!(first.as_ref().unwrap().tag_list(), tags(&["bug", "客户A"]));
+
+        let second = SessionNoteRecord::new(2, "已修复", &tags(&["bug", "发布"]));
+        let merged = merge_notes(1, first.clone(), second).unwrap();
+        assert_eq!(merged.session_id, 1);
+        assert_eq!(merged.note, "排查登录问题\n\n已修复");
+        assert_eq!(merged.tag_list(), tags(&["bug", "客户A", "发布"]));
+
+        let only_second = merge_notes(1, None, first).unwrap();
+        assert_eq!(only_second.session_id, 1);
+        assert!(merge_notes(1, None, None).is_none());
+    }
+}
@@ -126,6 +126,16 @@ impl DatabaseRepository for CachedRepository {
         Ok(id)
     }
 
+    async fn insert_session_with_cards(
+        &self,
+        session: &Session,
+        cards: &[TimelineCardRecord],
+    ) -> Result<i64> {
+        let id = self.inner.insert_session_with_cards(session, cards).await?;
+        self.clear_cache().await;
+        Ok(id)
+    }
+
     async fn insert_sessions(&self, sessions: &[Session]) -> Result<Vec<i64>> {
         let ids = self.inner.insert_sessions(sessions).await?;
         self.clear_cache().await;
@@ -206,6 +216,32 @@ impl DatabaseRepository for CachedRepository {
         Ok(())
     }
 
+    async fn update_session_times(
+        &self,
+        session_id: i64,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<()> {
+        self.inner
+            .update_session_times(session_id, start_time, end_time)
+            .await?;
+        self.invalidate_session(session_id).await;
+        Ok(())
+    }
+
+    async fn replace_session_content(
+        &self,
+        session_id: i64,
+        session: &Session,
+        cards: &[TimelineCardRecord],
+    ) -> Result<()> {
+        self.inner
+            .replace_session_content(session_id, session, cards)
+            .await?;
+        self.invalidate_session(session_id).await;
+        Ok(())
+    }
+
     async fn update_session_video_path(&self, session_id: i64, video_path: &str) -> Result<()> {
         self.inner
             .update_session_video_path(session_id, video_path)
@@ -214,6 +250,12 @@ impl DatabaseRepository for CachedRepository {
         Ok(())
     }
 
+    async fn clear_session_video_path(&self, session_id: i64) -> Result<()> {
+        self.inner.clear_session_video_path(session_id).await?;
+        self.invalidate_session(session_id).await;
+        Ok(())
+    }
+
     async fn update_device_info_for_all_sessions(&self) -> Result<u64> {
         let count = self.inner.update_device_info_for_all_sessions().await?;
         self.clear_cache().await;
@@ -285,12 +327,63 @@ impl DatabaseRepository for CachedRepository {
         Ok(())
     }
 
+    async fn delete_frames(&self, session_id: i64, frame_ids: &[i64]) -> Result<()> {
+        self.inner.delete_frames(session_id, frame_ids).await?;
+        let mut cache = self.frames_cache.write().await;
+        cache.invalidate(&session_id);
+        Ok(())
+    }
+
+    async fn update_frame_path(
+        &self,
+        session_id: i64,
+        frame_id: i64,
+        file_path: &str,
+    ) -> Result<()> {
+        self.inner
+            .update_frame_path(session_id, frame_id, file_path)
+            .await?;
+        let mut cache = self.frames_cache.write().await;
+        cache.invalidate(&session_id);
+        Ok(())
+    }
+
+    async fn move_frames(
+        &self,
+        from_session_id: i64,
+        to_session_id: i64,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<u64> {
+        let moved = self
+            .inner
+            .move_frames(from_session_id, to_session_id, since)
+            .await?;
+        self.invalidate_session(from_session_id).await;
+        self.invalidate_session(to_session_id).await;
+        Ok(moved)
+    }
+
     // ========== 其他操作（不缓存，直接透传） ==========
 
     async fn get_activities(&self, start_date: &str, end_date: &str) -> Result<Vec<Activity>> {
         self.inner.get_activities(start_date, end_date).await
     }
 
+    async fn get_device_activities(
+        &self,
+        start_date: &str,
+        end_date: &str,
+        device_name: &str,
+    ) -> Result<Vec<Activity>> {
+        self.inner
+            .get_device_activities(start_date, end_date, device_name)
+            .await
+    }
+
+    async fn get_device_names(&self) -> Result<Vec<String>> {
+        self.inner.get_device_names().await
+    }
+
     async fn insert_llm_call(&self, record: &LLMCallRecord) -> Result<i64> {
         self.inner.insert_llm_call(record).await
     }
@@ -353,6 +446,21 @@ impl DatabaseRepository for CachedRepository {
             .await
     }
 
+    async fn update_timeline_card_title(&self, id: i64, title: &str) -> Result<()> {
+        self.inner.update_timeline_card_title(id, title).await
+    }
+
+    async fn update_timeline_card_category(
+        &self,
+        id: i64,
+        category: &str,
+        subcategory: &str,
+    ) -> Result<()> {
+        self.inner
+            .update_timeline_card_category(id, category, subcategory)
+            .await
+    }
+
     async fn get_stats(&self) -> Result<(i64, i64, i64)> {
         self.inner.get_stats().await
     }
@@ -373,14 +481,334 @@ impl DatabaseRepository for CachedRepository {
         self.inner.delete_day_summary(date).await
     }
 
-    async fn initialize_tables(&self) -> Result<()> {
-        self.inner.initialize_tables().await
+    async fn save_daily_focus_metrics(
+        &self,
+        date: &str,
+        metrics: &DailyFocusMetricsRecord,
+    ) -> Result<()> {
+        self.inner.save_daily_focus_metrics(date, metrics).await
+    }
+
+    async fn get_daily_focus_metrics(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DailyFocusMetricsRecord>> {
+        self.inner
+            .get_daily_focus_metrics(start_date, end_date)
+            .await
+    }
+
+    async fn delete_daily_focus_metrics(&self, date: &str) -> Result<()> {
+        self.inner.delete_daily_focus_metrics(date).await
+    }
+
+    async fn insert_export_history(&self, record: &ExportHistoryRecord) -> Result<i64> {
+        self.inner.insert_export_history(record).await
+    }
+
+    async fn get_export_history(&self, limit: i64) -> Result<Vec<ExportHistoryRecord>> {
+        self.inner.get_export_history(limit).await
+    }
+
+    async fn get_export_history_entry(&self, id: i64) -> Result<ExportHistoryRecord> {
+        self.inner.get_export_history_entry(id).await
+    }
+
+    async fn insert_project_rule(&self, rule: &ProjectRuleRecord) -> Result<i64> {
+        self.inner.insert_project_rule(rule).await
+    }
+
+    async fn get_project_rules(&self) -> Result<Vec<ProjectRuleRecord>> {
+        self.inner.get_project_rules().await
+    }
+
+    async fn delete_project_rule(&self, id: i64) -> Result<()> {
+        self.inner.delete_project_rule(id).await
+    }
+
+    async fn insert_custom_category(&self, category: &CustomCategoryRecord) -> Result<i64> {
+        self.inner.insert_custom_category(category).await
+    }
+
+    async fn get_custom_categories(&self) -> Result<Vec<CustomCategoryRecord>> {
+        self.inner.get_custom_categories().await
+    }
+
+    async fn delete_custom_category(&self, id: i64) -> Result<()> {
+        self.inner.delete_custom_category(id).await
+    }
+
+    async fn insert_classification_rule(&self, rule: &ClassificationRuleRecord) -> Result<i64> {
+        self.inner.insert_classification_rule(rule).await
+    }
+
+    async fn get_classification_rules(&self) -> Result<Vec<ClassificationRuleRecord>> {
+        self.inner.get_classification_rules().await
+    }
+
+    async fn delete_classification_rule(&self, id: i64) -> Result<()> {
+        self.inner.delete_classification_rule(id).await
+    }
+
+    async fn save_notion_sync(&self, record: &NotionSyncRecord) -> Result<()> {
+        self.inner.save_notion_sync(record).await
+    }
+
+    async fn get_notion_sync(
+        &self,
+        kind: &str,
+        local_key: &str,
+    ) -> Result<Option<NotionSyncRecord>> {
+        self.inner.get_notion_sync(kind, local_key).await
+    }
+
+    async fn get_notion_syncs_by_date(
+        &self,
+        kind: &str,
+        date: &str,
+    ) -> Result<Vec<NotionSyncRecord>> {
+        self.inner.get_notion_syncs_by_date(kind, date).await
+    }
+
+    async fn delete_notion_sync(&self, kind: &str, local_key: &str) -> Result<()> {
+        self.inner.delete_notion_sync(kind, local_key).await
+    }
+
+    async fn replace_calendar_events(
+        &self,
+        date: &str,
+        events: &[CalendarEventRecord],
+    ) -> Result<()> {
+        self.inner.replace_calendar_events(date, events).await
+    }
+
+    async fn get_calendar_events_by_date(&self, date: &str) -> Result<Vec<CalendarEventRecord>> {
+        self.inner.get_calendar_events_by_date(date).await
+    }
+
+    async fn replace_issue_links(&self, session_id: i64, links: &[IssueLinkRecord]) -> Result<()> {
+        self.inner.replace_issue_links(session_id, links).await
+    }
+
+    async fn get_issue_links_by_session(&self, session_id: i64) -> Result<Vec<IssueLinkRecord>> {
+        self.inner.get_issue_links_by_session(session_id).await
+    }
+
+    async fn save_time_entry_sync(&self, record: &TimeEntrySyncRecord) -> Result<()> {
+        self.inner.save_time_entry_sync(record).await
+    }
+
+    async fn get_time_entry_sync(
+        &self,
+        provider: &str,
+        session_id: i64,
+    ) -> Result<Option<TimeEntrySyncRecord>> {
+        self.inner.get_time_entry_sync(provider, session_id).await
+    }
+
+    async fn save_session_note(&self, record: &SessionNoteRecord) -> Result<()> {
+        self.inner.save_session_note(record).await
+    }
+
+    async fn get_session_note(&self, session_id: i64) -> Result<Option<SessionNoteRecord>> {
+        self.inner.get_session_note(session_id).await
+    }
+
+    async fn delete_session_note(&self, session_id: i64) -> Result<()> {
+        self.inner.delete_session_note(session_id).await
+    }
+
+    async fn replace_app_usage(&self, session_id: i64, records: &[AppUsageRecord]) -> Result<()> {
+        self.inner.replace_app_usage(session_id, records).await
+    }
+
+    async fn get_app_usage_by_session(&self, session_id: i64) -> Result<Vec<AppUsageRecord>> {
+        self.inner.get_app_usage_by_session(session_id).await
+    }
+
+    async fn get_app_usage_summary(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<AppUsageSummary>> {
+        self.inner.get_app_usage_summary(start_date, end_date).await
+    }
+
+    async fn get_daily_app_usage(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DailyAppUsage>> {
+        self.inner.get_daily_app_usage(start_date, end_date).await
+    }
+
+    async fn replace_domain_usage(
+        &self,
+        session_id: i64,
+        records: &[DomainUsageRecord],
+    ) -> Result<()> {
+        self.inner.replace_domain_usage(session_id, records).await
+    }
+
+    async fn get_domain_usage_by_session(&self, session_id: i64) -> Result<Vec<DomainUsageRecord>> {
+        self.inner.get_domain_usage_by_session(session_id).await
+    }
+
+    async fn get_domain_usage_summary(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DomainUsageSummary>> {
+        self.inner
+            .get_domain_usage_summary(start_date, end_date)
+            .await
+    }
+
+    async fn insert_frame_texts(&self, texts: &[FrameText]) -> Result<()> {
+        self.inner.insert_frame_texts(texts).await
+    }
+
+    async fn get_frame_texts_by_session(&self, session_id: i64) -> Result<Vec<FrameText>> {
+        self.inner.get_frame_texts_by_session(session_id).await
+    }
+
+    async fn get_llm_cache(&self, cache_key: &str) -> Result<Option<LlmCacheEntry>> {
+        self.inner.get_llm_cache(cache_key).await
+    }
+
+    async fn save_llm_cache(&self, entry: &LlmCacheEntry) -> Result<()> {
+        self.inner.save_llm_cache(entry).await
+    }
+
+    async fn prune_llm_cache(&self, before: Option<DateTime<Utc>>) -> Result<u64> {
+        self.inner.prune_llm_cache(before).await
+    }
+
+    async fn insert_llm_parse_failure(&self, failure: &LlmParseFailure) -> Result<i64> {
+        self.inner.insert_llm_parse_failure(failure).await
+    }
+
+    async fn get_llm_parse_failures(&self, limit: i64) -> Result<Vec<LlmParseFailure>> {
+        self.inner.get_llm_parse_failures(limit).await
+    }
+
+    async fn get_timeline_card(&self, id: i64) -> Result<Option<TimelineCardRecord>> {
+        self.inner.get_timeline_card(id).await
+    }
+
+    async fn get_review_queue(
+        &self,
+        threshold: f64,
+        limit: i64,
+    ) -> Result<Vec<TimelineCardRecord>> {
+        self.inner.get_review_queue(threshold, limit).await
+    }
+
+    async fn save_card_review(&self, review: &CardReviewRecord) -> Result<()> {
+        self.inner.save_card_review(review).await
+    }
+
+    async fn get_card_reviews_by_app(&self, app: &str) -> Result<Vec<CardReviewRecord>> {
+        self.inner.get_card_reviews_by_app(app).await
+    }
+
+    async fn insert_goal(&self, goal: &GoalRecord) -> Result<i64> {
+        self.inner.insert_goal(goal).await
+    }
+
+    async fn update_goal(&self, goal: &GoalRecord) -> Result<()> {
+        self.inner.update_goal(goal).await
+    }
+
+    async fn get_goals(&self) -> Result<Vec<GoalRecord>> {
+        self.inner.get_goals().await
+    }
+
+    async fn delete_goal(&self, id: i64) -> Result<()> {
+        self.inner.delete_goal(id).await
+    }
+
+    async fn save_goal_result(&self, result: &GoalResultRecord) -> Result<()> {
+        self.inner.save_goal_result(result).await
+    }
+
+    async fn get_goal_results(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<GoalResultRecord>> {
+        self.inner.get_goal_results(start_date, end_date).await
+    }
+
+    async fn delete_goal_results_by_date(&self, date: &str) -> Result<()> {
+        self.inner.delete_goal_results_by_date(date).await
+    }
+
+    async fn insert_focus_block(&self, block: &FocusBlockRecord) -> Result<i64> {
+        self.inner.insert_focus_block(block).await
+    }
+
+    async fn update_focus_block(&self, block: &FocusBlockRecord) -> Result<()> {
+        self.inner.update_focus_block(block).await
+    }
+
+    async fn get_focus_blocks(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<FocusBlockRecord>> {
+        self.inner.get_focus_blocks(start, end).await
+    }
+
+    async fn replace_embeddings(&self, session_id: i64, records: &[EmbeddingRecord]) -> Result<()> {
+        self.inner.replace_embeddings(session_id, records).await
+    }
+
+    async fn get_embeddings_by_session(&self, session_id: i64) -> Result<Vec<EmbeddingRecord>> {
+        self.inner.get_embeddings_by_session(session_id).await
+    }
+
+    async fn get_embeddings(
+        &self,
+        model: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<EmbeddingRecord>> {
+        self.inner.get_embeddings(model, start_date, end_date).await
+    }
+
+    async fn search_sessions(
+        &self,
+        query: &str,
+        start_date: &str,
+        end_date: &str,
+        limit: i64,
+    ) -> Result<Vec<SessionSearchHit>> {
+        self.inner
+            .search_sessions(query, start_date, end_date, limit)
+            .await
+    }
+
+    async fn run_migrations(&self) -> Result<()> {
+        self.inner.run_migrations().await
     }
 
     fn db_type(&self) -> &str {
         self.inner.db_type()
     }
 
+    async fn snapshot_to(&self, path: &str) -> Result<()> {
+        self.inner.snapshot_to(path).await
+    }
+
+    async fn restore_from_dump(&self, path: &str) -> Result<()> {
+        let result = self.inner.restore_from_dump(path).await;
+        // 无论是否成功，数据都可能已改变
+        self.clear_cache().await;
+        result
+    }
+
     async fn migrate_timezone_to_local(&self) -> Result<(u64, u64, u64, u64, u64, u64)> {
         // 清空所有缓存，因为时间数据已改变
         self.clear_cache().await;
@@ -1,13 +1,18 @@
 // 存储清理模块 - 自动清理过期数据
 
-use super::Database;
+use super::{Database, Frame};
+use crate::capture::format;
+use crate::frame_crypto;
+use crate::frame_store::{self, FrameStore, LocalFrameStore};
+use crate::models::{CaptureSettings, FrameFormat, FrameStoreConfig, StorageBudgetConfig};
 use anyhow::Result;
-use chrono::{Duration as ChronoDuration, Utc};
-use std::path::PathBuf;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::time::{interval, Duration};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 /// 存储清理器
 pub struct StorageCleaner {
@@ -21,6 +26,34 @@ pub struct StorageCleaner {
     frames_dir: PathBuf,
     /// 视频文件目录
     videos_dir: PathBuf,
+    /// 旧截图转码配置
+    recompression: Arc<RwLock<FrameRecompression>>,
+    /// 已完成转码检查的截止时间（此前结束的会话不再检查，配置变更后重置）
+    recompressed_until: Arc<RwLock<Option<DateTime<Utc>>>>,
+    /// 磁盘空间预算
+    storage_budget: Arc<RwLock<StorageBudgetConfig>>,
+    /// 最近的超预算清理记录（最新在前）
+    budget_cleanups: Arc<RwLock<VecDeque<BudgetCleanupRecord>>>,
+    /// 截图存储后端
+    frame_store: Arc<RwLock<Arc<dyn FrameStore>>>,
+    /// 会话结束超过该小时数后转存截图
+    offload_after_hours: Arc<RwLock<u32>>,
+    /// 已完成转存检查的截止时间（此前结束的会话不再检查，配置变更后重置）
+    offloaded_until: Arc<RwLock<Option<DateTime<Utc>>>>,
+    /// frames 目录中的已有截图是否已全部加密（关闭截图加密后重置）
+    frames_encrypted: Arc<RwLock<bool>>,
+}
+
+/// 最多保留的超预算清理记录条数
+const MAX_BUDGET_CLEANUP_RECORDS: usize = 50;
+
+/// 旧截图转码配置（来自截屏设置）
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct FrameRecompression {
+    /// 截图保存超过该小时数后转码（0 为关闭）
+    after_hours: u32,
+    format: FrameFormat,
+    quality: u8,
 }
 
 impl StorageCleaner {
@@ -32,6 +65,14 @@ impl StorageCleaner {
             max_retention_days: 30,                   // 最大保留30天
             frames_dir,
             videos_dir,
+            recompression: Arc::new(RwLock::new(FrameRecompression::default())),
+            recompressed_until: Arc::new(RwLock::new(None)),
+            storage_budget: Arc::new(RwLock::new(StorageBudgetConfig::default())),
+            budget_cleanups: Arc::new(RwLock::new(VecDeque::new())),
+            frame_store: Arc::new(RwLock::new(Arc::new(LocalFrameStore))),
+            offload_after_hours: Arc::new(RwLock::new(0)),
+            offloaded_until: Arc::new(RwLock::new(None)),
+            frames_encrypted: Arc::new(RwLock::new(false)),
         }
     }
 
@@ -53,6 +94,39 @@ impl StorageCleaner {
         Ok(())
     }
 
+    /// 按截屏设置更新旧截图转码配置
+    pub async fn set_recompression(&self, settings: &CaptureSettings) {
+        let recompression = FrameRecompression {
+            after_hours: settings.recompress_after_hours,
+            format: settings.recompress_format,
+            quality: settings.recompress_quality,
+        };
+
+        let mut current = self.recompression.write().await;
+        if *current != recompression {
+            *current = recompression;
+            *self.recompressed_until.write().await = None;
+            info!("旧截图转码配置已更新: {:?}", recompression);
+        }
+    }
+
+    /// 更新磁盘空间预算
+    pub async fn set_storage_budget(&self, config: StorageBudgetConfig) {
+        info!("磁盘空间预算已更新: {:?}", config);
+        *self.storage_budget.write().await = config;
+    }
+
+    /// 更新截图存储后端
+    pub async fn set_frame_store(&self, config: &FrameStoreConfig) {
+        *self.frame_store.write().await = frame_store::from_config(config);
+        *self.offload_after_hours.write().await = config.offload_after_hours;
+        *self.offloaded_until.write().await = None;
+        info!(
+            "截图存储已更新: {:?}，会话结束 {} 小时后转存",
+            config.backend, config.offload_after_hours
+        );
+    }
+
     /// 获取当前保留天数
     pub async fn get_retention_days(&self) -> i64 {
         *self.retention_days.read().await
@@ -97,7 +171,27 @@ impl StorageCleaner {
         // 4. 清理孤立文件（没有数据库记录的文件）
         self.cleanup_orphaned_files().await?;
 
-        // 5. 记录清理结果
+        // 5. 启用截图加密时加密此前未加密的截图
+        if let Err(e) = self.encrypt_existing_frames().await {
+            error!("加密已有截图失败: {}", e);
+        }
+
+        // 6. 将较旧的截图转码为压缩率更高的格式
+        if let Err(e) = self.recompress_old_frames().await {
+            error!("旧截图转码失败: {}", e);
+        }
+
+        // 7. 将较旧的截图转存到配置的存储后端
+        if let Err(e) = self.offload_frames().await {
+            error!("截图转存失败: {}", e);
+        }
+
+        // 8. 超出空间预算时分级清理
+        if let Err(e) = self.enforce_storage_budget().await {
+            error!("空间预算清理失败: {}", e);
+        }
+
+        // 9. 记录清理结果
         if !failed_files.is_empty() {
             error!("清理完成，但有 {} 个文件删除失败", failed_files.len());
             for (path, err) in &failed_files {
@@ -151,11 +245,12 @@ impl StorageCleaner {
     /// 清理文件，返回失败列表
     async fn cleanup_files(&self, sessions: Vec<SessionFiles>) -> Result<Vec<(String, String)>> {
         let mut failed_files = Vec::new();
+        let store = self.frame_store.read().await.clone();
 
         for session in sessions {
-            // 删除帧文件
+            // 删除帧文件（已转存的截图从存储后端删除）
             for frame_path in session.frame_paths {
-                if let Err(e) = tokio::fs::remove_file(&frame_path).await {
+                if let Err(e) = store.delete(&frame_path).await {
                     error!("删除帧文件失败 {}: {}", frame_path, e);
                     failed_files.push((frame_path.clone(), e.to_string()));
                 }
@@ -173,6 +268,348 @@ impl StorageCleaner {
         Ok(failed_files)
     }
 
+    /// 加密 frames 目录中未加密的截图（启用加密前保存或从备份恢复的截图），全部成功后不再重复扫描
+    async fn encrypt_existing_frames(&self) -> Result<()> {
+        if !frame_crypto::is_enabled() {
+            *self.frames_encrypted.write().await = false;
+            return Ok(());
+        }
+        if *self.frames_encrypted.read().await || !self.frames_dir.exists() {
+            return Ok(());
+        }
+
+        let frames_dir = self.frames_dir.clone();
+        let (encrypted, failed) = tokio::task::spawn_blocking(move || -> Result<(usize, usize)> {
+            let (mut encrypted, mut failed) = (0usize, 0usize);
+            let mut pending = vec![frames_dir];
+            while let Some(dir) = pending.pop() {
+                for entry in std::fs::read_dir(&dir)? {
+                    let path = entry?.path();
+                    if path.is_dir() {
+                        pending.push(path);
+                        continue;
+                    }
+                    let is_frame = path
+                        .extension()
+                        .and_then(|ext| ext.to_str())
+                        .is_some_and(format::is_frame_extension);
+                    if !is_frame {
+                        continue;
+                    }
+                    match frame_crypto::encrypt_file(&path) {
+                        Ok(true) => encrypted += 1,
+                        Ok(false) => {}
+                        Err(e) => {
+                            warn!("加密截图失败 {:?}: {}", path, e);
+                            failed += 1;
+                        }
+                    }
+                }
+            }
+            Ok((encrypted, failed))
+        })
+        .await??;
+
+        if failed == 0 {
+            *self.frames_encrypted.write().await = true;
+        }
+        if encrypted > 0 || failed > 0 {
+            info!("已加密 {} 张已有截图，{} 张失败", encrypted, failed);
+        }
+        Ok(())
+    }
+
+    /// 将已结束超过设定时长的会话截图转码为目标格式，并更新数据库中的帧路径
+    async fn recompress_old_frames(&self) -> Result<()> {
+        let config = *self.recompression.read().await;
+        if config.after_hours == 0 {
+            return Ok(());
+        }
+
+        let cutoff =
+            crate::storage::local_now() - ChronoDuration::hours(i64::from(config.after_hours));
+        let checked_until = *self.recompressed_until.read().await;
+        let sessions = self.db.get_old_sessions(cutoff).await?;
+
+        let (mut converted, mut saved_bytes) = (0usize, 0u64);
+        for session in sessions {
+            let Some(session_id) = session.id else {
+                continue;
+            };
+            // 只处理已结束超过设定时长、且上次检查之后才满足条件的会话
+            if session.end_time > cutoff
+                || checked_until.is_some_and(|until| session.end_time <= until)
+            {
+                continue;
+            }
+
+            for frame in self.db.get_frames_by_session(session_id).await? {
+                let Some(frame_id) = frame.id else {
+                    continue;
+                };
+                let source = PathBuf::from(&frame.file_path);
+                let Ok(metadata) = tokio::fs::metadata(&source).await else {
+                    continue;
+                };
+
+                let result = tokio::task::spawn_blocking({
+                    let source = source.clone();
+                    move || format::recompress_frame(&source, config.format, config.quality)
+                })
+                .await?;
+                let target = match result {
+                    Ok(Some(target)) => target,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        warn!("转码截图失败 {}: {}", frame.file_path, e);
+                        continue;
+                    }
+                };
+
+                let target_path = target.to_string_lossy().replace('\\', "/");
+                if let Err(e) = self
+                    .db
+                    .update_frame_path(session_id, frame_id, &target_path)
+                    .await
+                {
+                    error!("更新帧路径失败 {}: {}", frame.file_path, e);
+                    let _ = tokio::fs::remove_file(&target).await;
+                    continue;
+                }
+
+                let target_size = tokio::fs::metadata(&target)
+                    .await
+                    .map(|m| m.len())
+                    .unwrap_or_default();
+                if let Err(e) = tokio::fs::remove_file(&source).await {
+                    warn!("删除转码前的截图失败 {}: {}", frame.file_path, e);
+                }
+                converted += 1;
+                saved_bytes += metadata.len().saturating_sub(target_size);
+            }
+        }
+
+        // 配置在转码期间未变更时记录本次检查的截止时间
+        if *self.recompression.read().await == config {
+            *self.recompressed_until.write().await = Some(cutoff);
+        }
+        if converted > 0 {
+            info!(
+                "已将 {} 张旧截图转码为 {}，节省 {:.1} MB",
+                converted,
+                format::extension(format::effective_format(config.format)),
+                saved_bytes as f64 / 1024.0 / 1024.0
+            );
+        }
+        Ok(())
+    }
+
+    /// 将已结束超过设定时长的会话截图转存到存储后端，更新帧路径后删除本机文件
+    async fn offload_frames(&self) -> Result<()> {
+        let store = self.frame_store.read().await.clone();
+        if store.kind() == "local" {
+            return Ok(());
+        }
+
+        let after_hours = *self.offload_after_hours.read().await;
+        let cutoff = crate::storage::local_now() - ChronoDuration::hours(i64::from(after_hours));
+        let checked_until = *self.offloaded_until.read().await;
+        let sessions = self.db.get_old_sessions(cutoff).await?;
+
+        let (mut moved, mut failed) = (0usize, 0usize);
+        for session in sessions {
+            let Some(session_id) = session.id else {
+                continue;
+            };
+            if session.end_time > cutoff
+                || checked_until.is_some_and(|until| session.end_time <= until)
+            {
+                continue;
+            }
+
+            let date = session.start_time.format("%Y-%m-%d").to_string();
+            for frame in self.db.get_frames_by_session(session_id).await? {
+                let Some(frame_id) = frame.id else {
+                    continue;
+                };
+                // 只转存仍在本机 frames 目录中的截图
+                let source = PathBuf::from(&frame.file_path);
+                if !self.is_local_frame(&frame) || tokio::fs::metadata(&source).await.is_err() {
+                    continue;
+                }
+
+                let target = match store.store(&source, &date).await {
+                    Ok(target) => target,
+                    Err(e) => {
+                        warn!("转存截图失败 {}: {}", frame.file_path, e);
+                        failed += 1;
+                        continue;
+                    }
+                };
+                if let Err(e) = self
+                    .db
+                    .update_frame_path(session_id, frame_id, &target)
+                    .await
+                {
+                    error!("更新帧路径失败 {}: {}", frame.file_path, e);
+                    let _ = store.delete(&target).await;
+                    failed += 1;
+                    continue;
+                }
+                if let Err(e) = tokio::fs::remove_file(&source).await {
+                    warn!("删除已转存的本机截图失败 {}: {}", frame.file_path, e);
+                }
+                moved += 1;
+            }
+        }
+
+        // 全部成功且配置未变更时记录本次检查的截止时间，否则下次清理时重试
+        if failed == 0 && Arc::ptr_eq(&store, &*self.frame_store.read().await) {
+            *self.offloaded_until.write().await = Some(cutoff);
+        }
+        if moved > 0 || failed > 0 {
+            info!(
+                "已将 {} 张截图转存到 {} 存储，{} 张失败",
+                moved,
+                store.kind(),
+                failed
+            );
+        }
+        Ok(())
+    }
+
+    /// 截图是否仍保存在本机 frames 目录
+    fn is_local_frame(&self, frame: &Frame) -> bool {
+        Path::new(&frame.file_path).starts_with(&self.frames_dir)
+    }
+
+    /// 空间超出预算时从最旧的会话开始分级清理：先按间隔抽稀截图，再删除全部截图，
+    /// 最后删除会话视频；会话总结与时间线始终保留
+    async fn enforce_storage_budget(&self) -> Result<()> {
+        let budget = self.storage_budget.read().await.clone();
+        if !budget.enabled || budget.max_gb <= 0.0 {
+            return Ok(());
+        }
+
+        let limit = (budget.max_gb * 1024.0 * 1024.0 * 1024.0) as u64;
+        let mut usage = self.get_storage_stats().await?.total_size.max(0) as u64;
+        if usage <= limit {
+            return Ok(());
+        }
+        warn!(
+            "存储占用 {:.2} GB 超出预算 {:.2} GB，开始分级清理",
+            usage as f64 / 1024.0 / 1024.0 / 1024.0,
+            budget.max_gb
+        );
+
+        let mut sessions = self
+            .db
+            .get_old_sessions(crate::storage::local_now())
+            .await?;
+        sessions.sort_by_key(|session| session.start_time);
+
+        for action in [
+            BudgetCleanupAction::ThinFrames,
+            BudgetCleanupAction::DeleteFrames,
+            BudgetCleanupAction::DeleteVideo,
+        ] {
+            for session in &sessions {
+                if usage <= limit {
+                    break;
+                }
+                let Some(session_id) = session.id else {
+                    continue;
+                };
+
+                let (files, bytes) = match action {
+                    BudgetCleanupAction::ThinFrames | BudgetCleanupAction::DeleteFrames => {
+                        let frames = self.db.get_frames_by_session(session_id).await?;
+                        let mut doomed = if action == BudgetCleanupAction::ThinFrames {
+                            frames_to_thin(&frames, budget.keep_every_minutes)
+                        } else {
+                            frames
+                        };
+                        // 已转存的截图不占用本机空间
+                        doomed.retain(|frame| self.is_local_frame(frame));
+                        self.delete_frame_files(session_id, &doomed).await?
+                    }
+                    BudgetCleanupAction::DeleteVideo => {
+                        let Some(video_path) = &session.video_path else {
+                            continue;
+                        };
+                        let bytes = tokio::fs::metadata(video_path)
+                            .await
+                            .map(|m| m.len())
+                            .unwrap_or_default();
+                        if let Err(e) = tokio::fs::remove_file(video_path).await {
+                            if e.kind() != std::io::ErrorKind::NotFound {
+                                error!("删除视频文件失败 {}: {}", video_path, e);
+                                continue;
+                            }
+                        }
+                        self.db.clear_session_video_path(session_id).await?;
+                        (1, bytes)
+                    }
+                };
+                if files == 0 {
+                    continue;
+                }
+
+                usage = usage.saturating_sub(bytes);
+                let record = BudgetCleanupRecord {
+                    time: crate::storage::local_now()
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string(),
+                    action,
+                    session_id,
+                    session_title: session.title.clone(),
+                    files,
+                    bytes,
+                };
+                info!(
+                    "空间预算清理：会话 {}（{}）{}，{} 个文件，释放 {:.1} MB",
+                    session_id,
+                    record.session_title,
+                    action.label(),
+                    files,
+                    bytes as f64 / 1024.0 / 1024.0
+                );
+                let mut records = self.budget_cleanups.write().await;
+                records.push_front(record);
+                records.truncate(MAX_BUDGET_CLEANUP_RECORDS);
+            }
+        }
+
+        if usage > limit {
+            warn!("已清理全部旧截图与视频，存储占用仍超出预算（会话总结不会被自动删除）");
+        }
+        Ok(())
+    }
+
+    /// 删除帧文件及其数据库记录，返回 (帧数, 释放字节数)
+    async fn delete_frame_files(&self, session_id: i64, frames: &[Frame]) -> Result<(usize, u64)> {
+        let mut frame_ids = Vec::new();
+        let mut bytes = 0u64;
+        for frame in frames {
+            let Some(frame_id) = frame.id else {
+                continue;
+            };
+            if let Ok(metadata) = tokio::fs::metadata(&frame.file_path).await {
+                if let Err(e) = tokio::fs::remove_file(&frame.file_path).await {
+                    error!("删除帧文件失败 {}: {}", frame.file_path, e);
+                    continue;
+                }
+                bytes += metadata.len();
+            }
+            frame_ids.push(frame_id);
+        }
+
+        if !frame_ids.is_empty() {
+            self.db.delete_frames(session_id, &frame_ids).await?;
+        }
+        Ok((frame_ids.len(), bytes))
+    }
+
     /// 清理孤立文件（数据库中没有记录的文件）
     async fn cleanup_orphaned_files(&self) -> Result<()> {
         // 清理frames目录中的孤立文件
@@ -239,6 +676,8 @@ impl StorageCleaner {
         let frames_size = self.calculate_dir_size(&self.frames_dir).await?;
         let videos_size = self.calculate_dir_size(&self.videos_dir).await?;
         let retention_days = *self.retention_days.read().await;
+        let budget = self.storage_budget.read().await.clone();
+        let budget_cleanups = self.budget_cleanups.read().await.iter().cloned().collect();
 
         Ok(StorageStats {
             session_count,
@@ -248,6 +687,10 @@ impl StorageCleaner {
             videos_size,
             total_size: db_size + frames_size + videos_size,
             retention_days,
+            storage_budget: budget
+                .enabled
+                .then_some((budget.max_gb * 1024.0 * 1024.0 * 1024.0) as i64),
+            budget_cleanups,
         })
     }
 
@@ -299,4 +742,108 @@ pub struct StorageStats {
     pub videos_size: i64,
     pub total_size: i64,
     pub retention_days: i64,
+    /// 空间预算（字节），未启用时为空
+    pub storage_budget: Option<i64>,
+    /// 最近的超预算清理记录（最新在前）
+    pub budget_cleanups: Vec<BudgetCleanupRecord>,
+}
+
+/// 超预算清理的级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetCleanupAction {
+    /// 按间隔抽稀截图
+    ThinFrames,
+    /// 删除全部截图
+    DeleteFrames,
+    /// 删除会话视频
+    DeleteVideo,
+}
+
+impl BudgetCleanupAction {
+    fn label(self) -> &'static str {
+        match self {
+            Self::ThinFrames => "抽稀截图",
+            Self::DeleteFrames => "删除截图",
+            Self::DeleteVideo => "删除视频",
+        }
+    }
+}
+
+/// 一次超预算清理记录
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BudgetCleanupRecord {
+    /// 清理时间（本地时间）
+    pub time: String,
+    pub action: BudgetCleanupAction,
+    pub session_id: i64,
+    pub session_title: String,
+    /// 删除的文件数
+    pub files: usize,
+    /// 释放的字节数
+    pub bytes: u64,
+}
+
+/// 抽稀时要删除的帧：每个显示器每 N 分钟只保留最早的一张
+fn frames_to_thin(frames: &[Frame], keep_every_minutes: u32) -> Vec<Frame> {
+    let bucket_ms = i64::from(keep_every_minutes.max(1)) * 60_000;
+    let mut sorted = frames.to_vec();
+    sorted.sort_by_key(|frame| frame.timestamp);
+
+    let mut kept = HashSet::new();
+    sorted
+        .into_iter()
+        .filter(|frame| {
+            let bucket = frame.timestamp.timestamp_millis().div_euclid(bucket_ms);
+            !kept.insert((frame.screen_id, bucket))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(id: i64, minute: u32, second: u32, screen_id: i64) -> Frame {
+        Frame {
+            id: Some(id),
+            session_id: 1,
+            timestamp: chrono::NaiveDate::from_ymd_opt(2024, 5, 12)
+                .unwrap()
+                .and_hms_opt(9, minute, second)
+                .unwrap()
+                .and_utc(),
+            file_path: format!("/frames/{}.jpg", id),
+            app_name: None,
+            window_title: None,
+            process_path: None,
+            domain: None,
+            screen_id,
+        }
+    }
+
+    #[test]
+    fn test_frames_to_thin_keeps_one_per_interval() {
+        let frames = vec![
+            frame(3, 4, 59, 0),
+            frame(1, 0, 0, 0),
+            frame(2, 0, 30, 0),
+            frame(4, 5, 0, 0),
+            frame(5, 0, 30, 1),
+            frame(6, 9, 0, 0),
+        ];
+
+        let doomed: Vec<i64> = frames_to_thin(&frames, 5)
+            .iter()
+            .filter_map(|frame| frame.id)
+            .collect();
+        assert_eq!(doomed, vec![2, 3, 6]);
+
+        // 已抽稀的会话再次抽稀时不再删除
+        let remaining: Vec<Frame> = frames
+            .into_iter()
+            .filter(|frame| !doomed.contains(&frame.id.unwrap()))
+            .collect();
+        assert!(frames_to_thin(&remaining, 5).is_empty());
+    }
 }
@@ -11,6 +11,9 @@ pub enum DatabaseConfig {
     SQLite {
         /// 数据库文件路径
         db_path: String,
+        /// 是否以 SQLCipher 加密（密钥保存在系统钥匙串，重启后转换已有数据库）
+        #[serde(default)]
+        encrypted: bool,
     },
     /// MariaDB 配置
     #[serde(rename = "mariadb")]
@@ -26,12 +29,27 @@ pub enum DatabaseConfig {
         /// 密码
         password: String,
     },
+    /// PostgreSQL 配置
+    #[serde(rename = "postgres")]
+    PostgreSQL {
+        /// 主机地址
+        host: String,
+        /// 端口
+        port: u16,
+        /// 数据库名
+        database: String,
+        /// 用户名
+        username: String,
+        /// 密码
+        password: String,
+    },
 }
 
 impl Default for DatabaseConfig {
     fn default() -> Self {
         DatabaseConfig::SQLite {
             db_path: "data/screen-analyzer.db".to_string(),
+            encrypted: false,
         }
     }
 }
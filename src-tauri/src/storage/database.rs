@@ -1,13 +1,19 @@
 // 数据库主接口 - Facade 模式统一对外接口
 
+use super::boundary;
 use super::cache::CachedRepository;
 use super::config::DatabaseConfig;
 use super::models::*;
-use super::repository::{mariadb::MariaDbRepository, sqlite::SqliteRepository, DatabaseRepository};
+use super::repository::{
+    mariadb::MariaDbRepository, postgres::PostgresRepository, sqlite::SqliteRepository,
+    DatabaseRepository,
+};
+use super::sqlcipher;
 use anyhow::{anyhow, Result};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::BTreeSet;
 use std::sync::Arc;
-use tracing::info;
+use tracing::{info, warn};
 
 /// 数据库管理器 - 对外统一接口
 pub struct Database {
@@ -21,7 +27,10 @@ impl Database {
     /// 从配置创建数据库连接
     pub async fn from_config(config: &DatabaseConfig) -> Result<Self> {
         match config {
-            DatabaseConfig::SQLite { db_path } => Self::new_sqlite(db_path).await,
+            DatabaseConfig::SQLite { db_path, encrypted } => {
+                let key = sqlcipher::prepare(db_path, *encrypted).await?;
+                Self::new_sqlite_with_key(db_path, key.as_deref()).await
+            }
             DatabaseConfig::MariaDB {
                 host,
                 port,
@@ -29,12 +38,24 @@ impl Database {
                 username,
                 password,
             } => Self::new_mariadb(host, *port, database, username, password).await,
+            DatabaseConfig::PostgreSQL {
+                host,
+                port,
+                database,
+                username,
+                password,
+            } => Self::new_postgres(host, *port, database, username, password).await,
         }
     }
 
     /// 创建 SQLite 数据库连接
     pub async fn new_sqlite(db_path: &str) -> Result<Self> {
-        let sqlite_repo = SqliteRepository::new(db_path).await?;
+        Self::new_sqlite_with_key(db_path, None).await
+    }
+
+    /// 创建 SQLite 数据库连接（有密钥时按 SQLCipher 加密数据库打开）
+    pub async fn new_sqlite_with_key(db_path: &str, key: Option<&str>) -> Result<Self> {
+        let sqlite_repo = SqliteRepository::new(db_path, key).await?;
         let cached_repo = CachedRepository::new(Arc::new(sqlite_repo));
 
         Ok(Self {
@@ -60,6 +81,24 @@ impl Database {
         })
     }
 
+    /// 创建 PostgreSQL 数据库连接
+    pub async fn new_postgres(
+        host: &str,
+        port: u16,
+        database: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<Self> {
+        let postgres_repo =
+            PostgresRepository::new(host, port, database, username, password).await?;
+        let cached_repo = CachedRepository::new(Arc::new(postgres_repo));
+
+        Ok(Self {
+            repository: Arc::new(cached_repo),
+            db_type: "postgres".to_string(),
+        })
+    }
+
     // ========== 会话操作 ==========
 
     pub async fn insert_session(&self, session: &Session) -> Result<i64> {
@@ -70,6 +109,17 @@ impl Database {
         self.repository.insert_sessions(sessions).await
     }
 
+    /// 在一个事务中插入会话及其时间线卡片，返回会话 ID
+    pub async fn insert_session_with_cards(
+        &self,
+        session: &Session,
+        cards: &[TimelineCardRecord],
+    ) -> Result<i64> {
+        self.repository
+            .insert_session_with_cards(session, cards)
+            .await
+    }
+
     pub async fn get_session(&self, session_id: i64) -> Result<Session> {
         self.repository.get_session(session_id).await
     }
@@ -99,6 +149,29 @@ impl Database {
             .await
     }
 
+    pub async fn update_session_times(
+        &self,
+        session_id: i64,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<()> {
+        self.repository
+            .update_session_times(session_id, start_time, end_time)
+            .await
+    }
+
+    /// 在一个事务中更新会话内容与起止时间，并替换其时间线卡片
+    pub async fn replace_session_content(
+        &self,
+        session_id: i64,
+        session: &Session,
+        cards: &[TimelineCardRecord],
+    ) -> Result<()> {
+        self.repository
+            .replace_session_content(session_id, session, cards)
+            .await
+    }
+
     pub async fn update_session_tags(&self, session_id: i64, tags: &str) -> Result<()> {
         self.repository.update_session_tags(session_id, tags).await
     }
@@ -109,12 +182,208 @@ impl Database {
             .await
     }
 
+    pub async fn clear_session_video_path(&self, session_id: i64) -> Result<()> {
+        self.repository.clear_session_video_path(session_id).await
+    }
+
     pub async fn update_device_info_for_all_sessions(&self) -> Result<u64> {
         self.repository.update_device_info_for_all_sessions().await
     }
 
     pub async fn delete_session(&self, session_id: i64) -> Result<()> {
-        self.repository.delete_session(session_id).await
+        let date = self
+            .repository
+            .get_session(session_id)
+            .await
+            .ok()
+            .map(|session| session.start_time.date_naive());
+        self.repository.delete_session(session_id).await?;
+        self.invalidate_focus_metrics(date).await;
+        Ok(())
+    }
+
+    /// 在 `at` 处拆分会话，返回新会话 ID。之后的帧、OCR 文字与时间线卡片移入新会话
+    /// （跨越拆分点的卡片一分为二），应用与域名使用时长按时长比例拆分；
+    /// 视频、LLM 调用等分析记录保留在原会话
+    pub async fn split_session(&self, session_id: i64, at: DateTime<Utc>) -> Result<i64> {
+        let session = self.repository.get_session(session_id).await?;
+        if at <= session.start_time || at >= session.end_time {
+            return Err(anyhow!("拆分时间不在会话时间范围内"));
+        }
+
+        let new_session_id = self
+            .repository
+            .insert_session(&Session {
+                id: None,
+                start_time: at,
+                end_time: session.end_time,
+                title: session.title.clone(),
+                summary: session.summary.clone(),
+                video_path: None,
+                tags: session.tags.clone(),
+                created_at: Some(local_now()),
+                device_name: session.device_name.clone(),
+                device_type: session.device_type.clone(),
+            })
+            .await?;
+        self.repository
+            .update_session_times(session_id, session.start_time, at)
+            .await?;
+        if let Some(note) = self.repository.get_session_note(session_id).await? {
+            self.repository
+                .save_session_note(&SessionNoteRecord {
+                    session_id: new_session_id,
+                    ..note
+                })
+                .await?;
+        }
+        let moved = self
+            .repository
+            .move_frames(session_id, new_session_id, Some(at))
+            .await?;
+
+        let cards = self
+            .repository
+            .get_timeline_cards_by_session(session_id)
+            .await?;
+        let (before, after) = boundary::split_cards(&cards, at, new_session_id);
+        self.repository
+            .delete_timeline_cards_by_session(session_id)
+            .await?;
+        self.repository.insert_timeline_cards(&before).await?;
+        self.repository.insert_timeline_cards(&after).await?;
+
+        let share = boundary::time_share(session.start_time, at, session.end_time);
+        let apps = self.repository.get_app_usage_by_session(session_id).await?;
+        let (before, after) = boundary::split_app_usage(&apps, share, new_session_id);
+        self.repository
+            .replace_app_usage(session_id, &before)
+            .await?;
+        self.repository
+            .replace_app_usage(new_session_id, &after)
+            .await?;
+        let domains = self
+            .repository
+            .get_domain_usage_by_session(session_id)
+            .await?;
+        let (before, after) = boundary::split_domain_usage(&domains, share, new_session_id);
+        self.repository
+            .replace_domain_usage(session_id, &before)
+            .await?;
+        self.repository
+            .replace_domain_usage(new_session_id, &after)
+            .await?;
+
+        let mut dates = card_dates(&cards);
+        dates.extend([session.start_time.date_naive(), at.date_naive()]);
+        self.invalidate_focus_metrics(dates).await;
+        info!(
+            "会话 {} 已在 {} 拆分，新会话 {}（移动 {} 帧）",
+            session_id, at, new_session_id, moved
+        );
+        Ok(new_session_id)
+    }
+
+    /// 合并两个相邻会话（中间没有其他会话），保留较早的会话并返回其 ID。
+    /// 较晚会话的帧、OCR 文字与时间线卡片移入保留的会话，使用时长相加，总结与标签合并；
+    /// 保留的会话没有视频时沿用较晚会话的视频，较晚会话的其他分析记录随会话删除
+    pub async fn merge_sessions(&self, first_id: i64, second_id: i64) -> Result<i64> {
+        if first_id == second_id {
+            return Err(anyhow!("不能合并同一个会话"));
+        }
+        let mut first = self.repository.get_session(first_id).await?;
+        let mut second = self.repository.get_session(second_id).await?;
+        if second.start_time < first.start_time {
+            std::mem::swap(&mut first, &mut second);
+        }
+        let (keep_id, drop_id) = (
+            first.id.ok_or_else(|| anyhow!("会话缺少 ID"))?,
+            second.id.ok_or_else(|| anyhow!("会话缺少 ID"))?,
+        );
+
+        // 两个会话之间不能有其他会话
+        let mut dates = BTreeSet::from([
+            first.start_time.date_naive(),
+            second.start_time.date_naive(),
+        ]);
+        for date in &dates {
+            let sessions = self
+                .repository
+                .get_sessions_by_date(&date.format("%Y-%m-%d").to_string())
+                .await?;
+            if sessions.iter().any(|session| {
+                session.id != Some(keep_id)
+                    && session.id != Some(drop_id)
+                    && session.start_time > first.start_time
+                    && session.start_time < second.start_time
+            }) {
+                return Err(anyhow!("只能合并相邻的会话"));
+            }
+        }
+
+        let moved = self.repository.move_frames(drop_id, keep_id, None).await?;
+
+        let cards = self
+            .repository
+            .get_timeline_cards_by_session(drop_id)
+            .await?;
+        let moved_cards: Vec<TimelineCardRecord> = cards
+            .iter()
+            .map(|card| TimelineCardRecord {
+                id: None,
+                session_id: keep_id,
+                ..card.clone()
+            })
+            .collect();
+        self.repository
+            .delete_timeline_cards_by_session(drop_id)
+            .await?;
+        self.repository.insert_timeline_cards(&moved_cards).await?;
+
+        let mut apps = self.repository.get_app_usage_by_session(keep_id).await?;
+        apps.extend(self.repository.get_app_usage_by_session(drop_id).await?);
+        self.repository
+            .replace_app_usage(keep_id, &boundary::merge_app_usage(keep_id, apps))
+            .await?;
+        let mut domains = self.repository.get_domain_usage_by_session(keep_id).await?;
+        domains.extend(self.repository.get_domain_usage_by_session(drop_id).await?);
+        self.repository
+            .replace_domain_usage(keep_id, &boundary::merge_domain_usage(keep_id, domains))
+            .await?;
+
+        self.repository
+            .update_session_times(
+                keep_id,
+                first.start_time,
+                first.end_time.max(second.end_time),
+            )
+            .await?;
+        self.repository
+            .update_session(
+                keep_id,
+                &first.title,
+                &boundary::merge_summaries(&first.summary, &second.summary),
+                first.video_path.as_deref().or(second.video_path.as_deref()),
+                &boundary::merge_tags(&first.tags, &second.tags),
+            )
+            .await?;
+        let note = boundary::merge_notes(
+            keep_id,
+            self.repository.get_session_note(keep_id).await?,
+            self.repository.get_session_note(drop_id).await?,
+        );
+        if let Some(note) = note {
+            self.repository.save_session_note(&note).await?;
+        }
+        self.repository.delete_session(drop_id).await?;
+
+        dates.extend(card_dates(&cards));
+        self.invalidate_focus_metrics(dates).await;
+        info!(
+            "会话 {} 已合并到会话 {}（移动 {} 帧）",
+            drop_id, keep_id, moved
+        );
+        Ok(keep_id)
     }
 
     pub async fn get_old_sessions(&self, cutoff_date: DateTime<Utc>) -> Result<Vec<Session>> {
@@ -143,12 +412,42 @@ impl Database {
         self.repository.delete_frames_by_session(session_id).await
     }
 
+    pub async fn delete_frames(&self, session_id: i64, frame_ids: &[i64]) -> Result<()> {
+        self.repository.delete_frames(session_id, frame_ids).await
+    }
+
+    pub async fn update_frame_path(
+        &self,
+        session_id: i64,
+        frame_id: i64,
+        file_path: &str,
+    ) -> Result<()> {
+        self.repository
+            .update_frame_path(session_id, frame_id, file_path)
+            .await
+    }
+
     // ========== 活动统计 ==========
 
     pub async fn get_activities(&self, start_date: &str, end_date: &str) -> Result<Vec<Activity>> {
         self.repository.get_activities(start_date, end_date).await
     }
 
+    pub async fn get_device_activities(
+        &self,
+        start_date: &str,
+        end_date: &str,
+        device_name: &str,
+    ) -> Result<Vec<Activity>> {
+        self.repository
+            .get_device_activities(start_date, end_date, device_name)
+            .await
+    }
+
+    pub async fn get_device_names(&self) -> Result<Vec<String>> {
+        self.repository.get_device_names().await
+    }
+
     // ========== LLM 调用记录 ==========
 
     pub async fn insert_llm_call(&self, record: &LLMCallRecord) -> Result<i64> {
@@ -197,11 +496,16 @@ impl Database {
     // ========== 时间线卡片 ==========
 
     pub async fn insert_timeline_card(&self, card: &TimelineCardRecord) -> Result<i64> {
-        self.repository.insert_timeline_card(card).await
+        let id = self.repository.insert_timeline_card(card).await?;
+        self.invalidate_focus_metrics(card_dates(std::slice::from_ref(card)))
+            .await;
+        Ok(id)
     }
 
     pub async fn insert_timeline_cards(&self, cards: &[TimelineCardRecord]) -> Result<()> {
-        self.repository.insert_timeline_cards(cards).await
+        self.repository.insert_timeline_cards(cards).await?;
+        self.invalidate_focus_metrics(card_dates(cards)).await;
+        Ok(())
     }
 
     pub async fn get_timeline_cards_by_session(
@@ -218,9 +522,37 @@ impl Database {
     }
 
     pub async fn delete_timeline_cards_by_session(&self, session_id: i64) -> Result<()> {
+        let cards = self
+            .repository
+            .get_timeline_cards_by_session(session_id)
+            .await
+            .unwrap_or_default();
         self.repository
             .delete_timeline_cards_by_session(session_id)
-            .await
+            .await?;
+        self.invalidate_focus_metrics(card_dates(&cards)).await;
+        Ok(())
+    }
+
+    pub async fn update_timeline_card_title(&self, id: i64, title: &str) -> Result<()> {
+        self.repository.update_timeline_card_title(id, title).await
+    }
+
+    /// 按卡片当前的类别与子类别批量更新（重新分类后调用），涉及日期的专注度指标随之失效
+    pub async fn update_timeline_card_categories(
+        &self,
+        cards: &[TimelineCardRecord],
+    ) -> Result<()> {
+        for card in cards {
+            let Some(id) = card.id else {
+                continue;
+            };
+            self.repository
+                .update_timeline_card_category(id, &card.category, &card.subcategory)
+                .await?;
+        }
+        self.invalidate_focus_metrics(card_dates(cards)).await;
+        Ok(())
     }
 
     // ========== 统计信息 ==========
@@ -235,14 +567,25 @@ impl Database {
 
     // ========== 数据库元数据 ==========
 
-    pub async fn initialize_tables(&self) -> Result<()> {
-        self.repository.initialize_tables().await
+    pub async fn run_migrations(&self) -> Result<()> {
+        self.repository.run_migrations().await
     }
 
     pub fn db_type(&self) -> &str {
         &self.db_type
     }
 
+    /// 将数据库快照写入指定文件（SQLite 为数据库文件，MariaDB / PostgreSQL 为 SQL 转储）
+    pub async fn snapshot_to(&self, path: &str) -> Result<()> {
+        self.repository.snapshot_to(path).await
+    }
+
+    /// 从 SQL 转储恢复（MariaDB / PostgreSQL），随后迁移旧版本转储的表结构
+    pub async fn restore_from_dump(&self, path: &str) -> Result<()> {
+        self.repository.restore_from_dump(path).await?;
+        self.repository.run_migrations().await
+    }
+
     pub fn is_sqlite(&self) -> bool {
         self.db_type == "sqlite"
     }
@@ -251,6 +594,10 @@ impl Database {
         self.db_type == "mariadb"
     }
 
+    pub fn is_postgres(&self) -> bool {
+        self.db_type == "postgres"
+    }
+
     // ========== 缓存管理 ==========
 
     pub async fn invalidate_session(&self, session_id: i64) {
@@ -273,19 +620,20 @@ impl Database {
     /// 从 SQLite 同步数据到当前数据库
     ///
     /// 此方法会清空当前数据库所有数据，然后从指定的 SQLite 数据库同步所有数据
-    /// 仅在 MariaDB 模式下可用
+    /// 仅在 MariaDB / PostgreSQL 模式下可用
     pub async fn sync_from_sqlite_to_mariadb(&self, sqlite_db_path: &str) -> Result<()> {
-        if !self.is_mariadb() {
-            return Err(anyhow!("只能在 MariaDB 模式下调用此方法"));
+        if self.is_sqlite() {
+            return Err(anyhow!("只能在 MariaDB 或 PostgreSQL 模式下调用此方法"));
         }
 
-        info!("开始从 SQLite 同步数据到 MariaDB");
+        info!("开始从 SQLite 同步数据到 {}", self.db_type);
 
-        // 创建 SQLite 临时连接
-        let sqlite_db = Self::new_sqlite(sqlite_db_path).await?;
+        // 创建 SQLite 临时连接（本地数据库可能已用 SQLCipher 加密）
+        let key = sqlcipher::existing_key(std::path::Path::new(sqlite_db_path))?;
+        let sqlite_db = Self::new_sqlite_with_key(sqlite_db_path, key.as_deref()).await?;
 
         // 清空当前数据库的所有数据（注意外键约束顺序）
-        info!("清空 MariaDB 数据...");
+        info!("清空 {} 数据...", self.db_type);
         self.delete_timeline_cards_by_session(0).await.ok(); // 清空所有
         self.delete_video_segments_by_session(0).await.ok();
         self.delete_llm_calls_by_session(0).await.ok();
@@ -297,7 +645,7 @@ impl Database {
                 self.delete_session(id).await.ok();
             }
         }
-        info!("MariaDB 数据已清空");
+        info!("{} 数据已清空", self.db_type);
 
         // 同步 sessions
         info!("同步 sessions...");
@@ -389,6 +737,408 @@ impl Database {
         self.repository.delete_day_summary(date).await
     }
 
+    // ========== 每日专注度指标缓存 ==========
+
+    pub async fn save_daily_focus_metrics(
+        &self,
+        date: &str,
+        metrics: &DailyFocusMetricsRecord,
+    ) -> Result<()> {
+        self.repository
+            .save_daily_focus_metrics(date, metrics)
+            .await
+    }
+
+    pub async fn get_daily_focus_metrics(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DailyFocusMetricsRecord>> {
+        self.repository
+            .get_daily_focus_metrics(start_date, end_date)
+            .await
+    }
+
+    /// 时间线卡片或会话变化后，使涉及日期的专注度指标缓存与目标评估结果失效（失败仅记录警告）
+    async fn invalidate_focus_metrics(&self, dates: impl IntoIterator<Item = NaiveDate>) {
+        for date in dates {
+            let date = date.format("%Y-%m-%d").to_string();
+            if let Err(e) = self.repository.delete_daily_focus_metrics(&date).await {
+                warn!("清除专注度指标缓存失败 ({}): {}", date, e);
+            }
+            if let Err(e) = self.repository.delete_goal_results_by_date(&date).await {
+                warn!("清除目标评估结果失败 ({}): {}", date, e);
+            }
+        }
+    }
+
+    // ========== 导出历史 ==========
+
+    pub async fn insert_export_history(&self, record: &ExportHistoryRecord) -> Result<i64> {
+        self.repository.insert_export_history(record).await
+    }
+
+    pub async fn get_export_history(&self, limit: i64) -> Result<Vec<ExportHistoryRecord>> {
+        self.repository.get_export_history(limit).await
+    }
+
+    pub async fn get_export_history_entry(&self, id: i64) -> Result<ExportHistoryRecord> {
+        self.repository.get_export_history_entry(id).await
+    }
+
+    // ========== 项目规则 ==========
+
+    pub async fn insert_project_rule(&self, rule: &ProjectRuleRecord) -> Result<i64> {
+        self.repository.insert_project_rule(rule).await
+    }
+
+    pub async fn get_project_rules(&self) -> Result<Vec<ProjectRuleRecord>> {
+        self.repository.get_project_rules().await
+    }
+
+    pub async fn delete_project_rule(&self, id: i64) -> Result<()> {
+        self.repository.delete_project_rule(id).await
+    }
+
+    // ========== 自定义类别 ==========
+
+    pub async fn insert_custom_category(&self, category: &CustomCategoryRecord) -> Result<i64> {
+        self.repository.insert_custom_category(category).await
+    }
+
+    pub async fn get_custom_categories(&self) -> Result<Vec<CustomCategoryRecord>> {
+        self.repository.get_custom_categories().await
+    }
+
+    pub async fn delete_custom_category(&self, id: i64) -> Result<()> {
+        self.repository.delete_custom_category(id).await
+    }
+
+    // ========== 预分类规则 ==========
+
+    pub async fn insert_classification_rule(&self, rule: &ClassificationRuleRecord) -> Result<i64> {
+        self.repository.insert_classification_rule(rule).await
+    }
+
+    pub async fn get_classification_rules(&self) -> Result<Vec<ClassificationRuleRecord>> {
+        self.repository.get_classification_rules().await
+    }
+
+    pub async fn delete_classification_rule(&self, id: i64) -> Result<()> {
+        self.repository.delete_classification_rule(id).await
+    }
+
+    // ========== Notion 同步记录 ==========
+
+    pub async fn save_notion_sync(&self, record: &NotionSyncRecord) -> Result<()> {
+        self.repository.save_notion_sync(record).await
+    }
+
+    pub async fn get_notion_sync(
+        &self,
+        kind: &str,
+        local_key: &str,
+    ) -> Result<Option<NotionSyncRecord>> {
+        self.repository.get_notion_sync(kind, local_key).await
+    }
+
+    pub async fn get_notion_syncs_by_date(
+        &self,
+        kind: &str,
+        date: &str,
+    ) -> Result<Vec<NotionSyncRecord>> {
+        self.repository.get_notion_syncs_by_date(kind, date).await
+    }
+
+    pub async fn delete_notion_sync(&self, kind: &str, local_key: &str) -> Result<()> {
+        self.repository.delete_notion_sync(kind, local_key).await
+    }
+
+    // ========== 日历事件 ==========
+
+    pub async fn replace_calendar_events(
+        &self,
+        date: &str,
+        events: &[CalendarEventRecord],
+    ) -> Result<()> {
+        self.repository.replace_calendar_events(date, events).await
+    }
+
+    pub async fn get_calendar_events_by_date(
+        &self,
+        date: &str,
+    ) -> Result<Vec<CalendarEventRecord>> {
+        self.repository.get_calendar_events_by_date(date).await
+    }
+
+    // ========== 工单关联 ==========
+
+    pub async fn replace_issue_links(
+        &self,
+        session_id: i64,
+        links: &[IssueLinkRecord],
+    ) -> Result<()> {
+        self.repository.replace_issue_links(session_id, links).await
+    }
+
+    pub async fn get_issue_links_by_session(
+        &self,
+        session_id: i64,
+    ) -> Result<Vec<IssueLinkRecord>> {
+        self.repository.get_issue_links_by_session(session_id).await
+    }
+
+    // ========== 工时同步记录 ==========
+
+    pub async fn save_time_entry_sync(&self, record: &TimeEntrySyncRecord) -> Result<()> {
+        self.repository.save_time_entry_sync(record).await
+    }
+
+    pub async fn get_time_entry_sync(
+        &self,
+        provider: &str,
+        session_id: i64,
+    ) -> Result<Option<TimeEntrySyncRecord>> {
+        self.repository
+            .get_time_entry_sync(provider, session_id)
+            .await
+    }
+
+    // ========== 会话备注 ==========
+
+    /// 保存会话备注（备注与标签都为空时删除），返回整理后的备注
+    pub async fn save_session_note(
+        &self,
+        session_id: i64,
+        note: &str,
+        tags: &[String],
+    ) -> Result<Option<SessionNoteRecord>> {
+        match SessionNoteRecord::new(session_id, note, tags) {
+            Some(record) => {
+                self.repository.save_session_note(&record).await?;
+                Ok(Some(record))
+            }
+            None => {
+                self.repository.delete_session_note(session_id).await?;
+                Ok(None)
+            }
+        }
+    }
+
+    pub async fn get_session_note(&self, session_id: i64) -> Result<Option<SessionNoteRecord>> {
+        self.repository.get_session_note(session_id).await
+    }
+
+    // ========== 前台应用使用时长 ==========
+
+    pub async fn replace_app_usage(
+        &self,
+        session_id: i64,
+        records: &[AppUsageRecord],
+    ) -> Result<()> {
+        self.repository.replace_app_usage(session_id, records).await
+    }
+
+    pub async fn get_app_usage_by_session(&self, session_id: i64) -> Result<Vec<AppUsageRecord>> {
+        self.repository.get_app_usage_by_session(session_id).await
+    }
+
+    pub async fn get_app_usage_summary(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<AppUsageSummary>> {
+        self.repository
+            .get_app_usage_summary(start_date, end_date)
+            .await
+    }
+
+    pub async fn get_daily_app_usage(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DailyAppUsage>> {
+        self.repository
+            .get_daily_app_usage(start_date, end_date)
+            .await
+    }
+
+    // ========== 网站域名访问时长 ==========
+
+    pub async fn replace_domain_usage(
+        &self,
+        session_id: i64,
+        records: &[DomainUsageRecord],
+    ) -> Result<()> {
+        self.repository
+            .replace_domain_usage(session_id, records)
+            .await
+    }
+
+    pub async fn get_domain_usage_by_session(
+        &self,
+        session_id: i64,
+    ) -> Result<Vec<DomainUsageRecord>> {
+        self.repository
+            .get_domain_usage_by_session(session_id)
+            .await
+    }
+
+    pub async fn get_domain_usage_summary(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DomainUsageSummary>> {
+        self.repository
+            .get_domain_usage_summary(start_date, end_date)
+            .await
+    }
+
+    // ========== 帧 OCR 文字 ==========
+
+    pub async fn insert_frame_texts(&self, texts: &[FrameText]) -> Result<()> {
+        self.repository.insert_frame_texts(texts).await
+    }
+
+    pub async fn get_frame_texts_by_session(&self, session_id: i64) -> Result<Vec<FrameText>> {
+        self.repository.get_frame_texts_by_session(session_id).await
+    }
+
+    // ========== LLM 响应缓存 ==========
+
+    pub async fn get_llm_cache(&self, cache_key: &str) -> Result<Option<LlmCacheEntry>> {
+        self.repository.get_llm_cache(cache_key).await
+    }
+
+    pub async fn save_llm_cache(&self, entry: &LlmCacheEntry) -> Result<()> {
+        self.repository.save_llm_cache(entry).await
+    }
+
+    pub async fn prune_llm_cache(&self, before: Option<DateTime<Utc>>) -> Result<u64> {
+        self.repository.prune_llm_cache(before).await
+    }
+
+    // ========== LLM 解析失败记录 ==========
+
+    pub async fn insert_llm_parse_failure(&self, failure: &LlmParseFailure) -> Result<i64> {
+        self.repository.insert_llm_parse_failure(failure).await
+    }
+
+    pub async fn get_llm_parse_failures(&self, limit: i64) -> Result<Vec<LlmParseFailure>> {
+        self.repository.get_llm_parse_failures(limit).await
+    }
+
+    // ========== 时间线卡片复核 ==========
+
+    pub async fn get_timeline_card(&self, id: i64) -> Result<Option<TimelineCardRecord>> {
+        self.repository.get_timeline_card(id).await
+    }
+
+    pub async fn get_review_queue(
+        &self,
+        threshold: f64,
+        limit: i64,
+    ) -> Result<Vec<TimelineCardRecord>> {
+        self.repository.get_review_queue(threshold, limit).await
+    }
+
+    pub async fn save_card_review(&self, review: &CardReviewRecord) -> Result<()> {
+        self.repository.save_card_review(review).await
+    }
+
+    pub async fn get_card_reviews_by_app(&self, app: &str) -> Result<Vec<CardReviewRecord>> {
+        self.repository.get_card_reviews_by_app(app).await
+    }
+
+    // ========== 目标 ==========
+
+    pub async fn insert_goal(&self, goal: &GoalRecord) -> Result<i64> {
+        self.repository.insert_goal(goal).await
+    }
+
+    pub async fn update_goal(&self, goal: &GoalRecord) -> Result<()> {
+        self.repository.update_goal(goal).await
+    }
+
+    pub async fn get_goals(&self) -> Result<Vec<GoalRecord>> {
+        self.repository.get_goals().await
+    }
+
+    pub async fn delete_goal(&self, id: i64) -> Result<()> {
+        self.repository.delete_goal(id).await
+    }
+
+    pub async fn save_goal_result(&self, result: &GoalResultRecord) -> Result<()> {
+        self.repository.save_goal_result(result).await
+    }
+
+    pub async fn get_goal_results(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<GoalResultRecord>> {
+        self.repository.get_goal_results(start_date, end_date).await
+    }
+
+    // ========== 专注时段 ==========
+
+    pub async fn insert_focus_block(&self, block: &FocusBlockRecord) -> Result<i64> {
+        self.repository.insert_focus_block(block).await
+    }
+
+    pub async fn update_focus_block(&self, block: &FocusBlockRecord) -> Result<()> {
+        self.repository.update_focus_block(block).await
+    }
+
+    pub async fn get_focus_blocks(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<FocusBlockRecord>> {
+        self.repository.get_focus_blocks(start, end).await
+    }
+
+    // ========== 语义向量 ==========
+
+    pub async fn replace_embeddings(
+        &self,
+        session_id: i64,
+        records: &[EmbeddingRecord],
+    ) -> Result<()> {
+        self.repository
+            .replace_embeddings(session_id, records)
+            .await
+    }
+
+    pub async fn get_embeddings_by_session(&self, session_id: i64) -> Result<Vec<EmbeddingRecord>> {
+        self.repository.get_embeddings_by_session(session_id).await
+    }
+
+    pub async fn get_embeddings(
+        &self,
+        model: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<EmbeddingRecord>> {
+        self.repository
+            .get_embeddings(model, start_date, end_date)
+            .await
+    }
+
+    // ========== 全文搜索 ==========
+
+    pub async fn search_sessions(
+        &self,
+        query: &str,
+        start_date: &str,
+        end_date: &str,
+        limit: i64,
+    ) -> Result<Vec<SessionSearchHit>> {
+        self.repository
+            .search_sessions(query, start_date, end_date, limit)
+            .await
+    }
+
     // ========== 数据库维护操作 ==========
 
     /// 迁移数据库时区：将 UTC 时间转换为本地时间
@@ -396,3 +1146,12 @@ impl Database {
         self.repository.migrate_timezone_to_local().await
     }
 }
+
+/// 时间线卡片涉及的日期（按卡片自身时区的本地日期，与会话存储日期一致）
+fn card_dates(cards: &[TimelineCardRecord]) -> BTreeSet<NaiveDate> {
+    cards
+        .iter()
+        .filter_map(|card| DateTime::parse_from_rfc3339(&card.start_time).ok())
+        .map(|start| start.date_naive())
+        .collect()
+}
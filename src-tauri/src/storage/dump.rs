@@ -0,0 +1,121 @@
+// SQL 转储辅助 - MariaDB / PostgreSQL 数据库备份的文本格式
+//
+// 每条语句单独一行（字符串中的换行写为转义序列），末行为此前全部内容的 SHA-256，
+// 恢复前据此校验文件完整、未被截断
+
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+
+/// 转储首行（恢复时据此识别格式）
+pub const DUMP_HEADER: &str = "-- screen-analyzer MariaDB dump v1";
+
+/// PostgreSQL 转储首行
+pub const POSTGRES_DUMP_HEADER: &str = "-- screen-analyzer PostgreSQL dump v1";
+
+/// 转储末行前缀，后接此前全部内容的 SHA-256
+const CHECKSUM_PREFIX: &str = "-- sha256: ";
+
+/// 每条 INSERT 语句包含的行数
+pub const INSERT_BATCH_ROWS: usize = 100;
+
+/// 单列转为 SQL 字面量的查询表达式（二进制列按十六进制，其他列经 QUOTE 并转义换行）
+pub fn column_literal(column: &str, data_type: &str) -> String {
+    let column = format!("`{}`", column.replace('`', "``"));
+    let data_type = data_type.to_ascii_lowercase();
+    if data_type.contains("blob") || data_type.contains("binary") {
+        format!(
+            "IF({0} IS NULL, 'NULL', CONCAT('X''', HEX({0}), ''''))",
+            column
+        )
+    } else {
+        format!(
+            "REPLACE(REPLACE(QUOTE({}), CHAR(10 USING utf8mb4), '\\\\n'), CHAR(13 USING utf8mb4), '\\\\r')",
+            column
+        )
+    }
+}
+
+/// PostgreSQL 单列转为 SQL 字面量的查询表达式（文本列按 UTF-8 十六进制编码，避免换行拆散语句）
+pub fn postgres_column_literal(column: &str, data_type: &str) -> String {
+    let column = format!("\"{}\"", column.replace('"', "\"\""));
+    match data_type.to_ascii_lowercase().as_str() {
+        "text" | "character varying" | "character" => format!(
+            "CASE WHEN {0} IS NULL THEN 'NULL' ELSE 'convert_from(''\\x' || encode(convert_to({0}, 'UTF8'), 'hex') || '''::bytea, ''UTF8'')' END",
+            column
+        ),
+        _ => format!("quote_nullable({})", column),
+    }
+}
+
+/// 为转储内容追加校验行
+pub fn finish_dump(mut body: String) -> String {
+    let checksum = hex::encode(Sha256::digest(body.as_bytes()));
+    body.push_str(CHECKSUM_PREFIX);
+    body.push_str(&checksum);
+    body.push('\n');
+    body
+}
+
+/// 校验转储格式（首行须为 `header`）与校验和，返回按顺序执行的语句
+pub fn verify_dump<'a>(content: &'a str, header: &str) -> Result<Vec<&'a str>> {
+    if content.lines().next() != Some(header) {
+        return Err(anyhow!(
+            "不是本应用导出的 {} 转储文件",
+            header
+                .trim_start_matches("-- screen-analyzer ")
+                .trim_end_matches(" dump v1")
+        ));
+    }
+    let checksum_at = content
+        .rfind(&format!("\n{}", CHECKSUM_PREFIX))
+        .map(|index| index + 1)
+        .ok_or_else(|| anyhow!("转储文件不完整：缺少校验行"))?;
+    let (body, checksum_line) = content.split_at(checksum_at);
+    let expected = checksum_line[CHECKSUM_PREFIX.len()..].trim();
+    if hex::encode(Sha256::digest(body.as_bytes())) != expected {
+        return Err(anyhow!("转储文件已损坏或被截断（校验和不匹配）"));
+    }
+
+    Ok(body
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with("--"))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_dump_detects_tampering() {
+        let dump = finish_dump(format!(
+            "{}\nSET FOREIGN_KEY_CHECKS=0;\nINSERT INTO `t` VALUES ('a\\nb');\nSET FOREIGN_KEY_CHECKS=1;\n",
+            DUMP_HEADER
+        ));
+        assert_eq!(
+            verify_dump(&dump, DUMP_HEADER).unwrap(),
+            vec![
+                "SET FOREIGN_KEY_CHECKS=0;",
+                "INSERT INTO `t` VALUES ('a\\nb');",
+                "SET FOREIGN_KEY_CHECKS=1;",
+            ]
+        );
+
+        // 修改内容、截断或缺少文件头都会被拒绝
+        assert!(verify_dump(&dump.replace("'a", "'x"), DUMP_HEADER).is_err());
+        assert!(verify_dump(&dump[..dump.len() / 2], DUMP_HEADER).is_err());
+        assert!(verify_dump("SET FOREIGN_KEY_CHECKS=0;\n", DUMP_HEADER).is_err());
+        // 不同数据库的转储不能互相恢复
+        assert!(verify_dump(&dump, POSTGRES_DUMP_HEADER).is_err());
+
+        assert_eq!(
+            column_literal("vector", "mediumblob"),
+            "IF(`vector` IS NULL, 'NULL', CONCAT('X''', HEX(`vector`), ''''))"
+        );
+        assert_eq!(
+            postgres_column_literal("vector", "bytea"),
+            "quote_nullable(\"vector\")"
+        );
+        assert!(postgres_column_literal("title", "text").contains("encode(convert_to(\"title\""));
+    }
+}
@@ -0,0 +1,226 @@
+// 数据库结构迁移 - 带版本号的迁移脚本
+//
+// 每个迁移在事务中执行，成功后把版本号写入 schema_version 表；启动时只执行版本号大于
+// 已记录版本的迁移。已发布的迁移不可修改，结构变更一律追加新版本
+
+use anyhow::{bail, Result};
+
+/// 记录已应用迁移的表（三种数据库通用的建表语句）
+pub const CREATE_VERSION_TABLE_SQL: &str = "CREATE TABLE IF NOT EXISTS schema_version (
+    version BIGINT PRIMARY KEY,
+    description VARCHAR(255) NOT NULL,
+    applied_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+)";
+
+/// 查询当前结构版本（尚未应用任何迁移时为 0）
+pub const CURRENT_VERSION_SQL: &str = "SELECT COALESCE(MAX(version), 0) FROM schema_version";
+
+/// 迁移步骤
+#[derive(Debug, Clone, PartialEq)]
+pub enum MigrationStep {
+    /// 直接执行的 SQL 语句
+    Sql(String),
+    /// 列不存在时才添加（兼容引入版本号之前由旧版本创建、缺少新字段的表）
+    AddColumn {
+        table: &'static str,
+        column: &'static str,
+        definition: &'static str,
+    },
+}
+
+impl MigrationStep {
+    /// 转为可直接执行的 SQL（MariaDB 与 PostgreSQL 原生支持 ADD COLUMN IF NOT EXISTS）
+    pub fn to_sql(&self) -> String {
+        match self {
+            Self::Sql(sql) => sql.clone(),
+            Self::AddColumn {
+                table,
+                column,
+                definition,
+            } => format!(
+                "ALTER TABLE {} ADD COLUMN IF NOT EXISTS {} {}",
+                table, column, definition
+            ),
+        }
+    }
+}
+
+/// 带版本号的迁移
+#[derive(Debug, Clone)]
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub steps: Vec<MigrationStep>,
+}
+
+impl Migration {
+    pub fn new(version: i64, description: &'static str) -> Self {
+        Self {
+            version,
+            description,
+            steps: Vec::new(),
+        }
+    }
+
+    /// 依次追加 SQL 语句
+    pub fn sql<I, S>(mut self, statements: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.steps.extend(
+            statements
+                .into_iter()
+                .map(|sql| MigrationStep::Sql(sql.into())),
+        );
+        self
+    }
+
+    /// 追加“列不存在时添加”步骤
+    pub fn add_column(
+        mut self,
+        table: &'static str,
+        column: &'static str,
+        definition: &'static str,
+    ) -> Self {
+        self.steps.push(MigrationStep::AddColumn {
+            table,
+            column,
+            definition,
+        });
+        self
+    }
+}
+
+/// 迁移列表中的最新版本
+pub fn latest_version(migrations: &[Migration]) -> i64 {
+    migrations.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// 返回尚未应用的迁移
+///
+/// 数据库版本高于程序已知的最新版本时报错，避免旧版程序读写新版结构
+pub fn pending(migrations: &[Migration], current: i64) -> Result<&[Migration]> {
+    if let Some(pair) = migrations
+        .windows(2)
+        .find(|pair| pair[0].version >= pair[1].version)
+    {
+        bail!(
+            "迁移版本号必须递增: {} 之后为 {}",
+            pair[0].version,
+            pair[1].version
+        );
+    }
+
+    let latest = latest_version(migrations);
+    if current > latest {
+        bail!(
+            "数据库结构版本 {} 高于当前程序支持的版本 {}，请升级应用后再使用此数据库",
+            current,
+            latest
+        );
+    }
+
+    let start = migrations.partition_point(|m| m.version <= current);
+    Ok(&migrations[start..])
+}
+
+/// 字符串转为 SQL 字面量（单引号转义）
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Database;
+
+    fn sample() -> Vec<Migration> {
+        vec![
+            Migration::new(1, "初始表结构").sql(["CREATE TABLE a (id INTEGER)"]),
+            Migration::new(2, "补充字段").add_column("a", "name", "TEXT"),
+            Migration::new(3, "索引").sql(["CREATE INDEX idx_a ON a(id)"]),
+        ]
+    }
+
+    #[test]
+    fn test_pending_migrations() {
+        let migrations = sample();
+        let versions = |current| -> Vec<i64> {
+            pending(&migrations, current)
+                .unwrap()
+                .iter()
+                .map(|m| m.version)
+                .collect()
+        };
+        assert_eq!(versions(0), vec![1, 2, 3]);
+        assert_eq!(versions(2), vec![3]);
+        assert!(versions(3).is_empty());
+
+        // 数据库来自更新的版本
+        assert!(pending(&migrations, 4).is_err());
+
+        // 版本号重复或乱序
+        let mut broken = sample();
+        broken.swap(1, 2);
+        assert!(pending(&broken, 0).is_err());
+
+        assert_eq!(
+            migrations[1].steps[0].to_sql(),
+            "ALTER TABLE a ADD COLUMN IF NOT EXISTS name TEXT"
+        );
+        assert_eq!(quote_literal("it's"), "'it''s'");
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_upgrades_legacy_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.db");
+        let url = format!("sqlite:{}?mode=rwc", path.display());
+
+        // 引入版本号之前的旧版数据库：缺少设备、前台窗口等字段，也没有全文索引
+        let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+        for sql in [
+            "CREATE TABLE sessions (id INTEGER PRIMARY KEY AUTOINCREMENT, start_time DATETIME NOT NULL, end_time DATETIME NOT NULL, title TEXT NOT NULL, summary TEXT NOT NULL, video_path TEXT, tags TEXT NOT NULL, created_at DATETIME DEFAULT CURRENT_TIMESTAMP)",
+            "CREATE TABLE frames (id INTEGER PRIMARY KEY AUTOINCREMENT, session_id INTEGER NOT NULL, timestamp DATETIME NOT NULL, file_path TEXT NOT NULL)",
+            "INSERT INTO sessions (start_time, end_time, title, summary, tags) VALUES ('2024-05-01 09:00:00', '2024-05-01 09:30:00', '旧版会话', '整理周报', '[]')",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        pool.close().await;
+
+        let db = Database::new_sqlite(path.to_str().unwrap()).await.unwrap();
+        let session = db.get_session(1).await.unwrap();
+        assert!(session.device_name.is_some());
+        let hits = db
+            .search_sessions("周报", "2024-05-01", "2024-05-01", 10)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        drop(db);
+
+        // 重复打开不会再次执行迁移
+        Database::new_sqlite(path.to_str().unwrap()).await.unwrap();
+        let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+        let applied: Vec<i64> = sqlx::query_scalar("SELECT version FROM schema_version")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(applied, vec![1]);
+        let columns: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pragma_table_info('frames') WHERE name IN ('app_name', 'domain', 'screen_id')",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(columns, 3);
+
+        // 更新版本程序写入的数据库拒绝打开
+        sqlx::query("INSERT INTO schema_version (version, description) VALUES (999, '未来版本')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+        assert!(Database::new_sqlite(path.to_str().unwrap()).await.is_err());
+    }
+}
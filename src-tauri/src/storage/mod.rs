@@ -1,12 +1,17 @@
 // 存储模块 - 统一的数据库抽象层
 
 // 子模块
+pub mod boundary;
 pub mod cache;
 pub mod cleaner;
 pub mod config;
 pub mod database;
+pub mod dump;
+pub mod migrations;
 pub mod models;
 pub mod repository;
+pub mod search;
+pub mod sqlcipher;
 
 // 重新导出主要类型
 pub use cache::CachedRepository;
@@ -18,4 +23,5 @@ pub use repository::DatabaseRepository;
 
 // 重新导出具体实现（可选，用于高级用法）
 pub use repository::mariadb::MariaDbRepository;
+pub use repository::postgres::PostgresRepository;
 pub use repository::sqlite::SqliteRepository;
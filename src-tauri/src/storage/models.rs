@@ -35,6 +35,14 @@ pub struct Frame {
     #[serde(serialize_with = "serialize_datetime_as_local")]
     pub timestamp: DateTime<Utc>,
     pub file_path: String,
+    /// 截屏时的前台应用、窗口标题与进程路径（未采集时为空）
+    pub app_name: Option<String>,
+    pub window_title: Option<String>,
+    pub process_path: Option<String>,
+    /// 截屏时浏览器当前标签页的域名（未上报时为空）
+    pub domain: Option<String>,
+    /// 显示器序号（多屏合成为一张图时为 0）
+    pub screen_id: i64,
 }
 
 /// 活动数据结构（用于日历视图）
@@ -102,6 +110,8 @@ pub struct TimelineCardRecord {
     pub detailed_summary: String,
     pub distractions: Option<String>,       // JSON格式的干扰活动
     pub app_sites: String,                  // JSON格式的应用/网站信息
+    pub confidence: Option<f64>, // LLM 对类别判断的置信度（0-1），规则/检测生成的卡片为空
+    pub heuristic: bool,         // 由本地启发式分类生成（未配置 AI 或 AI 不可用时）
     pub video_preview_path: Option<String>, // 本地视频文件路径
     #[serde(serialize_with = "serialize_datetime_as_local")]
     pub created_at: DateTime<Utc>,
@@ -128,6 +138,353 @@ pub struct DaySummaryRecord {
     pub updated_at: DateTime<Utc>, // 更新时间
 }
 
+/// 每日专注度指标缓存（按时间线卡片类别汇总的分钟数，供周报直接汇总）
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DailyFocusMetricsRecord {
+    #[serde(
+        serialize_with = "serialize_naive_date",
+        deserialize_with = "deserialize_naive_date"
+    )]
+    pub date: chrono::NaiveDate, // 日期
+    pub total_minutes: i64,
+    pub work_minutes: i64,
+    pub learning_minutes: i64,
+    pub communication_minutes: i64,
+    pub personal_minutes: i64,
+    pub idle_minutes: i64,
+    pub other_minutes: i64,
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub updated_at: DateTime<Utc>, // 更新时间
+}
+
+/// Obsidian 导出历史记录（每次导出运行一条，便于查看警告与重新运行失败的导出）
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ExportHistoryRecord {
+    pub id: Option<i64>,
+    pub kind: String,            // day, range, quarter, year, sessions, retry
+    pub target: String,          // vault, zip
+    pub request: String,         // JSON格式的导出参数（用于重新运行）
+    pub success: bool,           // 导出是否成功完成
+    pub message: String,         // 结果提示或错误信息
+    pub written_paths: String,   // JSON数组，写入的文件路径
+    pub warnings: String,        // JSON数组，导出警告
+    pub failed_sessions: String, // JSON数组，导出失败的会话ID
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// 项目归属规则（按标签/应用/标题关键词将会话归入项目）
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ProjectRuleRecord {
+    pub id: Option<i64>,
+    pub project: String,     // 项目名称
+    pub match_field: String, // tag, app, title
+    pub pattern: String,     // 关键词（忽略大小写，包含即匹配）
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// 自定义活动类别（如"客户 A"），归入内置类别以参与专注度统计
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CustomCategoryRecord {
+    pub id: Option<i64>,
+    pub name: String,          // 类别名称（不区分大小写唯一）
+    pub bucket: String, // 归入的内置类别：work, communication, learning, personal, idle, other
+    pub description: String, // 说明，提供给 LLM 判断
+    pub subcategories: String, // JSON 字符串数组
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl CustomCategoryRecord {
+    /// 子类别列表
+    pub fn subcategory_list(&self) -> Vec<String> {
+        serde_json::from_str(&self.subcategories).unwrap_or_default()
+    }
+}
+
+/// 预分类规则（按应用/窗口标题/域名的正则直接归类，命中的帧不交给 LLM）
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ClassificationRuleRecord {
+    pub id: Option<i64>,
+    pub match_field: String, // app, title, url
+    pub pattern: String,     // 正则表达式（忽略大小写）
+    pub category: String,    // 内置类别或自定义类别名称
+    pub subcategory: String, // 子类别，可为空
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Notion 同步记录（本地会话/每日总结/周报 -> Notion 页面，重新导出时更新而非重复创建）
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct NotionSyncRecord {
+    pub kind: String,      // session, day, week
+    pub local_key: String, // 会话ID / 日期 / 周标签（如 2024-W19）
+    pub date: String,      // 所属日期 YYYY-MM-DD（周报为周一）
+    pub page_id: String,   // Notion 页面 ID
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub synced_at: DateTime<Utc>,
+}
+
+/// 日历事件（从 Google 日历同步的当天会议，用于标注时间线卡片）
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CalendarEventRecord {
+    pub id: Option<i64>,
+    pub event_id: String, // 日历事件 ID
+    pub date: String,     // 所属日期 YYYY-MM-DD
+    pub title: String,
+    pub location: String,
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub start_time: DateTime<Utc>,
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub end_time: DateTime<Utc>,
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub synced_at: DateTime<Utc>,
+}
+
+/// 会话关联的工单（从会话文本中识别并经 GitHub / Jira API 解析）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct IssueLinkRecord {
+    pub session_id: i64,
+    pub provider: String,  // github, jira
+    pub issue_key: String, // owner/repo#123 或 PROJ-123
+    pub title: String,     // 工单标题（未能解析时为空）
+    pub state: String,     // open / closed / merged，Jira 为状态名
+    pub url: String,
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub resolved_at: DateTime<Utc>,
+}
+
+/// 工时同步记录（会话 -> Toggl / Clockify 工时条目，重新导出时更新而非重复创建）
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TimeEntrySyncRecord {
+    pub provider: String, // toggl, clockify
+    pub session_id: i64,
+    pub entry_id: String, // 工时条目 ID
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub synced_at: DateTime<Utc>,
+}
+
+/// 会话备注（用户手动填写的文字与自定义标签，生成总结与导出笔记时一并使用）
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SessionNoteRecord {
+    pub session_id: i64,
+    pub note: String,
+    pub tags: String, // JSON 格式的自定义标签（字符串数组）
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SessionNoteRecord {
+    /// 整理用户输入的备注：去掉首尾空白，标签去掉 `#` 前缀并去重；备注与标签都为空时返回 None
+    pub fn new(session_id: i64, note: &str, tags: &[String]) -> Option<Self> {
+        let note = note.trim().to_string();
+        let mut cleaned: Vec<String> = Vec::new();
+        for tag in tags {
+            let tag = tag.trim().trim_start_matches('#').trim();
+            if !tag.is_empty() && !cleaned.iter().any(|existing| existing == tag) {
+                cleaned.push(tag.to_string());
+            }
+        }
+        if note.is_empty() && cleaned.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            session_id,
+            note,
+            tags: serde_json::to_string(&cleaned).unwrap_or_else(|_| "[]".to_string()),
+            updated_at: local_now(),
+        })
+    }
+
+    /// 自定义标签列表（解析失败时为空）
+    pub fn tag_list(&self) -> Vec<String> {
+        serde_json::from_str(&self.tags).unwrap_or_default()
+    }
+
+    /// 附加到 LLM 提示词中的备注文字
+    pub fn prompt_text(&self) -> String {
+        let tags = self.tag_list();
+        match (self.note.is_empty(), tags.is_empty()) {
+            (false, false) => format!("用户备注：{}（标签：{}）", self.note, tags.join("、")),
+            (false, true) => format!("用户备注：{}", self.note),
+            _ => format!("用户标签：{}", tags.join("、")),
+        }
+    }
+}
+
+/// 语义向量记录（会话总结或时间线卡片的向量，f32 小端序存储）
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct EmbeddingRecord {
+    pub session_id: i64,
+    pub owner_kind: String, // session, card
+    pub owner_id: i64,      // 会话 ID 或时间线卡片 ID
+    pub model: String,
+    pub content_hash: String, // 文本与模型的哈希，未变化时复用已有向量
+    #[serde(skip)]
+    pub vector: Vec<u8>,
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 会话内单个前台应用的使用时长（按截屏时记录的前台窗口统计）
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AppUsageRecord {
+    pub session_id: i64,
+    pub app_name: String,
+    pub process_path: Option<String>,
+    pub window_title: String, // 停留最久的窗口标题
+    pub seconds: i64,
+}
+
+/// 日期范围内单个应用的使用时长汇总
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AppUsageSummary {
+    pub app_name: String,
+    pub process_path: Option<String>,
+    pub seconds: i64,
+    pub session_count: i64,
+}
+
+/// 某天（按会话开始日期）单个应用的使用时长
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DailyAppUsage {
+    pub date: String, // YYYY-MM-DD
+    pub app_name: String,
+    pub seconds: i64,
+}
+
+/// 会话内单个网站域名的访问时长（按浏览器扩展上报的当前标签页统计）
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DomainUsageRecord {
+    pub session_id: i64,
+    pub domain: String,
+    pub seconds: i64,
+}
+
+/// 日期范围内单个域名的访问时长汇总
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DomainUsageSummary {
+    pub domain: String,
+    pub seconds: i64,
+    pub session_count: i64,
+}
+
+/// 帧 OCR 文字（按会话与截图时间保存，截图生成视频后删除也不影响）
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct FrameText {
+    pub id: Option<i64>,
+    pub session_id: i64,
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub timestamp: DateTime<Utc>,
+    pub text: String,
+}
+
+/// LLM 响应缓存（按输入帧感知哈希、提示词版本与模型计算的键保存，重新分析未变化的数据时复用）
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct LlmCacheEntry {
+    pub cache_key: String,
+    /// 调用类型（timeline / day_summary）
+    pub task: String,
+    pub provider: String,
+    pub model: String,
+    /// 序列化后的响应
+    pub response: String,
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// 时间线卡片复核记录（低置信度卡片经用户确认或修正类别后写入，不再出现在复核队列）
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CardReviewRecord {
+    pub card_id: i64,
+    pub session_id: i64,
+    /// 卡片的主要应用或网站（用于生成预分类规则）
+    pub app: String,
+    /// 复核前的类别
+    pub original_category: String,
+    pub category: String,
+    pub subcategory: String,
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub reviewed_at: DateTime<Utc>,
+}
+
+/// 用户目标（如「工作日专注工作 ≥ 3 小时」「社交网站 ≤ 1 小时」），每天按时间线指标评估
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct GoalRecord {
+    pub id: Option<i64>,
+    pub name: String,
+    pub metric: String,     // focus, active, category, app, domain
+    pub target: String,     // 类别名称，或应用/域名关键词（逗号分隔，忽略大小写）
+    pub comparison: String, // at_least, at_most
+    pub target_minutes: i64,
+    pub schedule: String, // daily, weekdays, weekends
+    pub enabled: bool,
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// 目标的单日评估结果（当天结束后评估的结果才作为缓存复用）
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct GoalResultRecord {
+    pub goal_id: i64,
+    pub date: String, // YYYY-MM-DD
+    pub actual_minutes: i64,
+    pub passed: bool,
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub evaluated_at: DateTime<Utc>,
+}
+
+/// 专注时段（番茄钟）：计时期间截取的帧标记为该时段，干扰类活动计入干扰记录
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct FocusBlockRecord {
+    pub id: Option<i64>,
+    pub label: String,
+    pub planned_minutes: i64,
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub started_at: DateTime<Utc>,
+    #[serde(serialize_with = "serialize_datetime_as_local_option")]
+    pub ended_at: Option<DateTime<Utc>>,
+    pub status: String, // running, completed, cancelled
+    pub distraction_count: i64,
+    pub distractions: String, // JSON 数组：[{timestamp, category, app}]
+}
+
+/// LLM 结构化响应校验失败记录（保存原始响应便于排查提示词或模型问题）
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct LlmParseFailure {
+    pub id: Option<i64>,
+    pub session_id: Option<i64>,
+    pub provider: String,
+    pub model: String,
+    /// 调用类型（generate_timeline / categorize_cards）
+    pub task: String,
+    /// 原始响应
+    pub raw_response: String,
+    /// 修复重试返回的响应（修复请求失败时为空）
+    pub repaired_response: Option<String>,
+    /// 校验错误（每行一条）
+    pub errors: String,
+    /// 修复后是否通过校验
+    pub repaired: bool,
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// 会话全文搜索结果（按相关度排序，片段中命中词以【】标记）
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SessionSearchHit {
+    pub session_id: i64,
+    pub title: String,
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub start_time: DateTime<Utc>,
+    #[serde(serialize_with = "serialize_datetime_as_local")]
+    pub end_time: DateTime<Utc>,
+    pub snippet: String,
+    pub score: f64,
+}
+
 // 自定义序列化：NaiveDate -> String (YYYY-MM-DD)
 fn serialize_naive_date<S>(date: &chrono::NaiveDate, serializer: S) -> Result<S::Ok, S::Error>
 where
@@ -2,12 +2,15 @@
 
 use super::DatabaseRepository;
 use crate::storage::config::get_device_info;
+use crate::storage::dump;
+use crate::storage::migrations::{self, Migration};
 use crate::storage::models::*;
+use crate::storage::search;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use sqlx::mysql::{MySqlPool, MySqlPoolOptions};
-use sqlx::Row;
+use sqlx::{Connection, Executor, Row};
 use tracing::info;
 
 /// MariaDB 数据库实现
@@ -104,45 +107,12 @@ impl MariaDbRepository {
 
         let repo = Self { pool };
 
-        // 检查表是否存在，不存在则初始化
-        let tables_exist = repo.check_tables_exist().await?;
-        if !tables_exist {
-            info!("MariaDB 表不存在，开始初始化表结构");
-            repo.initialize_tables().await?;
-        } else {
-            info!("MariaDB 表已存在，直接使用");
-        }
+        // 执行结构迁移（新建数据库时即创建全部表）
+        repo.run_migrations().await?;
 
         Ok(repo)
     }
 
-    /// 检查所有必要的表是否存在
-    async fn check_tables_exist(&self) -> Result<bool> {
-        let tables = vec![
-            "sessions",
-            "frames",
-            "llm_calls",
-            "video_segments",
-            "timeline_cards",
-            "day_summaries",
-        ];
-
-        for table in tables {
-            let count: i64 = sqlx::query_scalar(
-                "SELECT COUNT(*) FROM information_schema.tables WHERE table_schema = DATABASE() AND table_name = ?",
-            )
-            .bind(table)
-            .fetch_one(&self.pool)
-            .await?;
-
-            if count == 0 {
-                return Ok(false);
-            }
-        }
-
-        Ok(true)
-    }
-
     /// 获取连接池引用（用于向后兼容）
     pub fn get_pool(&self) -> &MySqlPool {
         &self.pool
@@ -174,6 +144,43 @@ impl DatabaseRepository for MariaDbRepository {
         Ok(result.last_insert_id() as i64)
     }
 
+    async fn insert_session_with_cards(
+        &self,
+        session: &Session,
+        cards: &[TimelineCardRecord],
+    ) -> Result<i64> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO sessions (start_time, end_time, title, summary, video_path, tags, device_name, device_type)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+        )
+        .bind(session.start_time)
+        .bind(session.end_time)
+        .bind(&session.title)
+        .bind(&session.summary)
+        .bind(&session.video_path)
+        .bind(&session.tags)
+        .bind(&session.device_name)
+        .bind(&session.device_type)
+        .execute(&mut *tx)
+        .await?;
+        let session_id = result.last_insert_id() as i64;
+        let cards: Vec<TimelineCardRecord> = cards
+            .iter()
+            .map(|card| TimelineCardRecord {
+                session_id,
+                ..card.clone()
+            })
+            .collect();
+        insert_card_rows(&mut tx, &cards).await?;
+
+        tx.commit().await?;
+        Ok(session_id)
+    }
+
     async fn insert_sessions(&self, sessions: &[Session]) -> Result<Vec<i64>> {
         let mut ids = Vec::new();
         let mut tx = self.pool.begin().await?;
@@ -300,6 +307,51 @@ impl DatabaseRepository for MariaDbRepository {
         Ok(())
     }
 
+    async fn update_session_times(
+        &self,
+        session_id: i64,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query("UPDATE sessions SET start_time = ?, end_time = ? WHERE id = ?")
+            .bind(start_time)
+            .bind(end_time)
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn replace_session_content(
+        &self,
+        session_id: i64,
+        session: &Session,
+        cards: &[TimelineCardRecord],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "UPDATE sessions SET title = ?, summary = ?, tags = ?, start_time = ?, end_time = ? WHERE id = ?",
+        )
+        .bind(&session.title)
+        .bind(&session.summary)
+        .bind(&session.tags)
+        .bind(session.start_time)
+        .bind(session.end_time)
+        .bind(session_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM timeline_cards WHERE session_id = ?")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        insert_card_rows(&mut tx, cards).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
     async fn update_session_video_path(&self, session_id: i64, video_path: &str) -> Result<()> {
         sqlx::query("UPDATE sessions SET video_path = ? WHERE id = ?")
             .bind(video_path)
@@ -310,6 +362,15 @@ impl DatabaseRepository for MariaDbRepository {
         Ok(())
     }
 
+    async fn clear_session_video_path(&self, session_id: i64) -> Result<()> {
+        sqlx::query("UPDATE sessions SET video_path = NULL WHERE id = ?")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
     async fn update_device_info_for_all_sessions(&self) -> Result<u64> {
         let (device_name, device_type) = get_device_info();
 
@@ -376,13 +437,18 @@ impl DatabaseRepository for MariaDbRepository {
     async fn insert_frame(&self, frame: &Frame) -> Result<i64> {
         let result = sqlx::query(
             r#"
-            INSERT INTO frames (session_id, timestamp, file_path)
-            VALUES (?, ?, ?)
+            INSERT INTO frames (session_id, timestamp, file_path, app_name, window_title, process_path, domain, screen_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
         "#,
         )
         .bind(frame.session_id)
         .bind(&frame.timestamp)
         .bind(&frame.file_path)
+        .bind(&frame.app_name)
+        .bind(&frame.window_title)
+        .bind(&frame.process_path)
+        .bind(&frame.domain)
+        .bind(frame.screen_id)
         .execute(&self.pool)
         .await?;
 
@@ -395,13 +461,18 @@ impl DatabaseRepository for MariaDbRepository {
         for frame in frames {
             sqlx::query(
                 r#"
-                INSERT INTO frames (session_id, timestamp, file_path)
-                VALUES (?, ?, ?)
+                INSERT INTO frames (session_id, timestamp, file_path, app_name, window_title, process_path, domain, screen_id)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             "#,
             )
             .bind(frame.session_id)
             .bind(&frame.timestamp)
             .bind(&frame.file_path)
+            .bind(&frame.app_name)
+            .bind(&frame.window_title)
+            .bind(&frame.process_path)
+            .bind(&frame.domain)
+            .bind(frame.screen_id)
             .execute(&mut *tx)
             .await?;
         }
@@ -413,7 +484,7 @@ impl DatabaseRepository for MariaDbRepository {
     async fn get_frames_by_session(&self, session_id: i64) -> Result<Vec<Frame>> {
         let frames = sqlx::query_as::<_, Frame>(
             r#"
-            SELECT id, session_id, timestamp, file_path
+            SELECT id, session_id, timestamp, file_path, app_name, window_title, process_path, domain, screen_id
             FROM frames
             WHERE session_id = ?
             ORDER BY timestamp
@@ -435,6 +506,70 @@ impl DatabaseRepository for MariaDbRepository {
         Ok(())
     }
 
+    async fn delete_frames(&self, session_id: i64, frame_ids: &[i64]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for frame_id in frame_ids {
+            sqlx::query("DELETE FROM frames WHERE id = ? AND session_id = ?")
+                .bind(frame_id)
+                .bind(session_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn update_frame_path(
+        &self,
+        session_id: i64,
+        frame_id: i64,
+        file_path: &str,
+    ) -> Result<()> {
+        sqlx::query("UPDATE frames SET file_path = ? WHERE id = ? AND session_id = ?")
+            .bind(file_path)
+            .bind(frame_id)
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn move_frames(
+        &self,
+        from_session_id: i64,
+        to_session_id: i64,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut moved = 0;
+
+        for table in ["frames", "frame_texts"] {
+            let sql = format!(
+                "UPDATE {} SET session_id = ? WHERE session_id = ?{}",
+                table,
+                if since.is_some() {
+                    " AND timestamp >= ?"
+                } else {
+                    ""
+                }
+            );
+            let mut query = sqlx::query(&sql).bind(to_session_id).bind(from_session_id);
+            if let Some(since) = since {
+                query = query.bind(since);
+            }
+            let result = query.execute(&mut *tx).await?;
+            if table == "frames" {
+                moved = result.rows_affected();
+            }
+        }
+
+        tx.commit().await?;
+        Ok(moved)
+    }
+
     // ========== 活动统计 ==========
 
     async fn get_activities(&self, start_date: &str, end_date: &str) -> Result<Vec<Activity>> {
@@ -482,6 +617,70 @@ impl DatabaseRepository for MariaDbRepository {
         Ok(activities)
     }
 
+    async fn get_device_activities(
+        &self,
+        start_date: &str,
+        end_date: &str,
+        device_name: &str,
+    ) -> Result<Vec<Activity>> {
+        let start_datetime = format!("{} 00:00:00", start_date);
+        let end_datetime = format!("{} 23:59:59", end_date);
+
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                DATE_FORMAT(DATE(start_time), '%Y-%m-%d') as date,
+                COUNT(*) as session_count,
+                CAST(SUM(TIMESTAMPDIFF(MINUTE, start_time, end_time)) AS SIGNED) as total_duration_minutes,
+                GROUP_CONCAT(DISTINCT JSON_EXTRACT(tags, '$[0].category')) as main_categories
+            FROM sessions
+            WHERE start_time >= ? AND start_time <= ? AND device_name = ?
+            GROUP BY DATE_FORMAT(DATE(start_time), '%Y-%m-%d')
+            ORDER BY date DESC
+            "#
+        )
+        .bind(&start_datetime)
+        .bind(&end_datetime)
+        .bind(device_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut activities = Vec::new();
+        for row in rows {
+            let date: String = row.try_get("date")?;
+            let session_count: i64 = row.try_get("session_count")?;
+            let total_duration_minutes: Option<i64> = row.try_get("total_duration_minutes")?;
+            let main_categories_str: Option<String> = row.try_get("main_categories")?;
+
+            let main_categories = main_categories_str
+                .map(|s| s.split(',').map(|s| s.to_string()).collect())
+                .unwrap_or_default();
+
+            activities.push(Activity {
+                date,
+                session_count: session_count as i32,
+                total_duration_minutes: total_duration_minutes.unwrap_or(0) as i32,
+                main_categories,
+            });
+        }
+
+        Ok(activities)
+    }
+
+    async fn get_device_names(&self) -> Result<Vec<String>> {
+        let names = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT DISTINCT device_name FROM sessions
+            WHERE device_name IS NOT NULL AND device_name <> ''
+            ORDER BY device_name
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(names)
+    }
+
     // ========== LLM 调用记录 ==========
 
     async fn insert_llm_call(&self, record: &LLMCallRecord) -> Result<i64> {
@@ -700,9 +899,9 @@ impl DatabaseRepository for MariaDbRepository {
             INSERT INTO timeline_cards (
                 session_id, llm_call_id, start_time, end_time,
                 category, subcategory, title, summary, detailed_summary,
-                distractions, app_sites, video_preview_path, created_at
+                distractions, app_sites, confidence, heuristic, video_preview_path, created_at
             )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
         )
         .bind(&card.session_id)
@@ -716,6 +915,8 @@ impl DatabaseRepository for MariaDbRepository {
         .bind(&card.detailed_summary)
         .bind(&card.distractions)
         .bind(&card.app_sites)
+        .bind(card.confidence)
+        .bind(card.heuristic)
         .bind(&card.video_preview_path)
         .bind(&card.created_at)
         .execute(&self.pool)
@@ -745,35 +946,7 @@ impl DatabaseRepository for MariaDbRepository {
         }
 
         let mut tx = self.pool.begin().await?;
-
-        for card in cards {
-            sqlx::query(
-                r#"
-                INSERT INTO timeline_cards (
-                    session_id, llm_call_id, start_time, end_time,
-                    category, subcategory, title, summary, detailed_summary,
-                    distractions, app_sites, video_preview_path, created_at
-                )
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-            )
-            .bind(&card.session_id)
-            .bind(&card.llm_call_id)
-            .bind(&card.start_time)
-            .bind(&card.end_time)
-            .bind(&card.category)
-            .bind(&card.subcategory)
-            .bind(&card.title)
-            .bind(&card.summary)
-            .bind(&card.detailed_summary)
-            .bind(&card.distractions)
-            .bind(&card.app_sites)
-            .bind(&card.video_preview_path)
-            .bind(&card.created_at)
-            .execute(&mut *tx)
-            .await?;
-        }
-
+        insert_card_rows(&mut tx, cards).await?;
         tx.commit().await?;
         Ok(())
     }
@@ -820,6 +993,32 @@ impl DatabaseRepository for MariaDbRepository {
         Ok(())
     }
 
+    async fn update_timeline_card_title(&self, id: i64, title: &str) -> Result<()> {
+        sqlx::query("UPDATE timeline_cards SET title = ? WHERE id = ?")
+            .bind(title)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn update_timeline_card_category(
+        &self,
+        id: i64,
+        category: &str,
+        subcategory: &str,
+    ) -> Result<()> {
+        sqlx::query("UPDATE timeline_cards SET category = ?, subcategory = ? WHERE id = ?")
+            .bind(category)
+            .bind(subcategory)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
     // ========== 统计信息 ==========
 
     async fn get_stats(&self) -> Result<(i64, i64, i64)> {
@@ -866,168 +1065,59 @@ impl DatabaseRepository for MariaDbRepository {
 
     // ========== 数据库初始化 ==========
 
-    async fn initialize_tables(&self) -> Result<()> {
-        // 创建会话表
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS sessions (
-                id BIGINT PRIMARY KEY AUTO_INCREMENT,
-                start_time DATETIME NOT NULL,
-                end_time DATETIME NOT NULL,
-                title TEXT NOT NULL,
-                summary TEXT NOT NULL,
-                video_path TEXT,
-                tags TEXT NOT NULL,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                device_name VARCHAR(255),
-                device_type VARCHAR(50)
-            )
-        "#,
-        )
-        .execute(&self.pool)
-        .await?;
-
-        // 创建帧表
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS frames (
-                id BIGINT PRIMARY KEY AUTO_INCREMENT,
-                session_id BIGINT NOT NULL,
-                timestamp DATETIME NOT NULL,
-                file_path TEXT NOT NULL,
-                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
-            )
-        "#,
-        )
-        .execute(&self.pool)
-        .await?;
-
-        // 创建索引（MySQL 不支持 CREATE INDEX IF NOT EXISTS，需要忽略已存在错误）
-        let _ = sqlx::query("CREATE INDEX idx_sessions_start_time ON sessions(start_time)")
-            .execute(&self.pool)
-            .await;
-        let _ = sqlx::query("CREATE INDEX idx_frames_session_id ON frames(session_id)")
-            .execute(&self.pool)
-            .await;
-        let _ =
-            sqlx::query("CREATE INDEX idx_sessions_start_end ON sessions(start_time, end_time)")
-                .execute(&self.pool)
-                .await;
-        let _ = sqlx::query(
-            "CREATE INDEX idx_frames_session_timestamp ON frames(session_id, timestamp)",
-        )
-        .execute(&self.pool)
-        .await;
-
-        // 创建LLM调用记录表
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS llm_calls (
-                id BIGINT PRIMARY KEY AUTO_INCREMENT,
-                session_id BIGINT,
-                provider VARCHAR(100) NOT NULL,
-                model VARCHAR(100) NOT NULL,
-                call_type VARCHAR(100) NOT NULL,
-                request_headers TEXT NOT NULL,
-                request_body TEXT NOT NULL,
-                response_headers TEXT,
-                response_body TEXT,
-                status_code INT,
-                error_message TEXT,
-                latency_ms BIGINT,
-                token_usage TEXT,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
-            )
-        "#,
-        )
-        .execute(&self.pool)
-        .await?;
-
-        // 创建视频分段表
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS video_segments (
-                id BIGINT PRIMARY KEY AUTO_INCREMENT,
-                session_id BIGINT NOT NULL,
-                llm_call_id BIGINT,
-                start_timestamp VARCHAR(50) NOT NULL,
-                end_timestamp VARCHAR(50) NOT NULL,
-                description TEXT NOT NULL,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
-                FOREIGN KEY (llm_call_id) REFERENCES llm_calls(id) ON DELETE SET NULL
-            )
-        "#,
-        )
-        .execute(&self.pool)
-        .await?;
+    async fn run_migrations(&self) -> Result<()> {
+        // 多台设备可能共用同一数据库，迁移期间持有命名锁，避免同时升级同一版本
+        let mut conn = self.pool.acquire().await?;
+        let locked: Option<i64> = sqlx::query_scalar("SELECT CAST(GET_LOCK(?, 60) AS SIGNED)")
+            .bind(MIGRATION_LOCK)
+            .fetch_one(&mut *conn)
+            .await?;
+        if locked != Some(1) {
+            anyhow::bail!("等待其他设备完成 MariaDB 结构迁移超时");
+        }
 
-        // 创建时间线卡片表
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS timeline_cards (
-                id BIGINT PRIMARY KEY AUTO_INCREMENT,
-                session_id BIGINT NOT NULL,
-                llm_call_id BIGINT,
-                start_time VARCHAR(50) NOT NULL,
-                end_time VARCHAR(50) NOT NULL,
-                category VARCHAR(100) NOT NULL,
-                subcategory VARCHAR(100) NOT NULL,
-                title TEXT NOT NULL,
-                summary TEXT NOT NULL,
-                detailed_summary TEXT NOT NULL,
-                distractions TEXT,
-                app_sites TEXT NOT NULL,
-                video_preview_path TEXT,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
-                FOREIGN KEY (llm_call_id) REFERENCES llm_calls(id) ON DELETE SET NULL
-            )
-        "#,
-        )
-        .execute(&self.pool)
-        .await?;
+        let result = async {
+            sqlx::query(migrations::CREATE_VERSION_TABLE_SQL)
+                .execute(&mut *conn)
+                .await?;
+            let current: i64 = sqlx::query_scalar(migrations::CURRENT_VERSION_SQL)
+                .fetch_one(&mut *conn)
+                .await?;
 
-        // 创建每日总结表（缓存）
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS day_summaries (
-                id BIGINT PRIMARY KEY AUTO_INCREMENT,
-                date DATE NOT NULL UNIQUE,
-                summary_text TEXT NOT NULL,
-                device_stats TEXT NOT NULL,
-                parallel_work TEXT NOT NULL,
-                usage_patterns TEXT NOT NULL,
-                active_device_count INT NOT NULL,
-                llm_call_id BIGINT,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
-                FOREIGN KEY (llm_call_id) REFERENCES llm_calls(id) ON DELETE SET NULL
-            )
-        "#,
-        )
-        .execute(&self.pool)
-        .await?;
+            let all = mariadb_migrations();
+            for migration in migrations::pending(&all, current)? {
+                info!(
+                    "应用 MariaDB 结构迁移 v{}: {}",
+                    migration.version, migration.description
+                );
+
+                // MariaDB 的 DDL 会隐式提交，无法随事务回滚，因此迁移语句须可重复执行；
+                // 版本号在全部步骤成功后才写入，中途失败时下次启动重试整个版本
+                let mut tx = conn.begin().await?;
+                for step in &migration.steps {
+                    sqlx::query(&step.to_sql()).execute(&mut *tx).await?;
+                }
+                sqlx::query("INSERT INTO schema_version (version, description) VALUES (?, ?)")
+                    .bind(migration.version)
+                    .bind(migration.description)
+                    .execute(&mut *tx)
+                    .await?;
+                tx.commit().await?;
+            }
 
-        // 创建额外的索引（忽略已存在错误）
-        let _ = sqlx::query("CREATE INDEX idx_llm_calls_session_id ON llm_calls(session_id)")
-            .execute(&self.pool)
-            .await;
-        let _ = sqlx::query("CREATE INDEX idx_llm_calls_created_at ON llm_calls(created_at)")
-            .execute(&self.pool)
-            .await;
-        let _ =
-            sqlx::query("CREATE INDEX idx_video_segments_session_id ON video_segments(session_id)")
-                .execute(&self.pool)
-                .await;
-        let _ =
-            sqlx::query("CREATE INDEX idx_timeline_cards_session_id ON timeline_cards(session_id)")
-                .execute(&self.pool)
-                .await;
+            info!(
+                "MariaDB 数据库结构版本: {}",
+                migrations::latest_version(&all)
+            );
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
 
-        info!("MariaDB 数据库表初始化完成");
-        Ok(())
+        sqlx::query("SELECT RELEASE_LOCK(?)")
+            .bind(MIGRATION_LOCK)
+            .execute(&mut *conn)
+            .await?;
+        result
     }
 
     async fn save_day_summary(&self, date: &str, summary: &DaySummaryRecord) -> Result<()> {
@@ -1079,11 +1169,1183 @@ impl DatabaseRepository for MariaDbRepository {
         Ok(())
     }
 
-    fn db_type(&self) -> &str {
-        "mariadb"
-    }
-
-    async fn migrate_timezone_to_local(&self) -> Result<(u64, u64, u64, u64, u64, u64)> {
+    async fn save_daily_focus_metrics(
+        &self,
+        date: &str,
+        metrics: &DailyFocusMetricsRecord,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            REPLACE INTO daily_focus_metrics (
+                date, total_minutes, work_minutes, learning_minutes, communication_minutes,
+                personal_minutes, idle_minutes, other_minutes, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, NOW())
+            "#,
+        )
+        .bind(date)
+        .bind(metrics.total_minutes)
+        .bind(metrics.work_minutes)
+        .bind(metrics.learning_minutes)
+        .bind(metrics.communication_minutes)
+        .bind(metrics.personal_minutes)
+        .bind(metrics.idle_minutes)
+        .bind(metrics.other_minutes)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_daily_focus_metrics(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DailyFocusMetricsRecord>> {
+        let result = sqlx::query_as::<_, DailyFocusMetricsRecord>(
+            r#"
+            SELECT * FROM daily_focus_metrics WHERE date >= ? AND date <= ? ORDER BY date
+            "#,
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn delete_daily_focus_metrics(&self, date: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM daily_focus_metrics WHERE date = ?
+            "#,
+        )
+        .bind(date)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert_export_history(&self, record: &ExportHistoryRecord) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO export_history (
+                kind, target, request, success, message,
+                written_paths, warnings, failed_sessions, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+        )
+        .bind(&record.kind)
+        .bind(&record.target)
+        .bind(&record.request)
+        .bind(record.success)
+        .bind(&record.message)
+        .bind(&record.written_paths)
+        .bind(&record.warnings)
+        .bind(&record.failed_sessions)
+        .bind(record.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_id() as i64)
+    }
+
+    async fn get_export_history(&self, limit: i64) -> Result<Vec<ExportHistoryRecord>> {
+        let records = sqlx::query_as::<_, ExportHistoryRecord>(
+            r#"
+            SELECT * FROM export_history ORDER BY created_at DESC, id DESC LIMIT ?
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn get_export_history_entry(&self, id: i64) -> Result<ExportHistoryRecord> {
+        let record = sqlx::query_as::<_, ExportHistoryRecord>(
+            r#"
+            SELECT * FROM export_history WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    async fn insert_project_rule(&self, rule: &ProjectRuleRecord) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO project_rules (project, match_field, pattern, created_at)
+            VALUES (?, ?, ?, ?)
+        "#,
+        )
+        .bind(&rule.project)
+        .bind(&rule.match_field)
+        .bind(&rule.pattern)
+        .bind(rule.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_id() as i64)
+    }
+
+    async fn get_project_rules(&self) -> Result<Vec<ProjectRuleRecord>> {
+        let rules = sqlx::query_as::<_, ProjectRuleRecord>(
+            r#"
+            SELECT * FROM project_rules ORDER BY id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rules)
+    }
+
+    async fn delete_project_rule(&self, id: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM project_rules WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert_custom_category(&self, category: &CustomCategoryRecord) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO custom_categories (name, bucket, description, subcategories, created_at)
+            VALUES (?, ?, ?, ?, ?)
+        "#,
+        )
+        .bind(&category.name)
+        .bind(&category.bucket)
+        .bind(&category.description)
+        .bind(&category.subcategories)
+        .bind(category.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_id() as i64)
+    }
+
+    async fn get_custom_categories(&self) -> Result<Vec<CustomCategoryRecord>> {
+        let categories = sqlx::query_as::<_, CustomCategoryRecord>(
+            r#"
+            SELECT * FROM custom_categories ORDER BY id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(categories)
+    }
+
+    async fn delete_custom_category(&self, id: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM custom_categories WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert_classification_rule(&self, rule: &ClassificationRuleRecord) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO classification_rules (match_field, pattern, category, subcategory, created_at)
+            VALUES (?, ?, ?, ?, ?)
+        "#,
+        )
+        .bind(&rule.match_field)
+        .bind(&rule.pattern)
+        .bind(&rule.category)
+        .bind(&rule.subcategory)
+        .bind(rule.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_id() as i64)
+    }
+
+    async fn get_classification_rules(&self) -> Result<Vec<ClassificationRuleRecord>> {
+        let rules = sqlx::query_as::<_, ClassificationRuleRecord>(
+            r#"
+            SELECT * FROM classification_rules ORDER BY id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rules)
+    }
+
+    async fn delete_classification_rule(&self, id: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM classification_rules WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn save_notion_sync(&self, record: &NotionSyncRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            REPLACE INTO notion_sync (kind, local_key, date, page_id, synced_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&record.kind)
+        .bind(&record.local_key)
+        .bind(&record.date)
+        .bind(&record.page_id)
+        .bind(record.synced_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_notion_sync(
+        &self,
+        kind: &str,
+        local_key: &str,
+    ) -> Result<Option<NotionSyncRecord>> {
+        let record = sqlx::query_as::<_, NotionSyncRecord>(
+            r#"
+            SELECT * FROM notion_sync WHERE kind = ? AND local_key = ?
+            "#,
+        )
+        .bind(kind)
+        .bind(local_key)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    async fn get_notion_syncs_by_date(
+        &self,
+        kind: &str,
+        date: &str,
+    ) -> Result<Vec<NotionSyncRecord>> {
+        let records = sqlx::query_as::<_, NotionSyncRecord>(
+            r#"
+            SELECT * FROM notion_sync WHERE kind = ? AND date = ? ORDER BY local_key
+            "#,
+        )
+        .bind(kind)
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn delete_notion_sync(&self, kind: &str, local_key: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM notion_sync WHERE kind = ? AND local_key = ?
+            "#,
+        )
+        .bind(kind)
+        .bind(local_key)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn replace_calendar_events(
+        &self,
+        date: &str,
+        events: &[CalendarEventRecord],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM calendar_events WHERE date = ?")
+            .bind(date)
+            .execute(&mut *tx)
+            .await?;
+        for event in events {
+            sqlx::query(
+                r#"
+                INSERT INTO calendar_events (event_id, date, title, location, start_time, end_time, synced_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&event.event_id)
+            .bind(date)
+            .bind(&event.title)
+            .bind(&event.location)
+            .bind(event.start_time)
+            .bind(event.end_time)
+            .bind(event.synced_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_calendar_events_by_date(&self, date: &str) -> Result<Vec<CalendarEventRecord>> {
+        let events = sqlx::query_as::<_, CalendarEventRecord>(
+            r#"
+            SELECT * FROM calendar_events WHERE date = ? ORDER BY start_time
+            "#,
+        )
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    async fn replace_issue_links(&self, session_id: i64, links: &[IssueLinkRecord]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM issue_links WHERE session_id = ?")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        for link in links {
+            sqlx::query(
+                r#"
+                INSERT INTO issue_links (session_id, provider, issue_key, title, state, url, resolved_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(session_id)
+            .bind(&link.provider)
+            .bind(&link.issue_key)
+            .bind(&link.title)
+            .bind(&link.state)
+            .bind(&link.url)
+            .bind(link.resolved_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_issue_links_by_session(&self, session_id: i64) -> Result<Vec<IssueLinkRecord>> {
+        let links = sqlx::query_as::<_, IssueLinkRecord>(
+            r#"
+            SELECT * FROM issue_links WHERE session_id = ? ORDER BY provider, issue_key
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(links)
+    }
+
+    async fn save_time_entry_sync(&self, record: &TimeEntrySyncRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            REPLACE INTO time_entry_sync (provider, session_id, entry_id, synced_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(&record.provider)
+        .bind(record.session_id)
+        .bind(&record.entry_id)
+        .bind(record.synced_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_time_entry_sync(
+        &self,
+        provider: &str,
+        session_id: i64,
+    ) -> Result<Option<TimeEntrySyncRecord>> {
+        let record = sqlx::query_as::<_, TimeEntrySyncRecord>(
+            r#"
+            SELECT * FROM time_entry_sync WHERE provider = ? AND session_id = ?
+            "#,
+        )
+        .bind(provider)
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    async fn save_session_note(&self, record: &SessionNoteRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            REPLACE INTO session_notes (session_id, note, tags, updated_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(record.session_id)
+        .bind(&record.note)
+        .bind(&record.tags)
+        .bind(record.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_session_note(&self, session_id: i64) -> Result<Option<SessionNoteRecord>> {
+        let record = sqlx::query_as::<_, SessionNoteRecord>(
+            r#"
+            SELECT * FROM session_notes WHERE session_id = ?
+            "#,
+        )
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    async fn delete_session_note(&self, session_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM session_notes WHERE session_id = ?")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn replace_app_usage(&self, session_id: i64, records: &[AppUsageRecord]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM app_usage WHERE session_id = ?")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        for record in records {
+            sqlx::query(
+                r#"
+                INSERT INTO app_usage (session_id, app_name, process_path, window_title, seconds)
+                VALUES (?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(session_id)
+            .bind(&record.app_name)
+            .bind(&record.process_path)
+            .bind(&record.window_title)
+            .bind(record.seconds)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_app_usage_by_session(&self, session_id: i64) -> Result<Vec<AppUsageRecord>> {
+        let records = sqlx::query_as::<_, AppUsageRecord>(
+            "SELECT * FROM app_usage WHERE session_id = ? ORDER BY seconds DESC, app_name",
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn get_app_usage_summary(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<AppUsageSummary>> {
+        let summaries = sqlx::query_as::<_, AppUsageSummary>(
+            r#"
+            SELECT a.app_name, MAX(a.process_path) AS process_path,
+                   CAST(SUM(a.seconds) AS SIGNED) AS seconds,
+                   COUNT(DISTINCT a.session_id) AS session_count
+            FROM app_usage a
+            JOIN sessions s ON s.id = a.session_id
+            WHERE DATE(s.start_time) >= ? AND DATE(s.start_time) <= ?
+            GROUP BY a.app_name
+            ORDER BY seconds DESC, a.app_name
+            "#,
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(summaries)
+    }
+
+    async fn get_daily_app_usage(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DailyAppUsage>> {
+        let usage = sqlx::query_as::<_, DailyAppUsage>(
+            r#"
+            SELECT DATE_FORMAT(DATE(s.start_time), '%Y-%m-%d') AS date, a.app_name,
+                   CAST(SUM(a.seconds) AS SIGNED) AS seconds
+            FROM app_usage a
+            JOIN sessions s ON s.id = a.session_id
+            WHERE DATE(s.start_time) >= ? AND DATE(s.start_time) <= ?
+            GROUP BY DATE_FORMAT(DATE(s.start_time), '%Y-%m-%d'), a.app_name
+            ORDER BY date, seconds DESC, a.app_name
+            "#,
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(usage)
+    }
+
+    async fn replace_domain_usage(
+        &self,
+        session_id: i64,
+        records: &[DomainUsageRecord],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM domain_usage WHERE session_id = ?")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        for record in records {
+            sqlx::query("INSERT INTO domain_usage (session_id, domain, seconds) VALUES (?, ?, ?)")
+                .bind(session_id)
+                .bind(&record.domain)
+                .bind(record.seconds)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_domain_usage_by_session(&self, session_id: i64) -> Result<Vec<DomainUsageRecord>> {
+        let records = sqlx::query_as::<_, DomainUsageRecord>(
+            "SELECT * FROM domain_usage WHERE session_id = ? ORDER BY seconds DESC, domain",
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn get_domain_usage_summary(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DomainUsageSummary>> {
+        let summaries = sqlx::query_as::<_, DomainUsageSummary>(
+            r#"
+            SELECT d.domain, CAST(SUM(d.seconds) AS SIGNED) AS seconds,
+                   COUNT(DISTINCT d.session_id) AS session_count
+            FROM domain_usage d
+            JOIN sessions s ON s.id = d.session_id
+            WHERE DATE(s.start_time) >= ? AND DATE(s.start_time) <= ?
+            GROUP BY d.domain
+            ORDER BY seconds DESC, d.domain
+            "#,
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(summaries)
+    }
+
+    async fn insert_frame_texts(&self, texts: &[FrameText]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for text in texts {
+            sqlx::query("INSERT INTO frame_texts (session_id, timestamp, text) VALUES (?, ?, ?)")
+                .bind(text.session_id)
+                .bind(text.timestamp)
+                .bind(&text.text)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_frame_texts_by_session(&self, session_id: i64) -> Result<Vec<FrameText>> {
+        let texts = sqlx::query_as::<_, FrameText>(
+            "SELECT id, session_id, timestamp, text FROM frame_texts WHERE session_id = ? ORDER BY timestamp",
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(texts)
+    }
+
+    async fn get_llm_cache(&self, cache_key: &str) -> Result<Option<LlmCacheEntry>> {
+        let entry = sqlx::query_as::<_, LlmCacheEntry>(
+            "SELECT cache_key, task, provider, model, response, created_at FROM llm_cache WHERE cache_key = ?",
+        )
+        .bind(cache_key)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    async fn save_llm_cache(&self, entry: &LlmCacheEntry) -> Result<()> {
+        sqlx::query(
+            r#"
+            REPLACE INTO llm_cache (cache_key, task, provider, model, response, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&entry.cache_key)
+        .bind(&entry.task)
+        .bind(&entry.provider)
+        .bind(&entry.model)
+        .bind(&entry.response)
+        .bind(entry.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn prune_llm_cache(&self, before: Option<DateTime<Utc>>) -> Result<u64> {
+        let result = match before {
+            Some(before) => {
+                sqlx::query("DELETE FROM llm_cache WHERE created_at < ?")
+                    .bind(before)
+                    .execute(&self.pool)
+                    .await?
+            }
+            None => {
+                sqlx::query("DELETE FROM llm_cache")
+                    .execute(&self.pool)
+                    .await?
+            }
+        };
+
+        Ok(result.rows_affected())
+    }
+
+    async fn insert_llm_parse_failure(&self, failure: &LlmParseFailure) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO llm_parse_failures (
+                session_id, provider, model, task, raw_response, repaired_response,
+                errors, repaired, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(failure.session_id)
+        .bind(&failure.provider)
+        .bind(&failure.model)
+        .bind(&failure.task)
+        .bind(&failure.raw_response)
+        .bind(&failure.repaired_response)
+        .bind(&failure.errors)
+        .bind(failure.repaired)
+        .bind(failure.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_id() as i64)
+    }
+
+    async fn get_llm_parse_failures(&self, limit: i64) -> Result<Vec<LlmParseFailure>> {
+        let failures = sqlx::query_as::<_, LlmParseFailure>(
+            "SELECT * FROM llm_parse_failures ORDER BY created_at DESC, id DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(failures)
+    }
+
+    async fn get_timeline_card(&self, id: i64) -> Result<Option<TimelineCardRecord>> {
+        let card =
+            sqlx::query_as::<_, TimelineCardRecord>("SELECT * FROM timeline_cards WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(card)
+    }
+
+    async fn get_review_queue(
+        &self,
+        threshold: f64,
+        limit: i64,
+    ) -> Result<Vec<TimelineCardRecord>> {
+        let cards = sqlx::query_as::<_, TimelineCardRecord>(
+            r#"
+            SELECT c.* FROM timeline_cards c
+            WHERE c.confidence IS NOT NULL AND c.confidence < ?
+              AND LOWER(c.category) <> 'idle'
+              AND NOT EXISTS (SELECT 1 FROM card_reviews r WHERE r.card_id = c.id)
+            ORDER BY c.start_time DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(threshold)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(cards)
+    }
+
+    async fn save_card_review(&self, review: &CardReviewRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            REPLACE INTO card_reviews (
+                card_id, session_id, app, original_category, category, subcategory, reviewed_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(review.card_id)
+        .bind(review.session_id)
+        .bind(&review.app)
+        .bind(&review.original_category)
+        .bind(&review.category)
+        .bind(&review.subcategory)
+        .bind(review.reviewed_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_card_reviews_by_app(&self, app: &str) -> Result<Vec<CardReviewRecord>> {
+        let reviews = sqlx::query_as::<_, CardReviewRecord>(
+            "SELECT * FROM card_reviews WHERE app = ? ORDER BY reviewed_at",
+        )
+        .bind(app)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(reviews)
+    }
+
+    async fn insert_goal(&self, goal: &GoalRecord) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO goals (
+                name, metric, target, comparison, target_minutes, schedule, enabled, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&goal.name)
+        .bind(&goal.metric)
+        .bind(&goal.target)
+        .bind(&goal.comparison)
+        .bind(goal.target_minutes)
+        .bind(&goal.schedule)
+        .bind(goal.enabled)
+        .bind(goal.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_id() as i64)
+    }
+
+    async fn update_goal(&self, goal: &GoalRecord) -> Result<()> {
+        let id = goal.id.ok_or_else(|| anyhow::anyhow!("目标缺少 ID"))?;
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE goals
+            SET name = ?, metric = ?, target = ?, comparison = ?, target_minutes = ?,
+                schedule = ?, enabled = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&goal.name)
+        .bind(&goal.metric)
+        .bind(&goal.target)
+        .bind(&goal.comparison)
+        .bind(goal.target_minutes)
+        .bind(&goal.schedule)
+        .bind(goal.enabled)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM goal_results WHERE goal_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_goals(&self) -> Result<Vec<GoalRecord>> {
+        let goals = sqlx::query_as::<_, GoalRecord>("SELECT * FROM goals ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(goals)
+    }
+
+    async fn delete_goal(&self, id: i64) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM goal_results WHERE goal_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM goals WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn save_goal_result(&self, result: &GoalResultRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            REPLACE INTO goal_results (goal_id, date, actual_minutes, passed, evaluated_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(result.goal_id)
+        .bind(&result.date)
+        .bind(result.actual_minutes)
+        .bind(result.passed)
+        .bind(result.evaluated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_goal_results(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<GoalResultRecord>> {
+        let results = sqlx::query_as::<_, GoalResultRecord>(
+            "SELECT * FROM goal_results WHERE date >= ? AND date <= ? ORDER BY goal_id, date",
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
+    async fn delete_goal_results_by_date(&self, date: &str) -> Result<()> {
+        sqlx::query("DELETE FROM goal_results WHERE date = ?")
+            .bind(date)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn insert_focus_block(&self, block: &FocusBlockRecord) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO focus_blocks (
+                label, planned_minutes, started_at, ended_at, status, distraction_count, distractions
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&block.label)
+        .bind(block.planned_minutes)
+        .bind(block.started_at)
+        .bind(block.ended_at)
+        .bind(&block.status)
+        .bind(block.distraction_count)
+        .bind(&block.distractions)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_id() as i64)
+    }
+
+    async fn update_focus_block(&self, block: &FocusBlockRecord) -> Result<()> {
+        let id = block.id.ok_or_else(|| anyhow::anyhow!("专注时段缺少 ID"))?;
+        sqlx::query(
+            r#"
+            UPDATE focus_blocks
+            SET ended_at = ?, status = ?, distraction_count = ?, distractions = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(block.ended_at)
+        .bind(&block.status)
+        .bind(block.distraction_count)
+        .bind(&block.distractions)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_focus_blocks(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<FocusBlockRecord>> {
+        let blocks = sqlx::query_as::<_, FocusBlockRecord>(
+            r#"
+            SELECT * FROM focus_blocks
+            WHERE started_at < ? AND (ended_at IS NULL OR ended_at > ?)
+            ORDER BY started_at
+            "#,
+        )
+        .bind(end)
+        .bind(start)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(blocks)
+    }
+
+    async fn replace_embeddings(&self, session_id: i64, records: &[EmbeddingRecord]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM vectors WHERE session_id = ?")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        for record in records {
+            sqlx::query(
+                r#"
+                INSERT INTO vectors (session_id, owner_kind, owner_id, model, content_hash, vector, updated_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(session_id)
+            .bind(&record.owner_kind)
+            .bind(record.owner_id)
+            .bind(&record.model)
+            .bind(&record.content_hash)
+            .bind(&record.vector)
+            .bind(record.updated_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_embeddings_by_session(&self, session_id: i64) -> Result<Vec<EmbeddingRecord>> {
+        let records = sqlx::query_as::<_, EmbeddingRecord>(
+            r#"
+            SELECT * FROM vectors WHERE session_id = ? ORDER BY owner_kind DESC, owner_id
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn get_embeddings(
+        &self,
+        model: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<EmbeddingRecord>> {
+        let records = sqlx::query_as::<_, EmbeddingRecord>(
+            r#"
+            SELECT v.* FROM vectors v
+            JOIN sessions s ON s.id = v.session_id
+            WHERE v.model = ? AND DATE(s.start_time) >= ? AND DATE(s.start_time) <= ?
+            "#,
+        )
+        .bind(model)
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn search_sessions(
+        &self,
+        query: &str,
+        start_date: &str,
+        end_date: &str,
+        limit: i64,
+    ) -> Result<Vec<SessionSearchHit>> {
+        let terms = search::search_terms(query);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // InnoDB 全文索引缺少中文分词，这里用 LIKE 筛选后在应用层打分、截取片段
+        let mut sql = String::from(
+            r#"
+            SELECT s.id AS session_id, s.title, s.start_time, s.end_time, s.summary,
+                   COALESCE((
+                       SELECT GROUP_CONCAT(CONCAT_WS(' ', c.title, c.summary, c.detailed_summary) SEPARATOR '\n')
+                       FROM timeline_cards c WHERE c.session_id = s.id
+                   ), '') AS cards,
+                   COALESCE((
+                       SELECT GROUP_CONCAT(t.text ORDER BY t.timestamp SEPARATOR '\n')
+                       FROM frame_texts t WHERE t.session_id = s.id
+                   ), '') AS ocr
+            FROM sessions s
+            WHERE DATE(s.start_time) >= ? AND DATE(s.start_time) <= ?
+            "#,
+        );
+        for _ in &terms {
+            sql.push_str(
+                r#" AND (s.title LIKE ? OR s.summary LIKE ? OR EXISTS (
+                    SELECT 1 FROM timeline_cards c WHERE c.session_id = s.id
+                      AND (c.title LIKE ? OR c.summary LIKE ? OR c.detailed_summary LIKE ?)
+                ) OR EXISTS (
+                    SELECT 1 FROM frame_texts t WHERE t.session_id = s.id AND t.text LIKE ?
+                ))"#,
+            );
+        }
+
+        let mut query = sqlx::query_as::<_, search::SearchCandidate>(&sql)
+            .bind(start_date)
+            .bind(end_date);
+        for term in &terms {
+            let pattern = search::like_pattern(term);
+            for _ in 0..6 {
+                query = query.bind(pattern.clone());
+            }
+        }
+        let candidates = query.fetch_all(&self.pool).await?;
+
+        Ok(search::rank_candidates(
+            candidates,
+            &terms,
+            limit.max(0) as usize,
+        ))
+    }
+
+    fn db_type(&self) -> &str {
+        "mariadb"
+    }
+
+    async fn snapshot_to(&self, path: &str) -> Result<()> {
+        // 在同一连接的一致性快照事务中读取全部表
+        let mut conn = self.pool.acquire().await?;
+        sqlx::query("START TRANSACTION WITH CONSISTENT SNAPSHOT")
+            .execute(&mut *conn)
+            .await?;
+
+        let tables: Vec<String> = sqlx::query_scalar(
+            "SELECT CAST(table_name AS CHAR) FROM information_schema.tables WHERE table_schema = DATABASE() AND table_type = 'BASE TABLE' ORDER BY table_name",
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut body = format!("{}\nSET FOREIGN_KEY_CHECKS=0;\n", dump::DUMP_HEADER);
+        for table in &tables {
+            let create: String = sqlx::query(&format!("SHOW CREATE TABLE `{}`", table))
+                .fetch_one(&mut *conn)
+                .await?
+                .try_get(1)?;
+            let columns: Vec<(String, String)> = sqlx::query_as(
+                "SELECT CAST(column_name AS CHAR), CAST(data_type AS CHAR) FROM information_schema.columns WHERE table_schema = DATABASE() AND table_name = ? ORDER BY ordinal_position",
+            )
+            .bind(table)
+            .fetch_all(&mut *conn)
+            .await?;
+            let literals: Vec<String> = columns
+                .iter()
+                .map(|(name, data_type)| dump::column_literal(name, data_type))
+                .collect();
+            let rows: Vec<String> = sqlx::query_scalar(&format!(
+                "SELECT CAST(CONCAT('(', CONCAT_WS(',', {}), ')') AS CHAR CHARACTER SET utf8mb4) FROM `{}`",
+                literals.join(", "),
+                table
+            ))
+            .fetch_all(&mut *conn)
+            .await?;
+
+            body.push_str(&format!("DROP TABLE IF EXISTS `{}`;\n", table));
+            body.push_str(&format!("{};\n", create.replace('\n', " ")));
+            for chunk in rows.chunks(dump::INSERT_BATCH_ROWS) {
+                body.push_str(&format!(
+                    "INSERT INTO `{}` VALUES {};\n",
+                    table,
+                    chunk.join(",")
+                ));
+            }
+        }
+        body.push_str("SET FOREIGN_KEY_CHECKS=1;\n");
+        sqlx::query("COMMIT").execute(&mut *conn).await?;
+
+        tokio::fs::write(path, dump::finish_dump(body)).await?;
+        info!("MariaDB 转储完成: {} 张表", tables.len());
+        Ok(())
+    }
+
+    async fn restore_from_dump(&self, path: &str) -> Result<()> {
+        let content = tokio::fs::read_to_string(path).await?;
+        let statements = dump::verify_dump(&content, dump::DUMP_HEADER)?;
+
+        // 外键检查是会话级设置，全部语句须在同一连接上执行
+        let mut conn = self.pool.acquire().await?;
+        // 转储自带 schema_version 表时随之恢复；旧版转储没有该表，删除后由迁移从初始版本补齐结构
+        conn.execute("DROP TABLE IF EXISTS schema_version").await?;
+        let mut result = Ok(());
+        for statement in &statements {
+            // 以文本协议执行（不预处理），DDL 与批量 INSERT 均可执行
+            if let Err(e) = conn.execute(*statement).await {
+                result = Err(anyhow::anyhow!("执行转储语句失败: {}", e));
+                break;
+            }
+        }
+        conn.execute("SET FOREIGN_KEY_CHECKS=1").await?;
+        result?;
+
+        info!("已从 MariaDB 转储恢复: {} 条语句", statements.len());
+        Ok(())
+    }
+
+    async fn migrate_timezone_to_local(&self) -> Result<(u64, u64, u64, u64, u64, u64)> {
         use chrono::Local;
 
         // 计算时区偏移量（小时）
@@ -1170,3 +2432,422 @@ impl DatabaseRepository for MariaDbRepository {
         ))
     }
 }
+
+/// 结构迁移使用的命名锁
+const MIGRATION_LOCK: &str = "screen_analyzer_migrations";
+
+/// 在已开启的事务中逐条插入时间线卡片
+async fn insert_card_rows(
+    conn: &mut sqlx::mysql::MySqlConnection,
+    cards: &[TimelineCardRecord],
+) -> Result<()> {
+    for card in cards {
+        sqlx::query(
+            r#"
+            INSERT INTO timeline_cards (
+                session_id, llm_call_id, start_time, end_time,
+                category, subcategory, title, summary, detailed_summary,
+                distractions, app_sites, confidence, heuristic, video_preview_path, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+        )
+        .bind(card.session_id)
+        .bind(card.llm_call_id)
+        .bind(&card.start_time)
+        .bind(&card.end_time)
+        .bind(&card.category)
+        .bind(&card.subcategory)
+        .bind(&card.title)
+        .bind(&card.summary)
+        .bind(&card.detailed_summary)
+        .bind(&card.distractions)
+        .bind(&card.app_sites)
+        .bind(card.confidence)
+        .bind(card.heuristic)
+        .bind(&card.video_preview_path)
+        .bind(card.created_at)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// MariaDB 结构迁移（版本号递增；已发布的版本不可修改，结构变更追加新版本）
+fn mariadb_migrations() -> Vec<Migration> {
+    vec![Migration::new(1, "初始表结构").sql([
+        // 创建会话表
+        r#"
+        CREATE TABLE IF NOT EXISTS sessions (
+            id BIGINT PRIMARY KEY AUTO_INCREMENT,
+            start_time DATETIME NOT NULL,
+            end_time DATETIME NOT NULL,
+            title TEXT NOT NULL,
+            summary TEXT NOT NULL,
+            video_path TEXT,
+            tags TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            device_name VARCHAR(255),
+            device_type VARCHAR(50)
+        )
+        "#,
+        // 创建帧表
+        r#"
+        CREATE TABLE IF NOT EXISTS frames (
+            id BIGINT PRIMARY KEY AUTO_INCREMENT,
+            session_id BIGINT NOT NULL,
+            timestamp DATETIME NOT NULL,
+            file_path TEXT NOT NULL,
+            app_name TEXT,
+            window_title TEXT,
+            process_path TEXT,
+            domain TEXT,
+            screen_id INT NOT NULL DEFAULT 0,
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        )
+        "#,
+        // 创建索引
+        "CREATE INDEX IF NOT EXISTS idx_sessions_start_time ON sessions(start_time)",
+        "CREATE INDEX IF NOT EXISTS idx_frames_session_id ON frames(session_id)",
+        "CREATE INDEX IF NOT EXISTS idx_sessions_start_end ON sessions(start_time, end_time)",
+        "CREATE INDEX IF NOT EXISTS idx_frames_session_timestamp ON frames(session_id, timestamp)",
+        // 创建LLM调用记录表
+        r#"
+        CREATE TABLE IF NOT EXISTS llm_calls (
+            id BIGINT PRIMARY KEY AUTO_INCREMENT,
+            session_id BIGINT,
+            provider VARCHAR(100) NOT NULL,
+            model VARCHAR(100) NOT NULL,
+            call_type VARCHAR(100) NOT NULL,
+            request_headers TEXT NOT NULL,
+            request_body TEXT NOT NULL,
+            response_headers TEXT,
+            response_body TEXT,
+            status_code INT,
+            error_message TEXT,
+            latency_ms BIGINT,
+            token_usage TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        )
+        "#,
+        // 创建视频分段表
+        r#"
+        CREATE TABLE IF NOT EXISTS video_segments (
+            id BIGINT PRIMARY KEY AUTO_INCREMENT,
+            session_id BIGINT NOT NULL,
+            llm_call_id BIGINT,
+            start_timestamp VARCHAR(50) NOT NULL,
+            end_timestamp VARCHAR(50) NOT NULL,
+            description TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+            FOREIGN KEY (llm_call_id) REFERENCES llm_calls(id) ON DELETE SET NULL
+        )
+        "#,
+        // 创建时间线卡片表
+        r#"
+        CREATE TABLE IF NOT EXISTS timeline_cards (
+            id BIGINT PRIMARY KEY AUTO_INCREMENT,
+            session_id BIGINT NOT NULL,
+            llm_call_id BIGINT,
+            start_time VARCHAR(50) NOT NULL,
+            end_time VARCHAR(50) NOT NULL,
+            category VARCHAR(100) NOT NULL,
+            subcategory VARCHAR(100) NOT NULL,
+            title TEXT NOT NULL,
+            summary TEXT NOT NULL,
+            detailed_summary TEXT NOT NULL,
+            distractions TEXT,
+            app_sites TEXT NOT NULL,
+            confidence DOUBLE,
+            heuristic BOOLEAN NOT NULL DEFAULT FALSE,
+            video_preview_path TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+            FOREIGN KEY (llm_call_id) REFERENCES llm_calls(id) ON DELETE SET NULL
+        )
+        "#,
+        // 创建每日总结表（缓存）
+        r#"
+        CREATE TABLE IF NOT EXISTS day_summaries (
+            id BIGINT PRIMARY KEY AUTO_INCREMENT,
+            date DATE NOT NULL UNIQUE,
+            summary_text TEXT NOT NULL,
+            device_stats TEXT NOT NULL,
+            parallel_work TEXT NOT NULL,
+            usage_patterns TEXT NOT NULL,
+            active_device_count INT NOT NULL,
+            llm_call_id BIGINT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+            FOREIGN KEY (llm_call_id) REFERENCES llm_calls(id) ON DELETE SET NULL
+        )
+        "#,
+        // 创建每日专注度指标表（缓存，时间线卡片变化时按日期失效）
+        r#"
+        CREATE TABLE IF NOT EXISTS daily_focus_metrics (
+            date DATE PRIMARY KEY,
+            total_minutes BIGINT NOT NULL,
+            work_minutes BIGINT NOT NULL,
+            learning_minutes BIGINT NOT NULL,
+            communication_minutes BIGINT NOT NULL,
+            personal_minutes BIGINT NOT NULL,
+            idle_minutes BIGINT NOT NULL,
+            other_minutes BIGINT NOT NULL,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+        )
+        "#,
+        // 创建导出历史表
+        r#"
+        CREATE TABLE IF NOT EXISTS export_history (
+            id BIGINT PRIMARY KEY AUTO_INCREMENT,
+            kind VARCHAR(32) NOT NULL,
+            target VARCHAR(32) NOT NULL,
+            request TEXT NOT NULL,
+            success BOOLEAN NOT NULL,
+            message TEXT NOT NULL,
+            written_paths LONGTEXT NOT NULL,
+            warnings TEXT NOT NULL,
+            failed_sessions TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            INDEX idx_export_history_created_at (created_at)
+        )
+        "#,
+        // 创建项目归属规则表
+        r#"
+        CREATE TABLE IF NOT EXISTS project_rules (
+            id BIGINT PRIMARY KEY AUTO_INCREMENT,
+            project VARCHAR(255) NOT NULL,
+            match_field VARCHAR(32) NOT NULL,
+            pattern VARCHAR(255) NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        // 创建自定义类别表
+        r#"
+        CREATE TABLE IF NOT EXISTS custom_categories (
+            id BIGINT PRIMARY KEY AUTO_INCREMENT,
+            name VARCHAR(255) NOT NULL UNIQUE,
+            bucket VARCHAR(32) NOT NULL,
+            description TEXT NOT NULL,
+            subcategories TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        // 创建预分类规则表
+        r#"
+        CREATE TABLE IF NOT EXISTS classification_rules (
+            id BIGINT PRIMARY KEY AUTO_INCREMENT,
+            match_field VARCHAR(32) NOT NULL,
+            pattern VARCHAR(512) NOT NULL,
+            category VARCHAR(255) NOT NULL,
+            subcategory VARCHAR(255) NOT NULL DEFAULT '',
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        // 创建 Notion 同步记录表
+        r#"
+        CREATE TABLE IF NOT EXISTS notion_sync (
+            kind VARCHAR(16) NOT NULL,
+            local_key VARCHAR(64) NOT NULL,
+            date VARCHAR(10) NOT NULL,
+            page_id VARCHAR(64) NOT NULL,
+            synced_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (kind, local_key),
+            INDEX idx_notion_sync_date (kind, date)
+        )
+        "#,
+        // 创建日历事件表
+        r#"
+        CREATE TABLE IF NOT EXISTS calendar_events (
+            id BIGINT PRIMARY KEY AUTO_INCREMENT,
+            event_id VARCHAR(255) NOT NULL,
+            date VARCHAR(10) NOT NULL,
+            title TEXT NOT NULL,
+            location TEXT NOT NULL,
+            start_time DATETIME NOT NULL,
+            end_time DATETIME NOT NULL,
+            synced_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            INDEX idx_calendar_events_date (date)
+        )
+        "#,
+        // 创建工单关联表
+        r#"
+        CREATE TABLE IF NOT EXISTS issue_links (
+            session_id BIGINT NOT NULL,
+            provider VARCHAR(16) NOT NULL,
+            issue_key VARCHAR(255) NOT NULL,
+            title TEXT NOT NULL,
+            state VARCHAR(64) NOT NULL,
+            url TEXT NOT NULL,
+            resolved_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (session_id, provider, issue_key),
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        )
+        "#,
+        // 创建工时同步记录表
+        r#"
+        CREATE TABLE IF NOT EXISTS time_entry_sync (
+            provider VARCHAR(16) NOT NULL,
+            session_id BIGINT NOT NULL,
+            entry_id VARCHAR(64) NOT NULL,
+            synced_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (provider, session_id),
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        )
+        "#,
+        // 创建会话备注表
+        r#"
+        CREATE TABLE IF NOT EXISTS session_notes (
+            session_id BIGINT PRIMARY KEY,
+            note TEXT NOT NULL,
+            tags TEXT NOT NULL,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        )
+        "#,
+        // 创建语义向量表
+        r#"
+        CREATE TABLE IF NOT EXISTS vectors (
+            session_id BIGINT NOT NULL,
+            owner_kind VARCHAR(16) NOT NULL,
+            owner_id BIGINT NOT NULL,
+            model VARCHAR(128) NOT NULL,
+            content_hash VARCHAR(64) NOT NULL,
+            vector MEDIUMBLOB NOT NULL,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (session_id, owner_kind, owner_id),
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        )
+        "#,
+        // 旧版 frames 表补充前台窗口、浏览器域名与显示器序号字段
+        "ALTER TABLE frames ADD COLUMN IF NOT EXISTS app_name TEXT, \
+         ADD COLUMN IF NOT EXISTS window_title TEXT, \
+         ADD COLUMN IF NOT EXISTS process_path TEXT, \
+         ADD COLUMN IF NOT EXISTS domain TEXT, \
+         ADD COLUMN IF NOT EXISTS screen_id INT NOT NULL DEFAULT 0",
+        // 创建前台应用使用时长表
+        r#"
+        CREATE TABLE IF NOT EXISTS app_usage (
+            session_id BIGINT NOT NULL,
+            app_name VARCHAR(255) NOT NULL,
+            process_path TEXT,
+            window_title TEXT NOT NULL,
+            seconds BIGINT NOT NULL,
+            PRIMARY KEY (session_id, app_name),
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        )
+        "#,
+        // 创建网站域名访问时长表
+        r#"
+        CREATE TABLE IF NOT EXISTS domain_usage (
+            session_id BIGINT NOT NULL,
+            domain VARCHAR(255) NOT NULL,
+            seconds BIGINT NOT NULL,
+            PRIMARY KEY (session_id, domain),
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        )
+        "#,
+        // 创建帧 OCR 文字表
+        r#"
+        CREATE TABLE IF NOT EXISTS frame_texts (
+            id BIGINT PRIMARY KEY AUTO_INCREMENT,
+            session_id BIGINT NOT NULL,
+            timestamp DATETIME NOT NULL,
+            text MEDIUMTEXT NOT NULL,
+            INDEX idx_frame_texts_session_timestamp (session_id, timestamp),
+            FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+        )
+        "#,
+        // 创建 LLM 响应缓存表（不关联会话，会话删除后重新分析相同画面仍可复用）
+        r#"
+        CREATE TABLE IF NOT EXISTS llm_cache (
+            cache_key VARCHAR(64) PRIMARY KEY,
+            task VARCHAR(32) NOT NULL,
+            provider VARCHAR(32) NOT NULL,
+            model VARCHAR(128) NOT NULL,
+            response MEDIUMTEXT NOT NULL,
+            created_at DATETIME NOT NULL,
+            INDEX idx_llm_cache_created_at (created_at)
+        )
+        "#,
+        // 创建 LLM 解析失败记录表（不关联会话外键，会话删除后仍可排查）
+        r#"
+        CREATE TABLE IF NOT EXISTS llm_parse_failures (
+            id BIGINT PRIMARY KEY AUTO_INCREMENT,
+            session_id BIGINT,
+            provider VARCHAR(32) NOT NULL,
+            model VARCHAR(128) NOT NULL,
+            task VARCHAR(32) NOT NULL,
+            raw_response MEDIUMTEXT NOT NULL,
+            repaired_response MEDIUMTEXT,
+            errors TEXT NOT NULL,
+            repaired BOOLEAN NOT NULL DEFAULT FALSE,
+            created_at DATETIME NOT NULL,
+            INDEX idx_llm_parse_failures_created_at (created_at)
+        )
+        "#,
+        // 旧版 timeline_cards 表补充置信度与启发式标记字段
+        "ALTER TABLE timeline_cards ADD COLUMN IF NOT EXISTS confidence DOUBLE",
+        "ALTER TABLE timeline_cards ADD COLUMN IF NOT EXISTS heuristic BOOLEAN NOT NULL DEFAULT FALSE",
+        // 创建时间线卡片复核记录表
+        r#"
+        CREATE TABLE IF NOT EXISTS card_reviews (
+            card_id BIGINT PRIMARY KEY,
+            session_id BIGINT NOT NULL,
+            app VARCHAR(255) NOT NULL,
+            original_category VARCHAR(100) NOT NULL,
+            category VARCHAR(100) NOT NULL,
+            subcategory VARCHAR(100) NOT NULL DEFAULT '',
+            reviewed_at DATETIME NOT NULL,
+            INDEX idx_card_reviews_app (app),
+            FOREIGN KEY (card_id) REFERENCES timeline_cards(id) ON DELETE CASCADE
+        )
+        "#,
+        // 创建目标表与目标评估结果表
+        r#"
+        CREATE TABLE IF NOT EXISTS goals (
+            id BIGINT PRIMARY KEY AUTO_INCREMENT,
+            name VARCHAR(255) NOT NULL,
+            metric VARCHAR(32) NOT NULL,
+            target VARCHAR(512) NOT NULL DEFAULT '',
+            comparison VARCHAR(16) NOT NULL,
+            target_minutes BIGINT NOT NULL,
+            schedule VARCHAR(16) NOT NULL DEFAULT 'daily',
+            enabled BOOLEAN NOT NULL DEFAULT TRUE,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS goal_results (
+            goal_id BIGINT NOT NULL,
+            date VARCHAR(10) NOT NULL,
+            actual_minutes BIGINT NOT NULL,
+            passed BOOLEAN NOT NULL,
+            evaluated_at DATETIME NOT NULL,
+            PRIMARY KEY (goal_id, date),
+            INDEX idx_goal_results_date (date)
+        )
+        "#,
+        // 创建专注时段表
+        r#"
+        CREATE TABLE IF NOT EXISTS focus_blocks (
+            id BIGINT PRIMARY KEY AUTO_INCREMENT,
+            label VARCHAR(255) NOT NULL DEFAULT '',
+            planned_minutes BIGINT NOT NULL,
+            started_at DATETIME NOT NULL,
+            ended_at DATETIME NULL,
+            status VARCHAR(16) NOT NULL DEFAULT 'running',
+            distraction_count BIGINT NOT NULL DEFAULT 0,
+            distractions TEXT NOT NULL,
+            INDEX idx_focus_blocks_started_at (started_at)
+        )
+        "#,
+        // 创建额外的索引
+        "CREATE INDEX IF NOT EXISTS idx_llm_calls_session_id ON llm_calls(session_id)",
+        "CREATE INDEX IF NOT EXISTS idx_llm_calls_created_at ON llm_calls(created_at)",
+        "CREATE INDEX IF NOT EXISTS idx_video_segments_session_id ON video_segments(session_id)",
+        "CREATE INDEX IF NOT EXISTS idx_timeline_cards_session_id ON timeline_cards(session_id)",
+    ])]
+}
@@ -1,6 +1,7 @@
 // Repository 抽象层 - 定义数据库操作接口
 
 pub mod mariadb;
+pub mod postgres;
 pub mod sqlite;
 
 use super::models::*;
@@ -19,6 +20,13 @@ pub trait DatabaseRepository: Send + Sync {
     /// 批量插入会话
     async fn insert_sessions(&self, sessions: &[Session]) -> Result<Vec<i64>>;
 
+    /// 在一个事务中插入会话及其时间线卡片（卡片的 session_id 以新会话为准），返回会话 ID
+    async fn insert_session_with_cards(
+        &self,
+        session: &Session,
+        cards: &[TimelineCardRecord],
+    ) -> Result<i64>;
+
     /// 获取会话详情
     async fn get_session(&self, session_id: i64) -> Result<Session>;
 
@@ -44,9 +52,28 @@ pub trait DatabaseRepository: Send + Sync {
     /// 更新会话标签
     async fn update_session_tags(&self, session_id: i64, tags: &str) -> Result<()>;
 
+    /// 更新会话开始与结束时间（拆分、合并会话时）
+    async fn update_session_times(
+        &self,
+        session_id: i64,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<()>;
+
+    /// 在一个事务中更新会话的标题、总结、标签与起止时间，并替换其全部时间线卡片（局域网同步覆盖会话时）
+    async fn replace_session_content(
+        &self,
+        session_id: i64,
+        session: &Session,
+        cards: &[TimelineCardRecord],
+    ) -> Result<()>;
+
     /// 更新会话视频路径
     async fn update_session_video_path(&self, session_id: i64, video_path: &str) -> Result<()>;
 
+    /// 清除会话的视频路径（视频文件已删除）
+    async fn clear_session_video_path(&self, session_id: i64) -> Result<()>;
+
     /// 更新所有会话的设备信息
     async fn update_device_info_for_all_sessions(&self) -> Result<u64>;
 
@@ -73,11 +100,41 @@ pub trait DatabaseRepository: Send + Sync {
     /// 删除会话的所有帧
     async fn delete_frames_by_session(&self, session_id: i64) -> Result<()>;
 
+    /// 删除会话中的指定帧
+    async fn delete_frames(&self, session_id: i64, frame_ids: &[i64]) -> Result<()>;
+
+    /// 更新帧的文件路径（旧截图转码为其他格式后）
+    async fn update_frame_path(
+        &self,
+        session_id: i64,
+        frame_id: i64,
+        file_path: &str,
+    ) -> Result<()>;
+
+    /// 将会话中不早于 `since` 的帧与 OCR 文字移到另一会话（`since` 为空时全部移动），返回移动的帧数
+    async fn move_frames(
+        &self,
+        from_session_id: i64,
+        to_session_id: i64,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<u64>;
+
     // ========== 活动统计 ==========
 
     /// 获取指定日期范围的活动统计
     async fn get_activities(&self, start_date: &str, end_date: &str) -> Result<Vec<Activity>>;
 
+    /// 获取指定日期范围内某台设备的活动统计
+    async fn get_device_activities(
+        &self,
+        start_date: &str,
+        end_date: &str,
+        device_name: &str,
+    ) -> Result<Vec<Activity>>;
+
+    /// 获取出现过的设备名称（按名称排序）
+    async fn get_device_names(&self) -> Result<Vec<String>>;
+
     // ========== LLM 调用记录 ==========
 
     /// 插入 LLM 调用记录
@@ -129,6 +186,17 @@ pub trait DatabaseRepository: Send + Sync {
     /// 删除会话的时间线卡片
     async fn delete_timeline_cards_by_session(&self, session_id: i64) -> Result<()>;
 
+    /// 更新时间线卡片标题
+    async fn update_timeline_card_title(&self, id: i64, title: &str) -> Result<()>;
+
+    /// 更新时间线卡片的类别与子类别
+    async fn update_timeline_card_category(
+        &self,
+        id: i64,
+        category: &str,
+        subcategory: &str,
+    ) -> Result<()>;
+
     // ========== 统计信息 ==========
 
     /// 获取数据库统计信息 (会话数, 帧数, 数据库大小)
@@ -148,14 +216,300 @@ pub trait DatabaseRepository: Send + Sync {
     /// 删除某一天的总结
     async fn delete_day_summary(&self, date: &str) -> Result<()>;
 
+    // ========== 每日专注度指标缓存 ==========
+
+    /// 保存某一天的专注度指标（插入或更新）
+    async fn save_daily_focus_metrics(
+        &self,
+        date: &str,
+        metrics: &DailyFocusMetricsRecord,
+    ) -> Result<()>;
+
+    /// 获取日期范围内（含首尾）已缓存的专注度指标
+    async fn get_daily_focus_metrics(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DailyFocusMetricsRecord>>;
+
+    /// 删除某一天的专注度指标缓存
+    async fn delete_daily_focus_metrics(&self, date: &str) -> Result<()>;
+
+    // ========== 导出历史 ==========
+
+    /// 插入导出历史记录，返回记录ID
+    async fn insert_export_history(&self, record: &ExportHistoryRecord) -> Result<i64>;
+
+    /// 获取最近的导出历史（按时间倒序）
+    async fn get_export_history(&self, limit: i64) -> Result<Vec<ExportHistoryRecord>>;
+
+    /// 获取单条导出历史
+    async fn get_export_history_entry(&self, id: i64) -> Result<ExportHistoryRecord>;
+
+    // ========== 项目规则 ==========
+
+    /// 插入项目归属规则，返回规则ID
+    async fn insert_project_rule(&self, rule: &ProjectRuleRecord) -> Result<i64>;
+
+    /// 获取全部项目归属规则（按创建顺序，先匹配者优先）
+    async fn get_project_rules(&self) -> Result<Vec<ProjectRuleRecord>>;
+
+    /// 删除项目归属规则
+    async fn delete_project_rule(&self, id: i64) -> Result<()>;
+
+    // ========== 自定义类别 ==========
+
+    /// 插入自定义类别，返回类别ID
+    async fn insert_custom_category(&self, category: &CustomCategoryRecord) -> Result<i64>;
+
+    /// 获取全部自定义类别（按创建顺序）
+    async fn get_custom_categories(&self) -> Result<Vec<CustomCategoryRecord>>;
+
+    /// 删除自定义类别
+    async fn delete_custom_category(&self, id: i64) -> Result<()>;
+
+    // ========== 预分类规则 ==========
+
+    /// 插入预分类规则，返回规则ID
+    async fn insert_classification_rule(&self, rule: &ClassificationRuleRecord) -> Result<i64>;
+
+    /// 获取全部预分类规则（按创建顺序，先匹配者优先）
+    async fn get_classification_rules(&self) -> Result<Vec<ClassificationRuleRecord>>;
+
+    /// 删除预分类规则
+    async fn delete_classification_rule(&self, id: i64) -> Result<()>;
+
+    // ========== Notion 同步记录 ==========
+
+    /// 保存 Notion 同步记录（同一 kind + local_key 覆盖旧记录）
+    async fn save_notion_sync(&self, record: &NotionSyncRecord) -> Result<()>;
+
+    /// 获取单条 Notion 同步记录
+    async fn get_notion_sync(
+        &self,
+        kind: &str,
+        local_key: &str,
+    ) -> Result<Option<NotionSyncRecord>>;
+
+    /// 获取某一天某类实体的全部 Notion 同步记录
+    async fn get_notion_syncs_by_date(
+        &self,
+        kind: &str,
+        date: &str,
+    ) -> Result<Vec<NotionSyncRecord>>;
+
+    /// 删除 Notion 同步记录
+    async fn delete_notion_sync(&self, kind: &str, local_key: &str) -> Result<()>;
+
+    // ========== 日历事件 ==========
+
+    /// 替换某一天的日历事件（先删除当天旧记录）
+    async fn replace_calendar_events(
+        &self,
+        date: &str,
+        events: &[CalendarEventRecord],
+    ) -> Result<()>;
+
+    /// 获取某一天的日历事件（按开始时间排序）
+    async fn get_calendar_events_by_date(&self, date: &str) -> Result<Vec<CalendarEventRecord>>;
+
+    // ========== 工单关联 ==========
+
+    /// 替换会话关联的工单（先删除该会话旧记录）
+    async fn replace_issue_links(&self, session_id: i64, links: &[IssueLinkRecord]) -> Result<()>;
+
+    /// 获取会话关联的工单
+    async fn get_issue_links_by_session(&self, session_id: i64) -> Result<Vec<IssueLinkRecord>>;
+
+    // ========== 工时同步记录 ==========
+
+    /// 保存工时同步记录（同一 provider + session_id 覆盖旧记录）
+    async fn save_time_entry_sync(&self, record: &TimeEntrySyncRecord) -> Result<()>;
+
+    /// 获取会话在某个工时服务中的同步记录
+    async fn get_time_entry_sync(
+        &self,
+        provider: &str,
+        session_id: i64,
+    ) -> Result<Option<TimeEntrySyncRecord>>;
+
+    // ========== 会话备注 ==========
+
+    /// 保存会话备注（同一会话覆盖旧备注）
+    async fn save_session_note(&self, record: &SessionNoteRecord) -> Result<()>;
+
+    /// 获取会话备注
+    async fn get_session_note(&self, session_id: i64) -> Result<Option<SessionNoteRecord>>;
+
+    /// 删除会话备注
+    async fn delete_session_note(&self, session_id: i64) -> Result<()>;
+
+    // ========== 前台应用使用时长 ==========
+
+    /// 替换会话的应用使用时长
+    async fn replace_app_usage(&self, session_id: i64, records: &[AppUsageRecord]) -> Result<()>;
+
+    /// 获取会话的应用使用时长（按时长降序）
+    async fn get_app_usage_by_session(&self, session_id: i64) -> Result<Vec<AppUsageRecord>>;
+
+    /// 按应用汇总日期范围内（按会话开始日期，含首尾）的使用时长（按时长降序）
+    async fn get_app_usage_summary(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<AppUsageSummary>>;
+
+    /// 按日期与应用汇总日期范围内（按会话开始日期，含首尾）的使用时长（按日期升序、时长降序）
+    async fn get_daily_app_usage(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DailyAppUsage>>;
+
+    // ========== 网站域名访问时长 ==========
+
+    /// 替换会话的域名访问时长
+    async fn replace_domain_usage(
+        &self,
+        session_id: i64,
+        records: &[DomainUsageRecord],
+    ) -> Result<()>;
+
+    /// 获取会话的域名访问时长（按时长降序）
+    async fn get_domain_usage_by_session(&self, session_id: i64) -> Result<Vec<DomainUsageRecord>>;
+
+    /// 按域名汇总日期范围内（按会话开始日期，含首尾）的访问时长（按时长降序）
+    async fn get_domain_usage_summary(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DomainUsageSummary>>;
+
+    // ========== 帧 OCR 文字 ==========
+
+    /// 批量插入帧 OCR 文字
+    async fn insert_frame_texts(&self, texts: &[FrameText]) -> Result<()>;
+
+    /// 获取会话的帧 OCR 文字（按时间排序）
+    async fn get_frame_texts_by_session(&self, session_id: i64) -> Result<Vec<FrameText>>;
+
+    // ========== LLM 响应缓存 ==========
+
+    /// 按键读取缓存的 LLM 响应
+    async fn get_llm_cache(&self, cache_key: &str) -> Result<Option<LlmCacheEntry>>;
+
+    /// 写入 LLM 响应缓存（相同键覆盖）
+    async fn save_llm_cache(&self, entry: &LlmCacheEntry) -> Result<()>;
+
+    /// 删除早于指定时间的缓存（为空时全部删除），返回删除条数
+    async fn prune_llm_cache(&self, before: Option<DateTime<Utc>>) -> Result<u64>;
+
+    // ========== LLM 解析失败记录 ==========
+
+    /// 保存未通过结构校验的 LLM 响应
+    async fn insert_llm_parse_failure(&self, failure: &LlmParseFailure) -> Result<i64>;
+
+    /// 获取最近的解析失败记录
+    async fn get_llm_parse_failures(&self, limit: i64) -> Result<Vec<LlmParseFailure>>;
+
+    // ========== 时间线卡片复核 ==========
+
+    /// 按 ID 获取时间线卡片
+    async fn get_timeline_card(&self, id: i64) -> Result<Option<TimelineCardRecord>>;
+
+    /// 获取置信度低于阈值且尚未复核的卡片（空闲卡片除外，按开始时间倒序）
+    async fn get_review_queue(&self, threshold: f64, limit: i64)
+        -> Result<Vec<TimelineCardRecord>>;
+
+    /// 保存复核记录（同一卡片覆盖）
+    async fn save_card_review(&self, review: &CardReviewRecord) -> Result<()>;
+
+    /// 获取同一应用或网站的复核记录
+    async fn get_card_reviews_by_app(&self, app: &str) -> Result<Vec<CardReviewRecord>>;
+
+    // ========== 目标 ==========
+
+    /// 插入目标，返回目标ID
+    async fn insert_goal(&self, goal: &GoalRecord) -> Result<i64>;
+
+    /// 更新目标（同时清除该目标已保存的评估结果）
+    async fn update_goal(&self, goal: &GoalRecord) -> Result<()>;
+
+    /// 获取全部目标（按创建顺序）
+    async fn get_goals(&self) -> Result<Vec<GoalRecord>>;
+
+    /// 删除目标及其评估结果
+    async fn delete_goal(&self, id: i64) -> Result<()>;
+
+    /// 保存目标的单日评估结果（同一目标同一天覆盖）
+    async fn save_goal_result(&self, result: &GoalResultRecord) -> Result<()>;
+
+    /// 获取日期范围内（含首尾）全部目标的评估结果
+    async fn get_goal_results(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<GoalResultRecord>>;
+
+    /// 删除某天全部目标的评估结果（时间线卡片变化后重新评估）
+    async fn delete_goal_results_by_date(&self, date: &str) -> Result<()>;
+
+    // ========== 专注时段 ==========
+
+    /// 插入专注时段，返回时段ID
+    async fn insert_focus_block(&self, block: &FocusBlockRecord) -> Result<i64>;
+
+    /// 更新专注时段的结束时间、状态与干扰记录
+    async fn update_focus_block(&self, block: &FocusBlockRecord) -> Result<()>;
+
+    /// 获取与时间范围有重叠的专注时段（按开始时间排序，进行中的时段视为未结束）
+    async fn get_focus_blocks(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<FocusBlockRecord>>;
+
+    // ========== 语义向量 ==========
+
+    /// 替换会话的向量（先删除该会话旧记录）
+    async fn replace_embeddings(&self, session_id: i64, records: &[EmbeddingRecord]) -> Result<()>;
+
+    /// 获取会话的向量
+    async fn get_embeddings_by_session(&self, session_id: i64) -> Result<Vec<EmbeddingRecord>>;
+
+    /// 获取指定模型在日期范围内（按会话开始日期，含首尾）的全部向量
+    async fn get_embeddings(
+        &self,
+        model: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<EmbeddingRecord>>;
+
+    // ========== 全文搜索 ==========
+
+    /// 搜索会话标题、总结与时间线卡片文本，按相关度排序（日期为 YYYY-MM-DD，含首尾）
+    async fn search_sessions(
+        &self,
+        query: &str,
+        start_date: &str,
+        end_date: &str,
+        limit: i64,
+    ) -> Result<Vec<SessionSearchHit>>;
+
     // ========== 数据库初始化和元数据 ==========
 
-    /// 初始化数据库表结构
-    async fn initialize_tables(&self) -> Result<()>;
+    /// 执行尚未应用的结构迁移（建表与字段升级均由带版本号的迁移完成）
+    async fn run_migrations(&self) -> Result<()>;
 
     /// 获取数据库类型标识
     fn db_type(&self) -> &str;
 
+    /// 将数据库的一致性快照写入指定文件（SQLite 为数据库文件，MariaDB / PostgreSQL 为 SQL 转储）
+    async fn snapshot_to(&self, path: &str) -> Result<()>;
+
+    /// 从 `snapshot_to` 生成的 SQL 转储恢复（MariaDB / PostgreSQL，会覆盖已有数据）
+    async fn restore_from_dump(&self, path: &str) -> Result<()>;
+
     /// 迁移时间字段：将 UTC 时间转换为本地时间格式存储
     ///
     /// 此方法用于将旧的 UTC 时间数据迁移为本地时间格式。
@@ -0,0 +1,3062 @@
+// PostgreSQL 数据库实现
+
+use super::DatabaseRepository;
+use crate::storage::config::get_device_info;
+use crate::storage::dump;
+use crate::storage::migrations::{self, Migration};
+use crate::storage::models::*;
+use crate::storage::search;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
+use sqlx::{Executor, Row};
+use tracing::info;
+
+/// 应用使用的全部表（按外键依赖排序，转储与恢复按此顺序进行）
+const TABLES: &[&str] = &[
+    "sessions",
+    "frames",
+    "llm_calls",
+    "video_segments",
+    "timeline_cards",
+    "day_summaries",
+    "daily_focus_metrics",
+    "export_history",
+    "project_rules",
+    "custom_categories",
+    "classification_rules",
+    "notion_sync",
+    "calendar_events",
+    "issue_links",
+    "time_entry_sync",
+    "session_notes",
+    "vectors",
+    "frame_texts",
+    "llm_cache",
+    "llm_parse_failures",
+    "card_reviews",
+    "goals",
+    "goal_results",
+    "focus_blocks",
+    "app_usage",
+    "domain_usage",
+];
+
+/// PostgreSQL 数据库实现
+pub struct PostgresRepository {
+    pool: PgPool,
+}
+
+impl PostgresRepository {
+    /// 创建新的 PostgreSQL 数据库连接
+    pub async fn new(
+        host: &str,
+        port: u16,
+        database: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<Self> {
+        info!(
+            "初始化 PostgreSQL 数据库: {}@{}:{}/{}",
+            username, host, port, database
+        );
+
+        // 会话时区固定为 UTC：本应用把本地时间按 UTC 标记存储，
+        // 这样 TIMESTAMPTZ 读写与按日期截取都不会再被服务器时区偏移
+        let options = PgConnectOptions::new()
+            .host(host)
+            .port(port)
+            .username(username)
+            .password(password)
+            .options([("timezone", "UTC")]);
+
+        // 先连接到维护库 postgres，检查并创建数据库
+        info!("连接到 PostgreSQL 服务器检查数据库是否存在...");
+        let server_pool = PgPoolOptions::new()
+            .max_connections(1)
+            .acquire_timeout(std::time::Duration::from_secs(30))
+            .connect_with(options.clone().database("postgres"))
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "连接 PostgreSQL 服务器失败 ({}:{}): {}\n\n请检查：\n1. PostgreSQL 服务是否已启动\n2. 网络连接是否正常\n3. 防火墙是否阻止了端口 {}\n4. pg_hba.conf 是否允许本机连接",
+                    host, port, e, port
+                )
+            })?;
+
+        // 检查数据库是否存在
+        let db_exists: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM pg_database WHERE datname = $1")
+                .bind(database)
+                .fetch_one(&server_pool)
+                .await?;
+
+        if db_exists == 0 {
+            info!("数据库 '{}' 不存在，正在创建...", database);
+            // 基于 template0 创建，避免 template1 为其他编码时无法指定 UTF8
+            sqlx::query(&format!(
+                "CREATE DATABASE \"{}\" TEMPLATE template0 ENCODING 'UTF8'",
+                database.replace('"', "\"\"")
+            ))
+            .execute(&server_pool)
+            .await?;
+            info!("数据库 '{}' 创建成功", database);
+        } else {
+            info!("数据库 '{}' 已存在", database);
+        }
+
+        // 关闭临时连接
+        server_pool.close().await;
+
+        // 创建连接池
+        info!("创建 PostgreSQL 连接池...");
+        let pool = PgPoolOptions::new()
+            .max_connections(20)
+            .min_connections(2)
+            .idle_timeout(std::time::Duration::from_secs(180))
+            .max_lifetime(std::time::Duration::from_secs(1800))
+            .acquire_timeout(std::time::Duration::from_secs(30))
+            .connect_with(options.database(database))
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "创建 PostgreSQL 连接池失败 ({}:{}/{}): {}",
+                    host,
+                    port,
+                    database,
+                    e
+                )
+            })?;
+
+        info!("PostgreSQL 连接池创建成功");
+
+        let repo = Self { pool };
+
+        // 执行结构迁移（新建数据库时即创建全部表）
+        repo.run_migrations().await?;
+
+        Ok(repo)
+    }
+
+    /// 获取连接池引用
+    pub fn get_pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+#[async_trait]
+impl DatabaseRepository for PostgresRepository {
+    // ========== 会话操作 ==========
+
+    async fn insert_session(&self, session: &Session) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO sessions (start_time, end_time, title, summary, video_path, tags, device_name, device_type)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id
+        "#,
+        )
+        .bind(session.start_time)
+        .bind(session.end_time)
+        .bind(&session.title)
+        .bind(&session.summary)
+        .bind(&session.video_path)
+        .bind(&session.tags)
+        .bind(&session.device_name)
+        .bind(&session.device_type)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn insert_session_with_cards(
+        &self,
+        session: &Session,
+        cards: &[TimelineCardRecord],
+    ) -> Result<i64> {
+        let mut tx = self.pool.begin().await?;
+
+        let session_id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO sessions (start_time, end_time, title, summary, video_path, tags, device_name, device_type)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id
+        "#,
+        )
+        .bind(session.start_time)
+        .bind(session.end_time)
+        .bind(&session.title)
+        .bind(&session.summary)
+        .bind(&session.video_path)
+        .bind(&session.tags)
+        .bind(&session.device_name)
+        .bind(&session.device_type)
+        .fetch_one(&mut *tx)
+        .await?;
+        let cards: Vec<TimelineCardRecord> = cards
+            .iter()
+            .map(|card| TimelineCardRecord {
+                session_id,
+                ..card.clone()
+            })
+            .collect();
+        insert_card_rows(&mut tx, &cards).await?;
+
+        tx.commit().await?;
+        Ok(session_id)
+    }
+
+    async fn insert_sessions(&self, sessions: &[Session]) -> Result<Vec<i64>> {
+        let mut ids = Vec::new();
+        let mut tx = self.pool.begin().await?;
+
+        for session in sessions {
+            let id: i64 = sqlx::query_scalar(
+                r#"
+                INSERT INTO sessions (start_time, end_time, title, summary, video_path, tags, device_name, device_type)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                RETURNING id
+            "#,
+            )
+            .bind(session.start_time)
+            .bind(session.end_time)
+            .bind(&session.title)
+            .bind(&session.summary)
+            .bind(&session.video_path)
+            .bind(&session.tags)
+            .bind(&session.device_name)
+            .bind(&session.device_type)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            ids.push(id);
+        }
+
+        tx.commit().await?;
+        Ok(ids)
+    }
+
+    async fn get_session(&self, session_id: i64) -> Result<Session> {
+        let session = sqlx::query_as::<_, Session>(
+            r#"
+            SELECT id, start_time, end_time, title, summary,
+                   video_path, tags, created_at, device_name, device_type
+            FROM sessions
+            WHERE id = $1
+            "#,
+        )
+        .bind(session_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(session)
+    }
+
+    async fn get_session_detail(&self, session_id: i64) -> Result<SessionDetail> {
+        let session = self.get_session(session_id).await?;
+        let frames = self.get_frames_by_session(session_id).await?;
+        let tags = serde_json::from_str(&session.tags).unwrap_or_default();
+
+        Ok(SessionDetail {
+            session,
+            frames,
+            tags,
+        })
+    }
+
+    async fn get_sessions_by_date(&self, date: &str) -> Result<Vec<Session>> {
+        // 使用字符串拼接构造时间范围（连接时区为 UTC，不会发生偏移）
+        let start_datetime = format!("{} 00:00:00", date);
+        let end_datetime = format!("{} 23:59:59", date);
+
+        let sessions = sqlx::query_as::<_, Session>(
+            r#"
+            SELECT id, start_time, end_time, title, summary,
+                   video_path, tags, created_at, device_name, device_type
+            FROM sessions
+            WHERE start_time >= $1::timestamptz AND start_time <= $2::timestamptz
+            ORDER BY start_time DESC
+            "#,
+        )
+        .bind(&start_datetime)
+        .bind(&end_datetime)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    async fn get_all_sessions(&self) -> Result<Vec<Session>> {
+        let sessions = sqlx::query_as::<_, Session>(
+            r#"
+            SELECT id, start_time, end_time, title, summary,
+                   video_path, tags, created_at, device_name, device_type
+            FROM sessions
+            ORDER BY start_time
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    async fn update_session(
+        &self,
+        session_id: i64,
+        title: &str,
+        summary: &str,
+        video_path: Option<&str>,
+        tags: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE sessions SET title = $1, summary = $2, video_path = $3, tags = $4 WHERE id = $5",
+        )
+        .bind(title)
+        .bind(summary)
+        .bind(video_path)
+        .bind(tags)
+        .bind(session_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update_session_tags(&self, session_id: i64, tags: &str) -> Result<()> {
+        sqlx::query("UPDATE sessions SET tags = $1 WHERE id = $2")
+            .bind(tags)
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn update_session_times(
+        &self,
+        session_id: i64,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query("UPDATE sessions SET start_time = $1, end_time = $2 WHERE id = $3")
+            .bind(start_time)
+            .bind(end_time)
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn replace_session_content(
+        &self,
+        session_id: i64,
+        session: &Session,
+        cards: &[TimelineCardRecord],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "UPDATE sessions SET title = $1, summary = $2, tags = $3, start_time = $4, end_time = $5 WHERE id = $6",
+        )
+        .bind(&session.title)
+        .bind(&session.summary)
+        .bind(&session.tags)
+        .bind(session.start_time)
+        .bind(session.end_time)
+        .bind(session_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM timeline_cards WHERE session_id = $1")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        insert_card_rows(&mut tx, cards).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn update_session_video_path(&self, session_id: i64, video_path: &str) -> Result<()> {
+        sqlx::query("UPDATE sessions SET video_path = $1 WHERE id = $2")
+            .bind(video_path)
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn clear_session_video_path(&self, session_id: i64) -> Result<()> {
+        sqlx::query("UPDATE sessions SET video_path = NULL WHERE id = $1")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn update_device_info_for_all_sessions(&self) -> Result<u64> {
+        let (device_name, device_type) = get_device_info();
+
+        let result = sqlx::query(
+            "UPDATE sessions SET device_name = $1, device_type = $2 WHERE device_name IS NULL OR device_type = 'desktop'"
+        )
+        .bind(&device_name)
+        .bind(&device_type)
+        .execute(&self.pool)
+        .await?;
+
+        let updated_count = result.rows_affected();
+
+        if updated_count > 0 {
+            info!(
+                "已更新 {} 条历史会话的设备信息: device_name={}, device_type={}",
+                updated_count, device_name, device_type
+            );
+        }
+
+        Ok(updated_count)
+    }
+
+    async fn delete_session(&self, session_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM sessions WHERE id = $1")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        info!("删除会话: {}", session_id);
+        Ok(())
+    }
+
+    async fn get_old_sessions(&self, cutoff_date: DateTime<Utc>) -> Result<Vec<Session>> {
+        let sessions = sqlx::query_as::<_, Session>(
+            "SELECT id, start_time, end_time, title, summary, video_path, tags, created_at, device_name, device_type
+             FROM sessions
+             WHERE start_time < $1"
+        )
+        .bind(cutoff_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    async fn delete_old_sessions(&self, cutoff_date: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM sessions WHERE start_time < $1")
+            .bind(cutoff_date)
+            .execute(&self.pool)
+            .await?;
+
+        let deleted_count = result.rows_affected();
+
+        if deleted_count > 0 {
+            info!("删除了 {} 个过期会话", deleted_count);
+        }
+
+        Ok(deleted_count)
+    }
+
+    // ========== 帧操作 ==========
+
+    async fn insert_frame(&self, frame: &Frame) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO frames (session_id, timestamp, file_path, app_name, window_title, process_path, domain, screen_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id
+        "#,
+        )
+        .bind(frame.session_id)
+        .bind(frame.timestamp)
+        .bind(&frame.file_path)
+        .bind(&frame.app_name)
+        .bind(&frame.window_title)
+        .bind(&frame.process_path)
+        .bind(&frame.domain)
+        .bind(frame.screen_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn insert_frames(&self, frames: &[Frame]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for frame in frames {
+            sqlx::query(
+                r#"
+                INSERT INTO frames (session_id, timestamp, file_path, app_name, window_title, process_path, domain, screen_id)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            )
+            .bind(frame.session_id)
+            .bind(frame.timestamp)
+            .bind(&frame.file_path)
+            .bind(&frame.app_name)
+            .bind(&frame.window_title)
+            .bind(&frame.process_path)
+            .bind(&frame.domain)
+            .bind(frame.screen_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_frames_by_session(&self, session_id: i64) -> Result<Vec<Frame>> {
+        let frames = sqlx::query_as::<_, Frame>(
+            r#"
+            SELECT id, session_id, timestamp, file_path, app_name, window_title, process_path, domain, screen_id
+            FROM frames
+            WHERE session_id = $1
+            ORDER BY timestamp
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(frames)
+    }
+
+    async fn delete_frames_by_session(&self, session_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM frames WHERE session_id = $1")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_frames(&self, session_id: i64, frame_ids: &[i64]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for frame_id in frame_ids {
+            sqlx::query("DELETE FROM frames WHERE id = $1 AND session_id = $2")
+                .bind(frame_id)
+                .bind(session_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn update_frame_path(
+        &self,
+        session_id: i64,
+        frame_id: i64,
+        file_path: &str,
+    ) -> Result<()> {
+        sqlx::query("UPDATE frames SET file_path = $1 WHERE id = $2 AND session_id = $3")
+            .bind(file_path)
+            .bind(frame_id)
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn move_frames(
+        &self,
+        from_session_id: i64,
+        to_session_id: i64,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut moved = 0;
+
+        for table in ["frames", "frame_texts"] {
+            let sql = format!(
+                "UPDATE {} SET session_id = $1 WHERE session_id = $2{}",
+                table,
+                if since.is_some() {
+                    " AND timestamp >= $3"
+                } else {
+                    ""
+                }
+            );
+            let mut query = sqlx::query(&sql).bind(to_session_id).bind(from_session_id);
+            if let Some(since) = since {
+                query = query.bind(since);
+            }
+            let result = query.execute(&mut *tx).await?;
+            if table == "frames" {
+                moved = result.rows_affected();
+            }
+        }
+
+        tx.commit().await?;
+        Ok(moved)
+    }
+
+    // ========== 活动统计 ==========
+
+    async fn get_activities(&self, start_date: &str, end_date: &str) -> Result<Vec<Activity>> {
+        // 使用字符串拼接构造时间范围（连接时区为 UTC，不会发生偏移）
+        let start_datetime = format!("{} 00:00:00", start_date);
+        let end_datetime = format!("{} 23:59:59", end_date);
+
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                to_char(start_time, 'YYYY-MM-DD') as date,
+                COUNT(*) as session_count,
+                CAST(SUM(FLOOR(EXTRACT(EPOCH FROM (end_time - start_time)) / 60)) AS BIGINT) as total_duration_minutes,
+                string_agg(DISTINCT CASE WHEN tags LIKE '[%' THEN tags::jsonb -> 0 ->> 'category' END, ',') as main_categories
+            FROM sessions
+            WHERE start_time >= $1::timestamptz AND start_time <= $2::timestamptz
+            GROUP BY to_char(start_time, 'YYYY-MM-DD')
+            ORDER BY date DESC
+            "#
+        )
+        .bind(&start_datetime)
+        .bind(&end_datetime)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(activity_from_row).collect()
+    }
+
+    async fn get_device_activities(
+        &self,
+        start_date: &str,
+        end_date: &str,
+        device_name: &str,
+    ) -> Result<Vec<Activity>> {
+        let start_datetime = format!("{} 00:00:00", start_date);
+        let end_datetime = format!("{} 23:59:59", end_date);
+
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                to_char(start_time, 'YYYY-MM-DD') as date,
+                COUNT(*) as session_count,
+                CAST(SUM(FLOOR(EXTRACT(EPOCH FROM (end_time - start_time)) / 60)) AS BIGINT) as total_duration_minutes,
+                string_agg(DISTINCT CASE WHEN tags LIKE '[%' THEN tags::jsonb -> 0 ->> 'category' END, ',') as main_categories
+            FROM sessions
+            WHERE start_time >= $1::timestamptz AND start_time <= $2::timestamptz AND device_name = $3
+            GROUP BY to_char(start_time, 'YYYY-MM-DD')
+            ORDER BY date DESC
+            "#
+        )
+        .bind(&start_datetime)
+        .bind(&end_datetime)
+        .bind(device_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(activity_from_row).collect()
+    }
+
+    async fn get_device_names(&self) -> Result<Vec<String>> {
+        let names = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT DISTINCT device_name FROM sessions
+            WHERE device_name IS NOT NULL AND device_name <> ''
+            ORDER BY device_name
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(names)
+    }
+
+    // ========== LLM 调用记录 ==========
+
+    async fn insert_llm_call(&self, record: &LLMCallRecord) -> Result<i64> {
+        // 检查 session_id 是否存在（如果不是 NULL）
+        if let Some(sid) = record.session_id {
+            let exists: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions WHERE id = $1")
+                .bind(sid)
+                .fetch_one(&self.pool)
+                .await?;
+
+            if exists == 0 {
+                return Err(anyhow::anyhow!(
+                    "无法插入 LLM 调用记录：session_id {} 不存在。请先创建会话。",
+                    sid
+                ));
+            }
+        }
+
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO llm_calls (
+                session_id, provider, model, call_type,
+                request_headers, request_body, response_headers, response_body,
+                status_code, error_message, latency_ms, token_usage, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING id
+        "#,
+        )
+        .bind(record.session_id)
+        .bind(&record.provider)
+        .bind(&record.model)
+        .bind(&record.call_type)
+        .bind(&record.request_headers)
+        .bind(&record.request_body)
+        .bind(&record.response_headers)
+        .bind(&record.response_body)
+        .bind(record.status_code)
+        .bind(&record.error_message)
+        .bind(record.latency_ms)
+        .bind(&record.token_usage)
+        .bind(record.created_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn get_llm_calls_by_session(&self, session_id: i64) -> Result<Vec<LLMCallRecord>> {
+        let records = sqlx::query_as::<_, LLMCallRecord>(
+            r#"
+            SELECT * FROM llm_calls
+            WHERE session_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn get_recent_llm_errors(&self, limit: i64) -> Result<Vec<LLMCallRecord>> {
+        let records = sqlx::query_as::<_, LLMCallRecord>(
+            r#"
+            SELECT * FROM llm_calls
+            WHERE error_message IS NOT NULL
+            ORDER BY created_at DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn delete_llm_calls_by_session(&self, session_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM llm_calls WHERE session_id = $1")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // ========== 视频分段 ==========
+
+    async fn insert_video_segment(&self, segment: &VideoSegmentRecord) -> Result<i64> {
+        // 检查 session_id 是否存在
+        let exists: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions WHERE id = $1")
+            .bind(segment.session_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        if exists == 0 {
+            return Err(anyhow::anyhow!(
+                "无法插入视频分段记录：session_id {} 不存在。请先创建会话。",
+                segment.session_id
+            ));
+        }
+
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO video_segments (
+                session_id, llm_call_id, start_timestamp, end_timestamp,
+                description, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id
+        "#,
+        )
+        .bind(segment.session_id)
+        .bind(segment.llm_call_id)
+        .bind(&segment.start_timestamp)
+        .bind(&segment.end_timestamp)
+        .bind(&segment.description)
+        .bind(segment.created_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn insert_video_segments(&self, segments: &[VideoSegmentRecord]) -> Result<()> {
+        if segments.is_empty() {
+            return Ok(());
+        }
+
+        // 检查所有 session_id 是否存在
+        for segment in segments {
+            let exists: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions WHERE id = $1")
+                .bind(segment.session_id)
+                .fetch_one(&self.pool)
+                .await?;
+
+            if exists == 0 {
+                return Err(anyhow::anyhow!(
+                    "无法插入视频分段记录：session_id {} 不存在。请先创建会话。",
+                    segment.session_id
+                ));
+            }
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        for segment in segments {
+            sqlx::query(
+                r#"
+                INSERT INTO video_segments (
+                    session_id, llm_call_id, start_timestamp, end_timestamp,
+                    description, created_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            )
+            .bind(segment.session_id)
+            .bind(segment.llm_call_id)
+            .bind(&segment.start_timestamp)
+            .bind(&segment.end_timestamp)
+            .bind(&segment.description)
+            .bind(segment.created_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_video_segments_by_session(
+        &self,
+        session_id: i64,
+    ) -> Result<Vec<VideoSegmentRecord>> {
+        let segments = sqlx::query_as::<_, VideoSegmentRecord>(
+            r#"
+            SELECT * FROM video_segments
+            WHERE session_id = $1
+            ORDER BY start_timestamp
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(segments)
+    }
+
+    async fn delete_video_segments_by_session(&self, session_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM video_segments WHERE session_id = $1")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // ========== 时间线卡片 ==========
+
+    async fn insert_timeline_card(&self, card: &TimelineCardRecord) -> Result<i64> {
+        // 检查 session_id 是否存在
+        let exists: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions WHERE id = $1")
+            .bind(card.session_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        if exists == 0 {
+            return Err(anyhow::anyhow!(
+                "无法插入时间线卡片记录：session_id {} 不存在。请先创建会话。",
+                card.session_id
+            ));
+        }
+
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO timeline_cards (
+                session_id, llm_call_id, start_time, end_time,
+                category, subcategory, title, summary, detailed_summary,
+                distractions, app_sites, confidence, heuristic, video_preview_path, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            RETURNING id
+        "#,
+        )
+        .bind(card.session_id)
+        .bind(card.llm_call_id)
+        .bind(&card.start_time)
+        .bind(&card.end_time)
+        .bind(&card.category)
+        .bind(&card.subcategory)
+        .bind(&card.title)
+        .bind(&card.summary)
+        .bind(&card.detailed_summary)
+        .bind(&card.distractions)
+        .bind(&card.app_sites)
+        .bind(card.confidence)
+        .bind(card.heuristic)
+        .bind(&card.video_preview_path)
+        .bind(card.created_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn insert_timeline_cards(&self, cards: &[TimelineCardRecord]) -> Result<()> {
+        if cards.is_empty() {
+            return Ok(());
+        }
+
+        // 检查所有 session_id 是否存在
+        for card in cards {
+            let exists: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions WHERE id = $1")
+                .bind(card.session_id)
+                .fetch_one(&self.pool)
+                .await?;
+
+            if exists == 0 {
+                return Err(anyhow::anyhow!(
+                    "无法插入时间线卡片记录：session_id {} 不存在。请先创建会话。",
+                    card.session_id
+                ));
+            }
+        }
+
+        let mut tx = self.pool.begin().await?;
+        insert_card_rows(&mut tx, cards).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_timeline_cards_by_session(
+        &self,
+        session_id: i64,
+    ) -> Result<Vec<TimelineCardRecord>> {
+        let cards = sqlx::query_as::<_, TimelineCardRecord>(
+            r#"
+            SELECT * FROM timeline_cards
+            WHERE session_id = $1
+            ORDER BY start_time
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(cards)
+    }
+
+    async fn get_recent_timeline_cards(&self, limit: i64) -> Result<Vec<TimelineCardRecord>> {
+        let cards = sqlx::query_as::<_, TimelineCardRecord>(
+            r#"
+            SELECT * FROM timeline_cards
+            ORDER BY created_at DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(cards)
+    }
+
+    async fn delete_timeline_cards_by_session(&self, session_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM timeline_cards WHERE session_id = $1")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn update_timeline_card_title(&self, id: i64, title: &str) -> Result<()> {
+        sqlx::query("UPDATE timeline_cards SET title = $1 WHERE id = $2")
+            .bind(title)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn update_timeline_card_category(
+        &self,
+        id: i64,
+        category: &str,
+        subcategory: &str,
+    ) -> Result<()> {
+        sqlx::query("UPDATE timeline_cards SET category = $1, subcategory = $2 WHERE id = $3")
+            .bind(category)
+            .bind(subcategory)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // ========== 统计信息 ==========
+
+    async fn get_stats(&self) -> Result<(i64, i64, i64)> {
+        let session_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions")
+            .fetch_one(&self.pool)
+            .await?;
+
+        let frame_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM frames")
+            .fetch_one(&self.pool)
+            .await?;
+
+        // PostgreSQL 数据库大小（含索引与 TOAST）
+        let total_size: i64 = sqlx::query_scalar("SELECT pg_database_size(current_database())")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok((session_count, frame_count, total_size))
+    }
+
+    async fn get_analyzed_video_paths(&self) -> Result<Vec<String>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT video_path
+            FROM sessions
+            WHERE video_path IS NOT NULL
+              AND summary != '{}'
+              AND summary != ''
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut paths = Vec::new();
+        for row in rows {
+            if let Ok(Some(path)) = row.try_get::<Option<String>, _>("video_path") {
+                paths.push(path);
+            }
+        }
+
+        Ok(paths)
+    }
+
+    // ========== 数据库初始化 ==========
+
+    async fn run_migrations(&self) -> Result<()> {
+        let all = postgres_migrations();
+        loop {
+            // 多台设备可能共用同一数据库：事务级咨询锁串行化迁移，加锁后重新读取版本
+            let mut tx = self.pool.begin().await?;
+            sqlx::query("SELECT pg_advisory_xact_lock($1)")
+                .bind(MIGRATION_LOCK_KEY)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(migrations::CREATE_VERSION_TABLE_SQL)
+                .execute(&mut *tx)
+                .await?;
+            let current: i64 = sqlx::query_scalar(migrations::CURRENT_VERSION_SQL)
+                .fetch_one(&mut *tx)
+                .await?;
+
+            let Some(migration) = migrations::pending(&all, current)?.first() else {
+                tx.commit().await?;
+                break;
+            };
+            info!(
+                "应用 PostgreSQL 结构迁移 v{}: {}",
+                migration.version, migration.description
+            );
+
+            // PostgreSQL 的 DDL 可随事务回滚，任一步骤失败时该版本整体撤销
+            for step in &migration.steps {
+                sqlx::query(&step.to_sql()).execute(&mut *tx).await?;
+            }
+            sqlx::query("INSERT INTO schema_version (version, description) VALUES ($1, $2)")
+                .bind(migration.version)
+                .bind(migration.description)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+        }
+
+        info!(
+            "PostgreSQL 数据库结构版本: {}",
+            migrations::latest_version(&all)
+        );
+        Ok(())
+    }
+
+    async fn save_day_summary(&self, date: &str, summary: &DaySummaryRecord) -> Result<()> {
+        // 使用 ON CONFLICT 实现 upsert，保留首次生成时间
+        sqlx::query(
+            r#"
+            INSERT INTO day_summaries (
+                date, summary_text, device_stats, parallel_work, usage_patterns,
+                active_device_count, llm_call_id, updated_at
+            ) VALUES ($1::date, $2, $3, $4, $5, $6, $7, NOW())
+            ON CONFLICT (date) DO UPDATE SET
+                summary_text = EXCLUDED.summary_text,
+                device_stats = EXCLUDED.device_stats,
+                parallel_work = EXCLUDED.parallel_work,
+                usage_patterns = EXCLUDED.usage_patterns,
+                active_device_count = EXCLUDED.active_device_count,
+                llm_call_id = EXCLUDED.llm_call_id,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(date)
+        .bind(&summary.summary_text)
+        .bind(&summary.device_stats)
+        .bind(&summary.parallel_work)
+        .bind(&summary.usage_patterns)
+        .bind(summary.active_device_count)
+        .bind(summary.llm_call_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_day_summary(&self, date: &str) -> Result<Option<DaySummaryRecord>> {
+        let result = sqlx::query_as::<_, DaySummaryRecord>(
+            r#"
+            SELECT * FROM day_summaries WHERE date = $1::date
+            "#,
+        )
+        .bind(date)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn delete_day_summary(&self, date: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM day_summaries WHERE date = $1::date
+            "#,
+        )
+        .bind(date)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn save_daily_focus_metrics(
+        &self,
+        date: &str,
+        metrics: &DailyFocusMetricsRecord,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO daily_focus_metrics (
+                date, total_minutes, work_minutes, learning_minutes, communication_minutes,
+                personal_minutes, idle_minutes, other_minutes, updated_at
+            ) VALUES ($1::date, $2, $3, $4, $5, $6, $7, $8, NOW())
+            ON CONFLICT (date) DO UPDATE SET
+                total_minutes = EXCLUDED.total_minutes,
+                work_minutes = EXCLUDED.work_minutes,
+                learning_minutes = EXCLUDED.learning_minutes,
+                communication_minutes = EXCLUDED.communication_minutes,
+                personal_minutes = EXCLUDED.personal_minutes,
+                idle_minutes = EXCLUDED.idle_minutes,
+                other_minutes = EXCLUDED.other_minutes,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(date)
+        .bind(metrics.total_minutes)
+        .bind(metrics.work_minutes)
+        .bind(metrics.learning_minutes)
+        .bind(metrics.communication_minutes)
+        .bind(metrics.personal_minutes)
+        .bind(metrics.idle_minutes)
+        .bind(metrics.other_minutes)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_daily_focus_metrics(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DailyFocusMetricsRecord>> {
+        let result = sqlx::query_as::<_, DailyFocusMetricsRecord>(
+            r#"
+            SELECT * FROM daily_focus_metrics WHERE date >= $1::date AND date <= $2::date ORDER BY date
+            "#,
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn delete_daily_focus_metrics(&self, date: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM daily_focus_metrics WHERE date = $1::date
+            "#,
+        )
+        .bind(date)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert_export_history(&self, record: &ExportHistoryRecord) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO export_history (
+                kind, target, request, success, message,
+                written_paths, warnings, failed_sessions, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id
+        "#,
+        )
+        .bind(&record.kind)
+        .bind(&record.target)
+        .bind(&record.request)
+        .bind(record.success)
+        .bind(&record.message)
+        .bind(&record.written_paths)
+        .bind(&record.warnings)
+        .bind(&record.failed_sessions)
+        .bind(record.created_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn get_export_history(&self, limit: i64) -> Result<Vec<ExportHistoryRecord>> {
+        let records = sqlx::query_as::<_, ExportHistoryRecord>(
+            r#"
+            SELECT * FROM export_history ORDER BY created_at DESC, id DESC LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn get_export_history_entry(&self, id: i64) -> Result<ExportHistoryRecord> {
+        let record = sqlx::query_as::<_, ExportHistoryRecord>(
+            r#"
+            SELECT * FROM export_history WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    async fn insert_project_rule(&self, rule: &ProjectRuleRecord) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO project_rules (project, match_field, pattern, created_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+        "#,
+        )
+        .bind(&rule.project)
+        .bind(&rule.match_field)
+        .bind(&rule.pattern)
+        .bind(rule.created_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn get_project_rules(&self) -> Result<Vec<ProjectRuleRecord>> {
+        let rules = sqlx::query_as::<_, ProjectRuleRecord>(
+            r#"
+            SELECT * FROM project_rules ORDER BY id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rules)
+    }
+
+    async fn delete_project_rule(&self, id: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM project_rules WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert_custom_category(&self, category: &CustomCategoryRecord) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO custom_categories (name, bucket, description, subcategories, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+        "#,
+        )
+        .bind(&category.name)
+        .bind(&category.bucket)
+        .bind(&category.description)
+        .bind(&category.subcategories)
+        .bind(category.created_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn get_custom_categories(&self) -> Result<Vec<CustomCategoryRecord>> {
+        let categories = sqlx::query_as::<_, CustomCategoryRecord>(
+            r#"
+            SELECT * FROM custom_categories ORDER BY id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(categories)
+    }
+
+    async fn delete_custom_category(&self, id: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM custom_categories WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert_classification_rule(&self, rule: &ClassificationRuleRecord) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO classification_rules (match_field, pattern, category, subcategory, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+        "#,
+        )
+        .bind(&rule.match_field)
+        .bind(&rule.pattern)
+        .bind(&rule.category)
+        .bind(&rule.subcategory)
+        .bind(rule.created_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn get_classification_rules(&self) -> Result<Vec<ClassificationRuleRecord>> {
+        let rules = sqlx::query_as::<_, ClassificationRuleRecord>(
+            r#"
+            SELECT * FROM classification_rules ORDER BY id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rules)
+    }
+
+    async fn delete_classification_rule(&self, id: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM classification_rules WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn save_notion_sync(&self, record: &NotionSyncRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO notion_sync (kind, local_key, date, page_id, synced_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (kind, local_key) DO UPDATE SET
+                date = EXCLUDED.date, page_id = EXCLUDED.page_id, synced_at = EXCLUDED.synced_at
+            "#,
+        )
+        .bind(&record.kind)
+        .bind(&record.local_key)
+        .bind(&record.date)
+        .bind(&record.page_id)
+        .bind(record.synced_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_notion_sync(
+        &self,
+        kind: &str,
+        local_key: &str,
+    ) -> Result<Option<NotionSyncRecord>> {
+        let record = sqlx::query_as::<_, NotionSyncRecord>(
+            r#"
+            SELECT * FROM notion_sync WHERE kind = $1 AND local_key = $2
+            "#,
+        )
+        .bind(kind)
+        .bind(local_key)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    async fn get_notion_syncs_by_date(
+        &self,
+        kind: &str,
+        date: &str,
+    ) -> Result<Vec<NotionSyncRecord>> {
+        let records = sqlx::query_as::<_, NotionSyncRecord>(
+            r#"
+            SELECT * FROM notion_sync WHERE kind = $1 AND date = $2 ORDER BY local_key
+            "#,
+        )
+        .bind(kind)
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn delete_notion_sync(&self, kind: &str, local_key: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM notion_sync WHERE kind = $1 AND local_key = $2
+            "#,
+        )
+        .bind(kind)
+        .bind(local_key)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn replace_calendar_events(
+        &self,
+        date: &str,
+        events: &[CalendarEventRecord],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM calendar_events WHERE date = $1")
+            .bind(date)
+            .execute(&mut *tx)
+            .await?;
+        for event in events {
+            sqlx::query(
+                r#"
+                INSERT INTO calendar_events (event_id, date, title, location, start_time, end_time, synced_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(&event.event_id)
+            .bind(date)
+            .bind(&event.title)
+            .bind(&event.location)
+            .bind(event.start_time)
+            .bind(event.end_time)
+            .bind(event.synced_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_calendar_events_by_date(&self, date: &str) -> Result<Vec<CalendarEventRecord>> {
+        let events = sqlx::query_as::<_, CalendarEventRecord>(
+            r#"
+            SELECT * FROM calendar_events WHERE date = $1 ORDER BY start_time
+            "#,
+        )
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    async fn replace_issue_links(&self, session_id: i64, links: &[IssueLinkRecord]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM issue_links WHERE session_id = $1")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        for link in links {
+            sqlx::query(
+                r#"
+                INSERT INTO issue_links (session_id, provider, issue_key, title, state, url, resolved_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(session_id)
+            .bind(&link.provider)
+            .bind(&link.issue_key)
+            .bind(&link.title)
+            .bind(&link.state)
+            .bind(&link.url)
+            .bind(link.resolved_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_issue_links_by_session(&self, session_id: i64) -> Result<Vec<IssueLinkRecord>> {
+        let links = sqlx::query_as::<_, IssueLinkRecord>(
+            r#"
+            SELECT * FROM issue_links WHERE session_id = $1 ORDER BY provider, issue_key
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(links)
+    }
+
+    async fn save_time_entry_sync(&self, record: &TimeEntrySyncRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO time_entry_sync (provider, session_id, entry_id, synced_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (provider, session_id) DO UPDATE SET
+                entry_id = EXCLUDED.entry_id, synced_at = EXCLUDED.synced_at
+            "#,
+        )
+        .bind(&record.provider)
+        .bind(record.session_id)
+        .bind(&record.entry_id)
+        .bind(record.synced_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_time_entry_sync(
+        &self,
+        provider: &str,
+        session_id: i64,
+    ) -> Result<Option<TimeEntrySyncRecord>> {
+        let record = sqlx::query_as::<_, TimeEntrySyncRecord>(
+            r#"
+            SELECT * FROM time_entry_sync WHERE provider = $1 AND session_id = $2
+            "#,
+        )
+        .bind(provider)
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    async fn save_session_note(&self, record: &SessionNoteRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO session_notes (session_id, note, tags, updated_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (session_id) DO UPDATE SET
+                note = EXCLUDED.note, tags = EXCLUDED.tags, updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(record.session_id)
+        .bind(&record.note)
+        .bind(&record.tags)
+        .bind(record.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_session_note(&self, session_id: i64) -> Result<Option<SessionNoteRecord>> {
+        let record = sqlx::query_as::<_, SessionNoteRecord>(
+            r#"
+            SELECT * FROM session_notes WHERE session_id = $1
+            "#,
+        )
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    async fn delete_session_note(&self, session_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM session_notes WHERE session_id = $1")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn replace_app_usage(&self, session_id: i64, records: &[AppUsageRecord]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM app_usage WHERE session_id = $1")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        for record in records {
+            sqlx::query(
+                r#"
+                INSERT INTO app_usage (session_id, app_name, process_path, window_title, seconds)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+            )
+            .bind(session_id)
+            .bind(&record.app_name)
+            .bind(&record.process_path)
+            .bind(&record.window_title)
+            .bind(record.seconds)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_app_usage_by_session(&self, session_id: i64) -> Result<Vec<AppUsageRecord>> {
+        let records = sqlx::query_as::<_, AppUsageRecord>(
+            "SELECT * FROM app_usage WHERE session_id = $1 ORDER BY seconds DESC, app_name",
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn get_app_usage_summary(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<AppUsageSummary>> {
+        let summaries = sqlx::query_as::<_, AppUsageSummary>(
+            r#"
+            SELECT a.app_name, MAX(a.process_path) AS process_path,
+                   CAST(SUM(a.seconds) AS BIGINT) AS seconds,
+                   COUNT(DISTINCT a.session_id) AS session_count
+            FROM app_usage a
+            JOIN sessions s ON s.id = a.session_id
+            WHERE s.start_time::date >= $1::date AND s.start_time::date <= $2::date
+            GROUP BY a.app_name
+            ORDER BY seconds DESC, a.app_name
+            "#,
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(summaries)
+    }
+
+    async fn get_daily_app_usage(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DailyAppUsage>> {
+        let usage = sqlx::query_as::<_, DailyAppUsage>(
+            r#"
+            SELECT to_char(s.start_time, 'YYYY-MM-DD') AS date, a.app_name,
+                   CAST(SUM(a.seconds) AS BIGINT) AS seconds
+            FROM app_usage a
+            JOIN sessions s ON s.id = a.session_id
+            WHERE s.start_time::date >= $1::date AND s.start_time::date <= $2::date
+            GROUP BY to_char(s.start_time, 'YYYY-MM-DD'), a.app_name
+            ORDER BY date, seconds DESC, a.app_name
+            "#,
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(usage)
+    }
+
+    async fn replace_domain_usage(
+        &self,
+        session_id: i64,
+        records: &[DomainUsageRecord],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM domain_usage WHERE session_id = $1")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        for record in records {
+            sqlx::query(
+                "INSERT INTO domain_usage (session_id, domain, seconds) VALUES ($1, $2, $3)",
+            )
+            .bind(session_id)
+            .bind(&record.domain)
+            .bind(record.seconds)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_domain_usage_by_session(&self, session_id: i64) -> Result<Vec<DomainUsageRecord>> {
+        let records = sqlx::query_as::<_, DomainUsageRecord>(
+            "SELECT * FROM domain_usage WHERE session_id = $1 ORDER BY seconds DESC, domain",
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn get_domain_usage_summary(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DomainUsageSummary>> {
+        let summaries = sqlx::query_as::<_, DomainUsageSummary>(
+            r#"
+            SELECT d.domain, CAST(SUM(d.seconds) AS BIGINT) AS seconds,
+                   COUNT(DISTINCT d.session_id) AS session_count
+            FROM domain_usage d
+            JOIN sessions s ON s.id = d.session_id
+            WHERE s.start_time::date >= $1::date AND s.start_time::date <= $2::date
+            GROUP BY d.domain
+            ORDER BY seconds DESC, d.domain
+            "#,
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(summaries)
+    }
+
+    async fn insert_frame_texts(&self, texts: &[FrameText]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for text in texts {
+            sqlx::query(
+                "INSERT INTO frame_texts (session_id, timestamp, text) VALUES ($1, $2, $3)",
+            )
+            .bind(text.session_id)
+            .bind(text.timestamp)
+            .bind(&text.text)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_frame_texts_by_session(&self, session_id: i64) -> Result<Vec<FrameText>> {
+        let texts = sqlx::query_as::<_, FrameText>(
+            "SELECT id, session_id, timestamp, text FROM frame_texts WHERE session_id = $1 ORDER BY timestamp",
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(texts)
+    }
+
+    async fn get_llm_cache(&self, cache_key: &str) -> Result<Option<LlmCacheEntry>> {
+        let entry = sqlx::query_as::<_, LlmCacheEntry>(
+            "SELECT cache_key, task, provider, model, response, created_at FROM llm_cache WHERE cache_key = $1",
+        )
+        .bind(cache_key)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    async fn save_llm_cache(&self, entry: &LlmCacheEntry) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO llm_cache (cache_key, task, provider, model, response, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (cache_key) DO UPDATE SET
+                task = EXCLUDED.task, provider = EXCLUDED.provider, model = EXCLUDED.model,
+                response = EXCLUDED.response, created_at = EXCLUDED.created_at
+            "#,
+        )
+        .bind(&entry.cache_key)
+        .bind(&entry.task)
+        .bind(&entry.provider)
+        .bind(&entry.model)
+        .bind(&entry.response)
+        .bind(entry.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn prune_llm_cache(&self, before: Option<DateTime<Utc>>) -> Result<u64> {
+        let result = match before {
+            Some(before) => {
+                sqlx::query("DELETE FROM llm_cache WHERE created_at < $1")
+                    .bind(before)
+                    .execute(&self.pool)
+                    .await?
+            }
+            None => {
+                sqlx::query("DELETE FROM llm_cache")
+                    .execute(&self.pool)
+                    .await?
+            }
+        };
+
+        Ok(result.rows_affected())
+    }
+
+    async fn insert_llm_parse_failure(&self, failure: &LlmParseFailure) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO llm_parse_failures (
+                session_id, provider, model, task, raw_response, repaired_response,
+                errors, repaired, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id
+            "#,
+        )
+        .bind(failure.session_id)
+        .bind(&failure.provider)
+        .bind(&failure.model)
+        .bind(&failure.task)
+        .bind(&failure.raw_response)
+        .bind(&failure.repaired_response)
+        .bind(&failure.errors)
+        .bind(failure.repaired)
+        .bind(failure.created_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn get_llm_parse_failures(&self, limit: i64) -> Result<Vec<LlmParseFailure>> {
+        let failures = sqlx::query_as::<_, LlmParseFailure>(
+            "SELECT * FROM llm_parse_failures ORDER BY created_at DESC, id DESC LIMIT $1",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(failures)
+    }
+
+    async fn get_timeline_card(&self, id: i64) -> Result<Option<TimelineCardRecord>> {
+        let card =
+            sqlx::query_as::<_, TimelineCardRecord>("SELECT * FROM timeline_cards WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(card)
+    }
+
+    async fn get_review_queue(
+        &self,
+        threshold: f64,
+        limit: i64,
+    ) -> Result<Vec<TimelineCardRecord>> {
+        let cards = sqlx::query_as::<_, TimelineCardRecord>(
+            r#"
+            SELECT c.* FROM timeline_cards c
+            WHERE c.confidence IS NOT NULL AND c.confidence < $1
+              AND LOWER(c.category) <> 'idle'
+              AND NOT EXISTS (SELECT 1 FROM card_reviews r WHERE r.card_id = c.id)
+            ORDER BY c.start_time DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(threshold)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(cards)
+    }
+
+    async fn save_card_review(&self, review: &CardReviewRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO card_reviews (
+                card_id, session_id, app, original_category, category, subcategory, reviewed_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (card_id) DO UPDATE SET
+                session_id = EXCLUDED.session_id, app = EXCLUDED.app,
+                original_category = EXCLUDED.original_category, category = EXCLUDED.category,
+                subcategory = EXCLUDED.subcategory, reviewed_at = EXCLUDED.reviewed_at
+            "#,
+        )
+        .bind(review.card_id)
+        .bind(review.session_id)
+        .bind(&review.app)
+        .bind(&review.original_category)
+        .bind(&review.category)
+        .bind(&review.subcategory)
+        .bind(review.reviewed_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_card_reviews_by_app(&self, app: &str) -> Result<Vec<CardReviewRecord>> {
+        let reviews = sqlx::query_as::<_, CardReviewRecord>(
+            "SELECT * FROM card_reviews WHERE app = $1 ORDER BY reviewed_at",
+        )
+        .bind(app)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(reviews)
+    }
+
+    async fn insert_goal(&self, goal: &GoalRecord) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO goals (
+                name, metric, target, comparison, target_minutes, schedule, enabled, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id
+            "#,
+        )
+        .bind(&goal.name)
+        .bind(&goal.metric)
+        .bind(&goal.target)
+        .bind(&goal.comparison)
+        .bind(goal.target_minutes)
+        .bind(&goal.schedule)
+        .bind(goal.enabled)
+        .bind(goal.created_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn update_goal(&self, goal: &GoalRecord) -> Result<()> {
+        let id = goal.id.ok_or_else(|| anyhow::anyhow!("目标缺少 ID"))?;
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE goals
+            SET name = $1, metric = $2, target = $3, comparison = $4, target_minutes = $5,
+                schedule = $6, enabled = $7
+            WHERE id = $8
+            "#,
+        )
+        .bind(&goal.name)
+        .bind(&goal.metric)
+        .bind(&goal.target)
+        .bind(&goal.comparison)
+        .bind(goal.target_minutes)
+        .bind(&goal.schedule)
+        .bind(goal.enabled)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM goal_results WHERE goal_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_goals(&self) -> Result<Vec<GoalRecord>> {
+        let goals = sqlx::query_as::<_, GoalRecord>("SELECT * FROM goals ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(goals)
+    }
+
+    async fn delete_goal(&self, id: i64) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM goal_results WHERE goal_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM goals WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn save_goal_result(&self, result: &GoalResultRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO goal_results (goal_id, date, actual_minutes, passed, evaluated_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (goal_id, date) DO UPDATE SET
+                actual_minutes = EXCLUDED.actual_minutes, passed = EXCLUDED.passed,
+                evaluated_at = EXCLUDED.evaluated_at
+            "#,
+        )
+        .bind(result.goal_id)
+        .bind(&result.date)
+        .bind(result.actual_minutes)
+        .bind(result.passed)
+        .bind(result.evaluated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_goal_results(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<GoalResultRecord>> {
+        let results = sqlx::query_as::<_, GoalResultRecord>(
+            "SELECT * FROM goal_results WHERE date >= $1 AND date <= $2 ORDER BY goal_id, date",
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(results)
+    }
+
+    async fn delete_goal_results_by_date(&self, date: &str) -> Result<()> {
+        sqlx::query("DELETE FROM goal_results WHERE date = $1")
+            .bind(date)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn insert_focus_block(&self, block: &FocusBlockRecord) -> Result<i64> {
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO focus_blocks (
+                label, planned_minutes, started_at, ended_at, status, distraction_count, distractions
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id
+            "#,
+        )
+        .bind(&block.label)
+        .bind(block.planned_minutes)
+        .bind(block.started_at)
+        .bind(block.ended_at)
+        .bind(&block.status)
+        .bind(block.distraction_count)
+        .bind(&block.distractions)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn update_focus_block(&self, block: &FocusBlockRecord) -> Result<()> {
+        let id = block.id.ok_or_else(|| anyhow::anyhow!("专注时段缺少 ID"))?;
+        sqlx::query(
+            r#"
+            UPDATE focus_blocks
+            SET ended_at = $1, status = $2, distraction_count = $3, distractions = $4
+            WHERE id = $5
+            "#,
+        )
+        .bind(block.ended_at)
+        .bind(&block.status)
+        .bind(block.distraction_count)
+        .bind(&block.distractions)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_focus_blocks(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<FocusBlockRecord>> {
+        let blocks = sqlx::query_as::<_, FocusBlockRecord>(
+            r#"
+            SELECT * FROM focus_blocks
+            WHERE started_at < $1 AND (ended_at IS NULL OR ended_at > $2)
+            ORDER BY started_at
+            "#,
+        )
+        .bind(end)
+        .bind(start)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(blocks)
+    }
+
+    async fn replace_embeddings(&self, session_id: i64, records: &[EmbeddingRecord]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM vectors WHERE session_id = $1")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        for record in records {
+            sqlx::query(
+                r#"
+                INSERT INTO vectors (session_id, owner_kind, owner_id, model, content_hash, vector, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(session_id)
+            .bind(&record.owner_kind)
+            .bind(record.owner_id)
+            .bind(&record.model)
+            .bind(&record.content_hash)
+            .bind(&record.vector)
+            .bind(record.updated_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_embeddings_by_session(&self, session_id: i64) -> Result<Vec<EmbeddingRecord>> {
+        let records = sqlx::query_as::<_, EmbeddingRecord>(
+            r#"
+            SELECT * FROM vectors WHERE session_id = $1 ORDER BY owner_kind DESC, owner_id
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn get_embeddings(
+        &self,
+        model: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<EmbeddingRecord>> {
+        let records = sqlx::query_as::<_, EmbeddingRecord>(
+            r#"
+            SELECT v.* FROM vectors v
+            JOIN sessions s ON s.id = v.session_id
+            WHERE v.model = $1 AND s.start_time::date >= $2::date AND s.start_time::date <= $3::date
+            "#,
+        )
+        .bind(model)
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn search_sessions(
+        &self,
+        query: &str,
+        start_date: &str,
+        end_date: &str,
+        limit: i64,
+    ) -> Result<Vec<SessionSearchHit>> {
+        let terms = search::search_terms(query);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let sql = search_sql(terms.len());
+        let mut query = sqlx::query_as::<_, search::SearchCandidate>(&sql)
+            .bind(start_date)
+            .bind(end_date);
+        for term in &terms {
+            query = query.bind(search::like_pattern(term));
+        }
+        let candidates = query.fetch_all(&self.pool).await?;
+
+        Ok(search::rank_candidates(
+            candidates,
+            &terms,
+            limit.max(0) as usize,
+        ))
+    }
+
+    fn db_type(&self) -> &str {
+        "postgres"
+    }
+
+    async fn snapshot_to(&self, path: &str) -> Result<()> {
+        // 可重复读事务内的读取共享同一快照
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+
+        // 表结构由结构迁移维护，转储只保存数据；恢复时先清空全部表
+        let mut body = format!(
+            "{}\nTRUNCATE {} RESTART IDENTITY CASCADE;\n",
+            dump::POSTGRES_DUMP_HEADER,
+            TABLES.join(", ")
+        );
+        for table in TABLES {
+            let columns: Vec<(String, String)> = sqlx::query_as(
+                "SELECT column_name::text, data_type::text FROM information_schema.columns WHERE table_schema = current_schema() AND table_name = $1 ORDER BY ordinal_position",
+            )
+            .bind(table)
+            .fetch_all(&mut *tx)
+            .await?;
+            let names: Vec<String> = columns
+                .iter()
+                .map(|(name, _)| format!("\"{}\"", name))
+                .collect();
+            let literals: Vec<String> = columns
+                .iter()
+                .map(|(name, data_type)| dump::postgres_column_literal(name, data_type))
+                .collect();
+            let rows: Vec<String> = sqlx::query_scalar(&format!(
+                "SELECT '(' || concat_ws(',', {}) || ')' FROM {}",
+                literals.join(", "),
+                table
+            ))
+            .fetch_all(&mut *tx)
+            .await?;
+
+            for chunk in rows.chunks(dump::INSERT_BATCH_ROWS) {
+                body.push_str(&format!(
+                    "INSERT INTO {} ({}) VALUES {};\n",
+                    table,
+                    names.join(", "),
+                    chunk.join(",")
+                ));
+            }
+            // 自增序列从已有最大 ID 之后继续
+            if columns.iter().any(|(name, _)| name == "id") {
+                body.push_str(&format!(
+                    "SELECT setval(pg_get_serial_sequence('{0}', 'id'), COALESCE(MAX(id), 1), MAX(id) IS NOT NULL) FROM {0};\n",
+                    table
+                ));
+            }
+        }
+        tx.commit().await?;
+
+        tokio::fs::write(path, dump::finish_dump(body)).await?;
+        info!("PostgreSQL 转储完成: {} 张表", TABLES.len());
+        Ok(())
+    }
+
+    async fn restore_from_dump(&self, path: &str) -> Result<()> {
+        let content = tokio::fs::read_to_string(path).await?;
+        let statements = dump::verify_dump(&content, dump::POSTGRES_DUMP_HEADER)?;
+
+        // 先迁移到最新表结构，再在单个事务中清空并写入，任一语句失败时整体回滚
+        self.run_migrations().await?;
+        let mut tx = self.pool.begin().await?;
+        for statement in &statements {
+            (&mut *tx)
+                .execute(*statement)
+                .await
+                .map_err(|e| anyhow::anyhow!("执行转储语句失败: {}", e))?;
+        }
+        tx.commit().await?;
+
+        info!("已从 PostgreSQL 转储恢复: {} 条语句", statements.len());
+        Ok(())
+    }
+
+    async fn migrate_timezone_to_local(&self) -> Result<(u64, u64, u64, u64, u64, u64)> {
+        use chrono::Local;
+
+        // 计算时区偏移量（小时）
+        let local_offset = Local::now().offset().local_minus_utc() / 3600;
+
+        info!(
+            "开始时区迁移：将 UTC 时间转换为本地时间（偏移 {} 小时）",
+            local_offset
+        );
+
+        // 更新 sessions 表
+        let sessions_updated = sqlx::query(
+            "UPDATE sessions SET
+             start_time = start_time + make_interval(hours => $1),
+             end_time = end_time + make_interval(hours => $1),
+             created_at = created_at + make_interval(hours => $1)",
+        )
+        .bind(local_offset)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        // 更新 frames 表
+        let frames_updated =
+            sqlx::query("UPDATE frames SET timestamp = timestamp + make_interval(hours => $1)")
+                .bind(local_offset)
+                .execute(&self.pool)
+                .await?
+                .rows_affected();
+
+        // 更新 llm_calls 表
+        let llm_calls_updated = sqlx::query(
+            "UPDATE llm_calls SET created_at = created_at + make_interval(hours => $1)",
+        )
+        .bind(local_offset)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        // 更新 video_segments 表
+        let video_segments_updated = sqlx::query(
+            "UPDATE video_segments SET created_at = created_at + make_interval(hours => $1)",
+        )
+        .bind(local_offset)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        // 更新 timeline_cards 表
+        let timeline_cards_updated = sqlx::query(
+            "UPDATE timeline_cards SET created_at = created_at + make_interval(hours => $1)",
+        )
+        .bind(local_offset)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        // 更新 day_summaries 表
+        let day_summaries_updated = sqlx::query(
+            "UPDATE day_summaries SET
+             created_at = created_at + make_interval(hours => $1),
+             updated_at = updated_at + make_interval(hours => $1)",
+        )
+        .bind(local_offset)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        info!(
+            "时区迁移完成：sessions={}, frames={}, llm_calls={}, video_segments={}, timeline_cards={}, day_summaries={}",
+            sessions_updated, frames_updated, llm_calls_updated,
+            video_segments_updated, timeline_cards_updated, day_summaries_updated
+        );
+
+        Ok((
+            sessions_updated,
+            frames_updated,
+            llm_calls_updated,
+            video_segments_updated,
+            timeline_cards_updated,
+            day_summaries_updated,
+        ))
+    }
+}
+
+/// 结构迁移使用的咨询锁键
+const MIGRATION_LOCK_KEY: i64 = 0x5343_5245_454e;
+
+/// 按日期聚合的会话统计行转为活动（主要类别以逗号拼接）
+fn activity_from_row(row: &sqlx::postgres::PgRow) -> Result<Activity> {
+    Ok(build_activity(
+        row.try_get("date")?,
+        row.try_get("session_count")?,
+        row.try_get("total_duration_minutes")?,
+        row.try_get("main_categories")?,
+    ))
+}
+
+fn build_activity(
+    date: String,
+    session_count: i64,
+    total_duration_minutes: Option<i64>,
+    main_categories: Option<String>,
+) -> Activity {
+    Activity {
+        date,
+        session_count: session_count as i32,
+        total_duration_minutes: total_duration_minutes.unwrap_or(0) as i32,
+        main_categories: main_categories
+            .map(|s| s.split(',').map(|s| s.to_string()).collect())
+            .unwrap_or_default(),
+    }
+}
+
+/// 会话搜索语句：$1、$2 为起止日期，其后每个搜索词绑定一个参数
+///
+/// 用 ILIKE 筛选（不区分大小写，与 MariaDB 排序规则行为一致）后在应用层打分、截取片段
+fn search_sql(term_count: usize) -> String {
+    let mut sql = String::from(
+        r#"
+            SELECT s.id AS session_id, s.title, s.start_time, s.end_time, s.summary,
+                   COALESCE((
+                       SELECT string_agg(concat_ws(' ', c.title, c.summary, c.detailed_summary), chr(10))
+                       FROM timeline_cards c WHERE c.session_id = s.id
+                   ), '') AS cards,
+                   COALESCE((
+                       SELECT string_agg(t.text, chr(10) ORDER BY t.timestamp)
+                       FROM frame_texts t WHERE t.session_id = s.id
+                   ), '') AS ocr
+            FROM sessions s
+            WHERE s.start_time::date >= $1::date AND s.start_time::date <= $2::date
+            "#,
+    );
+    // 每个搜索词绑定一个参数，在各字段中重复引用
+    for index in 0..term_count {
+        sql.push_str(&format!(
+            r#" AND (s.title ILIKE ${0} OR s.summary ILIKE ${0} OR EXISTS (
+                    SELECT 1 FROM timeline_cards c WHERE c.session_id = s.id
+                      AND (c.title ILIKE ${0} OR c.summary ILIKE ${0} OR c.detailed_summary ILIKE ${0})
+                ) OR EXISTS (
+                    SELECT 1 FROM frame_texts t WHERE t.session_id = s.id AND t.text ILIKE ${0}
+                ))"#,
+            index + 3
+        ));
+    }
+    sql
+}
+
+/// 在已开启的事务中逐条插入时间线卡片
+async fn insert_card_rows(
+    conn: &mut sqlx::postgres::PgConnection,
+    cards: &[TimelineCardRecord],
+) -> Result<()> {
+    for card in cards {
+        sqlx::query(
+            r#"
+            INSERT INTO timeline_cards (
+                session_id, llm_call_id, start_time, end_time,
+                category, subcategory, title, summary, detailed_summary,
+                distractions, app_sites, confidence, heuristic, video_preview_path, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+        "#,
+        )
+        .bind(card.session_id)
+        .bind(card.llm_call_id)
+        .bind(&card.start_time)
+        .bind(&card.end_time)
+        .bind(&card.category)
+        .bind(&card.subcategory)
+        .bind(&card.title)
+        .bind(&card.summary)
+        .bind(&card.detailed_summary)
+        .bind(&card.distractions)
+        .bind(&card.app_sites)
+        .bind(card.confidence)
+        .bind(card.heuristic)
+        .bind(&card.video_preview_path)
+        .bind(card.created_at)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// PostgreSQL 结构迁移（版本号递增；已发布的版本不可修改，结构变更追加新版本）
+///
+/// 本地时间按 UTC 标记存储（见 local_now），时间列统一使用 TIMESTAMPTZ，
+/// 连接时区固定为 UTC，读写均不发生偏移
+fn postgres_migrations() -> Vec<Migration> {
+    vec![Migration::new(1, "初始表结构").sql([
+        // 会话表
+        r#"
+        CREATE TABLE IF NOT EXISTS sessions (
+            id BIGSERIAL PRIMARY KEY,
+            start_time TIMESTAMPTZ NOT NULL,
+            end_time TIMESTAMPTZ NOT NULL,
+            title TEXT NOT NULL,
+            summary TEXT NOT NULL,
+            video_path TEXT,
+            tags TEXT NOT NULL,
+            created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+            device_name VARCHAR(255),
+            device_type VARCHAR(50)
+        )
+        "#,
+        // 帧表
+        r#"
+        CREATE TABLE IF NOT EXISTS frames (
+            id BIGSERIAL PRIMARY KEY,
+            session_id BIGINT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+            timestamp TIMESTAMPTZ NOT NULL,
+            file_path TEXT NOT NULL,
+            app_name TEXT,
+            window_title TEXT,
+            process_path TEXT,
+            domain TEXT,
+            screen_id BIGINT NOT NULL DEFAULT 0
+        )
+        "#,
+        // LLM 调用记录表
+        r#"
+        CREATE TABLE IF NOT EXISTS llm_calls (
+            id BIGSERIAL PRIMARY KEY,
+            session_id BIGINT REFERENCES sessions(id) ON DELETE CASCADE,
+            provider VARCHAR(100) NOT NULL,
+            model VARCHAR(100) NOT NULL,
+            call_type VARCHAR(100) NOT NULL,
+            request_headers TEXT NOT NULL,
+            request_body TEXT NOT NULL,
+            response_headers TEXT,
+            response_body TEXT,
+            status_code INTEGER,
+            error_message TEXT,
+            latency_ms BIGINT,
+            token_usage TEXT,
+            created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        // 视频分段表
+        r#"
+        CREATE TABLE IF NOT EXISTS video_segments (
+            id BIGSERIAL PRIMARY KEY,
+            session_id BIGINT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+            llm_call_id BIGINT REFERENCES llm_calls(id) ON DELETE SET NULL,
+            start_timestamp VARCHAR(50) NOT NULL,
+            end_timestamp VARCHAR(50) NOT NULL,
+            description TEXT NOT NULL,
+            created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        // 时间线卡片表
+        r#"
+        CREATE TABLE IF NOT EXISTS timeline_cards (
+            id BIGSERIAL PRIMARY KEY,
+            session_id BIGINT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+            llm_call_id BIGINT REFERENCES llm_calls(id) ON DELETE SET NULL,
+            start_time VARCHAR(50) NOT NULL,
+            end_time VARCHAR(50) NOT NULL,
+            category VARCHAR(100) NOT NULL,
+            subcategory VARCHAR(100) NOT NULL,
+            title TEXT NOT NULL,
+            summary TEXT NOT NULL,
+            detailed_summary TEXT NOT NULL,
+            distractions TEXT,
+            app_sites TEXT NOT NULL,
+            confidence DOUBLE PRECISION,
+            heuristic BOOLEAN NOT NULL DEFAULT FALSE,
+            video_preview_path TEXT,
+            created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        // 每日总结表（缓存）
+        r#"
+        CREATE TABLE IF NOT EXISTS day_summaries (
+            id BIGSERIAL PRIMARY KEY,
+            date DATE NOT NULL UNIQUE,
+            summary_text TEXT NOT NULL,
+            device_stats TEXT NOT NULL,
+            parallel_work TEXT NOT NULL,
+            usage_patterns TEXT NOT NULL,
+            active_device_count INTEGER NOT NULL,
+            llm_call_id BIGINT REFERENCES llm_calls(id) ON DELETE SET NULL,
+            created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+            updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        // 每日专注度指标表（缓存，时间线卡片变化时按日期失效）
+        r#"
+        CREATE TABLE IF NOT EXISTS daily_focus_metrics (
+            date DATE PRIMARY KEY,
+            total_minutes BIGINT NOT NULL,
+            work_minutes BIGINT NOT NULL,
+            learning_minutes BIGINT NOT NULL,
+            communication_minutes BIGINT NOT NULL,
+            personal_minutes BIGINT NOT NULL,
+            idle_minutes BIGINT NOT NULL,
+            other_minutes BIGINT NOT NULL,
+            updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        // 导出历史表
+        r#"
+        CREATE TABLE IF NOT EXISTS export_history (
+            id BIGSERIAL PRIMARY KEY,
+            kind VARCHAR(32) NOT NULL,
+            target VARCHAR(32) NOT NULL,
+            request TEXT NOT NULL,
+            success BOOLEAN NOT NULL,
+            message TEXT NOT NULL,
+            written_paths TEXT NOT NULL,
+            warnings TEXT NOT NULL,
+            failed_sessions TEXT NOT NULL,
+            created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        // 项目归属规则表
+        r#"
+        CREATE TABLE IF NOT EXISTS project_rules (
+            id BIGSERIAL PRIMARY KEY,
+            project VARCHAR(255) NOT NULL,
+            match_field VARCHAR(32) NOT NULL,
+            pattern VARCHAR(255) NOT NULL,
+            created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        // 自定义类别表
+        r#"
+        CREATE TABLE IF NOT EXISTS custom_categories (
+            id BIGSERIAL PRIMARY KEY,
+            name VARCHAR(255) NOT NULL UNIQUE,
+            bucket VARCHAR(32) NOT NULL,
+            description TEXT NOT NULL,
+            subcategories TEXT NOT NULL,
+            created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        // 预分类规则表
+        r#"
+        CREATE TABLE IF NOT EXISTS classification_rules (
+            id BIGSERIAL PRIMARY KEY,
+            match_field VARCHAR(32) NOT NULL,
+            pattern VARCHAR(512) NOT NULL,
+            category VARCHAR(255) NOT NULL,
+            subcategory VARCHAR(255) NOT NULL DEFAULT '',
+            created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        // Notion 同步记录表
+        r#"
+        CREATE TABLE IF NOT EXISTS notion_sync (
+            kind VARCHAR(16) NOT NULL,
+            local_key VARCHAR(64) NOT NULL,
+            date VARCHAR(10) NOT NULL,
+            page_id VARCHAR(64) NOT NULL,
+            synced_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (kind, local_key)
+        )
+        "#,
+        // 日历事件表
+        r#"
+        CREATE TABLE IF NOT EXISTS calendar_events (
+            id BIGSERIAL PRIMARY KEY,
+            event_id VARCHAR(255) NOT NULL,
+            date VARCHAR(10) NOT NULL,
+            title TEXT NOT NULL,
+            location TEXT NOT NULL,
+            start_time TIMESTAMPTZ NOT NULL,
+            end_time TIMESTAMPTZ NOT NULL,
+            synced_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        // 工单关联表
+        r#"
+        CREATE TABLE IF NOT EXISTS issue_links (
+            session_id BIGINT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+            provider VARCHAR(16) NOT NULL,
+            issue_key VARCHAR(255) NOT NULL,
+            title TEXT NOT NULL,
+            state VARCHAR(64) NOT NULL,
+            url TEXT NOT NULL,
+            resolved_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (session_id, provider, issue_key)
+        )
+        "#,
+        // 工时同步记录表
+        r#"
+        CREATE TABLE IF NOT EXISTS time_entry_sync (
+            provider VARCHAR(16) NOT NULL,
+            session_id BIGINT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+            entry_id VARCHAR(64) NOT NULL,
+            synced_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (provider, session_id)
+        )
+        "#,
+        // 会话备注表
+        r#"
+        CREATE TABLE IF NOT EXISTS session_notes (
+            session_id BIGINT PRIMARY KEY REFERENCES sessions(id) ON DELETE CASCADE,
+            note TEXT NOT NULL,
+            tags TEXT NOT NULL,
+            updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        // 语义向量表
+        r#"
+        CREATE TABLE IF NOT EXISTS vectors (
+            session_id BIGINT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+            owner_kind VARCHAR(16) NOT NULL,
+            owner_id BIGINT NOT NULL,
+            model VARCHAR(128) NOT NULL,
+            content_hash VARCHAR(64) NOT NULL,
+            vector BYTEA NOT NULL,
+            updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (session_id, owner_kind, owner_id)
+        )
+        "#,
+        // 前台应用使用时长表
+        r#"
+        CREATE TABLE IF NOT EXISTS app_usage (
+            session_id BIGINT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+            app_name VARCHAR(255) NOT NULL,
+            process_path TEXT,
+            window_title TEXT NOT NULL,
+            seconds BIGINT NOT NULL,
+            PRIMARY KEY (session_id, app_name)
+        )
+        "#,
+        // 网站域名访问时长表
+        r#"
+        CREATE TABLE IF NOT EXISTS domain_usage (
+            session_id BIGINT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+            domain VARCHAR(255) NOT NULL,
+            seconds BIGINT NOT NULL,
+            PRIMARY KEY (session_id, domain)
+        )
+        "#,
+        // 帧 OCR 文字表
+        r#"
+        CREATE TABLE IF NOT EXISTS frame_texts (
+            id BIGSERIAL PRIMARY KEY,
+            session_id BIGINT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+            timestamp TIMESTAMPTZ NOT NULL,
+            text TEXT NOT NULL
+        )
+        "#,
+        // LLM 响应缓存表（不关联会话，会话删除后重新分析相同画面仍可复用）
+        r#"
+        CREATE TABLE IF NOT EXISTS llm_cache (
+            cache_key VARCHAR(64) PRIMARY KEY,
+            task VARCHAR(32) NOT NULL,
+            provider VARCHAR(32) NOT NULL,
+            model VARCHAR(128) NOT NULL,
+            response TEXT NOT NULL,
+            created_at TIMESTAMPTZ NOT NULL
+        )
+        "#,
+        // LLM 解析失败记录表（不关联会话外键，会话删除后仍可排查）
+        r#"
+        CREATE TABLE IF NOT EXISTS llm_parse_failures (
+            id BIGSERIAL PRIMARY KEY,
+            session_id BIGINT,
+            provider VARCHAR(32) NOT NULL,
+            model VARCHAR(128) NOT NULL,
+            task VARCHAR(32) NOT NULL,
+            raw_response TEXT NOT NULL,
+            repaired_response TEXT,
+            errors TEXT NOT NULL,
+            repaired BOOLEAN NOT NULL DEFAULT FALSE,
+            created_at TIMESTAMPTZ NOT NULL
+        )
+        "#,
+        // 时间线卡片复核记录表
+        r#"
+        CREATE TABLE IF NOT EXISTS card_reviews (
+            card_id BIGINT PRIMARY KEY REFERENCES timeline_cards(id) ON DELETE CASCADE,
+            session_id BIGINT NOT NULL,
+            app VARCHAR(255) NOT NULL,
+            original_category VARCHAR(100) NOT NULL,
+            category VARCHAR(100) NOT NULL,
+            subcategory VARCHAR(100) NOT NULL DEFAULT '',
+            reviewed_at TIMESTAMPTZ NOT NULL
+        )
+        "#,
+        // 目标表与目标评估结果表
+        r#"
+        CREATE TABLE IF NOT EXISTS goals (
+            id BIGSERIAL PRIMARY KEY,
+            name VARCHAR(255) NOT NULL,
+            metric VARCHAR(32) NOT NULL,
+            target VARCHAR(512) NOT NULL DEFAULT '',
+            comparison VARCHAR(16) NOT NULL,
+            target_minutes BIGINT NOT NULL,
+            schedule VARCHAR(16) NOT NULL DEFAULT 'daily',
+            enabled BOOLEAN NOT NULL DEFAULT TRUE,
+            created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+        r#"
+        CREATE TABLE IF NOT EXISTS goal_results (
+            goal_id BIGINT NOT NULL,
+            date VARCHAR(10) NOT NULL,
+            actual_minutes BIGINT NOT NULL,
+            passed BOOLEAN NOT NULL,
+            evaluated_at TIMESTAMPTZ NOT NULL,
+            PRIMARY KEY (goal_id, date)
+        )
+        "#,
+        // 专注时段表
+        r#"
+        CREATE TABLE IF NOT EXISTS focus_blocks (
+            id BIGSERIAL PRIMARY KEY,
+            label VARCHAR(255) NOT NULL DEFAULT '',
+            planned_minutes BIGINT NOT NULL,
+            started_at TIMESTAMPTZ NOT NULL,
+            ended_at TIMESTAMPTZ NULL,
+            status VARCHAR(16) NOT NULL DEFAULT 'running',
+            distraction_count BIGINT NOT NULL DEFAULT 0,
+            distractions TEXT NOT NULL
+        )
+        "#,
+        // 索引
+        "CREATE INDEX IF NOT EXISTS idx_sessions_start_time ON sessions(start_time)",
+        "CREATE INDEX IF NOT EXISTS idx_sessions_start_end ON sessions(start_time, end_time)",
+        "CREATE INDEX IF NOT EXISTS idx_frames_session_id ON frames(session_id)",
+        "CREATE INDEX IF NOT EXISTS idx_frames_session_timestamp ON frames(session_id, timestamp)",
+        "CREATE INDEX IF NOT EXISTS idx_llm_calls_session_id ON llm_calls(session_id)",
+        "CREATE INDEX IF NOT EXISTS idx_llm_calls_created_at ON llm_calls(created_at)",
+        "CREATE INDEX IF NOT EXISTS idx_video_segments_session_id ON video_segments(session_id)",
+        "CREATE INDEX IF NOT EXISTS idx_timeline_cards_session_id ON timeline_cards(session_id)",
+        "CREATE INDEX IF NOT EXISTS idx_export_history_created_at ON export_history(created_at)",
+        "CREATE INDEX IF NOT EXISTS idx_notion_sync_date ON notion_sync(kind, date)",
+        "CREATE INDEX IF NOT EXISTS idx_calendar_events_date ON calendar_events(date)",
+        "CREATE INDEX IF NOT EXISTS idx_frame_texts_session_timestamp ON frame_texts(session_id, timestamp)",
+        "CREATE INDEX IF NOT EXISTS idx_llm_cache_created_at ON llm_cache(created_at)",
+        "CREATE INDEX IF NOT EXISTS idx_llm_parse_failures_created_at ON llm_parse_failures(created_at)",
+        "CREATE INDEX IF NOT EXISTS idx_card_reviews_app ON card_reviews(app)",
+        "CREATE INDEX IF NOT EXISTS idx_goal_results_date ON goal_results(date)",
+        "CREATE INDEX IF NOT EXISTS idx_focus_blocks_started_at ON focus_blocks(started_at)",
+    ])]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_search_sql_binds_one_placeholder_per_term() {
+        let sql = search_sql(0);
+        assert!(sql.contains("$1::date") && sql.contains("$2::date"));
+        assert!(!sql.contains("$3"));
+
+        // 每个搜索词的参数在 6 个字段中重复引用，编号紧接日期参数
+        let sql = search_sql(2);
+        assert_eq!(sql.matches("ILIKE $3").count(), 6);
+        assert_eq!(sql.matches("ILIKE $4").count(), 6);
+        assert!(!sql.contains("$5"));
+    }
+
+    #[test]
+    fn test_build_activity() {
+        let activity = build_activity(
+            "2024-05-12".to_string(),
+            3,
+            Some(95),
+            Some("work,study".to_string()),
+        );
+        assert_eq!(activity.date, "2024-05-12");
+        assert_eq!(activity.session_count, 3);
+        assert_eq!(activity.total_duration_minutes, 95);
+        assert_eq!(activity.main_categories, vec!["work", "study"]);
+
+        // 没有时长与类别（全部会话未打标签）
+        let activity = build_activity("2024-05-12".to_string(), 1, None, None);
+        assert_eq!(activity.total_duration_minutes, 0);
+        assert!(activity.main_categories.is_empty());
+    }
+
+    #[test]
+    fn test_postgres_migrations_order() {
+        let all = postgres_migrations();
+        assert_eq!(all.first().map(|m| m.version), Some(1));
+        assert_eq!(migrations::pending(&all, 0).unwrap().len(), all.len());
+
+        // 建表顺序满足外键依赖，且覆盖转储使用的全部表
+        let mut created: Vec<String> = Vec::new();
+        for step in all.iter().flat_map(|m| &m.steps) {
+            let sql = step.to_sql();
+            let Some(rest) = sql.split("CREATE TABLE IF NOT EXISTS ").nth(1) else {
+                continue;
+            };
+            for reference in sql.split("REFERENCES ").skip(1) {
+                let parent = reference.split('(').next().unwrap().trim();
+                assert!(
+                    created.iter().any(|table| table == parent),
+                    "{} 在被引用前尚未创建",
+                    parent
+                );
+            }
+            created.push(rest.split_whitespace().next().unwrap().to_string());
+        }
+        for table in TABLES {
+            assert!(
+                created.iter().any(|created| created == table),
+                "缺少表 {}",
+                table
+            );
+        }
+    }
+
+    /// 连接 DATABASE_URL 指向的测试库（会写入并删除测试数据，请勿指向生产库）
+    async fn test_repository() -> PostgresRepository {
+        let url = std::env::var("DATABASE_URL")
+            .expect("需要设置 DATABASE_URL 才能运行 PostgreSQL 集成测试");
+        let options = url
+            .parse::<PgConnectOptions>()
+            .unwrap()
+            .options([("timezone", "UTC")]);
+        let pool = PgPoolOptions::new()
+            .max_connections(2)
+            .connect_with(options)
+            .await
+            .unwrap();
+        let repo = PostgresRepository { pool };
+        repo.run_migrations().await.unwrap();
+        repo
+    }
+
+    fn sample_session(title: &str) -> Session {
+        Session {
+            id: None,
+            start_time: Utc.with_ymd_and_hms(2024, 5, 12, 9, 0, 0).unwrap(),
+            end_time: Utc.with_ymd_and_hms(2024, 5, 12, 9, 30, 0).unwrap(),
+            title: title.to_string(),
+            summary: "整理周报".to_string(),
+            video_path: None,
+            tags: "[]".to_string(),
+            created_at: None,
+            device_name: Some("postgres-test".to_string()),
+            device_type: Some("desktop".to_string()),
+        }
+    }
+
+    fn sample_card(title: &str) -> TimelineCardRecord {
+        TimelineCardRecord {
+            id: None,
+            session_id: 0,
+            llm_call_id: None,
+            start_time: "2024-05-12T09:00:00+00:00".to_string(),
+            end_time: "2024-05-12T09:30:00+00:00".to_string(),
+            category: "work".to_string(),
+            subcategory: "writing".to_string(),
+            title: title.to_string(),
+            summary: "整理周报".to_string(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: "{}".to_string(),
+            confidence: Some(0.9),
+            heuristic: false,
+            video_preview_path: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "需要 DATABASE_URL 指向的 PostgreSQL"]
+    async fn test_session_crud() {
+        let repo = test_repository().await;
+
+        let id = repo.insert_session(&sample_session("周报")).await.unwrap();
+        let session = repo.get_session(id).await.unwrap();
+        assert_eq!(session.title, "周报");
+        assert_eq!(
+            session.start_time,
+            Utc.with_ymd_and_hms(2024, 5, 12, 9, 0, 0).unwrap()
+        );
+
+        repo.update_session(id, "周报（改）", "已完成", Some("a.mp4"), "[]")
+            .await
+            .unwrap();
+        let session = repo.get_session(id).await.unwrap();
+        assert_eq!(session.title, "周报（改）");
+        assert_eq!(session.video_path.as_deref(), Some("a.mp4"));
+
+        let hits = repo
+            .search_sessions("已完成", "2024-05-12", "2024-05-12", 10)
+            .await
+            .unwrap();
+        assert!(hits.iter().any(|hit| hit.session_id == id));
+
+        repo.delete_session(id).await.unwrap();
+        assert!(repo.get_session(id).await.is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "需要 DATABASE_URL 指向的 PostgreSQL"]
+    async fn test_insert_session_with_cards() {
+        let repo = test_repository().await;
+
+        let cards = [sample_card("写周报"), sample_card("回复邮件")];
+        let id = repo
+            .insert_session_with_cards(&sample_session("同步会话"), &cards)
+            .await
+            .unwrap();
+        let stored = repo.get_timeline_cards_by_session(id).await.unwrap();
+        assert_eq!(stored.len(), 2);
+        assert!(stored.iter().all(|card| card.session_id == id));
+
+        // 删除会话时级联删除卡片
+        repo.delete_session(id).await.unwrap();
+        assert!(repo
+            .get_timeline_cards_by_session(id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -2,7 +2,10 @@
 
 use super::DatabaseRepository;
 use crate::storage::config::get_device_info;
+use crate::storage::migrations::{self, Migration, MigrationStep};
 use crate::storage::models::*;
+use crate::storage::search;
+use crate::storage::sqlcipher;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
@@ -17,7 +20,7 @@ pub struct SqliteRepository {
 
 impl SqliteRepository {
     /// 创建新的 SQLite 数据库连接
-    pub async fn new(db_path: &str) -> Result<Self> {
+    pub async fn new(db_path: &str, key: Option<&str>) -> Result<Self> {
         info!("初始化 SQLite 数据库: {}", db_path);
 
         // 确保数据库文件的目录存在
@@ -25,20 +28,23 @@ impl SqliteRepository {
             std::fs::create_dir_all(parent)?;
         }
 
-        // 创建连接池
+        // 创建连接池（有密钥时每个连接先执行 PRAGMA key）
         let pool = SqlitePoolOptions::new()
             .max_connections(20)
             .min_connections(2)
             .idle_timeout(std::time::Duration::from_secs(180))
             .max_lifetime(std::time::Duration::from_secs(1800))
             .acquire_timeout(std::time::Duration::from_secs(10))
-            .connect(&format!("sqlite:{}?mode=rwc", db_path))
+            .connect_with(
+                sqlcipher::connect_options(std::path::Path::new(db_path), key)
+                    .create_if_missing(true),
+            )
             .await?;
 
         let repo = Self { pool };
 
-        // 初始化表结构
-        repo.initialize_tables().await?;
+        // 执行结构迁移
+        repo.run_migrations().await?;
 
         Ok(repo)
     }
@@ -74,6 +80,43 @@ impl DatabaseRepository for SqliteRepository {
         Ok(result.last_insert_rowid())
     }
 
+    async fn insert_session_with_cards(
+        &self,
+        session: &Session,
+        cards: &[TimelineCardRecord],
+    ) -> Result<i64> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO sessions (start_time, end_time, title, summary, video_path, tags, device_name, device_type)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+        "#,
+        )
+        .bind(session.start_time)
+        .bind(session.end_time)
+        .bind(&session.title)
+        .bind(&session.summary)
+        .bind(&session.video_path)
+        .bind(&session.tags)
+        .bind(&session.device_name)
+        .bind(&session.device_type)
+        .execute(&mut *tx)
+        .await?;
+        let session_id = result.last_insert_rowid();
+        let cards: Vec<TimelineCardRecord> = cards
+            .iter()
+            .map(|card| TimelineCardRecord {
+                session_id,
+                ..card.clone()
+            })
+            .collect();
+        insert_card_rows(&mut tx, &cards).await?;
+
+        tx.commit().await?;
+        Ok(session_id)
+    }
+
     async fn insert_sessions(&self, sessions: &[Session]) -> Result<Vec<i64>> {
         let mut ids = Vec::new();
         let mut tx = self.pool.begin().await?;
@@ -196,6 +239,51 @@ impl DatabaseRepository for SqliteRepository {
         Ok(())
     }
 
+    async fn update_session_times(
+        &self,
+        session_id: i64,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query("UPDATE sessions SET start_time = ?, end_time = ? WHERE id = ?")
+            .bind(start_time)
+            .bind(end_time)
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn replace_session_content(
+        &self,
+        session_id: i64,
+        session: &Session,
+        cards: &[TimelineCardRecord],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "UPDATE sessions SET title = ?, summary = ?, tags = ?, start_time = ?, end_time = ? WHERE id = ?",
+        )
+        .bind(&session.title)
+        .bind(&session.summary)
+        .bind(&session.tags)
+        .bind(session.start_time)
+        .bind(session.end_time)
+        .bind(session_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM timeline_cards WHERE session_id = ?")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        insert_card_rows(&mut tx, cards).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
     async fn update_session_video_path(&self, session_id: i64, video_path: &str) -> Result<()> {
         sqlx::query("UPDATE sessions SET video_path = ? WHERE id = ?")
             .bind(video_path)
@@ -206,6 +294,15 @@ impl DatabaseRepository for SqliteRepository {
         Ok(())
     }
 
+    async fn clear_session_video_path(&self, session_id: i64) -> Result<()> {
+        sqlx::query("UPDATE sessions SET video_path = NULL WHERE id = ?")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
     async fn update_device_info_for_all_sessions(&self) -> Result<u64> {
         let (device_name, device_type) = get_device_info();
 
@@ -272,13 +369,18 @@ impl DatabaseRepository for SqliteRepository {
     async fn insert_frame(&self, frame: &Frame) -> Result<i64> {
         let result = sqlx::query(
             r#"
-            INSERT INTO frames (session_id, timestamp, file_path)
-            VALUES (?1, ?2, ?3)
+            INSERT INTO frames (session_id, timestamp, file_path, app_name, window_title, process_path, domain, screen_id)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
         "#,
         )
         .bind(frame.session_id)
         .bind(&frame.timestamp)
         .bind(&frame.file_path)
+        .bind(&frame.app_name)
+        .bind(&frame.window_title)
+        .bind(&frame.process_path)
+        .bind(&frame.domain)
+        .bind(frame.screen_id)
         .execute(&self.pool)
         .await?;
 
@@ -291,13 +393,18 @@ impl DatabaseRepository for SqliteRepository {
         for frame in frames {
             sqlx::query(
                 r#"
-                INSERT INTO frames (session_id, timestamp, file_path)
-                VALUES (?1, ?2, ?3)
+                INSERT INTO frames (session_id, timestamp, file_path, app_name, window_title, process_path, domain, screen_id)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
             "#,
             )
             .bind(frame.session_id)
             .bind(&frame.timestamp)
             .bind(&frame.file_path)
+            .bind(&frame.app_name)
+            .bind(&frame.window_title)
+            .bind(&frame.process_path)
+            .bind(&frame.domain)
+            .bind(frame.screen_id)
             .execute(&mut *tx)
             .await?;
         }
@@ -309,7 +416,7 @@ impl DatabaseRepository for SqliteRepository {
     async fn get_frames_by_session(&self, session_id: i64) -> Result<Vec<Frame>> {
         let frames = sqlx::query_as::<_, Frame>(
             r#"
-            SELECT id, session_id, timestamp, file_path
+            SELECT id, session_id, timestamp, file_path, app_name, window_title, process_path, domain, screen_id
             FROM frames
             WHERE session_id = ?
             ORDER BY timestamp
@@ -331,6 +438,82 @@ impl DatabaseRepository for SqliteRepository {
         Ok(())
     }
 
+    async fn delete_frames(&self, session_id: i64, frame_ids: &[i64]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for frame_id in frame_ids {
+            sqlx::query("DELETE FROM frames WHERE id = ? AND session_id = ?")
+                .bind(frame_id)
+                .bind(session_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn update_frame_path(
+        &self,
+        session_id: i64,
+        frame_id: i64,
+        file_path: &str,
+    ) -> Result<()> {
+        sqlx::query("UPDATE frames SET file_path = ? WHERE id = ? AND session_id = ?")
+            .bind(file_path)
+            .bind(frame_id)
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn move_frames(
+        &self,
+        from_session_id: i64,
+        to_session_id: i64,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut moved = 0;
+
+        for table in ["frames", "frame_texts"] {
+            let sql = format!(
+                "UPDATE {} SET session_id = ? WHERE session_id = ?{}",
+                table,
+                if since.is_some() {
+                    " AND timestamp >= ?"
+                } else {
+                    ""
+                }
+            );
+            let mut query = sqlx::query(&sql).bind(to_session_id).bind(from_session_id);
+            if let Some(since) = since {
+                query = query.bind(since);
+            }
+            let result = query.execute(&mut *tx).await?;
+            if table == "frames" {
+                moved = result.rows_affected();
+            }
+        }
+
+        // frame_texts 没有 UPDATE 触发器，手动刷新两个会话的 OCR 索引
+        for session_id in [from_session_id, to_session_id] {
+            sqlx::query(&format!(
+                "UPDATE session_search SET ocr = {} WHERE rowid = ?",
+                ocr_text_sql("?")
+            ))
+            .bind(session_id)
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(moved)
+    }
+
     // ========== 活动统计 ==========
 
     async fn get_activities(&self, start_date: &str, end_date: &str) -> Result<Vec<Activity>> {
@@ -378,6 +561,70 @@ impl DatabaseRepository for SqliteRepository {
         Ok(activities)
     }
 
+    async fn get_device_activities(
+        &self,
+        start_date: &str,
+        end_date: &str,
+        device_name: &str,
+    ) -> Result<Vec<Activity>> {
+        let start_datetime = format!("{} 00:00:00", start_date);
+        let end_datetime = format!("{} 23:59:59", end_date);
+
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                DATE(start_time) as date,
+                COUNT(*) as session_count,
+                SUM(CAST((julianday(end_time) - julianday(start_time)) * 24 * 60 AS INTEGER)) as total_duration_minutes,
+                GROUP_CONCAT(DISTINCT json_extract(tags, '$[0].category')) as main_categories
+            FROM sessions
+            WHERE start_time >= ? AND start_time <= ? AND device_name = ?
+            GROUP BY DATE(start_time)
+            ORDER BY date DESC
+            "#
+        )
+        .bind(&start_datetime)
+        .bind(&end_datetime)
+        .bind(device_name)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut activities = Vec::new();
+        for row in rows {
+            let date: String = row.try_get("date")?;
+            let session_count: i32 = row.try_get("session_count")?;
+            let total_duration_minutes: Option<i32> = row.try_get("total_duration_minutes")?;
+            let main_categories_str: Option<String> = row.try_get("main_categories")?;
+
+            let main_categories = main_categories_str
+                .map(|s| s.split(',').map(|s| s.to_string()).collect())
+                .unwrap_or_default();
+
+            activities.push(Activity {
+                date,
+                session_count,
+                total_duration_minutes: total_duration_minutes.unwrap_or(0),
+                main_categories,
+            });
+        }
+
+        Ok(activities)
+    }
+
+    async fn get_device_names(&self) -> Result<Vec<String>> {
+        let names = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT DISTINCT device_name FROM sessions
+            WHERE device_name IS NOT NULL AND device_name <> ''
+            ORDER BY device_name
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(names)
+    }
+
     // ========== LLM 调用记录 ==========
 
     async fn insert_llm_call(&self, record: &LLMCallRecord) -> Result<i64> {
@@ -536,9 +783,9 @@ impl DatabaseRepository for SqliteRepository {
             INSERT INTO timeline_cards (
                 session_id, llm_call_id, start_time, end_time,
                 category, subcategory, title, summary, detailed_summary,
-                distractions, app_sites, video_preview_path, created_at
+                distractions, app_sites, confidence, heuristic, video_preview_path, created_at
             )
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
         "#,
         )
         .bind(&card.session_id)
@@ -552,6 +799,8 @@ impl DatabaseRepository for SqliteRepository {
         .bind(&card.detailed_summary)
         .bind(&card.distractions)
         .bind(&card.app_sites)
+        .bind(card.confidence)
+        .bind(card.heuristic)
         .bind(&card.video_preview_path)
         .bind(&card.created_at)
         .execute(&self.pool)
@@ -562,35 +811,7 @@ impl DatabaseRepository for SqliteRepository {
 
     async fn insert_timeline_cards(&self, cards: &[TimelineCardRecord]) -> Result<()> {
         let mut tx = self.pool.begin().await?;
-
-        for card in cards {
-            sqlx::query(
-                r#"
-                INSERT INTO timeline_cards (
-                    session_id, llm_call_id, start_time, end_time,
-                    category, subcategory, title, summary, detailed_summary,
-                    distractions, app_sites, video_preview_path, created_at
-                )
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
-            "#,
-            )
-            .bind(&card.session_id)
-            .bind(&card.llm_call_id)
-            .bind(&card.start_time)
-            .bind(&card.end_time)
-            .bind(&card.category)
-            .bind(&card.subcategory)
-            .bind(&card.title)
-            .bind(&card.summary)
-            .bind(&card.detailed_summary)
-            .bind(&card.distractions)
-            .bind(&card.app_sites)
-            .bind(&card.video_preview_path)
-            .bind(&card.created_at)
-            .execute(&mut *tx)
-            .await?;
-        }
-
+        insert_card_rows(&mut tx, cards).await?;
         tx.commit().await?;
         Ok(())
     }
@@ -637,6 +858,32 @@ impl DatabaseRepository for SqliteRepository {
         Ok(())
     }
 
+    async fn update_timeline_card_title(&self, id: i64, title: &str) -> Result<()> {
+        sqlx::query("UPDATE timeline_cards SET title = ? WHERE id = ?")
+            .bind(title)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn update_timeline_card_category(
+        &self,
+        id: i64,
+        category: &str,
+        subcategory: &str,
+    ) -> Result<()> {
+        sqlx::query("UPDATE timeline_cards SET category = ?, subcategory = ? WHERE id = ?")
+            .bind(category)
+            .bind(subcategory)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
     // ========== 统计信息 ==========
 
     async fn get_stats(&self) -> Result<(i64, i64, i64)> {
@@ -682,252 +929,1239 @@ impl DatabaseRepository for SqliteRepository {
 
     // ========== 数据库初始化 ==========
 
-    async fn initialize_tables(&self) -> Result<()> {
-        // 创建会话表
+    async fn run_migrations(&self) -> Result<()> {
+        sqlx::query(migrations::CREATE_VERSION_TABLE_SQL)
+            .execute(&self.pool)
+            .await?;
+        let current: i64 = sqlx::query_scalar(migrations::CURRENT_VERSION_SQL)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let all = sqlite_migrations();
+        for migration in migrations::pending(&all, current)? {
+            info!(
+                "应用 SQLite 结构迁移 v{}: {}",
+                migration.version, migration.description
+            );
+
+            // 每个版本在单个事务中执行，任一步骤失败时整体回滚，下次启动重试
+            let mut tx = self.pool.begin().await?;
+            for step in &migration.steps {
+                match step {
+                    MigrationStep::Sql(sql) => {
+                        sqlx::query(sql).execute(&mut *tx).await?;
+                    }
+                    // SQLite 不支持 ADD COLUMN IF NOT EXISTS，先查询列是否存在
+                    MigrationStep::AddColumn {
+                        table,
+                        column,
+                        definition,
+                    } => {
+                        let exists: i64 = sqlx::query_scalar(
+                            "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+                        )
+                        .bind(table)
+                        .bind(column)
+                        .fetch_one(&mut *tx)
+                        .await?;
+                        if exists == 0 {
+                            sqlx::query(&format!(
+                                "ALTER TABLE {} ADD COLUMN {} {}",
+                                table, column, definition
+                            ))
+                            .execute(&mut *tx)
+                            .await?;
+                        }
+                    }
+                }
+            }
+            sqlx::query("INSERT INTO schema_version (version, description) VALUES (?1, ?2)")
+                .bind(migration.version)
+                .bind(migration.description)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+        }
+
+        info!(
+            "SQLite 数据库结构版本: {}",
+            migrations::latest_version(&all)
+        );
+        Ok(())
+    }
+
+    async fn save_day_summary(&self, date: &str, summary: &DaySummaryRecord) -> Result<()> {
+        // 使用 INSERT OR REPLACE 实现 upsert
         sqlx::query(
             r#"
-            CREATE TABLE IF NOT EXISTS sessions (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                start_time DATETIME NOT NULL,
-                end_time DATETIME NOT NULL,
-                title TEXT NOT NULL,
-                summary TEXT NOT NULL,
-                video_path TEXT,
-                tags TEXT NOT NULL,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                device_name TEXT,
-                device_type TEXT
-            )
-        "#,
+            INSERT OR REPLACE INTO day_summaries (
+                date, summary_text, device_stats, parallel_work, usage_patterns,
+                active_device_count, llm_call_id, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+            "#,
         )
+        .bind(date)
+        .bind(&summary.summary_text)
+        .bind(&summary.device_stats)
+        .bind(&summary.parallel_work)
+        .bind(&summary.usage_patterns)
+        .bind(summary.active_device_count)
+        .bind(summary.llm_call_id)
         .execute(&self.pool)
         .await?;
 
-        // 创建帧表
-        sqlx::query(
+        Ok(())
+    }
+
+    async fn get_day_summary(&self, date: &str) -> Result<Option<DaySummaryRecord>> {
+        let result = sqlx::query_as::<_, DaySummaryRecord>(
             r#"
-            CREATE TABLE IF NOT EXISTS frames (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                session_id INTEGER NOT NULL,
-                timestamp DATETIME NOT NULL,
-                file_path TEXT NOT NULL,
-                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
-            )
-        "#,
+            SELECT * FROM day_summaries WHERE date = ?
+            "#,
         )
-        .execute(&self.pool)
+        .bind(date)
+        .fetch_optional(&self.pool)
         .await?;
 
-        // 创建索引
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_sessions_start_time ON sessions(start_time)")
-            .execute(&self.pool)
-            .await?;
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_frames_session_id ON frames(session_id)")
-            .execute(&self.pool)
-            .await?;
+        Ok(result)
+    }
+
+    async fn delete_day_summary(&self, date: &str) -> Result<()> {
         sqlx::query(
-            "CREATE INDEX IF NOT EXISTS idx_sessions_start_end ON sessions(start_time, end_time)",
+            r#"
+            DELETE FROM day_summaries WHERE date = ?
+            "#,
         )
+        .bind(date)
         .execute(&self.pool)
         .await?;
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_frames_session_timestamp ON frames(session_id, timestamp)")
-            .execute(&self.pool)
-            .await?;
 
-        // 创建LLM调用记录表
+        Ok(())
+    }
+
+    async fn save_daily_focus_metrics(
+        &self,
+        date: &str,
+        metrics: &DailyFocusMetricsRecord,
+    ) -> Result<()> {
         sqlx::query(
             r#"
-            CREATE TABLE IF NOT EXISTS llm_calls (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                session_id INTEGER,
-                provider TEXT NOT NULL,
-                model TEXT NOT NULL,
-                call_type TEXT NOT NULL,
-                request_headers TEXT NOT NULL,
-                request_body TEXT NOT NULL,
-                response_headers TEXT,
-                response_body TEXT,
-                status_code INTEGER,
-                error_message TEXT,
-                latency_ms INTEGER,
-                token_usage TEXT,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+            INSERT OR REPLACE INTO daily_focus_metrics (
+                date, total_minutes, work_minutes, learning_minutes, communication_minutes,
+                personal_minutes, idle_minutes, other_minutes, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+            "#,
+        )
+        .bind(date)
+        .bind(metrics.total_minutes)
+        .bind(metrics.work_minutes)
+        .bind(metrics.learning_minutes)
+        .bind(metrics.communication_minutes)
+        .bind(metrics.personal_minutes)
+        .bind(metrics.idle_minutes)
+        .bind(metrics.other_minutes)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_daily_focus_metrics(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DailyFocusMetricsRecord>> {
+        let result = sqlx::query_as::<_, DailyFocusMetricsRecord>(
+            r#"
+            SELECT * FROM daily_focus_metrics WHERE date >= ? AND date <= ? ORDER BY date
+            "#,
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn delete_daily_focus_metrics(&self, date: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM daily_focus_metrics WHERE date = ?
+            "#,
+        )
+        .bind(date)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert_export_history(&self, record: &ExportHistoryRecord) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO export_history (
+                kind, target, request, success, message,
+                written_paths, warnings, failed_sessions, created_at
             )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
         "#,
         )
-        .execute(&self.pool)
+        .bind(&record.kind)
+        .bind(&record.target)
+        .bind(&record.request)
+        .bind(record.success)
+        .bind(&record.message)
+        .bind(&record.written_paths)
+        .bind(&record.warnings)
+        .bind(&record.failed_sessions)
+        .bind(record.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn get_export_history(&self, limit: i64) -> Result<Vec<ExportHistoryRecord>> {
+        let records = sqlx::query_as::<_, ExportHistoryRecord>(
+            r#"
+            SELECT * FROM export_history ORDER BY created_at DESC, id DESC LIMIT ?
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn get_export_history_entry(&self, id: i64) -> Result<ExportHistoryRecord> {
+        let record = sqlx::query_as::<_, ExportHistoryRecord>(
+            r#"
+            SELECT * FROM export_history WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    async fn insert_project_rule(&self, rule: &ProjectRuleRecord) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO project_rules (project, match_field, pattern, created_at)
+            VALUES (?1, ?2, ?3, ?4)
+        "#,
+        )
+        .bind(&rule.project)
+        .bind(&rule.match_field)
+        .bind(&rule.pattern)
+        .bind(rule.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn get_project_rules(&self) -> Result<Vec<ProjectRuleRecord>> {
+        let rules = sqlx::query_as::<_, ProjectRuleRecord>(
+            r#"
+            SELECT * FROM project_rules ORDER BY id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rules)
+    }
+
+    async fn delete_project_rule(&self, id: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM project_rules WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert_custom_category(&self, category: &CustomCategoryRecord) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO custom_categories (name, bucket, description, subcategories, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+        "#,
+        )
+        .bind(&category.name)
+        .bind(&category.bucket)
+        .bind(&category.description)
+        .bind(&category.subcategories)
+        .bind(category.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn get_custom_categories(&self) -> Result<Vec<CustomCategoryRecord>> {
+        let categories = sqlx::query_as::<_, CustomCategoryRecord>(
+            r#"
+            SELECT * FROM custom_categories ORDER BY id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(categories)
+    }
+
+    async fn delete_custom_category(&self, id: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM custom_categories WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert_classification_rule(&self, rule: &ClassificationRuleRecord) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO classification_rules (match_field, pattern, category, subcategory, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+        "#,
+        )
+        .bind(&rule.match_field)
+        .bind(&rule.pattern)
+        .bind(&rule.category)
+        .bind(&rule.subcategory)
+        .bind(rule.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn get_classification_rules(&self) -> Result<Vec<ClassificationRuleRecord>> {
+        let rules = sqlx::query_as::<_, ClassificationRuleRecord>(
+            r#"
+            SELECT * FROM classification_rules ORDER BY id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rules)
+    }
+
+    async fn delete_classification_rule(&self, id: i64) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM classification_rules WHERE id = ?
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn save_notion_sync(&self, record: &NotionSyncRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO notion_sync (kind, local_key, date, page_id, synced_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&record.kind)
+        .bind(&record.local_key)
+        .bind(&record.date)
+        .bind(&record.page_id)
+        .bind(record.synced_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_notion_sync(
+        &self,
+        kind: &str,
+        local_key: &str,
+    ) -> Result<Option<NotionSyncRecord>> {
+        let record = sqlx::query_as::<_, NotionSyncRecord>(
+            r#"
+            SELECT * FROM notion_sync WHERE kind = ? AND local_key = ?
+            "#,
+        )
+        .bind(kind)
+        .bind(local_key)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    async fn get_notion_syncs_by_date(
+        &self,
+        kind: &str,
+        date: &str,
+    ) -> Result<Vec<NotionSyncRecord>> {
+        let records = sqlx::query_as::<_, NotionSyncRecord>(
+            r#"
+            SELECT * FROM notion_sync WHERE kind = ? AND date = ? ORDER BY local_key
+            "#,
+        )
+        .bind(kind)
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn delete_notion_sync(&self, kind: &str, local_key: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM notion_sync WHERE kind = ? AND local_key = ?
+            "#,
+        )
+        .bind(kind)
+        .bind(local_key)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn replace_calendar_events(
+        &self,
+        date: &str,
+        events: &[CalendarEventRecord],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM calendar_events WHERE date = ?")
+            .bind(date)
+            .execute(&mut *tx)
+            .await?;
+        for event in events {
+            sqlx::query(
+                r#"
+                INSERT INTO calendar_events (event_id, date, title, location, start_time, end_time, synced_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&event.event_id)
+            .bind(date)
+            .bind(&event.title)
+            .bind(&event.location)
+            .bind(event.start_time)
+            .bind(event.end_time)
+            .bind(event.synced_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_calendar_events_by_date(&self, date: &str) -> Result<Vec<CalendarEventRecord>> {
+        let events = sqlx::query_as::<_, CalendarEventRecord>(
+            r#"
+            SELECT * FROM calendar_events WHERE date = ? ORDER BY start_time
+            "#,
+        )
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    async fn replace_issue_links(&self, session_id: i64, links: &[IssueLinkRecord]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM issue_links WHERE session_id = ?")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        for link in links {
+            sqlx::query(
+                r#"
+                INSERT INTO issue_links (session_id, provider, issue_key, title, state, url, resolved_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(session_id)
+            .bind(&link.provider)
+            .bind(&link.issue_key)
+            .bind(&link.title)
+            .bind(&link.state)
+            .bind(&link.url)
+            .bind(link.resolved_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_issue_links_by_session(&self, session_id: i64) -> Result<Vec<IssueLinkRecord>> {
+        let links = sqlx::query_as::<_, IssueLinkRecord>(
+            r#"
+            SELECT * FROM issue_links WHERE session_id = ? ORDER BY provider, issue_key
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(links)
+    }
+
+    async fn save_time_entry_sync(&self, record: &TimeEntrySyncRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO time_entry_sync (provider, session_id, entry_id, synced_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(&record.provider)
+        .bind(record.session_id)
+        .bind(&record.entry_id)
+        .bind(record.synced_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_time_entry_sync(
+        &self,
+        provider: &str,
+        session_id: i64,
+    ) -> Result<Option<TimeEntrySyncRecord>> {
+        let record = sqlx::query_as::<_, TimeEntrySyncRecord>(
+            r#"
+            SELECT * FROM time_entry_sync WHERE provider = ? AND session_id = ?
+            "#,
+        )
+        .bind(provider)
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    async fn save_session_note(&self, record: &SessionNoteRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO session_notes (session_id, note, tags, updated_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(record.session_id)
+        .bind(&record.note)
+        .bind(&record.tags)
+        .bind(record.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_session_note(&self, session_id: i64) -> Result<Option<SessionNoteRecord>> {
+        let record = sqlx::query_as::<_, SessionNoteRecord>(
+            r#"
+            SELECT * FROM session_notes WHERE session_id = ?
+            "#,
+        )
+        .bind(session_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    async fn delete_session_note(&self, session_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM session_notes WHERE session_id = ?")
+            .bind(session_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn replace_app_usage(&self, session_id: i64, records: &[AppUsageRecord]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM app_usage WHERE session_id = ?")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        for record in records {
+            sqlx::query(
+                r#"
+                INSERT INTO app_usage (session_id, app_name, process_path, window_title, seconds)
+                VALUES (?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(session_id)
+            .bind(&record.app_name)
+            .bind(&record.process_path)
+            .bind(&record.window_title)
+            .bind(record.seconds)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_app_usage_by_session(&self, session_id: i64) -> Result<Vec<AppUsageRecord>> {
+        let records = sqlx::query_as::<_, AppUsageRecord>(
+            "SELECT * FROM app_usage WHERE session_id = ? ORDER BY seconds DESC, app_name",
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn get_app_usage_summary(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<AppUsageSummary>> {
+        let summaries = sqlx::query_as::<_, AppUsageSummary>(
+            r#"
+            SELECT a.app_name, MAX(a.process_path) AS process_path,
+                   SUM(a.seconds) AS seconds, COUNT(DISTINCT a.session_id) AS session_count
+            FROM app_usage a
+            JOIN sessions s ON s.id = a.session_id
+            WHERE DATE(s.start_time) >= ? AND DATE(s.start_time) <= ?
+            GROUP BY a.app_name
+            ORDER BY seconds DESC, a.app_name
+            "#,
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(summaries)
+    }
+
+    async fn get_daily_app_usage(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DailyAppUsage>> {
+        let usage = sqlx::query_as::<_, DailyAppUsage>(
+            r#"
+            SELECT DATE(s.start_time) AS date, a.app_name, SUM(a.seconds) AS seconds
+            FROM app_usage a
+            JOIN sessions s ON s.id = a.session_id
+            WHERE DATE(s.start_time) >= ? AND DATE(s.start_time) <= ?
+            GROUP BY DATE(s.start_time), a.app_name
+            ORDER BY date, seconds DESC, a.app_name
+            "#,
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(usage)
+    }
+
+    async fn replace_domain_usage(
+        &self,
+        session_id: i64,
+        records: &[DomainUsageRecord],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM domain_usage WHERE session_id = ?")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        for record in records {
+            sqlx::query("INSERT INTO domain_usage (session_id, domain, seconds) VALUES (?, ?, ?)")
+                .bind(session_id)
+                .bind(&record.domain)
+                .bind(record.seconds)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_domain_usage_by_session(&self, session_id: i64) -> Result<Vec<DomainUsageRecord>> {
+        let records = sqlx::query_as::<_, DomainUsageRecord>(
+            "SELECT * FROM domain_usage WHERE session_id = ? ORDER BY seconds DESC, domain",
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn get_domain_usage_summary(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<DomainUsageSummary>> {
+        let summaries = sqlx::query_as::<_, DomainUsageSummary>(
+            r#"
+            SELECT d.domain, SUM(d.seconds) AS seconds,
+                   COUNT(DISTINCT d.session_id) AS session_count
+            FROM domain_usage d
+            JOIN sessions s ON s.id = d.session_id
+            WHERE DATE(s.start_time) >= ? AND DATE(s.start_time) <= ?
+            GROUP BY d.domain
+            ORDER BY seconds DESC, d.domain
+            "#,
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(summaries)
+    }
+
+    async fn insert_frame_texts(&self, texts: &[FrameText]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for text in texts {
+            sqlx::query("INSERT INTO frame_texts (session_id, timestamp, text) VALUES (?, ?, ?)")
+                .bind(text.session_id)
+                .bind(text.timestamp)
+                .bind(&text.text)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_frame_texts_by_session(&self, session_id: i64) -> Result<Vec<FrameText>> {
+        let texts = sqlx::query_as::<_, FrameText>(
+            "SELECT id, session_id, timestamp, text FROM frame_texts WHERE session_id = ? ORDER BY timestamp",
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(texts)
+    }
+
+    async fn get_llm_cache(&self, cache_key: &str) -> Result<Option<LlmCacheEntry>> {
+        let entry = sqlx::query_as::<_, LlmCacheEntry>(
+            "SELECT cache_key, task, provider, model, response, created_at FROM llm_cache WHERE cache_key = ?",
+        )
+        .bind(cache_key)
+        .fetch_optional(&self.pool)
         .await?;
 
-        // 创建视频分段表
+        Ok(entry)
+    }
+
+    async fn save_llm_cache(&self, entry: &LlmCacheEntry) -> Result<()> {
         sqlx::query(
             r#"
-            CREATE TABLE IF NOT EXISTS video_segments (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                session_id INTEGER NOT NULL,
-                llm_call_id INTEGER,
-                start_timestamp TEXT NOT NULL,
-                end_timestamp TEXT NOT NULL,
-                description TEXT NOT NULL,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
-                FOREIGN KEY (llm_call_id) REFERENCES llm_calls(id) ON DELETE SET NULL
+            INSERT OR REPLACE INTO llm_cache (cache_key, task, provider, model, response, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&entry.cache_key)
+        .bind(&entry.task)
+        .bind(&entry.provider)
+        .bind(&entry.model)
+        .bind(&entry.response)
+        .bind(entry.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn prune_llm_cache(&self, before: Option<DateTime<Utc>>) -> Result<u64> {
+        let result = match before {
+            Some(before) => {
+                sqlx::query("DELETE FROM llm_cache WHERE created_at < ?")
+                    .bind(before)
+                    .execute(&self.pool)
+                    .await?
+            }
+            None => {
+                sqlx::query("DELETE FROM llm_cache")
+                    .execute(&self.pool)
+                    .await?
+            }
+        };
+
+        Ok(result.rows_affected())
+    }
+
+    async fn insert_llm_parse_failure(&self, failure: &LlmParseFailure) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO llm_parse_failures (
+                session_id, provider, model, task, raw_response, repaired_response,
+                errors, repaired, created_at
             )
-        "#,
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
         )
+        .bind(failure.session_id)
+        .bind(&failure.provider)
+        .bind(&failure.model)
+        .bind(&failure.task)
+        .bind(&failure.raw_response)
+        .bind(&failure.repaired_response)
+        .bind(&failure.errors)
+        .bind(failure.repaired)
+        .bind(failure.created_at)
         .execute(&self.pool)
         .await?;
 
-        // 创建时间线卡片表
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn get_llm_parse_failures(&self, limit: i64) -> Result<Vec<LlmParseFailure>> {
+        let failures = sqlx::query_as::<_, LlmParseFailure>(
+            "SELECT * FROM llm_parse_failures ORDER BY created_at DESC, id DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(failures)
+    }
+
+    async fn get_timeline_card(&self, id: i64) -> Result<Option<TimelineCardRecord>> {
+        let card =
+            sqlx::query_as::<_, TimelineCardRecord>("SELECT * FROM timeline_cards WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(card)
+    }
+
+    async fn get_review_queue(
+        &self,
+        threshold: f64,
+        limit: i64,
+    ) -> Result<Vec<TimelineCardRecord>> {
+        let cards = sqlx::query_as::<_, TimelineCardRecord>(
+            r#"
+            SELECT c.* FROM timeline_cards c
+            WHERE c.confidence IS NOT NULL AND c.confidence < ?
+              AND LOWER(c.category) <> 'idle'
+              AND NOT EXISTS (SELECT 1 FROM card_reviews r WHERE r.card_id = c.id)
+            ORDER BY c.start_time DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(threshold)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(cards)
+    }
+
+    async fn save_card_review(&self, review: &CardReviewRecord) -> Result<()> {
         sqlx::query(
             r#"
-            CREATE TABLE IF NOT EXISTS timeline_cards (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                session_id INTEGER NOT NULL,
-                llm_call_id INTEGER,
-                start_time TEXT NOT NULL,
-                end_time TEXT NOT NULL,
-                category TEXT NOT NULL,
-                subcategory TEXT NOT NULL,
-                title TEXT NOT NULL,
-                summary TEXT NOT NULL,
-                detailed_summary TEXT NOT NULL,
-                distractions TEXT,
-                app_sites TEXT NOT NULL,
-                video_preview_path TEXT,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
-                FOREIGN KEY (llm_call_id) REFERENCES llm_calls(id) ON DELETE SET NULL
+            INSERT OR REPLACE INTO card_reviews (
+                card_id, session_id, app, original_category, category, subcategory, reviewed_at
             )
-        "#,
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
         )
+        .bind(review.card_id)
+        .bind(review.session_id)
+        .bind(&review.app)
+        .bind(&review.original_category)
+        .bind(&review.category)
+        .bind(&review.subcategory)
+        .bind(review.reviewed_at)
         .execute(&self.pool)
         .await?;
 
-        // 创建每日总结表（缓存）
-        sqlx::query(
+        Ok(())
+    }
+
+    async fn get_card_reviews_by_app(&self, app: &str) -> Result<Vec<CardReviewRecord>> {
+        let reviews = sqlx::query_as::<_, CardReviewRecord>(
+            "SELECT * FROM card_reviews WHERE app = ? ORDER BY reviewed_at",
+        )
+        .bind(app)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(reviews)
+    }
+
+    async fn insert_goal(&self, goal: &GoalRecord) -> Result<i64> {
+        let result = sqlx::query(
             r#"
-            CREATE TABLE IF NOT EXISTS day_summaries (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                date DATE NOT NULL UNIQUE,
-                summary_text TEXT NOT NULL,
-                device_stats TEXT NOT NULL,
-                parallel_work TEXT NOT NULL,
-                usage_patterns TEXT NOT NULL,
-                active_device_count INTEGER NOT NULL,
-                llm_call_id INTEGER,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                FOREIGN KEY (llm_call_id) REFERENCES llm_calls(id) ON DELETE SET NULL
+            INSERT INTO goals (
+                name, metric, target, comparison, target_minutes, schedule, enabled, created_at
             )
-        "#,
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
         )
+        .bind(&goal.name)
+        .bind(&goal.metric)
+        .bind(&goal.target)
+        .bind(&goal.comparison)
+        .bind(goal.target_minutes)
+        .bind(&goal.schedule)
+        .bind(goal.enabled)
+        .bind(goal.created_at)
         .execute(&self.pool)
         .await?;
 
-        // 创建额外的索引
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_llm_calls_session_id ON llm_calls(session_id)")
-            .execute(&self.pool)
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn update_goal(&self, goal: &GoalRecord) -> Result<()> {
+        let id = goal.id.ok_or_else(|| anyhow::anyhow!("目标缺少 ID"))?;
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE goals
+            SET name = ?, metric = ?, target = ?, comparison = ?, target_minutes = ?,
+                schedule = ?, enabled = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&goal.name)
+        .bind(&goal.metric)
+        .bind(&goal.target)
+        .bind(&goal.comparison)
+        .bind(goal.target_minutes)
+        .bind(&goal.schedule)
+        .bind(goal.enabled)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM goal_results WHERE goal_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
             .await?;
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_llm_calls_created_at ON llm_calls(created_at)")
-            .execute(&self.pool)
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_goals(&self) -> Result<Vec<GoalRecord>> {
+        let goals = sqlx::query_as::<_, GoalRecord>("SELECT * FROM goals ORDER BY id")
+            .fetch_all(&self.pool)
             .await?;
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_video_segments_session_id ON video_segments(session_id)")
-            .execute(&self.pool)
+
+        Ok(goals)
+    }
+
+    async fn delete_goal(&self, id: i64) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM goal_results WHERE goal_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
             .await?;
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_timeline_cards_session_id ON timeline_cards(session_id)")
-            .execute(&self.pool)
+        sqlx::query("DELETE FROM goals WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
             .await?;
 
-        // 数据库迁移: 为已存在的sessions表添加设备字段
-        let check_device_name = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM pragma_table_info('sessions') WHERE name='device_name'",
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn save_goal_result(&self, result: &GoalResultRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO goal_results (goal_id, date, actual_minutes, passed, evaluated_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
         )
-        .fetch_one(&self.pool)
+        .bind(result.goal_id)
+        .bind(&result.date)
+        .bind(result.actual_minutes)
+        .bind(result.passed)
+        .bind(result.evaluated_at)
+        .execute(&self.pool)
         .await?;
 
-        if check_device_name == 0 {
-            info!("迁移数据库: 添加device_name和device_type字段");
-            sqlx::query("ALTER TABLE sessions ADD COLUMN device_name TEXT")
-                .execute(&self.pool)
-                .await?;
-            sqlx::query("ALTER TABLE sessions ADD COLUMN device_type TEXT")
-                .execute(&self.pool)
-                .await?;
+        Ok(())
+    }
 
-            let (device_name, device_type) = get_device_info();
+    async fn get_goal_results(
+        &self,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<GoalResultRecord>> {
+        let results = sqlx::query_as::<_, GoalResultRecord>(
+            "SELECT * FROM goal_results WHERE date >= ? AND date <= ? ORDER BY goal_id, date",
+        )
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
+        .await?;
 
-            sqlx::query(
-                "UPDATE sessions SET device_name = ?, device_type = ? WHERE device_name IS NULL",
-            )
-            .bind(&device_name)
-            .bind(&device_type)
+        Ok(results)
+    }
+
+    async fn delete_goal_results_by_date(&self, date: &str) -> Result<()> {
+        sqlx::query("DELETE FROM goal_results WHERE date = ?")
+            .bind(date)
             .execute(&self.pool)
             .await?;
 
-            info!(
-                "设备信息迁移完成: device_name={}, device_type={}",
-                device_name, device_type
-            );
-        }
-
-        info!("SQLite 数据库表初始化完成");
         Ok(())
     }
 
-    async fn save_day_summary(&self, date: &str, summary: &DaySummaryRecord) -> Result<()> {
-        // 使用 INSERT OR REPLACE 实现 upsert
+    async fn insert_focus_block(&self, block: &FocusBlockRecord) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO focus_blocks (
+                label, planned_minutes, started_at, ended_at, status, distraction_count, distractions
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&block.label)
+        .bind(block.planned_minutes)
+        .bind(block.started_at)
+        .bind(block.ended_at)
+        .bind(&block.status)
+        .bind(block.distraction_count)
+        .bind(&block.distractions)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn update_focus_block(&self, block: &FocusBlockRecord) -> Result<()> {
+        let id = block.id.ok_or_else(|| anyhow::anyhow!("专注时段缺少 ID"))?;
         sqlx::query(
             r#"
-            INSERT OR REPLACE INTO day_summaries (
-                date, summary_text, device_stats, parallel_work, usage_patterns,
-                active_device_count, llm_call_id, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+            UPDATE focus_blocks
+            SET ended_at = ?, status = ?, distraction_count = ?, distractions = ?
+            WHERE id = ?
             "#,
         )
-        .bind(date)
-        .bind(&summary.summary_text)
-        .bind(&summary.device_stats)
-        .bind(&summary.parallel_work)
-        .bind(&summary.usage_patterns)
-        .bind(summary.active_device_count)
-        .bind(summary.llm_call_id)
+        .bind(block.ended_at)
+        .bind(&block.status)
+        .bind(block.distraction_count)
+        .bind(&block.distractions)
+        .bind(id)
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
-    async fn get_day_summary(&self, date: &str) -> Result<Option<DaySummaryRecord>> {
-        let result = sqlx::query_as::<_, DaySummaryRecord>(
+    async fn get_focus_blocks(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<FocusBlockRecord>> {
+        let blocks = sqlx::query_as::<_, FocusBlockRecord>(
             r#"
-            SELECT * FROM day_summaries WHERE date = ?
+            SELECT * FROM focus_blocks
+            WHERE started_at < ? AND (ended_at IS NULL OR ended_at > ?)
+            ORDER BY started_at
             "#,
         )
-        .bind(date)
-        .fetch_optional(&self.pool)
+        .bind(end)
+        .bind(start)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(blocks)
+    }
+
+    async fn replace_embeddings(&self, session_id: i64, records: &[EmbeddingRecord]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM vectors WHERE session_id = ?")
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await?;
+        for record in records {
+            sqlx::query(
+                r#"
+                INSERT INTO vectors (session_id, owner_kind, owner_id, model, content_hash, vector, updated_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(session_id)
+            .bind(&record.owner_kind)
+            .bind(record.owner_id)
+            .bind(&record.model)
+            .bind(&record.content_hash)
+            .bind(&record.vector)
+            .bind(record.updated_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_embeddings_by_session(&self, session_id: i64) -> Result<Vec<EmbeddingRecord>> {
+        let records = sqlx::query_as::<_, EmbeddingRecord>(
+            r#"
+            SELECT * FROM vectors WHERE session_id = ? ORDER BY owner_kind DESC, owner_id
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    async fn get_embeddings(
+        &self,
+        model: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<Vec<EmbeddingRecord>> {
+        let records = sqlx::query_as::<_, EmbeddingRecord>(
+            r#"
+            SELECT v.* FROM vectors v
+            JOIN sessions s ON s.id = v.session_id
+            WHERE v.model = ? AND DATE(s.start_time) >= ? AND DATE(s.start_time) <= ?
+            "#,
+        )
+        .bind(model)
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(result)
+        Ok(records)
     }
 
-    async fn delete_day_summary(&self, date: &str) -> Result<()> {
-        sqlx::query(
+    async fn search_sessions(
+        &self,
+        query: &str,
+        start_date: &str,
+        end_date: &str,
+        limit: i64,
+    ) -> Result<Vec<SessionSearchHit>> {
+        let terms = search::search_terms(query);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        if let Some(expression) = search::fts5_match_expression(&terms) {
+            let hits = sqlx::query_as::<_, SessionSearchHit>(
+                r#"
+                SELECT s.id AS session_id, s.title, s.start_time, s.end_time,
+                       snippet(session_search, -1, '【', '】', '…', 24) AS snippet,
+                       -bm25(session_search, 5.0, 2.0, 1.0, 0.5) AS score
+                FROM session_search
+                JOIN sessions s ON s.id = session_search.rowid
+                WHERE session_search MATCH ?
+                  AND DATE(s.start_time) >= ? AND DATE(s.start_time) <= ?
+                ORDER BY bm25(session_search, 5.0, 2.0, 1.0, 0.5), s.start_time DESC
+                LIMIT ?
+                "#,
+            )
+            .bind(expression)
+            .bind(start_date)
+            .bind(end_date)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+            return Ok(hits);
+        }
+
+        // 搜索词不足 trigram 长度（如中文双字词）时回退为 LIKE，trigram 索引同样可加速 LIKE
+        let mut sql = String::from(
             r#"
-            DELETE FROM day_summaries WHERE date = ?
+            SELECT s.id AS session_id, s.title, s.start_time, s.end_time,
+                   f.summary, f.cards, f.ocr
+            FROM session_search f
+            JOIN sessions s ON s.id = f.rowid
+            WHERE DATE(s.start_time) >= ? AND DATE(s.start_time) <= ?
             "#,
-        )
-        .bind(date)
-        .execute(&self.pool)
-        .await?;
+        );
+        for _ in &terms {
+            sql.push_str(
+                " AND (f.title LIKE ? ESCAPE '\\' OR f.summary LIKE ? ESCAPE '\\' OR f.cards LIKE ? ESCAPE '\\' OR f.ocr LIKE ? ESCAPE '\\')",
+            );
+        }
 
-        Ok(())
+        let mut query = sqlx::query_as::<_, search::SearchCandidate>(&sql)
+            .bind(start_date)
+            .bind(end_date);
+        for term in &terms {
+            let pattern = search::like_pattern(term);
+            for _ in 0..4 {
+                query = query.bind(pattern.clone());
+            }
+        }
+        let candidates = query.fetch_all(&self.pool).await?;
+
+        Ok(search::rank_candidates(
+            candidates,
+            &terms,
+            limit.max(0) as usize,
+        ))
     }
 
     fn db_type(&self) -> &str {
         "sqlite"
     }
 
+    async fn snapshot_to(&self, path: &str) -> Result<()> {
+        // VACUUM INTO 在读事务中生成紧凑副本，不阻塞写入
+        sqlx::query("VACUUM INTO ?")
+            .bind(path)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn restore_from_dump(&self, _path: &str) -> Result<()> {
+        Err(anyhow::anyhow!(
+            "SQLite 数据库通过替换数据库文件恢复，不支持 SQL 转储"
+        ))
+    }
+
     async fn migrate_timezone_to_local(&self) -> Result<(u64, u64, u64, u64, u64, u64)> {
         use chrono::Local;
 
@@ -1018,3 +2252,532 @@ impl DatabaseRepository for SqliteRepository {
         ))
     }
 }
+
+/// 在已开启的事务中逐条插入时间线卡片
+async fn insert_card_rows(
+    conn: &mut sqlx::sqlite::SqliteConnection,
+    cards: &[TimelineCardRecord],
+) -> Result<()> {
+    for card in cards {
+        sqlx::query(
+            r#"
+            INSERT INTO timeline_cards (
+                session_id, llm_call_id, start_time, end_time,
+                category, subcategory, title, summary, detailed_summary,
+                distractions, app_sites, confidence, heuristic, video_preview_path, created_at
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+        "#,
+        )
+        .bind(card.session_id)
+        .bind(card.llm_call_id)
+        .bind(&card.start_time)
+        .bind(&card.end_time)
+        .bind(&card.category)
+        .bind(&card.subcategory)
+        .bind(&card.title)
+        .bind(&card.summary)
+        .bind(&card.detailed_summary)
+        .bind(&card.distractions)
+        .bind(&card.app_sites)
+        .bind(card.confidence)
+        .bind(card.heuristic)
+        .bind(&card.video_preview_path)
+        .bind(card.created_at)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// SQLite 结构迁移（版本号递增；已发布的版本不可修改，结构变更追加新版本）
+fn sqlite_migrations() -> Vec<Migration> {
+    let (device_name, device_type) = get_device_info();
+
+    vec![Migration::new(1, "初始表结构")
+        .sql([
+            // 创建会话表
+            r#"
+            CREATE TABLE IF NOT EXISTS sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                start_time DATETIME NOT NULL,
+                end_time DATETIME NOT NULL,
+                title TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                video_path TEXT,
+                tags TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                device_name TEXT,
+                device_type TEXT
+            )
+            "#,
+            // 创建帧表
+            r#"
+            CREATE TABLE IF NOT EXISTS frames (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                timestamp DATETIME NOT NULL,
+                file_path TEXT NOT NULL,
+                app_name TEXT,
+                window_title TEXT,
+                process_path TEXT,
+                domain TEXT,
+                screen_id INTEGER NOT NULL DEFAULT 0,
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+            )
+            "#,
+            // 创建索引
+            "CREATE INDEX IF NOT EXISTS idx_sessions_start_time ON sessions(start_time)",
+            "CREATE INDEX IF NOT EXISTS idx_frames_session_id ON frames(session_id)",
+            "CREATE INDEX IF NOT EXISTS idx_sessions_start_end ON sessions(start_time, end_time)",
+            "CREATE INDEX IF NOT EXISTS idx_frames_session_timestamp ON frames(session_id, timestamp)",
+            // 创建LLM调用记录表
+            r#"
+            CREATE TABLE IF NOT EXISTS llm_calls (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER,
+                provider TEXT NOT NULL,
+                model TEXT NOT NULL,
+                call_type TEXT NOT NULL,
+                request_headers TEXT NOT NULL,
+                request_body TEXT NOT NULL,
+                response_headers TEXT,
+                response_body TEXT,
+                status_code INTEGER,
+                error_message TEXT,
+                latency_ms INTEGER,
+                token_usage TEXT,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+            )
+            "#,
+            // 创建视频分段表
+            r#"
+            CREATE TABLE IF NOT EXISTS video_segments (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                llm_call_id INTEGER,
+                start_timestamp TEXT NOT NULL,
+                end_timestamp TEXT NOT NULL,
+                description TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+                FOREIGN KEY (llm_call_id) REFERENCES llm_calls(id) ON DELETE SET NULL
+            )
+            "#,
+            // 创建时间线卡片表
+            r#"
+            CREATE TABLE IF NOT EXISTS timeline_cards (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                llm_call_id INTEGER,
+                start_time TEXT NOT NULL,
+                end_time TEXT NOT NULL,
+                category TEXT NOT NULL,
+                subcategory TEXT NOT NULL,
+                title TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                detailed_summary TEXT NOT NULL,
+                distractions TEXT,
+                app_sites TEXT NOT NULL,
+                confidence REAL,
+                heuristic BOOLEAN NOT NULL DEFAULT 0,
+                video_preview_path TEXT,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+                FOREIGN KEY (llm_call_id) REFERENCES llm_calls(id) ON DELETE SET NULL
+            )
+            "#,
+            // 创建每日总结表（缓存）
+            r#"
+            CREATE TABLE IF NOT EXISTS day_summaries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                date DATE NOT NULL UNIQUE,
+                summary_text TEXT NOT NULL,
+                device_stats TEXT NOT NULL,
+                parallel_work TEXT NOT NULL,
+                usage_patterns TEXT NOT NULL,
+                active_device_count INTEGER NOT NULL,
+                llm_call_id INTEGER,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (llm_call_id) REFERENCES llm_calls(id) ON DELETE SET NULL
+            )
+            "#,
+            // 创建每日专注度指标表（缓存，时间线卡片变化时按日期失效）
+            r#"
+            CREATE TABLE IF NOT EXISTS daily_focus_metrics (
+                date DATE PRIMARY KEY,
+                total_minutes INTEGER NOT NULL,
+                work_minutes INTEGER NOT NULL,
+                learning_minutes INTEGER NOT NULL,
+                communication_minutes INTEGER NOT NULL,
+                personal_minutes INTEGER NOT NULL,
+                idle_minutes INTEGER NOT NULL,
+                other_minutes INTEGER NOT NULL,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+            // 创建导出历史表
+            r#"
+            CREATE TABLE IF NOT EXISTS export_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                target TEXT NOT NULL,
+                request TEXT NOT NULL,
+                success BOOLEAN NOT NULL,
+                message TEXT NOT NULL,
+                written_paths TEXT NOT NULL,
+                warnings TEXT NOT NULL,
+                failed_sessions TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+            // 创建项目归属规则表
+            r#"
+            CREATE TABLE IF NOT EXISTS project_rules (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project TEXT NOT NULL,
+                match_field TEXT NOT NULL,
+                pattern TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+            // 创建自定义类别表
+            r#"
+            CREATE TABLE IF NOT EXISTS custom_categories (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                bucket TEXT NOT NULL,
+                description TEXT NOT NULL DEFAULT '',
+                subcategories TEXT NOT NULL DEFAULT '[]',
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+            // 创建预分类规则表
+            r#"
+            CREATE TABLE IF NOT EXISTS classification_rules (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                match_field TEXT NOT NULL,
+                pattern TEXT NOT NULL,
+                category TEXT NOT NULL,
+                subcategory TEXT NOT NULL DEFAULT '',
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+            // 创建 Notion 同步记录表
+            r#"
+            CREATE TABLE IF NOT EXISTS notion_sync (
+                kind TEXT NOT NULL,
+                local_key TEXT NOT NULL,
+                date TEXT NOT NULL,
+                page_id TEXT NOT NULL,
+                synced_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (kind, local_key)
+            )
+            "#,
+            // 创建日历事件表
+            r#"
+            CREATE TABLE IF NOT EXISTS calendar_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                event_id TEXT NOT NULL,
+                date TEXT NOT NULL,
+                title TEXT NOT NULL,
+                location TEXT NOT NULL,
+                start_time DATETIME NOT NULL,
+                end_time DATETIME NOT NULL,
+                synced_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_calendar_events_date ON calendar_events(date)",
+            // 创建工单关联表
+            r#"
+            CREATE TABLE IF NOT EXISTS issue_links (
+                session_id INTEGER NOT NULL,
+                provider TEXT NOT NULL,
+                issue_key TEXT NOT NULL,
+                title TEXT NOT NULL,
+                state TEXT NOT NULL,
+                url TEXT NOT NULL,
+                resolved_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (session_id, provider, issue_key),
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+            )
+            "#,
+            // 创建工时同步记录表
+            r#"
+            CREATE TABLE IF NOT EXISTS time_entry_sync (
+                provider TEXT NOT NULL,
+                session_id INTEGER NOT NULL,
+                entry_id TEXT NOT NULL,
+                synced_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (provider, session_id),
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+            )
+            "#,
+            // 创建会话备注表
+            r#"
+            CREATE TABLE IF NOT EXISTS session_notes (
+                session_id INTEGER PRIMARY KEY,
+                note TEXT NOT NULL,
+                tags TEXT NOT NULL DEFAULT '[]',
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+            )
+            "#,
+            // 创建语义向量表
+            r#"
+            CREATE TABLE IF NOT EXISTS vectors (
+                session_id INTEGER NOT NULL,
+                owner_kind TEXT NOT NULL,
+                owner_id INTEGER NOT NULL,
+                model TEXT NOT NULL,
+                content_hash TEXT NOT NULL,
+                vector BLOB NOT NULL,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (session_id, owner_kind, owner_id),
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+            )
+            "#,
+            // 创建前台应用使用时长表
+            r#"
+            CREATE TABLE IF NOT EXISTS app_usage (
+                session_id INTEGER NOT NULL,
+                app_name TEXT NOT NULL,
+                process_path TEXT,
+                window_title TEXT NOT NULL,
+                seconds INTEGER NOT NULL,
+                PRIMARY KEY (session_id, app_name),
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+            )
+            "#,
+            // 创建网站域名访问时长表
+            r#"
+            CREATE TABLE IF NOT EXISTS domain_usage (
+                session_id INTEGER NOT NULL,
+                domain TEXT NOT NULL,
+                seconds INTEGER NOT NULL,
+                PRIMARY KEY (session_id, domain),
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+            )
+            "#,
+            // 创建帧 OCR 文字表
+            r#"
+            CREATE TABLE IF NOT EXISTS frame_texts (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER NOT NULL,
+                timestamp DATETIME NOT NULL,
+                text TEXT NOT NULL,
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_frame_texts_session_timestamp ON frame_texts(session_id, timestamp)",
+            // 创建 LLM 响应缓存表（不关联会话，会话删除后重新分析相同画面仍可复用）
+            r#"
+            CREATE TABLE IF NOT EXISTS llm_cache (
+                cache_key TEXT PRIMARY KEY,
+                task TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                model TEXT NOT NULL,
+                response TEXT NOT NULL,
+                created_at DATETIME NOT NULL
+            )
+            "#,
+            // 创建 LLM 解析失败记录表（不关联会话外键，会话删除后仍可排查）
+            r#"
+            CREATE TABLE IF NOT EXISTS llm_parse_failures (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER,
+                provider TEXT NOT NULL,
+                model TEXT NOT NULL,
+                task TEXT NOT NULL,
+                raw_response TEXT NOT NULL,
+                repaired_response TEXT,
+                errors TEXT NOT NULL,
+                repaired BOOLEAN NOT NULL DEFAULT 0,
+                created_at DATETIME NOT NULL
+            )
+            "#,
+            // 创建时间线卡片复核记录表
+            r#"
+            CREATE TABLE IF NOT EXISTS card_reviews (
+                card_id INTEGER PRIMARY KEY,
+                session_id INTEGER NOT NULL,
+                app TEXT NOT NULL,
+                original_category TEXT NOT NULL,
+                category TEXT NOT NULL,
+                subcategory TEXT NOT NULL DEFAULT '',
+                reviewed_at DATETIME NOT NULL,
+                FOREIGN KEY (card_id) REFERENCES timeline_cards(id) ON DELETE CASCADE
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_card_reviews_app ON card_reviews(app)",
+            // 创建目标表与目标评估结果表
+            r#"
+            CREATE TABLE IF NOT EXISTS goals (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                metric TEXT NOT NULL,
+                target TEXT NOT NULL DEFAULT '',
+                comparison TEXT NOT NULL,
+                target_minutes INTEGER NOT NULL,
+                schedule TEXT NOT NULL DEFAULT 'daily',
+                enabled BOOLEAN NOT NULL DEFAULT 1,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS goal_results (
+                goal_id INTEGER NOT NULL,
+                date TEXT NOT NULL,
+                actual_minutes INTEGER NOT NULL,
+                passed BOOLEAN NOT NULL,
+                evaluated_at DATETIME NOT NULL,
+                PRIMARY KEY (goal_id, date)
+            )
+            "#,
+            // 创建专注时段表
+            r#"
+            CREATE TABLE IF NOT EXISTS focus_blocks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                label TEXT NOT NULL DEFAULT '',
+                planned_minutes INTEGER NOT NULL,
+                started_at DATETIME NOT NULL,
+                ended_at DATETIME,
+                status TEXT NOT NULL DEFAULT 'running',
+                distraction_count INTEGER NOT NULL DEFAULT 0,
+                distractions TEXT NOT NULL DEFAULT '[]'
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_focus_blocks_started_at ON focus_blocks(started_at)",
+            // 创建额外的索引
+            "CREATE INDEX IF NOT EXISTS idx_llm_calls_session_id ON llm_calls(session_id)",
+            "CREATE INDEX IF NOT EXISTS idx_llm_calls_created_at ON llm_calls(created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_video_segments_session_id ON video_segments(session_id)",
+            "CREATE INDEX IF NOT EXISTS idx_timeline_cards_session_id ON timeline_cards(session_id)",
+        ])
+        // 引入版本号之前创建的旧表补充后续新增的字段
+        .add_column("sessions", "device_name", "TEXT")
+        .add_column("sessions", "device_type", "TEXT")
+        .add_column("frames", "app_name", "TEXT")
+        .add_column("frames", "window_title", "TEXT")
+        .add_column("frames", "process_path", "TEXT")
+        .add_column("frames", "domain", "TEXT")
+        .add_column("frames", "screen_id", "INTEGER NOT NULL DEFAULT 0")
+        .add_column("timeline_cards", "confidence", "REAL")
+        .add_column("timeline_cards", "heuristic", "BOOLEAN NOT NULL DEFAULT 0")
+        // 旧数据中缺少设备信息的会话归属本机
+        .sql([format!(
+            "UPDATE sessions SET device_name = {}, device_type = {} WHERE device_name IS NULL",
+            migrations::quote_literal(&device_name),
+            migrations::quote_literal(&device_type)
+        )])
+        .sql(search_index_statements())]
+}
+
+/// 会话全文索引（FTS5 trigram 分词，支持中文子串匹配；rowid 即会话 ID，由触发器同步）
+///
+/// 旧版索引可能缺少 OCR 列，统一删除后重建并回填已有会话
+fn search_index_statements() -> Vec<String> {
+    let mut statements: Vec<String> = SEARCH_TRIGGERS
+        .iter()
+        .map(|name| format!("DROP TRIGGER IF EXISTS {}", name))
+        .collect();
+    statements.push("DROP TABLE IF EXISTS session_search".to_string());
+    statements.push(
+        r#"
+        CREATE VIRTUAL TABLE session_search USING fts5(
+            title, summary, cards, ocr, tokenize = 'trigram'
+        )
+        "#
+        .to_string(),
+    );
+    statements.extend([
+        r#"
+        CREATE TRIGGER session_search_insert AFTER INSERT ON sessions BEGIN
+            INSERT INTO session_search (rowid, title, summary, cards, ocr)
+            VALUES (new.id, new.title, new.summary, '', '');
+        END
+        "#
+        .to_string(),
+        r#"
+        CREATE TRIGGER session_search_update AFTER UPDATE OF title, summary ON sessions BEGIN
+            UPDATE session_search SET title = new.title, summary = new.summary WHERE rowid = new.id;
+        END
+        "#
+        .to_string(),
+        r#"
+        CREATE TRIGGER session_search_delete AFTER DELETE ON sessions BEGIN
+            DELETE FROM session_search WHERE rowid = old.id;
+        END
+        "#
+        .to_string(),
+        format!(
+            "CREATE TRIGGER session_search_card_insert AFTER INSERT ON timeline_cards BEGIN
+                UPDATE session_search SET cards = {} WHERE rowid = new.session_id;
+            END",
+            card_text_sql("new.session_id")
+        ),
+        format!(
+            "CREATE TRIGGER session_search_card_update AFTER UPDATE ON timeline_cards BEGIN
+                UPDATE session_search SET cards = {} WHERE rowid = new.session_id;
+            END",
+            card_text_sql("new.session_id")
+        ),
+        format!(
+            "CREATE TRIGGER session_search_card_delete AFTER DELETE ON timeline_cards BEGIN
+                UPDATE session_search SET cards = {} WHERE rowid = old.session_id;
+            END",
+            card_text_sql("old.session_id")
+        ),
+        format!(
+            "CREATE TRIGGER session_search_ocr_insert AFTER INSERT ON frame_texts BEGIN
+                UPDATE session_search SET ocr = {} WHERE rowid = new.session_id;
+            END",
+            ocr_text_sql("new.session_id")
+        ),
+        format!(
+            "CREATE TRIGGER session_search_ocr_delete AFTER DELETE ON frame_texts BEGIN
+                UPDATE session_search SET ocr = {} WHERE rowid = old.session_id;
+            END",
+            ocr_text_sql("old.session_id")
+        ),
+    ]);
+    statements.push(format!(
+        "INSERT INTO session_search (rowid, title, summary, cards, ocr)
+         SELECT s.id, s.title, s.summary, {}, {} FROM sessions s",
+        card_text_sql("s.id"),
+        ocr_text_sql("s.id")
+    ));
+    statements
+}
+
+/// 全文索引的同步触发器
+const SEARCH_TRIGGERS: [&str; 8] = [
+    "session_search_insert",
+    "session_search_update",
+    "session_search_delete",
+    "session_search_card_insert",
+    "session_search_card_update",
+    "session_search_card_delete",
+    "session_search_ocr_insert",
+    "session_search_ocr_delete",
+];
+
+/// 会话下所有时间线卡片的文本（供全文索引使用）
+fn card_text_sql(session_id: &str) -> String {
+    format!(
+        "COALESCE((SELECT group_concat(c.title || ' ' || c.summary || ' ' || c.detailed_summary, char(10))
+                   FROM timeline_cards c WHERE c.session_id = {}), '')",
+        session_id
+    )
+}
+
+/// 会话下所有帧 OCR 文字（供全文索引使用）
+fn ocr_text_sql(session_id: &str) -> String {
+    format!(
+        "COALESCE((SELECT group_concat(t.text, char(10))
+                   FROM (SELECT text FROM frame_texts WHERE session_id = {} ORDER BY timestamp) t), '')",
+        session_id
+    )
+}
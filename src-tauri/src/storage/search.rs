@@ -0,0 +1,341 @@
+// 全文搜索辅助 - 搜索词拆分、FTS5 查询表达式、LIKE 回退时的打分与片段截取
+
+use super::models::SessionSearchHit;
+use chrono::{DateTime, Utc};
+
+/// FTS5 trigram 分词要求每个搜索词至少 3 个字符
+pub const TRIGRAM_MIN_CHARS: usize = 3;
+
+/// 片段前后保留的字符数
+const SNIPPET_CONTEXT_CHARS: usize = 24;
+
+/// 标题、总结、卡片文本、OCR 文字的命中权重（与 SQLite bm25 权重一致）
+const FIELD_WEIGHTS: [f64; 4] = [5.0, 2.0, 1.0, 0.5];
+
+/// 拆分搜索词（按空白分隔，忽略重复）
+pub fn search_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for term in query.split_whitespace() {
+        let term = term.trim_matches('"');
+        if !term.is_empty() && !terms.iter().any(|t| t.eq_ignore_ascii_case(term)) {
+            terms.push(term.to_string());
+        }
+    }
+    terms
+}
+
+/// 构造 FTS5 MATCH 表达式（各词按短语匹配并取交集）；存在过短的词时返回 None，由调用方回退到 LIKE
+pub fn fts5_match_expression(terms: &[String]) -> Option<String> {
+    if terms.is_empty()
+        || terms
+            .iter()
+            .any(|term| term.chars().count() < TRIGRAM_MIN_CHARS)
+    {
+        return None;
+    }
+    Some(
+        terms
+            .iter()
+            .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" AND "),
+    )
+}
+
+/// LIKE 模式（转义通配符，配合 ESCAPE '\'）
+pub fn like_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// LIKE 回退时的候选会话（卡片文本与 OCR 文字已按会话拼接）
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SearchCandidate {
+    pub session_id: i64,
+    pub title: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub summary: String,
+    pub cards: String,
+    pub ocr: String,
+}
+
+/// 对已按 LIKE 筛选的候选会话打分、截取片段，并按相关度取前 limit 条
+pub fn rank_candidates(
+    candidates: Vec<SearchCandidate>,
+    terms: &[String],
+    limit: usize,
+) -> Vec<SessionSearchHit> {
+    let mut hits: Vec<SessionSearchHit> = candidates
+        .into_iter()
+        .map(|candidate| {
+            let fields = [
+                &candidate.title,
+                &candidate.summary,
+                &candidate.cards,
+                &candidate.ocr,
+            ];
+            let score: f64 = fields
+                .iter()
+                .zip(FIELD_WEIGHTS)
+                .map(|(text, weight)| {
+                    let lower = text.to_lowercase();
+                    terms
+                        .iter()
+                        .map(|term| lower.matches(&term.to_lowercase()).count() as f64 * weight)
+                        .sum::<f64>()
+                })
+                .sum();
+
+            // 片段取自首个命中的字段（总结、卡片、OCR 文字优先于标题，标题会单独展示）
+            let snippet = [
+                &candidate.summary,
+                &candidate.cards,
+                &candidate.ocr,
+                &candidate.title,
+            ]
+            .into_iter()
+            .find_map(|text| build_snippet(text, terms))
+            .unwrap_or_default();
+
+            SessionSearchHit {
+                session_id: candidate.session_id,
+                title: candidate.title,
+                start_time: candidate.start_time,
+                end_time: candidate.end_time,
+                snippet,
+                score,
+            }
+        })
+        .collect();
+
+    hits.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(b.start_time.cmp(&a.start_time))
+    });
+    hits.truncate(limit);
+    hits
+}
+
+/// 截取包含首个命中词的片段，命中词以【】标记；未命中返回 None
+pub fn build_snippet(text: &str, terms: &[String]) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars.iter().map(|c| fold_char(*c)).collect();
+    let needles: Vec<Vec<char>> = terms
+        .iter()
+        .map(|term| term.chars().map(fold_char).collect())
+        .filter(|needle: &Vec<char>| !needle.is_empty())
+        .collect();
+
+    let find_at = |pos: usize| {
+        needles
+            .iter()
+            .find(|needle| lower[pos..].starts_with(needle))
+            .map(|needle| needle.len())
+    };
+
+    let first = (0..lower.len()).find(|&pos| find_at(pos).is_some())?;
+    let start = first.saturating_sub(SNIPPET_CONTEXT_CHARS);
+    let end = (first + SNIPPET_CONTEXT_CHARS * 2).min(chars.len());
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    let mut pos = start;
+    while pos < end {
+        match find_at(pos) {
+            Some(len) => {
+                let stop = (pos + len).min(chars.len());
+                snippet.push('【');
+                snippet.extend(&chars[pos..stop]);
+                snippet.push('】');
+                pos = stop;
+            }
+            None => {
+                snippet.push(if chars[pos] == '\n' { ' ' } else { chars[pos] });
+                pos += 1;
+            }
+        }
+    }
+    if pos < chars.len() {
+        snippet.push('…');
+    }
+    Some(snippet)
+}
+
+/// 大小写折叠（仅取单字符映射，保证与原文逐字符对齐）
+fn fold_char(c: char) -> char {
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(folded), None) => folded,
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(query: &str) -> Vec<String> {
+        search_terms(query)
+    }
+
+    #[test]
+    fn test_fts5_match_expression() {
+        assert_eq!(
+            fts5_match_expression(&terms("rust  Tauri rust")),
+            Some("\"rust\" AND \"Tauri\"".to_string())
+        );
+        assert_eq!(
+            fts5_match_expression(&terms("a\"b\"c")),
+            Some("\"a\"\"b\"\"c\"".to_string())
+        );
+        // 中文两字词不足 trigram 长度，需回退 LIKE
+        assert_eq!(fts5_match_expression(&terms("代码 评审")), None);
+        assert_eq!(fts5_match_expression(&terms("   ")), None);
+        assert_eq!(like_pattern("50%_a\\b"), "%50\\%\\_a\\\\b%");
+    }
+
+    #[test]
+    fn test_rank_candidates_scores_and_snippets() {
+        let time = Utc::now();
+        let candidate = |id: i64, title: &str, summary: &str, cards: &str| SearchCandidate {
+            session_id: id,
+            title: title.to_string(),
+            start_time: time,
+            end_time: time,
+            summary: summary.to_string(),
+            cards: cards.to_string(),
+            ocr: String::new(),
+        };
+        let hits = rank_candidates(
+            vec![
+                candidate(1, "阅读文档", "浏览 Rust 官方文档，学习异步编程", ""),
+                candidate(2, "代码评审", "评审同事的 PR", "代码评审：修复 rust 构建"),
+            ],
+            &terms("rust"),
+            10,
+        );
+
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].session_id, 1);
+        assert_eq!(hits[0].snippet, "浏览 【Rust】 官方文档，学习异步编程");
+        assert_eq!(hits[1].snippet, "代码评审：修复 【rust】 构建");
+
+        let long = format!("{}评审{}", "前".repeat(40), "后".repeat(60));
+        let snippet = build_snippet(&long, &terms("评审")).unwrap();
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+        assert!(snippet.contains("【评审】"));
+        assert_eq!(build_snippet("无关内容", &terms("评审")), None);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_search_index_stays_in_sync() {
+        use crate::storage::{Database, FrameText, Session, TimelineCardRecord};
+
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new_sqlite(dir.path().join("test.db").to_str().unwrap())
+            .await
+            .unwrap();
+        let start = chrono::NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_utc();
+        let session = |title: &str, summary: &str| Session {
+            id: None,
+            start_time: start,
+            end_time: start + chrono::Duration::minutes(15),
+            title: title.to_string(),
+            summary: summary.to_string(),
+            video_path: None,
+            tags: "[]".to_string(),
+            created_at: None,
+            device_name: None,
+            device_type: None,
+        };
+        let docs = db
+            .insert_session(&session("阅读文档", "浏览 Tokio 官方文档"))
+            .await
+            .unwrap();
+        let review = db
+            .insert_session(&session("代码评审", "处理同事的 PR"))
+            .await
+            .unwrap();
+        db.insert_timeline_card(&TimelineCardRecord {
+            id: None,
+            session_id: review,
+            llm_call_id: None,
+            start_time: start.to_rfc3339(),
+            end_time: start.to_rfc3339(),
+            category: "work".to_string(),
+            subcategory: "coding".to_string(),
+            title: "修复构建".to_string(),
+            summary: "修复 tokio 依赖升级导致的编译错误".to_string(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: "{}".to_string(),
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+            created_at: start,
+        })
+        .await
+        .unwrap();
+
+        // FTS5 路径：标题/总结命中排在仅卡片命中之前
+        let hits = db
+            .search_sessions("tokio", "2026-03-01", "2026-03-31", 10)
+            .await
+            .unwrap();
+        let ids: Vec<i64> = hits.iter().map(|hit| hit.session_id).collect();
+        assert_eq!(ids, vec![docs, review]);
+        assert!(hits[1].snippet.contains("【tokio】"));
+
+        // 双字中文词走 LIKE 回退
+        let hits = db
+            .search_sessions("评审", "2026-03-01", "2026-03-31", 10)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, review);
+
+        // 帧 OCR 文字同样进入索引
+        db.insert_frame_texts(&[FrameText {
+            id: None,
+            session_id: docs,
+            timestamp: start,
+            text: "Cargo.toml: serde_json = \"1.0\"".to_string(),
+        }])
+        .await
+        .unwrap();
+        let hits = db
+            .search_sessions("serde_json", "2026-03-01", "2026-03-31", 10)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, docs);
+        assert!(hits[0].snippet.contains("【serde_json】"));
+
+        // 日期范围、更新与删除同步到索引
+        assert!(db
+            .search_sessions("tokio", "2026-04-01", "2026-04-30", 10)
+            .await
+            .unwrap()
+            .is_empty());
+        db.update_session(docs, "开会", "周会", None, "[]")
+            .await
+            .unwrap();
+        db.delete_session(review).await.unwrap();
+        assert!(db
+            .search_sessions("tokio", "2026-03-01", "2026-03-31", 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,235 @@
+// SQLCipher 数据库加密 - 本地 SQLite 数据库可选以 SQLCipher 加密，密钥保存在系统钥匙串
+//
+// 需以 sqlcipher 特性构建。数据库配置在重启后生效：打开数据库前发现文件与配置不一致时一次性转换，
+// 启用加密时把明文数据库导出为加密数据库，关闭加密时反向导出为明文。导出先写入临时文件并校验完整性，
+// 成功后才替换原文件，中途失败不影响原数据库
+
+use anyhow::{anyhow, bail, Result};
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{ConnectOptions, Connection};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+use crate::keychain;
+
+/// 钥匙串中的密钥名
+const KEYCHAIN_ACCOUNT: &str = "database-key";
+
+/// 明文 SQLite 数据库的文件头
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// 数据库文件的加密状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileState {
+    /// 文件不存在或为空（打开时新建）
+    Missing,
+    Plaintext,
+    /// 文件头不是明文 SQLite：SQLCipher 加密的数据库，或已损坏的文件
+    Unrecognized,
+}
+
+/// 按文件头判断数据库文件的加密状态
+pub fn file_state(path: &Path) -> Result<FileState> {
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(FileState::Missing),
+        Err(e) => return Err(e.into()),
+    };
+    let mut header = [0u8; SQLITE_HEADER.len()];
+    match file.read_exact(&mut header) {
+        Ok(()) if &header == SQLITE_HEADER => Ok(FileState::Plaintext),
+        Ok(()) => Ok(FileState::Unrecognized),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(FileState::Missing),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// SQLite 连接参数（有密钥时首先执行 `PRAGMA key`）
+pub fn connect_options(path: &Path, key: Option<&str>) -> SqliteConnectOptions {
+    let options = SqliteConnectOptions::new().filename(path);
+    match key {
+        Some(key) => options.pragma("key", format!("\"{}\"", key)),
+        None => options,
+    }
+}
+
+/// 按配置准备数据库文件（文件与配置不一致时一次性转换），返回打开数据库使用的密钥
+pub async fn prepare(db_path: &str, encrypted: bool) -> Result<Option<String>> {
+    let path = Path::new(db_path);
+    let state = file_state(path)?;
+    if !encrypted {
+        if let Some(key) = existing_key(path)? {
+            convert(path, Some(&key), None).await?;
+            info!("已将 SQLCipher 数据库导出为明文数据库: {}", db_path);
+        }
+        return Ok(None);
+    }
+
+    let key = load_key(true)?.ok_or_else(|| anyhow!("无法生成数据库密钥"))?;
+    if state == FileState::Plaintext {
+        convert(path, None, Some(&key)).await?;
+        info!("已将明文数据库迁移为 SQLCipher 加密数据库: {}", db_path);
+    }
+    Ok(Some(key))
+}
+
+/// 打开已有数据库文件需要的密钥（明文或不存在时为 None）
+///
+/// 文件头无法识别时，只有启用了 sqlcipher 特性或钥匙串中已有数据库密钥才按加密数据库处理；
+/// 否则不可能是本应用加密的数据库，返回 None 按普通方式打开，以便暴露真实的 SQLite 错误
+pub fn existing_key(path: &Path) -> Result<Option<String>> {
+    if file_state(path)? != FileState::Unrecognized {
+        return Ok(None);
+    }
+    if !cfg!(feature = "sqlcipher") && !has_stored_key() {
+        return Ok(None);
+    }
+    load_key(false)?.map(Some).ok_or_else(|| {
+        anyhow!("数据库文件不是明文 SQLite（已加密或已损坏），但系统钥匙串中没有数据库密钥")
+    })
+}
+
+/// 通过 `sqlcipher_export` 把数据库导出到新文件（密钥为 None 时导出为明文），并校验导出结果
+pub async fn export(
+    source: &Path,
+    source_key: Option<&str>,
+    target: &Path,
+    target_key: Option<&str>,
+) -> Result<()> {
+    let mut conn = connect_options(source, source_key).connect().await?;
+    sqlx::query("ATTACH DATABASE ? AS export KEY ?")
+        .bind(target.to_string_lossy().to_string())
+        .bind(target_key.unwrap_or_default())
+        .execute(&mut conn)
+        .await?;
+    sqlx::query("SELECT sqlcipher_export('export')")
+        .execute(&mut conn)
+        .await?;
+    sqlx::query("DETACH DATABASE export")
+        .execute(&mut conn)
+        .await?;
+    conn.close().await?;
+
+    let mut conn = connect_options(target, target_key).connect().await?;
+    let report: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_all(&mut conn)
+        .await?;
+    conn.close().await?;
+    if report != ["ok"] {
+        bail!("导出的数据库完整性校验失败: {}", report.join("; "));
+    }
+    Ok(())
+}
+
+/// 导出到临时文件并替换原数据库
+async fn convert(path: &Path, from_key: Option<&str>, to_key: Option<&str>) -> Result<()> {
+    let staging = sidecar(path, ".converting");
+    if staging.exists() {
+        tokio::fs::remove_file(&staging).await?;
+    }
+    if let Err(e) = export(path, from_key, &staging, to_key).await {
+        let _ = tokio::fs::remove_file(&staging).await;
+        return Err(e);
+    }
+
+    // 导出连接关闭时已合并 WAL，旧数据库的 WAL 文件不能与新文件混用
+    for suffix in ["-wal", "-shm"] {
+        let file = sidecar(path, suffix);
+        if file.exists() {
+            tokio::fs::remove_file(&file).await?;
+        }
+    }
+    tokio::fs::rename(&staging, path).await?;
+    Ok(())
+}
+
+/// 从系统钥匙串读取数据库密钥（SQLCipher 原始密钥格式 `x'<十六进制>'`）
+fn load_key(create: bool) -> Result<Option<String>> {
+    if !cfg!(feature = "sqlcipher") {
+        bail!("当前构建未启用 SQLCipher，请以 sqlcipher 特性构建后再使用数据库加密");
+    }
+    Ok(keychain::load_key(KEYCHAIN_ACCOUNT, create)?.map(|key| raw_key(&key)))
+}
+
+/// 钥匙串中是否已保存数据库密钥（读取失败按没有处理）
+fn has_stored_key() -> bool {
+    keychain::load_key(KEYCHAIN_ACCOUNT, false).is_ok_and(|key| key.is_some())
+}
+
+fn raw_key(key: &[u8; 32]) -> String {
+    format!("x'{}'", hex::encode_upper(key))
+}
+
+fn sidecar(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Database;
+
+    #[tokio::test]
+    async fn test_file_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.db");
+        assert_eq!(file_state(&path).unwrap(), FileState::Missing);
+
+        Database::new_sqlite(&path.to_string_lossy()).await.unwrap();
+        assert_eq!(file_state(&path).unwrap(), FileState::Plaintext);
+        assert_eq!(existing_key(&path).unwrap(), None);
+
+        std::fs::write(&path, [0x5a; 64]).unwrap();
+        assert_eq!(file_state(&path).unwrap(), FileState::Unrecognized);
+        std::fs::write(&path, b"").unwrap();
+        assert_eq!(file_state(&path).unwrap(), FileState::Missing);
+
+        assert_eq!(raw_key(&[0xab; 32]), format!("x'{}'", "AB".repeat(32)));
+    }
+
+    #[cfg(not(feature = "sqlcipher"))]
+    #[tokio::test]
+    async fn test_corrupt_database_reports_sqlite_error() {
+        if has_stored_key() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.db");
+        std::fs::write(&path, [0x5a; 4096]).unwrap();
+        assert_eq!(existing_key(&path).unwrap(), None);
+
+        // 启动路径：按配置准备并打开数据库
+        let config = crate::storage::DatabaseConfig::SQLite {
+            db_path: path.to_string_lossy().to_string(),
+            encrypted: false,
+        };
+        let error = Database::from_config(&config)
+            .await
+            .err()
+            .expect("损坏的数据库不应打开成功")
+            .to_string();
+        assert!(!error.contains("SQLCipher"), "{}", error);
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[tokio::test]
+    async fn test_export_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.db");
+        let db = Database::new_sqlite(&path.to_string_lossy()).await.unwrap();
+        drop(db);
+
+        let key = raw_key(&[7u8; 32]);
+        convert(&path, None, Some(&key)).await.unwrap();
+        assert_eq!(file_state(&path).unwrap(), FileState::Unrecognized);
+        Database::new_sqlite_with_key(&path.to_string_lossy(), Some(&key))
+            .await
+            .unwrap();
+
+        convert(&path, Some(&key), None).await.unwrap();
+        assert_eq!(file_state(&path).unwrap(), FileState::Plaintext);
+    }
+}
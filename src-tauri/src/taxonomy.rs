@@ -0,0 +1,107 @@
+// 自定义类别 - 用户定义的类别（如"客户 A""客户 B"）作为时间线卡片类别保存，
+// 统计专注度和会话标签时按其归属映射到内置类别
+
+use anyhow::Result;
+use std::sync::RwLock;
+use tracing::info;
+
+use crate::llm::plugin::CustomCategory;
+use crate::llm::ActivityCategory;
+use crate::storage::Database;
+
+/// 当前生效的自定义类别（启动时及增删后从数据库重新加载）
+static CUSTOM_CATEGORIES: RwLock<Vec<CustomCategory>> = RwLock::new(Vec::new());
+
+/// 替换当前生效的自定义类别
+pub fn set_custom_categories(categories: Vec<CustomCategory>) {
+    if let Ok(mut current) = CUSTOM_CATEGORIES.write() {
+        *current = categories;
+    }
+}
+
+/// 当前生效的自定义类别
+pub fn custom_categories() -> Vec<CustomCategory> {
+    CUSTOM_CATEGORIES
+        .read()
+        .map(|current| current.clone())
+        .unwrap_or_default()
+}
+
+/// 按名称（忽略大小写）查找自定义类别
+pub fn find_custom_category(name: &str) -> Option<CustomCategory> {
+    let name = name.trim();
+    CUSTOM_CATEGORIES.read().ok().and_then(|current| {
+        current
+            .iter()
+            .find(|category| category.name.eq_ignore_ascii_case(name))
+            .cloned()
+    })
+}
+
+/// 自定义类别归属的内置类别，不是自定义类别时返回 None
+pub fn custom_bucket(name: &str) -> Option<ActivityCategory> {
+    find_custom_category(name).map(|category| bucket_category(&category.bucket))
+}
+
+/// 内置类别名称 -> 活动类别
+fn bucket_category(bucket: &str) -> ActivityCategory {
+    match bucket {
+        "work" => ActivityCategory::Work,
+        "communication" => ActivityCategory::Communication,
+        "learning" => ActivityCategory::Learning,
+        "personal" => ActivityCategory::Personal,
+        "idle" => ActivityCategory::Idle,
+        _ => ActivityCategory::Other,
+    }
+}
+
+/// 从数据库重新加载自定义类别
+pub async fn reload(db: &Database) -> Result<()> {
+    let categories: Vec<CustomCategory> = db
+        .get_custom_categories()
+        .await?
+        .into_iter()
+        .map(|record| CustomCategory {
+            subcategories: record.subcategory_list(),
+            name: record.name,
+            bucket: record.bucket,
+            description: record.description,
+        })
+        .collect();
+    info!("已加载 {} 个自定义类别", categories.len());
+    set_custom_categories(categories);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_bucket() {
+        set_custom_categories(vec![CustomCategory {
+            name: "Client A".to_string(),
+            bucket: "communication".to_string(),
+            description: String::new(),
+            subcategories: Vec::new(),
+        }]);
+        assert!(matches!(
+            custom_bucket("client a"),
+            Some(ActivityCategory::Communication)
+        ));
+        assert!(custom_bucket("work").is_none());
+        assert!(matches!(
+            crate::llm::map_category("Client A"),
+            ActivityCategory::Communication
+        ));
+        assert!(matches!(
+            crate::domains::summary::normalize_timeline_category("CLIENT A"),
+            ActivityCategory::Communication
+        ));
+        set_custom_categories(Vec::new());
+        assert!(matches!(
+            crate::llm::map_category("Client A"),
+            ActivityCategory::Other
+        ));
+    }
+}
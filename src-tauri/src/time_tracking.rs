@@ -0,0 +1,543 @@
+// 工时导出 - 将会话作为工时条目推送到 Toggl Track 或 Clockify（按项目规则归属项目），替代手动计时
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Local, Utc};
+use reqwest::{Client, Method, RequestBuilder, StatusCode};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+use crate::actors::LLMHandle;
+use crate::domains::summary::match_session_project;
+use crate::exporter::{
+    load_day_sessions, session_category, session_minutes, ExportReport, Exporter, NotePreview,
+    SessionExportData,
+};
+use crate::models::TimeTrackingExportConfig;
+use crate::storage::{local_now, Database, ProjectRuleRecord, TimeEntrySyncRecord};
+
+const TOGGL_API_URL: &str = "https://api.track.toggl.com/api/v9";
+const CLOCKIFY_API_URL: &str = "https://api.clockify.me/api/v1";
+
+/// 工时服务
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeTrackingProvider {
+    Toggl,
+    Clockify,
+}
+
+impl TimeTrackingProvider {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "toggl" => Ok(Self::Toggl),
+            "clockify" => Ok(Self::Clockify),
+            other => Err(anyhow!("不支持的工时服务: {}", other)),
+        }
+    }
+
+    /// 同步记录中使用的标识
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::Toggl => "toggl",
+            Self::Clockify => "clockify",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Toggl => "Toggl",
+            Self::Clockify => "Clockify",
+        }
+    }
+}
+
+/// 一条待导出的工时条目
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeEntryDraft {
+    pub session_id: i64,
+    pub description: String,
+    /// 真实 UTC 时间（数据库中存储的是本地时间值）
+    pub start: DateTime<Utc>,
+    pub stop: DateTime<Utc>,
+    /// 项目名称（项目规则命中或默认项目）
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// 数据库中的本地时间值 -> 真实 UTC 时间
+fn stored_to_utc(time: DateTime<Utc>) -> DateTime<Utc> {
+    time.naive_utc()
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|local| local.with_timezone(&Utc))
+        .unwrap_or(time)
+}
+
+/// 工时服务要求的时间格式（UTC，精确到秒）
+fn api_time(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+/// 生成当天的工时条目：跳过过短及排除类别的会话，按项目规则归属项目
+pub fn build_entries(
+    config: &TimeTrackingExportConfig,
+    sessions: &[SessionExportData],
+    rules: &[ProjectRuleRecord],
+) -> Vec<TimeEntryDraft> {
+    let default_project = config.default_project.trim();
+    sessions
+        .iter()
+        .filter_map(|data| {
+            let session = &data.session;
+            let session_id = session.id?;
+            if session_minutes(session) < config.min_minutes.max(1) {
+                return None;
+            }
+            let category = session_category(session);
+            if category.is_some_and(|category| {
+                config
+                    .skip_categories
+                    .iter()
+                    .any(|skip| skip.trim().eq_ignore_ascii_case(category))
+            }) {
+                return None;
+            }
+
+            let project = match_session_project(session, &data.cards, &data.apps, rules)
+                .or((!default_project.is_empty()).then_some(default_project))
+                .map(str::to_string);
+            let description = match session.title.trim() {
+                "" => "未命名会话".to_string(),
+                title => title.to_string(),
+            };
+            Some(TimeEntryDraft {
+                session_id,
+                description,
+                start: stored_to_utc(session.start_time),
+                stop: stored_to_utc(session.end_time),
+                project,
+                tags: category.map(str::to_string).into_iter().collect(),
+            })
+        })
+        .collect()
+}
+
+/// 工时服务 API 客户端
+struct TimeTrackingApi {
+    provider: TimeTrackingProvider,
+    token: String,
+    client: Client,
+}
+
+impl TimeTrackingApi {
+    fn base_url(&self) -> &'static str {
+        match self.provider {
+            TimeTrackingProvider::Toggl => TOGGL_API_URL,
+            TimeTrackingProvider::Clockify => CLOCKIFY_API_URL,
+        }
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self
+            .client
+            .request(method, format!("{}{}", self.base_url(), path));
+        match self.provider {
+            TimeTrackingProvider::Toggl => request.basic_auth(&self.token, Some("api_token")),
+            TimeTrackingProvider::Clockify => request.header("X-Api-Key", &self.token),
+        }
+    }
+
+    /// 发送请求，404 时返回 None
+    async fn send(&self, request: RequestBuilder) -> Result<Option<Value>> {
+        let response = request.send().await?;
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!(
+                "{} 返回 {}: {}",
+                self.provider.name(),
+                status.as_u16(),
+                text.trim()
+            ));
+        }
+        Ok(Some(response.json().await?))
+    }
+
+    /// 账号的默认工作区
+    async fn default_workspace(&self) -> Result<String> {
+        let (path, field) = match self.provider {
+            TimeTrackingProvider::Toggl => ("/me", "default_workspace_id"),
+            TimeTrackingProvider::Clockify => ("/user", "defaultWorkspace"),
+        };
+        self.send(self.request(Method::GET, path))
+            .await?
+            .and_then(|user| id_string(&user[field]))
+            .ok_or_else(|| anyhow!("无法获取 {} 默认工作区", self.provider.name()))
+    }
+
+    /// 工作区内的项目：名称（小写）-> 项目 ID
+    async fn projects(&self, workspace: &str) -> Result<HashMap<String, String>> {
+        let path = match self.provider {
+            TimeTrackingProvider::Toggl => {
+                format!("/workspaces/{}/projects?active=true", workspace)
+            }
+            TimeTrackingProvider::Clockify => {
+                format!(
+                    "/workspaces/{}/projects?archived=false&page-size=5000",
+                    workspace
+                )
+            }
+        };
+        let projects = self
+            .send(self.request(Method::GET, &path))
+            .await?
+            .ok_or_else(|| anyhow!("工作区 {} 不存在", workspace))?;
+        Ok(projects
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|project| {
+                let name = project["name"].as_str()?.trim().to_lowercase();
+                Some((name, id_string(&project["id"])?))
+            })
+            .collect())
+    }
+
+    fn entry_body(
+        &self,
+        workspace: &str,
+        entry: &TimeEntryDraft,
+        project_id: Option<&str>,
+        billable: bool,
+    ) -> Result<Value> {
+        Ok(match self.provider {
+            TimeTrackingProvider::Toggl => {
+                let workspace_id: i64 = workspace
+                    .parse()
+                    .map_err(|_| anyhow!("无效的 Toggl 工作区 ID: {}", workspace))?;
+                json!({
+                    "created_with": "screen-analyzer",
+                    "workspace_id": workspace_id,
+                    "description": entry.description,
+                    "start": api_time(entry.start),
+                    "stop": api_time(entry.stop),
+                    "duration": (entry.stop - entry.start).num_seconds(),
+                    "project_id": project_id.and_then(|id| id.parse::<i64>().ok()),
+                    "tags": entry.tags,
+                    "billable": billable,
+                })
+            }
+            TimeTrackingProvider::Clockify => json!({
+                "description": entry.description,
+                "start": api_time(entry.start),
+                "end": api_time(entry.stop),
+                "projectId": project_id,
+                "billable": billable,
+            }),
+        })
+    }
+
+    /// 创建或更新工时条目，返回条目 ID（已同步的条目在服务端被删除时重新创建）
+    async fn save_entry(
+        &self,
+        workspace: &str,
+        body: &Value,
+        existing: Option<&str>,
+    ) -> Result<String> {
+        let collection = match self.provider {
+            TimeTrackingProvider::Toggl => "time_entries",
+            TimeTrackingProvider::Clockify => "time-entries",
+        };
+        let path = format!("/workspaces/{}/{}", workspace, collection);
+
+        if let Some(entry_id) = existing {
+            let updated = self
+                .send(
+                    self.request(Method::PUT, &format!("{}/{}", path, entry_id))
+                        .json(body),
+                )
+                .await?;
+            if updated.is_some() {
+                return Ok(entry_id.to_string());
+            }
+        }
+
+        self.send(self.request(Method::POST, &path).json(body))
+            .await?
+            .and_then(|created| id_string(&created["id"]))
+            .ok_or_else(|| anyhow!("{} 未返回工时条目 ID", self.provider.name()))
+    }
+}
+
+/// Toggl 的 ID 为数字，Clockify 为字符串，统一转为字符串
+fn id_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Number(number) => Some(number.to_string()),
+        Value::String(text) if !text.is_empty() => Some(text.clone()),
+        _ => None,
+    }
+}
+
+/// 工时导出目标：每个会话对应一条工时条目，重新导出时更新已同步的条目
+pub struct TimeTrackingExporter {
+    config: TimeTrackingExportConfig,
+    api: TimeTrackingApi,
+}
+
+impl TimeTrackingExporter {
+    pub fn new(config: TimeTrackingExportConfig) -> Result<Self> {
+        let provider = TimeTrackingProvider::parse(&config.provider)?;
+        let token = config.api_token.trim().to_string();
+        if token.is_empty() {
+            return Err(anyhow!("请先配置 {} API Token", provider.name()));
+        }
+        let client = Client::builder().timeout(Duration::from_secs(30)).build()?;
+        Ok(Self {
+            config,
+            api: TimeTrackingApi {
+                provider,
+                token,
+                client,
+            },
+        })
+    }
+
+    async fn workspace(&self) -> Result<String> {
+        match self.config.workspace_id.trim() {
+            "" => self.api.default_workspace().await,
+            workspace => Ok(workspace.to_string()),
+        }
+    }
+
+    async fn day_entries(&self, db: &Database, date: &str) -> Result<Vec<TimeEntryDraft>> {
+        let sessions = load_day_sessions(db, date).await?;
+        let rules = db.get_project_rules().await.unwrap_or_default();
+        Ok(build_entries(&self.config, &sessions, &rules))
+    }
+}
+
+#[async_trait]
+impl Exporter for TimeTrackingExporter {
+    fn target_name(&self) -> &'static str {
+        self.api.provider.name()
+    }
+
+    async fn export_day(
+        &self,
+        db: Arc<Database>,
+        _llm_handle: LLMHandle,
+        date: &str,
+        _force_refresh: bool,
+    ) -> Result<ExportReport> {
+        let mut report = ExportReport {
+            target: self.target_name().to_string(),
+            exported_dates: vec![date.to_string()],
+            ..ExportReport::default()
+        };
+        let entries = self.day_entries(&db, date).await?;
+        if entries.is_empty() {
+            report.warnings.push("当天没有需要导出的会话".to_string());
+            return Ok(report);
+        }
+
+        let workspace = self.workspace().await?;
+        let projects = self.api.projects(&workspace).await?;
+        let provider = self.api.provider.key();
+        let mut failures = Vec::new();
+        for entry in &entries {
+            let project_id = entry.project.as_ref().and_then(|project| {
+                let id = projects.get(&project.to_lowercase());
+                if id.is_none() {
+                    let warning = format!("项目“{}”在工作区中不存在，未设置项目", project);
+                    if !report.warnings.contains(&warning) {
+                        report.warnings.push(warning);
+                    }
+                }
+                id.map(String::as_str)
+            });
+            let existing = db
+                .get_time_entry_sync(provider, entry.session_id)
+                .await
+                .ok()
+                .flatten();
+
+            let result = async {
+                let body =
+                    self.api
+                        .entry_body(&workspace, entry, project_id, self.config.billable)?;
+                let entry_id = self
+                    .api
+                    .save_entry(
+                        &workspace,
+                        &body,
+                        existing.as_ref().map(|record| record.entry_id.as_str()),
+                    )
+                    .await?;
+                db.save_time_entry_sync(&TimeEntrySyncRecord {
+                    provider: provider.to_string(),
+                    session_id: entry.session_id,
+                    entry_id,
+                    synced_at: local_now(),
+                })
+                .await
+            }
+            .await;
+
+            match result {
+                Ok(()) => report.session_count += 1,
+                Err(e) => failures.push(format!("会话 {} 导出失败: {}", entry.session_id, e)),
+            }
+        }
+
+        if report.session_count == 0 {
+            return Err(anyhow!("{}", failures.join("；")));
+        }
+        report.warnings.extend(failures);
+        info!(
+            "{} 已导出 {} 的 {} 条工时",
+            self.target_name(),
+            date,
+            report.session_count
+        );
+        Ok(report)
+    }
+
+    /// 预览将要导出的工时条目
+    async fn preview_day(
+        &self,
+        db: Arc<Database>,
+        _llm_handle: LLMHandle,
+        date: &str,
+    ) -> Result<Vec<NotePreview>> {
+        let entries = self.day_entries(&db, date).await?;
+        if entries.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(vec![NotePreview {
+            path: PathBuf::from(format!("{}/{}.json", self.target_name(), date)),
+            content: serde_json::to_string_pretty(&entries)?,
+            would_overwrite: false,
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{AppUsageRecord, Session, TimelineCardRecord};
+    use chrono::TimeZone;
+
+    fn session_data(id: i64, title: &str, minutes: i64, category: &str) -> SessionExportData {
+        let start = Utc.with_ymd_and_hms(2024, 5, 12, 9, 0, 0).unwrap();
+        SessionExportData {
+            session: Session {
+                id: Some(id),
+                start_time: start,
+                end_time: start + chrono::Duration::minutes(minutes),
+                title: title.to_string(),
+                summary: String::new(),
+                video_path: None,
+                tags: format!(
+                    r#"[{{"category":"{}","confidence":0.9,"keywords":["rust"]}}]"#,
+                    category
+                ),
+                created_at: None,
+                device_name: None,
+                device_type: None,
+            },
+            cards: Vec::<TimelineCardRecord>::new(),
+            issue_links: Vec::new(),
+            apps: Vec::new(),
+            domains: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_provider_parse() {
+        assert_eq!(
+            TimeTrackingProvider::parse(" Clockify ").unwrap(),
+            TimeTrackingProvider::Clockify
+        );
+        assert!(TimeTrackingProvider::parse("harvest").is_err());
+        assert!(TimeTrackingExporter::new(TimeTrackingExportConfig::default()).is_err());
+        assert!(TimeTrackingExporter::new(TimeTrackingExportConfig {
+            api_token: "token".to_string(),
+            ..TimeTrackingExportConfig::default()
+        })
+        .is_ok());
+    }
+
+    #[test]
+    fn test_build_entries() {
+        let config = TimeTrackingExportConfig {
+            default_project: "内部事务".to_string(),
+            min_minutes: 5,
+            ..TimeTrackingExportConfig::default()
+        };
+        let mut sessions = vec![
+            session_data(1, "接口重构", 90, "work"),
+            session_data(2, "发呆", 30, "idle"),
+            session_data(3, "查看通知", 3, "communication"),
+            session_data(4, " ", 20, "learning"),
+            session_data(5, "阅读源码", 30, "learning"),
+        ];
+        sessions[4].apps = vec![AppUsageRecord {
+            session_id: 5,
+            app_name: "Code".to_string(),
+            process_path: Some(
+                "/Applications/Visual Studio Code.app/Contents/MacOS/Electron".to_string(),
+            ),
+            window_title: "main.rs".to_string(),
+            seconds: 1500,
+        }];
+        let rule = |id: i64, project: &str, match_field: &str, pattern: &str| ProjectRuleRecord {
+            id: Some(id),
+            project: project.to_string(),
+            match_field: match_field.to_string(),
+            pattern: pattern.to_string(),
+            created_at: local_now(),
+        };
+        let rules = vec![
+            rule(1, "客户A", "tag", "work"),
+            rule(2, "开源", "process", "visual studio code"),
+        ];
+
+        let entries = build_entries(&config, &sessions, &rules);
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (
+                    entry.session_id,
+                    entry.description.as_str(),
+                    entry.project.as_deref(),
+                    entry.tags.clone(),
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, "接口重构", Some("客户A"), vec!["work".to_string()]),
+                (
+                    4,
+                    "未命名会话",
+                    Some("内部事务"),
+                    vec!["learning".to_string()]
+                ),
+                (5, "阅读源码", Some("开源"), vec!["learning".to_string()]),
+            ]
+        );
+        assert_eq!((entries[0].stop - entries[0].start).num_minutes(), 90);
+        assert_eq!(
+            entries[0].start,
+            stored_to_utc(sessions[0].session.start_time)
+        );
+        assert!(api_time(entries[0].start).ends_with('Z'));
+    }
+}
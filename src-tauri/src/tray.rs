@@ -0,0 +1,236 @@
+// 系统托盘菜单 - 显示截屏状态、当前会话时长与今日专注占比，
+// 并提供暂停 1 小时、开始新会话、导出今天到 Obsidian 等快捷操作（与前端调用同一组 Tauri 命令）
+
+use crate::storage::local_now;
+use crate::AppState;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::{AppHandle, Manager, Wry};
+use tracing::{info, warn};
+
+/// 托盘图标 ID（与 tauri.conf.json 的 trayIcon.id 一致）
+const TRAY_ID: &str = "main";
+
+/// 托盘状态刷新间隔（秒）
+const REFRESH_SECS: u64 = 30;
+
+/// 快捷暂停的分钟数
+const PAUSE_MINUTES: u64 = 60;
+
+/// 托盘显示的状态
+#[derive(Debug, Clone, Serialize)]
+pub struct TrayStatus {
+    /// 是否正在截屏（未暂停）
+    pub is_capturing: bool,
+    /// 是否处于免打扰时段
+    pub quiet_hours: bool,
+    /// 暂停后的自动恢复时间
+    pub resume_at: Option<DateTime<Utc>>,
+    /// 当前会话开始时间
+    pub session_start: Option<DateTime<Utc>>,
+    /// 当前会话已持续的分钟数
+    pub session_minutes: Option<i64>,
+    /// 今日专注占比（工作 + 学习，%），今天还没有分析结果时为空
+    pub focus_ratio: Option<i64>,
+}
+
+impl TrayStatus {
+    fn status_text(&self) -> String {
+        if !self.is_capturing {
+            return match self.resume_at {
+                Some(resume_at) => format!("已暂停（{} 恢复）", resume_at.format("%H:%M")),
+                None => "已暂停".to_string(),
+            };
+        }
+        if self.quiet_hours {
+            "免打扰中".to_string()
+        } else {
+            "正在截屏".to_string()
+        }
+    }
+
+    fn session_text(&self) -> String {
+        match self.session_minutes {
+            Some(minutes) => format!("当前会话：{} 分钟", minutes),
+            None => "当前会话：无".to_string(),
+        }
+    }
+
+    fn focus_text(&self) -> String {
+        match self.focus_ratio {
+            Some(ratio) => format!("今日专注：{}%", ratio),
+            None => "今日专注：暂无数据".to_string(),
+        }
+    }
+}
+
+/// 汇总托盘状态
+pub async fn tray_status(state: &AppState) -> TrayStatus {
+    let scheduler = state.capture_domain.get_scheduler();
+    let pause = scheduler.pause_state().await;
+    let session_start = scheduler.current_session_start();
+    let session_minutes = session_start.map(|start| (local_now() - start).num_minutes().max(0));
+
+    // 按今天已分析的时间线卡片统计（不调用 LLM）
+    let today = local_now().format("%Y-%m-%d").to_string();
+    let focus_ratio = match state.storage_domain.get_db().await {
+        Ok(db) => crate::domains::summary::SummaryGenerator::new(db)
+            .refresh_focus_metrics(&today)
+            .await
+            .ok()
+            .filter(|metrics| metrics.total_minutes > 0)
+            .map(|metrics| {
+                (metrics.work_minutes + metrics.learning_minutes) * 100 / metrics.total_minutes
+            }),
+        Err(_) => None,
+    };
+
+    TrayStatus {
+        is_capturing: !pause.paused,
+        quiet_hours: pause.quiet_hours,
+        resume_at: pause.resume_at,
+        session_start,
+        session_minutes,
+        focus_ratio,
+    }
+}
+
+/// 托盘菜单中需要更新的菜单项
+struct TrayMenu {
+    status: MenuItem<Wry>,
+    session: MenuItem<Wry>,
+    focus: MenuItem<Wry>,
+    pause: MenuItem<Wry>,
+    resume: MenuItem<Wry>,
+}
+
+impl TrayMenu {
+    fn update(&self, status: &TrayStatus) -> tauri::Result<()> {
+        self.status.set_text(status.status_text())?;
+        self.session.set_text(status.session_text())?;
+        self.focus.set_text(status.focus_text())?;
+        self.pause.set_enabled(status.is_capturing)?;
+        self.resume.set_enabled(!status.is_capturing)?;
+        Ok(())
+    }
+}
+
+/// 为托盘图标设置菜单，并定时刷新状态
+pub fn setup(app: &AppHandle) -> tauri::Result<()> {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        warn!("未找到托盘图标，跳过托盘菜单");
+        return Ok(());
+    };
+
+    let menu = TrayMenu {
+        status: MenuItem::with_id(app, "tray_status", "正在截屏", false, None::<&str>)?,
+        session: MenuItem::with_id(app, "tray_session", "当前会话：无", false, None::<&str>)?,
+        focus: MenuItem::with_id(app, "tray_focus", "今日专注：暂无数据", false, None::<&str>)?,
+        pause: MenuItem::with_id(app, "tray_pause", "暂停 1 小时", true, None::<&str>)?,
+        resume: MenuItem::with_id(app, "tray_resume", "恢复截屏", false, None::<&str>)?,
+    };
+    let new_session = MenuItem::with_id(app, "tray_new_session", "开始新会话", true, None::<&str>)?;
+    let export = MenuItem::with_id(
+        app,
+        "tray_export_today",
+        "导出今天到 Obsidian",
+        true,
+        None::<&str>,
+    )?;
+    let show = MenuItem::with_id(app, "tray_show", "显示主窗口", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "tray_quit", "退出", true, None::<&str>)?;
+
+    tray.set_menu(Some(Menu::with_items(
+        app,
+        &[
+            &menu.status,
+            &menu.session,
+            &menu.focus,
+            &PredefinedMenuItem::separator(app)?,
+            &menu.pause,
+            &menu.resume,
+            &new_session,
+            &export,
+            &PredefinedMenuItem::separator(app)?,
+            &show,
+            &quit,
+        ],
+    )?))?;
+    app.manage(menu);
+    app.on_menu_event(handle_menu_event);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            refresh(&app).await;
+            tokio::time::sleep(tokio::time::Duration::from_secs(REFRESH_SECS)).await;
+        }
+    });
+    Ok(())
+}
+
+/// 立即刷新托盘菜单（托盘未初始化时忽略）
+pub async fn refresh(app: &AppHandle) {
+    let (Some(state), Some(menu)) = (app.try_state::<AppState>(), app.try_state::<TrayMenu>())
+    else {
+        return;
+    };
+    let status = tray_status(&state).await;
+    if let Err(e) = menu.update(&status) {
+        warn!("更新托盘菜单失败: {}", e);
+    }
+}
+
+/// 在后台刷新托盘菜单（供前端触发的命令使用）
+pub fn request_refresh(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move { refresh(&app).await });
+}
+
+fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let app = app.clone();
+    match event.id().as_ref() {
+        "tray_pause" => {
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) =
+                    crate::pause_capture(app.clone(), app.state(), Some(PAUSE_MINUTES)).await
+                {
+                    warn!("托盘暂停截屏失败: {}", e);
+                }
+            });
+        }
+        "tray_resume" => {
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::resume_capture(app.clone(), app.state()).await {
+                    warn!("托盘恢复截屏失败: {}", e);
+                }
+            });
+        }
+        "tray_new_session" => {
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::start_new_session(app.clone(), app.state()).await {
+                    warn!("托盘开始新会话失败: {}", e);
+                }
+            });
+        }
+        "tray_export_today" => {
+            tauri::async_runtime::spawn(async move {
+                let today = local_now().format("%Y-%m-%d").to_string();
+                match crate::export_obsidian_day(app.clone(), app.state(), today, None).await {
+                    Ok(message) => info!("托盘导出今天到 Obsidian 完成: {}", message),
+                    Err(e) => warn!("托盘导出今天到 Obsidian 失败: {}", e),
+                }
+            });
+        }
+        "tray_show" => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.unminimize();
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+        "tray_quit" => app.exit(0),
+        _ => {}
+    }
+}
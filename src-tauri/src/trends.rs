@@ -0,0 +1,356 @@
+// 专注趋势 - 按天汇总投入时长、专注占比与生产力评分，计算 7/30 日移动平均与近 90 天百分位，
+// 供前端趋势图与周报的趋势小节使用；每日数据取自 daily_focus_metrics 缓存，缺失的日期按时间线卡片补算并回填
+
+use anyhow::{anyhow, Result};
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::domains::summary::focus_metrics_from_cards;
+use crate::models::ObsidianExportConfig;
+use crate::storage::{DailyFocusMetricsRecord, Database};
+
+/// 移动平均窗口（天）
+pub const MOVING_AVERAGE_DAYS: [i64; 2] = [7, 30];
+
+/// 百分位参考的回看天数（含当天）
+pub const PERCENTILE_LOOKBACK_DAYS: i64 = 90;
+
+/// 单次查询的最大天数
+pub const MAX_TREND_DAYS: i64 = 366;
+
+/// 每周按 5 个工作日折算每日目标投入时长
+const WORKDAYS_PER_WEEK: i64 = 5;
+
+/// 生产力评分参数（与周报一致：专注占比与投入时长按权重加权）
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ScoreWeights {
+    pub focus_weight: i64,
+    pub effort_weight: i64,
+    /// 每日目标投入时长（分钟）
+    pub daily_target_minutes: i64,
+}
+
+impl ScoreWeights {
+    /// 按周报评分配置折算（每日目标 = 每周目标 / 5）
+    pub fn from_weekly(focus_weight: i64, effort_weight: i64, weekly_target_minutes: i64) -> Self {
+        Self {
+            focus_weight,
+            effort_weight,
+            daily_target_minutes: (weekly_target_minutes / WORKDAYS_PER_WEEK).max(1),
+        }
+    }
+
+    pub fn from_config(config: &ObsidianExportConfig) -> Self {
+        let focus_weight = i64::from(config.weekly_focus_weight.min(100));
+        Self::from_weekly(
+            focus_weight,
+            100 - focus_weight,
+            config.weekly_target_minutes.max(1),
+        )
+    }
+
+    /// 单日生产力评分（0-100）
+    pub fn productivity_score(&self, record: &DailyFocusMetricsRecord) -> i64 {
+        let total_weight = (self.focus_weight + self.effort_weight).max(1);
+        let effort_score = if record.total_minutes == 0 {
+            0
+        } else {
+            (record.total_minutes * 100 / self.daily_target_minutes).clamp(0, 100)
+        };
+        (focus_ratio(record) * self.focus_weight + effort_score * self.effort_weight) / total_weight
+    }
+}
+
+/// 专注（工作 + 学习）时长占比（%）
+fn focus_ratio(record: &DailyFocusMetricsRecord) -> i64 {
+    if record.total_minutes == 0 {
+        0
+    } else {
+        ((record.work_minutes + record.learning_minutes) * 100 / record.total_minutes).max(0)
+    }
+}
+
+/// 三项指标各自的数值（移动平均、百分位、四分位共用）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct TrendValues {
+    pub total_minutes: f64,
+    pub focus_ratio: f64,
+    pub productivity_score: f64,
+}
+
+impl TrendValues {
+    fn map(values: &[TrendValues], f: impl Fn(&[f64]) -> f64) -> TrendValues {
+        let column =
+            |pick: fn(&TrendValues) -> f64| f(&values.iter().map(pick).collect::<Vec<_>>());
+        TrendValues {
+            total_minutes: column(|v| v.total_minutes),
+            focus_ratio: column(|v| v.focus_ratio),
+            productivity_score: column(|v| v.productivity_score),
+        }
+    }
+}
+
+/// 某天的趋势数据
+#[derive(Debug, Clone, Serialize)]
+pub struct TrendPoint {
+    pub date: String,
+    /// 当天有时间线记录
+    pub active: bool,
+    pub total_minutes: i64,
+    pub focus_minutes: i64,
+    pub focus_ratio: i64,
+    pub productivity_score: i64,
+    /// 截至当天的 7 日移动平均（只计有记录的日期，窗口内无记录时为空）
+    pub ma7: Option<TrendValues>,
+    /// 截至当天的 30 日移动平均
+    pub ma30: Option<TrendValues>,
+    /// 当天在近 90 天有记录日期中的百分位（0-100，当天无记录时为空）
+    pub percentile: Option<TrendValues>,
+}
+
+/// 日期范围内的趋势序列
+#[derive(Debug, Clone, Serialize)]
+pub struct TrendSeries {
+    pub start_date: String,
+    pub end_date: String,
+    pub weights: ScoreWeights,
+    pub points: Vec<TrendPoint>,
+    pub active_days: usize,
+    /// 范围内有记录日期的 25/50/75 分位数（用于趋势图的参考带）
+    pub quartiles: Option<[TrendValues; 3]>,
+}
+
+/// 某天的指标值（无记录时为空）
+fn day_values(record: &DailyFocusMetricsRecord, weights: &ScoreWeights) -> Option<TrendValues> {
+    (record.total_minutes > 0).then(|| TrendValues {
+        total_minutes: record.total_minutes as f64,
+        focus_ratio: focus_ratio(record) as f64,
+        productivity_score: weights.productivity_score(record) as f64,
+    })
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// 线性插值分位数（values 非空）
+fn quantile(values: &[f64], q: f64) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let position = q * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+/// 不超过 value 的比例（%）
+fn percentile_rank(values: &[f64], value: f64) -> f64 {
+    let below = values.iter().filter(|v| **v <= value).count();
+    (below * 100) as f64 / values.len() as f64
+}
+
+/// 按每日指标计算趋势序列；records 需覆盖 start 前的回看期（缺失日期视为无记录）
+pub fn build_series(
+    records: &[DailyFocusMetricsRecord],
+    weights: ScoreWeights,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> TrendSeries {
+    let by_date: HashMap<NaiveDate, &DailyFocusMetricsRecord> =
+        records.iter().map(|record| (record.date, record)).collect();
+    let values_on = |date: NaiveDate| {
+        by_date
+            .get(&date)
+            .and_then(|record| day_values(record, &weights))
+    };
+    let window = |date: NaiveDate, days: i64| -> Vec<TrendValues> {
+        (0..days)
+            .filter_map(|offset| values_on(date - Duration::days(offset)))
+            .collect()
+    };
+    let moving_average = |date: NaiveDate, days: i64| {
+        let values = window(date, days);
+        (!values.is_empty()).then(|| TrendValues::map(&values, mean))
+    };
+
+    let mut points = Vec::new();
+    let mut active_values = Vec::new();
+    for date in start.iter_days().take_while(|date| *date <= end) {
+        let record = by_date.get(&date);
+        let today = values_on(date);
+        if let Some(values) = today {
+            active_values.push(values);
+        }
+        let percentile = today.map(|values| {
+            let lookback = window(date, PERCENTILE_LOOKBACK_DAYS);
+            TrendValues {
+                total_minutes: percentile_rank(
+                    &lookback.iter().map(|v| v.total_minutes).collect::<Vec<_>>(),
+                    values.total_minutes,
+                ),
+                focus_ratio: percentile_rank(
+                    &lookback.iter().map(|v| v.focus_ratio).collect::<Vec<_>>(),
+                    values.focus_ratio,
+                ),
+                productivity_score: percentile_rank(
+                    &lookback
+                        .iter()
+                        .map(|v| v.productivity_score)
+                        .collect::<Vec<_>>(),
+                    values.productivity_score,
+                ),
+            }
+        });
+
+        points.push(TrendPoint {
+            date: date.format("%Y-%m-%d").to_string(),
+            active: today.is_some(),
+            total_minutes: record.map_or(0, |r| r.total_minutes),
+            focus_minutes: record.map_or(0, |r| r.work_minutes + r.learning_minutes),
+            focus_ratio: record.map_or(0, |r| focus_ratio(r)),
+            productivity_score: record.map_or(0, |r| weights.productivity_score(r)),
+            ma7: moving_average(date, MOVING_AVERAGE_DAYS[0]),
+            ma30: moving_average(date, MOVING_AVERAGE_DAYS[1]),
+            percentile,
+        });
+    }
+
+    let quartiles = (!active_values.is_empty()).then(|| {
+        [0.25, 0.5, 0.75].map(|q| TrendValues::map(&active_values, |values| quantile(values, q)))
+    });
+    TrendSeries {
+        start_date: start.format("%Y-%m-%d").to_string(),
+        end_date: end.format("%Y-%m-%d").to_string(),
+        weights,
+        points,
+        active_days: active_values.len(),
+        quartiles,
+    }
+}
+
+/// 读取日期范围内每天的专注度指标；缓存缺失的日期按时间线卡片补算，有会话的日期回填缓存
+pub async fn daily_metrics(
+    db: &Database,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<DailyFocusMetricsRecord>> {
+    let mut cached: HashMap<NaiveDate, DailyFocusMetricsRecord> = db
+        .get_daily_focus_metrics(
+            &start.format("%Y-%m-%d").to_string(),
+            &end.format("%Y-%m-%d").to_string(),
+        )
+        .await?
+        .into_iter()
+        .map(|record| (record.date, record))
+        .collect();
+
+    let today = crate::storage::local_now().date_naive();
+    let mut records = Vec::new();
+    for day in start.iter_days().take_while(|day| *day <= end.min(today)) {
+        if let Some(record) = cached.remove(&day) {
+            records.push(record);
+            continue;
+        }
+        let date = day.format("%Y-%m-%d").to_string();
+        let sessions = db.get_sessions_by_date(&date).await?;
+        if sessions.is_empty() {
+            continue;
+        }
+        let mut cards = Vec::new();
+        for session_id in sessions.iter().filter_map(|session| session.id) {
+            cards.extend(db.get_timeline_cards_by_session(session_id).await?);
+        }
+        let record = focus_metrics_from_cards(day, &cards);
+        db.save_daily_focus_metrics(&date, &record).await?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// 日期范围内的趋势序列（同时读取范围前的回看期用于移动平均与百分位）
+pub async fn focus_trend(
+    db: &Database,
+    start: NaiveDate,
+    end: NaiveDate,
+    weights: ScoreWeights,
+) -> Result<TrendSeries> {
+    if end < start {
+        return Err(anyhow!("结束日期早于开始日期"));
+    }
+    if (end - start).num_days() >= MAX_TREND_DAYS {
+        return Err(anyhow!("趋势查询最多 {} 天", MAX_TREND_DAYS));
+    }
+    let lookback = MOVING_AVERAGE_DAYS[1].max(PERCENTILE_LOOKBACK_DAYS) - 1;
+    let records = daily_metrics(db, start - Duration::days(lookback), end).await?;
+    Ok(build_series(&records, weights, start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(offset: i64) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, 1).unwrap() + Duration::days(offset)
+    }
+
+    fn record(offset: i64, total: i64, work: i64) -> DailyFocusMetricsRecord {
+        DailyFocusMetricsRecord {
+            date: day(offset),
+            total_minutes: total,
+            work_minutes: work,
+            learning_minutes: 0,
+            communication_minutes: 0,
+            personal_minutes: total - work,
+            idle_minutes: 0,
+            other_minutes: 0,
+            updated_at: crate::storage::local_now(),
+        }
+    }
+
+    #[test]
+    fn test_productivity_score_uses_daily_target() {
+        let weights = ScoreWeights::from_weekly(60, 40, 2400);
+        assert_eq!(weights.daily_target_minutes, 480);
+        // 专注 50%，投入 240/480 = 50%
+        assert_eq!(weights.productivity_score(&record(0, 240, 120)), 50);
+        // 投入超过目标按 100 计
+        assert_eq!(weights.productivity_score(&record(0, 960, 960)), 100);
+    }
+
+    #[test]
+    fn test_moving_averages_skip_inactive_days() {
+        let weights = ScoreWeights::from_weekly(100, 0, 2400);
+        let records = vec![
+            record(0, 100, 20),
+            record(2, 100, 60),
+            // 第 3 天无记录
+            record(9, 200, 200),
+        ];
+        let series = build_series(&records, weights, day(2), day(9));
+        assert_eq!(series.points.len(), 8);
+        assert_eq!(series.active_days, 2);
+
+        let third = &series.points[0];
+        assert_eq!(third.focus_ratio, 60);
+        assert_eq!(third.ma7.unwrap().focus_ratio, 40.0);
+        assert_eq!(third.percentile.unwrap().focus_ratio, 100.0);
+
+        // 第 4 天无记录：移动平均沿用窗口内的记录，百分位为空
+        let fourth = &series.points[1];
+        assert!(!fourth.active);
+        assert_eq!(fourth.ma7.unwrap().focus_ratio, 40.0);
+        assert!(fourth.percentile.is_none());
+
+        // 第 10 天：7 日窗口只剩当天，30 日窗口包含全部三天
+        let last = series.points.last().unwrap();
+        assert_eq!(last.ma7.unwrap().total_minutes, 200.0);
+        assert_eq!(last.ma30.unwrap().focus_ratio, 60.0);
+        assert_eq!(last.percentile.unwrap().total_minutes, 100.0);
+        assert_eq!(last.percentile.unwrap().focus_ratio, 100.0);
+
+        let [low, median, high] = series.quartiles.unwrap();
+        assert_eq!(median.focus_ratio, 80.0);
+        assert_eq!((low.focus_ratio, high.focus_ratio), (70.0, 90.0));
+    }
+}
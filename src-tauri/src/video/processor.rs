@@ -146,6 +146,15 @@ impl VideoProcessor {
             return Err(anyhow::anyhow!("没有可用的帧"));
         }
 
+        // ffmpeg 输出的视频无法加密保存，启用截图加密时不生成视频
+        if crate::frame_crypto::is_enabled() {
+            return Err(anyhow::anyhow!("已启用截图加密，不生成会话视频"));
+        }
+
+        // ffmpeg 直接读取图片文件，关闭加密前写入的加密截图先解密到临时目录（视频生成后删除）
+        let plaintext = crate::frame_crypto::plaintext_frames(&frames).await?;
+        let frames = plaintext.paths.clone();
+
         // 快速检测图片分辨率：只检查前几张图片，假设所有图片分辨率相同
         // 取输入图片和配置分辨率的最大值，确保容器足够大
         let mut resolution = config.resolution;
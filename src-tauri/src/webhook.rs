@@ -0,0 +1,219 @@
+// Webhook 导出 - 将每日总结与会话以 JSON POST 到用户配置的地址（HMAC 签名、失败重试）
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+use sha2::Sha256;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::actors::LLMHandle;
+use crate::exporter::{ExportReport, Exporter, NotePreview};
+use crate::json_export::build_day;
+use crate::models::{JsonExportDay, WebhookExportConfig, JSON_EXPORT_SCHEMA_VERSION};
+use crate::storage::{local_now, Database};
+
+/// 请求体签名头，值为 `sha256=<hex>`
+const SIGNATURE_HEADER: &str = "X-Screen-Analyzer-Signature-256";
+/// 事件类型头
+const EVENT_HEADER: &str = "X-Screen-Analyzer-Event";
+const DAY_EVENT: &str = "day.exported";
+
+/// 首次重试前的等待时间，之后逐次翻倍
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Webhook 请求体
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookPayload {
+    pub event: &'static str,
+    /// 与 JSON 导出共用的结构版本
+    pub schema_version: u32,
+    pub sent_at: chrono::NaiveDateTime,
+    pub day: JsonExportDay,
+}
+
+/// Webhook 导出目标：每天发送一次请求，包含当天总结与全部会话
+pub struct WebhookExporter {
+    config: WebhookExportConfig,
+    client: Client,
+}
+
+impl WebhookExporter {
+    pub fn new(config: WebhookExportConfig) -> Result<Self> {
+        let url = config.url.trim();
+        if url.is_empty() {
+            return Err(anyhow!("请先配置 Webhook 地址"));
+        }
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(anyhow!("Webhook 地址需以 http:// 或 https:// 开头"));
+        }
+        let client = Client::builder().timeout(Duration::from_secs(30)).build()?;
+        Ok(Self { config, client })
+    }
+
+    async fn build_payload(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        date: &str,
+        force_refresh: bool,
+    ) -> Result<Option<WebhookPayload>> {
+        Ok(build_day(db, llm_handle, date, force_refresh)
+            .await?
+            .map(|day| WebhookPayload {
+                event: DAY_EVENT,
+                schema_version: JSON_EXPORT_SCHEMA_VERSION,
+                sent_at: local_now().naive_utc(),
+                day,
+            }))
+    }
+
+    /// 发送请求体；网络错误、429 与 5xx 按指数退避重试
+    async fn deliver(&self, body: Vec<u8>) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            let mut request = self
+                .client
+                .post(self.config.url.trim())
+                .header("Content-Type", "application/json")
+                .header(EVENT_HEADER, DAY_EVENT);
+            if let Some(signature) = sign(&self.config.secret, &body) {
+                request = request.header(SIGNATURE_HEADER, signature);
+            }
+
+            let error = match request.body(body.clone()).send().await {
+                Ok(response) if response.status().is_success() => return Ok(()),
+                Ok(response) => {
+                    let status = response.status();
+                    let text = response.text().await.unwrap_or_default();
+                    let error = anyhow!("Webhook 返回 {}: {}", status.as_u16(), text);
+                    if !is_retryable(status) {
+                        return Err(error);
+                    }
+                    error
+                }
+                Err(e) => anyhow!("Webhook 请求失败: {}", e),
+            };
+
+            if attempt >= self.config.max_retries {
+                return Err(error);
+            }
+            let delay = RETRY_BASE_DELAY * 2u32.pow(attempt.min(5));
+            attempt += 1;
+            warn!(
+                "{}，{}ms 后重试（{}/{}）",
+                error,
+                delay.as_millis(),
+                attempt,
+                self.config.max_retries
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[async_trait]
+impl Exporter for WebhookExporter {
+    fn target_name(&self) -> &'static str {
+        "Webhook"
+    }
+
+    async fn export_day(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        date: &str,
+        force_refresh: bool,
+    ) -> Result<ExportReport> {
+        let mut report = ExportReport {
+            target: self.target_name().to_string(),
+            exported_dates: vec![date.to_string()],
+            ..ExportReport::default()
+        };
+        let Some(payload) = self
+            .build_payload(db, llm_handle, date, force_refresh)
+            .await?
+        else {
+            report.warnings.push("当天没有会话，未发送".to_string());
+            return Ok(report);
+        };
+        if payload.day.summary.is_none() {
+            report
+                .warnings
+                .push("每日总结生成失败，已只发送会话".to_string());
+        }
+
+        self.deliver(serde_json::to_vec(&payload)?).await?;
+        report.session_count = payload.day.sessions.len();
+        info!("Webhook 已发送 {} 的 {} 个会话", date, report.session_count);
+        Ok(report)
+    }
+
+    /// 预览将要发送的请求体
+    async fn preview_day(
+        &self,
+        db: Arc<Database>,
+        llm_handle: LLMHandle,
+        date: &str,
+    ) -> Result<Vec<NotePreview>> {
+        let Some(payload) = self.build_payload(db, llm_handle, date, false).await? else {
+            return Ok(Vec::new());
+        };
+        Ok(vec![NotePreview {
+            path: PathBuf::from(format!("Webhook/{}.json", date)),
+            content: serde_json::to_string_pretty(&payload)?,
+            would_overwrite: false,
+        }])
+    }
+}
+
+/// 以 HMAC-SHA256 签名请求体，密钥为空时不签名
+fn sign(secret: &str, body: &[u8]) -> Option<String> {
+    if secret.is_empty() {
+        return None;
+    }
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).ok()?;
+    mac.update(body);
+    Some(format!(
+        "sha256={}",
+        hex::encode(mac.finalize().into_bytes())
+    ))
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_body() {
+        // RFC 4231 测试用例 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?").as_deref(),
+            Some("sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843")
+        );
+        assert!(sign("", b"{}").is_none());
+    }
+
+    #[test]
+    fn test_webhook_config_validation() {
+        let config = |url: &str| WebhookExportConfig {
+            enabled: true,
+            url: url.to_string(),
+            ..WebhookExportConfig::default()
+        };
+        assert!(WebhookExporter::new(config("")).is_err());
+        assert!(WebhookExporter::new(config("ftp://example.com/hook")).is_err());
+        assert!(WebhookExporter::new(config("https://n8n.example.com/webhook/abc")).is_ok());
+        assert!(is_retryable(StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable(StatusCode::BAD_REQUEST));
+    }
+}
@@ -0,0 +1,421 @@
+// 过劳迹象分析 - 按天统计屏幕时长、深夜活动与休息时长（凌晨 5 点前的活动计入前一天），
+// 识别超长屏幕日、连续深夜工作与休息时间逐周减少，用于周报提醒与每日通知
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Timelike, Weekday};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use crate::domains::summary::normalize_timeline_category;
+use crate::llm::plugin::ActivityCategory;
+use crate::models::WellbeingConfig;
+use crate::storage::{Database, TimelineCardRecord};
+
+/// 早于该时刻（本地时间）的活动计入前一天
+const DAY_START_HOUR: u32 = 5;
+
+/// 超过该长度的空档视为下班或睡眠，不计入休息
+const MAX_BREAK_MINUTES: i64 = 180;
+
+/// 上一周期每小时屏幕时间的休息不足该分钟数时不做比较
+const MIN_BASELINE_BREAK_PER_HOUR: f64 = 3.0;
+
+/// 每个比较周期的天数（报告覆盖前后两个周期）
+pub const PERIOD_DAYS: i64 = 7;
+
+/// 当前生效的配置（启动时及保存设置后更新，供周报导出读取）
+static CONFIG: RwLock<Option<WellbeingConfig>> = RwLock::new(None);
+
+/// 替换当前生效的配置
+pub fn set_config(config: WellbeingConfig) {
+    if let Ok(mut current) = CONFIG.write() {
+        *current = Some(config);
+    }
+}
+
+/// 当前生效的配置
+pub fn config() -> WellbeingConfig {
+    CONFIG
+        .read()
+        .ok()
+        .and_then(|current| current.clone())
+        .unwrap_or_default()
+}
+
+/// 单日统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WellbeingDay {
+    pub date: String,
+    /// 非空闲活动时长（分钟）
+    pub screen_minutes: i64,
+    /// 深夜（配置时刻之后至次日凌晨 5 点）的活动时长（分钟）
+    pub late_night_minutes: i64,
+    /// 活动之间的短暂空档与空闲/休息卡片时长（分钟）
+    pub break_minutes: i64,
+}
+
+/// 过劳迹象
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WellbeingWarning {
+    /// 屏幕时长超过上限
+    LongDay { date: String, screen_minutes: i64 },
+    /// 连续多天深夜活动
+    LateNightStreak {
+        start_date: String,
+        end_date: String,
+        days: usize,
+    },
+    /// 每小时屏幕时间的休息时长较上一周期明显减少
+    ShrinkingBreaks {
+        previous_per_hour: f64,
+        current_per_hour: f64,
+        drop_percent: i64,
+    },
+}
+
+impl WellbeingWarning {
+    /// 是否为截至 `day` 新出现的迹象（用于每日通知：当天的超长屏幕日、截至当天的深夜连续，
+    /// 休息减少仅在周日按整周提醒）
+    pub fn concerns(&self, day: NaiveDate) -> bool {
+        let date_text = day.format("%Y-%m-%d").to_string();
+        match self {
+            WellbeingWarning::LongDay { date, .. } => *date == date_text,
+            WellbeingWarning::LateNightStreak { end_date, .. } => *end_date == date_text,
+            WellbeingWarning::ShrinkingBreaks { .. } => day.weekday() == Weekday::Sun,
+        }
+    }
+
+    /// 中文描述（用于通知）
+    pub fn describe(&self) -> String {
+        match self {
+            WellbeingWarning::LongDay {
+                date,
+                screen_minutes,
+            } => format!(
+                "{} 屏幕时间 {} 小时 {} 分钟",
+                date,
+                screen_minutes / 60,
+                screen_minutes % 60
+            ),
+            WellbeingWarning::LateNightStreak {
+                start_date,
+                end_date,
+                days,
+            } => format!("{} 至 {} 连续 {} 天深夜工作", start_date, end_date, days),
+            WellbeingWarning::ShrinkingBreaks {
+                previous_per_hour,
+                current_per_hour,
+                drop_percent,
+            } => format!(
+                "每小时休息从 {:.0} 分钟减少到 {:.0} 分钟（-{}%）",
+                previous_per_hour, current_per_hour, drop_percent
+            ),
+        }
+    }
+}
+
+/// 过劳迹象报告
+#[derive(Debug, Clone, Serialize)]
+pub struct WellbeingReport {
+    pub start_date: String,
+    pub end_date: String,
+    /// 当前周期的每日统计
+    pub days: Vec<WellbeingDay>,
+    pub warnings: Vec<WellbeingWarning>,
+}
+
+/// 活动所属的日期（凌晨 5 点前计入前一天）
+fn activity_day(time: NaiveDateTime) -> NaiveDate {
+    (time - Duration::hours(i64::from(DAY_START_HOUR))).date()
+}
+
+#[derive(Default)]
+struct DayAccumulator {
+    screen_seconds: i64,
+    late_night_seconds: i64,
+    idle_seconds: i64,
+    intervals: Vec<(NaiveDateTime, NaiveDateTime)>,
+}
+
+impl DayAccumulator {
+    /// 活动之间不超过 MAX_BREAK_MINUTES 的空档（秒）
+    fn gap_seconds(&mut self) -> i64 {
+        self.intervals.sort();
+        let mut gaps = 0;
+        let mut covered_until: Option<NaiveDateTime> = None;
+        for (start, end) in &self.intervals {
+            if let Some(until) = covered_until {
+                let gap = (*start - until).num_seconds();
+                if gap > 0 && gap <= MAX_BREAK_MINUTES * 60 {
+                    gaps += gap;
+                }
+            }
+            covered_until = Some(covered_until.map_or(*end, |until| until.max(*end)));
+        }
+        gaps
+    }
+}
+
+/// 按卡片统计 start..=end 每天的数据（没有记录的日期各项为 0）
+pub fn daily_stats(
+    cards: &[TimelineCardRecord],
+    start: NaiveDate,
+    end: NaiveDate,
+    config: &WellbeingConfig,
+) -> Vec<WellbeingDay> {
+    let late_hour = config.late_night_hour.clamp(DAY_START_HOUR, 23);
+    let mut days: BTreeMap<NaiveDate, DayAccumulator> = BTreeMap::new();
+
+    for card in cards {
+        let (Ok(card_start), Ok(card_end)) = (
+            DateTime::parse_from_rfc3339(&card.start_time),
+            DateTime::parse_from_rfc3339(&card.end_time),
+        ) else {
+            continue;
+        };
+        let (card_start, card_end) = (card_start.naive_local(), card_end.naive_local());
+        if card_end <= card_start {
+            continue;
+        }
+        if matches!(
+            normalize_timeline_category(&card.category),
+            ActivityCategory::Idle
+        ) {
+            days.entry(activity_day(card_start))
+                .or_default()
+                .idle_seconds += (card_end - card_start).num_seconds();
+            continue;
+        }
+
+        days.entry(activity_day(card_start))
+            .or_default()
+            .intervals
+            .push((card_start, card_end));
+        // 按整点拆分，深夜与日期分界都落在整点上
+        let mut cursor = card_start;
+        while cursor < card_end {
+            let hour_start = cursor.date().and_hms_opt(cursor.hour(), 0, 0).unwrap();
+            let slot_end = (hour_start + Duration::hours(1)).min(card_end);
+            let seconds = (slot_end - cursor).num_seconds();
+            let day = days.entry(activity_day(cursor)).or_default();
+            day.screen_seconds += seconds;
+            if cursor.hour() >= late_hour || cursor.hour() < DAY_START_HOUR {
+                day.late_night_seconds += seconds;
+            }
+            cursor = slot_end;
+        }
+    }
+
+    start
+        .iter_days()
+        .take_while(|day| *day <= end)
+        .map(|date| {
+            let mut day = days.remove(&date).unwrap_or_default();
+            WellbeingDay {
+                date: date.format("%Y-%m-%d").to_string(),
+                screen_minutes: day.screen_seconds / 60,
+                late_night_minutes: day.late_night_seconds / 60,
+                break_minutes: (day.gap_seconds() + day.idle_seconds) / 60,
+            }
+        })
+        .collect()
+}
+
+/// 每小时屏幕时间对应的休息分钟数（没有屏幕时间时为 None）
+fn break_per_hour(days: &[WellbeingDay]) -> Option<f64> {
+    let screen: i64 = days.iter().map(|day| day.screen_minutes).sum();
+    let breaks: i64 = days.iter().map(|day| day.break_minutes).sum();
+    (screen > 0).then(|| breaks as f64 * 60.0 / screen as f64)
+}
+
+/// 按日期升序的每日统计识别过劳迹象：最后 `PERIOD_DAYS` 天为当前周期，之前为上一周期
+pub fn evaluate(days: &[WellbeingDay], config: &WellbeingConfig) -> Vec<WellbeingWarning> {
+    let split = days.len().saturating_sub(PERIOD_DAYS as usize);
+    let (previous, current) = days.split_at(split);
+    let mut warnings = Vec::new();
+
+    let max_minutes = i64::from(config.max_screen_minutes);
+    for day in current {
+        if max_minutes > 0 && day.screen_minutes > max_minutes {
+            warnings.push(WellbeingWarning::LongDay {
+                date: day.date.clone(),
+                screen_minutes: day.screen_minutes,
+            });
+        }
+    }
+
+    // 连续深夜：可以从上一周期开始，只报告延续到当前周期的
+    let late_minutes = i64::from(config.late_night_minutes.max(1));
+    let streak_days = config.late_streak_days.max(1) as usize;
+    let mut run_start = 0;
+    for index in 0..=days.len() {
+        let late = days
+            .get(index)
+            .is_some_and(|day| day.late_night_minutes >= late_minutes);
+        if late {
+            continue;
+        }
+        if index - run_start >= streak_days && index > split {
+            warnings.push(WellbeingWarning::LateNightStreak {
+                start_date: days[run_start].date.clone(),
+                end_date: days[index - 1].date.clone(),
+                days: index - run_start,
+            });
+        }
+        run_start = index + 1;
+    }
+
+    if let (Some(previous_per_hour), Some(current_per_hour)) =
+        (break_per_hour(previous), break_per_hour(current))
+    {
+        let drop_percent = ((1.0 - current_per_hour / previous_per_hour) * 100.0).round() as i64;
+        if previous_per_hour >= MIN_BASELINE_BREAK_PER_HOUR
+            && config.break_drop_percent > 0
+            && drop_percent >= i64::from(config.break_drop_percent)
+        {
+            warnings.push(WellbeingWarning::ShrinkingBreaks {
+                previous_per_hour,
+                current_per_hour,
+                drop_percent,
+            });
+        }
+    }
+    warnings
+}
+
+/// 截至 `end`（含）的报告：当前周期为最近 7 天，与之前 7 天比较
+pub fn build_report(
+    cards: &[TimelineCardRecord],
+    end: NaiveDate,
+    config: &WellbeingConfig,
+) -> WellbeingReport {
+    let start = end - Duration::days(PERIOD_DAYS - 1);
+    let days = daily_stats(cards, start - Duration::days(PERIOD_DAYS), end, config);
+    let warnings = evaluate(&days, config);
+    WellbeingReport {
+        start_date: start.format("%Y-%m-%d").to_string(),
+        end_date: end.format("%Y-%m-%d").to_string(),
+        days: days[PERIOD_DAYS as usize..].to_vec(),
+        warnings,
+    }
+}
+
+/// 从数据库读取卡片并生成截至 `end` 的报告
+pub async fn wellbeing_report(
+    db: &Database,
+    end: NaiveDate,
+    config: &WellbeingConfig,
+) -> Result<WellbeingReport> {
+    // 前一天开始的会话可能跨过零点；次日凌晨的会话计入 end 当天
+    let first = end - Duration::days(PERIOD_DAYS * 2);
+    let mut cards = Vec::new();
+    for day in first
+        .iter_days()
+        .take_while(|day| *day <= end + Duration::days(1))
+    {
+        let sessions = db
+            .get_sessions_by_date(&day.format("%Y-%m-%d").to_string())
+            .await?;
+        for session_id in sessions.iter().filter_map(|session| session.id) {
+            cards.extend(db.get_timeline_cards_by_session(session_id).await?);
+        }
+    }
+    Ok(build_report(&cards, end, config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(category: &str, start: &str, end: &str) -> TimelineCardRecord {
+        TimelineCardRecord {
+            id: None,
+            session_id: 1,
+            llm_call_id: None,
+            start_time: format!("{}:00+08:00", start),
+            end_time: format!("{}:00+08:00", end),
+            category: category.to_string(),
+            subcategory: String::new(),
+            title: String::new(),
+            summary: String::new(),
+            detailed_summary: String::new(),
+            distractions: None,
+            app_sites: "[]".to_string(),
+            confidence: None,
+            heuristic: false,
+            video_preview_path: None,
+            created_at: crate::storage::local_now(),
+        }
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+    }
+
+    #[test]
+    fn test_daily_stats_splits_late_night_and_breaks() {
+        let cards = vec![
+            card("work", "2024-05-06T09:00", "2024-05-06T12:00"),
+            // 午饭 1 小时空档计为休息
+            card("work", "2024-05-06T13:00", "2024-05-06T18:00"),
+            card("break", "2024-05-06T15:00", "2024-05-06T15:15"),
+            // 晚间空档超过 3 小时不计；凌晨的活动计入前一天
+            card("personal", "2024-05-06T22:30", "2024-05-07T01:00"),
+        ];
+        let days = daily_stats(&cards, date(6), date(7), &WellbeingConfig::default());
+        assert_eq!(
+            days[0],
+            WellbeingDay {
+                date: "2024-05-06".to_string(),
+                screen_minutes: 630,
+                late_night_minutes: 150,
+                break_minutes: 75,
+            }
+        );
+        assert_eq!(days[1].screen_minutes, 0);
+    }
+
+    #[test]
+    fn test_evaluate_flags_overwork_patterns() {
+        let day = |offset: u32, screen: i64, late: i64, breaks: i64| WellbeingDay {
+            date: date(offset).format("%Y-%m-%d").to_string(),
+            screen_minutes: screen,
+            late_night_minutes: late,
+            break_minutes: breaks,
+        };
+        let mut days: Vec<WellbeingDay> = (1..=7).map(|d| day(d, 480, 0, 80)).collect();
+        days[6].late_night_minutes = 60;
+        days.extend((8..=14).map(|d| day(d, 480, 0, 40)));
+        days[7].late_night_minutes = 45;
+        days[8].late_night_minutes = 90;
+        days[13].screen_minutes = 700;
+
+        let config = WellbeingConfig::default();
+        let warnings = evaluate(&days, &config);
+        assert_eq!(
+            warnings,
+            vec![
+                WellbeingWarning::LongDay {
+                    date: "2024-05-14".to_string(),
+                    screen_minutes: 700,
+                },
+                WellbeingWarning::LateNightStreak {
+                    start_date: "2024-05-07".to_string(),
+                    end_date: "2024-05-09".to_string(),
+                    days: 3,
+                },
+                WellbeingWarning::ShrinkingBreaks {
+                    previous_per_hour: 10.0,
+                    current_per_hour: 40.0 * 7.0 * 60.0 / (480.0 * 6.0 + 700.0),
+                    drop_percent: 53,
+                },
+            ]
+        );
+        assert!(warnings[0].concerns(date(14)));
+        assert!(!warnings[1].concerns(date(14)));
+        assert!(!warnings[2].concerns(date(14)));
+        assert!(warnings[2].concerns(date(12)));
+    }
+}
@@ -32,6 +32,16 @@ export const useActivityStore = defineStore('activity', {
       },
       last_error: null
     },
+    // 截屏暂停与免打扰状态
+    capturePause: {
+      paused: false,
+      resume_at: null,
+      quiet_hours: false
+    },
+    // 进行中的手动会话（自定义标题）
+    manualSession: null,
+    // 进行中的专注时段（番茄钟）
+    focusBlock: null,
     // 应用配置
     appConfig: {
       retention_days: 7,
@@ -152,6 +162,9 @@ export const useActivityStore = defineStore('activity', {
       try {
         const status = await invoke('get_system_status')
         this.systemStatus = status
+        this.capturePause = await invoke('get_capture_pause_state')
+        this.manualSession = await invoke('get_manual_session')
+        this.focusBlock = await invoke('get_focus_status')
       } catch (error) {
         console.error('Failed to fetch system status:', error)
         ElMessage.error('获取系统状态失败: ' + error)
@@ -201,6 +214,87 @@ export const useActivityStore = defineStore('activity', {
       }
     },
 
+    // 暂停截屏，minutes 为空时直到手动恢复
+    async pauseCapture(minutes = null) {
+      try {
+        this.capturePause = await invoke('pause_capture', { minutes })
+        this.systemStatus.is_capturing = false
+        ElMessage.success(minutes ? `已暂停截屏 ${minutes} 分钟` : '已暂停截屏')
+      } catch (error) {
+        ElMessage.error('暂停截屏失败: ' + error)
+        console.error('Failed to pause capture:', error)
+      }
+    },
+
+    // 从现在起开始新会话
+    async startNewSession() {
+      try {
+        await invoke('start_new_session')
+        ElMessage.success('已开始新会话')
+      } catch (error) {
+        ElMessage.error('开始新会话失败: ' + error)
+        console.error('Failed to start new session:', error)
+      }
+    },
+
+    // 以自定义标题开始手动会话
+    async startManualSession(title) {
+      try {
+        this.manualSession = await invoke('start_manual_session', { title })
+        ElMessage.success(`已开始手动会话「${this.manualSession.title}」`)
+      } catch (error) {
+        ElMessage.error('开始手动会话失败: ' + error)
+        console.error('Failed to start manual session:', error)
+      }
+    },
+
+    // 结束进行中的手动会话
+    async stopManualSession() {
+      try {
+        const session = await invoke('stop_manual_session')
+        this.manualSession = null
+        ElMessage.success(`已结束手动会话「${session.title}」`)
+      } catch (error) {
+        ElMessage.error('结束手动会话失败: ' + error)
+        console.error('Failed to stop manual session:', error)
+      }
+    },
+
+    // 开始专注时段（期间截取的帧标记为该时段）
+    async startFocusBlock(minutes, label) {
+      try {
+        this.focusBlock = await invoke('start_focus_block', { minutes, label })
+        ElMessage.success(`已开始 ${minutes} 分钟专注`)
+      } catch (error) {
+        ElMessage.error('开始专注失败: ' + error)
+        console.error('Failed to start focus block:', error)
+      }
+    },
+
+    // 提前结束专注时段
+    async stopFocusBlock() {
+      try {
+        await invoke('stop_focus_block')
+        this.focusBlock = null
+        ElMessage.success('已结束专注')
+      } catch (error) {
+        ElMessage.error('结束专注失败: ' + error)
+        console.error('Failed to stop focus block:', error)
+      }
+    },
+
+    // 恢复截屏
+    async resumeCapture() {
+      try {
+        this.capturePause = await invoke('resume_capture')
+        this.systemStatus.is_capturing = true
+        ElMessage.success('已恢复截屏')
+      } catch (error) {
+        ElMessage.error('恢复截屏失败: ' + error)
+        console.error('Failed to resume capture:', error)
+      }
+    },
+
     // 手动触发分析
     async triggerAnalysis() {
       try {
@@ -294,6 +388,69 @@ export const useActivityStore = defineStore('activity', {
       }
     },
 
+    // 在指定时间拆分会话，返回新会话 ID
+    async splitSession(sessionId, at) {
+      try {
+        const newSessionId = await invoke('split_session', {
+          sessionId,
+          at: dayjs(at).format('YYYY-MM-DD HH:mm:ss')
+        })
+        await Promise.all([
+          this.fetchSessionDetail(sessionId),
+          this.fetchDaySessions(this.selectedDate)
+        ])
+        ElMessage.success('会话已拆分')
+        return newSessionId
+      } catch (error) {
+        ElMessage.error('拆分会话失败: ' + error)
+        console.error('Failed to split session:', error)
+        return null
+      }
+    },
+
+    // 合并两个相邻会话，返回保留的会话 ID
+    async mergeSessions(firstSessionId, secondSessionId) {
+      try {
+        const keptSessionId = await invoke('merge_sessions', {
+          firstSessionId,
+          secondSessionId
+        })
+        await Promise.all([
+          this.fetchSessionDetail(keptSessionId),
+          this.fetchDaySessions(this.selectedDate)
+        ])
+        ElMessage.success('会话已合并')
+        return keptSessionId
+      } catch (error) {
+        ElMessage.error('合并会话失败: ' + error)
+        console.error('Failed to merge sessions:', error)
+        return null
+      }
+    },
+
+    // 获取会话备注（没有备注时为 null）
+    async fetchSessionNote(sessionId) {
+      try {
+        return await invoke('get_session_note', { sessionId })
+      } catch (error) {
+        console.error('Failed to fetch session note:', error)
+        return null
+      }
+    },
+
+    // 保存会话备注与自定义标签，返回整理后的备注
+    async saveSessionNote(sessionId, note, tags) {
+      try {
+        const saved = await invoke('save_session_note', { sessionId, note, tags })
+        ElMessage.success('备注已保存')
+        return saved
+      } catch (error) {
+        ElMessage.error('保存备注失败: ' + error)
+        console.error('Failed to save session note:', error)
+        return undefined
+      }
+    },
+
     // 生成视频
     async generateVideo(sessionId, speedMultiplier = 20, silent = false) {
       try {